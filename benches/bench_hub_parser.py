@@ -0,0 +1,161 @@
+"""
+Benchmark: SignalR hub frame parsing, previous parser vs current.
+
+Builds a realistic market-open mix of GatewayQuote/GatewayTrade/GatewayDepth
+invocations (plus occasional pings), packs them several messages per frame
+as the server does, and times parsing every frame into HubMessages and
+dispatching each one by message type.
+
+Compared:
+- legacy: the parser before the fast path (UTF-8 decode, str split,
+  json.loads, HubMessage built by probing every field, if/elif dispatch)
+- json: current parse_messages with the stdlib decoder
+- orjson: current parse_messages with orjson (skipped when not installed)
+
+Usage:
+    python benches/bench_hub_parser.py
+    python benches/bench_hub_parser.py --messages 200000 --per-frame 8 --repeat 5
+"""
+
+import argparse
+import json
+import os
+import random
+import sys
+import time
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.websocket.protocol import RECORD_SEPARATOR, HubMessage, MessageType, orjson, parse_messages
+
+CONTRACTS = ('CON.F.US.MNQ.Z25', 'CON.F.US.MES.Z25', 'CON.F.US.MYM.Z25', 'CON.F.US.M2K.Z25')
+
+
+def build_frames(messages: int, per_frame: int, seed: int = 7):
+    """Encoded frames (bytes, as received from the socket) holding `messages` hub messages."""
+    rng = random.Random(seed)
+    records = []
+    for i in range(messages):
+        contract = rng.choice(CONTRACTS)
+        price = round(21000 + rng.uniform(-50, 50) * 4) / 4
+        roll = rng.random()
+        if roll < 0.01:
+            record = {'type': 6}
+        elif roll < 0.55:
+            record = {'type': 1, 'target': 'GatewayQuote', 'arguments': [contract, {
+                'symbol': contract, 'lastPrice': price, 'bestBid': price - 0.25, 'bestAsk': price,
+                'change': 12.5, 'changePercent': 0.06, 'open': 20990.0, 'high': 21060.0, 'low': 20950.0,
+                'volume': 100000 + i, 'lastUpdated': '2025-11-19T14:30:00.123456+00:00',
+                'timestamp': '2025-11-19T14:30:00.123456+00:00'}]}
+        elif roll < 0.85:
+            record = {'type': 1, 'target': 'GatewayTrade', 'arguments': [contract, [
+                {'symbolId': contract, 'price': price, 'timestamp': '2025-11-19T14:30:00.123456+00:00',
+                 'type': rng.randint(0, 1), 'volume': rng.randint(1, 10)}
+                for _ in range(rng.randint(1, 3))]]}
+        else:
+            record = {'type': 1, 'target': 'GatewayDepth', 'arguments': [contract, [
+                {'timestamp': '2025-11-19T14:30:00.123456+00:00', 'type': rng.choice((1, 2)),
+                 'price': price + rng.randint(-10, 10) * 0.25, 'volume': rng.randint(0, 50),
+                 'currentVolume': rng.randint(0, 50)}
+                for _ in range(rng.randint(1, 6))]]}
+        records.append(json.dumps(record) + RECORD_SEPARATOR)
+    return [''.join(records[i:i + per_frame]).encode('utf-8') for i in range(0, len(records), per_frame)]
+
+
+# Previous implementation, kept verbatim as the baseline
+def legacy_from_dict(data):
+    msg_type = int(data['type'])
+    arguments = data.get('arguments')
+    return HubMessage(
+        type=msg_type,
+        target=data.get('target'),
+        arguments=list(arguments) if isinstance(arguments, (list, tuple)) else [],
+        invocation_id=data.get('invocationId'),
+        result=data.get('result', data.get('item')),
+        error=data.get('error'),
+        allow_reconnect=bool(data.get('allowReconnect', False)),
+    )
+
+
+def legacy_parse_messages(data):
+    if isinstance(data, (bytes, bytearray)):
+        data = bytes(data).decode('utf-8')
+    messages = []
+    for record in [r for r in data.split(RECORD_SEPARATOR) if r.strip()]:
+        decoded = json.loads(record)
+        if not isinstance(decoded, dict):
+            raise ValueError('not an object')
+        messages.append(legacy_from_dict(decoded))
+    return messages
+
+
+def legacy_dispatch(messages, counts):
+    for message in messages:
+        if message.type == MessageType.INVOCATION:
+            key = (message.target or '').lower()
+            counts[key] = counts.get(key, 0) + 1
+        elif message.type == MessageType.COMPLETION:
+            pass
+        elif message.type == MessageType.PING:
+            counts['ping'] = counts.get('ping', 0) + 1
+
+
+def fast_dispatch(messages, counts):
+    def on_invocation(message):
+        counts[message.target] = counts.get(message.target, 0) + 1
+
+    def on_ping(message):
+        counts['ping'] = counts.get('ping', 0) + 1
+
+    dispatch = {MessageType.INVOCATION: on_invocation, MessageType.PING: on_ping}
+    for message in messages:
+        handle = dispatch.get(message.type)
+        if handle is not None:
+            handle(message)
+
+
+def run(name, frames, parse, dispatch, repeat):
+    """Best-of-`repeat` time to parse and dispatch every frame."""
+    best = float('inf')
+    total = 0
+    for _ in range(repeat):
+        counts = {}
+        start = time.perf_counter()
+        for frame in frames:
+            dispatch(parse(frame), counts)
+        best = min(best, time.perf_counter() - start)
+        total = sum(counts.values())
+    return name, best, total
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.strip().splitlines()[0])
+    parser.add_argument('--messages', type=int, default=50000, help='Hub messages per run (default 50000)')
+    parser.add_argument('--per-frame', type=int, default=4, help='Messages per WebSocket frame (default 4)')
+    parser.add_argument('--repeat', type=int, default=3, help='Runs per parser, best is reported (default 3)')
+    args = parser.parse_args()
+
+    frames = build_frames(args.messages, args.per_frame)
+    size_mb = sum(len(f) for f in frames) / 1e6
+    print(f"{args.messages} messages in {len(frames)} frames ({size_mb:.1f} MB), best of {args.repeat}\n")
+
+    results = [
+        run('legacy', frames, legacy_parse_messages, legacy_dispatch, args.repeat),
+        run('json', frames, lambda f: parse_messages(f, loads=json.loads), fast_dispatch, args.repeat),
+    ]
+    if orjson is not None:
+        results.append(run('orjson', frames, lambda f: parse_messages(f, loads=orjson.loads),
+                           fast_dispatch, args.repeat))
+    else:
+        print("orjson not installed, skipping (pip install orjson)\n")
+
+    baseline = results[0][1]
+    print(f"{'parser':<8} {'seconds':>8} {'msgs/sec':>12} {'speedup':>8}")
+    for name, seconds, total in results:
+        assert total == args.messages, f"{name} dispatched {total} of {args.messages} messages"
+        print(f"{name:<8} {seconds:>8.3f} {args.messages / seconds:>12,.0f} {baseline / seconds:>7.2f}x")
+
+
+if __name__ == '__main__':
+    main()
@@ -16,6 +16,9 @@ from typing import Dict, List, Optional, Tuple
 from dataclasses import dataclass, asdict
 from threading import Lock
 
+from core.fx_rates import FXConverter, FXRateError, get_fx_converter
+from infrastructure.file_lock import acquire_artifact_lock, atomic_write
+
 logger = logging.getLogger(__name__)
 
 
@@ -57,6 +60,11 @@ class AccountState:
     winning_trades: int
     losing_trades: int
     
+    # Currency (balances and PnL above are in the account currency)
+    currency: str = 'USD'
+    reporting_currency: str = 'USD'
+    realised_PnL_reporting: float = 0.0  # Net realised PnL converted at each fill's trade-time rate
+    
     def to_dict(self) -> Dict:
         """Convert to dictionary for JSON serialization."""
         return asdict(self)
@@ -92,18 +100,27 @@ class AccountTracker:
     2. Monitoring open positions with live quotes for unrealised PnL
     3. Computing compliance with daily/maximum loss limits
     4. Persisting state to database (or JSON file as fallback)
+    5. Converting PnL between account and reporting currencies
     """
     
-    def __init__(self, state_file: str = ".account_state.json", db=None):
+    def __init__(self, state_file: str = ".account_state.json", db=None,
+                 fx_converter: Optional[FXConverter] = None):
         """
         Initialize account tracker.
         
         Args:
             state_file: Path to file for persisting account state (fallback)
             db: Database manager instance (preferred)
+            fx_converter: Currency converter (defaults to the global converter)
+            
+        Raises:
+            ArtifactLockError: If another bot process owns the state file's directory
         """
         self.state_file = Path(state_file)
         self.db = db
+        self.fx = fx_converter or get_fx_converter()
+        # Fail fast if another process already writes snapshots here
+        self._artifact_lock = acquire_artifact_lock(self.state_file.parent)
         self.accounts: Dict[str, AccountState] = {}
         self.lock = Lock()
         self.current_account_id: Optional[str] = None  # Track current active account
@@ -113,7 +130,7 @@ class AccountTracker:
     
     def initialize_account(self, account_id: str, account_name: str, account_type: str,
                           starting_balance: float, daily_loss_limit: Optional[float] = None,
-                          maximum_loss_limit: Optional[float] = None) -> AccountState:
+                          maximum_loss_limit: Optional[float] = None, currency: str = 'USD') -> AccountState:
         """
         Initialize tracking for a new account or reset existing account.
         
@@ -124,6 +141,7 @@ class AccountTracker:
             starting_balance: Starting balance
             daily_loss_limit: Daily loss limit (auto-detected if None)
             maximum_loss_limit: Maximum loss limit (auto-detected if None)
+            currency: Account currency (balances and limits are in this currency)
             
         Returns:
             Initialized AccountState
@@ -154,14 +172,16 @@ class AccountTracker:
             last_EOD_update=datetime.now(timezone.utc).isoformat(),
             total_trades=0,
             winning_trades=0,
-            losing_trades=0
+            losing_trades=0,
+            currency=(currency or 'USD').upper(),
+            reporting_currency=self.fx.reporting_currency
         )
         
         with self.lock:
             self.accounts[account_id] = state
             self._save_state()
         
-        logger.info(f"Initialized tracking for account {account_name} ({account_id})")
+        logger.info(f"Initialized tracking for account {account_name} ({account_id}, {state.currency})")
         logger.info(f"  Starting Balance: ${starting_balance:,.2f}")
         logger.info(f"  Daily Loss Limit: ${daily_loss_limit:,.2f}")
         logger.info(f"  Maximum Loss Limit: ${maximum_loss_limit:,.2f}")
@@ -218,12 +238,18 @@ class AccountTracker:
         """
         Update account state based on a filled order.
         
+        Amounts in fill_data are in fill_data['currency'] (default: account currency)
+        and are converted at the fill's trade-time rate (fill_data['timestamp']).
+        
         Args:
             account_id: Account ID
             fill_data: Fill data containing side, qty, price, commission, fees
             
         Returns:
             Updated AccountState
+            
+        Raises:
+            FXRateError: If amounts need conversion and no rate is available (state is unchanged)
         """
         with self.lock:
             if account_id not in self.accounts:
@@ -240,6 +266,16 @@ class AccountTracker:
             fee = fill_data.get('fee', 0)
             pnl = fill_data.get('pnl', 0)  # Realised PnL from closing trades
             
+            # Convert at trade time before touching state so a missing rate leaves it unchanged
+            fill_currency = (fill_data.get('currency') or state.currency).upper()
+            fill_time = fill_data.get('timestamp')
+            if isinstance(fill_time, str):
+                fill_time = datetime.fromisoformat(fill_time.replace('Z', '+00:00'))
+            to_account = self.fx.rate(fill_currency, state.currency, fill_time)
+            pnl, commission, fee = pnl * to_account, commission * to_account, fee * to_account
+            net_reporting = self.fx.convert(pnl - commission - fee, state.currency,
+                                            state.reporting_currency, fill_time)
+            
             # Update PnL and costs
             if pnl != 0:
                 state.realised_PnL += pnl
@@ -251,6 +287,7 @@ class AccountTracker:
             
             state.commissions += commission
             state.fees += fee
+            state.realised_PnL_reporting += net_reporting
             
             # Recalculate current balance
             state.current_balance = (state.starting_balance + state.realised_PnL + 
@@ -273,6 +310,9 @@ class AccountTracker:
         """
         Update unrealised PnL based on current positions and live prices.
         
+        Position PnL is in the instrument currency (position 'currency', default USD)
+        and is converted to the account currency at the current rate.
+        
         Args:
             account_id: Account ID
             positions: List of open positions with entry_price, qty, symbol
@@ -308,6 +348,13 @@ class AccountTracker:
                 tick_value = self._get_tick_value(symbol)
                 position_pnl = price_diff * tick_value * qty
                 
+                instrument_currency = pos.get('currency', 'USD')
+                try:
+                    position_pnl = self.fx.convert(position_pnl, instrument_currency, state.currency)
+                except FXRateError as e:
+                    logger.error(f"❌ Unrealised PnL not updated for {account_id}: {e}")
+                    return state
+                
                 total_unrealised += position_pnl
             
             state.unrealised_PnL = total_unrealised
@@ -449,7 +496,8 @@ class AccountTracker:
                     'position_count': 0,
                     'positions': {},
                     'last_update': datetime.now(timezone.utc).isoformat(),
-                    'account_type': 'unknown'
+                    'account_type': 'unknown',
+                    'currency': 'USD'
                 }
             
             state = self.accounts[target_id]
@@ -465,13 +513,73 @@ class AccountTracker:
                 'position_count': 0,  # TODO: Track positions
                 'positions': {},  # TODO: Track positions
                 'last_update': state.last_update,
-                'account_type': state.account_type
+                'account_type': state.account_type,
+                'currency': state.currency
             }
     
     def get_all_states(self) -> Dict[str, AccountState]:
         """Get all tracked account states."""
         with self.lock:
             return self.accounts.copy()
+
+    def get_consolidated_report(self) -> Dict:
+        """
+        Aggregate all accounts in the reporting currency.
+
+        Realised PnL uses the trade-time conversions recorded at each fill;
+        unrealised PnL and balances are converted at the current rate.
+        Accounts without an available rate are listed under 'unconverted'
+        and excluded from the totals.
+
+        Returns:
+            Dict with per-account rows and totals
+        """
+        reporting = self.fx.reporting_currency
+        with self.lock:
+            states = list(self.accounts.values())
+
+        rows = []
+        unconverted = []
+        totals = {'realised_pnl': 0.0, 'unrealised_pnl': 0.0, 'net_pnl': 0.0, 'balance': 0.0}
+        for state in states:
+            try:
+                rate = self.fx.rate(state.currency, reporting)
+                if state.currency == reporting:
+                    realised = state.realised_PnL - state.commissions - state.fees
+                else:
+                    # Stored at trade time; only re-based if the reporting currency changed since
+                    realised = self.fx.convert(state.realised_PnL_reporting, state.reporting_currency, reporting)
+            except FXRateError as e:
+                logger.error(f"❌ Excluding account {state.account_id} from consolidated report: {e}")
+                unconverted.append({'account_id': state.account_id, 'currency': state.currency, 'error': str(e)})
+                continue
+
+            row = {
+                'account_id': state.account_id,
+                'account_name': state.account_name,
+                'currency': state.currency,
+                'report_rate': rate,
+                'realised_pnl': realised,
+                'unrealised_pnl': state.unrealised_PnL * rate,
+                'balance': state.current_balance * rate,
+                'native': {
+                    'realised_pnl': state.realised_PnL - state.commissions - state.fees,
+                    'unrealised_pnl': state.unrealised_PnL,
+                    'balance': state.current_balance,
+                },
+            }
+            row['net_pnl'] = row['realised_pnl'] + row['unrealised_pnl']
+            for key in totals:
+                totals[key] += row[key]
+            rows.append(row)
+
+        return {
+            'reporting_currency': reporting,
+            'generated_at': datetime.now(timezone.utc).isoformat(),
+            'accounts': rows,
+            'totals': totals,
+            'unconverted': unconverted,
+        }
     
     def _get_current_state(self, account_id: Optional[str] = None) -> Optional[AccountState]:
         """Get current account state, or None if not found."""
@@ -528,7 +636,8 @@ class AccountTracker:
             return 2500.0  # Default
         return state.maximum_loss_limit
     
-    def initialize(self, account_id: str, starting_balance: float, account_type: str) -> None:
+    def initialize(self, account_id: str, starting_balance: float, account_type: str,
+                   currency: str = 'USD') -> None:
         """
         Convenience method to initialize account tracking.
         
@@ -536,6 +645,7 @@ class AccountTracker:
             account_id: Account ID
             starting_balance: Starting balance
             account_type: Account type
+            currency: Account currency
         """
         account_name = f"Account-{account_id}"
         self.current_account_id = str(account_id)
@@ -543,7 +653,8 @@ class AccountTracker:
             account_id=str(account_id),
             account_name=account_name,
             account_type=account_type,
-            starting_balance=starting_balance
+            starting_balance=starting_balance,
+            currency=currency
         )
     
     def check_compliance(self, account_id: Optional[str] = None) -> Dict:
@@ -642,7 +753,10 @@ class AccountTracker:
                         'is_compliant': state.is_compliant,
                         'violation_reason': state.violation_reason,
                         'last_update': state.last_update,
-                        'last_EOD_update': state.last_EOD_update
+                        'last_EOD_update': state.last_EOD_update,
+                        'currency': state.currency,
+                        'reporting_currency': state.reporting_currency,
+                        'realised_PnL_reporting': state.realised_PnL_reporting
                     }
                     self.db.save_account_state(account_id, state_dict)
                 logger.debug(f"Saved {len(self.accounts)} account states to database")
@@ -654,8 +768,7 @@ class AccountTracker:
                 for account_id, state in self.accounts.items()
             }
             
-            with open(self.state_file, 'w') as f:
-                json.dump(state_dict, f, indent=2)
+            atomic_write(self.state_file, json.dumps(state_dict, indent=2))
             
             logger.debug(f"Saved account state to {self.state_file}")
         except Exception as e:
@@ -697,7 +810,10 @@ class AccountTracker:
                                 last_EOD_update=state_dict.get('last_EOD_update', datetime.now(timezone.utc).isoformat()),
                                 total_trades=state_dict.get('total_trades_today', 0),
                                 winning_trades=state_dict.get('winning_trades_today', 0),
-                                losing_trades=state_dict.get('losing_trades_today', 0)
+                                losing_trades=state_dict.get('losing_trades_today', 0),
+                                currency=state_dict.get('currency', 'USD'),
+                                reporting_currency=state_dict.get('reporting_currency', self.fx.reporting_currency),
+                                realised_PnL_reporting=state_dict.get('realised_PnL_reporting', 0.0)
                             )
                     
                     if self.accounts:
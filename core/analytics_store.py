@@ -0,0 +1,377 @@
+"""
+DuckDB analytics store for bars, ticks and trades.
+
+An embedded columnar database for ad-hoc aggregation from notebooks: load
+bars, ticks and closed trades (from the bot, PostgreSQL or Arrow batches),
+attach the Parquet archive written by ParquetStore, and query everything with
+SQL. Results come back as Arrow tables (core.arrow_export), so polars/pandas
+adopt them without copying.
+
+Tables (same columns as core.arrow_export's schemas):
+- bars:   one row per (symbol, timeframe, timestamp); loading replaces rows with the same key
+- ticks:  append-only
+- trades: closed round trips; loading replaces rows with the same id
+- parquet_bars / parquet_ticks: views over the Parquet archive after
+  attach_parquet(), plus the partition's UTC date column; filters on
+  symbol/timeframe/date skip the other partitions' directories.
+  Any other Parquet file can be scanned directly with read_parquet('path/*.parquet').
+
+Built-in queries (also on the Parquet views with source='parquet'):
+- volume_profile(): volume at price from ticks (or bar closes), split into buy/sell volume
+- session_stats(): per trading session OHLC, volume, range, VWAP and bar count
+
+Usage:
+    store = AnalyticsStore()                      # in memory; or AnalyticsStore('data/analytics.duckdb')
+    store.import_database(get_database(), 'MNQ', start, end)
+    store.attach_parquet()
+    table = store.query("SELECT symbol, count(*) FROM parquet_ticks GROUP BY 1")
+    to_polars(store.session_stats('MNQ', '1m', start, end))
+
+Requires `duckdb` and `pyarrow`.
+
+Configuration:
+- ANALYTICS_DB_PATH: Database file (default ':memory:')
+- ANALYTICS_THREADS: DuckDB worker threads (default: all cores)
+- ANALYTICS_TIMEZONE: Timezone sessions are cut in (default America/New_York)
+- ANALYTICS_DAY_START: Time a session starts, on the previous calendar day if
+  after midnight (default 18:00, the CME Globex open; 00:00 = calendar days)
+- PARQUET_STORE_DIR: Parquet archive attached by attach_parquet() (default 'data/parquet')
+"""
+
+import logging
+import os
+import threading
+from datetime import datetime, time, timezone
+from pathlib import Path
+from typing import Any, Dict, Iterable, List, Optional, Sequence, Union
+
+from core.arrow_export import bars_to_arrow, ticks_to_arrow, trades_to_arrow
+from core.bar_aggregator import Bar
+
+logger = logging.getLogger(__name__)
+
+TABLES_SQL = """
+    CREATE TABLE IF NOT EXISTS bars (
+        symbol VARCHAR NOT NULL,
+        timeframe VARCHAR NOT NULL,
+        timestamp TIMESTAMPTZ NOT NULL,
+        open DOUBLE,
+        high DOUBLE,
+        low DOUBLE,
+        close DOUBLE,
+        volume BIGINT,
+        tick_count BIGINT,
+        is_rth BOOLEAN
+    );
+    CREATE TABLE IF NOT EXISTS ticks (
+        symbol VARCHAR NOT NULL,
+        timestamp TIMESTAMPTZ NOT NULL,
+        price DOUBLE NOT NULL,
+        size BIGINT,
+        side VARCHAR,
+        bid DOUBLE,
+        ask DOUBLE
+    );
+    CREATE TABLE IF NOT EXISTS trades (
+        id BIGINT,
+        account_id VARCHAR,
+        strategy_name VARCHAR,
+        symbol VARCHAR NOT NULL,
+        side VARCHAR,
+        quantity BIGINT,
+        entry_price DOUBLE,
+        exit_price DOUBLE,
+        pnl DOUBLE,
+        entry_time TIMESTAMPTZ,
+        exit_time TIMESTAMPTZ,
+        duration_seconds BIGINT
+    );
+"""
+
+BAR_COLUMNS = ('symbol', 'timeframe', 'timestamp', 'open', 'high', 'low', 'close', 'volume', 'tick_count', 'is_rth')
+TICK_COLUMNS = ('symbol', 'timestamp', 'price', 'size', 'side', 'bid', 'ask')
+TRADE_COLUMNS = ('id', 'account_id', 'strategy_name', 'symbol', 'side', 'quantity', 'entry_price', 'exit_price',
+                 'pnl', 'entry_time', 'exit_time', 'duration_seconds')
+
+# Relation queried by the built-in queries per source
+SOURCES = {
+    'store': {'bars': 'bars', 'ticks': 'ticks'},
+    'parquet': {'bars': 'parquet_bars', 'ticks': 'parquet_ticks'},
+}
+
+
+def _duckdb():
+    try:
+        import duckdb
+    except ImportError as e:
+        raise ImportError("duckdb is required for the analytics store (pip install duckdb)") from e
+    return duckdb
+
+
+def _utc(ts: datetime) -> datetime:
+    return ts.replace(tzinfo=timezone.utc) if ts.tzinfo is None else ts.astimezone(timezone.utc)
+
+
+def _arrow_table(data: Any):
+    """pyarrow Table of a RecordBatch or Table (DuckDB scans registered Tables)."""
+    import pyarrow as pa
+    return pa.Table.from_batches([data]) if isinstance(data, pa.RecordBatch) else data
+
+
+class AnalyticsStore:
+    """
+    DuckDB database of bars, ticks and trades with Arrow query results.
+
+    Usage:
+        store = AnalyticsStore()
+        store.load_bars(bars)
+        store.query("SELECT * FROM bars WHERE symbol = ?", ['MNQ'])
+    """
+
+    def __init__(self, path: Union[str, Path, None] = None, threads: Optional[int] = None,
+                 tz: Optional[str] = None, day_start: Optional[str] = None):
+        """
+        Open (or create) the store.
+
+        Args:
+            path: Database file, or ':memory:' (env: ANALYTICS_DB_PATH)
+            threads: DuckDB worker threads (env: ANALYTICS_THREADS)
+            tz: Timezone sessions are cut in (env: ANALYTICS_TIMEZONE)
+            day_start: Session start 'HH:MM' in tz (env: ANALYTICS_DAY_START)
+        """
+        duckdb = _duckdb()
+        self.path = str(path or os.getenv('ANALYTICS_DB_PATH', ':memory:'))
+        threads = threads if threads is not None else int(os.getenv('ANALYTICS_THREADS', '0'))
+        self.tz = tz or os.getenv('ANALYTICS_TIMEZONE', 'America/New_York')
+        start = time.fromisoformat(day_start or os.getenv('ANALYTICS_DAY_START', '18:00'))
+        # Shift that moves a session's start to the following midnight (0 for calendar days)
+        self.session_shift = (86400 - (start.hour * 3600 + start.minute * 60)) % 86400
+        if self.path != ':memory:':
+            Path(self.path).parent.mkdir(parents=True, exist_ok=True)
+        config = {'threads': threads} if threads > 0 else {}
+        self.conn = duckdb.connect(self.path, config=config)
+        self.conn.execute("SET TimeZone = 'UTC'")
+        self.conn.execute(TABLES_SQL)
+        self.parquet_root: Optional[Path] = None
+        self._lock = threading.Lock()
+        logger.info(f"📊 Analytics store opened ({self.path})")
+
+    # ---------------------------
+    # Queries
+    # ---------------------------
+    def query(self, sql: str, params: Optional[Sequence[Any]] = None):
+        """
+        Run SQL and return the result as a pyarrow Table.
+
+        Args:
+            sql: Any DuckDB statement; ? placeholders are bound to params
+            params: Parameter values
+
+        Returns:
+            pyarrow.Table
+        """
+        with self._lock:
+            result = self.conn.execute(sql, list(params) if params is not None else [])
+            return result.fetch_arrow_table()
+
+    def _relation(self, kind: str, source: str) -> str:
+        if source not in SOURCES:
+            raise ValueError(f"Unknown source '{source}', expected one of {', '.join(SOURCES)}")
+        if source == 'parquet' and self.parquet_root is None:
+            raise ValueError("No Parquet archive attached (call attach_parquet() first)")
+        return SOURCES[source][kind]
+
+    @staticmethod
+    def _time_range(source: str, start: datetime, end: datetime) -> tuple:
+        """WHERE conditions for [start, end); on Parquet also the date= partitions, so others are skipped."""
+        where, params = " AND timestamp >= ? AND timestamp < ?", [_utc(start), _utc(end)]
+        if source == 'parquet':
+            where += " AND date BETWEEN ?::DATE AND ?::DATE"
+            params += [_utc(start).date(), _utc(end).date()]
+        return where, params
+
+    def volume_profile(self, symbol: str, start: datetime, end: datetime, tick_size: float = 0.25,
+                       timeframe: Optional[str] = None, source: str = 'store'):
+        """
+        Volume at price in [start, end).
+
+        Args:
+            symbol: Symbol
+            start: Start (inclusive)
+            end: End (exclusive)
+            tick_size: Price bucket width
+            timeframe: Build from bars of this timeframe (each bar's volume at its close)
+                instead of ticks
+            source: 'store' (loaded tables) or 'parquet' (attached archive)
+
+        Returns:
+            pyarrow.Table: price, volume, buy_volume, sell_volume (bars: unsplit), ascending price
+        """
+        if tick_size <= 0:
+            raise ValueError(f"tick_size must be > 0, got {tick_size}")
+        if timeframe:
+            relation = self._relation('bars', source)
+            price, size, side, where = 'close', 'volume', 'NULL', ' AND timeframe = ?'
+            params = [tick_size, tick_size, symbol.upper(), timeframe]
+        else:
+            relation = self._relation('ticks', source)
+            price, size, side, where = 'price', 'size', 'side', ''
+            params = [tick_size, tick_size, symbol.upper()]
+        time_range, time_params = self._time_range(source, start, end)
+        return self.query(f"""
+            SELECT round(round({price} / ?::DOUBLE) * ?::DOUBLE, 8) AS price,
+                   sum({size})::BIGINT AS volume,
+                   sum(CASE WHEN {side} = 'buy' THEN {size} ELSE 0 END)::BIGINT AS buy_volume,
+                   sum(CASE WHEN {side} = 'sell' THEN {size} ELSE 0 END)::BIGINT AS sell_volume
+            FROM {relation}
+            WHERE symbol = ?{where}{time_range}
+            GROUP BY 1
+            ORDER BY 1
+        """, params + time_params)
+
+    def session_stats(self, symbol: str, timeframe: str, start: datetime, end: datetime, source: str = 'store'):
+        """
+        Per-session statistics from bars in [start, end).
+
+        Sessions are trading days in the store's timezone, starting at day_start.
+
+        Returns:
+            pyarrow.Table: session (date), first_bar, last_bar, open, high, low, close,
+                volume, range, vwap (typical price weighted by volume) and bars, oldest first
+        """
+        relation = self._relation('bars', source)
+        time_range, time_params = self._time_range(source, start, end)
+        return self.query(f"""
+            SELECT CAST(timezone(?::VARCHAR, timestamp) + to_seconds(?::BIGINT) AS DATE) AS session,
+                   min(timestamp) AS first_bar,
+                   max(timestamp) AS last_bar,
+                   arg_min(open, timestamp) AS open,
+                   max(high) AS high,
+                   min(low) AS low,
+                   arg_max(close, timestamp) AS close,
+                   sum(volume)::BIGINT AS volume,
+                   max(high) - min(low) AS range,
+                   sum((high + low + close) / 3 * volume) / nullif(sum(volume), 0) AS vwap,
+                   count(*) AS bars
+            FROM {relation}
+            WHERE symbol = ? AND timeframe = ?{time_range}
+            GROUP BY 1
+            ORDER BY 1
+        """, [self.tz, self.session_shift, symbol.upper(), timeframe] + time_params)
+
+    # ---------------------------
+    # Loading
+    # ---------------------------
+    def _load(self, table: str, columns: Sequence[str], data: Any, key: Optional[Sequence[str]] = None) -> int:
+        """Insert an Arrow batch, first deleting rows whose key columns match an incoming row."""
+        data = _arrow_table(data)
+        if data.num_rows == 0:
+            return 0
+        names = ', '.join(columns)
+        with self._lock:
+            self.conn.register('incoming', data)
+            try:
+                self.conn.begin()
+                try:
+                    if key:
+                        match = ' AND '.join(f"{table}.{c} = incoming.{c}" for c in key)
+                        self.conn.execute(f"DELETE FROM {table} USING incoming WHERE {match}")
+                        select = f"SELECT DISTINCT ON ({', '.join(key)}) {names} FROM incoming"
+                    else:
+                        select = f"SELECT {names} FROM incoming"
+                    self.conn.execute(f"INSERT INTO {table} ({names}) {select}")
+                    self.conn.commit()
+                except Exception:
+                    self.conn.rollback()
+                    raise
+            finally:
+                self.conn.unregister('incoming')
+        logger.debug(f"📥 Loaded {data.num_rows} rows into {table}")
+        return data.num_rows
+
+    def load_bars(self, bars: Union[List[Bar], Any]) -> int:
+        """
+        Load bars (Bar objects or an Arrow batch/table with bar_schema()).
+
+        Returns:
+            int: Rows loaded
+        """
+        if isinstance(bars, list):
+            bars = bars_to_arrow(bars)
+        return self._load('bars', BAR_COLUMNS, bars, key=('symbol', 'timeframe', 'timestamp'))
+
+    def load_ticks(self, ticks: Union[Iterable[Any], Any]) -> int:
+        """Load ticks (Trade events, tick dicts or an Arrow batch/table with tick_schema())."""
+        if not hasattr(ticks, 'num_rows'):
+            ticks = ticks_to_arrow(ticks)
+        return self._load('ticks', TICK_COLUMNS, ticks)
+
+    def load_trades(self, trades: Union[Iterable[Dict[str, Any]], Any]) -> int:
+        """Load closed trades (get_trades() dicts or an Arrow batch/table with trade_schema())."""
+        if not hasattr(trades, 'num_rows'):
+            trades = trades_to_arrow(trades)
+        return self._load('trades', TRADE_COLUMNS, trades, key=('id',))
+
+    def import_database(self, db: Any, symbol: str, start: datetime, end: datetime,
+                        timeframe: str = '1m', ticks: bool = True) -> Dict[str, int]:
+        """
+        Copy one symbol's bars, ticks and closed trades in [start, end) from a DatabaseManager.
+
+        Bars are read columnar (get_bars(output='arrow'), requires numpy); trades
+        are the trade_history rows with exit_time in the range (get_trades()).
+
+        Returns:
+            Dict: Rows loaded per table
+        """
+        loaded = {'bars': self.load_bars(db.get_bars(symbol, timeframe, start, end, output='arrow'))}
+        loaded['ticks'] = self.load_ticks(db.get_ticks(symbol, start, end)) if ticks else 0
+        loaded['trades'] = self.load_trades(db.get_trades(start, end, symbol=symbol))
+        logger.info(f"📥 Imported {symbol} into the analytics store: {loaded}")
+        return loaded
+
+    def attach_parquet(self, root: Union[str, Path, None] = None) -> List[str]:
+        """
+        Create the parquet_bars / parquet_ticks views over a ParquetStore archive.
+
+        Views are only created for kinds with at least one part file; call again
+        after new partitions appear (the file list is expanded when a query runs,
+        so new part files in existing layouts are picked up automatically).
+
+        Args:
+            root: Archive root (env: PARQUET_STORE_DIR)
+
+        Returns:
+            List[str]: Views created
+        """
+        self.parquet_root = Path(root or os.getenv('PARQUET_STORE_DIR', 'data/parquet'))
+        layouts = {
+            'bars': ('parquet_bars', '*/*/*/part-*.parquet', BAR_COLUMNS),
+            'ticks': ('parquet_ticks', '*/*/part-*.parquet', TICK_COLUMNS),
+        }
+        created = []
+        with self._lock:
+            for kind, (view, pattern, columns) in layouts.items():
+                base = self.parquet_root / kind
+                if not any(base.glob(pattern)):
+                    continue
+                glob = str(base / pattern).replace("'", "''")
+                self.conn.execute(f"""
+                    CREATE OR REPLACE VIEW {view} AS
+                    SELECT {', '.join(columns)}, date
+                    FROM read_parquet('{glob}', hive_partitioning = true)
+                """)
+                created.append(view)
+        logger.info(f"📊 Attached Parquet archive {self.parquet_root}: {', '.join(created) or 'no data'}")
+        return created
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Row counts per table."""
+        with self._lock:
+            counts = {table: self.conn.execute(f"SELECT count(*) FROM {table}").fetchone()[0]
+                      for table in ('bars', 'ticks', 'trades')}
+        return {"path": self.path, "parquet_root": str(self.parquet_root) if self.parquet_root else None, **counts}
+
+    def close(self) -> None:
+        """Close the database (a file database keeps its tables for the next session)."""
+        with self._lock:
+            self.conn.close()
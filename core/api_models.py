@@ -0,0 +1,429 @@
+"""
+TopStepX Gateway API Models
+
+Typed request/response models for the REST endpoints the bot uses, in
+place of probing raw dicts for alternative keys (orderId vs id vs
+data.orderId, accounts vs data vs result). Each response is parsed once at
+the call site:
+
+- The envelope (success, errorCode, errorMessage) is always checked.
+- On success, the documented body fields are required with their JSON
+  types; a missing list, a missing id or a string where a number belongs
+  raises ApiSchemaError naming the endpoint and field, instead of the
+  value silently turning into None/0/[] further down.
+- Unknown extra fields are ignored, so additive API changes don't break
+  parsing. Nullable fields (limitPrice, stopPrice, customTag, ...) accept
+  null or absence.
+- Every model keeps the raw dict it was parsed from (raw), for consumers
+  that still work with dicts (dashboard, journal, reconciliation).
+
+Endpoints:
+- /api/Auth/loginKey                  -> LoginResponse
+- /api/Account/search                 -> AccountSearchResponse (Account)
+- /api/Order/place                    -> PlaceOrderResponse
+- /api/Order/search, searchOpen       -> OrderSearchResponse (Order)
+- /api/Order/cancel, modify           -> ApiResponse
+- /api/Position/searchOpen            -> PositionSearchResponse (Position)
+- /api/Position/closeContract,
+  partialCloseContract                -> ApiResponse
+- /api/Trade/search                   -> TradeSearchResponse (Trade)
+- /api/History/retrieveBars           -> BarsResponse (HistoryBar)
+- /api/Contract/search                -> ContractSearchResponse (Contract)
+
+Usage:
+    response = PlaceOrderResponse.parse(raw)     # Raises ApiSchemaError
+    if not response.success:
+        return {"error": response.error_text}
+    order_id = response.order_id
+"""
+
+from dataclasses import dataclass, field
+from enum import IntEnum
+from typing import Any, ClassVar, Dict, List, Optional, Tuple, Type, TypeVar
+
+T = TypeVar('T')
+R = TypeVar('R', bound='ApiResponse')
+
+
+class OrderStatus(IntEnum):
+    NONE = 0
+    OPEN = 1
+    FILLED = 2
+    CANCELLED = 3
+    EXPIRED = 4
+    REJECTED = 5
+    PENDING = 6
+
+
+class OrderType(IntEnum):
+    UNKNOWN = 0
+    LIMIT = 1
+    MARKET = 2
+    STOP_LIMIT = 3
+    STOP = 4
+    TRAILING_STOP = 5
+    JOIN_BID = 6
+    JOIN_ASK = 7
+
+
+class OrderSide(IntEnum):
+    BUY = 0   # Bid
+    SELL = 1  # Ask
+
+
+class PositionType(IntEnum):
+    UNDEFINED = 0
+    LONG = 1
+    SHORT = 2
+
+
+class ApiSchemaError(ValueError):
+    """A TopStepX response that does not match the expected schema."""
+
+    def __init__(self, endpoint: str, message: str, payload: Any = None):
+        self.endpoint = endpoint
+        self.payload = payload
+        excerpt = f" in {repr(payload)[:200]}" if payload is not None else ""
+        super().__init__(f"Unexpected {endpoint} response: {message}{excerpt}")
+
+
+_INT = (int,)
+_FLOAT = (int, float)
+_STR = (str,)
+_BOOL = (bool,)
+_TYPE_NAMES = {_INT: 'integer', _FLOAT: 'number', _STR: 'string', _BOOL: 'boolean'}
+
+
+def _read(data: Dict[str, Any], key: str, types: Tuple[type, ...], endpoint: str, path: str,
+          required: bool = True) -> Any:
+    """Field value checked against its JSON type (bool is not accepted as a number)."""
+    value = data.get(key)
+    if value is None:
+        if required:
+            raise ApiSchemaError(endpoint, f"missing {path}{key}", data)
+        return None
+    if not isinstance(value, types) or (isinstance(value, bool) and types is not _BOOL):
+        raise ApiSchemaError(endpoint, f"{path}{key} should be {_TYPE_NAMES[types]}, got {type(value).__name__}",
+                             data)
+    return float(value) if types is _FLOAT else value
+
+
+def _items(data: Dict[str, Any], key: str, model: Type[T], endpoint: str) -> List[T]:
+    items = data.get(key)
+    if not isinstance(items, list):
+        raise ApiSchemaError(endpoint, f"'{key}' should be a list, got {type(items).__name__}", data)
+    for index, item in enumerate(items):
+        if not isinstance(item, dict):
+            raise ApiSchemaError(endpoint, f"{key}[{index}] should be an object", item)
+    return [model.from_dict(item, endpoint, f"{key}[{index}].") for index, item in enumerate(items)]
+
+
+@dataclass(slots=True)
+class Account:
+    id: int
+    name: str
+    balance: float
+    can_trade: bool
+    is_visible: bool
+    simulated: Optional[bool] = None
+    raw: Dict[str, Any] = field(default_factory=dict, repr=False)
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any], endpoint: str = '/api/Account/search', path: str = '') -> 'Account':
+        return cls(
+            id=_read(data, 'id', _INT, endpoint, path),
+            name=_read(data, 'name', _STR, endpoint, path),
+            balance=_read(data, 'balance', _FLOAT, endpoint, path),
+            can_trade=_read(data, 'canTrade', _BOOL, endpoint, path, required=False) or False,
+            is_visible=_read(data, 'isVisible', _BOOL, endpoint, path, required=False) is not False,
+            simulated=_read(data, 'simulated', _BOOL, endpoint, path, required=False),
+            raw=data,
+        )
+
+
+@dataclass(slots=True)
+class Order:
+    id: int
+    account_id: int
+    contract_id: str
+    status: int
+    type: int
+    side: int
+    size: int
+    creation_timestamp: Optional[str] = None
+    update_timestamp: Optional[str] = None
+    limit_price: Optional[float] = None
+    stop_price: Optional[float] = None
+    fill_volume: int = 0
+    filled_price: Optional[float] = None
+    custom_tag: Optional[str] = None
+    raw: Dict[str, Any] = field(default_factory=dict, repr=False)
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any], endpoint: str = '/api/Order/search', path: str = '') -> 'Order':
+        return cls(
+            id=_read(data, 'id', _INT, endpoint, path),
+            account_id=_read(data, 'accountId', _INT, endpoint, path),
+            contract_id=_read(data, 'contractId', _STR, endpoint, path),
+            status=_read(data, 'status', _INT, endpoint, path),
+            type=_read(data, 'type', _INT, endpoint, path),
+            side=_read(data, 'side', _INT, endpoint, path),
+            size=_read(data, 'size', _INT, endpoint, path),
+            creation_timestamp=_read(data, 'creationTimestamp', _STR, endpoint, path, required=False),
+            update_timestamp=_read(data, 'updateTimestamp', _STR, endpoint, path, required=False),
+            limit_price=_read(data, 'limitPrice', _FLOAT, endpoint, path, required=False),
+            stop_price=_read(data, 'stopPrice', _FLOAT, endpoint, path, required=False),
+            fill_volume=_read(data, 'fillVolume', _INT, endpoint, path, required=False) or 0,
+            filled_price=_read(data, 'filledPrice', _FLOAT, endpoint, path, required=False),
+            custom_tag=_read(data, 'customTag', _STR, endpoint, path, required=False),
+            raw=data,
+        )
+
+    @property
+    def is_working(self) -> bool:
+        """Open or pending at the broker."""
+        return self.status in (OrderStatus.OPEN, OrderStatus.PENDING)
+
+
+@dataclass(slots=True)
+class Position:
+    id: int
+    account_id: int
+    contract_id: str
+    type: int
+    size: int
+    average_price: float
+    creation_timestamp: Optional[str] = None
+    raw: Dict[str, Any] = field(default_factory=dict, repr=False)
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any], endpoint: str = '/api/Position/searchOpen',
+                  path: str = '') -> 'Position':
+        return cls(
+            id=_read(data, 'id', _INT, endpoint, path),
+            account_id=_read(data, 'accountId', _INT, endpoint, path),
+            contract_id=_read(data, 'contractId', _STR, endpoint, path),
+            type=_read(data, 'type', _INT, endpoint, path),
+            size=_read(data, 'size', _INT, endpoint, path),
+            average_price=_read(data, 'averagePrice', _FLOAT, endpoint, path),
+            creation_timestamp=_read(data, 'creationTimestamp', _STR, endpoint, path, required=False),
+            raw=data,
+        )
+
+    @property
+    def signed_size(self) -> int:
+        """Size, negative for short positions."""
+        return -self.size if self.type == PositionType.SHORT else self.size
+
+
+@dataclass(slots=True)
+class Trade:
+    id: int
+    account_id: int
+    contract_id: str
+    price: float
+    side: int
+    size: int
+    order_id: Optional[int] = None
+    creation_timestamp: Optional[str] = None
+    profit_and_loss: Optional[float] = None  # None for half-turns (opening fills)
+    fees: float = 0.0
+    voided: bool = False
+    raw: Dict[str, Any] = field(default_factory=dict, repr=False)
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any], endpoint: str = '/api/Trade/search', path: str = '') -> 'Trade':
+        return cls(
+            id=_read(data, 'id', _INT, endpoint, path),
+            account_id=_read(data, 'accountId', _INT, endpoint, path),
+            contract_id=_read(data, 'contractId', _STR, endpoint, path),
+            price=_read(data, 'price', _FLOAT, endpoint, path),
+            side=_read(data, 'side', _INT, endpoint, path),
+            size=_read(data, 'size', _INT, endpoint, path),
+            order_id=_read(data, 'orderId', _INT, endpoint, path, required=False),
+            creation_timestamp=_read(data, 'creationTimestamp', _STR, endpoint, path, required=False),
+            profit_and_loss=_read(data, 'profitAndLoss', _FLOAT, endpoint, path, required=False),
+            fees=_read(data, 'fees', _FLOAT, endpoint, path, required=False) or 0.0,
+            voided=_read(data, 'voided', _BOOL, endpoint, path, required=False) or False,
+            raw=data,
+        )
+
+
+@dataclass(slots=True)
+class HistoryBar:
+    t: str
+    o: float
+    h: float
+    l: float  # noqa: E741 - API field name
+    c: float
+    v: float
+    raw: Dict[str, Any] = field(default_factory=dict, repr=False)
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any], endpoint: str = '/api/History/retrieveBars',
+                  path: str = '') -> 'HistoryBar':
+        return cls(
+            t=_read(data, 't', _STR, endpoint, path),
+            o=_read(data, 'o', _FLOAT, endpoint, path),
+            h=_read(data, 'h', _FLOAT, endpoint, path),
+            l=_read(data, 'l', _FLOAT, endpoint, path),
+            c=_read(data, 'c', _FLOAT, endpoint, path),
+            v=_read(data, 'v', _FLOAT, endpoint, path, required=False) or 0.0,
+            raw=data,
+        )
+
+
+@dataclass(slots=True)
+class Contract:
+    id: str
+    name: str
+    tick_size: float
+    tick_value: float
+    description: Optional[str] = None
+    active_contract: bool = True
+    symbol_id: Optional[str] = None
+    raw: Dict[str, Any] = field(default_factory=dict, repr=False)
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any], endpoint: str = '/api/Contract/search',
+                  path: str = '') -> 'Contract':
+        return cls(
+            id=_read(data, 'id', _STR, endpoint, path),
+            name=_read(data, 'name', _STR, endpoint, path),
+            tick_size=_read(data, 'tickSize', _FLOAT, endpoint, path),
+            tick_value=_read(data, 'tickValue', _FLOAT, endpoint, path),
+            description=_read(data, 'description', _STR, endpoint, path, required=False),
+            active_contract=_read(data, 'activeContract', _BOOL, endpoint, path, required=False) is not False,
+            symbol_id=_read(data, 'symbolId', _STR, endpoint, path, required=False),
+            raw=data,
+        )
+
+
+@dataclass(slots=True)
+class ApiResponse:
+    """Response envelope shared by all endpoints (body-less for cancel/modify/close)."""
+    success: bool
+    error_code: int = 0
+    error_message: Optional[str] = None
+    raw: Dict[str, Any] = field(default_factory=dict, repr=False)
+
+    ENDPOINT: ClassVar[str] = 'API'
+
+    @classmethod
+    def parse(cls: Type[R], payload: Any, endpoint: Optional[str] = None) -> R:
+        """
+        Parse a decoded JSON response.
+
+        Raises:
+            ApiSchemaError: Not an object, no boolean success, or (on success) a body field
+                missing or of the wrong type
+        """
+        endpoint = endpoint or cls.ENDPOINT
+        if not isinstance(payload, dict):
+            raise ApiSchemaError(endpoint, f"expected an object, got {type(payload).__name__}", payload)
+        success = _read(payload, 'success', _BOOL, endpoint, '')
+        body = cls._parse_body(payload, endpoint) if success else {}
+        return cls(
+            success=success,
+            error_code=_read(payload, 'errorCode', _INT, endpoint, '', required=False) or 0,
+            error_message=_read(payload, 'errorMessage', _STR, endpoint, '', required=False),
+            raw=payload,
+            **body,
+        )
+
+    @classmethod
+    def _parse_body(cls, payload: Dict[str, Any], endpoint: str) -> Dict[str, Any]:
+        return {}
+
+    @property
+    def error_text(self) -> str:
+        """Broker error for logs and error responses."""
+        return f"{self.error_message or 'No error message'} (Code: {self.error_code})"
+
+
+@dataclass(slots=True)
+class LoginResponse(ApiResponse):
+    token: Optional[str] = None
+
+    ENDPOINT: ClassVar[str] = '/api/Auth/loginKey'
+
+    @classmethod
+    def _parse_body(cls, payload: Dict[str, Any], endpoint: str) -> Dict[str, Any]:
+        return {'token': _read(payload, 'token', _STR, endpoint, '')}
+
+
+@dataclass(slots=True)
+class AccountSearchResponse(ApiResponse):
+    accounts: List[Account] = field(default_factory=list)
+
+    ENDPOINT: ClassVar[str] = '/api/Account/search'
+
+    @classmethod
+    def _parse_body(cls, payload: Dict[str, Any], endpoint: str) -> Dict[str, Any]:
+        return {'accounts': _items(payload, 'accounts', Account, endpoint)}
+
+
+@dataclass(slots=True)
+class PlaceOrderResponse(ApiResponse):
+    order_id: Optional[int] = None
+
+    ENDPOINT: ClassVar[str] = '/api/Order/place'
+
+    @classmethod
+    def _parse_body(cls, payload: Dict[str, Any], endpoint: str) -> Dict[str, Any]:
+        return {'order_id': _read(payload, 'orderId', _INT, endpoint, '')}
+
+
+@dataclass(slots=True)
+class OrderSearchResponse(ApiResponse):
+    orders: List[Order] = field(default_factory=list)
+
+    ENDPOINT: ClassVar[str] = '/api/Order/search'
+
+    @classmethod
+    def _parse_body(cls, payload: Dict[str, Any], endpoint: str) -> Dict[str, Any]:
+        return {'orders': _items(payload, 'orders', Order, endpoint)}
+
+
+@dataclass(slots=True)
+class PositionSearchResponse(ApiResponse):
+    positions: List[Position] = field(default_factory=list)
+
+    ENDPOINT: ClassVar[str] = '/api/Position/searchOpen'
+
+    @classmethod
+    def _parse_body(cls, payload: Dict[str, Any], endpoint: str) -> Dict[str, Any]:
+        return {'positions': _items(payload, 'positions', Position, endpoint)}
+
+
+@dataclass(slots=True)
+class TradeSearchResponse(ApiResponse):
+    trades: List[Trade] = field(default_factory=list)
+
+    ENDPOINT: ClassVar[str] = '/api/Trade/search'
+
+    @classmethod
+    def _parse_body(cls, payload: Dict[str, Any], endpoint: str) -> Dict[str, Any]:
+        return {'trades': _items(payload, 'trades', Trade, endpoint)}
+
+
+@dataclass(slots=True)
+class BarsResponse(ApiResponse):
+    bars: List[HistoryBar] = field(default_factory=list)
+
+    ENDPOINT: ClassVar[str] = '/api/History/retrieveBars'
+
+    @classmethod
+    def _parse_body(cls, payload: Dict[str, Any], endpoint: str) -> Dict[str, Any]:
+        return {'bars': _items(payload, 'bars', HistoryBar, endpoint)}
+
+
+@dataclass(slots=True)
+class ContractSearchResponse(ApiResponse):
+    contracts: List[Contract] = field(default_factory=list)
+
+    ENDPOINT: ClassVar[str] = '/api/Contract/search'
+
+    @classmethod
+    def _parse_body(cls, payload: Dict[str, Any], endpoint: str) -> Dict[str, Any]:
+        return {'contracts': _items(payload, 'contracts', Contract, endpoint)}
@@ -0,0 +1,182 @@
+"""
+Apache Arrow export for bars and ticks.
+
+Arrow RecordBatches are the bulk-data exchange format between the bot and
+Python analytics: Polars and pandas (ArrowDtype) adopt the buffers without
+copying, and the same batches can be written to Parquet/IPC or sent over
+Flight unchanged.
+
+Schemas (timestamps are microsecond UTC):
+- Bars:  symbol, timeframe, timestamp, open, high, low, close, volume,
+         tick_count, is_rth (nullable)
+- Ticks: symbol, timestamp, price, size, side ('buy'/'sell', nullable),
+         bid, ask (nullable)
+- Trades: id, account_id, strategy_name, symbol, side, quantity,
+         entry_price, exit_price, pnl, entry_time, exit_time,
+         duration_seconds (closed round trips; all but symbol nullable)
+
+Ticks can be Trade market events or dicts (e.g. DatabaseManager.get_ticks
+rows); trades are dicts (DatabaseManager.get_trades rows). Requires
+`pyarrow`; `to_polars` additionally requires `polars`.
+"""
+
+import logging
+from datetime import datetime, timezone
+from typing import Any, Dict, Iterable, List, Union
+
+from core.bar_aggregator import Bar
+from core.market_events import Trade, parse_timestamp
+
+logger = logging.getLogger(__name__)
+
+
+def _pyarrow():
+    try:
+        import pyarrow as pa
+    except ImportError as e:
+        raise ImportError("pyarrow is required for Arrow export (pip install pyarrow)") from e
+    return pa
+
+
+def bar_schema():
+    """Arrow schema of bars_to_arrow() batches."""
+    pa = _pyarrow()
+    return pa.schema([
+        ('symbol', pa.string()),
+        ('timeframe', pa.string()),
+        ('timestamp', pa.timestamp('us', tz='UTC')),
+        ('open', pa.float64()),
+        ('high', pa.float64()),
+        ('low', pa.float64()),
+        ('close', pa.float64()),
+        ('volume', pa.int64()),
+        ('tick_count', pa.int64()),
+        ('is_rth', pa.bool_()),
+    ])
+
+
+def tick_schema():
+    """Arrow schema of ticks_to_arrow() batches."""
+    pa = _pyarrow()
+    return pa.schema([
+        ('symbol', pa.string()),
+        ('timestamp', pa.timestamp('us', tz='UTC')),
+        ('price', pa.float64()),
+        ('size', pa.int64()),
+        ('side', pa.string()),
+        ('bid', pa.float64()),
+        ('ask', pa.float64()),
+    ])
+
+
+def trade_schema():
+    """Arrow schema of trades_to_arrow() batches."""
+    pa = _pyarrow()
+    return pa.schema([
+        ('id', pa.int64()),
+        ('account_id', pa.string()),
+        ('strategy_name', pa.string()),
+        ('symbol', pa.string()),
+        ('side', pa.string()),
+        ('quantity', pa.int64()),
+        ('entry_price', pa.float64()),
+        ('exit_price', pa.float64()),
+        ('pnl', pa.float64()),
+        ('entry_time', pa.timestamp('us', tz='UTC')),
+        ('exit_time', pa.timestamp('us', tz='UTC')),
+        ('duration_seconds', pa.int64()),
+    ])
+
+
+def _utc(ts: datetime) -> datetime:
+    return ts.replace(tzinfo=timezone.utc) if ts.tzinfo is None else ts
+
+
+def bars_to_arrow(bars: List[Bar]):
+    """
+    Convert bars to an Arrow RecordBatch.
+
+    Args:
+        bars: Bars in time order
+
+    Returns:
+        pyarrow.RecordBatch with bar_schema()
+    """
+    pa = _pyarrow()
+    return pa.RecordBatch.from_pydict({
+        'symbol': [b.symbol for b in bars],
+        'timeframe': [b.timeframe for b in bars],
+        'timestamp': [_utc(b.timestamp) for b in bars],
+        'open': [b.open for b in bars],
+        'high': [b.high for b in bars],
+        'low': [b.low for b in bars],
+        'close': [b.close for b in bars],
+        'volume': [b.volume for b in bars],
+        'tick_count': [b.tick_count for b in bars],
+        'is_rth': [b.is_rth for b in bars],
+    }, schema=bar_schema())
+
+
+def ticks_to_arrow(ticks: Iterable[Union[Trade, Dict[str, Any]]]):
+    """
+    Convert ticks to an Arrow RecordBatch.
+
+    Args:
+        ticks: Trade events or dicts with symbol, timestamp, price and optional
+            size, side, bid, ask
+
+    Returns:
+        pyarrow.RecordBatch with tick_schema()
+    """
+    pa = _pyarrow()
+    columns: Dict[str, list] = {name: [] for name in tick_schema().names}
+    for tick in ticks:
+        if isinstance(tick, Trade):
+            row = {'symbol': tick.symbol, 'timestamp': tick.timestamp, 'price': tick.price,
+                   'size': tick.size, 'side': tick.side}
+        else:
+            row = tick
+        columns['symbol'].append(row['symbol'])
+        columns['timestamp'].append(_utc(parse_timestamp(row.get('timestamp'))))
+        columns['price'].append(float(row['price']))
+        columns['size'].append(int(row.get('size') or 0))
+        columns['side'].append(row.get('side'))
+        columns['bid'].append(row.get('bid'))
+        columns['ask'].append(row.get('ask'))
+    return pa.RecordBatch.from_pydict(columns, schema=tick_schema())
+
+
+def trades_to_arrow(trades: Iterable[Dict[str, Any]]):
+    """
+    Convert closed trades to an Arrow RecordBatch.
+
+    Args:
+        trades: Dicts with the trade_schema() fields; timestamps may be ISO
+            strings, missing fields are null and other keys are ignored
+
+    Returns:
+        pyarrow.RecordBatch with trade_schema()
+    """
+    pa = _pyarrow()
+    schema = trade_schema()
+    columns: Dict[str, list] = {name: [] for name in schema.names}
+    for trade in trades:
+        for name in schema.names:
+            value = trade.get(name)
+            if name in ('entry_time', 'exit_time') and value is not None:
+                value = _utc(parse_timestamp(value))
+            elif name in ('entry_price', 'exit_price', 'pnl') and value is not None:
+                value = float(value)
+            columns[name].append(value)
+    return pa.RecordBatch.from_pydict(columns, schema=schema)
+
+
+def to_polars(batch):
+    """
+    Wrap a RecordBatch (or Table) as a polars DataFrame without copying the buffers.
+
+    Raises:
+        ImportError: If polars is not installed
+    """
+    import polars as pl
+    return pl.from_arrow(batch)
@@ -2,19 +2,113 @@
 Real-time bar aggregator for converting SignalR quote updates into OHLCV bars.
 
 Aggregates tick data into time-based bars (1m, 5m, etc.) and streams them
-to WebSocket clients for real-time chart updates.
+to WebSocket clients for real-time chart updates. A single instance maintains
+every registered timeframe per symbol from one tick stream and emits completed
+bars through an optional callback and/or a thread-safe queue.
+
+Besides time-based timeframes ('1s', '5m', '1h', ...) the aggregator supports
+activity-based bars using threshold timeframe codes:
+- '<N>v': volume bars, close once N contracts have traded
+- '<N>t': tick bars, close every N trades
+- '<N>$': dollar bars, close once N of notional (price x size x multiplier) has traded
+
+Gap repair: for the timeframes in BAR_GAP_TIMEFRAMES (default '1m') each
+completed bar is checked against the previous one. Missing bars (e.g., a
+websocket dropout) are backfilled from REST history in the update loop,
+merged into bar history and reported through gap_callback.
+
+Session alignment: with a SessionCalendar, time bars are anchored to the
+product's Globex session open instead of the epoch, the last bar of a session
+closes at the session close, completed bars carry an is_rth flag, and gaps
+across the maintenance break or weekend are not treated as dropouts.
+
+Clock: ticks without a timestamp and timer-driven closes use server_now()
+(core.clock_sync), the local clock corrected to broker time, so bar
+boundaries don't follow a drifting host clock.
+
+Bar close listeners: any number of callables can be registered with
+on_bar_close(); each is invoked with the completed Bar as soon as it closes,
+outside the aggregator's lock, on whichever thread closed it (the SignalR
+thread for tick-driven closes, the event loop for timer-driven closes).
+Coroutine functions are scheduled onto the event loop they were registered
+from, so async consumers never run on the quote thread.
+
+Multi-symbol: one instance aggregates every symbol. Per-symbol state is
+guarded by striped locks (BAR_LOCK_STRIPES, default 16), so quotes for
+different symbols arriving on different threads don't contend; the registry
+lock is only taken to add or remove a symbol. get_current_bars(),
+get_last_completed_bars() and get_symbol_stats() take optional symbol and
+timeframe filters.
+
+Array export: bars_to_numpy() / BarAggregator.to_numpy() return float64
+column arrays for numpy/pandas analysis. All columns share one contiguous
+block filled in a single pass, so large series convert without building
+per-column Python lists. to_arrow() returns an Arrow RecordBatch for
+zero-copy hand-off to Polars/pandas.
 """
 
 import asyncio
 import logging
 import os
-from collections import defaultdict
+import queue
+import threading
+from collections import defaultdict, deque
 from datetime import datetime, timezone, timedelta
-from typing import Dict, Optional, Callable, Any, Iterable, Set, List
+from functools import lru_cache
+from itertools import chain
+from typing import Dict, Optional, Callable, Any, Awaitable, Iterable, Set, List, Tuple
 from dataclasses import dataclass, field
 
+from core.clock_sync import server_now
+from core.session_calendar import SessionCalendar
+
 logger = logging.getLogger(__name__)
 
+# Threshold timeframe suffix -> bar scheme
+THRESHOLD_BAR_SCHEMES = {'v': 'volume', 't': 'tick', '$': 'dollar'}
+
+
+@lru_cache(maxsize=256)
+def parse_bar_threshold(timeframe: str) -> Optional[Tuple[str, float]]:
+    """
+    Parse an activity-based timeframe code.
+    
+    Args:
+        timeframe: Normalized timeframe (e.g., '1000v', '500t', '250000$')
+    
+    Returns:
+        (scheme, threshold) for volume/tick/dollar bars, or None for time-based timeframes
+    """
+    if not timeframe or timeframe[-1] not in THRESHOLD_BAR_SCHEMES:
+        return None
+    try:
+        threshold = float(timeframe[:-1])
+    except ValueError:
+        return None
+    if threshold <= 0:
+        return None
+    return THRESHOLD_BAR_SCHEMES[timeframe[-1]], threshold
+
+
+def timeframe_seconds(timeframe: str) -> int:
+    """
+    Length of a time-based timeframe in seconds ('30s', '5m', '1h', '1d').
+    
+    Unknown formats default to 1 minute.
+    """
+    units = {'s': 1, 'm': 60, 'h': 3600, 'd': 86400}
+    try:
+        return int(timeframe[:-1]) * units[timeframe[-1]]
+    except (KeyError, ValueError, IndexError):
+        return 60
+
+
+def bar_start_time(timestamp: datetime, timeframe: str) -> datetime:
+    """Start of the time-based bar containing a timestamp (aligned to the epoch, UTC)."""
+    bar_seconds = timeframe_seconds(timeframe)
+    bar_start_seconds = (int(timestamp.timestamp()) // bar_seconds) * bar_seconds
+    return datetime.fromtimestamp(bar_start_seconds, tz=timezone.utc)
+
 
 @dataclass
 class Bar:
@@ -28,6 +122,87 @@ class Bar:
     close: float
     volume: int = 0
     tick_count: int = 0
+    is_rth: Optional[bool] = None  # Set when the aggregator has a session calendar
+
+
+@dataclass
+class BarGap:
+    """Run of missing bars between two completed bars: [start, end)."""
+    symbol: str
+    timeframe: str
+    start: datetime
+    end: datetime
+    missing: int
+    detected_at: datetime = field(default_factory=lambda: server_now())
+    attempts: int = 0
+    filled: int = 0
+    bars: List[Bar] = field(default_factory=list)
+
+
+# Column order of bars_to_numpy() output
+BAR_ARRAY_FIELDS = ('open', 'high', 'low', 'close', 'volume', 'timestamp')
+
+
+def bars_to_numpy(bars: List[Bar]) -> Dict[str, Any]:
+    """
+    Convert bars to contiguous float64 column arrays.
+    
+    The columns are row views of a single (6, N) C-contiguous block, filled
+    with one np.fromiter pass over the bars.
+    
+    Args:
+        bars: Bars in time order
+    
+    Returns:
+        Dict with 'open', 'high', 'low', 'close', 'volume' and 'timestamp'
+        (epoch seconds, UTC) float64 arrays of equal length
+    """
+    try:
+        import numpy as np
+    except ImportError as e:
+        raise ImportError("numpy is required for array output (pip install numpy)") from e
+    count = len(bars)
+    width = len(BAR_ARRAY_FIELDS)
+    block = np.empty((width, count), dtype=np.float64)
+    if count:
+        values = np.fromiter(
+            chain.from_iterable((b.open, b.high, b.low, b.close, b.volume, b.timestamp.timestamp()) for b in bars),
+            dtype=np.float64, count=count * width,
+        )
+        block[...] = values.reshape(count, width).T
+    return dict(zip(BAR_ARRAY_FIELDS, block))
+
+
+GapBackfill = Callable[[str, str, datetime, datetime], Awaitable[List[Bar]]]
+
+
+@dataclass(frozen=True)
+class BarCloseListener:
+    """A callback registered through BarAggregator.on_bar_close."""
+    callback: Callable[[Bar], Any]
+    symbol: Optional[str] = None
+    timeframes: Optional[frozenset] = None
+    loop: Optional[asyncio.AbstractEventLoop] = None  # Set for coroutine functions
+    
+    def matches(self, bar: Bar) -> bool:
+        return ((self.symbol is None or bar.symbol == self.symbol)
+                and (self.timeframes is None or bar.timeframe in self.timeframes))
+    
+    def dispatch(self, bar: Bar):
+        """Invoke the callback, isolating its errors from the aggregator."""
+        try:
+            if self.loop is None:
+                self.callback(bar)
+            elif not self.loop.is_closed():
+                future = asyncio.run_coroutine_threadsafe(self.callback(bar), self.loop)
+                future.add_done_callback(lambda f: self._log_async_error(f, bar))
+        except Exception as e:
+            logger.error(f"Error in bar close listener for {bar.symbol} {bar.timeframe}: {e}")
+    
+    @staticmethod
+    def _log_async_error(future, bar: Bar):
+        if not future.cancelled() and future.exception() is not None:
+            logger.error(f"Error in async bar close listener for {bar.symbol} {bar.timeframe}: {future.exception()}")
 
 
 @dataclass
@@ -42,12 +217,13 @@ class BarBuilder:
     close: Optional[float] = None
     volume: int = 0
     tick_count: int = 0
+    notional: float = 0.0
     last_update: Optional[datetime] = None
     
     def add_tick(self, price: float, volume: int = 0, timestamp: Optional[datetime] = None):
         """Add a tick to the current bar."""
         if timestamp is None:
-            timestamp = datetime.now(timezone.utc)
+            timestamp = server_now()
         
         if self.open is None:
             self.open = price
@@ -86,23 +262,80 @@ class BarAggregator:
     Aggregates real-time quote updates into OHLCV bars.
     
     Features:
-    - Multiple timeframes (1m, 5m, 15m, etc.)
+    - Multiple timeframes (1s, 5s, 1m, 5m, 15m, etc.) from a single tick stream
+    - Volume, tick-count and dollar bars ('1000v', '500t', '250000$')
     - Real-time bar updates (3-5 per second)
     - Automatic bar completion and new bar creation
+    - Completed-bar emission via callback, on_bar_close listeners and/or thread-safe queue
+    - Per-timeframe completed bar history
+    - Gap detection with automatic backfill from REST history
+    - Session-anchored time bars and RTH flag via SessionCalendar
     - WebSocket broadcasting
     """
     
     def __init__(self, broadcast_callback: Optional[Callable[[Dict[str, Any]], None]] = None,
-                 default_timeframes: Optional[Iterable[str]] = None):
+                 default_timeframes: Optional[Iterable[str]] = None,
+                 bar_close_callback: Optional[Callable[[Bar], None]] = None,
+                 bar_queue_maxsize: Optional[int] = None,
+                 max_history: Optional[int] = None,
+                 contract_multipliers: Optional[Dict[str, float]] = None,
+                 gap_backfill: Optional[GapBackfill] = None,
+                 gap_callback: Optional[Callable[[BarGap], None]] = None,
+                 gap_timeframes: Optional[Iterable[str]] = None,
+                 max_gap_bars: Optional[int] = None,
+                 session_calendar: Optional[SessionCalendar] = None,
+                 lock_stripes: Optional[int] = None):
         """
         Initialize bar aggregator.
         
         Args:
             broadcast_callback: Function to call when a bar update is ready
+            default_timeframes: Timeframes built for every symbol (env: BAR_DEFAULT_TIMEFRAMES)
+            bar_close_callback: Function called with each completed Bar
+            bar_queue_maxsize: Size of the completed-bar queue, 0 disables it (env: BAR_QUEUE_MAXSIZE)
+            max_history: Completed bars kept per symbol/timeframe (env: BAR_HISTORY_SIZE)
+            contract_multipliers: {symbol: point value} used for dollar bars (default 1.0)
+            gap_backfill: async (symbol, timeframe, start, end) -> List[Bar], e.g. HistoryClient.fetch_bars
+            gap_callback: Called with each repaired BarGap
+            gap_timeframes: Timeframes checked for gaps (env: BAR_GAP_TIMEFRAMES, default '1m')
+            max_gap_bars: Larger gaps are treated as session breaks and not backfilled (env: BAR_GAP_MAX_BARS)
+            session_calendar: Anchors time bars to session open and flags RTH bars (epoch-aligned if None)
+            lock_stripes: Number of per-symbol lock partitions (env: BAR_LOCK_STRIPES)
         """
         self.broadcast_callback = broadcast_callback
+        self.session_calendar = session_calendar
+        self.bar_close_callback = bar_close_callback
+        # Copy-on-write so emission never holds a lock while calling user code
+        self._bar_close_listeners: Tuple[BarCloseListener, ...] = ()
+        self._listener_lock = threading.Lock()
+        self.contract_multipliers: Dict[str, float] = {
+            k.upper(): float(v) for k, v in (contract_multipliers or {}).items()
+        }
         self.bar_builders: Dict[str, Dict[str, BarBuilder]] = defaultdict(dict)  # {symbol: {timeframe: BarBuilder}}
         self.completed_bars: Dict[str, Dict[str, Bar]] = defaultdict(dict)  # {symbol: {timeframe: Bar}}
+        if max_history is None:
+            max_history = int(os.getenv('BAR_HISTORY_SIZE', '500'))
+        self.max_history = max_history
+        self.bar_history: Dict[str, Dict[str, deque]] = defaultdict(
+            lambda: defaultdict(lambda: deque(maxlen=self.max_history))
+        )  # {symbol: {timeframe: deque[Bar]}}
+        if bar_queue_maxsize is None:
+            bar_queue_maxsize = int(os.getenv('BAR_QUEUE_MAXSIZE', '0'))
+        # Thread-safe queue: quotes arrive on the SignalR thread, consumers may live anywhere
+        self.completed_bar_queue: Optional[queue.Queue] = (
+            queue.Queue(maxsize=bar_queue_maxsize) if bar_queue_maxsize > 0 else None
+        )
+        self.dropped_bars = 0
+        # Producers for different symbols hold different stripes; the drop-oldest swap needs its own lock
+        self._queue_lock = threading.Lock()
+        self._bars_completed_by_symbol: Dict[str, int] = defaultdict(int)
+        # Registry lock: adding/removing symbols. Per-symbol state uses the striped locks.
+        self._state_lock = threading.RLock()
+        if lock_stripes is None:
+            lock_stripes = int(os.getenv('BAR_LOCK_STRIPES', '16'))
+        self._symbol_locks: Tuple[threading.RLock, ...] = tuple(
+            threading.RLock() for _ in range(max(1, lock_stripes))
+        )
         self._broadcast_log_counts: Dict[str, int] = defaultdict(int)
         self.lock = asyncio.Lock()
         self.update_interval = 0.2  # 5 updates per second (200ms)
@@ -117,14 +350,37 @@ class BarAggregator:
             frames = env_frames.split(',')
         else:
             # Support all timeframes including seconds
-            frames = ['5s', '15s', '30s', '1m', '2m', '5m', '15m', '30m', '1h']
+            frames = ['1s', '5s', '15s', '30s', '1m', '2m', '5m', '15m', '30m', '1h']
         self.default_timeframes: List[str] = [
             self._normalize_timeframe(tf) for tf in frames if tf and tf.strip()
         ]
         if not self.default_timeframes:
-            self.default_timeframes = ['5s', '15s', '30s', '1m', '2m', '5m', '15m', '30m', '1h']
+            self.default_timeframes = ['1s', '5s', '15s', '30s', '1m', '2m', '5m', '15m', '30m', '1h']
         self.symbol_timeframes: Dict[str, Set[str]] = defaultdict(set)
+        # Gap detection / backfill
+        self.gap_backfill = gap_backfill
+        self.gap_callback = gap_callback
+        gap_frames = gap_timeframes if gap_timeframes is not None else os.getenv('BAR_GAP_TIMEFRAMES', '1m').split(',')
+        self.gap_timeframes: Set[str] = {
+            self._normalize_timeframe(tf) for tf in gap_frames
+            if tf and tf.strip() and parse_bar_threshold(self._normalize_timeframe(tf)) is None
+        }
+        self.max_gap_bars = max_gap_bars if max_gap_bars is not None else int(os.getenv('BAR_GAP_MAX_BARS', '120'))
+        self.pending_gaps: deque = deque()
+        self.gaps_detected = 0
+        self.gaps_repaired = 0
+        self.gaps_skipped = 0
+        self._repair_task: Optional[asyncio.Task] = None
         
+    @property
+    def bars_completed(self) -> int:
+        """Total bars completed across all symbols."""
+        return sum(list(self._bars_completed_by_symbol.values()))
+    
+    def _symbol_lock(self, symbol_key: str) -> threading.RLock:
+        """Lock partition guarding a symbol's builders, history and last-completed bars."""
+        return self._symbol_locks[hash(symbol_key) % len(self._symbol_locks)]
+    
     async def start(self):
         """Start the bar aggregator update loop."""
         if self._running:
@@ -150,7 +406,11 @@ class BarAggregator:
         while self._running:
             try:
                 await asyncio.sleep(self.update_interval)
+                self.close_elapsed_bars()
                 await self._broadcast_updates()
+                if self.pending_gaps and (self._repair_task is None or self._repair_task.done()):
+                    # Backfill in the background so REST latency never delays live bar updates
+                    self._repair_task = asyncio.create_task(self.repair_gaps())
             except asyncio.CancelledError:
                 break
             except Exception as e:
@@ -162,11 +422,11 @@ class BarAggregator:
             return
         
         async with self.lock:
-            for symbol, timeframes in self.bar_builders.items():
-                for timeframe, builder in timeframes.items():
+            for symbol, timeframes in list(self.bar_builders.items()):
+                for timeframe, builder in list(timeframes.items()):
                     if builder.last_update and builder.close is not None:
                         # Only broadcast if bar was updated recently (within last 2 seconds)
-                        time_since_update = (datetime.now(timezone.utc) - builder.last_update).total_seconds()
+                        time_since_update = (server_now() - builder.last_update).total_seconds()
                         if time_since_update > 2.0:
                             continue  # Skip stale bars
                         
@@ -191,7 +451,7 @@ class BarAggregator:
                                 self.broadcast_callback({
                                     "type": "market_update",
                                     "data": bar_data,
-                                    "timestamp": datetime.now(timezone.utc).isoformat()
+                                    "timestamp": server_now().isoformat()
                                 })
                                 key = f"{symbol}:{timeframe}"
                                 count = self._broadcast_log_counts[key]
@@ -217,30 +477,310 @@ class BarAggregator:
             timestamp: Quote timestamp
         """
         if timestamp is None:
-            timestamp = datetime.now(timezone.utc)
+            timestamp = server_now()
         
         symbol_key = symbol.upper()
+        completed: List[Bar] = []
         if symbol_key not in self.bar_builders:
-            self._initialize_symbol(symbol_key, timestamp)
-        
-        active_frames = self.bar_builders.get(symbol_key, {})
-        # Update bars for all active timeframes
-        for timeframe, builder in active_frames.items():
-            # Check if we need to start a new bar
-            if self._should_start_new_bar(builder, timeframe, timestamp):
-                # Complete the old bar
-                if builder.open is not None:
-                    completed_bar = builder.to_bar()
-                    self.completed_bars[symbol_key][timeframe] = completed_bar
-                    logger.debug(f"Completed bar for {symbol_key} {timeframe}: {completed_bar.close}")
+            with self._state_lock, self._symbol_lock(symbol_key):
+                if symbol_key not in self.bar_builders:
+                    self._initialize_symbol(symbol_key, timestamp)
+        
+        with self._symbol_lock(symbol_key):
+            active_frames = self.bar_builders.get(symbol_key, {})
+            # Update bars for all active timeframes
+            for timeframe, builder in list(active_frames.items()):
+                threshold = parse_bar_threshold(timeframe)
+                if threshold is not None:
+                    completed_bar = self._add_threshold_tick(symbol_key, timeframe, builder, threshold,
+                                                             price, volume, timestamp)
+                    if completed_bar is not None:
+                        completed.append(completed_bar)
+                    continue
+                
+                # Check if we need to start a new bar
+                if self._should_start_new_bar(builder, timeframe, timestamp):
+                    # Complete the old bar
+                    if builder.open is not None:
+                        completed.append(self._record_completed_bar(builder))
+                    
+                    # Start new bar
+                    bar_start = self._get_bar_start_time(timestamp, timeframe, symbol_key)
+                    builder = BarBuilder(symbol_key, timeframe, bar_start)
+                    active_frames[timeframe] = builder
                 
-                # Start new bar
-                bar_start = self._get_bar_start_time(timestamp, timeframe)
-                builder = BarBuilder(symbol_key, timeframe, bar_start)
-                self.bar_builders[symbol_key][timeframe] = builder
-            
-            # Add tick to current bar
-            builder.add_tick(price, volume, timestamp)
+                # Add tick to current bar
+                builder.add_tick(price, volume, timestamp)
+        
+        for bar in completed:
+            self._emit_completed_bar(bar)
+    
+    def _add_threshold_tick(self, symbol_key: str, timeframe: str, builder: BarBuilder,
+                            threshold: Tuple[str, float], price: float, volume: int,
+                            timestamp: datetime) -> Optional[Bar]:
+        """
+        Add a tick to a volume/tick/dollar bar (caller holds the symbol lock).
+        
+        Returns:
+            Bar if the tick completed the bar, otherwise None
+        """
+        scheme, size = threshold
+        if builder.tick_count == 0:
+            # Activity bars are stamped with their first tick's time
+            builder.bar_start = timestamp
+        builder.add_tick(price, volume, timestamp)
+        builder.notional += price * volume * self.contract_multipliers.get(symbol_key, 1.0)
+        
+        if scheme == 'volume':
+            progress = builder.volume
+        elif scheme == 'tick':
+            progress = builder.tick_count
+        else:
+            progress = builder.notional
+        if progress < size:
+            return None
+        
+        completed_bar = self._record_completed_bar(builder)
+        self.bar_builders[symbol_key][timeframe] = BarBuilder(symbol_key, timeframe, timestamp)
+        return completed_bar
+    
+    def close_elapsed_bars(self, now: Optional[datetime] = None) -> List[Bar]:
+        """
+        Complete bars whose period has ended even if no new tick arrived.
+        
+        Called from the update loop so quiet markets still emit bars on time.
+        
+        Args:
+            now: Reference time (defaults to the server clock, see core.clock_sync)
+        
+        Returns:
+            List[Bar]: Bars completed by this call
+        """
+        if now is None:
+            now = server_now()
+        completed: List[Bar] = []
+        for symbol_key, frames in list(self.bar_builders.items()):
+            with self._symbol_lock(symbol_key):
+                for timeframe, builder in list(frames.items()):
+                    if builder.open is None or not self._should_start_new_bar(builder, timeframe, now):
+                        continue
+                    completed.append(self._record_completed_bar(builder))
+                    frames[timeframe] = BarBuilder(symbol_key, timeframe, self._get_bar_start_time(now, timeframe, symbol_key))
+        for bar in completed:
+            self._emit_completed_bar(bar)
+        return completed
+    
+    def _record_completed_bar(self, builder: BarBuilder) -> Bar:
+        """Store a finished bar as last-completed and in history (caller holds the symbol lock)."""
+        completed_bar = builder.to_bar()
+        completed_bar.is_rth = self.is_rth(builder.symbol, completed_bar.timestamp)
+        history = self.bar_history[builder.symbol][builder.timeframe]
+        if history and builder.timeframe in self.gap_timeframes:
+            self._check_gap(history[-1], completed_bar)
+        self.completed_bars[builder.symbol][builder.timeframe] = completed_bar
+        history.append(completed_bar)
+        self._bars_completed_by_symbol[builder.symbol] += 1
+        logger.debug(f"Completed bar for {builder.symbol} {builder.timeframe}: {completed_bar.close}")
+        return completed_bar
+    
+    def _check_gap(self, previous: Bar, bar: Bar):
+        """Queue a gap if bars are missing between previous and bar (caller holds the symbol lock)."""
+        bar_seconds = timeframe_seconds(bar.timeframe)
+        expected = previous.timestamp + timedelta(seconds=bar_seconds)
+        if bar.timestamp <= expected:
+            return
+        if self.session_calendar is not None and (
+                self.session_calendar.session_bounds(bar.symbol, previous.timestamp)
+                != self.session_calendar.session_bounds(bar.symbol, bar.timestamp)):
+            return  # Maintenance break or weekend, nothing traded
+        missing = int((bar.timestamp - expected).total_seconds() // bar_seconds)
+        self.gaps_detected += 1
+        if self.gap_backfill is None or missing > self.max_gap_bars:
+            # No history source, or a session break rather than a dropout
+            self.gaps_skipped += 1
+            logger.info(f"⏭️  {missing} {bar.timeframe} bars missing for {bar.symbol} "
+                        f"({expected.isoformat()} → {bar.timestamp.isoformat()}) - not backfilling")
+            return
+        logger.warning(f"🕳️  Gap detected: {missing} {bar.timeframe} bars missing for {bar.symbol} "
+                       f"({expected.isoformat()} → {bar.timestamp.isoformat()})")
+        self.pending_gaps.append(BarGap(bar.symbol, bar.timeframe, expected, bar.timestamp, missing))
+    
+    async def repair_gaps(self, max_attempts: int = 3) -> List[BarGap]:
+        """
+        Backfill queued gaps from REST history.
+        
+        Repaired bars are merged into bar history (not re-emitted as closed bars,
+        since they arrive out of order); consumers are told via gap_callback.
+        Failed backfills are retried on later calls up to max_attempts.
+        
+        Returns:
+            List[BarGap]: Gaps repaired by this call
+        """
+        repaired: List[BarGap] = []
+        retry: List[BarGap] = []
+        while self.pending_gaps:
+            gap = self.pending_gaps.popleft()
+            gap.attempts += 1
+            try:
+                bars = await self.gap_backfill(gap.symbol, gap.timeframe, gap.start,
+                                               gap.end - timedelta(milliseconds=1))
+            except Exception as e:
+                logger.error(f"❌ Backfill failed for {gap.symbol} {gap.timeframe} gap at {gap.start.isoformat()} "
+                             f"(attempt {gap.attempts}/{max_attempts}): {e}")
+                if gap.attempts < max_attempts:
+                    retry.append(gap)
+                continue
+            gap.bars = sorted((b for b in bars or [] if gap.start <= b.timestamp < gap.end),
+                              key=lambda b: b.timestamp)
+            gap.filled = len(gap.bars)
+            self._merge_history(gap.symbol, gap.timeframe, gap.bars)
+            self.gaps_repaired += 1
+            repaired.append(gap)
+            logger.info(f"🩹 Gap repaired for {gap.symbol} {gap.timeframe}: {gap.filled}/{gap.missing} bars backfilled")
+            if self.gap_callback:
+                try:
+                    self.gap_callback(gap)
+                except Exception as e:
+                    logger.error(f"Error in gap callback for {gap.symbol} {gap.timeframe}: {e}")
+        self.pending_gaps.extend(retry)
+        return repaired
+    
+    def _merge_history(self, symbol_key: str, timeframe: str, bars: List[Bar]):
+        """Insert backfilled bars into history in timestamp order."""
+        if not bars:
+            return
+        for bar in bars:
+            if bar.is_rth is None:
+                bar.is_rth = self.is_rth(symbol_key, bar.timestamp)
+        with self._symbol_lock(symbol_key):
+            history = self.bar_history[symbol_key][timeframe]
+            merged = {bar.timestamp: bar for bar in bars}
+            merged.update({bar.timestamp: bar for bar in history})  # Live bars win over backfill
+            history.clear()
+            history.extend(merged[ts] for ts in sorted(merged)[-self.max_history:])
+    
+    def preload_history(self, symbol: str, timeframe: str, bars: List[Bar]):
+        """
+        Seed bar history (e.g. at startup) so history consumers don't start cold.
+        
+        Registers the timeframe for the symbol. Preloaded bars are not emitted
+        to callbacks, the queue or bar close listeners.
+        """
+        symbol_key = symbol.upper()
+        normalized_tf = self._normalize_timeframe(timeframe)
+        self.register_timeframes(symbol_key, [normalized_tf])
+        self._merge_history(symbol_key, normalized_tf, list(bars))
+    
+    def get_gap_stats(self) -> Dict[str, Any]:
+        """Gap detection counters."""
+        return {
+            "timeframes": sorted(self.gap_timeframes),
+            "detected": self.gaps_detected,
+            "repaired": self.gaps_repaired,
+            "skipped": self.gaps_skipped,
+            "pending": len(self.pending_gaps),
+        }
+    
+    def _emit_completed_bar(self, bar: Bar):
+        """Deliver a completed bar to the queue and callback."""
+        if self.completed_bar_queue is not None:
+            with self._queue_lock:
+                while True:
+                    try:
+                        self.completed_bar_queue.put_nowait(bar)
+                        break
+                    except queue.Full:
+                        # Drop the oldest bar so consumers always see the freshest data
+                        try:
+                            self.completed_bar_queue.get_nowait()
+                            self.dropped_bars += 1
+                        except queue.Empty:
+                            pass
+        if self.bar_close_callback:
+            try:
+                self.bar_close_callback(bar)
+            except Exception as e:
+                logger.error(f"Error in bar close callback for {bar.symbol} {bar.timeframe}: {e}")
+        for listener in self._bar_close_listeners:
+            if listener.matches(bar):
+                listener.dispatch(bar)
+    
+    def on_bar_close(self, callback: Callable[[Bar], Any], symbol: Optional[str] = None,
+                     timeframes: Optional[Iterable[str]] = None) -> Callable[[], None]:
+        """
+        Register a listener invoked with each completed Bar the moment it closes.
+        
+        Plain callables run inline on the thread that closed the bar, so they
+        should be quick. Coroutine functions must be registered from a running
+        event loop and are scheduled onto that loop.
+        
+        Args:
+            callback: Callable (or coroutine function) taking the completed Bar
+            symbol: Only bars for this symbol (default: all symbols)
+            timeframes: Only bars for these timeframes (default: all timeframes)
+        
+        Returns:
+            Callable that unregisters the listener
+        """
+        loop = None
+        if asyncio.iscoroutinefunction(callback):
+            try:
+                loop = asyncio.get_running_loop()
+            except RuntimeError:
+                raise RuntimeError("Async bar close listeners must be registered from a running event loop")
+        listener = BarCloseListener(
+            callback=callback,
+            symbol=symbol.upper() if symbol else None,
+            timeframes=frozenset(self._normalize_timeframe(tf) for tf in timeframes) if timeframes else None,
+            loop=loop,
+        )
+        with self._listener_lock:
+            self._bar_close_listeners = self._bar_close_listeners + (listener,)
+        
+        def unsubscribe():
+            with self._listener_lock:
+                self._bar_close_listeners = tuple(l for l in self._bar_close_listeners if l is not listener)
+        return unsubscribe
+    
+    def get_completed_bars(self, max_items: Optional[int] = None) -> List[Bar]:
+        """
+        Drain completed bars from the queue (non-blocking).
+        
+        Args:
+            max_items: Maximum bars to return (None = all queued)
+        
+        Returns:
+            List[Bar]: Completed bars in completion order
+        """
+        bars: List[Bar] = []
+        if self.completed_bar_queue is None:
+            return bars
+        while max_items is None or len(bars) < max_items:
+            try:
+                bars.append(self.completed_bar_queue.get_nowait())
+            except queue.Empty:
+                break
+        return bars
+    
+    def get_bar_history(self, symbol: str, timeframe: str, count: Optional[int] = None) -> List[Bar]:
+        """Get completed bars for a symbol/timeframe (oldest first)."""
+        symbol_key = symbol.upper()
+        normalized_tf = self._normalize_timeframe(timeframe)
+        with self._symbol_lock(symbol_key):
+            frames = self.bar_history.get(symbol_key)
+            if not frames or normalized_tf not in frames:
+                return []
+            history = list(frames[normalized_tf])
+        return history[-count:] if count else history
+    
+    def to_numpy(self, symbol: str, timeframe: str, count: Optional[int] = None) -> Dict[str, Any]:
+        """Completed bar history as float64 column arrays (see bars_to_numpy)."""
+        return bars_to_numpy(self.get_bar_history(symbol, timeframe, count))
+    
+    def to_arrow(self, symbol: str, timeframe: str, count: Optional[int] = None):
+        """Completed bar history as an Arrow RecordBatch (see core.arrow_export)."""
+        from core.arrow_export import bars_to_arrow
+        return bars_to_arrow(self.get_bar_history(symbol, timeframe, count))
     
     def subscribe_timeframe(self, symbol: str, timeframe: str):
         """
@@ -252,99 +792,174 @@ class BarAggregator:
         """
         symbol_key = symbol.upper()
         normalized_tf = self._normalize_timeframe(timeframe)
-        self.symbol_timeframes[symbol_key].add(normalized_tf)
-        if normalized_tf not in self.bar_builders[symbol_key]:
-            now = datetime.now(timezone.utc)
-            bar_start = self._get_bar_start_time(now, normalized_tf)
-            builder = BarBuilder(symbol_key, normalized_tf, bar_start)
-            self.bar_builders[symbol_key][normalized_tf] = builder
-            logger.debug(f"Subscribed to {symbol_key} {normalized_tf} bars")
+        with self._state_lock, self._symbol_lock(symbol_key):
+            self.symbol_timeframes[symbol_key].add(normalized_tf)
+            if normalized_tf not in self.bar_builders[symbol_key]:
+                now = server_now()
+                bar_start = self._get_bar_start_time(now, normalized_tf, symbol_key)
+                builder = BarBuilder(symbol_key, normalized_tf, bar_start)
+                self.bar_builders[symbol_key][normalized_tf] = builder
+                logger.debug(f"Subscribed to {symbol_key} {normalized_tf} bars")
     
     def register_timeframes(self, symbol: str, timeframes: Iterable[str]):
         """Register one or more timeframes for a symbol (ensures builders exist)."""
         symbol_key = symbol.upper()
-        now = datetime.now(timezone.utc)
-        for tf in timeframes:
-            normalized = self._normalize_timeframe(tf)
-            if not normalized:
-                continue
-            self.symbol_timeframes[symbol_key].add(normalized)
-            if normalized not in self.bar_builders[symbol_key]:
-                bar_start = self._get_bar_start_time(now, normalized)
-                self.bar_builders[symbol_key][normalized] = BarBuilder(symbol_key, normalized, bar_start)
-                logger.debug(f"Registered timeframe {normalized} for {symbol_key}")
+        now = server_now()
+        with self._state_lock, self._symbol_lock(symbol_key):
+            for tf in timeframes:
+                normalized = self._normalize_timeframe(tf)
+                if not normalized:
+                    continue
+                self.symbol_timeframes[symbol_key].add(normalized)
+                if normalized not in self.bar_builders[symbol_key]:
+                    bar_start = self._get_bar_start_time(now, normalized, symbol_key)
+                    self.bar_builders[symbol_key][normalized] = BarBuilder(symbol_key, normalized, bar_start)
+                    logger.debug(f"Registered timeframe {normalized} for {symbol_key}")
     
     def unsubscribe_timeframe(self, symbol: str, timeframe: str):
         """Unsubscribe from bar updates for a symbol/timeframe."""
         symbol_key = symbol.upper()
-        if symbol_key in self.bar_builders:
-            self.bar_builders[symbol_key].pop(timeframe, None)
-            if not self.bar_builders[symbol_key]:
-                del self.bar_builders[symbol_key]
-        if symbol_key in self.symbol_timeframes:
-            self.symbol_timeframes[symbol_key].discard(self._normalize_timeframe(timeframe))
+        with self._state_lock, self._symbol_lock(symbol_key):
+            if symbol_key in self.bar_builders:
+                self.bar_builders[symbol_key].pop(timeframe, None)
+                if not self.bar_builders[symbol_key]:
+                    del self.bar_builders[symbol_key]
+            if symbol_key in self.symbol_timeframes:
+                self.symbol_timeframes[symbol_key].discard(self._normalize_timeframe(timeframe))
+    
+    def remove_symbol(self, symbol: str) -> bool:
+        """
+        Drop all state for a symbol (builders, history, last-completed bars, timeframes).
+        
+        The forming bars are discarded, not emitted.
+        
+        Returns:
+            bool: True if the symbol was tracked
+        """
+        symbol_key = symbol.upper()
+        with self._state_lock, self._symbol_lock(symbol_key):
+            tracked = symbol_key in self.bar_builders or symbol_key in self.bar_history
+            for state in (self.bar_builders, self.completed_bars, self.bar_history,
+                          self.symbol_timeframes, self._bars_completed_by_symbol):
+                state.pop(symbol_key, None)
+        if tracked:
+            logger.info(f"📊 Removed {symbol_key} from bar aggregator")
+        return tracked
+    
+    def symbols(self) -> List[str]:
+        """Symbols with active bar builders."""
+        return sorted(list(self.bar_builders.keys()))
+    
+    def _select(self, state: Dict[str, Dict[str, Any]], symbols: Optional[Iterable[str]],
+                timeframes: Optional[Iterable[str]]) -> Iterable[Tuple[str, Dict[str, Any]]]:
+        """Yield (symbol, {timeframe: value}) snapshots filtered by symbol/timeframe, each under its symbol lock."""
+        keys = [s.upper() for s in symbols] if symbols is not None else list(state.keys())
+        wanted = {self._normalize_timeframe(tf) for tf in timeframes} if timeframes is not None else None
+        for symbol_key in keys:
+            with self._symbol_lock(symbol_key):
+                frames = state.get(symbol_key)
+                if not frames:
+                    continue
+                snapshot = {tf: value for tf, value in frames.items() if wanted is None or tf in wanted}
+            if snapshot:
+                yield symbol_key, snapshot
+    
+    def get_current_bars(self, symbols: Optional[Iterable[str]] = None,
+                         timeframes: Optional[Iterable[str]] = None) -> Dict[str, Dict[str, Bar]]:
+        """
+        Forming bars for many symbols at once.
+        
+        Args:
+            symbols: Only these symbols (default: all)
+            timeframes: Only these timeframes (default: all)
+        
+        Returns:
+            {symbol: {timeframe: Bar}} for builders that have received a tick
+        """
+        result: Dict[str, Dict[str, Bar]] = {}
+        for symbol_key, builders in self._select(self.bar_builders, symbols, timeframes):
+            bars = {}
+            for timeframe, builder in builders.items():
+                if builder.open is None:
+                    continue
+                bar = builder.to_bar()
+                bar.is_rth = self.is_rth(symbol_key, bar.timestamp)
+                bars[timeframe] = bar
+            if bars:
+                result[symbol_key] = bars
+        return result
+    
+    def get_last_completed_bars(self, symbols: Optional[Iterable[str]] = None,
+                                timeframes: Optional[Iterable[str]] = None) -> Dict[str, Dict[str, Bar]]:
+        """Last completed bar per symbol/timeframe, filtered like get_current_bars()."""
+        return dict(self._select(self.completed_bars, symbols, timeframes))
+    
+    def get_symbol_stats(self, symbols: Optional[Iterable[str]] = None) -> Dict[str, Dict[str, Any]]:
+        """Per-symbol timeframes, completed-bar count and last tick time."""
+        stats: Dict[str, Dict[str, Any]] = {}
+        for symbol_key, builders in self._select(self.bar_builders, symbols, None):
+            updates = [b.last_update for b in builders.values() if b.last_update is not None]
+            stats[symbol_key] = {
+                "timeframes": sorted(builders),
+                "bars_completed": self._bars_completed_by_symbol.get(symbol_key, 0),
+                "last_update": max(updates).isoformat() if updates else None,
+            }
+        return stats
     
     def _should_start_new_bar(self, builder: BarBuilder, timeframe: str, current_time: datetime) -> bool:
         """Check if we should start a new bar based on timeframe."""
+        if parse_bar_threshold(timeframe) is not None:
+            return False  # Activity bars close on volume/tick/notional, never on time
         if builder.bar_start is None:
             return True
         
-        bar_end = self._get_bar_end_time(builder.bar_start, timeframe)
+        bar_end = self._get_bar_end_time(builder.bar_start, timeframe, builder.symbol)
         return current_time >= bar_end
     
-    def _get_bar_start_time(self, timestamp: datetime, timeframe: str) -> datetime:
-        """Get the start time for a bar given a timestamp and timeframe."""
-        # Parse timeframe (e.g., '5m' -> 5 minutes)
-        if timeframe.endswith('m'):
-            minutes = int(timeframe[:-1])
-            bar_seconds = minutes * 60
-        elif timeframe.endswith('s'):
-            bar_seconds = int(timeframe[:-1])
-        elif timeframe.endswith('h'):
-            hours = int(timeframe[:-1])
-            bar_seconds = hours * 3600
-        elif timeframe.endswith('d'):
-            days = int(timeframe[:-1])
-            bar_seconds = days * 86400
-        else:
-            # Default to 1 minute if unknown timeframe
-            bar_seconds = 60
-        total_seconds = timestamp.timestamp()
-        bar_start_seconds = (int(total_seconds) // bar_seconds) * bar_seconds
-        return datetime.fromtimestamp(bar_start_seconds, tz=timezone.utc)
-    
-    def _get_bar_end_time(self, bar_start: datetime, timeframe: str) -> datetime:
-        """Get the end time for a bar."""
-        if timeframe.endswith('m'):
-            minutes = int(timeframe[:-1])
-            return bar_start + timedelta(minutes=minutes)
-        elif timeframe.endswith('s'):
-            seconds = int(timeframe[:-1])
-            return bar_start + timedelta(seconds=seconds)
-        elif timeframe.endswith('h'):
-            hours = int(timeframe[:-1])
-            return bar_start + timedelta(hours=hours)
-        elif timeframe.endswith('d'):
-            days = int(timeframe[:-1])
-            return bar_start + timedelta(days=days)
-        else:
-            return bar_start + timedelta(minutes=1)
+    def _get_bar_start_time(self, timestamp: datetime, timeframe: str, symbol: Optional[str] = None) -> datetime:
+        """Get the start time for a bar given a timestamp and timeframe (session-anchored with a calendar)."""
+        bounds = self._session_bounds(symbol, timestamp)
+        if bounds is None:
+            return bar_start_time(timestamp, timeframe)
+        bar_seconds = timeframe_seconds(timeframe)
+        elapsed = int((timestamp - bounds[0]).total_seconds())
+        return bounds[0] + timedelta(seconds=(elapsed // bar_seconds) * bar_seconds)
+    
+    def _get_bar_end_time(self, bar_start: datetime, timeframe: str, symbol: Optional[str] = None) -> datetime:
+        """Get the end time for a bar (never past the session close with a calendar)."""
+        bar_end = bar_start + timedelta(seconds=timeframe_seconds(timeframe))
+        bounds = self._session_bounds(symbol, bar_start)
+        if bounds is not None and bar_end > bounds[1]:
+            return bounds[1]
+        return bar_end
+    
+    def _session_bounds(self, symbol: Optional[str], timestamp: datetime) -> Optional[Tuple[datetime, datetime]]:
+        if self.session_calendar is None or not symbol:
+            return None
+        if timestamp.tzinfo is None:
+            timestamp = timestamp.replace(tzinfo=timezone.utc)
+        return self.session_calendar.session_bounds(symbol, timestamp)
+    
+    def is_rth(self, symbol: str, timestamp: datetime) -> Optional[bool]:
+        """True if a timestamp is inside regular trading hours (None without a session calendar)."""
+        if self.session_calendar is None:
+            return None
+        return self.session_calendar.is_rth(symbol, timestamp)
     
     def get_current_bar(self, symbol: str, timeframe: str) -> Optional[Bar]:
         """Get the current (forming) bar for a symbol/timeframe."""
         symbol_key = symbol.upper()
-        if symbol_key in self.bar_builders:
-            builder = self.bar_builders[symbol_key].get(timeframe)
-            if builder and builder.open is not None:
-                return builder.to_bar()
-        return None
+        with self._symbol_lock(symbol_key):
+            builder = self.bar_builders.get(symbol_key, {}).get(timeframe)
+            if builder is None or builder.open is None:
+                return None
+            bar = builder.to_bar()
+        bar.is_rth = self.is_rth(symbol_key, bar.timestamp)
+        return bar
     
     def get_last_completed_bar(self, symbol: str, timeframe: str) -> Optional[Bar]:
         """Get the last completed bar for a symbol/timeframe."""
-        symbol_key = symbol.upper()
-        if symbol_key in self.completed_bars:
-            return self.completed_bars[symbol_key].get(timeframe)
-        return None
+        return self.completed_bars.get(symbol.upper(), {}).get(timeframe)
 
     def _normalize_timeframe(self, timeframe: str) -> str:
         """Normalize timeframe strings (strip spaces, lower-case)."""
@@ -365,7 +980,7 @@ class BarAggregator:
                 continue
             self.symbol_timeframes[symbol_key].add(normalized)
             if normalized not in self.bar_builders[symbol_key]:
-                bar_start = self._get_bar_start_time(timestamp, normalized)
+                bar_start = self._get_bar_start_time(timestamp, normalized, symbol_key)
                 self.bar_builders[symbol_key][normalized] = BarBuilder(symbol_key, normalized, bar_start)
                 logger.debug(f"Initialized {normalized} bar builder for {symbol_key}")
         
@@ -0,0 +1,330 @@
+"""
+One-call session bootstrap.
+
+Runs the full startup sequence and returns a ready-to-trade handle:
+
+1. authenticate      - obtain a valid token (retries with linear backoff)
+2. select_account    - preferred account (config > persisted dashboard default),
+                       falling back to a PRAC/PRACTICE account, then the first one
+3. resolve_contracts - load the contract list and map each watchlist symbol to its contract
+4. connect_hubs      - start the market hub and subscribe the watchlist to quotes
+5. warm_up           - preload bar history per symbol/timeframe into the bar
+                       aggregator and seed indicators (tick validator ATR)
+6. reconcile         - reseed the position ledger from broker positions and load working orders
+7. strategies        - apply persisted strategy state and auto-start enabled strategies
+
+Each stage reports 'started' and then 'completed', 'skipped' or 'failed'
+through an optional progress callback (sync or async). Authentication and
+account selection failures are fatal (BootstrapError); problems in later
+stages are recorded as warnings on the returned session so one bad symbol
+does not keep the bot from starting.
+
+Configuration:
+- PROJECT_X_API_KEY / PROJECT_X_USERNAME: Credentials (TOPSETPX_* accepted)
+- TOPSTEPX_ACCOUNT_ID: Preferred account ID
+- BOOTSTRAP_SYMBOLS: Comma-separated watchlist (default empty)
+- BOOTSTRAP_WARMUP_TIMEFRAMES: Timeframes to preload (default '1m')
+- BOOTSTRAP_WARMUP_BARS: Bars preloaded per symbol/timeframe (default 300, 0 disables)
+- BOOTSTRAP_AUTH_RETRIES: Authentication attempts (default 3)
+"""
+
+import asyncio
+import inspect
+import logging
+import os
+import time
+from dataclasses import dataclass, field
+from datetime import datetime, timedelta, timezone
+from enum import Enum
+from typing import Any, Callable, Dict, List, Optional
+
+from core.history_client import parse_timeframe
+
+logger = logging.getLogger(__name__)
+
+# Minimum warm-up lookback so short timeframes still reach back across a weekend
+MIN_WARMUP_LOOKBACK = timedelta(days=4)
+
+
+class BootstrapStage(Enum):
+    """Startup stages, in execution order."""
+    AUTHENTICATE = "authenticate"
+    SELECT_ACCOUNT = "select_account"
+    RESOLVE_CONTRACTS = "resolve_contracts"
+    CONNECT_HUBS = "connect_hubs"
+    WARM_UP = "warm_up"
+    RECONCILE = "reconcile"
+    STRATEGIES = "strategies"
+
+
+class BootstrapError(Exception):
+    """A fatal startup failure."""
+
+    def __init__(self, stage: BootstrapStage, message: str):
+        super().__init__(f"{stage.value}: {message}")
+        self.stage = stage
+
+
+@dataclass
+class BootstrapProgress:
+    """Progress report for one stage."""
+    stage: BootstrapStage
+    status: str  # 'started', 'completed', 'skipped', 'failed'
+    message: str = ""
+    elapsed: float = 0.0  # Seconds spent in the stage (0 for 'started')
+
+
+def _env_list(name: str, default: str = '') -> List[str]:
+    return [item.strip() for item in os.getenv(name, default).split(',') if item.strip()]
+
+
+@dataclass
+class BootstrapConfig:
+    """What to bring up. from_env() reads the environment."""
+    api_key: Optional[str] = None
+    username: Optional[str] = None
+    account_id: Optional[str] = None
+    symbols: List[str] = field(default_factory=list)
+    warmup_timeframes: List[str] = field(default_factory=lambda: ['1m'])
+    warmup_bars: int = 300
+    auth_retries: int = 3
+    connect_hubs: bool = True
+    reconcile: bool = True
+    start_strategies: bool = True
+
+    @classmethod
+    def from_env(cls) -> 'BootstrapConfig':
+        return cls(
+            api_key=os.getenv('PROJECT_X_API_KEY') or os.getenv('TOPSETPX_API_KEY'),
+            username=os.getenv('PROJECT_X_USERNAME') or os.getenv('TOPSETPX_USERNAME'),
+            account_id=os.getenv('TOPSTEPX_ACCOUNT_ID'),
+            symbols=[s.upper() for s in _env_list('BOOTSTRAP_SYMBOLS')],
+            warmup_timeframes=_env_list('BOOTSTRAP_WARMUP_TIMEFRAMES', '1m'),
+            warmup_bars=int(os.getenv('BOOTSTRAP_WARMUP_BARS', '300')),
+            auth_retries=int(os.getenv('BOOTSTRAP_AUTH_RETRIES', '3')),
+        )
+
+
+@dataclass
+class TradingSession:
+    """Handle returned by bootstrap()."""
+    bot: Any
+    account: Optional[Dict] = None
+    contracts: Dict[str, str] = field(default_factory=dict)
+    subscribed: List[str] = field(default_factory=list)
+    warmed_bars: Dict[str, int] = field(default_factory=dict)  # 'MNQ:1m' -> bars loaded
+    positions: Dict[str, int] = field(default_factory=dict)
+    open_orders: int = 0
+    warnings: List[str] = field(default_factory=list)
+    stage_seconds: Dict[str, float] = field(default_factory=dict)
+
+    @property
+    def account_id(self) -> Optional[str]:
+        return str(self.account['id']) if self.account else None
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "account": {"id": self.account.get('id'), "name": self.account.get('name')} if self.account else None,
+            "contracts": dict(self.contracts),
+            "subscribed": list(self.subscribed),
+            "warmed_bars": dict(self.warmed_bars),
+            "positions": dict(self.positions),
+            "open_orders": self.open_orders,
+            "warnings": list(self.warnings),
+            "stage_seconds": {k: round(v, 3) for k, v in self.stage_seconds.items()},
+        }
+
+
+def _is_practice(account: Dict) -> bool:
+    name = (account.get('name') or '').upper()
+    return 'PRAC' in name or 'PRACTICE' in name
+
+
+def select_account(accounts: List[Dict], preferred_id: Optional[str] = None) -> Dict:
+    """
+    Pick the trading account.
+
+    A preferred account that is not a PRAC/PRACTICE account is swapped for one
+    that is, when available. Without a preference the first PRAC account is
+    used, then the first account.
+
+    Raises:
+        BootstrapError: No accounts, or the preferred account does not exist
+    """
+    if not accounts:
+        raise BootstrapError(BootstrapStage.SELECT_ACCOUNT, "no accounts found")
+    practice = next((acc for acc in accounts if _is_practice(acc)), None)
+    if preferred_id:
+        selected = next((acc for acc in accounts if str(acc['id']) == str(preferred_id)), None)
+        if selected is None:
+            raise BootstrapError(BootstrapStage.SELECT_ACCOUNT,
+                                 f"preferred account {preferred_id} not found among "
+                                 f"{[acc.get('name') for acc in accounts]}")
+        if _is_practice(selected):
+            return selected
+        logger.warning(f"⚠️  Selected account '{selected.get('name')}' does not appear to be a PRAC/PRACTICE account!")
+        if practice:
+            logger.warning(f"✅ Switched to PRAC account: {practice['name']} (ID: {practice['id']})")
+            return practice
+        logger.error(f"❌ No PRAC account found! Continuing with non-PRAC account '{selected.get('name')}'")
+        return selected
+    if practice:
+        return practice
+    logger.warning(f"⚠️  Auto-selected first account (not PRAC): {accounts[0].get('name')} (ID: {accounts[0]['id']})")
+    return accounts[0]
+
+
+class _Reporter:
+    """Times stages and forwards progress, isolating callback errors."""
+
+    def __init__(self, session: TradingSession, callback: Optional[Callable[[BootstrapProgress], Any]]):
+        self.session = session
+        self.callback = callback
+
+    async def emit(self, progress: BootstrapProgress) -> None:
+        level = logging.WARNING if progress.status == 'failed' else logging.INFO
+        logger.log(level, f"🚀 Bootstrap {progress.stage.value}: {progress.status}"
+                          f"{' - ' + progress.message if progress.message else ''}")
+        if self.callback is None:
+            return
+        try:
+            result = self.callback(progress)
+            if inspect.isawaitable(result):
+                await result
+        except Exception as e:
+            logger.debug(f"Bootstrap progress callback error: {e}")
+
+    async def run(self, stage: BootstrapStage, step: Callable[[], Any], skip: Optional[str] = None) -> None:
+        """Run one stage. Fatal errors propagate as BootstrapError; others become warnings."""
+        if skip:
+            await self.emit(BootstrapProgress(stage, 'skipped', skip))
+            return
+        await self.emit(BootstrapProgress(stage, 'started'))
+        started = time.monotonic()
+        try:
+            message = await step()
+        except BootstrapError as e:
+            await self.emit(BootstrapProgress(stage, 'failed', str(e), time.monotonic() - started))
+            raise
+        except Exception as e:
+            self.session.warnings.append(f"{stage.value}: {e}")
+            await self.emit(BootstrapProgress(stage, 'failed', str(e), time.monotonic() - started))
+            return
+        finally:
+            self.session.stage_seconds[stage.value] = time.monotonic() - started
+        await self.emit(BootstrapProgress(stage, 'completed', message or "", time.monotonic() - started))
+
+
+async def bootstrap(config: Optional[BootstrapConfig] = None, bot: Any = None,
+                    progress: Optional[Callable[[BootstrapProgress], Any]] = None) -> TradingSession:
+    """
+    Bring a trading session up from nothing.
+
+    Args:
+        config: What to bring up (default: BootstrapConfig.from_env())
+        bot: Existing TopStepXTradingBot (created from the config credentials if None)
+        progress: Called with a BootstrapProgress per stage transition (may be async)
+
+    Returns:
+        TradingSession: Handle with the bot, selected account and startup report
+
+    Raises:
+        BootstrapError: Missing credentials, authentication failure or no usable account
+        ArtifactLockError: Another bot process holds the bot's artifact directories
+    """
+    config = config or BootstrapConfig.from_env()
+    if bot is None:
+        if not config.api_key or not config.username:
+            raise BootstrapError(BootstrapStage.AUTHENTICATE, "missing API credentials")
+        from trading_bot import TopStepXTradingBot
+        bot = TopStepXTradingBot(api_key=config.api_key, username=config.username)
+    bot.claim_artifact_dirs()  # Fail fast before touching the API
+    session = TradingSession(bot=bot)
+    reporter = _Reporter(session, progress)
+
+    async def authenticate():
+        attempts = max(1, config.auth_retries)
+        for attempt in range(1, attempts + 1):
+            if await bot._ensure_valid_token():
+                return f"attempt {attempt}"
+            if attempt < attempts:
+                wait_time = attempt * 5
+                logger.warning(f"⚠️  Authentication failed (attempt {attempt}/{attempts}), retrying in {wait_time}s...")
+                await asyncio.sleep(wait_time)
+        raise BootstrapError(BootstrapStage.AUTHENTICATE, f"failed after {attempts} attempt(s)")
+
+    async def choose_account():
+        preferred = config.account_id
+        db = getattr(bot, 'db', None)
+        if not preferred and db is not None:
+            try:
+                settings = db.get_dashboard_settings()
+                preferred = settings.get('defaultAccount') or settings.get('default_account')
+            except Exception as e:
+                logger.warning(f"⚠️  Failed to load persisted settings: {e}")
+        session.account = select_account(await bot.list_accounts(), preferred)
+        bot.selected_account = session.account
+        return f"{session.account.get('name')} (ID: {session.account['id']})"
+
+    async def resolve_contracts():
+        await bot.get_available_contracts()
+        for symbol in config.symbols:
+            try:
+                session.contracts[symbol] = bot._get_contract_id(symbol)
+            except ValueError as e:
+                session.warnings.append(f"resolve_contracts: {symbol}: {e}")
+        return f"{len(session.contracts)}/{len(config.symbols)} resolved"
+
+    async def connect_hubs():
+        await bot._ensure_market_socket_started()
+        for symbol in session.contracts:
+            await bot._ensure_quote_subscription(symbol)
+            session.subscribed.append(symbol)
+        return f"{len(session.subscribed)} symbol(s)"
+
+    async def warm_up():
+        validator = getattr(bot, 'tick_validator', None)
+        now = datetime.now(timezone.utc)
+        for symbol in session.contracts:
+            for timeframe in config.warmup_timeframes:
+                key = f"{symbol}:{timeframe}"
+                try:
+                    bar_seconds = parse_timeframe(timeframe)[2]
+                    lookback = max(timedelta(seconds=bar_seconds * config.warmup_bars), MIN_WARMUP_LOOKBACK)
+                    bars = (await bot.history_client.fetch_bars(symbol, timeframe, now - lookback, now))
+                    bars = bars[-config.warmup_bars:]
+                except Exception as e:
+                    session.warnings.append(f"warm_up: {key}: {e}")
+                    continue
+                bot.bar_aggregator.preload_history(symbol, timeframe, bars)
+                if validator is not None and timeframe == validator.atr_timeframe:
+                    for bar in bars:
+                        validator.add_bar(bar)
+                session.warmed_bars[key] = len(bars)
+        return f"{sum(session.warmed_bars.values())} bars"
+
+    async def reconcile():
+        session.positions, open_orders = await bot.reconcile_account_state(session.account_id)
+        session.open_orders = len(open_orders)
+        return f"{len(session.positions)} position(s), {session.open_orders} working order(s)"
+
+    async def start_strategies():
+        await bot.strategy_manager.apply_persisted_states()
+        await bot.strategy_manager.auto_start_enabled_strategies()
+
+    await reporter.run(BootstrapStage.AUTHENTICATE, authenticate)
+    await reporter.run(BootstrapStage.SELECT_ACCOUNT, choose_account)
+    await reporter.run(BootstrapStage.RESOLVE_CONTRACTS, resolve_contracts,
+                       skip=None if config.symbols else "empty watchlist")
+    await reporter.run(BootstrapStage.CONNECT_HUBS, connect_hubs,
+                       skip=None if config.connect_hubs and session.contracts else "disabled or no contracts")
+    await reporter.run(BootstrapStage.WARM_UP, warm_up,
+                       skip=None if config.warmup_bars > 0 and session.contracts else "disabled or no contracts")
+    await reporter.run(BootstrapStage.RECONCILE, reconcile, skip=None if config.reconcile else "disabled")
+    await reporter.run(BootstrapStage.STRATEGIES, start_strategies,
+                       skip=None if config.start_strategies and hasattr(bot, 'strategy_manager') else "disabled")
+    if session.warnings:
+        logger.warning(f"⚠️  Bootstrap finished with {len(session.warnings)} warning(s): {session.warnings}")
+    else:
+        logger.info("✅ Bootstrap complete - ready to trade")
+    return session
@@ -0,0 +1,373 @@
+"""
+Break-Even Stop Management
+
+Moves the protective stop of an open position to its entry price (plus an
+offset) once the position is a configurable number of ticks in profit.
+
+The trigger is evaluated on every live quote and trade, on the thread that
+delivers the market event, so the stop modification is sent on the first
+price that crosses the threshold instead of on the next polling interval.
+The only hop is scheduling the REST call on the bot's event loop.
+
+Lifecycle of a position:
+- watch(entry_order_id): the entry order should get break-even management
+- on_fill(): the watched entry filled; the position is armed at its fill
+  price and the stop leg of its bracket is looked up right away, so the
+  lookup is not on the critical path when the trigger fires
+- on_market_event(): profit in ticks is measured from the fill (longs exit
+  at the bid, shorts at the ask); at the trigger the stop is moved
+- an opposite-side fill on the same symbol (stop, target, manual close)
+  disarms the account's positions on that symbol
+
+Stops are only ever tightened: if the stop is already at or beyond the
+break-even price, nothing is sent. A failed modification re-arms the
+position so the next price retries it, up to BREAK_EVEN_MAX_ATTEMPTS.
+
+Usage:
+    manager = BreakEvenManager(modify=bot.modify_order, find_legs=bot.find_bracket_legs)
+    manager.bind_loop()                      # From the bot's event loop
+    bot.add_market_event_listener(manager.on_market_event)
+    manager.watch(order_id)                  # After placing a bracketed entry
+    manager.on_fill(account_id, order)       # From fill processing
+
+Configuration:
+- BREAK_EVEN_ENABLED: Create the manager in the bot (default false)
+- BREAK_EVEN_TRIGGER_TICKS: Profit in ticks that triggers the move (default 40)
+- BREAK_EVEN_OFFSET_TICKS: Ticks beyond entry to place the stop, locking in profit (default 1)
+- BREAK_EVEN_MAX_ATTEMPTS: Modification attempts per position before giving up (default 3)
+"""
+
+import asyncio
+import inspect
+import logging
+import os
+import threading
+import time
+from dataclasses import asdict, dataclass
+from typing import Any, Callable, Dict, Optional
+
+from core.market_events import MarketEvent, Quote, Trade
+from core.strategy_engine.backtest import TICK_SIZES
+from core.volume_profile import normalize_symbol
+
+logger = logging.getLogger(__name__)
+
+# modify_order(order_id, new_price=..., account_id=..., order_type=...) -> response dict (or awaitable)
+# find_bracket_legs(order_id, account_id=...) -> {'stop': order, ...} (or awaitable)
+OrderModifier = Callable[..., Any]
+LegFinder = Callable[..., Any]
+
+
+@dataclass
+class BreakEvenPosition:
+    """A filled entry under break-even management."""
+    entry_order_id: str
+    account_id: str
+    symbol: str
+    side: str  # BUY (long) / SELL (short)
+    entry_price: float
+    trigger_ticks: int
+    offset_ticks: int
+    tick_size: float
+    stop_order_id: Optional[str] = None
+    stop_price: Optional[float] = None
+    stop_order_type: int = 4
+    state: str = 'armed'  # armed -> moving -> moved / skipped / failed
+    attempts: int = 0
+    trigger_price: Optional[float] = None
+    armed_at: float = 0.0
+    triggered_at: Optional[float] = None
+
+    @property
+    def break_even_price(self) -> float:
+        offset = self.offset_ticks * self.tick_size
+        return round(self.entry_price + offset if self.side == 'BUY' else self.entry_price - offset, 10)
+
+    def profit_ticks(self, price: float) -> float:
+        move = price - self.entry_price if self.side == 'BUY' else self.entry_price - price
+        return move / self.tick_size
+
+    def to_dict(self) -> Dict[str, Any]:
+        data = asdict(self)
+        data['break_even_price'] = self.break_even_price
+        return data
+
+
+class BreakEvenManager:
+    """
+    Moves stops to break-even on live prices for watched entries.
+    """
+
+    def __init__(self, modify: OrderModifier, find_legs: Optional[LegFinder] = None,
+                 trigger_ticks: Optional[int] = None, offset_ticks: Optional[int] = None,
+                 max_attempts: Optional[int] = None, tick_sizes: Optional[Dict[str, float]] = None):
+        """
+        Initialize manager.
+
+        Args:
+            modify: Order modification (e.g. TradingBot.modify_order)
+            find_legs: Bracket leg lookup for the stop order (e.g. TradingBot.find_bracket_legs)
+            trigger_ticks: Default profit in ticks that triggers the move (env: BREAK_EVEN_TRIGGER_TICKS)
+            offset_ticks: Default ticks beyond entry for the new stop (env: BREAK_EVEN_OFFSET_TICKS)
+            max_attempts: Modification attempts per position (env: BREAK_EVEN_MAX_ATTEMPTS)
+            tick_sizes: Tick size overrides by root symbol
+
+        Raises:
+            ValueError: The trigger does not leave room for the offset
+        """
+        self.modify = modify
+        self.find_legs = find_legs
+        self.trigger_ticks = trigger_ticks if trigger_ticks is not None else int(
+            os.getenv('BREAK_EVEN_TRIGGER_TICKS', '40'))
+        self.offset_ticks = offset_ticks if offset_ticks is not None else int(
+            os.getenv('BREAK_EVEN_OFFSET_TICKS', '1'))
+        self.max_attempts = max_attempts if max_attempts is not None else int(
+            os.getenv('BREAK_EVEN_MAX_ATTEMPTS', '3'))
+        self._validate(self.trigger_ticks, self.offset_ticks)
+        self.tick_sizes = dict(tick_sizes or {})
+        self._watched: Dict[str, Dict[str, int]] = {}  # entry order ID -> tick overrides
+        self._positions: Dict[str, BreakEvenPosition] = {}  # entry order ID -> position
+        self._by_symbol: Dict[str, Dict[str, BreakEvenPosition]] = {}  # root symbol -> armed positions
+        self._lock = threading.Lock()
+        self._loop: Optional[asyncio.AbstractEventLoop] = None
+        self._tasks: set = set()
+        self.stops_moved = 0
+        self.stops_skipped = 0
+        self.modify_failures = 0
+        self.last_error: Optional[str] = None
+        self.trigger_latencies_ms: list = []  # Trigger -> modification acknowledged, last 100
+
+    @staticmethod
+    def _validate(trigger_ticks: int, offset_ticks: int) -> None:
+        if offset_ticks < 0 or trigger_ticks <= offset_ticks:
+            raise ValueError(f"Break-even trigger ({trigger_ticks} ticks) must exceed the offset "
+                             f"({offset_ticks} ticks)")
+
+    def bind_loop(self, loop: Optional[asyncio.AbstractEventLoop] = None) -> None:
+        """Run stop modifications on this loop (default: the running loop)."""
+        self._loop = loop or asyncio.get_running_loop()
+
+    def tick_size(self, symbol: str) -> float:
+        root = normalize_symbol(symbol)
+        return self.tick_sizes.get(root, TICK_SIZES.get(root, 0.25))
+
+    # ---------------------------
+    # Registration
+    # ---------------------------
+    def watch(self, entry_order_id: str, trigger_ticks: Optional[int] = None,
+              offset_ticks: Optional[int] = None) -> None:
+        """
+        Manage the position an entry order opens once it fills.
+
+        Raises:
+            ValueError: The trigger does not leave room for the offset
+        """
+        trigger = trigger_ticks if trigger_ticks is not None else self.trigger_ticks
+        offset = offset_ticks if offset_ticks is not None else self.offset_ticks
+        self._validate(trigger, offset)
+        with self._lock:
+            self._watched[str(entry_order_id)] = {'trigger_ticks': trigger, 'offset_ticks': offset}
+
+    def unwatch(self, entry_order_id: str) -> None:
+        """Stop managing an entry (cancelled order or closed position)."""
+        with self._lock:
+            self._watched.pop(str(entry_order_id), None)
+            position = self._positions.pop(str(entry_order_id), None)
+            if position is not None:
+                self._by_symbol.get(position.symbol, {}).pop(position.entry_order_id, None)
+
+    def arm(self, entry_order_id: str, account_id: str, symbol: str, side: str, entry_price: float,
+            trigger_ticks: Optional[int] = None, offset_ticks: Optional[int] = None,
+            stop_order_id: Optional[str] = None) -> BreakEvenPosition:
+        """
+        Start managing an open position directly (without watching its entry first).
+
+        Raises:
+            ValueError: The trigger does not leave room for the offset
+        """
+        trigger = trigger_ticks if trigger_ticks is not None else self.trigger_ticks
+        offset = offset_ticks if offset_ticks is not None else self.offset_ticks
+        self._validate(trigger, offset)
+        root = normalize_symbol(symbol)
+        position = BreakEvenPosition(
+            entry_order_id=str(entry_order_id), account_id=str(account_id), symbol=root,
+            side='BUY' if str(side).upper() in ('BUY', 'LONG', '0') else 'SELL', entry_price=float(entry_price),
+            trigger_ticks=trigger, offset_ticks=offset, tick_size=self.tick_size(root),
+            stop_order_id=str(stop_order_id) if stop_order_id else None, armed_at=time.time(),
+        )
+        with self._lock:
+            self._watched.pop(position.entry_order_id, None)
+            self._positions[position.entry_order_id] = position
+            self._by_symbol.setdefault(root, {})[position.entry_order_id] = position
+        logger.info(f"🔧 Break-even armed for {root} {position.side} @ {position.entry_price} "
+                    f"(+{trigger} ticks -> stop {position.break_even_price})")
+        if position.stop_order_id is None and self.find_legs is not None:
+            self._schedule(self._resolve_stop(position))
+        return position
+
+    def on_fill(self, account_id: str, order: Dict) -> None:
+        """
+        Feed a filled gateway order: arms watched entries, disarms positions an opposite fill closes.
+        """
+        order_id = str(order.get('id') or order.get('orderId') or '')
+        side = 'BUY' if order.get('side', 0) in (0, '0', 'BUY') else 'SELL'
+        symbol = normalize_symbol(str(order.get('symbol') or order.get('contractId') or ''))
+        with self._lock:
+            overrides = self._watched.get(order_id)
+            closed = [p for p in self._positions.values()
+                      if p.symbol == symbol and p.account_id == str(account_id) and p.side != side]
+        if overrides is not None:
+            price = order.get('fillPrice') or order.get('executionPrice') or order.get('filledPrice')
+            if price and symbol:
+                self.arm(order_id, account_id, symbol, side, float(price), **overrides)
+            return
+        for position in closed:
+            self.unwatch(position.entry_order_id)
+            logger.debug(f"Break-even disarmed for {position.symbol}: position closed by order {order_id}")
+
+    # ---------------------------
+    # Hot path
+    # ---------------------------
+    def on_market_event(self, event: MarketEvent) -> None:
+        """
+        Market event listener: move stops of positions whose trigger the price reached.
+
+        Runs on the thread delivering the event; only the REST call is scheduled.
+        """
+        if not isinstance(event, (Quote, Trade)) or not self._by_symbol:
+            return
+        positions = self._by_symbol.get(normalize_symbol(event.symbol))
+        if not positions:
+            return
+        triggered = []
+        with self._lock:
+            for position in positions.values():
+                if position.state != 'armed':
+                    continue
+                price = self._exit_price(event, position.side)
+                if price is None or position.profit_ticks(price) < position.trigger_ticks:
+                    continue
+                position.state = 'moving'
+                position.attempts += 1
+                position.trigger_price = price
+                position.triggered_at = time.time()
+                triggered.append(position)
+        for position in triggered:
+            self._schedule(self._move_stop(position))
+
+    @staticmethod
+    def _exit_price(event: MarketEvent, side: str) -> Optional[float]:
+        """Price a position could be closed at: trade price, or bid (long) / ask (short)."""
+        if isinstance(event, Trade):
+            return event.price
+        price = event.bid if side == 'BUY' else event.ask
+        return price if price is not None else event.last
+
+    # ---------------------------
+    # Stop modification
+    # ---------------------------
+    @staticmethod
+    async def _call(function: Callable[..., Any], *args: Any, **kwargs: Any) -> Any:
+        result = function(*args, **kwargs)
+        return await result if inspect.isawaitable(result) else result
+
+    async def _resolve_stop(self, position: BreakEvenPosition) -> bool:
+        """Look up the stop leg of the position's bracket."""
+        try:
+            legs = await self._call(self.find_legs, position.entry_order_id, account_id=position.account_id)
+        except Exception as e:
+            legs = {'error': str(e)}
+        stop = (legs or {}).get('stop')
+        if not stop:
+            logger.warning(f"⚠️  No stop leg found for break-even on {position.symbol} entry "
+                           f"{position.entry_order_id}: {(legs or {}).get('error', 'no working stop')}")
+            return False
+        position.stop_order_id = str(stop.get('id'))
+        position.stop_order_type = int(stop.get('type') or 4)
+        if stop.get('stopPrice') is not None:
+            position.stop_price = float(stop['stopPrice'])
+        return True
+
+    async def _move_stop(self, position: BreakEvenPosition) -> None:
+        if position.stop_order_id is None and (self.find_legs is None or not await self._resolve_stop(position)):
+            self._finish(position, 'failed', "no stop order to move")
+            return
+        target = position.break_even_price
+        if position.stop_price is not None and (
+                position.stop_price >= target if position.side == 'BUY' else position.stop_price <= target):
+            self._finish(position, 'skipped')
+            return
+        try:
+            result = await self._call(self.modify, position.stop_order_id, new_price=target,
+                                      account_id=position.account_id, order_type=position.stop_order_type)
+            error = None if result and result.get('success', True) and not result.get('error') else \
+                (result or {}).get('error') or (result or {}).get('errorMessage') or 'modification rejected'
+        except Exception as e:
+            error = str(e)
+        if error:
+            self._finish(position, 'failed' if position.attempts >= self.max_attempts else 'armed', error)
+            return
+        position.stop_price = target
+        self._finish(position, 'moved')
+
+    def _finish(self, position: BreakEvenPosition, state: str, error: Optional[str] = None) -> None:
+        with self._lock:
+            position.state = state
+            if state == 'moved':
+                self.stops_moved += 1
+                self.trigger_latencies_ms = self.trigger_latencies_ms[-99:] + [
+                    round((time.time() - position.triggered_at) * 1000, 1)]
+            elif state == 'skipped':
+                self.stops_skipped += 1
+            if error:
+                self.modify_failures += 1
+                self.last_error = error
+            if state != 'armed':
+                self._by_symbol.get(position.symbol, {}).pop(position.entry_order_id, None)
+        if state == 'moved':
+            logger.info(f"✅ Stop for {position.symbol} {position.side} moved to break-even {position.stop_price} "
+                        f"(triggered at {position.trigger_price})")
+        elif state == 'skipped':
+            logger.info(f"Stop for {position.symbol} already at {position.stop_price}, break-even not needed")
+        elif state == 'armed':
+            logger.warning(f"⚠️  Break-even stop move for {position.symbol} failed ({error}), retrying on next price")
+        else:
+            logger.error(f"❌ Break-even stop move for {position.symbol} failed: {error}")
+
+    def _schedule(self, coro: Any) -> None:
+        """Run a coroutine on the bound loop, or on a thread of its own without one."""
+        try:
+            running = asyncio.get_running_loop()
+        except RuntimeError:
+            running = None
+        if running is not None and (self._loop is None or running is self._loop):
+            task = running.create_task(coro)
+            self._tasks.add(task)
+            task.add_done_callback(self._tasks.discard)
+        elif self._loop is not None and self._loop.is_running():
+            asyncio.run_coroutine_threadsafe(coro, self._loop)
+        else:
+            threading.Thread(target=asyncio.run, args=(coro,), name='break-even', daemon=True).start()
+
+    # ---------------------------
+    # Status
+    # ---------------------------
+    def get_positions(self) -> Dict[str, Dict[str, Any]]:
+        """Managed positions by entry order ID."""
+        with self._lock:
+            return {order_id: position.to_dict() for order_id, position in self._positions.items()}
+
+    def get_stats(self) -> Dict[str, Any]:
+        with self._lock:
+            latencies = sorted(self.trigger_latencies_ms)
+            return {
+                'trigger_ticks': self.trigger_ticks,
+                'offset_ticks': self.offset_ticks,
+                'watched_orders': len(self._watched),
+                'armed_positions': sum(len(positions) for positions in self._by_symbol.values()),
+                'stops_moved': self.stops_moved,
+                'stops_skipped': self.stops_skipped,
+                'modify_failures': self.modify_failures,
+                'median_trigger_to_ack_ms': latencies[len(latencies) // 2] if latencies else None,
+                'last_error': self.last_error,
+            }
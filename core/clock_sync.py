@@ -0,0 +1,236 @@
+"""
+Server Time Synchronization
+
+Estimates the offset between the local clock and broker/exchange time so
+bar boundaries, session windows and journal/audit timestamps follow the
+exchange instead of a drifting VPS clock.
+
+Two sources, the first one with enough samples wins:
+- Market feed: every hub payload with an exchange timestamp gives
+  exchange - received, which is the offset minus the message's delay.
+  The largest value seen (the fastest message) over the window is the
+  estimate, kept as per-minute maxima like the feed latency skew.
+- REST: the Date header of API responses, compared with the midpoint of
+  the request (second resolution, so half a second is added to the
+  truncated header time; the median of recent samples is used). Coarser,
+  but available before the feed is up and while markets are closed.
+
+Samples further off than CLOCK_SYNC_MAX_OFFSET_SECS (stale or replayed
+timestamps) are discarded. now() / time() return the corrected clock;
+with CLOCK_SYNC_ENABLED=false the offset is still estimated and reported
+but not applied.
+
+Usage:
+    clock = get_server_clock()
+    clock.record_payload(data)                              # In the hub handlers
+    clock.record_http_date(response.headers.get('Date'), sent_at, received_at)
+    clock.now()                                             # Corrected UTC datetime
+    server_now()                                            # Same, from anywhere
+
+Configuration:
+- CLOCK_SYNC_ENABLED: Apply the estimated offset to the bot's clock (default true)
+- CLOCK_SYNC_WINDOW: Minutes of feed samples used for the estimate (default 15)
+- CLOCK_SYNC_MIN_SAMPLES: Feed samples needed before they replace the REST estimate (default 20)
+- CLOCK_SYNC_MAX_OFFSET_SECS: Samples further off are discarded (default 300)
+- CLOCK_SYNC_WARN_MS: Log a warning when the offset exceeds this (default 250)
+"""
+
+import logging
+import os
+import statistics
+import threading
+import time
+from collections import deque
+from datetime import datetime, timezone, tzinfo
+from email.utils import parsedate_to_datetime
+from typing import Any, Deque, Dict, List, Optional
+
+logger = logging.getLogger(__name__)
+
+
+class ServerClock:
+    """
+    Local clock corrected by the estimated broker/exchange offset.
+    """
+
+    def __init__(self, enabled: Optional[bool] = None, window_minutes: Optional[int] = None,
+                 min_samples: Optional[int] = None, max_offset_secs: Optional[float] = None,
+                 warn_ms: Optional[float] = None, http_samples: int = 50):
+        """
+        Initialize clock.
+
+        Args:
+            enabled: Apply the offset in now()/time() (env: CLOCK_SYNC_ENABLED)
+            window_minutes: Feed estimate horizon (env: CLOCK_SYNC_WINDOW)
+            min_samples: Feed samples needed for the feed estimate (env: CLOCK_SYNC_MIN_SAMPLES)
+            max_offset_secs: Larger sample offsets are discarded (env: CLOCK_SYNC_MAX_OFFSET_SECS)
+            warn_ms: Offset that triggers a drift warning (env: CLOCK_SYNC_WARN_MS)
+            http_samples: REST Date samples kept for the median
+        """
+        self.enabled = enabled if enabled is not None else os.getenv(
+            'CLOCK_SYNC_ENABLED', 'true').lower() in ('true', '1', 'yes', 'on')
+        self.window_minutes = window_minutes if window_minutes is not None else int(
+            os.getenv('CLOCK_SYNC_WINDOW', '15'))
+        self.min_samples = min_samples if min_samples is not None else int(
+            os.getenv('CLOCK_SYNC_MIN_SAMPLES', '20'))
+        self.max_offset_secs = max_offset_secs if max_offset_secs is not None else float(
+            os.getenv('CLOCK_SYNC_MAX_OFFSET_SECS', '300'))
+        self.warn_ms = warn_ms if warn_ms is not None else float(os.getenv('CLOCK_SYNC_WARN_MS', '250'))
+        self._minute_maximums: Deque[List[float]] = deque()  # [minute, max offset ms, samples]
+        self._http: Deque[float] = deque(maxlen=http_samples)  # Offsets (ms) from Date headers
+        self._lock = threading.Lock()
+        self._offset_ms = 0.0
+        self._source: Optional[str] = None
+        self._warned = False
+        self.feed_samples = 0
+        self.http_samples = 0
+        self.samples_rejected = 0
+
+    # ---------------------------
+    # Samples
+    # ---------------------------
+    def record_exchange_time(self, exchange_time: Any, received_at: Optional[float] = None) -> Optional[float]:
+        """
+        Record an exchange timestamp received at received_at (epoch seconds, default now).
+
+        Returns:
+            Optional[float]: The sample's offset in ms (exchange - received), None if unusable
+        """
+        from core.websocket.latency import parse_exchange_time  # core.websocket imports the bar aggregator
+        exchange_ts = parse_exchange_time(exchange_time)
+        if exchange_ts is None:
+            return None
+        received = received_at if received_at is not None else time.time()
+        offset_ms = (exchange_ts - received) * 1000.0
+        if abs(offset_ms) > self.max_offset_secs * 1000.0:
+            self.samples_rejected += 1
+            return None
+        minute = received // 60
+        with self._lock:
+            if self._minute_maximums and self._minute_maximums[-1][0] == minute:
+                last = self._minute_maximums[-1]
+                last[1] = max(last[1], offset_ms)
+                last[2] += 1
+            else:
+                self._minute_maximums.append([minute, offset_ms, 1])
+            while self._minute_maximums and self._minute_maximums[0][0] <= minute - self.window_minutes:
+                self._minute_maximums.popleft()
+            self.feed_samples += 1
+        self._update()
+        return offset_ms
+
+    def record_payload(self, payload: Any, received_at: Optional[float] = None) -> Optional[float]:
+        """Record the latest exchange timestamp found in a hub payload (no-op without one)."""
+        from core.websocket.latency import payload_exchange_time
+        exchange_ts = payload_exchange_time(payload)
+        if exchange_ts is None:
+            return None
+        return self.record_exchange_time(exchange_ts, received_at)
+
+    def record_http_date(self, date_header: Optional[str], sent_at: float,
+                         received_at: Optional[float] = None) -> Optional[float]:
+        """
+        Record the Date header of a REST response to a request sent at sent_at.
+
+        Returns:
+            Optional[float]: The sample's offset in ms, None without a parseable header
+        """
+        if not date_header or not isinstance(date_header, str):
+            return None
+        try:
+            server_time = parsedate_to_datetime(date_header)
+        except (TypeError, ValueError, IndexError):
+            return None
+        if server_time is None:
+            return None
+        if server_time.tzinfo is None:
+            server_time = server_time.replace(tzinfo=timezone.utc)
+        received = received_at if received_at is not None else time.time()
+        # The header is truncated to the second: its expected value is half a second later
+        offset_ms = (server_time.timestamp() + 0.5 - (sent_at + received) / 2) * 1000.0
+        if abs(offset_ms) > self.max_offset_secs * 1000.0:
+            self.samples_rejected += 1
+            return None
+        with self._lock:
+            self._http.append(offset_ms)
+            self.http_samples += 1
+        self._update()
+        return offset_ms
+
+    # ---------------------------
+    # Estimate
+    # ---------------------------
+    def _update(self) -> None:
+        with self._lock:
+            feed_count = sum(int(m[2]) for m in self._minute_maximums)
+            if feed_count >= self.min_samples:
+                offset, source = max(m[1] for m in self._minute_maximums), 'feed'
+            elif self._http:
+                offset, source = statistics.median(self._http), 'http'
+            else:
+                return
+            self._offset_ms, self._source = offset, source
+        if abs(offset) > self.warn_ms and not self._warned:
+            self._warned = True
+            logger.warning(f"⚠️  Local clock is {abs(offset):.0f}ms {'behind' if offset > 0 else 'ahead of'} "
+                           f"broker time ({source} estimate)" + ("; correcting" if self.enabled else ""))
+        elif abs(offset) <= self.warn_ms / 2:
+            self._warned = False
+
+    @property
+    def offset_ms(self) -> float:
+        """Estimated broker-minus-local offset in ms (positive: the local clock is behind), 0 before any sample."""
+        return self._offset_ms
+
+    @property
+    def source(self) -> Optional[str]:
+        """'feed' or 'http' for the current estimate, None before any sample."""
+        return self._source
+
+    def time(self) -> float:
+        """Corrected epoch seconds."""
+        return time.time() + (self._offset_ms / 1000.0 if self.enabled else 0.0)
+
+    def now(self, tz: Optional[tzinfo] = timezone.utc) -> datetime:
+        """Corrected current time (UTC by default)."""
+        return datetime.fromtimestamp(self.time(), tz)
+
+    def reset(self) -> None:
+        """Drop all samples and the estimate (e.g. after the host clock was stepped)."""
+        with self._lock:
+            self._minute_maximums.clear()
+            self._http.clear()
+            self._offset_ms, self._source = 0.0, None
+            self._warned = False
+
+    def get_stats(self) -> Dict[str, Any]:
+        with self._lock:
+            http = list(self._http)
+            feed_count = sum(int(m[2]) for m in self._minute_maximums)
+        return {
+            'enabled': self.enabled,
+            'offset_ms': round(self._offset_ms, 1),
+            'applied_offset_ms': round(self._offset_ms, 1) if self.enabled else 0.0,
+            'source': self._source,
+            'feed_samples_in_window': feed_count,
+            'http_offset_ms': round(statistics.median(http), 1) if http else None,
+            'feed_samples': self.feed_samples,
+            'http_samples': self.http_samples,
+            'samples_rejected': self.samples_rejected,
+        }
+
+
+_server_clock: Optional[ServerClock] = None
+
+
+def get_server_clock() -> ServerClock:
+    """Get or create the process-wide server clock."""
+    global _server_clock
+    if _server_clock is None:
+        _server_clock = ServerClock()
+    return _server_clock
+
+
+def server_now(tz: Optional[tzinfo] = timezone.utc) -> datetime:
+    """Current time on the process-wide server clock (UTC by default)."""
+    return get_server_clock().now(tz)
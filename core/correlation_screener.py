@@ -0,0 +1,268 @@
+"""
+Historical correlation / cointegration screener across the symbol universe.
+
+Loads stored daily/hourly bars for every cached symbol, aligns them on
+timestamp and screens all pairs in parallel:
+- Pearson correlation of log returns
+- Engle-Granger cointegration (OLS hedge ratio + ADF test on the spread)
+- Mean-reversion half-life of the spread
+
+Results are ranked and can be exported as an Arrow table (via polars) for
+research notebooks.
+"""
+
+import logging
+import math
+import os
+from concurrent.futures import ProcessPoolExecutor
+from dataclasses import dataclass, asdict
+from itertools import combinations
+from typing import Dict, List, Optional, Sequence, Tuple
+
+logger = logging.getLogger(__name__)
+
+# MacKinnon (2010) 5% critical value for a two-variable Engle-Granger test with constant
+ENGLE_GRANGER_CRITICAL_5PCT = -3.34
+
+
+@dataclass
+class PairScreenResult:
+    """Screen result for one symbol pair."""
+    symbol_a: str
+    symbol_b: str
+    observations: int
+    correlation: float
+    hedge_ratio: float
+    adf_stat: float
+    cointegrated: bool
+    half_life: Optional[float]
+    score: float
+
+    def to_dict(self) -> Dict:
+        """Convert to plain dictionary."""
+        return asdict(self)
+
+
+def _mean(values: Sequence[float]) -> float:
+    return sum(values) / len(values)
+
+
+def _ols(x: Sequence[float], y: Sequence[float]) -> Tuple[float, float]:
+    """Ordinary least squares y = alpha + beta * x. Returns (alpha, beta)."""
+    mx, my = _mean(x), _mean(y)
+    sxx = sum((xi - mx) ** 2 for xi in x)
+    if sxx == 0:
+        return my, 0.0
+    sxy = sum((xi - mx) * (yi - my) for xi, yi in zip(x, y))
+    beta = sxy / sxx
+    return my - beta * mx, beta
+
+
+def pearson_correlation(x: Sequence[float], y: Sequence[float]) -> float:
+    """Pearson correlation coefficient (0.0 when undefined)."""
+    if len(x) < 2 or len(x) != len(y):
+        return 0.0
+    mx, my = _mean(x), _mean(y)
+    sxx = sum((xi - mx) ** 2 for xi in x)
+    syy = sum((yi - my) ** 2 for yi in y)
+    if sxx == 0 or syy == 0:
+        return 0.0
+    sxy = sum((xi - mx) * (yi - my) for xi, yi in zip(x, y))
+    return sxy / math.sqrt(sxx * syy)
+
+
+def adf_statistic(series: Sequence[float]) -> Tuple[float, float]:
+    """
+    Dickey-Fuller t-statistic (no lags) for a series.
+
+    Regresses diff(series) on lagged series with a constant.
+
+    Returns:
+        (t_stat, gamma) where gamma is the lag coefficient
+    """
+    if len(series) < 3:
+        return 0.0, 0.0
+    lagged = series[:-1]
+    diffs = [series[i] - series[i - 1] for i in range(1, len(series))]
+    alpha, gamma = _ols(lagged, diffs)
+    residuals = [d - (alpha + gamma * l) for d, l in zip(diffs, lagged)]
+    n = len(diffs)
+    ml = _mean(lagged)
+    sxx = sum((l - ml) ** 2 for l in lagged)
+    if n <= 2 or sxx == 0:
+        return 0.0, gamma
+    sigma2 = sum(r * r for r in residuals) / (n - 2)
+    se = math.sqrt(sigma2 / sxx) if sigma2 > 0 else 0.0
+    if se == 0:
+        return float('-inf') if gamma < 0 else 0.0, gamma
+    return gamma / se, gamma
+
+
+def screen_pair(symbol_a: str, symbol_b: str, closes_a: Sequence[float], closes_b: Sequence[float],
+                critical_value: float = ENGLE_GRANGER_CRITICAL_5PCT) -> Optional[PairScreenResult]:
+    """
+    Screen a single aligned pair of close series.
+
+    Returns:
+        PairScreenResult or None if the series are unusable (non-positive prices, too short)
+    """
+    if len(closes_a) != len(closes_b) or len(closes_a) < 3:
+        return None
+    if min(closes_a) <= 0 or min(closes_b) <= 0:
+        return None
+
+    log_a = [math.log(p) for p in closes_a]
+    log_b = [math.log(p) for p in closes_b]
+    returns_a = [log_a[i] - log_a[i - 1] for i in range(1, len(log_a))]
+    returns_b = [log_b[i] - log_b[i - 1] for i in range(1, len(log_b))]
+    correlation = pearson_correlation(returns_a, returns_b)
+
+    # Engle-Granger step 1: hedge ratio from log-price regression
+    alpha, hedge_ratio = _ols(log_b, log_a)
+    spread = [a - (alpha + hedge_ratio * b) for a, b in zip(log_a, log_b)]
+
+    # Step 2: unit-root test on the spread
+    adf_stat, gamma = adf_statistic(spread)
+    cointegrated = adf_stat < critical_value
+
+    half_life = None
+    if -1 < gamma < 0:
+        half_life = -math.log(2) / math.log(1 + gamma)
+    elif gamma <= -1:
+        half_life = 0.0  # Spread fully reverts within one bar
+
+    # Rank cointegrated pairs first, then by test strength and correlation
+    score = (-adf_stat if math.isfinite(adf_stat) else 100.0) + abs(correlation)
+    if cointegrated:
+        score += 100.0
+
+    return PairScreenResult(
+        symbol_a=symbol_a,
+        symbol_b=symbol_b,
+        observations=len(closes_a),
+        correlation=correlation,
+        hedge_ratio=hedge_ratio,
+        adf_stat=adf_stat,
+        cointegrated=cointegrated,
+        half_life=half_life,
+        score=score,
+    )
+
+
+def _screen_pair_job(job: Tuple) -> Optional[PairScreenResult]:
+    """Process-pool entry point (must be module-level to be picklable)."""
+    return screen_pair(*job)
+
+
+def align_series(series_a: Dict[str, float], series_b: Dict[str, float]) -> Tuple[List[float], List[float]]:
+    """Inner-join two {timestamp: close} maps on timestamp (sorted ascending)."""
+    common = sorted(set(series_a) & set(series_b))
+    return [series_a[ts] for ts in common], [series_b[ts] for ts in common]
+
+
+class CorrelationScreener:
+    """
+    Batch pair screener over all cached symbols.
+
+    Usage:
+        screener = CorrelationScreener(db=get_database(), timeframe="1d")
+        results = screener.run()
+        table = results_to_arrow(results)
+    """
+
+    def __init__(self, db=None, timeframe: str = "1d", lookback_bars: int = 500,
+                 min_observations: int = 60, max_workers: Optional[int] = None,
+                 critical_value: float = ENGLE_GRANGER_CRITICAL_5PCT):
+        """
+        Initialize screener.
+
+        Args:
+            db: DatabaseManager used to load cached bars
+            timeframe: Bar timeframe to screen ("1d", "1h", ...)
+            lookback_bars: Bars loaded per symbol
+            min_observations: Minimum aligned bars required for a pair
+            max_workers: Process pool size (defaults to SCREENER_MAX_WORKERS or CPU count)
+            critical_value: ADF critical value for the cointegration decision
+        """
+        self.db = db
+        self.timeframe = timeframe
+        self.lookback_bars = lookback_bars
+        self.min_observations = min_observations
+        env_workers = os.getenv('SCREENER_MAX_WORKERS')
+        self.max_workers = max_workers or (int(env_workers) if env_workers else (os.cpu_count() or 1))
+        self.critical_value = critical_value
+
+    def load_universe(self, symbols: Optional[List[str]] = None) -> Dict[str, Dict[str, float]]:
+        """
+        Load close series for all symbols from the database.
+
+        Returns:
+            Dict: {symbol: {timestamp_iso: close}}
+        """
+        if self.db is None:
+            raise RuntimeError("CorrelationScreener.load_universe requires a database")
+        if symbols is None:
+            symbols = self.db.get_cached_symbols(self.timeframe)
+
+        universe: Dict[str, Dict[str, float]] = {}
+        for symbol in symbols:
+            bars = self.db.get_cached_bars(symbol, self.timeframe, limit=self.lookback_bars)
+            closes = {
+                bar['timestamp']: float(bar['close'])
+                for bar in bars
+                if bar.get('close') is not None and bar.get('timestamp')
+            }
+            if len(closes) >= self.min_observations:
+                universe[symbol] = closes
+            else:
+                logger.debug(f"Skipping {symbol}: only {len(closes)} {self.timeframe} bars cached")
+        logger.info(f"📥 Loaded {len(universe)} symbols for {self.timeframe} correlation screen")
+        return universe
+
+    def screen(self, universe: Dict[str, Dict[str, float]]) -> List[PairScreenResult]:
+        """
+        Screen every pair in a loaded universe.
+
+        Args:
+            universe: {symbol: {timestamp: close}}
+
+        Returns:
+            List[PairScreenResult]: Ranked best-first
+        """
+        jobs = []
+        for symbol_a, symbol_b in combinations(sorted(universe), 2):
+            closes_a, closes_b = align_series(universe[symbol_a], universe[symbol_b])
+            if len(closes_a) < self.min_observations:
+                continue
+            jobs.append((symbol_a, symbol_b, closes_a, closes_b, self.critical_value))
+
+        if not jobs:
+            return []
+
+        if self.max_workers > 1 and len(jobs) > 1:
+            chunksize = max(1, len(jobs) // (self.max_workers * 4))
+            with ProcessPoolExecutor(max_workers=self.max_workers) as executor:
+                raw_results = list(executor.map(_screen_pair_job, jobs, chunksize=chunksize))
+        else:
+            raw_results = [_screen_pair_job(job) for job in jobs]
+
+        results = [r for r in raw_results if r is not None]
+        results.sort(key=lambda r: r.score, reverse=True)
+        logger.info(f"📊 Screened {len(jobs)} pairs: {sum(r.cointegrated for r in results)} cointegrated")
+        return results
+
+    def run(self, symbols: Optional[List[str]] = None, top_n: Optional[int] = None) -> List[PairScreenResult]:
+        """Load the universe and screen it. Returns ranked results (optionally top N)."""
+        results = self.screen(self.load_universe(symbols))
+        return results[:top_n] if top_n else results
+
+
+def results_to_arrow(results: List[PairScreenResult]):
+    """
+    Convert screen results to a pyarrow Table (via polars).
+
+    Raises:
+        ImportError: If polars/pyarrow are not installed
+    """
+    import polars as pl
+    return pl.DataFrame([r.to_dict() for r in results]).to_arrow()
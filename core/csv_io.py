@@ -0,0 +1,287 @@
+"""
+CSV import/export for bar and tick data with configurable schemas.
+
+Lets data exported from other platforms be ingested into the aggregation and
+backtest pipeline, and bars/ticks be written back out in a chosen layout.
+
+A CsvSchema maps each field to a CSV column (header name, matched
+case-insensitively, or zero-based index for header-less files) and says how
+timestamps are encoded:
+- 'iso'                 : ISO 8601 ('2025-11-19T14:30:00Z', '2025-11-19 14:30:00')
+- 'epoch_s' / 'epoch_ms': Unix time
+- any strptime pattern  : e.g. '%Y%m%d %H%M%S'
+Timestamps without an offset are interpreted in the schema's timezone and
+converted to UTC. Date and time in separate columns are mapped as 'date' and
+'time' and joined with a space before parsing.
+
+Built-in schemas (CSV_SCHEMAS):
+- 'default'            : timestamp,open,high,low,close,volume / timestamp,price,size,side,bid,ask (ISO, UTC)
+- 'ninjatrader'        : NinjaTrader 8 bar export, 'yyyyMMdd HHmmss;O;H;L;C;V' (no header, exchange time)
+- 'ninjatrader_tick'   : NinjaTrader 8 tick export, 'yyyyMMdd HHmmss fffffff;last;bid;ask;volume'
+- 'sierra_chart'       : Sierra Chart bar export (Date, Time, Open, High, Low, Last, Volume, ...)
+- 'sierra_chart_tick'  : Sierra Chart 1-tick export (Last = price, Volume = size; Sierra stores
+                         the bid in Low and the ask in High for 1-tick data)
+
+Configuration:
+- CSV_DEFAULT_TIMEZONE: Timezone for platform schemas without offsets (default 'America/Chicago')
+"""
+
+import csv
+import logging
+import os
+from dataclasses import dataclass, replace
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import Any, Dict, Iterable, Iterator, List, Optional, Union
+from zoneinfo import ZoneInfo
+
+from core.bar_aggregator import Bar
+from core.market_events import Trade
+
+logger = logging.getLogger(__name__)
+
+BAR_FIELDS = ('timestamp', 'open', 'high', 'low', 'close', 'volume')
+TICK_FIELDS = ('timestamp', 'price', 'size', 'side', 'bid', 'ask')
+
+Column = Union[str, int]
+
+
+@dataclass(frozen=True)
+class CsvSchema:
+    """Column mapping and timestamp encoding for a CSV layout."""
+    name: str
+    columns: Dict[str, Column]
+    timestamp_format: str = 'iso'
+    timezone: str = 'UTC'
+    delimiter: str = ','
+    has_header: bool = True
+    # Fractional seconds appended as a separate token (NinjaTrader ticks: '... 143000 1230000')
+    fraction_digits: int = 0
+
+    def with_timezone(self, tz: str) -> 'CsvSchema':
+        return replace(self, timezone=tz)
+
+
+_EXCHANGE_TZ = os.getenv('CSV_DEFAULT_TIMEZONE', 'America/Chicago')
+
+CSV_SCHEMAS: Dict[str, CsvSchema] = {
+    'default': CsvSchema('default', {f: f for f in BAR_FIELDS + TICK_FIELDS}),
+    'ninjatrader': CsvSchema(
+        'ninjatrader', {'timestamp': 0, 'open': 1, 'high': 2, 'low': 3, 'close': 4, 'volume': 5},
+        timestamp_format='%Y%m%d %H%M%S', timezone=_EXCHANGE_TZ, delimiter=';', has_header=False),
+    'ninjatrader_tick': CsvSchema(
+        'ninjatrader_tick', {'timestamp': 0, 'price': 1, 'bid': 2, 'ask': 3, 'size': 4},
+        timestamp_format='%Y%m%d %H%M%S', timezone=_EXCHANGE_TZ, delimiter=';', has_header=False,
+        fraction_digits=7),
+    'sierra_chart': CsvSchema(
+        'sierra_chart', {'date': 'Date', 'time': 'Time', 'open': 'Open', 'high': 'High', 'low': 'Low',
+                         'close': 'Last', 'volume': 'Volume'},
+        timestamp_format='%Y/%m/%d %H:%M:%S', timezone=_EXCHANGE_TZ),
+    'sierra_chart_tick': CsvSchema(
+        'sierra_chart_tick', {'date': 'Date', 'time': 'Time', 'price': 'Last', 'size': 'Volume',
+                              'bid': 'Low', 'ask': 'High'},
+        timestamp_format='%Y/%m/%d %H:%M:%S', timezone=_EXCHANGE_TZ),
+}
+
+
+def get_schema(schema: Union[str, CsvSchema, None]) -> CsvSchema:
+    """Resolve a schema name (or pass a CsvSchema through)."""
+    if isinstance(schema, CsvSchema):
+        return schema
+    name = (schema or 'default').lower()
+    if name not in CSV_SCHEMAS:
+        raise ValueError(f"Unknown CSV schema '{name}'. Use one of {sorted(CSV_SCHEMAS)}")
+    return CSV_SCHEMAS[name]
+
+
+class _TimestampParser:
+    """Parses one schema's timestamps to aware UTC datetimes."""
+
+    def __init__(self, schema: CsvSchema):
+        self.format = schema.timestamp_format
+        self.tz = ZoneInfo(schema.timezone) if schema.timezone.upper() != 'UTC' else timezone.utc
+        self.fraction_digits = schema.fraction_digits
+
+    def __call__(self, text: str) -> datetime:
+        text = text.strip()
+        micros = 0
+        if self.fraction_digits:
+            text, _, fraction = text.rpartition(' ')
+            micros = int(fraction.ljust(self.fraction_digits, '0')[:self.fraction_digits]) // \
+                10 ** max(0, self.fraction_digits - 6)
+        if self.format == 'epoch_s':
+            return datetime.fromtimestamp(float(text), tz=timezone.utc)
+        if self.format == 'epoch_ms':
+            return datetime.fromtimestamp(float(text) / 1000, tz=timezone.utc)
+        if self.format == 'iso':
+            ts = datetime.fromisoformat(text.replace('Z', '+00:00'))
+        else:
+            ts = datetime.strptime(text, self.format)
+        if micros:
+            ts = ts.replace(microsecond=micros)
+        if ts.tzinfo is None:
+            ts = ts.replace(tzinfo=self.tz)
+        return ts.astimezone(timezone.utc)
+
+    def format_ts(self, ts: datetime) -> str:
+        if ts.tzinfo is None:
+            ts = ts.replace(tzinfo=timezone.utc)
+        if self.format == 'epoch_s':
+            return f"{ts.timestamp():.6f}".rstrip('0').rstrip('.')
+        if self.format == 'epoch_ms':
+            return str(int(round(ts.timestamp() * 1000)))
+        local = ts.astimezone(self.tz)
+        if self.format == 'iso':
+            return local.isoformat().replace('+00:00', 'Z')
+        text = local.strftime(self.format)
+        if self.fraction_digits:
+            text += f" {local.microsecond * 10 ** max(0, self.fraction_digits - 6):0{self.fraction_digits}d}"
+        return text
+
+
+def _rows(path: Union[str, Path], schema: CsvSchema, fields: Iterable[str]) -> Iterator[Dict[str, str]]:
+    """Yield {field: raw text} per data row, resolving header names to indexes once."""
+    with open(path, newline='', encoding='utf-8-sig') as f:
+        reader = csv.reader(f, delimiter=schema.delimiter)
+        wanted = [name for name in fields if name in schema.columns]
+        if 'timestamp' not in schema.columns:
+            wanted += [name for name in ('date', 'time') if name in schema.columns]
+        if schema.has_header:
+            header = [h.strip().lower() for h in next(reader, [])]
+            indexes = {}
+            for name in wanted:
+                column = schema.columns[name]
+                if isinstance(column, int):
+                    indexes[name] = column
+                elif column.strip().lower() in header:
+                    indexes[name] = header.index(column.strip().lower())
+                elif name in ('timestamp', 'date', 'time', 'price', 'open', 'high', 'low', 'close'):
+                    raise ValueError(f"CSV {path} has no '{column}' column for {name} (schema {schema.name})")
+        else:
+            indexes = {name: int(schema.columns[name]) for name in wanted}
+        for row in reader:
+            if not row or not any(cell.strip() for cell in row):
+                continue
+            yield {name: row[i].strip() if i < len(row) else '' for name, i in indexes.items()}
+
+
+def _timestamp_text(raw: Dict[str, str]) -> str:
+    return raw['timestamp'] if 'timestamp' in raw else f"{raw['date']} {raw['time']}"
+
+
+def _number(text: Optional[str], cast=float):
+    return cast(float(text)) if text not in (None, '') else None
+
+
+def read_bars_csv(path: Union[str, Path], symbol: str, timeframe: str,
+                  schema: Union[str, CsvSchema, None] = None) -> List[Bar]:
+    """
+    Load bars from a CSV file.
+
+    Args:
+        path: CSV file
+        symbol: Symbol the bars belong to
+        timeframe: Bar timeframe (e.g. '1m')
+        schema: Schema name or CsvSchema (default 'default')
+
+    Returns:
+        List[Bar]: Bars sorted oldest first (malformed rows are skipped and counted in the log)
+
+    Raises:
+        ValueError: Unknown schema or a required column is missing
+    """
+    schema = get_schema(schema)
+    parse_ts = _TimestampParser(schema)
+    bars: List[Bar] = []
+    skipped = 0
+    for raw in _rows(path, schema, BAR_FIELDS):
+        try:
+            bars.append(Bar(
+                symbol=symbol.upper(), timeframe=timeframe, timestamp=parse_ts(_timestamp_text(raw)),
+                open=float(raw['open']), high=float(raw['high']), low=float(raw['low']),
+                close=float(raw['close']), volume=_number(raw.get('volume'), int) or 0,
+            ))
+        except (KeyError, ValueError):
+            skipped += 1
+    if skipped:
+        logger.warning(f"⚠️  Skipped {skipped} malformed bar row(s) in {path}")
+    bars.sort(key=lambda b: b.timestamp)
+    return bars
+
+
+def read_ticks_csv(path: Union[str, Path], symbol: str,
+                   schema: Union[str, CsvSchema, None] = None) -> List[Dict[str, Any]]:
+    """
+    Load ticks from a CSV file.
+
+    Returns:
+        List[Dict]: Ticks oldest first with symbol, timestamp (UTC datetime), price,
+        size and optional side/bid/ask (the dict shape used by ParquetStore and
+        DatabaseManager.save_ticks)
+    """
+    schema = get_schema(schema)
+    parse_ts = _TimestampParser(schema)
+    ticks: List[Dict[str, Any]] = []
+    skipped = 0
+    for raw in _rows(path, schema, TICK_FIELDS):
+        try:
+            ticks.append({
+                'symbol': symbol.upper(),
+                'timestamp': parse_ts(_timestamp_text(raw)),
+                'price': float(raw['price']),
+                'size': _number(raw.get('size'), int) or 0,
+                'side': (raw.get('side') or '').lower() or None,
+                'bid': _number(raw.get('bid')),
+                'ask': _number(raw.get('ask')),
+            })
+        except (KeyError, ValueError):
+            skipped += 1
+    if skipped:
+        logger.warning(f"⚠️  Skipped {skipped} malformed tick row(s) in {path}")
+    ticks.sort(key=lambda t: t['timestamp'])
+    return ticks
+
+
+def _write(path: Union[str, Path], schema: CsvSchema, fields: Iterable[str],
+           records: Iterable[Dict[str, Any]]) -> int:
+    fmt = _TimestampParser(schema)
+    mapped = [name for name in fields if name in schema.columns]
+    split_time = 'timestamp' not in schema.columns and 'date' in schema.columns
+    if split_time:
+        mapped = ['date', 'time'] + [name for name in mapped if name != 'timestamp']
+    if not schema.has_header:
+        mapped.sort(key=lambda name: int(schema.columns[name]))
+    date_format, _, time_format = schema.timestamp_format.partition(' ')
+    count = 0
+    with open(path, 'w', newline='', encoding='utf-8') as f:
+        writer = csv.writer(f, delimiter=schema.delimiter)
+        if schema.has_header:
+            writer.writerow([schema.columns[name] for name in mapped])
+        for record in records:
+            values = dict(record)
+            if split_time:
+                local = values['timestamp'].astimezone(fmt.tz)
+                values['date'], values['time'] = local.strftime(date_format), local.strftime(time_format)
+            values['timestamp'] = fmt.format_ts(values['timestamp'])
+            writer.writerow(['' if values.get(name) is None else values[name] for name in mapped])
+            count += 1
+    return count
+
+
+def write_bars_csv(path: Union[str, Path], bars: Iterable[Bar], schema: Union[str, CsvSchema, None] = None) -> int:
+    """Write bars in a schema's layout. Returns rows written."""
+    return _write(path, get_schema(schema), BAR_FIELDS, (
+        {'timestamp': b.timestamp, 'open': b.open, 'high': b.high, 'low': b.low,
+         'close': b.close, 'volume': b.volume} for b in bars))
+
+
+def write_ticks_csv(path: Union[str, Path], ticks: Iterable[Union[Trade, Dict[str, Any]]],
+                    schema: Union[str, CsvSchema, None] = None) -> int:
+    """Write ticks (Trade events or tick dicts) in a schema's layout. Returns rows written."""
+    def records():
+        for tick in ticks:
+            if isinstance(tick, Trade):
+                yield {'timestamp': tick.timestamp, 'price': tick.price, 'size': tick.size, 'side': tick.side}
+            else:
+                yield tick
+    return _write(path, get_schema(schema), TICK_FIELDS, records())
@@ -0,0 +1,379 @@
+"""
+Level 2 Depth-of-Market Book
+
+Maintains sorted bid/ask ladders per symbol from depth snapshots and
+incremental updates.
+
+Sequencing:
+- Updates carrying a sequence number are applied strictly in order: stale
+  ones (already applied) are dropped, early ones are buffered until the gap
+  fills. If the gap does not fill within max_pending updates the book is
+  marked out of sync and waits for the next snapshot.
+- With require_snapshot=True, deltas received before the first snapshot
+  are buffered and replayed on top of it (newer than the snapshot only).
+- Without sequence numbers, each price level keeps the timestamp of its
+  last change and older updates for that level are ignored.
+
+TopStepX GatewayDepth payloads (lists of DOM entries with a DomType) are
+supported via apply_gateway().
+
+Features:
+- best_bid() / best_ask() / spread() / mid()
+- imbalance(levels) over the top N levels
+- Immutable snapshots for strategies and the dashboard
+"""
+
+import bisect
+import heapq
+import logging
+import os
+import threading
+from dataclasses import dataclass, field
+from datetime import datetime, timezone
+from typing import Any, Dict, Iterable, List, Optional, Tuple
+
+from core.market_events import DepthUpdate, PriceLevel, parse_timestamp
+
+logger = logging.getLogger(__name__)
+
+BID = "bid"
+ASK = "ask"
+
+# ProjectX DomType values
+DOM_ASK = 1
+DOM_BID = 2
+DOM_BEST_ASK = 3
+DOM_BEST_BID = 4
+DOM_RESET = 6
+DOM_NEW_BEST_BID = 9
+DOM_NEW_BEST_ASK = 10
+DOM_BID_TYPES = (DOM_BID, DOM_BEST_BID, DOM_NEW_BEST_BID)
+DOM_ASK_TYPES = (DOM_ASK, DOM_BEST_ASK, DOM_NEW_BEST_ASK)
+
+
+@dataclass(frozen=True)
+class DepthSnapshot:
+    """Point-in-time view of a depth book (best prices first)."""
+    symbol: str
+    timestamp: datetime
+    bids: Tuple[PriceLevel, ...]
+    asks: Tuple[PriceLevel, ...]
+    sequence: Optional[int] = None
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        return {
+            "symbol": self.symbol,
+            "timestamp": self.timestamp.isoformat(),
+            "sequence": self.sequence,
+            "bids": [{"price": p, "size": s} for p, s in self.bids],
+            "asks": [{"price": p, "size": s} for p, s in self.asks],
+        }
+
+
+class _Ladder:
+    """One side of the book: sorted prices plus size and last-update time per level."""
+
+    def __init__(self, descending: bool):
+        self.descending = descending
+        self._keys: List[float] = []  # Ascending sort keys (negated prices for bids)
+        self.sizes: Dict[float, int] = {}
+        self.updated: Dict[float, datetime] = {}
+
+    def _key(self, price: float) -> float:
+        return -price if self.descending else price
+
+    def set(self, price: float, size: int, timestamp: Optional[datetime] = None) -> bool:
+        """Set a level's size (0 removes it). Returns False if the update is older than the level."""
+        if timestamp is not None:
+            last = self.updated.get(price)
+            if last is not None and timestamp < last:
+                return False
+            self.updated[price] = timestamp
+        if size <= 0:
+            if self.sizes.pop(price, None) is not None:
+                index = bisect.bisect_left(self._keys, self._key(price))
+                del self._keys[index]
+            return True
+        if price not in self.sizes:
+            bisect.insort(self._keys, self._key(price))
+        self.sizes[price] = size
+        return True
+
+    def clear(self):
+        self._keys.clear()
+        self.sizes.clear()
+        self.updated.clear()
+
+    def levels(self, count: Optional[int] = None) -> List[PriceLevel]:
+        keys = self._keys if count is None else self._keys[:count]
+        prices = [-k if self.descending else k for k in keys]
+        return [(price, self.sizes[price]) for price in prices]
+
+    def best(self) -> Optional[PriceLevel]:
+        if not self._keys:
+            return None
+        price = -self._keys[0] if self.descending else self._keys[0]
+        return price, self.sizes[price]
+
+    def __len__(self) -> int:
+        return len(self._keys)
+
+
+@dataclass(order=True)
+class _PendingUpdate:
+    sequence: int
+    side: str = field(compare=False)
+    price: float = field(compare=False)
+    size: int = field(compare=False)
+    timestamp: Optional[datetime] = field(compare=False, default=None)
+
+
+class DepthBook:
+    """
+    Order book for one symbol.
+
+    Usage:
+        book = DepthBook('MNQ')
+        book.apply_snapshot(bids=[(100.0, 5)], asks=[(100.25, 3)], sequence=10)
+        book.apply_delta('bid', 100.0, 7, sequence=11)
+        book.best_bid()          # (100.0, 7)
+        book.imbalance(levels=5)
+    """
+
+    def __init__(self, symbol: str, require_snapshot: bool = False, max_pending: Optional[int] = None):
+        """
+        Initialize depth book.
+
+        Args:
+            symbol: Trading symbol
+            require_snapshot: Buffer deltas until the first snapshot arrives
+            max_pending: Out-of-order updates buffered before giving up on a gap (env: DEPTH_MAX_PENDING)
+        """
+        self.symbol = symbol.upper()
+        self.require_snapshot = require_snapshot
+        self.max_pending = max_pending if max_pending is not None else int(os.getenv('DEPTH_MAX_PENDING', '500'))
+        self._bids = _Ladder(descending=True)
+        self._asks = _Ladder(descending=False)
+        self._pending: List[_PendingUpdate] = []  # Heap of early sequenced updates
+        self._presnapshot: List[Tuple[Optional[int], str, float, int, Optional[datetime]]] = []
+        self._last_sequence: Optional[int] = None
+        self._has_snapshot = False
+        self._out_of_sync = False
+        self.last_update: Optional[datetime] = None
+        self.stale_updates = 0
+        self._lock = threading.RLock()
+
+    @property
+    def is_synced(self) -> bool:
+        """True when the book can be trusted (snapshot received if required, no open gap)."""
+        with self._lock:
+            if self._out_of_sync:
+                return False
+            return self._has_snapshot or not self.require_snapshot
+
+    @property
+    def last_sequence(self) -> Optional[int]:
+        return self._last_sequence
+
+    def _ladder(self, side: str) -> _Ladder:
+        side = side.lower()
+        if side in (BID, 'bids', 'buy'):
+            return self._bids
+        if side in (ASK, 'asks', 'sell'):
+            return self._asks
+        raise ValueError(f"Unknown book side '{side}'")
+
+    def apply_snapshot(self, bids: Iterable[PriceLevel], asks: Iterable[PriceLevel],
+                       sequence: Optional[int] = None, timestamp: Optional[datetime] = None) -> None:
+        """
+        Replace the book with a full snapshot, then replay buffered newer deltas.
+
+        Args:
+            bids: (price, size) levels
+            asks: (price, size) levels
+            sequence: Snapshot sequence number, if the feed provides one
+            timestamp: Snapshot time (defaults to now)
+        """
+        timestamp = timestamp or datetime.now(timezone.utc)
+        with self._lock:
+            self._bids.clear()
+            self._asks.clear()
+            for price, size in bids:
+                self._bids.set(float(price), int(size), timestamp)
+            for price, size in asks:
+                self._asks.set(float(price), int(size), timestamp)
+            self._has_snapshot = True
+            self._out_of_sync = False
+            self._last_sequence = sequence
+            self.last_update = timestamp
+
+            pending = [update for update in self._pending if sequence is not None and update.sequence > sequence]
+            heapq.heapify(pending)
+            self._pending = pending
+
+            # Replay deltas that arrived before the snapshot, keeping only newer ones
+            presnapshot, self._presnapshot = self._presnapshot, []
+            for seq, side, price, size, ts in presnapshot:
+                if seq is not None and sequence is not None:
+                    if seq > sequence:
+                        heapq.heappush(self._pending, _PendingUpdate(seq, side, price, size, ts))
+                elif ts is not None and ts > timestamp:
+                    self._apply(side, price, size, ts)
+            self._drain_pending()
+
+    def apply_delta(self, side: str, price: float, size: int, sequence: Optional[int] = None,
+                    timestamp: Optional[datetime] = None) -> bool:
+        """
+        Apply an incremental level change (size is the new absolute size; 0 removes the level).
+
+        Returns:
+            bool: True if applied now, False if dropped as stale or buffered
+        """
+        with self._lock:
+            if self.require_snapshot and not self._has_snapshot:
+                if len(self._presnapshot) < self.max_pending:
+                    self._presnapshot.append((sequence, side, price, size, timestamp))
+                return False
+
+            if sequence is None:
+                return self._apply(side, price, size, timestamp)
+
+            if self._last_sequence is not None and sequence <= self._last_sequence:
+                self.stale_updates += 1
+                return False
+            if self._last_sequence is not None and sequence > self._last_sequence + 1:
+                self._buffer(_PendingUpdate(sequence, side, price, size, timestamp))
+                return False
+
+            applied = self._apply(side, price, size, timestamp)
+            self._last_sequence = sequence
+            self._drain_pending()
+            return applied
+
+    def _apply(self, side: str, price: float, size: int, timestamp: Optional[datetime]) -> bool:
+        """Apply a level change (caller holds _lock)."""
+        applied = self._ladder(side).set(float(price), int(size), timestamp)
+        if not applied:
+            self.stale_updates += 1
+            return False
+        self.last_update = timestamp or datetime.now(timezone.utc)
+        return True
+
+    def _buffer(self, update: _PendingUpdate) -> None:
+        """Hold an early update until its predecessors arrive (caller holds _lock)."""
+        if len(self._pending) >= self.max_pending:
+            logger.warning(f"⚠️  Depth book for {self.symbol} lost sequence at "
+                           f"{self._last_sequence} - waiting for a snapshot")
+            # Deltas from here on are held until the next snapshot re-bases the book
+            self._out_of_sync = True
+            self._has_snapshot = False
+            self.require_snapshot = True
+            self._pending.clear()
+            self._presnapshot.append((update.sequence, update.side, update.price, update.size, update.timestamp))
+            return
+        heapq.heappush(self._pending, update)
+
+    def _drain_pending(self) -> None:
+        """Apply buffered updates that are now contiguous (caller holds _lock)."""
+        while self._pending and self._last_sequence is not None:
+            head = self._pending[0]
+            if head.sequence <= self._last_sequence:
+                heapq.heappop(self._pending)
+                self.stale_updates += 1
+            elif head.sequence == self._last_sequence + 1:
+                heapq.heappop(self._pending)
+                self._apply(head.side, head.price, head.size, head.timestamp)
+                self._last_sequence = head.sequence
+            else:
+                break
+
+    def apply_update(self, event: DepthUpdate, snapshot: bool = False) -> None:
+        """Apply a DepthUpdate market event as a snapshot or as level deltas."""
+        if snapshot:
+            self.apply_snapshot(event.bids, event.asks, timestamp=event.timestamp)
+            return
+        for price, size in event.bids:
+            self.apply_delta(BID, price, size, timestamp=event.timestamp)
+        for price, size in event.asks:
+            self.apply_delta(ASK, price, size, timestamp=event.timestamp)
+
+    def apply_gateway(self, payload: Any) -> int:
+        """
+        Apply a GatewayDepth payload: a list of DOM entries
+        ({type, price, volume, timestamp}) or a {bids, asks} book.
+
+        Returns:
+            int: Number of entries processed
+        """
+        if isinstance(payload, dict) and ("bids" in payload or "asks" in payload or "orderBook" in payload):
+            self.apply_update(DepthUpdate.from_gateway(self.symbol, payload), snapshot=True)
+            return 1
+        entries = payload if isinstance(payload, list) else [payload]
+        processed = 0
+        for entry in entries:
+            if not isinstance(entry, dict):
+                continue
+            dom_type = entry.get("type")
+            timestamp = parse_timestamp(entry.get("timestamp"))
+            if dom_type == DOM_RESET:
+                self.apply_snapshot((), (), timestamp=timestamp)
+            elif dom_type in DOM_BID_TYPES or dom_type in DOM_ASK_TYPES:
+                if entry.get("price") is None:
+                    continue
+                side = BID if dom_type in DOM_BID_TYPES else ASK
+                self.apply_delta(side, float(entry["price"]), int(entry.get("volume") or 0), timestamp=timestamp)
+            else:
+                continue  # Trades, fills, session highs/lows don't change the book
+            processed += 1
+        return processed
+
+    def best_bid(self) -> Optional[PriceLevel]:
+        """Highest bid (price, size)."""
+        with self._lock:
+            return self._bids.best()
+
+    def best_ask(self) -> Optional[PriceLevel]:
+        """Lowest ask (price, size)."""
+        with self._lock:
+            return self._asks.best()
+
+    def spread(self) -> Optional[float]:
+        """Best ask minus best bid."""
+        with self._lock:
+            bid, ask = self._bids.best(), self._asks.best()
+        return ask[0] - bid[0] if bid and ask else None
+
+    def mid(self) -> Optional[float]:
+        """Midpoint of best bid and ask."""
+        with self._lock:
+            bid, ask = self._bids.best(), self._asks.best()
+        return (bid[0] + ask[0]) / 2 if bid and ask else None
+
+    def imbalance(self, levels: int = 5) -> Optional[float]:
+        """
+        Size imbalance over the top N levels: (bid - ask) / (bid + ask), in [-1, 1].
+
+        Positive values mean more resting bid size. None when the book is empty.
+        """
+        with self._lock:
+            bid_size = sum(size for _, size in self._bids.levels(levels))
+            ask_size = sum(size for _, size in self._asks.levels(levels))
+        total = bid_size + ask_size
+        return (bid_size - ask_size) / total if total else None
+
+    def snapshot(self, levels: Optional[int] = None) -> DepthSnapshot:
+        """Immutable copy of the book (optionally top N levels per side)."""
+        with self._lock:
+            return DepthSnapshot(
+                symbol=self.symbol,
+                timestamp=self.last_update or datetime.now(timezone.utc),
+                bids=tuple(self._bids.levels(levels)),
+                asks=tuple(self._asks.levels(levels)),
+                sequence=self._last_sequence,
+            )
+
+    def depth(self) -> Tuple[int, int]:
+        """Number of (bid, ask) levels."""
+        with self._lock:
+            return len(self._bids), len(self._asks)
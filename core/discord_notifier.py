@@ -132,6 +132,22 @@ class DiscordNotifier:
             logger.error(f"Failed to send Discord error notification: {e}")
             return False
     
+    @staticmethod
+    def _fill_context_fields(context: Optional[Dict]) -> list:
+        """Embed fields for enriched fill context (slippage, spread at submission, latency)."""
+        if not context:
+            return []
+        fields = []
+        if context.get('slippage') is not None:
+            fields.append({"name": "Slippage", "value": f"{context['slippage']:+g} pts", "inline": True})
+        submission = context.get('submission') or {}
+        book = (submission.get('market') or {}).get('book') or {}
+        if book.get('spread') is not None:
+            fields.append({"name": "Spread @ Submit", "value": f"{book['spread']:g}", "inline": True})
+        if context.get('latency_seconds') is not None:
+            fields.append({"name": "Submit → Fill", "value": f"{context['latency_seconds']:.1f}s", "inline": True})
+        return fields
+    
     def send_order_fill_notification(self, order_data: Dict, account_name: str) -> bool:
         """Send order fill notification to Discord"""
         if not self.enabled:
@@ -167,6 +183,7 @@ class DiscordNotifier:
                 ],
                 "timestamp": datetime.now(timezone.utc).isoformat()
             }
+            embed["fields"].extend(self._fill_context_fields(order_data.get('context')))
             
             payload = {"embeds": [embed]}
             
@@ -187,6 +204,48 @@ class DiscordNotifier:
             logger.error(f"Failed to send Discord order fill notification: {e}")
             return False
     
+    def send_position_divergence_notification(self, divergence: Dict, account_name: str) -> bool:
+        """Send local vs broker position divergence alert to Discord"""
+        if not self.enabled:
+            return False
+        
+        try:
+            symbol = divergence.get('symbol', 'Unknown')
+            embed = {
+                "title": "🚨 Position Divergence Detected",
+                "description": "Local position ledger does not match broker positions",
+                "color": 15158332,  # Red
+                "fields": [
+                    {"name": "Account", "value": account_name, "inline": True},
+                    {"name": "Symbol", "value": symbol, "inline": True},
+                    {"name": "Local Qty", "value": str(divergence.get('local_qty', 0)), "inline": True},
+                    {"name": "Broker Qty", "value": str(divergence.get('broker_qty', 0)), "inline": True},
+                    {"name": "Checks", "value": str(divergence.get('consecutive_checks', 0)), "inline": True},
+                    {"name": "Symbol Frozen", "value": "Yes" if divergence.get('frozen') else "No", "inline": True},
+                    {"name": "First Detected", "value": str(divergence.get('first_detected', 'Unknown')), "inline": False}
+                ],
+                "timestamp": datetime.now(timezone.utc).isoformat()
+            }
+            
+            payload = {"embeds": [embed]}
+            
+            response = requests.post(
+                self.webhook_url,
+                json=payload,
+                timeout=5
+            )
+            
+            if response.status_code == 204:
+                logger.info(f"Discord position divergence alert sent for {symbol}")
+                return True
+            else:
+                logger.warning(f"Discord position divergence alert failed: {response.status_code}")
+                return False
+                
+        except Exception as e:
+            logger.error(f"Failed to send Discord position divergence alert: {e}")
+            return False
+    
     def send_position_close_notification(self, position_data: Dict, account_name: str) -> bool:
         """Send position close notification to Discord"""
         if not self.enabled:
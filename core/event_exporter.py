@@ -0,0 +1,222 @@
+"""
+JSON Lines exporter for the market event bus.
+
+Writes every MarketEvent published by the bot as one compact JSON object per
+line (the MarketEvent.to_dict() format), producing a complete machine-readable
+session record that any tooling can parse and that replay_fixtures can read
+directly.
+
+The hot path only appends the event object to an in-memory buffer; JSON
+encoding, compression and file I/O happen on a background writer thread that
+drains the buffer in batches. If the writer falls behind and the buffer is
+full, new events are dropped and counted rather than blocking the SignalR
+thread.
+
+Features:
+- Compact JSONL, optionally zstd- (requires `zstandard`) or gzip-compressed
+- Rotation by size and at UTC midnight; files named events-YYYYMMDD-HHMMSS.jsonl[.zst|.gz]
+- Batched writes with periodic flush so a crash loses at most one flush interval
+
+Configuration:
+- EVENT_EXPORT_ENABLED: Enable the exporter in the bot (default false)
+- EVENT_EXPORT_DIR: Output directory (default 'recordings')
+- EVENT_EXPORT_COMPRESSION: 'none', 'zstd' or 'gzip' (default 'none')
+- EVENT_EXPORT_ROTATE_MB: Rotate after this many uncompressed MB (default 256)
+- EVENT_EXPORT_BUFFER_SIZE: Max events buffered before dropping (default 100000)
+- EVENT_EXPORT_FLUSH_INTERVAL: Seconds between writer flushes (default 1.0)
+"""
+
+import gzip
+import json
+import logging
+import os
+import threading
+from collections import deque
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import Any, Dict, IO, Optional, Union
+
+from core.market_events import MarketEvent
+
+logger = logging.getLogger(__name__)
+
+COMPRESSION_SUFFIXES = {'none': '', 'zstd': '.zst', 'gzip': '.gz'}
+
+
+def _open_compressed(path: Path, compression: str, level: Optional[int]) -> IO[bytes]:
+    """Open a binary writer for the given compression."""
+    if compression == 'zstd':
+        import zstandard
+        return zstandard.ZstdCompressor(level=level or 3).stream_writer(open(path, 'wb'), closefd=True)
+    if compression == 'gzip':
+        return gzip.open(path, 'wb', compresslevel=level or 6)
+    return open(path, 'wb')
+
+
+class EventExporter:
+    """
+    Background JSONL writer for MarketEvents.
+
+    Usage:
+        exporter = EventExporter(directory='recordings', compression='zstd')
+        exporter.start()
+        bot.add_market_event_listener(exporter.on_market_event)
+        ...
+        exporter.stop()
+    """
+
+    def __init__(self, directory: Union[str, Path, None] = None, compression: Optional[str] = None,
+                 rotate_bytes: Optional[int] = None, buffer_size: Optional[int] = None,
+                 flush_interval: Optional[float] = None, compression_level: Optional[int] = None):
+        """
+        Initialize exporter.
+
+        Args:
+            directory: Output directory (env: EVENT_EXPORT_DIR)
+            compression: 'none', 'zstd' or 'gzip' (env: EVENT_EXPORT_COMPRESSION)
+            rotate_bytes: Rotate after this many uncompressed bytes (env: EVENT_EXPORT_ROTATE_MB)
+            buffer_size: Max buffered events before new ones are dropped (env: EVENT_EXPORT_BUFFER_SIZE)
+            flush_interval: Seconds between writer flushes (env: EVENT_EXPORT_FLUSH_INTERVAL)
+            compression_level: zstd/gzip level (library default if None)
+
+        Raises:
+            ValueError: Unknown compression, or zstd requested without `zstandard` installed
+        """
+        self.directory = Path(directory or os.getenv('EVENT_EXPORT_DIR', 'recordings'))
+        self.compression = (compression or os.getenv('EVENT_EXPORT_COMPRESSION', 'none')).strip().lower()
+        if self.compression not in COMPRESSION_SUFFIXES:
+            raise ValueError(f"Unknown event export compression '{self.compression}'. "
+                             f"Use one of {sorted(COMPRESSION_SUFFIXES)}")
+        if self.compression == 'zstd':
+            try:
+                import zstandard  # noqa: F401
+            except ImportError:
+                raise ValueError("zstd event export requires the 'zstandard' package")
+        self.compression_level = compression_level
+        self.rotate_bytes = rotate_bytes if rotate_bytes is not None else \
+            int(float(os.getenv('EVENT_EXPORT_ROTATE_MB', '256')) * 1024 * 1024)
+        self.buffer_size = buffer_size if buffer_size is not None else int(os.getenv('EVENT_EXPORT_BUFFER_SIZE', '100000'))
+        self.flush_interval = flush_interval if flush_interval is not None else \
+            float(os.getenv('EVENT_EXPORT_FLUSH_INTERVAL', '1.0'))
+
+        self._buffer: deque = deque()
+        self._stop = threading.Event()
+        self._thread: Optional[threading.Thread] = None
+        self._file: Optional[IO[bytes]] = None
+        self._file_path: Optional[Path] = None
+        self._file_bytes = 0
+        self._file_day = None
+        self.events_written = 0
+        self.events_dropped = 0
+        self.bytes_written = 0
+        self.files_written = 0
+        self.write_errors = 0
+
+    def on_market_event(self, event: MarketEvent) -> None:
+        """Buffer an event for export (market event listener; never blocks)."""
+        if len(self._buffer) >= self.buffer_size:
+            self.events_dropped += 1
+            return
+        self._buffer.append(event)
+
+    __call__ = on_market_event
+
+    def start(self) -> None:
+        """Start the background writer thread."""
+        if self._thread and self._thread.is_alive():
+            return
+        self.directory.mkdir(parents=True, exist_ok=True)
+        self._stop.clear()
+        self._thread = threading.Thread(target=self._run, name="event-exporter", daemon=True)
+        self._thread.start()
+        logger.info(f"📼 Exporting market events to {self.directory} "
+                    f"(compression={self.compression}, rotate at {self.rotate_bytes // (1024 * 1024)} MB)")
+
+    def stop(self, timeout: float = 10.0) -> None:
+        """Drain buffered events, close the current file and stop the writer."""
+        self._stop.set()
+        if self._thread:
+            self._thread.join(timeout)
+            self._thread = None
+        else:
+            self._drain()
+        self._close_file()
+
+    def _run(self) -> None:
+        while not self._stop.wait(self.flush_interval):
+            self._drain()
+        self._drain()
+
+    def _drain(self) -> None:
+        """Encode and write everything currently buffered as one batch."""
+        if not self._buffer:
+            return
+        lines = []
+        while self._buffer:
+            event = self._buffer.popleft()
+            try:
+                lines.append(json.dumps(event.to_dict(), separators=(',', ':'), default=str))
+            except Exception as e:
+                self.write_errors += 1
+                logger.debug(f"Could not encode {getattr(event, 'type', type(event).__name__)} event: {e}")
+        if not lines:
+            return
+        data = ('\n'.join(lines) + '\n').encode('utf-8')
+        try:
+            self._ensure_file(len(data))
+            self._file.write(data)
+            self._file.flush()
+        except Exception as e:
+            self.write_errors += 1
+            self.events_dropped += len(lines)
+            logger.error(f"❌ Event export write failed ({self._file_path}): {e}")
+            self._close_file()
+            return
+        self._file_bytes += len(data)
+        self.bytes_written += len(data)
+        self.events_written += len(lines)
+
+    def _ensure_file(self, incoming: int) -> None:
+        """Open a file, rotating on size or UTC day change."""
+        now = datetime.now(timezone.utc)
+        if self._file is not None and (self._file_day != now.date()
+                                       or self._file_bytes + incoming > self.rotate_bytes):
+            self._close_file()
+        if self._file is not None:
+            return
+        name = f"events-{now.strftime('%Y%m%d-%H%M%S')}"
+        path = self.directory / f"{name}.jsonl{COMPRESSION_SUFFIXES[self.compression]}"
+        suffix = 1
+        while path.exists():
+            path = self.directory / f"{name}-{suffix}.jsonl{COMPRESSION_SUFFIXES[self.compression]}"
+            suffix += 1
+        self._file = _open_compressed(path, self.compression, self.compression_level)
+        self._file_path = path
+        self._file_bytes = 0
+        self._file_day = now.date()
+        self.files_written += 1
+        logger.debug(f"Event export file opened: {path}")
+
+    def _close_file(self) -> None:
+        if self._file is None:
+            return
+        try:
+            self._file.close()
+        except Exception as e:
+            logger.error(f"❌ Failed to close event export file {self._file_path}: {e}")
+        self._file = None
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Exporter counters."""
+        return {
+            "running": bool(self._thread and self._thread.is_alive()),
+            "directory": str(self.directory),
+            "compression": self.compression,
+            "current_file": str(self._file_path) if self._file is not None else None,
+            "buffered": len(self._buffer),
+            "events_written": self.events_written,
+            "events_dropped": self.events_dropped,
+            "bytes_written": self.bytes_written,
+            "files_written": self.files_written,
+            "write_errors": self.write_errors,
+        }
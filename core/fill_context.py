@@ -0,0 +1,215 @@
+"""
+Enriched Fill Context
+
+Captures market state when an order is submitted and again when it fills, so
+fill notifications and the trade journal carry complete context without
+racing to gather it after the fact (by the time a fill is polled, the book
+has already moved).
+
+Captured at submission and at fill:
+- Book state: best bid/ask, sizes, mid, spread, top-of-book imbalance
+- Indicator snapshot from registered providers (e.g. cumulative delta, last bar)
+
+Derived at fill:
+- Slippage vs. the submission reference price (limit/stop price, else the
+  touch: ask for buys, bid for sells), positive = adverse
+- Submission-to-fill latency
+
+Configuration:
+- FILL_CONTEXT_MAX_PENDING: Submission snapshots kept for unfilled orders (default 1000)
+"""
+
+import logging
+import os
+import threading
+from collections import OrderedDict
+from dataclasses import dataclass, field, asdict
+from datetime import datetime, timezone
+from typing import Any, Callable, Dict, Optional
+
+logger = logging.getLogger(__name__)
+
+
+@dataclass
+class BookState:
+    """Top-of-book state at a point in time."""
+    bid: Optional[float] = None
+    ask: Optional[float] = None
+    bid_size: Optional[int] = None
+    ask_size: Optional[int] = None
+    last: Optional[float] = None
+    imbalance: Optional[float] = None  # (bid size - ask size) / total, -1..1
+    source: str = "unknown"
+
+    @property
+    def mid(self) -> Optional[float]:
+        if self.bid is None or self.ask is None:
+            return None
+        return (self.bid + self.ask) / 2
+
+    @property
+    def spread(self) -> Optional[float]:
+        if self.bid is None or self.ask is None:
+            return None
+        return round(self.ask - self.bid, 10)
+
+    def to_dict(self) -> Dict[str, Any]:
+        data = asdict(self)
+        data.update(mid=self.mid, spread=self.spread)
+        return data
+
+
+@dataclass
+class MarketSnapshot:
+    """Book and indicator state captured together."""
+    captured_at: datetime
+    book: Optional[BookState] = None
+    indicators: Dict[str, Any] = field(default_factory=dict)
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "captured_at": self.captured_at.isoformat(),
+            "book": self.book.to_dict() if self.book else None,
+            "indicators": self.indicators,
+        }
+
+
+@dataclass
+class SubmissionSnapshot:
+    """Order parameters plus market state when the order was submitted."""
+    order_id: str
+    symbol: str
+    side: str  # 'BUY' / 'SELL'
+    quantity: int
+    order_type: str
+    limit_price: Optional[float] = None
+    stop_price: Optional[float] = None
+    custom_tag: Optional[str] = None
+    market: Optional[MarketSnapshot] = None
+
+    @property
+    def reference_price(self) -> Optional[float]:
+        """Price the fill is measured against for slippage."""
+        if self.limit_price is not None:
+            return self.limit_price
+        if self.stop_price is not None:
+            return self.stop_price
+        book = self.market.book if self.market else None
+        if book is None:
+            return None
+        touch = book.ask if self.side == 'BUY' else book.bid
+        return touch if touch is not None else book.last
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "order_id": self.order_id,
+            "symbol": self.symbol,
+            "side": self.side,
+            "quantity": self.quantity,
+            "order_type": self.order_type,
+            "limit_price": self.limit_price,
+            "stop_price": self.stop_price,
+            "custom_tag": self.custom_tag,
+            "reference_price": self.reference_price,
+            "market": self.market.to_dict() if self.market else None,
+        }
+
+
+class FillContextRecorder:
+    """
+    Records submission snapshots and assembles fill context.
+
+    Usage:
+        recorder = FillContextRecorder(book_provider=bot.get_book_state)
+        recorder.register_indicator('cumulative_delta', lambda sym: footprints.get_cumulative_delta(sym))
+        recorder.capture_submission('123', 'MNQ', 'BUY', 1, 'Market')
+        context = recorder.build_fill_context('123', 'MNQ', 'BUY', 15000.5)
+    """
+
+    def __init__(self, book_provider: Optional[Callable[[str], Optional[BookState]]] = None,
+                 max_pending: Optional[int] = None):
+        """
+        Initialize recorder.
+
+        Args:
+            book_provider: Returns the current BookState for a symbol (or None)
+            max_pending: Snapshots kept for unfilled orders (env: FILL_CONTEXT_MAX_PENDING, default 1000)
+        """
+        self.book_provider = book_provider
+        self.max_pending = max_pending if max_pending is not None else int(os.getenv('FILL_CONTEXT_MAX_PENDING', '1000'))
+        self._indicators: Dict[str, Callable[[str], Any]] = {}
+        self._submissions: 'OrderedDict[str, SubmissionSnapshot]' = OrderedDict()
+        self._lock = threading.Lock()
+
+    def register_indicator(self, name: str, provider: Callable[[str], Any]) -> None:
+        """Add an indicator to every snapshot (provider receives the symbol; must be fast and JSON-friendly)."""
+        self._indicators[name] = provider
+
+    def unregister_indicator(self, name: str) -> None:
+        self._indicators.pop(name, None)
+
+    def snapshot(self, symbol: str) -> MarketSnapshot:
+        """Capture book and indicator state now (provider errors are logged and skipped)."""
+        book = None
+        if self.book_provider:
+            try:
+                book = self.book_provider(symbol)
+            except Exception as e:
+                logger.debug(f"Book state unavailable for {symbol}: {e}")
+        indicators: Dict[str, Any] = {}
+        for name, provider in list(self._indicators.items()):
+            try:
+                indicators[name] = provider(symbol)
+            except Exception as e:
+                logger.debug(f"Indicator '{name}' failed for {symbol}: {e}")
+        return MarketSnapshot(datetime.now(timezone.utc), book, indicators)
+
+    def capture_submission(self, order_id: str, symbol: str, side: str, quantity: int, order_type: str,
+                           limit_price: Optional[float] = None, stop_price: Optional[float] = None,
+                           custom_tag: Optional[str] = None) -> SubmissionSnapshot:
+        """Record market state for an order that was just accepted by the broker."""
+        symbol = symbol.upper()
+        submission = SubmissionSnapshot(str(order_id), symbol, side.upper(), int(quantity), order_type,
+                                        limit_price, stop_price, custom_tag, self.snapshot(symbol))
+        with self._lock:
+            self._submissions[submission.order_id] = submission
+            while len(self._submissions) > self.max_pending:
+                self._submissions.popitem(last=False)
+        return submission
+
+    def get_submission(self, order_id: str) -> Optional[SubmissionSnapshot]:
+        with self._lock:
+            return self._submissions.get(str(order_id))
+
+    def build_fill_context(self, order_id: str, symbol: str, side: str, fill_price: Optional[float]) -> Dict[str, Any]:
+        """
+        Assemble context for a fill and release the order's submission snapshot.
+
+        Args:
+            order_id: Filled order ID
+            symbol: Trading symbol
+            side: 'BUY' or 'SELL'
+            fill_price: Average fill price
+
+        Returns:
+            Dict with 'submission' (None if not captured, e.g. manual or bracket
+            child orders), 'at_fill', 'slippage' and 'latency_seconds'
+        """
+        with self._lock:
+            submission = self._submissions.pop(str(order_id), None)
+        at_fill = self.snapshot(symbol.upper())
+        slippage = None
+        latency = None
+        if submission is not None:
+            reference = submission.reference_price
+            if reference is not None and fill_price is not None:
+                direction = 1 if side.upper() == 'BUY' else -1
+                slippage = round((float(fill_price) - reference) * direction, 10)
+            if submission.market is not None:
+                latency = (at_fill.captured_at - submission.market.captured_at).total_seconds()
+        return {
+            "submission": submission.to_dict() if submission else None,
+            "at_fill": at_fill.to_dict(),
+            "slippage": slippage,
+            "latency_seconds": latency,
+        }
@@ -0,0 +1,487 @@
+"""
+Order-Flow Footprint Bars
+
+Classifies trades as buyer- or seller-initiated and aggregates them into
+per-bar footprints: volume-at-price split by aggressor side plus delta
+statistics. FootprintBar extends Bar, so footprints can be passed anywhere a
+regular OHLCV bar is accepted.
+
+Trade classification:
+- Aggressor side from the feed when available (GatewayTrade type 0 = buy, 1 = sell)
+- Otherwise the tick rule: uptick = buy, downtick = sell, zero tick repeats
+  the previous classification; unclassifiable trades count toward volume only
+
+Features:
+- Delta (buy - sell volume), intrabar max/min delta, cumulative delta
+- Volume-at-price ladder: {price: (sell volume at bid, buy volume at ask)}
+- Multiple time-based timeframes per symbol
+- Completed footprint callback and per-timeframe history
+- Usable as a market event listener (consumes Trade events)
+- Streaming cumulative delta per trade (on_delta listeners, get_delta_series)
+- Stacked imbalance detection on completed footprints (on_imbalance listeners)
+
+Imbalances are diagonal: a buy imbalance at price P means ask volume at P is
+at least `ratio` times the bid volume one tick below; a sell imbalance means
+bid volume at P is at least `ratio` times the ask volume one tick above. A
+missing opposite level counts as 1 contract. `stacked_levels` or more
+consecutive same-side imbalances produce one Imbalance event.
+
+Configuration:
+- FOOTPRINT_IMBALANCE_RATIO: Diagonal volume ratio (default 3.0)
+- FOOTPRINT_IMBALANCE_MIN_VOLUME: Minimum aggressor volume at the level (default 5)
+- FOOTPRINT_STACKED_LEVELS: Consecutive imbalanced levels per event (default 3)
+- FOOTPRINT_DELTA_SERIES_SIZE: Cumulative delta points kept per symbol (default 2000)
+"""
+
+import logging
+import os
+import threading
+from collections import defaultdict, deque
+from dataclasses import dataclass, field
+from datetime import datetime, timezone, timedelta
+from typing import Any, Callable, Dict, Iterable, List, Optional, Tuple
+
+from core.bar_aggregator import Bar, bar_start_time, timeframe_seconds
+from core.market_events import Imbalance, MarketEvent, Trade
+
+logger = logging.getLogger(__name__)
+
+BUY = "buy"
+SELL = "sell"
+
+
+@dataclass
+class FootprintBar(Bar):
+    """OHLCV bar with order-flow footprint data."""
+    buy_volume: int = 0
+    sell_volume: int = 0
+    delta: int = 0
+    max_delta: int = 0
+    min_delta: int = 0
+    cumulative_delta: int = 0  # Running delta for the symbol/timeframe at bar close
+    ladder: Dict[float, Tuple[int, int]] = field(default_factory=dict)  # price -> (bid volume, ask volume)
+
+    @property
+    def poc(self) -> Optional[float]:
+        """Price with the most total volume in the bar."""
+        if not self.ladder:
+            return None
+        return max(self.ladder.items(), key=lambda kv: (kv[1][0] + kv[1][1], -kv[0]))[0]
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        return {
+            "symbol": self.symbol,
+            "timeframe": self.timeframe,
+            "timestamp": self.timestamp.isoformat(),
+            "open": self.open,
+            "high": self.high,
+            "low": self.low,
+            "close": self.close,
+            "volume": self.volume,
+            "tick_count": self.tick_count,
+            "buy_volume": self.buy_volume,
+            "sell_volume": self.sell_volume,
+            "delta": self.delta,
+            "max_delta": self.max_delta,
+            "min_delta": self.min_delta,
+            "cumulative_delta": self.cumulative_delta,
+            "ladder": [{"price": price, "bid": bid, "ask": ask}
+                       for price, (bid, ask) in sorted(self.ladder.items(), reverse=True)],
+        }
+
+
+@dataclass(frozen=True)
+class DeltaPoint:
+    """Cumulative delta after one classified trade."""
+    symbol: str
+    timestamp: datetime
+    delta: int  # Signed size of this trade
+    cumulative_delta: int
+
+
+def find_stacked_imbalances(bar: FootprintBar, tick_size: Optional[float] = None, ratio: float = 3.0,
+                            min_volume: int = 5, stacked_levels: int = 3) -> List[Imbalance]:
+    """
+    Detect stacked diagonal imbalances in a footprint.
+
+    Args:
+        bar: Completed (or forming) footprint
+        tick_size: Price increment between ladder levels (inferred from the ladder if None)
+        ratio: Minimum aggressor / opposite volume ratio
+        min_volume: Minimum aggressor volume at an imbalanced level
+        stacked_levels: Consecutive imbalanced levels required
+
+    Returns:
+        List[Imbalance]: One event per stack, lowest price first
+    """
+    prices = sorted(bar.ladder)
+    if tick_size is None:
+        steps = [round(b - a, 10) for a, b in zip(prices, prices[1:])]
+        tick_size = min(steps) if steps else None
+    if not tick_size:
+        return []
+
+    def level(price: float) -> Tuple[int, int]:
+        return bar.ladder.get(round(price, 10), (0, 0))
+
+    events: List[Imbalance] = []
+    for side in ('buy', 'sell'):
+        run: List[Tuple[float, float]] = []  # (price, ratio)
+
+        def flush():
+            if len(run) >= stacked_levels:
+                events.append(Imbalance(bar.symbol, bar.timestamp, bar.timeframe, side, run[0][0], run[-1][0],
+                                        len(run), round(min(r for _, r in run), 4)))
+            run.clear()
+
+        for price in prices:
+            bid, ask = level(price)
+            if side == 'buy':
+                aggressor, opposite = ask, level(price - tick_size)[0]
+            else:
+                aggressor, opposite = bid, level(price + tick_size)[1]
+            level_ratio = aggressor / max(opposite, 1)
+            if aggressor >= min_volume and level_ratio >= ratio:
+                if run and round(price - run[-1][0] - tick_size, 10) != 0:
+                    flush()  # Not adjacent to the current stack
+                run.append((price, level_ratio))
+            else:
+                flush()
+        flush()
+    events.sort(key=lambda e: (e.price_low, e.side))
+    return events
+
+
+class TickRuleClassifier:
+    """Per-symbol tick-rule trade classification state."""
+
+    def __init__(self):
+        self._last: Dict[str, Tuple[float, Optional[str]]] = {}  # symbol -> (price, side)
+
+    def classify(self, symbol: str, price: float, side: Optional[str] = None) -> Optional[str]:
+        """
+        Classify a trade.
+
+        Args:
+            symbol: Trading symbol
+            price: Trade price
+            side: Aggressor side from the feed ('buy'/'sell'), if known
+
+        Returns:
+            'buy', 'sell' or None when unclassifiable
+        """
+        previous = self._last.get(symbol)
+        if side in (BUY, SELL):
+            classified = side
+        elif previous is None:
+            classified = None
+        elif price > previous[0]:
+            classified = BUY
+        elif price < previous[0]:
+            classified = SELL
+        else:
+            classified = previous[1]
+        self._last[symbol] = (price, classified)
+        return classified
+
+    def reset(self, symbol: Optional[str] = None):
+        if symbol is None:
+            self._last.clear()
+        else:
+            self._last.pop(symbol, None)
+
+
+@dataclass
+class _FootprintBuilder:
+    symbol: str
+    timeframe: str
+    bar_start: datetime
+    open: Optional[float] = None
+    high: float = 0.0
+    low: float = 0.0
+    close: float = 0.0
+    volume: int = 0
+    tick_count: int = 0
+    buy_volume: int = 0
+    sell_volume: int = 0
+    max_delta: int = 0
+    min_delta: int = 0
+    ladder: Dict[float, List[int]] = field(default_factory=lambda: defaultdict(lambda: [0, 0]))
+
+    @property
+    def delta(self) -> int:
+        return self.buy_volume - self.sell_volume
+
+    def add_trade(self, price: float, size: int, side: Optional[str]):
+        if self.open is None:
+            self.open = self.high = self.low = price
+        else:
+            self.high = max(self.high, price)
+            self.low = min(self.low, price)
+        self.close = price
+        self.volume += size
+        self.tick_count += 1
+        level = self.ladder[price]  # Unclassified trades still mark the price as traded
+        if side == BUY:
+            self.buy_volume += size
+            level[1] += size
+        elif side == SELL:
+            self.sell_volume += size
+            level[0] += size
+        self.max_delta = max(self.max_delta, self.delta)
+        self.min_delta = min(self.min_delta, self.delta)
+
+    def to_bar(self, cumulative_delta: int) -> FootprintBar:
+        return FootprintBar(
+            symbol=self.symbol, timeframe=self.timeframe, timestamp=self.bar_start,
+            open=self.open, high=self.high, low=self.low, close=self.close,
+            volume=self.volume, tick_count=self.tick_count,
+            buy_volume=self.buy_volume, sell_volume=self.sell_volume, delta=self.delta,
+            max_delta=self.max_delta, min_delta=self.min_delta, cumulative_delta=cumulative_delta,
+            ladder={price: (bid, ask) for price, (bid, ask) in self.ladder.items()},
+        )
+
+
+class FootprintAggregator:
+    """
+    Builds footprint bars from a trade stream.
+
+    Usage:
+        footprints = FootprintAggregator(timeframes=['1m', '5m'], footprint_callback=on_footprint)
+        footprints.add_trade('MNQ', 15000.25, 2, ts, side='buy')
+
+        # Or as a market event listener on the bot
+        bot.add_market_event_listener(footprints.on_market_event)
+    """
+
+    def __init__(self, timeframes: Optional[Iterable[str]] = None,
+                 footprint_callback: Optional[Callable[[FootprintBar], None]] = None,
+                 tick_sizes: Optional[Dict[str, float]] = None,
+                 max_history: Optional[int] = None,
+                 imbalance_ratio: Optional[float] = None,
+                 imbalance_min_volume: Optional[int] = None,
+                 stacked_levels: Optional[int] = None,
+                 delta_series_size: Optional[int] = None):
+        """
+        Initialize footprint aggregator.
+
+        Args:
+            timeframes: Time-based timeframes to build (env: FOOTPRINT_TIMEFRAMES, default '1m')
+            footprint_callback: Called with each completed FootprintBar
+            tick_sizes: {symbol: tick size} used to snap ladder prices (unsnapped if absent)
+            max_history: Completed footprints kept per symbol/timeframe (env: FOOTPRINT_HISTORY_SIZE)
+            imbalance_ratio: Diagonal imbalance ratio (env: FOOTPRINT_IMBALANCE_RATIO)
+            imbalance_min_volume: Minimum volume at an imbalanced level (env: FOOTPRINT_IMBALANCE_MIN_VOLUME)
+            stacked_levels: Consecutive imbalanced levels per event (env: FOOTPRINT_STACKED_LEVELS)
+            delta_series_size: Cumulative delta points kept per symbol (env: FOOTPRINT_DELTA_SERIES_SIZE)
+        """
+        frames = timeframes if timeframes is not None else os.getenv('FOOTPRINT_TIMEFRAMES', '1m').split(',')
+        self.timeframes = [tf.strip().lower() for tf in frames if tf and tf.strip()] or ['1m']
+        self.footprint_callback = footprint_callback
+        self.tick_sizes = {k.upper(): float(v) for k, v in (tick_sizes or {}).items()}
+        if max_history is None:
+            max_history = int(os.getenv('FOOTPRINT_HISTORY_SIZE', '500'))
+        self.classifier = TickRuleClassifier()
+        self._builders: Dict[str, Dict[str, _FootprintBuilder]] = defaultdict(dict)
+        self._cumulative_delta: Dict[str, Dict[str, int]] = defaultdict(lambda: defaultdict(int))
+        self._history: Dict[str, Dict[str, deque]] = defaultdict(
+            lambda: defaultdict(lambda: deque(maxlen=max_history))
+        )
+        self.imbalance_ratio = (imbalance_ratio if imbalance_ratio is not None
+                                else float(os.getenv('FOOTPRINT_IMBALANCE_RATIO', '3.0')))
+        self.imbalance_min_volume = (imbalance_min_volume if imbalance_min_volume is not None
+                                     else int(os.getenv('FOOTPRINT_IMBALANCE_MIN_VOLUME', '5')))
+        self.stacked_levels = (stacked_levels if stacked_levels is not None
+                               else int(os.getenv('FOOTPRINT_STACKED_LEVELS', '3')))
+        if delta_series_size is None:
+            delta_series_size = int(os.getenv('FOOTPRINT_DELTA_SERIES_SIZE', '2000'))
+        self._session_delta: Dict[str, int] = defaultdict(int)
+        self._delta_series: Dict[str, deque] = defaultdict(lambda: deque(maxlen=delta_series_size))
+        # Copy-on-write listener tuples so emission never holds a lock while calling user code
+        self._delta_listeners: Tuple[Callable[[DeltaPoint], Any], ...] = ()
+        self._imbalance_listeners: Tuple[Tuple[Callable[[Imbalance], Any], Optional[str]], ...] = ()
+        self._lock = threading.RLock()
+
+    def _snap(self, symbol_key: str, price: float) -> float:
+        tick = self.tick_sizes.get(symbol_key)
+        return round(round(price / tick) * tick, 10) if tick else price
+
+    def add_trade(self, symbol: str, price: float, size: int, timestamp: Optional[datetime] = None,
+                  side: Optional[str] = None) -> Optional[str]:
+        """
+        Add a trade.
+
+        Args:
+            symbol: Trading symbol
+            price: Trade price
+            size: Contracts traded
+            timestamp: Trade time (defaults to now)
+            side: Aggressor side from the feed, if known ('buy'/'sell')
+
+        Returns:
+            The trade's classification ('buy', 'sell' or None)
+        """
+        if timestamp is None:
+            timestamp = datetime.now(timezone.utc)
+        symbol_key = symbol.upper()
+        price = self._snap(symbol_key, price)
+        completed: List[FootprintBar] = []
+        point: Optional[DeltaPoint] = None
+        with self._lock:
+            classified = self.classifier.classify(symbol_key, price, side)
+            if classified is not None:
+                signed = size if classified == BUY else -size
+                self._session_delta[symbol_key] += signed
+                point = DeltaPoint(symbol_key, timestamp, signed, self._session_delta[symbol_key])
+                self._delta_series[symbol_key].append(point)
+            for timeframe in self.timeframes:
+                builder = self._builders[symbol_key].get(timeframe)
+                if builder is None or timestamp >= builder.bar_start + timedelta(seconds=timeframe_seconds(timeframe)):
+                    if builder is not None and builder.open is not None:
+                        completed.append(self._complete(builder))
+                    builder = _FootprintBuilder(symbol_key, timeframe, bar_start_time(timestamp, timeframe))
+                    self._builders[symbol_key][timeframe] = builder
+                elif timestamp < builder.bar_start:
+                    continue  # Late trade for an already-closed bar
+                builder.add_trade(price, size, classified)
+        if point is not None:
+            for listener in self._delta_listeners:
+                try:
+                    listener(point)
+                except Exception as e:
+                    logger.error(f"Error in delta listener for {symbol_key}: {e}")
+        for bar in completed:
+            self._emit(bar)
+        return classified
+
+    def on_market_event(self, event: MarketEvent) -> None:
+        """Market event listener: consumes Trade events, ignores everything else."""
+        if isinstance(event, Trade):
+            self.add_trade(event.symbol, event.price, event.size, event.timestamp, event.side)
+
+    def close_elapsed_bars(self, now: Optional[datetime] = None) -> List[FootprintBar]:
+        """Complete footprints whose period has ended even if no new trade arrived."""
+        if now is None:
+            now = datetime.now(timezone.utc)
+        completed: List[FootprintBar] = []
+        with self._lock:
+            for symbol_key, frames in self._builders.items():
+                for timeframe, builder in list(frames.items()):
+                    end = builder.bar_start + timedelta(seconds=timeframe_seconds(timeframe))
+                    if builder.open is None or now < end:
+                        continue
+                    completed.append(self._complete(builder))
+                    frames[timeframe] = _FootprintBuilder(symbol_key, timeframe, bar_start_time(now, timeframe))
+        for bar in completed:
+            self._emit(bar)
+        return completed
+
+    def _complete(self, builder: _FootprintBuilder) -> FootprintBar:
+        """Finish a footprint and record it (caller holds _lock)."""
+        running = self._cumulative_delta[builder.symbol]
+        running[builder.timeframe] += builder.delta
+        bar = builder.to_bar(running[builder.timeframe])
+        self._history[builder.symbol][builder.timeframe].append(bar)
+        return bar
+
+    def _emit(self, bar: FootprintBar):
+        if self.footprint_callback:
+            try:
+                self.footprint_callback(bar)
+            except Exception as e:
+                logger.error(f"Error in footprint callback for {bar.symbol} {bar.timeframe}: {e}")
+        if not self._imbalance_listeners:
+            return
+        for event in self.find_imbalances(bar):
+            for listener, symbol in self._imbalance_listeners:
+                if symbol is not None and symbol != event.symbol:
+                    continue
+                try:
+                    listener(event)
+                except Exception as e:
+                    logger.error(f"Error in imbalance listener for {event.symbol} {event.timeframe}: {e}")
+
+    def find_imbalances(self, bar: FootprintBar) -> List[Imbalance]:
+        """Stacked imbalances in a footprint using this aggregator's thresholds."""
+        return find_stacked_imbalances(bar, self.tick_sizes.get(bar.symbol), self.imbalance_ratio,
+                                       self.imbalance_min_volume, self.stacked_levels)
+
+    def on_imbalance(self, callback: Callable[[Imbalance], Any], symbol: Optional[str] = None) -> Callable[[], None]:
+        """
+        Register a listener for stacked imbalances found in completed footprints.
+
+        Args:
+            callback: Called with each Imbalance event (e.g. TopStepXTradingBot._publish_market_event)
+            symbol: Only imbalances for this symbol (default: all symbols)
+
+        Returns:
+            Callable that unregisters the listener
+        """
+        entry = (callback, symbol.upper() if symbol else None)
+        with self._lock:
+            self._imbalance_listeners = self._imbalance_listeners + (entry,)
+
+        def unsubscribe():
+            with self._lock:
+                self._imbalance_listeners = tuple(l for l in self._imbalance_listeners if l is not entry)
+        return unsubscribe
+
+    def on_delta(self, callback: Callable[[DeltaPoint], Any]) -> Callable[[], None]:
+        """
+        Register a listener called with a DeltaPoint after every classified trade.
+
+        Listeners run inline on the trade thread, so they should be quick.
+
+        Returns:
+            Callable that unregisters the listener
+        """
+        with self._lock:
+            self._delta_listeners = self._delta_listeners + (callback,)
+
+        def unsubscribe():
+            with self._lock:
+                self._delta_listeners = tuple(l for l in self._delta_listeners if l is not callback)
+        return unsubscribe
+
+    def get_delta_series(self, symbol: str, count: Optional[int] = None) -> List[DeltaPoint]:
+        """Recent per-trade cumulative delta points for a symbol (oldest first)."""
+        with self._lock:
+            series = list(self._delta_series.get(symbol.upper(), ()))
+        return series[-count:] if count else series
+
+    def get_current(self, symbol: str, timeframe: str) -> Optional[FootprintBar]:
+        """Forming footprint (cumulative_delta includes the forming bar's delta)."""
+        symbol_key = symbol.upper()
+        with self._lock:
+            builder = self._builders.get(symbol_key, {}).get(timeframe.lower())
+            if builder is None or builder.open is None:
+                return None
+            cumulative = self._cumulative_delta[symbol_key][builder.timeframe] + builder.delta
+            return builder.to_bar(cumulative)
+
+    def get_history(self, symbol: str, timeframe: str, count: Optional[int] = None) -> List[FootprintBar]:
+        """Completed footprints for a symbol/timeframe (oldest first)."""
+        with self._lock:
+            frames = self._history.get(symbol.upper())
+            history = list(frames.get(timeframe.lower(), ())) if frames else []
+        return history[-count:] if count else history
+
+    def get_cumulative_delta(self, symbol: str, timeframe: Optional[str] = None) -> int:
+        """Cumulative delta including the forming bar."""
+        timeframe = (timeframe or self.timeframes[0]).lower()
+        current = self.get_current(symbol, timeframe)
+        if current is not None:
+            return current.cumulative_delta
+        with self._lock:
+            return self._cumulative_delta[symbol.upper()][timeframe]
+
+    def reset_cumulative_delta(self, symbol: Optional[str] = None):
+        """Reset running cumulative delta (e.g., at session open)."""
+        with self._lock:
+            if symbol is None:
+                self._cumulative_delta.clear()
+                self._session_delta.clear()
+            else:
+                self._cumulative_delta.pop(symbol.upper(), None)
+                self._session_delta.pop(symbol.upper(), None)
@@ -0,0 +1,186 @@
+"""
+FX Rates and Currency Conversion
+
+Some accounts report in a currency other than USD (e.g., CAD), while the
+instruments we trade are USD-denominated. This module converts amounts between
+currencies using a pluggable rate source.
+
+Conversion policy:
+- Realised P&L is converted at the rate in effect when the trade filled
+  (trade time) and the converted amount is stored, so history never moves
+- Unrealised P&L and balances are converted at the current rate (report time)
+
+Features:
+- FXRateSource base class - implement get_rate() to plug in any provider
+- StaticFXRateSource from a dict or FX_RATES env ("CAD/USD=0.72,EUR/USD=1.08")
+- CallableFXRateSource to wrap an existing fetch function
+- Inverse and USD-cross rates derived automatically
+- Reporting currency from REPORTING_CURRENCY env (default USD)
+"""
+
+import logging
+import os
+import threading
+from datetime import datetime
+from typing import Callable, Dict, Optional, Tuple
+
+logger = logging.getLogger(__name__)
+
+CROSS_CURRENCY = 'USD'
+
+
+class FXRateError(LookupError):
+    """Raised when no rate is available for a currency pair."""
+
+
+def _pair(base: str, quote: str) -> Tuple[str, str]:
+    return base.strip().upper(), quote.strip().upper()
+
+
+class FXRateSource:
+    """
+    Base class for FX rate providers.
+
+    Subclasses return the price of one unit of `base` in `quote`, or None when
+    the pair is unknown. Inverse and cross rates are handled by FXConverter.
+    """
+
+    def get_rate(self, base: str, quote: str, at: Optional[datetime] = None) -> Optional[float]:
+        """
+        Get the base/quote rate.
+
+        Args:
+            base: Currency being converted from (e.g., 'CAD')
+            quote: Currency being converted to (e.g., 'USD')
+            at: Point in time for the rate (None = latest)
+        """
+        raise NotImplementedError
+
+
+class StaticFXRateSource(FXRateSource):
+    """Fixed rates, e.g. {'CAD/USD': 0.72}. Updatable at runtime via set_rate()."""
+
+    def __init__(self, rates: Optional[Dict[str, float]] = None):
+        self._rates: Dict[Tuple[str, str], float] = {}
+        self._lock = threading.Lock()
+        for pair, rate in (rates or {}).items():
+            base, quote = pair.split('/')
+            self.set_rate(base, quote, rate)
+
+    @classmethod
+    def from_env(cls) -> 'StaticFXRateSource':
+        """Build from FX_RATES ("CAD/USD=0.72,EUR/USD=1.08"); malformed entries are skipped."""
+        rates: Dict[str, float] = {}
+        for entry in os.getenv('FX_RATES', '').split(','):
+            if not entry.strip():
+                continue
+            try:
+                pair, rate = entry.split('=')
+                rates[pair.strip()] = float(rate)
+            except ValueError:
+                logger.warning(f"⚠️  Ignoring malformed FX_RATES entry '{entry}'")
+        return cls(rates)
+
+    def set_rate(self, base: str, quote: str, rate: float) -> None:
+        """Set or replace the base/quote rate."""
+        if rate <= 0:
+            raise ValueError(f"FX rate must be positive, got {rate} for {base}/{quote}")
+        with self._lock:
+            self._rates[_pair(base, quote)] = float(rate)
+
+    def get_rate(self, base: str, quote: str, at: Optional[datetime] = None) -> Optional[float]:
+        with self._lock:
+            return self._rates.get(_pair(base, quote))
+
+
+class CallableFXRateSource(FXRateSource):
+    """Wrap a function (base, quote, at) -> rate, e.g. an HTTP or database lookup."""
+
+    def __init__(self, fetch: Callable[[str, str, Optional[datetime]], Optional[float]]):
+        self._fetch = fetch
+
+    def get_rate(self, base: str, quote: str, at: Optional[datetime] = None) -> Optional[float]:
+        try:
+            return self._fetch(base, quote, at)
+        except Exception as e:
+            logger.error(f"❌ FX rate lookup failed for {base}/{quote}: {e}")
+            return None
+
+
+class FXConverter:
+    """
+    Converts amounts between currencies using an FXRateSource.
+
+    Usage:
+        fx = FXConverter(StaticFXRateSource({'CAD/USD': 0.72}), reporting_currency='USD')
+        fx.convert(100.0, 'CAD', 'USD')         # 72.0
+        fx.to_reporting(100.0, 'CAD', at=fill_time)
+    """
+
+    def __init__(self, source: Optional[FXRateSource] = None, reporting_currency: Optional[str] = None):
+        """
+        Initialize converter.
+
+        Args:
+            source: Rate provider (default: StaticFXRateSource from FX_RATES env)
+            reporting_currency: Currency for consolidated reports (env: REPORTING_CURRENCY, default USD)
+        """
+        self.source = source or StaticFXRateSource.from_env()
+        self.reporting_currency = (reporting_currency or os.getenv('REPORTING_CURRENCY', 'USD')).upper()
+
+    def rate(self, from_currency: str, to_currency: str, at: Optional[datetime] = None) -> float:
+        """
+        Rate to multiply a from_currency amount by to get to_currency.
+
+        Tries the direct pair, its inverse, then a cross through USD.
+
+        Raises:
+            FXRateError: If no rate can be derived
+        """
+        base, quote = _pair(from_currency, to_currency)
+        if base == quote:
+            return 1.0
+        rate = self._direct(base, quote, at)
+        if rate is None and CROSS_CURRENCY not in (base, quote):
+            leg1 = self._direct(base, CROSS_CURRENCY, at)
+            leg2 = self._direct(CROSS_CURRENCY, quote, at)
+            if leg1 is not None and leg2 is not None:
+                rate = leg1 * leg2
+        if rate is None:
+            raise FXRateError(f"No FX rate available for {base}/{quote}")
+        return rate
+
+    def _direct(self, base: str, quote: str, at: Optional[datetime]) -> Optional[float]:
+        rate = self.source.get_rate(base, quote, at)
+        if rate:
+            return rate
+        inverse = self.source.get_rate(quote, base, at)
+        return 1.0 / inverse if inverse else None
+
+    def convert(self, amount: float, from_currency: str, to_currency: str,
+                at: Optional[datetime] = None) -> float:
+        """Convert an amount (raises FXRateError if no rate)."""
+        return amount * self.rate(from_currency, to_currency, at)
+
+    def to_reporting(self, amount: float, currency: str, at: Optional[datetime] = None) -> float:
+        """Convert an amount into the reporting currency."""
+        return self.convert(amount, currency, self.reporting_currency, at)
+
+
+_fx_converter: Optional[FXConverter] = None
+
+
+def get_fx_converter() -> FXConverter:
+    """Get the global FX converter (rates from FX_RATES env by default)."""
+    global _fx_converter
+    if _fx_converter is None:
+        _fx_converter = FXConverter()
+    return _fx_converter
+
+
+def set_fx_rate_source(source: FXRateSource) -> FXConverter:
+    """Plug a different rate source into the global converter."""
+    converter = get_fx_converter()
+    converter.source = source
+    logger.info(f"💱 FX rate source set to {type(source).__name__}")
+    return converter
@@ -0,0 +1,157 @@
+"""
+Heiken Ashi bar transform.
+
+Provides a batch transform for historical bars and a streaming aggregator
+that keeps Heiken Ashi state per symbol/timeframe so completed bars can be
+converted incrementally instead of recomputing the whole series every bar.
+
+    HA close = (open + high + low + close) / 4
+    HA open  = (previous HA open + previous HA close) / 2   (first bar: (open + close) / 2)
+    HA high  = max(high, HA open, HA close)
+    HA low   = min(low, HA open, HA close)
+"""
+
+import logging
+import threading
+from dataclasses import replace
+from typing import Any, Callable, Dict, Iterable, List, Optional, Tuple, Union
+
+from core.bar_aggregator import Bar
+
+logger = logging.getLogger(__name__)
+
+BarLike = Union[Bar, Dict[str, Any]]
+
+
+def _ohlc(bar: BarLike) -> Tuple[float, float, float, float]:
+    if isinstance(bar, dict):
+        return float(bar['open']), float(bar['high']), float(bar['low']), float(bar['close'])
+    return bar.open, bar.high, bar.low, bar.close
+
+
+def heiken_ashi_step(bar: BarLike, prev: Optional[Tuple[float, float]]) -> Tuple[float, float, float, float]:
+    """
+    Compute one Heiken Ashi bar.
+
+    Args:
+        bar: Source bar (Bar or dict with open/high/low/close)
+        prev: (HA open, HA close) of the previous bar, or None for the first bar
+
+    Returns:
+        (HA open, HA high, HA low, HA close)
+    """
+    o, h, l, c = _ohlc(bar)
+    ha_close = (o + h + l + c) / 4
+    ha_open = (o + c) / 2 if prev is None else (prev[0] + prev[1]) / 2
+    return ha_open, max(h, ha_open, ha_close), min(l, ha_open, ha_close), ha_close
+
+
+def _with_ohlc(bar: BarLike, ha: Tuple[float, float, float, float]) -> BarLike:
+    """Copy a bar with Heiken Ashi prices (other fields preserved)."""
+    ha_open, ha_high, ha_low, ha_close = ha
+    if isinstance(bar, dict):
+        out = dict(bar)
+        out.update(open=ha_open, high=ha_high, low=ha_low, close=ha_close)
+        return out
+    return replace(bar, open=ha_open, high=ha_high, low=ha_low, close=ha_close)
+
+
+def to_heiken_ashi(bars: Iterable[BarLike]) -> List[BarLike]:
+    """
+    Convert a bar series (oldest first) to Heiken Ashi.
+
+    Accepts Bar objects or dicts (e.g., cached bars from the database); each
+    output has the same type and non-price fields as its input.
+    """
+    result: List[BarLike] = []
+    prev: Optional[Tuple[float, float]] = None
+    for bar in bars:
+        ha = heiken_ashi_step(bar, prev)
+        prev = (ha[0], ha[3])
+        result.append(_with_ohlc(bar, ha))
+    return result
+
+
+class HeikenAshiAggregator:
+    """
+    Streaming Heiken Ashi transform keyed by symbol/timeframe.
+
+    Features:
+    - O(1) per completed bar (keeps previous HA open/close only)
+    - Optional callback with each HA bar
+    - Seeding from history so the first live bar continues the series
+    - Thread-safe
+
+    Usage:
+        ha = HeikenAshiAggregator(ha_bar_callback=on_ha_bar)
+        bar_aggregator = BarAggregator(bar_close_callback=ha.add_bar)
+    """
+
+    def __init__(self, ha_bar_callback: Optional[Callable[[Bar], None]] = None):
+        """
+        Initialize Heiken Ashi aggregator.
+
+        Args:
+            ha_bar_callback: Function called with each Heiken Ashi bar
+        """
+        self.ha_bar_callback = ha_bar_callback
+        self._state: Dict[Tuple[str, str], Tuple[float, float]] = {}  # (symbol, tf) -> (HA open, HA close)
+        self._last: Dict[Tuple[str, str], Bar] = {}
+        self._lock = threading.Lock()
+
+    def add_bar(self, bar: Bar) -> Bar:
+        """
+        Convert a completed bar, continuing the series for its symbol/timeframe.
+
+        Signature matches BarAggregator's bar_close_callback.
+
+        Returns:
+            Bar: Heiken Ashi bar
+        """
+        key = (bar.symbol.upper(), bar.timeframe)
+        with self._lock:
+            ha = heiken_ashi_step(bar, self._state.get(key))
+            self._state[key] = (ha[0], ha[3])
+            ha_bar = _with_ohlc(bar, ha)
+            self._last[key] = ha_bar
+        if self.ha_bar_callback:
+            try:
+                self.ha_bar_callback(ha_bar)
+            except Exception as e:
+                logger.error(f"Error in Heiken Ashi callback for {bar.symbol} {bar.timeframe}: {e}")
+        return ha_bar
+
+    def seed(self, bars: Iterable[Bar]) -> List[Bar]:
+        """
+        Warm up state from historical bars (oldest first) without invoking the callback.
+
+        Returns:
+            List[Bar]: Heiken Ashi bars for the history
+        """
+        converted: List[Bar] = []
+        with self._lock:
+            for bar in bars:
+                key = (bar.symbol.upper(), bar.timeframe)
+                ha = heiken_ashi_step(bar, self._state.get(key))
+                self._state[key] = (ha[0], ha[3])
+                ha_bar = _with_ohlc(bar, ha)
+                self._last[key] = ha_bar
+                converted.append(ha_bar)
+        return converted
+
+    def get_last(self, symbol: str, timeframe: str) -> Optional[Bar]:
+        """Most recent Heiken Ashi bar for a symbol/timeframe."""
+        with self._lock:
+            return self._last.get((symbol.upper(), timeframe))
+
+    def reset(self, symbol: Optional[str] = None, timeframe: Optional[str] = None):
+        """Clear state for a symbol (optionally one timeframe) or everything."""
+        with self._lock:
+            if symbol is None:
+                self._state.clear()
+                self._last.clear()
+                return
+            symbol_key = symbol.upper()
+            for key in [k for k in self._state if k[0] == symbol_key and (timeframe is None or k[1] == timeframe)]:
+                self._state.pop(key, None)
+                self._last.pop(key, None)
@@ -0,0 +1,301 @@
+"""
+Historical Bar Fetcher
+
+Downloads historical bars from the TopStepX REST history endpoint
+(/api/History/retrieveBars) for arbitrary date ranges. The API caps each
+response at a maximum bar count, so long ranges are split into date chunks
+that each fit in one request, and truncated responses are paginated
+backwards from the earliest bar received.
+
+Features:
+- Date-range chunking sized to the per-request bar limit
+- Pagination of truncated responses, de-duplicated by bar timestamp
+- Retries with exponential backoff (longer on HTTP 429), drawing from the
+  session-wide retry budget
+- Request pacing to stay under the broker's rate limit
+- Results as List[Bar] or as numpy column arrays
+- Responses validated against the API schema (BarsResponse); a schema
+  mismatch fails immediately instead of being retried or read as no bars
+
+Configuration:
+- HISTORY_MAX_BARS_PER_REQUEST: Bars requested per call (default 20000, the API maximum)
+- HISTORY_MAX_RETRIES: Retries per request (default 3)
+- HISTORY_MIN_REQUEST_INTERVAL: Minimum seconds between requests (default 0.25)
+"""
+
+import asyncio
+import logging
+import os
+import re
+import time
+from datetime import datetime, timedelta, timezone
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+from core.api_models import ApiSchemaError, BarsResponse
+from core.bar_aggregator import Bar
+from infrastructure.retry_budget import RetryBudget, get_retry_budget
+
+logger = logging.getLogger(__name__)
+
+# TopStepX API units: 1=Second, 2=Minute, 3=Hour, 4=Day, 5=Week, 6=Month
+API_UNITS = {'s': (1, 1), 'm': (2, 60), 'h': (3, 3600), 'd': (4, 86400), 'w': (5, 604800), 'M': (6, 2592000)}
+
+
+class HistoryFetchError(RuntimeError):
+    """History request failed after all retries."""
+
+
+def parse_timeframe(timeframe: str) -> Tuple[int, int, int]:
+    """
+    Parse a timeframe into API units.
+
+    Args:
+        timeframe: e.g. '1s', '5m', '1h', '1d', '1w', '1M' (months approximated as 30 days)
+
+    Returns:
+        Tuple of (api_unit, unit_number, bar_seconds)
+    """
+    match = re.match(r'^(\d+)([smhdwM])$', timeframe.strip())
+    if not match:
+        raise ValueError(f"Invalid timeframe '{timeframe}'. Use format like: 1s, 5m, 1h, 1d, 1w, 1M")
+    number = int(match.group(1))
+    unit, seconds = API_UNITS[match.group(2)]
+    return unit, number, number * seconds
+
+
+def _parse_timestamp(value: Any) -> Optional[datetime]:
+    if isinstance(value, str):
+        dt = datetime.fromisoformat(value.replace('Z', '+00:00'))
+        return dt if dt.tzinfo else dt.replace(tzinfo=timezone.utc)
+    if isinstance(value, (int, float)):
+        # Epoch seconds or milliseconds
+        return datetime.fromtimestamp(value / 1000 if value > 10_000_000_000 else value, tz=timezone.utc)
+    return None
+
+
+def _field(raw: Dict, names: Tuple[str, ...], default: Any = 0) -> Any:
+    for name in names:
+        value = raw.get(name)
+        if value is not None:
+            return value
+    return default
+
+
+def parse_bar(raw: Dict, symbol: str, timeframe: str) -> Optional[Bar]:
+    """
+    Convert an API bar (single-letter keys t/o/h/l/c/v, or full names) to a Bar.
+
+    Returns:
+        Bar with a UTC timestamp, or None if the bar has no usable timestamp
+    """
+    try:
+        timestamp = _parse_timestamp(_field(raw, ('t', 'time', 'timestamp', 'Time', 'Timestamp'), None))
+    except (ValueError, OverflowError, OSError):
+        timestamp = None
+    if timestamp is None:
+        logger.debug(f"Skipping history bar without timestamp: {raw}")
+        return None
+    return Bar(
+        symbol=symbol,
+        timeframe=timeframe,
+        timestamp=timestamp.astimezone(timezone.utc),
+        open=float(_field(raw, ('o', 'open', 'Open'))),
+        high=float(_field(raw, ('h', 'high', 'High'))),
+        low=float(_field(raw, ('l', 'low', 'Low'))),
+        close=float(_field(raw, ('c', 'close', 'Close'))),
+        volume=int(_field(raw, ('v', 'volume', 'Volume'))),
+    )
+
+
+def _format_time(dt: datetime) -> str:
+    return dt.astimezone(timezone.utc).strftime("%Y-%m-%dT%H:%M:%S.%f")[:-3] + "Z"
+
+
+def bars_to_arrays(bars: List[Bar]) -> Dict[str, Any]:
+    """
+    Convert bars to numpy column arrays.
+
+    Returns:
+        Dict with 'timestamp' (datetime64[ms], UTC), 'open', 'high', 'low',
+        'close' (float64) and 'volume' (int64) arrays
+    """
+    try:
+        import numpy as np
+    except ImportError as e:
+        raise ImportError("numpy is required for array output (pip install numpy)") from e
+    return {
+        "timestamp": np.array([int(b.timestamp.timestamp() * 1000) for b in bars], dtype='datetime64[ms]'),
+        "open": np.array([b.open for b in bars], dtype=np.float64),
+        "high": np.array([b.high for b in bars], dtype=np.float64),
+        "low": np.array([b.low for b in bars], dtype=np.float64),
+        "close": np.array([b.close for b in bars], dtype=np.float64),
+        "volume": np.array([b.volume for b in bars], dtype=np.int64),
+    }
+
+
+class HistoryClient:
+    """
+    Fetches historical bars over arbitrary date ranges.
+
+    Usage:
+        client = HistoryClient(fetch=bot._retrieve_bars, contract_resolver=bot._get_contract_id)
+        bars = await client.fetch_bars('MNQ', '1m', start, end)
+        arrays = await client.fetch_arrays('MNQ', '1m', start, end)
+    """
+
+    def __init__(self, fetch: Callable[[Dict], Any],
+                 contract_resolver: Optional[Callable[[str], str]] = None,
+                 max_bars_per_request: Optional[int] = None,
+                 max_retries: Optional[int] = None,
+                 min_request_interval: Optional[float] = None,
+                 retry_budget: Optional[RetryBudget] = None,
+                 backoff_base: float = 0.5):
+        """
+        Initialize history client.
+
+        Args:
+            fetch: Sends one retrieveBars request body and returns the parsed response
+                   (blocking; run in a worker thread)
+            contract_resolver: Maps symbol to contract ID (symbols are used as-is if absent)
+            max_bars_per_request: Bar limit per call (env: HISTORY_MAX_BARS_PER_REQUEST, default 20000)
+            max_retries: Retries per request (env: HISTORY_MAX_RETRIES, default 3)
+            min_request_interval: Seconds between requests (env: HISTORY_MIN_REQUEST_INTERVAL, default 0.25)
+            retry_budget: Budget retries draw from (default: session-wide budget)
+            backoff_base: First retry delay in seconds (doubles per attempt)
+        """
+        self.fetch = fetch
+        self.contract_resolver = contract_resolver
+        self.max_bars_per_request = int(max_bars_per_request if max_bars_per_request is not None
+                                        else os.getenv('HISTORY_MAX_BARS_PER_REQUEST', '20000'))
+        self.max_retries = int(max_retries if max_retries is not None else os.getenv('HISTORY_MAX_RETRIES', '3'))
+        self.min_request_interval = float(min_request_interval if min_request_interval is not None
+                                          else os.getenv('HISTORY_MIN_REQUEST_INTERVAL', '0.25'))
+        self.retry_budget = retry_budget
+        self.backoff_base = backoff_base
+        self._last_request = 0.0
+        self._request_lock = asyncio.Lock()
+        self.stats = {"requests": 0, "retries": 0, "pages": 0, "bars": 0, "failures": 0}
+
+    async def fetch_bars(self, symbol: str, timeframe: str, start: datetime,
+                         end: Optional[datetime] = None, include_partial: bool = False) -> List[Bar]:
+        """
+        Download all bars in [start, end].
+
+        Args:
+            symbol: Trading symbol (e.g. 'MNQ')
+            timeframe: Bar timeframe (e.g. '1m', '1h')
+            start: Range start (naive datetimes are treated as UTC)
+            end: Range end (default: now)
+            include_partial: Include the still-forming last bar
+
+        Returns:
+            List[Bar]: Bars sorted oldest first, one per timestamp
+
+        Raises:
+            HistoryFetchError: If a request fails after all retries
+        """
+        unit, unit_number, bar_seconds = parse_timeframe(timeframe)
+        start = start if start.tzinfo else start.replace(tzinfo=timezone.utc)
+        end = end or datetime.now(timezone.utc)
+        end = end if end.tzinfo else end.replace(tzinfo=timezone.utc)
+        if end <= start:
+            return []
+        contract_id = self.contract_resolver(symbol) if self.contract_resolver else symbol
+        symbol_key = symbol.upper()
+
+        chunk = timedelta(seconds=bar_seconds * self.max_bars_per_request)
+        collected: Dict[datetime, Bar] = {}
+        chunk_start = start
+        while chunk_start < end:
+            chunk_end = min(chunk_start + chunk, end)
+            for bar in await self._fetch_chunk(contract_id, symbol_key, timeframe, unit, unit_number,
+                                               chunk_start, chunk_end, include_partial):
+                if start <= bar.timestamp <= end:
+                    collected[bar.timestamp] = bar
+            chunk_start = chunk_end
+
+        bars = [collected[ts] for ts in sorted(collected)]
+        self.stats["bars"] += len(bars)
+        logger.info(f"📥 Fetched {len(bars)} {timeframe} bars for {symbol_key} ({start} → {end})")
+        return bars
+
+    async def fetch_arrays(self, symbol: str, timeframe: str, start: datetime,
+                           end: Optional[datetime] = None, include_partial: bool = False) -> Dict[str, Any]:
+        """Like fetch_bars, returned as numpy column arrays (see bars_to_arrays)."""
+        return bars_to_arrays(await self.fetch_bars(symbol, timeframe, start, end, include_partial))
+
+    async def _fetch_chunk(self, contract_id: str, symbol: str, timeframe: str, unit: int, unit_number: int,
+                           chunk_start: datetime, chunk_end: datetime, include_partial: bool) -> List[Bar]:
+        """Fetch one date chunk, paginating backwards while responses come back full."""
+        bars: List[Bar] = []
+        page_end = chunk_end
+        while True:
+            payload = {
+                "contractId": contract_id,
+                "live": False,
+                "startTime": _format_time(chunk_start),
+                "endTime": _format_time(page_end),
+                "unit": unit,
+                "unitNumber": unit_number,
+                "limit": self.max_bars_per_request,
+                "includePartialBar": include_partial,
+            }
+            raw_bars = [bar.raw for bar in (await self._request(payload)).bars]
+            self.stats["pages"] += 1
+            page = [bar for bar in (parse_bar(raw, symbol, timeframe) for raw in raw_bars) if bar is not None]
+            bars.extend(page)
+            if len(raw_bars) < self.max_bars_per_request or not page:
+                return bars
+            earliest = min(bar.timestamp for bar in page)
+            if earliest <= chunk_start or earliest >= page_end:
+                return bars
+            page_end = earliest - timedelta(milliseconds=1)
+
+    @staticmethod
+    def _parse(response: Any) -> Tuple[Optional[BarsResponse], Optional[str]]:
+        """Parsed response, or the error to retry on (transport error or broker errorCode)."""
+        if isinstance(response, dict) and "error" in response:
+            return None, str(response["error"])
+        try:
+            parsed = BarsResponse.parse(response)
+        except ApiSchemaError as e:
+            raise HistoryFetchError(str(e)) from e
+        if not parsed.success or parsed.error_code:
+            return None, f"errorCode {parsed.error_code}: {parsed.error_message or 'no message'}"
+        return parsed, None
+
+    async def _request(self, payload: Dict) -> BarsResponse:
+        """
+        Send one request with pacing and budgeted retries.
+
+        Raises:
+            HistoryFetchError: Retries exhausted, or a response that does not match the schema
+        """
+        attempt = 0
+        while True:
+            async with self._request_lock:
+                wait = self.min_request_interval - (time.monotonic() - self._last_request)
+                if wait > 0:
+                    await asyncio.sleep(wait)
+                self._last_request = time.monotonic()
+            self.stats["requests"] += 1
+            try:
+                response = await asyncio.to_thread(self.fetch, payload)
+            except Exception as e:
+                response, error = None, str(e)
+            else:
+                parsed, error = self._parse(response)
+                if parsed is not None:
+                    return parsed
+
+            budget = self.retry_budget or get_retry_budget()
+            if attempt >= self.max_retries or not budget.try_acquire("history"):
+                self.stats["failures"] += 1
+                raise HistoryFetchError(f"History request failed after {attempt} retries: {error}")
+            attempt += 1
+            self.stats["retries"] += 1
+            delay = self.backoff_base * (2 ** (attempt - 1))
+            if "429" in error:
+                delay *= 4  # Rate limited: back off harder
+            logger.warning(f"⚠️  History request failed ({error}) - retry {attempt}/{self.max_retries} in {delay:.1f}s")
+            await asyncio.sleep(delay)
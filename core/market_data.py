@@ -0,0 +1,183 @@
+"""
+Market data utilities for completed bar series.
+
+Resampling rolls fine bars (usually 1m) up into coarser timeframes without
+pandas: one pass over the input, OHLCV merged per bucket.
+
+Alignment:
+- Without a SessionCalendar, intraday buckets are aligned to the epoch (UTC),
+  daily buckets to UTC midnight and weekly buckets to Monday 00:00 UTC
+- With a SessionCalendar, intraday buckets are anchored to the Globex session
+  open and the last bucket of a session ends at the session close, matching
+  BarAggregator's live bars. Daily bars are one Globex session (stamped with
+  the session open, e.g. 17:00 CT / 18:00 ET, closing 16:00 CT / 17:00 ET),
+  multi-day and weekly bars group sessions by trading date
+- Bars outside any session (maintenance, weekend) fall back to epoch alignment
+
+Supported timeframes: '<N>s', '<N>m', '<N>h', '<N>d', '<N>w'. The target
+must be a whole multiple of the source timeframe.
+"""
+
+import logging
+from datetime import date, datetime, timedelta, timezone
+from typing import Any, Dict, Iterable, List, Optional, Tuple
+
+from core.bar_aggregator import Bar, bar_start_time
+from core.session_calendar import SessionCalendar
+
+logger = logging.getLogger(__name__)
+
+_UNIT_SECONDS = {'s': 1, 'm': 60, 'h': 3600, 'd': 86400, 'w': 604800}
+_DAY = 86400
+_EPOCH_MONDAY = date(1970, 1, 5)
+
+
+def resample_timeframe_seconds(timeframe: str) -> int:
+    """
+    Length of a resampling timeframe in seconds.
+
+    Raises:
+        ValueError: Unknown or activity-based (volume/tick/dollar) timeframe
+    """
+    tf = timeframe.strip().lower()
+    try:
+        seconds = int(tf[:-1]) * _UNIT_SECONDS[tf[-1]]
+    except (KeyError, ValueError, IndexError):
+        raise ValueError(f"Cannot resample timeframe '{timeframe}'. Use <N>s, <N>m, <N>h, <N>d or <N>w")
+    if seconds <= 0:
+        raise ValueError(f"Timeframe '{timeframe}' must be positive")
+    return seconds
+
+
+def _utc(ts: datetime) -> datetime:
+    return ts if ts.tzinfo else ts.replace(tzinfo=timezone.utc)
+
+
+def _date_group(day: date, seconds: int) -> Tuple[date, date]:
+    """First and last calendar date of the N-day / N-week group containing a date."""
+    span = seconds // _DAY
+    if seconds % _UNIT_SECONDS['w'] == 0:
+        first = _EPOCH_MONDAY + timedelta(days=((day - _EPOCH_MONDAY).days // span) * span)
+    else:
+        first = date.fromordinal(((day.toordinal() - 1) // span) * span + 1)
+    return first, first + timedelta(days=span - 1)
+
+
+def _bucket(symbol: str, ts: datetime, timeframe: str, seconds: int,
+            calendar: Optional[SessionCalendar]) -> Tuple[Any, datetime, datetime]:
+    """(key, start, end) of the target bar containing a source bar timestamp."""
+    day = calendar.trading_date(symbol, ts) if calendar is not None else None
+    if day is not None:
+        session_open, session_close = calendar.bounds_for_date(symbol, day)
+        if seconds < _DAY:
+            elapsed = int((ts - session_open).total_seconds())
+            start = session_open + timedelta(seconds=(elapsed // seconds) * seconds)
+            return start, start, min(start + timedelta(seconds=seconds), session_close)
+        if seconds == _DAY:
+            return day, session_open, session_close
+        first, last = _date_group(day, seconds)
+        # Stamped with the group's first session that actually traded (set by the first bar)
+        return (first, last), session_open, calendar.bounds_for_date(symbol, last)[1]
+    if seconds < _DAY:
+        start = bar_start_time(ts, timeframe)
+        return start, start, start + timedelta(seconds=seconds)
+    first, last = _date_group(ts.astimezone(timezone.utc).date(), seconds)
+    start = datetime(first.year, first.month, first.day, tzinfo=timezone.utc)
+    return start, start, start + timedelta(seconds=seconds)
+
+
+def resample_bars(bars: Iterable[Bar], from_tf: str, to_tf: str,
+                  session_calendar: Optional[SessionCalendar] = None,
+                  include_partial: bool = True) -> List[Bar]:
+    """
+    Roll bars up into a coarser timeframe.
+
+    Args:
+        bars: Source bars (any order; several symbols are resampled independently)
+        from_tf: Source timeframe (e.g. '1m')
+        to_tf: Target timeframe (e.g. '5m', '1h', '1d')
+        session_calendar: Session-aware alignment (see module docstring); epoch-aligned if None
+        include_partial: Keep each symbol's last bucket even if its period is not fully covered
+
+    Returns:
+        List[Bar]: Resampled bars ordered by symbol, then time
+
+    Raises:
+        ValueError: Unsupported timeframes, or to_tf not a multiple of from_tf
+    """
+    from_seconds = resample_timeframe_seconds(from_tf)
+    to_seconds = resample_timeframe_seconds(to_tf)
+    if to_seconds < from_seconds or to_seconds % from_seconds:
+        raise ValueError(f"Cannot resample {from_tf} bars to {to_tf}: target must be a multiple of the source")
+    to_tf = to_tf.strip().lower()
+    intraday_rth = session_calendar is not None and to_seconds < _DAY
+    source_span = timedelta(seconds=from_seconds)
+
+    result: List[Bar] = []
+    current: Optional[Bar] = None
+    current_key: Any = None
+    current_end: Optional[datetime] = None
+    last_source_end: Optional[datetime] = None
+    rth_values: set = set()
+
+    def finish(complete: bool):
+        if current is None or not (complete or include_partial or last_source_end >= current_end):
+            return
+        if not intraday_rth:
+            current.is_rth = rth_values.pop() if len(rth_values) == 1 else None
+        result.append(current)
+
+    for bar in sorted(bars, key=lambda b: (b.symbol, _utc(b.timestamp))):
+        ts = _utc(bar.timestamp)
+        key, start, end = _bucket(bar.symbol, ts, to_tf, to_seconds, session_calendar)
+        if current is None or bar.symbol != current.symbol or key != current_key:
+            finish(current is not None and bar.symbol == current.symbol)
+            current = Bar(bar.symbol, to_tf, start, bar.open, bar.high, bar.low, bar.close,
+                          bar.volume, bar.tick_count)
+            if intraday_rth:
+                current.is_rth = session_calendar.is_rth(bar.symbol, start)
+            current_key, current_end = key, end
+            rth_values = {bar.is_rth}
+        else:
+            current.high = max(current.high, bar.high)
+            current.low = min(current.low, bar.low)
+            current.close = bar.close
+            current.volume += bar.volume
+            current.tick_count += bar.tick_count
+            rth_values.add(bar.is_rth)
+        last_source_end = ts + source_span
+    finish(False)
+    return result
+
+
+def resample_bar_dicts(bars: List[Dict[str, Any]], from_tf: str, to_tf: str, symbol: str = '',
+                       session_calendar: Optional[SessionCalendar] = None) -> List[Dict[str, Any]]:
+    """
+    resample_bars() for API-style bar dicts ('timestamp' or 'time', open, high, low, close, volume).
+
+    Bars with a missing or unparseable timestamp are skipped.
+
+    Returns:
+        List[Dict]: Bars with 'timestamp'/'time' (ISO), open, high, low, close, volume
+    """
+    parsed: List[Bar] = []
+    for raw in bars:
+        ts = raw.get('timestamp') or raw.get('time')
+        if isinstance(ts, str):
+            try:
+                ts = datetime.fromisoformat(ts.replace('Z', '+00:00'))
+            except ValueError:
+                continue
+        elif not isinstance(ts, datetime):
+            continue
+        open_price = raw.get('open') or 0
+        parsed.append(Bar(symbol, from_tf, _utc(ts), open_price,
+                          raw.get('high') if raw.get('high') is not None else open_price,
+                          raw.get('low') if raw.get('low') is not None else open_price,
+                          raw.get('close') or 0, raw.get('volume') or 0))
+    resampled = []
+    for bar in resample_bars(parsed, from_tf, to_tf, session_calendar):
+        stamp = bar.timestamp.isoformat()
+        resampled.append({'timestamp': stamp, 'time': stamp, 'open': bar.open, 'high': bar.high,
+                          'low': bar.low, 'close': bar.close, 'volume': bar.volume})
+    return resampled
@@ -0,0 +1,310 @@
+"""
+Typed market events.
+
+Consumers of market data (strategies, WebSocket broadcasters, recorders) can
+branch on event class with isinstance or structural pattern matching instead
+of inspecting dict keys:
+
+    match event:
+        case Trade(symbol=sym, price=px, size=qty):
+            ...
+        case Quote(bid=bid, ask=ask):
+            ...
+        case BarClosed(bar=bar) if bar.timeframe == '5m':
+            ...
+
+Features:
+- MarketEvent base class with Trade, Quote, DepthUpdate, BarClosed, GapRepaired, SessionEvent, Imbalance variants
+- Cheap conversion from TopStepX gateway payloads and internal Bar objects
+- Round-trip to/from JSON-friendly dicts keyed by "type"
+"""
+
+import logging
+from dataclasses import dataclass
+from datetime import datetime, timezone
+from enum import Enum
+from typing import Any, ClassVar, Dict, Optional, Tuple, Type
+
+from core.bar_aggregator import Bar, BarGap
+
+logger = logging.getLogger(__name__)
+
+PriceLevel = Tuple[float, int]  # (price, size)
+
+
+def parse_timestamp(value: Any) -> datetime:
+    """Parse an ISO string/datetime, defaulting to now (UTC)."""
+    if isinstance(value, datetime):
+        return value
+    if isinstance(value, str) and value:
+        try:
+            return datetime.fromisoformat(value.replace('Z', '+00:00'))
+        except ValueError:
+            pass
+    return datetime.now(timezone.utc)
+
+
+def _optional_float(value: Any) -> Optional[float]:
+    return float(value) if value is not None else None
+
+
+def _optional_int(value: Any) -> Optional[int]:
+    return int(value) if value is not None else None
+
+
+@dataclass(frozen=True, slots=True)
+class MarketEvent:
+    """Base class for all market events."""
+    symbol: str
+    timestamp: datetime
+
+    type: ClassVar[str] = "market_event"
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary (inverse of market_event_from_dict)."""
+        data = {"type": self.type, "symbol": self.symbol, "timestamp": self.timestamp.isoformat()}
+        data.update(self._payload())
+        return data
+
+    def _payload(self) -> Dict[str, Any]:
+        return {}
+
+
+@dataclass(frozen=True, slots=True)
+class Trade(MarketEvent):
+    """Executed trade print."""
+    price: float
+    size: int = 0
+    side: Optional[str] = None  # "buy" / "sell" aggressor, if known
+
+    type: ClassVar[str] = "trade"
+
+    @classmethod
+    def from_gateway(cls, symbol: str, data: Dict[str, Any], timestamp: Optional[datetime] = None) -> 'Trade':
+        """Build from a GatewayTrade payload (type: 0 = buy, 1 = sell)."""
+        side_code = data.get("type")
+        side = {0: "buy", 1: "sell"}.get(side_code) if side_code is not None else None
+        return cls(
+            symbol=symbol,
+            timestamp=timestamp or parse_timestamp(data.get("timestamp")),
+            price=float(data["price"]),
+            size=int(data.get("volume") or 0),
+            side=side,
+        )
+
+    def _payload(self) -> Dict[str, Any]:
+        return {"price": self.price, "size": self.size, "side": self.side}
+
+
+@dataclass(frozen=True, slots=True)
+class Quote(MarketEvent):
+    """Top-of-book quote update (fields are None when not in the update)."""
+    bid: Optional[float] = None
+    ask: Optional[float] = None
+    last: Optional[float] = None
+    bid_size: Optional[int] = None
+    ask_size: Optional[int] = None
+    volume: Optional[int] = None
+
+    type: ClassVar[str] = "quote"
+
+    @property
+    def mid(self) -> Optional[float]:
+        """Mid price when both sides are present."""
+        if self.bid is None or self.ask is None:
+            return None
+        return (self.bid + self.ask) / 2
+
+    @classmethod
+    def from_gateway(cls, symbol: str, data: Dict[str, Any], timestamp: Optional[datetime] = None) -> 'Quote':
+        """Build from a GatewayQuote payload (bestBid/bestAsk/lastPrice/volume)."""
+        return cls(
+            symbol=symbol,
+            timestamp=timestamp or parse_timestamp(data.get("timestamp") or data.get("lastUpdated")),
+            bid=_optional_float(data.get("bestBid")),
+            ask=_optional_float(data.get("bestAsk")),
+            last=_optional_float(data.get("lastPrice")),
+            bid_size=_optional_int(data.get("bestBidSize")),
+            ask_size=_optional_int(data.get("bestAskSize")),
+            volume=_optional_int(data.get("volume")),
+        )
+
+    def _payload(self) -> Dict[str, Any]:
+        return {"bid": self.bid, "ask": self.ask, "last": self.last,
+                "bid_size": self.bid_size, "ask_size": self.ask_size, "volume": self.volume}
+
+
+def _parse_levels(levels: Any) -> Tuple[PriceLevel, ...]:
+    """Normalize depth levels given as dicts or [price, size] pairs."""
+    parsed = []
+    for level in levels or ():
+        if isinstance(level, dict):
+            price = level.get("price")
+            size = level.get("volume", level.get("size", 0))
+        elif isinstance(level, (list, tuple)) and len(level) >= 2:
+            price, size = level[0], level[1]
+        else:
+            continue
+        if price is not None:
+            parsed.append((float(price), int(size or 0)))
+    return tuple(parsed)
+
+
+@dataclass(frozen=True, slots=True)
+class DepthUpdate(MarketEvent):
+    """Order book depth snapshot or update."""
+    bids: Tuple[PriceLevel, ...] = ()
+    asks: Tuple[PriceLevel, ...] = ()
+
+    type: ClassVar[str] = "depth_update"
+
+    @classmethod
+    def from_gateway(cls, symbol: str, data: Dict[str, Any], timestamp: Optional[datetime] = None) -> 'DepthUpdate':
+        """Build from a depth payload ({bids, asks} or {orderBook: {bids, asks}})."""
+        book = data.get("orderBook") or data
+        return cls(
+            symbol=symbol,
+            timestamp=timestamp or parse_timestamp(data.get("timestamp")),
+            bids=_parse_levels(book.get("bids")),
+            asks=_parse_levels(book.get("asks")),
+        )
+
+    def _payload(self) -> Dict[str, Any]:
+        return {"bids": [list(level) for level in self.bids], "asks": [list(level) for level in self.asks]}
+
+
+@dataclass(frozen=True, slots=True)
+class BarClosed(MarketEvent):
+    """A bar completed by the bar aggregator."""
+    bar: Bar
+
+    type: ClassVar[str] = "bar_closed"
+
+    @property
+    def timeframe(self) -> str:
+        return self.bar.timeframe
+
+    @classmethod
+    def from_bar(cls, bar: Bar) -> 'BarClosed':
+        """Wrap a completed Bar (no copy)."""
+        return cls(symbol=bar.symbol, timestamp=bar.timestamp, bar=bar)
+
+    def _payload(self) -> Dict[str, Any]:
+        return {
+            "timeframe": self.bar.timeframe,
+            "bar": {
+                "open": self.bar.open,
+                "high": self.bar.high,
+                "low": self.bar.low,
+                "close": self.bar.close,
+                "volume": self.bar.volume,
+                "tick_count": self.bar.tick_count,
+                "is_rth": self.bar.is_rth,
+            },
+        }
+
+
+@dataclass(frozen=True, slots=True)
+class GapRepaired(MarketEvent):
+    """Missing bars [timestamp, gap_end) were backfilled from REST history."""
+    timeframe: str
+    gap_end: datetime
+    missing: int = 0
+    filled: int = 0
+    bars: Tuple[Bar, ...] = ()  # Not included in to_dict
+
+    type: ClassVar[str] = "gap_repaired"
+
+    @classmethod
+    def from_gap(cls, gap: BarGap) -> 'GapRepaired':
+        """Build from a repaired BarGap."""
+        return cls(symbol=gap.symbol, timestamp=gap.start, timeframe=gap.timeframe, gap_end=gap.end,
+                   missing=gap.missing, filled=gap.filled, bars=tuple(gap.bars))
+
+    def _payload(self) -> Dict[str, Any]:
+        return {"timeframe": self.timeframe, "gap_end": self.gap_end.isoformat(),
+                "missing": self.missing, "filled": self.filled}
+
+
+class SessionEventKind(Enum):
+    """Trading session transitions."""
+    OPEN = "open"
+    CLOSE = "close"
+    HALT = "halt"
+    RESUME = "resume"
+    MAINTENANCE = "maintenance"
+
+
+@dataclass(frozen=True, slots=True)
+class SessionEvent(MarketEvent):
+    """Session open/close/halt transition for a symbol."""
+    kind: SessionEventKind
+    session: Optional[str] = None  # e.g., "RTH", "ETH"
+
+    type: ClassVar[str] = "session_event"
+
+    def _payload(self) -> Dict[str, Any]:
+        return {"kind": self.kind.value, "session": self.session}
+
+
+@dataclass(frozen=True, slots=True)
+class Imbalance(MarketEvent):
+    """Stacked bid/ask imbalance in a completed footprint bar (timestamp is the bar start)."""
+    timeframe: str
+    side: str  # "buy" (ask-side imbalances) / "sell" (bid-side imbalances)
+    price_low: float
+    price_high: float
+    levels: int
+    min_ratio: float
+
+    type: ClassVar[str] = "imbalance"
+
+    def _payload(self) -> Dict[str, Any]:
+        return {"timeframe": self.timeframe, "side": self.side, "price_low": self.price_low,
+                "price_high": self.price_high, "levels": self.levels, "min_ratio": self.min_ratio}
+
+
+EVENT_TYPES: Dict[str, Type[MarketEvent]] = {
+    cls.type: cls for cls in (Trade, Quote, DepthUpdate, BarClosed, GapRepaired, SessionEvent, Imbalance)
+}
+
+
+def market_event_from_dict(data: Dict[str, Any]) -> MarketEvent:
+    """
+    Rebuild a typed event from its dict form (as produced by MarketEvent.to_dict).
+
+    Raises:
+        ValueError: If the "type" key is missing or unknown
+    """
+    event_type = data.get("type")
+    cls = EVENT_TYPES.get(event_type)
+    if cls is None:
+        raise ValueError(f"Unknown market event type '{event_type}'. Valid types: {sorted(EVENT_TYPES)}")
+
+    symbol = str(data.get("symbol", "")).upper()
+    timestamp = parse_timestamp(data.get("timestamp"))
+    if cls is Trade:
+        return Trade(symbol, timestamp, float(data["price"]), int(data.get("size") or 0), data.get("side"))
+    if cls is Quote:
+        return Quote(symbol, timestamp,
+                     bid=_optional_float(data.get("bid")), ask=_optional_float(data.get("ask")),
+                     last=_optional_float(data.get("last")),
+                     bid_size=_optional_int(data.get("bid_size")), ask_size=_optional_int(data.get("ask_size")),
+                     volume=_optional_int(data.get("volume")))
+    if cls is DepthUpdate:
+        return DepthUpdate(symbol, timestamp, _parse_levels(data.get("bids")), _parse_levels(data.get("asks")))
+    if cls is BarClosed:
+        bar_data = data.get("bar") or {}
+        bar = Bar(symbol=symbol, timeframe=data.get("timeframe", ""), timestamp=timestamp,
+                  open=float(bar_data["open"]), high=float(bar_data["high"]),
+                  low=float(bar_data["low"]), close=float(bar_data["close"]),
+                  volume=int(bar_data.get("volume") or 0), tick_count=int(bar_data.get("tick_count") or 0),
+                  is_rth=bar_data.get("is_rth"))
+        return BarClosed(symbol, timestamp, bar)
+    if cls is GapRepaired:
+        return GapRepaired(symbol, timestamp, data.get("timeframe", ""), parse_timestamp(data.get("gap_end")),
+                           int(data.get("missing") or 0), int(data.get("filled") or 0))
+    if cls is Imbalance:
+        return Imbalance(symbol, timestamp, data.get("timeframe", ""), data["side"], float(data["price_low"]),
+                         float(data["price_high"]), int(data.get("levels") or 0), float(data.get("min_ratio") or 0))
+    return SessionEvent(symbol, timestamp, SessionEventKind(data["kind"]), data.get("session"))
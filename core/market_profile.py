@@ -0,0 +1,330 @@
+"""
+Market Profile (TPO)
+
+Builds time-price-opportunity profiles per symbol and session: the session is
+split into fixed periods (default 30 minutes) lettered A, B, C, ... and every
+price row traded in a period gets that period's letter. From the letters:
+- POC: the row with the most TPOs (ties go to the row nearest the range midpoint)
+- Value area: rows holding 70% of TPOs, expanded outward from the POC
+- Initial balance: range of the first two periods
+
+Profiles can be built two ways:
+- Streaming: MarketProfileBuilder.add_trade() / add_bar() (bar_close_callback
+  compatible), with profile() returning the forming session at any time
+- Batch: build_market_profiles() over a list of bars, Trade events or tick dicts
+
+Symbols are normalized to their root like the volume profile, and prices are
+bucketed on the instrument's tick grid (optionally several ticks per row).
+
+Configuration:
+- MP_PERIOD_MINUTES: TPO period length (default 30)
+- MP_VALUE_AREA_PCT: Share of TPOs in the value area (default 0.7)
+- MP_ROW_TICKS: Ticks per price row (default 1)
+- MP_SESSION_ANCHOR: 'globex', 'rth', 'midnight' or 'HH:MM' (default 'globex')
+- MP_TIMEZONE: Exchange timezone (default 'US/Eastern')
+- MP_HISTORY_SESSIONS: Completed sessions kept per symbol (default 5)
+"""
+
+import logging
+import os
+import threading
+from collections import defaultdict, deque
+from dataclasses import dataclass, field
+from datetime import datetime, timezone
+from typing import Any, Deque, Dict, Iterable, List, Optional, Set, Tuple
+from zoneinfo import ZoneInfo
+
+from core.market_events import Trade, parse_timestamp
+from core.volume_profile import normalize_symbol
+from core.vwap import parse_anchor, session_anchor
+
+logger = logging.getLogger(__name__)
+
+TPO_LETTERS = 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz'
+
+
+def tpo_letter(period: int) -> str:
+    """Letter for a period index (wraps after 52 periods)."""
+    return TPO_LETTERS[period % len(TPO_LETTERS)]
+
+
+@dataclass
+class MarketProfile:
+    """TPO profile of one session."""
+    symbol: str
+    session_start: datetime
+    period_minutes: int
+    row_size: float
+    tpos: Dict[float, str]  # row price -> letters, ascending by price
+    poc: float
+    value_area_low: float
+    value_area_high: float
+    high: float
+    low: float
+    initial_balance_high: float
+    initial_balance_low: float
+    periods: int
+    volume: int = 0
+
+    @property
+    def total_tpos(self) -> int:
+        return sum(len(letters) for letters in self.tpos.values())
+
+    def single_prints(self) -> List[float]:
+        """Rows touched by only one period (excluding the session extremes)."""
+        return [price for price, letters in self.tpos.items()
+                if len(letters) == 1 and self.low < price < self.high]
+
+    def render(self) -> str:
+        """Classic text profile, highest price first."""
+        return '\n'.join(f"{price:>12g} {letters}" for price, letters in reversed(list(self.tpos.items())))
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "symbol": self.symbol,
+            "session_start": self.session_start.isoformat(),
+            "period_minutes": self.period_minutes,
+            "row_size": self.row_size,
+            "tpos": {str(price): letters for price, letters in self.tpos.items()},
+            "poc": self.poc,
+            "value_area_low": self.value_area_low,
+            "value_area_high": self.value_area_high,
+            "high": self.high,
+            "low": self.low,
+            "initial_balance_high": self.initial_balance_high,
+            "initial_balance_low": self.initial_balance_low,
+            "periods": self.periods,
+            "total_tpos": self.total_tpos,
+            "volume": self.volume,
+        }
+
+
+@dataclass
+class _SessionTPOs:
+    session_start: datetime
+    rows: Dict[int, Set[int]] = field(default_factory=lambda: defaultdict(set))  # row bucket -> periods
+    last_period: int = 0
+    volume: int = 0
+
+
+@dataclass
+class _SymbolState:
+    current: _SessionTPOs
+    history: Deque[_SessionTPOs]
+
+
+class MarketProfileBuilder:
+    """
+    Streaming TPO profiles per symbol.
+
+    Usage:
+        builder = MarketProfileBuilder(period_minutes=30, tick_sizes={'MNQ': 0.25}, row_ticks=4)
+        builder.add_trade('CON.F.US.MNQ.Z25', 15000.25, 3, ts)
+        profile = builder.profile('MNQ')
+        profile.poc, profile.value_area_low, profile.value_area_high
+    """
+
+    def __init__(self, period_minutes: Optional[int] = None, value_area_pct: Optional[float] = None,
+                 tick_sizes: Optional[Dict[str, float]] = None, default_tick_size: float = 0.25,
+                 row_ticks: Optional[int] = None, anchor: Optional[str] = None, tz: Optional[str] = None,
+                 history_sessions: Optional[int] = None):
+        """
+        Initialize profile builder.
+
+        Args:
+            period_minutes: TPO period length (env: MP_PERIOD_MINUTES, default 30)
+            value_area_pct: Share of TPOs in the value area, 0-1 (env: MP_VALUE_AREA_PCT, default 0.7)
+            tick_sizes: {root symbol: tick size}
+            default_tick_size: Tick size for symbols not in tick_sizes
+            row_ticks: Ticks per price row (env: MP_ROW_TICKS, default 1)
+            anchor: Session anchor 'globex', 'rth', 'midnight' or 'HH:MM' (env: MP_SESSION_ANCHOR, default 'globex')
+            tz: Exchange timezone (env: MP_TIMEZONE, default 'US/Eastern')
+            history_sessions: Completed sessions kept per symbol (env: MP_HISTORY_SESSIONS, default 5)
+        """
+        self.period_minutes = period_minutes if period_minutes is not None else int(os.getenv('MP_PERIOD_MINUTES', '30'))
+        self.value_area_pct = (value_area_pct if value_area_pct is not None
+                               else float(os.getenv('MP_VALUE_AREA_PCT', '0.7')))
+        self.row_ticks = row_ticks if row_ticks is not None else int(os.getenv('MP_ROW_TICKS', '1'))
+        if self.period_minutes <= 0:
+            raise ValueError(f"period_minutes must be > 0, got {self.period_minutes}")
+        if not 0 < self.value_area_pct <= 1:
+            raise ValueError(f"value_area_pct must be in (0, 1], got {self.value_area_pct}")
+        if self.row_ticks <= 0:
+            raise ValueError(f"row_ticks must be > 0, got {self.row_ticks}")
+        self.tick_sizes = {normalize_symbol(k): float(v) for k, v in (tick_sizes or {}).items()}
+        self.default_tick_size = default_tick_size
+        self.anchor_hour, self.anchor_minute = parse_anchor(anchor or os.getenv('MP_SESSION_ANCHOR', 'globex'))
+        self.tz = ZoneInfo(tz or os.getenv('MP_TIMEZONE', 'US/Eastern'))
+        self.history_sessions = (history_sessions if history_sessions is not None
+                                 else int(os.getenv('MP_HISTORY_SESSIONS', '5')))
+        self._states: Dict[str, _SymbolState] = {}
+        self._lock = threading.Lock()
+
+    def _row_size(self, root: str) -> float:
+        return self.tick_sizes.get(root, self.default_tick_size) * self.row_ticks
+
+    def _row(self, root: str, price: float) -> int:
+        return int(round(price / self.tick_sizes.get(root, self.default_tick_size))) // self.row_ticks
+
+    def _price(self, root: str, row: int) -> float:
+        return round(row * self._row_size(root), 10)
+
+    def _session(self, root: str, timestamp: datetime) -> Optional[Tuple[_SessionTPOs, int]]:
+        """Session profile and period index for a timestamp, rolling sessions (caller holds _lock)."""
+        start = session_anchor(timestamp, self.anchor_hour, self.anchor_minute, self.tz)
+        state = self._states.get(root)
+        if state is None:
+            state = _SymbolState(current=_SessionTPOs(start), history=deque(maxlen=max(0, self.history_sessions)))
+            self._states[root] = state
+        elif start > state.current.session_start:
+            if state.current.rows:
+                state.history.append(state.current)
+            state.current = _SessionTPOs(start)
+            logger.debug(f"📊 Market profile for {root} rolled to session {start.isoformat()}")
+        elif start < state.current.session_start:
+            return None  # Late data from a closed session
+        if timestamp.tzinfo is None:
+            timestamp = timestamp.replace(tzinfo=timezone.utc)
+        period = int((timestamp - start).total_seconds() // (self.period_minutes * 60))
+        return state.current, period
+
+    def add_trade(self, symbol: str, price: float, volume: int = 0, timestamp: Optional[datetime] = None) -> None:
+        """
+        Add a trade print.
+
+        Args:
+            symbol: Symbol or contract ID (normalized to its root)
+            price: Trade price
+            volume: Contracts traded (tracked as session volume only)
+            timestamp: Trade time (defaults to now)
+        """
+        if timestamp is None:
+            timestamp = datetime.now(timezone.utc)
+        root = normalize_symbol(symbol)
+        with self._lock:
+            located = self._session(root, timestamp)
+            if located is None:
+                return
+            session, period = located
+            session.rows[self._row(root, price)].add(period)
+            session.last_period = max(session.last_period, period)
+            session.volume += max(0, volume)
+
+    def add_bar(self, bar) -> None:
+        """Add every row from a completed bar's low to high (bar_close_callback compatible)."""
+        root = normalize_symbol(bar.symbol)
+        with self._lock:
+            located = self._session(root, bar.timestamp)
+            if located is None:
+                return
+            session, period = located
+            for row in range(self._row(root, bar.low), self._row(root, bar.high) + 1):
+                session.rows[row].add(period)
+            session.last_period = max(session.last_period, period)
+            session.volume += max(0, bar.volume or 0)
+
+    def _snapshot(self, root: str, session: _SessionTPOs) -> Optional[MarketProfile]:
+        """Build a MarketProfile from session state (caller holds _lock)."""
+        if not session.rows:
+            return None
+        low_row, high_row = min(session.rows), max(session.rows)
+        rows = list(range(low_row, high_row + 1))
+        counts = [len(session.rows.get(r, ())) for r in rows]
+
+        midpoint = (low_row + high_row) / 2
+        poc_index = max(range(len(rows)), key=lambda i: (counts[i], -abs(rows[i] - midpoint), -rows[i]))
+        target = sum(counts) * self.value_area_pct
+        lo = hi = poc_index
+        total = counts[poc_index]
+        while total < target and (lo > 0 or hi < len(rows) - 1):
+            below = counts[lo - 1] if lo > 0 else -1
+            above = counts[hi + 1] if hi < len(rows) - 1 else -1
+            if above >= below:
+                hi += 1
+                total += above
+            else:
+                lo -= 1
+                total += below
+
+        ib_rows = [r for r, periods in session.rows.items() if periods & {0, 1}]
+        return MarketProfile(
+            symbol=root,
+            session_start=session.session_start,
+            period_minutes=self.period_minutes,
+            row_size=self._row_size(root),
+            tpos={self._price(root, r): ''.join(tpo_letter(p) for p in sorted(session.rows[r]))
+                  for r in rows if session.rows.get(r)},
+            poc=self._price(root, rows[poc_index]),
+            value_area_low=self._price(root, rows[lo]),
+            value_area_high=self._price(root, rows[hi]),
+            high=self._price(root, high_row),
+            low=self._price(root, low_row),
+            initial_balance_high=self._price(root, max(ib_rows)) if ib_rows else self._price(root, high_row),
+            initial_balance_low=self._price(root, min(ib_rows)) if ib_rows else self._price(root, low_row),
+            periods=session.last_period + 1,
+            volume=session.volume,
+        )
+
+    def profile(self, symbol: str) -> Optional[MarketProfile]:
+        """Profile of the current (forming) session, or None before any data."""
+        root = normalize_symbol(symbol)
+        with self._lock:
+            state = self._states.get(root)
+            return self._snapshot(root, state.current) if state else None
+
+    def completed_profiles(self, symbol: str) -> List[MarketProfile]:
+        """Profiles of finished sessions, oldest first."""
+        root = normalize_symbol(symbol)
+        with self._lock:
+            state = self._states.get(root)
+            if state is None:
+                return []
+            return [p for p in (self._snapshot(root, s) for s in state.history) if p is not None]
+
+    def symbols(self) -> List[str]:
+        """Root symbols with profile data."""
+        with self._lock:
+            return sorted(self._states)
+
+    def reset(self, symbol: Optional[str] = None):
+        """Clear profile state for a symbol or all symbols."""
+        with self._lock:
+            if symbol is None:
+                self._states.clear()
+            else:
+                self._states.pop(normalize_symbol(symbol), None)
+
+
+def build_market_profiles(data: Iterable[Any], **builder_kwargs) -> List[MarketProfile]:
+    """
+    Batch-build TPO profiles for every session in a data set.
+
+    Args:
+        data: Bars, Trade events, or tick dicts (symbol, price, timestamp, optional size)
+        **builder_kwargs: MarketProfileBuilder options (history_sessions is unlimited here)
+
+    Returns:
+        List[MarketProfile]: One profile per symbol/session, ordered by symbol then session
+    """
+    builder_kwargs.setdefault('history_sessions', 1_000_000)
+    builder = MarketProfileBuilder(**builder_kwargs)
+
+    def when(item) -> datetime:
+        ts = item['timestamp'] if isinstance(item, dict) else item.timestamp
+        ts = parse_timestamp(ts)
+        return ts if ts.tzinfo else ts.replace(tzinfo=timezone.utc)
+
+    for item in sorted(data, key=when):
+        if isinstance(item, Trade):
+            builder.add_trade(item.symbol, item.price, item.size, item.timestamp)
+        elif isinstance(item, dict):
+            builder.add_trade(item['symbol'], float(item['price']), int(item.get('size') or 0), when(item))
+        else:
+            builder.add_bar(item)
+    profiles: List[MarketProfile] = []
+    for symbol in builder.symbols():
+        profiles.extend(builder.completed_profiles(symbol))
+        current = builder.profile(symbol)
+        if current is not None:
+            profiles.append(current)
+    return profiles
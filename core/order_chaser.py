@@ -0,0 +1,362 @@
+"""
+Limit Order Chasing (Pegged Execution)
+
+Keeps an unfilled limit order pegged to the market: every interval the
+order is repriced to the near touch (bid for buys, ask for sells) as the
+market moves away, but never more than a maximum offset from its original
+price. Once the market is beyond that offset the order either stays
+working at the cap or, optionally, is cancelled and replaced by a market
+order for the same size.
+
+Prices come from the live quote stream (on_market_event keeps the latest
+bid/ask per symbol), so a reprice decision needs no REST round trip; the
+only call on the critical path is the modification itself.
+
+Orders are only ever moved toward the market. A fill (on_fill()) or a
+manual cancel (forget()) ends the chase. Modifications that keep failing
+(the order filled in the meantime, or was rejected) end it after
+ORDER_CHASE_MAX_FAILURES attempts; a conversion whose cancel fails never
+places the market order, so a filled order is not doubled.
+
+Usage:
+    chaser = OrderChaser(modify=bot.modify_order, cancel=bot.cancel_order, place_market=bot.place_market_order)
+    bot.add_market_event_listener(chaser.on_market_event)
+    chaser.chase(account_id, order_id, 'MNQ', 'BUY', 2, limit_price=21000.0, max_offset_ticks=4)
+    chaser.on_fill(order_id)                 # From fill processing
+    await chaser.stop()
+
+Configuration:
+- ORDER_CHASE_INTERVAL_MS: Milliseconds between reprices (default 250)
+- ORDER_CHASE_MAX_OFFSET_TICKS: Ticks an order may move from its original price (default 4)
+- ORDER_CHASE_CONVERT_TO_MARKET: Replace the order with a market order past the cap (default false)
+- ORDER_CHASE_MAX_FAILURES: Failed modifications before a chase is abandoned (default 3)
+"""
+
+import asyncio
+import inspect
+import logging
+import os
+import threading
+import time
+from dataclasses import asdict, dataclass
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+from core.market_events import MarketEvent, Quote
+from core.strategy_engine.backtest import TICK_SIZES
+from core.volume_profile import normalize_symbol
+
+logger = logging.getLogger(__name__)
+
+
+@dataclass
+class ChasedOrder:
+    """A limit order being repriced toward the market."""
+    order_id: str
+    account_id: str
+    symbol: str
+    side: str  # BUY / SELL
+    quantity: int
+    start_price: float
+    price: float
+    tick_size: float
+    interval_ms: float
+    max_offset_ticks: int
+    convert_to_market: bool
+    strategy: Optional[str] = None
+    state: str = 'chasing'  # chasing -> filled / cancelled / capped / converted / failed
+    reprices: int = 0
+    failures: int = 0
+    market_order_id: Optional[str] = None
+    error: Optional[str] = None
+    started_at: float = 0.0
+    finished_at: Optional[float] = None
+
+    @property
+    def cap_price(self) -> float:
+        """Farthest price the order may be moved to."""
+        offset = self.max_offset_ticks * self.tick_size
+        return round(self.start_price + offset if self.side == 'BUY' else self.start_price - offset, 10)
+
+    def target(self, touch: float) -> float:
+        """Price to peg to for the touch: the touch rounded onto the tick grid, capped, never backing off."""
+        ticks = round(touch / self.tick_size)
+        touch = round(ticks * self.tick_size, 10)
+        if self.side == 'BUY':
+            return max(self.price, min(touch, self.cap_price))
+        return min(self.price, max(touch, self.cap_price))
+
+    def beyond_cap(self, touch: float) -> bool:
+        """Whether the market has moved past the cap (the order can no longer follow it)."""
+        return touch > self.cap_price if self.side == 'BUY' else touch < self.cap_price
+
+    def to_dict(self) -> Dict[str, Any]:
+        data = asdict(self)
+        data['cap_price'] = self.cap_price
+        return data
+
+
+class OrderChaser:
+    """
+    Reprices working limit orders toward the market up to a maximum offset.
+    """
+
+    def __init__(self, modify: Callable[..., Any], cancel: Callable[..., Any],
+                 place_market: Optional[Callable[..., Any]] = None, interval_ms: Optional[float] = None,
+                 max_offset_ticks: Optional[int] = None, convert_to_market: Optional[bool] = None,
+                 max_failures: Optional[int] = None, tick_sizes: Optional[Dict[str, float]] = None,
+                 history: int = 50):
+        """
+        Initialize chaser.
+
+        Args:
+            modify: modify_order(order_id, new_price=..., account_id=..., order_type=...) (sync or coroutine)
+            cancel: cancel_order(order_id, account_id=...) (sync or coroutine)
+            place_market: place_market_order(symbol, side, quantity, account_id=..., strategy_name=...),
+                needed for conversion to market
+            interval_ms: Default milliseconds between reprices (env: ORDER_CHASE_INTERVAL_MS)
+            max_offset_ticks: Default maximum move from the original price (env: ORDER_CHASE_MAX_OFFSET_TICKS)
+            convert_to_market: Default for conversion past the cap (env: ORDER_CHASE_CONVERT_TO_MARKET)
+            max_failures: Failed modifications before giving up (env: ORDER_CHASE_MAX_FAILURES)
+            tick_sizes: Tick size overrides by root symbol
+            history: Finished chases kept for get_stats()
+        """
+        self.modify = modify
+        self.cancel = cancel
+        self.place_market = place_market
+        self.interval_ms = interval_ms if interval_ms is not None else float(
+            os.getenv('ORDER_CHASE_INTERVAL_MS', '250'))
+        self.max_offset_ticks = max_offset_ticks if max_offset_ticks is not None else int(
+            os.getenv('ORDER_CHASE_MAX_OFFSET_TICKS', '4'))
+        self.convert_to_market = convert_to_market if convert_to_market is not None else os.getenv(
+            'ORDER_CHASE_CONVERT_TO_MARKET', 'false').lower() in ('true', '1', 'yes', 'on')
+        self.max_failures = max_failures if max_failures is not None else int(
+            os.getenv('ORDER_CHASE_MAX_FAILURES', '3'))
+        self._validate(self.interval_ms, self.max_offset_ticks)
+        self.tick_sizes = dict(tick_sizes or {})
+        self.history = history
+        self._quotes: Dict[str, Tuple[Optional[float], Optional[float]]] = {}  # root symbol -> (bid, ask)
+        self._orders: Dict[str, ChasedOrder] = {}  # order ID -> order being chased
+        self._tasks: Dict[str, asyncio.Task] = {}
+        self._lock = threading.Lock()
+        self.finished: List[ChasedOrder] = []
+        self.orders_chased = 0
+        self.reprices = 0
+        self.conversions = 0
+        self.modify_failures = 0
+        self.reprice_latencies_ms: List[float] = []  # Decision -> modification acknowledged, last 100
+
+    @staticmethod
+    def _validate(interval_ms: float, max_offset_ticks: int) -> None:
+        if interval_ms <= 0 or max_offset_ticks < 0:
+            raise ValueError(f"Chase interval must be positive and max offset >= 0 "
+                             f"(got {interval_ms}ms, {max_offset_ticks} ticks)")
+
+    def tick_size(self, symbol: str) -> float:
+        root = normalize_symbol(symbol)
+        return self.tick_sizes.get(root, TICK_SIZES.get(root, 0.25))
+
+    # ---------------------------
+    # Market data
+    # ---------------------------
+    def on_market_event(self, event: MarketEvent) -> None:
+        """Market event listener: keep the latest bid/ask per symbol (fields missing from an update are kept)."""
+        if not isinstance(event, Quote) or (event.bid is None and event.ask is None):
+            return
+        root = normalize_symbol(event.symbol)
+        with self._lock:
+            bid, ask = self._quotes.get(root, (None, None))
+            self._quotes[root] = (event.bid if event.bid is not None else bid,
+                                  event.ask if event.ask is not None else ask)
+
+    def touch(self, symbol: str, side: str) -> Optional[float]:
+        """Near touch a limit order pegs to: bid for buys, ask for sells (None without a quote)."""
+        bid, ask = self._quotes.get(normalize_symbol(symbol), (None, None))
+        return bid if str(side).upper() == 'BUY' else ask
+
+    # ---------------------------
+    # Registration
+    # ---------------------------
+    def chase(self, account_id: str, order_id: str, symbol: str, side: str, quantity: int, limit_price: float,
+              interval_ms: Optional[float] = None, max_offset_ticks: Optional[int] = None,
+              convert_to_market: Optional[bool] = None, strategy: Optional[str] = None) -> ChasedOrder:
+        """
+        Start chasing a working limit order.
+
+        Must be called from the event loop; the chase runs as a task of its own.
+
+        Raises:
+            ValueError: Non-positive interval or negative offset, or conversion without place_market
+        """
+        interval = interval_ms if interval_ms is not None else self.interval_ms
+        offset = max_offset_ticks if max_offset_ticks is not None else self.max_offset_ticks
+        convert = convert_to_market if convert_to_market is not None else self.convert_to_market
+        self._validate(interval, offset)
+        if convert and self.place_market is None:
+            raise ValueError("Conversion to market needs a place_market function")
+        root = normalize_symbol(symbol)
+        order = ChasedOrder(
+            order_id=str(order_id), account_id=str(account_id), symbol=root,
+            side='BUY' if str(side).upper() in ('BUY', 'LONG', '0') else 'SELL', quantity=int(quantity),
+            start_price=float(limit_price), price=float(limit_price), tick_size=self.tick_size(root),
+            interval_ms=float(interval), max_offset_ticks=int(offset), convert_to_market=bool(convert),
+            strategy=strategy, started_at=time.time(),
+        )
+        with self._lock:
+            self._orders[order.order_id] = order
+        self.orders_chased += 1
+        self._tasks[order.order_id] = asyncio.create_task(self._run(order))
+        logger.info(f"🏃 Chasing {root} {order.side} x{order.quantity} from {order.start_price} "
+                    f"every {interval:g}ms up to {order.cap_price}" + (" then market" if convert else ""))
+        return order
+
+    def on_fill(self, order_id: str) -> bool:
+        """The order filled: stop chasing it; True if it was being chased."""
+        return self._finish_by_id(str(order_id), 'filled')
+
+    def forget(self, order_id: str) -> bool:
+        """The order was cancelled elsewhere: stop chasing it; True if it was being chased."""
+        return self._finish_by_id(str(order_id), 'cancelled')
+
+    def _finish_by_id(self, order_id: str, state: str) -> bool:
+        order = self._orders.get(order_id)
+        if order is None or order.state != 'chasing':
+            return False  # Unknown, or a conversion in progress that ends the chase itself
+        self._finish(order, state)
+        return True
+
+    async def stop(self) -> None:
+        """Cancel every chase task; the orders stay working at their current price."""
+        tasks = list(self._tasks.values())
+        self._tasks.clear()
+        for task in tasks:
+            task.cancel()
+        for task in tasks:
+            try:
+                await task
+            except asyncio.CancelledError:
+                pass
+
+    # ---------------------------
+    # Repricing
+    # ---------------------------
+    async def _run(self, order: ChasedOrder) -> None:
+        try:
+            while order.state == 'chasing':
+                await asyncio.sleep(order.interval_ms / 1000)
+                if order.state == 'chasing':
+                    await self.step(order)
+        except asyncio.CancelledError:
+            pass
+        except Exception as e:
+            logger.error(f"❌ Chase of order {order.order_id} failed: {e}")
+            self._finish(order, 'failed', str(e))
+        finally:
+            self._tasks.pop(order.order_id, None)
+
+    @staticmethod
+    async def _call(function: Callable[..., Any], *args: Any, **kwargs: Any) -> Any:
+        result = function(*args, **kwargs)
+        return await result if inspect.isawaitable(result) else result
+
+    @staticmethod
+    def _error(result: Any) -> Optional[str]:
+        if isinstance(result, dict) and (result.get('error') or result.get('success') is False):
+            return str(result.get('error') or result.get('errorMessage') or 'rejected')
+        return None
+
+    async def step(self, order: ChasedOrder) -> None:
+        """Reprice one order to the current touch (the chase task calls this every interval; tests may too)."""
+        touch = self.touch(order.symbol, order.side)
+        if touch is None or order.state != 'chasing':
+            return
+        target = order.target(touch)
+        if target != order.price:
+            decided = time.perf_counter()
+            try:
+                error = self._error(await self._call(self.modify, order.order_id, new_price=target,
+                                                     account_id=order.account_id, order_type=1))
+            except Exception as e:
+                error = str(e)
+            if order.state != 'chasing':
+                return  # Filled or cancelled while the modification was in flight
+            if error:
+                order.failures += 1
+                self.modify_failures += 1
+                order.error = error
+                logger.warning(f"⚠️  Repricing order {order.order_id} to {target} failed: {error}")
+                if order.failures >= self.max_failures:
+                    self._finish(order, 'failed', error)
+                return
+            order.price = target
+            order.reprices += 1
+            self.reprices += 1
+            self.reprice_latencies_ms = self.reprice_latencies_ms[-99:] + [
+                round((time.perf_counter() - decided) * 1000, 2)]
+            logger.debug(f"Order {order.order_id} {order.symbol} repriced to {target}")
+        if order.price == order.cap_price and order.beyond_cap(touch):
+            if order.convert_to_market:
+                await self._convert(order)
+            else:
+                self._finish(order, 'capped')
+                logger.info(f"Order {order.order_id} {order.symbol} reached its chase cap {order.cap_price}, "
+                            f"left working")
+
+    async def _convert(self, order: ChasedOrder) -> None:
+        """Replace the capped limit order with a market order (only once its cancel succeeded)."""
+        order.state = 'converting'
+        try:
+            error = self._error(await self._call(self.cancel, order.order_id, account_id=order.account_id))
+        except Exception as e:
+            error = str(e)
+        if error:
+            self._finish(order, 'failed', f"cancel before market conversion failed (filled?): {error}")
+            return
+        try:
+            result = await self._call(self.place_market, order.symbol, order.side, order.quantity,
+                                      account_id=order.account_id, strategy_name=order.strategy)
+            error = self._error(result)
+        except Exception as e:
+            result, error = None, str(e)
+        if error:
+            self._finish(order, 'failed', f"market order after chase failed: {error}")
+            return
+        order.market_order_id = str(result.get('orderId') or result.get('id') or '') or None
+        self.conversions += 1
+        self._finish(order, 'converted')
+        logger.info(f"🏁 Order {order.order_id} {order.symbol} converted to market order {order.market_order_id} "
+                    f"past its chase cap {order.cap_price}")
+
+    def _finish(self, order: ChasedOrder, state: str, error: Optional[str] = None) -> None:
+        with self._lock:
+            order.state = state
+            order.finished_at = time.time()
+            if error:
+                order.error = error
+            self._orders.pop(order.order_id, None)
+            self.finished = (self.finished + [order])[-self.history:]
+        if state == 'failed':
+            logger.error(f"❌ Chase of order {order.order_id} {order.symbol} abandoned: {error}")
+
+    # ---------------------------
+    # Status
+    # ---------------------------
+    def get_orders(self) -> Dict[str, Dict[str, Any]]:
+        """Orders being chased by order ID."""
+        with self._lock:
+            return {order_id: order.to_dict() for order_id, order in self._orders.items()}
+
+    def get_stats(self) -> Dict[str, Any]:
+        with self._lock:
+            latencies = sorted(self.reprice_latencies_ms)
+            return {
+                'interval_ms': self.interval_ms,
+                'max_offset_ticks': self.max_offset_ticks,
+                'convert_to_market': self.convert_to_market,
+                'chasing': len(self._orders),
+                'orders_chased': self.orders_chased,
+                'reprices': self.reprices,
+                'conversions': self.conversions,
+                'modify_failures': self.modify_failures,
+                'median_reprice_ms': latencies[len(latencies) // 2] if latencies else None,
+                'recent': [order.to_dict() for order in self.finished[-10:]],
+            }
@@ -0,0 +1,210 @@
+"""
+Order Auto-Expiration
+
+Cancels working orders that have not filled within a window set at
+placement (expire_after_secs on limit and stop orders). Breakout entries
+that did not trigger in time go stale; instead of a strategy polling its
+own orders, the scheduler keeps one deadline per order and a single
+background task sleeps until the earliest one.
+
+When a deadline passes, the order is cancelled and an OrderExpiration is
+handed to the expiration callback (the bot dispatches it as the
+ORDER_EXPIRED lifecycle event). Fills and manual cancels drop the deadline
+(forget()). A cancel that fails because the order filled in the meantime is
+still reported, with cancelled=False and the error.
+
+Features:
+- One background task for any number of orders (heap of deadlines)
+- Started with the first scheduled order, woken early by earlier deadlines
+- Pending deadlines and counters for /health
+
+Usage:
+    scheduler = OrderExpiryScheduler(cancel=bot.cancel_order, on_expired=bot._on_order_expired)
+    scheduler.schedule(account_id, order_id, expire_after_secs=90, symbol='MNQ')
+    scheduler.forget(order_id)               # Filled or cancelled
+    await scheduler.stop()
+"""
+
+import asyncio
+import heapq
+import inspect
+import logging
+import time
+from dataclasses import asdict, dataclass
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+from core.clock_sync import server_now
+
+logger = logging.getLogger(__name__)
+
+
+@dataclass
+class OrderExpiration:
+    """An order whose expiration window passed."""
+    account_id: str
+    order_id: str
+    symbol: Optional[str]
+    expire_after_secs: float
+    placed_at: str
+    expired_at: str
+    cancelled: bool
+    error: Optional[str] = None
+
+    def to_dict(self) -> Dict[str, Any]:
+        return asdict(self)
+
+
+class OrderExpiryScheduler:
+    """
+    Cancels orders still working when their expiration window ends.
+    """
+
+    def __init__(self, cancel: Callable[..., Any],
+                 on_expired: Optional[Callable[[OrderExpiration], Any]] = None, history: int = 50):
+        """
+        Initialize scheduler.
+
+        Args:
+            cancel: cancel_order(order_id, account_id=...) -> response dict (sync or coroutine)
+            on_expired: Called with every OrderExpiration (sync or coroutine)
+            history: Expirations kept for get_stats()
+        """
+        self.cancel = cancel
+        self.on_expired = on_expired
+        self.history = history
+        self._orders: Dict[str, Dict[str, Any]] = {}  # order ID -> account_id, symbol, window, deadline
+        self._heap: List[Tuple[float, str]] = []  # (monotonic deadline, order ID); stale entries skipped
+        self._wakeup: Optional[asyncio.Event] = None
+        self._running = False
+        self._task: Optional[asyncio.Task] = None
+        self.expirations: List[OrderExpiration] = []
+        self.orders_scheduled = 0
+        self.orders_expired = 0
+        self.cancel_failures = 0
+
+    def schedule(self, account_id: str, order_id: str, expire_after_secs: float,
+                 symbol: Optional[str] = None) -> None:
+        """
+        Cancel an order unless it fills (or is cancelled) within expire_after_secs.
+
+        Must be called from the event loop; starts the background task if needed.
+
+        Raises:
+            ValueError: Non-positive window
+        """
+        if not expire_after_secs or float(expire_after_secs) <= 0:
+            raise ValueError(f"expire_after_secs must be positive, got {expire_after_secs}")
+        deadline = time.monotonic() + float(expire_after_secs)
+        self._orders[str(order_id)] = {
+            'account_id': str(account_id), 'symbol': symbol, 'expire_after_secs': float(expire_after_secs),
+            'placed_at': server_now().isoformat(), 'deadline': deadline,
+        }
+        heapq.heappush(self._heap, (deadline, str(order_id)))
+        self.orders_scheduled += 1
+        logger.info(f"⏳ Order {order_id} expires in {float(expire_after_secs):g}s unless filled")
+        if not self._running:
+            self.start()
+        elif self._heap[0][1] == str(order_id) and self._wakeup is not None:
+            self._wakeup.set()  # Earlier than what the loop is sleeping towards
+
+    def forget(self, order_id: str) -> bool:
+        """Drop an order's deadline (filled or cancelled); True if it had one."""
+        return self._orders.pop(str(order_id), None) is not None
+
+    def start(self) -> None:
+        """Start the background task (on the running loop)."""
+        if self._running:
+            return
+        self._running = True
+        self._wakeup = asyncio.Event()
+        self._task = asyncio.create_task(self._loop())
+
+    async def stop(self) -> None:
+        """Stop the background task; pending deadlines are kept but no longer enforced."""
+        self._running = False
+        if self._task:
+            self._task.cancel()
+            try:
+                await self._task
+            except asyncio.CancelledError:
+                pass
+            self._task = None
+
+    async def _loop(self) -> None:
+        while self._running:
+            try:
+                self._wakeup.clear()
+                delay = self._heap[0][0] - time.monotonic() if self._heap else None
+                if delay is None or delay > 0:
+                    try:
+                        await asyncio.wait_for(self._wakeup.wait(), timeout=delay)
+                    except asyncio.TimeoutError:
+                        pass
+                    continue
+                await self.expire_due()
+            except asyncio.CancelledError:
+                break
+            except Exception as e:
+                logger.error(f"❌ Order expiry loop error: {e}")
+                await asyncio.sleep(1)
+
+    async def expire_due(self, now: Optional[float] = None) -> List[OrderExpiration]:
+        """Cancel every order whose deadline has passed (the loop calls this; tests may too)."""
+        now = time.monotonic() if now is None else now
+        expired = []
+        while self._heap and self._heap[0][0] <= now:
+            deadline, order_id = heapq.heappop(self._heap)
+            entry = self._orders.get(order_id)
+            if entry is None or entry['deadline'] != deadline:
+                continue  # Filled, cancelled or rescheduled
+            del self._orders[order_id]
+            expired.append(await self._expire(order_id, entry))
+        return expired
+
+    async def _expire(self, order_id: str, entry: Dict[str, Any]) -> OrderExpiration:
+        error = None
+        try:
+            response = self.cancel(order_id, account_id=entry['account_id'])
+            response = await response if inspect.isawaitable(response) else response
+            if isinstance(response, dict) and (response.get('error') or response.get('success') is False):
+                error = str(response.get('error') or response.get('errorMessage') or 'cancel rejected')
+        except Exception as e:
+            error = f"{type(e).__name__}: {e}"
+        expiration = OrderExpiration(
+            account_id=entry['account_id'], order_id=order_id, symbol=entry['symbol'],
+            expire_after_secs=entry['expire_after_secs'], placed_at=entry['placed_at'],
+            expired_at=server_now().isoformat(), cancelled=error is None, error=error,
+        )
+        self.orders_expired += 1
+        if error:
+            self.cancel_failures += 1
+            logger.warning(f"⚠️  Expired order {order_id} could not be cancelled (filled?): {error}")
+        else:
+            logger.info(f"⌛ Order {order_id} {entry['symbol'] or ''} expired after "
+                        f"{entry['expire_after_secs']:g}s and was cancelled")
+        self.expirations = (self.expirations + [expiration])[-self.history:]
+        if self.on_expired is not None:
+            try:
+                result = self.on_expired(expiration)
+                if inspect.isawaitable(result):
+                    await result
+            except Exception as e:
+                logger.error(f"❌ Order expiration callback failed: {e}")
+        return expiration
+
+    def get_pending(self) -> Dict[str, Dict[str, Any]]:
+        """Working orders with a deadline: seconds left by order ID."""
+        now = time.monotonic()
+        return {order_id: {'account_id': entry['account_id'], 'symbol': entry['symbol'],
+                           'expires_in': round(max(entry['deadline'] - now, 0.0), 1)}
+                for order_id, entry in self._orders.items()}
+
+    def get_stats(self) -> Dict[str, Any]:
+        return {
+            'running': self._running,
+            'pending': len(self._orders),
+            'orders_scheduled': self.orders_scheduled,
+            'orders_expired': self.orders_expired,
+            'cancel_failures': self.cancel_failures,
+            'recent_expirations': [expiration.to_dict() for expiration in self.expirations[-10:]],
+        }
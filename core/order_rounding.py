@@ -0,0 +1,181 @@
+"""
+Per-venue rounding policy for order prices and quantities.
+
+Venues disagree on how off-grid values are handled - some round, some
+truncate, some reject outright - and a price that is valid on one can be
+rejected by another. The bot applies one policy per venue to every order
+payload right before it is sent, so all order paths round the same way.
+
+Price modes (prices are snapped to the instrument's tick size):
+- 'half_even'       : nearest tick, ties to the even tick (banker's rounding)
+- 'toward_zero'     : truncate to the tick at or below the price's magnitude
+- 'order_direction' : never more aggressive than requested - buy limits and
+                      sell stops round down, sell limits and buy stops round up
+
+Quantity modes (sizes are snapped to the venue's lot size):
+- 'half_even', 'toward_zero' as above; 'order_direction' truncates (never
+  sends more contracts than requested)
+
+Arithmetic uses Decimal so results are exact tick multiples (2000.1, not
+2000.1000000000001).
+
+Configuration:
+- ORDER_VENUE: Venue whose policy is used (default 'topstepx')
+- ORDER_PRICE_ROUNDING: Override the venue's price mode
+- ORDER_SIZE_ROUNDING: Override the venue's quantity mode
+"""
+
+import logging
+import os
+from dataclasses import dataclass, replace
+from decimal import Decimal, ROUND_CEILING, ROUND_DOWN, ROUND_FLOOR, ROUND_HALF_EVEN
+from enum import Enum
+from typing import Any, Dict, List, Optional, Tuple
+
+logger = logging.getLogger(__name__)
+
+# Payload price fields and whether rounding up makes that order more aggressive for a buy
+PRICE_FIELDS = {
+    'limitPrice': True,   # Higher buy limit = more aggressive
+    'stopPrice': False,   # Higher buy stop = less aggressive (triggers later)
+    'trailPrice': False,
+}
+
+
+class RoundingMode(Enum):
+    """How off-grid values are snapped."""
+    HALF_EVEN = "half_even"
+    TOWARD_ZERO = "toward_zero"
+    ORDER_DIRECTION = "order_direction"
+
+
+@dataclass(frozen=True)
+class VenueRoundingPolicy:
+    """Rounding rules for one venue."""
+    venue: str
+    price_mode: RoundingMode = RoundingMode.HALF_EVEN
+    quantity_mode: RoundingMode = RoundingMode.TOWARD_ZERO
+    lot_size: int = 1
+
+
+VENUE_ROUNDING_POLICIES: Dict[str, VenueRoundingPolicy] = {
+    # TopStepX (ProjectX gateway) rejects off-tick prices; the bot has always rounded to the nearest tick
+    'topstepx': VenueRoundingPolicy('topstepx', RoundingMode.HALF_EVEN, RoundingMode.TOWARD_ZERO),
+}
+
+
+def register_venue_policy(policy: VenueRoundingPolicy) -> None:
+    """Add or replace a venue's rounding policy."""
+    VENUE_ROUNDING_POLICIES[policy.venue.lower()] = policy
+
+
+def get_venue_policy(venue: Optional[str] = None) -> VenueRoundingPolicy:
+    """
+    Rounding policy for a venue, with env overrides applied.
+
+    Args:
+        venue: Venue name (env: ORDER_VENUE, default 'topstepx')
+
+    Raises:
+        ValueError: Unknown venue or rounding mode
+    """
+    name = (venue or os.getenv('ORDER_VENUE', 'topstepx')).strip().lower()
+    if name not in VENUE_ROUNDING_POLICIES:
+        raise ValueError(f"No rounding policy for venue '{name}'. Known: {sorted(VENUE_ROUNDING_POLICIES)}")
+    policy = VENUE_ROUNDING_POLICIES[name]
+    price_mode = os.getenv('ORDER_PRICE_ROUNDING')
+    size_mode = os.getenv('ORDER_SIZE_ROUNDING')
+    if price_mode:
+        policy = replace(policy, price_mode=RoundingMode(price_mode.strip().lower()))
+    if size_mode:
+        policy = replace(policy, quantity_mode=RoundingMode(size_mode.strip().lower()))
+    return policy
+
+
+def _is_buy(side: Any) -> Optional[bool]:
+    """Accept API side values (0 = buy, 1 = sell) or 'BUY'/'SELL'."""
+    if side in (0, '0') or str(side).upper() == 'BUY':
+        return True
+    if side in (1, '1') or str(side).upper() == 'SELL':
+        return False
+    return None
+
+
+def round_price(price: float, tick_size: float, mode: RoundingMode = RoundingMode.HALF_EVEN,
+                side: Any = None, price_field: str = 'limitPrice') -> float:
+    """
+    Snap a price to the tick grid.
+
+    Args:
+        price: Price to round
+        tick_size: Instrument tick size (returned unchanged if <= 0)
+        mode: Rounding mode
+        side: Order side, required for ORDER_DIRECTION (falls back to HALF_EVEN without it)
+        price_field: Payload field the price is for ('limitPrice', 'stopPrice', ...)
+
+    Returns:
+        float: Price on the tick grid
+    """
+    if tick_size <= 0:
+        return price
+    if mode is RoundingMode.HALF_EVEN:
+        rounding = ROUND_HALF_EVEN
+    elif mode is RoundingMode.TOWARD_ZERO:
+        rounding = ROUND_DOWN
+    else:
+        is_buy = _is_buy(side)
+        if is_buy is None:
+            rounding = ROUND_HALF_EVEN
+        else:
+            up_is_aggressive = PRICE_FIELDS.get(price_field, True) == is_buy
+            rounding = ROUND_FLOOR if up_is_aggressive else ROUND_CEILING
+    tick = Decimal(str(tick_size))
+    ticks = (Decimal(str(price)) / tick).quantize(Decimal(1), rounding=rounding)
+    return float(ticks * tick)
+
+
+def round_quantity(quantity: float, mode: RoundingMode = RoundingMode.TOWARD_ZERO, lot_size: int = 1) -> int:
+    """Snap a quantity to the venue's lot size (ORDER_DIRECTION truncates)."""
+    rounding = ROUND_HALF_EVEN if mode is RoundingMode.HALF_EVEN else ROUND_DOWN
+    lots = (Decimal(str(quantity)) / Decimal(lot_size)).quantize(Decimal(1), rounding=rounding)
+    return int(lots) * lot_size
+
+
+def normalize_order_payload(payload: Dict[str, Any], tick_size: float,
+                            policy: VenueRoundingPolicy) -> List[Tuple[str, Any, Any]]:
+    """
+    Apply a venue policy to an order payload in place.
+
+    Rounds every price field present (limitPrice, stopPrice, trailPrice) using
+    the payload's side, and the size field.
+
+    Args:
+        payload: /api/Order/place or /api/Order/modify payload
+        tick_size: Instrument tick size
+        policy: Venue rounding policy
+
+    Returns:
+        List of (field, original, rounded) for values that changed
+
+    Raises:
+        ValueError: If the size rounds to zero
+    """
+    changes = []
+    side = payload.get('side')
+    for field in PRICE_FIELDS:
+        value = payload.get(field)
+        if value is None:
+            continue
+        rounded = round_price(float(value), tick_size, policy.price_mode, side, field)
+        if rounded != value:
+            changes.append((field, value, rounded))
+        payload[field] = rounded
+    size = payload.get('size')
+    if size is not None:
+        rounded_size = round_quantity(size, policy.quantity_mode, policy.lot_size)
+        if rounded_size <= 0:
+            raise ValueError(f"Order size {size} rounds to 0 for {policy.venue} (lot size {policy.lot_size})")
+        if rounded_size != size:
+            changes.append(('size', size, rounded_size))
+        payload['size'] = rounded_size
+    return changes
@@ -0,0 +1,325 @@
+"""
+Partitioned Parquet storage for bars and ticks.
+
+Cheap long-term storage of recorded market data without a database. Rows are
+buffered per partition and written as immutable part files; readers prune by
+directory before touching any file, so a query for one symbol and a few days
+only opens those days.
+
+Layout (hive-style, dates are UTC):
+    <root>/bars/symbol=MNQ/timeframe=1m/date=2025-11-19/part-<utc time>-<seq>.parquet
+    <root>/ticks/symbol=MNQ/date=2025-11-19/part-<utc time>-<seq>.parquet
+
+Streaming: write_bars()/write_ticks() (or on_market_event() as a market event
+listener for Trade and BarClosed events) buffer rows; a partition is flushed
+once it holds PARQUET_FLUSH_ROWS rows, on flush(), and every
+PARQUET_FLUSH_INTERVAL seconds while the background flusher runs (start()).
+With the flusher running, listeners never write on the calling thread.
+compact() merges a partition's part files into one.
+
+Requires `polars`.
+
+Configuration:
+- PARQUET_STORE_DIR: Root directory (default 'data/parquet')
+- PARQUET_COMPRESSION: Parquet codec (default 'zstd')
+- PARQUET_FLUSH_ROWS: Buffered rows per partition before a write (default 50000)
+- PARQUET_FLUSH_INTERVAL: Background flush interval in seconds (default 60)
+"""
+
+import logging
+import os
+import threading
+from collections import defaultdict
+from datetime import date, datetime, timezone
+from pathlib import Path
+from typing import Any, Dict, Iterable, List, Optional, Tuple, Union
+
+from core.bar_aggregator import Bar
+from core.market_events import BarClosed, MarketEvent, Trade, parse_timestamp
+
+logger = logging.getLogger(__name__)
+
+# Partition key: ('bars', symbol, timeframe, day) or ('ticks', symbol, None, day)
+PartitionKey = Tuple[str, str, Optional[str], date]
+
+
+def _polars():
+    try:
+        import polars as pl
+    except ImportError as e:
+        raise ImportError("polars is required for Parquet storage (pip install polars)") from e
+    return pl
+
+
+def _schema(pl, kind: str) -> Dict[str, Any]:
+    timestamp = pl.Datetime('us', 'UTC')
+    if kind == 'bars':
+        return {'symbol': pl.Utf8, 'timeframe': pl.Utf8, 'timestamp': timestamp, 'open': pl.Float64,
+                'high': pl.Float64, 'low': pl.Float64, 'close': pl.Float64, 'volume': pl.Int64,
+                'tick_count': pl.Int64, 'is_rth': pl.Boolean}
+    return {'symbol': pl.Utf8, 'timestamp': timestamp, 'price': pl.Float64, 'size': pl.Int64,
+            'side': pl.Utf8, 'bid': pl.Float64, 'ask': pl.Float64}
+
+
+def _utc(ts: datetime) -> datetime:
+    return ts.replace(tzinfo=timezone.utc) if ts.tzinfo is None else ts.astimezone(timezone.utc)
+
+
+class ParquetStore:
+    """
+    Partitioned Parquet writer/reader.
+
+    Usage:
+        store = ParquetStore('data/parquet')
+        store.start()
+        bot.add_market_event_listener(store.on_market_event)
+        ...
+        bars = store.read_bars('MNQ', '1m', start, end)
+        store.stop()
+    """
+
+    def __init__(self, root: Union[str, Path, None] = None, compression: Optional[str] = None,
+                 flush_rows: Optional[int] = None, flush_interval: Optional[float] = None):
+        """
+        Initialize store.
+
+        Args:
+            root: Root directory (env: PARQUET_STORE_DIR)
+            compression: Parquet codec, e.g. 'zstd', 'lz4', 'snappy' (env: PARQUET_COMPRESSION)
+            flush_rows: Rows buffered per partition before it is written (env: PARQUET_FLUSH_ROWS)
+            flush_interval: Background flush interval in seconds (env: PARQUET_FLUSH_INTERVAL)
+        """
+        self.root = Path(root or os.getenv('PARQUET_STORE_DIR', 'data/parquet'))
+        self.compression = compression or os.getenv('PARQUET_COMPRESSION', 'zstd')
+        self.flush_rows = flush_rows if flush_rows is not None else int(os.getenv('PARQUET_FLUSH_ROWS', '50000'))
+        self.flush_interval = flush_interval if flush_interval is not None else \
+            float(os.getenv('PARQUET_FLUSH_INTERVAL', '60'))
+        self._buffers: Dict[PartitionKey, List[Dict[str, Any]]] = defaultdict(list)
+        self._lock = threading.Lock()
+        self._write_lock = threading.Lock()
+        self._seq = 0
+        self._stop = threading.Event()
+        self._thread: Optional[threading.Thread] = None
+        self.rows_written = 0
+        self.files_written = 0
+        self.write_errors = 0
+
+    # ---------------------------
+    # Writing
+    # ---------------------------
+    def partition_dir(self, kind: str, symbol: str, day: date, timeframe: Optional[str] = None) -> Path:
+        """Directory holding one partition's part files."""
+        path = self.root / kind / f"symbol={symbol.upper()}"
+        if kind == 'bars':
+            path = path / f"timeframe={timeframe}"
+        return path / f"date={day.isoformat()}"
+
+    def write_bars(self, bars: Iterable[Bar]) -> None:
+        """Buffer completed bars for writing."""
+        rows = []
+        for bar in bars:
+            ts = _utc(bar.timestamp)
+            rows.append((('bars', bar.symbol.upper(), bar.timeframe, ts.date()), {
+                'symbol': bar.symbol.upper(), 'timeframe': bar.timeframe, 'timestamp': ts,
+                'open': bar.open, 'high': bar.high, 'low': bar.low, 'close': bar.close,
+                'volume': bar.volume, 'tick_count': bar.tick_count, 'is_rth': bar.is_rth,
+            }))
+        self._buffer(rows)
+
+    def write_ticks(self, ticks: Iterable[Union[Trade, Dict[str, Any]]]) -> None:
+        """Buffer ticks (Trade events or dicts with symbol, timestamp, price, size, side, bid, ask)."""
+        rows = []
+        for tick in ticks:
+            if isinstance(tick, Trade):
+                tick = {'symbol': tick.symbol, 'timestamp': tick.timestamp, 'price': tick.price,
+                        'size': tick.size, 'side': tick.side}
+            ts = _utc(parse_timestamp(tick.get('timestamp')))
+            symbol = str(tick['symbol']).upper()
+            rows.append((('ticks', symbol, None, ts.date()), {
+                'symbol': symbol, 'timestamp': ts, 'price': float(tick['price']),
+                'size': int(tick.get('size') or 0), 'side': tick.get('side'),
+                'bid': tick.get('bid'), 'ask': tick.get('ask'),
+            }))
+        self._buffer(rows)
+
+    def on_market_event(self, event: MarketEvent) -> None:
+        """Market event listener: stores Trade and BarClosed events."""
+        if isinstance(event, Trade):
+            self.write_ticks([event])
+        elif isinstance(event, BarClosed):
+            self.write_bars([event.bar])
+
+    def _buffer(self, rows: List[Tuple[PartitionKey, Dict[str, Any]]]) -> None:
+        full = []
+        with self._lock:
+            for key, row in rows:
+                buffer = self._buffers[key]
+                buffer.append(row)
+                if len(buffer) >= self.flush_rows:
+                    full.append(key)
+        if full and not self._running():
+            self.flush(full)
+
+    def _running(self) -> bool:
+        return bool(self._thread and self._thread.is_alive())
+
+    def flush(self, keys: Optional[Iterable[PartitionKey]] = None) -> int:
+        """
+        Write buffered rows (all partitions, or only the given ones).
+
+        Returns:
+            int: Rows written
+        """
+        with self._lock:
+            targets = list(keys) if keys is not None else list(self._buffers)
+            pending = [(key, self._buffers.pop(key)) for key in targets if self._buffers.get(key)]
+        written = 0
+        with self._write_lock:
+            for key, rows in pending:
+                try:
+                    self._write_part(key, rows)
+                    written += len(rows)
+                except Exception as e:
+                    self.write_errors += 1
+                    logger.error(f"❌ Parquet write failed for {key[0]}/{key[1]}/{key[3]}: {e}")
+        return written
+
+    def _write_part(self, key: PartitionKey, rows: List[Dict[str, Any]]) -> Path:
+        pl = _polars()
+        kind, symbol, timeframe, day = key
+        directory = self.partition_dir(kind, symbol, day, timeframe)
+        directory.mkdir(parents=True, exist_ok=True)
+        self._seq += 1
+        name = f"part-{datetime.now(timezone.utc).strftime('%Y%m%dT%H%M%S')}-{self._seq:06d}.parquet"
+        frame = pl.DataFrame(rows, schema=_schema(pl, kind)).sort('timestamp')
+        # Temp file + rename so readers never see a partial file
+        tmp_path = directory / f".{name}.tmp"
+        frame.write_parquet(tmp_path, compression=self.compression)
+        path = directory / name
+        os.replace(tmp_path, path)
+        self.rows_written += len(rows)
+        self.files_written += 1
+        logger.debug(f"Wrote {len(rows)} {kind} rows to {path}")
+        return path
+
+    def start(self) -> None:
+        """Start the background flusher."""
+        if self._running():
+            return
+        self._stop.clear()
+        self._thread = threading.Thread(target=self._run, name="parquet-store", daemon=True)
+        self._thread.start()
+        logger.info(f"🗄️  Parquet store writing to {self.root} (compression={self.compression})")
+
+    def stop(self, timeout: float = 30.0) -> None:
+        """Stop the flusher and write everything still buffered."""
+        self._stop.set()
+        if self._thread:
+            self._thread.join(timeout)
+            self._thread = None
+        self.flush()
+
+    def _run(self) -> None:
+        elapsed = 0.0
+        tick = min(1.0, self.flush_interval)
+        while not self._stop.wait(tick):
+            elapsed += tick
+            with self._lock:
+                full = [key for key, rows in self._buffers.items() if len(rows) >= self.flush_rows]
+            if elapsed >= self.flush_interval:
+                self.flush()
+                elapsed = 0.0
+            elif full:
+                self.flush(full)
+
+    # ---------------------------
+    # Reading
+    # ---------------------------
+    def _partition_files(self, kind: str, symbol: str, start: Optional[datetime], end: Optional[datetime],
+                         timeframe: Optional[str] = None) -> List[Path]:
+        """Part files of the partitions overlapping [start, end) (directory pruning only)."""
+        base = self.root / kind / f"symbol={symbol.upper()}"
+        if kind == 'bars':
+            base = base / f"timeframe={timeframe}"
+        if not base.is_dir():
+            return []
+        first = _utc(start).date() if start else None
+        last = _utc(end).date() if end else None
+        files = []
+        for directory in sorted(base.glob('date=*')):
+            try:
+                day = date.fromisoformat(directory.name[len('date='):])
+            except ValueError:
+                continue
+            if (first and day < first) or (last and day > last):
+                continue
+            files.extend(sorted(directory.glob('part-*.parquet')))
+        return files
+
+    def _scan(self, kind: str, symbol: str, start: Optional[datetime], end: Optional[datetime],
+              timeframe: Optional[str] = None):
+        pl = _polars()
+        files = self._partition_files(kind, symbol, start, end, timeframe)
+        if not files:
+            return pl.DataFrame(schema=_schema(pl, kind)).lazy()
+        frame = pl.scan_parquet([str(f) for f in files])
+        if start:
+            frame = frame.filter(pl.col('timestamp') >= _utc(start))
+        if end:
+            frame = frame.filter(pl.col('timestamp') < _utc(end))
+        return frame.sort('timestamp')
+
+    def scan_bars(self, symbol: str, timeframe: str, start: Optional[datetime] = None,
+                  end: Optional[datetime] = None):
+        """Lazy polars frame of bars in [start, end) for analytics."""
+        return self._scan('bars', symbol, start, end, timeframe)
+
+    def scan_ticks(self, symbol: str, start: Optional[datetime] = None, end: Optional[datetime] = None):
+        """Lazy polars frame of ticks in [start, end) for analytics."""
+        return self._scan('ticks', symbol, start, end)
+
+    def read_bars(self, symbol: str, timeframe: str, start: Optional[datetime] = None,
+                  end: Optional[datetime] = None) -> List[Bar]:
+        """Stored bars in [start, end), oldest first (duplicates by timestamp collapse to the last written)."""
+        frame = self.scan_bars(symbol, timeframe, start, end).unique('timestamp', keep='last', maintain_order=True)
+        return [Bar(**row) for row in frame.collect().iter_rows(named=True)]
+
+    def read_ticks(self, symbol: str, start: Optional[datetime] = None,
+                   end: Optional[datetime] = None) -> List[Dict[str, Any]]:
+        """Stored ticks in [start, end), oldest first."""
+        return self.scan_ticks(symbol, start, end).collect().to_dicts()
+
+    def compact(self, kind: str, symbol: str, day: date, timeframe: Optional[str] = None) -> int:
+        """
+        Merge a partition's part files into one.
+
+        Returns:
+            int: Number of part files merged (0 if there was nothing to do)
+        """
+        pl = _polars()
+        directory = self.partition_dir(kind, symbol, day, timeframe)
+        with self._write_lock:
+            parts = sorted(directory.glob('part-*.parquet'))
+            if len(parts) < 2:
+                return 0
+            frame = pl.concat([pl.read_parquet(p) for p in parts]).sort('timestamp')
+            tmp_path = directory / ".compact.tmp"
+            frame.write_parquet(tmp_path, compression=self.compression)
+            os.replace(tmp_path, parts[0])  # Oldest name keeps the partition's sort order
+            for part in parts[1:]:
+                part.unlink()
+        logger.info(f"🗜️  Compacted {len(parts)} part files in {directory}")
+        return len(parts)
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Writer counters."""
+        with self._lock:
+            buffered = sum(len(rows) for rows in self._buffers.values())
+        return {
+            "running": self._running(),
+            "root": str(self.root),
+            "buffered_rows": buffered,
+            "rows_written": self.rows_written,
+            "files_written": self.files_written,
+            "write_errors": self.write_errors,
+        }
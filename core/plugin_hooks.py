@@ -0,0 +1,228 @@
+"""
+Lifecycle plugin hooks for the trading bot.
+
+Lets external Python modules register callables for bot lifecycle events
+(post-auth, pre-trading-open, post-flatten, order-filled, order-expired, pre-shutdown) without modifying
+the bot itself. Hooks receive a HookContext describing the event.
+
+Plugins are plain modules exposing a ``register(registry)`` function and are
+loaded from the BOT_PLUGINS environment variable (comma-separated module paths).
+"""
+
+import asyncio
+import importlib
+import inspect
+import logging
+import os
+from dataclasses import dataclass, field
+from datetime import datetime, timezone
+from enum import Enum
+from typing import Any, Callable, Dict, List, Optional
+
+logger = logging.getLogger(__name__)
+
+
+class LifecycleEvent(Enum):
+    """Bot lifecycle events plugins can hook into."""
+    POST_AUTH = "post_auth"                # After successful authentication
+    PRE_TRADING_OPEN = "pre_trading_open"  # Before strategies are started
+    POST_FLATTEN = "post_flatten"          # After flatten_all_positions completes
+    ORDER_FILLED = "order_filled"          # Bot order filled (data: fill incl. enriched context, order)
+    ORDER_EXPIRED = "order_expired"        # expire_after_secs window passed (data: expiration)
+    PRE_SHUTDOWN = "pre_shutdown"          # Before the bot/server shuts down
+
+
+@dataclass
+class HookContext:
+    """Context passed to every lifecycle hook."""
+    event: LifecycleEvent
+    trading_bot: Any = None
+    account_id: Optional[str] = None
+    timestamp: datetime = field(default_factory=lambda: datetime.now(timezone.utc))
+    data: Dict[str, Any] = field(default_factory=dict)
+
+
+@dataclass
+class RegisteredHook:
+    """A registered hook callable."""
+    name: str
+    callback: Callable[[HookContext], Any]
+    priority: int = 100
+
+
+class PluginHookRegistry:
+    """
+    Registry and dispatcher for lifecycle hooks.
+
+    Features:
+    - Sync and async callables
+    - Priority ordering (lower runs first)
+    - Per-hook error isolation (a failing plugin never breaks the bot)
+    - Optional per-hook timeout
+    """
+
+    def __init__(self, hook_timeout: Optional[float] = None):
+        """
+        Initialize hook registry.
+
+        Args:
+            hook_timeout: Maximum seconds an async hook may run (None = no limit)
+        """
+        self._hooks: Dict[LifecycleEvent, List[RegisteredHook]] = {event: [] for event in LifecycleEvent}
+        self.hook_timeout = hook_timeout
+        self.loaded_plugins: List[str] = []
+        self.dispatch_counts: Dict[str, int] = {event.value: 0 for event in LifecycleEvent}
+        self.hook_errors: int = 0
+
+    def register(self, event, callback: Callable[[HookContext], Any],
+                 name: Optional[str] = None, priority: int = 100) -> str:
+        """
+        Register a callable for a lifecycle event.
+
+        Args:
+            event: LifecycleEvent or its string value (e.g., "post_auth")
+            callback: Callable taking a HookContext (sync or async)
+            name: Optional hook name (defaults to the callable's qualified name)
+            priority: Execution order, lower runs first
+
+        Returns:
+            str: Hook name (used for unregister)
+        """
+        event = self._coerce_event(event)
+        if not callable(callback):
+            raise TypeError(f"Hook for {event.value} must be callable, got {type(callback).__name__}")
+        hook_name = name or getattr(callback, '__qualname__', repr(callback))
+        hooks = self._hooks[event]
+        hooks.append(RegisteredHook(name=hook_name, callback=callback, priority=priority))
+        hooks.sort(key=lambda h: h.priority)
+        logger.info(f"🔌 Registered {event.value} hook: {hook_name}")
+        return hook_name
+
+    def unregister(self, event, name: str) -> bool:
+        """Remove a hook by name. Returns True if a hook was removed."""
+        event = self._coerce_event(event)
+        before = len(self._hooks[event])
+        self._hooks[event] = [h for h in self._hooks[event] if h.name != name]
+        return len(self._hooks[event]) < before
+
+    def get_hooks(self, event) -> List[RegisteredHook]:
+        """Get registered hooks for an event (in execution order)."""
+        return list(self._hooks[self._coerce_event(event)])
+
+    def clear(self):
+        """Remove all registered hooks."""
+        for event in LifecycleEvent:
+            self._hooks[event] = []
+
+    def load_plugins(self, module_paths: List[str]) -> int:
+        """
+        Import plugin modules and call their ``register(registry)`` function.
+
+        Args:
+            module_paths: Importable module paths
+
+        Returns:
+            int: Number of plugins loaded
+        """
+        loaded = 0
+        for path in module_paths:
+            path = path.strip()
+            if not path or path in self.loaded_plugins:
+                continue
+            try:
+                module = importlib.import_module(path)
+                register_fn = getattr(module, 'register', None)
+                if not callable(register_fn):
+                    logger.warning(f"⚠️  Plugin {path} has no register(registry) function, skipping")
+                    continue
+                register_fn(self)
+                self.loaded_plugins.append(path)
+                loaded += 1
+                logger.info(f"✅ Loaded plugin: {path}")
+            except Exception as e:
+                logger.error(f"❌ Failed to load plugin {path}: {e}")
+        return loaded
+
+    def load_plugins_from_env(self) -> int:
+        """Load plugins listed in BOT_PLUGINS (comma-separated module paths)."""
+        env_plugins = os.getenv('BOT_PLUGINS', '')
+        if not env_plugins.strip():
+            return 0
+        return self.load_plugins(env_plugins.split(','))
+
+    async def dispatch(self, event, trading_bot: Any = None,
+                       account_id: Optional[str] = None, **data) -> List[Any]:
+        """
+        Invoke all hooks for an event.
+
+        Args:
+            event: LifecycleEvent or string value
+            trading_bot: Bot instance passed through to hooks
+            account_id: Account ID (defaults to the bot's selected account)
+            **data: Event-specific payload placed in HookContext.data
+
+        Returns:
+            List of hook return values (None for hooks that failed)
+        """
+        event = self._coerce_event(event)
+        self.dispatch_counts[event.value] += 1
+        hooks = self._hooks[event]
+        if not hooks:
+            return []
+
+        if account_id is None and trading_bot is not None:
+            selected = getattr(trading_bot, 'selected_account', None)
+            if isinstance(selected, dict) and selected.get('id') is not None:
+                account_id = str(selected['id'])
+
+        context = HookContext(event=event, trading_bot=trading_bot, account_id=account_id, data=data)
+        results: List[Any] = []
+        for hook in hooks:
+            try:
+                result = hook.callback(context)
+                if inspect.isawaitable(result):
+                    if self.hook_timeout:
+                        result = await asyncio.wait_for(result, timeout=self.hook_timeout)
+                    else:
+                        result = await result
+                results.append(result)
+            except Exception as e:
+                self.hook_errors += 1
+                logger.error(f"❌ {event.value} hook '{hook.name}' failed: {e}")
+                results.append(None)
+        logger.debug(f"🔌 Dispatched {event.value} to {len(hooks)} hook(s)")
+        return results
+
+    def get_stats(self) -> Dict:
+        """Get registry statistics."""
+        return {
+            "loaded_plugins": list(self.loaded_plugins),
+            "hooks": {event.value: [h.name for h in hooks] for event, hooks in self._hooks.items()},
+            "dispatch_counts": dict(self.dispatch_counts),
+            "hook_errors": self.hook_errors,
+        }
+
+    @staticmethod
+    def _coerce_event(event) -> LifecycleEvent:
+        """Accept LifecycleEvent or its string value."""
+        if isinstance(event, LifecycleEvent):
+            return event
+        try:
+            return LifecycleEvent(str(event).strip().lower())
+        except ValueError:
+            valid = [e.value for e in LifecycleEvent]
+            raise ValueError(f"Unknown lifecycle event '{event}'. Valid values: {valid}")
+
+
+# Global hook registry instance
+_plugin_registry: Optional[PluginHookRegistry] = None
+
+
+def get_plugin_registry() -> PluginHookRegistry:
+    """Get or create global plugin hook registry (loads BOT_PLUGINS on first use)."""
+    global _plugin_registry
+    if _plugin_registry is None:
+        timeout_env = os.getenv('BOT_PLUGIN_HOOK_TIMEOUT')
+        _plugin_registry = PluginHookRegistry(hook_timeout=float(timeout_env) if timeout_env else None)
+        _plugin_registry.load_plugins_from_env()
+    return _plugin_registry
@@ -0,0 +1,359 @@
+"""
+Position Consistency Checker
+
+Keeps a local net-position ledger built from fills and periodically compares
+it with broker-reported positions. Silent drift between the two (missed fill,
+manual trade, broker-side liquidation) is surfaced as a structured alert and
+can optionally freeze new entries on the affected symbol until an operator
+reviews it.
+
+Features:
+- Thread-safe local position ledger per account/symbol
+- Divergence must persist for N consecutive checks before alerting
+  (avoids false alarms from fills that are still in flight)
+- Configurable tolerance (contracts)
+- Optional per-symbol freeze on divergence (POSITION_CHECK_FREEZE, off by
+  default: the bot feeds the ledger from polled order history, which can miss
+  fills; orders that only reduce the broker position are never frozen)
+- Background check loop
+"""
+
+import asyncio
+import inspect
+import logging
+import os
+from dataclasses import dataclass, asdict
+from datetime import datetime, timezone
+from threading import Lock
+from typing import Any, Awaitable, Callable, Dict, List, Optional, Set, Tuple
+
+from infrastructure.config import env_bool
+
+logger = logging.getLogger(__name__)
+
+
+class PositionTracker:
+    """
+    Local net-position ledger (signed contracts: long > 0, short < 0).
+
+    Fed from order fills and seeded from the broker on startup.
+    """
+
+    def __init__(self):
+        """Initialize position tracker."""
+        self._positions: Dict[str, Dict[str, int]] = {}  # {account_id: {symbol: net_qty}}
+        self._lock = Lock()
+
+    def apply_fill(self, account_id: str, symbol: str, side, quantity: int) -> int:
+        """
+        Apply a fill to the ledger.
+
+        Args:
+            account_id: Account ID
+            symbol: Trading symbol
+            side: "BUY"/"SELL" or TopStepX side code (0 = buy, 1 = sell)
+            quantity: Filled contracts
+
+        Returns:
+            int: New net position for the symbol
+        """
+        if isinstance(side, str):
+            sign = 1 if side.upper() in ('BUY', 'LONG') else -1
+        else:
+            sign = 1 if int(side) == 0 else -1
+        symbol_key = symbol.upper()
+        with self._lock:
+            account = self._positions.setdefault(str(account_id), {})
+            net = account.get(symbol_key, 0) + sign * int(quantity)
+            if net == 0:
+                account.pop(symbol_key, None)
+            else:
+                account[symbol_key] = net
+            return net
+
+    def set_positions(self, account_id: str, positions: Dict[str, int]) -> None:
+        """Replace the ledger for an account (used to seed or resync from the broker)."""
+        with self._lock:
+            self._positions[str(account_id)] = {
+                symbol.upper(): int(qty) for symbol, qty in positions.items() if int(qty) != 0
+            }
+
+    def has_account(self, account_id: str) -> bool:
+        """Whether the ledger has been seeded for an account."""
+        with self._lock:
+            return str(account_id) in self._positions
+
+    def get_position(self, account_id: str, symbol: str) -> int:
+        """Get net position for a symbol (0 if flat)."""
+        with self._lock:
+            return self._positions.get(str(account_id), {}).get(symbol.upper(), 0)
+
+    def get_positions(self, account_id: str) -> Dict[str, int]:
+        """Get all non-flat positions for an account."""
+        with self._lock:
+            return dict(self._positions.get(str(account_id), {}))
+
+
+@dataclass
+class PositionDivergence:
+    """Local vs broker position mismatch for one symbol."""
+    account_id: str
+    symbol: str
+    local_qty: int
+    broker_qty: int
+    difference: int
+    consecutive_checks: int
+    first_detected: str
+    frozen: bool = False
+
+    def to_dict(self) -> Dict:
+        """Convert to dictionary (structured alert payload)."""
+        data = asdict(self)
+        data['alert_type'] = 'position_divergence'
+        return data
+
+
+def net_broker_positions(positions: List[Dict], symbol_resolver: Optional[Callable[[str], str]] = None) -> Dict[str, int]:
+    """
+    Net broker position records by symbol.
+
+    TopStepX positions use type 1 = long, 2 = short; records with only a
+    side field use 0 = long, 1 = short.
+
+    Args:
+        positions: Position dicts from /api/Position/searchOpen
+        symbol_resolver: Maps contractId to symbol (defaults to CON.F.US.MNQ.Z25 -> MNQ)
+    """
+    netted: Dict[str, int] = {}
+    for pos in positions:
+        contract_id = str(pos.get('contractId') or '')
+        if symbol_resolver and contract_id:
+            symbol = symbol_resolver(contract_id)
+        elif '.' in contract_id:
+            symbol = contract_id.split('.')[-2]
+        else:
+            symbol = pos.get('symbol') or contract_id
+        if not symbol:
+            continue
+        size = int(pos.get('size') or 0)
+        if 'type' in pos:
+            sign = -1 if pos.get('type') == 2 else 1
+        else:
+            sign = -1 if pos.get('side', 0) == 1 else 1
+        key = symbol.upper()
+        netted[key] = netted.get(key, 0) + sign * size
+    return {symbol: qty for symbol, qty in netted.items() if qty != 0}
+
+
+class PositionConsistencyChecker:
+    """
+    Compares the local PositionTracker with broker-reported positions.
+
+    Usage:
+        checker = PositionConsistencyChecker(tracker, fetch_positions=bot._fetch_positions_strict)
+        divergences = await checker.check(account_id)
+    """
+
+    def __init__(self, tracker: PositionTracker,
+                 fetch_positions: Callable[[str], Awaitable[List[Dict]]],
+                 symbol_resolver: Optional[Callable[[str], str]] = None,
+                 alert_callback: Optional[Callable[[PositionDivergence], Any]] = None,
+                 tolerance: Optional[int] = None,
+                 confirmations: Optional[int] = None,
+                 freeze_on_divergence: Optional[bool] = None,
+                 interval: Optional[float] = None):
+        """
+        Initialize consistency checker.
+
+        Args:
+            tracker: Local position ledger
+            fetch_positions: Async callable returning broker positions for an account (must raise on API failure)
+            symbol_resolver: Maps contractId to symbol
+            alert_callback: Called (sync or async) once per divergence episode
+            tolerance: Allowed absolute difference in contracts (env: POSITION_CHECK_TOLERANCE)
+            confirmations: Consecutive divergent checks before alerting (env: POSITION_CHECK_CONFIRMATIONS)
+            freeze_on_divergence: Freeze entries on diverged symbols (env: POSITION_CHECK_FREEZE, default off)
+            interval: Seconds between background checks (env: POSITION_CHECK_INTERVAL)
+        """
+        self.tracker = tracker
+        self.fetch_positions = fetch_positions
+        self.symbol_resolver = symbol_resolver
+        self.alert_callback = alert_callback
+        self.tolerance = tolerance if tolerance is not None else int(os.getenv('POSITION_CHECK_TOLERANCE', '0'))
+        self.confirmations = max(1, confirmations if confirmations is not None
+                                 else int(os.getenv('POSITION_CHECK_CONFIRMATIONS', '2')))
+        self.freeze_on_divergence = (freeze_on_divergence if freeze_on_divergence is not None
+                                     else env_bool('POSITION_CHECK_FREEZE', 'false'))
+        self.interval = interval if interval is not None else float(os.getenv('POSITION_CHECK_INTERVAL', '60'))
+
+        self._pending: Dict[Tuple[str, str], PositionDivergence] = {}  # (account, symbol) -> divergence
+        self._alerted: Set[Tuple[str, str]] = set()
+        self.frozen_symbols: Dict[str, PositionDivergence] = {}
+        self.checks_run = 0
+        self.check_failures = 0
+        self.alerts_raised = 0
+        self._task: Optional[asyncio.Task] = None
+        self._running = False
+
+    async def check(self, account_id: str) -> List[PositionDivergence]:
+        """
+        Run one consistency check for an account.
+
+        The first check for an unseeded account seeds the ledger from the broker.
+
+        Returns:
+            List[PositionDivergence]: Divergences currently exceeding tolerance
+        """
+        account_key = str(account_id)
+        try:
+            broker_positions = net_broker_positions(await self.fetch_positions(account_key), self.symbol_resolver)
+        except Exception as e:
+            # Never compare against an empty list from a failed request
+            self.check_failures += 1
+            logger.warning(f"⚠️  Position consistency check skipped for {account_key}: {e}")
+            return []
+
+        self.checks_run += 1
+        if not self.tracker.has_account(account_key):
+            self.tracker.set_positions(account_key, broker_positions)
+            logger.info(f"📒 Seeded local position ledger for {account_key}: {broker_positions or 'flat'}")
+            return []
+
+        local_positions = self.tracker.get_positions(account_key)
+        now = datetime.now(timezone.utc).isoformat()
+        divergences: List[PositionDivergence] = []
+
+        for symbol in sorted(set(local_positions) | set(broker_positions)):
+            key = (account_key, symbol)
+            local_qty = local_positions.get(symbol, 0)
+            broker_qty = broker_positions.get(symbol, 0)
+            difference = broker_qty - local_qty
+
+            if abs(difference) <= self.tolerance:
+                if key in self._pending:
+                    logger.info(f"✅ Position for {symbol} back in sync (local={local_qty}, broker={broker_qty})")
+                self._pending.pop(key, None)
+                self._alerted.discard(key)
+                continue
+
+            divergence = self._pending.get(key)
+            if divergence is None:
+                divergence = PositionDivergence(account_key, symbol, local_qty, broker_qty, difference, 0, now)
+                self._pending[key] = divergence
+            divergence.local_qty = local_qty
+            divergence.broker_qty = broker_qty
+            divergence.difference = difference
+            divergence.consecutive_checks += 1
+            divergences.append(divergence)
+
+            if divergence.consecutive_checks >= self.confirmations and key not in self._alerted:
+                await self._raise_alert(divergence)
+
+        # Drop pending entries for symbols that vanished from both sides
+        for key in [k for k in self._pending if k[0] == account_key and k[1] not in local_positions
+                    and k[1] not in broker_positions]:
+            self._pending.pop(key, None)
+            self._alerted.discard(key)
+
+        return divergences
+
+    async def _raise_alert(self, divergence: PositionDivergence) -> None:
+        """Alert (and optionally freeze) once per divergence episode."""
+        self._alerted.add((divergence.account_id, divergence.symbol))
+        self.alerts_raised += 1
+        if self.freeze_on_divergence:
+            divergence.frozen = True
+            self.frozen_symbols[divergence.symbol] = divergence
+        logger.error(
+            f"🚨 Position divergence on {divergence.symbol} (account {divergence.account_id}): "
+            f"local={divergence.local_qty}, broker={divergence.broker_qty} "
+            f"after {divergence.consecutive_checks} checks"
+            f"{' - symbol frozen' if divergence.frozen else ''}"
+        )
+        if self.alert_callback:
+            try:
+                result = self.alert_callback(divergence)
+                if inspect.isawaitable(result):
+                    await result
+            except Exception as e:
+                logger.error(f"❌ Position divergence alert callback failed: {e}")
+
+    def is_frozen(self, symbol: str) -> bool:
+        """Whether new entries on a symbol are frozen."""
+        return symbol.upper() in self.frozen_symbols
+
+    def unfreeze(self, symbol: str, resync_account: Optional[str] = None, broker_qty: Optional[int] = None) -> bool:
+        """
+        Unfreeze a symbol after review.
+
+        Args:
+            symbol: Trading symbol
+            resync_account: Optionally reset the local ledger for this account/symbol
+            broker_qty: Quantity to resync to (defaults to the last broker quantity seen)
+
+        Returns:
+            bool: True if the symbol was frozen
+        """
+        symbol_key = symbol.upper()
+        divergence = self.frozen_symbols.pop(symbol_key, None)
+        if divergence is None:
+            return False
+        if resync_account is not None:
+            positions = self.tracker.get_positions(resync_account)
+            positions[symbol_key] = broker_qty if broker_qty is not None else divergence.broker_qty
+            self.tracker.set_positions(resync_account, positions)
+        key = (divergence.account_id, symbol_key)
+        self._pending.pop(key, None)
+        self._alerted.discard(key)
+        logger.info(f"🔓 Unfroze {symbol_key}")
+        return True
+
+    async def start(self, account_id_provider: Callable[[], Optional[str]]):
+        """
+        Start the background check loop.
+
+        Args:
+            account_id_provider: Returns the account to check (None skips the cycle)
+        """
+        if self._running:
+            logger.warning("⚠️  Position consistency checker already running")
+            return
+        self._running = True
+        self._task = asyncio.create_task(self._check_loop(account_id_provider))
+        logger.info(f"✅ Position consistency checker started (every {self.interval}s, tolerance {self.tolerance})")
+
+    async def stop(self):
+        """Stop the background check loop."""
+        self._running = False
+        if self._task:
+            self._task.cancel()
+            try:
+                await self._task
+            except asyncio.CancelledError:
+                pass
+            self._task = None
+
+    async def _check_loop(self, account_id_provider: Callable[[], Optional[str]]):
+        while self._running:
+            try:
+                account_id = account_id_provider()
+                if account_id:
+                    await self.check(account_id)
+            except asyncio.CancelledError:
+                break
+            except Exception as e:
+                logger.error(f"❌ Position consistency loop error: {e}")
+            await asyncio.sleep(self.interval)
+
+    def get_status(self) -> Dict:
+        """Get checker status for dashboards/APIs."""
+        return {
+            "checks_run": self.checks_run,
+            "check_failures": self.check_failures,
+            "alerts_raised": self.alerts_raised,
+            "tolerance": self.tolerance,
+            "confirmations": self.confirmations,
+            "freeze_on_divergence": self.freeze_on_divergence,
+            "frozen_symbols": {symbol: d.to_dict() for symbol, d in self.frozen_symbols.items()},
+            "pending": [d.to_dict() for d in self._pending.values()],
+        }
@@ -0,0 +1,467 @@
+"""
+Order and Position Reconciliation
+
+Periodically compares what the broker reports (open orders and positions
+over REST) with the bot's local state (PositionTracker, OrderTracker) and
+with the database view of positions (the latest journaled snapshot plus the
+fills journaled since). Every run produces a ReconciliationReport listing
+the discrepancies; reports are kept in memory, passed to an optional
+callback and served by the API.
+
+With auto-correction on, local state (and the database, by journaling a
+fresh position snapshot) is reset to what the broker reports, but only for
+discrepancies that persisted for several consecutive runs: a fill that is
+still in flight must not be "corrected" away.
+
+Discrepancy kinds:
+- position: local ledger net position differs from the broker's
+- db_position: database position differs from the broker's
+- unknown_order: the broker has a working order the bot does not track
+- missing_order: the bot tracks a working order the broker no longer has
+- order_mismatch: symbol, side, size or price of a working order differ
+
+Features:
+- Local working-order ledger fed from placements, user hub updates and fills
+- Confirmation over N consecutive runs before correcting
+- Report history for dashboards and post-mortems
+- Background loop (like PositionConsistencyChecker)
+
+Configuration:
+- RECONCILE_ENABLED: Run the background loop (default false)
+- RECONCILE_INTERVAL: Seconds between runs (default 300)
+- RECONCILE_AUTO_CORRECT: Reset local state and the database to the broker's (default false)
+- RECONCILE_CONFIRMATIONS: Consecutive runs a discrepancy must persist before it is corrected (default 2)
+- RECONCILE_HISTORY: Reports kept in memory (default 50)
+"""
+
+import asyncio
+import inspect
+import logging
+import os
+import time
+from collections import deque
+from dataclasses import asdict, dataclass, field
+from datetime import datetime, timezone
+from threading import Lock
+from typing import Any, Awaitable, Callable, Deque, Dict, List, Optional, Tuple
+
+from core.position_reconciler import PositionTracker, net_broker_positions
+from core.time_in_force import time_in_force_name
+from infrastructure.config import env_bool
+
+logger = logging.getLogger(__name__)
+
+# Gateway OrderStatus values of a working order (open, pending)
+WORKING_ORDER_STATUSES = (1, 6)
+ORDER_FIELDS = ('symbol', 'side', 'size', 'limit_price', 'stop_price')
+
+
+def _side(value: Any) -> Optional[str]:
+    """BUY/SELL of a gateway side code (0 = bid/buy, 1 = ask/sell) or a side string."""
+    if value is None or value == '':
+        return None
+    if isinstance(value, str) and not value.isdigit():
+        return 'BUY' if value.upper() in ('BUY', 'BID', 'LONG') else 'SELL'
+    return 'BUY' if int(value) == 0 else 'SELL'
+
+
+def _price(value: Any) -> Optional[float]:
+    return float(value) if value is not None else None
+
+
+class OrderTracker:
+    """
+    Local working-order ledger per account.
+
+    Fed with gateway order dicts (placements, user hub updates, fills);
+    orders leave the ledger when they reach a terminal status.
+    """
+
+    def __init__(self, symbol_resolver: Optional[Callable[[str], str]] = None):
+        """
+        Initialize order tracker.
+
+        Args:
+            symbol_resolver: Maps contractId to symbol (defaults to CON.F.US.MNQ.Z25 -> MNQ)
+        """
+        self.symbol_resolver = symbol_resolver
+        self._orders: Dict[str, Dict[str, Dict]] = {}  # {account_id: {order_id: order}}
+        self._lock = Lock()
+
+    def normalize(self, order: Dict) -> Dict:
+        """Comparable form of a gateway order (id, symbol, side, size, prices, time in force, status)."""
+        symbol = order.get('symbol')
+        contract_id = str(order.get('contractId') or '')
+        if not symbol and contract_id:
+            symbol = self.symbol_resolver(contract_id) if self.symbol_resolver else \
+                contract_id.split('.')[-2] if '.' in contract_id else contract_id
+        status = order.get('status', 1)
+        return {
+            'order_id': str(order.get('id') or order.get('orderId') or order.get('order_id') or ''),
+            'symbol': str(symbol).upper() if symbol else None,
+            'side': _side(order.get('side')),
+            'size': int(order['size']) if order.get('size') is not None else None,
+            'limit_price': _price(order.get('limitPrice', order.get('limit_price'))),
+            'stop_price': _price(order.get('stopPrice', order.get('stop_price'))),
+            'time_in_force': time_in_force_name(order.get('timeInForce', order.get('time_in_force'))),
+            'status': int(status) if str(status).isdigit() else status,
+        }
+
+    def apply_update(self, account_id: str, order: Dict) -> bool:
+        """
+        Apply an order update (working orders are added/replaced, terminal ones removed).
+
+        Returns:
+            bool: True if the order is working after the update
+        """
+        entry = self.normalize(order)
+        if not entry['order_id']:
+            return False
+        working = entry['status'] in WORKING_ORDER_STATUSES
+        with self._lock:
+            orders = self._orders.setdefault(str(account_id), {})
+            if working:
+                known = orders.get(entry['order_id'], {})
+                # Placements carry fewer fields than gateway updates: keep what is already known
+                orders[entry['order_id']] = {k: v if v is not None else known.get(k) for k, v in entry.items()}
+            else:
+                orders.pop(entry['order_id'], None)
+        return working
+
+    def set_orders(self, account_id: str, orders: List[Dict]) -> None:
+        """Replace the ledger for an account (seed or resync from the broker)."""
+        entries = [self.normalize(order) for order in orders]
+        with self._lock:
+            self._orders[str(account_id)] = {
+                e['order_id']: e for e in entries if e['order_id'] and e['status'] in WORKING_ORDER_STATUSES
+            }
+
+    def remove(self, account_id: str, order_id: str) -> None:
+        with self._lock:
+            self._orders.get(str(account_id), {}).pop(str(order_id), None)
+
+    def has_account(self, account_id: str) -> bool:
+        """Whether the ledger has been seeded for an account."""
+        with self._lock:
+            return str(account_id) in self._orders
+
+    def get_orders(self, account_id: str) -> Dict[str, Dict]:
+        """Working orders of an account by order ID."""
+        with self._lock:
+            return {order_id: dict(order) for order_id, order in self._orders.get(str(account_id), {}).items()}
+
+
+@dataclass
+class Discrepancy:
+    """One difference between broker state and local or database state."""
+    kind: str
+    account_id: str
+    symbol: Optional[str] = None
+    order_id: Optional[str] = None
+    local: Any = None
+    broker: Any = None
+    consecutive_runs: int = 1
+    first_detected: str = ''
+    corrected: bool = False
+
+    @property
+    def key(self) -> Tuple[str, str, str]:
+        return self.account_id, self.kind, self.order_id or self.symbol or ''
+
+    def to_dict(self) -> Dict:
+        return asdict(self)
+
+
+@dataclass
+class ReconciliationReport:
+    """Outcome of one reconciliation run."""
+    account_id: str
+    started_at: str
+    duration_ms: float = 0.0
+    broker_positions: Dict[str, int] = field(default_factory=dict)
+    local_positions: Dict[str, int] = field(default_factory=dict)
+    db_positions: Optional[Dict[str, int]] = None  # None without a database baseline
+    broker_orders: int = 0
+    local_orders: int = 0
+    discrepancies: List[Discrepancy] = field(default_factory=list)
+    corrected: int = 0
+    error: Optional[str] = None
+
+    @property
+    def in_sync(self) -> bool:
+        return self.error is None and not self.discrepancies
+
+    def to_dict(self) -> Dict:
+        data = asdict(self)
+        data['in_sync'] = self.in_sync
+        return data
+
+
+class Reconciler:
+    """
+    Compares broker orders/positions with local and database state.
+
+    Usage:
+        reconciler = Reconciler(bot.position_tracker, bot.order_tracker,
+                                fetch_positions=bot._fetch_positions_strict,
+                                fetch_orders=bot._fetch_open_orders_strict, db=bot.db)
+        report = await reconciler.run(account_id)
+    """
+
+    def __init__(self, position_tracker: PositionTracker, order_tracker: OrderTracker,
+                 fetch_positions: Callable[[str], Awaitable[List[Dict]]],
+                 fetch_orders: Callable[[str], Awaitable[List[Dict]]],
+                 db: Any = None,
+                 symbol_resolver: Optional[Callable[[str], str]] = None,
+                 report_callback: Optional[Callable[[ReconciliationReport], Any]] = None,
+                 auto_correct: Optional[bool] = None,
+                 confirmations: Optional[int] = None,
+                 interval: Optional[float] = None,
+                 history: Optional[int] = None):
+        """
+        Initialize reconciler.
+
+        Args:
+            position_tracker: Local position ledger
+            order_tracker: Local working-order ledger
+            fetch_positions: Async callable returning broker positions (must raise on API failure)
+            fetch_orders: Async callable returning broker open orders (must raise on API failure)
+            db: DatabaseManager for the database position view (optional)
+            symbol_resolver: Maps contractId to symbol
+            report_callback: Called (sync or async) with every report that has discrepancies
+            auto_correct: Reset local state and the database to the broker's (env: RECONCILE_AUTO_CORRECT)
+            confirmations: Consecutive runs before correcting (env: RECONCILE_CONFIRMATIONS)
+            interval: Seconds between background runs (env: RECONCILE_INTERVAL)
+            history: Reports kept in memory (env: RECONCILE_HISTORY)
+        """
+        self.position_tracker = position_tracker
+        self.order_tracker = order_tracker
+        self.fetch_positions = fetch_positions
+        self.fetch_orders = fetch_orders
+        self.db = db
+        self.symbol_resolver = symbol_resolver
+        self.report_callback = report_callback
+        self.auto_correct = auto_correct if auto_correct is not None else env_bool('RECONCILE_AUTO_CORRECT', 'false')
+        self.confirmations = max(1, confirmations if confirmations is not None
+                                 else int(os.getenv('RECONCILE_CONFIRMATIONS', '2')))
+        self.interval = interval if interval is not None else float(os.getenv('RECONCILE_INTERVAL', '300'))
+        self.reports: Deque[ReconciliationReport] = deque(
+            maxlen=history if history is not None else int(os.getenv('RECONCILE_HISTORY', '50')))
+
+        self._open: Dict[Tuple[str, str, str], Discrepancy] = {}  # Discrepancies seen on the last run
+        self._run_lock = asyncio.Lock()
+        self._task: Optional[asyncio.Task] = None
+        self._running = False
+        self.runs = 0
+        self.run_failures = 0
+        self.discrepancies_found = 0
+        self.corrections = 0
+
+    # ---------------------------
+    # Comparison
+    # ---------------------------
+    def _compare_positions(self, report: ReconciliationReport, kind: str, other: Dict[str, int]) -> None:
+        for symbol in sorted(set(other) | set(report.broker_positions)):
+            local_qty, broker_qty = other.get(symbol, 0), report.broker_positions.get(symbol, 0)
+            if local_qty != broker_qty:
+                report.discrepancies.append(Discrepancy(kind, report.account_id, symbol=symbol,
+                                                        local=local_qty, broker=broker_qty))
+
+    def _compare_orders(self, report: ReconciliationReport, local: Dict[str, Dict],
+                        broker: Dict[str, Dict]) -> None:
+        for order_id in sorted(set(local) | set(broker)):
+            mine, theirs = local.get(order_id), broker.get(order_id)
+            if mine is None:
+                report.discrepancies.append(Discrepancy('unknown_order', report.account_id, theirs['symbol'],
+                                                        order_id, None, theirs))
+            elif theirs is None:
+                report.discrepancies.append(Discrepancy('missing_order', report.account_id, mine['symbol'],
+                                                        order_id, mine, None))
+            elif any(mine[f] is not None and theirs[f] is not None and mine[f] != theirs[f] for f in ORDER_FIELDS):
+                report.discrepancies.append(Discrepancy('order_mismatch', report.account_id, theirs['symbol'],
+                                                        order_id, mine, theirs))
+
+    def _db_positions(self, account_id: str, broker_positions: Dict[str, int]) -> Optional[Dict[str, int]]:
+        """Database position view, seeding a baseline snapshot from the broker if there is none."""
+        positions = self.db.get_journal_net_positions(account_id)
+        if positions is None and broker_positions:
+            self._journal_snapshot(account_id, broker_positions, {})
+            logger.info(f"📒 Seeded journaled positions for {account_id}: {broker_positions}")
+        return positions
+
+    def _journal_snapshot(self, account_id: str, positions: Dict[str, int], previous: Dict[str, int]) -> None:
+        """Journal the broker's positions as the account's net snapshot (flattened symbols as 0)."""
+        rows = [{'symbol': symbol, 'quantity': positions.get(symbol, 0), 'source': 'reconciliation'}
+                for symbol in sorted(set(positions) | set(previous))]
+        self.db.record_positions(account_id, rows)
+
+    # ---------------------------
+    # Running
+    # ---------------------------
+    async def run(self, account_id: str) -> ReconciliationReport:
+        """
+        Run one reconciliation for an account.
+
+        The first run for an account seeds unseeded local ledgers from the broker.
+
+        Returns:
+            ReconciliationReport: Discrepancies found (report.error is set if broker state could not be loaded)
+        """
+        account_key = str(account_id)
+        started = time.perf_counter()
+        report = ReconciliationReport(account_key, datetime.now(timezone.utc).isoformat())
+        async with self._run_lock:
+            try:
+                await self._reconcile(report)
+            except Exception as e:
+                # Never compare against empty lists from a failed request
+                self.run_failures += 1
+                report.error = f"{type(e).__name__}: {e}"
+                logger.warning(f"⚠️  Reconciliation skipped for {account_key}: {report.error}")
+            report.duration_ms = round((time.perf_counter() - started) * 1000, 2)
+            self.reports.append(report)
+        if report.discrepancies and self.report_callback:
+            try:
+                result = self.report_callback(report)
+                if inspect.isawaitable(result):
+                    await result
+            except Exception as e:
+                logger.error(f"❌ Reconciliation report callback failed: {e}")
+        return report
+
+    async def _reconcile(self, report: ReconciliationReport) -> None:
+        account_id = report.account_id
+        broker_positions = net_broker_positions(await self.fetch_positions(account_id), self.symbol_resolver)
+        broker_orders = {}
+        for order in await self.fetch_orders(account_id):
+            entry = self.order_tracker.normalize(order)
+            if entry['order_id'] and entry['status'] in WORKING_ORDER_STATUSES:
+                broker_orders[entry['order_id']] = entry
+        self.runs += 1
+        report.broker_positions = broker_positions
+        report.broker_orders = len(broker_orders)
+
+        if not self.position_tracker.has_account(account_id):
+            self.position_tracker.set_positions(account_id, broker_positions)
+        if not self.order_tracker.has_account(account_id):
+            self.order_tracker.set_orders(account_id, list(broker_orders.values()))
+        report.local_positions = self.position_tracker.get_positions(account_id)
+        local_orders = self.order_tracker.get_orders(account_id)
+        report.local_orders = len(local_orders)
+
+        self._compare_positions(report, 'position', report.local_positions)
+        if self.db is not None:
+            report.db_positions = await asyncio.to_thread(self._db_positions, account_id, broker_positions)
+            if report.db_positions is not None:
+                self._compare_positions(report, 'db_position', report.db_positions)
+        self._compare_orders(report, local_orders, broker_orders)
+
+        # Carry confirmation counts over from the previous run; forget what was resolved
+        now = report.started_at
+        previous = {k: d for k, d in self._open.items() if k[0] == account_id}
+        for discrepancy in report.discrepancies:
+            seen = previous.get(discrepancy.key)
+            discrepancy.first_detected = seen.first_detected if seen else now
+            discrepancy.consecutive_runs = seen.consecutive_runs + 1 if seen else 1
+            if not seen:
+                self.discrepancies_found += 1
+        self._open = {k: d for k, d in self._open.items() if k[0] != account_id}
+        self._open.update((d.key, d) for d in report.discrepancies)
+
+        if report.discrepancies:
+            logger.warning(f"⚠️  Reconciliation found {len(report.discrepancies)} discrepancies for {account_id}: "
+                           + ", ".join(f"{d.kind} {d.order_id or d.symbol}" for d in report.discrepancies))
+        if self.auto_correct:
+            await self._correct(report)
+
+    async def _correct(self, report: ReconciliationReport) -> None:
+        """Reset confirmed discrepancies to the broker's state."""
+        confirmed = [d for d in report.discrepancies if d.consecutive_runs >= self.confirmations]
+        account_id = report.account_id
+        for discrepancy in confirmed:
+            if discrepancy.kind == 'position':
+                positions = self.position_tracker.get_positions(account_id)
+                positions[discrepancy.symbol] = discrepancy.broker
+                self.position_tracker.set_positions(account_id, positions)
+            elif discrepancy.kind == 'missing_order':
+                self.order_tracker.remove(account_id, discrepancy.order_id)
+            elif discrepancy.kind in ('unknown_order', 'order_mismatch'):
+                self.order_tracker.apply_update(account_id, dict(discrepancy.broker, id=discrepancy.order_id))
+            else:
+                continue
+            discrepancy.corrected = True
+        db_fixes = [d for d in confirmed if d.kind == 'db_position']
+        if db_fixes:
+            try:
+                await asyncio.to_thread(self._journal_snapshot, account_id, report.broker_positions,
+                                        report.db_positions or {})
+                for discrepancy in db_fixes:
+                    discrepancy.corrected = True
+            except Exception as e:
+                logger.error(f"❌ Failed to journal reconciled positions for {account_id}: {e}")
+        corrected = [d for d in confirmed if d.corrected]
+        for discrepancy in corrected:
+            self._open.pop(discrepancy.key, None)
+        report.corrected = len(corrected)
+        self.corrections += len(corrected)
+        if corrected:
+            logger.warning(f"🔧 Reconciliation corrected {len(corrected)} discrepancies for {account_id} "
+                           f"to the broker's state")
+
+    async def start(self, account_id_provider: Callable[[], Optional[str]]):
+        """
+        Start the background reconciliation loop.
+
+        Args:
+            account_id_provider: Returns the account to reconcile (None skips the cycle)
+        """
+        if self._running:
+            logger.warning("⚠️  Reconciler already running")
+            return
+        self._running = True
+        self._task = asyncio.create_task(self._loop(account_id_provider))
+        logger.info(f"✅ Reconciler started (every {self.interval}s, auto-correct "
+                    f"{'on' if self.auto_correct else 'off'})")
+
+    async def stop(self):
+        """Stop the background loop."""
+        self._running = False
+        if self._task:
+            self._task.cancel()
+            try:
+                await self._task
+            except asyncio.CancelledError:
+                pass
+            self._task = None
+
+    async def _loop(self, account_id_provider: Callable[[], Optional[str]]):
+        while self._running:
+            try:
+                account_id = account_id_provider()
+                if account_id:
+                    await self.run(account_id)
+            except asyncio.CancelledError:
+                break
+            except Exception as e:
+                logger.error(f"❌ Reconciliation loop error: {e}")
+            await asyncio.sleep(self.interval)
+
+    @property
+    def last_report(self) -> Optional[ReconciliationReport]:
+        return self.reports[-1] if self.reports else None
+
+    def get_status(self) -> Dict:
+        """Reconciler status for dashboards/APIs."""
+        last = self.last_report
+        return {
+            "running": self._running,
+            "auto_correct": self.auto_correct,
+            "confirmations": self.confirmations,
+            "interval": self.interval,
+            "runs": self.runs,
+            "run_failures": self.run_failures,
+            "discrepancies_found": self.discrepancies_found,
+            "corrections": self.corrections,
+            "open_discrepancies": [d.to_dict() for d in self._open.values()],
+            "last_report": last.to_dict() if last else None,
+        }
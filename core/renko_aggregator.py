@@ -0,0 +1,276 @@
+"""
+Renko Brick Aggregator
+
+Converts ticks or bar closes into renko bricks. A brick completes when price
+moves one brick size beyond the last brick; reversals require the price to
+clear the opposite side of the last brick (the classic two-brick reversal).
+
+Brick size can be:
+- Fixed in price points
+- A number of instrument ticks (brick_ticks x tick_size)
+- ATR-based (ATR of fed bars x multiplier), recomputed as bars arrive
+
+Each brick tracks the high/low reached while it formed (wicks) and is emitted
+through an optional callback and kept in per-symbol history.
+"""
+
+import logging
+import os
+import threading
+from collections import defaultdict, deque
+from dataclasses import dataclass, asdict
+from datetime import datetime, timezone
+from typing import Any, Callable, Dict, List, Optional
+
+logger = logging.getLogger(__name__)
+
+
+@dataclass
+class RenkoBrick:
+    """A completed renko brick."""
+    symbol: str
+    timestamp: datetime      # Time the brick completed
+    open: float
+    close: float
+    high: float              # Includes upper wick on down bricks
+    low: float               # Includes lower wick on up bricks
+    direction: int           # 1 = up brick, -1 = down brick
+    brick_size: float
+    volume: int = 0
+    tick_count: int = 0
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        data = asdict(self)
+        data['timestamp'] = self.timestamp.isoformat()
+        return data
+
+
+@dataclass
+class _RenkoState:
+    """Per-symbol brick formation state."""
+    brick_high: float        # Top of the last brick (or anchor price)
+    brick_low: float         # Bottom of the last brick (or anchor price)
+    wick_high: float
+    wick_low: float
+    volume: int = 0
+    tick_count: int = 0
+
+
+class RenkoAggregator:
+    """
+    Builds renko bricks from a price stream.
+
+    Features:
+    - Fixed, tick-based or ATR-based brick size
+    - Wick tracking per brick
+    - Multiple bricks from a single large move
+    - Brick completion callback and per-symbol history
+    - Thread-safe (ticks may arrive on the SignalR thread)
+
+    Usage:
+        renko = RenkoAggregator(brick_ticks=10, tick_size=0.25, brick_callback=on_brick)
+        renko.add_tick('MNQ', 15000.25, volume=2)
+
+        # Or ATR-based, driven by completed bars from BarAggregator
+        renko = RenkoAggregator(atr_period=14, atr_multiplier=0.5)
+        bar_aggregator = BarAggregator(default_timeframes=['5m'], bar_close_callback=renko.add_bar)
+    """
+
+    def __init__(self, brick_size: Optional[float] = None,
+                 brick_ticks: Optional[int] = None,
+                 tick_size: Optional[float] = None,
+                 atr_period: Optional[int] = None,
+                 atr_multiplier: float = 1.0,
+                 brick_callback: Optional[Callable[[RenkoBrick], None]] = None,
+                 max_history: Optional[int] = None):
+        """
+        Initialize renko aggregator.
+
+        Args:
+            brick_size: Fixed brick size in price points
+            brick_ticks: Brick size in ticks (requires tick_size)
+            tick_size: Instrument tick size used with brick_ticks
+            atr_period: Use ATR of fed bars for brick size (bricks start once ATR is available)
+            atr_multiplier: Multiplier applied to ATR
+            brick_callback: Function called with each completed RenkoBrick
+            max_history: Bricks kept per symbol (env: RENKO_HISTORY_SIZE)
+        """
+        if brick_size is None and brick_ticks is not None:
+            if not tick_size or tick_size <= 0:
+                raise ValueError("brick_ticks requires a positive tick_size")
+            brick_size = brick_ticks * tick_size
+        if brick_size is None and not atr_period:
+            raise ValueError("RenkoAggregator needs brick_size, brick_ticks or atr_period")
+        if brick_size is not None and brick_size <= 0:
+            raise ValueError(f"brick_size must be positive, got {brick_size}")
+        if atr_period is not None and atr_period < 1:
+            raise ValueError(f"atr_period must be >= 1, got {atr_period}")
+
+        self.fixed_brick_size = brick_size
+        self.atr_period = atr_period
+        self.atr_multiplier = atr_multiplier
+        self.brick_callback = brick_callback
+        if max_history is None:
+            max_history = int(os.getenv('RENKO_HISTORY_SIZE', '500'))
+        self.max_history = max_history
+
+        self._states: Dict[str, _RenkoState] = {}
+        self._true_ranges: Dict[str, deque] = defaultdict(lambda: deque(maxlen=self.atr_period or 1))
+        self._prev_close: Dict[str, float] = {}
+        self.brick_history: Dict[str, deque] = defaultdict(lambda: deque(maxlen=self.max_history))
+        self.bricks_completed = 0
+        self._lock = threading.RLock()
+
+    def get_brick_size(self, symbol: str) -> Optional[float]:
+        """Current brick size for a symbol (None while ATR is warming up)."""
+        if not self.atr_period:
+            return self.fixed_brick_size
+        ranges = self._true_ranges.get(symbol.upper())
+        if not ranges or len(ranges) < self.atr_period:
+            return self.fixed_brick_size
+        atr = sum(ranges) / len(ranges)
+        return atr * self.atr_multiplier if atr > 0 else self.fixed_brick_size
+
+    def add_tick(self, symbol: str, price: float, volume: int = 0,
+                 timestamp: Optional[datetime] = None) -> List[RenkoBrick]:
+        """
+        Add a trade/quote price.
+
+        Args:
+            symbol: Trading symbol
+            price: Trade price
+            volume: Trade volume
+            timestamp: Tick timestamp (defaults to now)
+
+        Returns:
+            List[RenkoBrick]: Bricks completed by this tick (may be several)
+        """
+        if timestamp is None:
+            timestamp = datetime.now(timezone.utc)
+        symbol_key = symbol.upper()
+
+        with self._lock:
+            bricks = self._process_price(symbol_key, price, volume, timestamp)
+        for brick in bricks:
+            self._emit_brick(brick)
+        return bricks
+
+    def add_bar(self, bar) -> List[RenkoBrick]:
+        """
+        Add a completed bar (any object with symbol/timestamp/high/low/close/volume).
+
+        Updates the ATR used for brick sizing, then feeds the bar close.
+        Signature matches BarAggregator's bar_close_callback.
+
+        Returns:
+            List[RenkoBrick]: Bricks completed by this bar
+        """
+        symbol_key = bar.symbol.upper()
+        with self._lock:
+            if self.atr_period:
+                prev_close = self._prev_close.get(symbol_key)
+                true_range = bar.high - bar.low
+                if prev_close is not None:
+                    true_range = max(true_range, abs(bar.high - prev_close), abs(bar.low - prev_close))
+                self._true_ranges[symbol_key].append(true_range)
+            self._prev_close[symbol_key] = bar.close
+            bricks = self._process_price(symbol_key, bar.close, getattr(bar, 'volume', 0) or 0, bar.timestamp,
+                                         wick_high=bar.high, wick_low=bar.low)
+        for brick in bricks:
+            self._emit_brick(brick)
+        return bricks
+
+    def _process_price(self, symbol_key: str, price: float, volume: int, timestamp: datetime,
+                       wick_high: Optional[float] = None, wick_low: Optional[float] = None) -> List[RenkoBrick]:
+        """Advance brick state for one price (caller holds _lock)."""
+        wick_high = price if wick_high is None else max(wick_high, price)
+        wick_low = price if wick_low is None else min(wick_low, price)
+
+        state = self._states.get(symbol_key)
+        if state is None:
+            # First price anchors the brick grid
+            self._states[symbol_key] = _RenkoState(brick_high=price, brick_low=price,
+                                                   wick_high=wick_high, wick_low=wick_low,
+                                                   volume=volume, tick_count=1)
+            return []
+
+        state.wick_high = max(state.wick_high, wick_high)
+        state.wick_low = min(state.wick_low, wick_low)
+        state.volume += volume
+        state.tick_count += 1
+
+        size = self.get_brick_size(symbol_key)
+        if not size:
+            return []
+
+        bricks: List[RenkoBrick] = []
+        while price >= state.brick_high + size:
+            bricks.append(self._make_brick(symbol_key, state, state.brick_high, state.brick_high + size, size, timestamp))
+        while price <= state.brick_low - size:
+            bricks.append(self._make_brick(symbol_key, state, state.brick_low, state.brick_low - size, size, timestamp))
+
+        if bricks:
+            state.wick_high = price
+            state.wick_low = price
+            state.volume = 0
+            state.tick_count = 0
+            self.brick_history[symbol_key].extend(bricks)
+            self.bricks_completed += len(bricks)
+        return bricks
+
+    def _make_brick(self, symbol_key: str, state: _RenkoState, open_price: float, close_price: float,
+                    size: float, timestamp: datetime) -> RenkoBrick:
+        """Build a brick and move the grid to it (caller holds _lock)."""
+        direction = 1 if close_price > open_price else -1
+        body_high = max(open_price, close_price)
+        body_low = min(open_price, close_price)
+        brick = RenkoBrick(
+            symbol=symbol_key,
+            timestamp=timestamp,
+            open=open_price,
+            close=close_price,
+            # Up bricks wick below (pullback), down bricks wick above (rally)
+            high=body_high if direction > 0 else max(body_high, state.wick_high),
+            low=min(body_low, state.wick_low) if direction > 0 else body_low,
+            direction=direction,
+            brick_size=size,
+            volume=state.volume,
+            tick_count=state.tick_count,
+        )
+        # Wicks and activity belong to the first brick of a multi-brick move
+        state.wick_high = body_high
+        state.wick_low = body_low
+        state.volume = 0
+        state.tick_count = 0
+        state.brick_high = body_high
+        state.brick_low = body_low
+        return brick
+
+    def _emit_brick(self, brick: RenkoBrick):
+        """Deliver a completed brick to the callback."""
+        logger.debug(f"🧱 Renko {brick.symbol} {'▲' if brick.direction > 0 else '▼'} {brick.close}")
+        if self.brick_callback:
+            try:
+                self.brick_callback(brick)
+            except Exception as e:
+                logger.error(f"Error in renko brick callback for {brick.symbol}: {e}")
+
+    def get_bricks(self, symbol: str, count: Optional[int] = None) -> List[RenkoBrick]:
+        """Get completed bricks for a symbol (oldest first)."""
+        with self._lock:
+            history = list(self.brick_history.get(symbol.upper(), ()))
+        return history[-count:] if count else history
+
+    def reset(self, symbol: Optional[str] = None):
+        """Clear brick state for one symbol or all symbols."""
+        with self._lock:
+            if symbol is None:
+                self._states.clear()
+                self._true_ranges.clear()
+                self._prev_close.clear()
+                self.brick_history.clear()
+                return
+            symbol_key = symbol.upper()
+            for store in (self._states, self._true_ranges, self._prev_close, self.brick_history):
+                store.pop(symbol_key, None)
@@ -0,0 +1,303 @@
+"""
+Tick/market replay engine.
+
+Replays recorded market data through the same components the live bot uses,
+for strategy debugging against real sessions:
+- BarAggregator: trades (and last-price quotes) are fed with their recorded
+  timestamps, so bars close exactly as they did live
+- Listeners: any number of market event callbacks (strategy hooks, fill
+  simulators, recorders) receive every replayed event in time order
+
+Sources:
+- Event recordings: JSONL from EventExporter (.jsonl, .jsonl.gz, .jsonl.zst)
+- CSV tick files (.csv/.txt, any core.csv_io schema; symbol required)
+- Parquet: a ParquetStore root directory (symbol required)
+- Any iterable of MarketEvents or event dicts
+
+Playback speed is a multiple of real time (1.0 = as recorded, 10.0 = ten
+times faster); 0 replays as fast as possible. While running, playback can be
+paused, resumed, stepped event-by-event, re-timed and seeked to a timestamp.
+Seeking moves the replay position only; components keep their state, so seek
+backwards with a fresh aggregator when bars must be rebuilt.
+
+Recorded BarClosed/GapRepaired events are skipped by default because the
+replay aggregator produces its own.
+"""
+
+import asyncio
+import bisect
+import logging
+import time
+from datetime import datetime, timedelta
+from pathlib import Path
+from typing import Any, Callable, Dict, Iterable, List, Optional, Union
+
+from core.market_events import BarClosed, GapRepaired, MarketEvent, Quote, Trade, market_event_from_dict
+from core.replay_fixtures import EventSource, _as_utc, iter_recorded_events
+
+logger = logging.getLogger(__name__)
+
+RECORDING_SUFFIXES = ('.jsonl', '.gz', '.zst')
+CSV_SUFFIXES = ('.csv', '.txt')
+
+# Events dispatched between yields to the event loop in as-fast-as-possible mode
+_YIELD_EVERY = 1000
+
+
+def load_replay_events(source: EventSource, symbol: Optional[str] = None,
+                       csv_schema: Any = None, start: Optional[datetime] = None,
+                       end: Optional[datetime] = None) -> List[MarketEvent]:
+    """
+    Load and time-sort events from any supported source.
+
+    Args:
+        source: Recording path, CSV file, ParquetStore directory or iterable of events/dicts
+        symbol: Symbol for CSV and Parquet sources (also filters recordings when given)
+        csv_schema: core.csv_io schema name or CsvSchema for CSV sources
+        start: Drop events before this time
+        end: Drop events at or after this time
+
+    Returns:
+        List[MarketEvent]: Events sorted by timestamp (stable for equal timestamps)
+
+    Raises:
+        ValueError: Symbol missing for a CSV/Parquet source, or unsupported file type
+    """
+    events: List[MarketEvent] = []
+    path = Path(source) if isinstance(source, (str, Path)) else None
+    if path is not None and (path.is_dir() or path.suffix.lower() in CSV_SUFFIXES):
+        if not symbol:
+            raise ValueError(f"A symbol is required to replay {path}")
+        if path.is_dir():
+            from core.parquet_store import ParquetStore
+            ticks = ParquetStore(path).read_ticks(symbol, start, end)
+        else:
+            from core.csv_io import read_ticks_csv
+            ticks = read_ticks_csv(path, symbol, csv_schema)
+        events = [Trade(symbol=t['symbol'], timestamp=_as_utc(t['timestamp']), price=float(t['price']),
+                        size=int(t.get('size') or 0), side=t.get('side')) for t in ticks]
+    elif path is not None and path.suffix.lower() not in RECORDING_SUFFIXES:
+        raise ValueError(f"Unsupported replay source '{path}'. Use a JSONL recording, CSV file or Parquet store")
+    else:
+        wanted = symbol.upper() if symbol else None
+        for data in iter_recorded_events(source):
+            try:
+                event = market_event_from_dict(data)
+            except (KeyError, ValueError) as e:
+                logger.debug(f"Skipping unreadable recorded event: {e}")
+                continue
+            if wanted is None or event.symbol == wanted:
+                events.append(event)
+    if start is not None:
+        events = [e for e in events if _as_utc(e.timestamp) >= _as_utc(start)]
+    if end is not None:
+        events = [e for e in events if _as_utc(e.timestamp) < _as_utc(end)]
+    events.sort(key=lambda e: _as_utc(e.timestamp))
+    return events
+
+
+class ReplayEngine:
+    """
+    Replays recorded events at a controllable speed.
+
+    Usage:
+        engine = ReplayEngine('recordings/events-20251119-133000.jsonl.zst',
+                              bar_aggregator=BarAggregator(default_timeframes=['1m', '5m']),
+                              listeners=[strategy_hook, fill_simulator.on_market_event], speed=10)
+        task = engine.start()
+        engine.pause(); engine.seek(incident_time); engine.step(); engine.resume()
+        await task
+
+    Controls must be called from the event loop the engine runs on.
+    """
+
+    def __init__(self, source: Union[EventSource, List[MarketEvent]], bar_aggregator=None,
+                 listeners: Optional[Iterable[Callable[[MarketEvent], Any]]] = None,
+                 speed: float = 1.0, symbol: Optional[str] = None, csv_schema: Any = None,
+                 start: Optional[datetime] = None, end: Optional[datetime] = None,
+                 include_recorded_bars: bool = False, flush_bars_at_end: bool = True):
+        """
+        Initialize replay engine.
+
+        Args:
+            source: Anything load_replay_events accepts
+            bar_aggregator: BarAggregator fed with replayed trades/quotes
+            listeners: Callables invoked with every replayed event
+            speed: Real-time multiple; 0 = as fast as possible
+            symbol: Symbol for CSV/Parquet sources (filters recordings)
+            csv_schema: CSV schema for CSV sources
+            start: Replay window start
+            end: Replay window end (exclusive)
+            include_recorded_bars: Also replay recorded BarClosed/GapRepaired events
+            flush_bars_at_end: Close the last forming bars when the replay finishes
+        """
+        self.events = load_replay_events(source, symbol, csv_schema, start, end)
+        if not include_recorded_bars:
+            self.events = [e for e in self.events if not isinstance(e, (BarClosed, GapRepaired))]
+        self._timestamps = [_as_utc(e.timestamp) for e in self.events]
+        self.bar_aggregator = bar_aggregator
+        self.listeners: List[Callable[[MarketEvent], Any]] = list(listeners or [])
+        self.speed = max(0.0, float(speed))
+        self.flush_bars_at_end = flush_bars_at_end
+        self.position = 0
+        self.dispatched = 0
+        self.listener_errors = 0
+        self.paused = False
+        self.finished = False
+        self._stopped = False
+        self._pending_steps = 0
+        self._wake: Optional[asyncio.Event] = None
+        self._anchor_wall = 0.0
+        self._anchor_ts: Optional[datetime] = None
+        self._task: Optional[asyncio.Task] = None
+
+    def __len__(self) -> int:
+        return len(self.events)
+
+    def add_listener(self, callback: Callable[[MarketEvent], Any]) -> None:
+        """Add a replayed-event callback."""
+        self.listeners.append(callback)
+
+    @property
+    def current_time(self) -> Optional[datetime]:
+        """Recorded timestamp of the next event to replay (None when finished)."""
+        return self._timestamps[self.position] if self.position < len(self.events) else None
+
+    # ---------------------------
+    # Controls
+    # ---------------------------
+    def _signal(self) -> None:
+        self._anchor_ts = None  # Re-anchor timing at the next event
+        if self._wake is not None:
+            self._wake.set()
+
+    def pause(self) -> None:
+        """Pause playback after the current event."""
+        self.paused = True
+        self._signal()
+
+    def resume(self) -> None:
+        """Resume playback."""
+        self.paused = False
+        self._signal()
+
+    def step(self, count: int = 1) -> None:
+        """While paused, replay the next `count` events immediately."""
+        if not self.paused:
+            return
+        self._pending_steps += max(0, count)
+        self._signal()
+
+    def set_speed(self, speed: float) -> None:
+        """Change playback speed (0 = as fast as possible)."""
+        self.speed = max(0.0, float(speed))
+        self._signal()
+
+    def seek(self, timestamp: datetime) -> int:
+        """
+        Move playback to the first event at or after a timestamp.
+
+        Returns:
+            int: New position (len(self) if past the end)
+        """
+        self.position = bisect.bisect_left(self._timestamps, _as_utc(timestamp))
+        self._signal()
+        return self.position
+
+    def stop(self) -> None:
+        """Stop playback (run() returns)."""
+        self._stopped = True
+        self._signal()
+
+    # ---------------------------
+    # Playback
+    # ---------------------------
+    def start(self) -> asyncio.Task:
+        """Run the replay as a task on the current event loop."""
+        if self._task is None or self._task.done():
+            self._task = asyncio.ensure_future(self.run())
+        return self._task
+
+    async def run(self) -> int:
+        """
+        Replay until the end, or until stop().
+
+        Returns:
+            int: Events dispatched
+        """
+        self._wake = asyncio.Event()
+        self._stopped = False
+        self._anchor_ts = None
+        since_yield = 0
+        logger.info(f"▶️  Replaying {len(self.events) - self.position} events at "
+                    f"{'max' if not self.speed else f'{self.speed:g}x'} speed")
+        while not self._stopped and self.position < len(self.events):
+            if self.paused and self._pending_steps == 0:
+                await self._wait(None)
+                continue
+            stepping = self.paused
+            if not stepping and self.speed > 0:
+                delay = self._delay_until(self._timestamps[self.position])
+                if delay > 0:
+                    await self._wait(delay)
+                    if self._anchor_ts is None:
+                        continue  # A control changed timing or position; re-evaluate
+            index = self.position
+            self.position += 1
+            if stepping:
+                self._pending_steps -= 1
+            self._dispatch(self.events[index])
+            since_yield += 1
+            if since_yield >= _YIELD_EVERY or stepping:
+                since_yield = 0
+                await asyncio.sleep(0)
+        if self.position >= len(self.events):
+            self.finished = True
+            if self.flush_bars_at_end and self.bar_aggregator is not None and self._timestamps:
+                self.bar_aggregator.close_elapsed_bars(now=self._timestamps[-1] + timedelta(days=1))
+            logger.info(f"⏹️  Replay finished: {self.dispatched} events dispatched")
+        return self.dispatched
+
+    def _delay_until(self, event_ts: datetime) -> float:
+        now = time.monotonic()
+        if self._anchor_ts is None:
+            self._anchor_ts, self._anchor_wall = event_ts, now
+        target = self._anchor_wall + (event_ts - self._anchor_ts).total_seconds() / self.speed
+        return target - now
+
+    async def _wait(self, timeout: Optional[float]) -> None:
+        """Sleep until the timeout or a control change, whichever is first."""
+        self._wake.clear()
+        try:
+            await asyncio.wait_for(self._wake.wait(), timeout)
+        except asyncio.TimeoutError:
+            pass
+
+    def _dispatch(self, event: MarketEvent) -> None:
+        """Feed one event to the aggregator and listeners, isolating listener errors."""
+        if self.bar_aggregator is not None:
+            if isinstance(event, Trade):
+                self.bar_aggregator.add_quote(event.symbol, event.price, event.size, event.timestamp)
+            elif isinstance(event, Quote) and event.last is not None:
+                self.bar_aggregator.add_quote(event.symbol, event.last, event.volume or 0, event.timestamp)
+        for listener in self.listeners:
+            try:
+                listener(event)
+            except Exception as e:
+                self.listener_errors += 1
+                logger.debug(f"Replay listener error ({event.type} {event.symbol}): {e}")
+        self.dispatched += 1
+
+    def get_status(self) -> Dict[str, Any]:
+        """Playback position and counters."""
+        current = self.current_time
+        return {
+            "position": self.position,
+            "total": len(self.events),
+            "current_time": current.isoformat() if current else None,
+            "speed": self.speed,
+            "paused": self.paused,
+            "finished": self.finished,
+            "dispatched": self.dispatched,
+            "listener_errors": self.listener_errors,
+        }
@@ -0,0 +1,230 @@
+"""
+Replay fixture generator for reproducing production incidents in tests.
+
+Cuts a short time slice (e.g., 5 minutes around a bug) out of a recorded
+session and writes it as a compact, self-contained fixture file that can be
+committed next to a test and replayed deterministically.
+
+Recorded sessions are JSONL files with one MarketEvent dict per line (the
+MarketEvent.to_dict() format). Cached bars from the database can also be
+extracted directly.
+
+Usage:
+    extract_window_around("recordings/2025-11-19.jsonl", center=incident_time,
+                          output_path="tests/fixtures/nq_gap_bug.json.gz", symbols=["NQ"])
+
+    fixture = load_fixture("tests/fixtures/nq_gap_bug.json.gz")
+    fixture.replay_into(BarAggregator(default_timeframes=['1m']))
+"""
+
+import gzip
+import json
+import logging
+from dataclasses import dataclass, field
+from datetime import datetime, timedelta, timezone
+from pathlib import Path
+from typing import Any, Callable, Dict, Iterable, Iterator, List, Optional, Union
+
+from core.market_events import (
+    MarketEvent, Trade, Quote, BarClosed, market_event_from_dict, parse_timestamp,
+)
+
+logger = logging.getLogger(__name__)
+
+FIXTURE_FORMAT_VERSION = 1
+
+EventSource = Union[str, Path, Iterable[Union[Dict[str, Any], MarketEvent]]]
+
+
+def _as_utc(ts: datetime) -> datetime:
+    return ts if ts.tzinfo else ts.replace(tzinfo=timezone.utc)
+
+
+def iter_recorded_events(source: EventSource) -> Iterator[Dict[str, Any]]:
+    """
+    Iterate event dicts from a JSONL recording (optionally .gz or .zst) or an iterable.
+
+    Malformed lines are skipped with a warning so a truncated recording is still usable.
+    """
+    if isinstance(source, (str, Path)):
+        path = Path(source)
+        if path.suffix == '.zst':
+            import zstandard
+            opener = lambda p, mode, encoding: zstandard.open(p, mode, encoding=encoding)
+        else:
+            opener = gzip.open if path.suffix == '.gz' else open
+        with opener(path, 'rt', encoding='utf-8') as f:
+            for line_no, line in enumerate(f, 1):
+                line = line.strip()
+                if not line:
+                    continue
+                try:
+                    yield json.loads(line)
+                except ValueError:
+                    logger.warning(f"⚠️  Skipping malformed line {line_no} in {path}")
+        return
+    for item in source:
+        yield item.to_dict() if isinstance(item, MarketEvent) else item
+
+
+@dataclass
+class ReplayFixture:
+    """A loaded replay fixture."""
+    start: datetime
+    end: datetime
+    symbols: List[str]
+    events: List[MarketEvent]
+    metadata: Dict[str, Any] = field(default_factory=dict)
+
+    def __len__(self) -> int:
+        return len(self.events)
+
+    def iter_events(self, types: Optional[Iterable[type]] = None) -> Iterator[MarketEvent]:
+        """Iterate events in time order, optionally filtered by event class."""
+        wanted = tuple(types) if types else None
+        for event in self.events:
+            if wanted is None or isinstance(event, wanted):
+                yield event
+
+    def replay(self, callback: Callable[[MarketEvent], Any]) -> int:
+        """Feed every event to a callback. Returns the number of events replayed."""
+        for event in self.events:
+            callback(event)
+        return len(self.events)
+
+    def replay_into(self, bar_aggregator) -> int:
+        """
+        Feed trades and last-price quotes into a BarAggregator using recorded timestamps.
+
+        Returns:
+            int: Number of ticks fed
+        """
+        fed = 0
+        for event in self.events:
+            if isinstance(event, Trade):
+                bar_aggregator.add_quote(event.symbol, event.price, event.size, event.timestamp)
+            elif isinstance(event, Quote) and event.last is not None:
+                bar_aggregator.add_quote(event.symbol, event.last, event.volume or 0, event.timestamp)
+            else:
+                continue
+            fed += 1
+        return fed
+
+
+def extract_fixture(source: EventSource, start: datetime, end: datetime,
+                    output_path: Union[str, Path], symbols: Optional[Iterable[str]] = None,
+                    metadata: Optional[Dict[str, Any]] = None) -> Path:
+    """
+    Extract events in [start, end] from a recording into a fixture file.
+
+    Args:
+        source: JSONL recording path or iterable of event dicts/MarketEvents
+        start: Window start (naive datetimes are treated as UTC)
+        end: Window end (inclusive)
+        output_path: Fixture path (gzip-compressed when it ends in .gz)
+        symbols: Only keep these symbols (default: all)
+        metadata: Free-form notes stored with the fixture (ticket, description, ...)
+
+    Returns:
+        Path: Written fixture path
+    """
+    start, end = _as_utc(start), _as_utc(end)
+    if end < start:
+        raise ValueError(f"Fixture window end {end.isoformat()} is before start {start.isoformat()}")
+    wanted = {s.upper() for s in symbols} if symbols else None
+
+    events: List[Dict[str, Any]] = []
+    for event in iter_recorded_events(source):
+        if wanted is not None and str(event.get('symbol', '')).upper() not in wanted:
+            continue
+        ts = _as_utc(parse_timestamp(event.get('timestamp')))
+        if start <= ts <= end:
+            events.append(event)
+    events.sort(key=lambda e: _as_utc(parse_timestamp(e.get('timestamp'))))
+
+    document = {
+        "format_version": FIXTURE_FORMAT_VERSION,
+        "created_at": datetime.now(timezone.utc).isoformat(),
+        "start": start.isoformat(),
+        "end": end.isoformat(),
+        "symbols": sorted(wanted) if wanted else sorted({str(e.get('symbol', '')).upper() for e in events}),
+        "event_count": len(events),
+        "metadata": metadata or {},
+        "events": events,
+    }
+
+    path = Path(output_path)
+    path.parent.mkdir(parents=True, exist_ok=True)
+    payload = json.dumps(document, separators=(',', ':'), default=str)
+    if path.suffix == '.gz':
+        with gzip.open(path, 'wt', encoding='utf-8') as f:
+            f.write(payload)
+    else:
+        path.write_text(payload, encoding='utf-8')
+    logger.info(f"🧪 Wrote replay fixture {path} ({len(events)} events, {start.isoformat()} → {end.isoformat()})")
+    return path
+
+
+def extract_window_around(source: EventSource, center: datetime, output_path: Union[str, Path],
+                          before: timedelta = timedelta(minutes=2, seconds=30),
+                          after: timedelta = timedelta(minutes=2, seconds=30),
+                          symbols: Optional[Iterable[str]] = None,
+                          metadata: Optional[Dict[str, Any]] = None) -> Path:
+    """Extract a rolling window around an incident time (default ±2.5 minutes)."""
+    center = _as_utc(center)
+    meta = {"incident_time": center.isoformat(), **(metadata or {})}
+    return extract_fixture(source, center - before, center + after, output_path, symbols, meta)
+
+
+def extract_bars_fixture(db, symbol: str, timeframe: str, start: datetime, end: datetime,
+                         output_path: Union[str, Path], metadata: Optional[Dict[str, Any]] = None) -> Path:
+    """
+    Extract cached bars from the database into a fixture of BarClosed events.
+
+    Args:
+        db: DatabaseManager (uses get_cached_bars)
+        symbol: Trading symbol
+        timeframe: Bar timeframe
+        start: Window start
+        end: Window end
+    """
+    bars = db.get_cached_bars(symbol, timeframe, start_time=start, end_time=end) or []
+    events = []
+    for bar in bars:
+        events.append({
+            "type": BarClosed.type,
+            "symbol": symbol.upper(),
+            "timestamp": bar.get('timestamp'),
+            "timeframe": timeframe,
+            "bar": {k: bar.get(k) for k in ('open', 'high', 'low', 'close', 'volume')},
+        })
+    meta = {"source": "database", "timeframe": timeframe, **(metadata or {})}
+    return extract_fixture(events, start, end, output_path, [symbol], meta)
+
+
+def load_fixture(path: Union[str, Path]) -> ReplayFixture:
+    """
+    Load a fixture written by extract_fixture.
+
+    Raises:
+        ValueError: If the fixture format version is not supported
+    """
+    path = Path(path)
+    if path.suffix == '.gz':
+        with gzip.open(path, 'rt', encoding='utf-8') as f:
+            document = json.load(f)
+    else:
+        document = json.loads(path.read_text(encoding='utf-8'))
+
+    version = document.get("format_version")
+    if version != FIXTURE_FORMAT_VERSION:
+        raise ValueError(f"Unsupported replay fixture version {version} in {path} "
+                         f"(expected {FIXTURE_FORMAT_VERSION})")
+
+    return ReplayFixture(
+        start=parse_timestamp(document["start"]),
+        end=parse_timestamp(document["end"]),
+        symbols=list(document.get("symbols", [])),
+        events=[market_event_from_dict(e) for e in document.get("events", [])],
+        metadata=document.get("metadata", {}),
+    )
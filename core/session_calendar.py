@@ -0,0 +1,230 @@
+"""
+Futures Session Calendar
+
+Encodes CME Globex session times per product so bar alignment and RTH
+filtering do not rely on UTC-hour heuristics.
+
+CME futures trade Sunday evening through Friday afternoon (exchange time,
+America/Chicago). Each session opens in the evening and is named after the
+trading date it closes on:
+- Globex session: e.g. 17:00 CT (previous day) -> 16:00 CT
+- Daily maintenance break: between session close and the next open (16:00-17:00 CT)
+- Weekend: Friday close -> Sunday open
+- RTH: the regular (pit) hours inside the session, e.g. 08:30-15:00 CT for equity index
+
+Session states:
+- 'rth'         : inside regular trading hours
+- 'eth'         : inside the Globex session but outside RTH
+- 'maintenance' : daily break between sessions
+- 'weekend'     : Friday close through Sunday open
+
+Configuration:
+- SESSION_DEFAULT_PRODUCT: Product group for unknown symbols (default 'equity_index')
+- SESSION_TIMEZONE: Exchange timezone (default 'America/Chicago')
+"""
+
+import logging
+import os
+from dataclasses import dataclass
+from datetime import date, datetime, time, timedelta, timezone
+from enum import Enum
+from typing import Dict, Iterable, Optional, Tuple
+from zoneinfo import ZoneInfo
+
+logger = logging.getLogger(__name__)
+
+
+class SessionState(Enum):
+    """Where a timestamp falls in a product's trading week."""
+    RTH = "rth"
+    ETH = "eth"
+    MAINTENANCE = "maintenance"
+    WEEKEND = "weekend"
+
+
+@dataclass(frozen=True)
+class ProductSessions:
+    """Session times for a product group, in exchange time."""
+    name: str
+    session_open: time   # Globex open (evening before the trading date)
+    session_close: time  # Globex close on the trading date
+    rth_open: time
+    rth_close: time
+    symbols: Tuple[str, ...] = ()
+
+
+PRODUCT_SESSIONS: Dict[str, ProductSessions] = {
+    'equity_index': ProductSessions('equity_index', time(17, 0), time(16, 0), time(8, 30), time(15, 0),
+                                    ('ES', 'MES', 'NQ', 'MNQ', 'YM', 'MYM', 'RTY', 'M2K', 'EMD')),
+    'energy': ProductSessions('energy', time(17, 0), time(16, 0), time(8, 0), time(13, 30),
+                              ('CL', 'MCL', 'QM', 'NG', 'QG', 'RB', 'HO')),
+    'metals': ProductSessions('metals', time(17, 0), time(16, 0), time(7, 20), time(12, 30),
+                              ('GC', 'MGC', 'SI', 'SIL', 'HG', 'MHG', 'PL', 'PA')),
+    'rates': ProductSessions('rates', time(17, 0), time(16, 0), time(7, 20), time(14, 0),
+                             ('ZT', 'ZF', 'ZN', 'TN', 'ZB', 'UB')),
+    'fx': ProductSessions('fx', time(17, 0), time(16, 0), time(7, 20), time(14, 0),
+                          ('6A', '6B', '6C', '6E', '6J', '6S', 'M6A', 'M6B', 'M6E')),
+}
+
+
+class SessionCalendar:
+    """
+    Per-product CME session calendar.
+
+    Usage:
+        calendar = SessionCalendar()
+        calendar.state('MNQ', ts)            # SessionState.RTH / ETH / MAINTENANCE / WEEKEND
+        calendar.is_rth('MNQ', ts)
+        open_utc, close_utc = calendar.session_bounds('MNQ', ts)
+
+        # Custom product (or override a built-in one)
+        calendar.set_product(ProductSessions('crypto', time(17), time(16), time(8, 30), time(15), ('MBT', 'MET')))
+    """
+
+    def __init__(self, products: Optional[Iterable[ProductSessions]] = None,
+                 default_product: Optional[str] = None, tz: Optional[str] = None):
+        """
+        Initialize calendar.
+
+        Args:
+            products: Product groups added to (or replacing) the built-in ones
+            default_product: Group used for unknown symbols (env: SESSION_DEFAULT_PRODUCT)
+            tz: Exchange timezone (env: SESSION_TIMEZONE, default 'America/Chicago')
+        """
+        self.tz = ZoneInfo(tz or os.getenv('SESSION_TIMEZONE', 'America/Chicago'))
+        self._products: Dict[str, ProductSessions] = dict(PRODUCT_SESSIONS)
+        self._symbols: Dict[str, str] = {}
+        for product in self._products.values():
+            self._index(product)
+        for product in products or ():
+            self.set_product(product)
+        self.default_product = default_product or os.getenv('SESSION_DEFAULT_PRODUCT', 'equity_index')
+        if self.default_product not in self._products:
+            raise ValueError(f"Unknown default session product '{self.default_product}'. "
+                             f"Use one of {sorted(self._products)}")
+
+    def _index(self, product: ProductSessions):
+        for symbol in product.symbols:
+            self._symbols[symbol.upper()] = product.name
+
+    def set_product(self, product: ProductSessions):
+        """Add or replace a product group and map its symbols to it."""
+        if product.session_open <= product.session_close:
+            raise ValueError(f"Session for '{product.name}' must open in the evening and close the next day")
+        self._products[product.name] = product
+        self._index(product)
+
+    def assign(self, symbol: str, product: str):
+        """Map a symbol to an existing product group."""
+        if product not in self._products:
+            raise ValueError(f"Unknown session product '{product}'")
+        self._symbols[symbol.upper()] = product
+
+    def product_for(self, symbol: str) -> ProductSessions:
+        """
+        Product group for a symbol.
+
+        Contract codes with a month suffix (e.g. 'MNQZ5') resolve through
+        their root; unknown symbols use the default product.
+        """
+        key = symbol.strip().upper()
+        name = self._symbols.get(key)
+        if name is None:
+            # Longest known root that prefixes the symbol ('MNQZ5' -> 'MNQ', not 'NQ')
+            roots = [root for root in self._symbols if key.startswith(root)]
+            name = self._symbols[max(roots, key=len)] if roots else self.default_product
+        return self._products[name]
+
+    def _local(self, timestamp: datetime) -> datetime:
+        if timestamp.tzinfo is None:
+            timestamp = timestamp.replace(tzinfo=timezone.utc)
+        return timestamp.astimezone(self.tz)
+
+    def _at(self, day: date, at: time) -> datetime:
+        return datetime.combine(day, at, tzinfo=self.tz).astimezone(timezone.utc)
+
+    def trading_date(self, symbol: str, timestamp: datetime) -> Optional[date]:
+        """
+        Trading date of the session containing a timestamp.
+
+        Evening trading belongs to the next day's session (Sunday 18:00 CT
+        trades for Monday). Returns None during maintenance and weekends.
+        """
+        product = self.product_for(symbol)
+        local = self._local(timestamp)
+        clock = local.time()
+        if clock >= product.session_open:
+            day = local.date() + timedelta(days=1)
+        elif clock < product.session_close:
+            day = local.date()
+        else:
+            return None
+        return day if day.weekday() < 5 else None
+
+    def session_bounds(self, symbol: str, timestamp: datetime) -> Optional[Tuple[datetime, datetime]]:
+        """(open, close) in UTC of the Globex session containing a timestamp, or None if closed."""
+        day = self.trading_date(symbol, timestamp)
+        if day is None:
+            return None
+        return self.bounds_for_date(symbol, day)
+
+    def bounds_for_date(self, symbol: str, day: date) -> Tuple[datetime, datetime]:
+        """(open, close) in UTC of the Globex session for a trading date."""
+        product = self.product_for(symbol)
+        return self._at(day - timedelta(days=1), product.session_open), self._at(day, product.session_close)
+
+    def rth_bounds(self, symbol: str, timestamp: datetime) -> Optional[Tuple[datetime, datetime]]:
+        """(open, close) in UTC of regular trading hours for the session containing a timestamp."""
+        day = self.trading_date(symbol, timestamp)
+        if day is None:
+            return None
+        product = self.product_for(symbol)
+        return self._at(day, product.rth_open), self._at(day, product.rth_close)
+
+    def state(self, symbol: str, timestamp: datetime) -> SessionState:
+        """Classify a timestamp as RTH, ETH, maintenance break or weekend."""
+        product = self.product_for(symbol)
+        local = self._local(timestamp)
+        day = self.trading_date(symbol, timestamp)
+        if day is None:
+            in_break = product.session_close <= local.time() < product.session_open
+            # Friday's break runs into the weekend; Sunday's ends at the open
+            if in_break and local.weekday() < 4:
+                return SessionState.MAINTENANCE
+            return SessionState.WEEKEND
+        if local.date() == day and product.rth_open <= local.time() < product.rth_close:
+            return SessionState.RTH
+        return SessionState.ETH
+
+    def is_open(self, symbol: str, timestamp: datetime) -> bool:
+        """True while the Globex session is trading."""
+        return self.trading_date(symbol, timestamp) is not None
+
+    def is_rth(self, symbol: str, timestamp: datetime) -> bool:
+        """True inside regular trading hours."""
+        return self.state(symbol, timestamp) is SessionState.RTH
+
+    def next_open(self, symbol: str, timestamp: datetime) -> datetime:
+        """Next Globex session open (UTC) strictly after a timestamp."""
+        product = self.product_for(symbol)
+        local = self._local(timestamp)
+        day = local.date()
+        while True:
+            candidate = self._at(day, product.session_open)
+            # Sessions open Sunday-Thursday evenings (trading dates Monday-Friday)
+            if candidate > local and (day + timedelta(days=1)).weekday() < 5:
+                return candidate
+            day += timedelta(days=1)
+
+    def get_products(self) -> Dict[str, Dict]:
+        """Product groups and their symbols (JSON-friendly)."""
+        result = {}
+        for name, product in sorted(self._products.items()):
+            result[name] = {
+                "session_open": product.session_open.strftime('%H:%M'),
+                "session_close": product.session_close.strftime('%H:%M'),
+                "rth_open": product.rth_open.strftime('%H:%M'),
+                "rth_close": product.rth_close.strftime('%H:%M'),
+                "symbols": sorted(s for s, p in self._symbols.items() if p == name),
+            }
+        return result
@@ -0,0 +1,245 @@
+"""
+Slippage Tracking
+
+Records three prices for every order the bot places and measures the
+slippage between them, so execution quality can be quantified per symbol
+and strategy and backtest slippage models calibrated against live fills:
+
+- Signal price: where the strategy or webhook wanted to trade (passed as
+  signal_price when placing the order; optional)
+- Submit price: the order's reference price when it went out (limit/stop
+  price, else the touch: ask for buys, bid for sells)
+- Fill price: the average fill price reported by the broker
+
+Slippage is measured in the trade's direction, positive = adverse:
+- signal: submit vs. signal (decision-to-submission delay)
+- execution: fill vs. submit (what the market took)
+- total: fill vs. signal (fill vs. submit when there is no signal price)
+
+Usage:
+    tracker = SlippageTracker()
+    tracker.record_submission('123', 'MNQ', 'BUY', 'Market', submit_price=21000.25, strategy='orb')
+    tracker.set_signal_price('123', 21000.0)
+    tracker.record_fill('123', 21000.5)
+    report = tracker.get_slippage_report()    # overall, by_symbol, by_strategy, by_order_type
+
+Configuration:
+- SLIPPAGE_MAX_PENDING: Submissions kept for unfilled orders (default 1000)
+- SLIPPAGE_MAX_TRADES: Filled trades kept for the report (default 5000)
+"""
+
+import logging
+import os
+import statistics
+import threading
+from collections import OrderedDict, deque
+from dataclasses import asdict, dataclass
+from typing import Any, Callable, Deque, Dict, List, Optional
+
+from core.clock_sync import server_now
+from core.strategy_engine.backtest import POINT_VALUES, TICK_SIZES
+from core.volume_profile import normalize_symbol
+
+logger = logging.getLogger(__name__)
+
+
+@dataclass
+class SlippageRecord:
+    """Signal, submit and fill prices of one order."""
+    order_id: str
+    symbol: str
+    side: str  # 'BUY' / 'SELL'
+    order_type: str
+    strategy: Optional[str]
+    quantity: int
+    signal_price: Optional[float]
+    submit_price: Optional[float]
+    fill_price: Optional[float] = None
+    tick_size: float = 0.25
+    point_value: float = 1.0
+    submitted_at: str = ""
+    filled_at: Optional[str] = None
+
+    def _slippage(self, price: Optional[float], reference: Optional[float]) -> Optional[float]:
+        if price is None or reference is None:
+            return None
+        direction = 1 if self.side == 'BUY' else -1
+        return round((price - reference) * direction, 10)
+
+    @property
+    def signal_slippage(self) -> Optional[float]:
+        return self._slippage(self.submit_price, self.signal_price)
+
+    @property
+    def execution_slippage(self) -> Optional[float]:
+        return self._slippage(self.fill_price, self.submit_price)
+
+    @property
+    def total_slippage(self) -> Optional[float]:
+        reference = self.signal_price if self.signal_price is not None else self.submit_price
+        return self._slippage(self.fill_price, reference)
+
+    def ticks(self, points: Optional[float]) -> Optional[float]:
+        return round(points / self.tick_size, 4) if points is not None else None
+
+    @property
+    def cost(self) -> Optional[float]:
+        """Total slippage in dollars across the filled quantity."""
+        total = self.total_slippage
+        return round(total * self.point_value * self.quantity, 2) if total is not None else None
+
+    def to_dict(self) -> Dict[str, Any]:
+        data = asdict(self)
+        data.update(
+            signal_slippage=self.signal_slippage, signal_slippage_ticks=self.ticks(self.signal_slippage),
+            execution_slippage=self.execution_slippage,
+            execution_slippage_ticks=self.ticks(self.execution_slippage),
+            total_slippage=self.total_slippage, total_slippage_ticks=self.ticks(self.total_slippage),
+            cost=self.cost,
+        )
+        return data
+
+
+def _average(values: List[float]) -> Optional[float]:
+    return round(statistics.fmean(values), 4) if values else None
+
+
+def summarize(records: List[SlippageRecord]) -> Dict[str, Any]:
+    """Aggregate slippage of filled records (ticks, positive = adverse)."""
+    signal = [r.ticks(r.signal_slippage) for r in records if r.signal_slippage is not None]
+    execution = [r.ticks(r.execution_slippage) for r in records if r.execution_slippage is not None]
+    total = [r.ticks(r.total_slippage) for r in records if r.total_slippage is not None]
+    return {
+        'trades': len(records),
+        'with_signal_price': len(signal),
+        'avg_signal_slippage_ticks': _average(signal),
+        'avg_execution_slippage_ticks': _average(execution),
+        'avg_total_slippage_ticks': _average(total),
+        'median_total_slippage_ticks': round(statistics.median(total), 4) if total else None,
+        'worst_total_slippage_ticks': max(total) if total else None,
+        'total_cost': round(sum(r.cost for r in records if r.cost is not None), 2),
+    }
+
+
+class SlippageTracker:
+    """
+    Signal/submit/fill prices per order and aggregate slippage stats.
+    """
+
+    def __init__(self, max_pending: Optional[int] = None, max_trades: Optional[int] = None,
+                 tick_sizes: Optional[Dict[str, float]] = None):
+        """
+        Initialize tracker.
+
+        Args:
+            max_pending: Submissions kept for unfilled orders (env: SLIPPAGE_MAX_PENDING, default 1000)
+            max_trades: Filled trades kept (env: SLIPPAGE_MAX_TRADES, default 5000)
+            tick_sizes: Tick size overrides by root symbol
+        """
+        self.max_pending = max_pending if max_pending is not None else int(os.getenv('SLIPPAGE_MAX_PENDING', '1000'))
+        self.max_trades = max_trades if max_trades is not None else int(os.getenv('SLIPPAGE_MAX_TRADES', '5000'))
+        self.tick_sizes = dict(tick_sizes or {})
+        self._pending: 'OrderedDict[str, SlippageRecord]' = OrderedDict()
+        self._trades: Deque[SlippageRecord] = deque(maxlen=self.max_trades)
+        self._lock = threading.Lock()
+
+    def record_submission(self, order_id: str, symbol: str, side: str, order_type: str,
+                          submit_price: Optional[float], quantity: int = 0, strategy: Optional[str] = None,
+                          signal_price: Optional[float] = None) -> SlippageRecord:
+        """Record an order the broker just accepted (submit_price: its reference price, if known)."""
+        root = normalize_symbol(symbol)
+        record = SlippageRecord(
+            order_id=str(order_id), symbol=root, side=side.upper(), order_type=order_type, strategy=strategy,
+            quantity=int(quantity or 0), signal_price=_price(signal_price), submit_price=_price(submit_price),
+            tick_size=self.tick_sizes.get(root, TICK_SIZES.get(root, 0.25)),
+            point_value=POINT_VALUES.get(root, 1.0), submitted_at=server_now().isoformat(),
+        )
+        with self._lock:
+            self._pending[record.order_id] = record
+            while len(self._pending) > self.max_pending:
+                self._pending.popitem(last=False)
+        return record
+
+    def set_signal_price(self, order_id: str, signal_price: Optional[float]) -> bool:
+        """Attach the signal price to a recorded order (also if it already filled); True if found."""
+        if signal_price is None:
+            return False
+        with self._lock:
+            record = self._pending.get(str(order_id)) or next(
+                (r for r in reversed(self._trades) if r.order_id == str(order_id)), None)
+            if record is None:
+                return False
+            record.signal_price = _price(signal_price)
+            return True
+
+    def record_fill(self, order_id: str, fill_price: Optional[float],
+                    quantity: Optional[int] = None) -> Optional[SlippageRecord]:
+        """
+        Complete an order with its fill price.
+
+        Returns:
+            The filled record, or None for orders without a recorded submission
+            (manual orders, bracket legs placed by the broker) or price
+        """
+        if fill_price is None:
+            return None
+        with self._lock:
+            record = self._pending.pop(str(order_id), None)
+            if record is None:
+                return None
+            record.fill_price = float(fill_price)
+            record.filled_at = server_now().isoformat()
+            if quantity:
+                record.quantity = int(quantity)
+            self._trades.append(record)
+        total = record.ticks(record.total_slippage)
+        if total is not None:
+            logger.debug(f"📊 {record.symbol} {record.side} {record.order_id} slippage: {total:+g} ticks")
+        return record
+
+    def _filtered(self, symbol: Optional[str], strategy: Optional[str]) -> List[SlippageRecord]:
+        root = normalize_symbol(symbol) if symbol else None
+        with self._lock:
+            return [r for r in self._trades
+                    if (root is None or r.symbol == root) and (strategy is None or r.strategy == strategy)]
+
+    def get_trades(self, symbol: Optional[str] = None, strategy: Optional[str] = None,
+                   limit: Optional[int] = None) -> List[Dict[str, Any]]:
+        """Filled trades with their slippage, newest first."""
+        records = list(reversed(self._filtered(symbol, strategy)))
+        return [record.to_dict() for record in records[:limit]]
+
+    def get_slippage_report(self, symbol: Optional[str] = None,
+                            strategy: Optional[str] = None) -> Dict[str, Any]:
+        """
+        Aggregate slippage of filled trades.
+
+        Args:
+            symbol: Only this symbol
+            strategy: Only this strategy
+
+        Returns:
+            Dict: 'overall' plus 'by_symbol', 'by_strategy' ('manual' for untagged
+            orders) and 'by_order_type' summaries (trades, average signal /
+            execution / total slippage in ticks, median and worst total, total
+            cost in dollars)
+        """
+        records = self._filtered(symbol, strategy)
+
+        def grouped(key: Callable[[SlippageRecord], str]) -> Dict[str, Dict[str, Any]]:
+            groups: Dict[str, List[SlippageRecord]] = {}
+            for record in records:
+                groups.setdefault(key(record), []).append(record)
+            return {name: summarize(group) for name, group in sorted(groups.items())}
+
+        return {
+            'overall': summarize(records),
+            'by_symbol': grouped(lambda r: r.symbol),
+            'by_strategy': grouped(lambda r: r.strategy or 'manual'),
+            'by_order_type': grouped(lambda r: r.order_type),
+            'pending_orders': len(self._pending),
+        }
+
+
+def _price(value: Any) -> Optional[float]:
+    return float(value) if value is not None else None
@@ -0,0 +1,127 @@
+"""
+Event-driven strategy engine.
+
+Modules:
+- strategy: Strategy base class (on_bar/on_tick -> Signal), Signal and Direction
+- indicators: streaming SMA/EMA/ATR/MACD updated one bar at a time
+- context: MarketContext giving strategies higher-timeframe bars and shared,
+  lazily maintained indicators
+- engine: StrategyEngine routing BarAggregator bars and market events to
+  strategies, with per-strategy error isolation
+- python_strategy: PyStrategy adapter running plain Python objects (and
+  legacy strategies/ signal dicts) as strategies
+- backtest: Backtester replaying history through the same engine with a
+  simulated fill model (slippage, commissions, stops/targets)
+- fees: CommissionModel per-symbol commission and exchange fees per contract
+  per side, shared by backtests, risk and performance statistics
+- runner: BacktestRunner running many parameter sets over the same data in
+  worker processes
+- optimizer: ParameterSpace, GridSearch and GeneticOptimizer ranking
+  parameter sets by an objective (net P&L, Sharpe, profit factor, ...)
+- performance: PerformanceStats (win rate, profit factor, expectancy,
+  Sharpe/Sortino, drawdown, MAE/MFE, per-session breakdowns) for backtest
+  and live trade lists
+- monte_carlo: MonteCarlo resampling of a trade list (i.i.d. or block
+  bootstrap) into drawdown and ruin-probability distributions
+- walk_forward: rolling/anchored walk-forward analysis (in-sample selection,
+  out-of-sample evaluation)
+- risk: RiskManager daily loss and trailing drawdown limits with proximity
+  warnings and a kill switch (cancel orders, flatten, block entry signals)
+- throttle: SignalThrottle gate (per strategy/symbol cooldown, duplicate
+  entries while in position, max concurrent entries)
+- config_watcher: StrategyConfigWatcher hot-reloading strategy parameters
+  from a JSON file into a running engine
+- session_filter: SessionFilter gate (per strategy entry windows, RTH only,
+  no entries around the open/close, flatten before the close) on the
+  futures session calendar
+- portfolio: PortfolioCoordinator gate (net contracts per symbol, total margin,
+  no opposing positions across strategies, priority preemption)
+- trading_core: TradingCore wiring market data -> bars -> strategies -> gates ->
+  OrderExecutor in one synchronous pass, with pause/flatten/parameter controls
+- workers: SymbolWorkerPool running each symbol's events in order on its own
+  worker thread
+- bus: SignalBus fanning signals out to filtered subscribers (inline
+  callbacks, queued consumers, async streams)
+- ema_cross: EMA crossover strategy (confirmation bars, long/short flags, ATR exits)
+- macd_momentum: MACD signal line cross strategy (zero-line filter, histogram
+  divergence, ATR exits)
+
+Strategies only emit signals; what happens to a signal (orders, alerts,
+logging) is up to whoever subscribes to the signal bus.
+"""
+
+from core.strategy_engine.backtest import BacktestConfig, Backtester, BacktestResult, BacktestTrade
+from core.strategy_engine.bus import SignalBus, SignalFilter
+from core.strategy_engine.config_watcher import StrategyConfigWatcher
+from core.strategy_engine.context import MarketContext
+from core.strategy_engine.ema_cross import EmaCrossParams, EmaCrossStrategy
+from core.strategy_engine.engine import StrategyEngine
+from core.strategy_engine.fees import CommissionModel
+from core.strategy_engine.indicators import ATR, EMA, MACD, SMA
+from core.strategy_engine.macd_momentum import MacdMomentumStrategy, MacdParams
+from core.strategy_engine.monte_carlo import MonteCarlo, MonteCarloResult
+from core.strategy_engine.optimizer import (
+    GenerationProgress, GeneticOptimizer, GeneticParams, GridSearch, OptimizationResult, ParameterSpace, Range,
+)
+from core.strategy_engine.performance import PerformanceStats, TradeRecord
+from core.strategy_engine.portfolio import PortfolioCoordinator, StrategyAllocation
+from core.strategy_engine.python_strategy import PyStrategy, as_strategy
+from core.strategy_engine.risk import AccountRisk, RiskEvent, RiskManager
+from core.strategy_engine.runner import BacktestRunner, RunResult
+from core.strategy_engine.session_filter import SessionFilter, SessionRule
+from core.strategy_engine.strategy import Direction, Signal, Strategy
+from core.strategy_engine.throttle import SignalThrottle, ThrottleRule
+from core.strategy_engine.trading_core import OrderExecutor, TradingCore
+from core.strategy_engine.walk_forward import WalkForward, WalkForwardResult
+
+__all__ = [
+    'AccountRisk',
+    'ATR',
+    'BacktestConfig',
+    'BacktestResult',
+    'BacktestRunner',
+    'BacktestTrade',
+    'Backtester',
+    'CommissionModel',
+    'Direction',
+    'EMA',
+    'EmaCrossParams',
+    'EmaCrossStrategy',
+    'GenerationProgress',
+    'GeneticOptimizer',
+    'GeneticParams',
+    'GridSearch',
+    'MACD',
+    'MacdMomentumStrategy',
+    'MacdParams',
+    'MarketContext',
+    'MonteCarlo',
+    'MonteCarloResult',
+    'OptimizationResult',
+    'OrderExecutor',
+    'ParameterSpace',
+    'PerformanceStats',
+    'PortfolioCoordinator',
+    'PyStrategy',
+    'Range',
+    'RiskEvent',
+    'RiskManager',
+    'RunResult',
+    'SMA',
+    'SessionFilter',
+    'SessionRule',
+    'Signal',
+    'SignalBus',
+    'SignalFilter',
+    'SignalThrottle',
+    'Strategy',
+    'StrategyAllocation',
+    'StrategyConfigWatcher',
+    'StrategyEngine',
+    'ThrottleRule',
+    'TradeRecord',
+    'TradingCore',
+    'WalkForward',
+    'WalkForwardResult',
+    'as_strategy',
+]
@@ -0,0 +1,494 @@
+"""
+Event-driven backtester.
+
+Replays historical bars (and optionally ticks) through a StrategyEngine, so
+strategies run exactly the code they run live: the same on_bar()/on_tick()
+routing, symbol/timeframe filtering and error isolation, with signals
+published on the engine's SignalBus. The only thing swapped out is the
+consumer: a SimulatedBroker turns signals into fills instead of orders.
+
+Fill model:
+- Market entries/exits fill at the next bar's open (fill='next_open', the
+  default, so a signal computed from a bar's close can't trade at that same
+  close) or at the signal price (fill='signal'). With ticks, pending orders
+  fill at the next trade/quote price of the symbol instead
+- Slippage: slippage_ticks ticks against the trade on every market fill
+  (entries, signal exits, stops); targets are limit orders and fill at the
+  target price
+- Commission: per contract per side, flat (commission) or per symbol with
+  exchange fees (fees, a CommissionModel)
+- Stops/targets from the signal are checked against every later bar's
+  range (and every tick); a bar that gaps through a stop fills at its open,
+  and a bar touching both stop and target is assumed to hit the stop first
+- One position per strategy and symbol: an opposite signal reverses it, a
+  FLAT signal closes it, a same-side signal is ignored. Open positions are
+  closed at the last price when the data ends
+- Signal filters passed as filters= (SignalThrottle, SessionFilter, ...)
+  are reset and attached to every run's engine, gating signals as live
+- Every trade records its MAE/MFE: the worst and best price seen while
+  open (bar ranges, ticks and the exit price), in dollars
+
+Usage:
+    backtester = Backtester([EmaCrossStrategy('ema', symbols=['MNQ'])], BacktestConfig(commission=0.37))
+    result = backtester.run(bars)
+    print(result.stats(), result.performance().to_dict())
+    for trade in result.trades: ...
+
+Configuration (BacktestConfig.from_env):
+- BACKTEST_QUANTITY: Contracts per entry (default 1)
+- BACKTEST_COMMISSION: Commission per contract per side in dollars (default 0)
+- COMMISSIONS, EXCHANGE_FEES, ...: Per-symbol costs (see fees.CommissionModel); when any is set they
+  replace BACKTEST_COMMISSION
+- BACKTEST_SLIPPAGE_TICKS: Slippage per market fill in ticks (default 0)
+- BACKTEST_FILL: next_open or signal (default next_open)
+- BACKTEST_INITIAL_CAPITAL: Starting equity (default 50000)
+"""
+
+import heapq
+import logging
+import math
+import os
+import statistics
+from dataclasses import dataclass, field
+from datetime import datetime, timedelta
+from typing import Any, Dict, Iterable, List, Optional, Tuple
+
+from core.bar_aggregator import Bar, parse_bar_threshold, timeframe_seconds
+from core.market_events import MarketEvent, Quote, Trade
+from core.session_calendar import SessionCalendar
+from core.strategy_engine.engine import StrategyEngine
+from core.strategy_engine.fees import CommissionModel
+from core.strategy_engine.performance import PerformanceStats
+from core.strategy_engine.strategy import Direction, Signal, Strategy
+from core.volume_profile import normalize_symbol
+
+logger = logging.getLogger(__name__)
+
+# Root symbol -> minimum price increment
+TICK_SIZES = {
+    'ES': 0.25, 'MES': 0.25, 'NQ': 0.25, 'MNQ': 0.25, 'YM': 1.0, 'MYM': 0.5, 'RTY': 0.1, 'M2K': 0.1,
+    'CL': 0.01, 'MCL': 0.01, 'NG': 0.001, 'GC': 0.1, 'MGC': 0.1, 'SI': 0.005,
+}
+
+# Root symbol -> dollars per point per contract
+POINT_VALUES = {
+    'ES': 50.0, 'MES': 5.0, 'NQ': 20.0, 'MNQ': 2.0, 'YM': 5.0, 'MYM': 0.5, 'RTY': 50.0, 'M2K': 5.0,
+    'CL': 1000.0, 'MCL': 100.0, 'NG': 10000.0, 'GC': 100.0, 'MGC': 10.0, 'SI': 5000.0,
+}
+
+FILL_MODES = ('next_open', 'signal')
+
+
+@dataclass
+class BacktestConfig:
+    """Fill model and account parameters."""
+    quantity: int = 1
+    commission: float = 0.0  # Per contract per side
+    fees: Optional[CommissionModel] = None  # Per-symbol commission and exchange fees (replaces commission)
+    slippage_ticks: float = 0.0
+    fill: str = 'next_open'
+    initial_capital: float = 50000.0
+    tick_sizes: Dict[str, float] = field(default_factory=dict)  # Overrides of TICK_SIZES
+    point_values: Dict[str, float] = field(default_factory=dict)  # Overrides of POINT_VALUES
+
+    def validate(self) -> 'BacktestConfig':
+        """
+        Raises:
+            ValueError: Inconsistent parameters
+        """
+        if self.quantity < 1:
+            raise ValueError(f"quantity must be >= 1, got {self.quantity}")
+        if self.commission < 0 or self.slippage_ticks < 0:
+            raise ValueError("commission and slippage_ticks must be >= 0")
+        if self.fill not in FILL_MODES:
+            raise ValueError(f"fill must be one of {FILL_MODES}, got {self.fill!r}")
+        return self
+
+    @classmethod
+    def from_env(cls, prefix: str = 'BACKTEST_') -> 'BacktestConfig':
+        """Load parameters from environment variables."""
+        fees = CommissionModel()
+        return cls(
+            quantity=int(os.getenv(f"{prefix}QUANTITY", "1")),
+            commission=float(os.getenv(f"{prefix}COMMISSION", "0")),
+            fees=fees if fees.configured else None,
+            slippage_ticks=float(os.getenv(f"{prefix}SLIPPAGE_TICKS", "0")),
+            fill=os.getenv(f"{prefix}FILL", "next_open").strip().lower(),
+            initial_capital=float(os.getenv(f"{prefix}INITIAL_CAPITAL", "50000")),
+        ).validate()
+
+    def tick_size(self, symbol: str) -> float:
+        root = normalize_symbol(symbol)
+        return self.tick_sizes.get(root, TICK_SIZES.get(root, 0.01))
+
+    def point_value(self, symbol: str) -> float:
+        root = normalize_symbol(symbol)
+        return self.point_values.get(root, POINT_VALUES.get(root, 1.0))
+
+    def fill_cost(self, symbol: str, quantity: int) -> float:
+        """Commission and fees of one side of a trade."""
+        return self.fees.cost(symbol, quantity) if self.fees is not None else self.commission * quantity
+
+
+@dataclass(frozen=True)
+class BacktestTrade:
+    """A completed round trip."""
+    strategy_id: str
+    symbol: str
+    direction: Direction
+    quantity: int
+    entry_time: datetime
+    entry_price: float
+    exit_time: datetime
+    exit_price: float
+    exit_reason: str  # signal, reverse, stop, target, end
+    gross_pnl: float
+    commission: float
+    mae: float = 0.0  # Maximum adverse excursion in dollars (>= 0)
+    mfe: float = 0.0  # Maximum favourable excursion in dollars (>= 0)
+
+    @property
+    def pnl(self) -> float:
+        return self.gross_pnl - self.commission
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        return {
+            "strategy_id": self.strategy_id,
+            "symbol": self.symbol,
+            "direction": self.direction.value,
+            "quantity": self.quantity,
+            "entry_time": self.entry_time.isoformat(),
+            "entry_price": self.entry_price,
+            "exit_time": self.exit_time.isoformat(),
+            "exit_price": self.exit_price,
+            "exit_reason": self.exit_reason,
+            "gross_pnl": round(self.gross_pnl, 2),
+            "commission": round(self.commission, 2),
+            "pnl": round(self.pnl, 2),
+            "mae": round(self.mae, 2),
+            "mfe": round(self.mfe, 2),
+        }
+
+
+@dataclass
+class BacktestResult:
+    """Trades, equity curve and summary of one run."""
+    trades: List[BacktestTrade]
+    equity_curve: List[Tuple[datetime, float]]
+    initial_capital: float
+    signals: int = 0
+    bars: int = 0
+    ticks: int = 0
+
+    def stats(self) -> Dict[str, Any]:
+        """Summary statistics."""
+        pnls = [t.pnl for t in self.trades]
+        wins = [p for p in pnls if p > 0]
+        losses = [p for p in pnls if p < 0]
+        gross_win, gross_loss = sum(wins), -sum(losses)
+        peak = self.initial_capital
+        max_drawdown = max_drawdown_pct = 0.0
+        for _, equity in self.equity_curve:
+            peak = max(peak, equity)
+            if peak - equity > max_drawdown:
+                max_drawdown = peak - equity
+                max_drawdown_pct = max_drawdown / peak * 100 if peak > 0 else 0.0
+        net = sum(pnls)
+        # Sharpe from daily returns of the last equity mark of each day (risk-free rate 0)
+        daily: Dict[Any, float] = {}
+        for timestamp, equity in self.equity_curve:
+            daily[timestamp.date()] = equity
+        returns, previous = [], self.initial_capital
+        for equity in daily.values():
+            if previous > 0:
+                returns.append(equity / previous - 1)
+            previous = equity
+        deviation = statistics.stdev(returns) if len(returns) > 1 else 0.0
+        sharpe = statistics.mean(returns) / deviation * math.sqrt(252) if deviation > 0 else None
+        return {
+            "trades": len(pnls),
+            "wins": len(wins),
+            "losses": len(losses),
+            "win_rate": round(len(wins) / len(pnls) * 100, 2) if pnls else 0.0,
+            "net_pnl": round(net, 2),
+            "gross_pnl": round(sum(t.gross_pnl for t in self.trades), 2),
+            "commissions": round(sum(t.commission for t in self.trades), 2),
+            "avg_win": round(gross_win / len(wins), 2) if wins else 0.0,
+            "avg_loss": round(-gross_loss / len(losses), 2) if losses else 0.0,
+            "profit_factor": round(gross_win / gross_loss, 3) if gross_loss else None,
+            "max_drawdown": round(max_drawdown, 2),
+            "max_drawdown_pct": round(max_drawdown_pct, 2),
+            "final_equity": round(self.initial_capital + net, 2),
+            "return_pct": round(net / self.initial_capital * 100, 2) if self.initial_capital else 0.0,
+            "sharpe": round(sharpe, 3) if sharpe is not None else None,
+            "signals": self.signals,
+            "bars": self.bars,
+            "ticks": self.ticks,
+        }
+
+    def performance(self, calendar: Optional[SessionCalendar] = None) -> PerformanceStats:
+        """Full trade statistics (Sortino, streaks, MAE/MFE, session/strategy breakdowns)."""
+        return PerformanceStats.from_trades(self.trades, initial_capital=self.initial_capital, calendar=calendar)
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        return {
+            "stats": self.stats(),
+            "trades": [t.to_dict() for t in self.trades],
+            "equity_curve": [(ts.isoformat(), round(equity, 2)) for ts, equity in self.equity_curve],
+        }
+
+
+@dataclass
+class _Position:
+    strategy_id: str
+    symbol: str
+    sign: int
+    quantity: int
+    entry_time: datetime
+    entry_price: float
+    stop: Optional[float]
+    target: Optional[float]
+    commission: float  # Entry side
+    best: float = 0.0  # Most favourable price seen while open
+    worst: float = 0.0  # Most adverse price seen while open
+
+    def __post_init__(self) -> None:
+        self.best = self.worst = self.entry_price
+
+    def extend(self, high: float, low: float) -> None:
+        favourable, adverse = (high, low) if self.sign > 0 else (low, high)
+        if (favourable - self.best) * self.sign > 0:
+            self.best = favourable
+        if (adverse - self.worst) * self.sign < 0:
+            self.worst = adverse
+
+
+class SimulatedBroker:
+    """
+    Turns signals into simulated fills and positions.
+    """
+
+    def __init__(self, config: BacktestConfig):
+        self.config = config
+        self.positions: Dict[Tuple[str, str], _Position] = {}
+        self.pending: Dict[Tuple[str, str], Signal] = {}  # Waiting for the next price (fill='next_open')
+        self.trades: List[BacktestTrade] = []
+        self.realized = 0.0
+        self.last_price: Dict[str, float] = {}
+        self.active = True  # False while strategies warm up: signals are ignored
+
+    # ---------------------------
+    # Inputs
+    # ---------------------------
+    def on_signal(self, signal: Signal) -> None:
+        if not self.active:
+            return
+        if self.config.fill == 'signal':
+            self._execute(signal, signal.price, signal.timestamp)
+        else:
+            self.pending[(signal.strategy_id, signal.symbol)] = signal
+
+    def on_bar(self, bar: Bar) -> None:
+        """Fill pending orders at the bar's open, then check stops/targets against its range."""
+        self._fill_pending(bar.symbol, bar.open, bar.timestamp)
+        for key, position in list(self.positions.items()):
+            if position.symbol == bar.symbol:
+                self._check_exits(key, position, bar.open, bar.high, bar.low, bar.timestamp)
+        self.last_price[bar.symbol] = bar.close
+
+    def on_price(self, symbol: str, price: float, timestamp: datetime) -> None:
+        """Fill pending orders and check stops/targets at a tick price."""
+        self._fill_pending(symbol, price, timestamp)
+        for key, position in list(self.positions.items()):
+            if position.symbol == symbol:
+                self._check_exits(key, position, price, price, price, timestamp)
+        self.last_price[symbol] = price
+
+    def close_all(self, timestamp: datetime, reason: str = 'end') -> None:
+        for key, position in list(self.positions.items()):
+            price = self.last_price.get(position.symbol, position.entry_price)
+            self._close(key, position, self._slipped(position.symbol, price, -position.sign), timestamp, reason)
+
+    def equity(self) -> float:
+        """Initial capital + realized + open P&L at the last prices."""
+        unrealized = sum(
+            (self.last_price.get(p.symbol, p.entry_price) - p.entry_price) * p.sign * p.quantity
+            * self.config.point_value(p.symbol) - p.commission
+            for p in self.positions.values()
+        )
+        return self.config.initial_capital + self.realized + unrealized
+
+    # ---------------------------
+    # Fills
+    # ---------------------------
+    def _fill_pending(self, symbol: str, price: float, timestamp: datetime) -> None:
+        for key in [k for k in self.pending if k[1] == symbol]:
+            self._execute(self.pending.pop(key), price, timestamp)
+
+    def _execute(self, signal: Signal, price: float, timestamp: datetime) -> None:
+        key = (signal.strategy_id, signal.symbol)
+        position = self.positions.get(key)
+        sign = signal.direction.sign
+        if position is not None:
+            if position.sign == sign:
+                return  # Already positioned that way
+            self._close(key, position, self._slipped(signal.symbol, price, -position.sign), timestamp,
+                        'signal' if sign == 0 else 'reverse')
+        if sign == 0:
+            return
+        quantity = self.config.quantity
+        self.positions[key] = _Position(
+            strategy_id=signal.strategy_id, symbol=signal.symbol, sign=sign, quantity=quantity,
+            entry_time=timestamp, entry_price=self._slipped(signal.symbol, price, sign),
+            stop=signal.stop, target=signal.target, commission=self.config.fill_cost(signal.symbol, quantity),
+        )
+
+    def _check_exits(self, key: Tuple[str, str], position: _Position, open_: float, high: float, low: float,
+                     timestamp: datetime) -> None:
+        sign, stop, target = position.sign, position.stop, position.target
+        if stop is not None:
+            if (open_ - stop) * sign <= 0:  # Gapped through the stop
+                self._close(key, position, self._slipped(position.symbol, open_, -sign), timestamp, 'stop')
+                return
+            if (low if sign > 0 else high) * sign <= stop * sign:
+                self._close(key, position, self._slipped(position.symbol, stop, -sign), timestamp, 'stop')
+                return
+        if target is not None:
+            if (open_ - target) * sign >= 0:
+                self._close(key, position, open_, timestamp, 'target')  # Gapped through: limit fills at the open
+            elif (high if sign > 0 else low) * sign >= target * sign:
+                self._close(key, position, target, timestamp, 'target')
+                return
+        # Still open: the whole range counts towards MAE/MFE (on an exit bar only the fill price does)
+        position.extend(high, low)
+
+    def _close(self, key: Tuple[str, str], position: _Position, price: float, timestamp: datetime,
+               reason: str) -> None:
+        del self.positions[key]
+        position.extend(price, price)
+        point_value = self.config.point_value(position.symbol)
+        gross = (price - position.entry_price) * position.sign * position.quantity * point_value
+        excursion = position.quantity * point_value
+        commission = position.commission + self.config.fill_cost(position.symbol, position.quantity)
+        trade = BacktestTrade(
+            strategy_id=position.strategy_id, symbol=position.symbol,
+            direction=Direction.LONG if position.sign > 0 else Direction.SHORT, quantity=position.quantity,
+            entry_time=position.entry_time, entry_price=position.entry_price, exit_time=timestamp,
+            exit_price=price, exit_reason=reason, gross_pnl=gross, commission=commission,
+            mae=(position.entry_price - position.worst) * position.sign * excursion,
+            mfe=(position.best - position.entry_price) * position.sign * excursion,
+        )
+        self.trades.append(trade)
+        self.realized += trade.pnl
+
+    def _slipped(self, symbol: str, price: float, sign: int) -> float:
+        """Price moved slippage_ticks against a buy (sign > 0) or sell (sign < 0)."""
+        return price + sign * self.config.slippage_ticks * self.config.tick_size(symbol)
+
+
+def bar_end(bar: Bar) -> datetime:
+    """When a bar's close is known: its end for time bars, its timestamp for activity bars."""
+    if parse_bar_threshold(bar.timeframe) is not None:
+        return bar.timestamp
+    return bar.timestamp + timedelta(seconds=timeframe_seconds(bar.timeframe))
+
+
+def event_price(event: MarketEvent) -> Optional[float]:
+    """Trade price, or a quote's last/mid price (None for other events)."""
+    if isinstance(event, Trade):
+        return event.price
+    if isinstance(event, Quote):
+        return event.last if event.last is not None else event.mid
+    return None
+
+
+class Backtester:
+    """
+    Runs strategies over historical data through the live StrategyEngine code path.
+    """
+
+    def __init__(self, strategies: Iterable[Any], config: Optional[BacktestConfig] = None,
+                 filters: Iterable[Any] = ()):
+        """
+        Initialize backtester.
+
+        Args:
+            strategies: Strategy instances (or plain objects, see StrategyEngine.add_strategy)
+            config: Fill model (default: BacktestConfig.from_env())
+            filters: Signal filters with attach(engine) and reset() (SignalThrottle, SessionFilter, ...),
+                attached to every run's engine
+        """
+        self.config = (config or BacktestConfig.from_env()).validate()
+        self.strategies: List[Strategy] = []
+        self._strategy_inputs = list(strategies)
+        self.filters = list(filters)
+
+    def run(self, bars: Iterable[Bar], ticks: Iterable[MarketEvent] = (),
+            trade_from: Optional[datetime] = None) -> BacktestResult:
+        """
+        Replay bars (and ticks) in time order.
+
+        Args:
+            bars: Completed bars, any symbols/timeframes, sorted by time per stream
+            ticks: Optional Trade/Quote events sorted by time, for tick-level fills and on_tick()
+            trade_from: Events before this only warm the strategies up; their signals aren't traded
+
+        Returns:
+            BacktestResult: Trades, equity curve and stats
+        """
+        engine = StrategyEngine(max_errors=0, workers=0)  # Inline and never auto-disabled, like a replay
+        for strategy in self._strategy_inputs:
+            registered = engine.add_strategy(strategy)
+            registered.reset()
+        self.strategies = engine.strategies
+        for signal_filter in self.filters:
+            signal_filter.reset()
+            signal_filter.attach(engine)
+        broker = SimulatedBroker(self.config)
+        engine.on_signal(broker.on_signal)
+
+        equity_curve: List[Tuple[datetime, float]] = []
+        stream = heapq.merge(((bar_end(b), 1, i, b) for i, b in enumerate(bars)),
+                             ((t.timestamp, 0, i, t) for i, t in enumerate(ticks)),
+                             key=lambda item: item[:3])
+        n_bars = n_ticks = 0
+        last_time = None
+        for timestamp, is_bar, _, event in stream:
+            trading = trade_from is None or timestamp >= trade_from
+            broker.active = trading
+            if trading:
+                last_time = timestamp
+            if is_bar:
+                n_bars += 1
+                if trading:
+                    broker.on_bar(event)
+                engine.on_bar(event)
+                if trading:
+                    self._mark(equity_curve, timestamp, broker.equity())
+            else:
+                n_ticks += 1
+                price = event_price(event)
+                if trading and price is not None:
+                    broker.on_price(event.symbol, price, event.timestamp)
+                engine.on_tick(event)
+        if last_time is not None:
+            broker.close_all(last_time)
+            self._mark(equity_curve, last_time, broker.equity())
+
+        result = BacktestResult(trades=broker.trades, equity_curve=equity_curve,
+                                initial_capital=self.config.initial_capital,
+                                signals=engine.signals_emitted, bars=n_bars, ticks=n_ticks)
+        stats = result.stats()
+        logger.debug(f"📊 Backtest: {n_bars} bars, {stats['trades']} trades, net ${stats['net_pnl']:,.2f}, "
+                    f"max drawdown ${stats['max_drawdown']:,.2f}")
+        return result
+
+    @staticmethod
+    def _mark(equity_curve: List[Tuple[datetime, float]], timestamp: datetime, equity: float) -> None:
+        """Append an equity point, one per timestamp (bars of several symbols can close together)."""
+        if equity_curve and equity_curve[-1][0] == timestamp:
+            equity_curve[-1] = (timestamp, equity)
+        else:
+            equity_curve.append((timestamp, equity))
@@ -0,0 +1,278 @@
+"""
+Signal bus: pub/sub between the strategies that generate signals and the
+consumers that act on them.
+
+Strategies (through StrategyEngine) publish Signals; consumers subscribe
+without either side knowing about the other, so the order router, a signal
+logger and ad-hoc Python callbacks can come and go independently of the
+strategies.
+
+Every subscription carries a SignalFilter (strategy ids, symbols, directions,
+minimum confidence and/or a predicate), so a consumer only sees the signals
+it asked for. Delivery modes:
+- subscribe(): called inline on the publishing thread (bars close on the
+  quote thread); coroutine functions are scheduled onto the event loop they
+  were registered from
+- add_consumer(): the signals go through the consumer's own bounded channel
+  and are drained by a task on the loop, so a slow consumer (e.g. an order
+  router waiting on the API) never holds up the strategies
+- stream(): `async for signal in bus.stream(symbols=['MNQ']):`
+
+A failing subscriber is logged and counted without affecting the others.
+
+Usage:
+    bus = SignalBus()
+    bus.subscribe(signal_log.write, name='log')
+    bus.add_consumer('orders', order_router.on_signal, strategies=['ema_cross'], min_confidence=0.6)
+    engine = StrategyEngine(bus=bus)
+
+Configuration:
+- SIGNAL_BUS_CAPACITY: Channel capacity of add_consumer()/stream() subscribers (default 1000)
+- SIGNAL_BUS_POLICY: Their overflow policy, block/drop_oldest (default block)
+"""
+
+import asyncio
+import itertools
+import logging
+import os
+import threading
+from dataclasses import dataclass
+from typing import Any, Callable, Dict, FrozenSet, Iterable, Optional, Tuple, Union
+
+from core.strategy_engine.strategy import Direction, Signal
+from core.websocket.channels import BoundedChannel, ChannelFanout, EventStream, OverflowPolicy
+
+logger = logging.getLogger(__name__)
+
+
+@dataclass(frozen=True)
+class SignalFilter:
+    """Which signals a subscriber receives; unset fields match everything."""
+    strategies: Optional[FrozenSet[str]] = None
+    symbols: Optional[FrozenSet[str]] = None
+    directions: Optional[FrozenSet[Direction]] = None
+    min_confidence: float = 0.0
+    where: Optional[Callable[[Signal], bool]] = None
+
+    @classmethod
+    def build(cls, strategies: Optional[Iterable[str]] = None, symbols: Optional[Iterable[str]] = None,
+              directions: Optional[Iterable[Union[Direction, str]]] = None, min_confidence: float = 0.0,
+              where: Optional[Callable[[Signal], bool]] = None) -> 'SignalFilter':
+        """
+        Build a filter from plain iterables.
+
+        Raises:
+            ValueError: Unknown direction
+        """
+        return cls(
+            strategies=frozenset(strategies) if strategies else None,
+            symbols=frozenset(s.strip().upper() for s in symbols) if symbols else None,
+            directions=frozenset(Direction(d) for d in directions) if directions else None,
+            min_confidence=min_confidence,
+            where=where,
+        )
+
+    def matches(self, signal: Signal) -> bool:
+        if self.strategies is not None and signal.strategy_id not in self.strategies:
+            return False
+        if self.symbols is not None and signal.symbol.upper() not in self.symbols:
+            return False
+        if self.directions is not None and signal.direction not in self.directions:
+            return False
+        if signal.confidence < self.min_confidence:
+            return False
+        return self.where is None or bool(self.where(signal))
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        return {
+            "strategies": sorted(self.strategies) if self.strategies else None,
+            "symbols": sorted(self.symbols) if self.symbols else None,
+            "directions": sorted(d.value for d in self.directions) if self.directions else None,
+            "min_confidence": self.min_confidence,
+            "where": getattr(self.where, '__name__', repr(self.where)) if self.where else None,
+        }
+
+
+@dataclass
+class _Subscriber:
+    """An inline subscription and its counters."""
+    name: str
+    callback: Callable[[Signal], Any]
+    filter: SignalFilter
+    loop: Optional[asyncio.AbstractEventLoop] = None
+    delivered: int = 0
+    errors: int = 0
+    last_error: Optional[str] = None
+
+
+class SignalBus:
+    """
+    Publish/subscribe hub for strategy Signals.
+    """
+
+    def __init__(self, capacity: Optional[int] = None, policy: Union[OverflowPolicy, str, None] = None):
+        """
+        Initialize bus.
+
+        Args:
+            capacity: Channel capacity of queued subscribers (env: SIGNAL_BUS_CAPACITY)
+            policy: Overflow policy of queued subscribers (env: SIGNAL_BUS_POLICY)
+        """
+        self.capacity = capacity if capacity is not None else int(os.getenv('SIGNAL_BUS_CAPACITY', '1000'))
+        self.policy = OverflowPolicy(policy if policy is not None else os.getenv('SIGNAL_BUS_POLICY', 'block'))
+        self._subscribers: Tuple[_Subscriber, ...] = ()  # Copy-on-write
+        self._filters: Dict[str, SignalFilter] = {}  # add_consumer() filters, for get_stats()
+        self._channels = ChannelFanout()
+        self._lock = threading.Lock()
+        self._ids = itertools.count(1)
+        self.published = 0
+        self.subscriber_errors = 0
+
+    # ---------------------------
+    # Subscriptions
+    # ---------------------------
+    def subscribe(self, callback: Callable[[Signal], Any], name: Optional[str] = None,
+                  strategies: Optional[Iterable[str]] = None, symbols: Optional[Iterable[str]] = None,
+                  directions: Optional[Iterable[Union[Direction, str]]] = None, min_confidence: float = 0.0,
+                  where: Optional[Callable[[Signal], bool]] = None) -> Callable[[], None]:
+        """
+        Call back with matching signals inline (coroutine functions must be registered from a running loop).
+
+        Args:
+            callback: Sync or async signal callback
+            name: Subscriber name in get_stats() (default: callback name)
+            strategies: Only signals from these strategy ids
+            symbols: Only these symbols
+            directions: Only these directions, e.g. ['long', 'short'] to skip exits
+            min_confidence: Skip signals below this confidence
+            where: Extra predicate
+
+        Returns:
+            Callable: Unsubscribe function
+        """
+        loop = None
+        if asyncio.iscoroutinefunction(callback):
+            try:
+                loop = asyncio.get_running_loop()
+            except RuntimeError:
+                raise RuntimeError("Async signal callbacks must be registered from a running event loop")
+        subscriber = _Subscriber(
+            name=name or f"{getattr(callback, '__qualname__', 'callback')}-{next(self._ids)}",
+            callback=callback,
+            filter=SignalFilter.build(strategies, symbols, directions, min_confidence, where),
+            loop=loop,
+        )
+        with self._lock:
+            self._subscribers = self._subscribers + (subscriber,)
+
+        def unsubscribe():
+            with self._lock:
+                self._subscribers = tuple(s for s in self._subscribers if s is not subscriber)
+        return unsubscribe
+
+    def add_consumer(self, name: str, callback: Callable[[Signal], Any], capacity: Optional[int] = None,
+                     policy: Union[OverflowPolicy, str, None] = None, **filters: Any) -> BoundedChannel:
+        """
+        Deliver matching signals to a callback through its own bounded channel (call from the event loop).
+
+        Args:
+            name: Consumer name
+            callback: Sync or async signal callback, awaited one signal at a time
+            capacity: Channel capacity (default: the bus capacity)
+            policy: Overflow policy (default: the bus policy)
+            **filters: strategies, symbols, directions, min_confidence, where (see subscribe())
+
+        Raises:
+            ValueError: A consumer with this name exists
+        """
+        signal_filter = SignalFilter.build(**filters)
+        channel = self._channels.add_consumer(name, callback, capacity or self.capacity, policy or self.policy,
+                                              conflate_key=None, accept=signal_filter.matches)
+        self._filters[name] = signal_filter
+        return channel
+
+    def remove_consumer(self, name: str) -> None:
+        """Remove a consumer added with add_consumer() (queued signals are still delivered)."""
+        self._channels.remove(name)
+        self._filters.pop(name, None)
+
+    def stream(self, capacity: Optional[int] = None, policy: Union[OverflowPolicy, str, None] = None,
+               **filters: Any) -> EventStream:
+        """
+        Matching signals as an async iterator (`async for signal in bus.stream(symbols=['MNQ']):`).
+
+        Returns:
+            EventStream: Registered immediately; ends when closed or the bus is closed
+        """
+        signal_filter = SignalFilter.build(**filters)
+        name = f"stream-{next(self._ids)}"
+        channel = self._channels.add_channel(name, capacity or self.capacity, policy or self.policy,
+                                             conflate_key=None, accept=signal_filter.matches)
+        return EventStream(self._channels, name, channel)
+
+    def __len__(self) -> int:
+        return len(self._subscribers) + len(self._channels)
+
+    # ---------------------------
+    # Publishing
+    # ---------------------------
+    def publish(self, signal: Signal) -> int:
+        """
+        Deliver a signal to every matching subscriber (thread-safe).
+
+        Returns:
+            int: Inline subscribers the signal matched (queued consumers not included)
+        """
+        self.published += 1
+        matched = 0
+        for subscriber in self._subscribers:
+            try:
+                if not subscriber.filter.matches(signal):
+                    continue
+                matched += 1
+                if subscriber.loop is None:
+                    subscriber.callback(signal)
+                elif not subscriber.loop.is_closed():
+                    asyncio.run_coroutine_threadsafe(subscriber.callback(signal), subscriber.loop)
+                subscriber.delivered += 1
+            except Exception as e:
+                subscriber.errors += 1
+                subscriber.last_error = f"{type(e).__name__}: {e}"
+                self.subscriber_errors += 1
+                logger.error(f"❌ Signal subscriber {subscriber.name} failed on "
+                             f"{signal.strategy_id} {signal.symbol}: {subscriber.last_error}")
+        self._channels.publish(signal)
+        return matched
+
+    async def close(self, timeout: float = 5.0) -> None:
+        """Drop inline subscribers, end streams and wait for queued consumers to drain."""
+        with self._lock:
+            self._subscribers = ()
+        self._filters.clear()
+        await self._channels.close(timeout)
+
+    # ---------------------------
+    # Status
+    # ---------------------------
+    def get_stats(self) -> Dict[str, Any]:
+        """Publish counters, per-subscriber delivery and per-consumer channel stats."""
+        channels = self._channels.get_stats()
+        return {
+            "published": self.published,
+            "subscriber_errors": self.subscriber_errors,
+            "consumer_errors": self._channels.consumer_errors,
+            "subscribers": {
+                s.name: {
+                    "filter": s.filter.to_dict(),
+                    "delivered": s.delivered,
+                    "errors": s.errors,
+                    "last_error": s.last_error,
+                }
+                for s in self._subscribers
+            },
+            "consumers": {
+                name: {"filter": self._filters[name].to_dict() if name in self._filters else None, **stats}
+                for name, stats in channels.items()
+            },
+        }
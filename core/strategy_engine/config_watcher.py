@@ -0,0 +1,182 @@
+"""
+Hot-reloadable strategy configuration.
+
+Watches a JSON file of strategy parameters and pushes changes into a
+running StrategyEngine, so parameters can be tuned without a restart:
+
+    {
+        "strategies": {
+            "ema_cross": {"fast_period": 8, "slow_period": 21, "confirmation_bars": 2},
+            "macd": {"divergence": "boost", "enabled": false}
+        }
+    }
+
+(the "strategies" wrapper is optional). "enabled" enables/disables a
+strategy, every other key is a parameter.
+
+- The file is polled (modification time and size) from a daemon thread;
+  only parameters that differ from the strategy's current ones are sent
+- A reload goes through StrategyEngine.update_many(): the whole file is
+  validated first and either every change is staged or none is, so a typo
+  can't leave strategies half-updated. Staged changes apply at each
+  strategy's next bar
+- A broken file (invalid JSON, unknown strategy or parameter, bad value) is
+  logged and skipped; the strategies keep running on what they have, and
+  the next save is picked up again
+
+Usage:
+    watcher = engine.watch_config('config/strategies.json')
+    ...
+    watcher.stop()
+
+Configuration:
+- STRATEGY_CONFIG_FILE: Path of the JSON file to watch (default: none)
+- STRATEGY_CONFIG_POLL: Seconds between checks for changes (default 2)
+"""
+
+import json
+import logging
+import os
+import threading
+from datetime import datetime, timezone
+from typing import Any, Dict, Optional, Tuple
+
+logger = logging.getLogger(__name__)
+
+
+class StrategyConfigWatcher:
+    """
+    Polls a strategy config file and applies its changes to a StrategyEngine.
+    """
+
+    def __init__(self, engine: Any, path: Optional[str] = None, interval: Optional[float] = None):
+        """
+        Initialize watcher.
+
+        Args:
+            engine: StrategyEngine to update
+            path: JSON config file (env: STRATEGY_CONFIG_FILE)
+            interval: Seconds between checks (env: STRATEGY_CONFIG_POLL)
+
+        Raises:
+            ValueError: No path configured
+        """
+        self.engine = engine
+        self.path = path or os.getenv('STRATEGY_CONFIG_FILE', '')
+        if not self.path:
+            raise ValueError("No strategy config file given (path or STRATEGY_CONFIG_FILE)")
+        self.interval = interval if interval is not None else float(os.getenv('STRATEGY_CONFIG_POLL', '2'))
+        self._signature: Optional[Tuple[float, int]] = None
+        self._stop = threading.Event()
+        self._thread: Optional[threading.Thread] = None
+        self.reloads = 0
+        self.errors = 0
+        self.last_error: Optional[str] = None
+        self.last_reload: Optional[datetime] = None
+
+    def _file_signature(self) -> Optional[Tuple[float, int]]:
+        try:
+            stat = os.stat(self.path)
+        except OSError:
+            return None
+        return stat.st_mtime, stat.st_size
+
+    def check(self) -> bool:
+        """Reload if the file changed since the last check."""
+        signature = self._file_signature()
+        if signature is None or signature == self._signature:
+            return False
+        self._signature = signature
+        return self.reload()
+
+    def reload(self) -> bool:
+        """
+        Read the file and apply what differs from the running strategies.
+
+        Returns:
+            bool: True if the file was valid (even if nothing changed)
+        """
+        try:
+            with open(self.path) as f:
+                config = json.load(f)
+            updates, enabled = self._diff(config)
+            if updates:
+                self.engine.update_many(updates)
+        except Exception as e:
+            self.errors += 1
+            self.last_error = f"{type(e).__name__}: {e}"
+            logger.error(f"❌ Strategy config {self.path} not applied: {self.last_error}")
+            return False
+        for strategy_id, on in enabled.items():
+            if on:
+                self.engine.enable(strategy_id)
+            else:
+                self.engine.disable(strategy_id)
+            logger.info(f"📊 Strategy {strategy_id} {'enabled' if on else 'disabled'} by {self.path}")
+        self.reloads += 1
+        self.last_reload = datetime.now(timezone.utc)
+        if updates or enabled:
+            logger.info(f"✅ Strategy config {self.path} reloaded: {len(updates)} parameter update(s)")
+        return True
+
+    def _diff(self, config: Any) -> Tuple[Dict[str, Dict[str, Any]], Dict[str, bool]]:
+        """Parameter changes and enable/disable switches the file asks for."""
+        strategies = config.get('strategies', config) if isinstance(config, dict) else None
+        if not isinstance(strategies, dict):
+            raise ValueError("Expected an object of strategy_id -> parameters")
+        updates: Dict[str, Dict[str, Any]] = {}
+        enabled: Dict[str, bool] = {}
+        stats = self.engine.get_stats()['strategies']
+        for strategy_id, entry in strategies.items():
+            if not isinstance(entry, dict):
+                raise ValueError(f"Parameters of {strategy_id} must be an object")
+            if strategy_id not in stats:
+                raise KeyError(f"Unknown strategy '{strategy_id}'")
+            entry = dict(entry)
+            if 'enabled' in entry:
+                on = bool(entry.pop('enabled'))
+                if on != stats[strategy_id]['enabled']:
+                    enabled[strategy_id] = on
+            current = self.engine.get_params(strategy_id)
+            changes = {name: value for name, value in entry.items()
+                       if name not in current or current[name] != value}
+            if changes:
+                updates[strategy_id] = changes
+        return updates, enabled
+
+    # ---------------------------
+    # Polling
+    # ---------------------------
+    def start(self) -> None:
+        """Apply the file now and keep watching it on a daemon thread."""
+        self.check()
+        if self._thread is not None and self._thread.is_alive():
+            return
+        self._stop.clear()
+        self._thread = threading.Thread(target=self._run, name='strategy-config-watcher', daemon=True)
+        self._thread.start()
+        logger.info(f"✨ Watching strategy config {self.path} every {self.interval:g}s")
+
+    def stop(self, timeout: float = 5.0) -> None:
+        self._stop.set()
+        if self._thread is not None:
+            self._thread.join(timeout)
+            self._thread = None
+
+    def _run(self) -> None:
+        while not self._stop.wait(self.interval):
+            try:
+                self.check()
+            except Exception as e:
+                logger.error(f"❌ Strategy config watcher error: {e}")
+
+    def get_stats(self) -> Dict[str, Any]:
+        return {
+            "path": self.path,
+            "interval": self.interval,
+            "running": bool(self._thread and self._thread.is_alive()),
+            "reloads": self.reloads,
+            "errors": self.errors,
+            "last_error": self.last_error,
+            "last_reload": self.last_reload.isoformat() if self.last_reload else None,
+        }
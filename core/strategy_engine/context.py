@@ -0,0 +1,206 @@
+"""
+Multi-timeframe market context for strategies.
+
+A strategy trading 1m signals often wants the 15m trend of the same symbol.
+Instead of every strategy aggregating its own higher-timeframe bars and
+indicators, StrategyEngine gives each strategy a shared MarketContext
+(strategy.context):
+
+- Bars: completed bars of any symbol/timeframe. Attached to a BarAggregator
+  (StrategyEngine.attach() does this) the context reads the aggregator's bar
+  history, and asking for a timeframe the aggregator doesn't build yet
+  registers it there; otherwise (backtests, direct on_bar() calls) it keeps
+  the bars the engine routes through it
+- Indicators: ema()/sma()/atr()/macd()/trend() on any symbol/timeframe are
+  created on first use, caught up from the bar history, and then only fed
+  the bars that closed since the last call, so the cost is shared by every
+  strategy asking for the same thing
+- No lookahead: only completed bars are visible (the forming bar is
+  available separately from an aggregator via forming_bar())
+- An optional history loader seeds a timeframe on first use (e.g. REST
+  history), so higher-timeframe indicators don't start cold
+
+Usage (inside a strategy):
+    def on_bar(self, bar):
+        if self.context.trend(bar.symbol, '15m', 9, 21) > 0:
+            ...
+        atr_15m = self.context.atr(bar.symbol, '15m', 14)
+
+Configuration:
+- MARKET_CONTEXT_HISTORY: Bars kept per symbol/timeframe without an aggregator (default 500)
+"""
+
+import logging
+import os
+import threading
+from collections import defaultdict, deque
+from datetime import datetime
+from typing import Any, Callable, Deque, Dict, List, Optional, Tuple
+
+from core.bar_aggregator import Bar
+from core.strategy_engine.indicators import ATR, EMA, MACD, SMA
+
+logger = logging.getLogger(__name__)
+
+HistoryLoader = Callable[[str, str], List[Bar]]
+
+
+class _Feed:
+    """An indicator plus the time of the last bar it has seen."""
+
+    def __init__(self, indicator: Any, update: Callable[[Any, Bar], Any]):
+        self.indicator = indicator
+        self.update = update
+        self.last: Optional[datetime] = None
+
+
+class MarketContext:
+    """
+    Shared multi-timeframe bars and lazily maintained indicators.
+    """
+
+    def __init__(self, aggregator: Any = None, history_size: Optional[int] = None,
+                 loader: Optional[HistoryLoader] = None):
+        """
+        Initialize context.
+
+        Args:
+            aggregator: BarAggregator to read bar history from (see bind())
+            history_size: Bars kept per symbol/timeframe without an aggregator (env: MARKET_CONTEXT_HISTORY)
+            loader: (symbol, timeframe) -> bars, called once per timeframe on first use
+        """
+        self.aggregator = aggregator
+        self.history_size = (history_size if history_size is not None
+                             else int(os.getenv('MARKET_CONTEXT_HISTORY', '500')))
+        self.loader = loader
+        self._bars: Dict[Tuple[str, str], Deque[Bar]] = defaultdict(lambda: deque(maxlen=self.history_size))
+        self._feeds: Dict[Tuple[Any, ...], _Feed] = {}
+        self._seen: set = set()  # (symbol, timeframe) already registered/loaded
+        self._lock = threading.RLock()
+
+    def bind(self, aggregator: Any) -> None:
+        """Read bars from a BarAggregator from now on."""
+        with self._lock:
+            self.aggregator = aggregator
+            self._seen.clear()
+
+    def on_bar(self, bar: Bar) -> None:
+        """Record a completed bar (StrategyEngine calls this before its strategies see the bar)."""
+        if self.aggregator is not None:
+            return  # The aggregator already has it in its history
+        with self._lock:
+            self._bars[(bar.symbol.upper(), bar.timeframe)].append(bar)
+
+    def reset(self) -> None:
+        """Forget recorded bars and indicators."""
+        with self._lock:
+            self._bars.clear()
+            self._feeds.clear()
+            self._seen.clear()
+
+    # ---------------------------
+    # Bars
+    # ---------------------------
+    def _ensure(self, symbol: str, timeframe: str) -> None:
+        """First use of a symbol/timeframe: have the aggregator build it and seed its history."""
+        key = (symbol, timeframe)
+        if key in self._seen:
+            return
+        self._seen.add(key)
+        history = self.loader(symbol, timeframe) if self.loader is not None else []
+        if self.aggregator is not None:
+            if history:
+                self.aggregator.preload_history(symbol, timeframe, history)
+            else:
+                self.aggregator.register_timeframes(symbol, [timeframe])
+        elif history:
+            known = {bar.timestamp for bar in self._bars[key]}
+            merged = sorted([bar for bar in history if bar.timestamp not in known] + list(self._bars[key]),
+                            key=lambda b: b.timestamp)
+            self._bars[key].clear()
+            self._bars[key].extend(merged)
+        if history:
+            logger.info(f"📊 Market context: loaded {len(history)} {symbol} {timeframe} bars")
+
+    def bars(self, symbol: str, timeframe: str, count: Optional[int] = None) -> List[Bar]:
+        """Completed bars, oldest first (the last count only, if given)."""
+        symbol, timeframe = symbol.upper(), timeframe.strip().lower()
+        with self._lock:
+            self._ensure(symbol, timeframe)
+            if self.aggregator is not None:
+                return self.aggregator.get_bar_history(symbol, timeframe, count)
+            history = list(self._bars.get((symbol, timeframe), ()))
+        return history[-count:] if count else history
+
+    def last_bar(self, symbol: str, timeframe: str) -> Optional[Bar]:
+        bars = self.bars(symbol, timeframe, 1)
+        return bars[-1] if bars else None
+
+    def forming_bar(self, symbol: str, timeframe: str) -> Optional[Bar]:
+        """The bar still being built (aggregator only; None otherwise)."""
+        if self.aggregator is None:
+            return None
+        return self.aggregator.get_current_bar(symbol.upper(), timeframe.strip().lower())
+
+    # ---------------------------
+    # Indicators
+    # ---------------------------
+    def indicator(self, symbol: str, timeframe: str, key: Tuple[Any, ...], factory: Callable[[], Any],
+                  update: Callable[[Any, Bar], Any]) -> Any:
+        """
+        Shared indicator over a symbol/timeframe, fed every completed bar not seen yet.
+
+        Args:
+            key: Identifies the indicator among those on the same symbol/timeframe, e.g. ('ema', 20)
+            factory: Creates the indicator on first use
+            update: (indicator, bar) -> feeds one bar
+        """
+        symbol, timeframe = symbol.upper(), timeframe.strip().lower()
+        bars = self.bars(symbol, timeframe)
+        with self._lock:
+            feed = self._feeds.get((symbol, timeframe) + tuple(key))
+            if feed is None:
+                feed = self._feeds[(symbol, timeframe) + tuple(key)] = _Feed(factory(), update)
+            new: List[Bar] = []
+            for bar in reversed(bars):
+                if feed.last is not None and bar.timestamp <= feed.last:
+                    break
+                new.append(bar)
+            for bar in reversed(new):
+                feed.update(feed.indicator, bar)
+                feed.last = bar.timestamp
+            return feed.indicator
+
+    def ema(self, symbol: str, timeframe: str, period: int) -> Optional[float]:
+        return self.indicator(symbol, timeframe, ('ema', period), lambda: EMA(period),
+                              lambda ema, bar: ema.update(bar.close)).value
+
+    def sma(self, symbol: str, timeframe: str, period: int) -> Optional[float]:
+        return self.indicator(symbol, timeframe, ('sma', period), lambda: SMA(period),
+                              lambda sma, bar: sma.update(bar.close)).value
+
+    def atr(self, symbol: str, timeframe: str, period: int = 14) -> Optional[float]:
+        return self.indicator(symbol, timeframe, ('atr', period), lambda: ATR(period),
+                              lambda atr, bar: atr.update_bar(bar)).value
+
+    def macd(self, symbol: str, timeframe: str, fast: int = 12, slow: int = 26, signal: int = 9) -> MACD:
+        """MACD indicator (value, signal_line, histogram; check ready)."""
+        return self.indicator(symbol, timeframe, ('macd', fast, slow, signal), lambda: MACD(fast, slow, signal),
+                              lambda macd, bar: macd.update(bar.close))
+
+    def trend(self, symbol: str, timeframe: str, fast: int = 9, slow: int = 21) -> int:
+        """+1 if the fast EMA is above the slow one, -1 below, 0 while warming up or equal."""
+        fast_value = self.ema(symbol, timeframe, fast)
+        slow_value = self.ema(symbol, timeframe, slow)
+        if fast_value is None or slow_value is None or fast_value == slow_value:
+            return 0
+        return 1 if fast_value > slow_value else -1
+
+    def get_stats(self) -> Dict[str, Any]:
+        with self._lock:
+            return {
+                "source": "aggregator" if self.aggregator is not None else "engine",
+                "series": sorted(f"{symbol}/{timeframe}" for symbol, timeframe in self._seen),
+                "indicators": len(self._feeds),
+                "bars_kept": sum(len(bars) for bars in self._bars.values()),
+            }
@@ -0,0 +1,192 @@
+"""
+EMA crossover strategy.
+
+Goes long when the fast EMA crosses above the slow EMA and short when it
+crosses below, per symbol:
+- Confirmation: the new ordering must hold for confirmation_bars closed bars
+  (1 = signal on the crossing bar). A cross back before confirmation cancels
+  the pending signal
+- Long/short flags: with one side disabled, a cross towards that side emits
+  a FLAT signal (exit) instead of an entry
+- Stops/targets are ATR multiples from the signal bar's close; confidence
+  grows with the EMA separation measured in ATRs (0.5 at the cross, 1.0 at
+  one ATR apart)
+
+Each symbol keeps its own fast EMA, slow EMA and ATR, all updated from the
+same bar, so the strategy needs warm-up: no signal until the slow EMA has
+seen slow_period bars. Parameter updates (StrategyEngine.update_params)
+re-period each symbol's indicators on its next bar, continuing from their
+current values instead of warming up again.
+
+Configuration (EmaCrossParams.from_env):
+- EMA_CROSS_FAST: Fast EMA period (default 9)
+- EMA_CROSS_SLOW: Slow EMA period (default 21)
+- EMA_CROSS_CONFIRM_BARS: Bars the cross must hold before signalling (default 1)
+- EMA_CROSS_LONG / EMA_CROSS_SHORT: Enable long / short entries (default true)
+- EMA_CROSS_ATR_PERIOD: ATR period for stops and confidence (default 14)
+- EMA_CROSS_STOP_ATR: Stop distance in ATRs, 0 = none (default 1.5)
+- EMA_CROSS_TARGET_ATR: Target distance in ATRs, 0 = none (default 3.0)
+- EMA_CROSS_SYMBOLS: Symbols to trade (default MNQ)
+- EMA_CROSS_TIMEFRAME: Bar timeframe (default 5m)
+"""
+
+import logging
+import os
+from dataclasses import asdict, dataclass
+from typing import Any, Dict, Iterable, Optional
+
+from core.bar_aggregator import Bar
+from core.strategy_engine.indicators import ATR, EMA
+from core.strategy_engine.strategy import Direction, Signal, Strategy, atr_exits
+from infrastructure.config import env_bool
+
+logger = logging.getLogger(__name__)
+
+
+@dataclass
+class EmaCrossParams:
+    """EMA crossover parameters."""
+    fast_period: int = 9
+    slow_period: int = 21
+    confirmation_bars: int = 1
+    allow_long: bool = True
+    allow_short: bool = True
+    atr_period: int = 14
+    stop_atr: float = 1.5
+    target_atr: float = 3.0
+
+    def validate(self) -> 'EmaCrossParams':
+        """
+        Raises:
+            ValueError: Inconsistent parameters
+        """
+        if not 1 <= self.fast_period < self.slow_period:
+            raise ValueError(f"Need 1 <= fast_period < slow_period, got {self.fast_period}/{self.slow_period}")
+        if self.confirmation_bars < 1:
+            raise ValueError(f"confirmation_bars must be >= 1, got {self.confirmation_bars}")
+        if not (self.allow_long or self.allow_short):
+            raise ValueError("At least one of allow_long/allow_short must be enabled")
+        if self.atr_period < 1 or self.stop_atr < 0 or self.target_atr < 0:
+            raise ValueError("atr_period must be >= 1 and stop_atr/target_atr >= 0")
+        return self
+
+    @classmethod
+    def from_env(cls, prefix: str = 'EMA_CROSS_') -> 'EmaCrossParams':
+        """Load parameters from environment variables."""
+        return cls(
+            fast_period=int(os.getenv(f"{prefix}FAST", "9")),
+            slow_period=int(os.getenv(f"{prefix}SLOW", "21")),
+            confirmation_bars=int(os.getenv(f"{prefix}CONFIRM_BARS", "1")),
+            allow_long=env_bool(f"{prefix}LONG", "true"),
+            allow_short=env_bool(f"{prefix}SHORT", "true"),
+            atr_period=int(os.getenv(f"{prefix}ATR_PERIOD", "14")),
+            stop_atr=float(os.getenv(f"{prefix}STOP_ATR", "1.5")),
+            target_atr=float(os.getenv(f"{prefix}TARGET_ATR", "3.0")),
+        ).validate()
+
+
+class _SymbolState:
+    """Indicators and cross tracking for one symbol."""
+
+    def __init__(self, params: EmaCrossParams):
+        self.params = params
+        self.fast = EMA(params.fast_period)
+        self.slow = EMA(params.slow_period)
+        self.atr = ATR(params.atr_period)
+        self.relation = 0  # +1 fast above slow, -1 below, 0 unknown
+        self.pending = 0  # Direction of an unconfirmed cross
+        self.confirmed = 0  # Bars the pending cross has held
+
+    def reconfigure(self, params: EmaCrossParams) -> None:
+        """Adopt new parameters, keeping the indicator values."""
+        self.fast.set_period(params.fast_period)
+        self.slow.set_period(params.slow_period)
+        self.atr.set_period(params.atr_period)
+        self.params = params
+
+
+class EmaCrossStrategy(Strategy):
+    """
+    Fast/slow EMA crossover with confirmation bars and ATR-based exits.
+
+    Usage:
+        strategy = EmaCrossStrategy('ema_cross', EmaCrossParams(fast_period=9, slow_period=21),
+                                    symbols=['MNQ'], timeframes=['5m'])
+        engine.add_strategy(strategy)
+    """
+
+    def __init__(self, strategy_id: str = 'ema_cross', params: Optional[EmaCrossParams] = None,
+                 symbols: Optional[Iterable[str]] = None, timeframes: Optional[Iterable[str]] = None):
+        super().__init__(strategy_id, symbols, timeframes)
+        self.params = (params or EmaCrossParams()).validate()
+        self._state: Dict[str, _SymbolState] = {}
+
+    @classmethod
+    def from_env(cls, strategy_id: str = 'ema_cross', prefix: str = 'EMA_CROSS_') -> 'EmaCrossStrategy':
+        """Build from EMA_CROSS_* environment variables."""
+        return cls(strategy_id, EmaCrossParams.from_env(prefix),
+                   symbols=os.getenv(f"{prefix}SYMBOLS", "MNQ").split(","),
+                   timeframes=[os.getenv(f"{prefix}TIMEFRAME", "5m")])
+
+    def on_bar(self, bar: Bar) -> Optional[Signal]:
+        params = self.params
+        state = self._state.get(bar.symbol)
+        if state is None:
+            state = self._state[bar.symbol] = _SymbolState(params)
+        elif state.params is not params:
+            state.reconfigure(params)
+        fast = state.fast.update(bar.close)
+        slow = state.slow.update(bar.close)
+        state.atr.update_bar(bar)
+        if fast is None or slow is None or fast == slow:
+            return None
+
+        relation = 1 if fast > slow else -1
+        if relation != state.relation:
+            crossed = state.relation != 0  # The first ready bar sets the ordering, it isn't a cross
+            state.relation = relation
+            state.pending, state.confirmed = (relation, 0) if crossed else (0, 0)
+        if not state.pending:
+            return None
+        state.confirmed += 1
+        if state.confirmed < params.confirmation_bars:
+            return None
+        state.pending = 0
+        return self._cross_signal(bar, params, relation, fast, slow, state.atr.value)
+
+    def _cross_signal(self, bar: Bar, params: EmaCrossParams, relation: int, fast: float, slow: float,
+                      atr: Optional[float]) -> Optional[Signal]:
+        wanted = Direction.LONG if relation > 0 else Direction.SHORT
+        allowed = params.allow_long if wanted is Direction.LONG else params.allow_short
+        side = 'above' if relation > 0 else 'below'
+        reason = f"EMA{params.fast_period} crossed {side} EMA{params.slow_period}"
+        if params.confirmation_bars > 1:
+            reason += f" ({params.confirmation_bars} bars confirmed)"
+        if not allowed:
+            return self.signal(bar.symbol, Direction.FLAT, bar.close, bar.timestamp,
+                               timeframe=bar.timeframe, reason=f"{reason}, {wanted.value} entries disabled")
+
+        stop, target = atr_exits(bar.close, relation, atr, params.stop_atr, params.target_atr)
+        confidence = min(1.0, 0.5 + 0.5 * abs(fast - slow) / atr) if atr else 0.5
+        return self.signal(bar.symbol, wanted, bar.close, bar.timestamp, confidence=round(confidence, 3),
+                           stop=stop, target=target, timeframe=bar.timeframe, reason=reason)
+
+    def reset(self, symbol: Optional[str] = None) -> None:
+        if symbol is None:
+            self._state.clear()
+        else:
+            self._state.pop(symbol.upper(), None)
+
+    def get_state(self, symbol: str) -> Dict[str, Any]:
+        state = self._state.get(symbol.upper())
+        if state is None:
+            return {}
+        return {
+            "fast_ema": state.fast.value,
+            "slow_ema": state.slow.value,
+            "atr": state.atr.value,
+            "trend": {1: "up", -1: "down"}.get(state.relation),
+            "pending_cross": {1: "up", -1: "down"}.get(state.pending),
+            "bars_confirmed": state.confirmed,
+            "params": asdict(self.params),
+        }
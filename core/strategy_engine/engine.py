@@ -0,0 +1,470 @@
+"""
+Strategy engine: routes bars and market events to strategies and fans out
+the signals they produce.
+
+- Bars: attach() registers the engine as a BarAggregator bar close listener;
+  each completed bar goes to every enabled strategy that wants its symbol
+  and timeframe. on_bar() can also be called directly, and on_bars() hands
+  a whole batch (backfill, replay) to each strategy in one call
+- Strategies: Strategy subclasses, or any Python object with on_bar/on_tick
+  methods (wrapped in a PyStrategy, see python_strategy)
+- Market events: on_tick() takes Quote/Trade/... events, e.g. as a market
+  event listener of the bot
+- Signals: every Signal is published to the engine's SignalBus; on_signal()
+  is a shortcut for bus.subscribe() (inline, optionally filtered). Queued
+  consumers such as the order router subscribe on the bus directly
+- Market context: every strategy gets the engine's MarketContext
+  (strategy.context) for higher-timeframe bars and shared indicators; the
+  engine records each bar there before the strategies see it, and attach()
+  points it at the aggregator's history
+- Gates: add_gate() registers a check every signal must pass before it is
+  published (e.g. RiskManager blocking entries after the daily loss limit);
+  a gate that raises blocks the signal
+- Parameter updates: update_params() (or a watched config file, see
+  config_watcher) validates new parameters right away and stages them; each
+  strategy switches just before its next bar, so a bar is never evaluated
+  half with old and half with new parameters. The strategy stays
+  registered and subscribed and keeps its indicator state
+- Error isolation: an exception in a strategy is logged and counted without
+  touching the other strategies; after STRATEGY_MAX_ERRORS consecutive
+  errors the strategy is disabled until enable() is called
+- Parallel symbols: with STRATEGY_WORKERS > 0, dispatch_bar()/dispatch_tick()
+  (and attach()) hand events to a SymbolWorkerPool instead of running the
+  strategies on the caller's thread. Each symbol is pinned to one worker, so
+  per-symbol event order is preserved; strategies must then keep their
+  state per symbol (as EmaCrossStrategy does) or lock what they share
+
+Usage:
+    engine = StrategyEngine()
+    engine.add_strategy(EmaCrossStrategy('ema_cross', symbols=['MNQ'], timeframes=['5m']))
+    engine.on_signal(lambda signal: print(signal.to_dict()), min_confidence=0.6)
+    engine.attach(bot.bar_aggregator)
+    engine.update_params('ema_cross', {'fast_period': 8, 'confirmation_bars': 2})
+    engine.watch_config('config/strategies.json')
+
+Configuration:
+- STRATEGY_MAX_ERRORS: Consecutive errors before a strategy is disabled, 0 = never (default 10)
+- STRATEGY_WORKERS: Symbol worker threads, 0 = run inline on the caller's thread (default 0)
+- STRATEGY_WORKER_QUEUE: Events queued per worker before bars block and ticks drop (default 10000)
+- STRATEGY_CONFIG_FILE / STRATEGY_CONFIG_POLL: see config_watcher
+"""
+
+import logging
+import os
+import threading
+from dataclasses import dataclass
+from typing import Any, Callable, Dict, Iterable, List, Optional, Tuple
+
+from core.bar_aggregator import Bar
+from core.market_events import MarketEvent
+from core.strategy_engine.bus import SignalBus
+from core.strategy_engine.config_watcher import StrategyConfigWatcher
+from core.strategy_engine.context import MarketContext
+from core.strategy_engine.python_strategy import as_strategy
+from core.strategy_engine.strategy import Signal, SignalResult, Strategy, params_dict
+from core.strategy_engine.workers import SymbolWorkerPool
+
+logger = logging.getLogger(__name__)
+
+
+@dataclass
+class _StrategySlot:
+    """A registered strategy and its engine-side counters."""
+    strategy: Strategy
+    enabled: bool = True
+    bars: int = 0
+    ticks: int = 0
+    signals: int = 0
+    errors: int = 0
+    consecutive_errors: int = 0
+    last_error: Optional[str] = None
+    pending_params: Any = None  # Staged by update_params(), applied before the next bar
+    param_updates: int = 0
+
+
+class StrategyEngine:
+    """
+    Event router between market data and strategies.
+    """
+
+    def __init__(self, max_errors: Optional[int] = None, bus: Optional[SignalBus] = None,
+                 workers: Optional[int] = None, worker_queue_size: Optional[int] = None,
+                 context: Optional[MarketContext] = None):
+        """
+        Initialize engine.
+
+        Args:
+            max_errors: Consecutive errors before a strategy is disabled, 0 = never (env: STRATEGY_MAX_ERRORS)
+            bus: Signal bus to publish to (default: a bus of its own)
+            workers: Symbol worker threads, 0 = inline (env: STRATEGY_WORKERS)
+            worker_queue_size: Events queued per worker (env: STRATEGY_WORKER_QUEUE)
+            context: Market context shared by the strategies (default: one of its own)
+        """
+        self.max_errors = max_errors if max_errors is not None else int(os.getenv('STRATEGY_MAX_ERRORS', '10'))
+        self.bus = bus if bus is not None else SignalBus()
+        self.context = context if context is not None else MarketContext()
+        workers = workers if workers is not None else int(os.getenv('STRATEGY_WORKERS', '0'))
+        queue_size = (worker_queue_size if worker_queue_size is not None
+                      else int(os.getenv('STRATEGY_WORKER_QUEUE', '10000')))
+        self._pool = SymbolWorkerPool(workers, self._handle, queue_size) if workers > 0 else None
+        self._slots: Dict[str, _StrategySlot] = {}
+        self._order: Tuple[_StrategySlot, ...] = ()  # Registration order, copy-on-write
+        self._lock = threading.Lock()
+        self._counter_lock = threading.Lock()  # Counters are bumped from every worker thread
+        self._params_lock = threading.Lock()
+        self._gates: Tuple[Tuple[str, Callable[[Signal], bool]], ...] = ()  # Copy-on-write
+        self.bars_processed = 0
+        self.ticks_processed = 0
+        self.signals_emitted = 0
+        self.signals_blocked = 0
+
+    # ---------------------------
+    # Registry
+    # ---------------------------
+    def add_strategy(self, strategy: Any, enabled: bool = True, strategy_id: Optional[str] = None) -> Strategy:
+        """
+        Register a strategy.
+
+        Args:
+            strategy: Strategy, or an object with on_bar/on_tick (wrapped in a PyStrategy)
+            enabled: Start enabled
+            strategy_id: Id for a wrapped object (default: its strategy_id, name or class name)
+
+        Returns:
+            Strategy: The registered strategy (the PyStrategy wrapper for plain objects)
+
+        Raises:
+            ValueError: A strategy with the same id is already registered
+            TypeError: The object has no usable handlers
+        """
+        strategy = as_strategy(strategy, strategy_id)
+        with self._lock:
+            if strategy.strategy_id in self._slots:
+                raise ValueError(f"Strategy '{strategy.strategy_id}' is already registered")
+            slot = _StrategySlot(strategy, enabled=enabled)
+            self._slots[strategy.strategy_id] = slot
+            self._order = self._order + (slot,)
+        strategy.bind_context(self.context)
+        logger.info(f"✨ Strategy engine: added {strategy!r}")
+        return strategy
+
+    def remove_strategy(self, strategy_id: str) -> bool:
+        with self._lock:
+            slot = self._slots.pop(strategy_id, None)
+            if slot is None:
+                return False
+            self._order = tuple(s for s in self._order if s is not slot)
+        return True
+
+    def get_strategy(self, strategy_id: str) -> Optional[Strategy]:
+        slot = self._slots.get(strategy_id)
+        return slot.strategy if slot else None
+
+    @property
+    def strategies(self) -> List[Strategy]:
+        return [slot.strategy for slot in self._order]
+
+    def enable(self, strategy_id: str) -> None:
+        """Enable a strategy (also clears its consecutive error count)."""
+        slot = self._slot(strategy_id)
+        slot.enabled = True
+        slot.consecutive_errors = 0
+
+    def disable(self, strategy_id: str) -> None:
+        self._slot(strategy_id).enabled = False
+
+    def _slot(self, strategy_id: str) -> _StrategySlot:
+        slot = self._slots.get(strategy_id)
+        if slot is None:
+            raise KeyError(f"Unknown strategy '{strategy_id}'")
+        return slot
+
+    # ---------------------------
+    # Parameters
+    # ---------------------------
+    def update_params(self, strategy_id: str, params: Dict[str, Any], immediate: bool = False) -> Any:
+        """
+        Change some of a strategy's parameters (see update_many()).
+
+        Returns:
+            The strategy's new parameters
+        """
+        return self.update_many({strategy_id: params}, immediate=immediate)[strategy_id]
+
+    def update_many(self, updates: Dict[str, Dict[str, Any]], immediate: bool = False) -> Dict[str, Any]:
+        """
+        Change parameters of several strategies together.
+
+        Every change is validated before any is staged, so a bad value leaves all strategies as they
+        were. Staged parameters are applied just before each strategy's next bar; changes made before
+        that build on the staged ones.
+
+        Args:
+            updates: strategy_id -> {parameter: value}
+            immediate: Apply now instead of at the next bar (strategies that only see ticks)
+
+        Returns:
+            Dict: strategy_id -> new parameters
+
+        Raises:
+            KeyError: Unknown strategy (nothing staged)
+            TypeError / ValueError: Strategy without parameters, unknown or invalid values (nothing staged)
+        """
+        with self._params_lock:
+            staged = {}
+            for strategy_id, changes in updates.items():
+                slot = self._slot(strategy_id)
+                staged[strategy_id] = (slot, slot.strategy.params_with(dict(changes), slot.pending_params))
+            for slot, params in staged.values():
+                slot.pending_params = params
+        for strategy_id, changes in updates.items():
+            logger.info(f"📊 Strategy {strategy_id}: staged parameter update {dict(changes)}")
+        if immediate:
+            for slot, _ in staged.values():
+                self._apply_params(slot)
+        return {strategy_id: params for strategy_id, (_, params) in staged.items()}
+
+    def get_params(self, strategy_id: str) -> Dict[str, Any]:
+        """A strategy's parameters, including staged changes not applied yet."""
+        slot = self._slot(strategy_id)
+        pending = slot.pending_params
+        return params_dict(pending) if pending is not None else slot.strategy.get_params()
+
+    def _apply_params(self, slot: _StrategySlot) -> None:
+        with self._params_lock:
+            params, slot.pending_params = slot.pending_params, None
+        if params is None:
+            return
+        try:
+            slot.strategy.set_params(params)
+        except Exception as e:
+            logger.error(f"❌ Strategy {slot.strategy.strategy_id}: parameter update failed: {e}")
+            return
+        slot.param_updates += 1
+        logger.info(f"✅ Strategy {slot.strategy.strategy_id}: parameters updated")
+
+    def watch_config(self, path: Optional[str] = None, interval: Optional[float] = None) -> StrategyConfigWatcher:
+        """
+        Apply parameter changes from a JSON config file whenever it changes (see config_watcher).
+
+        Returns:
+            StrategyConfigWatcher: The started watcher (stop() it to stop watching)
+        """
+        watcher = StrategyConfigWatcher(self, path, interval)
+        watcher.start()
+        return watcher
+
+    # ---------------------------
+    # Signals
+    # ---------------------------
+    def on_signal(self, callback: Callable[[Signal], Any], **filters: Any) -> Callable[[], None]:
+        """
+        Subscribe a callback to the engine's signals (see SignalBus.subscribe() for filters).
+
+        Returns:
+            Callable: Unsubscribe function
+        """
+        return self.bus.subscribe(callback, **filters)
+
+    def add_gate(self, gate: Callable[[Signal], bool], name: Optional[str] = None) -> Callable[[], None]:
+        """
+        Register a check run before every signal is published; signals it returns False for are dropped.
+
+        Returns:
+            Callable: Remove function
+        """
+        entry = (name or getattr(gate, '__qualname__', repr(gate)), gate)
+        with self._lock:
+            self._gates = self._gates + (entry,)
+
+        def remove() -> None:
+            with self._lock:
+                self._gates = tuple(g for g in self._gates if g is not entry)
+        return remove
+
+    def _passes_gates(self, signal: Signal) -> bool:
+        for name, gate in self._gates:
+            try:
+                if gate(signal) is False:
+                    logger.debug(f"Signal {signal.strategy_id}/{signal.symbol} {signal.direction.value} "
+                                 f"blocked by gate {name}")
+                    return False
+            except Exception as e:
+                # Fail closed: a broken risk check must not let signals through
+                logger.error(f"❌ Signal gate {name} failed, blocking {signal.strategy_id}/{signal.symbol}: {e}")
+                return False
+        return True
+
+    def _emit(self, signal: Signal) -> bool:
+        if self._gates and not self._passes_gates(signal):
+            with self._counter_lock:
+                self.signals_blocked += 1
+            return False
+        with self._counter_lock:
+            self.signals_emitted += 1
+        self.bus.publish(signal)
+        return True
+
+    def publish(self, signal: Signal) -> bool:
+        """
+        Publish a signal that doesn't come from a strategy handler (e.g. a gate flattening a position).
+
+        Returns:
+            bool: False if a gate dropped it
+        """
+        return self._emit(signal)
+
+    # ---------------------------
+    # Event routing
+    # ---------------------------
+    def attach(self, bar_aggregator: Any, symbol: Optional[str] = None,
+               timeframes: Optional[Iterable[str]] = None) -> Callable[[], None]:
+        """
+        Receive completed bars from a BarAggregator (and read market context history from it).
+
+        Returns:
+            Callable: Detach function
+        """
+        if self.context.aggregator is None:
+            self.context.bind(bar_aggregator)
+        return bar_aggregator.on_bar_close(self.dispatch_bar, symbol=symbol, timeframes=timeframes)
+
+    def dispatch_bar(self, bar: Bar) -> None:
+        """Process a bar on its symbol's worker (inline without workers)."""
+        if self._pool is None:
+            self.on_bar(bar)
+        else:
+            self._pool.submit('bar', bar)
+
+    def dispatch_tick(self, event: MarketEvent) -> None:
+        """Process a market event on its symbol's worker (inline without workers, dropped if it is backed up)."""
+        if self._pool is None:
+            self.on_tick(event)
+        else:
+            self._pool.submit('tick', event, block=False)
+
+    def _handle(self, kind: str, event: Any) -> None:
+        if kind == 'bar':
+            self.on_bar(event)
+        else:
+            self.on_tick(event)
+
+    def flush(self, timeout: Optional[float] = None) -> bool:
+        """
+        Wait until dispatched events have been processed (no-op without workers).
+
+        Returns:
+            bool: False on timeout
+        """
+        return self._pool.flush(timeout) if self._pool is not None else True
+
+    def stop(self, timeout: float = 5.0) -> None:
+        """Process queued events and stop the worker threads (dispatching again restarts them)."""
+        if self._pool is not None:
+            self._pool.stop(timeout)
+
+    def on_bar(self, bar: Bar) -> List[Signal]:
+        """Run a completed bar through every interested strategy on this thread, returning the signals emitted."""
+        with self._counter_lock:
+            self.bars_processed += 1
+        self.context.on_bar(bar)
+        signals: List[Signal] = []
+        for slot in self._order:
+            if slot.strategy.wants(bar.symbol, bar.timeframe):
+                if slot.pending_params is not None:
+                    self._apply_params(slot)
+                if slot.enabled:
+                    self._collect(slot, slot.strategy.on_bar, bar, signals, 'bars')
+        return signals
+
+    def on_bars(self, bars: Iterable[Bar]) -> List[Signal]:
+        """
+        Run a batch of bars through every interested strategy, one handler call per strategy.
+
+        Each strategy sees its bars in order, but strategies run one after another, so signals come
+        grouped by strategy rather than interleaved by time. An exception counts as one error for the
+        whole batch.
+        """
+        bars = list(bars)
+        with self._counter_lock:
+            self.bars_processed += len(bars)
+        for bar in bars:
+            self.context.on_bar(bar)
+        signals: List[Signal] = []
+        for slot in self._order:
+            wanted = [bar for bar in bars if slot.strategy.wants(bar.symbol, bar.timeframe)]
+            if wanted and slot.pending_params is not None:
+                self._apply_params(slot)
+            if slot.enabled and wanted:
+                self._collect(slot, slot.strategy.on_bars, wanted, signals, 'bars', len(wanted))
+        return signals
+
+    def on_tick(self, event: MarketEvent) -> List[Signal]:
+        """Run a market event through every interested strategy on this thread, returning the signals emitted."""
+        with self._counter_lock:
+            self.ticks_processed += 1
+        signals: List[Signal] = []
+        for slot in self._order:
+            if slot.enabled and slot.strategy.wants(event.symbol):
+                self._collect(slot, slot.strategy.on_tick, event, signals, 'ticks')
+        return signals
+
+    def _collect(self, slot: _StrategySlot, handler: Callable[[Any], SignalResult], event: Any,
+                 signals: List[Signal], counter: str, count: int = 1) -> None:
+        """Call a strategy handler with error isolation and emit what it returns."""
+        symbol = event[-1].symbol if isinstance(event, list) else event.symbol
+        try:
+            result = handler(event)
+        except Exception as e:
+            with self._counter_lock:
+                setattr(slot, counter, getattr(slot, counter) + count)
+                slot.errors += 1
+                slot.consecutive_errors += 1
+                slot.last_error = f"{type(e).__name__}: {e}"
+                disable = bool(self.max_errors) and slot.enabled and slot.consecutive_errors >= self.max_errors
+                if disable:
+                    slot.enabled = False
+            logger.error(f"❌ Strategy {slot.strategy.strategy_id} failed on {symbol}: {slot.last_error}")
+            if disable:
+                logger.error(f"❌ Strategy {slot.strategy.strategy_id} disabled after "
+                             f"{slot.consecutive_errors} consecutive errors")
+            return
+        emitted = [] if result is None else result if isinstance(result, list) else [result]
+        with self._counter_lock:
+            setattr(slot, counter, getattr(slot, counter) + count)
+            slot.consecutive_errors = 0
+            slot.signals += len(emitted)
+        for signal in emitted:
+            if self._emit(signal):
+                signals.append(signal)
+
+    # ---------------------------
+    # Status
+    # ---------------------------
+    def get_stats(self) -> Dict[str, Any]:
+        """Engine counters and per-strategy status."""
+        return {
+            "bars_processed": self.bars_processed,
+            "ticks_processed": self.ticks_processed,
+            "signals_emitted": self.signals_emitted,
+            "signals_blocked": self.signals_blocked,
+            "gates": [name for name, _ in self._gates],
+            "bus": self.bus.get_stats(),
+            "workers": self._pool.get_stats() if self._pool is not None else None,
+            "strategies": {
+                slot.strategy.strategy_id: {
+                    "type": type(slot.strategy).__name__,
+                    "enabled": slot.enabled,
+                    "symbols": sorted(slot.strategy.symbols) if slot.strategy.symbols else None,
+                    "timeframes": sorted(slot.strategy.timeframes) if slot.strategy.timeframes else None,
+                    "bars": slot.bars,
+                    "ticks": slot.ticks,
+                    "signals": slot.signals,
+                    "errors": slot.errors,
+                    "last_error": slot.last_error,
+                    "param_updates": slot.param_updates,
+                    "params_pending": slot.pending_params is not None,
+                }
+                for slot in self._order
+            },
+        }
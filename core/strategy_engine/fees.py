@@ -0,0 +1,109 @@
+"""
+Commission and exchange fee model.
+
+Per-contract, per-side trading costs by root symbol, so realized P&L
+computed locally (RiskManager, backtests, the bot's trade history and
+performance statistics) is net of the same costs the broker statement
+deducts.
+
+Every fill (entry or exit side) costs quantity x (commission + exchange fee):
+- commission: the broker's charge
+- exchange fee: exchange, clearing and NFA fees
+A round trip costs twice that. Symbols without an entry of their own use
+the default commission and fee.
+
+Usage:
+    fees = CommissionModel(commissions={'MNQ': 0.25}, exchange_fees={'MNQ': 0.37})
+    fees.cost('MNQZ5', 2)                # One side, 2 contracts: 1.24
+    fees.round_turn('MNQ')               # 1.24
+    Backtester(strategies, BacktestConfig(fees=fees))
+    RiskManager(fees=fees)
+
+Configuration:
+- COMMISSION_PER_CONTRACT: Default commission per contract per side in dollars (default 0)
+- COMMISSIONS: Per-symbol commissions, e.g. "MNQ:0.25,ES:1.40" (default empty)
+- EXCHANGE_FEE_PER_CONTRACT: Default exchange/clearing/NFA fee per contract per side (default 0)
+- EXCHANGE_FEES: Per-symbol exchange fees, e.g. "MNQ:0.37,ES:1.38" (default empty)
+"""
+
+import os
+from dataclasses import dataclass
+from typing import Any, Dict, Optional
+
+from core.strategy_engine.portfolio import parse_symbol_values
+from core.volume_profile import normalize_symbol
+
+
+@dataclass(frozen=True)
+class FeeSchedule:
+    """Costs of one contract on one side of a trade."""
+    commission: float = 0.0
+    exchange_fee: float = 0.0
+
+    @property
+    def per_side(self) -> float:
+        return self.commission + self.exchange_fee
+
+
+class CommissionModel:
+    """
+    Commission and exchange fees per contract per side, by root symbol.
+    """
+
+    def __init__(self, commissions: Optional[Dict[str, float]] = None,
+                 exchange_fees: Optional[Dict[str, float]] = None,
+                 default_commission: Optional[float] = None, default_exchange_fee: Optional[float] = None):
+        """
+        Initialize model.
+
+        Args:
+            commissions: Commission by root symbol (env: COMMISSIONS)
+            exchange_fees: Exchange fee by root symbol (env: EXCHANGE_FEES)
+            default_commission: Commission for other symbols (env: COMMISSION_PER_CONTRACT)
+            default_exchange_fee: Exchange fee for other symbols (env: EXCHANGE_FEE_PER_CONTRACT)
+
+        Raises:
+            ValueError: Malformed per-symbol entry or a negative cost
+        """
+        if commissions is None:
+            commissions = parse_symbol_values(os.getenv('COMMISSIONS'))
+        if exchange_fees is None:
+            exchange_fees = parse_symbol_values(os.getenv('EXCHANGE_FEES'))
+        self.commissions = {normalize_symbol(k): float(v) for k, v in commissions.items()}
+        self.exchange_fees = {normalize_symbol(k): float(v) for k, v in exchange_fees.items()}
+        self.default_commission = (default_commission if default_commission is not None
+                                   else float(os.getenv('COMMISSION_PER_CONTRACT', '0')))
+        self.default_exchange_fee = (default_exchange_fee if default_exchange_fee is not None
+                                     else float(os.getenv('EXCHANGE_FEE_PER_CONTRACT', '0')))
+        values = [*self.commissions.values(), *self.exchange_fees.values(),
+                  self.default_commission, self.default_exchange_fee]
+        if any(value < 0 for value in values):
+            raise ValueError("Commissions and exchange fees must be >= 0")
+
+    @property
+    def configured(self) -> bool:
+        """Whether any symbol has a non-zero cost."""
+        return any((*self.commissions.values(), *self.exchange_fees.values(),
+                    self.default_commission, self.default_exchange_fee))
+
+    def schedule(self, symbol: str) -> FeeSchedule:
+        root = normalize_symbol(symbol)
+        return FeeSchedule(commission=self.commissions.get(root, self.default_commission),
+                           exchange_fee=self.exchange_fees.get(root, self.default_exchange_fee))
+
+    def cost(self, symbol: str, quantity: int, sides: int = 1) -> float:
+        """Commission plus fees in dollars for quantity contracts on the given number of sides."""
+        return round(self.schedule(symbol).per_side * abs(int(quantity)) * sides, 10)
+
+    def round_turn(self, symbol: str, quantity: int = 1) -> float:
+        return self.cost(symbol, quantity, sides=2)
+
+    def get_schedule(self) -> Dict[str, Any]:
+        """Configured costs per contract per side (symbols with their own entry, plus defaults)."""
+        symbols = sorted(set(self.commissions) | set(self.exchange_fees))
+        return {
+            'default': {'commission': self.default_commission, 'exchange_fee': self.default_exchange_fee},
+            'symbols': {symbol: {'commission': self.schedule(symbol).commission,
+                                 'exchange_fee': self.schedule(symbol).exchange_fee,
+                                 'round_turn': self.round_turn(symbol)} for symbol in symbols},
+        }
@@ -0,0 +1,210 @@
+"""
+Streaming technical indicators for strategy_engine.
+
+Every indicator is fed one input at a time and updates in O(1):
+- update(...): feed the next input, returns the new value (None while warming up)
+- value: latest value (None while warming up)
+- ready: True once value is available
+- reset(): forget all inputs
+- set_period(period): change the period in place, keeping the state built
+  so far (for parameter updates on running strategies)
+
+Strategies keep one instance per symbol, so per-symbol indicator state is a
+handful of floats and stays in step with the bars the strategy has seen.
+
+Indicators:
+- SMA(period): simple moving average
+- EMA(period): exponential moving average, seeded with the SMA of its first
+  `period` inputs (the TradingView / TA-Lib convention)
+- ATR(period): Wilder's average true range, fed with bars (high/low/close)
+- MACD(fast, slow, signal): MACD line (fast EMA - slow EMA), its signal line
+  (EMA of the MACD line) and the histogram (MACD - signal)
+"""
+
+import logging
+from collections import deque
+from typing import Deque, Optional
+
+from core.bar_aggregator import Bar
+
+logger = logging.getLogger(__name__)
+
+
+def _check_period(period: int) -> int:
+    if int(period) != period or period < 1:
+        raise ValueError(f"Indicator period must be a positive integer, got {period}")
+    return int(period)
+
+
+class Indicator:
+    """Shared value/ready/reset behaviour of streaming indicators."""
+
+    def __init__(self, period: int):
+        self.period = _check_period(period)
+        self.value: Optional[float] = None
+        self.count = 0  # Inputs seen
+
+    @property
+    def ready(self) -> bool:
+        return self.value is not None
+
+    def reset(self) -> None:
+        self.value = None
+        self.count = 0
+
+    def __repr__(self) -> str:
+        return f"{type(self).__name__}({self.period}, value={self.value})"
+
+
+class SMA(Indicator):
+    """Simple moving average of the last `period` inputs."""
+
+    def __init__(self, period: int):
+        super().__init__(period)
+        self._window: Deque[float] = deque()
+        self._sum = 0.0
+
+    def update(self, value: float) -> Optional[float]:
+        self.count += 1
+        self._window.append(value)
+        self._sum += value
+        if len(self._window) > self.period:
+            self._sum -= self._window.popleft()
+        if len(self._window) == self.period:
+            self.value = self._sum / self.period
+        return self.value
+
+    def set_period(self, period: int) -> None:
+        """Change the window: shrinking keeps the newest inputs, growing waits for more inputs."""
+        self.period = _check_period(period)
+        while len(self._window) > self.period:
+            self._sum -= self._window.popleft()
+        self.value = self._sum / self.period if len(self._window) == self.period else None
+
+    def reset(self) -> None:
+        super().reset()
+        self._window.clear()
+        self._sum = 0.0
+
+
+class EMA(Indicator):
+    """Exponential moving average (alpha = 2 / (period + 1)), SMA-seeded."""
+
+    def __init__(self, period: int):
+        super().__init__(period)
+        self.alpha = 2.0 / (self.period + 1)
+        self._seed_sum = 0.0
+
+    def update(self, value: float) -> Optional[float]:
+        self.count += 1
+        if self.value is None:
+            self._seed_sum += value
+            if self.count == self.period:
+                self.value = self._seed_sum / self.period
+            return self.value
+        self.value += self.alpha * (value - self.value)
+        return self.value
+
+    def set_period(self, period: int) -> None:
+        """Change the smoothing, continuing from the current value (still seeding: the mean of the inputs seen)."""
+        self.period = _check_period(period)
+        self.alpha = 2.0 / (self.period + 1)
+        if self.value is None and self.count >= self.period:
+            self.value = self._seed_sum / self.count
+
+    def reset(self) -> None:
+        super().reset()
+        self._seed_sum = 0.0
+
+
+class ATR(Indicator):
+    """Wilder's average true range, seeded with the mean of the first `period` true ranges."""
+
+    def __init__(self, period: int = 14):
+        super().__init__(period)
+        self._prev_close: Optional[float] = None
+        self._seed_sum = 0.0
+
+    def update(self, high: float, low: float, close: float) -> Optional[float]:
+        if self._prev_close is None:
+            true_range = high - low
+        else:
+            true_range = max(high - low, abs(high - self._prev_close), abs(low - self._prev_close))
+        self._prev_close = close
+        self.count += 1
+        if self.value is None:
+            self._seed_sum += true_range
+            if self.count == self.period:
+                self.value = self._seed_sum / self.period
+            return self.value
+        self.value = (self.value * (self.period - 1) + true_range) / self.period
+        return self.value
+
+    def update_bar(self, bar: Bar) -> Optional[float]:
+        return self.update(bar.high, bar.low, bar.close)
+
+    def set_period(self, period: int) -> None:
+        """Change the smoothing, continuing from the current value (still seeding: the mean of the ranges seen)."""
+        self.period = _check_period(period)
+        if self.value is None and self.count >= self.period:
+            self.value = self._seed_sum / self.count
+
+    def reset(self) -> None:
+        super().reset()
+        self._prev_close = None
+        self._seed_sum = 0.0
+
+
+class MACD(Indicator):
+    """
+    Moving average convergence/divergence.
+
+    value is the MACD line; signal_line and histogram follow once the signal
+    EMA has warmed up (slow + signal - 1 inputs).
+    """
+
+    def __init__(self, fast: int = 12, slow: int = 26, signal: int = 9):
+        if not _check_period(fast) < _check_period(slow):
+            raise ValueError(f"MACD fast period must be below the slow period, got {fast}/{slow}")
+        super().__init__(slow)
+        self.fast = EMA(fast)
+        self.slow = EMA(slow)
+        self.signal = EMA(signal)
+        self.signal_line: Optional[float] = None
+        self.histogram: Optional[float] = None
+
+    @property
+    def ready(self) -> bool:
+        return self.histogram is not None
+
+    def update(self, value: float) -> Optional[float]:
+        """Feed a close, returns the histogram (None while warming up)."""
+        self.count += 1
+        fast = self.fast.update(value)
+        slow = self.slow.update(value)
+        if fast is None or slow is None:
+            return None
+        self.value = fast - slow
+        self.signal_line = self.signal.update(self.value)
+        if self.signal_line is not None:
+            self.histogram = self.value - self.signal_line
+        return self.histogram
+
+    def set_periods(self, fast: int, slow: int, signal: int) -> None:
+        """Change all three periods, continuing from the current EMA values."""
+        if not _check_period(fast) < _check_period(slow):
+            raise ValueError(f"MACD fast period must be below the slow period, got {fast}/{slow}")
+        self.period = int(slow)
+        for ema, period in ((self.fast, fast), (self.slow, slow), (self.signal, signal)):
+            ema.set_period(period)
+
+    def reset(self) -> None:
+        super().reset()
+        for ema in (self.fast, self.slow, self.signal):
+            ema.reset()
+        self.signal_line = None
+        self.histogram = None
+
+    def __repr__(self) -> str:
+        return (f"MACD({self.fast.period}, {self.slow.period}, {self.signal.period}, "
+                f"value={self.value}, signal={self.signal_line}, histogram={self.histogram})")
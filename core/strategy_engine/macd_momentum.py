@@ -0,0 +1,226 @@
+"""
+MACD momentum strategy.
+
+Trades MACD / signal line crosses (the histogram changing sign), per symbol:
+- Zero-line filter: longs only while the MACD line is above zero and shorts
+  only while it is below, so crosses against the prevailing trend are skipped
+- Histogram divergence: each run of same-signed histogram bars is a swing;
+  a bullish cross that closes a negative swing whose price low undercut the
+  previous negative swing while its histogram low did not is a bullish
+  divergence (mirror image for bearish). With divergence='boost' it raises
+  confidence, with divergence='require' crosses without one are skipped
+- Long/short flags: with one side disabled, a cross towards that side emits
+  a FLAT signal (exit) instead of an entry
+- Stops/targets are ATR multiples from the signal bar's close; confidence
+  grows with the MACD line's distance from zero measured in ATRs
+
+No signal until the signal line has warmed up (slow + signal - 1 bars).
+
+Configuration (MacdParams.from_env):
+- MACD_FAST / MACD_SLOW / MACD_SIGNAL: EMA periods (default 12 / 26 / 9)
+- MACD_ZERO_LINE_FILTER: Only trade crosses on the trend side of zero (default true)
+- MACD_DIVERGENCE: off, boost or require (default off)
+- MACD_LONG / MACD_SHORT: Enable long / short entries (default true)
+- MACD_ATR_PERIOD: ATR period for stops and confidence (default 14)
+- MACD_STOP_ATR: Stop distance in ATRs, 0 = none (default 1.5)
+- MACD_TARGET_ATR: Target distance in ATRs, 0 = none (default 3.0)
+- MACD_SYMBOLS: Symbols to trade (default MNQ)
+- MACD_TIMEFRAME: Bar timeframe (default 5m)
+"""
+
+import logging
+import os
+from dataclasses import asdict, dataclass
+from typing import Any, Dict, Iterable, Optional
+
+from core.bar_aggregator import Bar
+from core.strategy_engine.indicators import ATR, MACD
+from core.strategy_engine.strategy import Direction, Signal, Strategy, atr_exits
+from infrastructure.config import env_bool
+
+logger = logging.getLogger(__name__)
+
+DIVERGENCE_MODES = ('off', 'boost', 'require')
+
+
+@dataclass
+class MacdParams:
+    """MACD momentum parameters."""
+    fast_period: int = 12
+    slow_period: int = 26
+    signal_period: int = 9
+    zero_line_filter: bool = True
+    divergence: str = 'off'
+    allow_long: bool = True
+    allow_short: bool = True
+    atr_period: int = 14
+    stop_atr: float = 1.5
+    target_atr: float = 3.0
+
+    def validate(self) -> 'MacdParams':
+        """
+        Raises:
+            ValueError: Inconsistent parameters
+        """
+        if not 1 <= self.fast_period < self.slow_period or self.signal_period < 1:
+            raise ValueError(f"Need 1 <= fast_period < slow_period and signal_period >= 1, got "
+                             f"{self.fast_period}/{self.slow_period}/{self.signal_period}")
+        if self.divergence not in DIVERGENCE_MODES:
+            raise ValueError(f"divergence must be one of {DIVERGENCE_MODES}, got {self.divergence!r}")
+        if not (self.allow_long or self.allow_short):
+            raise ValueError("At least one of allow_long/allow_short must be enabled")
+        if self.atr_period < 1 or self.stop_atr < 0 or self.target_atr < 0:
+            raise ValueError("atr_period must be >= 1 and stop_atr/target_atr >= 0")
+        return self
+
+    @classmethod
+    def from_env(cls, prefix: str = 'MACD_') -> 'MacdParams':
+        """Load parameters from environment variables."""
+        return cls(
+            fast_period=int(os.getenv(f"{prefix}FAST", "12")),
+            slow_period=int(os.getenv(f"{prefix}SLOW", "26")),
+            signal_period=int(os.getenv(f"{prefix}SIGNAL", "9")),
+            zero_line_filter=env_bool(f"{prefix}ZERO_LINE_FILTER", "true"),
+            divergence=os.getenv(f"{prefix}DIVERGENCE", "off").strip().lower(),
+            allow_long=env_bool(f"{prefix}LONG", "true"),
+            allow_short=env_bool(f"{prefix}SHORT", "true"),
+            atr_period=int(os.getenv(f"{prefix}ATR_PERIOD", "14")),
+            stop_atr=float(os.getenv(f"{prefix}STOP_ATR", "1.5")),
+            target_atr=float(os.getenv(f"{prefix}TARGET_ATR", "3.0")),
+        ).validate()
+
+
+class _Swing:
+    """Price and histogram extremes of one run of same-signed histogram bars."""
+
+    __slots__ = ('sign', 'price', 'histogram')
+
+    def __init__(self, sign: int, bar: Bar, histogram: float):
+        self.sign = sign
+        self.price = bar.high if sign > 0 else bar.low
+        self.histogram = histogram
+
+    def extend(self, bar: Bar, histogram: float) -> None:
+        if self.sign > 0:
+            self.price = max(self.price, bar.high)
+            self.histogram = max(self.histogram, histogram)
+        else:
+            self.price = min(self.price, bar.low)
+            self.histogram = min(self.histogram, histogram)
+
+    def diverges_from(self, previous: Optional['_Swing']) -> bool:
+        """True if price made a new extreme beyond `previous` but the histogram didn't."""
+        if previous is None:
+            return False
+        if self.sign > 0:
+            return self.price > previous.price and self.histogram < previous.histogram
+        return self.price < previous.price and self.histogram > previous.histogram
+
+
+class _SymbolState:
+    """Indicators and histogram swings for one symbol."""
+
+    def __init__(self, params: MacdParams):
+        self.params = params
+        self.macd = MACD(params.fast_period, params.slow_period, params.signal_period)
+        self.atr = ATR(params.atr_period)
+        self.swing: Optional[_Swing] = None  # Current histogram run
+        self.last_swing: Dict[int, _Swing] = {}  # Last completed run per sign
+
+    def reconfigure(self, params: MacdParams) -> None:
+        """Adopt new parameters, keeping the indicator values and swings."""
+        self.macd.set_periods(params.fast_period, params.slow_period, params.signal_period)
+        self.atr.set_period(params.atr_period)
+        self.params = params
+
+
+class MacdMomentumStrategy(Strategy):
+    """
+    MACD signal line crosses with zero-line filter, optional histogram divergence and ATR-based exits.
+
+    Usage:
+        strategy = MacdMomentumStrategy('macd', MacdParams(divergence='boost'),
+                                        symbols=['MNQ'], timeframes=['5m'])
+        engine.add_strategy(strategy)
+    """
+
+    def __init__(self, strategy_id: str = 'macd_momentum', params: Optional[MacdParams] = None,
+                 symbols: Optional[Iterable[str]] = None, timeframes: Optional[Iterable[str]] = None):
+        super().__init__(strategy_id, symbols, timeframes)
+        self.params = (params or MacdParams()).validate()
+        self._state: Dict[str, _SymbolState] = {}
+
+    @classmethod
+    def from_env(cls, strategy_id: str = 'macd_momentum', prefix: str = 'MACD_') -> 'MacdMomentumStrategy':
+        """Build from MACD_* environment variables."""
+        return cls(strategy_id, MacdParams.from_env(prefix),
+                   symbols=os.getenv(f"{prefix}SYMBOLS", "MNQ").split(","),
+                   timeframes=[os.getenv(f"{prefix}TIMEFRAME", "5m")])
+
+    def on_bar(self, bar: Bar) -> Optional[Signal]:
+        params = self.params
+        state = self._state.get(bar.symbol)
+        if state is None:
+            state = self._state[bar.symbol] = _SymbolState(params)
+        elif state.params is not params:
+            state.reconfigure(params)
+        histogram = state.macd.update(bar.close)
+        state.atr.update_bar(bar)
+        if histogram is None or histogram == 0:
+            return None
+
+        sign = 1 if histogram > 0 else -1
+        swing = state.swing
+        if swing is not None and swing.sign == sign:
+            swing.extend(bar, histogram)
+            return None
+        state.swing = _Swing(sign, bar, histogram)
+        if swing is None:
+            return None  # The first ready bar sets the side, it isn't a cross
+
+        # The histogram flipped: the run that just ended is the swing a divergence is judged on
+        divergence = swing.diverges_from(state.last_swing.get(swing.sign))
+        state.last_swing[swing.sign] = swing
+        return self._cross_signal(bar, params, sign, state.macd.value, divergence, state.atr.value)
+
+    def _cross_signal(self, bar: Bar, params: MacdParams, sign: int, macd: float, divergence: bool,
+                      atr: Optional[float]) -> Optional[Signal]:
+        wanted = Direction.LONG if sign > 0 else Direction.SHORT
+        allowed = params.allow_long if wanted is Direction.LONG else params.allow_short
+        reason = f"MACD crossed {'above' if sign > 0 else 'below'} signal line"
+        if not allowed:
+            # Exits aren't filtered: an opposite cross always flattens the disabled side
+            return self.signal(bar.symbol, Direction.FLAT, bar.close, bar.timestamp,
+                               timeframe=bar.timeframe, reason=f"{reason}, {wanted.value} entries disabled")
+        if params.zero_line_filter and macd * sign <= 0:
+            return None
+        if params.divergence == 'require' and not divergence:
+            return None
+
+        if divergence:
+            reason += f", {'bullish' if sign > 0 else 'bearish'} histogram divergence"
+        stop, target = atr_exits(bar.close, sign, atr, params.stop_atr, params.target_atr)
+        confidence = min(1.0, 0.5 + 0.5 * abs(macd) / atr) if atr else 0.5
+        if divergence and params.divergence != 'off':
+            confidence = min(1.0, confidence + 0.25)
+        return self.signal(bar.symbol, wanted, bar.close, bar.timestamp, confidence=round(confidence, 3),
+                           stop=stop, target=target, timeframe=bar.timeframe, reason=reason)
+
+    def reset(self, symbol: Optional[str] = None) -> None:
+        if symbol is None:
+            self._state.clear()
+        else:
+            self._state.pop(symbol.upper(), None)
+
+    def get_state(self, symbol: str) -> Dict[str, Any]:
+        state = self._state.get(symbol.upper())
+        if state is None:
+            return {}
+        return {
+            "macd": state.macd.value,
+            "signal": state.macd.signal_line,
+            "histogram": state.macd.histogram,
+            "atr": state.atr.value,
+            "momentum": {1: "up", -1: "down"}.get(state.swing.sign) if state.swing else None,
+            "params": asdict(self.params),
+        }
@@ -0,0 +1,224 @@
+"""
+Monte Carlo trade-sequence simulation.
+
+A backtest produces one path through a strategy's trades; the same trades in
+a different order can have a much deeper drawdown. MonteCarlo resamples the
+trade P&L distribution thousands of times to show the range of outcomes:
+
+- Resampling with replacement (i.i.d.), or block bootstrap (block_size > 1:
+  runs of consecutive trades are drawn together, keeping streaks and
+  volatility clustering that plain resampling breaks up); blocks wrap around
+  the end of the trade list
+- Every path starts at the initial capital and records its maximum drawdown
+  (dollars and percent of peak), final equity, longest losing streak and
+  whether it hit the ruin level (equity at or below initial capital minus
+  ruin_loss, e.g. a prop account's max loss limit)
+- Summary percentiles of each distribution and the probability of ruin;
+  probability_of_drawdown() answers "how likely is a $X drawdown"
+- Simulations run in chunks with their own seeds across worker processes,
+  so a seeded run gives the same result with any number of workers
+
+Usage:
+    mc = MonteCarlo(result.trades, initial_capital=50000, ruin_loss=2000, block_size=5, seed=7)
+    summary = mc.run()
+    print(summary.ruin_probability, summary.percentiles()['max_drawdown'][95])
+    payload = summary.to_dict()
+
+Configuration:
+- MONTE_CARLO_SIMULATIONS: Paths per run (default 5000)
+- MONTE_CARLO_BLOCK_SIZE: Trades per bootstrap block, 1 = plain resampling (default 1)
+- MONTE_CARLO_RUIN_LOSS: Loss from the initial capital that counts as ruin (default: the whole capital)
+- MONTE_CARLO_WORKERS: Worker processes, 1 = in-process (default: CPU count)
+"""
+
+import logging
+import os
+import random
+import time
+from concurrent.futures import ProcessPoolExecutor
+from dataclasses import dataclass
+from typing import Any, Dict, Iterable, List, Optional, Sequence, Tuple
+
+from core.strategy_engine.performance import TradeRecord
+
+logger = logging.getLogger(__name__)
+
+CHUNK_SIZE = 500  # Simulations per task and per seed
+PERCENTILES = (1, 5, 25, 50, 75, 95, 99)
+DISTRIBUTIONS = ('max_drawdown', 'max_drawdown_pct', 'final_equity', 'net_pnl', 'max_loss_streak')
+
+# (max_drawdown, max_drawdown_pct, final_equity, max_loss_streak, ruined)
+PathOutcome = Tuple[float, float, float, int, bool]
+
+
+def percentile(values: Sequence[float], q: float) -> float:
+    """q-th percentile (0-100) of sorted values, linearly interpolated."""
+    if not values:
+        raise ValueError("percentile of no values")
+    position = (len(values) - 1) * q / 100
+    lower = int(position)
+    upper = min(lower + 1, len(values) - 1)
+    return values[lower] + (values[upper] - values[lower]) * (position - lower)
+
+
+def _simulate_chunk(pnls: Sequence[float], initial_capital: float, ruin_equity: float, length: int,
+                    block_size: int, count: int, seed: int) -> List[PathOutcome]:
+    """Simulate count paths of length trades (worker process entry point)."""
+    rng = random.Random(seed)
+    n = len(pnls)
+    outcomes: List[PathOutcome] = []
+    for _ in range(count):
+        equity = peak = initial_capital
+        max_drawdown = max_drawdown_pct = 0.0
+        streak = max_streak = 0
+        ruined = False
+        drawn = 0
+        while drawn < length:
+            start = rng.randrange(n)
+            for offset in range(min(block_size, length - drawn)):
+                pnl = pnls[(start + offset) % n]
+                equity += pnl
+                if equity > peak:
+                    peak = equity
+                elif peak - equity > max_drawdown:
+                    max_drawdown = peak - equity
+                    max_drawdown_pct = max_drawdown / peak * 100 if peak > 0 else 100.0
+                streak = streak + 1 if pnl < 0 else 0
+                max_streak = max(max_streak, streak)
+                ruined = ruined or equity <= ruin_equity
+            drawn += block_size
+        outcomes.append((max_drawdown, max_drawdown_pct, equity, max_streak, ruined))
+    return outcomes
+
+
+@dataclass
+class MonteCarloResult:
+    """Outcome distributions of all simulated paths."""
+    initial_capital: float
+    ruin_equity: float
+    simulations: int
+    trades_per_path: int
+    block_size: int
+    max_drawdowns: List[float]  # Sorted ascending, like the other distributions
+    max_drawdown_pcts: List[float]
+    final_equities: List[float]
+    max_loss_streaks: List[int]
+    ruined: int
+    elapsed: float = 0.0
+
+    @property
+    def ruin_probability(self) -> float:
+        return self.ruined / self.simulations if self.simulations else 0.0
+
+    def _distribution(self, name: str) -> Sequence[float]:
+        if name == 'net_pnl':
+            return [equity - self.initial_capital for equity in self.final_equities]
+        return {'max_drawdown': self.max_drawdowns, 'max_drawdown_pct': self.max_drawdown_pcts,
+                'final_equity': self.final_equities, 'max_loss_streak': self.max_loss_streaks}[name]
+
+    def percentiles(self, levels: Iterable[float] = PERCENTILES) -> Dict[str, Dict[float, float]]:
+        """{distribution: {level: value}} for max_drawdown(_pct), final_equity, net_pnl, max_loss_streak."""
+        levels = list(levels)
+        if not self.simulations:
+            return {name: {} for name in DISTRIBUTIONS}
+        return {name: {level: round(percentile(self._distribution(name), level), 2) for level in levels}
+                for name in DISTRIBUTIONS}
+
+    def probability_of_drawdown(self, amount: float) -> float:
+        """Share of paths with a maximum drawdown of at least amount dollars."""
+        if not self.simulations:
+            return 0.0
+        return sum(1 for drawdown in self.max_drawdowns if drawdown >= amount) / self.simulations
+
+    def probability_of_loss(self) -> float:
+        """Share of paths ending below the initial capital."""
+        if not self.simulations:
+            return 0.0
+        return sum(1 for equity in self.final_equities if equity < self.initial_capital) / self.simulations
+
+    def to_dict(self, levels: Iterable[float] = PERCENTILES) -> Dict[str, Any]:
+        """Summary: percentiles (keyed 'p95' etc.) and probabilities, without the raw paths."""
+        return {
+            "simulations": self.simulations,
+            "trades_per_path": self.trades_per_path,
+            "block_size": self.block_size,
+            "initial_capital": self.initial_capital,
+            "ruin_equity": self.ruin_equity,
+            "ruin_probability": round(self.ruin_probability, 4),
+            "loss_probability": round(self.probability_of_loss(), 4),
+            "percentiles": {name: {f"p{level:g}": value for level, value in values.items()}
+                            for name, values in self.percentiles(levels).items()},
+            "elapsed": round(self.elapsed, 3),
+        }
+
+
+class MonteCarlo:
+    """
+    Bootstrap simulation of a strategy's trade sequence.
+    """
+
+    def __init__(self, trades: Iterable[Any], initial_capital: float = 50000.0,
+                 simulations: Optional[int] = None, block_size: Optional[int] = None,
+                 ruin_loss: Optional[float] = None, trades_per_path: Optional[int] = None,
+                 seed: Optional[int] = None, workers: Optional[int] = None):
+        """
+        Initialize simulation.
+
+        Args:
+            trades: Closed trades (anything PerformanceStats accepts) or plain P&L numbers, in time order
+            initial_capital: Starting equity of every path
+            simulations: Paths (env: MONTE_CARLO_SIMULATIONS)
+            block_size: Consecutive trades per draw, 1 = i.i.d. (env: MONTE_CARLO_BLOCK_SIZE)
+            ruin_loss: Loss from initial_capital counting as ruin (env: MONTE_CARLO_RUIN_LOSS, default: all of it)
+            trades_per_path: Trades per path (default: as many as given)
+            seed: Random seed for reproducible runs
+            workers: Worker processes, 1 = in-process (env: MONTE_CARLO_WORKERS, default CPU count)
+        """
+        self.pnls = [float(t) if isinstance(t, (int, float)) else TradeRecord.from_any(t).pnl for t in trades]
+        if not self.pnls:
+            raise ValueError("MonteCarlo needs at least one trade")
+        self.initial_capital = initial_capital
+        self.simulations = (simulations if simulations is not None
+                            else int(os.getenv('MONTE_CARLO_SIMULATIONS', '5000')))
+        self.block_size = block_size if block_size is not None else int(os.getenv('MONTE_CARLO_BLOCK_SIZE', '1'))
+        ruin_env = os.getenv('MONTE_CARLO_RUIN_LOSS')
+        self.ruin_loss = ruin_loss if ruin_loss is not None else (float(ruin_env) if ruin_env else initial_capital)
+        self.trades_per_path = trades_per_path or len(self.pnls)
+        self.seed = seed if seed is not None else random.SystemRandom().randrange(2 ** 31)
+        workers = workers if workers is not None else int(os.getenv('MONTE_CARLO_WORKERS', str(os.cpu_count() or 1)))
+        self.workers = max(1, workers)
+        if self.simulations < 1 or self.block_size < 1 or self.trades_per_path < 1:
+            raise ValueError("simulations, block_size and trades_per_path must be >= 1")
+        if self.ruin_loss <= 0:
+            raise ValueError(f"ruin_loss must be positive, got {self.ruin_loss}")
+
+    def run(self) -> MonteCarloResult:
+        """
+        Simulate all paths.
+
+        Returns:
+            MonteCarloResult: Sorted outcome distributions and ruin count
+        """
+        started = time.monotonic()
+        ruin_equity = self.initial_capital - self.ruin_loss
+        chunks = [(self.pnls, self.initial_capital, ruin_equity, self.trades_per_path, self.block_size,
+                   min(CHUNK_SIZE, self.simulations - first), self.seed + index)
+                  for index, first in enumerate(range(0, self.simulations, CHUNK_SIZE))]
+        if self.workers == 1 or len(chunks) == 1:
+            batches = [_simulate_chunk(*chunk) for chunk in chunks]
+        else:
+            with ProcessPoolExecutor(max_workers=min(self.workers, len(chunks))) as pool:
+                batches = list(pool.map(_simulate_chunk, *zip(*chunks)))
+        outcomes = [outcome for batch in batches for outcome in batch]
+
+        result = MonteCarloResult(
+            initial_capital=self.initial_capital, ruin_equity=ruin_equity, simulations=len(outcomes),
+            trades_per_path=self.trades_per_path, block_size=self.block_size,
+            max_drawdowns=sorted(o[0] for o in outcomes), max_drawdown_pcts=sorted(o[1] for o in outcomes),
+            final_equities=sorted(o[2] for o in outcomes), max_loss_streaks=sorted(o[3] for o in outcomes),
+            ruined=sum(1 for o in outcomes if o[4]), elapsed=time.monotonic() - started,
+        )
+        logger.info(f"📊 Monte Carlo: {result.simulations} paths of {self.trades_per_path} trades in "
+                    f"{result.elapsed:.2f}s, ruin probability {result.ruin_probability:.2%}, "
+                    f"95th percentile drawdown ${percentile(result.max_drawdowns, 95):,.2f}")
+        return result
@@ -0,0 +1,502 @@
+"""
+Strategy parameter optimization.
+
+Defines a parameter space and searches it with BacktestRunner, so every
+candidate is backtested on the same data with the same fill model, in
+parallel worker processes:
+
+- ParameterSpace: per-parameter Range(start, stop, step) (inclusive), an
+  explicit list of values, or a fixed value, plus an optional constraint to
+  skip invalid combinations (e.g. fast period >= slow period)
+- GridSearch: exhaustive search over every combination
+- GeneticOptimizer: evolutionary search for spaces too large to enumerate:
+  tournament selection, uniform crossover, mutation (neighbouring values of
+  ordered numeric parameters, any value otherwise), elitism and early
+  stopping after `patience` generations without improvement. Each
+  parameter set is backtested once, repeats come from a cache, and every
+  generation is one parallel runner batch. A progress callback receives a
+  GenerationProgress after each generation and stops the search by
+  returning False
+- Results are ranked by the objective ('net_pnl', 'sharpe', 'profit_factor',
+  any other numeric stats key, or a callable(stats) -> float); failed runs
+  and runs with fewer than min_trades trades rank last, so a parameter set
+  that never trades can't win by not losing
+
+Usage:
+    space = ParameterSpace({'fast_period': Range(3, 12), 'slow_period': Range(15, 40, 5),
+                            'stop_atr': [1.0, 1.5, 2.0]},
+                           constraint=lambda p: p['fast_period'] < p['slow_period'])
+    result = GridSearch(make_ema, space, objective='sharpe').run(bars)
+    for run in result.top(5):
+        print(run.params, run.stats['sharpe'], run.stats['net_pnl'])
+
+    def progress(p):
+        print(f"gen {p.generation}: best {p.best.params} {p.best_score:.2f}")
+        return not stop_requested  # False stops the search
+
+    result = GeneticOptimizer(make_ema, space, objective='sharpe',
+                              params=GeneticParams(population=40, generations=30)).run(bars, on_generation=progress)
+
+Configuration:
+- OPTIMIZER_MIN_TRADES: Trades a run needs to rank by its objective (default 1)
+- OPTIMIZER_MAX_COMBINATIONS: Largest grid GridSearch accepts (default 100000)
+- GENETIC_POPULATION / GENETIC_GENERATIONS: Population size and generation limit (default 40/30)
+- GENETIC_CROSSOVER / GENETIC_MUTATION: Crossover probability and per-parameter mutation probability
+  (default 0.8/0.15)
+- GENETIC_ELITE / GENETIC_TOURNAMENT: Best individuals kept as-is and tournament size (default 2/3)
+- GENETIC_PATIENCE: Generations without improvement before stopping, 0 = never (default 8)
+- GENETIC_SEED: Random seed for reproducible runs (default: random)
+- BACKTEST_WORKERS: Worker processes (see runner)
+"""
+
+import itertools
+import logging
+import math
+import os
+import random
+import time
+from dataclasses import dataclass, field
+from datetime import datetime
+from typing import Any, Callable, Dict, Iterator, List, Optional, Sequence, Tuple
+
+from core.bar_aggregator import Bar
+from core.market_events import MarketEvent
+from core.strategy_engine.backtest import BacktestConfig
+from core.strategy_engine.runner import BacktestRunner, Objective, RunResult, StrategyFactory
+
+logger = logging.getLogger(__name__)
+
+
+@dataclass(frozen=True)
+class Range:
+    """Inclusive numeric range: Range(5, 20, 5) -> 5, 10, 15, 20."""
+    start: float
+    stop: float
+    step: float = 1
+
+    def values(self) -> List[Any]:
+        if self.step <= 0:
+            raise ValueError(f"Range step must be positive, got {self.step}")
+        if self.stop < self.start:
+            raise ValueError(f"Range stop {self.stop} is below start {self.start}")
+        count = int(math.floor((self.stop - self.start) / self.step + 1e-9)) + 1
+        if all(isinstance(v, int) for v in (self.start, self.stop, self.step)):
+            return [self.start + i * self.step for i in range(count)]
+        # Round away float accumulation (0.1 + 0.2 ...) so values print and compare cleanly
+        return [round(self.start + i * self.step, 10) for i in range(count)]
+
+
+class ParameterSpace:
+    """
+    Named parameter dimensions and their candidate values.
+    """
+
+    def __init__(self, params: Dict[str, Any], constraint: Optional[Callable[[Dict[str, Any]], bool]] = None):
+        """
+        Define a parameter space.
+
+        Args:
+            params: name -> Range, {"start", "stop", "step"} dict, list of values or a fixed value
+            constraint: params -> bool, combinations returning False are skipped
+
+        Raises:
+            ValueError: Empty space, empty dimension or invalid range
+        """
+        if not params:
+            raise ValueError("ParameterSpace needs at least one parameter")
+        self.values: Dict[str, List[Any]] = {}
+        for name, spec in params.items():
+            if isinstance(spec, dict):
+                spec = Range(spec['start'], spec['stop'], spec.get('step', 1))
+            if isinstance(spec, Range):
+                values = spec.values()
+            elif isinstance(spec, (list, tuple, range)):
+                values = list(spec)
+            else:
+                values = [spec]
+            if not values:
+                raise ValueError(f"Parameter {name!r} has no values")
+            self.values[name] = values
+        self.constraint = constraint
+
+    @property
+    def names(self) -> List[str]:
+        return list(self.values)
+
+    @property
+    def size(self) -> int:
+        """Number of combinations before the constraint is applied."""
+        return math.prod(len(v) for v in self.values.values())
+
+    def is_valid(self, params: Dict[str, Any]) -> bool:
+        return self.constraint is None or bool(self.constraint(params))
+
+    def grid(self) -> Iterator[Dict[str, Any]]:
+        """Every combination that passes the constraint."""
+        for combination in itertools.product(*self.values.values()):
+            params = dict(zip(self.values, combination))
+            if self.is_valid(params):
+                yield params
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        return {"values": self.values, "size": self.size, "constrained": self.constraint is not None}
+
+
+def objective_name(objective: Objective) -> str:
+    return objective if isinstance(objective, str) else getattr(objective, '__name__', 'custom')
+
+
+def _rank_key(run: RunResult, objective: Objective, min_trades: int) -> Tuple[bool, bool, float]:
+    return (run.error is None, run.stats.get('trades', 0) >= min_trades, run.score(objective))
+
+
+def rank(results: Sequence[RunResult], objective: Objective, min_trades: int = 0) -> List[RunResult]:
+    """Best first: successful runs with at least min_trades trades by objective, then the rest, failures last."""
+    return sorted(results, key=lambda run: _rank_key(run, objective, min_trades), reverse=True)
+
+
+@dataclass
+class OptimizationResult:
+    """Ranked runs of an optimization."""
+    results: List[RunResult]  # Best first
+    objective: str
+    evaluated: int
+    elapsed: float
+    min_trades: int = 0
+    extra: Dict[str, Any] = field(default_factory=dict)
+
+    @property
+    def best(self) -> Optional[RunResult]:
+        if not self.results or self.results[0].error or \
+                self.results[0].stats.get('trades', 0) < self.min_trades:
+            return None
+        return self.results[0]
+
+    def top(self, n: int = 10) -> List[RunResult]:
+        return self.results[:n]
+
+    def to_dict(self, top: Optional[int] = None) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary (all runs, or the best top)."""
+        runs = self.results if top is None else self.results[:top]
+        return {
+            "objective": self.objective,
+            "evaluated": self.evaluated,
+            "elapsed": round(self.elapsed, 3),
+            "min_trades": self.min_trades,
+            "best": self.best.params if self.best else None,
+            "results": [dict(r.to_dict(), rank=i + 1) for i, r in enumerate(runs)],
+            **self.extra,
+        }
+
+
+class GridSearch:
+    """
+    Exhaustive parameter search.
+    """
+
+    def __init__(self, factory: StrategyFactory, space: ParameterSpace, objective: Objective = 'net_pnl',
+                 config: Optional[BacktestConfig] = None, workers: Optional[int] = None,
+                 min_trades: Optional[int] = None, max_combinations: Optional[int] = None):
+        """
+        Initialize grid search.
+
+        Args:
+            factory: params -> strategy or list of strategies (see BacktestRunner)
+            space: Parameter space to search
+            objective: Stats key or callable(stats) -> float, higher is better
+            config: Fill model (default: BacktestConfig.from_env())
+            workers: Worker processes (env: BACKTEST_WORKERS)
+            min_trades: Trades a run needs to rank by objective (env: OPTIMIZER_MIN_TRADES, default 1)
+            max_combinations: Refuse larger grids (env: OPTIMIZER_MAX_COMBINATIONS, default 100000)
+        """
+        self.factory = factory
+        self.space = space
+        self.objective = objective
+        self.config = (config or BacktestConfig.from_env()).validate()
+        self.workers = workers
+        self.min_trades = min_trades if min_trades is not None else int(os.getenv('OPTIMIZER_MIN_TRADES', '1'))
+        self.max_combinations = max_combinations if max_combinations is not None else int(
+            os.getenv('OPTIMIZER_MAX_COMBINATIONS', '100000'))
+
+    def run(self, bars: Sequence[Bar], ticks: Sequence[MarketEvent] = (), start: Optional[datetime] = None,
+            end: Optional[datetime] = None) -> OptimizationResult:
+        """
+        Backtest every combination of the space.
+
+        Args:
+            bars: Historical bars
+            ticks: Optional historical ticks
+            start / end: Only use data with start <= timestamp < end
+
+        Returns:
+            OptimizationResult: Runs ranked by objective
+
+        Raises:
+            ValueError: The grid exceeds max_combinations
+        """
+        if self.space.size > self.max_combinations:
+            raise ValueError(f"Grid has {self.space.size:,} combinations (max {self.max_combinations:,}); "
+                             f"use coarser steps or raise OPTIMIZER_MAX_COMBINATIONS")
+        candidates = list(self.space.grid())
+        logger.info(f"📊 Grid search: {len(candidates):,} combinations "
+                    f"({self.space.size - len(candidates):,} skipped by constraint), objective {self.objective!r}")
+        started = time.monotonic()
+        with BacktestRunner(self.factory, bars, self.config, ticks=ticks, workers=self.workers) as runner:
+            results = runner.run(candidates, start=start, end=end)
+        result = OptimizationResult(rank(results, self.objective, self.min_trades), objective_name(self.objective),
+                                    len(results), time.monotonic() - started, self.min_trades)
+        failed = sum(1 for r in results if r.error)
+        if failed:
+            logger.warning(f"⚠️ Grid search: {failed} of {len(results)} runs failed, first: "
+                           f"{next(r.error for r in results if r.error)}")
+        if result.best:
+            logger.info(f"✅ Grid search done in {result.elapsed:.1f}s: best {result.best.params} "
+                        f"({result.objective} {result.best.score(self.objective):.3f})")
+        else:
+            logger.warning(f"⚠️ Grid search done in {result.elapsed:.1f}s: no run reached {self.min_trades} trades")
+        return result
+
+
+@dataclass
+class GeneticParams:
+    """Genetic optimizer settings."""
+    population: int = 40
+    generations: int = 30
+    crossover_rate: float = 0.8
+    mutation_rate: float = 0.15
+    elite: int = 2
+    tournament: int = 3
+    patience: int = 8  # 0 = run all generations
+    seed: Optional[int] = None
+
+    def validate(self) -> 'GeneticParams':
+        """
+        Raises:
+            ValueError: Inconsistent parameters
+        """
+        if self.population < 2 or self.generations < 1:
+            raise ValueError(f"Need population >= 2 and generations >= 1, got {self.population}/{self.generations}")
+        if not (0 <= self.crossover_rate <= 1 and 0 <= self.mutation_rate <= 1):
+            raise ValueError("crossover_rate and mutation_rate must be between 0 and 1")
+        if not 0 <= self.elite < self.population:
+            raise ValueError(f"elite must be between 0 and population - 1, got {self.elite}")
+        if self.tournament < 1 or self.patience < 0:
+            raise ValueError("tournament must be >= 1 and patience >= 0")
+        return self
+
+    @classmethod
+    def from_env(cls, prefix: str = 'GENETIC_') -> 'GeneticParams':
+        """Load settings from environment variables."""
+        seed = os.getenv(f"{prefix}SEED")
+        return cls(
+            population=int(os.getenv(f"{prefix}POPULATION", "40")),
+            generations=int(os.getenv(f"{prefix}GENERATIONS", "30")),
+            crossover_rate=float(os.getenv(f"{prefix}CROSSOVER", "0.8")),
+            mutation_rate=float(os.getenv(f"{prefix}MUTATION", "0.15")),
+            elite=int(os.getenv(f"{prefix}ELITE", "2")),
+            tournament=int(os.getenv(f"{prefix}TOURNAMENT", "3")),
+            patience=int(os.getenv(f"{prefix}PATIENCE", "8")),
+            seed=int(seed) if seed else None,
+        ).validate()
+
+
+@dataclass
+class GenerationProgress:
+    """State after one generation, passed to progress callbacks."""
+    generation: int  # 1-based
+    generations: int
+    evaluated: int  # Distinct parameter sets backtested so far
+    best: RunResult
+    best_score: float
+    mean_score: Optional[float]  # Of the generation's ranked individuals
+    improved: bool
+    stalled: int  # Generations since the last improvement
+    elapsed: float
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        return {
+            "generation": self.generation,
+            "generations": self.generations,
+            "evaluated": self.evaluated,
+            "best_params": self.best.params,
+            "best_score": self.best_score if math.isfinite(self.best_score) else None,
+            "mean_score": self.mean_score,
+            "improved": self.improved,
+            "stalled": self.stalled,
+            "elapsed": round(self.elapsed, 3),
+        }
+
+
+ProgressCallback = Callable[[GenerationProgress], Optional[bool]]
+Genome = Tuple[int, ...]  # Index into each dimension's values
+
+
+class GeneticOptimizer:
+    """
+    Evolutionary parameter search over a ParameterSpace.
+    """
+
+    def __init__(self, factory: StrategyFactory, space: ParameterSpace, objective: Objective = 'net_pnl',
+                 params: Optional[GeneticParams] = None, config: Optional[BacktestConfig] = None,
+                 workers: Optional[int] = None, min_trades: Optional[int] = None):
+        """
+        Initialize genetic optimizer.
+
+        Args:
+            factory: params -> strategy or list of strategies (see BacktestRunner)
+            space: Parameter space to search
+            objective: Stats key or callable(stats) -> float, higher is better
+            params: Population/operator settings (default: GeneticParams.from_env())
+            config: Fill model (default: BacktestConfig.from_env())
+            workers: Worker processes (env: BACKTEST_WORKERS)
+            min_trades: Trades a run needs to rank by objective (env: OPTIMIZER_MIN_TRADES, default 1)
+        """
+        self.factory = factory
+        self.space = space
+        self.objective = objective
+        self.params = (params or GeneticParams.from_env()).validate()
+        self.config = (config or BacktestConfig.from_env()).validate()
+        self.workers = workers
+        self.min_trades = min_trades if min_trades is not None else int(os.getenv('OPTIMIZER_MIN_TRADES', '1'))
+        self._dimensions = list(space.values.values())
+        # Ordered numeric dimensions mutate to nearby values, others to any value
+        self._ordered = [all(isinstance(v, (int, float)) and not isinstance(v, bool) for v in values)
+                         and values == sorted(values) for values in self._dimensions]
+
+    def _decode(self, genome: Genome) -> Dict[str, Any]:
+        return {name: values[i] for (name, values), i in zip(self.space.values.items(), genome)}
+
+    def _valid(self, genome: Genome) -> bool:
+        return self.space.is_valid(self._decode(genome))
+
+    def _random_genome(self, rng: random.Random) -> Optional[Genome]:
+        for _ in range(100):
+            genome = tuple(rng.randrange(len(values)) for values in self._dimensions)
+            if self._valid(genome):
+                return genome
+        return None
+
+    def _crossover(self, a: Genome, b: Genome, rng: random.Random) -> Genome:
+        if rng.random() >= self.params.crossover_rate:
+            return a
+        return tuple(x if rng.random() < 0.5 else y for x, y in zip(a, b))
+
+    def _mutate(self, genome: Genome, rng: random.Random) -> Genome:
+        genes = list(genome)
+        for i, values in enumerate(self._dimensions):
+            if len(values) < 2 or rng.random() >= self.params.mutation_rate:
+                continue
+            if self._ordered[i]:
+                reach = max(1, len(values) // 5)
+                genes[i] = min(len(values) - 1, max(0, genes[i] + rng.choice((-1, 1)) * rng.randint(1, reach)))
+            else:
+                genes[i] = rng.choice([j for j in range(len(values)) if j != genes[i]])
+        return tuple(genes)
+
+    def _select(self, ranked: List[Genome], rng: random.Random) -> Genome:
+        """Tournament selection; ranked is best first, so the lowest index wins."""
+        return ranked[min(rng.randrange(len(ranked)) for _ in range(self.params.tournament))]
+
+    def run(self, bars: Sequence[Bar], ticks: Sequence[MarketEvent] = (), start: Optional[datetime] = None,
+            end: Optional[datetime] = None, on_generation: Optional[ProgressCallback] = None) -> OptimizationResult:
+        """
+        Evolve parameter sets.
+
+        Args:
+            bars: Historical bars
+            ticks: Optional historical ticks
+            start / end: Only use data with start <= timestamp < end
+            on_generation: Called with a GenerationProgress after every generation; returning False stops
+
+        Returns:
+            OptimizationResult: Every evaluated run ranked by objective; extra holds generations,
+            stop_reason and the per-generation history
+        """
+        p = self.params
+        rng = random.Random(p.seed)
+        started = time.monotonic()
+        cache: Dict[Genome, RunResult] = {}
+        history: List[Dict[str, Any]] = []
+        best_key: Optional[Tuple[bool, bool, float]] = None
+        stalled, generation, stop_reason = 0, 0, 'generations'
+
+        def key(genome: Genome) -> Tuple[bool, bool, float]:
+            return _rank_key(cache[genome], self.objective, self.min_trades)
+
+        # Initial population: distinct valid individuals (fewer if the space is smaller)
+        population: List[Genome] = []
+        for _ in range(p.population * 20):
+            genome = self._random_genome(rng)
+            if genome is not None and genome not in population:
+                population.append(genome)
+            if len(population) >= p.population:
+                break
+        if not population:
+            raise ValueError("No parameter set in the space satisfies the constraint")
+        logger.info(f"📊 Genetic optimizer: population {len(population)}, up to {p.generations} generations "
+                    f"over {self.space.size:,} combinations, objective {self.objective!r}")
+
+        with BacktestRunner(self.factory, bars, self.config, ticks=ticks, workers=self.workers) as runner:
+            while True:
+                generation += 1
+                new = list(dict.fromkeys(g for g in population if g not in cache))
+                for genome, run in zip(new, runner.run([self._decode(g) for g in new], start=start, end=end)):
+                    cache[genome] = run
+                ranked = sorted(set(population), key=key, reverse=True)
+                improved = best_key is None or key(ranked[0]) > best_key
+                if improved:
+                    best_key, stalled = key(ranked[0]), 0
+                else:
+                    stalled += 1
+                scores = [s for s in (cache[g].score(self.objective) for g in ranked) if math.isfinite(s)]
+                progress = GenerationProgress(
+                    generation=generation, generations=p.generations, evaluated=len(cache),
+                    best=cache[max(cache, key=key)], best_score=best_key[2],
+                    mean_score=round(sum(scores) / len(scores), 4) if scores else None,
+                    improved=improved, stalled=stalled, elapsed=time.monotonic() - started)
+                history.append(progress.to_dict())
+                logger.debug(f"Genetic generation {generation}: best {progress.best.params} "
+                             f"({progress.best_score:.4f}), {len(new)} new runs")
+                if on_generation is not None:
+                    try:
+                        if on_generation(progress) is False:
+                            stop_reason = 'callback'
+                            break
+                    except Exception as e:
+                        logger.error(f"❌ Genetic optimizer progress callback failed: {e}")
+                if generation >= p.generations:
+                    break
+                if p.patience and stalled >= p.patience:
+                    stop_reason = 'patience'
+                    break
+                if len(cache) >= self.space.size:
+                    stop_reason = 'exhausted'
+                    break
+
+                # Next generation: elites survive, the rest are bred from tournament winners
+                offspring = ranked[:p.elite]
+                for _ in range(p.population * 10):
+                    if len(offspring) >= p.population:
+                        break
+                    child = self._mutate(self._crossover(self._select(ranked, rng), self._select(ranked, rng), rng),
+                                         rng)
+                    if not self._valid(child):
+                        child = self._random_genome(rng)
+                    if child is not None:
+                        offspring.append(child)
+                population = offspring
+
+        results = rank(list(cache.values()), self.objective, self.min_trades)
+        result = OptimizationResult(results, objective_name(self.objective), len(results),
+                                    time.monotonic() - started, self.min_trades,
+                                    extra={"generations": generation, "stop_reason": stop_reason, "history": history})
+        if result.best:
+            logger.info(f"✅ Genetic optimizer done in {result.elapsed:.1f}s after {generation} generations "
+                        f"({stop_reason}), {len(results):,} runs: best {result.best.params} "
+                        f"({result.objective} {result.best.score(self.objective):.3f})")
+        else:
+            logger.warning(f"⚠️ Genetic optimizer done in {result.elapsed:.1f}s: "
+                           f"no run reached {self.min_trades} trades")
+        return result
@@ -0,0 +1,307 @@
+"""
+Strategy performance statistics.
+
+Computes the usual trade statistics from a list of closed trades, the same
+way for backtests and for live trading:
+
+- Accepted trades: BacktestTrade objects, their to_dict() form, and the
+  consolidated trade dicts of the bot's order history
+  ({"symbol", "side": "LONG"/"SHORT", "quantity", "entry_price",
+  "exit_price", "entry_time", "exit_time" (ISO strings), "pnl",
+  "strategy", optionally "fees"/"commission"}). Missing fields (MAE/MFE,
+  commission) are simply left out of the statistics they feed
+- Commissions: P&L is taken as net of the trade's commission. With a
+  CommissionModel (fees=), trades without a known commission are charged
+  the round-trip cost of their symbol and quantity
+- Trade statistics: win rate, profit factor, expectancy, payoff ratio,
+  largest win/loss, win/loss streaks, average holding time, average MAE/MFE
+- Risk statistics: Sharpe and Sortino ratios (annualized from daily P&L by
+  trading date, risk-free rate 0), maximum drawdown of closed-trade equity
+- Breakdowns: by session (RTH/ETH of the entry, per the SessionCalendar),
+  strategy, symbol and direction, each a full set of trade statistics
+
+Usage:
+    stats = PerformanceStats.from_trades(result.trades, initial_capital=50000)
+    print(stats.win_rate, stats.sharpe, stats.by_session['rth'].expectancy)
+    payload = stats.to_dict()
+
+    live = PerformanceStats.from_trades(bot._consolidate_orders_into_trades(orders))
+    charged = PerformanceStats.from_trades(external_trades, fees=CommissionModel())
+"""
+
+import logging
+import math
+import statistics
+from dataclasses import dataclass, field, replace
+from datetime import date, datetime
+from typing import Any, Dict, Iterable, List, Optional
+
+from core.market_events import parse_timestamp
+from core.session_calendar import SessionCalendar
+from core.strategy_engine.fees import CommissionModel
+
+logger = logging.getLogger(__name__)
+
+TRADING_DAYS = 252
+
+
+@dataclass(frozen=True)
+class TradeRecord:
+    """A closed trade in the form the statistics need."""
+    symbol: str
+    direction: str  # 'long' / 'short'
+    quantity: int
+    entry_time: datetime
+    exit_time: datetime
+    entry_price: float
+    exit_price: float
+    pnl: float  # Net of commission when known
+    strategy: Optional[str] = None
+    mae: Optional[float] = None
+    mfe: Optional[float] = None
+    commission: Optional[float] = None  # Round trip, None when unknown
+
+    @classmethod
+    def from_any(cls, trade: Any) -> 'TradeRecord':
+        """
+        Normalize a BacktestTrade, its to_dict() or a consolidated order-history trade dict.
+
+        Raises:
+            ValueError: No P&L or exit time
+        """
+        if isinstance(trade, TradeRecord):
+            return trade
+        if not isinstance(trade, dict):
+            return cls(
+                symbol=trade.symbol, direction=trade.direction.value, quantity=trade.quantity,
+                entry_time=trade.entry_time, exit_time=trade.exit_time, entry_price=trade.entry_price,
+                exit_price=trade.exit_price, pnl=trade.pnl, strategy=trade.strategy_id,
+                mae=getattr(trade, 'mae', None), mfe=getattr(trade, 'mfe', None),
+                commission=getattr(trade, 'commission', None),
+            )
+        pnl = trade.get('pnl')
+        exit_time = trade.get('exit_time')
+        if pnl is None or not exit_time:
+            raise ValueError(f"Trade needs pnl and exit_time: {trade!r}")
+        exit_time = parse_timestamp(exit_time)
+        side = str(trade.get('direction') or trade.get('side') or '').lower()
+        commission = next((trade[key] for key in ('commission', 'fees') if trade.get(key) is not None), None)
+        return cls(
+            symbol=str(trade.get('symbol', '')),
+            direction='short' if side in ('short', 'sell') else 'long',
+            quantity=int(trade.get('quantity') or 1),
+            entry_time=parse_timestamp(trade['entry_time']) if trade.get('entry_time') else exit_time,
+            exit_time=exit_time,
+            entry_price=float(trade.get('entry_price') or 0.0),
+            exit_price=float(trade.get('exit_price') or 0.0),
+            pnl=float(pnl),
+            strategy=trade.get('strategy_id') or trade.get('strategy'),
+            mae=float(trade['mae']) if trade.get('mae') is not None else None,
+            mfe=float(trade['mfe']) if trade.get('mfe') is not None else None,
+            commission=float(commission) if commission is not None else None,
+        )
+
+
+def _ratio(numerator: float, denominator: float, digits: int = 3) -> Optional[float]:
+    return round(numerator / denominator, digits) if denominator else None
+
+
+def _mean(values: List[float]) -> Optional[float]:
+    return round(statistics.mean(values), 2) if values else None
+
+
+@dataclass
+class PerformanceStats:
+    """Performance of a set of closed trades."""
+    trades: int = 0
+    wins: int = 0
+    losses: int = 0
+    breakeven: int = 0
+    win_rate: float = 0.0  # Percent
+    net_pnl: float = 0.0
+    commissions: float = 0.0  # Known commissions and fees, already deducted from net_pnl
+    gross_profit: float = 0.0
+    gross_loss: float = 0.0  # Positive
+    profit_factor: Optional[float] = None  # None without losing trades
+    expectancy: float = 0.0  # Average P&L per trade
+    avg_win: float = 0.0
+    avg_loss: float = 0.0  # Negative
+    payoff_ratio: Optional[float] = None  # avg_win / |avg_loss|
+    largest_win: float = 0.0
+    largest_loss: float = 0.0
+    max_win_streak: int = 0
+    max_loss_streak: int = 0
+    avg_holding_minutes: Optional[float] = None
+    avg_mae: Optional[float] = None
+    avg_mfe: Optional[float] = None
+    sharpe: Optional[float] = None
+    sortino: Optional[float] = None
+    max_drawdown: float = 0.0
+    max_drawdown_pct: Optional[float] = None  # Of peak equity, with an initial capital
+    trading_days: int = 0
+    daily_pnl: Dict[date, float] = field(default_factory=dict)
+    by_session: Dict[str, 'PerformanceStats'] = field(default_factory=dict)
+    by_strategy: Dict[str, 'PerformanceStats'] = field(default_factory=dict)
+    by_symbol: Dict[str, 'PerformanceStats'] = field(default_factory=dict)
+    by_direction: Dict[str, 'PerformanceStats'] = field(default_factory=dict)
+
+    @classmethod
+    def from_trades(cls, trades: Iterable[Any], initial_capital: Optional[float] = None,
+                    calendar: Optional[SessionCalendar] = None, breakdowns: bool = True,
+                    fees: Optional[CommissionModel] = None) -> 'PerformanceStats':
+        """
+        Compute statistics from closed trades.
+
+        Args:
+            trades: BacktestTrade objects or trade dicts (see module docstring), in any order
+            initial_capital: Starting equity; enables return-based Sharpe/Sortino and max_drawdown_pct
+            calendar: Session calendar for trading dates and RTH/ETH (default: SessionCalendar())
+            breakdowns: Also compute the by_session/strategy/symbol/direction breakdowns
+            fees: Charge trades without a known commission their round-trip cost
+
+        Returns:
+            PerformanceStats: Statistics (all zero/None without trades)
+        """
+        calendar = calendar or SessionCalendar()
+        records = sorted((TradeRecord.from_any(t) for t in trades), key=lambda r: r.exit_time)
+        if fees is not None:
+            records = [cls._charge(record, fees) for record in records]
+        stats = cls._compute(records, initial_capital, calendar)
+        if breakdowns and records:
+            groups: Dict[str, Dict[str, List[TradeRecord]]] = {
+                'by_session': {}, 'by_strategy': {}, 'by_symbol': {}, 'by_direction': {}}
+            for record in records:
+                session = calendar.state(record.symbol or 'ES', record.entry_time).value
+                groups['by_session'].setdefault(session, []).append(record)
+                groups['by_strategy'].setdefault(record.strategy or 'unknown', []).append(record)
+                groups['by_symbol'].setdefault(record.symbol, []).append(record)
+                groups['by_direction'].setdefault(record.direction, []).append(record)
+            for name, grouped in groups.items():
+                setattr(stats, name, {key: cls._compute(group, None, calendar)
+                                      for key, group in sorted(grouped.items())})
+        return stats
+
+    @staticmethod
+    def _charge(record: TradeRecord, fees: CommissionModel) -> TradeRecord:
+        if record.commission is not None:
+            return record
+        commission = fees.round_turn(record.symbol, record.quantity)
+        return replace(record, pnl=record.pnl - commission, commission=commission)
+
+    @classmethod
+    def _compute(cls, records: List[TradeRecord], initial_capital: Optional[float],
+                 calendar: SessionCalendar) -> 'PerformanceStats':
+        stats = cls()
+        if not records:
+            return stats
+        pnls = [r.pnl for r in records]
+        wins = [p for p in pnls if p > 0]
+        losses = [p for p in pnls if p < 0]
+        stats.trades = len(pnls)
+        stats.wins, stats.losses = len(wins), len(losses)
+        stats.breakeven = stats.trades - stats.wins - stats.losses
+        stats.win_rate = round(stats.wins / stats.trades * 100, 2)
+        stats.net_pnl = round(sum(pnls), 2)
+        stats.commissions = round(sum(r.commission for r in records if r.commission is not None), 2)
+        stats.gross_profit = round(sum(wins), 2)
+        stats.gross_loss = round(-sum(losses), 2)
+        stats.profit_factor = _ratio(stats.gross_profit, stats.gross_loss)
+        stats.expectancy = round(sum(pnls) / stats.trades, 2)
+        stats.avg_win = round(sum(wins) / len(wins), 2) if wins else 0.0
+        stats.avg_loss = round(sum(losses) / len(losses), 2) if losses else 0.0
+        stats.payoff_ratio = _ratio(stats.avg_win, -stats.avg_loss) if wins else None
+        stats.largest_win = round(max(wins), 2) if wins else 0.0
+        stats.largest_loss = round(min(losses), 2) if losses else 0.0
+
+        streak = 0  # > 0 wins in a row, < 0 losses in a row
+        for pnl in pnls:
+            if pnl > 0:
+                streak = streak + 1 if streak > 0 else 1
+            elif pnl < 0:
+                streak = streak - 1 if streak < 0 else -1
+            else:
+                streak = 0
+            stats.max_win_streak = max(stats.max_win_streak, streak)
+            stats.max_loss_streak = max(stats.max_loss_streak, -streak)
+
+        stats.avg_holding_minutes = _mean([(r.exit_time - r.entry_time).total_seconds() / 60 for r in records])
+        stats.avg_mae = _mean([r.mae for r in records if r.mae is not None])
+        stats.avg_mfe = _mean([r.mfe for r in records if r.mfe is not None])
+
+        # Closed-trade equity drawdown
+        equity = peak = initial_capital or 0.0
+        for pnl in pnls:
+            equity += pnl
+            peak = max(peak, equity)
+            if peak - equity > stats.max_drawdown:
+                stats.max_drawdown = round(peak - equity, 2)
+                if initial_capital and peak > 0:
+                    stats.max_drawdown_pct = round((peak - equity) / peak * 100, 2)
+        if initial_capital and stats.max_drawdown_pct is None:
+            stats.max_drawdown_pct = 0.0
+
+        # Daily P&L by trading date of the exit (closed maintenance/weekend exits count on their calendar date)
+        for record in records:
+            day = calendar.trading_date(record.symbol or 'ES', record.exit_time) or record.exit_time.date()
+            stats.daily_pnl[day] = stats.daily_pnl.get(day, 0.0) + record.pnl
+        stats.trading_days = len(stats.daily_pnl)
+        stats.sharpe, stats.sortino = cls._risk_ratios(list(stats.daily_pnl.values()), initial_capital)
+        return stats
+
+    @staticmethod
+    def _risk_ratios(daily: List[float], initial_capital: Optional[float]):
+        """Annualized Sharpe and Sortino of daily returns (daily dollar P&L without a capital)."""
+        if len(daily) < 2:
+            return None, None
+        if initial_capital:
+            returns, equity = [], initial_capital
+            for pnl in daily:
+                returns.append(pnl / equity if equity > 0 else 0.0)
+                equity += pnl
+        else:
+            returns = daily
+        mean = statistics.mean(returns)
+        deviation = statistics.stdev(returns)
+        sharpe = round(mean / deviation * math.sqrt(TRADING_DAYS), 3) if deviation > 0 else None
+        # Downside deviation over all days (target 0)
+        downside = math.sqrt(sum(min(r, 0.0) ** 2 for r in returns) / len(returns))
+        sortino = round(mean / downside * math.sqrt(TRADING_DAYS), 3) if downside > 0 else None
+        return sharpe, sortino
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary (breakdowns nested, without their daily P&L)."""
+        data = {
+            "trades": self.trades,
+            "wins": self.wins,
+            "losses": self.losses,
+            "breakeven": self.breakeven,
+            "win_rate": self.win_rate,
+            "net_pnl": self.net_pnl,
+            "commissions": self.commissions,
+            "gross_profit": self.gross_profit,
+            "gross_loss": self.gross_loss,
+            "profit_factor": self.profit_factor,
+            "expectancy": self.expectancy,
+            "avg_win": self.avg_win,
+            "avg_loss": self.avg_loss,
+            "payoff_ratio": self.payoff_ratio,
+            "largest_win": self.largest_win,
+            "largest_loss": self.largest_loss,
+            "max_win_streak": self.max_win_streak,
+            "max_loss_streak": self.max_loss_streak,
+            "avg_holding_minutes": self.avg_holding_minutes,
+            "avg_mae": self.avg_mae,
+            "avg_mfe": self.avg_mfe,
+            "sharpe": self.sharpe,
+            "sortino": self.sortino,
+            "max_drawdown": self.max_drawdown,
+            "max_drawdown_pct": self.max_drawdown_pct,
+            "trading_days": self.trading_days,
+        }
+        for name in ('by_session', 'by_strategy', 'by_symbol', 'by_direction'):
+            breakdown = getattr(self, name)
+            if breakdown:
+                data[name] = {key: stats.to_dict() for key, stats in breakdown.items()}
+        if self.daily_pnl and (self.by_session or self.by_strategy):
+            data["daily_pnl"] = {day.isoformat(): round(pnl, 2) for day, pnl in self.daily_pnl.items()}
+        return data
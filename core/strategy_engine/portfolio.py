@@ -0,0 +1,262 @@
+"""
+Portfolio-level coordination of strategies.
+
+Every strategy decides on its own; PortfolioCoordinator is the StrategyEngine
+gate that keeps their combined positions inside account-wide limits:
+
+- Net contracts: |net position| per symbol across all strategies stays at or
+  below max_net_contracts (global default, per-symbol overrides)
+- Margin: the sum over symbols of |net contracts| x margin per contract stays
+  at or below max_margin
+- No opposing positions: one strategy can't go long a symbol while another
+  is short it (the account nets them out, so both would be trading noise)
+- Priorities: when an entry opposes positions of strategies with a lower
+  priority and preempt is on, those strategies are flattened (FLAT signals
+  published through the engine before the entry) and the entry passes;
+  against an equal or higher priority the entry is dropped, so the first
+  signal wins among equals
+
+Entries are sized per strategy (allocation quantity, default 1 contract,
+which should match what the order router trades). Signals that reduce
+exposure always pass, and FLAT signals always pass. Positions are inferred
+from the published signals, as in SignalThrottle; mark_flat() forgets one
+closed at the broker. Attach the coordinator after the other gates, so a
+preemption isn't undone by a later gate dropping the entry.
+
+Usage:
+    portfolio = PortfolioCoordinator(max_net_contracts=3, max_margin=5000, margins={'MNQ': 1800, 'MES': 1400})
+    portfolio.set_allocation('orb', priority=10, quantity=2)
+    portfolio.set_allocation('ema_cross', priority=1)
+    portfolio.attach(engine)
+
+Configuration:
+- PORTFOLIO_MAX_NET_CONTRACTS: Net contracts per symbol, 0 = unlimited (default 0)
+- PORTFOLIO_SYMBOL_LIMITS: Per-symbol net contract limits, e.g. "MNQ:4,MES:2" (default empty)
+- PORTFOLIO_MAX_MARGIN: Total margin of the net positions in dollars, 0 = unlimited (default 0)
+- PORTFOLIO_MARGINS: Margin per contract by root symbol, e.g. "MNQ:1800,MES:1400" (default empty = 0)
+- PORTFOLIO_PREEMPT: Higher-priority entries flatten opposing lower-priority positions (default true)
+- PORTFOLIO_DEFAULT_QUANTITY: Contracts per entry for strategies without an allocation (default 1)
+"""
+
+import logging
+import os
+import threading
+from dataclasses import dataclass
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+from core.strategy_engine.strategy import Direction, Signal
+from core.volume_profile import normalize_symbol
+from infrastructure.config import env_bool
+
+logger = logging.getLogger(__name__)
+
+
+def parse_symbol_values(value: Optional[str]) -> Dict[str, float]:
+    """
+    Parse 'MNQ:1800,MES:1400' into {'MNQ': 1800.0, 'MES': 1400.0}.
+
+    Raises:
+        ValueError: Malformed entry
+    """
+    values: Dict[str, float] = {}
+    for entry in (value or '').split(','):
+        entry = entry.strip()
+        if not entry:
+            continue
+        symbol, sep, number = entry.partition(':')
+        try:
+            values[normalize_symbol(symbol)] = float(number)
+        except ValueError:
+            sep = ''
+        if not sep or not symbol.strip():
+            raise ValueError(f"Invalid entry '{entry}', expected SYMBOL:VALUE")
+    return values
+
+
+@dataclass(frozen=True)
+class StrategyAllocation:
+    """How a strategy takes part in the portfolio."""
+    priority: int = 0  # Higher wins conflicts
+    quantity: int = 1  # Contracts per entry
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {"priority": self.priority, "quantity": self.quantity}
+
+
+class PortfolioCoordinator:
+    """
+    Cross-strategy position, margin and conflict limits for entry signals.
+    """
+
+    def __init__(self, max_net_contracts: Optional[int] = None, max_margin: Optional[float] = None,
+                 margins: Optional[Dict[str, float]] = None, symbol_limits: Optional[Dict[str, int]] = None,
+                 preempt: Optional[bool] = None, default_quantity: Optional[int] = None):
+        """
+        Initialize coordinator.
+
+        Args:
+            max_net_contracts: Net contracts per symbol, 0 = unlimited (env: PORTFOLIO_MAX_NET_CONTRACTS)
+            max_margin: Total margin in dollars, 0 = unlimited (env: PORTFOLIO_MAX_MARGIN)
+            margins: Margin per contract by root symbol (env: PORTFOLIO_MARGINS)
+            symbol_limits: Net contract limits by root symbol (env: PORTFOLIO_SYMBOL_LIMITS)
+            preempt: Let higher-priority entries flatten opposing positions (env: PORTFOLIO_PREEMPT)
+            default_quantity: Contracts per entry without an allocation (env: PORTFOLIO_DEFAULT_QUANTITY)
+        """
+        self.max_net_contracts = (max_net_contracts if max_net_contracts is not None
+                                  else int(os.getenv('PORTFOLIO_MAX_NET_CONTRACTS', '0')))
+        self.max_margin = max_margin if max_margin is not None else float(os.getenv('PORTFOLIO_MAX_MARGIN', '0'))
+        margins = margins if margins is not None else parse_symbol_values(os.getenv('PORTFOLIO_MARGINS'))
+        self.margins = {normalize_symbol(k): float(v) for k, v in margins.items()}
+        limits = (symbol_limits if symbol_limits is not None
+                  else parse_symbol_values(os.getenv('PORTFOLIO_SYMBOL_LIMITS')))
+        self.symbol_limits = {normalize_symbol(k): int(v) for k, v in limits.items()}
+        self.preempt = preempt if preempt is not None else env_bool('PORTFOLIO_PREEMPT', 'true')
+        self.default_allocation = StrategyAllocation(
+            quantity=default_quantity if default_quantity is not None
+            else int(os.getenv('PORTFOLIO_DEFAULT_QUANTITY', '1')))
+        self._allocations: Dict[str, StrategyAllocation] = {}
+        self._positions: Dict[Tuple[str, str], int] = {}  # (strategy, root symbol) -> signed contracts
+        self._lock = threading.Lock()
+        self._engine: Any = None
+        self.passed = 0
+        self.preemptions = 0
+        self.blocked: Dict[str, int] = {"opposing": 0, "max_net": 0, "max_margin": 0}
+
+    # ---------------------------
+    # Allocations
+    # ---------------------------
+    def set_allocation(self, strategy_id: str, priority: int = 0,
+                       quantity: Optional[int] = None) -> StrategyAllocation:
+        if quantity is not None and quantity < 1:
+            raise ValueError(f"quantity must be >= 1, got {quantity}")
+        allocation = StrategyAllocation(priority=priority,
+                                        quantity=quantity if quantity is not None else self.default_allocation.quantity)
+        with self._lock:
+            self._allocations[strategy_id] = allocation
+        return allocation
+
+    def allocation_for(self, strategy_id: str) -> StrategyAllocation:
+        return self._allocations.get(strategy_id, self.default_allocation)
+
+    # ---------------------------
+    # Exposure
+    # ---------------------------
+    def net_position(self, symbol: str) -> int:
+        root = normalize_symbol(symbol)
+        with self._lock:
+            return self._net(root)
+
+    def _net(self, root: str, positions: Optional[Dict[Tuple[str, str], int]] = None) -> int:
+        positions = self._positions if positions is None else positions
+        return sum(qty for (_, sym), qty in positions.items() if sym == root)
+
+    def _margin(self, positions: Dict[Tuple[str, str], int]) -> float:
+        roots = {sym for _, sym in positions}
+        return sum(abs(self._net(root, positions)) * self.margins.get(root, 0.0) for root in roots)
+
+    def margin_used(self) -> float:
+        with self._lock:
+            return self._margin(self._positions)
+
+    # ---------------------------
+    # Gate
+    # ---------------------------
+    def allows(self, signal: Signal) -> bool:
+        """Whether an entry fits the portfolio (StrategyEngine gate); may flatten lower-priority positions."""
+        if signal.direction is Direction.FLAT:
+            return True
+        root = normalize_symbol(signal.symbol)
+        allocation = self.allocation_for(signal.strategy_id)
+        sign = signal.direction.sign
+        reason = None
+        with self._lock:
+            opposing = [key for key, qty in self._positions.items()
+                        if key[1] == root and key[0] != signal.strategy_id and qty * sign < 0]
+            stronger = [key for key in opposing if self.allocation_for(key[0]).priority >= allocation.priority]
+            if stronger or (opposing and not self.preempt):
+                reason = "opposing"
+            else:
+                after = {key: qty for key, qty in self._positions.items() if key not in opposing}
+                after[(signal.strategy_id, root)] = sign * allocation.quantity
+                limit = self.symbol_limits.get(root, self.max_net_contracts)
+                net_before, net_after = abs(self._net(root)), abs(self._net(root, after))
+                margin_before, margin_after = self._margin(self._positions), self._margin(after)
+                if limit and net_after > limit and net_after > net_before:
+                    reason = "max_net"
+                elif self.max_margin and margin_after > self.max_margin and margin_after > margin_before:
+                    reason = "max_margin"
+            if reason is not None:
+                self.blocked[reason] += 1
+            elif opposing:
+                self.preemptions += len(opposing)
+        if reason is not None:
+            logger.info(f"⚠️ Portfolio: dropped {signal.strategy_id}/{signal.symbol} {signal.direction.value} "
+                        f"({reason})")
+            return False
+        for strategy_id, _ in opposing:
+            self._flatten(strategy_id, signal)
+        return True
+
+    def _flatten(self, strategy_id: str, cause: Signal) -> None:
+        """Publish a FLAT for a preempted strategy (outside the lock: the bus calls record())."""
+        logger.info(f"🔓 Portfolio: flattening {strategy_id}/{cause.symbol} for higher-priority "
+                    f"{cause.strategy_id} {cause.direction.value}")
+        flat = Signal(strategy_id=strategy_id, symbol=cause.symbol, direction=Direction.FLAT, price=cause.price,
+                      timestamp=cause.timestamp, reason=f"Preempted by {cause.strategy_id}")
+        self.mark_flat(strategy_id, cause.symbol)
+        if self._engine is not None:
+            self._engine.publish(flat)
+
+    def record(self, signal: Signal) -> None:
+        """Track a published signal's position."""
+        key = (signal.strategy_id, normalize_symbol(signal.symbol))
+        with self._lock:
+            if signal.direction is Direction.FLAT:
+                self._positions.pop(key, None)
+            else:
+                self.passed += 1
+                self._positions[key] = signal.direction.sign * self.allocation_for(signal.strategy_id).quantity
+
+    def mark_flat(self, strategy_id: str, symbol: str) -> None:
+        with self._lock:
+            self._positions.pop((strategy_id, normalize_symbol(symbol)), None)
+
+    def reset(self) -> None:
+        with self._lock:
+            self._positions.clear()
+
+    def attach(self, engine: Any) -> Callable[[], None]:
+        """
+        Gate a StrategyEngine's signals and track the positions they open.
+
+        Returns:
+            Callable: Detach function
+        """
+        self._engine = engine
+        remove_gate = engine.add_gate(self.allows, name='portfolio')
+        unsubscribe = engine.on_signal(self.record)
+
+        def detach() -> None:
+            remove_gate()
+            unsubscribe()
+            self._engine = None
+        return detach
+
+    def get_stats(self) -> Dict[str, Any]:
+        with self._lock:
+            roots = sorted({sym for _, sym in self._positions})
+            positions: Dict[str, List[Dict[str, Any]]] = {}
+            for (strategy_id, sym), qty in self._positions.items():
+                positions.setdefault(sym, []).append({"strategy_id": strategy_id, "contracts": qty})
+            return {
+                "passed": self.passed,
+                "preemptions": self.preemptions,
+                "blocked": dict(self.blocked),
+                "net": {root: self._net(root) for root in roots},
+                "positions": positions,
+                "margin_used": round(self._margin(self._positions), 2),
+                "max_margin": self.max_margin,
+                "max_net_contracts": self.max_net_contracts,
+                "symbol_limits": dict(self.symbol_limits),
+                "allocations": {strategy_id: a.to_dict() for strategy_id, a in self._allocations.items()},
+            }
@@ -0,0 +1,207 @@
+"""
+Plain Python objects as strategies.
+
+StrategyEngine runs Strategy subclasses, but prototypes rarely start as one:
+add_strategy() also takes any object with an on_bar(bar) and/or
+on_tick(event) method and wraps it in a PyStrategy, so the engine still does
+the routing, batching and per-strategy error isolation.
+
+Wrapped handlers may return anything a strategy returns (None, a Signal, a
+list of Signals) or plain dicts, including the signal dicts of the legacy
+strategies/ package:
+
+    {"action": "LONG", "symbol": "MNQ", "entry_price": 21000.0, "stop_loss": 20990.0,
+     "take_profit": 21020.0, "confidence": 0.7, "reason": "..."}
+
+("action" or "direction" LONG/SHORT/CLOSE/FLAT, "entry_price" or "price",
+"stop_loss" or "stop", "take_profit" or "target"). Missing symbol, price and
+timestamp are taken from the event being handled.
+
+Optional attributes of the wrapped object are honoured: strategy_id (or
+name), symbols, timeframes, reset(), get_state(symbol) and on_bars(bars),
+which receives StrategyEngine.on_bars() batches (e.g. a backfill in one call,
+so the object can vectorise it). Handlers must be synchronous; do I/O in a
+SignalBus consumer instead.
+
+Usage:
+    class MyIdea:
+        symbols = ['MNQ']
+
+        def on_bar(self, bar):
+            if bar.close > bar.open + 10:
+                return {"action": "LONG", "stop_loss": bar.low}
+
+    engine.add_strategy(MyIdea())  # Wrapped as PyStrategy('MyIdea')
+"""
+
+import asyncio
+import logging
+from typing import Any, Dict, Iterable, List, Optional, Sequence
+
+from core.bar_aggregator import Bar
+from core.market_events import MarketEvent
+from core.strategy_engine.strategy import Direction, Signal, SignalResult, Strategy, params_dict
+
+logger = logging.getLogger(__name__)
+
+_ACTIONS = {
+    'long': Direction.LONG,
+    'buy': Direction.LONG,
+    'short': Direction.SHORT,
+    'sell': Direction.SHORT,
+    'flat': Direction.FLAT,
+    'close': Direction.FLAT,
+    'exit': Direction.FLAT,
+}
+
+
+def signal_from_dict(data: Dict[str, Any], strategy_id: str, event: Any = None) -> Signal:
+    """
+    Build a Signal from a plain or legacy strategies/ signal dict.
+
+    Args:
+        data: Signal dict (see module docstring for accepted keys)
+        strategy_id: Id stamped on the signal when the dict has none
+        event: Bar/event being handled, fills in symbol, price, timestamp and timeframe
+
+    Raises:
+        ValueError: Unknown action or missing symbol/price/timestamp
+    """
+    action = data.get('direction', data.get('action'))
+    direction = action if isinstance(action, Direction) else _ACTIONS.get(str(action).strip().lower())
+    if direction is None:
+        raise ValueError(f"Unknown signal action {action!r}")
+    symbol = data.get('symbol') or getattr(event, 'symbol', None)
+    price = data.get('price', data.get('entry_price'))
+    if price is None:
+        price = getattr(event, 'close', None)
+        if price is None:
+            price = getattr(event, 'price', None)
+    timestamp = data.get('timestamp') or getattr(event, 'timestamp', None)
+    if symbol is None or price is None or timestamp is None:
+        raise ValueError(f"Signal dict needs symbol, price and timestamp (directly or from the event): {data!r}")
+    return Signal(
+        strategy_id=data.get('strategy_id', strategy_id),
+        symbol=symbol,
+        direction=direction,
+        price=float(price),
+        timestamp=timestamp,
+        confidence=float(data.get('confidence', 1.0)),
+        stop=data.get('stop', data.get('stop_loss')),
+        target=data.get('target', data.get('take_profit')),
+        timeframe=data.get('timeframe') or getattr(event, 'timeframe', None),
+        reason=data.get('reason', ''),
+    )
+
+
+def to_signals(result: Any, strategy_id: str, event: Any = None) -> List[Signal]:
+    """Normalize a handler's return value (None, Signal, dict or a list of them) to a list of Signals."""
+    if result is None:
+        return []
+    if isinstance(result, (Signal, dict)):
+        result = [result]
+    if not isinstance(result, (list, tuple)):
+        raise TypeError(f"Strategy {strategy_id} returned {type(result).__name__}, expected Signal, dict or list")
+    return [item if isinstance(item, Signal) else signal_from_dict(item, strategy_id, event)
+            for item in result if item is not None]
+
+
+class PyStrategy(Strategy):
+    """
+    Strategy adapter around a duck-typed Python object.
+    """
+
+    def __init__(self, target: Any, strategy_id: Optional[str] = None,
+                 symbols: Optional[Iterable[str]] = None, timeframes: Optional[Iterable[str]] = None):
+        """
+        Wrap an object.
+
+        Args:
+            target: Object with on_bar(bar) and/or on_tick(event)
+            strategy_id: Id (default: target.strategy_id, target.name or the class name)
+            symbols: Symbols (default: target.symbols, else all)
+            timeframes: Bar timeframes (default: target.timeframes, else all)
+
+        Raises:
+            TypeError: No handlers, or coroutine handlers
+        """
+        handlers = [name for name in ('on_bar', 'on_bars', 'on_tick') if callable(getattr(target, name, None))]
+        if not handlers:
+            raise TypeError(f"{type(target).__name__} has no on_bar/on_bars/on_tick method")
+        for name in handlers:
+            if asyncio.iscoroutinefunction(getattr(target, name)):
+                raise TypeError(f"{type(target).__name__}.{name} is a coroutine function; strategy handlers "
+                                f"run synchronously (do I/O in a SignalBus consumer)")
+        strategy_id = (strategy_id or getattr(target, 'strategy_id', None) or getattr(target, 'name', None)
+                       or type(target).__name__)
+        super().__init__(str(strategy_id),
+                         symbols if symbols is not None else getattr(target, 'symbols', None),
+                         timeframes if timeframes is not None else getattr(target, 'timeframes', None))
+        self.target = target
+        self._on_bar = getattr(target, 'on_bar', None)
+        self._on_bars = getattr(target, 'on_bars', None)
+        self._on_tick = getattr(target, 'on_tick', None)
+
+    def on_bar(self, bar: Bar) -> SignalResult:
+        if self._on_bar is not None:
+            return to_signals(self._on_bar(bar), self.strategy_id, bar)
+        if self._on_bars is not None:
+            return self.on_bars([bar])
+        return None
+
+    def on_bars(self, bars: Sequence[Bar]) -> SignalResult:
+        if self._on_bars is None:
+            return super().on_bars(bars)
+        # Dicts without a symbol/price default to the last bar of the batch
+        return to_signals(self._on_bars(list(bars)), self.strategy_id, bars[-1] if bars else None)
+
+    def on_tick(self, event: MarketEvent) -> SignalResult:
+        if self._on_tick is None:
+            return None
+        return to_signals(self._on_tick(event), self.strategy_id, event)
+
+    def reset(self, symbol: Optional[str] = None) -> None:
+        reset = getattr(self.target, 'reset', None)
+        if not callable(reset):
+            return
+        if symbol is None:
+            reset()
+        else:
+            reset(symbol)
+
+    def get_state(self, symbol: str) -> Dict[str, Any]:
+        get_state = getattr(self.target, 'get_state', None)
+        return get_state(symbol) if callable(get_state) else {}
+
+    def bind_context(self, context: Any) -> None:
+        super().bind_context(context)
+        try:
+            self.target.context = context
+        except AttributeError:
+            pass  # __slots__ or read-only property: the object does without
+
+    def get_params(self) -> Dict[str, Any]:
+        return params_dict(getattr(self.target, 'params', None))
+
+    def params_with(self, changes: Dict[str, Any], base: Any = None) -> Any:
+        params_with = getattr(self.target, 'params_with', None)
+        if callable(params_with):
+            return params_with(changes) if base is None else params_with(changes, base)
+        return super().params_with(changes, base if base is not None else getattr(self.target, 'params', None))
+
+    def set_params(self, params: Any) -> None:
+        set_params = getattr(self.target, 'set_params', None)
+        if callable(set_params):
+            set_params(params)
+        else:
+            self.target.params = params
+
+    def __repr__(self) -> str:
+        return f"PyStrategy({self.strategy_id!r}, {type(self.target).__name__})"
+
+
+def as_strategy(target: Any, strategy_id: Optional[str] = None) -> Strategy:
+    """Return target if it already is a Strategy, otherwise wrap it in a PyStrategy."""
+    if isinstance(target, Strategy):
+        return target
+    return PyStrategy(target, strategy_id)
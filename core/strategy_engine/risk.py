@@ -0,0 +1,695 @@
+"""
+Daily loss limit and trailing drawdown kill switch.
+
+Tracks each account's P&L for the current trading session in real time
+(realized from fills, net of commissions, plus unrealized from open
+positions marked at the latest price) and trips a kill switch when it
+reaches the account's daily loss limit or its trailing drawdown limit:
+
+1. New entries are blocked: attach() registers a StrategyEngine gate that
+   drops LONG/SHORT signals while the account is halted (FLAT exits always
+   pass)
+2. All working orders are cancelled (cancel_orders), so nothing resting can
+   re-open a position
+3. All positions are flattened (flatten)
+4. The alert callback is notified with the account state
+
+A daily loss halt holds until the next trading session (the SessionCalendar
+trading date of RISK_SESSION_SYMBOL changes), when the session P&L starts
+from zero again, or until reset() is called.
+
+Trailing drawdown (TopStep maximum loss limit), for accounts with a known
+balance (set_balance() or load_account() from AccountTracker):
+- The account fails once equity (session start balance + session P&L) falls
+  to the drawdown threshold: high-water mark - max loss limit
+- The high-water mark is the highest end-of-session balance (trailing='eod',
+  TopStep's rule) or the highest equity seen intraday (trailing='intraday')
+- With lock_trailing the threshold stops trailing once it reaches the
+  starting balance
+- A drawdown halt does not lift at the next session; only reset() clears it
+
+Warnings: when the loss reaches each configured fraction of either limit
+(e.g. 80%), one RiskEvent per limit and level is sent to on_risk_event()
+listeners (again after the next session starts), followed by a 'breach'
+event when the limit itself is hit.
+
+Breach detection and the entry block run synchronously on whatever thread
+reports the fill or price (market data callbacks, strategy workers), so they
+don't wait on a busy event loop. cancel_orders/flatten may be coroutine
+functions (the bot's REST calls); they run on the event loop the manager was
+created or bound on, with retries.
+
+Usage:
+    risk = RiskManager(cancel_orders=cancel_all_orders,
+                       flatten=lambda account_id: bot.flatten_all_positions(interactive=False),
+                       alert_callback=discord_alert)
+    risk.set_limit(account_id, 1000.0)
+    risk.attach(engine)                                  # Block entries while halted
+    risk.on_fill(account_id, 'MNQ', 'BUY', 1, 21000.25, commission=0.37)  # Or from fees= when omitted
+    risk.on_event(quote)                                 # Marks open positions to market
+
+    risk.load_account(bot.account_tracker.get_all_states()[account_id])  # Balance, HWM, MLL, DLL
+    risk.on_risk_event(lambda event: logger.warning(event.message))
+
+Configuration:
+- RISK_DAILY_LOSS_LIMIT: Default daily loss limit per account in dollars, 0 = disabled (default 0)
+- RISK_MAX_LOSS_LIMIT: Default trailing drawdown limit per account in dollars, 0 = disabled (default 0)
+- RISK_TRAILING: High-water mark mode, eod or intraday (default eod)
+- RISK_TRAILING_LOCK: Stop trailing at the starting balance (default true)
+- RISK_WARNING_LEVELS: Comma-separated fractions of a limit that raise warnings (default 0.8)
+- RISK_SESSION_SYMBOL: Symbol whose session calendar defines the trading day (default MNQ)
+- RISK_KILL_RETRIES: Attempts per kill switch call (default 3)
+- RISK_KILL_RETRY_DELAY: Seconds between attempts (default 1)
+"""
+
+import asyncio
+import inspect
+import logging
+import os
+import threading
+from dataclasses import dataclass, field
+from datetime import date, datetime, timezone
+from typing import Any, Awaitable, Callable, Dict, Iterable, List, Optional, Set, Tuple, Union
+
+from core.market_events import MarketEvent
+from core.session_calendar import SessionCalendar
+from core.strategy_engine.backtest import POINT_VALUES, event_price
+from core.strategy_engine.fees import CommissionModel
+from core.strategy_engine.strategy import Direction, Signal
+from core.volume_profile import normalize_symbol
+from infrastructure.config import env_bool
+
+logger = logging.getLogger(__name__)
+
+AccountAction = Callable[[str], Union[Any, Awaitable[Any]]]
+
+DAILY_LOSS = 'daily_loss'
+TRAILING_DRAWDOWN = 'trailing_drawdown'
+TRAILING_MODES = ('eod', 'intraday')
+
+
+def _side_sign(side: Any) -> int:
+    """BUY/LONG or TopStepX side code 0 -> +1, SELL/SHORT or 1 -> -1."""
+    if isinstance(side, str):
+        return 1 if side.upper() in ('BUY', 'LONG') else -1
+    return 1 if int(side) == 0 else -1
+
+
+@dataclass
+class RiskPosition:
+    """Net position in one symbol (quantity > 0 long, < 0 short)."""
+    quantity: int = 0
+    avg_price: float = 0.0
+
+
+@dataclass
+class AccountRisk:
+    """Session P&L and kill switch state of one account."""
+    account_id: str
+    daily_loss_limit: float
+    session_date: Optional[date] = None
+    realized_pnl: float = 0.0  # Net of commissions, since the session started
+    unrealized_pnl: float = 0.0
+    positions: Dict[str, RiskPosition] = field(default_factory=dict)
+    max_loss_limit: float = 0.0
+    starting_balance: Optional[float] = None
+    session_balance: Optional[float] = None  # Balance when the session started
+    high_water_mark: Optional[float] = None
+    halted: bool = False
+    halted_at: Optional[datetime] = None
+    halt_reason: Optional[str] = None
+    halt_limit: Optional[str] = None  # DAILY_LOSS, TRAILING_DRAWDOWN or None (manual)
+    kill_switch: Dict[str, Any] = field(default_factory=dict)  # Outcome of the last kill switch run
+    warned: Set[Tuple[str, float]] = field(default_factory=set)  # (limit, level) warned this session
+
+    @property
+    def daily_pnl(self) -> float:
+        return self.realized_pnl + self.unrealized_pnl
+
+    @property
+    def remaining(self) -> Optional[float]:
+        """Loss still allowed today (None without a limit)."""
+        return self.daily_loss_limit + self.daily_pnl if self.daily_loss_limit > 0 else None
+
+    @property
+    def equity(self) -> Optional[float]:
+        return self.session_balance + self.daily_pnl if self.session_balance is not None else None
+
+    def drawdown_threshold(self, lock: bool = True) -> Optional[float]:
+        """Equity at which the trailing drawdown limit is hit (None without a limit or balance)."""
+        if self.max_loss_limit <= 0 or self.high_water_mark is None:
+            return None
+        threshold = self.high_water_mark - self.max_loss_limit
+        if lock and self.starting_balance is not None:
+            threshold = min(threshold, self.starting_balance)
+        return threshold
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        return {
+            "account_id": self.account_id,
+            "session_date": self.session_date.isoformat() if self.session_date else None,
+            "daily_loss_limit": self.daily_loss_limit,
+            "realized_pnl": round(self.realized_pnl, 2),
+            "unrealized_pnl": round(self.unrealized_pnl, 2),
+            "daily_pnl": round(self.daily_pnl, 2),
+            "remaining": round(self.remaining, 2) if self.remaining is not None else None,
+            "max_loss_limit": self.max_loss_limit,
+            "starting_balance": self.starting_balance,
+            "equity": round(self.equity, 2) if self.equity is not None else None,
+            "high_water_mark": round(self.high_water_mark, 2) if self.high_water_mark is not None else None,
+            "drawdown_threshold": self.drawdown_threshold(),
+            "positions": {symbol: {"quantity": p.quantity, "avg_price": p.avg_price}
+                          for symbol, p in self.positions.items()},
+            "halted": self.halted,
+            "halted_at": self.halted_at.isoformat() if self.halted_at else None,
+            "halt_reason": self.halt_reason,
+            "halt_limit": self.halt_limit,
+            "kill_switch": self.kill_switch,
+        }
+
+
+@dataclass(frozen=True)
+class RiskEvent:
+    """Proximity warning or breach of a risk limit."""
+    account_id: str
+    kind: str  # 'warning' or 'breach'
+    limit: str  # DAILY_LOSS or TRAILING_DRAWDOWN
+    level: float  # Fraction of the limit used when the event fired
+    loss: float  # Dollars of the limit used
+    limit_amount: float
+    timestamp: datetime
+    message: str
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        return {
+            "account_id": self.account_id,
+            "kind": self.kind,
+            "limit": self.limit,
+            "level": self.level,
+            "loss": round(self.loss, 2),
+            "limit_amount": self.limit_amount,
+            "timestamp": self.timestamp.isoformat(),
+            "message": self.message,
+        }
+
+
+class RiskManager:
+    """
+    Per-account daily loss and trailing drawdown limits with a cancel/flatten kill switch and signal blocking.
+    """
+
+    def __init__(self, daily_loss_limit: Optional[float] = None,
+                 max_loss_limit: Optional[float] = None,
+                 trailing: Optional[str] = None,
+                 lock_trailing: Optional[bool] = None,
+                 warning_levels: Optional[Iterable[float]] = None,
+                 cancel_orders: Optional[AccountAction] = None,
+                 flatten: Optional[AccountAction] = None,
+                 alert_callback: Optional[Callable[[AccountRisk], Any]] = None,
+                 account_resolver: Optional[Callable[[Signal], Optional[str]]] = None,
+                 calendar: Optional[SessionCalendar] = None,
+                 session_symbol: Optional[str] = None,
+                 point_values: Optional[Dict[str, float]] = None,
+                 fees: Optional[CommissionModel] = None,
+                 kill_retries: Optional[int] = None,
+                 retry_delay: Optional[float] = None,
+                 loop: Optional[asyncio.AbstractEventLoop] = None):
+        """
+        Initialize risk manager.
+
+        Args:
+            daily_loss_limit: Default limit for accounts without set_limit() (env: RISK_DAILY_LOSS_LIMIT)
+            max_loss_limit: Default trailing drawdown limit (env: RISK_MAX_LOSS_LIMIT)
+            trailing: High-water mark mode, 'eod' or 'intraday' (env: RISK_TRAILING)
+            lock_trailing: Stop trailing at the starting balance (env: RISK_TRAILING_LOCK)
+            warning_levels: Fractions of a limit that raise warnings (env: RISK_WARNING_LEVELS)
+            cancel_orders: Called (sync or async) with the account id to cancel all working orders
+            flatten: Called (sync or async) with the account id to close all positions
+            alert_callback: Called (sync or async) with the AccountRisk after the kill switch ran
+            account_resolver: Maps a signal to the account it would trade (default: block while any account
+                is halted)
+            calendar: Session calendar (default: SessionCalendar())
+            session_symbol: Symbol defining the trading day (env: RISK_SESSION_SYMBOL)
+            point_values: Overrides of the dollar value per point by root symbol
+            fees: Commission and fees charged on fills reported without a commission
+            kill_retries: Attempts per kill switch call (env: RISK_KILL_RETRIES)
+            retry_delay: Seconds between attempts (env: RISK_KILL_RETRY_DELAY)
+            loop: Event loop for async kill switch calls (default: the running loop, if any)
+
+        Raises:
+            ValueError: Unknown trailing mode or a warning level outside (0, 1)
+        """
+        self.daily_loss_limit = (daily_loss_limit if daily_loss_limit is not None
+                                 else float(os.getenv('RISK_DAILY_LOSS_LIMIT', '0')))
+        self.max_loss_limit = (max_loss_limit if max_loss_limit is not None
+                               else float(os.getenv('RISK_MAX_LOSS_LIMIT', '0')))
+        self.trailing = (trailing or os.getenv('RISK_TRAILING', 'eod')).lower()
+        if self.trailing not in TRAILING_MODES:
+            raise ValueError(f"trailing must be one of {TRAILING_MODES}, got {self.trailing!r}")
+        self.lock_trailing = lock_trailing if lock_trailing is not None else env_bool('RISK_TRAILING_LOCK', 'true')
+        if warning_levels is None:
+            warning_levels = [float(v) for v in os.getenv('RISK_WARNING_LEVELS', '0.8').split(',') if v.strip()]
+        self.warning_levels = sorted(set(float(v) for v in warning_levels))
+        if any(not 0 < level < 1 for level in self.warning_levels):
+            raise ValueError(f"warning_levels must be between 0 and 1, got {self.warning_levels}")
+        self.cancel_orders = cancel_orders
+        self.flatten = flatten
+        self.alert_callback = alert_callback
+        self.account_resolver = account_resolver
+        self.calendar = calendar or SessionCalendar()
+        self.session_symbol = session_symbol or os.getenv('RISK_SESSION_SYMBOL', 'MNQ')
+        self.point_values = {normalize_symbol(k): v for k, v in (point_values or {}).items()}
+        self.fees = fees
+        self.kill_retries = max(1, kill_retries if kill_retries is not None
+                                else int(os.getenv('RISK_KILL_RETRIES', '3')))
+        self.retry_delay = retry_delay if retry_delay is not None else float(os.getenv('RISK_KILL_RETRY_DELAY', '1'))
+        if loop is None:
+            try:
+                loop = asyncio.get_running_loop()
+            except RuntimeError:
+                loop = None
+        self._loop = loop
+        self._accounts: Dict[str, AccountRisk] = {}
+        self._limits: Dict[str, float] = {}
+        self._marks: Dict[str, float] = {}  # Root symbol -> last price
+        self._lock = threading.RLock()
+        self._tasks: set = set()
+        self._listeners: Tuple[Callable[[RiskEvent], Any], ...] = ()  # Copy-on-write
+        self.breaches = 0
+        self.warnings = 0
+
+    def bind_loop(self, loop: Optional[asyncio.AbstractEventLoop] = None) -> None:
+        """Run async kill switch calls on this loop (default: the running loop)."""
+        self._loop = loop or asyncio.get_running_loop()
+
+    def point_value(self, symbol: str) -> float:
+        root = normalize_symbol(symbol)
+        return self.point_values.get(root, POINT_VALUES.get(root, 1.0))
+
+    # ---------------------------
+    # Accounts
+    # ---------------------------
+    def set_limit(self, account_id: str, limit: float) -> None:
+        """Set an account's daily loss limit in dollars (0 disables it)."""
+        with self._lock:
+            self._limits[str(account_id)] = float(limit)
+            state = self._accounts.get(str(account_id))
+            if state is not None:
+                state.daily_loss_limit = float(limit)
+        self._check(str(account_id), datetime.now(timezone.utc))
+
+    def set_balance(self, account_id: str, balance: float, max_loss_limit: Optional[float] = None,
+                    high_water_mark: Optional[float] = None, starting_balance: Optional[float] = None) -> AccountRisk:
+        """
+        Set an account's balance at the start of the current session, enabling the trailing drawdown limit.
+
+        Args:
+            account_id: Account ID
+            balance: Balance excluding the current session's P&L
+            max_loss_limit: Trailing drawdown in dollars (default: the manager's max_loss_limit)
+            high_water_mark: Highest balance so far (default: balance, or the current mark if higher)
+            starting_balance: Initial account balance the threshold locks at (default: balance)
+        """
+        with self._lock:
+            state = self.account(account_id)
+            state.session_balance = float(balance)
+            if max_loss_limit is not None:
+                state.max_loss_limit = float(max_loss_limit)
+            if starting_balance is not None or state.starting_balance is None:
+                state.starting_balance = float(starting_balance if starting_balance is not None else balance)
+            marks = [v for v in (high_water_mark, state.high_water_mark, balance) if v is not None]
+            state.high_water_mark = float(high_water_mark if high_water_mark is not None else max(marks))
+        self._check(state.account_id, datetime.now(timezone.utc))
+        return state
+
+    def load_account(self, account_state: Any) -> AccountRisk:
+        """
+        Take balance and limits from an AccountTracker AccountState.
+
+        The session balance is the tracker's current balance minus the P&L this manager has
+        already counted for the session.
+        """
+        account_id = str(account_state.account_id)
+        with self._lock:
+            counted = self.account(account_id).daily_pnl
+        self.set_limit(account_id, account_state.daily_loss_limit)
+        return self.set_balance(account_id, account_state.current_balance - counted,
+                                max_loss_limit=account_state.maximum_loss_limit,
+                                high_water_mark=account_state.highest_EOD_balance,
+                                starting_balance=account_state.starting_balance)
+
+    def account(self, account_id: str) -> AccountRisk:
+        """State of an account (created on first use)."""
+        account_id = str(account_id)
+        with self._lock:
+            state = self._accounts.get(account_id)
+            if state is None:
+                state = AccountRisk(account_id, self._limits.get(account_id, self.daily_loss_limit),
+                                    max_loss_limit=self.max_loss_limit)
+                self._accounts[account_id] = state
+            return state
+
+    def is_halted(self, account_id: Optional[str] = None) -> bool:
+        """Whether an account (default: any account) is halted."""
+        with self._lock:
+            if account_id is None:
+                return any(state.halted for state in self._accounts.values())
+            state = self._accounts.get(str(account_id))
+            return state is not None and state.halted
+
+    # ---------------------------
+    # P&L updates
+    # ---------------------------
+    def on_fill(self, account_id: str, symbol: str, side: Any, quantity: int, price: float,
+                commission: Optional[float] = None, timestamp: Optional[datetime] = None) -> AccountRisk:
+        """
+        Apply a fill to the account's positions and session P&L.
+
+        Args:
+            account_id: Account ID
+            symbol: Symbol or contract ID
+            side: "BUY"/"SELL" or TopStepX side code (0 = buy, 1 = sell)
+            quantity: Filled contracts
+            price: Fill price
+            commission: Commission and fees for this fill in dollars (default: from fees, else 0)
+            timestamp: Fill time (default: now)
+        """
+        timestamp = timestamp or datetime.now(timezone.utc)
+        root = normalize_symbol(symbol)
+        quantity = int(quantity)
+        if commission is None:
+            commission = self.fees.cost(root, quantity) if self.fees is not None else 0.0
+        delta = _side_sign(side) * quantity
+        with self._lock:
+            state = self.account(account_id)
+            self._roll(state, timestamp)
+            position = state.positions.setdefault(root, RiskPosition())
+            if position.quantity == 0 or (position.quantity > 0) == (delta > 0):
+                total = position.quantity + delta
+                position.avg_price = (position.avg_price * abs(position.quantity) + price * quantity) / abs(total)
+                position.quantity = total
+            else:
+                closing = min(quantity, abs(position.quantity))
+                sign = 1 if position.quantity > 0 else -1
+                state.realized_pnl += (price - position.avg_price) * closing * sign * self.point_value(root)
+                remaining = position.quantity + delta
+                if remaining and (remaining > 0) != (position.quantity > 0):
+                    position.avg_price = price  # Reversed through flat
+                position.quantity = remaining
+            if position.quantity == 0:
+                del state.positions[root]
+            state.realized_pnl -= commission
+            self._marks[root] = float(price)  # A fill is a traded price
+            self._mark(state)
+        self._check(state.account_id, timestamp)
+        return state
+
+    def set_realized_pnl(self, account_id: str, pnl: float, timestamp: Optional[datetime] = None) -> AccountRisk:
+        """Overwrite the session's realized P&L with an authoritative figure (broker, AccountTracker)."""
+        timestamp = timestamp or datetime.now(timezone.utc)
+        with self._lock:
+            state = self.account(account_id)
+            self._roll(state, timestamp)
+            state.realized_pnl = float(pnl)
+        self._check(state.account_id, timestamp)
+        return state
+
+    def on_price(self, symbol: str, price: float, timestamp: Optional[datetime] = None) -> None:
+        """Mark open positions in a symbol to a new price."""
+        timestamp = timestamp or datetime.now(timezone.utc)
+        root = normalize_symbol(symbol)
+        with self._lock:
+            self._marks[root] = float(price)
+            affected = []
+            for state in self._accounts.values():
+                self._roll(state, timestamp)
+                if root in state.positions:
+                    self._mark(state)
+                    affected.append(state.account_id)
+        for account_id in affected:
+            self._check(account_id, timestamp)
+
+    def on_event(self, event: MarketEvent) -> None:
+        """Market event listener: marks positions at trade prices and quote last/mid prices."""
+        price = event_price(event)
+        if price is not None:
+            self.on_price(event.symbol, price, event.timestamp)
+
+    def _mark(self, state: AccountRisk) -> None:
+        state.unrealized_pnl = sum(
+            (self._marks.get(symbol, p.avg_price) - p.avg_price) * p.quantity * self.point_value(symbol)
+            for symbol, p in state.positions.items())
+        if self.trailing == 'intraday' and state.equity is not None and state.equity > state.high_water_mark:
+            state.high_water_mark = state.equity
+
+    def roll(self, timestamp: Optional[datetime] = None) -> None:
+        """Start a new session for accounts whose trading day has ended (also done on every update)."""
+        timestamp = timestamp or datetime.now(timezone.utc)
+        with self._lock:
+            for state in self._accounts.values():
+                self._roll(state, timestamp)
+
+    def _roll(self, state: AccountRisk, timestamp: datetime) -> None:
+        """Reset session P&L and lift the halt once the trading date changes (caller holds the lock)."""
+        day = self.calendar.trading_date(self.session_symbol, timestamp)
+        if day is None or day == state.session_date:
+            return
+        previous, state.session_date = state.session_date, day
+        if previous is None:
+            return
+        if state.session_balance is not None:
+            # Close the session: its P&L, open positions at their current mark, moves into the balance
+            closing = state.equity
+            if closing > state.high_water_mark:
+                state.high_water_mark = closing
+            state.session_balance = closing
+        state.realized_pnl = 0.0
+        state.warned.clear()
+        if state.halted and state.halt_limit != TRAILING_DRAWDOWN:
+            logger.info(f"🔓 Risk: new session {day} for account {state.account_id}, trading re-enabled")
+            state.halted = False
+            state.halted_at = None
+            state.halt_reason = None
+            state.halt_limit = None
+        # Positions carried over the session break start the new session at their settlement mark
+        state.positions = {symbol: RiskPosition(p.quantity, self._marks.get(symbol, p.avg_price))
+                           for symbol, p in state.positions.items()}
+        self._mark(state)
+
+    # ---------------------------
+    # Kill switch
+    # ---------------------------
+    def _usage(self, state: AccountRisk) -> List[Tuple[str, float, float, str]]:
+        """(limit, loss in dollars, limit in dollars, description) of every active limit."""
+        usage = []
+        if state.daily_loss_limit > 0:
+            usage.append((DAILY_LOSS, -state.daily_pnl, state.daily_loss_limit,
+                          f"daily P&L ${state.daily_pnl:,.2f} vs limit ${-state.daily_loss_limit:,.2f}"))
+        threshold = state.drawdown_threshold(self.lock_trailing)
+        if threshold is not None and state.equity is not None:
+            loss = state.max_loss_limit - (state.equity - threshold)
+            usage.append((TRAILING_DRAWDOWN, loss, state.max_loss_limit,
+                          f"equity ${state.equity:,.2f} vs drawdown threshold ${threshold:,.2f} "
+                          f"(high-water mark ${state.high_water_mark:,.2f})"))
+        return usage
+
+    def _check(self, account_id: str, timestamp: datetime) -> None:
+        events: List[RiskEvent] = []
+        with self._lock:
+            state = self._accounts.get(account_id)
+            if state is None:
+                return
+            for limit, loss, amount, description in self._usage(state):
+                used = loss / amount
+                for level in self.warning_levels:
+                    if used >= level and (limit, level) not in state.warned and used < 1:
+                        state.warned.add((limit, level))
+                        events.append(RiskEvent(account_id, 'warning', limit, round(used, 4), loss, amount, timestamp,
+                                                f"{limit.replace('_', ' ').capitalize()} at {used:.0%} of the limit: "
+                                                f"{description}"))
+                if used >= 1 and not (state.halted and (state.halt_limit == limit or limit == DAILY_LOSS)):
+                    events.append(RiskEvent(account_id, 'breach', limit, round(used, 4), loss, amount, timestamp,
+                                            f"{limit.replace('_', ' ').capitalize()} limit reached: {description}"))
+        for event in events:
+            if event.kind == 'warning':
+                self.warnings += 1
+                logger.warning(f"⚠️ Risk: account {account_id}: {event.message}")
+            self._publish(event)
+        for event in events:
+            if event.kind == 'breach':
+                self.halt(account_id, event.message, timestamp, limit=event.limit)
+
+    def on_risk_event(self, callback: Callable[[RiskEvent], Any]) -> Callable[[], None]:
+        """
+        Receive warnings and breaches (sync callbacks run inline, async ones on the bound loop).
+
+        Returns:
+            Callable: Unsubscribe function
+        """
+        with self._lock:
+            self._listeners = self._listeners + (callback,)
+
+        def unsubscribe() -> None:
+            with self._lock:
+                self._listeners = tuple(c for c in self._listeners if c is not callback)
+        return unsubscribe
+
+    def _publish(self, event: RiskEvent) -> None:
+        for callback in self._listeners:
+            try:
+                result = callback(event)
+                if inspect.isawaitable(result):
+                    self._schedule(result)
+            except Exception as e:
+                logger.error(f"❌ Risk event listener failed: {e}")
+
+    def halt(self, account_id: str, reason: str = 'manual', timestamp: Optional[datetime] = None,
+             limit: Optional[str] = None) -> bool:
+        """
+        Halt an account and run the kill switch (cancel orders, flatten, alert) in the background.
+
+        Args:
+            account_id: Account ID
+            reason: Logged and reported reason
+            timestamp: Halt time (default: now)
+            limit: Limit that was hit; a TRAILING_DRAWDOWN halt survives the session change
+
+        Returns:
+            bool: False if the account was already halted (a drawdown breach still makes the halt permanent)
+        """
+        with self._lock:
+            state = self.account(account_id)
+            if state.halted:
+                if limit == TRAILING_DRAWDOWN and state.halt_limit != TRAILING_DRAWDOWN:
+                    state.halt_limit, state.halt_reason = limit, reason
+                    logger.error(f"🚨 Risk: account {state.account_id} halted until reset - {reason}")
+                return False
+            state.halted = True
+            state.halted_at = timestamp or datetime.now(timezone.utc)
+            state.halt_reason = reason
+            state.halt_limit = limit
+            self.breaches += 1
+        logger.error(f"🚨 Risk: account {state.account_id} halted - {reason}. Blocking new entries, "
+                     f"cancelling orders and flattening positions")
+        self._schedule(self.kill(state.account_id, reason))
+        return True
+
+    def _schedule(self, coro: Awaitable[Any]) -> None:
+        """Run a kill switch coroutine on the bound loop, or on a thread of its own without one."""
+        try:
+            running = asyncio.get_running_loop()
+        except RuntimeError:
+            running = None
+        if running is not None and (self._loop is None or running is self._loop):
+            task = running.create_task(coro)
+            self._tasks.add(task)
+            task.add_done_callback(self._tasks.discard)
+        elif self._loop is not None and self._loop.is_running():
+            asyncio.run_coroutine_threadsafe(coro, self._loop)
+        else:
+            threading.Thread(target=asyncio.run, args=(coro,), name='risk-kill-switch', daemon=True).start()
+
+    async def kill(self, account_id: str, reason: str = 'manual') -> Dict[str, Any]:
+        """
+        Cancel all orders and flatten all positions of an account now.
+
+        Returns:
+            Dict: Outcome per step ("ok", "not configured" or "failed: <error>")
+        """
+        account_id = str(account_id)
+        outcome: Dict[str, Any] = {"reason": reason, "started_at": datetime.now(timezone.utc).isoformat()}
+        # Orders first, so a resting order can't fill after the flatten
+        for step, action in (('cancel_orders', self.cancel_orders), ('flatten', self.flatten)):
+            outcome[step] = 'not configured' if action is None else await self._attempt(step, action, account_id)
+        outcome["finished_at"] = datetime.now(timezone.utc).isoformat()
+        with self._lock:
+            state = self.account(account_id)
+            state.kill_switch = outcome
+        if any(str(result).startswith('failed') for result in outcome.values()):
+            logger.error(f"❌ Risk: kill switch for account {account_id} incomplete: {outcome}")
+        else:
+            logger.info(f"✅ Risk: kill switch for account {account_id} done "
+                        f"(orders: {outcome['cancel_orders']}, positions: {outcome['flatten']})")
+        if self.alert_callback:
+            try:
+                result = self.alert_callback(state)
+                if inspect.isawaitable(result):
+                    await result
+            except Exception as e:
+                logger.error(f"❌ Risk alert callback failed: {e}")
+        return outcome
+
+    async def _attempt(self, step: str, action: AccountAction, account_id: str) -> str:
+        error = None
+        for attempt in range(1, self.kill_retries + 1):
+            try:
+                result = action(account_id)
+                if inspect.isawaitable(result):
+                    result = await result
+                if isinstance(result, dict) and result.get('error'):
+                    raise RuntimeError(result['error'])
+                return 'ok'
+            except Exception as e:
+                error = f"{type(e).__name__}: {e}"
+                logger.warning(f"⚠️ Risk: {step} for account {account_id} failed "
+                               f"(attempt {attempt}/{self.kill_retries}): {error}")
+            if attempt < self.kill_retries:
+                await asyncio.sleep(self.retry_delay)
+        return f"failed: {error}"
+
+    def reset(self, account_id: str) -> bool:
+        """
+        Lift a halt before the next session (the session P&L is kept, so a further loss trips it again).
+
+        Returns:
+            bool: True if the account was halted
+        """
+        with self._lock:
+            state = self._accounts.get(str(account_id))
+            if state is None or not state.halted:
+                return False
+            state.halted = False
+            state.halted_at = None
+            state.halt_reason = None
+            state.halt_limit = None
+        logger.warning(f"⚠️ Risk: halt of account {account_id} lifted manually")
+        return True
+
+    # ---------------------------
+    # Signal gate
+    # ---------------------------
+    def allows(self, signal: Signal) -> bool:
+        """Whether a signal may be published: exits always, entries only while its account is not halted."""
+        if signal.direction is Direction.FLAT:
+            return True
+        self.roll(signal.timestamp)
+        account_id = self.account_resolver(signal) if self.account_resolver else None
+        return not self.is_halted(account_id)
+
+    def attach(self, engine: Any) -> Callable[[], None]:
+        """
+        Block entry signals of a StrategyEngine while halted.
+
+        Returns:
+            Callable: Detach function
+        """
+        return engine.add_gate(self.allows, name='risk_manager')
+
+    def get_status(self) -> Dict[str, Any]:
+        """Get risk status for dashboards/APIs."""
+        with self._lock:
+            accounts: List[Dict[str, Any]] = [state.to_dict() for state in self._accounts.values()]
+        return {
+            "daily_loss_limit": self.daily_loss_limit,
+            "max_loss_limit": self.max_loss_limit,
+            "trailing": self.trailing,
+            "lock_trailing": self.lock_trailing,
+            "warning_levels": self.warning_levels,
+            "session_symbol": self.session_symbol,
+            "breaches": self.breaches,
+            "warnings": self.warnings,
+            "halted": [a["account_id"] for a in accounts if a["halted"]],
+            "accounts": {a["account_id"]: a for a in accounts},
+        }
@@ -0,0 +1,189 @@
+"""
+Parallel backtest runner.
+
+Runs the same historical data through many parameter sets, one Backtester
+per parameter set, across CPU cores. Optimizers and walk-forward analysis
+build on it:
+
+- The data (bars, ticks) and the strategy factory are shipped to each worker
+  process once, when the pool starts; a task only carries its parameters and
+  the time range to test, so thousands of runs don't re-pickle the history
+- The factory maps a parameter dict to the strategies to test:
+  `factory(params) -> Strategy | [Strategy, ...]`. It must be picklable
+  (a module-level function or class) to run in worker processes; otherwise
+  the runner falls back to running in-process and says so
+- Objectives: any numeric key of BacktestResult.stats() ('net_pnl',
+  'sharpe', 'profit_factor', 'win_rate', ...) or a callable(stats) -> float
+
+Usage:
+    def make_ema(params):
+        return EmaCrossStrategy('ema', EmaCrossParams(**params), symbols=['MNQ'])
+
+    with BacktestRunner(make_ema, bars, BacktestConfig(commission=0.37)) as runner:
+        results = runner.run([{'fast_period': 9, 'slow_period': 21}, {'fast_period': 5, 'slow_period': 34}])
+        best = max(results, key=lambda r: r.score('net_pnl'))
+
+Configuration:
+- BACKTEST_WORKERS: Worker processes, 1 = in-process (default: CPU count)
+"""
+
+import logging
+import math
+import os
+import pickle
+from concurrent.futures import ProcessPoolExecutor
+from dataclasses import dataclass, field
+from datetime import datetime
+from typing import Any, Callable, Dict, Iterable, List, Optional, Sequence, Tuple, Union
+
+from core.bar_aggregator import Bar
+from core.market_events import MarketEvent
+from core.strategy_engine.backtest import BacktestConfig, Backtester, BacktestTrade
+
+logger = logging.getLogger(__name__)
+
+StrategyFactory = Callable[[Dict[str, Any]], Any]
+Objective = Union[str, Callable[[Dict[str, Any]], float]]
+
+
+def score(stats: Dict[str, Any], objective: Objective) -> float:
+    """
+    Objective value of a run's stats (higher is better).
+
+    A missing value scores -inf, except profit_factor, which is None when
+    there were no losing trades: +inf with winners, -inf without trades.
+    """
+    if callable(objective):
+        value = objective(stats)
+    else:
+        value = stats.get(objective)
+        if value is None and objective == 'profit_factor' and stats.get('wins'):
+            return math.inf
+    if value is None or (isinstance(value, float) and math.isnan(value)):
+        return -math.inf
+    return float(value)
+
+
+@dataclass
+class RunResult:
+    """Outcome of one parameter set."""
+    params: Dict[str, Any]
+    stats: Dict[str, Any] = field(default_factory=dict)
+    trades: List[BacktestTrade] = field(default_factory=list)
+    error: Optional[str] = None
+
+    def score(self, objective: Objective) -> float:
+        return -math.inf if self.error else score(self.stats, objective)
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary (without the trade list)."""
+        return {"params": self.params, "stats": self.stats, "error": self.error}
+
+
+# Data shipped to each worker process by the pool initializer
+_worker_state: Dict[str, Any] = {}
+
+
+def _init_worker(factory: StrategyFactory, bars: Sequence[Bar], ticks: Sequence[MarketEvent],
+                 config: BacktestConfig) -> None:
+    _worker_state.update(factory=factory, bars=bars, ticks=ticks, config=config)
+
+
+def _in_window(timestamp: datetime, start: Optional[datetime], end: Optional[datetime]) -> bool:
+    return (start is None or timestamp >= start) and (end is None or timestamp < end)
+
+
+def _run_one(params: Dict[str, Any], window: Tuple[Optional[datetime], Optional[datetime], Optional[datetime]],
+             keep_trades: bool) -> RunResult:
+    """Backtest one parameter set over [start, end), trading from trade_from."""
+    state = _worker_state
+    start, end, trade_from = window
+    try:
+        strategies = state['factory'](dict(params))
+        if not isinstance(strategies, (list, tuple)):
+            strategies = [strategies]
+        bars = [b for b in state['bars'] if _in_window(b.timestamp, start, end)]
+        ticks = [t for t in state['ticks'] if _in_window(t.timestamp, start, end)]
+        result = Backtester(strategies, state['config']).run(bars, ticks, trade_from=trade_from)
+        return RunResult(params=dict(params), stats=result.stats(), trades=result.trades if keep_trades else [])
+    except Exception as e:
+        return RunResult(params=dict(params), error=f"{type(e).__name__}: {e}")
+
+
+class BacktestRunner:
+    """
+    Runs one dataset through many parameter sets, in worker processes when possible.
+    """
+
+    def __init__(self, factory: StrategyFactory, bars: Iterable[Bar], config: Optional[BacktestConfig] = None,
+                 ticks: Iterable[MarketEvent] = (), workers: Optional[int] = None):
+        """
+        Initialize runner (worker processes start on the first run()).
+
+        Args:
+            factory: params -> strategy or list of strategies
+            bars: Historical bars
+            config: Fill model (default: BacktestConfig.from_env())
+            ticks: Optional historical ticks
+            workers: Worker processes, 1 = in-process (env: BACKTEST_WORKERS, default CPU count)
+        """
+        self.factory = factory
+        self.bars = list(bars)
+        self.ticks = list(ticks)
+        self.config = (config or BacktestConfig.from_env()).validate()
+        workers = workers if workers is not None else int(os.getenv('BACKTEST_WORKERS', str(os.cpu_count() or 1)))
+        self.workers = max(1, workers)
+        if self.workers > 1 and not self._picklable(factory):
+            logger.warning(f"⚠️ Strategy factory {factory!r} can't be pickled (lambda/closure?), "
+                           f"running backtests in-process")
+            self.workers = 1
+        self._pool: Optional[ProcessPoolExecutor] = None
+        self.runs = 0
+
+    @staticmethod
+    def _picklable(obj: Any) -> bool:
+        try:
+            pickle.dumps(obj)
+            return True
+        except Exception:
+            return False
+
+    def run(self, param_sets: Iterable[Dict[str, Any]], start: Optional[datetime] = None,
+            end: Optional[datetime] = None, trade_from: Optional[datetime] = None,
+            keep_trades: bool = False) -> List[RunResult]:
+        """
+        Backtest every parameter set, results in input order.
+
+        Args:
+            param_sets: Parameter dicts passed to the factory
+            start / end: Only bars/ticks with start <= timestamp < end (default: all data)
+            trade_from: Bars before this only warm the strategies up
+            keep_trades: Return each run's trade list (costs memory and inter-process traffic)
+
+        Returns:
+            List[RunResult]: One per parameter set; failed runs carry .error and score -inf
+        """
+        param_sets = [dict(p) for p in param_sets]
+        window = (start, end, trade_from)
+        self.runs += len(param_sets)
+        if self.workers == 1 or len(param_sets) <= 1:
+            _init_worker(self.factory, self.bars, self.ticks, self.config)
+            return [_run_one(params, window, keep_trades) for params in param_sets]
+        if self._pool is None:
+            self._pool = ProcessPoolExecutor(max_workers=self.workers, initializer=_init_worker,
+                                             initargs=(self.factory, self.bars, self.ticks, self.config))
+        chunksize = max(1, len(param_sets) // (self.workers * 4))
+        return list(self._pool.map(_run_one, param_sets, [window] * len(param_sets),
+                                   [keep_trades] * len(param_sets), chunksize=chunksize))
+
+    def close(self) -> None:
+        """Shut down worker processes."""
+        if self._pool is not None:
+            self._pool.shutdown()
+            self._pool = None
+
+    def __enter__(self) -> 'BacktestRunner':
+        return self
+
+    def __exit__(self, *exc_info: Any) -> None:
+        self.close()
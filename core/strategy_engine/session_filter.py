@@ -0,0 +1,319 @@
+"""
+Session-window trade filters.
+
+Per-strategy trading hours, evaluated against the futures SessionCalendar
+instead of ad-hoc clock checks inside strategies:
+
+- Windows: entries only inside the given wall-clock windows, e.g.
+  09:30-11:30 and 14:00-15:30 ET (windows may cross midnight)
+- rth_only: entries only during regular trading hours
+- skip_open / skip_close: no entries in the first / last N minutes of the
+  anchor session ('rth' or the whole Globex 'session')
+- flatten_before: N minutes before the anchor close, open positions get a
+  FLAT signal and no new entries are taken until the next session
+- Entries during the maintenance break and weekends are always dropped
+
+FLAT signals always pass. SessionFilter is a StrategyEngine gate; attach()
+also registers a clock (a small pseudo-strategy fed the engine's bars and
+ticks) that emits the flatten signals, so flattening follows bar/tick time
+and behaves the same in backtests. Positions are inferred from the signals
+that got through, as in SignalThrottle; mark_flat() forgets one closed at
+the broker.
+
+Strategies without a rule of their own use the default rule (none = no
+filtering).
+
+Usage:
+    rule = SessionRule.parse_windows('09:30-11:30,14:00-15:30', skip_open=5, flatten_before=10)
+    sessions = SessionFilter()
+    sessions.set_rule('ema_cross', rule)
+    sessions.attach(engine)
+
+Configuration (default rule, SessionRule.from_env):
+- SESSION_FILTER_WINDOWS: Entry windows, e.g. "09:30-11:30,14:00-15:30" (default: none = any time)
+- SESSION_FILTER_RTH_ONLY: Entries only in regular trading hours (default false)
+- SESSION_FILTER_SKIP_OPEN: Minutes after the open without entries (default 0)
+- SESSION_FILTER_SKIP_CLOSE: Minutes before the close without entries (default 0)
+- SESSION_FILTER_FLATTEN_BEFORE: Minutes before the close to flatten, empty = never (default empty)
+- SESSION_FILTER_ANCHOR: Open/close the minutes refer to, rth or session (default rth)
+- SESSION_FILTER_TIMEZONE: Timezone of the windows (default America/New_York)
+"""
+
+import logging
+import os
+import threading
+from dataclasses import dataclass
+from datetime import date, datetime, time, timedelta, timezone
+from typing import Any, Callable, Dict, List, Optional, Tuple
+from zoneinfo import ZoneInfo
+
+from core.bar_aggregator import Bar
+from core.market_events import MarketEvent
+from core.session_calendar import SessionCalendar, SessionState
+from core.strategy_engine.backtest import bar_end, event_price
+from core.strategy_engine.strategy import Direction, Signal, SignalResult, Strategy
+from infrastructure.config import env_bool
+
+logger = logging.getLogger(__name__)
+
+ANCHORS = ('rth', 'session')
+CLOCK_ID = 'session_filter'
+
+
+def _parse_time(text: str) -> time:
+    hours, _, minutes = text.strip().partition(':')
+    return time(int(hours), int(minutes or 0))
+
+
+@dataclass(frozen=True)
+class SessionRule:
+    """When a strategy may enter, and when it must be flat."""
+    windows: Tuple[Tuple[time, time], ...] = ()  # Wall-clock [start, end) in the filter's timezone
+    rth_only: bool = False
+    skip_open: float = 0.0  # Minutes
+    skip_close: float = 0.0  # Minutes
+    flatten_before: Optional[float] = None  # Minutes before the close
+    anchor: str = 'rth'
+
+    def validate(self) -> 'SessionRule':
+        """
+        Raises:
+            ValueError: Inconsistent settings
+        """
+        if self.anchor not in ANCHORS:
+            raise ValueError(f"anchor must be one of {ANCHORS}, got {self.anchor!r}")
+        if self.skip_open < 0 or self.skip_close < 0 or (self.flatten_before is not None and self.flatten_before < 0):
+            raise ValueError("skip_open, skip_close and flatten_before must be >= 0")
+        for start, end in self.windows:
+            if start == end:
+                raise ValueError(f"Empty session window {start:%H:%M}-{end:%H:%M}")
+        return self
+
+    @classmethod
+    def parse_windows(cls, spec: str, **kwargs: Any) -> 'SessionRule':
+        """Rule from a window list like "09:30-11:30,14:00-15:30" plus other fields."""
+        windows = []
+        for part in spec.split(','):
+            if part.strip():
+                start, _, end = part.partition('-')
+                windows.append((_parse_time(start), _parse_time(end)))
+        return cls(windows=tuple(windows), **kwargs).validate()
+
+    @classmethod
+    def from_env(cls, prefix: str = 'SESSION_FILTER_') -> 'SessionRule':
+        """Load a rule from environment variables."""
+        flatten = os.getenv(f"{prefix}FLATTEN_BEFORE", "")
+        return cls.parse_windows(
+            os.getenv(f"{prefix}WINDOWS", ""),
+            rth_only=env_bool(f"{prefix}RTH_ONLY", "false"),
+            skip_open=float(os.getenv(f"{prefix}SKIP_OPEN", "0")),
+            skip_close=float(os.getenv(f"{prefix}SKIP_CLOSE", "0")),
+            flatten_before=float(flatten) if flatten else None,
+            anchor=os.getenv(f"{prefix}ANCHOR", "rth").lower(),
+        )
+
+    @property
+    def filters(self) -> bool:
+        """True if the rule restricts anything beyond the closed market."""
+        return bool(self.windows or self.rth_only or self.skip_open or self.skip_close
+                    or self.flatten_before is not None)
+
+    def in_windows(self, clock: time) -> bool:
+        if not self.windows:
+            return True
+        for start, end in self.windows:
+            inside = start <= clock < end if start < end else (clock >= start or clock < end)
+            if inside:
+                return True
+        return False
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "windows": [f"{start:%H:%M}-{end:%H:%M}" for start, end in self.windows],
+            "rth_only": self.rth_only,
+            "skip_open": self.skip_open,
+            "skip_close": self.skip_close,
+            "flatten_before": self.flatten_before,
+            "anchor": self.anchor,
+        }
+
+
+class _SessionClock(Strategy):
+    """Feeds engine bar/tick time to the filter's flatten check."""
+
+    def __init__(self, session_filter: 'SessionFilter'):
+        super().__init__(CLOCK_ID)
+        self.session_filter = session_filter
+
+    def on_bar(self, bar: Bar) -> SignalResult:
+        return self.session_filter.flatten_due(bar.symbol, bar_end(bar), bar.close)
+
+    def on_tick(self, event: MarketEvent) -> SignalResult:
+        price = event_price(event)
+        if price is None:
+            return None
+        return self.session_filter.flatten_due(event.symbol, event.timestamp, price)
+
+
+class SessionFilter:
+    """
+    Per-strategy session windows for entry signals, with flatten-before-close.
+    """
+
+    def __init__(self, default_rule: Optional[SessionRule] = None, calendar: Optional[SessionCalendar] = None,
+                 tz: Optional[str] = None):
+        """
+        Initialize filter.
+
+        Args:
+            default_rule: Rule for strategies without their own (default: SessionRule.from_env(),
+                unused if it filters nothing)
+            calendar: Session calendar (default: SessionCalendar())
+            tz: Timezone of the rule windows (env: SESSION_FILTER_TIMEZONE, default America/New_York)
+        """
+        rule = (default_rule or SessionRule.from_env()).validate()
+        self.default_rule: Optional[SessionRule] = rule if rule.filters else None
+        self.calendar = calendar or SessionCalendar()
+        self.tz = ZoneInfo(tz or os.getenv('SESSION_FILTER_TIMEZONE', 'America/New_York'))
+        self._rules: Dict[str, SessionRule] = {}
+        self._positions: Dict[Tuple[str, str], int] = {}  # (strategy, symbol) -> +1 long / -1 short
+        self._flattened: Dict[Tuple[str, str], date] = {}  # Flatten sent for this trading date
+        self._lock = threading.Lock()
+        self.passed = 0
+        self.flattens = 0
+        self.blocked: Dict[str, int] = {"closed": 0, "rth": 0, "window": 0, "open": 0, "close": 0}
+
+    # ---------------------------
+    # Rules
+    # ---------------------------
+    def set_rule(self, strategy_id: str, rule: SessionRule) -> SessionRule:
+        rule.validate()
+        with self._lock:
+            self._rules[strategy_id] = rule
+        return rule
+
+    def remove_rule(self, strategy_id: str) -> bool:
+        with self._lock:
+            return self._rules.pop(strategy_id, None) is not None
+
+    def rule_for(self, strategy_id: str) -> Optional[SessionRule]:
+        return self._rules.get(strategy_id, self.default_rule)
+
+    def _anchor_bounds(self, rule: SessionRule, symbol: str,
+                       timestamp: datetime) -> Optional[Tuple[datetime, datetime]]:
+        if rule.anchor == 'rth':
+            return self.calendar.rth_bounds(symbol, timestamp)
+        return self.calendar.session_bounds(symbol, timestamp)
+
+    def block_reason(self, strategy_id: str, symbol: str, timestamp: datetime) -> Optional[str]:
+        """Why an entry at timestamp is not allowed (None if it is)."""
+        rule = self.rule_for(strategy_id)
+        state = self.calendar.state(symbol, timestamp)
+        if state in (SessionState.MAINTENANCE, SessionState.WEEKEND):
+            return "closed"
+        if rule is None:
+            return None
+        if rule.rth_only and state is not SessionState.RTH:
+            return "rth"
+        if timestamp.tzinfo is None:
+            timestamp = timestamp.replace(tzinfo=timezone.utc)
+        if not rule.in_windows(timestamp.astimezone(self.tz).time()):
+            return "window"
+        bounds = self._anchor_bounds(rule, symbol, timestamp)
+        if bounds is not None:
+            open_at, close_at = bounds
+            if rule.skip_open and open_at <= timestamp < open_at + timedelta(minutes=rule.skip_open):
+                return "open"
+            closing = max(rule.skip_close, rule.flatten_before or 0.0)
+            if closing and close_at - timedelta(minutes=closing) <= timestamp < close_at:
+                return "close"
+        return None
+
+    # ---------------------------
+    # Gate
+    # ---------------------------
+    def allows(self, signal: Signal) -> bool:
+        """Whether a signal may be published (StrategyEngine gate)."""
+        if signal.direction is Direction.FLAT:
+            return True
+        reason = self.block_reason(signal.strategy_id, signal.symbol, signal.timestamp)
+        if reason is None:
+            return True
+        with self._lock:
+            self.blocked[reason] += 1
+        logger.debug(f"Session filter: dropped {signal.strategy_id}/{signal.symbol} {signal.direction.value} "
+                     f"at {signal.timestamp:%Y-%m-%d %H:%M} ({reason})")
+        return False
+
+    def record(self, signal: Signal) -> None:
+        """Track a published signal's position."""
+        key = (signal.strategy_id, signal.symbol.upper())
+        with self._lock:
+            if signal.direction is Direction.FLAT:
+                self._positions.pop(key, None)
+            else:
+                self.passed += 1
+                self._positions[key] = signal.direction.sign
+
+    def mark_flat(self, strategy_id: str, symbol: str) -> None:
+        with self._lock:
+            self._positions.pop((strategy_id, symbol.upper()), None)
+
+    def reset(self) -> None:
+        with self._lock:
+            self._positions.clear()
+            self._flattened.clear()
+
+    def flatten_due(self, symbol: str, timestamp: datetime, price: float) -> List[Signal]:
+        """FLAT signals for positions in symbol that reached their flatten time (once per session)."""
+        symbol = symbol.upper()
+        with self._lock:
+            open_keys = [key for key in self._positions if key[1] == symbol]
+        signals = []
+        for key in open_keys:
+            rule = self.rule_for(key[0])
+            if rule is None or rule.flatten_before is None:
+                continue
+            bounds = self._anchor_bounds(rule, symbol, timestamp)
+            if bounds is None or not bounds[1] - timedelta(minutes=rule.flatten_before) <= timestamp < bounds[1]:
+                continue
+            day = self.calendar.trading_date(symbol, timestamp)
+            with self._lock:
+                if self._flattened.get(key) == day:
+                    continue
+                self._flattened[key] = day
+                self.flattens += 1
+            logger.info(f"🔓 Session filter: flattening {key[0]}/{symbol}, "
+                        f"{rule.flatten_before:g} min before the {rule.anchor} close")
+            signals.append(Signal(strategy_id=key[0], symbol=symbol, direction=Direction.FLAT, price=price,
+                                  timestamp=timestamp, reason=f"Flatten {rule.flatten_before:g} min before close"))
+        return signals
+
+    def attach(self, engine: Any) -> Callable[[], None]:
+        """
+        Gate a StrategyEngine's signals, track its positions and flatten them on its bar/tick clock.
+
+        Returns:
+            Callable: Detach function
+        """
+        remove_gate = engine.add_gate(self.allows, name='session_filter')
+        unsubscribe = engine.on_signal(self.record)
+        engine.add_strategy(_SessionClock(self))
+
+        def detach() -> None:
+            remove_gate()
+            unsubscribe()
+            engine.remove_strategy(CLOCK_ID)
+        return detach
+
+    def get_stats(self) -> Dict[str, Any]:
+        with self._lock:
+            return {
+                "passed": self.passed,
+                "flattens": self.flattens,
+                "blocked": dict(self.blocked),
+                "open": {f"{strategy}/{symbol}": position for (strategy, symbol), position in self._positions.items()},
+                "timezone": str(self.tz),
+                "default_rule": self.default_rule.to_dict() if self.default_rule else None,
+                "rules": {strategy_id: rule.to_dict() for strategy_id, rule in self._rules.items()},
+            }
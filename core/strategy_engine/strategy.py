@@ -0,0 +1,208 @@
+"""
+Strategy interface and signal type for strategy_engine.
+
+A Strategy is event driven: StrategyEngine calls on_bar() with every
+completed bar (and on_tick() with market events) for the symbols and
+timeframes the strategy asked for. Handlers return None, a Signal, or a list
+of Signals; the strategy never places orders itself, so the same code runs
+live and offline.
+
+Strategies keep their own per-symbol state (indicators, pending crosses,
+...), which is why one instance can trade many symbols. Bars and indicators
+of other timeframes come from the engine's shared MarketContext
+(self.context) instead.
+
+Parameters can change while a strategy runs: params_with() validates
+changes without applying them and set_params() switches over. The defaults
+handle a params dataclass (or dict) in self.params; strategies with
+per-symbol indicators adapt them on each symbol's next bar rather than
+starting over.
+"""
+
+import logging
+from abc import ABC, abstractmethod
+from dataclasses import asdict, dataclass, fields, is_dataclass, replace
+from datetime import datetime
+from enum import Enum
+from typing import Any, Dict, Iterable, List, Optional, Sequence, Tuple, Union
+
+from core.bar_aggregator import Bar
+from core.market_events import MarketEvent
+
+logger = logging.getLogger(__name__)
+
+
+class Direction(str, Enum):
+    """What a signal asks for."""
+    LONG = "long"
+    SHORT = "short"
+    FLAT = "flat"  # Exit any position in the symbol
+
+    @property
+    def sign(self) -> int:
+        return 1 if self is Direction.LONG else -1 if self is Direction.SHORT else 0
+
+
+@dataclass(frozen=True)
+class Signal:
+    """A strategy's trade intent for one symbol."""
+    strategy_id: str
+    symbol: str
+    direction: Direction
+    price: float  # Reference price when generated (bar close or last trade)
+    timestamp: datetime
+    confidence: float = 1.0  # 0.0-1.0
+    stop: Optional[float] = None
+    target: Optional[float] = None
+    timeframe: Optional[str] = None
+    reason: str = ""
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        return {
+            "strategy_id": self.strategy_id,
+            "symbol": self.symbol,
+            "direction": self.direction.value,
+            "price": self.price,
+            "timestamp": self.timestamp.isoformat(),
+            "confidence": self.confidence,
+            "stop": self.stop,
+            "target": self.target,
+            "timeframe": self.timeframe,
+            "reason": self.reason,
+        }
+
+
+SignalResult = Union[None, Signal, List[Signal]]
+
+
+def atr_exits(price: float, sign: int, atr: Optional[float], stop_atr: float,
+              target_atr: float) -> Tuple[Optional[float], Optional[float]]:
+    """
+    Stop and target at ATR multiples from price.
+
+    Args:
+        price: Entry reference price
+        sign: +1 long, -1 short
+        atr: Current ATR (None/0 = no exits)
+        stop_atr: Stop distance in ATRs, 0 = no stop
+        target_atr: Target distance in ATRs, 0 = no target
+
+    Returns:
+        (stop, target), None where not set
+    """
+    if not atr:
+        return None, None
+    stop = price - sign * stop_atr * atr if stop_atr > 0 else None
+    target = price + sign * target_atr * atr if target_atr > 0 else None
+    return stop, target
+
+
+def params_dict(params: Any) -> Dict[str, Any]:
+    """Parameters dataclass or dict as a plain dict."""
+    if isinstance(params, dict):
+        return dict(params)
+    if params is not None and is_dataclass(params):
+        return asdict(params)
+    return {}
+
+
+class Strategy(ABC):
+    """
+    Base class for strategies run by StrategyEngine.
+
+    Subclasses implement on_bar(); on_bars(), on_tick(), reset() and get_state() are optional.
+    """
+
+    def __init__(self, strategy_id: str, symbols: Optional[Iterable[str]] = None,
+                 timeframes: Optional[Iterable[str]] = None):
+        """
+        Initialize strategy.
+
+        Args:
+            strategy_id: Unique id, carried by every Signal
+            symbols: Symbols to trade (default: every symbol the engine sees)
+            timeframes: Bar timeframes to receive, e.g. ['5m'] (default: all)
+        """
+        self.strategy_id = strategy_id
+        self.symbols = frozenset(s.strip().upper() for s in symbols if s.strip()) if symbols else None
+        self.timeframes = frozenset(tf.strip().lower() for tf in timeframes) if timeframes else None
+        self.context: Any = None  # MarketContext, set by StrategyEngine.add_strategy()
+
+    def wants(self, symbol: str, timeframe: Optional[str] = None) -> bool:
+        """True if events for this symbol (and bar timeframe) should reach the strategy."""
+        if self.symbols is not None and symbol.upper() not in self.symbols:
+            return False
+        return timeframe is None or self.timeframes is None or timeframe in self.timeframes
+
+    @abstractmethod
+    def on_bar(self, bar: Bar) -> SignalResult:
+        """Handle a completed bar."""
+
+    def on_bars(self, bars: Sequence[Bar]) -> SignalResult:
+        """Handle a batch of completed bars in order (backfill, replay); calls on_bar() for each by default."""
+        signals: List[Signal] = []
+        for bar in bars:
+            result = self.on_bar(bar)
+            if isinstance(result, Signal):
+                signals.append(result)
+            elif result:
+                signals.extend(result)
+        return signals
+
+    def on_tick(self, event: MarketEvent) -> SignalResult:
+        """Handle a market event (Quote, Trade, ...). Bars-only strategies ignore these."""
+        return None
+
+    def reset(self, symbol: Optional[str] = None) -> None:
+        """Forget per-symbol state (all symbols if None)."""
+
+    def get_state(self, symbol: str) -> Dict[str, Any]:
+        """Per-symbol state for status pages and debugging."""
+        return {}
+
+    def bind_context(self, context: Any) -> None:
+        """Give the strategy its engine's MarketContext (higher-timeframe bars and indicators)."""
+        self.context = context
+
+    def get_params(self) -> Dict[str, Any]:
+        """Current parameters as a dict (empty if the strategy has none)."""
+        return params_dict(getattr(self, 'params', None))
+
+    def params_with(self, changes: Dict[str, Any], base: Any = None) -> Any:
+        """
+        Validated parameters with changes applied, without switching to them.
+
+        Args:
+            changes: Parameter name -> new value
+            base: Parameters to change (default: the current ones)
+
+        Raises:
+            TypeError: The strategy has no params dataclass or dict
+            ValueError: Unknown parameter names or invalid values
+        """
+        base = base if base is not None else getattr(self, 'params', None)
+        if isinstance(base, dict):
+            return {**base, **changes}
+        if base is None or not is_dataclass(base):
+            raise TypeError(f"Strategy {self.strategy_id} has no parameters to update")
+        unknown = set(changes) - {f.name for f in fields(base)}
+        if unknown:
+            raise ValueError(f"Unknown parameters for strategy {self.strategy_id}: {', '.join(sorted(unknown))}")
+        params = replace(base, **changes)
+        validate = getattr(params, 'validate', None)
+        if callable(validate):
+            validate()
+        return params
+
+    def set_params(self, params: Any) -> None:
+        """Switch to parameters from params_with() (StrategyEngine calls this between bars)."""
+        self.params = params
+
+    def signal(self, symbol: str, direction: Direction, price: float, timestamp: datetime, **kwargs: Any) -> Signal:
+        """Build a Signal stamped with this strategy's id."""
+        return Signal(strategy_id=self.strategy_id, symbol=symbol, direction=direction, price=price,
+                      timestamp=timestamp, **kwargs)
+
+    def __repr__(self) -> str:
+        return f"{type(self).__name__}({self.strategy_id!r})"
@@ -0,0 +1,219 @@
+"""
+Signal cooldown and deduplication.
+
+Strategies evaluate every bar, and in choppy markets they re-fire the same
+entry bar after bar. SignalThrottle is a StrategyEngine gate that drops the
+noise before it reaches the signal bus (and the order router behind it):
+
+- Cooldown: at least min_interval seconds between entries of the same
+  strategy and symbol (measured on signal timestamps, so backtests throttle
+  like live trading)
+- Deduplication: an entry in the direction the strategy is already
+  positioned in is dropped; an opposite entry (reversal) passes
+- Concurrency: at most max_concurrent open entries overall and
+  max_per_strategy per strategy; reversals and exits don't count as new
+
+FLAT signals always pass and mark the strategy/symbol flat. Positions are
+inferred from the signals that got through; when a stop or target closes a
+position at the broker, call mark_flat(), or pass a position_provider
+(e.g. PositionTracker-backed) to use real positions for deduplication.
+
+Rules are per strategy and/or symbol; the most specific one applies:
+(strategy, symbol) > (strategy, any) > (any, symbol) > defaults.
+
+Usage:
+    throttle = SignalThrottle(min_interval=300, max_concurrent=2)
+    throttle.set_rule(strategy_id='ema_cross', symbol='MNQ', min_interval=900)
+    throttle.attach(engine)
+
+Configuration:
+- SIGNAL_MIN_INTERVAL: Default seconds between entries per strategy/symbol, 0 = off (default 0)
+- SIGNAL_DEDUPE: Drop same-direction entries while in position (default true)
+- SIGNAL_MAX_CONCURRENT: Open entries allowed at once, 0 = unlimited (default 0)
+- SIGNAL_MAX_PER_STRATEGY: Open entries per strategy, 0 = unlimited (default 0)
+"""
+
+import logging
+import os
+import threading
+from dataclasses import dataclass
+from datetime import datetime
+from typing import Any, Callable, Dict, Optional, Tuple
+
+from core.strategy_engine.strategy import Direction, Signal
+from infrastructure.config import env_bool
+
+logger = logging.getLogger(__name__)
+
+_SIGN = {Direction.LONG: 1, Direction.SHORT: -1, Direction.FLAT: 0}
+
+
+@dataclass(frozen=True)
+class ThrottleRule:
+    """Throttling for a strategy and/or symbol."""
+    min_interval: float = 0.0  # Seconds between entries
+    dedupe: bool = True
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {"min_interval": self.min_interval, "dedupe": self.dedupe}
+
+
+class SignalThrottle:
+    """
+    Cooldown, duplicate and concurrency gate for entry signals.
+    """
+
+    def __init__(self, min_interval: Optional[float] = None, dedupe: Optional[bool] = None,
+                 max_concurrent: Optional[int] = None, max_per_strategy: Optional[int] = None,
+                 position_provider: Optional[Callable[[str, str], int]] = None):
+        """
+        Initialize throttle.
+
+        Args:
+            min_interval: Default seconds between entries per strategy/symbol (env: SIGNAL_MIN_INTERVAL)
+            dedupe: Default for dropping same-direction entries in position (env: SIGNAL_DEDUPE)
+            max_concurrent: Open entries at once, 0 = unlimited (env: SIGNAL_MAX_CONCURRENT)
+            max_per_strategy: Open entries per strategy, 0 = unlimited (env: SIGNAL_MAX_PER_STRATEGY)
+            position_provider: (strategy_id, symbol) -> signed position, used instead of the inferred one
+                for deduplication
+        """
+        self.default_rule = ThrottleRule(
+            min_interval=min_interval if min_interval is not None else float(os.getenv('SIGNAL_MIN_INTERVAL', '0')),
+            dedupe=dedupe if dedupe is not None else env_bool('SIGNAL_DEDUPE', 'true'),
+        )
+        self.max_concurrent = (max_concurrent if max_concurrent is not None
+                               else int(os.getenv('SIGNAL_MAX_CONCURRENT', '0')))
+        self.max_per_strategy = (max_per_strategy if max_per_strategy is not None
+                                 else int(os.getenv('SIGNAL_MAX_PER_STRATEGY', '0')))
+        self.position_provider = position_provider
+        self._rules: Dict[Tuple[Optional[str], Optional[str]], ThrottleRule] = {}
+        self._last_entry: Dict[Tuple[str, str], datetime] = {}
+        self._positions: Dict[Tuple[str, str], int] = {}  # (strategy, symbol) -> +1 long / -1 short
+        self._lock = threading.Lock()
+        self.passed = 0
+        self.blocked: Dict[str, int] = {"cooldown": 0, "duplicate": 0, "max_concurrent": 0}
+
+    # ---------------------------
+    # Rules
+    # ---------------------------
+    def set_rule(self, strategy_id: Optional[str] = None, symbol: Optional[str] = None,
+                 min_interval: Optional[float] = None, dedupe: Optional[bool] = None) -> ThrottleRule:
+        """Set the rule for a strategy and/or symbol (unset fields fall back to the defaults)."""
+        rule = ThrottleRule(
+            min_interval=min_interval if min_interval is not None else self.default_rule.min_interval,
+            dedupe=dedupe if dedupe is not None else self.default_rule.dedupe,
+        )
+        with self._lock:
+            self._rules[(strategy_id, symbol.upper() if symbol else None)] = rule
+        return rule
+
+    def remove_rule(self, strategy_id: Optional[str] = None, symbol: Optional[str] = None) -> bool:
+        with self._lock:
+            return self._rules.pop((strategy_id, symbol.upper() if symbol else None), None) is not None
+
+    def rule_for(self, strategy_id: str, symbol: str) -> ThrottleRule:
+        """Most specific rule for a strategy and symbol."""
+        symbol = symbol.upper()
+        for key in ((strategy_id, symbol), (strategy_id, None), (None, symbol)):
+            rule = self._rules.get(key)
+            if rule is not None:
+                return rule
+        return self.default_rule
+
+    # ---------------------------
+    # Gate
+    # ---------------------------
+    def _position(self, key: Tuple[str, str]) -> int:
+        if self.position_provider is not None:
+            quantity = self.position_provider(*key)
+            return (quantity > 0) - (quantity < 0)
+        return self._positions.get(key, 0)
+
+    def allows(self, signal: Signal) -> bool:
+        """Whether a signal may be published (StrategyEngine gate; doesn't record it)."""
+        if signal.direction is Direction.FLAT:
+            return True
+        key = (signal.strategy_id, signal.symbol.upper())
+        rule = self.rule_for(*key)
+        reason = None
+        with self._lock:
+            position = self._position(key)
+            last = self._last_entry.get(key)
+            if rule.dedupe and position == _SIGN[signal.direction]:
+                reason = "duplicate"
+            elif rule.min_interval > 0 and last is not None and \
+                    (signal.timestamp - last).total_seconds() < rule.min_interval:
+                reason = "cooldown"
+            elif position == 0 and not self._has_capacity(signal.strategy_id):
+                reason = "max_concurrent"
+            if reason is not None:
+                self.blocked[reason] += 1
+        if reason is not None:
+            logger.debug(f"Throttle: dropped {signal.strategy_id}/{signal.symbol} {signal.direction.value} "
+                         f"({reason})")
+            return False
+        return True
+
+    def _has_capacity(self, strategy_id: str) -> bool:
+        """Room for one more open entry (caller holds the lock)."""
+        if self.max_concurrent and len(self._positions) >= self.max_concurrent:
+            return False
+        if self.max_per_strategy:
+            open_for_strategy = sum(1 for strategy, _ in self._positions if strategy == strategy_id)
+            if open_for_strategy >= self.max_per_strategy:
+                return False
+        return True
+
+    def record(self, signal: Signal) -> None:
+        """Track a published signal (entry time and inferred position)."""
+        key = (signal.strategy_id, signal.symbol.upper())
+        with self._lock:
+            if signal.direction is Direction.FLAT:
+                self._positions.pop(key, None)
+                return
+            self.passed += 1
+            self._last_entry[key] = signal.timestamp
+            self._positions[key] = _SIGN[signal.direction]
+
+    def mark_flat(self, strategy_id: str, symbol: str) -> None:
+        """Position closed outside the strategy's signals (stop, target, manual close)."""
+        with self._lock:
+            self._positions.pop((strategy_id, symbol.upper()), None)
+
+    def reset(self) -> None:
+        """Forget entry times and positions."""
+        with self._lock:
+            self._last_entry.clear()
+            self._positions.clear()
+
+    def attach(self, engine: Any) -> Callable[[], None]:
+        """
+        Gate a StrategyEngine's signals and track the ones it publishes.
+
+        Recording from the bus rather than the gate means a signal dropped by a later gate
+        (e.g. RiskManager) doesn't start a cooldown.
+
+        Returns:
+            Callable: Detach function
+        """
+        remove_gate = engine.add_gate(self.allows, name='signal_throttle')
+        unsubscribe = engine.on_signal(self.record)
+
+        def detach() -> None:
+            remove_gate()
+            unsubscribe()
+        return detach
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Counters, rules and open entries."""
+        with self._lock:
+            return {
+                "passed": self.passed,
+                "blocked": dict(self.blocked),
+                "open": {f"{strategy}/{symbol}": position for (strategy, symbol), position in self._positions.items()},
+                "max_concurrent": self.max_concurrent,
+                "max_per_strategy": self.max_per_strategy,
+                "default_rule": self.default_rule.to_dict(),
+                "rules": {f"{strategy or '*'}/{symbol or '*'}": rule.to_dict()
+                          for (strategy, symbol), rule in self._rules.items()},
+            }
@@ -0,0 +1,856 @@
+"""
+Tick-to-order trading pipeline in one object.
+
+TradingCore wires the pieces of the automated trading path together so a
+market event goes from the websocket callback to an order submission in a
+single synchronous pass, without hopping through the event loop or any
+queue on the way:
+
+    market event -> TickValidator -> BarAggregator -> StrategyEngine
+                 -> gates (RiskManager, filters, PortfolioCoordinator)
+                 -> OrderExecutor -> submit order
+
+- Events are processed on the thread that delivers them (the market hub
+  callback); bars close inside add_quote() and strategies run inline (unless
+  the engine was built with symbol workers)
+- OrderExecutor turns each published signal into orders: entries sized by
+  the strategy's allocation, reversals close first, FLAT closes what the
+  strategy holds; stops/targets become bracket ticks. A synchronous submit
+  function runs inline; a coroutine function (the bot's REST call) is
+  scheduled on the bound event loop, the only hop on the path
+- Bracket legs can be moved after entry: modify_bracket() finds the stop and
+  target orders of a filled entry (find_legs, e.g. bot.find_bracket_legs) and
+  reprices them in ticks from the fill (modify, e.g. bot.modify_order);
+  entry_order() returns the entry order ID of a strategy's position
+- Laddered entries and partial exits: scale_in() places limit entries at
+  tick offsets, scale_out() places limit targets for parts of the position;
+  fills reported to on_fill() (or a size from set_position()) trim targets
+  beyond the remaining size, farthest first, and resize the stop
+- Python stays in charge around the hot path: configure strategies
+  (update_params), pause/resume new entries, flatten on demand, halt via
+  the RiskManager, and read get_stats() for monitoring, including the
+  event-to-submission latency
+
+Usage:
+    core = TradingCore(submit=bot.place_market_order, account_id=account_id,
+                       strategies=[EmaCrossStrategy(), MacdMomentumStrategy()],
+                       risk=risk, portfolio=portfolio, tick_validator=bot.tick_validator)
+    core.bind_loop()                         # From the bot's event loop
+    bot.add_market_event_listener(core.on_event)
+    bot.add_order_fill_listener(core.executor.on_fill)  # Ladder fills (scale_in/scale_out)
+    ...
+    core.pause()                             # Manual override: no new entries
+    core.flatten()
+
+Configuration:
+- TRADING_CORE_QUANTITY: Contracts per entry without a portfolio allocation (default 1)
+- TRADING_CORE_BRACKETS: Send signal stops/targets as bracket orders (default true)
+- TRADING_CORE_LATENCY_WINDOW: Event-to-submission latencies kept for percentiles (default 1000)
+"""
+
+import asyncio
+import inspect
+import logging
+import os
+import threading
+import time
+from collections import deque
+from datetime import datetime, timezone
+from typing import Any, Callable, Deque, Dict, Iterable, List, Optional, Tuple
+
+from core.bar_aggregator import BarAggregator
+from core.market_events import MarketEvent, Quote, Trade
+from core.strategy_engine.backtest import TICK_SIZES, event_price
+from core.strategy_engine.engine import StrategyEngine
+from core.strategy_engine.monte_carlo import percentile
+from core.strategy_engine.strategy import Direction, Signal
+from core.volume_profile import normalize_symbol
+from infrastructure.config import env_bool
+
+logger = logging.getLogger(__name__)
+
+# place_market_order(symbol, side, quantity, account_id=..., stop_loss_ticks=..., take_profit_ticks=...,
+#                    strategy_name=...) -> order response dict (or awaitable of one). An error response with
+#                    status_unknown=True means the broker may have accepted the order (e.g. a malformed success)
+OrderSubmitter = Callable[..., Any]
+# find_bracket_legs(order_id, account_id=...) -> {'parent': order, 'symbol', 'stop': order, 'target': order}
+# modify_order(order_id, new_price=..., account_id=..., order_type=...) -> response dict (or awaitables)
+LegFinder = Callable[..., Any]
+OrderModifier = Callable[..., Any]
+# cancel_order(order_id, account_id=...) -> response dict (or awaitable)
+OrderCanceller = Callable[..., Any]
+
+
+class OrderExecutor:
+    """
+    Turns published signals into orders, tracking each strategy's position per symbol.
+    """
+
+    def __init__(self, submit: OrderSubmitter, account_id: Optional[str] = None,
+                 quantity_for: Optional[Callable[[str], int]] = None, brackets: Optional[bool] = None,
+                 tick_sizes: Optional[Dict[str, float]] = None, find_legs: Optional[LegFinder] = None,
+                 modify: Optional[OrderModifier] = None, cancel: Optional[OrderCanceller] = None):
+        """
+        Initialize executor.
+
+        Args:
+            submit: Order submission function (sync or coroutine, e.g. TradingBot.place_market_order)
+            account_id: Account to trade (default: whatever submit uses)
+            quantity_for: strategy_id -> contracts per entry (default: TRADING_CORE_QUANTITY)
+            brackets: Attach signal stops/targets as bracket ticks (env: TRADING_CORE_BRACKETS)
+            tick_sizes: Tick size overrides by root symbol
+            find_legs: Bracket leg lookup for modify_bracket() (e.g. TradingBot.find_bracket_legs)
+            modify: Order modification for modify_bracket() and rebalance() (e.g. TradingBot.modify_order)
+            cancel: Order cancellation for scale_out() and rebalance() (e.g. TradingBot.cancel_order)
+        """
+        self.submit = submit
+        self.find_legs = find_legs
+        self.modify = modify
+        self.cancel = cancel
+        self.account_id = account_id
+        default_quantity = int(os.getenv('TRADING_CORE_QUANTITY', '1'))
+        self.quantity_for = quantity_for or (lambda strategy_id: default_quantity)
+        self.brackets = brackets if brackets is not None else env_bool('TRADING_CORE_BRACKETS', 'true')
+        self.tick_sizes = dict(tick_sizes or {})
+        self._positions: Dict[Tuple[str, str], int] = {}  # (strategy, symbol) -> signed contracts
+        self._entry_orders: Dict[Tuple[str, str], str] = {}  # (strategy, symbol) -> entry order ID
+        self._ladders: Dict[Tuple[str, str], Dict[str, Any]] = {}  # (strategy, symbol) -> scale in/out orders
+        self._ladder_orders: Dict[str, Tuple[Tuple[str, str], str]] = {}  # order ID -> (key, entries/targets)
+        self._ladder_fills: Dict[str, Tuple[int, float]] = {}  # order ID -> (contracts filled, their cost)
+        self._lock = threading.Lock()
+        self._loop: Optional[asyncio.AbstractEventLoop] = None
+        self._tasks: set = set()
+        self.orders_submitted = 0
+        self.orders_failed = 0
+        self.orders_unknown = 0
+        self.signals_ignored = 0
+        self.brackets_modified = 0
+        self.ladders_rebalanced = 0
+        self.last_error: Optional[str] = None
+
+    def bind_loop(self, loop: Optional[asyncio.AbstractEventLoop] = None) -> None:
+        """Run coroutine submissions on this loop (default: the running loop)."""
+        self._loop = loop or asyncio.get_running_loop()
+
+    def tick_size(self, symbol: str) -> float:
+        root = normalize_symbol(symbol)
+        return self.tick_sizes.get(root, TICK_SIZES.get(root, 0.25))
+
+    def position(self, strategy_id: str, symbol: str) -> int:
+        with self._lock:
+            return self._positions.get((strategy_id, symbol.upper()), 0)
+
+    def entry_order(self, strategy_id: str, symbol: str) -> Optional[str]:
+        """Order ID of the entry behind a strategy's open position (None if flat or unknown)."""
+        with self._lock:
+            return self._entry_orders.get((strategy_id, symbol.upper()))
+
+    def positions(self) -> Dict[str, Dict[str, int]]:
+        """{symbol: {strategy_id: signed contracts}} of open positions."""
+        with self._lock:
+            result: Dict[str, Dict[str, int]] = {}
+            for (strategy_id, symbol), qty in self._positions.items():
+                result.setdefault(symbol, {})[strategy_id] = qty
+            return result
+
+    def _bracket_ticks(self, signal: Signal) -> Dict[str, Optional[int]]:
+        if not self.brackets:
+            return {"stop_loss_ticks": None, "take_profit_ticks": None}
+        tick = self.tick_size(signal.symbol)
+
+        def ticks(level: Optional[float]) -> Optional[int]:
+            return max(1, round(abs(signal.price - level) / tick)) if level is not None else None
+        return {"stop_loss_ticks": ticks(signal.stop), "take_profit_ticks": ticks(signal.target)}
+
+    def orders_for(self, signal: Signal) -> List[Dict[str, Any]]:
+        """
+        Orders that move the strategy from its tracked position to what the signal asks for.
+
+        Returns:
+            List of submit() keyword arguments (close first, then the entry), empty if already there
+        """
+        current = self.position(signal.strategy_id, signal.symbol)
+        target = signal.direction.sign * self.quantity_for(signal.strategy_id)
+        if signal.direction is not Direction.FLAT and current == target:
+            return []  # Already in the position
+        base = {"symbol": signal.symbol, "account_id": self.account_id, "strategy_name": signal.strategy_id}
+        orders: List[Dict[str, Any]] = []
+        if current and (signal.direction is Direction.FLAT or current * target <= 0):
+            orders.append(dict(base, side="SELL" if current > 0 else "BUY", quantity=abs(current),
+                               stop_loss_ticks=None, take_profit_ticks=None))
+            current = 0
+        if target != current:
+            delta = target - current
+            orders.append(dict(base, side="BUY" if delta > 0 else "SELL", quantity=abs(delta),
+                               **self._bracket_ticks(signal)))
+        return orders
+
+    def on_signal(self, signal: Signal) -> None:
+        """Signal bus subscriber: submit the orders a signal needs."""
+        orders = self.orders_for(signal)
+        if not orders:
+            with self._lock:
+                self.signals_ignored += 1
+            return
+        key = (signal.strategy_id, signal.symbol.upper())
+        target = signal.direction.sign * self.quantity_for(signal.strategy_id)
+        with self._lock:
+            previous = self._positions.get(key, 0)
+            # Tracked optimistically, so the next signal sizes from here; undone if submission fails
+            if target:
+                self._positions[key] = target
+            else:
+                self._positions.pop(key, None)
+        if inspect.iscoroutinefunction(self.submit):
+            self._schedule(self._submit_async(signal, orders, key, previous, target))
+        else:
+            self._finish(signal, key, previous, target, *self._submit_sync(orders))
+
+    def _submit_sync(self, orders: List[Dict[str, Any]]) -> Tuple[Optional[str], Any]:
+        result = None
+        for order in orders:
+            try:
+                result = self.submit(**order)
+                if isinstance(result, dict) and result.get('error'):
+                    if result.get('status_unknown'):
+                        return str(result['error']), result
+                    raise RuntimeError(result['error'])
+            except Exception as e:
+                return f"{type(e).__name__}: {e}", None
+        return None, result
+
+    async def _submit_async(self, signal: Signal, orders: List[Dict[str, Any]], key: Tuple[str, str],
+                            previous: int, target: int) -> None:
+        error, result = None, None
+        for order in orders:
+            try:
+                result = await self.submit(**order)
+                if isinstance(result, dict) and result.get('error'):
+                    if result.get('status_unknown'):
+                        error = str(result['error'])
+                        break
+                    raise RuntimeError(result['error'])
+            except Exception as e:
+                error, result = f"{type(e).__name__}: {e}", None
+                break
+        self._finish(signal, key, previous, target, error, result)
+
+    def _finish(self, signal: Signal, key: Tuple[str, str], previous: int, target: int,
+                error: Optional[str], result: Any = None) -> None:
+        if error is None:
+            with self._lock:
+                self.orders_submitted += 1
+                # The last order sent is the entry (after any close); its ID finds the bracket legs later
+                order_id = result.get('orderId') if isinstance(result, dict) else None
+                if target and order_id is not None:
+                    self._entry_orders[key] = str(order_id)
+                elif not target:
+                    self._entry_orders.pop(key, None)
+            logger.info(f"✅ Orders sent for {signal.strategy_id} {signal.symbol} {signal.direction.value}")
+            return
+        if isinstance(result, dict) and result.get('status_unknown'):
+            # The broker may hold the order: keep the tracked position (reconciliation settles it), don't undo it
+            with self._lock:
+                self.orders_unknown += 1
+                self.last_error = error
+            logger.warning(f"⚠️  Order for {signal.strategy_id} {signal.symbol} {signal.direction.value} status "
+                           f"unknown, keeping the position pending reconciliation: {error}")
+            return
+        with self._lock:
+            self.orders_failed += 1
+            self.last_error = error
+            if self._positions.get(key, 0) == target:  # Not moved on by a later signal
+                if previous:
+                    self._positions[key] = previous
+                else:
+                    self._positions.pop(key, None)
+        logger.error(f"❌ Order for {signal.strategy_id} {signal.symbol} {signal.direction.value} failed: {error}")
+
+    def _schedule(self, coro: Any) -> None:
+        """Run a submission coroutine on the bound loop, or on a thread of its own without one."""
+        try:
+            running = asyncio.get_running_loop()
+        except RuntimeError:
+            running = None
+        if running is not None and (self._loop is None or running is self._loop):
+            task = running.create_task(coro)
+            self._tasks.add(task)
+            task.add_done_callback(self._tasks.discard)
+        elif self._loop is not None and self._loop.is_running():
+            asyncio.run_coroutine_threadsafe(coro, self._loop)
+        else:
+            threading.Thread(target=asyncio.run, args=(coro,), name='order-executor', daemon=True).start()
+
+    # ---------------------------
+    # Bracket legs
+    # ---------------------------
+    @staticmethod
+    async def _call(function: Callable[..., Any], *args: Any, **kwargs: Any) -> Any:
+        result = function(*args, **kwargs)
+        return await result if inspect.isawaitable(result) else result
+
+    async def modify_bracket(self, order_id: str, stop_ticks: Optional[int] = None,
+                             target_ticks: Optional[int] = None) -> Dict[str, Any]:
+        """
+        Move the stop and/or target leg of a filled entry's bracket, in ticks from its fill price.
+
+        Args:
+            order_id: Entry (parent) order ID, e.g. entry_order(strategy_id, symbol)
+            stop_ticks: New stop distance from the fill (None keeps the stop)
+            target_ticks: New target distance from the fill (None keeps the target)
+
+        Returns:
+            Dict: success with the new stop/target order IDs and prices, or error (legs not
+                modified yet are left alone; one already modified stays modified)
+        """
+        requested = {leg: ticks for leg, ticks in (('stop', stop_ticks), ('target', target_ticks))
+                     if ticks is not None}
+        if not requested:
+            return {"error": "Nothing to modify: pass stop_ticks and/or target_ticks"}
+        if any(int(ticks) < 1 for ticks in requested.values()):
+            return {"error": "Bracket distances must be at least 1 tick"}
+        if self.find_legs is None or self.modify is None:
+            return {"error": "Bracket modification needs find_legs and modify"}
+        try:
+            legs = await self._call(self.find_legs, str(order_id), account_id=self.account_id)
+        except Exception as e:
+            return {"error": f"Bracket lookup for order {order_id} failed: {type(e).__name__}: {e}"}
+        if not isinstance(legs, dict) or legs.get('error'):
+            return {"error": (legs or {}).get('error') or f"No bracket found for order {order_id}"}
+        parent = legs.get('parent') or {}
+        fill_price = parent.get('filledPrice') or parent.get('fillPrice') or parent.get('executionPrice')
+        if not fill_price:
+            return {"error": f"Order {order_id} has not filled"}
+        missing = [leg for leg in requested if not legs.get(leg)]
+        if missing:
+            return {"error": f"No working {' or '.join(missing)} order for order {order_id}"}
+
+        long_entry = str(parent.get('side')).upper() in ('0', 'BUY')
+        tick = self.tick_size(str(legs.get('symbol') or parent.get('contractId') or ''))
+        result: Dict[str, Any] = {"success": True, "order_id": str(order_id), "fill_price": float(fill_price)}
+        for leg, ticks in requested.items():
+            away = -1 if (leg == 'stop') == long_entry else 1
+            price = round(round(float(fill_price) / tick + away * int(ticks)) * tick, 10)
+            child = legs[leg]
+            child_id = str(child.get('id'))
+            try:
+                response = await self._call(self.modify, child_id, new_price=price, account_id=self.account_id,
+                                            order_type=child.get('type', 4 if leg == 'stop' else 1))
+                if isinstance(response, dict) and response.get('error'):
+                    raise RuntimeError(response['error'])
+            except Exception as e:
+                error = f"{type(e).__name__}: {e}"
+                with self._lock:
+                    self.last_error = error
+                logger.error(f"❌ Modifying the {leg} of order {order_id} failed: {error}")
+                return dict(result, success=False, error=f"Modifying the {leg} order {child_id} failed: {error}")
+            result[leg] = {"order_id": child_id, "price": price, "ticks": int(ticks)}
+            with self._lock:
+                self.brackets_modified += 1
+        logger.info(f"✅ Bracket of order {order_id} moved: "
+                    + ", ".join(f"{leg} {result[leg]['price']} ({result[leg]['ticks']} ticks)" for leg in requested))
+        return result
+
+    # ---------------------------
+    # Scaling in and out
+    # ---------------------------
+    async def _request(self, function: Optional[Callable[..., Any]], *args: Any,
+                       **kwargs: Any) -> Tuple[Optional[str], Any]:
+        """Call an order function: (error, response)."""
+        if function is None:
+            return "no order function configured", None
+        try:
+            response = await self._call(function, *args, **kwargs)
+            if isinstance(response, dict) and response.get('error'):
+                raise RuntimeError(response['error'])
+            return None, response
+        except Exception as e:
+            return f"{type(e).__name__}: {e}", None
+
+    @staticmethod
+    def _levels(levels: Iterable[Tuple[int, int]], min_ticks: int) -> Optional[List[Tuple[int, int]]]:
+        parsed = [(int(quantity), int(ticks)) for quantity, ticks in levels]
+        if not parsed or any(quantity < 1 or ticks < min_ticks for quantity, ticks in parsed):
+            return None
+        return parsed
+
+    def _ladder(self, key: Tuple[str, str]) -> Dict[str, Any]:
+        return self._ladders.setdefault(key, {'side': None, 'entries': [], 'targets': [], 'stop': None,
+                                               'opened': False, 'avg_price': None})
+
+    async def _place_levels(self, key: Tuple[str, str], kind: str, side: str, levels: List[Dict[str, Any]],
+                            result: Dict[str, Any]) -> Dict[str, Any]:
+        """Place limit orders for ladder levels, recording each one that goes out."""
+        strategy_id, symbol = key
+        for level in levels:
+            error, response = await self._request(
+                self.submit, symbol=symbol, side=side, quantity=level['quantity'], account_id=self.account_id,
+                strategy_name=strategy_id, order_type='limit', limit_price=level['price'],
+                stop_loss_ticks=None, take_profit_ticks=None)
+            order_id = response.get('orderId') if isinstance(response, dict) else None
+            if error is None and order_id is None:
+                error = "no order ID in the response"
+            if error is not None:
+                with self._lock:
+                    self.orders_failed += 1
+                    self.last_error = error
+                logger.error(f"❌ Ladder order at {level['price']} for {strategy_id} {symbol} failed: {error}")
+                return dict(result, success=False, error=f"Placing the {level['price']} level failed: {error}")
+            level['order_id'] = str(order_id)
+            with self._lock:
+                self.orders_submitted += 1
+                self._ladder(key)[kind].append(level)
+                self._ladder_orders[level['order_id']] = (key, kind)
+            result[kind].append(dict(level))
+        return result
+
+    async def scale_in(self, strategy_id: str, symbol: str, side: str, price: float,
+                       levels: Iterable[Tuple[int, int]]) -> Dict[str, Any]:
+        """
+        Laddered entry: one limit order per (quantity, ticks) level, ticks better than price.
+
+        Entries do not move the tracked position until they fill (report fills to on_fill()).
+
+        Args:
+            strategy_id: Strategy the position belongs to
+            symbol: Symbol to trade
+            side: BUY or SELL
+            price: Reference price; a 0-tick level enters at it
+            levels: (quantity, ticks) pairs
+
+        Returns:
+            Dict: success with the placed entries, or error (levels placed before a failure stay working)
+        """
+        side = str(side).upper()
+        parsed = self._levels(levels, min_ticks=0)
+        if side not in ('BUY', 'SELL') or parsed is None:
+            return {"error": "scale_in needs a BUY/SELL side and levels of at least 1 contract, 0+ ticks"}
+        sign = 1 if side == 'BUY' else -1
+        if self.position(strategy_id, symbol) * sign < 0:
+            return {"error": f"{strategy_id} holds an opposite {symbol.upper()} position"}
+        key = (strategy_id, symbol.upper())
+        tick = self.tick_size(symbol)
+        with self._lock:
+            self._ladder(key)['side'] = side
+        pending = [{'quantity': quantity, 'ticks': ticks, 'price': round(round(price / tick - sign * ticks) * tick, 10)}
+                   for quantity, ticks in parsed]
+        result = await self._place_levels(key, 'entries', side, pending, {"success": True, "entries": []})
+        if result['success']:
+            logger.info(f"✅ {strategy_id} {key[1]} {side} ladder placed: "
+                        + ", ".join(f"{level['quantity']} @ {level['price']}" for level in result['entries']))
+        return result
+
+    async def scale_out(self, strategy_id: str, symbol: str, levels: Iterable[Tuple[int, int]],
+                        entry_price: Optional[float] = None) -> Dict[str, Any]:
+        """
+        Partial exits: one limit target per (quantity, ticks) level, ticks in profit from the entry.
+
+        Replaces earlier scale_out() targets and the target leg of the entry's bracket; the
+        bracket's stop stays and is resized along with the position by rebalance().
+
+        Args:
+            strategy_id: Strategy the position belongs to
+            symbol: Symbol of the position
+            levels: (quantity, ticks) pairs; quantities may not add up to more than the position
+            entry_price: Price the ticks count from (default: the average ladder fill, else the
+                entry order's fill from find_legs)
+
+        Returns:
+            Dict: success with entry_price and the placed targets, or error
+        """
+        parsed = self._levels(levels, min_ticks=1)
+        if parsed is None:
+            return {"error": "scale_out needs levels of at least 1 contract and 1 tick"}
+        current = self.position(strategy_id, symbol)
+        if not current:
+            return {"error": f"{strategy_id} has no open {symbol.upper()} position"}
+        total = sum(quantity for quantity, _ in parsed)
+        if total > abs(current):
+            return {"error": f"Targets for {total} contracts exceed the {abs(current)} contract position"}
+        key = (strategy_id, symbol.upper())
+        legs: Dict[str, Any] = {}
+        entry_order = self.entry_order(strategy_id, symbol)
+        if self.find_legs is not None and entry_order is not None:
+            error, response = await self._request(self.find_legs, entry_order, account_id=self.account_id)
+            legs = response if error is None and isinstance(response, dict) else {}
+        with self._lock:
+            ladder = self._ladder(key)
+            replaced = [level['order_id'] for level in ladder['targets']]
+            average = ladder['avg_price']
+        if entry_price is None:
+            parent = legs.get('parent') or {}
+            entry_price = (average or parent.get('filledPrice') or parent.get('fillPrice')
+                           or parent.get('executionPrice'))
+        if not entry_price:
+            return {"error": f"Entry price of the {key[1]} position is unknown: pass entry_price"}
+        if legs.get('target'):
+            replaced.append(str(legs['target'].get('id')))
+        for order_id in replaced:
+            error, _ = await self._request(self.cancel, order_id, account_id=self.account_id)
+            if error is not None:
+                return {"error": f"Cancelling target {order_id} failed: {error}"}
+            self._forget(key, order_id)
+
+        sign = 1 if current > 0 else -1
+        tick = self.tick_size(symbol)
+        with self._lock:
+            ladder['side'] = 'BUY' if sign > 0 else 'SELL'
+            ladder['opened'] = True
+            if legs.get('stop'):
+                stop = legs['stop']
+                ladder['stop'] = {'order_id': str(stop.get('id')), 'quantity': int(stop.get('size') or abs(current))}
+            has_stop = ladder['stop'] is not None
+        pending = [{'quantity': quantity, 'ticks': ticks,
+                    'price': round(round(float(entry_price) / tick + sign * ticks) * tick, 10)}
+                   for quantity, ticks in parsed]
+        result = await self._place_levels(key, 'targets', 'SELL' if sign > 0 else 'BUY', pending,
+                                          {"success": True, "entry_price": float(entry_price), "targets": []})
+        if result['success']:
+            logger.info(f"✅ {strategy_id} {key[1]} scale-out targets placed: "
+                        + ", ".join(f"{level['quantity']} @ {level['price']}" for level in result['targets']))
+        if has_stop:
+            self._schedule(self.rebalance(strategy_id, symbol))  # Stop sized to the position
+        return result
+
+    def _forget(self, key: Tuple[str, str], order_id: str) -> None:
+        """Drop a ladder order that is no longer working."""
+        with self._lock:
+            self._ladder_orders.pop(order_id, None)
+            self._ladder_fills.pop(order_id, None)
+            ladder = self._ladders.get(key)
+            if ladder is not None:
+                for kind in ('entries', 'targets'):
+                    ladder[kind] = [level for level in ladder[kind] if level['order_id'] != order_id]
+
+    def on_fill(self, order: Dict[str, Any]) -> bool:
+        """
+        Report a (partially) filled order: ladder entries grow the position, scale-out targets shrink it.
+
+        fillVolume is the order's cumulative fill and the fill price its average, so repeated
+        updates for the same order only apply the contracts filled since the last one.
+
+        Returns:
+            bool: True if the order was placed by scale_in()/scale_out() (a rebalance is scheduled
+                when it filled more contracts)
+        """
+        order_id = str(order.get('id') or order.get('orderId') or '')
+        with self._lock:
+            known = self._ladder_orders.get(order_id)
+            if known is None:
+                return False
+            key, kind = known
+            ladder = self._ladders[key]
+            level = next(level for level in ladder[kind] if level['order_id'] == order_id)
+            filled, cost = self._ladder_fills.get(order_id, (0, 0.0))
+            total = int(order.get('fillVolume') or order.get('size') or filled + level['quantity'])
+            quantity = min(total - filled, level['quantity'])
+            if quantity <= 0:
+                return True
+            average = float(order.get('fillPrice') or order.get('filledPrice') or level['price'])
+            price = (average * (filled + quantity) - cost) / quantity if filled else average
+            level['quantity'] -= quantity
+            self._ladder_fills[order_id] = (filled + quantity, cost + price * quantity)
+            if not level['quantity']:
+                ladder[kind].remove(level)
+                del self._ladder_orders[order_id]
+                del self._ladder_fills[order_id]
+            sign = 1 if ladder['side'] == 'BUY' else -1
+            held = abs(self._positions.get(key, 0))
+            if kind == 'entries':
+                average = ladder['avg_price'] if ladder['avg_price'] is not None else price
+                ladder['avg_price'] = (average * held + price * quantity) / (held + quantity)
+                ladder['opened'] = True
+                held += quantity
+            else:
+                held = max(held - quantity, 0)
+            if held:
+                self._positions[key] = sign * held
+            else:
+                self._positions.pop(key, None)
+        self._schedule(self.rebalance(*key))
+        return True
+
+    def set_position(self, strategy_id: str, symbol: str, quantity: int) -> None:
+        """Set a position changed outside the executor (manual partial close, resync) and rebalance its ladder."""
+        key = (strategy_id, symbol.upper())
+        with self._lock:
+            if quantity:
+                self._positions[key] = int(quantity)
+            else:
+                self._positions.pop(key, None)
+            scaled = key in self._ladders
+        if scaled:
+            self._schedule(self.rebalance(strategy_id, symbol))
+
+    async def rebalance(self, strategy_id: str, symbol: str) -> Dict[str, Any]:
+        """
+        Keep scale-out targets and the stop consistent with the current position size.
+
+        Targets beyond the size are cancelled or reduced, farthest first, and the stop is
+        resized to the position. Once a scaled position is flat, its remaining entries and
+        targets are cancelled.
+
+        Returns:
+            Dict: success with the cancelled order IDs and resized orders, or error
+        """
+        key = (strategy_id, symbol.upper())
+        cancels: List[str] = []
+        resizes: List[Tuple[Dict[str, Any], int]] = []
+        with self._lock:
+            ladder = self._ladders.get(key)
+            size = abs(self._positions.get(key, 0))
+            if ladder is not None and not size and ladder['opened']:
+                cancels = [level['order_id'] for level in ladder['entries'] + ladder['targets']]
+            elif ladder is not None:
+                excess = sum(level['quantity'] for level in ladder['targets']) - size
+                for level in sorted(ladder['targets'], key=lambda level: -level['ticks']):
+                    if excess <= 0:
+                        break
+                    if level['quantity'] <= excess:
+                        cancels.append(level['order_id'])
+                    else:
+                        resizes.append((level, level['quantity'] - excess))
+                    excess -= level['quantity']
+                if ladder['stop'] is not None and size and ladder['stop']['quantity'] != size:
+                    resizes.append((ladder['stop'], size))
+        result: Dict[str, Any] = {"success": True, "cancelled": [], "resized": []}
+        errors = []
+        for order_id in cancels:
+            error, _ = await self._request(self.cancel, order_id, account_id=self.account_id)
+            if error is None:
+                self._forget(key, order_id)
+                result['cancelled'].append(order_id)
+            else:
+                errors.append(f"cancelling {order_id}: {error}")
+        for level, quantity in resizes:
+            error, _ = await self._request(self.modify, level['order_id'], new_quantity=quantity,
+                                           account_id=self.account_id)
+            if error is None:
+                with self._lock:
+                    level['quantity'] = quantity
+                result['resized'].append({"order_id": level['order_id'], "quantity": quantity})
+            else:
+                errors.append(f"resizing {level['order_id']}: {error}")
+        with self._lock:
+            if ladder is not None and not size and ladder['opened'] and not errors:
+                self._ladders.pop(key, None)
+            if cancels or resizes:
+                self.ladders_rebalanced += 1
+            if errors:
+                self.last_error = "; ".join(errors)
+        if errors:
+            logger.error(f"❌ Rebalancing {strategy_id} {key[1]} to {size} contracts failed: {'; '.join(errors)}")
+            return dict(result, success=False, error="; ".join(errors))
+        if cancels or resizes:
+            logger.info(f"🔧 {strategy_id} {key[1]} orders rebalanced to {size} contracts "
+                        f"({len(cancels)} cancelled, {len(resizes)} resized)")
+        return result
+
+    def reset(self) -> None:
+        with self._lock:
+            self._positions.clear()
+            self._entry_orders.clear()
+            self._ladders.clear()
+            self._ladder_orders.clear()
+            self._ladder_fills.clear()
+
+    def get_stats(self) -> Dict[str, Any]:
+        with self._lock:
+            return {
+                "account_id": self.account_id,
+                "orders_submitted": self.orders_submitted,
+                "orders_failed": self.orders_failed,
+                "orders_unknown": self.orders_unknown,
+                "signals_ignored": self.signals_ignored,
+                "brackets_modified": self.brackets_modified,
+                "ladder_orders": len(self._ladder_orders),
+                "ladders_rebalanced": self.ladders_rebalanced,
+                "last_error": self.last_error,
+            }
+
+
+class TradingCore:
+    """
+    Market data to order submission pipeline with Python-side controls.
+    """
+
+    def __init__(self, submit: OrderSubmitter, account_id: Optional[str] = None, strategies: Iterable[Any] = (),
+                 aggregator: Optional[BarAggregator] = None, engine: Optional[StrategyEngine] = None,
+                 risk: Any = None, portfolio: Any = None, filters: Iterable[Any] = (),
+                 tick_validator: Any = None, executor: Optional[OrderExecutor] = None,
+                 latency_window: Optional[int] = None):
+        """
+        Initialize and wire the pipeline.
+
+        Args:
+            submit: Order submission function (see OrderExecutor)
+            account_id: Account to trade
+            strategies: Strategies to run (anything StrategyEngine.add_strategy() accepts)
+            aggregator: BarAggregator fed by on_event() (default: one of its own)
+            engine: StrategyEngine (default: one of its own)
+            risk: RiskManager gating entries and marked to market by on_event()
+            portfolio: PortfolioCoordinator (attached after the other gates; sizes entries)
+            filters: Other gates with attach(engine), e.g. SessionFilter, SignalThrottle
+            tick_validator: TickValidator dropping bad prints before they reach bars
+            executor: OrderExecutor (default: one built from submit/account_id/portfolio)
+            latency_window: Latencies kept for percentiles (env: TRADING_CORE_LATENCY_WINDOW)
+        """
+        self.aggregator = aggregator if aggregator is not None else BarAggregator()
+        self.engine = engine if engine is not None else StrategyEngine()
+        self.risk = risk
+        self.portfolio = portfolio
+        self.tick_validator = tick_validator
+        quantity_for = (lambda strategy_id: portfolio.allocation_for(strategy_id).quantity) if portfolio else None
+        self.executor = executor or OrderExecutor(submit, account_id=account_id, quantity_for=quantity_for)
+        window = (latency_window if latency_window is not None
+                  else int(os.getenv('TRADING_CORE_LATENCY_WINDOW', '1000')))
+        self._latencies: Deque[float] = deque(maxlen=window)  # Milliseconds
+        self._current = threading.local()  # perf_counter() of the event being processed on this thread
+        self._lock = threading.Lock()
+        self.paused = False
+        self.events = 0
+        self.rejected_ticks = 0
+        self.started_at = datetime.now(timezone.utc)
+
+        for strategy in strategies:
+            self.engine.add_strategy(strategy)
+        self._detach: List[Callable[[], None]] = [self.engine.attach(self.aggregator)]
+        self._detach.append(self.engine.add_gate(self._allows, name='trading_core'))
+        if risk is not None:
+            self._detach.append(risk.attach(self.engine))
+        for gate in filters:
+            self._detach.append(gate.attach(self.engine))
+        if portfolio is not None:
+            self._detach.append(portfolio.attach(self.engine))
+        self._detach.append(self.engine.on_signal(self._execute))
+        logger.info(f"✨ Trading core ready: {len(self.engine.strategies)} strategies, "
+                    f"account {account_id or 'default'}")
+
+    def bind_loop(self, loop: Optional[asyncio.AbstractEventLoop] = None) -> None:
+        """Submit orders (and run kill switches) on this loop (default: the running loop)."""
+        loop = loop or asyncio.get_running_loop()
+        self.executor.bind_loop(loop)
+        if self.risk is not None:
+            self.risk.bind_loop(loop)
+
+    # ---------------------------
+    # Hot path
+    # ---------------------------
+    def on_event(self, event: MarketEvent) -> None:
+        """Market event listener: validate, build bars, run strategies and submit their orders."""
+        self._current.started = time.perf_counter()
+        try:
+            with self._lock:
+                self.events += 1
+            price = event_price(event)
+            if price is not None:
+                if self.tick_validator is not None and self.tick_validator.validate(
+                        event.symbol, price, event.timestamp, bid=getattr(event, 'bid', None),
+                        ask=getattr(event, 'ask', None)) is not None:
+                    with self._lock:
+                        self.rejected_ticks += 1
+                    return  # Bad print: keep it out of bars, risk marks and strategies
+                if self.risk is not None:
+                    self.risk.on_event(event)
+                if isinstance(event, Trade):
+                    volume = event.size
+                else:
+                    volume = (event.volume or 0) if isinstance(event, Quote) else 0
+                self.aggregator.add_quote(event.symbol, price, volume, event.timestamp)
+            self.engine.dispatch_tick(event)
+        finally:
+            self._current.started = None
+
+    def _execute(self, signal: Signal) -> None:
+        """Hand a published signal to the executor and time it from the event that caused it."""
+        self.executor.on_signal(signal)
+        started = getattr(self._current, 'started', None)
+        if started is not None:  # Not a manual flatten or a worker-thread signal
+            with self._lock:
+                self._latencies.append((time.perf_counter() - started) * 1000)
+
+    def on_fill(self, symbol: str, side: Any, quantity: int, price: float, commission: Optional[float] = None,
+                timestamp: Optional[datetime] = None, order_id: Optional[str] = None) -> None:
+        """
+        Report a fill of the traded account to the RiskManager (and, with order_id, to scaled positions).
+
+        Without a commission, the RiskManager charges its fee model.
+        """
+        if self.risk is not None and self.executor.account_id is not None:
+            self.risk.on_fill(self.executor.account_id, symbol, side, quantity, price, commission=commission,
+                              timestamp=timestamp)
+        if order_id is not None:
+            self.executor.on_fill({'id': order_id, 'fillVolume': quantity, 'fillPrice': price})
+
+    # ---------------------------
+    # Controls
+    # ---------------------------
+    def _allows(self, signal: Signal) -> bool:
+        return signal.direction is Direction.FLAT or not self.paused
+
+    def pause(self) -> None:
+        """Stop opening positions (exits still go out)."""
+        self.paused = True
+        logger.warning("⚠️ Trading core paused: new entries blocked")
+
+    def resume(self) -> None:
+        self.paused = False
+        logger.info("✅ Trading core resumed")
+
+    def update_params(self, strategy_id: str, params: Dict[str, Any], immediate: bool = False) -> Any:
+        """Change a strategy's parameters (applied at its next bar unless immediate)."""
+        return self.engine.update_params(strategy_id, params, immediate=immediate)
+
+    def flatten(self, symbol: Optional[str] = None, strategy_id: Optional[str] = None) -> int:
+        """
+        Close tracked positions through the pipeline (FLAT signals pass every gate).
+
+        Returns:
+            int: Positions closed
+        """
+        closed = 0
+        for pos_symbol, holders in self.executor.positions().items():
+            if symbol is not None and pos_symbol != symbol.upper():
+                continue
+            for holder in holders:
+                if strategy_id is not None and holder != strategy_id:
+                    continue
+                last = self.aggregator.get_last_completed_bar(pos_symbol, '1m')
+                signal = Signal(strategy_id=holder, symbol=pos_symbol, direction=Direction.FLAT,
+                                price=last.close if last else 0.0, timestamp=datetime.now(timezone.utc),
+                                reason="Manual flatten")
+                closed += int(self.engine.publish(signal))
+        if closed:
+            logger.warning(f"⚠️ Trading core: flattened {closed} position(s)")
+        return closed
+
+    def stop(self) -> None:
+        """Detach everything from the engine and aggregator."""
+        for detach in reversed(self._detach):
+            detach()
+        self._detach.clear()
+        self.engine.stop()
+
+    def get_stats(self) -> Dict[str, Any]:
+        with self._lock:
+            latencies = sorted(self._latencies)
+            stats: Dict[str, Any] = {
+                "paused": self.paused,
+                "events": self.events,
+                "rejected_ticks": self.rejected_ticks,
+                "started_at": self.started_at.isoformat(),
+                "latency_ms": {
+                    "samples": len(latencies),
+                    "p50": round(percentile(latencies, 50), 3) if latencies else None,
+                    "p99": round(percentile(latencies, 99), 3) if latencies else None,
+                    "max": round(latencies[-1], 3) if latencies else None,
+                },
+            }
+        stats["positions"] = self.executor.positions()
+        stats["executor"] = self.executor.get_stats()
+        stats["engine"] = self.engine.get_stats()
+        if self.risk is not None:
+            stats["risk"] = self.risk.get_status()
+        if self.portfolio is not None:
+            stats["portfolio"] = self.portfolio.get_stats()
+        return stats
@@ -0,0 +1,201 @@
+"""
+Walk-forward analysis.
+
+Splits history into consecutive windows, each an in-sample (training) part
+followed by an out-of-sample (test) part:
+
+    |---- train ----|-- test --|
+          |---- train ----|-- test --|
+                |---- train ----|-- test --|      (step = test length by default)
+
+For every window all candidate parameter sets are backtested in-sample (in
+parallel through BacktestRunner), the best one by the objective is then run
+on the out-of-sample part it has never seen, with the training bars as
+indicator warm-up. Only out-of-sample trades count towards the aggregated
+result, which is what makes the numbers an honest estimate of live
+performance. Walk-forward efficiency (out-of-sample vs in-sample P&L per day)
+shows how much of the in-sample edge survives.
+
+- anchored=True keeps every training window starting at the beginning of the
+  data (expanding window) instead of rolling it forward
+
+Usage:
+    wf = WalkForward(make_ema, candidates=[{'fast_period': f, 'slow_period': s} for f, s in pairs],
+                     train=timedelta(days=20), test=timedelta(days=5), objective='net_pnl')
+    result = wf.run(bars)
+    print(result.stats(), [w.best_params for w in result.windows])
+
+Configuration:
+- WALK_FORWARD_TRAIN_DAYS: In-sample window length in days (default 20)
+- WALK_FORWARD_TEST_DAYS: Out-of-sample window length in days (default 5)
+- WALK_FORWARD_STEP_DAYS: Window step in days (default: the test length)
+- BACKTEST_WORKERS: Worker processes for the in-sample runs (see runner)
+"""
+
+import logging
+import os
+from dataclasses import dataclass, field
+from datetime import datetime, timedelta
+from typing import Any, Dict, Iterable, List, Optional, Sequence
+
+from core.bar_aggregator import Bar
+from core.market_events import MarketEvent
+from core.strategy_engine.backtest import BacktestConfig, BacktestResult, BacktestTrade
+from core.strategy_engine.runner import BacktestRunner, Objective, StrategyFactory
+
+logger = logging.getLogger(__name__)
+
+
+@dataclass
+class WalkForwardWindow:
+    """One train/test split and its results."""
+    index: int
+    train_start: datetime
+    train_end: datetime  # = test start
+    test_end: datetime
+    best_params: Optional[Dict[str, Any]] = None
+    in_sample: Dict[str, Any] = field(default_factory=dict)  # Stats of the best candidate
+    out_of_sample: Dict[str, Any] = field(default_factory=dict)
+    trades: List[BacktestTrade] = field(default_factory=list)  # Out-of-sample
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        return {
+            "index": self.index,
+            "train_start": self.train_start.isoformat(),
+            "train_end": self.train_end.isoformat(),
+            "test_end": self.test_end.isoformat(),
+            "best_params": self.best_params,
+            "in_sample": self.in_sample,
+            "out_of_sample": self.out_of_sample,
+        }
+
+
+@dataclass
+class WalkForwardResult:
+    """All windows plus the stitched out-of-sample performance."""
+    windows: List[WalkForwardWindow]
+    initial_capital: float
+    objective: str
+
+    @property
+    def trades(self) -> List[BacktestTrade]:
+        return [t for w in self.windows for t in w.trades]
+
+    def out_of_sample(self) -> BacktestResult:
+        """Out-of-sample trades of all windows as one backtest result (equity marked at each exit)."""
+        trades = sorted(self.trades, key=lambda t: t.exit_time)
+        equity, curve = self.initial_capital, []
+        for trade in trades:
+            equity += trade.pnl
+            curve.append((trade.exit_time, equity))
+        return BacktestResult(trades=trades, equity_curve=curve, initial_capital=self.initial_capital)
+
+    def efficiency(self) -> Optional[float]:
+        """Out-of-sample net P&L per day over in-sample net P&L per day (None without in-sample profit)."""
+        tested = [w for w in self.windows if w.best_params is not None]
+        is_days = sum((w.train_end - w.train_start).total_seconds() for w in tested) / 86400
+        oos_days = sum((w.test_end - w.train_end).total_seconds() for w in tested) / 86400
+        is_pnl = sum(w.in_sample.get('net_pnl', 0.0) for w in tested)
+        oos_pnl = sum(w.out_of_sample.get('net_pnl', 0.0) for w in tested)
+        if is_pnl <= 0 or not is_days or not oos_days:
+            return None
+        return round((oos_pnl / oos_days) / (is_pnl / is_days), 3)
+
+    def stats(self) -> Dict[str, Any]:
+        """Aggregated out-of-sample statistics."""
+        stats = self.out_of_sample().stats()
+        for key in ('signals', 'bars', 'ticks'):  # Not tracked across windows
+            stats.pop(key)
+        stats.update(windows=len(self.windows), objective=self.objective, efficiency=self.efficiency())
+        return stats
+
+    def to_dict(self) -> Dict[str, Any]:
+        """Convert to JSON-friendly dictionary."""
+        return {"stats": self.stats(), "windows": [w.to_dict() for w in self.windows]}
+
+
+class WalkForward:
+    """
+    Rolling (or anchored) in-sample optimization with out-of-sample evaluation.
+    """
+
+    def __init__(self, factory: StrategyFactory, candidates: Iterable[Dict[str, Any]],
+                 train: Optional[timedelta] = None, test: Optional[timedelta] = None,
+                 step: Optional[timedelta] = None, objective: Objective = 'net_pnl',
+                 config: Optional[BacktestConfig] = None, anchored: bool = False,
+                 workers: Optional[int] = None):
+        """
+        Initialize walk-forward analysis.
+
+        Args:
+            factory: params -> strategy or list of strategies (see BacktestRunner)
+            candidates: Parameter sets to choose from in each training window
+            train: In-sample length (env: WALK_FORWARD_TRAIN_DAYS)
+            test: Out-of-sample length (env: WALK_FORWARD_TEST_DAYS)
+            step: Distance between window starts (env: WALK_FORWARD_STEP_DAYS, default: test)
+            objective: Stats key or callable(stats) -> float, higher is better
+            config: Fill model (default: BacktestConfig.from_env())
+            anchored: Keep training windows anchored at the start of the data
+            workers: Worker processes (env: BACKTEST_WORKERS)
+        """
+        self.factory = factory
+        self.candidates = [dict(c) for c in candidates]
+        if not self.candidates:
+            raise ValueError("WalkForward needs at least one candidate parameter set")
+        self.train = train or timedelta(days=float(os.getenv('WALK_FORWARD_TRAIN_DAYS', '20')))
+        self.test = test or timedelta(days=float(os.getenv('WALK_FORWARD_TEST_DAYS', '5')))
+        step_days = os.getenv('WALK_FORWARD_STEP_DAYS')
+        self.step = step or (timedelta(days=float(step_days)) if step_days else self.test)
+        if min(self.train, self.test, self.step) <= timedelta(0):
+            raise ValueError("train, test and step must be positive")
+        self.objective = objective
+        self.config = (config or BacktestConfig.from_env()).validate()
+        self.anchored = anchored
+        self.workers = workers
+
+    def windows(self, start: datetime, end: datetime) -> List[WalkForwardWindow]:
+        """Train/test splits covering [start, end); the last test window may be cut short."""
+        windows: List[WalkForwardWindow] = []
+        offset = timedelta(0)
+        while start + offset + self.train < end:
+            train_start = start if self.anchored else start + offset
+            train_end = start + offset + self.train
+            windows.append(WalkForwardWindow(index=len(windows), train_start=train_start, train_end=train_end,
+                                             test_end=min(train_end + self.test, end)))
+            offset += self.step
+        return windows
+
+    def run(self, bars: Sequence[Bar], ticks: Sequence[MarketEvent] = ()) -> WalkForwardResult:
+        """
+        Run every window.
+
+        Returns:
+            WalkForwardResult: Per-window picks and aggregated out-of-sample stats
+        """
+        bars = sorted(bars, key=lambda b: b.timestamp)
+        objective_name = self.objective if isinstance(self.objective, str) else getattr(
+            self.objective, '__name__', 'custom')
+        if not bars:
+            return WalkForwardResult([], self.config.initial_capital, objective_name)
+        windows = self.windows(bars[0].timestamp, bars[-1].timestamp + timedelta(microseconds=1))
+        if not windows:
+            logger.warning(f"⚠️ Walk-forward: {bars[-1].timestamp - bars[0].timestamp} of data is shorter "
+                           f"than one training window ({self.train})")
+
+        with BacktestRunner(self.factory, bars, self.config, ticks=ticks, workers=self.workers) as runner:
+            for window in windows:
+                results = runner.run(self.candidates, start=window.train_start, end=window.train_end)
+                best = max(results, key=lambda r: r.score(self.objective))
+                if best.error:
+                    logger.error(f"❌ Walk-forward window {window.index}: every candidate failed ({best.error})")
+                    continue
+                window.best_params, window.in_sample = best.params, best.stats
+                # Test on unseen data, with the training bars as warm-up
+                oos = runner.run([best.params], start=window.train_start, end=window.test_end,
+                                 trade_from=window.train_end, keep_trades=True)[0]
+                window.out_of_sample, window.trades = oos.stats, oos.trades
+                logger.info(f"📊 Walk-forward window {window.index} ({window.train_end:%Y-%m-%d}): "
+                            f"best {best.params}, in-sample ${best.stats.get('net_pnl', 0):,.2f}, "
+                            f"out-of-sample ${oos.stats.get('net_pnl', 0):,.2f}")
+        return WalkForwardResult(windows, self.config.initial_capital, objective_name)
@@ -0,0 +1,166 @@
+"""
+Per-symbol worker threads for StrategyEngine.
+
+Events are sharded by symbol: every event of a symbol goes to the same
+worker, so each symbol's bars and ticks reach its strategies in the order
+they happened, while different symbols are processed side by side instead
+of one after another on the quote thread.
+
+- Bars are never dropped: a full worker queue blocks the producer (bars are
+  a few per minute per symbol, so this only happens if a strategy hangs)
+- Ticks are dropped and counted when the worker queue is full, so a slow
+  strategy can't back up the market feed
+- flush() waits until everything queued so far has been processed (replay,
+  tests, shutdown)
+
+Threads only run strategies in parallel while they wait on I/O or run code
+that releases the GIL (numpy, native indicators); pure Python strategies
+still interleave. The ordering guarantee is what lets many symbols share one
+engine either way.
+"""
+
+import logging
+import queue
+import threading
+import time
+import zlib
+from typing import Any, Callable, Dict, List, Optional
+
+logger = logging.getLogger(__name__)
+
+_STOP = object()
+
+
+class _Worker(threading.Thread):
+    """One shard: a queue and the thread draining it."""
+
+    def __init__(self, index: int, handler: Callable[[str, Any], Any], queue_size: int):
+        super().__init__(name=f"strategy-worker-{index}", daemon=True)
+        self.index = index
+        self.handler = handler
+        self.queue: "queue.Queue[Any]" = queue.Queue(maxsize=queue_size)
+        self.processed = 0
+        self.dropped = 0
+        self.errors = 0
+
+    def run(self) -> None:
+        while True:
+            item = self.queue.get()
+            try:
+                if item is _STOP:
+                    return
+                kind, event = item
+                self.handler(kind, event)
+                self.processed += 1
+            except Exception as e:
+                self.errors += 1
+                logger.error(f"❌ Strategy worker {self.index} failed on {item!r}: {e}")
+            finally:
+                self.queue.task_done()
+
+
+class SymbolWorkerPool:
+    """
+    Fixed pool of worker threads with events pinned to a worker by symbol.
+
+    Usage:
+        pool = SymbolWorkerPool(4, handler=lambda kind, event: ...)
+        pool.submit('bar', bar)
+        pool.flush()
+        pool.stop()
+    """
+
+    def __init__(self, workers: int, handler: Callable[[str, Any], Any], queue_size: int = 10000):
+        """
+        Initialize pool (threads start on first submit()).
+
+        Args:
+            workers: Number of worker threads (>= 1)
+            handler: Called on a worker thread with (kind, event)
+            queue_size: Per-worker queue size
+        """
+        if workers < 1:
+            raise ValueError(f"workers must be >= 1, got {workers}")
+        self.handler = handler
+        self.queue_size = queue_size
+        self._workers: List[_Worker] = []
+        self._size = workers
+        self._lock = threading.Lock()
+
+    def shard_for(self, symbol: str) -> int:
+        """Worker index a symbol is pinned to."""
+        return zlib.crc32(symbol.upper().encode('utf-8')) % self._size
+
+    @property
+    def running(self) -> bool:
+        return bool(self._workers)
+
+    def start(self) -> None:
+        with self._lock:
+            if self._workers:
+                return
+            self._workers = [_Worker(i, self.handler, self.queue_size) for i in range(self._size)]
+            for worker in self._workers:
+                worker.start()
+        logger.info(f"✅ Strategy engine: {self._size} symbol workers started")
+
+    def submit(self, kind: str, event: Any, block: bool = True) -> bool:
+        """
+        Queue an event on its symbol's worker.
+
+        Args:
+            kind: Handler kind, e.g. 'bar' or 'tick'
+            event: Event with a .symbol
+            block: Wait for room when the queue is full (False = drop and count)
+
+        Returns:
+            bool: False if the event was dropped
+        """
+        if not self._workers:
+            self.start()
+        worker = self._workers[self.shard_for(event.symbol)]
+        try:
+            worker.queue.put((kind, event), block=block)
+            return True
+        except queue.Full:
+            worker.dropped += 1
+            return False
+
+    def flush(self, timeout: Optional[float] = None) -> bool:
+        """
+        Wait until every queued event has been processed.
+
+        Returns:
+            bool: False on timeout
+        """
+        deadline = time.monotonic() + timeout if timeout is not None else None
+        for worker in self._workers:
+            while worker.queue.unfinished_tasks:
+                if deadline is not None and time.monotonic() >= deadline:
+                    return False
+                time.sleep(0.001)
+        return True
+
+    def stop(self, timeout: float = 5.0) -> None:
+        """Process what is queued, then stop the threads."""
+        with self._lock:
+            workers, self._workers = self._workers, []
+        for worker in workers:
+            worker.queue.put(_STOP)
+        for worker in workers:
+            worker.join(timeout)
+
+    def get_stats(self) -> Dict[str, Any]:
+        return {
+            "workers": self._size,
+            "running": self.running,
+            "shards": [
+                {
+                    "queued": worker.queue.qsize(),
+                    "processed": worker.processed,
+                    "dropped": worker.dropped,
+                    "errors": worker.errors,
+                }
+                for worker in self._workers
+            ],
+        }
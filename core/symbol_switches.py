@@ -0,0 +1,169 @@
+"""
+Per-Symbol Trading Switches
+
+Runtime switches that disable trading for specific instruments without
+touching strategy code. A disabled symbol still receives market data and
+keeps its bars, indicators and analytics up to date; only new orders are
+refused in the bot's pre-trade checks. Exits (closing positions, cancelling
+orders) are never blocked.
+
+Switches are persisted so a blacklist survives restarts:
+- PostgreSQL (symbol_trading_switches table) when a database is available
+- Otherwise a JSON file (SYMBOL_SWITCHES_PATH, default symbol_switches.json)
+
+Configuration:
+- SYMBOL_SWITCHES_PATH: JSON file used when no database is available
+- TRADING_DISABLED_SYMBOLS: Comma-separated symbols disabled at startup unless
+  a persisted switch says otherwise
+"""
+
+import json
+import logging
+import os
+import threading
+from dataclasses import dataclass, asdict
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import Dict, List, Optional, Union
+
+from infrastructure.file_lock import atomic_write
+
+logger = logging.getLogger(__name__)
+
+
+@dataclass
+class SymbolSwitch:
+    """Trading switch state for one symbol."""
+    symbol: str
+    enabled: bool = True
+    reason: Optional[str] = None
+    updated_by: Optional[str] = None
+    updated_at: Optional[str] = None
+
+    def to_dict(self) -> Dict:
+        return asdict(self)
+
+
+class SymbolTradingSwitches:
+    """
+    Enable/disable trading per symbol.
+
+    Usage:
+        switches = SymbolTradingSwitches(db=bot.db)
+        switches.disable('MES', reason='bad fills during roll', updated_by='dashboard')
+        error = switches.check('MES')   # -> error message, or None if trading is allowed
+    """
+
+    def __init__(self, db=None, path: Union[str, Path, None] = None):
+        """
+        Initialize switches and load persisted state.
+
+        Args:
+            db: DatabaseManager for persistence (file fallback if None)
+            path: JSON file for file persistence (env: SYMBOL_SWITCHES_PATH)
+        """
+        self.db = db
+        self.path = Path(path or os.getenv('SYMBOL_SWITCHES_PATH', 'symbol_switches.json'))
+        self._switches: Dict[str, SymbolSwitch] = {}
+        self._lock = threading.RLock()
+        self.load()
+
+    @staticmethod
+    def _key(symbol: str) -> str:
+        return symbol.strip().upper()
+
+    def load(self) -> None:
+        """Load persisted switches, then apply TRADING_DISABLED_SYMBOLS for symbols with no saved state."""
+        stored = self.db.get_symbol_switches() if self.db is not None else None
+        if stored is None:
+            stored = self._read_file()
+        switches = {
+            self._key(symbol): SymbolSwitch(self._key(symbol), bool(data.get('enabled', True)), data.get('reason'),
+                                            data.get('updated_by'), data.get('updated_at'))
+            for symbol, data in stored.items()
+        }
+        for symbol in os.getenv('TRADING_DISABLED_SYMBOLS', '').split(','):
+            key = self._key(symbol) if symbol.strip() else None
+            if key and key not in switches:
+                switches[key] = SymbolSwitch(key, enabled=False, reason="TRADING_DISABLED_SYMBOLS", updated_by="env")
+        with self._lock:
+            self._switches = switches
+        disabled = self.disabled_symbols()
+        if disabled:
+            logger.warning(f"🚫 Trading disabled for: {', '.join(disabled)}")
+
+    def _read_file(self) -> Dict[str, Dict]:
+        try:
+            return json.loads(self.path.read_text())
+        except FileNotFoundError:
+            return {}
+        except (OSError, ValueError) as e:
+            logger.error(f"❌ Failed to read symbol switches from {self.path}: {e}")
+            return {}
+
+    def _persist(self, switch: SymbolSwitch) -> bool:
+        if self.db is not None and self.db.save_symbol_switch(switch.symbol, switch.enabled,
+                                                              switch.reason, switch.updated_by):
+            return True
+        # No database (or it failed): keep the blacklist on disk so it survives restarts
+        try:
+            with self._lock:
+                data = {symbol: {k: v for k, v in s.to_dict().items() if k != 'symbol'}
+                        for symbol, s in self._switches.items()}
+            atomic_write(self.path, json.dumps(data, indent=2))
+            return True
+        except OSError as e:
+            logger.error(f"❌ Failed to persist symbol switches to {self.path}: {e}")
+            return False
+
+    def set_enabled(self, symbol: str, enabled: bool, reason: Optional[str] = None,
+                    updated_by: Optional[str] = None) -> SymbolSwitch:
+        """
+        Set the trading switch for a symbol and persist it.
+
+        Args:
+            symbol: Trading symbol (e.g. 'MNQ')
+            enabled: False to block new orders
+            reason: Why the switch changed (shown in order rejections)
+            updated_by: Who changed it (e.g. 'dashboard', 'api')
+
+        Returns:
+            SymbolSwitch: New switch state
+        """
+        key = self._key(symbol)
+        switch = SymbolSwitch(key, enabled, reason, updated_by, datetime.now(timezone.utc).isoformat())
+        with self._lock:
+            self._switches[key] = switch
+        persisted = self._persist(switch)
+        logger.warning(f"{'✅' if enabled else '🚫'} Trading {'enabled' if enabled else 'disabled'} for {key}"
+                       f"{f' ({reason})' if reason else ''}{'' if persisted else ' - NOT persisted'}")
+        return switch
+
+    def disable(self, symbol: str, reason: Optional[str] = None, updated_by: Optional[str] = None) -> SymbolSwitch:
+        return self.set_enabled(symbol, False, reason, updated_by)
+
+    def enable(self, symbol: str, reason: Optional[str] = None, updated_by: Optional[str] = None) -> SymbolSwitch:
+        return self.set_enabled(symbol, True, reason, updated_by)
+
+    def is_enabled(self, symbol: str) -> bool:
+        """True unless trading has been disabled for the symbol."""
+        with self._lock:
+            switch = self._switches.get(self._key(symbol))
+        return switch is None or switch.enabled
+
+    def check(self, symbol: str) -> Optional[str]:
+        """Pre-trade check: error message if new orders on the symbol are blocked, else None."""
+        with self._lock:
+            switch = self._switches.get(self._key(symbol))
+        if switch is None or switch.enabled:
+            return None
+        return f"Trading disabled for {switch.symbol}" + (f": {switch.reason}" if switch.reason else "")
+
+    def disabled_symbols(self) -> List[str]:
+        with self._lock:
+            return sorted(symbol for symbol, switch in self._switches.items() if not switch.enabled)
+
+    def get_all(self) -> Dict[str, Dict]:
+        """All switches keyed by symbol."""
+        with self._lock:
+            return {symbol: switch.to_dict() for symbol, switch in sorted(self._switches.items())}
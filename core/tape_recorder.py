@@ -0,0 +1,284 @@
+"""
+Time-and-sales recorder.
+
+Captures every trade print (price, size, aggressor side, timestamp) per
+symbol in an in-memory ring buffer for fast tape queries. Prints evicted from
+a full buffer are spilled to disk instead of being lost: the hot path only
+moves them to a pending queue, and a background writer (or flush()) appends
+them to daily JSONL files in the MarketEvent.to_dict() format, so spilled
+tape can be read back by replay_fixtures / the replay engine.
+
+Queries:
+- prints(symbol, window): prints in the last `window` (oldest first)
+- largest_trades(n, window): biggest prints by size
+- volume_in_window(window, side=None): traded volume, optionally by aggressor
+
+Windows are measured back from `now`, which defaults to the time of the
+latest recorded print (tape time) so queries behave the same live and in
+replay. include_spilled=True also scans spill files for the window.
+
+Configuration:
+- TAPE_RECORDER_ENABLED: Record the trade stream in the bot (default false)
+- TAPE_BUFFER_SIZE: Prints kept in memory per symbol (default 50000)
+- TAPE_SPILL_DIR: Spill directory (default 'data/tape'), files <dir>/<SYMBOL>/tape-YYYYMMDD.jsonl
+- TAPE_SPILL_ENABLED: Spill evicted prints to disk (default true)
+- TAPE_FLUSH_INTERVAL: Seconds between background spill writes (default 5)
+"""
+
+import heapq
+import json
+import logging
+import os
+import threading
+from collections import defaultdict, deque
+from datetime import datetime, timedelta, timezone
+from pathlib import Path
+from typing import Any, Deque, Dict, List, Optional, Union
+
+from core.market_events import MarketEvent, Trade, market_event_from_dict
+
+logger = logging.getLogger(__name__)
+
+Window = Union[timedelta, float, int, None]
+
+
+def _as_timedelta(window: Window) -> Optional[timedelta]:
+    if window is None or isinstance(window, timedelta):
+        return window
+    return timedelta(seconds=float(window))
+
+
+def _utc(ts: datetime) -> datetime:
+    return ts if ts.tzinfo else ts.replace(tzinfo=timezone.utc)
+
+
+class TapeRecorder:
+    """
+    Ring-buffered time-and-sales tape with spill-to-disk.
+
+    Usage:
+        tape = TapeRecorder()
+        tape.start()
+        bot.add_market_event_listener(tape.on_market_event)
+        tape.largest_trades(5, window=300, symbol='MNQ')
+        tape.volume_in_window(60, symbol='MNQ', side='buy')
+    """
+
+    def __init__(self, buffer_size: Optional[int] = None, spill_dir: Union[str, Path, None] = None,
+                 spill: Optional[bool] = None, flush_interval: Optional[float] = None):
+        """
+        Initialize recorder.
+
+        Args:
+            buffer_size: Prints kept in memory per symbol (env: TAPE_BUFFER_SIZE)
+            spill_dir: Spill directory (env: TAPE_SPILL_DIR)
+            spill: Spill evicted prints to disk (env: TAPE_SPILL_ENABLED)
+            flush_interval: Seconds between background spill writes (env: TAPE_FLUSH_INTERVAL)
+        """
+        self.buffer_size = buffer_size if buffer_size is not None else int(os.getenv('TAPE_BUFFER_SIZE', '50000'))
+        if self.buffer_size <= 0:
+            raise ValueError(f"buffer_size must be > 0, got {self.buffer_size}")
+        self.spill_dir = Path(spill_dir or os.getenv('TAPE_SPILL_DIR', 'data/tape'))
+        self.spill_enabled = spill if spill is not None else \
+            os.getenv('TAPE_SPILL_ENABLED', 'true').lower() in ('true', '1', 'yes', 'on')
+        self.flush_interval = flush_interval if flush_interval is not None else \
+            float(os.getenv('TAPE_FLUSH_INTERVAL', '5'))
+        self._tapes: Dict[str, Deque[Trade]] = defaultdict(lambda: deque(maxlen=self.buffer_size))
+        self._pending: Deque[Trade] = deque()
+        self._lock = threading.Lock()
+        self._spill_lock = threading.RLock()  # Serializes spill writes and reads
+        self._stop = threading.Event()
+        self._thread: Optional[threading.Thread] = None
+        self._last_time: Optional[datetime] = None
+        self.prints_recorded = 0
+        self.prints_spilled = 0
+        self.prints_evicted = 0
+        self.spill_errors = 0
+
+    # ---------------------------
+    # Recording
+    # ---------------------------
+    def record(self, symbol: str, price: float, size: int, side: Optional[str] = None,
+               timestamp: Optional[datetime] = None) -> None:
+        """
+        Record a trade print.
+
+        Args:
+            symbol: Trading symbol
+            price: Trade price
+            size: Contracts traded
+            side: Aggressor side ('buy'/'sell'), if known
+            timestamp: Print time (defaults to now)
+        """
+        self.record_trade(Trade(symbol.upper(), _utc(timestamp or datetime.now(timezone.utc)),
+                                float(price), int(size), side))
+
+    def record_trade(self, trade: Trade) -> None:
+        """Record a Trade event as-is."""
+        with self._lock:
+            tape = self._tapes[trade.symbol]
+            if len(tape) == tape.maxlen:
+                evicted = tape[0]
+                if self.spill_enabled:
+                    self._pending.append(evicted)
+                else:
+                    self.prints_evicted += 1
+            tape.append(trade)
+            self.prints_recorded += 1
+            ts = _utc(trade.timestamp)
+            if self._last_time is None or ts > self._last_time:
+                self._last_time = ts
+
+    def on_market_event(self, event: MarketEvent) -> None:
+        """Market event listener: records Trade events, ignores everything else."""
+        if isinstance(event, Trade):
+            self.record_trade(event)
+
+    # ---------------------------
+    # Spill to disk
+    # ---------------------------
+    def start(self) -> None:
+        """Start the background spill writer."""
+        if not self.spill_enabled or (self._thread and self._thread.is_alive()):
+            return
+        self._stop.clear()
+        self._thread = threading.Thread(target=self._run, name="tape-recorder", daemon=True)
+        self._thread.start()
+        logger.info(f"📼 Tape recorder spilling to {self.spill_dir} (buffer {self.buffer_size} prints/symbol)")
+
+    def stop(self, timeout: float = 10.0) -> None:
+        """Write pending spills and stop the writer."""
+        self._stop.set()
+        if self._thread:
+            self._thread.join(timeout)
+            self._thread = None
+        self.flush()
+
+    def _run(self) -> None:
+        while not self._stop.wait(self.flush_interval):
+            self.flush()
+
+    def _spill_path(self, symbol: str, day) -> Path:
+        return self.spill_dir / symbol / f"tape-{day.strftime('%Y%m%d')}.jsonl"
+
+    def flush(self) -> int:
+        """
+        Append pending evicted prints to their spill files.
+
+        Returns:
+            int: Prints written
+        """
+        with self._spill_lock:
+            return self._write_pending()
+
+    def _write_pending(self) -> int:
+        batch: List[Trade] = []
+        while self._pending:
+            batch.append(self._pending.popleft())
+        if not batch:
+            return 0
+        files: Dict[Path, List[str]] = defaultdict(list)
+        for trade in batch:
+            day = _utc(trade.timestamp).astimezone(timezone.utc).date()
+            files[self._spill_path(trade.symbol, day)].append(json.dumps(trade.to_dict(), separators=(',', ':')))
+        written = 0
+        for path, lines in files.items():
+            try:
+                path.parent.mkdir(parents=True, exist_ok=True)
+                with open(path, 'a', encoding='utf-8') as f:
+                    f.write('\n'.join(lines) + '\n')
+                written += len(lines)
+            except OSError as e:
+                self.spill_errors += 1
+                logger.error(f"❌ Tape spill write failed ({path}): {e}")
+        self.prints_spilled += written
+        return written
+
+    def _read_spilled(self, symbol: str, start: datetime, end: datetime) -> List[Trade]:
+        """Spilled prints in [start, end] (pending prints are flushed first)."""
+        trades: List[Trade] = []
+        day = start.astimezone(timezone.utc).date()
+        with self._spill_lock:
+            self._write_pending()
+            while day <= end.astimezone(timezone.utc).date():
+                path = self._spill_path(symbol, day)
+                if path.exists():
+                    with open(path, encoding='utf-8') as f:
+                        for line in f:
+                            try:
+                                trade = market_event_from_dict(json.loads(line))
+                            except (ValueError, KeyError):
+                                continue
+                            if start <= _utc(trade.timestamp) <= end:
+                                trades.append(trade)
+                day += timedelta(days=1)
+        return trades
+
+    # ---------------------------
+    # Queries
+    # ---------------------------
+    def symbols(self) -> List[str]:
+        """Symbols with recorded prints."""
+        with self._lock:
+            return sorted(self._tapes)
+
+    def prints(self, symbol: Optional[str] = None, window: Window = None, now: Optional[datetime] = None,
+               include_spilled: bool = False) -> List[Trade]:
+        """
+        Prints within a window, oldest first.
+
+        Args:
+            symbol: Only this symbol (default: all symbols)
+            window: timedelta or seconds back from `now` (None = everything in memory)
+            now: Window end (default: latest recorded print time)
+            include_spilled: Also read spilled prints for the window (requires a window)
+
+        Returns:
+            List[Trade]: Prints ordered by timestamp
+        """
+        span = _as_timedelta(window)
+        with self._lock:
+            end = _utc(now) if now is not None else self._last_time
+            keys = [symbol.upper()] if symbol else list(self._tapes)
+            tapes = {key: list(self._tapes.get(key, ())) for key in keys}
+        if end is None:
+            return []
+        start = end - span if span is not None else None
+        result: List[Trade] = []
+        for key, tape in tapes.items():
+            selected = [t for t in tape if (start is None or _utc(t.timestamp) >= start) and _utc(t.timestamp) <= end]
+            if include_spilled and start is not None:
+                oldest = _utc(tape[0].timestamp) if tape else end
+                selected = [t for t in self._read_spilled(key, start, end) if _utc(t.timestamp) < oldest] + selected
+            result.extend(selected)
+        result.sort(key=lambda t: _utc(t.timestamp))
+        return result
+
+    def largest_trades(self, n: int, window: Window = None, symbol: Optional[str] = None,
+                       now: Optional[datetime] = None, include_spilled: bool = False) -> List[Trade]:
+        """Largest n prints by size in the window (largest first; earlier print wins ties)."""
+        prints = self.prints(symbol, window, now, include_spilled)
+        return heapq.nlargest(n, prints, key=lambda t: t.size)
+
+    def volume_in_window(self, window: Window, symbol: Optional[str] = None, side: Optional[str] = None,
+                         now: Optional[datetime] = None, include_spilled: bool = False) -> int:
+        """Contracts traded in the window, optionally only 'buy' or 'sell' aggressor prints."""
+        return sum(t.size for t in self.prints(symbol, window, now, include_spilled)
+                   if side is None or t.side == side)
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Recorder counters."""
+        with self._lock:
+            buffered = {symbol: len(tape) for symbol, tape in self._tapes.items()}
+        return {
+            "running": bool(self._thread and self._thread.is_alive()),
+            "buffer_size": self.buffer_size,
+            "buffered": buffered,
+            "recorded": self.prints_recorded,
+            "spill_dir": str(self.spill_dir) if self.spill_enabled else None,
+            "spilled": self.prints_spilled,
+            "pending_spill": len(self._pending),
+            "evicted": self.prints_evicted,
+            "spill_errors": self.spill_errors,
+            "last_print": self._last_time.isoformat() if self._last_time else None,
+        }
@@ -0,0 +1,227 @@
+"""
+Tick validation and outlier filtering.
+
+Runs before ticks reach the bar aggregator (and trade listeners) so bad
+prints don't poison bars and trigger false signals. Rejected ticks are
+dropped and counted per reason.
+
+Checks:
+- 'non_positive' : zero or negative price
+- 'crossed'      : quote with bid above ask
+- 'price_limit'  : outside configured exchange price limits for the symbol
+- 'price_jump'   : move from the last accepted price larger than
+                   TICK_MAX_JUMP_ATR x ATR (or TICK_MAX_JUMP_PCT of price while
+                   no ATR is available yet)
+- 'stale'        : timestamp older than TICK_MAX_AGE_SECONDS
+
+A genuine level shift (e.g. a news gap) is accepted once TICK_JUMP_CONFIRM
+consecutive jump-rejected ticks agree with each other, so the filter never
+locks a symbol out.
+
+ATR comes from completed bars fed through add_bar (bar_close_callback /
+on_bar_close compatible) on the TICK_ATR_TIMEFRAME timeframe.
+
+Configuration:
+- TICK_VALIDATION_ENABLED: Validate ticks in the bot (default true)
+- TICK_MAX_JUMP_ATR: Max move in ATRs between consecutive ticks (default 10)
+- TICK_MAX_JUMP_PCT: Max move in percent before ATR is known (default 2.0)
+- TICK_JUMP_CONFIRM: Consecutive agreeing jump ticks that confirm a new level (default 3)
+- TICK_MAX_AGE_SECONDS: Reject ticks older than this, 0 disables (default 30)
+- TICK_ATR_PERIOD: Bars in the ATR (default 14)
+- TICK_ATR_TIMEFRAME: Bar timeframe the ATR is built from (default '1m')
+"""
+
+import logging
+import os
+import threading
+from collections import defaultdict, deque
+from dataclasses import dataclass, field
+from datetime import datetime, timezone
+from enum import Enum
+from typing import Any, Dict, List, Optional, Tuple
+
+from core.bar_aggregator import Bar
+
+logger = logging.getLogger(__name__)
+
+# Rejections logged per symbol/reason before going quiet
+_LOG_LIMIT = 5
+
+
+class TickRejectReason(Enum):
+    """Why a tick was rejected."""
+    NON_POSITIVE = "non_positive"
+    CROSSED = "crossed"
+    PRICE_LIMIT = "price_limit"
+    PRICE_JUMP = "price_jump"
+    STALE = "stale"
+
+
+@dataclass
+class _SymbolState:
+    last_price: Optional[float] = None
+    prev_close: Optional[float] = None
+    true_ranges: deque = field(default_factory=deque)
+    pending_jumps: List[float] = field(default_factory=list)
+    accepted: int = 0
+    rejected: Dict[str, int] = field(default_factory=lambda: defaultdict(int))
+
+
+class TickValidator:
+    """
+    Validates ticks before aggregation.
+
+    Usage:
+        validator = TickValidator()
+        bar_aggregator.on_bar_close(validator.add_bar, timeframes=['1m'])
+        reason = validator.validate('MNQ', price, timestamp, bid=bid, ask=ask)
+        if reason is None:
+            bar_aggregator.add_quote('MNQ', price, volume, timestamp)
+    """
+
+    def __init__(self, max_jump_atr: Optional[float] = None, max_jump_pct: Optional[float] = None,
+                 jump_confirm: Optional[int] = None, max_age_seconds: Optional[float] = None,
+                 atr_period: Optional[int] = None, atr_timeframe: Optional[str] = None):
+        """
+        Initialize validator.
+
+        Args:
+            max_jump_atr: Max move in ATRs between ticks (env: TICK_MAX_JUMP_ATR)
+            max_jump_pct: Max move in percent until ATR is known (env: TICK_MAX_JUMP_PCT)
+            jump_confirm: Agreeing jump ticks that confirm a new price level (env: TICK_JUMP_CONFIRM)
+            max_age_seconds: Stale threshold, 0 disables (env: TICK_MAX_AGE_SECONDS)
+            atr_period: Bars in the ATR (env: TICK_ATR_PERIOD)
+            atr_timeframe: Timeframe of bars used for ATR (env: TICK_ATR_TIMEFRAME)
+        """
+        self.max_jump_atr = max_jump_atr if max_jump_atr is not None else float(os.getenv('TICK_MAX_JUMP_ATR', '10'))
+        self.max_jump_pct = max_jump_pct if max_jump_pct is not None else float(os.getenv('TICK_MAX_JUMP_PCT', '2.0'))
+        self.jump_confirm = jump_confirm if jump_confirm is not None else int(os.getenv('TICK_JUMP_CONFIRM', '3'))
+        self.max_age_seconds = max_age_seconds if max_age_seconds is not None else \
+            float(os.getenv('TICK_MAX_AGE_SECONDS', '30'))
+        self.atr_period = atr_period if atr_period is not None else int(os.getenv('TICK_ATR_PERIOD', '14'))
+        self.atr_timeframe = atr_timeframe or os.getenv('TICK_ATR_TIMEFRAME', '1m')
+        self.price_limits: Dict[str, Tuple[float, float]] = {}
+        self._states: Dict[str, _SymbolState] = defaultdict(_SymbolState)
+        self._lock = threading.Lock()
+
+    def set_price_limits(self, symbol: str, low: Optional[float], high: Optional[float]) -> None:
+        """Set exchange price limits for a symbol (None for both clears them)."""
+        key = symbol.upper()
+        if low is None and high is None:
+            self.price_limits.pop(key, None)
+        else:
+            self.price_limits[key] = (low if low is not None else 0.0, high if high is not None else float('inf'))
+
+    def add_bar(self, bar: Bar) -> None:
+        """Update ATR from a completed bar (ignores other timeframes)."""
+        if bar.timeframe != self.atr_timeframe:
+            return
+        with self._lock:
+            state = self._states[bar.symbol.upper()]
+            prev_close = state.prev_close if state.prev_close is not None else bar.close
+            state.true_ranges.append(max(bar.high, prev_close) - min(bar.low, prev_close))
+            while len(state.true_ranges) > self.atr_period:
+                state.true_ranges.popleft()
+            state.prev_close = bar.close
+
+    def get_atr(self, symbol: str) -> Optional[float]:
+        """ATR for a symbol once a full period of bars has been seen."""
+        with self._lock:
+            state = self._states.get(symbol.upper())
+            if state is None or len(state.true_ranges) < self.atr_period:
+                return None
+            return sum(state.true_ranges) / len(state.true_ranges)
+
+    def _max_jump(self, state: _SymbolState, reference: float) -> float:
+        if len(state.true_ranges) >= self.atr_period:
+            atr = sum(state.true_ranges) / len(state.true_ranges)
+            if atr > 0:
+                return self.max_jump_atr * atr
+        return reference * self.max_jump_pct / 100
+
+    def validate(self, symbol: str, price: Optional[float], timestamp: Optional[datetime] = None,
+                 bid: Optional[float] = None, ask: Optional[float] = None,
+                 now: Optional[datetime] = None) -> Optional[TickRejectReason]:
+        """
+        Check a tick and record the outcome.
+
+        Args:
+            symbol: Trading symbol
+            price: Trade or last price
+            timestamp: Tick time (naive values are treated as UTC)
+            bid: Best bid, if known
+            ask: Best ask, if known
+            now: Reference time for the stale check (defaults to current UTC time)
+
+        Returns:
+            None if the tick is valid, otherwise the rejection reason
+        """
+        key = symbol.upper()
+        with self._lock:
+            state = self._states[key]
+            reason = self._check(key, state, price, timestamp, bid, ask, now)
+            if reason is None:
+                state.accepted += 1
+                state.last_price = float(price)
+                return None
+            state.rejected[reason.value] += 1
+            count = state.rejected[reason.value]
+        if count <= _LOG_LIMIT:
+            logger.warning(f"🚫 Rejected {key} tick ({reason.value}): price={price} bid={bid} ask={ask} "
+                           f"ts={timestamp.isoformat() if timestamp else None}"
+                           f"{' - suppressing further logs' if count == _LOG_LIMIT else ''}")
+        return reason
+
+    def _check(self, key: str, state: _SymbolState, price: Optional[float], timestamp: Optional[datetime],
+               bid: Optional[float], ask: Optional[float], now: Optional[datetime]) -> Optional[TickRejectReason]:
+        """Run all checks (caller holds _lock)."""
+        if price is None or price <= 0:
+            return TickRejectReason.NON_POSITIVE
+        if bid is not None and ask is not None and bid > 0 and ask > 0 and bid > ask:
+            return TickRejectReason.CROSSED
+        if self.max_age_seconds > 0 and timestamp is not None:
+            if timestamp.tzinfo is None:
+                timestamp = timestamp.replace(tzinfo=timezone.utc)
+            if ((now or datetime.now(timezone.utc)) - timestamp).total_seconds() > self.max_age_seconds:
+                return TickRejectReason.STALE
+        limits = self.price_limits.get(key)
+        if limits and not (limits[0] <= price <= limits[1]):
+            return TickRejectReason.PRICE_LIMIT
+        if state.last_price is None:
+            return None
+        max_jump = self._max_jump(state, state.last_price)
+        if abs(price - state.last_price) <= max_jump:
+            state.pending_jumps.clear()
+            return None
+        # Several consecutive ticks agreeing on the new level: the market really moved
+        if state.pending_jumps and abs(price - state.pending_jumps[-1]) > max_jump:
+            state.pending_jumps.clear()
+        state.pending_jumps.append(price)
+        if len(state.pending_jumps) >= self.jump_confirm:
+            logger.warning(f"⚡ {key} price level shift confirmed: {state.last_price} -> {price}")
+            state.pending_jumps.clear()
+            return None
+        return TickRejectReason.PRICE_JUMP
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Accepted/rejected tick counts per symbol and in total."""
+        with self._lock:
+            symbols = {
+                symbol: {
+                    "accepted": state.accepted,
+                    "rejected": dict(state.rejected),
+                    "last_price": state.last_price,
+                    "atr": (sum(state.true_ranges) / len(state.true_ranges)
+                            if len(state.true_ranges) >= self.atr_period else None),
+                }
+                for symbol, state in self._states.items()
+            }
+        totals: Dict[str, int] = defaultdict(int)
+        for stats in symbols.values():
+            for reason, count in stats["rejected"].items():
+                totals[reason] += count
+        return {
+            "accepted": sum(s["accepted"] for s in symbols.values()),
+            "rejected": dict(totals),
+            "symbols": symbols,
+        }
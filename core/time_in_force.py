@@ -0,0 +1,84 @@
+"""
+Time-in-force for order payloads.
+
+The TopStepX (ProjectX Gateway) /api/Order/place payload has no time-in-force
+field: every order is placed with the venue default and works as a DAY
+order. Callers may still pass DAY, GTC, IOC or FOK (case-insensitive), but
+only DAY is accepted. The others are rejected before the order goes out
+rather than silently placed as DAY orders, which would leave an IOC/FOK
+order resting or drop a GTC order at the session end. For a limit or stop
+that should only work briefly, use expire_after_secs instead.
+
+Nothing is added to the payload; the accepted TIF is only echoed back in the
+order tracker.
+"""
+
+from enum import Enum
+from typing import Any, Dict, FrozenSet, Optional
+
+
+class TimeInForce(Enum):
+    """How long an order stays working."""
+    DAY = "DAY"  # Until the end of the trading session
+    GTC = "GTC"  # Until cancelled
+    IOC = "IOC"  # Fill what is available now, cancel the rest
+    FOK = "FOK"  # Fill completely now or cancel
+
+
+# What the Order/place API can express (no time-in-force field: venue default only)
+SUPPORTED_TIME_IN_FORCE: FrozenSet[TimeInForce] = frozenset({TimeInForce.DAY})
+
+ORDER_TYPE_NAMES: Dict[int, str] = {
+    1: 'limit', 2: 'market', 4: 'stop', 5: 'trailing stop', 6: 'join bid', 7: 'join ask',
+}
+
+
+def parse_time_in_force(value: Any) -> Optional[TimeInForce]:
+    """
+    Parse a TIF name or TimeInForce (None/'' for no TIF).
+
+    Raises:
+        ValueError: Unknown TIF
+    """
+    if value is None or value == '':
+        return None
+    if isinstance(value, TimeInForce):
+        return value
+    if isinstance(value, str):
+        try:
+            return TimeInForce(value.strip().upper())
+        except ValueError:
+            pass
+    raise ValueError(f"Unsupported time in force {value!r} (use {', '.join(t.value for t in TimeInForce)})")
+
+
+def time_in_force_name(value: Any) -> Optional[str]:
+    """DAY/GTC/IOC/FOK for a TIF name (None if missing or unknown), for echoing back."""
+    try:
+        tif = parse_time_in_force(value)
+    except ValueError:
+        return None
+    return tif.value if tif else None
+
+
+def check_time_in_force(payload: Dict[str, Any], value: Any) -> Optional[TimeInForce]:
+    """
+    Validate a TIF for an order payload (the payload is not changed).
+
+    Args:
+        payload: /api/Order/place payload (its type names the order in errors)
+        value: TIF name (None: venue default)
+
+    Returns:
+        The accepted TIF, or None
+
+    Raises:
+        ValueError: Unknown TIF or one the API cannot express
+    """
+    tif = parse_time_in_force(value)
+    if tif is None or tif in SUPPORTED_TIME_IN_FORCE:
+        return tif
+    order_type = payload.get('type')
+    name = ORDER_TYPE_NAMES.get(int(order_type), f"type {order_type}") if order_type is not None else 'these'
+    raise ValueError(f"Time in force {tif.value} is not supported by the TopStepX API for {name} orders "
+                     f"(orders work as DAY orders; use expire_after_secs to limit how long one works)")
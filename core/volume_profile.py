@@ -0,0 +1,281 @@
+"""
+Composite Volume Profile
+
+Accumulates volume-at-price per symbol across the current session and a
+configurable number of prior sessions, with exponential decay so older
+sessions count less. The composite is maintained incrementally:
+- each trade adds its volume to one price bucket
+- a session rollover scales the composite by the decay factor and subtracts
+  the session that falls out of the window
+
+Symbols are normalized to their root (CON.F.US.MNQ.Z25 / MNQZ25 -> MNQ) so a
+profile continues across contract rolls, and prices are bucketed on the
+instrument's tick grid.
+
+Queryable levels used by strategies:
+- POC (point of control) of the composite
+- Value area (default 70% of volume)
+- HVN / LVN (local volume maxima / minima)
+- Naked POCs (prior session POCs that price has not revisited)
+"""
+
+import logging
+import os
+import re
+import threading
+from collections import defaultdict, deque
+from dataclasses import dataclass, field
+from datetime import datetime, timezone
+from typing import Deque, Dict, List, Optional, Tuple
+from zoneinfo import ZoneInfo
+
+from core.vwap import parse_anchor, session_anchor
+
+logger = logging.getLogger(__name__)
+
+# Root symbol followed by a futures month code and year (MNQZ25, ESH6)
+_CONTRACT_SUFFIX = re.compile(r'^([A-Z0-9]+?)([FGHJKMNQUVXZ]\d{1,2})$')
+
+
+def normalize_symbol(symbol: str) -> str:
+    """Map a contract ID or dated contract to its root symbol (CON.F.US.MNQ.Z25 -> MNQ)."""
+    symbol = symbol.strip().upper()
+    if '.' in symbol:
+        parts = symbol.split('.')
+        return parts[-2] if len(parts) >= 2 else symbol
+    match = _CONTRACT_SUFFIX.match(symbol)
+    return match.group(1) if match else symbol
+
+
+@dataclass
+class NakedPOC:
+    """A prior session's point of control that has not been revisited."""
+    symbol: str
+    session_start: datetime
+    price: float
+    volume: float
+
+
+@dataclass
+class _SessionProfile:
+    session_start: datetime
+    volumes: Dict[int, float] = field(default_factory=lambda: defaultdict(float))  # tick bucket -> volume
+
+    def poc_bucket(self) -> Optional[int]:
+        if not self.volumes:
+            return None
+        return max(self.volumes.items(), key=lambda kv: (kv[1], -kv[0]))[0]
+
+
+@dataclass
+class _SymbolState:
+    current: _SessionProfile
+    history: Deque[_SessionProfile]
+    composite: Dict[int, float] = field(default_factory=lambda: defaultdict(float))
+    naked: List[Tuple[datetime, int, float]] = field(default_factory=list)  # (session_start, bucket, volume)
+    last_bucket: Optional[int] = None
+
+
+class CompositeVolumeProfile:
+    """
+    Multi-session volume-at-price with decay weighting.
+
+    Usage:
+        profile = CompositeVolumeProfile(sessions=5, decay=0.8, tick_sizes={'MNQ': 0.25})
+        profile.add_trade('CON.F.US.MNQ.Z25', 15000.25, 3, ts)
+        profile.poc('MNQ'); profile.hvn_lvn('MNQ'); profile.naked_pocs('MNQ')
+    """
+
+    def __init__(self, sessions: Optional[int] = None, decay: Optional[float] = None,
+                 tick_sizes: Optional[Dict[str, float]] = None, default_tick_size: float = 0.25,
+                 anchor: Optional[str] = None, tz: Optional[str] = None):
+        """
+        Initialize composite profile.
+
+        Args:
+            sessions: Prior sessions included besides the current one (env: VP_COMPOSITE_SESSIONS, default 5)
+            decay: Weight multiplier per session of age, 0-1 (env: VP_DECAY, default 0.85)
+            tick_sizes: {root symbol: tick size} used for price buckets
+            default_tick_size: Tick size for symbols not in tick_sizes
+            anchor: Session anchor 'globex', 'rth', 'midnight' or 'HH:MM' (env: VP_SESSION_ANCHOR, default 'globex')
+            tz: Exchange timezone (env: VP_TIMEZONE, default 'US/Eastern')
+        """
+        self.sessions = sessions if sessions is not None else int(os.getenv('VP_COMPOSITE_SESSIONS', '5'))
+        self.decay = decay if decay is not None else float(os.getenv('VP_DECAY', '0.85'))
+        if self.sessions < 0:
+            raise ValueError(f"sessions must be >= 0, got {self.sessions}")
+        if not 0 < self.decay <= 1:
+            raise ValueError(f"decay must be in (0, 1], got {self.decay}")
+        self.tick_sizes = {normalize_symbol(k): float(v) for k, v in (tick_sizes or {}).items()}
+        self.default_tick_size = default_tick_size
+        self.anchor_hour, self.anchor_minute = parse_anchor(anchor or os.getenv('VP_SESSION_ANCHOR', 'globex'))
+        self.tz = ZoneInfo(tz or os.getenv('VP_TIMEZONE', 'US/Eastern'))
+        self._states: Dict[str, _SymbolState] = {}
+        self._lock = threading.Lock()
+
+    def _tick_size(self, root: str) -> float:
+        return self.tick_sizes.get(root, self.default_tick_size)
+
+    def _bucket(self, root: str, price: float) -> int:
+        return int(round(price / self._tick_size(root)))
+
+    def _price(self, root: str, bucket: int) -> float:
+        return round(bucket * self._tick_size(root), 10)
+
+    def add_trade(self, symbol: str, price: float, volume: int, timestamp: Optional[datetime] = None) -> None:
+        """
+        Add traded volume at a price.
+
+        Args:
+            symbol: Symbol or contract ID (normalized to its root)
+            price: Trade price
+            volume: Contracts traded
+            timestamp: Trade time (defaults to now)
+        """
+        if timestamp is None:
+            timestamp = datetime.now(timezone.utc)
+        root = normalize_symbol(symbol)
+        start = session_anchor(timestamp, self.anchor_hour, self.anchor_minute, self.tz)
+        bucket = self._bucket(root, price)
+
+        with self._lock:
+            state = self._states.get(root)
+            if state is None:
+                state = _SymbolState(current=_SessionProfile(start), history=deque())
+                self._states[root] = state
+            elif start > state.current.session_start:
+                self._roll_session(root, state, start)
+            elif start < state.current.session_start:
+                return  # Late trade from a closed session
+
+            self._touch_naked(state, bucket)
+            if volume > 0:
+                state.current.volumes[bucket] += volume
+                state.composite[bucket] += volume
+
+    def add_bar(self, bar) -> None:
+        """Add a completed bar's volume at its close (bar_close_callback compatible)."""
+        self.add_trade(bar.symbol, bar.close, bar.volume, bar.timestamp)
+
+    def _roll_session(self, root: str, state: _SymbolState, new_start: datetime) -> None:
+        """Age the composite by one session (caller holds _lock)."""
+        finished = state.current
+        poc = finished.poc_bucket()
+        if poc is not None:
+            state.naked.append((finished.session_start, poc, finished.volumes[poc]))
+
+        # Every existing session ages by one: scale weights by decay
+        for bucket in list(state.composite):
+            state.composite[bucket] *= self.decay
+        state.history.append(finished)
+
+        # Drop the session that is now beyond the window
+        if len(state.history) > self.sessions:
+            dropped = state.history.popleft()
+            weight = self.decay ** (self.sessions + 1)
+            for bucket, volume in dropped.volumes.items():
+                remaining = state.composite.get(bucket, 0.0) - volume * weight
+                if remaining <= 1e-9:
+                    state.composite.pop(bucket, None)
+                else:
+                    state.composite[bucket] = remaining
+            state.naked = [n for n in state.naked if n[0] >= state.history[0].session_start] if state.history else []
+
+        state.current = _SessionProfile(new_start)
+        state.last_bucket = None  # A gap between sessions does not trade through levels
+        logger.debug(f"📊 Volume profile for {root} rolled to session {new_start.isoformat()}")
+
+    def _touch_naked(self, state: _SymbolState, bucket: int) -> None:
+        """Remove naked POCs traded through since the previous trade (caller holds _lock)."""
+        if state.naked:
+            low = min(bucket, state.last_bucket) if state.last_bucket is not None else bucket
+            high = max(bucket, state.last_bucket) if state.last_bucket is not None else bucket
+            state.naked = [n for n in state.naked if not (low <= n[1] <= high)]
+        state.last_bucket = bucket
+
+    def get_composite(self, symbol: str) -> Dict[float, float]:
+        """Weighted volume-at-price, ascending by price."""
+        root = normalize_symbol(symbol)
+        with self._lock:
+            state = self._states.get(root)
+            if state is None:
+                return {}
+            items = sorted(state.composite.items())
+        return {self._price(root, bucket): volume for bucket, volume in items}
+
+    def poc(self, symbol: str) -> Optional[float]:
+        """Composite point of control (highest weighted volume price)."""
+        profile = self.get_composite(symbol)
+        if not profile:
+            return None
+        return max(profile.items(), key=lambda kv: (kv[1], -kv[0]))[0]
+
+    def value_area(self, symbol: str, pct: float = 0.7) -> Optional[Tuple[float, float]]:
+        """
+        Value area (low, high) containing pct of composite volume, expanded from the POC.
+        """
+        profile = self.get_composite(symbol)
+        if not profile:
+            return None
+        prices = list(profile)
+        volumes = [profile[p] for p in prices]
+        target = sum(volumes) * pct
+        lo = hi = volumes.index(max(volumes))
+        total = volumes[lo]
+        while total < target and (lo > 0 or hi < len(prices) - 1):
+            below = volumes[lo - 1] if lo > 0 else -1.0
+            above = volumes[hi + 1] if hi < len(prices) - 1 else -1.0
+            if above >= below:
+                hi += 1
+                total += above
+            else:
+                lo -= 1
+                total += below
+        return prices[lo], prices[hi]
+
+    def hvn_lvn(self, symbol: str, window: int = 2) -> Dict[str, List[float]]:
+        """
+        High/low volume nodes: buckets that are the max/min of their ±window neighbourhood.
+
+        Empty buckets inside the traded range count as zero volume, so gaps show up as LVNs.
+
+        Returns:
+            {"hvn": [prices], "lvn": [prices]}
+        """
+        root = normalize_symbol(symbol)
+        with self._lock:
+            state = self._states.get(root)
+            composite = dict(state.composite) if state else {}
+        if not composite:
+            return {"hvn": [], "lvn": []}
+
+        lo_bucket, hi_bucket = min(composite), max(composite)
+        buckets = list(range(lo_bucket, hi_bucket + 1))
+        volumes = [composite.get(b, 0.0) for b in buckets]
+        hvn: List[float] = []
+        lvn: List[float] = []
+        for i, volume in enumerate(volumes):
+            neighbourhood = volumes[max(0, i - window):i + window + 1]
+            if len(neighbourhood) < 2 * window + 1:
+                continue  # Edges of the profile are not nodes
+            if volume == max(neighbourhood) and volume > min(neighbourhood):
+                hvn.append(self._price(root, buckets[i]))
+            elif volume == min(neighbourhood) and volume < max(neighbourhood):
+                lvn.append(self._price(root, buckets[i]))
+        return {"hvn": hvn, "lvn": lvn}
+
+    def naked_pocs(self, symbol: str) -> List[NakedPOC]:
+        """Prior session POCs within the window that price has not traded back through."""
+        root = normalize_symbol(symbol)
+        with self._lock:
+            state = self._states.get(root)
+            naked = list(state.naked) if state else []
+        return [NakedPOC(root, start, self._price(root, bucket), volume) for start, bucket, volume in naked]
+
+    def reset(self, symbol: Optional[str] = None):
+        """Clear profile state for a symbol or all symbols."""
+        with self._lock:
+            if symbol is None:
+                self._states.clear()
+            else:
+                self._states.pop(normalize_symbol(symbol), None)
@@ -0,0 +1,217 @@
+"""
+Session-Anchored VWAP Calculator
+
+Streaming volume-weighted average price per symbol, reset at a configurable
+session anchor, with standard deviation bands. Each tick is an O(1) update of
+running sums, so querying the current VWAP is effectively free.
+
+Anchors:
+- 'rth'      : 09:30 exchange time (regular trading hours open)
+- 'globex'   : 18:00 exchange time (futures session open)
+- 'midnight' : 00:00 exchange time
+- 'HH:MM'    : any custom time
+
+Ticks before the anchor on a given day belong to the previous anchored
+session (e.g., overnight trades extend the prior RTH VWAP until 09:30).
+"""
+
+import logging
+import math
+import os
+import threading
+from dataclasses import dataclass, field
+from datetime import datetime, timedelta, timezone
+from typing import Dict, Iterable, Optional, Tuple
+from zoneinfo import ZoneInfo
+
+logger = logging.getLogger(__name__)
+
+ANCHOR_PRESETS = {
+    'rth': '09:30',
+    'globex': '18:00',
+    'midnight': '00:00',
+}
+
+
+def parse_anchor(anchor: str) -> Tuple[int, int]:
+    """
+    Parse a session anchor preset or 'HH:MM' into (hour, minute).
+
+    Raises:
+        ValueError: If the anchor is not a preset or valid time
+    """
+    anchor_time = ANCHOR_PRESETS.get(anchor.strip().lower(), anchor.strip())
+    try:
+        hour, minute = (int(part) for part in anchor_time.split(':'))
+        if not (0 <= hour < 24 and 0 <= minute < 60):
+            raise ValueError
+    except ValueError:
+        raise ValueError(f"Invalid session anchor '{anchor}'. Use {sorted(ANCHOR_PRESETS)} or 'HH:MM'")
+    return hour, minute
+
+
+def session_anchor(timestamp: datetime, hour: int, minute: int, tz: ZoneInfo) -> datetime:
+    """
+    Most recent HH:MM (exchange time) at or before a timestamp, returned in UTC.
+
+    Args:
+        timestamp: Timestamp (naive values are treated as UTC)
+        hour: Anchor hour in exchange time
+        minute: Anchor minute
+        tz: Exchange timezone
+    """
+    if timestamp.tzinfo is None:
+        timestamp = timestamp.replace(tzinfo=timezone.utc)
+    local = timestamp.astimezone(tz)
+    anchor_local = local.replace(hour=hour, minute=minute, second=0, microsecond=0)
+    if anchor_local > local:
+        # Wall-clock arithmetic keeps the anchor at HH:MM across DST changes
+        anchor_local = (anchor_local.replace(tzinfo=None) - timedelta(days=1)).replace(tzinfo=tz)
+    return anchor_local.astimezone(timezone.utc)
+
+
+@dataclass
+class VWAPSnapshot:
+    """Point-in-time VWAP state for a symbol."""
+    symbol: str
+    anchor_start: datetime
+    vwap: float
+    std_dev: float
+    volume: int
+    bands: Dict[float, Tuple[float, float]] = field(default_factory=dict)  # multiplier -> (lower, upper)
+
+    def to_dict(self) -> Dict:
+        """Convert to JSON-friendly dictionary."""
+        return {
+            "symbol": self.symbol,
+            "anchor_start": self.anchor_start.isoformat(),
+            "vwap": self.vwap,
+            "std_dev": self.std_dev,
+            "volume": self.volume,
+            "bands": {str(k): {"lower": lo, "upper": hi} for k, (lo, hi) in self.bands.items()},
+        }
+
+
+@dataclass
+class _VWAPState:
+    anchor_start: datetime
+    cum_pv: float = 0.0
+    cum_pv2: float = 0.0
+    cum_volume: int = 0
+
+
+class SessionVWAP:
+    """
+    Streaming session-anchored VWAP with standard deviation bands.
+
+    Usage:
+        vwap = SessionVWAP(anchor='rth', band_multipliers=(1.0, 2.0))
+        vwap.update('MNQ', 15000.25, volume=3, timestamp=ts)
+        vwap.get_vwap('MNQ')
+        vwap.get_snapshot('MNQ').bands[2.0]
+
+        # Or driven by completed bars (typical price x bar volume)
+        bar_aggregator = BarAggregator(default_timeframes=['1m'], bar_close_callback=vwap.add_bar)
+    """
+
+    def __init__(self, anchor: Optional[str] = None, tz: Optional[str] = None,
+                 band_multipliers: Iterable[float] = (1.0, 2.0, 3.0)):
+        """
+        Initialize VWAP calculator.
+
+        Args:
+            anchor: 'rth', 'globex', 'midnight' or 'HH:MM' (env: VWAP_ANCHOR, default 'rth')
+            tz: Exchange timezone for the anchor (env: VWAP_TIMEZONE, default 'US/Eastern')
+            band_multipliers: Standard deviation multipliers for bands
+        """
+        anchor = (anchor or os.getenv('VWAP_ANCHOR', 'rth')).strip().lower()
+        self.anchor = anchor
+        self.anchor_hour, self.anchor_minute = parse_anchor(anchor)
+        self.tz = ZoneInfo(tz or os.getenv('VWAP_TIMEZONE', 'US/Eastern'))
+        self.band_multipliers = tuple(sorted(float(m) for m in band_multipliers))
+        self._states: Dict[str, _VWAPState] = {}
+        self._lock = threading.Lock()
+
+    def session_anchor(self, timestamp: datetime) -> datetime:
+        """
+        Most recent anchor at or before a timestamp (returned in UTC).
+
+        Args:
+            timestamp: Timestamp (naive values are treated as UTC)
+        """
+        return session_anchor(timestamp, self.anchor_hour, self.anchor_minute, self.tz)
+
+    def update(self, symbol: str, price: float, volume: int = 1,
+               timestamp: Optional[datetime] = None) -> Optional[float]:
+        """
+        Add a trade.
+
+        Args:
+            symbol: Trading symbol
+            price: Trade price
+            volume: Trade size (zero-volume updates are ignored)
+            timestamp: Trade time (defaults to now)
+
+        Returns:
+            Current VWAP (None until the session has volume)
+        """
+        if timestamp is None:
+            timestamp = datetime.now(timezone.utc)
+        symbol_key = symbol.upper()
+        anchor_start = self.session_anchor(timestamp)
+
+        with self._lock:
+            state = self._states.get(symbol_key)
+            if state is None or anchor_start > state.anchor_start:
+                if state is not None:
+                    logger.debug(f"VWAP session reset for {symbol_key} at {anchor_start.isoformat()}")
+                state = _VWAPState(anchor_start=anchor_start)
+                self._states[symbol_key] = state
+            elif anchor_start < state.anchor_start:
+                return self._vwap(state)  # Late tick from a previous session
+
+            if volume > 0:
+                state.cum_pv += price * volume
+                state.cum_pv2 += price * price * volume
+                state.cum_volume += volume
+            return self._vwap(state)
+
+    def add_bar(self, bar) -> Optional[float]:
+        """
+        Add a completed bar using typical price (H+L+C)/3 weighted by bar volume.
+
+        Signature matches BarAggregator's bar_close_callback.
+        """
+        typical = (bar.high + bar.low + bar.close) / 3
+        return self.update(bar.symbol, typical, bar.volume, bar.timestamp)
+
+    @staticmethod
+    def _vwap(state: _VWAPState) -> Optional[float]:
+        return state.cum_pv / state.cum_volume if state.cum_volume else None
+
+    def get_vwap(self, symbol: str) -> Optional[float]:
+        """Current VWAP for a symbol (None if no volume this session)."""
+        with self._lock:
+            state = self._states.get(symbol.upper())
+            return self._vwap(state) if state else None
+
+    def get_snapshot(self, symbol: str) -> Optional[VWAPSnapshot]:
+        """Current VWAP, standard deviation and bands for a symbol."""
+        with self._lock:
+            state = self._states.get(symbol.upper())
+            if state is None or not state.cum_volume:
+                return None
+            vwap = state.cum_pv / state.cum_volume
+            variance = max(0.0, state.cum_pv2 / state.cum_volume - vwap * vwap)
+            anchor_start, volume = state.anchor_start, state.cum_volume
+        std_dev = math.sqrt(variance)
+        bands = {m: (vwap - m * std_dev, vwap + m * std_dev) for m in self.band_multipliers}
+        return VWAPSnapshot(symbol.upper(), anchor_start, vwap, std_dev, volume, bands)
+
+    def reset(self, symbol: Optional[str] = None):
+        """Clear VWAP state for a symbol or all symbols."""
+        with self._lock:
+            if symbol is None:
+                self._states.clear()
+            else:
+                self._states.pop(symbol.upper(), None)
@@ -0,0 +1,64 @@
+"""
+SignalR WebSocket client for the TopStepX real-time hubs.
+
+Modules:
+- protocol: SignalR JSON hub protocol (handshake, record-separator framing, message types)
+- client: asyncio hub connection (negotiate, handshake, invocations, keep-alive pings,
+  auto-reconnect with subscription replay)
+- market_hub: GatewayQuote/GatewayTrade/GatewayDepth invocations as typed MarketEvents,
+  stale feed watchdog
+- subscriptions: reference-counted quote/trade/depth subscriptions shared by consumers
+- user_hub: account/order/position/fill updates as typed events
+- channels: bounded per-consumer channels with block/drop-oldest/conflate overflow policies
+- capture: raw inbound frame recording to compressed capture files and offline replay
+- latency: exchange-to-receive latency percentiles and clock-skew estimate
+- manager: user hub plus symbol-sharded market hubs behind one event feed
+- raw: passthrough client handing raw frames of any ws/wss feed to callbacks
+
+This package sits under core/ so it does not shadow the third-party
+`websocket` (websocket-client) module that signalrcore imports.
+"""
+
+from core.websocket.capture import CapturedFrame, FrameRecorder, iter_capture, replay_capture
+from core.websocket.channels import BoundedChannel, ChannelFanout, EventStream, OverflowPolicy
+from core.websocket.client import ConnectionState, FailoverEvent, HubConnectionError, SignalRClient
+from core.websocket.latency import FeedLatencyMonitor
+from core.websocket.manager import WebSocketManager
+from core.websocket.market_hub import (
+    FeedAlert, FeedWatchdog, MarketHubClient, market_events_from_invocation, symbol_from_contract_id,
+)
+from core.websocket.protocol import HubMessage, MessageType
+from core.websocket.raw import RawWebSocketClient
+from core.websocket.subscriptions import SubscriptionManager
+from core.websocket.user_hub import AccountUpdate, OrderUpdate, PositionUpdate, UserHubClient, UserTrade
+
+__all__ = [
+    'AccountUpdate',
+    'BoundedChannel',
+    'CapturedFrame',
+    'ChannelFanout',
+    'ConnectionState',
+    'EventStream',
+    'FailoverEvent',
+    'FeedAlert',
+    'FeedLatencyMonitor',
+    'FeedWatchdog',
+    'FrameRecorder',
+    'HubConnectionError',
+    'HubMessage',
+    'MarketHubClient',
+    'MessageType',
+    'OrderUpdate',
+    'OverflowPolicy',
+    'PositionUpdate',
+    'RawWebSocketClient',
+    'SignalRClient',
+    'SubscriptionManager',
+    'UserHubClient',
+    'UserTrade',
+    'WebSocketManager',
+    'iter_capture',
+    'market_events_from_invocation',
+    'replay_capture',
+    'symbol_from_contract_id',
+]
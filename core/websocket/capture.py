@@ -0,0 +1,299 @@
+"""
+Raw hub frame capture and replay.
+
+FrameRecorder appends every inbound WebSocket frame, exactly as received and
+stamped with its receive time, to a compressed JSON Lines capture file. Like
+EventExporter, the hot path only appends to an in-memory buffer; encoding,
+compression and file I/O run on a background writer thread, and frames are
+dropped and counted (never blocking the reader) if the buffer is full.
+
+Capture records:
+- {"capture": 1, "hub": ..., "started": ...}: header, first line of every file
+- {"t": <receive time, epoch seconds>, "f": <frame text>}: inbound frame
+  ("b": base64 instead of "f" for binary frames, "h": 1 on the handshake response)
+
+replay_capture() feeds a capture back through SignalRClient.feed_frame(), the
+same handshake/parse/dispatch path live frames take, so handlers, MarketHubClient
+events and parser bugs reproduce offline exactly as they happened.
+
+Usage:
+    SIGNALR_RECORD=true python trading_bot.py   # capture the live market hub
+    hub = MarketHubClient('offline')
+    hub.add_listener(print)
+    await replay_capture('captures/capture-market-20251119-143000.jsonl.gz', hub.client)
+
+Configuration:
+- SIGNALR_RECORD: Record inbound frames of every SignalRClient (default false)
+- SIGNALR_RECORD_DIR: Capture directory (default 'captures')
+- SIGNALR_RECORD_COMPRESSION: 'gzip', 'zstd' or 'none' (default 'gzip')
+- SIGNALR_RECORD_ROTATE_MB: Rotate after this many uncompressed MB (default 256)
+- SIGNALR_RECORD_BUFFER_SIZE: Max frames buffered before dropping (default 100000)
+"""
+
+import asyncio
+import base64
+import gzip
+import json
+import logging
+import os
+import threading
+import time
+from collections import deque
+from dataclasses import dataclass
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import Any, Dict, IO, Iterable, Iterator, Optional, Union
+
+from core.event_exporter import COMPRESSION_SUFFIXES, _open_compressed
+
+logger = logging.getLogger(__name__)
+
+CAPTURE_VERSION = 1
+
+
+@dataclass(frozen=True)
+class CapturedFrame:
+    """One recorded inbound frame."""
+    received_at: float  # Epoch seconds
+    data: Union[str, bytes]
+    handshake: bool = False
+
+    @property
+    def received_datetime(self) -> datetime:
+        return datetime.fromtimestamp(self.received_at, tz=timezone.utc)
+
+
+class FrameRecorder:
+    """
+    Background writer of raw inbound frames.
+
+    Usage:
+        recorder = FrameRecorder(directory='captures', hub='market')
+        client = SignalRClient(url, recorder=recorder)   # started/stopped with the client
+    """
+
+    def __init__(self, directory: Union[str, Path, None] = None, hub: str = 'hub',
+                 compression: Optional[str] = None, rotate_bytes: Optional[int] = None,
+                 buffer_size: Optional[int] = None, flush_interval: float = 1.0,
+                 compression_level: Optional[int] = None):
+        """
+        Initialize recorder.
+
+        Args:
+            directory: Capture directory (env: SIGNALR_RECORD_DIR)
+            hub: Hub name, used in file names and the header
+            compression: 'gzip', 'zstd' or 'none' (env: SIGNALR_RECORD_COMPRESSION)
+            rotate_bytes: Rotate after this many uncompressed bytes (env: SIGNALR_RECORD_ROTATE_MB)
+            buffer_size: Max buffered frames before new ones are dropped (env: SIGNALR_RECORD_BUFFER_SIZE)
+            flush_interval: Seconds between writer flushes
+            compression_level: zstd/gzip level (library default if None)
+
+        Raises:
+            ValueError: Unknown compression, or zstd requested without `zstandard` installed
+        """
+        self.directory = Path(directory or os.getenv('SIGNALR_RECORD_DIR', 'captures'))
+        self.hub = hub
+        self.compression = (compression or os.getenv('SIGNALR_RECORD_COMPRESSION', 'gzip')).strip().lower()
+        if self.compression not in COMPRESSION_SUFFIXES:
+            raise ValueError(f"Unknown capture compression '{self.compression}'. "
+                             f"Use one of {sorted(COMPRESSION_SUFFIXES)}")
+        if self.compression == 'zstd':
+            try:
+                import zstandard  # noqa: F401
+            except ImportError:
+                raise ValueError("zstd captures require the 'zstandard' package")
+        self.compression_level = compression_level
+        self.rotate_bytes = rotate_bytes if rotate_bytes is not None else \
+            int(float(os.getenv('SIGNALR_RECORD_ROTATE_MB', '256')) * 1024 * 1024)
+        self.buffer_size = buffer_size if buffer_size is not None else \
+            int(os.getenv('SIGNALR_RECORD_BUFFER_SIZE', '100000'))
+        self.flush_interval = flush_interval
+
+        self._buffer: deque = deque()
+        self._stop = threading.Event()
+        self._thread: Optional[threading.Thread] = None
+        self._write_lock = threading.Lock()
+        self._file: Optional[IO[bytes]] = None
+        self._file_path: Optional[Path] = None
+        self._file_bytes = 0
+        self.frames_written = 0
+        self.frames_dropped = 0
+        self.bytes_written = 0
+        self.files_written = 0
+        self.write_errors = 0
+
+    def record(self, frame: Union[str, bytes], received_at: Optional[float] = None, handshake: bool = False) -> None:
+        """Buffer an inbound frame (never blocks)."""
+        if len(self._buffer) >= self.buffer_size:
+            self.frames_dropped += 1
+            return
+        self._buffer.append((received_at if received_at is not None else time.time(), frame, handshake))
+
+    def start(self) -> None:
+        """Start the background writer thread."""
+        if self._thread and self._thread.is_alive():
+            return
+        self.directory.mkdir(parents=True, exist_ok=True)
+        self._stop.clear()
+        self._thread = threading.Thread(target=self._run, name=f"capture-{self.hub}", daemon=True)
+        self._thread.start()
+        logger.info(f"📼 Recording {self.hub} hub frames to {self.directory} (compression={self.compression})")
+
+    def stop(self, timeout: float = 10.0) -> None:
+        """Write buffered frames, close the current file and stop the writer."""
+        self._stop.set()
+        if self._thread:
+            self._thread.join(timeout)
+            self._thread = None
+        self.flush()
+        self._close_file()
+
+    def _run(self) -> None:
+        while not self._stop.wait(self.flush_interval):
+            self.flush()
+        self.flush()
+
+    def flush(self) -> None:
+        """Encode and write everything currently buffered as one batch."""
+        with self._write_lock:
+            lines = []
+            while self._buffer:
+                lines.append(self._encode(*self._buffer.popleft()))
+            if not lines:
+                return
+            data = ('\n'.join(lines) + '\n').encode('utf-8')
+            try:
+                self._ensure_file(len(data))
+                self._file.write(data)
+                self._file.flush()
+            except Exception as e:
+                self.write_errors += 1
+                self.frames_dropped += len(lines)
+                logger.error(f"❌ Capture write failed ({self._file_path}): {e}")
+                self._close_file()
+                return
+            self._file_bytes += len(data)
+            self.bytes_written += len(data)
+            self.frames_written += len(lines)
+
+    @staticmethod
+    def _encode(received_at: float, frame: Union[str, bytes], handshake: bool) -> str:
+        record: Dict[str, Any] = {'t': round(received_at, 6)}
+        if isinstance(frame, str):
+            record['f'] = frame
+        else:
+            record['b'] = base64.b64encode(bytes(frame)).decode('ascii')
+        if handshake:
+            record['h'] = 1
+        return json.dumps(record, separators=(',', ':'))
+
+    def _ensure_file(self, incoming: int) -> None:
+        """Open a file (with its header line), rotating on size."""
+        if self._file is not None and self._file_bytes + incoming > self.rotate_bytes:
+            self._close_file()
+        if self._file is not None:
+            return
+        now = datetime.now(timezone.utc)
+        name = f"capture-{self.hub}-{now.strftime('%Y%m%d-%H%M%S')}"
+        suffix = COMPRESSION_SUFFIXES[self.compression]
+        path = self.directory / f"{name}.jsonl{suffix}"
+        counter = 1
+        while path.exists():
+            path = self.directory / f"{name}-{counter}.jsonl{suffix}"
+            counter += 1
+        self._file = _open_compressed(path, self.compression, self.compression_level)
+        header = json.dumps({'capture': CAPTURE_VERSION, 'hub': self.hub, 'started': now.isoformat()})
+        self._file.write((header + '\n').encode('utf-8'))
+        self._file_path = path
+        self._file_bytes = 0
+        self.files_written += 1
+        logger.debug(f"Capture file opened: {path}")
+
+    def _close_file(self) -> None:
+        if self._file is None:
+            return
+        try:
+            self._file.close()
+        except Exception as e:
+            logger.error(f"❌ Failed to close capture file {self._file_path}: {e}")
+        self._file = None
+
+    @property
+    def current_file(self) -> Optional[Path]:
+        return self._file_path if self._file is not None else None
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Recorder counters."""
+        return {
+            "running": bool(self._thread and self._thread.is_alive()),
+            "directory": str(self.directory),
+            "compression": self.compression,
+            "current_file": str(self.current_file) if self.current_file else None,
+            "frames_written": self.frames_written,
+            "frames_dropped": self.frames_dropped,
+            "buffered": len(self._buffer),
+            "bytes_written": self.bytes_written,
+            "files_written": self.files_written,
+            "write_errors": self.write_errors,
+        }
+
+
+def _open_capture(path: Path) -> IO[str]:
+    if path.suffix == '.zst':
+        import zstandard
+        return zstandard.open(path, 'rt', encoding='utf-8')
+    if path.suffix == '.gz':
+        return gzip.open(path, 'rt', encoding='utf-8')
+    return open(path, 'r', encoding='utf-8')
+
+
+def iter_capture(source: Union[str, Path, Iterable[str]]) -> Iterator[CapturedFrame]:
+    """
+    Iterate the frames of a capture file (.jsonl, .jsonl.gz or .jsonl.zst) or of capture lines.
+
+    Header lines are skipped; malformed lines are skipped with a warning so a
+    capture truncated by a crash is still usable.
+    """
+    if isinstance(source, (str, Path)):
+        path = Path(source)
+        with _open_capture(path) as f:
+            yield from iter_capture(f)
+        return
+    for line_no, line in enumerate(source, 1):
+        line = line.strip()
+        if not line:
+            continue
+        try:
+            record = json.loads(line)
+            if 'capture' in record:
+                continue
+            data = record['f'] if 'f' in record else base64.b64decode(record['b'])
+            yield CapturedFrame(float(record['t']), data, bool(record.get('h')))
+        except (ValueError, KeyError, TypeError) as e:
+            logger.warning(f"⚠️  Skipping malformed capture line {line_no}: {e}")
+
+
+async def replay_capture(source: Union[str, Path, Iterable[str]], client: Any, speed: float = 0.0) -> int:
+    """
+    Replay a capture through a SignalRClient's inbound pipeline (no connection needed).
+
+    Args:
+        source: Capture file or capture lines
+        client: SignalRClient (or anything with feed_frame) whose handlers receive the replay
+        speed: 0 = as fast as possible, 1.0 = recorded pace, 10.0 = ten times faster
+
+    Returns:
+        int: Frames replayed
+    """
+    replayed = 0
+    first_at = started = None
+    for frame in iter_capture(source):
+        if speed > 0:
+            if first_at is None:
+                first_at, started = frame.received_at, time.monotonic()
+            delay = (frame.received_at - first_at) / speed - (time.monotonic() - started)
+            if delay > 0:
+                await asyncio.sleep(delay)
+        await client.feed_frame(frame.data, handshake=frame.handshake)
+        replayed += 1
+    return replayed
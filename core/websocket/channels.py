@@ -0,0 +1,448 @@
+"""
+Bounded channels between the feed and its consumers.
+
+Each consumer reads from its own bounded channel, so one slow consumer can't
+make the others (or the socket reader) fall behind. What happens when a
+channel is full is chosen per consumer:
+- block: the producer waits for space (backpressure). `await put()` waits on
+  the event loop; a sync offer() waits only when called from another thread
+  (up to block_timeout) and otherwise drops the item, because blocking the
+  consumer's own loop would deadlock
+- drop_oldest: the oldest queued item is discarded to make room
+- conflate: items with the same conflation key are merged into the queued
+  one in place (e.g. the latest quote per symbol, keeping fields the newer
+  partial update left out); items without a key are queued normally and the
+  oldest item is dropped when full
+
+Drop, conflation and block counters are reported by get_stats().
+
+ChannelFanout.events() wraps a channel in an EventStream, so asyncio code can
+consume a feed with `async for event in hub.events():`; the stream's channel
+is removed when the stream is closed or the hub stops.
+
+Configuration:
+- FEED_CHANNEL_CAPACITY: Default channel capacity (default 10000)
+- FEED_CHANNEL_POLICY: Default overflow policy, block/drop_oldest/conflate (default drop_oldest)
+- FEED_CHANNEL_BLOCK_TIMEOUT: Seconds a thread waits in offer() under the block policy (default 5)
+"""
+
+import asyncio
+import inspect
+import itertools
+import logging
+import os
+import threading
+from collections import deque
+from dataclasses import fields, replace
+from enum import Enum
+from typing import Any, Callable, Deque, Dict, Hashable, List, Optional, Tuple, Type, Union
+
+from core.market_events import Quote
+
+logger = logging.getLogger(__name__)
+
+
+class OverflowPolicy(str, Enum):
+    BLOCK = 'block'
+    DROP_OLDEST = 'drop_oldest'
+    CONFLATE = 'conflate'
+
+
+class ChannelClosed(Exception):
+    """The channel was closed and is empty."""
+
+
+def market_event_key(event: Any) -> Optional[Hashable]:
+    """Default conflation key: quotes conflate per symbol; trades and depth deltas are never conflated."""
+    if isinstance(event, Quote):
+        return (event.type, event.symbol)
+    return None
+
+
+def merge_market_events(queued: Any, newer: Any) -> Any:
+    """Default conflation merge: the newer quote, with fields it left out (None) taken from the queued one."""
+    if isinstance(queued, Quote) and isinstance(newer, Quote):
+        missing = {f.name: getattr(queued, f.name) for f in fields(newer)
+                   if getattr(newer, f.name) is None and getattr(queued, f.name) is not None}
+        return replace(newer, **missing) if missing else newer
+    return newer
+
+
+def _running_loop() -> Optional[asyncio.AbstractEventLoop]:
+    try:
+        return asyncio.get_running_loop()
+    except RuntimeError:
+        return None
+
+
+class BoundedChannel:
+    """
+    Thread-safe bounded queue with an overflow policy, read from asyncio.
+
+    Usage:
+        channel = BoundedChannel(capacity=1000, policy='conflate')
+        channel.offer(event)                 # producer, any thread
+        async for event in channel:          # consumer
+            ...
+    """
+
+    def __init__(self, capacity: Optional[int] = None, policy: Union[OverflowPolicy, str, None] = None,
+                 conflate_key: Optional[Callable[[Any], Optional[Hashable]]] = market_event_key,
+                 block_timeout: Optional[float] = None, name: str = 'channel',
+                 conflate_merge: Callable[[Any, Any], Any] = merge_market_events,
+                 accept: Optional[Callable[[Any], bool]] = None):
+        """
+        Initialize channel.
+
+        Args:
+            capacity: Maximum queued items (env: FEED_CHANNEL_CAPACITY)
+            policy: Overflow policy (env: FEED_CHANNEL_POLICY)
+            conflate_key: Key function for the conflate policy (None key = never conflated)
+            conflate_merge: Combines the queued item with a newer one of the same key
+            accept: Filter; items it rejects are ignored (not queued, not counted)
+            block_timeout: Max seconds a thread waits in offer() (env: FEED_CHANNEL_BLOCK_TIMEOUT)
+            name: Name used in stats
+
+        Raises:
+            ValueError: Capacity < 1 or unknown policy
+        """
+        self.name = name
+        self.capacity = capacity if capacity is not None else int(os.getenv('FEED_CHANNEL_CAPACITY', '10000'))
+        if self.capacity < 1:
+            raise ValueError(f"Channel capacity must be >= 1, got {self.capacity}")
+        self.policy = OverflowPolicy(policy or os.getenv('FEED_CHANNEL_POLICY', 'drop_oldest').lower())
+        self.conflate_key = conflate_key if self.policy == OverflowPolicy.CONFLATE else None
+        self.conflate_merge = conflate_merge
+        self.accept = accept
+        self.block_timeout = block_timeout if block_timeout is not None else \
+            float(os.getenv('FEED_CHANNEL_BLOCK_TIMEOUT', '5'))
+        self._items: Deque[List[Any]] = deque()  # [key, item] entries
+        self._keys: Dict[Hashable, List[Any]] = {}
+        self._lock = threading.Lock()
+        self._space = threading.Condition(self._lock)
+        self._loop: Optional[asyncio.AbstractEventLoop] = None
+        self._ready: Optional[asyncio.Event] = None
+        self._space_ready: Optional[asyncio.Event] = None
+        self.closed = False
+        self.offered = 0
+        self.delivered = 0
+        self.dropped = 0
+        self.conflated = 0
+        self.blocked = 0
+        self.max_depth = 0
+
+    def __len__(self) -> int:
+        return len(self._items)
+
+    # ---------------------------
+    # Producer side
+    # ---------------------------
+    def offer(self, item: Any) -> bool:
+        """
+        Queue an item without awaiting (thread-safe).
+
+        Returns:
+            bool: False if the item was dropped (channel closed or full under the block policy)
+        """
+        if self.accept is not None and not self.accept(item):
+            return True
+        key = self.conflate_key(item) if self.conflate_key else None
+        with self._lock:
+            if self.closed:
+                return False
+            self.offered += 1
+            if key is not None and key in self._keys:
+                entry = self._keys[key]
+                entry[1] = self.conflate_merge(entry[1], item)
+                self.conflated += 1
+                return True
+            if len(self._items) >= self.capacity:
+                if self.policy == OverflowPolicy.BLOCK:
+                    if self._loop is not None and _running_loop() is self._loop:
+                        self.dropped += 1  # Can't wait on the consumer's own loop
+                        return False
+                    self.blocked += 1
+                    if not self._space.wait_for(lambda: len(self._items) < self.capacity or self.closed,
+                                                self.block_timeout) or self.closed:
+                        self.dropped += 1
+                        return False
+                else:
+                    self._evict_oldest()
+            self._append(key, item)
+        self._wake(self._ready)
+        return True
+
+    async def put(self, item: Any) -> bool:
+        """
+        Queue an item, waiting for space under the block policy.
+
+        Returns:
+            bool: False if the channel is closed
+        """
+        if self.policy != OverflowPolicy.BLOCK:
+            return self.offer(item)
+        if self.accept is not None and not self.accept(item):
+            return True
+        waited = False
+        while True:
+            with self._lock:
+                if self.closed:
+                    return False
+                if len(self._items) < self.capacity:
+                    self.offered += 1
+                    self._append(None, item)
+                    break
+                if not waited:
+                    self.blocked += 1
+                    waited = True
+                if self._space_ready is None:
+                    self._space_ready = asyncio.Event()
+                self._space_ready.clear()
+                space_ready = self._space_ready
+            await space_ready.wait()
+        self._wake(self._ready)
+        return True
+
+    def _append(self, key: Optional[Hashable], item: Any) -> None:
+        entry = [key, item]
+        self._items.append(entry)
+        if key is not None:
+            self._keys[key] = entry
+        if len(self._items) > self.max_depth:
+            self.max_depth = len(self._items)
+
+    def _evict_oldest(self) -> None:
+        key, _ = self._items.popleft()
+        if key is not None:
+            self._keys.pop(key, None)
+        self.dropped += 1
+
+    def _wake(self, event: Optional[asyncio.Event]) -> None:
+        """Set an asyncio.Event from any thread."""
+        loop = self._loop
+        if event is None or loop is None:
+            return
+        if _running_loop() is loop:
+            event.set()
+        elif not loop.is_closed():
+            loop.call_soon_threadsafe(event.set)
+
+    # ---------------------------
+    # Consumer side
+    # ---------------------------
+    async def get(self) -> Any:
+        """
+        Next item, waiting while the channel is empty.
+
+        Raises:
+            ChannelClosed: The channel is closed and drained
+        """
+        if self._ready is None:
+            self._loop = asyncio.get_running_loop()
+            self._ready = asyncio.Event()
+        while True:
+            with self._lock:
+                if self._items:
+                    key, item = self._items.popleft()
+                    if key is not None:
+                        self._keys.pop(key, None)
+                    self.delivered += 1
+                    self._space.notify()
+                    space_ready = self._space_ready
+                    break
+                if self.closed:
+                    raise ChannelClosed(self.name)
+                self._ready.clear()
+            await self._ready.wait()
+        self._wake(space_ready)
+        return item
+
+    def __aiter__(self):
+        return self
+
+    async def __anext__(self) -> Any:
+        try:
+            return await self.get()
+        except ChannelClosed:
+            raise StopAsyncIteration
+
+    def close(self) -> None:
+        """Stop accepting items; consumers drain what is queued, then stop."""
+        with self._lock:
+            self.closed = True
+            self._space.notify_all()
+        self._wake(self._ready)
+        self._wake(self._space_ready)
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Depth and overflow counters."""
+        return {
+            "policy": self.policy.value,
+            "capacity": self.capacity,
+            "depth": len(self._items),
+            "max_depth": self.max_depth,
+            "offered": self.offered,
+            "delivered": self.delivered,
+            "dropped": self.dropped,
+            "conflated": self.conflated,
+            "blocked": self.blocked,
+            "closed": self.closed,
+        }
+
+
+class EventStream:
+    """
+    Async iterator over one fan-out channel.
+
+    Usage:
+        async with hub.events(types=(Quote, Trade)) as stream:
+            async for event in stream:
+                ...
+    """
+
+    def __init__(self, fanout: 'ChannelFanout', name: str, channel: BoundedChannel):
+        self.name = name
+        self.channel = channel
+        self._fanout = fanout
+
+    def __aiter__(self) -> 'EventStream':
+        return self
+
+    async def __anext__(self) -> Any:
+        try:
+            return await self.channel.get()
+        except ChannelClosed:
+            self.close()
+            raise StopAsyncIteration
+
+    async def __aenter__(self) -> 'EventStream':
+        return self
+
+    async def __aexit__(self, *exc_info: Any) -> None:
+        self.close()
+
+    def close(self) -> None:
+        """Stop receiving events (iteration ends once queued events are consumed)."""
+        self._fanout.remove(self.name)
+
+    def get_stats(self) -> Dict[str, Any]:
+        return self.channel.get_stats()
+
+
+class ChannelFanout:
+    """
+    Fans items out to one bounded channel per consumer.
+
+    Usage:
+        fanout = ChannelFanout()
+        fanout.add_consumer('strategy', strategy.on_market_event, policy='conflate')
+        fanout.add_consumer('recorder', recorder.on_market_event, policy='block')
+        fanout.publish(event)            # sync producers
+        await fanout.publish_async(event)  # async producers (waits on full 'block' channels)
+    """
+
+    def __init__(self):
+        self._channels: Dict[str, BoundedChannel] = {}
+        self._tasks: Dict[str, asyncio.Task] = {}
+        self._lock = threading.Lock()
+        self._stream_ids = itertools.count(1)
+        self.consumer_errors = 0
+
+    def add_channel(self, name: str, capacity: Optional[int] = None,
+                    policy: Union[OverflowPolicy, str, None] = None,
+                    conflate_key: Optional[Callable[[Any], Optional[Hashable]]] = market_event_key,
+                    accept: Optional[Callable[[Any], bool]] = None) -> BoundedChannel:
+        """
+        Add a channel read by the caller (e.g. `async for item in channel`).
+
+        Raises:
+            ValueError: A consumer with this name exists
+        """
+        channel = BoundedChannel(capacity, policy, conflate_key, name=name, accept=accept)
+        with self._lock:
+            if name in self._channels:
+                raise ValueError(f"Consumer '{name}' already exists")
+            channels = dict(self._channels)
+            channels[name] = channel
+            self._channels = channels
+        return channel
+
+    def add_consumer(self, name: str, callback: Callable[[Any], Any], capacity: Optional[int] = None,
+                     policy: Union[OverflowPolicy, str, None] = None,
+                     conflate_key: Optional[Callable[[Any], Optional[Hashable]]] = market_event_key,
+                     accept: Optional[Callable[[Any], bool]] = None) -> BoundedChannel:
+        """
+        Add a channel drained into a callback by a task on the running loop.
+
+        Coroutine callbacks are awaited, so a slow consumer only backs up its own channel.
+        """
+        channel = self.add_channel(name, capacity, policy, conflate_key, accept)
+        self._tasks[name] = asyncio.ensure_future(self._drain(channel, callback))
+        return channel
+
+    def events(self, types: Union[Type, Tuple[Type, ...], None] = None, capacity: Optional[int] = None,
+               policy: Union[OverflowPolicy, str, None] = None) -> EventStream:
+        """
+        Async iterator over items published from now on.
+
+        Args:
+            types: Only these event classes (default: everything)
+            capacity: Channel capacity (env: FEED_CHANNEL_CAPACITY)
+            policy: Overflow policy (env: FEED_CHANNEL_POLICY)
+
+        Returns:
+            EventStream: Registered immediately, so nothing published after this call is missed
+        """
+        accept = (lambda item: isinstance(item, types)) if types is not None else None
+        name = f"events-{next(self._stream_ids)}"
+        return EventStream(self, name, self.add_channel(name, capacity, policy, accept=accept))
+
+    async def _drain(self, channel: BoundedChannel, callback: Callable[[Any], Any]) -> None:
+        async for item in channel:
+            try:
+                result = callback(item)
+                if inspect.isawaitable(result):
+                    await result
+            except Exception as e:
+                self.consumer_errors += 1
+                logger.debug(f"Channel consumer '{channel.name}' error: {e}")
+
+    def remove(self, name: str) -> None:
+        """Close and remove a consumer's channel (its queued items are still delivered)."""
+        with self._lock:
+            channels = dict(self._channels)
+            channel = channels.pop(name, None)
+            self._channels = channels
+        if channel is not None:
+            channel.close()
+        self._tasks.pop(name, None)
+
+    def __len__(self) -> int:
+        return len(self._channels)
+
+    @property
+    def has_blocking(self) -> bool:
+        return any(c.policy == OverflowPolicy.BLOCK for c in self._channels.values())
+
+    def publish(self, item: Any) -> None:
+        """Offer an item to every channel (thread-safe, never awaits)."""
+        for channel in self._channels.values():
+            channel.offer(item)
+
+    async def publish_async(self, item: Any) -> None:
+        """Put an item into every channel, waiting on full 'block' channels."""
+        for channel in self._channels.values():
+            await channel.put(item)
+
+    async def close(self, timeout: float = 5.0) -> None:
+        """Close all channels and wait for consumers to drain them."""
+        for name in list(self._channels):
+            self.remove(name)
+        tasks = [t for t in self._tasks.values() if not t.done()]
+        self._tasks = {}
+        if tasks:
+            await asyncio.wait(tasks, timeout=timeout)
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Per-consumer channel stats."""
+        return {name: channel.get_stats() for name, channel in self._channels.items()}
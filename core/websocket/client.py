@@ -0,0 +1,949 @@
+"""
+Asyncio SignalR hub client.
+
+Connects to a SignalR hub over WebSockets using the JSON hub protocol:
+- Negotiate: POST <hub>/negotiate?negotiateVersion=1 for a connection token
+  (follows redirects to another URL/access token). TopStepX accepts direct
+  WebSocket connections, so negotiation is skipped by default
+- Handshake: {"protocol": "json", "version": 1} answered by {}
+- Invocations from the server are dispatched to callbacks registered with
+  on(target, callback) as callback(*arguments); awaitables returned by
+  invocation handlers are awaited before the next frame is read, so a slow
+  async consumer applies backpressure to the socket instead of piling up
+  tasks. Targets are matched case-insensitively
+- send() posts fire-and-forget invocations (callable from any thread);
+  invoke() awaits the server's completion
+- Keep-alive pings every keep_alive_interval seconds; a connection that
+  receives nothing (not even a server ping) for server_timeout seconds is
+  treated as half-dead and dropped, which triggers a reconnect.
+  force_reconnect() does the same on demand (see FeedWatchdog)
+- Auto-reconnect: when the connection drops (or the server closes it with
+  allowReconnect), reconnect with exponential backoff and jitter, negotiating
+  again with a fresh token, then replay every subscribe() invocation.
+  on_state_change() callbacks receive 'connected', 'reconnecting' and
+  'disconnected' transitions
+- Failover: with backup_urls, failover_after consecutive failures on the
+  current endpoint (failed connect attempts, or connections dropped before
+  they were up failover_stable_seconds) switch to the next endpoint in
+  round-robin order. on_failover() callbacks receive a FailoverEvent and
+  subscriptions are replayed on the new endpoint like any reconnect. start()
+  tries each endpoint once before giving up
+- Compression: per-message deflate is offered when connecting (SIGNALR_COMPRESSION)
+  and undone by the transport; binary frames that arrive gzip or zlib
+  compressed are inflated before parsing whatever the setting, so a server
+  switching compression on doesn't break the feed
+- Recording: with a FrameRecorder (or SIGNALR_RECORD=true) every inbound
+  frame is written to a compressed capture file; feed_frame() is the entry
+  point capture.replay_capture() uses to push frames back through the parser
+
+RawWebSocketClient (raw.py) reuses this machinery for non-SignalR feeds by
+overriding the handshake, frame handling and subscription replay hooks.
+
+The transport is pluggable: `connector(url, headers)` must return an object
+with async send(str), recv() -> str|bytes and close(). The default uses the
+`websockets` package. Callbacks run on the event loop that called start()
+and must not block.
+
+Configuration:
+- SIGNALR_CLIENT: Market hub client used by the bot, 'signalrcore' (default) or 'native'
+- SIGNALR_SKIP_NEGOTIATION: Connect the WebSocket directly (default true)
+- SIGNALR_KEEP_ALIVE_INTERVAL: Seconds between client pings (default 15)
+- SIGNALR_SERVER_TIMEOUT: Seconds without any server message before reconnecting, 0 = off (default 30)
+- SIGNALR_CONNECT_TIMEOUT: Seconds allowed for negotiate/connect/handshake (default 10)
+- SIGNALR_COMPRESSION: Offer per-message deflate when connecting (default true)
+- SIGNALR_RECONNECT: Reconnect automatically after a lost connection (default true)
+- SIGNALR_RECONNECT_INITIAL_DELAY: First backoff delay in seconds (default 1)
+- SIGNALR_RECONNECT_MAX_DELAY: Backoff cap in seconds (default 60)
+- SIGNALR_RECONNECT_JITTER: Random +/- fraction applied to each delay (default 0.2)
+- SIGNALR_RECONNECT_MAX_ATTEMPTS: Attempts before giving up, 0 = forever (default 0)
+- SIGNALR_FAILOVER_AFTER: Consecutive failures before switching to a backup URL (default 3)
+- SIGNALR_FAILOVER_STABLE_SECONDS: A connection up this long resets the failure count (default 60)
+- SIGNALR_RECORD: Record inbound frames to SIGNALR_RECORD_DIR (default false, see capture.py)
+"""
+
+import asyncio
+import functools
+import inspect
+import itertools
+import json
+import logging
+import os
+import random
+import threading
+import time
+from collections import deque
+from dataclasses import dataclass
+from datetime import datetime, timezone
+from enum import Enum
+from typing import Any, Awaitable, Callable, Deque, Dict, List, Optional, Sequence, Tuple
+from urllib.parse import parse_qsl, urlencode, urlsplit, urlunsplit
+
+from core.websocket.capture import FrameRecorder
+from core.websocket.protocol import (
+    PING_MESSAGE, HubMessage, MessageType, ProtocolError, encode_message, handshake_request, inflate_frame,
+    invocation, is_compressed_frame, parse_handshake_response, parse_messages,
+)
+
+logger = logging.getLogger(__name__)
+
+Connector = Callable[[str, Dict[str, str]], Awaitable[Any]]
+Negotiator = Callable[[str, Dict[str, str]], Awaitable[Dict[str, Any]]]
+
+_MAX_NEGOTIATE_REDIRECTS = 10
+_SECRET_QUERY_KEYS = frozenset(('id', 'key', 'apikey', 'api_key', 'secret', 'signature', 'password'))
+
+
+class HubConnectionError(ConnectionError):
+    """The hub connection could not be established or was lost."""
+
+
+class HubInvocationError(RuntimeError):
+    """The server completed an invocation with an error."""
+
+
+class ConnectionState(str, Enum):
+    CONNECTED = 'connected'
+    RECONNECTING = 'reconnecting'
+    DISCONNECTED = 'disconnected'
+
+
+@dataclass(frozen=True)
+class FailoverEvent:
+    """A hub client switched to another endpoint."""
+    hub: str
+    from_url: str  # Redacted
+    to_url: str  # Redacted
+    reason: str
+    failures: int
+    timestamp: datetime
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {"hub": self.hub, "from_url": self.from_url, "to_url": self.to_url, "reason": self.reason,
+                "failures": self.failures, "timestamp": self.timestamp.isoformat()}
+
+
+def parse_url_list(value: Optional[str]) -> List[str]:
+    """Comma-separated URLs from an env var, blanks dropped."""
+    return [url.strip() for url in (value or '').split(',') if url.strip()]
+
+
+def backoff_delay(attempt: int, initial: float, maximum: float, jitter: float = 0.0,
+                  rand: Callable[[], float] = random.random) -> float:
+    """
+    Delay before a reconnect attempt.
+
+    Args:
+        attempt: Attempt number (1 = first retry)
+        initial: Delay of the first attempt
+        maximum: Cap on the exponential delay
+        jitter: Random +/- fraction of the delay (0.2 = within 20%)
+        rand: Uniform [0, 1) source
+
+    Returns:
+        float: Seconds to wait (initial * 2^(attempt-1), capped, then jittered)
+    """
+    delay = min(maximum, initial * (2 ** max(0, attempt - 1)))
+    if jitter > 0:
+        delay *= 1 + jitter * (2 * rand() - 1)
+    return max(0.0, delay)
+
+
+def _with_scheme(url: str, secure: str, plain: str) -> str:
+    parts = urlsplit(url)
+    scheme = {'https': secure, 'wss': secure, 'http': plain, 'ws': plain}.get(parts.scheme, parts.scheme)
+    return urlunsplit(parts._replace(scheme=scheme))
+
+
+def _with_query(url: str, **params: Optional[str]) -> str:
+    """Set query parameters (None values are left out)."""
+    parts = urlsplit(url)
+    query = [(k, v) for k, v in parse_qsl(parts.query, keep_blank_values=True) if k not in params]
+    query.extend((k, v) for k, v in params.items() if v is not None)
+    return urlunsplit(parts._replace(query=urlencode(query)))
+
+
+def redact_url(url: str) -> str:
+    """URL with access_token/id query values hidden, for logs and status."""
+    parts = urlsplit(url)
+    query = [(k, '***' if k in _SECRET_QUERY_KEYS or 'token' in k.lower() else v)
+             for k, v in parse_qsl(parts.query, keep_blank_values=True)]
+    return urlunsplit(parts._replace(query=urlencode(query, safe='*')))
+
+
+async def websockets_connector(url: str, headers: Dict[str, str], compression: bool = True) -> Any:
+    """Open a connection with the `websockets` package (offering per-message deflate unless compression=False)."""
+    try:
+        import websockets
+    except ImportError:
+        raise HubConnectionError("The websockets package is required for the SignalR client (pip install websockets)")
+    # SignalR has its own keep-alive; disable websockets' ping frames and message size limit
+    options = {'max_size': None, 'ping_interval': None, 'compression': 'deflate' if compression else None}
+    try:
+        return await websockets.connect(url, additional_headers=headers, **options)
+    except TypeError:
+        return await websockets.connect(url, extra_headers=headers, **options)  # websockets < 13
+
+
+async def aiohttp_negotiator(url: str, headers: Dict[str, str]) -> Dict[str, Any]:
+    """POST the negotiate request with aiohttp."""
+    import aiohttp
+    async with aiohttp.ClientSession() as session:
+        async with session.post(url, headers=headers) as response:
+            if response.status != 200:
+                raise HubConnectionError(f"Negotiate failed: HTTP {response.status}")
+            return await response.json(content_type=None)
+
+
+def negotiated_extensions(conn: Any) -> Optional[str]:
+    """Sec-WebSocket-Extensions the server accepted, if the transport exposes its upgrade response."""
+    response = getattr(conn, 'response', None)
+    headers = getattr(response, 'headers', None) or getattr(conn, 'response_headers', None)
+    if headers is None:
+        return None
+    try:
+        return headers.get('Sec-WebSocket-Extensions')
+    except Exception:
+        return None
+
+
+class SignalRClient:
+    """
+    SignalR hub connection.
+
+    Usage:
+        hub = SignalRClient('https://rtc.topstepx.com/hubs/market', access_token_factory=lambda: token)
+        hub.on('GatewayQuote', on_quote)
+        await hub.start()
+        hub.send('SubscribeContractQuotes', ['CON.F.US.MNQ.Z25'])
+        ...
+        await hub.stop()
+    """
+
+    kind = 'SignalR'  # Log prefix
+    token_query_param: Optional[str] = 'access_token'  # The token is also sent in this query parameter
+    ping_message: Optional[str] = PING_MESSAGE  # Keep-alive payload, None = no client pings
+
+    def __init__(self, url: str, access_token_factory: Optional[Callable[[], Optional[str]]] = None,
+                 headers: Optional[Dict[str, str]] = None, skip_negotiation: Optional[bool] = None,
+                 keep_alive_interval: Optional[float] = None, server_timeout: Optional[float] = None,
+                 connect_timeout: Optional[float] = None, compression: Optional[bool] = None,
+                 connector: Optional[Connector] = None, negotiator: Optional[Negotiator] = None,
+                 reconnect: Optional[bool] = None, reconnect_initial_delay: Optional[float] = None,
+                 reconnect_max_delay: Optional[float] = None, reconnect_jitter: Optional[float] = None,
+                 reconnect_max_attempts: Optional[int] = None, name: str = 'hub',
+                 recorder: Optional[FrameRecorder] = None, backup_urls: Optional[Sequence[str]] = None,
+                 failover_after: Optional[int] = None, failover_stable_seconds: Optional[float] = None):
+        """
+        Initialize client (does not connect).
+
+        Args:
+            url: Hub URL (http/https/ws/wss)
+            access_token_factory: Returns the bearer token, sent as access_token and Authorization
+            headers: Extra HTTP headers for negotiate and the WebSocket upgrade
+            skip_negotiation: Connect directly without negotiating (env: SIGNALR_SKIP_NEGOTIATION)
+            keep_alive_interval: Seconds between pings (env: SIGNALR_KEEP_ALIVE_INTERVAL)
+            server_timeout: Seconds of server silence before reconnecting (env: SIGNALR_SERVER_TIMEOUT)
+            connect_timeout: Seconds for negotiate/connect/handshake (env: SIGNALR_CONNECT_TIMEOUT)
+            compression: Offer per-message deflate (env: SIGNALR_COMPRESSION); custom connectors negotiate their own
+            connector: Transport factory (default: websockets_connector)
+            negotiator: Negotiate request (default: aiohttp_negotiator)
+            reconnect: Reconnect after a lost connection (env: SIGNALR_RECONNECT)
+            reconnect_initial_delay: First backoff delay (env: SIGNALR_RECONNECT_INITIAL_DELAY)
+            reconnect_max_delay: Backoff cap (env: SIGNALR_RECONNECT_MAX_DELAY)
+            reconnect_jitter: +/- fraction per delay (env: SIGNALR_RECONNECT_JITTER)
+            reconnect_max_attempts: Attempts before giving up, 0 = forever (env: SIGNALR_RECONNECT_MAX_ATTEMPTS)
+            name: Name used in logs and status
+            recorder: Writes every inbound frame to a capture file (env: SIGNALR_RECORD)
+            backup_urls: Endpoints to fail over to, in order
+            failover_after: Consecutive failures before failing over (env: SIGNALR_FAILOVER_AFTER)
+            failover_stable_seconds: Uptime that resets the failure count (env: SIGNALR_FAILOVER_STABLE_SECONDS)
+        """
+        self.url = url  # Current endpoint
+        self.endpoints: List[str] = [url] + [u for u in (backup_urls or ()) if u and u != url]
+        self.failover_after = max(1, failover_after if failover_after is not None else
+                                  int(os.getenv('SIGNALR_FAILOVER_AFTER', '3')))
+        self.failover_stable_seconds = failover_stable_seconds if failover_stable_seconds is not None else \
+            float(os.getenv('SIGNALR_FAILOVER_STABLE_SECONDS', '60'))
+        self.name = name
+        self.access_token_factory = access_token_factory
+        self.headers = dict(headers or {})
+        self.skip_negotiation = skip_negotiation if skip_negotiation is not None else \
+            os.getenv('SIGNALR_SKIP_NEGOTIATION', 'true').lower() in ('true', '1', 'yes', 'on')
+        self.keep_alive_interval = keep_alive_interval if keep_alive_interval is not None else \
+            float(os.getenv('SIGNALR_KEEP_ALIVE_INTERVAL', '15'))
+        self.server_timeout = server_timeout if server_timeout is not None else \
+            float(os.getenv('SIGNALR_SERVER_TIMEOUT', '30'))
+        self.connect_timeout = connect_timeout if connect_timeout is not None else \
+            float(os.getenv('SIGNALR_CONNECT_TIMEOUT', '10'))
+        self.compression = compression if compression is not None else \
+            os.getenv('SIGNALR_COMPRESSION', 'true').lower() in ('true', '1', 'yes', 'on')
+        self._connector = connector or functools.partial(websockets_connector, compression=self.compression)
+        self._negotiator = negotiator or aiohttp_negotiator
+        self.reconnect = reconnect if reconnect is not None else \
+            os.getenv('SIGNALR_RECONNECT', 'true').lower() in ('true', '1', 'yes', 'on')
+        self.reconnect_initial_delay = reconnect_initial_delay if reconnect_initial_delay is not None else \
+            float(os.getenv('SIGNALR_RECONNECT_INITIAL_DELAY', '1'))
+        self.reconnect_max_delay = reconnect_max_delay if reconnect_max_delay is not None else \
+            float(os.getenv('SIGNALR_RECONNECT_MAX_DELAY', '60'))
+        self.reconnect_jitter = reconnect_jitter if reconnect_jitter is not None else \
+            float(os.getenv('SIGNALR_RECONNECT_JITTER', '0.2'))
+        self.reconnect_max_attempts = reconnect_max_attempts if reconnect_max_attempts is not None else \
+            int(os.getenv('SIGNALR_RECONNECT_MAX_ATTEMPTS', '0'))
+        if recorder is None and os.getenv('SIGNALR_RECORD', 'false').lower() in ('true', '1', 'yes', 'on'):
+            recorder = FrameRecorder(hub=name)
+        self.recorder = recorder
+
+        self._handlers: Dict[str, Tuple[Callable[..., Any], ...]] = {}
+        self._open_callbacks: Tuple[Callable[[], Any], ...] = ()
+        self._close_callbacks: Tuple[Callable[[], Any], ...] = ()
+        self._error_callbacks: Tuple[Callable[[Any], Any], ...] = ()
+        self._state_callbacks: Tuple[Callable[[ConnectionState, Optional[str]], Any], ...] = ()
+        self._failover_callbacks: Tuple[Callable[[FailoverEvent], Any], ...] = ()
+        self._callback_lock = threading.Lock()
+        self._subscriptions: Dict[Tuple[str, str], Tuple[str, List[Any]]] = {}
+
+        self._loop: Optional[asyncio.AbstractEventLoop] = None
+        self._conn: Any = None
+        self._outbox: Optional[asyncio.Queue] = None
+        self._tasks: List[asyncio.Task] = []
+        self._pending: Dict[str, asyncio.Future] = {}
+        self._backlog: Deque[Awaitable[Any]] = deque()
+        self._target_keys: Dict[str, str] = {}  # Invocation target -> handler key, saves a lower() per message
+        self._message_dispatch: Dict[int, Callable[[HubMessage], None]] = {
+            MessageType.INVOCATION: self._dispatch_invocation,
+            MessageType.COMPLETION: self._complete,
+            MessageType.PING: self._on_ping,
+        }
+        self._invocation_ids = itertools.count(1)
+        self._reconnect_task: Optional[asyncio.Task] = None
+        self._stopping = False
+        self._server_closed = False
+        self._close_allows_reconnect = False
+        self.state = ConnectionState.DISCONNECTED
+        self.connected = False
+        self.connected_url: Optional[str] = None
+        self.extensions: Optional[str] = None
+        self.connected_since: Optional[float] = None
+        self.last_message_at: Optional[float] = None
+        self.close_error: Optional[str] = None
+        self.endpoint_failures = 0  # Consecutive failures on the current endpoint
+        self.last_failover: Optional[FailoverEvent] = None
+
+        self.messages_received = 0
+        self.invocations_dispatched = 0
+        self.unhandled_invocations = 0
+        self.pings_received = 0
+        self.handler_errors = 0
+        self.protocol_errors = 0
+        self.messages_sent = 0
+        self.reconnects = 0
+        self.reconnect_attempts = 0
+        self.server_timeouts = 0
+        self.forced_reconnects = 0
+        self.failovers = 0
+        self.compressed_frames = 0
+        self.compressed_bytes = 0
+        self.inflated_bytes = 0
+
+    # ---------------------------
+    # Callbacks
+    # ---------------------------
+    def on(self, target: str, callback: Callable[..., Any]) -> Callable[[], None]:
+        """
+        Register a handler for a server invocation target.
+
+        Returns:
+            Callable: Unsubscribe function
+        """
+        key = target.lower()
+        with self._callback_lock:
+            self._handlers[key] = self._handlers.get(key, ()) + (callback,)
+
+        def unsubscribe():
+            with self._callback_lock:
+                remaining = tuple(cb for cb in self._handlers.get(key, ()) if cb is not callback)
+                if remaining:
+                    self._handlers[key] = remaining
+                else:
+                    self._handlers.pop(key, None)
+        return unsubscribe
+
+    def on_open(self, callback: Callable[[], Any]) -> None:
+        """Called after each successful handshake."""
+        with self._callback_lock:
+            self._open_callbacks += (callback,)
+
+    def on_close(self, callback: Callable[[], Any]) -> None:
+        """Called when an open connection closes or is lost."""
+        with self._callback_lock:
+            self._close_callbacks += (callback,)
+
+    def on_error(self, callback: Callable[[Any], Any]) -> None:
+        """Called with the error when connecting fails or the connection breaks."""
+        with self._callback_lock:
+            self._error_callbacks += (callback,)
+
+    def on_state_change(self, callback: Callable[[ConnectionState, Optional[str]], Any]) -> Callable[[], None]:
+        """
+        Register a connection state callback, called as callback(state, detail).
+
+        Returns:
+            Callable: Unsubscribe function
+        """
+        with self._callback_lock:
+            self._state_callbacks += (callback,)
+
+        def unsubscribe():
+            with self._callback_lock:
+                self._state_callbacks = tuple(cb for cb in self._state_callbacks if cb is not callback)
+        return unsubscribe
+
+    def on_failover(self, callback: Callable[[FailoverEvent], Any]) -> Callable[[], None]:
+        """
+        Register a callback for endpoint switches, called as callback(FailoverEvent).
+
+        Returns:
+            Callable: Unsubscribe function
+        """
+        with self._callback_lock:
+            self._failover_callbacks += (callback,)
+
+        def unsubscribe():
+            with self._callback_lock:
+                self._failover_callbacks = tuple(cb for cb in self._failover_callbacks if cb is not callback)
+        return unsubscribe
+
+    def _set_state(self, state: ConnectionState, detail: Optional[str] = None) -> None:
+        if state == self.state:
+            return
+        self.state = state
+        logger.info(f"🔌 {self.kind} {self.name} {state.value}" + (f" ({detail})" if detail else ""))
+        for callback in self._state_callbacks:
+            self._call(callback, state, detail)
+
+    def _call(self, callback: Callable[..., Any], *args: Any, backlog: Optional[Deque] = None) -> None:
+        """Invoke a callback, scheduling (or queueing into backlog) coroutines and isolating errors."""
+        try:
+            result = callback(*args)
+            if inspect.isawaitable(result):
+                if backlog is not None:
+                    backlog.append(result)
+                else:
+                    asyncio.ensure_future(result).add_done_callback(self._task_done)
+        except Exception as e:
+            self.handler_errors += 1
+            logger.debug(f"{self.kind} {self.name} callback error: {e}")
+
+    def _task_done(self, task: asyncio.Future) -> None:
+        if not task.cancelled() and task.exception() is not None:
+            self.handler_errors += 1
+            logger.debug(f"{self.kind} {self.name} callback error: {task.exception()}")
+
+    async def _drain_backlog(self) -> None:
+        """Await handler awaitables in arrival order before reading further."""
+        while self._backlog:
+            try:
+                await self._backlog.popleft()
+            except asyncio.CancelledError:
+                raise
+            except Exception as e:
+                self.handler_errors += 1
+                logger.debug(f"{self.kind} {self.name} callback error: {e}")
+
+    # ---------------------------
+    # Connection
+    # ---------------------------
+    def _access_token(self) -> Optional[str]:
+        return (self.access_token_factory() or None) if self.access_token_factory else None
+
+    def _request_headers(self, token: Optional[str]) -> Dict[str, str]:
+        headers = dict(self.headers)
+        if token:
+            headers.setdefault('Authorization', f"Bearer {token}")
+        return headers
+
+    async def _resolve_endpoint(self) -> Tuple[str, Dict[str, str]]:
+        """WebSocket URL and headers, negotiating unless skipped."""
+        url, token = self.url, self._access_token()
+        connection_token = None
+        if not self.skip_negotiation:
+            for _ in range(_MAX_NEGOTIATE_REDIRECTS):
+                negotiate_url = _with_scheme(url, 'https', 'http')
+                parts = urlsplit(negotiate_url)
+                negotiate_url = _with_query(urlunsplit(parts._replace(path=parts.path.rstrip('/') + '/negotiate')),
+                                            negotiateVersion='1')
+                response = await self._negotiator(negotiate_url, self._request_headers(token))
+                if response.get('error'):
+                    raise HubConnectionError(f"Negotiate rejected: {response['error']}")
+                if response.get('url'):
+                    url, token = response['url'], response.get('accessToken') or token
+                    continue
+                transports = [t.get('transport') for t in response.get('availableTransports') or []]
+                if transports and 'WebSockets' not in transports:
+                    raise HubConnectionError(f"Hub does not offer WebSockets (offers {transports})")
+                connection_token = response.get('connectionToken') or response.get('connectionId')
+                break
+            else:
+                raise HubConnectionError("Too many negotiate redirects")
+        ws_url = _with_query(_with_scheme(url, 'wss', 'ws'), id=connection_token)
+        param = self.token_query_param
+        if token and param and f"{param}=" not in ws_url:
+            ws_url = _with_query(ws_url, **{param: token})
+        return ws_url, self._request_headers(token)
+
+    async def start(self) -> None:
+        """
+        Connect and complete the handshake (subscriptions registered earlier are sent).
+
+        Raises:
+            HubConnectionError: Negotiate, connect or handshake failed
+        """
+        if self._conn is not None or self.state == ConnectionState.RECONNECTING:
+            return
+        self._loop = asyncio.get_running_loop()
+        self._stopping = False
+        if self.recorder is not None:
+            self.recorder.start()
+        for remaining in range(len(self.endpoints) - 1, -1, -1):
+            try:
+                await self._connect()
+                break
+            except HubConnectionError as e:
+                if not remaining:
+                    raise
+                self.endpoint_failures += 1
+                self._failover(str(e))
+        self._set_state(ConnectionState.CONNECTED)
+
+    async def _connect(self) -> None:
+        conn = None
+        try:
+            url, headers = await asyncio.wait_for(self._resolve_endpoint(), self.connect_timeout)
+            conn = await asyncio.wait_for(self._connector(url, headers), self.connect_timeout)
+            rest = await self._handshake(conn)
+        except BaseException as e:
+            if conn is not None:
+                await self._close_transport(conn)
+            if not isinstance(e, Exception):
+                raise
+            error = e if isinstance(e, HubConnectionError) else \
+                HubConnectionError(f"{self.kind} {self.name} connect failed: {str(e) or type(e).__name__}")
+            for callback in self._error_callbacks:
+                self._call(callback, str(error))
+            raise error from e
+
+        self._conn = conn
+        self._outbox = asyncio.Queue()
+        self.connected = True
+        self.connected_url = url
+        self.extensions = negotiated_extensions(conn)
+        self.connected_since = self.last_message_at = time.monotonic()
+        self.close_error = None
+        self._server_closed = self._close_allows_reconnect = False
+        self._tasks = [
+            asyncio.ensure_future(self._read_loop(conn)),
+            asyncio.ensure_future(self._write_loop(conn, self._outbox)),
+        ]
+        if self.keep_alive_interval > 0 or self.server_timeout > 0:
+            self._tasks.append(asyncio.ensure_future(self._keepalive_loop()))
+        compressed = ', permessage-deflate' if self.extensions and 'permessage-deflate' in self.extensions else ''
+        logger.info(f"🔌 {self.kind} {self.name} connected ({redact_url(url)}{compressed})")
+        self._replay_subscriptions()
+        for callback in self._open_callbacks:
+            self._call(callback)
+        if rest:
+            self._handle_frame(rest)
+
+    async def _handshake(self, conn: Any) -> Any:
+        """
+        Complete the protocol handshake on a fresh transport.
+
+        Returns:
+            Any: Data received after the handshake response (dispatched once connected), or None
+        """
+        await conn.send(handshake_request())
+        response = await asyncio.wait_for(conn.recv(), self.connect_timeout)
+        if self.recorder is not None:
+            self.recorder.record(response, handshake=True)
+        error, rest = parse_handshake_response(self._inflate(response))
+        if error:
+            raise HubConnectionError(f"Handshake rejected: {error}")
+        return rest
+
+    def _replay_subscriptions(self) -> None:
+        for method, arguments in list(self._subscriptions.values()):
+            self.send(method, arguments)
+
+    async def stop(self) -> None:
+        """Close the connection and stop reconnecting (on_close callbacks fire if it was open)."""
+        self._stopping = True
+        task, self._reconnect_task = self._reconnect_task, None
+        if task is not None and task is not asyncio.current_task():
+            task.cancel()
+        await self._connection_lost(None)
+        self._set_state(ConnectionState.DISCONNECTED)
+        if self.recorder is not None:
+            self.recorder.stop()
+
+    async def _close_transport(self, conn: Any) -> None:
+        try:
+            await asyncio.wait_for(conn.close(), 5)
+        except Exception:
+            pass
+
+    async def _connection_lost(self, error: Optional[BaseException]) -> None:
+        """Tear down the current connection once, failing pending invocations."""
+        conn, self._conn = self._conn, None
+        if conn is None:
+            return
+        self.connected = False
+        current = asyncio.current_task()
+        for task in self._tasks:
+            if task is not current:
+                task.cancel()
+        self._tasks = []
+        for future in self._pending.values():
+            if not future.done():
+                future.set_exception(HubConnectionError(f"{self.kind} {self.name} connection closed"))
+        self._pending.clear()
+        while self._backlog:
+            pending = self._backlog.popleft()
+            if inspect.iscoroutine(pending):
+                pending.close()
+        await self._close_transport(conn)
+        if error is not None or self.close_error:
+            reason = self.close_error or str(error) or type(error).__name__
+            if not self._stopping:
+                uptime = time.monotonic() - (self.connected_since or 0)
+                if uptime >= self.failover_stable_seconds:
+                    self.endpoint_failures = 0
+                else:
+                    self._record_failure(f"connection lost after {uptime:.0f}s: {reason}")
+            logger.warning(f"⚠️  {self.kind} {self.name} connection lost: {reason}")
+            for callback in self._error_callbacks:
+                self._call(callback, reason)
+        else:
+            logger.info(f"🔌 {self.kind} {self.name} disconnected")
+        for callback in self._close_callbacks:
+            self._call(callback)
+        if self._stopping:
+            return
+        if self.reconnect and (not self._server_closed or self._close_allows_reconnect):
+            self._set_state(ConnectionState.RECONNECTING, self.close_error or (str(error) if error else None))
+            self._reconnect_task = asyncio.ensure_future(self._reconnect_loop())
+        else:
+            self._set_state(ConnectionState.DISCONNECTED, self.close_error)
+
+    async def _reconnect_loop(self) -> None:
+        """Retry with backoff until connected, stopped or out of attempts."""
+        attempt = backoff_step = 0
+        while not self._stopping:
+            attempt += 1
+            backoff_step += 1
+            if self.reconnect_max_attempts and attempt > self.reconnect_max_attempts:
+                logger.error(f"❌ {self.kind} {self.name} gave up after {self.reconnect_max_attempts} reconnect attempts")
+                self._reconnect_task = None
+                self._set_state(ConnectionState.DISCONNECTED, 'reconnect attempts exhausted')
+                return
+            delay = backoff_delay(backoff_step, self.reconnect_initial_delay, self.reconnect_max_delay,
+                                  self.reconnect_jitter)
+            await asyncio.sleep(delay)
+            self.reconnect_attempts += 1
+            try:
+                await self._connect()
+            except HubConnectionError as e:
+                logger.warning(f"⚠️  {self.kind} {self.name} reconnect attempt {attempt} failed: {e}")
+                if self._record_failure(str(e)):
+                    backoff_step = 0  # Fresh endpoint, start the backoff over
+                continue
+            self.reconnects += 1
+            self._reconnect_task = None
+            self._set_state(ConnectionState.CONNECTED, f"reconnected after {attempt} attempt(s)")
+            return
+
+    def _record_failure(self, reason: str) -> bool:
+        """
+        Count a failure on the current endpoint, failing over once there are failover_after in a row.
+
+        Returns:
+            bool: True if the client switched endpoints
+        """
+        self.endpoint_failures += 1
+        if len(self.endpoints) < 2 or self.endpoint_failures < self.failover_after:
+            return False
+        self._failover(reason)
+        return True
+
+    def _failover(self, reason: str) -> None:
+        """Switch to the next endpoint (used by the next connect attempt)."""
+        previous = self.url
+        self.url = self.endpoints[(self.endpoints.index(previous) + 1) % len(self.endpoints)]
+        event = FailoverEvent(hub=self.name, from_url=redact_url(previous), to_url=redact_url(self.url),
+                              reason=reason, failures=self.endpoint_failures, timestamp=datetime.now(timezone.utc))
+        self.endpoint_failures = 0
+        self.failovers += 1
+        self.last_failover = event
+        logger.warning(f"⚠️  {self.kind} {self.name} failing over {event.from_url} -> {event.to_url}: {reason}")
+        for callback in self._failover_callbacks:
+            self._call(callback, event)
+
+    async def _read_loop(self, conn: Any) -> None:
+        error: Optional[BaseException] = None
+        try:
+            while True:
+                await self._drain_backlog()
+                frame = await conn.recv()
+                self.last_message_at = time.monotonic()
+                if self.recorder is not None:
+                    self.recorder.record(frame)
+                if self._handle_frame(frame):
+                    await self._drain_backlog()
+                    break
+        except asyncio.CancelledError:
+            raise
+        except Exception as e:
+            error = e
+        await self._connection_lost(error)
+
+    async def _write_loop(self, conn: Any, outbox: asyncio.Queue) -> None:
+        try:
+            while True:
+                payload = await outbox.get()
+                await conn.send(payload)
+                self.messages_sent += 1
+        except asyncio.CancelledError:
+            raise
+        except Exception as e:
+            await self._connection_lost(e)
+
+    async def _keepalive_loop(self) -> None:
+        """Ping the server and drop the connection when the server goes silent."""
+        interval = min(i for i in (self.keep_alive_interval, self.server_timeout / 2) if i > 0)
+        last_ping = time.monotonic()
+        while True:
+            await asyncio.sleep(interval)
+            now = time.monotonic()
+            silent = now - (self.last_message_at or now)
+            if self.server_timeout > 0 and silent > self.server_timeout:
+                self.server_timeouts += 1
+                await self._connection_lost(HubConnectionError(f"no message from server for {silent:.0f}s"))
+                return
+            if self.ping_message is not None and self.keep_alive_interval > 0 \
+                    and now - last_ping >= self.keep_alive_interval:
+                self._post(self.ping_message)
+                last_ping = now
+
+    async def force_reconnect(self, reason: str) -> None:
+        """Drop the current connection as lost, so the reconnect logic takes over."""
+        if self._conn is None:
+            return
+        self.forced_reconnects += 1
+        await self._connection_lost(HubConnectionError(reason))
+
+    # ---------------------------
+    # Incoming messages
+    # ---------------------------
+    def _handle_frame(self, frame: Any) -> bool:
+        """
+        Dispatch every message in a frame.
+
+        Returns:
+            bool: True if the server sent a Close message
+        """
+        try:
+            messages = parse_messages(self._inflate(frame))
+        except (ProtocolError, UnicodeDecodeError) as e:
+            self.protocol_errors += 1
+            logger.debug(f"{self.kind} {self.name} dropped malformed frame: {e}")
+            return False
+        self.messages_received += len(messages)
+        dispatch = self._message_dispatch
+        for message in messages:
+            handle = dispatch.get(message.type)
+            if handle is not None:
+                handle(message)
+            elif message.type == MessageType.CLOSE:
+                self.close_error = message.error
+                self._server_closed = True
+                self._close_allows_reconnect = message.allow_reconnect
+                return True
+        return False
+
+    async def feed_frame(self, frame: Any, handshake: bool = False) -> bool:
+        """
+        Process a frame as if it had just been received (used to replay captures offline).
+
+        Args:
+            frame: Frame text or bytes
+            handshake: Frame is a handshake response (only the messages after it are dispatched)
+
+        Returns:
+            bool: True if the frame held a server Close message
+        """
+        self.last_message_at = time.monotonic()
+        if handshake:
+            try:
+                _, frame = parse_handshake_response(self._inflate(frame))
+            except ProtocolError as e:
+                self.protocol_errors += 1
+                logger.debug(f"{self.kind} {self.name} dropped malformed handshake: {e}")
+                return False
+            if not frame:
+                return False
+        closed = self._handle_frame(frame)
+        await self._drain_backlog()
+        return closed
+
+    def _inflate(self, frame: Any) -> Any:
+        """Frame with gzip/zlib compression undone (text frames pass straight through)."""
+        if isinstance(frame, str) or not is_compressed_frame(frame):
+            return frame
+        inflated = inflate_frame(frame)
+        self.compressed_frames += 1
+        self.compressed_bytes += len(frame)
+        self.inflated_bytes += len(inflated)
+        return inflated
+
+    def _on_ping(self, message: HubMessage) -> None:
+        self.pings_received += 1
+
+    def _dispatch_invocation(self, message: HubMessage) -> None:
+        target = message.target or ''
+        key = self._target_keys.get(target)
+        if key is None:
+            key = target.lower()
+            if len(self._target_keys) < 1024:
+                self._target_keys[target] = key
+        handlers = self._handlers.get(key)
+        if not handlers:
+            self.unhandled_invocations += 1
+            return
+        self.invocations_dispatched += 1
+        for handler in handlers:
+            self._call(handler, *message.arguments, backlog=self._backlog)
+
+    def _complete(self, message: HubMessage) -> None:
+        future = self._pending.pop(message.invocation_id or '', None)
+        if future is None or future.done():
+            return
+        if message.error:
+            future.set_exception(HubInvocationError(message.error))
+        else:
+            future.set_result(message.result)
+
+    # ---------------------------
+    # Outgoing messages
+    # ---------------------------
+    def _post(self, payload: str) -> None:
+        """Queue a payload for the writer (thread-safe)."""
+        if not self.connected or self._outbox is None or self._loop is None:
+            raise HubConnectionError(f"{self.kind} {self.name} is not connected")
+        try:
+            running = asyncio.get_running_loop()
+        except RuntimeError:
+            running = None
+        if running is self._loop:
+            self._outbox.put_nowait(payload)
+        else:
+            self._loop.call_soon_threadsafe(self._outbox.put_nowait, payload)
+
+    def send(self, method: str, arguments: Sequence[Any] = ()) -> None:
+        """
+        Invoke a hub method without waiting for a result.
+
+        Raises:
+            HubConnectionError: Not connected
+        """
+        self._post(encode_message(invocation(method, arguments)))
+
+    def subscribe(self, method: str, arguments: Sequence[Any] = ()) -> None:
+        """
+        Invoke a subscription method now (if connected) and again after every reconnect.
+
+        Subscriptions registered before start() are sent once connected.
+        """
+        key = (method, json.dumps(list(arguments), sort_keys=True, default=str))
+        with self._callback_lock:
+            self._subscriptions[key] = (method, list(arguments))
+        if self.connected:
+            self.send(method, arguments)
+
+    def unsubscribe(self, method: str, arguments: Sequence[Any] = (),
+                    unsubscribe_method: Optional[str] = None) -> bool:
+        """
+        Forget a subscription so it is not replayed, invoking unsubscribe_method if connected.
+
+        Returns:
+            bool: True if the subscription was registered
+        """
+        key = (method, json.dumps(list(arguments), sort_keys=True, default=str))
+        with self._callback_lock:
+            found = self._subscriptions.pop(key, None) is not None
+        if found and unsubscribe_method and self.connected:
+            self.send(unsubscribe_method, arguments)
+        return found
+
+    def subscriptions(self) -> List[Tuple[str, List[Any]]]:
+        """Registered (method, arguments) subscriptions in registration order."""
+        with self._callback_lock:
+            return list(self._subscriptions.values())
+
+    async def invoke(self, method: str, arguments: Sequence[Any] = (), timeout: float = 30.0) -> Any:
+        """
+        Invoke a hub method and wait for its completion.
+
+        Returns:
+            Any: The method's result
+
+        Raises:
+            HubConnectionError: Not connected, or the connection closed first
+            HubInvocationError: The server returned an error
+            asyncio.TimeoutError: No completion within timeout
+        """
+        invocation_id = str(next(self._invocation_ids))
+        future = asyncio.get_running_loop().create_future()
+        self._pending[invocation_id] = future
+        try:
+            self._post(encode_message(invocation(method, arguments, invocation_id)))
+            return await asyncio.wait_for(future, timeout)
+        finally:
+            self._pending.pop(invocation_id, None)
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Connection state and counters."""
+        now = time.monotonic()
+        return {
+            "name": self.name,
+            "state": self.state.value,
+            "connected": self.connected,
+            "url": redact_url(self.connected_url or self.url),
+            "connected_seconds": round(now - self.connected_since, 1) if self.connected and self.connected_since else None,
+            "last_message_age": round(now - self.last_message_at, 3) if self.last_message_at else None,
+            "messages_received": self.messages_received,
+            "messages_sent": self.messages_sent,
+            "invocations_dispatched": self.invocations_dispatched,
+            "unhandled_invocations": self.unhandled_invocations,
+            "pings_received": self.pings_received,
+            "pending_invocations": len(self._pending),
+            "handler_errors": self.handler_errors,
+            "protocol_errors": self.protocol_errors,
+            "close_error": self.close_error,
+            "reconnects": self.reconnects,
+            "reconnect_attempts": self.reconnect_attempts,
+            "server_timeouts": self.server_timeouts,
+            "forced_reconnects": self.forced_reconnects,
+            "endpoint": redact_url(self.url),
+            "endpoints": len(self.endpoints),
+            "endpoint_failures": self.endpoint_failures,
+            "failovers": self.failovers,
+            "last_failover": self.last_failover.to_dict() if self.last_failover else None,
+            "subscriptions": len(self._subscriptions),
+            "compression": {
+                "offered": self.compression,
+                "extensions": self.extensions,
+                "compressed_frames": self.compressed_frames,
+                "compressed_bytes": self.compressed_bytes,
+                "inflated_bytes": self.inflated_bytes,
+            },
+            "recording": self.recorder.get_stats() if self.recorder is not None else None,
+        }
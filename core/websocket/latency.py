@@ -0,0 +1,248 @@
+"""
+Feed latency and clock-skew measurement.
+
+Every market hub payload that carries an exchange timestamp (GatewayTrade
+prints, GatewayQuote timestamp/lastUpdated, GatewayDepth entries) is compared
+against the local receive time. The raw delta mixes three things: network
+and gateway delay, our own processing backlog, and the offset between the
+exchange clock and ours. To separate them:
+
+- clock skew is estimated as the smallest delta seen over the skew window
+  (the fastest messages carry almost no delay, so their delta is mostly
+  clock offset); a negative skew means the local clock is behind
+- latency is reported both raw and skew-adjusted (raw minus skew); the
+  adjusted figure is how far behind the fastest path the feed is running,
+  which is what grows when the feed, the gateway or our reader falls behind
+
+Rolling percentiles (p50/p95/p99) cover the last `window` samples per
+stream. is_lagging() compares the adjusted p95 against max_lag_ms so the bot
+can refuse new entries while the feed is behind.
+
+Configuration:
+- FEED_LATENCY_WINDOW: Samples kept per stream for percentiles (default 1000)
+- FEED_MAX_LAG_MS: Adjusted p95 above which the feed counts as lagging (default 1500)
+- FEED_CLOCK_SKEW_WINDOW: Minutes of per-minute minimums used for the skew estimate (default 15)
+- FEED_LATENCY_MIN_SAMPLES: Samples needed before the feed can be judged lagging (default 20)
+- FEED_LAG_BLOCKS_ENTRIES: Reject new bot entries while the feed is lagging; exits still go out (default false)
+"""
+
+import logging
+import math
+import os
+import threading
+import time
+from collections import deque
+from datetime import datetime, timezone
+from typing import Any, Deque, Dict, List, Optional, Tuple
+
+logger = logging.getLogger(__name__)
+
+TIMESTAMP_FIELDS = ('timestamp', 'lastUpdated')
+
+
+def parse_exchange_time(value: Any) -> Optional[float]:
+    """
+    Exchange timestamp as epoch seconds.
+
+    Accepts ISO strings ('Z' or offset; naive = UTC), datetimes and epoch
+    seconds/milliseconds. Returns None for missing or unparseable values.
+    """
+    if value is None or isinstance(value, bool):
+        return None
+    if isinstance(value, (int, float)):
+        return value / 1000.0 if value > 1e11 else float(value)
+    if isinstance(value, str) and value:
+        try:
+            value = datetime.fromisoformat(value.replace('Z', '+00:00'))
+        except ValueError:
+            return None
+    if isinstance(value, datetime):
+        return (value if value.tzinfo else value.replace(tzinfo=timezone.utc)).timestamp()
+    return None
+
+
+def payload_exchange_time(payload: Any) -> Optional[float]:
+    """Latest exchange timestamp in a hub payload (a dict or a list of dicts)."""
+    items = payload if isinstance(payload, list) else [payload]
+    latest = None
+    for item in items:
+        if not isinstance(item, dict):
+            continue
+        for field in TIMESTAMP_FIELDS:
+            ts = parse_exchange_time(item.get(field))
+            if ts is not None:
+                if latest is None or ts > latest:
+                    latest = ts
+                break
+    return latest
+
+
+def _percentile(ordered: List[float], pct: float) -> Optional[float]:
+    """Nearest-rank percentile of a sorted list."""
+    if not ordered:
+        return None
+    rank = min(len(ordered) - 1, max(0, math.ceil(pct / 100.0 * len(ordered)) - 1))
+    return ordered[rank]
+
+
+class FeedLatencyMonitor:
+    """
+    Rolling exchange-to-receive latency per stream, with a clock-skew estimate.
+
+    Usage:
+        monitor = FeedLatencyMonitor()
+        monitor.record_payload('quotes', 'MNQ', data)   # in the hub handler
+        if monitor.is_lagging():
+            ...  # skip new entries
+        monitor.get_feed_latency()
+    """
+
+    def __init__(self, window: Optional[int] = None, max_lag_ms: Optional[float] = None,
+                 skew_window_minutes: Optional[int] = None, min_samples: Optional[int] = None):
+        """
+        Initialize monitor.
+
+        Args:
+            window: Samples kept per stream (env: FEED_LATENCY_WINDOW)
+            max_lag_ms: Adjusted p95 lag threshold (env: FEED_MAX_LAG_MS)
+            skew_window_minutes: Skew estimate horizon (env: FEED_CLOCK_SKEW_WINDOW)
+            min_samples: Samples needed before judging lag (env: FEED_LATENCY_MIN_SAMPLES)
+        """
+        self.window = window if window is not None else int(os.getenv('FEED_LATENCY_WINDOW', '1000'))
+        self.max_lag_ms = max_lag_ms if max_lag_ms is not None else float(os.getenv('FEED_MAX_LAG_MS', '1500'))
+        self.skew_window_minutes = skew_window_minutes if skew_window_minutes is not None else \
+            int(os.getenv('FEED_CLOCK_SKEW_WINDOW', '15'))
+        self.min_samples = min_samples if min_samples is not None else \
+            int(os.getenv('FEED_LATENCY_MIN_SAMPLES', '20'))
+        self._samples: Dict[str, Deque[float]] = {}  # stream -> raw deltas (ms)
+        self._minute_minimums: Deque[List[float]] = deque()  # [minute, min delta ms]
+        self._last: Dict[str, Tuple[float, float, str]] = {}  # stream -> (raw ms, received_at, symbol)
+        self._lock = threading.Lock()
+        self.samples_recorded = 0
+        self.samples_skipped = 0
+
+    def record(self, stream: str, exchange_time: Any, received_at: Optional[float] = None,
+               symbol: str = '') -> Optional[float]:
+        """
+        Record one message.
+
+        Args:
+            stream: 'quotes', 'trades', 'depth', ...
+            exchange_time: Exchange timestamp (ISO string, datetime or epoch)
+            received_at: Local receive time, epoch seconds (default: now)
+            symbol: Symbol, reported with the latest sample
+
+        Returns:
+            Optional[float]: Raw latency in ms, None if the timestamp was unusable
+        """
+        exchange_ts = parse_exchange_time(exchange_time)
+        if exchange_ts is None:
+            self.samples_skipped += 1
+            return None
+        received = received_at if received_at is not None else time.time()
+        delta_ms = (received - exchange_ts) * 1000.0
+        minute = received // 60
+        with self._lock:
+            samples = self._samples.get(stream)
+            if samples is None:
+                samples = self._samples[stream] = deque(maxlen=self.window)
+            samples.append(delta_ms)
+            self._last[stream] = (delta_ms, received, symbol)
+            if self._minute_minimums and self._minute_minimums[-1][0] == minute:
+                if delta_ms < self._minute_minimums[-1][1]:
+                    self._minute_minimums[-1][1] = delta_ms
+            else:
+                self._minute_minimums.append([minute, delta_ms])
+            while self._minute_minimums and self._minute_minimums[0][0] <= minute - self.skew_window_minutes:
+                self._minute_minimums.popleft()
+            self.samples_recorded += 1
+        return delta_ms
+
+    def record_payload(self, stream: str, symbol: str, payload: Any,
+                       received_at: Optional[float] = None) -> Optional[float]:
+        """Record the latest exchange timestamp found in a hub payload (no-op without one)."""
+        exchange_ts = payload_exchange_time(payload)
+        if exchange_ts is None:
+            self.samples_skipped += 1
+            return None
+        return self.record(stream, exchange_ts, received_at, symbol)
+
+    @property
+    def clock_skew_ms(self) -> Optional[float]:
+        """Estimated local-minus-exchange clock offset (ms), None before any sample."""
+        with self._lock:
+            if not self._minute_minimums:
+                return None
+            return min(m[1] for m in self._minute_minimums)
+
+    def _summary(self, deltas: List[float], skew: Optional[float]) -> Dict[str, Any]:
+        ordered = sorted(deltas)
+        adjusted = [d - skew for d in ordered] if skew is not None else ordered
+
+        def rounded(value: Optional[float]) -> Optional[float]:
+            return round(value, 1) if value is not None else None
+
+        return {
+            "samples": len(ordered),
+            "p50_ms": rounded(_percentile(ordered, 50)),
+            "p95_ms": rounded(_percentile(ordered, 95)),
+            "p99_ms": rounded(_percentile(ordered, 99)),
+            "max_ms": rounded(ordered[-1] if ordered else None),
+            "adjusted_p50_ms": rounded(_percentile(adjusted, 50)),
+            "adjusted_p95_ms": rounded(_percentile(adjusted, 95)),
+            "adjusted_p99_ms": rounded(_percentile(adjusted, 99)),
+        }
+
+    def get_feed_latency(self, stream: Optional[str] = None) -> Dict[str, Any]:
+        """
+        Latency percentiles, skew estimate and lag verdict.
+
+        Args:
+            stream: Limit to one stream (default: all streams combined, plus a per-stream breakdown)
+
+        Returns:
+            Dict: p50/p95/p99/max (raw and skew-adjusted, ms), clock_skew_ms, lagging, per-stream details
+        """
+        skew = self.clock_skew_ms
+        now = time.time()
+        with self._lock:
+            if stream is not None:
+                streams = {stream: list(self._samples.get(stream, ()))}
+            else:
+                streams = {name: list(samples) for name, samples in self._samples.items()}
+            last = dict(self._last)
+        combined = [d for deltas in streams.values() for d in deltas]
+        result = self._summary(combined, skew)
+        adjusted_p95 = result["adjusted_p95_ms"]
+        result.update({
+            "clock_skew_ms": round(skew, 1) if skew is not None else None,
+            "max_lag_ms": self.max_lag_ms,
+            "lagging": bool(result["samples"] >= self.min_samples and adjusted_p95 is not None
+                            and adjusted_p95 > self.max_lag_ms),
+            "streams": {},
+        })
+        for name, deltas in streams.items():
+            details = self._summary(deltas, skew)
+            if name in last:
+                raw, received, symbol = last[name]
+                details.update({"last_ms": round(raw, 1), "last_symbol": symbol,
+                                "last_age_seconds": round(now - received, 3)})
+            result["streams"][name] = details
+        return result
+
+    def is_lagging(self, stream: Optional[str] = None) -> bool:
+        """True when the skew-adjusted p95 latency exceeds max_lag_ms."""
+        return self.get_feed_latency(stream)["lagging"]
+
+    def reset(self) -> None:
+        """Drop all samples (e.g. after a reconnect or a clock correction)."""
+        with self._lock:
+            self._samples.clear()
+            self._minute_minimums.clear()
+            self._last.clear()
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Latency summary plus counters."""
+        stats = self.get_feed_latency()
+        stats.update({"samples_recorded": self.samples_recorded, "samples_skipped": self.samples_skipped})
+        return stats
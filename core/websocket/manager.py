@@ -0,0 +1,315 @@
+"""
+Multiplexed hub connections.
+
+WebSocketManager runs the user hub and one or more market hub connections
+side by side and presents them as one feed:
+- Market data can be sharded across several market hub connections by
+  symbol, so one busy contract (or one stalled socket) doesn't hold up the
+  rest. A symbol always maps to the same shard: an explicit shard map entry,
+  otherwise a stable hash of the root symbol
+- Every hub publishes into one shared ChannelFanout, so add_listener(),
+  add_consumer() and events() see market and user events together, and
+  block-policy consumers apply backpressure to whichever hub feeds them
+- get_health() reports state, endpoint, last message age, reconnect and
+  failover counts per connection, plus an overall healthy flag
+- Backup URLs are handed to every connection, which fails over on its own
+  (see SignalRClient); on_failover() hears about any of them switching
+
+Usage:
+    manager = WebSocketManager(access_token_factory=lambda: bot.session_token, market_shards=2)
+    manager.subscribe('CON.F.US.MNQ.Z25', quotes=True, trades=True)
+    manager.subscribe_account(12345)
+    await manager.start()
+    async for event in manager.events():
+        ...  # Quote, Trade, DepthUpdate, OrderUpdate, PositionUpdate, ...
+
+Configuration:
+- PROJECT_X_MARKET_HUB_URL: Market hub URL (default https://rtc.topstepx.com/hubs/market)
+- PROJECT_X_USER_HUB_URL: User hub URL (default https://rtc.topstepx.com/hubs/user)
+- PROJECT_X_MARKET_HUB_BACKUP_URLS: Comma-separated market hub URLs to fail over to (default none)
+- PROJECT_X_USER_HUB_BACKUP_URLS: Comma-separated user hub URLs to fail over to (default none)
+- MARKET_HUB_SHARDS: Market hub connections (default 1)
+- MARKET_HUB_SHARD_MAP: Pinned shards, e.g. 'MNQ:0,MES:1' (default none)
+"""
+
+import asyncio
+import logging
+import os
+import threading
+import time
+import zlib
+from typing import Any, Callable, Dict, List, Optional, Tuple, Union
+
+from core.websocket.channels import BoundedChannel, ChannelFanout, EventStream, OverflowPolicy
+from core.websocket.client import FailoverEvent, HubConnectionError, SignalRClient, parse_url_list
+from core.websocket.market_hub import MarketHubClient, symbol_from_contract_id
+from core.websocket.user_hub import DEFAULT_USER_HUB_URL, UserHubClient
+
+logger = logging.getLogger(__name__)
+
+DEFAULT_MARKET_HUB_URL = 'https://rtc.topstepx.com/hubs/market'
+
+
+def parse_shard_map(value: Optional[str]) -> Dict[str, int]:
+    """
+    Parse 'MNQ:0,MES:1' into {'MNQ': 0, 'MES': 1}.
+
+    Raises:
+        ValueError: Malformed entry
+    """
+    shard_map: Dict[str, int] = {}
+    for entry in (value or '').split(','):
+        entry = entry.strip()
+        if not entry:
+            continue
+        symbol, sep, shard = entry.partition(':')
+        if not sep or not symbol.strip() or not shard.strip().isdigit():
+            raise ValueError(f"Invalid shard map entry '{entry}', expected SYMBOL:INDEX")
+        shard_map[symbol.strip().upper()] = int(shard)
+    return shard_map
+
+
+class WebSocketManager:
+    """
+    User hub plus sharded market hub connections behind one event feed.
+    """
+
+    def __init__(self, access_token_factory: Optional[Callable[[], Optional[str]]] = None,
+                 market_url: Optional[str] = None, user_url: Optional[str] = None,
+                 market_shards: Optional[int] = None, shard_map: Optional[Dict[str, int]] = None,
+                 user_hub: bool = True, session_calendar=None, market_backup_urls: Optional[List[str]] = None,
+                 user_backup_urls: Optional[List[str]] = None, **client_kwargs: Any):
+        """
+        Initialize manager (does not connect).
+
+        Args:
+            access_token_factory: Returns the session token
+            market_url: Market hub URL (env: PROJECT_X_MARKET_HUB_URL)
+            user_url: User hub URL (env: PROJECT_X_USER_HUB_URL)
+            market_shards: Market hub connections (env: MARKET_HUB_SHARDS)
+            shard_map: Symbol -> shard index overrides (env: MARKET_HUB_SHARD_MAP)
+            user_hub: Also connect the user hub
+            session_calendar: SessionCalendar for the market feed watchdogs
+            market_backup_urls: Market hub failover URLs (env: PROJECT_X_MARKET_HUB_BACKUP_URLS)
+            user_backup_urls: User hub failover URLs (env: PROJECT_X_USER_HUB_BACKUP_URLS)
+            **client_kwargs: Passed to every SignalRClient
+
+        Raises:
+            ValueError: market_shards < 1, or a shard map entry out of range
+        """
+        market_url = market_url or os.getenv('PROJECT_X_MARKET_HUB_URL', DEFAULT_MARKET_HUB_URL)
+        user_url = user_url or os.getenv('PROJECT_X_USER_HUB_URL', DEFAULT_USER_HUB_URL)
+        shards = market_shards if market_shards is not None else int(os.getenv('MARKET_HUB_SHARDS', '1'))
+        if shards < 1:
+            raise ValueError(f"market_shards must be >= 1, got {shards}")
+        self.shard_map = {k.upper(): v for k, v in shard_map.items()} if shard_map is not None else \
+            parse_shard_map(os.getenv('MARKET_HUB_SHARD_MAP'))
+        for symbol, index in self.shard_map.items():
+            if not 0 <= index < shards:
+                raise ValueError(f"Shard {index} for {symbol} is out of range (0-{shards - 1})")
+
+        if market_backup_urls is None:
+            market_backup_urls = parse_url_list(os.getenv('PROJECT_X_MARKET_HUB_BACKUP_URLS'))
+        if user_backup_urls is None:
+            user_backup_urls = parse_url_list(os.getenv('PROJECT_X_USER_HUB_BACKUP_URLS'))
+
+        self.channels = ChannelFanout()
+        self.market_hubs: List[MarketHubClient] = [
+            MarketHubClient(market_url, access_token_factory, session_calendar=session_calendar,
+                            channels=self.channels, name=f"market-{i}" if shards > 1 else 'market',
+                            backup_urls=market_backup_urls, **client_kwargs)
+            for i in range(shards)
+        ]
+        self.user_hub: Optional[UserHubClient] = UserHubClient(
+            user_url, access_token_factory, channels=self.channels, backup_urls=user_backup_urls,
+            **client_kwargs) if user_hub else None
+        self._symbols: Dict[str, int] = {}  # contract id -> shard it was subscribed on
+        self._lock = threading.Lock()
+        self.started_at: Optional[float] = None
+
+    @property
+    def hubs(self) -> List[Union[MarketHubClient, UserHubClient]]:
+        return list(self.market_hubs) + ([self.user_hub] if self.user_hub is not None else [])
+
+    @property
+    def clients(self) -> Dict[str, SignalRClient]:
+        """Connections by name."""
+        return {hub.client.name: hub.client for hub in self.hubs}
+
+    # ---------------------------
+    # Market data routing
+    # ---------------------------
+    def shard_for(self, symbol: str) -> int:
+        """Market hub shard of a symbol or contract id (stable across restarts)."""
+        root = (symbol_from_contract_id(symbol) or symbol).upper()
+        if root in self.shard_map:
+            return self.shard_map[root]
+        return zlib.crc32(root.encode('utf-8')) % len(self.market_hubs)
+
+    def market_hub_for(self, symbol: str) -> MarketHubClient:
+        return self.market_hubs[self.shard_for(symbol)]
+
+    def subscribe(self, contract_id: str, quotes: bool = True, trades: bool = False, depth: bool = False) -> int:
+        """
+        Subscribe a contract on its shard (replayed after reconnects).
+
+        Returns:
+            int: Shard index
+        """
+        shard = self.shard_for(contract_id)
+        self.market_hubs[shard].subscribe(contract_id, quotes=quotes, trades=trades, depth=depth)
+        with self._lock:
+            self._symbols[contract_id] = shard
+        return shard
+
+    def unsubscribe(self, contract_id: str, quotes: bool = True, trades: bool = False, depth: bool = False) -> None:
+        """Unsubscribe a contract from its shard."""
+        with self._lock:
+            shard = self._symbols.get(contract_id, self.shard_for(contract_id))
+        hub = self.market_hubs[shard]
+        hub.unsubscribe(contract_id, quotes=quotes, trades=trades, depth=depth)
+        if not any(contract_id in arguments for _, arguments in hub.client.subscriptions()):
+            with self._lock:
+                self._symbols.pop(contract_id, None)
+
+    # ---------------------------
+    # Account streaming
+    # ---------------------------
+    def subscribe_account(self, account_id: Union[int, str]) -> None:
+        """
+        Stream orders, positions and fills for an account.
+
+        Raises:
+            RuntimeError: Manager was created without the user hub
+        """
+        if self.user_hub is None:
+            raise RuntimeError("WebSocketManager was created without the user hub")
+        self.user_hub.subscribe_account(account_id)
+
+    def unsubscribe_account(self, account_id: Union[int, str]) -> None:
+        if self.user_hub is not None:
+            self.user_hub.unsubscribe_account(account_id)
+
+    # ---------------------------
+    # Unified event delivery
+    # ---------------------------
+    def add_listener(self, callback: Callable[[Any], Any]) -> Callable[[], None]:
+        """
+        Subscribe to market and user events from every connection.
+
+        Returns:
+            Callable: Unsubscribe function
+        """
+        removers = [hub.add_listener(callback) for hub in self.hubs]
+
+        def unsubscribe():
+            for remove in removers:
+                remove()
+        return unsubscribe
+
+    def add_consumer(self, name: str, callback: Callable[[Any], Any], capacity: Optional[int] = None,
+                     policy: Union[OverflowPolicy, str, None] = None) -> BoundedChannel:
+        """Deliver every connection's events to a callback through its own bounded channel."""
+        return self.channels.add_consumer(name, callback, capacity, policy)
+
+    def remove_consumer(self, name: str) -> None:
+        self.channels.remove(name)
+
+    def events(self, types: Union[type, Tuple[type, ...], None] = None, capacity: Optional[int] = None,
+               policy: Union[OverflowPolicy, str, None] = None) -> EventStream:
+        """
+        Events from every connection as one async iterator.
+
+        Args:
+            types: Only these classes, e.g. (Trade, OrderUpdate) (default: everything)
+            capacity: Channel capacity (env: FEED_CHANNEL_CAPACITY)
+            policy: Overflow policy (env: FEED_CHANNEL_POLICY)
+        """
+        return self.channels.events(types, capacity, policy)
+
+    def on_failover(self, callback: Callable[[FailoverEvent], Any]) -> Callable[[], None]:
+        """
+        Hear about any connection switching endpoints (FailoverEvent.hub names the connection).
+
+        Returns:
+            Callable: Unsubscribe function
+        """
+        removers = [hub.client.on_failover(callback) for hub in self.hubs]
+
+        def unsubscribe():
+            for remove in removers:
+                remove()
+        return unsubscribe
+
+    # ---------------------------
+    # Lifecycle
+    # ---------------------------
+    async def start(self) -> None:
+        """
+        Connect every hub concurrently.
+
+        Connections that come up stay up when others fail; calling start()
+        again retries only the ones that are down.
+
+        Raises:
+            HubConnectionError: One or more connections failed (all failures in the message)
+        """
+        self.started_at = self.started_at or time.monotonic()
+        hubs = [hub for hub in self.hubs if not hub.connected]
+        results = await asyncio.gather(*(hub.start() for hub in hubs), return_exceptions=True)
+        failures = [f"{hub.client.name}: {result}" for hub, result in zip(hubs, results)
+                    if isinstance(result, BaseException)]
+        if failures:
+            logger.error(f"❌ {len(failures)} of {len(self.hubs)} hub connection(s) failed: {'; '.join(failures)}")
+            raise HubConnectionError("; ".join(failures))
+        logger.info(f"🔌 WebSocketManager connected {len(self.hubs)} hub(s) "
+                    f"({len(self.market_hubs)} market, {'1' if self.user_hub else '0'} user)")
+
+    async def stop(self) -> None:
+        """Disconnect every hub and end event streams."""
+        await asyncio.gather(*(hub.stop() for hub in self.hubs), return_exceptions=True)
+        await self.channels.close()
+        self.started_at = None
+
+    @property
+    def connected(self) -> bool:
+        """All connections are up."""
+        return all(hub.connected for hub in self.hubs)
+
+    def get_health(self) -> Dict[str, Any]:
+        """Per-connection health plus an overall flag."""
+        connections = {}
+        for hub in self.hubs:
+            stats = hub.client.get_stats()
+            health = {
+                "state": stats["state"],
+                "connected": stats["connected"],
+                "endpoint": stats["endpoint"],
+                "connected_seconds": stats["connected_seconds"],
+                "last_message_age": stats["last_message_age"],
+                "reconnects": stats["reconnects"],
+                "failovers": stats["failovers"],
+                "subscriptions": stats["subscriptions"],
+            }
+            if isinstance(hub, MarketHubClient):
+                with self._lock:
+                    health["contracts"] = sorted(cid for cid, shard in self._symbols.items()
+                                                 if self.market_hubs[shard] is hub)
+                ages = hub.watchdog.feed_ages()
+                health["oldest_feed_age"] = round(max(ages.values()), 1) if ages else None
+                health["feed_alerts"] = hub.watchdog.alerts_raised
+            connections[hub.client.name] = health
+        return {
+            "healthy": all(c["connected"] for c in connections.values()),
+            "connections": connections,
+        }
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Health plus full stats of every hub and the shared channels."""
+        stats = self.get_health()
+        stats.update({
+            "hubs": {hub.client.name: hub.get_stats() for hub in self.hubs},
+            "channels": self.channels.get_stats(),
+            "shard_map": dict(self.shard_map),
+        })
+        return stats
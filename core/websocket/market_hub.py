@@ -0,0 +1,508 @@
+"""
+TopStepX market hub on top of SignalRClient.
+
+Turns market hub invocations into typed MarketEvents:
+- GatewayQuote (contractId, quote) -> Quote
+- GatewayTrade (contractId, [trade, ...]) -> Trade per print
+- GatewayDepth (contractId, [DOM entry, ...] or {bids, asks}) -> DepthUpdate
+
+Payload shapes are normalized the same way the bot's signalrcore handlers do
+it: (contractId, data), a single dict carrying contractId, or a [contractId,
+data] pair. Symbols come from the contract id (CON.F.US.MNQ.Z25 -> MNQ).
+
+FeedWatchdog tracks the time since the last data message of every active
+subscription (stream + contract). When a subscribed feed stays silent longer
+than stale_after while its session is open, it raises a FeedAlert to its
+callbacks and forces the hub to reconnect, which catches connections that
+still answer pings but no longer deliver data.
+
+Consumers that may fall behind should use add_consumer() or events()
+instead of add_listener(): each gets its own bounded channel with an
+overflow policy (see core.websocket.channels). Channels with the block
+policy make the hub stop reading from the socket until they have room.
+
+Configuration:
+- MARKET_FEED_STALE_SECONDS: Silence per subscription before reconnecting, 0 = off (default 120)
+- MARKET_FEED_WATCHDOG_INTERVAL: Seconds between watchdog checks (default 5)
+"""
+
+import asyncio
+import logging
+import os
+import threading
+import time
+from dataclasses import dataclass
+from datetime import datetime, timezone
+from typing import Any, Callable, Dict, List, Optional, Sequence, Tuple, Union
+
+from core.clock_sync import get_server_clock, server_now
+from core.depth_book import DOM_ASK_TYPES, DOM_BID_TYPES
+from core.market_events import DepthUpdate, MarketEvent, Quote, Trade
+from core.websocket.channels import BoundedChannel, ChannelFanout, EventStream, OverflowPolicy
+from core.websocket.client import SignalRClient
+from core.websocket.latency import FeedLatencyMonitor
+
+logger = logging.getLogger(__name__)
+
+QUOTE_TARGETS = ('GatewayQuote',)
+TRADE_TARGETS = ('GatewayTrade',)
+DEPTH_TARGETS = ('GatewayDepth',)
+
+SUBSCRIBE_METHODS = {
+    'quotes': ('SubscribeContractQuotes', 'UnsubscribeContractQuotes'),
+    'trades': ('SubscribeContractTrades', 'UnsubscribeContractTrades'),
+    'depth': ('SubscribeContractMarketDepth', 'UnsubscribeContractMarketDepth'),
+}
+STREAM_TARGETS = {'quotes': QUOTE_TARGETS, 'trades': TRADE_TARGETS, 'depth': DEPTH_TARGETS}
+_STREAM_BY_METHOD = {methods[0]: stream for stream, methods in SUBSCRIBE_METHODS.items()}
+_STREAM_BY_TARGET = {target.lower(): stream for stream, targets in STREAM_TARGETS.items() for target in targets}
+
+
+def stream_for_method(method: str) -> Optional[str]:
+    """Stream ('quotes'/'trades'/'depth') of a SubscribeContract* method, None for other methods."""
+    return _STREAM_BY_METHOD.get(method)
+
+
+def stream_for_target(target: str) -> Optional[str]:
+    """Stream ('quotes'/'trades'/'depth') of a market hub invocation target, None for others."""
+    return _STREAM_BY_TARGET.get(target.lower())
+
+
+def symbol_from_contract_id(contract_id: Any) -> str:
+    """Root symbol of a contract id ('CON.F.US.MNQ.Z25' -> 'MNQ'); '' if not a contract id."""
+    if not isinstance(contract_id, str) or '.' not in contract_id:
+        return ''
+    parts = contract_id.split('.')
+    return parts[-2].upper()
+
+
+def split_payload(arguments: Sequence[Any]) -> Tuple[str, Any]:
+    """(contractId, payload) from invocation arguments in any of the gateway shapes."""
+    if len(arguments) >= 2:
+        return arguments[0] or '', arguments[1]
+    if len(arguments) == 1:
+        only = arguments[0]
+        if isinstance(only, dict):
+            return only.get('contractId') or '', only
+        if isinstance(only, (list, tuple)) and len(only) >= 2 and isinstance(only[0], str):
+            return only[0] or '', only[1]
+        return '', only
+    return '', None
+
+
+def _resolve_symbol(contract_id: str, payload: Any) -> str:
+    symbol = symbol_from_contract_id(contract_id)
+    if not symbol and isinstance(payload, dict):
+        symbol_id = str(payload.get('symbol') or payload.get('symbolId') or '').upper()
+        symbol = symbol_id.split('.')[-1] if symbol_id else ''
+    return symbol
+
+
+def _depth_from_dom(symbol: str, entries: List[Any], timestamp: datetime) -> Optional[DepthUpdate]:
+    """DepthUpdate from DOM entries (bid/ask levels only; trades and resets are skipped)."""
+    bids, asks = [], []
+    for entry in entries:
+        if not isinstance(entry, dict) or entry.get('price') is None:
+            continue
+        level = (float(entry['price']), int(entry.get('volume') or 0))
+        if entry.get('type') in DOM_BID_TYPES:
+            bids.append(level)
+        elif entry.get('type') in DOM_ASK_TYPES:
+            asks.append(level)
+    if not bids and not asks:
+        return None
+    return DepthUpdate(symbol=symbol, timestamp=timestamp, bids=tuple(bids), asks=tuple(asks))
+
+
+def market_events_from_invocation(target: str, arguments: Sequence[Any],
+                                  timestamp: Optional[datetime] = None) -> List[MarketEvent]:
+    """
+    Typed events for one market hub invocation.
+
+    Args:
+        target: Invocation target (GatewayQuote/GatewayTrade/GatewayDepth, case-insensitive)
+        arguments: Invocation arguments
+        timestamp: Receive time for quotes/depth (default: now on the server clock); trades use their own timestamp
+
+    Returns:
+        List[MarketEvent]: Parsed events (empty for unknown targets or unresolvable symbols)
+    """
+    parser = _EVENT_PARSERS.get(target) or _EVENT_PARSERS.get(target.lower())
+    if parser is None:
+        return []
+    contract_id, payload = split_payload(arguments)
+    symbol = _resolve_symbol(contract_id, payload)
+    if not symbol or payload is None:
+        return []
+    return parser(symbol, payload, timestamp or server_now())
+
+
+def _quote_events(symbol: str, payload: Any, received: datetime) -> List[MarketEvent]:
+    return [Quote.from_gateway(symbol, payload, received)] if isinstance(payload, dict) else []
+
+
+def _trade_events(symbol: str, payload: Any, received: datetime) -> List[MarketEvent]:
+    trades = payload if isinstance(payload, list) else [payload]
+    return [Trade.from_gateway(symbol, t) for t in trades if isinstance(t, dict) and t.get('price') is not None]
+
+
+def _depth_events(symbol: str, payload: Any, received: datetime) -> List[MarketEvent]:
+    if isinstance(payload, dict) and ('bids' in payload or 'asks' in payload or 'orderBook' in payload):
+        return [DepthUpdate.from_gateway(symbol, payload, received)]
+    depth = _depth_from_dom(symbol, payload if isinstance(payload, list) else [payload], received)
+    return [depth] if depth is not None else []
+
+
+# Target -> event parser, under both the exact and lower-cased target names
+_EVENT_PARSERS: Dict[str, Callable[[str, Any, datetime], List[MarketEvent]]] = {
+    name: parser
+    for targets, parser in ((QUOTE_TARGETS, _quote_events), (TRADE_TARGETS, _trade_events),
+                            (DEPTH_TARGETS, _depth_events))
+    for target in targets
+    for name in (target, target.lower())
+}
+
+
+class MarketHubClient:
+    """
+    Market hub connection publishing typed events.
+
+    Usage:
+        hub = MarketHubClient('https://rtc.topstepx.com/hubs/market', access_token_factory=lambda: token)
+        hub.add_listener(bot._publish_market_event)
+        await hub.start()
+        hub.subscribe('CON.F.US.MNQ.Z25', quotes=True, trades=True)
+        async for event in hub.events(types=Trade):
+            ...
+    """
+
+    def __init__(self, url: str, access_token_factory: Optional[Callable[[], Optional[str]]] = None,
+                 client: Optional[SignalRClient] = None, stale_after: Optional[float] = None,
+                 session_calendar=None, channels: Optional[ChannelFanout] = None, name: str = 'market',
+                 **client_kwargs: Any):
+        """
+        Initialize market hub.
+
+        Args:
+            url: Market hub URL
+            access_token_factory: Returns the session token
+            client: Existing SignalRClient to use instead of creating one
+            stale_after: Feed watchdog silence threshold (env: MARKET_FEED_STALE_SECONDS)
+            session_calendar: SessionCalendar for the watchdog
+            channels: Shared fan-out for consumers/events() (default: one owned by this hub)
+            name: Connection name in logs and stats
+            **client_kwargs: Passed to SignalRClient
+        """
+        self.client = client or SignalRClient(url, access_token_factory, name=name, **client_kwargs)
+        self.watchdog = FeedWatchdog(self.client, stale_after=stale_after, session_calendar=session_calendar)
+        self._listeners: Tuple[Callable[[MarketEvent], Any], ...] = ()
+        self._lock = threading.Lock()
+        self._owns_channels = channels is None
+        self.channels = channels if channels is not None else ChannelFanout()
+        self.latency = FeedLatencyMonitor()
+        self.client.on_failover(lambda event: self.latency.reset())  # Another gateway has its own clock skew
+        self.events_published = 0
+        self.parse_errors = 0
+        self.listener_errors = 0
+        for target in QUOTE_TARGETS + TRADE_TARGETS + DEPTH_TARGETS:
+            self.client.on(target, self._handler(target))
+
+    def _handler(self, target: str) -> Callable[..., Any]:
+        stream = stream_for_target(target)
+
+        def handle(*arguments: Any) -> Any:
+            received_at = time.time()
+            try:
+                events = market_events_from_invocation(target, arguments)
+            except (KeyError, TypeError, ValueError) as e:
+                self.parse_errors += 1
+                logger.debug(f"Unparseable {target} payload: {e}")
+                return None
+            if events and stream:
+                payload = split_payload(arguments)[1]
+                self.latency.record_payload(stream, events[0].symbol, payload, received_at)
+                get_server_clock().record_payload(payload, received_at)
+            for event in events:
+                self._publish(event)
+            if self.channels.has_blocking:
+                return self._publish_channels(events)  # Awaited by the client before the next frame
+            for event in events:
+                self.channels.publish(event)
+            return None
+        return handle
+
+    async def _publish_channels(self, events: List[MarketEvent]) -> None:
+        for event in events:
+            await self.channels.publish_async(event)
+
+    def add_listener(self, callback: Callable[[MarketEvent], Any]) -> Callable[[], None]:
+        """
+        Subscribe to typed market events.
+
+        Returns:
+            Callable: Unsubscribe function
+        """
+        with self._lock:
+            self._listeners += (callback,)
+
+        def unsubscribe():
+            with self._lock:
+                self._listeners = tuple(cb for cb in self._listeners if cb is not callback)
+        return unsubscribe
+
+    def add_consumer(self, name: str, callback: Callable[[MarketEvent], Any], capacity: Optional[int] = None,
+                     policy: Union[OverflowPolicy, str, None] = None) -> BoundedChannel:
+        """
+        Deliver events to a callback through its own bounded channel (call from the event loop).
+
+        Args:
+            name: Consumer name (key in get_stats()['channels'])
+            callback: Sync or async event callback
+            capacity: Channel capacity (env: FEED_CHANNEL_CAPACITY)
+            policy: 'block', 'drop_oldest' or 'conflate' (env: FEED_CHANNEL_POLICY)
+
+        Returns:
+            BoundedChannel: The consumer's channel
+        """
+        return self.channels.add_consumer(name, callback, capacity, policy)
+
+    def remove_consumer(self, name: str) -> None:
+        """Remove a consumer added with add_consumer()."""
+        self.channels.remove(name)
+
+    def events(self, types: Union[type, Tuple[type, ...], None] = None, capacity: Optional[int] = None,
+               policy: Union[OverflowPolicy, str, None] = None) -> EventStream:
+        """
+        Typed events as an async iterator (`async for event in hub.events():`).
+
+        Args:
+            types: Only these classes, e.g. (Quote, Trade) (default: all market events)
+            capacity: Channel capacity (env: FEED_CHANNEL_CAPACITY)
+            policy: Overflow policy (env: FEED_CHANNEL_POLICY)
+
+        Returns:
+            EventStream: Ends when the hub is stopped or the stream is closed
+        """
+        return self.channels.events(types, capacity, policy)
+
+    def _publish(self, event: MarketEvent) -> None:
+        self.events_published += 1
+        for listener in self._listeners:
+            try:
+                listener(event)
+            except Exception as e:
+                self.listener_errors += 1
+                logger.debug(f"Market hub listener error ({event.type} {event.symbol}): {e}")
+
+    async def start(self) -> None:
+        """Connect the underlying hub and start the feed watchdog."""
+        await self.client.start()
+        self.watchdog.start()
+
+    async def stop(self) -> None:
+        """Stop the watchdog and disconnect the underlying hub."""
+        self.watchdog.stop()
+        await self.client.stop()
+        if self._owns_channels:
+            await self.channels.close()
+
+    @property
+    def connected(self) -> bool:
+        return self.client.connected
+
+    def subscribe(self, contract_id: str, quotes: bool = True, trades: bool = False, depth: bool = False) -> None:
+        """Subscribe to a contract's streams (replayed after reconnects)."""
+        for stream, wanted in (('quotes', quotes), ('trades', trades), ('depth', depth)):
+            if wanted:
+                self.client.subscribe(SUBSCRIBE_METHODS[stream][0], [contract_id])
+
+    def unsubscribe(self, contract_id: str, quotes: bool = True, trades: bool = False, depth: bool = False) -> None:
+        """Unsubscribe from a contract's streams."""
+        for stream, wanted in (('quotes', quotes), ('trades', trades), ('depth', depth)):
+            if wanted:
+                subscribe_method, unsubscribe_method = SUBSCRIBE_METHODS[stream]
+                self.client.unsubscribe(subscribe_method, [contract_id], unsubscribe_method)
+
+    def get_feed_latency(self, stream: Optional[str] = None) -> Dict[str, Any]:
+        """Exchange-to-receive latency percentiles and clock-skew estimate (see core.websocket.latency)."""
+        return self.latency.get_feed_latency(stream)
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Hub connection stats plus event counters."""
+        stats = self.client.get_stats()
+        stats.update({
+            "events_published": self.events_published,
+            "parse_errors": self.parse_errors,
+            "listener_errors": self.listener_errors,
+            "channels": self.channels.get_stats(),
+            "feed_latency": self.latency.get_stats(),
+            "feed_watchdog": self.watchdog.get_stats(),
+        })
+        return stats
+
+
+@dataclass(frozen=True)
+class FeedAlert:
+    """A subscribed feed went silent."""
+    stream: str
+    contract_id: str
+    symbol: str
+    silent_seconds: float
+    timestamp: datetime
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {"stream": self.stream, "contract_id": self.contract_id, "symbol": self.symbol,
+                "silent_seconds": round(self.silent_seconds, 1), "timestamp": self.timestamp.isoformat()}
+
+
+class FeedWatchdog:
+    """
+    Per-subscription stale feed detector for a market hub client.
+
+    Usage:
+        watchdog = FeedWatchdog(hub_client, session_calendar=calendar)
+        watchdog.on_alert(lambda alert: logger.warning(alert.to_dict()))
+        watchdog.start()
+    """
+
+    def __init__(self, client: SignalRClient, stale_after: Optional[float] = None,
+                 check_interval: Optional[float] = None, session_calendar=None):
+        """
+        Initialize watchdog (data handlers are registered on the client immediately).
+
+        Args:
+            client: Hub client whose subscribe() registrations are watched
+            stale_after: Seconds of silence before alerting (env: MARKET_FEED_STALE_SECONDS)
+            check_interval: Seconds between checks (env: MARKET_FEED_WATCHDOG_INTERVAL)
+            session_calendar: SessionCalendar; feeds are not checked while their session is closed
+        """
+        self.client = client
+        self.stale_after = stale_after if stale_after is not None else \
+            float(os.getenv('MARKET_FEED_STALE_SECONDS', '120'))
+        self.check_interval = check_interval if check_interval is not None else \
+            float(os.getenv('MARKET_FEED_WATCHDOG_INTERVAL', '5'))
+        self.session_calendar = session_calendar
+        self._last_data: Dict[Tuple[str, str], float] = {}
+        self._watch_start: Dict[Tuple[str, str], float] = {}
+        self._callbacks: Tuple[Callable[[FeedAlert], Any], ...] = ()
+        self._lock = threading.Lock()
+        self._task: Optional[asyncio.Task] = None
+        self.alerts_raised = 0
+        self.stale_reconnects = 0
+        self.last_alert: Optional[FeedAlert] = None
+        for stream, targets in STREAM_TARGETS.items():
+            for target in targets:
+                client.on(target, self._data_handler(stream))
+
+    def _data_handler(self, stream: str) -> Callable[..., None]:
+        def handle(*arguments: Any) -> None:
+            contract_id = split_payload(arguments)[0]
+            if contract_id:
+                self._last_data[(stream, contract_id)] = time.monotonic()
+        return handle
+
+    def on_alert(self, callback: Callable[[FeedAlert], Any]) -> Callable[[], None]:
+        """
+        Register a stale feed callback.
+
+        Returns:
+            Callable: Unsubscribe function
+        """
+        with self._lock:
+            self._callbacks += (callback,)
+
+        def unsubscribe():
+            with self._lock:
+                self._callbacks = tuple(cb for cb in self._callbacks if cb is not callback)
+        return unsubscribe
+
+    def _session_open(self, symbol: str) -> bool:
+        if self.session_calendar is None or not symbol:
+            return True
+        try:
+            return self.session_calendar.is_open(symbol, server_now())
+        except Exception:
+            return True
+
+    def feed_ages(self, now: Optional[float] = None) -> Dict[str, float]:
+        """Seconds since the last message of each watched feed, keyed 'stream:contractId'."""
+        now = now if now is not None else time.monotonic()
+        connected_since = self.client.connected_since or 0.0
+        ages = {}
+        for (stream, contract_id), started in list(self._watch_start.items()):
+            baseline = max(self._last_data.get((stream, contract_id), 0.0), started, connected_since)
+            ages[f"{stream}:{contract_id}"] = max(0.0, now - baseline)
+        return ages
+
+    def check(self, now: Optional[float] = None) -> List[FeedAlert]:
+        """
+        Find silent feeds among the client's current subscriptions.
+
+        Silence is measured from the latest of: last data message, when the
+        subscription was first seen, and the current connection's start.
+
+        Returns:
+            List[FeedAlert]: Feeds silent for longer than stale_after (empty while disconnected)
+        """
+        now = now if now is not None else time.monotonic()
+        active = set()
+        for method, arguments in self.client.subscriptions():
+            stream = stream_for_method(method)
+            if stream and arguments:
+                key = (stream, str(arguments[0]))
+                active.add(key)
+                self._watch_start.setdefault(key, now)
+        for key in list(self._watch_start):
+            if key not in active:
+                self._watch_start.pop(key, None)
+                self._last_data.pop(key, None)
+        if self.stale_after <= 0 or not self.client.connected:
+            return []
+        alerts = []
+        for feed, silent in self.feed_ages(now).items():
+            stream, contract_id = feed.split(':', 1)
+            symbol = symbol_from_contract_id(contract_id)
+            if silent > self.stale_after and self._session_open(symbol):
+                alerts.append(FeedAlert(stream, contract_id, symbol, silent, datetime.now(timezone.utc)))
+        return alerts
+
+    def start(self) -> None:
+        """Run periodic checks on the current event loop."""
+        if self._task is None or self._task.done():
+            self._task = asyncio.ensure_future(self._run())
+
+    def stop(self) -> None:
+        """Stop periodic checks."""
+        if self._task is not None:
+            self._task.cancel()
+            self._task = None
+
+    async def _run(self) -> None:
+        while True:
+            await asyncio.sleep(self.check_interval)
+            alerts = self.check()
+            if not alerts:
+                continue
+            self.alerts_raised += len(alerts)
+            self.last_alert = alerts[0]
+            for alert in alerts:
+                logger.warning(f"⚠️  {alert.symbol or alert.contract_id} {alert.stream} feed silent for "
+                               f"{alert.silent_seconds:.0f}s - forcing reconnect")
+                for callback in self._callbacks:
+                    try:
+                        callback(alert)
+                    except Exception as e:
+                        logger.debug(f"Feed alert callback error: {e}")
+            self.stale_reconnects += 1
+            await self.client.force_reconnect(f"{len(alerts)} stale feed(s)")
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Watchdog settings, counters and current feed ages."""
+        return {
+            "stale_after": self.stale_after,
+            "running": bool(self._task and not self._task.done()),
+            "alerts": self.alerts_raised,
+            "stale_reconnects": self.stale_reconnects,
+            "last_alert": self.last_alert.to_dict() if self.last_alert else None,
+            "feed_ages": {feed: round(age, 1) for feed, age in self.feed_ages().items()},
+        }
@@ -0,0 +1,263 @@
+"""
+SignalR JSON hub protocol.
+
+Every message is a JSON object terminated by the record separator (0x1E); a
+single WebSocket frame may carry several messages. The connection opens with
+a handshake request {"protocol": "json", "version": 1} answered by {} (or
+{"error": ...}).
+
+Message types handled:
+- 1 Invocation: server -> client method call (target + arguments), or client -> server
+  (with invocationId when a completion is expected)
+- 3 Completion: result/error for an invocationId
+- 6 Ping: keep-alive, both directions
+- 7 Close: server is closing the connection (optional error, allowReconnect)
+
+Frames are decoded with orjson when it is installed (straight from the
+received bytes, without a UTF-8 decode and record copy first) and with the
+stdlib json module otherwise. Messages are built by a per-type builder
+looked up once per message instead of probing every optional field;
+benches/bench_hub_parser.py compares this against the previous parser.
+
+Compressed frames: per-message deflate (RFC 7692) is negotiated and undone by
+the WebSocket transport itself. Servers can also send whole frames gzip or
+zlib compressed as binary messages; inflate_frame() unpacks those (a SignalR
+JSON frame always starts with '{', so the gzip/zlib header is unambiguous).
+
+Configuration:
+- SIGNALR_JSON_PARSER: orjson or json (default orjson when installed)
+"""
+
+import json
+import logging
+import os
+import zlib
+from dataclasses import dataclass, field
+from enum import IntEnum
+from typing import Any, Callable, Dict, List, Optional, Sequence, Tuple, Union
+
+try:
+    import orjson
+except ImportError:  # pragma: no cover - optional speedup
+    orjson = None
+
+logger = logging.getLogger(__name__)
+
+RECORD_SEPARATOR = '\x1e'
+RECORD_SEPARATOR_BYTES = b'\x1e'
+PROTOCOL_NAME = 'json'
+PROTOCOL_VERSION = 1
+MAX_INFLATED_FRAME_BYTES = 64 * 1024 * 1024  # Guard against decompression bombs
+
+
+class MessageType(IntEnum):
+    INVOCATION = 1
+    STREAM_ITEM = 2
+    COMPLETION = 3
+    STREAM_INVOCATION = 4
+    CANCEL_INVOCATION = 5
+    PING = 6
+    CLOSE = 7
+
+
+class ProtocolError(ValueError):
+    """Malformed hub protocol data."""
+
+
+@dataclass(slots=True)
+class HubMessage:
+    """One parsed hub message."""
+    type: int
+    target: Optional[str] = None
+    arguments: List[Any] = field(default_factory=list)
+    invocation_id: Optional[str] = None
+    result: Any = None
+    error: Optional[str] = None
+    allow_reconnect: bool = False
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any]) -> 'HubMessage':
+        """Build from a decoded message object."""
+        msg_type = data.get('type')
+        if type(msg_type) is int and msg_type in _BUILDERS:
+            return _BUILDERS[msg_type](data)
+        try:
+            msg_type = int(data['type'])
+        except (KeyError, TypeError, ValueError):
+            raise ProtocolError(f"Hub message without a valid type: {data!r}")
+        return _build_generic(data, msg_type)
+
+
+def _arguments(data: Dict[str, Any]) -> List[Any]:
+    arguments = data.get('arguments')
+    if type(arguments) is list:
+        return arguments
+    return list(arguments) if isinstance(arguments, tuple) else []
+
+
+def _build_generic(data: Dict[str, Any], msg_type: int) -> HubMessage:
+    return HubMessage(
+        type=msg_type,
+        target=data.get('target'),
+        arguments=_arguments(data),
+        invocation_id=data.get('invocationId'),
+        result=data.get('result', data.get('item')),
+        error=data.get('error'),
+        allow_reconnect=bool(data.get('allowReconnect', False)),
+    )
+
+
+def _build_invocation(data: Dict[str, Any]) -> HubMessage:
+    return HubMessage(MessageType.INVOCATION, data.get('target'), _arguments(data), data.get('invocationId'))
+
+
+def _build_completion(data: Dict[str, Any]) -> HubMessage:
+    return HubMessage(MessageType.COMPLETION, invocation_id=data.get('invocationId'),
+                      result=data.get('result'), error=data.get('error'))
+
+
+def _build_ping(data: Dict[str, Any]) -> HubMessage:
+    return HubMessage(MessageType.PING)
+
+
+def _build_close(data: Dict[str, Any]) -> HubMessage:
+    return HubMessage(MessageType.CLOSE, error=data.get('error'),
+                      allow_reconnect=bool(data.get('allowReconnect', False)))
+
+
+# Builders for the hot message types, keyed by the raw JSON type value
+_BUILDERS = {
+    int(MessageType.INVOCATION): _build_invocation,
+    int(MessageType.COMPLETION): _build_completion,
+    int(MessageType.PING): _build_ping,
+    int(MessageType.CLOSE): _build_close,
+}
+
+
+def _select_loads(name: Optional[str] = None) -> Callable[[Union[str, bytes]], Any]:
+    """JSON decoder for hub frames (env: SIGNALR_JSON_PARSER)."""
+    name = (name or os.getenv('SIGNALR_JSON_PARSER', 'orjson')).lower()
+    if name == 'orjson' and orjson is not None:
+        return orjson.loads
+    if name not in ('orjson', 'json'):
+        logger.warning(f"⚠️  Unknown SIGNALR_JSON_PARSER '{name}', using json")
+    return json.loads
+
+
+json_loads = _select_loads()
+
+
+def encode_message(message: Dict[str, Any]) -> str:
+    """Serialize one message with its record separator."""
+    return json.dumps(message, separators=(',', ':')) + RECORD_SEPARATOR
+
+
+def handshake_request() -> str:
+    """Handshake request for the JSON protocol."""
+    return encode_message({'protocol': PROTOCOL_NAME, 'version': PROTOCOL_VERSION})
+
+
+def invocation(target: str, arguments: Sequence[Any], invocation_id: Optional[str] = None) -> Dict[str, Any]:
+    """Invocation message (no invocationId = fire-and-forget)."""
+    message: Dict[str, Any] = {'type': int(MessageType.INVOCATION), 'target': target, 'arguments': list(arguments)}
+    if invocation_id is not None:
+        message['invocationId'] = invocation_id
+    return message
+
+
+PING_MESSAGE = encode_message({'type': int(MessageType.PING)})
+
+
+def split_records(data: Union[str, bytes]) -> List[Union[str, bytes]]:
+    """Split a frame into message payloads (empty records are dropped; bytes stay bytes)."""
+    if isinstance(data, (bytes, bytearray, memoryview)):
+        return [record for record in bytes(data).split(RECORD_SEPARATOR_BYTES) if record.strip()]
+    return [record for record in data.split(RECORD_SEPARATOR) if record.strip()]
+
+
+def is_compressed_frame(data: Any) -> bool:
+    """True for a binary frame with a gzip or zlib header."""
+    if not isinstance(data, (bytes, bytearray, memoryview)) or len(data) < 2:
+        return False
+    first, second = data[0], data[1]
+    if first == 0x1f and second == 0x8b:
+        return True
+    return (first & 0x0f) == 8 and ((first << 8) | second) % 31 == 0  # zlib CMF/FLG check
+
+
+def inflate_frame(data: Union[bytes, bytearray, memoryview], max_size: int = MAX_INFLATED_FRAME_BYTES) -> bytes:
+    """
+    Decompress a gzip or zlib compressed frame.
+
+    Raises:
+        ProtocolError: Corrupt or truncated data, or more than max_size bytes once inflated
+    """
+    decompressor = zlib.decompressobj(zlib.MAX_WBITS | 32)  # Detects the gzip or zlib header
+    try:
+        inflated = decompressor.decompress(bytes(data), max_size)
+    except zlib.error as e:
+        raise ProtocolError(f"Invalid compressed frame: {e}")
+    if decompressor.unconsumed_tail:
+        raise ProtocolError(f"Compressed frame inflates to more than {max_size} bytes")
+    if not decompressor.eof:
+        raise ProtocolError("Truncated compressed frame")
+    return inflated
+
+
+def parse_messages(data: Union[str, bytes],
+                   loads: Optional[Callable[[Union[str, bytes]], Any]] = None) -> List[HubMessage]:
+    """
+    Parse every message in a frame.
+
+    Args:
+        data: Frame text or UTF-8 bytes
+        loads: JSON decoder (default: json_loads)
+
+    Raises:
+        ProtocolError: A record is not valid JSON or has no type
+    """
+    loads = loads or json_loads
+    if orjson is None or loads is not orjson.loads:
+        if isinstance(data, (bytes, bytearray, memoryview)):
+            try:
+                data = bytes(data).decode('utf-8')  # One decode per frame beats json.loads sniffing each record
+            except UnicodeDecodeError as e:
+                raise ProtocolError(f"Invalid hub message encoding: {e}")
+    messages = []
+    for record in split_records(data):
+        try:
+            decoded = loads(record)
+        except ValueError as e:  # orjson.JSONDecodeError and UnicodeDecodeError are ValueErrors
+            raise ProtocolError(f"Invalid hub message JSON: {e}")
+        if type(decoded) is not dict:
+            raise ProtocolError(f"Hub message is not an object: {record[:100]!r}")
+        msg_type = decoded.get('type')
+        builder = _BUILDERS.get(msg_type) if type(msg_type) is int else None
+        messages.append(builder(decoded) if builder is not None else HubMessage.from_dict(decoded))
+    return messages
+
+
+def parse_handshake_response(data: Union[str, bytes]) -> Tuple[Optional[str], str]:
+    """
+    Parse the handshake response at the start of the first frame.
+
+    Returns:
+        Tuple[Optional[str], str]: (server error or None, remaining frame data)
+
+    Raises:
+        ProtocolError: Incomplete or malformed handshake response
+    """
+    if isinstance(data, (bytes, bytearray)):
+        data = bytes(data).decode('utf-8')
+    head, sep, rest = data.partition(RECORD_SEPARATOR)
+    if not sep:
+        raise ProtocolError("Incomplete handshake response")
+    try:
+        response = json.loads(head)
+    except ValueError as e:
+        raise ProtocolError(f"Invalid handshake response: {e}")
+    if not isinstance(response, dict):
+        raise ProtocolError(f"Invalid handshake response: {head[:100]}")
+    if response.get('type') is not None:
+        raise ProtocolError("Expected a handshake response, got a hub message")
+    return response.get('error'), rest
@@ -0,0 +1,192 @@
+"""
+Raw WebSocket passthrough client.
+
+RawWebSocketClient connects to any ws/wss URL and hands every frame, as
+received (str for text, bytes for binary), to Python callbacks. It reuses the
+SignalRClient machinery, so non-SignalR feeds get the same behaviour:
+- Optional headers and bearer auth (access_token_factory -> Authorization
+  header, plus a query parameter when token_query_param is set), refreshed on
+  every reconnect
+- Auto-reconnect with backoff and jitter, backup URL failover, server silence
+  timeout and force_reconnect()
+- subscribe(payload) messages are sent on connect and replayed after every
+  reconnect
+- Backpressure: awaitables returned by on_frame() callbacks are awaited
+  before the next frame is read; frames() streams through a bounded channel
+  whose block policy holds the reader the same way
+- Recording and replay through FrameRecorder / replay_capture()
+
+There is no handshake and no client ping unless ping_message is given (the
+transport's own ping frames are left off, as for SignalR).
+
+Usage:
+    feed = RawWebSocketClient('wss://stream.example.com/ws', headers={'X-Api-Key': key})
+    feed.subscribe(json.dumps({'op': 'subscribe', 'channel': 'trades'}))
+    feed.on_frame(lambda frame: print(frame))
+    await feed.start()
+"""
+
+import logging
+from typing import Any, Callable, Dict, List, Optional, Sequence, Tuple, Union
+
+from core.websocket.channels import ChannelFanout, EventStream, OverflowPolicy
+from core.websocket.client import HubConnectionError, SignalRClient
+
+logger = logging.getLogger(__name__)
+
+Frame = Union[str, bytes]
+
+
+class RawWebSocketClient(SignalRClient):
+    """
+    WebSocket connection that passes raw frames to callbacks.
+    """
+
+    kind = 'WebSocket'
+
+    def __init__(self, url: str, access_token_factory: Optional[Callable[[], Optional[str]]] = None,
+                 headers: Optional[Dict[str, str]] = None, token_query_param: Optional[str] = None,
+                 ping_message: Optional[Frame] = None, keep_alive_interval: Optional[float] = None,
+                 server_timeout: float = 0, inflate: bool = False, name: str = 'raw', **client_kwargs: Any):
+        """
+        Initialize client (does not connect).
+
+        Args:
+            url: ws/wss URL (http/https are converted)
+            access_token_factory: Returns a bearer token, sent as the Authorization header
+            headers: Extra HTTP headers for the upgrade request
+            token_query_param: Also send the token as this query parameter (e.g. 'token')
+            ping_message: Application-level keep-alive payload (default: none)
+            keep_alive_interval: Seconds between ping_message sends (default 15 when ping_message is set)
+            server_timeout: Seconds of server silence before reconnecting, 0 = off
+            inflate: Undo gzip/zlib compression of binary frames before the callbacks see them
+            name: Name used in logs and status
+            **client_kwargs: Passed to SignalRClient (connector, reconnect_*, backup_urls, recorder, ...)
+        """
+        if keep_alive_interval is None:
+            keep_alive_interval = 15.0 if ping_message is not None else 0.0
+        super().__init__(url, access_token_factory, headers=headers, skip_negotiation=True,
+                         keep_alive_interval=keep_alive_interval, server_timeout=server_timeout,
+                         name=name, **client_kwargs)
+        self.token_query_param = token_query_param
+        self.ping_message = ping_message
+        self.inflate = inflate
+        self._frame_callbacks: Tuple[Callable[[Frame], Any], ...] = ()
+        self._payloads: Dict[Frame, None] = {}  # Subscription payloads, in registration order
+        self.channels = ChannelFanout()
+        self.frames_received = 0
+        self.bytes_received = 0
+
+    # ---------------------------
+    # Frames
+    # ---------------------------
+    def on_frame(self, callback: Callable[[Frame], Any]) -> Callable[[], None]:
+        """
+        Register a frame callback, called as callback(frame).
+
+        Returns:
+            Callable: Unsubscribe function
+        """
+        with self._callback_lock:
+            self._frame_callbacks += (callback,)
+
+        def unsubscribe():
+            with self._callback_lock:
+                self._frame_callbacks = tuple(cb for cb in self._frame_callbacks if cb is not callback)
+        return unsubscribe
+
+    def frames(self, capacity: Optional[int] = None,
+               policy: Union[OverflowPolicy, str, None] = OverflowPolicy.BLOCK) -> EventStream:
+        """
+        Frames as an async iterator, through a bounded channel.
+
+        Args:
+            capacity: Channel capacity (env: FEED_CHANNEL_CAPACITY)
+            policy: Overflow policy (default block: a slow iterator holds the reader)
+        """
+        return self.channels.events(None, capacity, policy)
+
+    async def _handshake(self, conn: Any) -> Any:
+        return None
+
+    def _handle_frame(self, frame: Any) -> bool:
+        if self.inflate:
+            try:
+                frame = self._inflate(frame)
+            except ValueError as e:
+                self.protocol_errors += 1
+                logger.debug(f"WebSocket {self.name} dropped malformed compressed frame: {e}")
+                return False
+        self.frames_received += 1
+        self.messages_received += 1
+        self.bytes_received += len(frame)
+        for callback in self._frame_callbacks:
+            self._call(callback, frame, backlog=self._backlog)
+        if len(self.channels):
+            if self.channels.has_blocking:
+                self._backlog.append(self.channels.publish_async(frame))
+            else:
+                self.channels.publish(frame)
+        return False
+
+    # ---------------------------
+    # Outgoing frames
+    # ---------------------------
+    def send(self, payload: Frame, arguments: Sequence[Any] = ()) -> None:
+        """
+        Send a raw text or binary frame.
+
+        Raises:
+            HubConnectionError: Not connected
+        """
+        self._post(payload)
+
+    def subscribe(self, payload: Frame, arguments: Sequence[Any] = ()) -> None:
+        """Send a payload now (if connected) and again after every reconnect."""
+        with self._callback_lock:
+            self._payloads[payload] = None
+        if self.connected:
+            self.send(payload)
+
+    def unsubscribe(self, payload: Frame, arguments: Sequence[Any] = (),
+                    unsubscribe_payload: Optional[Frame] = None) -> bool:
+        """
+        Stop replaying a subscription payload, sending unsubscribe_payload if connected.
+
+        Returns:
+            bool: True if the payload was registered
+        """
+        with self._callback_lock:
+            found = payload in self._payloads
+            self._payloads.pop(payload, None)
+        if found and unsubscribe_payload is not None and self.connected:
+            self.send(unsubscribe_payload)
+        return found
+
+    def subscriptions(self) -> List[Frame]:
+        """Registered subscription payloads in registration order."""
+        with self._callback_lock:
+            return list(self._payloads)
+
+    def _replay_subscriptions(self) -> None:
+        for payload in self.subscriptions():
+            self.send(payload)
+
+    async def invoke(self, method: str, arguments: Sequence[Any] = (), timeout: float = 30.0) -> Any:
+        raise HubConnectionError("invoke() is a SignalR call; use send() on a raw WebSocket")
+
+    async def stop(self) -> None:
+        """Close the connection, stop reconnecting and end frames() iterators."""
+        await super().stop()
+        await self.channels.close()
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Connection state, counters and channel stats."""
+        stats = super().get_stats()
+        stats.update({
+            "frames_received": self.frames_received,
+            "bytes_received": self.bytes_received,
+            "subscriptions": len(self._payloads),
+            "channels": self.channels.get_stats(),
+        })
+        return stats
@@ -0,0 +1,163 @@
+"""
+Reference-counted market hub subscriptions.
+
+Several consumers (bar aggregator, strategies, recorders) can ask for the
+same feed; only the first subscribe sends the upstream SubscribeContract*
+invocation and only the last unsubscribe sends the Unsubscribe. Upstream
+subscriptions are registered with SignalRClient.subscribe(), so they are
+replayed after reconnects.
+
+Symbols are resolved to contract ids with the given resolver (e.g. the bot's
+_get_contract_id); values that already look like contract ids
+(CON.F.US.MNQ.Z25) are used as-is. Counts are kept per contract, so 'MNQ'
+and its contract id share one subscription.
+"""
+
+import logging
+import threading
+from typing import Any, Callable, Dict, Optional
+
+from core.websocket.client import SignalRClient
+from core.websocket.market_hub import SUBSCRIBE_METHODS, symbol_from_contract_id
+
+logger = logging.getLogger(__name__)
+
+STREAMS = tuple(SUBSCRIBE_METHODS)
+
+
+class SubscriptionManager:
+    """
+    Shared quote/trade/depth subscriptions.
+
+    Usage:
+        subs = SubscriptionManager(hub, contract_resolver=bot._get_contract_id)
+        subs.subscribe_quotes('MNQ')      # sends SubscribeContractQuotes
+        subs.subscribe_quotes('MNQ')      # second consumer, no upstream call
+        subs.unsubscribe_quotes('MNQ')    # still one consumer left
+        subs.unsubscribe_quotes('MNQ')    # sends UnsubscribeContractQuotes
+    """
+
+    def __init__(self, client: SignalRClient, contract_resolver: Optional[Callable[[str], str]] = None):
+        """
+        Initialize manager.
+
+        Args:
+            client: Market hub client
+            contract_resolver: Maps a symbol to its contract id (raises ValueError if unknown)
+        """
+        self.client = client
+        self.contract_resolver = contract_resolver
+        self._counts: Dict[str, Dict[str, int]] = {stream: {} for stream in STREAMS}
+        self._lock = threading.Lock()
+        self.upstream_subscribes = 0
+        self.upstream_unsubscribes = 0
+
+    def resolve(self, symbol: str) -> str:
+        """
+        Contract id for a symbol or contract id.
+
+        Raises:
+            ValueError: Symbol cannot be resolved
+        """
+        value = symbol.strip()
+        if symbol_from_contract_id(value):
+            return value
+        if self.contract_resolver is None:
+            raise ValueError(f"No contract resolver to map '{symbol}' to a contract id")
+        return self.contract_resolver(value.upper())
+
+    def subscribe(self, stream: str, symbol: str) -> int:
+        """
+        Add a consumer to a feed, subscribing upstream for the first one.
+
+        Args:
+            stream: 'quotes', 'trades' or 'depth'
+            symbol: Symbol or contract id
+
+        Returns:
+            int: Consumers of the feed after this call
+
+        Raises:
+            ValueError: Unknown stream or unresolvable symbol
+        """
+        if stream not in self._counts:
+            raise ValueError(f"Unknown stream '{stream}'. Use one of {', '.join(STREAMS)}")
+        contract_id = self.resolve(symbol)
+        with self._lock:
+            count = self._counts[stream].get(contract_id, 0) + 1
+            self._counts[stream][contract_id] = count
+            if count == 1:
+                self.client.subscribe(SUBSCRIBE_METHODS[stream][0], [contract_id])
+                self.upstream_subscribes += 1
+        if count == 1:
+            logger.info(f"📡 Subscribed to {stream} for {contract_id}")
+        return count
+
+    def unsubscribe(self, stream: str, symbol: str) -> int:
+        """
+        Remove a consumer from a feed, unsubscribing upstream after the last one.
+
+        Returns:
+            int: Consumers left (0 also when the feed was not subscribed)
+        """
+        if stream not in self._counts:
+            raise ValueError(f"Unknown stream '{stream}'. Use one of {', '.join(STREAMS)}")
+        contract_id = self.resolve(symbol)
+        with self._lock:
+            count = self._counts[stream].get(contract_id, 0)
+            if count == 0:
+                logger.debug(f"Unsubscribe for {stream} {contract_id} without a subscription")
+                return 0
+            count -= 1
+            if count:
+                self._counts[stream][contract_id] = count
+                return count
+            del self._counts[stream][contract_id]
+            subscribe_method, unsubscribe_method = SUBSCRIBE_METHODS[stream]
+            self.client.unsubscribe(subscribe_method, [contract_id], unsubscribe_method)
+            self.upstream_unsubscribes += 1
+        logger.info(f"📴 Unsubscribed from {stream} for {contract_id}")
+        return 0
+
+    def subscribe_quotes(self, symbol: str) -> int:
+        """Add a GatewayQuote consumer."""
+        return self.subscribe('quotes', symbol)
+
+    def unsubscribe_quotes(self, symbol: str) -> int:
+        """Remove a GatewayQuote consumer."""
+        return self.unsubscribe('quotes', symbol)
+
+    def subscribe_trades(self, symbol: str) -> int:
+        """Add a GatewayTrade consumer."""
+        return self.subscribe('trades', symbol)
+
+    def unsubscribe_trades(self, symbol: str) -> int:
+        """Remove a GatewayTrade consumer."""
+        return self.unsubscribe('trades', symbol)
+
+    def subscribe_depth(self, symbol: str) -> int:
+        """Add a GatewayDepth consumer."""
+        return self.subscribe('depth', symbol)
+
+    def unsubscribe_depth(self, symbol: str) -> int:
+        """Remove a GatewayDepth consumer."""
+        return self.unsubscribe('depth', symbol)
+
+    def ref_count(self, stream: str, symbol: str) -> int:
+        """Current consumers of a feed."""
+        contract_id = self.resolve(symbol)
+        with self._lock:
+            return self._counts.get(stream, {}).get(contract_id, 0)
+
+    def active(self) -> Dict[str, Dict[str, int]]:
+        """Consumer counts per stream and contract id."""
+        with self._lock:
+            return {stream: dict(counts) for stream, counts in self._counts.items()}
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Active feeds and upstream call counters."""
+        return {
+            "active": self.active(),
+            "upstream_subscribes": self.upstream_subscribes,
+            "upstream_unsubscribes": self.upstream_unsubscribes,
+        }
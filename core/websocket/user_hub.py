@@ -0,0 +1,387 @@
+"""
+TopStepX user hub on top of SignalRClient.
+
+Streams account, order, position and trade (fill) updates for subscribed
+accounts, so order tracking and position bookkeeping react in real time
+instead of polling the REST API.
+
+Hub methods / events:
+- SubscribeAccounts()              -> GatewayUserAccount
+- SubscribeOrders(accountId)       -> GatewayUserOrder
+- SubscribePositions(accountId)    -> GatewayUserPosition
+- SubscribeTrades(accountId)       -> GatewayUserTrade
+
+Payloads arrive either bare or wrapped as {action, data}; both are accepted.
+Subscriptions go through SignalRClient.subscribe(), so they are replayed
+after reconnects. events() yields the typed updates as an async iterator;
+its channel blocks by default, so order and fill updates are never dropped
+(the hub stops reading until the consumer catches up).
+
+Configuration:
+- USER_HUB_ENABLED: Stream fills/orders/positions in the bot instead of polling (default false)
+- PROJECT_X_USER_HUB_URL: User hub URL (default https://rtc.topstepx.com/hubs/user)
+- PROJECT_X_USER_HUB_BACKUP_URLS: Comma-separated user hub URLs to fail over to (default none)
+"""
+
+import logging
+import threading
+from dataclasses import dataclass, field
+from datetime import datetime
+from typing import Any, Callable, Dict, List, Optional, Sequence, Set, Tuple, Union
+
+from core.market_events import parse_timestamp
+from core.websocket.channels import ChannelFanout, EventStream, OverflowPolicy
+from core.websocket.client import SignalRClient
+from core.websocket.market_hub import symbol_from_contract_id
+
+logger = logging.getLogger(__name__)
+
+DEFAULT_USER_HUB_URL = 'https://rtc.topstepx.com/hubs/user'
+
+# Gateway OrderStatus
+ORDER_STATUS_OPEN = 1
+ORDER_STATUS_FILLED = 2
+ORDER_STATUS_CANCELLED = 3
+ORDER_STATUS_EXPIRED = 4
+ORDER_STATUS_REJECTED = 5
+ORDER_STATUS_PENDING = 6
+ORDER_STATUS_NAMES = {0: 'none', 1: 'open', 2: 'filled', 3: 'cancelled', 4: 'expired', 5: 'rejected', 6: 'pending'}
+
+# Gateway PositionType
+POSITION_LONG = 1
+POSITION_SHORT = 2
+
+ACCOUNT_SUBSCRIPTIONS = ('SubscribeOrders', 'SubscribePositions', 'SubscribeTrades')
+
+
+def _unwrap(arguments: Sequence[Any]) -> Optional[Dict[str, Any]]:
+    """Payload dict from invocation arguments ({action, data} envelopes are unwrapped)."""
+    payload = arguments[-1] if arguments else None
+    if isinstance(payload, dict) and isinstance(payload.get('data'), dict) and 'action' in payload:
+        payload = payload['data']
+    return payload if isinstance(payload, dict) else None
+
+
+def _side(code: Any) -> Optional[str]:
+    return {0: 'buy', 1: 'sell'}.get(code)
+
+
+@dataclass(frozen=True)
+class UserEvent:
+    """Base class for user hub events (raw keeps the gateway payload)."""
+    account_id: str
+    timestamp: datetime
+    raw: Dict[str, Any] = field(repr=False, compare=False)
+
+    type = 'user_event'
+
+    def to_dict(self) -> Dict[str, Any]:
+        data = {k: v for k, v in self.__dict__.items() if k != 'raw'}
+        data['type'] = self.type
+        data['timestamp'] = self.timestamp.isoformat()
+        return data
+
+
+@dataclass(frozen=True)
+class AccountUpdate(UserEvent):
+    """Account balance / tradability change."""
+    name: str = ''
+    balance: Optional[float] = None
+    can_trade: Optional[bool] = None
+
+    type = 'account'
+
+    @classmethod
+    def from_gateway(cls, data: Dict[str, Any]) -> 'AccountUpdate':
+        balance = data.get('balance')
+        return cls(str(data.get('id', '')), parse_timestamp(data.get('timestamp')), data,
+                   name=data.get('name') or '', balance=float(balance) if balance is not None else None,
+                   can_trade=data.get('canTrade'))
+
+
+@dataclass(frozen=True)
+class OrderUpdate(UserEvent):
+    """Order status change (placed, partially/fully filled, cancelled, rejected...)."""
+    order_id: str = ''
+    contract_id: str = ''
+    symbol: str = ''
+    status: int = 0
+    side: Optional[str] = None
+    size: int = 0
+    fill_volume: int = 0
+    filled_price: Optional[float] = None
+    limit_price: Optional[float] = None
+    stop_price: Optional[float] = None
+    custom_tag: str = ''
+
+    type = 'order'
+
+    @property
+    def status_name(self) -> str:
+        return ORDER_STATUS_NAMES.get(self.status, str(self.status))
+
+    @property
+    def is_filled(self) -> bool:
+        return self.status == ORDER_STATUS_FILLED
+
+    @classmethod
+    def from_gateway(cls, data: Dict[str, Any]) -> 'OrderUpdate':
+        def price(key):
+            return float(data[key]) if data.get(key) is not None else None
+        contract_id = data.get('contractId') or ''
+        return cls(str(data.get('accountId', '')),
+                   parse_timestamp(data.get('updateTimestamp') or data.get('creationTimestamp')), data,
+                   order_id=str(data.get('id', '')), contract_id=contract_id,
+                   symbol=symbol_from_contract_id(contract_id), status=int(data.get('status') or 0),
+                   side=_side(data.get('side')), size=int(data.get('size') or 0),
+                   fill_volume=int(data.get('fillVolume') or 0), filled_price=price('filledPrice'),
+                   limit_price=price('limitPrice'), stop_price=price('stopPrice'),
+                   custom_tag=data.get('customTag') or '')
+
+
+@dataclass(frozen=True)
+class PositionUpdate(UserEvent):
+    """Broker position for a contract (size 0 = closed)."""
+    position_id: str = ''
+    contract_id: str = ''
+    symbol: str = ''
+    size: int = 0
+    average_price: Optional[float] = None
+    position_type: int = 0
+
+    type = 'position'
+
+    @property
+    def net_quantity(self) -> int:
+        """Signed contracts (long > 0, short < 0)."""
+        return -self.size if self.position_type == POSITION_SHORT else self.size
+
+    @classmethod
+    def from_gateway(cls, data: Dict[str, Any]) -> 'PositionUpdate':
+        contract_id = data.get('contractId') or ''
+        average = data.get('averagePrice')
+        return cls(str(data.get('accountId', '')), parse_timestamp(data.get('creationTimestamp')), data,
+                   position_id=str(data.get('id', '')), contract_id=contract_id,
+                   symbol=symbol_from_contract_id(contract_id), size=int(data.get('size') or 0),
+                   average_price=float(average) if average is not None else None,
+                   position_type=int(data.get('type') or 0))
+
+
+@dataclass(frozen=True)
+class UserTrade(UserEvent):
+    """An execution (fill) on the account."""
+    trade_id: str = ''
+    order_id: str = ''
+    contract_id: str = ''
+    symbol: str = ''
+    side: Optional[str] = None
+    size: int = 0
+    price: float = 0.0
+    profit_and_loss: Optional[float] = None
+    fees: float = 0.0
+    voided: bool = False
+
+    type = 'fill'
+
+    @classmethod
+    def from_gateway(cls, data: Dict[str, Any]) -> 'UserTrade':
+        contract_id = data.get('contractId') or ''
+        pnl = data.get('profitAndLoss')
+        return cls(str(data.get('accountId', '')), parse_timestamp(data.get('creationTimestamp')), data,
+                   trade_id=str(data.get('id', '')), order_id=str(data.get('orderId', '')),
+                   contract_id=contract_id, symbol=symbol_from_contract_id(contract_id),
+                   side=_side(data.get('side')), size=int(data.get('size') or 0),
+                   price=float(data.get('price') or 0), profit_and_loss=float(pnl) if pnl is not None else None,
+                   fees=float(data.get('fees') or 0), voided=bool(data.get('voided', False)))
+
+
+USER_EVENT_TARGETS = {
+    'GatewayUserAccount': AccountUpdate,
+    'GatewayUserOrder': OrderUpdate,
+    'GatewayUserPosition': PositionUpdate,
+    'GatewayUserTrade': UserTrade,
+}
+
+
+def user_event_from_invocation(target: str, arguments: Sequence[Any]) -> Optional[UserEvent]:
+    """Typed event for a user hub invocation (None for unknown targets or payloads)."""
+    event_cls = next((cls for name, cls in USER_EVENT_TARGETS.items() if name.lower() == target.lower()), None)
+    payload = _unwrap(arguments)
+    if event_cls is None or payload is None:
+        return None
+    return event_cls.from_gateway(payload)
+
+
+class UserHubClient:
+    """
+    User hub connection publishing typed account/order/position/fill events.
+
+    Usage:
+        hub = UserHubClient(access_token_factory=lambda: token)
+        hub.add_listener(on_user_event)
+        hub.subscribe_account(12345)
+        await hub.start()
+        async for update in hub.events(types=OrderUpdate):
+            ...
+    """
+
+    def __init__(self, url: str = DEFAULT_USER_HUB_URL,
+                 access_token_factory: Optional[Callable[[], Optional[str]]] = None,
+                 client: Optional[SignalRClient] = None, channels: Optional[ChannelFanout] = None,
+                 **client_kwargs: Any):
+        """
+        Initialize user hub.
+
+        Args:
+            url: User hub URL
+            access_token_factory: Returns the session token
+            client: Existing SignalRClient to use instead of creating one
+            channels: Shared fan-out for events() (default: one owned by this hub)
+            **client_kwargs: Passed to SignalRClient
+        """
+        self.client = client or SignalRClient(url, access_token_factory, name='user', **client_kwargs)
+        self._listeners: Tuple[Callable[[UserEvent], Any], ...] = ()
+        self._lock = threading.Lock()
+        self._owns_channels = channels is None
+        self.channels = channels if channels is not None else ChannelFanout()
+        self._accounts: Set[str] = set()
+        self._positions: Dict[Tuple[str, str], PositionUpdate] = {}
+        self._account_state: Dict[str, AccountUpdate] = {}
+        self.events_published = 0
+        self.parse_errors = 0
+        self.listener_errors = 0
+        for target in USER_EVENT_TARGETS:
+            self.client.on(target, self._handler(target))
+
+    def _handler(self, target: str) -> Callable[..., Any]:
+        def handle(*arguments: Any) -> Any:
+            try:
+                event = user_event_from_invocation(target, arguments)
+            except (KeyError, TypeError, ValueError) as e:
+                self.parse_errors += 1
+                logger.debug(f"Unparseable {target} payload: {e}")
+                return None
+            if event is None:
+                return None
+            self._publish(event)
+            if self.channels.has_blocking:
+                return self.channels.publish_async(event)  # Awaited by the client before the next frame
+            self.channels.publish(event)
+            return None
+        return handle
+
+    def events(self, types: Union[type, Tuple[type, ...], None] = None, capacity: Optional[int] = None,
+               policy: Union[OverflowPolicy, str, None] = OverflowPolicy.BLOCK) -> EventStream:
+        """
+        Typed user events as an async iterator (`async for update in hub.events():`).
+
+        Args:
+            types: Only these classes, e.g. OrderUpdate (default: all user events)
+            capacity: Channel capacity (env: FEED_CHANNEL_CAPACITY)
+            policy: Overflow policy (default block, so no update is lost)
+
+        Returns:
+            EventStream: Ends when the hub is stopped or the stream is closed
+        """
+        return self.channels.events(types, capacity, policy)
+
+    def add_listener(self, callback: Callable[[UserEvent], Any]) -> Callable[[], None]:
+        """
+        Subscribe to typed user events.
+
+        Returns:
+            Callable: Unsubscribe function
+        """
+        with self._lock:
+            self._listeners += (callback,)
+
+        def unsubscribe():
+            with self._lock:
+                self._listeners = tuple(cb for cb in self._listeners if cb is not callback)
+        return unsubscribe
+
+    def _publish(self, event: UserEvent) -> None:
+        with self._lock:
+            if isinstance(event, PositionUpdate):
+                key = (event.account_id, event.contract_id)
+                if event.size:
+                    self._positions[key] = event
+                else:
+                    self._positions.pop(key, None)
+            elif isinstance(event, AccountUpdate):
+                self._account_state[event.account_id] = event
+        self.events_published += 1
+        for listener in self._listeners:
+            try:
+                listener(event)
+            except Exception as e:
+                self.listener_errors += 1
+                logger.debug(f"User hub listener error ({event.type} {event.account_id}): {e}")
+
+    def subscribe_account(self, account_id: Union[int, str]) -> None:
+        """Stream orders, positions and fills for an account (plus account updates)."""
+        key = str(account_id)
+        with self._lock:
+            if key in self._accounts:
+                return
+            self._accounts.add(key)
+        arg = int(account_id) if key.isdigit() else account_id
+        self.client.subscribe('SubscribeAccounts', [])
+        for method in ACCOUNT_SUBSCRIPTIONS:
+            self.client.subscribe(method, [arg])
+
+    def unsubscribe_account(self, account_id: Union[int, str]) -> None:
+        """Stop streaming an account."""
+        key = str(account_id)
+        with self._lock:
+            if key not in self._accounts:
+                return
+            self._accounts.discard(key)
+        arg = int(account_id) if key.isdigit() else account_id
+        for method in ACCOUNT_SUBSCRIPTIONS:
+            self.client.unsubscribe(method, [arg], 'Unsubscribe' + method[len('Subscribe'):])
+
+    def is_subscribed(self, account_id: Union[int, str]) -> bool:
+        with self._lock:
+            return str(account_id) in self._accounts
+
+    def positions(self, account_id: Union[int, str]) -> List[PositionUpdate]:
+        """Open positions last streamed for an account."""
+        key = str(account_id)
+        with self._lock:
+            return [p for (account, _), p in self._positions.items() if account == key]
+
+    def account(self, account_id: Union[int, str]) -> Optional[AccountUpdate]:
+        """Last streamed account update."""
+        with self._lock:
+            return self._account_state.get(str(account_id))
+
+    async def start(self) -> None:
+        """Connect the underlying hub."""
+        await self.client.start()
+
+    async def stop(self) -> None:
+        """Disconnect the underlying hub and end event streams."""
+        await self.client.stop()
+        if self._owns_channels:
+            await self.channels.close()
+
+    @property
+    def connected(self) -> bool:
+        return self.client.connected
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Hub connection stats plus event counters."""
+        stats = self.client.get_stats()
+        with self._lock:
+            stats.update({
+                "accounts": sorted(self._accounts),
+                "open_positions": len(self._positions),
+            })
+        stats.update({
+            "events_published": self.events_published,
+            "parse_errors": self.parse_errors,
+            "listener_errors": self.listener_errors,
+            "channels": self.channels.get_stats(),
+        })
+        return stats
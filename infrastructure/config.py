@@ -0,0 +1,19 @@
+"""
+Environment Configuration Helpers
+
+Shared parsing for settings read from environment variables, so every
+module treats the same spellings as true.
+
+Usage:
+    from infrastructure.config import env_bool
+    enabled = env_bool('TRADING_CORE_BRACKETS', 'true')
+"""
+
+import os
+
+TRUE_VALUES = ('true', '1', 'yes', 'on')
+
+
+def env_bool(name: str, default: str) -> bool:
+    """Read a boolean environment variable (true/1/yes/on, case-insensitive; default when unset)."""
+    return os.getenv(name, default).lower() in TRUE_VALUES
@@ -6,22 +6,339 @@ Provides persistent storage for:
 - Account state and balance tracking
 - Strategy performance metrics
 - API performance metrics
+- Tick data (market_ticks), time-partitioned: TimescaleDB hypertable with
+  compression when available, otherwise native daily RANGE partitions
+
+Tick storage configuration:
+- DB_TICK_STORAGE: 'auto' (default), 'timescale' or 'partitioned'
+- DB_TICK_COMPRESS_AFTER_DAYS: TimescaleDB compression policy age (default 3, 0 disables)
+- DB_TICK_RETENTION_DAYS: Drop tick data older than this (default 0 = keep forever)
+- DB_TICK_PARTITIONS_AHEAD: Daily partitions created ahead of today (default 3)
+
+Bar storage (historical_bars) and downsampling:
+- Every bar write is an upsert on (symbol, timeframe, timestamp): re-delivered
+  or corrected bars replace the stored row (the last of duplicates in a batch
+  wins). rewrite_range() swaps a whole range for corrected bars in one
+  transaction, dropping stored bars the correction does not contain.
+- DB_BAR_STORAGE: 'auto' (default: convert the table to a hypertable when the
+  timescaledb extension is enabled), 'timescale' (enable it, or fail) or 'plain'.
+  Conversion migrates existing rows and locks the table while it runs; the
+  serial id loses its primary key (the unique key includes the time column).
+- DB_BAR_CHUNK_DAYS: Days per hypertable chunk (default 30)
+- DB_BAR_COMPRESS_AFTER_DAYS: Compression policy age (default 30, 0 disables).
+  Upserts into compressed chunks (back-filled history) need TimescaleDB 2.11+.
+- get_downsampled_bars() / get_tick_bars() roll stored bars or raw ticks up
+  into any timeframe with time_bucket(), or date_bin() without TimescaleDB
+
+Read replica:
+- DATABASE_READ_URL: Optional replica DSN. Heavy read helpers (cached bars,
+  ticks, stats) are routed to a read-only pool on it; writes and state that is
+  read back right after being written stay on the primary. If the replica
+  cannot hand out a connection the read falls back to the primary.
+- DB_POOL_MAX_CONN / DB_READ_POOL_MAX_CONN: Max connections per pool (default 10)
+
+Streaming writes (MarketDataWriter, DB_MARKET_DATA_WRITER_ENABLED in the bot):
+live bars and ticks are buffered and flushed as one transaction per batch
+(COPY for ticks, multi-row upserts for bars) instead of a round trip per row.
+- DB_WRITE_BATCH_SIZE: Buffered rows that trigger a flush (default 5000)
+- DB_WRITE_FLUSH_INTERVAL: Seconds between background flushes (default 1)
+- DB_WRITE_MAX_BUFFER: Rows kept while the database is unreachable; the oldest
+  ticks are dropped beyond it (default 500000)
+- DB_WRITE_USE_COPY: COPY ticks instead of multi-row INSERTs (default true)
+
+Write-ahead log (infrastructure.write_ahead_log, DB_WAL_ENABLED in the bot):
+market data and journal fills are appended to an on-disk log and applied by
+a background writer, so a database outage never blocks order flow; records
+not applied before a crash are replayed on the next start. get_durable_writer()
+returns the shared writer. See that module for DB_WAL_* settings.
+
+Trade journal (journal_signals, journal_orders, journal_fills,
+journal_positions, plus round trips in trade_history), all attributed to a
+strategy: record_*() writers and trades_for_day() / pnl_by_strategy() /
+get_journal() / get_journal_net_positions() queries. Days are trading days, not calendar days:
+- JOURNAL_TIMEZONE: Timezone trading days are cut in (default America/New_York)
+- JOURNAL_DAY_START: Time the trading day starts, on the previous calendar day
+  if after midnight (default 18:00, the CME Globex open; 00:00 = calendar days)
+- TRADE_JOURNAL_ENABLED: The bot journals every fill it sees (default true);
+  signals are journaled by adding db.record_signal as a consumer of a
+  StrategyEngine's bus (engine.bus.add_consumer)
+
+Bulk history: get_bars() returns numpy column arrays or an Arrow RecordBatch
+(core.arrow_export.bar_schema) read with a binary COPY that numpy decodes in
+one pass, instead of building a dict per row. Requires numpy (and pyarrow for
+Arrow output).
+
+Schema migrations: versioned SQL files in infrastructure/migrations
+(NNNN_name.sql) applied in order by DatabaseManager.migrate() and recorded in
+schema_migrations. Schema changes go in a new file; applied files must not be
+edited (migrate() refuses to run on a checksum mismatch).
+- DB_AUTO_MIGRATE: Apply pending migrations on startup (default true)
+
+SQLite: a DATABASE_URL starting with sqlite: selects SQLiteDatabaseManager
+(infrastructure/sqlite_database.py), the same API over one WAL-mode file for
+single-machine deployments. Its migrations live in infrastructure/migrations/sqlite;
+a schema change adds a file to both directories.
 
 Uses connection pooling for efficiency and supports Railway's PostgreSQL.
 """
 
+import hashlib
+import io
 import os
+import re
 import logging
+import struct
+import threading
+import time
 import psycopg2
 from psycopg2 import pool, sql
 from psycopg2.extras import RealDictCursor, execute_values
 from typing import List, Dict, Optional, Any
-from datetime import datetime, timezone
+from datetime import date, datetime, time as dt_time, timedelta, timezone
 from contextlib import contextmanager
+from pathlib import Path
+from zoneinfo import ZoneInfo
+from dataclasses import dataclass, field
 import json
 
 logger = logging.getLogger(__name__)
 
+MIGRATIONS_DIR = Path(__file__).resolve().parent / 'migrations'
+MIGRATION_FILE_PATTERN = re.compile(r'^(\d+)_([a-z0-9_]+)\.sql$')
+MIGRATION_LOCK_ID = 7346120001  # pg_advisory_lock key shared by every migrate() caller
+MIGRATIONS_TABLE_SQL = """
+    CREATE TABLE IF NOT EXISTS schema_migrations (
+        version INT PRIMARY KEY,
+        name VARCHAR(200) NOT NULL,
+        checksum CHAR(64) NOT NULL,
+        execution_ms DECIMAL(10, 2),
+        applied_at TIMESTAMPTZ DEFAULT NOW()
+    )
+"""
+
+# Ticks are append-only and queried by symbol + time range; no surrogate key
+# (a unique index would have to include ts on a partitioned table anyway)
+TICK_TABLE_SQL = """
+    CREATE TABLE IF NOT EXISTS market_ticks (
+        ts TIMESTAMPTZ NOT NULL,
+        symbol VARCHAR(20) NOT NULL,
+        price DOUBLE PRECISION NOT NULL,
+        size INTEGER NOT NULL DEFAULT 0,
+        side SMALLINT,  -- 0 = buy aggressor, 1 = sell aggressor, NULL = unknown
+        bid DOUBLE PRECISION,
+        ask DOUBLE PRECISION
+    ) {partition_clause}
+"""
+TICK_INDEX_SQL = "CREATE INDEX IF NOT EXISTS idx_ticks_symbol_ts ON market_ticks (symbol, ts)"
+TICK_COPY_SQL = "COPY market_ticks (ts, symbol, price, size, side, bid, ask) FROM STDIN"
+
+# get_bars() columns, all float8 in the binary COPY (timestamp as epoch seconds, UTC)
+BAR_COPY_FIELDS = ('timestamp', 'open', 'high', 'low', 'close', 'volume', 'tick_count')
+BAR_COPY_SQL = """
+    SELECT EXTRACT(EPOCH FROM timestamp)::float8,
+           COALESCE(open, 'NaN')::float8, COALESCE(high, 'NaN')::float8,
+           COALESCE(low, 'NaN')::float8, COALESCE(close, 'NaN')::float8,
+           COALESCE(volume, 0)::float8, COALESCE((metadata->>'tick_count')::float8, 0)
+    FROM historical_bars
+    WHERE symbol = %s AND timeframe = %s AND timestamp >= %s AND timestamp < %s
+    ORDER BY timestamp ASC
+"""
+PGCOPY_SIGNATURE = b'PGCOPY\n\xff\r\n\x00'
+
+# time_bucket() and date_bin() buckets both start from this origin (TimescaleDB's default)
+BUCKET_ORIGIN = "TIMESTAMPTZ '2000-01-03 00:00:00+00'"
+BUCKET_UNITS = {'s': 'seconds', 'm': 'minutes', 'h': 'hours', 'd': 'days', 'w': 'weeks'}
+
+BAR_UPSERT_SQL = """
+    INSERT INTO historical_bars 
+    (symbol, timeframe, timestamp, open, high, low, close, volume, metadata)
+    VALUES %s
+    ON CONFLICT (symbol, timeframe, timestamp) 
+    DO UPDATE SET 
+        open = EXCLUDED.open,
+        high = EXCLUDED.high,
+        low = EXCLUDED.low,
+        close = EXCLUDED.close,
+        volume = EXCLUDED.volume,
+        metadata = EXCLUDED.metadata,
+        created_at = NOW()
+"""
+
+@dataclass(frozen=True)
+class Migration:
+    """A versioned schema migration file (NNNN_name.sql)."""
+    version: int
+    name: str
+    sql: str
+    
+    @property
+    def filename(self) -> str:
+        return f"{self.version:04d}_{self.name}.sql"
+    
+    @property
+    def checksum(self) -> str:
+        return hashlib.sha256(self.sql.encode('utf-8')).hexdigest()
+
+
+def load_migrations(directory: Optional[Path] = None) -> List[Migration]:
+    """
+    Migrations in a directory (default MIGRATIONS_DIR), by version.
+    
+    Raises:
+        ValueError: A .sql file not named NNNN_name.sql, or two files with one version
+    """
+    migrations: Dict[int, Migration] = {}
+    for path in sorted(Path(directory or MIGRATIONS_DIR).glob('*.sql')):
+        match = MIGRATION_FILE_PATTERN.match(path.name)
+        if not match:
+            raise ValueError(f"Migration file '{path.name}' must be named NNNN_name.sql")
+        version = int(match.group(1))
+        if version in migrations:
+            raise ValueError(f"Duplicate migration version {version}: {migrations[version].filename}, {path.name}")
+        migrations[version] = Migration(version, match.group(2), path.read_text(encoding='utf-8'))
+    return [migrations[version] for version in sorted(migrations)]
+
+
+def parse_pgcopy_float8(payload: Any, fields: tuple) -> Dict[str, Any]:
+    """
+    Decode a binary COPY of non-null float8 columns into float64 numpy arrays.
+    
+    Every row is a fixed-size record (int16 field count, then an int32 length
+    and 8 bytes per field), so the payload is read with one np.frombuffer and
+    each column is a row of a single contiguous (fields, N) block.
+    
+    Raises:
+        ValueError: Not a binary COPY of len(fields) non-null float8 columns
+        ImportError: numpy is not installed
+    """
+    data = memoryview(payload).cast('B')
+    if bytes(data[:11]) != PGCOPY_SIGNATURE:
+        raise ValueError("Not a binary COPY payload")
+    extension = struct.unpack_from('>I', data, 15)[0]
+    body = data[19 + extension:]
+    if len(body) < 2 or bytes(body[-2:]) != b'\xff\xff':
+        raise ValueError("Binary COPY payload is truncated")
+    body = body[:-2]
+    width = len(fields)
+    if len(body) % (2 + 12 * width):
+        raise ValueError(f"Binary COPY rows are not {width} non-null float8 columns")
+    try:
+        import numpy as np
+    except ImportError as e:
+        raise ImportError("numpy is required for array output (pip install numpy)") from e
+    layout = [('_count', '>i2')]
+    for index, name in enumerate(fields):
+        layout += [(f'_len{index}', '>i4'), (name, '>f8')]
+    dtype = np.dtype(layout)
+    records = np.frombuffer(body, dtype=dtype) if len(body) else np.zeros(0, dtype=dtype)
+    if len(records) and ((records['_count'] != width).any()
+                         or any((records[f'_len{index}'] != 8).any() for index in range(width))):
+        raise ValueError(f"Binary COPY rows are not {width} non-null float8 columns")
+    block = np.empty((width, len(records)), dtype=np.float64)
+    for index, name in enumerate(fields):
+        block[index] = records[name]  # Big-endian to native in one vectorized copy
+    return dict(zip(fields, block))
+
+
+def bucket_interval(timeframe: str) -> str:
+    """
+    PostgreSQL interval of a timeframe ('5m' -> '5 minutes').
+    
+    Raises:
+        ValueError: Not a time-based timeframe (s/m/h/d/w)
+    """
+    text = str(timeframe).strip().lower()
+    try:
+        count = int(text[:-1])
+        unit = BUCKET_UNITS[text[-1:]]
+    except (KeyError, ValueError):
+        count = 0
+    if count <= 0:
+        raise ValueError(f"Invalid timeframe '{timeframe}', expected e.g. 30s, 5m, 1h, 1d, 1w")
+    return f"{count} {unit}"
+
+
+JOURNAL_TABLES = {
+    'signals': 'journal_signals',
+    'orders': 'journal_orders',
+    'fills': 'journal_fills',
+    'positions': 'journal_positions',
+}
+
+
+def trading_day_bounds(day: date) -> tuple:
+    """
+    UTC [start, end) of a trading day (JOURNAL_TIMEZONE / JOURNAL_DAY_START).
+    
+    With the default 18:00 America/New_York start, trading day 2025-11-19 runs
+    from 2025-11-18 18:00 to 2025-11-19 18:00 New York time.
+    """
+    tz = ZoneInfo(os.getenv('JOURNAL_TIMEZONE', 'America/New_York'))
+    start_time = dt_time.fromisoformat(os.getenv('JOURNAL_DAY_START', '18:00'))
+    first_day = day - timedelta(days=1) if start_time != dt_time(0) else day
+    start = datetime.combine(first_day, start_time, tzinfo=tz)
+    end = datetime.combine(first_day + timedelta(days=1), start_time, tzinfo=tz)
+    return start.astimezone(timezone.utc), end.astimezone(timezone.utc)
+
+
+def _journal_side(side: Any) -> Optional[str]:
+    """BUY/SELL from API side codes (0/1), BUY/SELL or long/short."""
+    if side is None:
+        return None
+    text = str(side).strip().upper()
+    return {'0': 'BUY', 'LONG': 'BUY', '1': 'SELL', 'SHORT': 'SELL'}.get(text, text)
+
+
+def _journal_time(value: Any) -> datetime:
+    """Timestamp of a journal entry (ISO string, datetime or now), timezone-aware."""
+    if isinstance(value, str):
+        value = datetime.fromisoformat(value.replace('Z', '+00:00'))
+    if value is None:
+        return datetime.now(timezone.utc)
+    return value if value.tzinfo else value.replace(tzinfo=timezone.utc)
+
+
+def _journal_metadata(entry: Dict, known) -> Optional[str]:
+    extra = {k: v for k, v in entry.items() if k not in known and v is not None}
+    return json.dumps(extra, default=str) if extra else None
+
+
+@dataclass
+class PoolStats:
+    """Checkout counters for one connection pool."""
+    name: str
+    checkouts: int = 0
+    errors: int = 0
+    stale_reconnects: int = 0
+    total_wait_ms: float = 0.0
+    max_wait_ms: float = 0.0
+    lock: threading.Lock = field(default_factory=threading.Lock, repr=False)
+    
+    def record_checkout(self, wait_ms: float):
+        with self.lock:
+            self.checkouts += 1
+            self.total_wait_ms += wait_ms
+            self.max_wait_ms = max(self.max_wait_ms, wait_ms)
+    
+    def record_error(self):
+        with self.lock:
+            self.errors += 1
+    
+    def to_dict(self, pool) -> Dict[str, Any]:
+        with self.lock:
+            return {
+                "checkouts": self.checkouts,
+                "errors": self.errors,
+                "stale_reconnects": self.stale_reconnects,
+                "avg_wait_ms": round(self.total_wait_ms / self.checkouts, 3) if self.checkouts else 0.0,
+                "max_wait_ms": round(self.max_wait_ms, 3),
+                # psycopg2 pools don't expose these publicly
+                "in_use": len(getattr(pool, '_used', {}) or {}),
+                "idle": len(getattr(pool, '_pool', []) or []),
+                "max_size": getattr(pool, 'maxconn', None),
+                "closed": bool(getattr(pool, 'closed', False)),
+            }
+
 
 class DatabaseManager:
     """
@@ -32,13 +349,24 @@ class DatabaseManager:
     - Automatic schema creation/migration
     - Thread-safe operations
     - Railway PostgreSQL compatible
+    - Optional read replica pool (DATABASE_READ_URL) with per-pool health metrics
     """
     
     def __init__(self):
         """Initialize database manager with connection pool."""
         self.pool = None
+        self.read_pool = None  # Replica pool, None when reads share the primary
+        self.pool_stats = {'primary': PoolStats('primary'), 'read': PoolStats('read')}
+        self.read_fallbacks = 0
+        self.tick_storage: Optional[str] = None  # 'timescale', 'partitioned' or 'plain'
+        self._tick_partitions: set = set()  # Days with a native partition
+        self.bar_storage: Optional[str] = None  # 'timescale' or 'plain'
+        self.market_data_writer: Optional['MarketDataWriter'] = None
+        self.durable_writer = None  # write_ahead_log.DurableWriter, see get_durable_writer()
         self._initialize_pool()
         self._initialize_schema()
+        self._initialize_tick_storage()
+        self._initialize_bar_storage()
         logger.info("✅ Database manager initialized")
     
     def _get_connection_params(self) -> Dict[str, str]:
@@ -132,7 +460,7 @@ class DatabaseManager:
             # Create connection pool (min 2, max 10 connections)
             self.pool = psycopg2.pool.ThreadedConnectionPool(
                 minconn=2,
-                maxconn=10,
+                maxconn=int(os.getenv('DB_POOL_MAX_CONN', '10')),
                 **params
             )
             
@@ -140,20 +468,70 @@ class DatabaseManager:
         except Exception as e:
             logger.error(f"❌ Failed to create database pool: {e}")
             raise
+        self._initialize_read_pool()
+    
+    def _initialize_read_pool(self):
+        """Create the read replica pool if DATABASE_READ_URL is set (reads use the primary otherwise)."""
+        read_url = os.getenv('DATABASE_READ_URL')
+        if not read_url:
+            return
+        try:
+            self.read_pool = psycopg2.pool.ThreadedConnectionPool(
+                minconn=1,
+                maxconn=int(os.getenv('DB_READ_POOL_MAX_CONN', os.getenv('DB_POOL_MAX_CONN', '10'))),
+                dsn=read_url,
+                # Guard against a helper routed here by mistake writing to a promoted replica
+                options='-c default_transaction_read_only=on',
+            )
+            logger.info("✅ Read replica connection pool created")
+        except Exception as e:
+            self.read_pool = None
+            logger.warning(f"⚠️  Read replica unavailable, reads will use the primary: {e}")
+    
+    def _checkout(self, read_only: bool):
+        """Get a connection and the pool it belongs to, falling back to the primary for reads."""
+        if read_only and self.read_pool is not None:
+            started = time.perf_counter()
+            try:
+                conn = self.read_pool.getconn()
+                self.pool_stats['read'].record_checkout((time.perf_counter() - started) * 1000)
+                return conn, self.read_pool, self.pool_stats['read']
+            except (psycopg2.pool.PoolError, psycopg2.OperationalError) as e:
+                self.pool_stats['read'].record_error()
+                self.read_fallbacks += 1
+                logger.warning(f"⚠️  Read replica checkout failed, using primary: {e}")
+        started = time.perf_counter()
+        try:
+            conn = self.pool.getconn()
+        except Exception:
+            self.pool_stats['primary'].record_error()
+            raise
+        self.pool_stats['primary'].record_checkout((time.perf_counter() - started) * 1000)
+        return conn, self.pool, self.pool_stats['primary']
+    
+    @staticmethod
+    def _execute_values(cur, query: str, values: List[tuple], page_size: int = 100) -> None:
+        """Multi-row INSERT of values into the query's 'VALUES %s' (SQLiteDatabaseManager uses executemany)."""
+        execute_values(cur, query, values, page_size=page_size)
     
     @contextmanager
-    def get_connection(self):
+    def get_connection(self, read_only: bool = False):
         """
         Context manager for database connections with health check.
         
+        Args:
+            read_only: Use the read replica pool when configured (replica lag
+                applies, so only for reads that tolerate slightly stale data)
+        
         Usage:
             with db.get_connection() as conn:
                 with conn.cursor() as cur:
                     cur.execute("SELECT ...")
         """
         conn = None
+        conn_pool = self.pool
         try:
-            conn = self.pool.getconn()
+            conn, conn_pool, stats = self._checkout(read_only)
             
             # Health check: Test if connection is still alive
             try:
@@ -161,11 +539,12 @@ class DatabaseManager:
             except (psycopg2.InterfaceError, psycopg2.OperationalError):
                 # Connection is dead, close it and get a new one
                 logger.warning("⚠️ Stale database connection detected, reconnecting...")
+                stats.stale_reconnects += 1
                 try:
                     conn.close()
                 except:
                     pass
-                conn = self.pool.getconn()
+                conn = conn_pool.getconn()
             
             yield conn
             conn.commit()
@@ -181,204 +560,114 @@ class DatabaseManager:
         finally:
             if conn:
                 try:
-                    self.pool.putconn(conn)
+                    conn_pool.putconn(conn)
                 except:
                     # Connection might be already closed, that's ok
                     pass
     
     def _initialize_schema(self):
-        """Create database schema if it doesn't exist."""
+        """Create or upgrade the schema with the versioned migrations (unless DB_AUTO_MIGRATE=false)."""
+        if os.getenv('DB_AUTO_MIGRATE', 'true').lower() not in ('true', '1', 'yes', 'on'):
+            logger.info("⏭️  DB_AUTO_MIGRATE disabled, schema left as is")
+            return
         logger.info("🔨 Initializing database schema...")
+        try:
+            applied = self.migrate()
+            logger.info(f"✅ Database schema initialized ({len(applied)} migration(s) applied)")
+        except Exception as e:
+            logger.error(f"❌ Failed to initialize schema: {e}")
+            raise
+    
+    # ==================== Schema Migration Methods ====================
+    
+    def migrate(self, target: Optional[int] = None, directory: Optional[Path] = None) -> List[int]:
+        """
+        Apply pending schema migrations in version order.
         
-        schema_sql = """
-        -- Historical market data (OHLCV bars)
-        CREATE TABLE IF NOT EXISTS historical_bars (
-            id SERIAL PRIMARY KEY,
-            symbol VARCHAR(20) NOT NULL,
-            timeframe VARCHAR(10) NOT NULL,
-            timestamp TIMESTAMPTZ NOT NULL,
-            open DECIMAL(12, 4),
-            high DECIMAL(12, 4),
-            low DECIMAL(12, 4),
-            close DECIMAL(12, 4),
-            volume BIGINT,
-            metadata JSONB,  -- Store additional data (bid/ask, etc.)
-            created_at TIMESTAMPTZ DEFAULT NOW(),
-            UNIQUE(symbol, timeframe, timestamp)
-        );
-        
-        -- Indexes for fast lookups
-        CREATE INDEX IF NOT EXISTS idx_bars_lookup 
-            ON historical_bars(symbol, timeframe, timestamp DESC);
-        CREATE INDEX IF NOT EXISTS idx_bars_symbol_timeframe 
-            ON historical_bars(symbol, timeframe);
-        CREATE INDEX IF NOT EXISTS idx_bars_created 
-            ON historical_bars(created_at);
-        
-        -- Account state tracking
-        CREATE TABLE IF NOT EXISTS account_state (
-            account_id VARCHAR(50) PRIMARY KEY,
-            account_name VARCHAR(100),
-            balance DECIMAL(12, 2),
-            starting_balance DECIMAL(12, 2),
-            daily_pnl DECIMAL(12, 2),
-            dll_remaining DECIMAL(12, 2),
-            mll_remaining DECIMAL(12, 2),
-            total_trades_today INT DEFAULT 0,
-            winning_trades_today INT DEFAULT 0,
-            losing_trades_today INT DEFAULT 0,
-            metadata JSONB,  -- Additional account data
-            last_updated TIMESTAMPTZ DEFAULT NOW()
-        );
-        
-        -- Strategy performance metrics
-        CREATE TABLE IF NOT EXISTS strategy_performance (
-            id SERIAL PRIMARY KEY,
-            strategy_name VARCHAR(50) NOT NULL,
-            symbol VARCHAR(20),
-            timestamp TIMESTAMPTZ DEFAULT NOW(),
-            total_trades INT DEFAULT 0,
-            winning_trades INT DEFAULT 0,
-            losing_trades INT DEFAULT 0,
-            total_pnl DECIMAL(12, 2) DEFAULT 0,
-            win_rate DECIMAL(5, 2),
-            profit_factor DECIMAL(8, 2),
-            max_drawdown DECIMAL(12, 2),
-            sharpe_ratio DECIMAL(8, 2),
-            avg_win DECIMAL(12, 2),
-            avg_loss DECIMAL(12, 2),
-            best_trade DECIMAL(12, 2),
-            worst_trade DECIMAL(12, 2),
-            metadata JSONB  -- Additional metrics
-        );
-        
-        CREATE INDEX IF NOT EXISTS idx_strategy_name 
-            ON strategy_performance(strategy_name, timestamp DESC);
-        CREATE INDEX IF NOT EXISTS idx_strategy_symbol 
-            ON strategy_performance(symbol, timestamp DESC);
-        
-        -- API performance metrics
-        CREATE TABLE IF NOT EXISTS api_metrics (
-            id SERIAL PRIMARY KEY,
-            endpoint VARCHAR(200) NOT NULL,
-            method VARCHAR(10) NOT NULL,
-            duration_ms DECIMAL(10, 2),
-            status_code INT,
-            success BOOLEAN,
-            error_message TEXT,
-            timestamp TIMESTAMPTZ DEFAULT NOW()
-        );
-        
-        CREATE INDEX IF NOT EXISTS idx_api_endpoint 
-            ON api_metrics(endpoint, timestamp DESC);
-        CREATE INDEX IF NOT EXISTS idx_api_timestamp 
-            ON api_metrics(timestamp DESC);
-        
-        -- Trade history (for detailed tracking)
-        CREATE TABLE IF NOT EXISTS trade_history (
-            id SERIAL PRIMARY KEY,
-            account_id VARCHAR(50),
-            strategy_name VARCHAR(50),
-            symbol VARCHAR(20),
-            side VARCHAR(10),  -- BUY or SELL
-            quantity INT,
-            entry_price DECIMAL(12, 4),
-            exit_price DECIMAL(12, 4),
-            pnl DECIMAL(12, 2),
-            entry_time TIMESTAMPTZ,
-            exit_time TIMESTAMPTZ,
-            duration_seconds INT,
-            metadata JSONB,  -- Order IDs, stop/target prices, etc.
-            created_at TIMESTAMPTZ DEFAULT NOW()
-        );
-        
-        -- Order history cache (raw API responses for faster dashboard loads)
-        CREATE TABLE IF NOT EXISTS order_history_cache (
-            id SERIAL PRIMARY KEY,
-            account_id VARCHAR(50) NOT NULL,
-            order_data JSONB NOT NULL,
-            order_timestamp TIMESTAMPTZ NOT NULL,
-            cached_at TIMESTAMPTZ DEFAULT NOW()
-        );
-        CREATE INDEX IF NOT EXISTS idx_order_cache_account_time 
-            ON order_history_cache(account_id, order_timestamp DESC);
-        CREATE INDEX IF NOT EXISTS idx_order_cache_cached_at 
-            ON order_history_cache(cached_at);
-        -- Composite index for fast lookups
-        CREATE INDEX IF NOT EXISTS idx_order_cache_lookup
-            ON order_history_cache(account_id, order_timestamp DESC, cached_at);
-        
-        CREATE INDEX IF NOT EXISTS idx_trades_account 
-            ON trade_history(account_id, created_at DESC);
-        CREATE INDEX IF NOT EXISTS idx_trades_strategy 
-            ON trade_history(strategy_name, created_at DESC);
-        CREATE INDEX IF NOT EXISTS idx_trades_symbol 
-            ON trade_history(symbol, created_at DESC);
-        
-        -- Cache metadata (track what's cached and when)
-        CREATE TABLE IF NOT EXISTS cache_metadata (
-            cache_key VARCHAR(200) PRIMARY KEY,
-            cache_type VARCHAR(50),  -- 'historical_bars', 'account', etc.
-            last_updated TIMESTAMPTZ DEFAULT NOW(),
-            expires_at TIMESTAMPTZ,
-            hit_count INT DEFAULT 0,
-            metadata JSONB
-        );
-        
-        CREATE INDEX IF NOT EXISTS idx_cache_type 
-            ON cache_metadata(cache_type, last_updated DESC);
-
-        -- Strategy state persistence
-        CREATE TABLE IF NOT EXISTS strategy_states (
-            account_id VARCHAR(50) NOT NULL,
-            strategy_name VARCHAR(50) NOT NULL,
-            enabled BOOLEAN DEFAULT FALSE,
-            symbols TEXT[] DEFAULT ARRAY[]::TEXT[],
-            settings JSONB,
-            metadata JSONB,
-            last_started TIMESTAMPTZ,
-            last_stopped TIMESTAMPTZ,
-            created_at TIMESTAMPTZ DEFAULT NOW(),
-            updated_at TIMESTAMPTZ DEFAULT NOW(),
-            PRIMARY KEY (account_id, strategy_name)
-        );
-        CREATE INDEX IF NOT EXISTS idx_strategy_states_account
-            ON strategy_states(account_id);
-
-        -- Dashboard/UI settings persistence
-        CREATE TABLE IF NOT EXISTS dashboard_settings (
-            account_id VARCHAR(50) PRIMARY KEY,
-            settings JSONB NOT NULL,
-            updated_at TIMESTAMPTZ DEFAULT NOW()
-        );
-        
-        -- Notifications table for server-side notification tracking
-        CREATE TABLE IF NOT EXISTS notifications (
-            id SERIAL PRIMARY KEY,
-            account_id VARCHAR(50) NOT NULL,
-            notification_type VARCHAR(50) NOT NULL,
-            message TEXT NOT NULL,
-            level VARCHAR(20) DEFAULT 'info',  -- 'info', 'success', 'warning', 'error'
-            meta JSONB,
-            created_at TIMESTAMPTZ DEFAULT NOW()
-        );
-        
-        CREATE INDEX IF NOT EXISTS idx_notifications_account 
-            ON notifications(account_id, created_at DESC);
-        CREATE INDEX IF NOT EXISTS idx_notifications_type 
-            ON notifications(notification_type, created_at DESC);
-        CREATE INDEX IF NOT EXISTS idx_notifications_level 
-            ON notifications(level, created_at DESC);
+        Each migration runs in its own transaction and is recorded in
+        schema_migrations with its checksum. A session advisory lock serializes
+        concurrent callers (bot and web server starting together), so every
+        migration runs exactly once.
+        
+        Args:
+            target: Stop after this version (default: apply all)
+            directory: Migration files (default: infrastructure/migrations)
+        
+        Returns:
+            List[int]: Versions applied by this call
+        
+        Raises:
+            RuntimeError: A migration failed (it is rolled back) or an applied one was edited
+        """
+        migrations = load_migrations(directory)
+        applied_now: List[int] = []
+        with self.get_connection() as conn:
+            with conn.cursor() as cur:
+                cur.execute("SELECT pg_advisory_lock(%s)", (MIGRATION_LOCK_ID,))
+                try:
+                    cur.execute(MIGRATIONS_TABLE_SQL)
+                    conn.commit()
+                    cur.execute("SELECT version, checksum FROM schema_migrations")
+                    applied = {version: checksum for version, checksum in cur.fetchall()}
+                    self._check_applied(migrations, applied)
+                    for migration in migrations:
+                        if migration.version in applied or (target is not None and migration.version > target):
+                            continue
+                        started = time.perf_counter()
+                        try:
+                            cur.execute(migration.sql)
+                            cur.execute("""
+                                INSERT INTO schema_migrations (version, name, checksum, execution_ms)
+                                VALUES (%s, %s, %s, %s)
+                            """, (migration.version, migration.name, migration.checksum,
+                                  round((time.perf_counter() - started) * 1000, 2)))
+                            conn.commit()
+                        except Exception as e:
+                            conn.rollback()
+                            raise RuntimeError(f"Migration {migration.filename} failed: {e}") from e
+                        applied_now.append(migration.version)
+                        logger.info(f"🗄️  Applied migration {migration.filename}")
+                finally:
+                    cur.execute("SELECT pg_advisory_unlock(%s)", (MIGRATION_LOCK_ID,))
+        return applied_now
+    
+    @staticmethod
+    def _check_applied(migrations: List['Migration'], applied: Dict[int, str]) -> None:
+        """Refuse to run when an applied migration's file changed; warn about versions this code doesn't know."""
+        known = {migration.version: migration for migration in migrations}
+        for version, checksum in sorted(applied.items()):
+            migration = known.get(version)
+            if migration is None:
+                logger.warning(f"⚠️  Database has migration {version:04d}, which this code doesn't ship "
+                               f"(newer release?)")
+            elif migration.checksum != checksum:
+                raise RuntimeError(f"Migration {migration.filename} was modified after it was applied; "
+                                   f"add a new migration instead of editing it")
+    
+    def migration_status(self, directory: Optional[Path] = None) -> Dict[str, Any]:
         """
+        Applied and pending migrations.
         
+        Returns:
+            Dict: version (latest applied, 0 for none), applied and pending versions
+        """
+        migrations = load_migrations(directory)
         try:
-            with self.get_connection() as conn:
+            with self.get_connection(read_only=True) as conn:
                 with conn.cursor() as cur:
-                    cur.execute(schema_sql)
-            logger.info("✅ Database schema initialized")
+                    cur.execute("SELECT to_regclass('public.schema_migrations') IS NOT NULL")
+                    applied: List[int] = []
+                    if cur.fetchone()[0]:
+                        cur.execute("SELECT version FROM schema_migrations ORDER BY version")
+                        applied = [row[0] for row in cur.fetchall()]
         except Exception as e:
-            logger.error(f"❌ Failed to initialize schema: {e}")
-            raise
+            logger.error(f"❌ Failed to read migration status: {e}")
+            return {}
+        return {
+            'version': applied[-1] if applied else 0,
+            'applied': applied,
+            'pending': [m.version for m in migrations if m.version not in applied],
+        }
     
     # ==================== Historical Data Methods ====================
     
@@ -401,58 +690,13 @@ class DatabaseManager:
             with self.get_connection() as conn:
                 with conn.cursor() as cur:
                     # Prepare data for bulk insert
-                    values = []
-                    for bar in bars:
-                        timestamp = bar.get('timestamp') or bar.get('time')
-                        if not timestamp:
-                            continue
-                        
-                        # Convert timestamp string to datetime if needed
-                        if isinstance(timestamp, str):
-                            try:
-                                timestamp = datetime.fromisoformat(timestamp.replace('Z', '+00:00'))
-                            except:
-                                continue
-                        
-                        # Extract additional metadata
-                        metadata = {
-                            k: v for k, v in bar.items() 
-                            if k not in ['symbol', 'timeframe', 'timestamp', 'time', 
-                                       'open', 'high', 'low', 'close', 'volume']
-                        }
-                        
-                        values.append((
-                            symbol,
-                            timeframe,
-                            timestamp,
-                            bar.get('open'),
-                            bar.get('high'),
-                            bar.get('low'),
-                            bar.get('close'),
-                            bar.get('volume'),
-                            json.dumps(metadata) if metadata else None
-                        ))
+                    values = self._bar_rows(symbol, timeframe, bars)
                     
                     if not values:
                         return 0
                     
                     # Use ON CONFLICT to handle duplicates
-                    insert_sql = """
-                        INSERT INTO historical_bars 
-                        (symbol, timeframe, timestamp, open, high, low, close, volume, metadata)
-                        VALUES %s
-                        ON CONFLICT (symbol, timeframe, timestamp) 
-                        DO UPDATE SET 
-                            open = EXCLUDED.open,
-                            high = EXCLUDED.high,
-                            low = EXCLUDED.low,
-                            close = EXCLUDED.close,
-                            volume = EXCLUDED.volume,
-                            metadata = EXCLUDED.metadata,
-                            created_at = NOW()
-                    """
-                    
-                    execute_values(cur, insert_sql, values)
+                    self._execute_values(cur, BAR_UPSERT_SQL, values)
                     
                     logger.info(f"✅ Cached {len(values)} bars for {symbol} {timeframe}")
                     return len(values)
@@ -461,6 +705,102 @@ class DatabaseManager:
             logger.error(f"❌ Failed to cache historical bars: {e}")
             return 0
     
+    def rewrite_range(self, symbol: str, timeframe: str, start_time: datetime, end_time: datetime,
+                      bars: List[Dict]) -> Dict[str, int]:
+        """
+        Replace the stored bars of a range with corrected ones, in one transaction.
+        
+        Stored bars in [start_time, end_time) that are not in bars are deleted, so
+        an empty list clears the range. Readers see either the old or the new range.
+        
+        Args:
+            symbol: Trading symbol
+            timeframe: Timeframe (e.g., "1m")
+            start_time: Range start (inclusive, UTC)
+            end_time: Range end (exclusive, UTC)
+            bars: Corrected OHLCV bar dictionaries, all inside the range
+        
+        Returns:
+            Dict: 'deleted' (stored bars removed) and 'written' counts; {} if the rewrite failed
+        
+        Raises:
+            ValueError: Empty range, or a bar without a timestamp or outside the range
+        """
+        if start_time >= end_time:
+            raise ValueError(f"Empty range: {start_time} >= {end_time}")
+        for bar in bars:
+            row = self._bar_row(symbol, timeframe, bar)
+            if row is None or not isinstance(row[2], datetime):
+                raise ValueError(f"Bar without a usable timestamp: {bar}")
+            timestamp = row[2] if row[2].tzinfo else row[2].replace(tzinfo=timezone.utc)
+            if not start_time <= timestamp < end_time:
+                raise ValueError(f"Bar at {row[2]} is outside {start_time} - {end_time}")
+        values = self._bar_rows(symbol, timeframe, bars)
+        
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    cur.execute("""
+                        DELETE FROM historical_bars
+                        WHERE symbol = %s AND timeframe = %s AND timestamp >= %s AND timestamp < %s
+                    """, (symbol, timeframe, start_time, end_time))
+                    deleted = cur.rowcount
+                    self._execute_values(cur, BAR_UPSERT_SQL, values)
+            logger.info(f"✅ Rewrote {symbol} {timeframe} {start_time} - {end_time}: "
+                        f"{deleted} bars replaced by {len(values)}")
+            return {'deleted': deleted, 'written': len(values)}
+        except Exception as e:
+            logger.error(f"❌ Failed to rewrite {symbol} {timeframe} bars: {e}")
+            return {}
+    
+    @classmethod
+    def _bar_rows(cls, symbol: str, timeframe: str, bars: List[Dict]) -> List[tuple]:
+        """
+        historical_bars rows of OHLCV dicts, one per timestamp (the last one wins).
+        
+        A single upsert statement cannot update the same row twice, so duplicates
+        within a batch (re-delivered bars) are collapsed first.
+        """
+        rows = {}
+        for bar in bars:
+            row = cls._bar_row(symbol, timeframe, bar)
+            if row:
+                rows[row[2]] = row
+        return list(rows.values())
+    
+    @staticmethod
+    def _bar_row(symbol: str, timeframe: str, bar: Dict) -> Optional[tuple]:
+        """historical_bars row of an OHLCV dict (None without a usable timestamp)."""
+        timestamp = bar.get('timestamp') or bar.get('time')
+        if not timestamp:
+            return None
+        
+        # Convert timestamp string to datetime if needed
+        if isinstance(timestamp, str):
+            try:
+                timestamp = datetime.fromisoformat(timestamp.replace('Z', '+00:00'))
+            except ValueError:
+                return None
+        
+        # Extract additional metadata
+        metadata = {
+            k: v for k, v in bar.items() 
+            if k not in ['symbol', 'timeframe', 'timestamp', 'time', 
+                       'open', 'high', 'low', 'close', 'volume']
+        }
+        
+        return (
+            symbol,
+            timeframe,
+            timestamp,
+            bar.get('open'),
+            bar.get('high'),
+            bar.get('low'),
+            bar.get('close'),
+            bar.get('volume'),
+            json.dumps(metadata) if metadata else None
+        )
+    
     def get_cached_bars(self, symbol: str, timeframe: str, 
                        start_time: Optional[datetime] = None,
                        end_time: Optional[datetime] = None,
@@ -479,7 +819,7 @@ class DatabaseManager:
             List[Dict]: Cached bars in standard format
         """
         try:
-            with self.get_connection() as conn:
+            with self.get_connection(read_only=True) as conn:
                 with conn.cursor(cursor_factory=RealDictCursor) as cur:
                     # Build query based on time constraints
                     if start_time and end_time:
@@ -550,6 +890,71 @@ class DatabaseManager:
             logger.error(f"❌ Failed to retrieve cached bars: {e}")
             return []
     
+    def get_bars(self, symbol: str, timeframe: str, start_time: datetime, end_time: datetime,
+                 output: str = 'numpy') -> Any:
+        """
+        Cached bars in [start_time, end_time) as columns, for large history loads.
+        
+        The rows are streamed with COPY ... TO STDOUT (FORMAT binary) and decoded
+        by numpy in one pass (parse_pgcopy_float8), so no Python object is built
+        per row. Missing prices are NaN; missing volume/tick_count are 0.
+        
+        Args:
+            symbol: Trading symbol
+            timeframe: Stored timeframe
+            start_time: Start (UTC, inclusive)
+            end_time: End (UTC, exclusive)
+            output: 'numpy' (dict of float64 arrays like bars_to_numpy(), timestamp in
+                epoch seconds, plus tick_count) or 'arrow' (RecordBatch with
+                core.arrow_export.bar_schema())
+        
+        Raises:
+            ValueError: Unknown output
+            ImportError: numpy (or pyarrow for 'arrow') is not installed
+        """
+        if output not in ('numpy', 'arrow'):
+            raise ValueError(f"Unknown output '{output}', expected 'numpy' or 'arrow'")
+        buffer = io.BytesIO()
+        started = time.perf_counter()
+        try:
+            with self.get_connection(read_only=True) as conn:
+                with conn.cursor() as cur:
+                    select = cur.mogrify(BAR_COPY_SQL, (symbol, timeframe, start_time, end_time))
+                    if isinstance(select, bytes):
+                        select = select.decode('utf-8')
+                    cur.copy_expert(f"COPY ({select.strip()}) TO STDOUT WITH (FORMAT binary)", buffer)
+        except Exception as e:
+            logger.error(f"❌ Failed to load {symbol} {timeframe} bars: {e}")
+            raise
+        columns = parse_pgcopy_float8(buffer.getbuffer(), BAR_COPY_FIELDS)
+        logger.debug(f"📥 Loaded {len(columns['timestamp'])} {symbol} {timeframe} bars as arrays in "
+                     f"{(time.perf_counter() - started) * 1000:.1f}ms")
+        if output == 'numpy':
+            return columns
+        return self._bar_columns_to_arrow(symbol, timeframe, columns)
+    
+    @staticmethod
+    def _bar_columns_to_arrow(symbol: str, timeframe: str, columns: Dict[str, Any]):
+        """RecordBatch with bar_schema() over get_bars() columns (prices are adopted without copying)."""
+        from core.arrow_export import bar_schema
+        import numpy as np
+        import pyarrow as pa
+        schema = bar_schema()
+        count = len(columns['timestamp'])
+        micros = np.rint(columns['timestamp'] * 1_000_000).astype(np.int64)
+        return pa.RecordBatch.from_arrays([
+            pa.repeat(symbol, count).cast(pa.string()),
+            pa.repeat(timeframe, count).cast(pa.string()),
+            pa.array(micros).cast(schema.field('timestamp').type),
+            pa.array(columns['open']),
+            pa.array(columns['high']),
+            pa.array(columns['low']),
+            pa.array(columns['close']),
+            pa.array(columns['volume'].astype(np.int64)),
+            pa.array(columns['tick_count'].astype(np.int64)),
+            pa.nulls(count, pa.bool_()),
+        ], schema=schema)
+    
     def get_cache_coverage(self, symbol: str, timeframe: str) -> Dict:
         """
         Get information about cache coverage for a symbol/timeframe.
@@ -558,7 +963,7 @@ class DatabaseManager:
             Dict: Coverage info (oldest, newest, count)
         """
         try:
-            with self.get_connection() as conn:
+            with self.get_connection(read_only=True) as conn:
                 with conn.cursor(cursor_factory=RealDictCursor) as cur:
                     query = """
                         SELECT 
@@ -587,6 +992,527 @@ class DatabaseManager:
             logger.error(f"❌ Failed to get cache coverage: {e}")
             return {'cached': False, 'bar_count': 0}
     
+    def get_cached_symbols(self, timeframe: str) -> List[str]:
+        """
+        List all symbols that have cached bars for a timeframe.
+        
+        Args:
+            timeframe: Timeframe (e.g., "1d", "1h")
+        
+        Returns:
+            List[str]: Symbols sorted alphabetically
+        """
+        try:
+            with self.get_connection(read_only=True) as conn:
+                with conn.cursor() as cur:
+                    cur.execute("""
+                        SELECT DISTINCT symbol
+                        FROM historical_bars
+                        WHERE timeframe = %s
+                        ORDER BY symbol
+                    """, (timeframe,))
+                    return [row[0] for row in cur.fetchall()]
+        
+        except Exception as e:
+            logger.error(f"❌ Failed to list cached symbols: {e}")
+            return []
+    
+    # ==================== Tick Data Methods ====================
+    
+    def _initialize_tick_storage(self):
+        """
+        Create the market_ticks table as a TimescaleDB hypertable or a natively partitioned table.
+        
+        Mode (env DB_TICK_STORAGE):
+        - 'auto' (default): TimescaleDB when the extension is available, else native partitions
+        - 'timescale': Require TimescaleDB
+        - 'partitioned': Native declarative partitioning (daily RANGE partitions on ts)
+        
+        An existing table keeps its layout; the mode only applies on creation.
+        """
+        mode = os.getenv('DB_TICK_STORAGE', 'auto').strip().lower()
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    existing = self._detect_tick_storage(cur)
+                    if existing:
+                        self.tick_storage = existing
+                    elif mode in ('auto', 'timescale') and self._enable_timescale(cur, required=mode == 'timescale'):
+                        self._create_tick_hypertable(cur)
+                        self.tick_storage = 'timescale'
+                    else:
+                        self._create_partitioned_ticks(cur)
+                        self.tick_storage = 'partitioned'
+            if self.tick_storage == 'partitioned':
+                self.maintain_tick_partitions()
+            elif self.tick_storage == 'timescale':
+                self._apply_timescale_policies()
+            logger.info(f"✅ Tick storage ready ({self.tick_storage})")
+        except Exception as e:
+            self.tick_storage = None
+            logger.error(f"❌ Failed to initialize tick storage: {e}")
+    
+    @staticmethod
+    def _detect_tick_storage(cur) -> Optional[str]:
+        """Layout of an existing market_ticks table ('timescale', 'partitioned', 'plain') or None."""
+        cur.execute("SELECT to_regclass('public.market_ticks') IS NOT NULL")
+        if not cur.fetchone()[0]:
+            return None
+        cur.execute("SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb')")
+        if cur.fetchone()[0]:
+            cur.execute("""
+                SELECT EXISTS (SELECT 1 FROM timescaledb_information.hypertables
+                               WHERE hypertable_name = 'market_ticks')
+            """)
+            if cur.fetchone()[0]:
+                return 'timescale'
+        cur.execute("""
+            SELECT EXISTS (SELECT 1 FROM pg_partitioned_table
+                           WHERE partrelid = 'public.market_ticks'::regclass)
+        """)
+        return 'partitioned' if cur.fetchone()[0] else 'plain'
+    
+    @staticmethod
+    def _enable_timescale(cur, required: bool = False, setting: str = 'DB_TICK_STORAGE') -> bool:
+        """Enable the timescaledb extension if the server has it."""
+        cur.execute("SELECT EXISTS (SELECT 1 FROM pg_available_extensions WHERE name = 'timescaledb')")
+        if not cur.fetchone()[0]:
+            if required:
+                raise RuntimeError(f"{setting}=timescale but the timescaledb extension is not available")
+            return False
+        cur.execute("SAVEPOINT enable_timescale")
+        try:
+            cur.execute("CREATE EXTENSION IF NOT EXISTS timescaledb")
+            cur.execute("RELEASE SAVEPOINT enable_timescale")
+            return True
+        except psycopg2.Error as e:
+            cur.execute("ROLLBACK TO SAVEPOINT enable_timescale")
+            if required:
+                raise
+            logger.warning(f"⚠️  TimescaleDB available but could not be enabled ({e}); using native partitions")
+            return False
+    
+    @staticmethod
+    def _create_tick_hypertable(cur):
+        cur.execute(TICK_TABLE_SQL.format(partition_clause=""))
+        cur.execute("""
+            SELECT create_hypertable('market_ticks', 'ts',
+                                     chunk_time_interval => INTERVAL '1 day', if_not_exists => TRUE)
+        """)
+        cur.execute(TICK_INDEX_SQL)
+    
+    @staticmethod
+    def _create_partitioned_ticks(cur):
+        cur.execute(TICK_TABLE_SQL.format(partition_clause="PARTITION BY RANGE (ts)"))
+        cur.execute(TICK_INDEX_SQL)  # Propagated to every partition
+    
+    def _apply_timescale_policies(self):
+        """Compress chunks older than DB_TICK_COMPRESS_AFTER_DAYS, drop those past DB_TICK_RETENTION_DAYS."""
+        compress_after = int(os.getenv('DB_TICK_COMPRESS_AFTER_DAYS', '3'))
+        retention_days = int(os.getenv('DB_TICK_RETENTION_DAYS', '0'))
+        with self.get_connection() as conn:
+            with conn.cursor() as cur:
+                if compress_after > 0:
+                    self._add_compression_policy(cur, 'market_ticks', 'symbol', 'ts', compress_after)
+                if retention_days > 0:
+                    cur.execute("SELECT add_retention_policy('market_ticks', %s::interval, if_not_exists => TRUE)",
+                                (f"{retention_days} days",))
+    
+    @staticmethod
+    def _add_compression_policy(cur, table: str, segmentby: str, orderby: str, days: int) -> None:
+        """Enable native compression on a hypertable and compress chunks older than days."""
+        cur.execute(sql.SQL("""
+            ALTER TABLE {} SET (
+                timescaledb.compress,
+                timescaledb.compress_segmentby = %s,
+                timescaledb.compress_orderby = %s
+            )
+        """).format(sql.Identifier(table)), (segmentby, orderby))
+        cur.execute("SELECT add_compression_policy(%s, %s::interval, if_not_exists => TRUE)", (table, f"{days} days"))
+    
+    @staticmethod
+    def _tick_partition_name(day: date) -> str:
+        return f"market_ticks_p{day.strftime('%Y%m%d')}"
+    
+    def _ensure_tick_partitions(self, cur, days) -> None:
+        """Create daily partitions that don't exist yet (native partitioning only)."""
+        for day in sorted(set(days) - self._tick_partitions):
+            cur.execute(sql.SQL(
+                "CREATE TABLE IF NOT EXISTS {} PARTITION OF market_ticks FOR VALUES FROM (%s) TO (%s)"
+            ).format(sql.Identifier(self._tick_partition_name(day))), (
+                datetime.combine(day, datetime.min.time(), tzinfo=timezone.utc),
+                datetime.combine(day + timedelta(days=1), datetime.min.time(), tzinfo=timezone.utc),
+            ))
+            self._tick_partitions.add(day)
+    
+    def maintain_tick_partitions(self) -> Dict[str, int]:
+        """
+        Pre-create upcoming daily partitions and drop expired ones (native partitioning).
+        
+        Keeps DB_TICK_PARTITIONS_AHEAD days (default 3) created ahead of today and
+        drops partitions older than DB_TICK_RETENTION_DAYS (default 0 = keep forever).
+        Dropping a partition is instant and frees space immediately, unlike DELETE.
+        
+        Returns:
+            Dict: Partitions created and dropped
+        """
+        if self.tick_storage != 'partitioned':
+            return {'created': 0, 'dropped': 0}
+        ahead = int(os.getenv('DB_TICK_PARTITIONS_AHEAD', '3'))
+        retention_days = int(os.getenv('DB_TICK_RETENTION_DAYS', '0'))
+        today = datetime.now(timezone.utc).date()
+        dropped = 0
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    cur.execute("""
+                        SELECT c.relname FROM pg_inherits i
+                        JOIN pg_class c ON c.oid = i.inhrelid
+                        WHERE i.inhparent = 'public.market_ticks'::regclass
+                    """)
+                    existing = {}
+                    for (name,) in cur.fetchall():
+                        try:
+                            existing[datetime.strptime(name[-8:], '%Y%m%d').date()] = name
+                        except ValueError:
+                            continue  # Not one of ours
+                    self._tick_partitions = set(existing)
+                    wanted = [today + timedelta(days=offset) for offset in range(-1, ahead + 1)]
+                    created = len(set(wanted) - self._tick_partitions)
+                    self._ensure_tick_partitions(cur, wanted)
+                    if retention_days > 0:
+                        cutoff = today - timedelta(days=retention_days)
+                        for day, name in sorted(existing.items()):
+                            if day < cutoff:
+                                cur.execute(sql.SQL("DROP TABLE IF EXISTS {}").format(sql.Identifier(name)))
+                                self._tick_partitions.discard(day)
+                                dropped += 1
+            if created or dropped:
+                logger.info(f"🗂️  Tick partitions: {created} created, {dropped} dropped")
+            return {'created': created, 'dropped': dropped}
+        except Exception as e:
+            logger.error(f"❌ Failed to maintain tick partitions: {e}")
+            return {'created': 0, 'dropped': 0}
+    
+    def save_ticks(self, ticks: List[Dict]) -> int:
+        """
+        Bulk insert ticks.
+        
+        Args:
+            ticks: Dicts with symbol, timestamp (datetime or ISO string), price and
+                optional size, side ('buy'/'sell'), bid, ask
+        
+        Returns:
+            int: Number of ticks written
+        """
+        if not ticks or not self.tick_storage:
+            return 0
+        values = [row for row in (self._tick_row(tick) for tick in ticks) if row]
+        if not values:
+            return 0
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    self._insert_ticks(cur, values)
+            return len(values)
+        except Exception as e:
+            logger.error(f"❌ Failed to save {len(values)} ticks: {e}")
+            return 0
+    
+    @staticmethod
+    def _tick_row(tick: Dict) -> Optional[tuple]:
+        """market_ticks row of a tick dict (None if it lacks a timestamp, price or symbol)."""
+        ts = tick.get('timestamp') or tick.get('ts')
+        if isinstance(ts, str):
+            try:
+                ts = datetime.fromisoformat(ts.replace('Z', '+00:00'))
+            except ValueError:
+                return None
+        if ts is None or tick.get('price') is None or not tick.get('symbol'):
+            return None
+        if ts.tzinfo is None:
+            ts = ts.replace(tzinfo=timezone.utc)
+        side = {'buy': 0, 'sell': 1}.get(str(tick.get('side') or '').lower())
+        return (ts, str(tick['symbol']).upper(), float(tick['price']), int(tick.get('size') or 0),
+                side, tick.get('bid'), tick.get('ask'))
+    
+    def _insert_ticks(self, cur, values: List[tuple], copy: bool = False) -> None:
+        """Insert tick rows with multi-row INSERTs or COPY, creating missing partitions first."""
+        if self.tick_storage == 'partitioned':
+            self._ensure_tick_partitions(cur, {v[0].astimezone(timezone.utc).date() for v in values})
+        if copy:
+            buffer = io.StringIO()
+            for row in values:
+                buffer.write('\t'.join('\\N' if v is None else v.isoformat() if isinstance(v, datetime) else str(v)
+                                       for v in row))
+                buffer.write('\n')
+            buffer.seek(0)
+            cur.copy_expert(TICK_COPY_SQL, buffer)
+        else:
+            self._execute_values(cur, """
+                INSERT INTO market_ticks (ts, symbol, price, size, side, bid, ask) VALUES %s
+            """, values, page_size=1000)
+    
+    def write_market_data(self, ticks: List[tuple], bars: List[tuple], copy: bool = True) -> None:
+        """
+        Write prepared tick and bar rows in one transaction (MarketDataWriter's flush).
+        
+        Args:
+            ticks: _tick_row() rows, written with COPY (or multi-row INSERTs)
+            bars: _bar_row() rows, upserted; at most one row per (symbol, timeframe, timestamp)
+            copy: Use COPY for ticks
+        
+        Raises:
+            Exception: Any database error; nothing is written then
+        """
+        with self.get_connection() as conn:
+            with conn.cursor() as cur:
+                if ticks and self.tick_storage:
+                    self._insert_ticks(cur, ticks, copy=copy)
+                if bars:
+                    self._execute_values(cur, BAR_UPSERT_SQL, bars, page_size=1000)
+    
+    def get_ticks(self, symbol: str, start_time: datetime, end_time: datetime,
+                  limit: Optional[int] = None) -> List[Dict]:
+        """
+        Ticks for a symbol in [start_time, end_time).
+        
+        Both bounds are required and passed as plain ts comparisons so the planner
+        prunes to the partitions (or chunks) covering the range instead of scanning
+        the whole table.
+        
+        Returns:
+            List[Dict]: Ticks in time order
+        """
+        if not self.tick_storage:
+            return []
+        query = """
+            SELECT ts, symbol, price, size, side, bid, ask
+            FROM market_ticks
+            WHERE symbol = %s AND ts >= %s AND ts < %s
+            ORDER BY ts ASC
+        """
+        params: List[Any] = [symbol.upper(), start_time, end_time]
+        if limit:
+            query += " LIMIT %s"
+            params.append(limit)
+        try:
+            with self.get_connection(read_only=True) as conn:
+                with conn.cursor(cursor_factory=RealDictCursor) as cur:
+                    cur.execute(query, params)
+                    return [{
+                        'timestamp': row['ts'].isoformat(),
+                        'symbol': row['symbol'],
+                        'price': row['price'],
+                        'size': row['size'],
+                        'side': {0: 'buy', 1: 'sell'}.get(row['side']),
+                        'bid': row['bid'],
+                        'ask': row['ask'],
+                    } for row in cur.fetchall()]
+        except Exception as e:
+            logger.error(f"❌ Failed to retrieve ticks for {symbol}: {e}")
+            return []
+    
+    def get_tick_storage_stats(self) -> Dict:
+        """Tick table layout, partition/chunk count and on-disk size."""
+        stats: Dict[str, Any] = {'storage': self.tick_storage}
+        if not self.tick_storage:
+            return stats
+        try:
+            with self.get_connection(read_only=True) as conn:
+                with conn.cursor(cursor_factory=RealDictCursor) as cur:
+                    if self.tick_storage == 'timescale':
+                        cur.execute("""
+                            SELECT COUNT(*) AS partitions,
+                                   COUNT(*) FILTER (WHERE is_compressed) AS compressed
+                            FROM timescaledb_information.chunks WHERE hypertable_name = 'market_ticks'
+                        """)
+                        stats.update(cur.fetchone())
+                        cur.execute("SELECT hypertable_size('market_ticks') AS bytes")
+                    else:
+                        cur.execute("""
+                            SELECT COUNT(*) AS partitions,
+                                   COALESCE(SUM(pg_total_relation_size(inhrelid)), 0) AS bytes
+                            FROM pg_inherits WHERE inhparent = 'public.market_ticks'::regclass
+                        """)
+                    stats.update(cur.fetchone())
+        except Exception as e:
+            logger.error(f"❌ Failed to get tick storage stats: {e}")
+        return stats
+    
+    # ==================== Bar Storage & Downsampling Methods ====================
+    
+    def _initialize_bar_storage(self):
+        """
+        Convert historical_bars to a TimescaleDB hypertable when configured (env DB_BAR_STORAGE).
+        
+        Mode:
+        - 'auto' (default): Hypertable when the timescaledb extension is already enabled
+        - 'timescale': Enable the extension and require a hypertable
+        - 'plain': Leave the table alone
+        """
+        mode = os.getenv('DB_BAR_STORAGE', 'auto').strip().lower()
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    cur.execute("SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb')")
+                    enabled = cur.fetchone()[0]
+                    if enabled and self._is_hypertable(cur, 'historical_bars'):
+                        self.bar_storage = 'timescale'
+                    elif mode == 'timescale' or (mode == 'auto' and enabled):
+                        if not enabled:
+                            self._enable_timescale(cur, required=True, setting='DB_BAR_STORAGE')
+                        self._create_bar_hypertable(cur)
+                        self.bar_storage = 'timescale'
+                    else:
+                        self.bar_storage = 'plain'
+            if self.bar_storage == 'timescale':
+                compress_after = int(os.getenv('DB_BAR_COMPRESS_AFTER_DAYS', '30'))
+                if compress_after > 0:
+                    with self.get_connection() as conn:
+                        with conn.cursor() as cur:
+                            self._add_compression_policy(cur, 'historical_bars', 'symbol, timeframe',
+                                                         'timestamp DESC', compress_after)
+            logger.info(f"✅ Bar storage ready ({self.bar_storage})")
+        except Exception as e:
+            self.bar_storage = 'plain'
+            logger.error(f"❌ Failed to initialize bar hypertable, keeping a plain table: {e}")
+    
+    @staticmethod
+    def _is_hypertable(cur, table: str) -> bool:
+        cur.execute("""
+            SELECT EXISTS (SELECT 1 FROM timescaledb_information.hypertables WHERE hypertable_name = %s)
+        """, (table,))
+        return bool(cur.fetchone()[0])
+    
+    @staticmethod
+    def _create_bar_hypertable(cur):
+        """Convert historical_bars in place (unique keys must include the time column, so id loses its PK)."""
+        chunk_days = int(os.getenv('DB_BAR_CHUNK_DAYS', '30'))
+        cur.execute("ALTER TABLE historical_bars DROP CONSTRAINT IF EXISTS historical_bars_pkey")
+        cur.execute("""
+            SELECT create_hypertable('historical_bars', 'timestamp', chunk_time_interval => %s::interval,
+                                     migrate_data => TRUE, if_not_exists => TRUE)
+        """, (f"{chunk_days} days",))
+        logger.info("📊 historical_bars converted to a TimescaleDB hypertable")
+    
+    def _bucket_sql(self, column: str) -> str:
+        """Bucket expression for a %s interval parameter: time_bucket() on TimescaleDB, else date_bin()."""
+        if 'timescale' in (self.tick_storage, self.bar_storage):
+            return f"time_bucket(%s::interval, {column}, {BUCKET_ORIGIN})"
+        return f"date_bin(%s::interval, {column}, {BUCKET_ORIGIN})"
+    
+    def _first_last_sql(self, value: str, column: str) -> tuple:
+        """first()/last() aggregates of a value by time (array_agg without TimescaleDB)."""
+        if 'timescale' in (self.tick_storage, self.bar_storage):
+            return f"first({value}, {column})", f"last({value}, {column})"
+        return (f"(array_agg({value} ORDER BY {column} ASC))[1]",
+                f"(array_agg({value} ORDER BY {column} DESC))[1]")
+    
+    def _bucketed_query(self, select: str, table: str, where: str, params: List[Any], interval: str,
+                        start_time: Optional[datetime], limit: int) -> List[Dict]:
+        """Run a GROUP BY bucket query; the latest buckets when there is no start time, always oldest first."""
+        order = 'ASC' if start_time else 'DESC'
+        query = f"{select} FROM {table} WHERE {where} GROUP BY bucket ORDER BY bucket {order} LIMIT %s"
+        with self.get_connection(read_only=True) as conn:
+            with conn.cursor(cursor_factory=RealDictCursor) as cur:
+                cur.execute(query, [interval] + params + [limit])
+                rows = cur.fetchall()
+        return rows if start_time else rows[::-1]
+    
+    def get_downsampled_bars(self, symbol: str, timeframe: str, source_timeframe: str = '1m',
+                             start_time: Optional[datetime] = None, end_time: Optional[datetime] = None,
+                             limit: int = 1000) -> List[Dict]:
+        """
+        Stored bars rolled up into a higher timeframe in the database.
+        
+        Args:
+            symbol: Trading symbol
+            timeframe: Target timeframe, e.g. '15m', '4h', '1d', '1w'
+            source_timeframe: Stored bars to roll up (default '1m')
+            start_time: Optional start (UTC); without it the latest buckets are returned
+            end_time: Optional end (UTC, exclusive)
+            limit: Maximum number of bars
+        
+        Returns:
+            List[Dict]: Bars in the get_cached_bars() format plus bar_count, oldest first.
+            The last bar may be partial.
+        
+        Raises:
+            ValueError: Timeframe that isn't time-based
+        """
+        interval = bucket_interval(timeframe)
+        first = self._first_last_sql('open', 'timestamp')[0]
+        last = self._first_last_sql('close', 'timestamp')[1]
+        select = f"""
+            SELECT {self._bucket_sql('timestamp')} AS bucket,
+                   {first} AS open, MAX(high) AS high, MIN(low) AS low, {last} AS close,
+                   SUM(volume) AS volume, COUNT(*) AS bar_count
+        """
+        where = "symbol = %s AND timeframe = %s"
+        params: List[Any] = [symbol, source_timeframe]
+        if start_time:
+            where += " AND timestamp >= %s"
+            params.append(start_time)
+        if end_time:
+            where += " AND timestamp < %s"
+            params.append(end_time)
+        try:
+            rows = self._bucketed_query(select, 'historical_bars', where, params, interval, start_time, limit)
+        except Exception as e:
+            logger.error(f"❌ Failed to downsample {symbol} {source_timeframe} bars to {timeframe}: {e}")
+            return []
+        logger.debug(f"📥 Downsampled {len(rows)} {symbol} bars to {interval}")
+        return [self._bucket_row(row, symbol, timeframe) for row in rows]
+    
+    def get_tick_bars(self, symbol: str, timeframe: str, start_time: datetime, end_time: datetime,
+                      limit: int = 10000) -> List[Dict]:
+        """
+        OHLCV bars built from raw ticks in the database (both bounds required so chunks are pruned).
+        
+        Returns:
+            List[Dict]: Bars with tick_count, buy_volume and sell_volume, oldest first
+        
+        Raises:
+            ValueError: Timeframe that isn't time-based
+        """
+        interval = bucket_interval(timeframe)
+        if not self.tick_storage:
+            return []
+        first, last = self._first_last_sql('price', 'ts')
+        select = f"""
+            SELECT {self._bucket_sql('ts')} AS bucket,
+                   {first} AS open, MAX(price) AS high, MIN(price) AS low, {last} AS close,
+                   SUM(size) AS volume, COUNT(*) AS tick_count,
+                   COALESCE(SUM(size) FILTER (WHERE side = 0), 0) AS buy_volume,
+                   COALESCE(SUM(size) FILTER (WHERE side = 1), 0) AS sell_volume
+        """
+        try:
+            rows = self._bucketed_query(select, 'market_ticks', "symbol = %s AND ts >= %s AND ts < %s",
+                                        [symbol.upper(), start_time, end_time], interval, start_time, limit)
+        except Exception as e:
+            logger.error(f"❌ Failed to build {timeframe} bars from {symbol} ticks: {e}")
+            return []
+        return [self._bucket_row(row, symbol.upper(), timeframe) for row in rows]
+    
+    @staticmethod
+    def _bucket_row(row: Dict, symbol: str, timeframe: str) -> Dict:
+        bar = {
+            'symbol': symbol,
+            'timeframe': timeframe,
+            'timestamp': row['bucket'].isoformat(),
+            'time': row['bucket'].isoformat(),
+        }
+        for key, value in row.items():
+            if key == 'bucket':
+                continue
+            if key in ('open', 'high', 'low', 'close'):
+                value = float(value) if value is not None else None
+            elif value is not None:
+                value = int(value)
+            bar[key] = value
+        return bar
+    
     # ==================== Account State Methods ====================
     
     def save_account_state(self, account_id: str, state: Dict) -> bool:
@@ -840,6 +1766,57 @@ class DatabaseManager:
         
         return {}
     
+    # ==================== Symbol Trading Switch Methods ====================
+    
+    def save_symbol_switch(self, symbol: str, enabled: bool, reason: Optional[str] = None,
+                           updated_by: Optional[str] = None) -> bool:
+        """Persist the trading enable/disable switch for a symbol."""
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    cur.execute(
+                        """
+                        INSERT INTO symbol_trading_switches (symbol, enabled, reason, updated_by, updated_at)
+                        VALUES (%s, %s, %s, %s, NOW())
+                        ON CONFLICT (symbol)
+                        DO UPDATE SET
+                            enabled = EXCLUDED.enabled,
+                            reason = EXCLUDED.reason,
+                            updated_by = EXCLUDED.updated_by,
+                            updated_at = NOW()
+                        """,
+                        (symbol.upper(), enabled, reason, updated_by),
+                    )
+            logger.debug(f"💾 Saved trading switch for {symbol.upper()} -> enabled={enabled}")
+            return True
+        except Exception as e:
+            logger.error(f"❌ Failed to save trading switch ({symbol}): {e}")
+            return False
+    
+    def get_symbol_switches(self) -> Optional[Dict[str, Dict]]:
+        """
+        Retrieve all symbol trading switches keyed by symbol.
+        
+        Returns:
+            Dict of switches, or None if the query failed (distinguishes "none set" from "unknown")
+        """
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor(cursor_factory=RealDictCursor) as cur:
+                    cur.execute("SELECT symbol, enabled, reason, updated_by, updated_at FROM symbol_trading_switches")
+                    return {
+                        row['symbol']: {
+                            "enabled": row['enabled'],
+                            "reason": row['reason'],
+                            "updated_by": row['updated_by'],
+                            "updated_at": row['updated_at'].isoformat() if row.get('updated_at') else None,
+                        }
+                        for row in cur.fetchall()
+                    }
+        except Exception as e:
+            logger.error(f"❌ Failed to load symbol trading switches: {e}")
+            return None
+    
     # ==================== Strategy Performance Methods ====================
     
     def save_strategy_metrics(self, strategy_name: str, metrics: Dict) -> bool:
@@ -992,7 +1969,7 @@ class DatabaseManager:
                         return True
                     
                     # Bulk insert with ON CONFLICT DO NOTHING to avoid duplicates
-                    execute_values(
+                    self._execute_values(
                         cur,
                         """
                         INSERT INTO order_history_cache (account_id, order_data, order_timestamp)
@@ -1060,6 +2037,387 @@ class DatabaseManager:
             logger.error(f"❌ Failed to get cached order history: {e}")
             return None
     
+    # ==================== Trade Journal Methods ====================
+    
+    def record_signal(self, signal: Any, account_id: Optional[str] = None) -> Optional[int]:
+        """
+        Journal a strategy signal.
+        
+        Args:
+            signal: strategy_engine Signal, or a dict with strategy_id (or strategy_name), symbol,
+                direction, price, timestamp and optional stop, target, confidence, reason
+            account_id: Account the signal trades, if known
+        
+        Returns:
+            Optional[int]: Journal id (pass it to record_order() as signal_id), None on failure
+        """
+        entry = signal.to_dict() if hasattr(signal, 'to_dict') else dict(signal)
+        known = ('strategy_id', 'strategy_name', 'symbol', 'direction', 'price', 'timestamp', 'stop', 'target',
+                 'confidence', 'reason')
+        direction = entry.get('direction')
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    cur.execute("""
+                        INSERT INTO journal_signals
+                        (ts, account_id, strategy_name, symbol, direction, price, stop_price, target_price,
+                         confidence, reason, metadata)
+                        VALUES (%s, %s, %s, %s, %s, %s, %s, %s, %s, %s, %s)
+                        RETURNING id
+                    """, (
+                        _journal_time(entry.get('timestamp')),
+                        str(account_id) if account_id else None,
+                        entry.get('strategy_id') or entry.get('strategy_name'),
+                        str(entry['symbol']).upper(),
+                        str(getattr(direction, 'value', direction)).lower(),
+                        entry.get('price'),
+                        entry.get('stop'),
+                        entry.get('target'),
+                        entry.get('confidence'),
+                        entry.get('reason') or None,
+                        _journal_metadata(entry, known),
+                    ))
+                    return cur.fetchone()[0]
+        except Exception as e:
+            logger.error(f"❌ Failed to journal signal: {e}")
+            return None
+    
+    def record_order(self, account_id: str, order: Dict) -> bool:
+        """
+        Journal an order (each submission or status change is its own entry).
+        
+        Args:
+            account_id: Account ID
+            order: order_id (or id), symbol, side (BUY/SELL or 0/1), quantity (or size) and optional
+                strategy_name, signal_id, order_type, price, status, timestamp; other keys go to metadata
+        
+        Returns:
+            bool: Success status
+        """
+        known = ('order_id', 'id', 'strategy_name', 'signal_id', 'symbol', 'side', 'quantity', 'size',
+                 'order_type', 'price', 'status', 'timestamp')
+        order_id = order.get('order_id') or order.get('id')
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    cur.execute("""
+                        INSERT INTO journal_orders
+                        (ts, account_id, strategy_name, order_id, signal_id, symbol, side, quantity,
+                         order_type, price, status, metadata)
+                        VALUES (%s, %s, %s, %s, %s, %s, %s, %s, %s, %s, %s, %s)
+                    """, (
+                        _journal_time(order.get('timestamp')),
+                        str(account_id),
+                        order.get('strategy_name'),
+                        str(order_id) if order_id is not None else None,
+                        order.get('signal_id'),
+                        str(order['symbol']).upper(),
+                        _journal_side(order.get('side')),
+                        int(order.get('quantity') or order.get('size') or 0),
+                        order.get('order_type'),
+                        order.get('price'),
+                        order.get('status'),
+                        _journal_metadata(order, known),
+                    ))
+            return True
+        except Exception as e:
+            logger.error(f"❌ Failed to journal order: {e}")
+            return False
+    
+    def record_fill(self, account_id: str, fill: Dict) -> bool:
+        """
+        Journal a fill; a fill_id already journaled for the account is ignored (replays are safe).
+        
+        Args:
+            account_id: Account ID
+            fill: symbol, side, quantity, price and optional fill_id, order_id, strategy_name,
+                commission, realized_pnl, timestamp; other keys go to metadata
+        
+        Returns:
+            bool: Success status (True for an ignored duplicate too)
+        """
+        known = ('fill_id', 'order_id', 'strategy_name', 'symbol', 'side', 'quantity', 'price', 'commission',
+                 'realized_pnl', 'timestamp')
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    cur.execute("""
+                        INSERT INTO journal_fills
+                        (ts, account_id, strategy_name, order_id, fill_id, symbol, side, quantity, price,
+                         commission, realized_pnl, metadata)
+                        VALUES (%s, %s, %s, %s, %s, %s, %s, %s, %s, %s, %s, %s)
+                        ON CONFLICT (account_id, fill_id) DO NOTHING
+                    """, (
+                        _journal_time(fill.get('timestamp')),
+                        str(account_id),
+                        fill.get('strategy_name'),
+                        str(fill['order_id']) if fill.get('order_id') is not None else None,
+                        str(fill['fill_id']) if fill.get('fill_id') is not None else None,
+                        str(fill['symbol']).upper(),
+                        _journal_side(fill.get('side')),
+                        int(fill.get('quantity') or 0),
+                        float(fill['price']),
+                        fill.get('commission') or 0,
+                        fill.get('realized_pnl'),
+                        _journal_metadata(fill, known),
+                    ))
+            return True
+        except Exception as e:
+            logger.error(f"❌ Failed to journal fill: {e}")
+            return False
+    
+    def record_positions(self, account_id: str, positions: List[Dict], timestamp: Optional[datetime] = None) -> int:
+        """
+        Journal a snapshot of positions.
+        
+        Args:
+            account_id: Account ID
+            positions: Dicts with symbol, quantity (signed) and optional strategy_name, avg_price,
+                unrealized_pnl, realized_pnl
+            timestamp: Snapshot time (default now)
+        
+        Returns:
+            int: Positions written
+        """
+        if not positions:
+            return 0
+        ts = _journal_time(timestamp)
+        known = ('strategy_name', 'symbol', 'quantity', 'avg_price', 'unrealized_pnl', 'realized_pnl')
+        values = [(
+            ts, str(account_id), p.get('strategy_name'), str(p['symbol']).upper(), int(p.get('quantity') or 0),
+            p.get('avg_price'), p.get('unrealized_pnl'), p.get('realized_pnl'), _journal_metadata(p, known),
+        ) for p in positions]
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    self._execute_values(cur, """
+                        INSERT INTO journal_positions
+                        (ts, account_id, strategy_name, symbol, quantity, avg_price, unrealized_pnl,
+                         realized_pnl, metadata)
+                        VALUES %s
+                    """, values)
+            return len(values)
+        except Exception as e:
+            logger.error(f"❌ Failed to journal positions: {e}")
+            return 0
+    
+    def get_journal_net_positions(self, account_id: str) -> Optional[Dict[str, int]]:
+        """
+        Net positions per the journal: the account's latest net snapshot (strategy_name NULL)
+        moved by the fills journaled after it.
+        
+        Returns:
+            Optional[Dict]: {symbol: signed quantity} of non-flat symbols; None without a
+                snapshot to start from, or on failure
+        """
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor(cursor_factory=RealDictCursor) as cur:
+                    cur.execute("""
+                        SELECT MAX(ts) AS ts FROM journal_positions
+                        WHERE account_id = %s AND strategy_name IS NULL
+                    """, (str(account_id),))
+                    row = cur.fetchone()
+                    snapshot_ts = row['ts'] if row else None
+                    if snapshot_ts is None:
+                        return None
+                    cur.execute("""
+                        SELECT symbol, quantity FROM journal_positions
+                        WHERE account_id = %s AND strategy_name IS NULL AND ts = %s
+                    """, (str(account_id), snapshot_ts))
+                    positions = {r['symbol']: int(r['quantity']) for r in cur.fetchall()}
+                    cur.execute("""
+                        SELECT symbol, side, SUM(quantity) AS quantity FROM journal_fills
+                        WHERE account_id = %s AND ts > %s
+                        GROUP BY symbol, side
+                    """, (str(account_id), snapshot_ts))
+                    for r in cur.fetchall():
+                        sign = 1 if r['side'] == 'BUY' else -1
+                        positions[r['symbol']] = positions.get(r['symbol'], 0) + sign * int(r['quantity'])
+            return {symbol: qty for symbol, qty in positions.items() if qty != 0}
+        except Exception as e:
+            logger.error(f"❌ Failed to read journaled positions: {e}")
+            return None
+    
+    def record_trade(self, account_id: str, trade: Dict) -> bool:
+        """
+        Journal a closed round trip in trade_history (what trades_for_day() and pnl_by_strategy() read).
+        
+        Args:
+            account_id: Account ID
+            trade: strategy_name, symbol, side (or direction long/short), quantity, entry_price,
+                exit_price, pnl, entry_time, exit_time; other keys go to metadata
+        
+        Returns:
+            bool: Success status
+        """
+        known = ('strategy_name', 'symbol', 'side', 'direction', 'quantity', 'entry_price', 'exit_price', 'pnl',
+                 'entry_time', 'exit_time')
+        entry_time = _journal_time(trade.get('entry_time')) if trade.get('entry_time') else None
+        exit_time = _journal_time(trade.get('exit_time'))
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    cur.execute("""
+                        INSERT INTO trade_history
+                        (account_id, strategy_name, symbol, side, quantity, entry_price, exit_price, pnl,
+                         entry_time, exit_time, duration_seconds, metadata)
+                        VALUES (%s, %s, %s, %s, %s, %s, %s, %s, %s, %s, %s, %s)
+                    """, (
+                        str(account_id),
+                        trade.get('strategy_name'),
+                        str(trade['symbol']).upper(),
+                        _journal_side(trade.get('side') or trade.get('direction')),
+                        int(trade.get('quantity') or 0),
+                        trade.get('entry_price'),
+                        trade.get('exit_price'),
+                        trade.get('pnl'),
+                        entry_time,
+                        exit_time,
+                        int((exit_time - entry_time).total_seconds()) if entry_time else None,
+                        _journal_metadata(trade, known),
+                    ))
+            return True
+        except Exception as e:
+            logger.error(f"❌ Failed to journal trade: {e}")
+            return False
+    
+    @staticmethod
+    def _journal_filters(account_id: Optional[str], strategy_name: Optional[str],
+                         symbol: Optional[str] = None) -> tuple:
+        """Extra WHERE conditions and parameters for the optional journal filters."""
+        conditions, params = [], []
+        for column, value in (('account_id', account_id), ('strategy_name', strategy_name), ('symbol', symbol)):
+            if value is not None:
+                conditions.append(f" AND {column} = %s")
+                params.append(str(value).upper() if column == 'symbol' else str(value))
+        return ''.join(conditions), params
+    
+    @staticmethod
+    def _journal_row(row: Dict) -> Dict:
+        """JSON-friendly journal row: decimals as floats, timestamps as ISO strings."""
+        result = {}
+        for key, value in row.items():
+            if isinstance(value, datetime):
+                value = value.isoformat()
+            elif value is not None and type(value).__name__ == 'Decimal':
+                value = float(value)
+            result[key] = value
+        return result
+    
+    def get_journal(self, kind: str, start: datetime, end: datetime, account_id: Optional[str] = None,
+                    strategy_name: Optional[str] = None, symbol: Optional[str] = None,
+                    limit: Optional[int] = None) -> List[Dict]:
+        """
+        Journal entries of one kind in [start, end), oldest first.
+        
+        Args:
+            kind: 'signals', 'orders', 'fills' or 'positions'
+        
+        Raises:
+            ValueError: Unknown kind
+        """
+        if kind not in JOURNAL_TABLES:
+            raise ValueError(f"Unknown journal '{kind}', expected one of {', '.join(JOURNAL_TABLES)}")
+        filters, params = self._journal_filters(account_id, strategy_name, symbol)
+        query = f"SELECT * FROM {JOURNAL_TABLES[kind]} WHERE ts >= %s AND ts < %s{filters} ORDER BY ts ASC, id ASC"
+        params = [start, end] + params
+        if limit:
+            query += " LIMIT %s"
+            params.append(limit)
+        try:
+            with self.get_connection(read_only=True) as conn:
+                with conn.cursor(cursor_factory=RealDictCursor) as cur:
+                    cur.execute(query, params)
+                    return [self._journal_row(row) for row in cur.fetchall()]
+        except Exception as e:
+            logger.error(f"❌ Failed to read {kind} journal: {e}")
+            return []
+    
+    def trades_for_day(self, day: date, account_id: Optional[str] = None,
+                       strategy_name: Optional[str] = None) -> List[Dict]:
+        """
+        Round trips closed on a trading day (see trading_day_bounds()), in exit order.
+        
+        Returns:
+            List[Dict]: trade_history rows
+        """
+        start, end = trading_day_bounds(day)
+        return self.get_trades(start, end, account_id=account_id, strategy_name=strategy_name)
+    
+    def get_trades(self, start: datetime, end: datetime, account_id: Optional[str] = None,
+                   strategy_name: Optional[str] = None, symbol: Optional[str] = None) -> List[Dict]:
+        """
+        Round trips closed in [start, end), in exit order.
+        
+        Returns:
+            List[Dict]: trade_history rows
+        """
+        filters, params = self._journal_filters(account_id, strategy_name, symbol)
+        try:
+            with self.get_connection(read_only=True) as conn:
+                with conn.cursor(cursor_factory=RealDictCursor) as cur:
+                    cur.execute(f"""
+                        SELECT id, account_id, strategy_name, symbol, side, quantity, entry_price, exit_price,
+                               pnl, entry_time, exit_time, duration_seconds, metadata
+                        FROM trade_history
+                        WHERE exit_time >= %s AND exit_time < %s{filters}
+                        ORDER BY exit_time ASC, id ASC
+                    """, [start, end] + params)
+                    return [self._journal_row(row) for row in cur.fetchall()]
+        except Exception as e:
+            logger.error(f"❌ Failed to get trades: {e}")
+            return []
+    
+    def pnl_by_strategy(self, start: Any, end: Any, account_id: Optional[str] = None) -> Dict[str, Dict]:
+        """
+        Closed-trade P&L per strategy.
+        
+        Args:
+            start: First trading day (date) or start time (datetime)
+            end: Last trading day, inclusive (date) or end time, exclusive (datetime)
+            account_id: Only this account
+        
+        Returns:
+            Dict: strategy_name ('unattributed' for NULL) -> trades, wins, losses, pnl, win_rate,
+            avg_pnl, best, worst
+        """
+        if not isinstance(start, datetime):
+            start = trading_day_bounds(start)[0]
+        if not isinstance(end, datetime):
+            end = trading_day_bounds(end)[1]
+        filters, params = self._journal_filters(account_id, None)
+        try:
+            with self.get_connection(read_only=True) as conn:
+                with conn.cursor(cursor_factory=RealDictCursor) as cur:
+                    cur.execute(f"""
+                        SELECT COALESCE(strategy_name, 'unattributed') AS strategy,
+                               COUNT(*) AS trades,
+                               COUNT(*) FILTER (WHERE pnl > 0) AS wins,
+                               COUNT(*) FILTER (WHERE pnl < 0) AS losses,
+                               COALESCE(SUM(pnl), 0) AS pnl,
+                               MAX(pnl) AS best,
+                               MIN(pnl) AS worst
+                        FROM trade_history
+                        WHERE exit_time >= %s AND exit_time < %s{filters}
+                        GROUP BY 1
+                        ORDER BY 1
+                    """, [start, end] + params)
+                    rows = cur.fetchall()
+        except Exception as e:
+            logger.error(f"❌ Failed to get P&L by strategy: {e}")
+            return {}
+        result = {}
+        for row in rows:
+            row = self._journal_row(row)
+            strategy, trades = row.pop('strategy'), row['trades']
+            result[strategy] = dict(
+                row,
+                win_rate=round(row['wins'] / trades * 100, 2) if trades else 0.0,
+                avg_pnl=round(row['pnl'] / trades, 2) if trades else 0.0,
+            )
+        return result
+    
     # ==================== Utility Methods ====================
     
     def cleanup_old_data(self, days: int = 30):
@@ -1089,6 +2447,9 @@ class DatabaseManager:
                     metrics_deleted = cur.rowcount
                     
                     logger.info(f"🧹 Cleanup: Deleted {bars_deleted} old bars, {metrics_deleted} old metrics")
+            
+            # Ticks expire by dropping whole partitions, never by DELETE
+            self.maintain_tick_partitions()
         
         except Exception as e:
             logger.error(f"❌ Failed to cleanup old data: {e}")
@@ -1101,7 +2462,7 @@ class DatabaseManager:
             Dict: Statistics about cached data
         """
         try:
-            with self.get_connection() as conn:
+            with self.get_connection(read_only=True) as conn:
                 with conn.cursor(cursor_factory=RealDictCursor) as cur:
                     stats = {}
                     
@@ -1130,8 +2491,10 @@ class DatabaseManager:
                     # API metrics count
                     cur.execute("SELECT COUNT(*) as count FROM api_metrics")
                     stats['api_metrics'] = cur.fetchone()['count']
-                    
-                    return stats
+            
+            stats['ticks'] = self.get_tick_storage_stats()
+            stats['bar_storage'] = self.bar_storage
+            return stats
         
         except Exception as e:
             logger.error(f"❌ Failed to get database stats: {e}")
@@ -1167,21 +2530,292 @@ class DatabaseManager:
             logger.error(f"❌ Failed to record notification: {e}")
             # Don't raise - notification recording failure shouldn't break the app
     
+    def get_pool_stats(self) -> Dict[str, Any]:
+        """
+        Connection pool health: checkouts, errors, wait times and occupancy per pool.
+        
+        Returns:
+            Dict: 'primary', 'read' (None without a replica) and 'read_fallbacks'
+        """
+        return {
+            "primary": self.pool_stats['primary'].to_dict(self.pool) if self.pool else None,
+            "read": self.pool_stats['read'].to_dict(self.read_pool) if self.read_pool else None,
+            "read_fallbacks": self.read_fallbacks,
+        }
+    
+    def get_market_data_writer(self) -> 'MarketDataWriter':
+        """The shared batched bar/tick writer, started on first use (flushed and stopped by close())."""
+        if getattr(self, 'market_data_writer', None) is None:
+            self.market_data_writer = MarketDataWriter(self)
+            self.market_data_writer.start()
+        return self.market_data_writer
+    
+    def get_durable_writer(self):
+        """
+        The shared write-ahead log writer, started on first use (replays unapplied
+        records; stopped by close()).
+        
+        Returns:
+            DurableWriter: See infrastructure.write_ahead_log
+        """
+        if getattr(self, 'durable_writer', None) is None:
+            from infrastructure.write_ahead_log import DurableWriter
+            self.durable_writer = DurableWriter(self)
+            self.durable_writer.start()
+        return self.durable_writer
+    
     def close(self):
-        """Close all connections in the pool."""
+        """Flush buffered market data and close all connections in the pool."""
+        if getattr(self, 'durable_writer', None) is not None:
+            self.durable_writer.stop()
+        if getattr(self, 'market_data_writer', None) is not None:
+            self.market_data_writer.stop()
+        if self.read_pool:
+            self.read_pool.closeall()
         if self.pool:
             self.pool.closeall()
             logger.info("✅ Database connections closed")
 
 
+class MarketDataWriter:
+    """
+    Buffered, batched writer for streaming bars and ticks.
+    
+    Rows are buffered and written by flush(): all buffered ticks and bars in
+    one transaction (ticks with COPY, bars as one multi-row upsert). Flushes
+    happen when DB_WRITE_BATCH_SIZE rows are buffered and every
+    DB_WRITE_FLUSH_INTERVAL seconds on the background flusher, and stop()
+    writes whatever is left.
+    
+    At-least-once: a failed batch goes back to the front of the buffer and is
+    retried on the next flush, so a batch whose commit succeeded but whose
+    acknowledgement was lost can be written twice (bars are upserts, so only
+    ticks can be duplicated). Rows are only lost past DB_WRITE_MAX_BUFFER
+    while the database stays unreachable (oldest ticks first, counted in
+    rows_dropped).
+    
+    Usage:
+        writer = db.get_market_data_writer()
+        bot.add_market_event_listener(writer.on_market_event)
+        writer.add_ticks([{'symbol': 'MNQ', 'timestamp': ts, 'price': 21000.25, 'size': 1}])
+        ...
+        db.close()  # Flushes
+    """
+    
+    def __init__(self, db: DatabaseManager, batch_size: Optional[int] = None,
+                 flush_interval: Optional[float] = None, max_buffer: Optional[int] = None,
+                 use_copy: Optional[bool] = None):
+        """
+        Initialize writer.
+        
+        Args:
+            db: Database to write to
+            batch_size: Buffered rows that trigger a flush (env: DB_WRITE_BATCH_SIZE)
+            flush_interval: Seconds between background flushes (env: DB_WRITE_FLUSH_INTERVAL)
+            max_buffer: Rows kept while writes fail (env: DB_WRITE_MAX_BUFFER)
+            use_copy: COPY ticks instead of multi-row INSERTs (env: DB_WRITE_USE_COPY)
+        """
+        self.db = db
+        self.batch_size = batch_size if batch_size is not None else int(os.getenv('DB_WRITE_BATCH_SIZE', '5000'))
+        self.flush_interval = flush_interval if flush_interval is not None else \
+            float(os.getenv('DB_WRITE_FLUSH_INTERVAL', '1'))
+        self.max_buffer = max_buffer if max_buffer is not None else int(os.getenv('DB_WRITE_MAX_BUFFER', '500000'))
+        self.use_copy = use_copy if use_copy is not None else \
+            os.getenv('DB_WRITE_USE_COPY', 'true').lower() in ('true', '1', 'yes', 'on')
+        self._ticks: List[tuple] = []
+        self._bars: Dict[tuple, tuple] = {}  # (symbol, timeframe, timestamp) -> row; the latest version wins
+        self._lock = threading.Lock()
+        self._write_lock = threading.Lock()
+        self._wake = threading.Event()
+        self._stop = threading.Event()
+        self._thread: Optional[threading.Thread] = None
+        self.ticks_written = 0
+        self.bars_written = 0
+        self.batches_written = 0
+        self.write_errors = 0
+        self.rows_dropped = 0
+        self.last_error: Optional[str] = None
+        self.last_flush_ms = 0.0
+    
+    # ---------------------------
+    # Buffering
+    # ---------------------------
+    def add_ticks(self, ticks: List[Any]) -> int:
+        """
+        Buffer ticks (dicts as accepted by save_ticks(), or Trade events).
+        
+        Returns:
+            int: Ticks buffered (malformed ones are skipped)
+        """
+        rows = []
+        for tick in ticks:
+            if not isinstance(tick, dict):
+                tick = {'symbol': tick.symbol, 'timestamp': tick.timestamp, 'price': tick.price,
+                        'size': getattr(tick, 'size', 0), 'side': getattr(tick, 'side', None)}
+            row = DatabaseManager._tick_row(tick)
+            if row:
+                rows.append(row)
+        if rows:
+            with self._lock:
+                self._ticks.extend(rows)
+            self._maybe_flush()
+        return len(rows)
+    
+    def add_bars(self, bars: List[Any], symbol: Optional[str] = None, timeframe: Optional[str] = None) -> int:
+        """
+        Buffer completed bars (Bar objects, or OHLCV dicts of the given symbol/timeframe).
+        
+        Returns:
+            int: Bars buffered
+        """
+        rows = []
+        for bar in bars:
+            if isinstance(bar, dict):
+                row = DatabaseManager._bar_row(symbol or bar.get('symbol'), timeframe or bar.get('timeframe'), bar)
+            else:
+                row = DatabaseManager._bar_row(bar.symbol.upper(), bar.timeframe, {
+                    'timestamp': bar.timestamp, 'open': bar.open, 'high': bar.high, 'low': bar.low,
+                    'close': bar.close, 'volume': bar.volume, 'tick_count': bar.tick_count,
+                })
+            if row and row[0] and row[1]:
+                rows.append(row)
+        if rows:
+            with self._lock:
+                for row in rows:
+                    self._bars[row[:3]] = row
+            self._maybe_flush()
+        return len(rows)
+    
+    def on_market_event(self, event: Any) -> None:
+        """Market event listener: stores Trade and BarClosed events."""
+        kind = getattr(event, 'type', None)
+        if kind == 'trade':
+            self.add_ticks([event])
+        elif kind == 'bar_closed':
+            self.add_bars([event.bar])
+    
+    def pending(self) -> int:
+        with self._lock:
+            return len(self._ticks) + len(self._bars)
+    
+    def _maybe_flush(self) -> None:
+        if self.pending() < self.batch_size:
+            return
+        if self._running():
+            self._wake.set()
+        else:
+            self.flush()
+    
+    def _running(self) -> bool:
+        return bool(self._thread and self._thread.is_alive())
+    
+    # ---------------------------
+    # Writing
+    # ---------------------------
+    def flush(self) -> int:
+        """
+        Write everything buffered as one batch.
+        
+        Returns:
+            int: Rows written (0 if the batch failed and was put back)
+        """
+        with self._write_lock:
+            with self._lock:
+                ticks, self._ticks = self._ticks, []
+                bars, self._bars = self._bars, {}
+            if not ticks and not bars:
+                return 0
+            started = time.perf_counter()
+            try:
+                self.db.write_market_data(ticks, list(bars.values()), copy=self.use_copy)
+            except Exception as e:
+                self._requeue(ticks, bars)
+                self.write_errors += 1
+                self.last_error = f"{type(e).__name__}: {e}"
+                logger.error(f"❌ Market data batch of {len(ticks)} ticks / {len(bars)} bars not written, "
+                             f"will retry: {self.last_error}")
+                return 0
+            self.last_flush_ms = (time.perf_counter() - started) * 1000
+            self.ticks_written += len(ticks)
+            self.bars_written += len(bars)
+            self.batches_written += 1
+            logger.debug(f"Wrote {len(ticks)} ticks and {len(bars)} bars in {self.last_flush_ms:.1f}ms")
+            return len(ticks) + len(bars)
+    
+    def _requeue(self, ticks: List[tuple], bars: Dict[tuple, tuple]) -> None:
+        """Put a failed batch back ahead of rows buffered since, within max_buffer."""
+        with self._lock:
+            self._ticks = ticks + self._ticks
+            bars.update(self._bars)  # Newer versions of the same bar win
+            self._bars = bars
+            excess = len(self._ticks) + len(self._bars) - self.max_buffer
+            if excess > 0:
+                dropped = min(excess, len(self._ticks))
+                del self._ticks[:dropped]
+                self.rows_dropped += dropped
+        if excess > 0:
+            logger.error(f"❌ Market data buffer full ({self.max_buffer} rows): dropped {dropped} oldest ticks")
+    
+    def start(self) -> None:
+        """Start the background flusher."""
+        if self._running():
+            return
+        self._stop.clear()
+        self._thread = threading.Thread(target=self._run, name="db-market-data-writer", daemon=True)
+        self._thread.start()
+        logger.info(f"✅ Market data writer started (batch {self.batch_size} rows / {self.flush_interval:g}s, "
+                    f"{'COPY' if self.use_copy else 'INSERT'})")
+    
+    def stop(self, timeout: float = 30.0) -> None:
+        """Stop the flusher and write everything still buffered."""
+        self._stop.set()
+        self._wake.set()
+        if self._thread:
+            self._thread.join(timeout)
+            self._thread = None
+        self.flush()
+        left = self.pending()
+        if left:
+            logger.error(f"❌ Market data writer stopped with {left} unwritten rows")
+    
+    def _run(self) -> None:
+        while not self._stop.is_set():
+            self._wake.wait(self.flush_interval)
+            self._wake.clear()
+            if self._stop.is_set():
+                break
+            try:
+                self.flush()
+            except Exception as e:
+                logger.error(f"❌ Market data writer error: {e}")
+    
+    def get_stats(self) -> Dict[str, Any]:
+        """Writer counters."""
+        return {
+            "running": self._running(),
+            "pending_rows": self.pending(),
+            "ticks_written": self.ticks_written,
+            "bars_written": self.bars_written,
+            "batches_written": self.batches_written,
+            "write_errors": self.write_errors,
+            "rows_dropped": self.rows_dropped,
+            "last_error": self.last_error,
+            "last_flush_ms": round(self.last_flush_ms, 3),
+        }
+
+
 # Global database manager instance
 _db_manager: Optional[DatabaseManager] = None
 
 
 def get_database() -> DatabaseManager:
-    """Get or create global database manager instance."""
+    """Get or create global database manager instance (SQLite for a sqlite: DATABASE_URL)."""
     global _db_manager
     if _db_manager is None:
-        _db_manager = DatabaseManager()
+        if os.getenv('DATABASE_URL', '').startswith('sqlite:'):
+            from infrastructure.sqlite_database import SQLiteDatabaseManager
+            _db_manager = SQLiteDatabaseManager()
+        else:
+            _db_manager = DatabaseManager()
     return _db_manager
 
@@ -0,0 +1,265 @@
+"""
+Multi-Process Safe Locking for Persistent Artifacts
+
+Journals, snapshots and caches get corrupted when two bot processes point at
+the same directory. Every directory holding persistent artifacts is claimed by
+one process through an advisory lock file that also records who holds it
+(instance ID, PID, host, start time). A second process fails fast with
+ArtifactLockError naming the current holder.
+
+Individual writes are made atomic (temp file + rename under a per-file lock)
+so readers never observe a half-written snapshot.
+
+Features:
+- Process-lifetime directory claims (fcntl/msvcrt advisory locks)
+- Instance identity markers inside each lock file
+- Re-entrant within a process (several components may share a directory)
+- Atomic file writes
+- Disable with ARTIFACT_LOCKING_ENABLED=false (e.g., read-only tooling)
+"""
+
+import json
+import logging
+import os
+import socket
+import tempfile
+import threading
+import uuid
+from contextlib import contextmanager
+from dataclasses import dataclass, asdict
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import Dict, Iterator, Optional, Union
+
+try:
+    import fcntl  # POSIX
+except ImportError:  # pragma: no cover - Windows
+    fcntl = None
+    import msvcrt
+
+logger = logging.getLogger(__name__)
+
+LOCK_FILE_NAME = ".bot_instance.lock"
+
+
+class ArtifactLockError(RuntimeError):
+    """Raised when persistent artifacts are already owned by another process."""
+
+    def __init__(self, path: Path, holder: Optional[Dict] = None):
+        self.path = path
+        self.holder = holder or {}
+        if holder:
+            detail = (f"held by instance {holder.get('instance_id', '?')} "
+                      f"(pid {holder.get('pid', '?')} on {holder.get('hostname', '?')}, "
+                      f"started {holder.get('started_at', '?')})")
+        else:
+            detail = "held by another process"
+        super().__init__(
+            f"Artifact directory {path} is {detail}. Another bot instance is using the same "
+            f"journal/snapshot directory - stop it or point this instance at a different directory."
+        )
+
+
+@dataclass(frozen=True)
+class InstanceIdentity:
+    """Identity of this bot process, written into every lock file."""
+    instance_id: str
+    pid: int
+    hostname: str
+    started_at: str
+
+    def to_dict(self) -> Dict:
+        """Convert to dictionary."""
+        return asdict(self)
+
+
+_instance: Optional[InstanceIdentity] = None
+
+
+def get_instance_identity() -> InstanceIdentity:
+    """Get this process's identity (BOT_INSTANCE_ID overrides the random ID)."""
+    global _instance
+    if _instance is None or _instance.pid != os.getpid():
+        _instance = InstanceIdentity(
+            instance_id=os.getenv('BOT_INSTANCE_ID') or uuid.uuid4().hex[:12],
+            pid=os.getpid(),
+            hostname=socket.gethostname(),
+            started_at=datetime.now(timezone.utc).isoformat(),
+        )
+    return _instance
+
+
+def _try_lock(fd: int) -> bool:
+    """Non-blocking exclusive lock on an open file descriptor."""
+    try:
+        if fcntl is not None:
+            fcntl.flock(fd, fcntl.LOCK_EX | fcntl.LOCK_NB)
+        else:  # pragma: no cover - Windows
+            msvcrt.locking(fd, msvcrt.LK_NBLCK, 1)
+        return True
+    except OSError:
+        return False
+
+
+def _unlock(fd: int) -> None:
+    try:
+        if fcntl is not None:
+            fcntl.flock(fd, fcntl.LOCK_UN)
+        else:  # pragma: no cover - Windows
+            os.lseek(fd, 0, os.SEEK_SET)
+            msvcrt.locking(fd, msvcrt.LK_UNLCK, 1)
+    except OSError:
+        pass
+
+
+def read_lock_holder(directory: Union[str, Path]) -> Optional[Dict]:
+    """Read the identity marker from a directory's lock file (None if absent/unreadable)."""
+    try:
+        with open(Path(directory) / LOCK_FILE_NAME, 'r') as f:
+            return json.load(f)
+    except (OSError, ValueError):
+        return None
+
+
+class DirectoryLock:
+    """
+    Process-lifetime claim on a directory of persistent artifacts.
+
+    Usage:
+        lock = DirectoryLock(".cache")
+        lock.acquire()   # raises ArtifactLockError if another process holds it
+        ...
+        lock.release()
+    """
+
+    def __init__(self, directory: Union[str, Path]):
+        self.directory = Path(directory).resolve()
+        self.lock_path = self.directory / LOCK_FILE_NAME
+        self._fd: Optional[int] = None
+
+    @property
+    def held(self) -> bool:
+        return self._fd is not None
+
+    def acquire(self) -> 'DirectoryLock':
+        """Claim the directory and write this instance's identity marker."""
+        if self._fd is not None:
+            return self
+        self.directory.mkdir(parents=True, exist_ok=True)
+        fd = os.open(self.lock_path, os.O_RDWR | os.O_CREAT, 0o644)
+        if not _try_lock(fd):
+            os.close(fd)
+            raise ArtifactLockError(self.directory, read_lock_holder(self.directory))
+
+        marker = json.dumps(get_instance_identity().to_dict()).encode('utf-8')
+        os.ftruncate(fd, 0)
+        os.lseek(fd, 0, os.SEEK_SET)
+        os.write(fd, marker)
+        os.fsync(fd)
+        self._fd = fd
+        logger.debug(f"🔒 Claimed artifact directory {self.directory}")
+        return self
+
+    def release(self) -> None:
+        """Release the claim (the lock file is left in place, only unlocked)."""
+        if self._fd is None:
+            return
+        try:
+            os.ftruncate(self._fd, 0)
+        except OSError:
+            pass
+        _unlock(self._fd)
+        os.close(self._fd)
+        self._fd = None
+        logger.debug(f"🔓 Released artifact directory {self.directory}")
+
+    def __enter__(self) -> 'DirectoryLock':
+        return self.acquire()
+
+    def __exit__(self, exc_type, exc, tb) -> None:
+        self.release()
+
+
+# Directory claims held by this process: {resolved_path: DirectoryLock}
+_directory_locks: Dict[Path, DirectoryLock] = {}
+_registry_lock = threading.Lock()
+
+
+def locking_enabled() -> bool:
+    return os.getenv('ARTIFACT_LOCKING_ENABLED', 'true').lower() in ('true', '1', 'yes', 'on')
+
+
+def acquire_artifact_lock(directory: Union[str, Path]) -> Optional[DirectoryLock]:
+    """
+    Claim a directory of persistent artifacts for this process (re-entrant).
+
+    Args:
+        directory: Directory holding journals/snapshots/caches
+
+    Returns:
+        DirectoryLock, or None when locking is disabled
+
+    Raises:
+        ArtifactLockError: If another process already holds the directory
+    """
+    if not locking_enabled():
+        return None
+    path = Path(directory).resolve()
+    with _registry_lock:
+        lock = _directory_locks.get(path)
+        if lock is None or not lock.held:
+            lock = DirectoryLock(path).acquire()
+            _directory_locks[path] = lock
+            logger.info(f"🔒 Artifact directory {path} claimed by instance {get_instance_identity().instance_id}")
+        return lock
+
+
+def release_artifact_locks() -> None:
+    """Release every directory claim held by this process."""
+    with _registry_lock:
+        for lock in _directory_locks.values():
+            lock.release()
+        _directory_locks.clear()
+
+
+@contextmanager
+def exclusive_file_lock(path: Path) -> Iterator[None]:
+    """Short-lived exclusive lock for a single file update (blocking, uses a sibling .lock file)."""
+    lock_path = path.with_name(path.name + '.lock')
+    fd = os.open(lock_path, os.O_RDWR | os.O_CREAT, 0o644)
+    try:
+        if fcntl is not None:
+            fcntl.flock(fd, fcntl.LOCK_EX)
+        else:  # pragma: no cover - Windows
+            msvcrt.locking(fd, msvcrt.LK_LOCK, 1)
+        yield
+    finally:
+        _unlock(fd)
+        os.close(fd)
+
+
+def atomic_write(path: Union[str, Path], data: Union[str, bytes]) -> None:
+    """
+    Write a file atomically: temp file in the same directory, fsync, rename.
+
+    Args:
+        path: Destination file
+        data: Text or bytes content
+    """
+    path = Path(path)
+    path.parent.mkdir(parents=True, exist_ok=True)
+    mode = 'wb' if isinstance(data, bytes) else 'w'
+    with exclusive_file_lock(path):
+        fd, tmp_name = tempfile.mkstemp(dir=path.parent, prefix=f".{path.name}.", suffix='.tmp')
+        try:
+            with os.fdopen(fd, mode) as f:
+                f.write(data)
+                f.flush()
+                os.fsync(f.fileno())
+            os.replace(tmp_name, path)
+        except BaseException:
+            try:
+                os.unlink(tmp_name)
+            except OSError:
+                pass
+            raise
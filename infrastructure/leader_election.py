@@ -0,0 +1,411 @@
+"""
+Leader Election for Warm Standby Failover
+
+Two bot instances can run as an active/standby pair. Both keep market data
+and state warm, but only the holder of a time-limited leadership lease may
+submit orders. The standby keeps trying to acquire the lease; when the
+leader stops renewing (crash, network loss, planned handover) the standby
+wins it and runs a takeover procedure (reconcile positions and open orders
+with the broker) before enabling order flow.
+
+Lease backends:
+- FileLeaseBackend: lease file on a shared filesystem (same host or NFS)
+- PostgresLeaseBackend: row in the leader_lease table (instances on different hosts)
+
+Safety:
+- Each acquisition bumps an epoch (fencing token) so a stale leader can be detected
+- A leader whose lease expires locally (e.g., event loop stalled) stops order
+  flow immediately, even before the next renewal attempt fails
+- Takeover failure releases the lease so the other instance can retry
+
+Configuration:
+- FAILOVER_ENABLED: Enable leader election (default false - single instance, always leader)
+- LEADER_LEASE_BACKEND: 'file' or 'postgres' (default file)
+- LEADER_LEASE_PATH: Lease file for the file backend
+- LEADER_LEASE_NAME: Lease name (default 'trading-bot'), one per account/pair
+- LEADER_LEASE_TTL: Lease duration in seconds (default 15)
+- LEADER_RENEW_INTERVAL: Seconds between acquire/renew attempts (default 5)
+"""
+
+import asyncio
+import inspect
+import json
+import logging
+import os
+import tempfile
+from dataclasses import dataclass, asdict
+from datetime import datetime, timedelta, timezone
+from enum import Enum
+from pathlib import Path
+from typing import Any, Awaitable, Callable, Dict, Optional, Union
+
+from infrastructure.file_lock import exclusive_file_lock, get_instance_identity
+
+logger = logging.getLogger(__name__)
+
+
+class LeaderRole(Enum):
+    """Role of this instance in the failover pair."""
+    STANDBY = "standby"
+    TAKING_OVER = "taking_over"
+    LEADER = "leader"
+
+
+@dataclass
+class Lease:
+    """Leadership lease as stored by a backend."""
+    name: str
+    holder: str
+    epoch: int
+    expires_at: datetime
+
+    def is_expired(self, now: Optional[datetime] = None) -> bool:
+        return (now or datetime.now(timezone.utc)) >= self.expires_at
+
+    def to_dict(self) -> Dict[str, Any]:
+        data = asdict(self)
+        data['expires_at'] = self.expires_at.isoformat()
+        return data
+
+
+class LeaseBackend:
+    """Storage for the leadership lease. Implementations must make try_acquire atomic."""
+
+    def try_acquire(self, name: str, holder: str, ttl: float) -> Optional[Lease]:
+        """
+        Acquire or renew the lease for holder.
+
+        Succeeds if the lease is free, expired, or already held by holder.
+        The epoch increments whenever the holder changes.
+
+        Returns:
+            Lease if holder now owns it, otherwise None
+        """
+        raise NotImplementedError
+
+    def release(self, name: str, holder: str) -> bool:
+        """Release the lease if held by holder (expires it immediately)."""
+        raise NotImplementedError
+
+    def get(self, name: str) -> Optional[Lease]:
+        """Current lease (may be expired), or None if never acquired."""
+        raise NotImplementedError
+
+
+class FileLeaseBackend(LeaseBackend):
+    """Lease stored as JSON in a file, updated under an exclusive file lock."""
+
+    def __init__(self, path: Union[str, Path, None] = None):
+        self.path = Path(path or os.getenv('LEADER_LEASE_PATH')
+                         or Path(tempfile.gettempdir()) / 'tradebot-leader.lease')
+
+    def _read(self) -> Dict[str, Dict]:
+        try:
+            return json.loads(self.path.read_text())
+        except (OSError, ValueError):
+            return {}
+
+    @staticmethod
+    def _parse(name: str, data: Optional[Dict]) -> Optional[Lease]:
+        if not data:
+            return None
+        return Lease(name, data['holder'], int(data['epoch']), datetime.fromisoformat(data['expires_at']))
+
+    def try_acquire(self, name: str, holder: str, ttl: float) -> Optional[Lease]:
+        self.path.parent.mkdir(parents=True, exist_ok=True)
+        with exclusive_file_lock(self.path):
+            leases = self._read()
+            now = datetime.now(timezone.utc)
+            current = self._parse(name, leases.get(name))
+            if current is not None and current.holder != holder and not current.is_expired(now):
+                return None
+            epoch = current.epoch if current is not None and current.holder == holder else (
+                current.epoch + 1 if current is not None else 1)
+            lease = Lease(name, holder, epoch, now + timedelta(seconds=ttl))
+            leases[name] = {k: v for k, v in lease.to_dict().items() if k != 'name'}
+            self._write(leases)
+            return lease
+
+    def _write(self, leases: Dict[str, Dict]) -> None:
+        # Already inside exclusive_file_lock(self.path); write through a temp file and rename
+        tmp = self.path.with_name(f".{self.path.name}.tmp")
+        tmp.write_text(json.dumps(leases))
+        os.replace(tmp, self.path)
+
+    def release(self, name: str, holder: str) -> bool:
+        if not self.path.exists():
+            return False
+        with exclusive_file_lock(self.path):
+            leases = self._read()
+            current = self._parse(name, leases.get(name))
+            if current is None or current.holder != holder:
+                return False
+            leases[name]['expires_at'] = datetime.now(timezone.utc).isoformat()
+            self._write(leases)
+            return True
+
+    def get(self, name: str) -> Optional[Lease]:
+        return self._parse(name, self._read().get(name))
+
+
+class PostgresLeaseBackend(LeaseBackend):
+    """Lease stored in the leader_lease table; acquisition is a single conditional upsert."""
+
+    def __init__(self, db):
+        """
+        Args:
+            db: DatabaseManager (uses get_connection)
+        """
+        self.db = db
+        with self.db.get_connection() as conn:
+            with conn.cursor() as cur:
+                cur.execute("""
+                    CREATE TABLE IF NOT EXISTS leader_lease (
+                        name VARCHAR(100) PRIMARY KEY,
+                        holder VARCHAR(200) NOT NULL,
+                        epoch BIGINT NOT NULL DEFAULT 1,
+                        expires_at TIMESTAMPTZ NOT NULL,
+                        updated_at TIMESTAMPTZ DEFAULT NOW()
+                    )
+                """)
+
+    def try_acquire(self, name: str, holder: str, ttl: float) -> Optional[Lease]:
+        with self.db.get_connection() as conn:
+            with conn.cursor() as cur:
+                cur.execute("""
+                    INSERT INTO leader_lease (name, holder, epoch, expires_at, updated_at)
+                    VALUES (%s, %s, 1, NOW() + make_interval(secs => %s), NOW())
+                    ON CONFLICT (name) DO UPDATE SET
+                        epoch = CASE WHEN leader_lease.holder = EXCLUDED.holder
+                                     THEN leader_lease.epoch ELSE leader_lease.epoch + 1 END,
+                        holder = EXCLUDED.holder,
+                        expires_at = EXCLUDED.expires_at,
+                        updated_at = NOW()
+                    WHERE leader_lease.holder = EXCLUDED.holder OR leader_lease.expires_at < NOW()
+                    RETURNING holder, epoch, expires_at
+                """, (name, holder, float(ttl)))
+                row = cur.fetchone()
+        if not row:
+            return None
+        return Lease(name, row[0], int(row[1]), row[2])
+
+    def release(self, name: str, holder: str) -> bool:
+        with self.db.get_connection() as conn:
+            with conn.cursor() as cur:
+                cur.execute("UPDATE leader_lease SET expires_at = NOW(), updated_at = NOW() "
+                            "WHERE name = %s AND holder = %s", (name, holder))
+                return cur.rowcount > 0
+
+    def get(self, name: str) -> Optional[Lease]:
+        with self.db.get_connection() as conn:
+            with conn.cursor() as cur:
+                cur.execute("SELECT holder, epoch, expires_at FROM leader_lease WHERE name = %s", (name,))
+                row = cur.fetchone()
+        return Lease(name, row[0], int(row[1]), row[2]) if row else None
+
+
+TakeoverCallback = Callable[[Lease], Union[bool, Awaitable[bool]]]
+
+
+class LeaderElector:
+    """
+    Acquires and renews the leadership lease, running a takeover procedure
+    before this instance is allowed to submit orders.
+
+    Usage:
+        elector = LeaderElector(FileLeaseBackend(), takeover=bot.prepare_takeover)
+        await elector.start()
+        if elector.is_leader: ...   # order flow enabled
+        await elector.step_down()   # controlled handover to the standby
+    """
+
+    def __init__(self, backend: LeaseBackend, name: Optional[str] = None, holder: Optional[str] = None,
+                 ttl: Optional[float] = None, renew_interval: Optional[float] = None,
+                 takeover: Optional[TakeoverCallback] = None,
+                 on_role_change: Optional[Callable[[LeaderRole, LeaderRole], Any]] = None):
+        """
+        Initialize elector.
+
+        Args:
+            backend: Lease storage
+            name: Lease name (env: LEADER_LEASE_NAME, default 'trading-bot')
+            holder: This instance's ID (default: artifact-lock instance ID + host)
+            ttl: Lease duration in seconds (env: LEADER_LEASE_TTL, default 15)
+            renew_interval: Seconds between attempts (env: LEADER_RENEW_INTERVAL, default 5)
+            takeover: Reconciliation run after winning the lease; returns True when safe to trade
+            on_role_change: Called with (old_role, new_role)
+        """
+        identity = get_instance_identity()
+        self.backend = backend
+        self.name = name or os.getenv('LEADER_LEASE_NAME', 'trading-bot')
+        self.holder = holder or f"{identity.instance_id}@{identity.hostname}"
+        self.ttl = ttl if ttl is not None else float(os.getenv('LEADER_LEASE_TTL', '15'))
+        self.renew_interval = (renew_interval if renew_interval is not None
+                               else float(os.getenv('LEADER_RENEW_INTERVAL', '5')))
+        if self.renew_interval >= self.ttl:
+            raise ValueError(f"Renew interval ({self.renew_interval}s) must be shorter than lease TTL ({self.ttl}s)")
+        self.takeover = takeover
+        self.on_role_change = on_role_change
+        self.role = LeaderRole.STANDBY
+        self.lease: Optional[Lease] = None
+        self.takeovers = 0
+        self.failed_takeovers = 0
+        self.last_error: Optional[str] = None
+        self._hold_off_until: Optional[datetime] = None
+        self._task: Optional[asyncio.Task] = None
+        self._running = False
+
+    @property
+    def is_leader(self) -> bool:
+        """True only while leader with an unexpired lease (checked locally on every call)."""
+        return self.role == LeaderRole.LEADER and self.lease is not None and not self.lease.is_expired()
+
+    def _set_role(self, role: LeaderRole) -> None:
+        old, self.role = self.role, role
+        if old == role:
+            return
+        logger.warning(f"👑 Leadership role {old.value} → {role.value} ({self.holder}, lease '{self.name}')")
+        if self.on_role_change:
+            try:
+                self.on_role_change(old, role)
+            except Exception as e:
+                logger.error(f"❌ Leader role change callback failed: {e}")
+
+    async def tick(self) -> LeaderRole:
+        """Run one acquire/renew cycle."""
+        now = datetime.now(timezone.utc)
+        if self._hold_off_until and now < self._hold_off_until and self.role == LeaderRole.STANDBY:
+            return self.role
+        try:
+            lease = await asyncio.to_thread(self.backend.try_acquire, self.name, self.holder, self.ttl)
+            self.last_error = None
+        except Exception as e:
+            self.last_error = str(e)
+            logger.error(f"❌ Leader lease renewal failed: {e}")
+            lease = None
+            if self.lease is not None and not self.lease.is_expired():
+                return self.role  # Keep the current role until the lease we hold runs out
+
+        if lease is None:
+            if self.role != LeaderRole.STANDBY:
+                logger.error(f"🚨 Lost leadership lease '{self.name}' - order flow disabled")
+            self.lease = None
+            self._set_role(LeaderRole.STANDBY)
+            return self.role
+
+        if self.lease is not None and lease.epoch != self.lease.epoch and self.role == LeaderRole.LEADER:
+            # Someone else held the lease in between: our view of open state is stale
+            logger.warning(f"⚠️  Lease epoch jumped {self.lease.epoch} → {lease.epoch}; re-running takeover")
+            self._set_role(LeaderRole.STANDBY)
+        self.lease = lease
+        if self.role == LeaderRole.STANDBY:
+            await self._take_over(lease)
+        return self.role
+
+    async def _take_over(self, lease: Lease) -> None:
+        """Reconcile before enabling order flow; release the lease if reconciliation fails."""
+        self._set_role(LeaderRole.TAKING_OVER)
+        logger.warning(f"🔁 Acquired leadership lease '{self.name}' (epoch {lease.epoch}) - reconciling before trading")
+        ok = True
+        if self.takeover:
+            try:
+                result = self.takeover(lease)
+                ok = bool(await result) if inspect.isawaitable(result) else bool(result)
+            except Exception as e:
+                logger.error(f"❌ Takeover procedure failed: {e}")
+                ok = False
+        if ok and self.lease is not None and not self.lease.is_expired():
+            self.takeovers += 1
+            self._set_role(LeaderRole.LEADER)
+            logger.warning(f"✅ Takeover complete - order flow enabled on {self.holder}")
+            return
+        self.failed_takeovers += 1
+        logger.error("❌ Takeover not completed - releasing lease and staying on standby")
+        await self._release()
+        # Give the other instance a chance to take over before retrying
+        self._hold_off_until = datetime.now(timezone.utc) + timedelta(seconds=self.ttl)
+        self._set_role(LeaderRole.STANDBY)
+
+    async def _release(self) -> None:
+        try:
+            await asyncio.to_thread(self.backend.release, self.name, self.holder)
+        except Exception as e:
+            logger.error(f"❌ Failed to release leader lease: {e}")
+        self.lease = None
+
+    async def start(self) -> None:
+        """Start the background acquire/renew loop."""
+        if self._running:
+            logger.warning("⚠️  Leader elector already running")
+            return
+        self._running = True
+        self._task = asyncio.create_task(self._loop())
+        logger.info(f"✅ Leader election started for '{self.name}' as {self.holder} "
+                    f"(ttl {self.ttl}s, renew every {self.renew_interval}s)")
+
+    async def _loop(self) -> None:
+        while self._running:
+            try:
+                await self.tick()
+                await asyncio.sleep(self.renew_interval)
+            except asyncio.CancelledError:
+                break
+            except Exception as e:
+                logger.error(f"Error in leader election loop: {e}")
+                await asyncio.sleep(self.renew_interval)
+
+    async def step_down(self, hold_off: Optional[float] = None) -> None:
+        """
+        Controlled handover: stop trading, release the lease and wait before competing again.
+
+        Args:
+            hold_off: Seconds to stay on standby (default: one TTL)
+        """
+        self._set_role(LeaderRole.STANDBY)
+        await self._release()
+        self._hold_off_until = datetime.now(timezone.utc) + timedelta(seconds=hold_off if hold_off is not None else self.ttl)
+        logger.warning(f"👋 Stepped down from leadership '{self.name}'")
+
+    async def stop(self, release: bool = True) -> None:
+        """Stop the loop and (by default) release the lease so the standby takes over quickly."""
+        self._running = False
+        if self._task:
+            self._task.cancel()
+            try:
+                await self._task
+            except asyncio.CancelledError:
+                pass
+            self._task = None
+        if release and self.lease is not None:
+            await self._release()
+        self._set_role(LeaderRole.STANDBY)
+        logger.info("Leader election stopped")
+
+    def get_status(self) -> Dict[str, Any]:
+        """Status for health/metrics endpoints."""
+        return {
+            "name": self.name,
+            "holder": self.holder,
+            "role": self.role.value,
+            "is_leader": self.is_leader,
+            "lease": self.lease.to_dict() if self.lease else None,
+            "takeovers": self.takeovers,
+            "failed_takeovers": self.failed_takeovers,
+            "last_error": self.last_error,
+        }
+
+
+def failover_enabled() -> bool:
+    return os.getenv('FAILOVER_ENABLED', 'false').lower() in ('true', '1', 'yes', 'on')
+
+
+def create_lease_backend(db=None) -> LeaseBackend:
+    """Build the lease backend selected by LEADER_LEASE_BACKEND."""
+    backend = os.getenv('LEADER_LEASE_BACKEND', 'file').lower()
+    if backend == 'postgres':
+        if db is None:
+            raise ValueError("LEADER_LEASE_BACKEND=postgres requires a database connection")
+        return PostgresLeaseBackend(db)
+    if backend != 'file':
+        raise ValueError(f"Unknown LEADER_LEASE_BACKEND '{backend}'. Use 'file' or 'postgres'")
+    return FileLeaseBackend()
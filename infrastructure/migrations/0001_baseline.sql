@@ -0,0 +1,190 @@
+-- Baseline schema: market data cache, account/strategy state, metrics, trade history, notifications.
+-- IF NOT EXISTS throughout so databases created before migrations adopt it unchanged.
+
+-- Historical market data (OHLCV bars)
+CREATE TABLE IF NOT EXISTS historical_bars (
+    id SERIAL PRIMARY KEY,
+    symbol VARCHAR(20) NOT NULL,
+    timeframe VARCHAR(10) NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    open DECIMAL(12, 4),
+    high DECIMAL(12, 4),
+    low DECIMAL(12, 4),
+    close DECIMAL(12, 4),
+    volume BIGINT,
+    metadata JSONB,  -- Store additional data (bid/ask, etc.)
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE(symbol, timeframe, timestamp)
+);
+
+-- Indexes for fast lookups
+CREATE INDEX IF NOT EXISTS idx_bars_lookup
+    ON historical_bars(symbol, timeframe, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_bars_symbol_timeframe
+    ON historical_bars(symbol, timeframe);
+CREATE INDEX IF NOT EXISTS idx_bars_created
+    ON historical_bars(created_at);
+
+-- Account state tracking
+CREATE TABLE IF NOT EXISTS account_state (
+    account_id VARCHAR(50) PRIMARY KEY,
+    account_name VARCHAR(100),
+    balance DECIMAL(12, 2),
+    starting_balance DECIMAL(12, 2),
+    daily_pnl DECIMAL(12, 2),
+    dll_remaining DECIMAL(12, 2),
+    mll_remaining DECIMAL(12, 2),
+    total_trades_today INT DEFAULT 0,
+    winning_trades_today INT DEFAULT 0,
+    losing_trades_today INT DEFAULT 0,
+    metadata JSONB,  -- Additional account data
+    last_updated TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- Strategy performance metrics
+CREATE TABLE IF NOT EXISTS strategy_performance (
+    id SERIAL PRIMARY KEY,
+    strategy_name VARCHAR(50) NOT NULL,
+    symbol VARCHAR(20),
+    timestamp TIMESTAMPTZ DEFAULT NOW(),
+    total_trades INT DEFAULT 0,
+    winning_trades INT DEFAULT 0,
+    losing_trades INT DEFAULT 0,
+    total_pnl DECIMAL(12, 2) DEFAULT 0,
+    win_rate DECIMAL(5, 2),
+    profit_factor DECIMAL(8, 2),
+    max_drawdown DECIMAL(12, 2),
+    sharpe_ratio DECIMAL(8, 2),
+    avg_win DECIMAL(12, 2),
+    avg_loss DECIMAL(12, 2),
+    best_trade DECIMAL(12, 2),
+    worst_trade DECIMAL(12, 2),
+    metadata JSONB  -- Additional metrics
+);
+
+CREATE INDEX IF NOT EXISTS idx_strategy_name
+    ON strategy_performance(strategy_name, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_strategy_symbol
+    ON strategy_performance(symbol, timestamp DESC);
+
+-- API performance metrics
+CREATE TABLE IF NOT EXISTS api_metrics (
+    id SERIAL PRIMARY KEY,
+    endpoint VARCHAR(200) NOT NULL,
+    method VARCHAR(10) NOT NULL,
+    duration_ms DECIMAL(10, 2),
+    status_code INT,
+    success BOOLEAN,
+    error_message TEXT,
+    timestamp TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_endpoint
+    ON api_metrics(endpoint, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_api_timestamp
+    ON api_metrics(timestamp DESC);
+
+-- Trade history (for detailed tracking)
+CREATE TABLE IF NOT EXISTS trade_history (
+    id SERIAL PRIMARY KEY,
+    account_id VARCHAR(50),
+    strategy_name VARCHAR(50),
+    symbol VARCHAR(20),
+    side VARCHAR(10),  -- BUY or SELL
+    quantity INT,
+    entry_price DECIMAL(12, 4),
+    exit_price DECIMAL(12, 4),
+    pnl DECIMAL(12, 2),
+    entry_time TIMESTAMPTZ,
+    exit_time TIMESTAMPTZ,
+    duration_seconds INT,
+    metadata JSONB,  -- Order IDs, stop/target prices, etc.
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- Order history cache (raw API responses for faster dashboard loads)
+CREATE TABLE IF NOT EXISTS order_history_cache (
+    id SERIAL PRIMARY KEY,
+    account_id VARCHAR(50) NOT NULL,
+    order_data JSONB NOT NULL,
+    order_timestamp TIMESTAMPTZ NOT NULL,
+    cached_at TIMESTAMPTZ DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_order_cache_account_time
+    ON order_history_cache(account_id, order_timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_order_cache_cached_at
+    ON order_history_cache(cached_at);
+-- Composite index for fast lookups
+CREATE INDEX IF NOT EXISTS idx_order_cache_lookup
+    ON order_history_cache(account_id, order_timestamp DESC, cached_at);
+
+CREATE INDEX IF NOT EXISTS idx_trades_account
+    ON trade_history(account_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_trades_strategy
+    ON trade_history(strategy_name, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_trades_symbol
+    ON trade_history(symbol, created_at DESC);
+
+-- Cache metadata (track what's cached and when)
+CREATE TABLE IF NOT EXISTS cache_metadata (
+    cache_key VARCHAR(200) PRIMARY KEY,
+    cache_type VARCHAR(50),  -- 'historical_bars', 'account', etc.
+    last_updated TIMESTAMPTZ DEFAULT NOW(),
+    expires_at TIMESTAMPTZ,
+    hit_count INT DEFAULT 0,
+    metadata JSONB
+);
+
+CREATE INDEX IF NOT EXISTS idx_cache_type
+    ON cache_metadata(cache_type, last_updated DESC);
+
+-- Strategy state persistence
+CREATE TABLE IF NOT EXISTS strategy_states (
+    account_id VARCHAR(50) NOT NULL,
+    strategy_name VARCHAR(50) NOT NULL,
+    enabled BOOLEAN DEFAULT FALSE,
+    symbols TEXT[] DEFAULT ARRAY[]::TEXT[],
+    settings JSONB,
+    metadata JSONB,
+    last_started TIMESTAMPTZ,
+    last_stopped TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    PRIMARY KEY (account_id, strategy_name)
+);
+CREATE INDEX IF NOT EXISTS idx_strategy_states_account
+    ON strategy_states(account_id);
+
+-- Dashboard/UI settings persistence
+CREATE TABLE IF NOT EXISTS dashboard_settings (
+    account_id VARCHAR(50) PRIMARY KEY,
+    settings JSONB NOT NULL,
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- Per-symbol trading switches (disabled symbols keep data, block new orders)
+CREATE TABLE IF NOT EXISTS symbol_trading_switches (
+    symbol VARCHAR(20) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    reason TEXT,
+    updated_by VARCHAR(100),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- Notifications table for server-side notification tracking
+CREATE TABLE IF NOT EXISTS notifications (
+    id SERIAL PRIMARY KEY,
+    account_id VARCHAR(50) NOT NULL,
+    notification_type VARCHAR(50) NOT NULL,
+    message TEXT NOT NULL,
+    level VARCHAR(20) DEFAULT 'info',  -- 'info', 'success', 'warning', 'error'
+    meta JSONB,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_account
+    ON notifications(account_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_notifications_type
+    ON notifications(notification_type, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_notifications_level
+    ON notifications(level, created_at DESC);
@@ -0,0 +1,82 @@
+-- Trade journal (DatabaseManager.record_* / trades_for_day / pnl_by_strategy)
+
+-- Every signal, order, fill and position snapshot, attributed to a strategy
+CREATE TABLE IF NOT EXISTS journal_signals (
+    id BIGSERIAL PRIMARY KEY,
+    ts TIMESTAMPTZ NOT NULL,
+    account_id VARCHAR(50),
+    strategy_name VARCHAR(50) NOT NULL,
+    symbol VARCHAR(20) NOT NULL,
+    direction VARCHAR(10) NOT NULL,  -- long, short, flat
+    price DECIMAL(12, 4),
+    stop_price DECIMAL(12, 4),
+    target_price DECIMAL(12, 4),
+    confidence DECIMAL(5, 4),
+    reason TEXT,
+    metadata JSONB
+);
+CREATE INDEX IF NOT EXISTS idx_journal_signals_ts
+    ON journal_signals(ts DESC);
+CREATE INDEX IF NOT EXISTS idx_journal_signals_strategy
+    ON journal_signals(strategy_name, ts DESC);
+
+CREATE TABLE IF NOT EXISTS journal_orders (
+    id BIGSERIAL PRIMARY KEY,
+    ts TIMESTAMPTZ NOT NULL,
+    account_id VARCHAR(50) NOT NULL,
+    strategy_name VARCHAR(50),
+    order_id VARCHAR(50),
+    signal_id BIGINT,  -- journal_signals.id of the signal behind the order, if known
+    symbol VARCHAR(20) NOT NULL,
+    side VARCHAR(10) NOT NULL,  -- BUY or SELL
+    quantity INT NOT NULL,
+    order_type VARCHAR(20),
+    price DECIMAL(12, 4),  -- Limit/stop price
+    status VARCHAR(20),
+    metadata JSONB
+);
+CREATE INDEX IF NOT EXISTS idx_journal_orders_account
+    ON journal_orders(account_id, ts DESC);
+CREATE INDEX IF NOT EXISTS idx_journal_orders_strategy
+    ON journal_orders(strategy_name, ts DESC);
+CREATE INDEX IF NOT EXISTS idx_journal_orders_order
+    ON journal_orders(order_id);
+
+CREATE TABLE IF NOT EXISTS journal_fills (
+    id BIGSERIAL PRIMARY KEY,
+    ts TIMESTAMPTZ NOT NULL,
+    account_id VARCHAR(50) NOT NULL,
+    strategy_name VARCHAR(50),
+    order_id VARCHAR(50),
+    fill_id VARCHAR(100),  -- Broker fill/order id; a repeated one is ignored
+    symbol VARCHAR(20) NOT NULL,
+    side VARCHAR(10) NOT NULL,
+    quantity INT NOT NULL,
+    price DECIMAL(12, 4) NOT NULL,
+    commission DECIMAL(10, 2) DEFAULT 0,
+    realized_pnl DECIMAL(12, 2),
+    metadata JSONB,
+    UNIQUE (account_id, fill_id)
+);
+CREATE INDEX IF NOT EXISTS idx_journal_fills_account
+    ON journal_fills(account_id, ts DESC);
+CREATE INDEX IF NOT EXISTS idx_journal_fills_strategy
+    ON journal_fills(strategy_name, ts DESC);
+
+CREATE TABLE IF NOT EXISTS journal_positions (
+    id BIGSERIAL PRIMARY KEY,
+    ts TIMESTAMPTZ NOT NULL,
+    account_id VARCHAR(50) NOT NULL,
+    strategy_name VARCHAR(50),  -- NULL for the account's net position
+    symbol VARCHAR(20) NOT NULL,
+    quantity INT NOT NULL,  -- Signed: long > 0, short < 0
+    avg_price DECIMAL(12, 4),
+    unrealized_pnl DECIMAL(12, 2),
+    realized_pnl DECIMAL(12, 2),
+    metadata JSONB
+);
+CREATE INDEX IF NOT EXISTS idx_journal_positions_account
+    ON journal_positions(account_id, ts DESC);
+
+CREATE INDEX IF NOT EXISTS idx_trades_exit_time
+    ON trade_history(exit_time DESC);
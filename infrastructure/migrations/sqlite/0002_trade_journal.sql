@@ -0,0 +1,82 @@
+-- Trade journal (SQLite twin of ../0002_trade_journal.sql)
+
+-- Every signal, order, fill and position snapshot, attributed to a strategy
+CREATE TABLE IF NOT EXISTS journal_signals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts TIMESTAMPTZ NOT NULL,
+    account_id VARCHAR(50),
+    strategy_name VARCHAR(50) NOT NULL,
+    symbol VARCHAR(20) NOT NULL,
+    direction VARCHAR(10) NOT NULL,  -- long, short, flat
+    price REAL,
+    stop_price REAL,
+    target_price REAL,
+    confidence REAL,
+    reason TEXT,
+    metadata JSONB
+);
+CREATE INDEX IF NOT EXISTS idx_journal_signals_ts
+    ON journal_signals(ts DESC);
+CREATE INDEX IF NOT EXISTS idx_journal_signals_strategy
+    ON journal_signals(strategy_name, ts DESC);
+
+CREATE TABLE IF NOT EXISTS journal_orders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts TIMESTAMPTZ NOT NULL,
+    account_id VARCHAR(50) NOT NULL,
+    strategy_name VARCHAR(50),
+    order_id VARCHAR(50),
+    signal_id BIGINT,  -- journal_signals.id of the signal behind the order, if known
+    symbol VARCHAR(20) NOT NULL,
+    side VARCHAR(10) NOT NULL,  -- BUY or SELL
+    quantity INT NOT NULL,
+    order_type VARCHAR(20),
+    price REAL,  -- Limit/stop price
+    status VARCHAR(20),
+    metadata JSONB
+);
+CREATE INDEX IF NOT EXISTS idx_journal_orders_account
+    ON journal_orders(account_id, ts DESC);
+CREATE INDEX IF NOT EXISTS idx_journal_orders_strategy
+    ON journal_orders(strategy_name, ts DESC);
+CREATE INDEX IF NOT EXISTS idx_journal_orders_order
+    ON journal_orders(order_id);
+
+CREATE TABLE IF NOT EXISTS journal_fills (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts TIMESTAMPTZ NOT NULL,
+    account_id VARCHAR(50) NOT NULL,
+    strategy_name VARCHAR(50),
+    order_id VARCHAR(50),
+    fill_id VARCHAR(100),  -- Broker fill/order id; a repeated one is ignored
+    symbol VARCHAR(20) NOT NULL,
+    side VARCHAR(10) NOT NULL,
+    quantity INT NOT NULL,
+    price REAL NOT NULL,
+    commission REAL DEFAULT 0,
+    realized_pnl REAL,
+    metadata JSONB,
+    UNIQUE (account_id, fill_id)
+);
+CREATE INDEX IF NOT EXISTS idx_journal_fills_account
+    ON journal_fills(account_id, ts DESC);
+CREATE INDEX IF NOT EXISTS idx_journal_fills_strategy
+    ON journal_fills(strategy_name, ts DESC);
+
+CREATE TABLE IF NOT EXISTS journal_positions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts TIMESTAMPTZ NOT NULL,
+    account_id VARCHAR(50) NOT NULL,
+    strategy_name VARCHAR(50),  -- NULL for the account's net position
+    symbol VARCHAR(20) NOT NULL,
+    quantity INT NOT NULL,  -- Signed: long > 0, short < 0
+    avg_price REAL,
+    unrealized_pnl REAL,
+    realized_pnl REAL,
+    metadata JSONB
+);
+CREATE INDEX IF NOT EXISTS idx_journal_positions_account
+    ON journal_positions(account_id, ts DESC);
+
+CREATE INDEX IF NOT EXISTS idx_trades_exit_time
+    ON trade_history(exit_time DESC);
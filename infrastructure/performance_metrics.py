@@ -14,6 +14,8 @@ from dataclasses import dataclass, field
 from datetime import datetime
 from collections import defaultdict, deque
 
+from infrastructure.retry_budget import get_retry_budget
+
 logger = logging.getLogger(__name__)
 
 
@@ -97,6 +99,8 @@ class MetricsTracker:
     
     Tracks:
     - API call performance (timing, success rate, errors)
+    - HTTP connection pool usage (critical vs bulk)
+    - Session-wide retry budget
     - Cache hit/miss rates
     - Memory usage
     - Strategy execution times
@@ -112,6 +116,8 @@ class MetricsTracker:
         self.api_metrics: Dict[str, PerformanceStats] = defaultdict(PerformanceStats)
         self.cache_metrics: Dict[str, CacheMetrics] = defaultdict(CacheMetrics)
         self.strategy_metrics: Dict[str, PerformanceStats] = defaultdict(PerformanceStats)
+        self.http_pool_metrics: Dict[str, PerformanceStats] = defaultdict(PerformanceStats)
+        self.http_pool_in_flight: Dict[str, int] = defaultdict(int)
         self.recent_api_calls: deque = deque(maxlen=1000)  # Keep last 1000 API calls
         
         self.start_time = datetime.now()
@@ -166,6 +172,15 @@ class MetricsTracker:
             except Exception as e:
                 logger.debug(f"Failed to save API metric to database: {e}")
     
+    def record_http_pool_start(self, pool_name: str):
+        """Record a request entering an HTTP connection pool."""
+        self.http_pool_in_flight[pool_name] += 1
+    
+    def record_http_pool_call(self, pool_name: str, duration_ms: float, success: bool = True):
+        """Record a finished request on an HTTP connection pool."""
+        self.http_pool_in_flight[pool_name] = max(0, self.http_pool_in_flight[pool_name] - 1)
+        self.http_pool_metrics[pool_name].record(duration_ms, success)
+    
     def record_cache_hit(self, cache_name: str):
         """Record a cache hit."""
         self.cache_metrics[cache_name].record_hit()
@@ -245,6 +260,21 @@ class MetricsTracker:
             for strategy, stat in self.strategy_metrics.items()
         }
     
+    def get_http_pool_summary(self) -> Dict:
+        """Get summary of HTTP connection pool metrics."""
+        pools = set(self.http_pool_metrics) | set(self.http_pool_in_flight)
+        return {
+            pool: {
+                "requests": self.http_pool_metrics[pool].count,
+                "in_flight": self.http_pool_in_flight[pool],
+                "avg_ms": f"{self.http_pool_metrics[pool].avg_time_ms:.1f}",
+                "p95_ms": f"{self.http_pool_metrics[pool].p95_time_ms:.1f}",
+                "max_ms": f"{self.http_pool_metrics[pool].max_time_ms:.1f}",
+                "errors": self.http_pool_metrics[pool].errors
+            }
+            for pool in sorted(pools)
+        }
+    
     def get_system_metrics(self) -> Dict:
         """Get system resource metrics."""
         uptime = datetime.now() - self.start_time
@@ -259,6 +289,8 @@ class MetricsTracker:
         return {
             "system": self.get_system_metrics(),
             "api": self.get_api_summary(),
+            "http_pools": self.get_http_pool_summary(),
+            "retry_budget": get_retry_budget().get_stats(),
             "cache": self.get_cache_summary(),
             "strategies": self.get_strategy_summary()
         }
@@ -286,6 +318,19 @@ class MetricsTracker:
             for item in api["slowest_endpoints"]:
                 logger.info(f"    - {item['endpoint']}: {item['avg_ms']}ms avg")
         
+        # HTTP pool metrics
+        if report["http_pools"]:
+            logger.info(f"\n🔌 HTTP POOLS:")
+            for pool, metrics in report["http_pools"].items():
+                logger.info(f"  {pool}: {metrics['requests']} requests, {metrics['avg_ms']}ms avg, "
+                            f"{metrics['p95_ms']}ms p95, {metrics['in_flight']} in flight")
+        
+        # Retry budget
+        budget = report["retry_budget"]
+        logger.info(f"\n🚦 RETRY BUDGET:")
+        logger.info(f"  {budget['tokens']}/{budget['capacity']} tokens | Granted: {budget['total_granted']} | "
+                    f"Shed: {budget['total_denied']}")
+        
         # Cache metrics
         if report["cache"]:
             logger.info(f"\n💾 CACHE:")
@@ -0,0 +1,301 @@
+"""
+Redis Cache and Pub/Sub Bridge
+
+Mirrors the bot's live state into Redis so external dashboards and other
+processes can read it without touching PostgreSQL or the bot's HTTP API:
+
+- Latest quote per symbol (merged top of book), cached with a TTL
+- Net position per symbol for each account, updated on every fill
+- Fills, signals, position changes and health published on channels, with
+  the most recent events of each kind kept in a capped list for late joiners
+
+Keys and channels (prefix REDIS_KEY_PREFIX, default 'tradebot'):
+    tradebot:quote:MNQ            JSON quote, expires after REDIS_QUOTE_TTL
+    tradebot:positions:<account>  Latest positions message ({"positions": {symbol: qty}, ...})
+    tradebot:health               JSON health snapshot, expires after 3 health intervals
+    tradebot:fills | :signals | :positions | :health          pub/sub channels
+    tradebot:fills:recent | tradebot:signals:recent            newest first
+
+Every message is a JSON object with "type", "timestamp" and the event fields.
+
+The hot path only updates in-memory state; a background thread writes
+everything pending in one pipeline per flush interval. Quotes are coalesced
+(only the latest per symbol is written). While Redis is unreachable events
+stay buffered up to REDIS_BUFFER_SIZE (the oldest are dropped beyond it) and
+are written once it is back; the bot never blocks on Redis.
+
+Usage:
+    bridge = RedisBridge()
+    bridge.start()
+    bot.add_market_event_listener(bridge.on_market_event)
+    engine.bus.add_consumer('redis', bridge.publish_signal)  # strategy signals
+
+Configuration:
+- REDIS_URL: redis://[:password@]host:6379/0 (the bot enables the bridge when set)
+- REDIS_KEY_PREFIX: Prefix of all keys and channels (default 'tradebot')
+- REDIS_QUOTE_TTL: Seconds a cached quote lives without updates (default 60)
+- REDIS_FLUSH_INTERVAL: Seconds between pipeline writes (default 0.25)
+- REDIS_BUFFER_SIZE: Events kept while Redis is unreachable (default 10000)
+- REDIS_RECENT_EVENTS: Events kept per recent list, 0 disables (default 100)
+- REDIS_HEALTH_INTERVAL: Seconds between health publishes by the server (default 15)
+"""
+
+import json
+import logging
+import os
+import threading
+from collections import deque
+from datetime import datetime, timezone
+from typing import Any, Deque, Dict, Optional, Tuple
+
+from core.market_events import MarketEvent, Quote
+
+logger = logging.getLogger(__name__)
+
+# Event kinds also kept in a capped recent-events list
+RECENT_KINDS = ('fills', 'signals')
+
+
+def _now() -> str:
+    return datetime.now(timezone.utc).isoformat()
+
+
+def _encode(data: Dict[str, Any]) -> str:
+    return json.dumps(data, separators=(',', ':'), default=str)
+
+
+class RedisBridge:
+    """
+    Background writer of quotes, positions and bot events to Redis.
+
+    Usage:
+        bridge = RedisBridge('redis://localhost:6379/0')
+        bridge.start()
+        bridge.publish_fill('123', {'symbol': 'MNQ', 'side': 'BUY', 'quantity': 1, 'price': 21000.25})
+        bridge.stop()
+    """
+
+    def __init__(self, url: Optional[str] = None, prefix: Optional[str] = None, quote_ttl: Optional[int] = None,
+                 flush_interval: Optional[float] = None, buffer_size: Optional[int] = None,
+                 recent_events: Optional[int] = None, client: Any = None):
+        """
+        Initialize bridge.
+
+        Args:
+            url: Redis URL (env: REDIS_URL)
+            prefix: Key and channel prefix (env: REDIS_KEY_PREFIX)
+            quote_ttl: Cached quote lifetime in seconds (env: REDIS_QUOTE_TTL)
+            flush_interval: Seconds between pipeline writes (env: REDIS_FLUSH_INTERVAL)
+            buffer_size: Events buffered while Redis is unreachable (env: REDIS_BUFFER_SIZE)
+            recent_events: Length of the recent fills/signals lists (env: REDIS_RECENT_EVENTS)
+            client: redis.Redis-compatible client (default: built from url)
+
+        Raises:
+            ValueError: No URL configured
+            ImportError: The redis package is not installed
+        """
+        self.url = url or os.getenv('REDIS_URL')
+        self.prefix = (prefix or os.getenv('REDIS_KEY_PREFIX', 'tradebot')).rstrip(':')
+        self.quote_ttl = quote_ttl if quote_ttl is not None else int(os.getenv('REDIS_QUOTE_TTL', '60'))
+        self.flush_interval = flush_interval if flush_interval is not None else \
+            float(os.getenv('REDIS_FLUSH_INTERVAL', '0.25'))
+        self.buffer_size = buffer_size if buffer_size is not None else int(os.getenv('REDIS_BUFFER_SIZE', '10000'))
+        self.recent_events = recent_events if recent_events is not None else \
+            int(os.getenv('REDIS_RECENT_EVENTS', '100'))
+        self.health_interval = float(os.getenv('REDIS_HEALTH_INTERVAL', '15'))
+        if client is None:
+            if not self.url:
+                raise ValueError("REDIS_URL is not set")
+            try:
+                import redis
+            except ImportError as e:
+                raise ImportError("redis is required for the Redis bridge (pip install redis)") from e
+            client = redis.Redis.from_url(self.url, socket_timeout=5, socket_connect_timeout=5,
+                                          health_check_interval=30)
+        self.client = client
+
+        self._lock = threading.Lock()
+        self._quotes: Dict[str, Dict[str, Any]] = {}
+        self._dirty_quotes: set = set()
+        self._events: Deque[Tuple[str, Dict[str, Any]]] = deque()
+        self._health: Optional[Dict[str, Any]] = None
+        self._stop = threading.Event()
+        self._thread: Optional[threading.Thread] = None
+        self.connected = True
+        self.quotes_written = 0
+        self.events_published = 0
+        self.events_dropped = 0
+        self.write_errors = 0
+        self.last_error: Optional[str] = None
+
+    def key(self, *parts: str) -> str:
+        """Prefixed key or channel name."""
+        return ':'.join((self.prefix,) + parts)
+
+    # ---------------------------
+    # Producers (never block)
+    # ---------------------------
+    def on_market_event(self, event: MarketEvent) -> None:
+        """Cache the latest quote per symbol (market event listener; other events are ignored)."""
+        if not isinstance(event, Quote):
+            return
+        symbol = event.symbol.upper()
+        with self._lock:
+            quote = self._quotes.setdefault(symbol, {'symbol': symbol})
+            for name, value in event.to_dict().items():
+                if value is not None and name != 'type':
+                    quote[name] = value
+            self._dirty_quotes.add(symbol)
+
+    __call__ = on_market_event
+
+    def _enqueue(self, kind: str, message: Dict[str, Any]) -> None:
+        with self._lock:
+            if len(self._events) >= self.buffer_size:
+                self._events.popleft()
+                self.events_dropped += 1
+            self._events.append((kind, message))
+
+    def publish_fill(self, account_id: Any, fill: Dict[str, Any]) -> None:
+        """Publish a fill (any JSON-friendly dict) on the fills channel."""
+        self._enqueue('fills', dict(fill, type='fill', account_id=str(account_id),
+                                    timestamp=fill.get('timestamp') or _now()))
+
+    def publish_signal(self, signal: Any) -> None:
+        """Publish a strategy Signal (or dict) on the signals channel; usable as a SignalBus consumer."""
+        data = signal.to_dict() if hasattr(signal, 'to_dict') else dict(signal)
+        self._enqueue('signals', dict(data, type='signal', timestamp=data.get('timestamp') or _now()))
+
+    def set_positions(self, account_id: Any, positions: Dict[str, int]) -> None:
+        """Cache an account's net positions ({symbol: signed quantity}) and publish the change."""
+        self._enqueue('positions', {'type': 'positions', 'account_id': str(account_id),
+                                    'positions': dict(positions), 'timestamp': _now()})
+
+    def publish_health(self, health: Dict[str, Any]) -> None:
+        """Cache and publish a health snapshot (only the latest pending snapshot is written)."""
+        with self._lock:
+            self._health = dict(health, type='health', timestamp=health.get('timestamp') or _now())
+
+    async def on_order_filled(self, context: Any) -> None:
+        """ORDER_FILLED lifecycle hook: publish the bot's fill notification."""
+        fill = {k: v for k, v in (context.data.get('fill') or {}).items() if k != 'context'}
+        self.publish_fill(context.account_id, fill)
+
+    # ---------------------------
+    # Writer
+    # ---------------------------
+    def start(self) -> None:
+        """Start the background writer thread."""
+        if self._thread and self._thread.is_alive():
+            return
+        self._stop.clear()
+        self._thread = threading.Thread(target=self._run, name="redis-bridge", daemon=True)
+        self._thread.start()
+        logger.info(f"🔴 Redis bridge started (prefix '{self.prefix}', flush every {self.flush_interval}s)")
+
+    def stop(self, timeout: float = 10.0) -> None:
+        """Write what is pending and stop the writer."""
+        self._stop.set()
+        if self._thread:
+            self._thread.join(timeout)
+            self._thread = None
+        else:
+            self.flush()
+
+    def _run(self) -> None:
+        while not self._stop.wait(self.flush_interval):
+            self.flush()
+        self.flush()
+
+    def flush(self) -> int:
+        """
+        Write pending quotes, positions, health and events in one pipeline.
+
+        Returns:
+            int: Quotes and events written (0 if nothing was pending or Redis is unreachable)
+        """
+        with self._lock:
+            quotes = [dict(self._quotes[s]) for s in self._dirty_quotes]
+            self._dirty_quotes.clear()
+            events = list(self._events)
+            self._events.clear()
+            health, self._health = self._health, None
+        if not quotes and not events and health is None:
+            return 0
+        try:
+            pipe = self.client.pipeline(transaction=False)
+            for quote in quotes:
+                pipe.set(self.key('quote', quote['symbol']), _encode(quote), ex=self.quote_ttl)
+            for kind, message in events:
+                payload = _encode(message)
+                if kind == 'positions':
+                    pipe.set(self.key('positions', message['account_id']), payload)
+                pipe.publish(self.key(kind), payload)
+                if kind in RECENT_KINDS and self.recent_events > 0:
+                    pipe.lpush(self.key(kind, 'recent'), payload)
+                    pipe.ltrim(self.key(kind, 'recent'), 0, self.recent_events - 1)
+            if health is not None:
+                payload = _encode(health)
+                pipe.set(self.key('health'), payload, ex=max(1, int(3 * self.health_interval)))
+                pipe.publish(self.key('health'), payload)
+            pipe.execute()
+        except Exception as e:
+            self._requeue(quotes, events, health)
+            self.write_errors += 1
+            self.last_error = str(e)
+            if self.connected:
+                logger.warning(f"⚠️  Redis unreachable, buffering events: {e}")
+            self.connected = False
+            return 0
+        if not self.connected:
+            logger.info("✅ Redis reachable again, buffered events written")
+        self.connected = True
+        self.quotes_written += len(quotes)
+        self.events_published += len(events) + (health is not None)
+        return len(quotes) + len(events)
+
+    def _requeue(self, quotes, events, health) -> None:
+        """Put a failed batch back ahead of anything produced since (within the buffer limit)."""
+        with self._lock:
+            self._dirty_quotes.update(q['symbol'] for q in quotes)
+            if self._health is None:
+                self._health = health
+            merged = list(events) + list(self._events)
+            overflow = max(0, len(merged) - self.buffer_size)
+            self.events_dropped += overflow
+            self._events = deque(merged[overflow:])
+
+    # ---------------------------
+    # Readers (for other processes)
+    # ---------------------------
+    def _get_json(self, key: str) -> Optional[Dict[str, Any]]:
+        raw = self.client.get(key)
+        return json.loads(raw) if raw else None
+
+    def get_quote(self, symbol: str) -> Optional[Dict[str, Any]]:
+        """Cached quote of a symbol (None if unknown or expired)."""
+        return self._get_json(self.key('quote', symbol.upper()))
+
+    def get_positions(self, account_id: Any) -> Optional[Dict[str, Any]]:
+        """Cached positions of an account."""
+        return self._get_json(self.key('positions', str(account_id)))
+
+    def get_health(self) -> Optional[Dict[str, Any]]:
+        """Latest health snapshot (None once the bot has stopped publishing)."""
+        return self._get_json(self.key('health'))
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Writer counters."""
+        with self._lock:
+            buffered = len(self._events)
+        return {
+            "running": bool(self._thread and self._thread.is_alive()),
+            "connected": self.connected,
+            "prefix": self.prefix,
+            "buffered": buffered,
+            "quotes_written": self.quotes_written,
+            "events_published": self.events_published,
+            "events_dropped": self.events_dropped,
+            "write_errors": self.write_errors,
+            "last_error": self.last_error,
+        }
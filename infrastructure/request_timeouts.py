@@ -0,0 +1,209 @@
+"""
+Per-Operation Request Timeouts and Deadlines
+
+One API_TIMEOUT for every call is wrong in both directions: a cancel that
+hangs for 30 seconds while the market moves is worse than a fast failure,
+and a large history download can legitimately take longer. Each endpoint
+maps to an operation with its own per-attempt timeout:
+
+- cancel:  /api/Order/cancel
+- order:   order placement/modification and position closes
+- history: /api/History/ bar downloads
+- default: everything else (API_TIMEOUT)
+
+A Deadline bounds the whole call including the adapter's retries and
+their backoff. Each attempt's timeout is capped at the time left, and a
+retry is refused when its backoff (or Retry-After) would not leave any
+time for the attempt. A caller passes a deadline explicitly or wraps
+several calls in deadline_scope(); nested scopes keep the tighter one.
+
+Usage:
+    timeouts = RequestTimeouts()
+    timeouts.timeout_for('/api/Order/cancel')       # 5.0
+    with deadline_scope(2.0):                        # Everything inside finishes within 2s
+        await bot.cancel_order(order_id)
+    await bot.cancel_order(order_id, deadline=2.0)   # Same for one call
+
+Configuration:
+- API_TIMEOUT: Default per-attempt timeout in seconds (default 30)
+- API_TIMEOUT_CANCEL: Timeout for order cancels (default 5)
+- API_TIMEOUT_ORDER: Timeout for order placement, modification and position closes (default 10)
+- API_TIMEOUT_HISTORY: Timeout for history downloads (default 120)
+"""
+
+import contextvars
+import logging
+import os
+import threading
+import time
+from collections import defaultdict
+from contextlib import contextmanager
+from typing import Dict, Iterator, Optional, Tuple, Union
+
+from urllib3.util.timeout import Timeout
+
+logger = logging.getLogger(__name__)
+
+OPERATION_ENDPOINTS: Tuple[Tuple[str, Tuple[str, ...]], ...] = (
+    ('cancel', ("/api/Order/cancel",)),
+    ('order', ("/api/Order/place", "/api/Order/modify", "/api/Position/closeContract",
+               "/api/Position/partialCloseContract")),
+    ('history', ("/api/History/",)),
+)
+
+_current_deadline: contextvars.ContextVar[Optional['Deadline']] = contextvars.ContextVar(
+    'request_deadline', default=None)
+
+
+class Deadline:
+    """
+    Point in time (monotonic clock) by which a call and all its retries must finish.
+    """
+
+    def __init__(self, seconds: float):
+        """
+        Args:
+            seconds: Time from now until the deadline (0 or less: already expired)
+        """
+        self.seconds = seconds
+        self.expires_at = time.monotonic() + seconds
+        self.retries_refused = 0
+
+    @classmethod
+    def coerce(cls, deadline: Union['Deadline', float, None]) -> Optional['Deadline']:
+        """Deadline from seconds (or an existing Deadline / None)."""
+        if deadline is None or isinstance(deadline, Deadline):
+            return deadline
+        return cls(float(deadline))
+
+    def remaining(self) -> float:
+        """Seconds left (0 once expired)."""
+        return max(0.0, self.expires_at - time.monotonic())
+
+    @property
+    def expired(self) -> bool:
+        return self.remaining() <= 0.0
+
+    def cap(self, timeout: float) -> float:
+        """timeout, shortened to the time left."""
+        return min(timeout, self.remaining())
+
+    def __repr__(self) -> str:
+        return f"Deadline({self.seconds}s, {self.remaining():.3f}s left)"
+
+
+def current_deadline() -> Optional[Deadline]:
+    """Deadline of the enclosing deadline_scope(), if any."""
+    return _current_deadline.get()
+
+
+def tighter(*deadlines: Optional[Deadline]) -> Optional[Deadline]:
+    """The deadline that expires first (None if none is set)."""
+    candidates = [d for d in deadlines if d is not None]
+    return min(candidates, key=lambda d: d.expires_at) if candidates else None
+
+
+@contextmanager
+def deadline_scope(deadline: Union[Deadline, float, None]) -> Iterator[Optional[Deadline]]:
+    """
+    Apply a deadline to every request made inside the block (a looser
+    deadline than the enclosing one has no effect; None keeps the enclosing one).
+    """
+    effective = tighter(Deadline.coerce(deadline), current_deadline())
+    token = _current_deadline.set(effective)
+    try:
+        yield effective
+    finally:
+        _current_deadline.reset(token)
+
+
+class DeadlineTimeout(Timeout):
+    """
+    urllib3 Timeout whose copies are capped at the deadline's remaining time.
+
+    urllib3 clones the timeout for every attempt, so each retry gets the time
+    that is actually left instead of the full per-attempt timeout.
+    """
+
+    def __init__(self, timeout: float, deadline: Deadline):
+        self.base_timeout = timeout
+        self.deadline = deadline
+        remaining = self._remaining()
+        super().__init__(connect=remaining, read=remaining)
+
+    def _remaining(self) -> float:
+        return max(self.deadline.cap(self.base_timeout), 0.001)  # urllib3 rejects 0
+
+    def clone(self) -> Timeout:
+        remaining = self._remaining()
+        return Timeout(connect=remaining, read=remaining)
+
+
+class RequestTimeouts:
+    """
+    Per-operation timeouts and timeout/deadline counters.
+    """
+
+    def __init__(self, default: Optional[float] = None, cancel: Optional[float] = None,
+                 order: Optional[float] = None, history: Optional[float] = None):
+        """
+        Initialize timeouts.
+
+        Args:
+            default: Timeout for other endpoints (env: API_TIMEOUT, read per request when not given)
+            cancel: Order cancel timeout (env: API_TIMEOUT_CANCEL)
+            order: Order placement/modification timeout (env: API_TIMEOUT_ORDER)
+            history: History download timeout (env: API_TIMEOUT_HISTORY)
+        """
+        self.default = default
+        self.timeouts: Dict[str, float] = {
+            'cancel': cancel if cancel is not None else float(os.getenv('API_TIMEOUT_CANCEL', '5')),
+            'order': order if order is not None else float(os.getenv('API_TIMEOUT_ORDER', '10')),
+            'history': history if history is not None else float(os.getenv('API_TIMEOUT_HISTORY', '120')),
+        }
+        for operation, value in self.timeouts.items():
+            if value <= 0:
+                raise ValueError(f"Timeout for {operation} must be positive")
+        self._lock = threading.Lock()
+        self.timed_out: Dict[str, int] = defaultdict(int)
+        self.deadline_exceeded: Dict[str, int] = defaultdict(int)
+
+    @staticmethod
+    def operation_for(endpoint: str) -> str:
+        """Operation name of an endpoint ('default' when none matches)."""
+        for operation, prefixes in OPERATION_ENDPOINTS:
+            if endpoint.startswith(prefixes):
+                return operation
+        return 'default'
+
+    def default_timeout(self) -> float:
+        return self.default if self.default is not None else float(os.getenv('API_TIMEOUT', '30'))
+
+    def timeout_for(self, endpoint: str) -> float:
+        """Per-attempt timeout in seconds for an endpoint."""
+        return self.timeouts.get(self.operation_for(endpoint), self.default_timeout())
+
+    def request_timeout(self, endpoint: str, timeout: Optional[float] = None,
+                        deadline: Optional[Deadline] = None) -> Union[float, DeadlineTimeout]:
+        """
+        Timeout argument for requests: the operation's (or the given) timeout,
+        bounded by the deadline on every attempt when there is one.
+        """
+        seconds = timeout if timeout is not None else self.timeout_for(endpoint)
+        return DeadlineTimeout(seconds, deadline) if deadline is not None else seconds
+
+    def record_timeout(self, endpoint: str) -> None:
+        with self._lock:
+            self.timed_out[self.operation_for(endpoint)] += 1
+
+    def record_deadline_exceeded(self, endpoint: str) -> None:
+        with self._lock:
+            self.deadline_exceeded[self.operation_for(endpoint)] += 1
+
+    def get_stats(self) -> Dict:
+        with self._lock:
+            return {
+                'timeouts_secs': {**self.timeouts, 'default': self.default_timeout()},
+                'timed_out': dict(self.timed_out),
+                'deadline_exceeded': dict(self.deadline_exceeded),
+            }
@@ -0,0 +1,180 @@
+"""
+Session-Wide Retry Budget
+
+Independent per-request retries multiply load exactly when the broker API is
+struggling: every failing call retries 3x, so a 5xx incident quadruples
+traffic. All retry paths (HTTP adapter retries, order re-submits, task queue
+retries) draw from one shared token bucket instead. Each retry costs a token;
+tokens refill at a steady rate. When the bucket is empty retries are shed and
+the original failure is returned to the caller.
+
+First attempts never consume budget - only retries do.
+
+Features:
+- Thread-safe token bucket shared across the process
+- urllib3 Retry subclass that consults the budget before each retry and
+  refuses retries that would overrun the request's deadline
+- Granted/denied counters per retry source for the metrics endpoint
+
+Configuration:
+- RETRY_BUDGET_CAPACITY: Maximum tokens (burst of retries allowed, default 20)
+- RETRY_BUDGET_REFILL_PER_SEC: Tokens added per second (default 0.5)
+"""
+
+import logging
+import os
+import threading
+import time
+from collections import defaultdict
+from typing import Dict, Optional
+
+from urllib3.exceptions import MaxRetryError, ResponseError
+from urllib3.util.retry import Retry
+
+from infrastructure.request_timeouts import current_deadline
+
+logger = logging.getLogger(__name__)
+
+
+class RetryBudget:
+    """
+    Token bucket limiting the total retry rate of the session.
+
+    Usage:
+        budget = get_retry_budget()
+        if budget.try_acquire("order_place"):
+            response = resend()
+    """
+
+    def __init__(self, capacity: Optional[float] = None, refill_per_sec: Optional[float] = None):
+        """
+        Initialize retry budget.
+
+        Args:
+            capacity: Maximum tokens (env: RETRY_BUDGET_CAPACITY, default 20)
+            refill_per_sec: Refill rate (env: RETRY_BUDGET_REFILL_PER_SEC, default 0.5)
+        """
+        self.capacity = float(capacity if capacity is not None else os.getenv('RETRY_BUDGET_CAPACITY', '20'))
+        self.refill_per_sec = float(refill_per_sec if refill_per_sec is not None
+                                    else os.getenv('RETRY_BUDGET_REFILL_PER_SEC', '0.5'))
+        self._tokens = self.capacity
+        self._updated = time.monotonic()
+        self._lock = threading.Lock()
+        self.granted: Dict[str, int] = defaultdict(int)
+        self.denied: Dict[str, int] = defaultdict(int)
+        self._exhausted = False
+
+    def _refill(self) -> None:
+        """Add tokens for the time elapsed since the last update (caller holds _lock)."""
+        now = time.monotonic()
+        self._tokens = min(self.capacity, self._tokens + (now - self._updated) * self.refill_per_sec)
+        self._updated = now
+
+    def try_acquire(self, source: str = "unknown", cost: float = 1.0) -> bool:
+        """
+        Take a token for one retry.
+
+        Args:
+            source: Retry path, for metrics (e.g. 'http', 'order_place', 'task_queue')
+            cost: Tokens consumed
+
+        Returns:
+            bool: True if the retry may proceed, False if it should be shed
+        """
+        with self._lock:
+            self._refill()
+            if self._tokens >= cost:
+                self._tokens -= cost
+                self.granted[source] += 1
+                if self._exhausted:
+                    self._exhausted = False
+                    logger.info("✅ Retry budget recovered - retries resumed")
+                return True
+            self.denied[source] += 1
+            if not self._exhausted:
+                self._exhausted = True
+                logger.warning(f"🚦 Retry budget exhausted - shedding retries (source: {source})")
+            return False
+
+    @property
+    def tokens(self) -> float:
+        with self._lock:
+            self._refill()
+            return self._tokens
+
+    def get_stats(self) -> Dict:
+        """Budget state for the metrics endpoint."""
+        with self._lock:
+            self._refill()
+            return {
+                "tokens": round(self._tokens, 2),
+                "capacity": self.capacity,
+                "refill_per_sec": self.refill_per_sec,
+                "exhausted": self._exhausted,
+                "granted": dict(self.granted),
+                "denied": dict(self.denied),
+                "total_granted": sum(self.granted.values()),
+                "total_denied": sum(self.denied.values()),
+            }
+
+    def reset(self) -> None:
+        """Refill the bucket and clear counters."""
+        with self._lock:
+            self._tokens = self.capacity
+            self._updated = time.monotonic()
+            self.granted.clear()
+            self.denied.clear()
+            self._exhausted = False
+
+
+class BudgetedRetry(Retry):
+    """
+    urllib3 Retry that also requires a retry budget token before each retry,
+    and gives up when the backoff would outlast the current deadline.
+    """
+
+    def __init__(self, *args, budget: Optional[RetryBudget] = None, source: str = "http", **kwargs):
+        super().__init__(*args, **kwargs)
+        self.budget = budget
+        self.source = source
+
+    def new(self, **kw) -> "BudgetedRetry":
+        kw.setdefault('budget', self.budget)
+        kw.setdefault('source', self.source)
+        return super().new(**kw)
+
+    def increment(self, method=None, url=None, response=None, error=None, _pool=None, _stacktrace=None):
+        # Raises MaxRetryError itself once the per-request limit is reached
+        new_retry = super().increment(method, url, response, error, _pool, _stacktrace)
+        deadline = current_deadline()
+        if deadline is not None:
+            wait = new_retry.get_backoff_time()
+            if response is not None and self.respect_retry_after_header:
+                wait = max(wait, new_retry.get_retry_after(response) or 0.0)
+            if deadline.remaining() <= wait:
+                deadline.retries_refused += 1
+                raise MaxRetryError(_pool, url, error or ResponseError(
+                    f"deadline exceeded ({deadline.remaining():.2f}s left, next retry in {wait:.2f}s)"))
+        budget = self.budget or get_retry_budget()
+        if not budget.try_acquire(self.source):
+            reason = error or ResponseError(
+                f"retry budget exhausted (last status {response.status if response is not None else 'n/a'})")
+            raise MaxRetryError(_pool, url, reason)
+        return new_retry
+
+
+# Global retry budget instance
+_retry_budget: Optional[RetryBudget] = None
+
+
+def get_retry_budget() -> RetryBudget:
+    """
+    Get or create the global retry budget.
+
+    Returns:
+        RetryBudget: Budget shared by all retry paths
+    """
+    global _retry_budget
+    if _retry_budget is None:
+        _retry_budget = RetryBudget()
+    return _retry_budget
@@ -0,0 +1,539 @@
+"""
+SQLite Database Backend for Single-Machine Deployments
+
+SQLiteDatabaseManager is a DatabaseManager over one SQLite file, for running
+the bot on a VPS without PostgreSQL. It is selected by the connection string:
+
+    DATABASE_URL=sqlite:///var/lib/tradebot/trading.db   (absolute path)
+    DATABASE_URL=sqlite://trading.db                     (relative path)
+
+Same API as DatabaseManager: the connection layer accepts the same %s
+placeholders and cursor(cursor_factory=RealDictCursor) calls, so the portable
+queries are inherited unchanged and only the PostgreSQL-specific ones
+(intervals, COPY, partitions, hypertables, advisory locks) are overridden:
+
+- WAL journal: readers never block the writer (the bot, the web server and the
+  market data writer thread share the file); synchronous=NORMAL by default
+- One connection per thread, with a busy timeout for concurrent writers
+- Batched writes: MarketDataWriter works as on PostgreSQL, flushing each batch
+  as one transaction with executemany
+- Schema from infrastructure/migrations/sqlite (versioned like the PostgreSQL
+  migrations, applied by migrate() under BEGIN IMMEDIATE)
+- time_bucket(), first() and last() are registered as SQL functions, so
+  get_downsampled_bars() and get_tick_bars() run the same queries
+- Timestamps are stored as ISO-8601 UTC text with microseconds (they sort
+  and compare as strings) and come back as datetimes; JSONB and BOOLEAN
+  columns come back decoded
+
+Not available: read replicas, tick partitions/compression, and the postgres
+leader lease backend (use LEADER_LEASE_BACKEND=file). Ticks expire by DELETE
+(DB_TICK_RETENTION_DAYS) in cleanup_old_data().
+
+Configuration:
+- DATABASE_URL: sqlite:///path/to/file.db (or sqlite::memory: for a throwaway database)
+- SQLITE_BUSY_TIMEOUT_MS: Wait for a locked database before failing (default 5000)
+- SQLITE_SYNCHRONOUS: PRAGMA synchronous, NORMAL or FULL (default NORMAL)
+"""
+
+import json
+import logging
+import os
+import re
+import sqlite3
+import threading
+import time
+from contextlib import contextmanager
+from datetime import date, datetime, timedelta, timezone
+from decimal import Decimal
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from psycopg2.extras import RealDictCursor
+
+from infrastructure.database import (
+    BAR_COPY_FIELDS, BUCKET_ORIGIN, MIGRATIONS_DIR, PoolStats, DatabaseManager, load_migrations,
+)
+
+logger = logging.getLogger(__name__)
+
+SQLITE_MIGRATIONS_DIR = MIGRATIONS_DIR / 'sqlite'
+TIMESTAMP_FORMAT = '%Y-%m-%dT%H:%M:%S.%f+00:00'
+TIMESTAMP_PATTERN = re.compile(r'^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{6}\+00:00$')
+VALUES_PLACEHOLDER = re.compile(r'VALUES\s+%s')
+ORIGIN = datetime.fromisoformat(BUCKET_ORIGIN.split("'")[1])
+INTERVAL_SECONDS = {'second': 1, 'minute': 60, 'hour': 3600, 'day': 86400, 'week': 604800}
+
+# Declared column types decoded on read (connections use PARSE_DECLTYPES)
+sqlite3.register_converter('JSONB', lambda raw: json.loads(raw))
+sqlite3.register_converter('BOOLEAN', lambda raw: raw not in (b'0', b''))
+
+
+def sqlite_path(url: str) -> str:
+    """
+    Database file of a sqlite connection string ('sqlite:///abs.db', 'sqlite://rel.db', 'sqlite:rel.db').
+
+    Raises:
+        ValueError: Not a sqlite connection string
+    """
+    if not url.startswith('sqlite:'):
+        raise ValueError(f"Not a SQLite connection string: {url}")
+    path = url[len('sqlite:'):].split('?', 1)[0]
+    if path.startswith('//'):
+        path = path[2:]
+    if not path:
+        raise ValueError(f"SQLite connection string without a path: {url}")
+    return path
+
+
+def format_timestamp(value: datetime) -> str:
+    """Stored form of a timestamp (naive values are UTC)."""
+    if value.tzinfo is None:
+        value = value.replace(tzinfo=timezone.utc)
+    return value.astimezone(timezone.utc).strftime(TIMESTAMP_FORMAT)
+
+
+def _adapt(value: Any) -> Any:
+    """Python value to a SQLite parameter, as psycopg2 would adapt it."""
+    if isinstance(value, datetime):
+        return format_timestamp(value)
+    if isinstance(value, date):
+        return value.isoformat()
+    if isinstance(value, Decimal):
+        return float(value)
+    if isinstance(value, (dict, list)):
+        return json.dumps(value, default=str)
+    return value
+
+
+def _convert(value: Any) -> Any:
+    """Stored timestamps back to datetimes (also from MIN()/MAX() and time_bucket(), which carry no type)."""
+    if isinstance(value, str) and TIMESTAMP_PATTERN.match(value):
+        return datetime.fromisoformat(value)
+    return value
+
+
+def _interval_seconds(interval: str) -> int:
+    """Seconds in a bucket_interval() string ('5 minutes')."""
+    count, unit = interval.split()
+    return int(count) * INTERVAL_SECONDS[unit.rstrip('s')]
+
+
+def _time_bucket(interval: str, value: Optional[str]) -> Optional[str]:
+    """SQL time_bucket(interval, ts): start of the bucket, counted from TimescaleDB's origin."""
+    if value is None:
+        return None
+    width = _interval_seconds(interval)
+    offset = (datetime.fromisoformat(value) - ORIGIN).total_seconds()
+    return format_timestamp(ORIGIN + timedelta(seconds=offset // width * width))
+
+
+class _First:
+    """SQL first(value, ts) aggregate: the value with the earliest ts."""
+
+    def __init__(self):
+        self.key = None
+        self.value = None
+
+    def step(self, value, key):
+        if key is not None and (self.key is None or key < self.key):
+            self.key, self.value = key, value
+
+    def finalize(self):
+        return self.value
+
+
+class _Last(_First):
+    """SQL last(value, ts) aggregate: the value with the latest ts."""
+
+    def step(self, value, key):
+        if key is not None and (self.key is None or key >= self.key):
+            self.key, self.value = key, value
+
+
+class SQLiteCursor:
+    """psycopg2-style cursor over sqlite3: %s placeholders, context manager, optional dict rows."""
+
+    def __init__(self, cursor: sqlite3.Cursor, dict_rows: bool = False):
+        self._cursor = cursor
+        self._dict_rows = dict_rows
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        self._cursor.close()
+        return False
+
+    @staticmethod
+    def _query(query: Any) -> str:
+        return str(query).replace('%s', '?')
+
+    def execute(self, query: Any, params: Any = None) -> None:
+        self._cursor.execute(self._query(query), [_adapt(v) for v in params or ()])
+
+    def executemany(self, query: Any, rows: List[Any]) -> None:
+        self._cursor.executemany(self._query(query), ([_adapt(v) for v in row] for row in rows))
+
+    def _row(self, row: Optional[tuple]) -> Any:
+        if row is None:
+            return None
+        values = [_convert(v) for v in row]
+        if self._dict_rows:
+            return {column[0]: value for column, value in zip(self._cursor.description, values)}
+        return tuple(values)
+
+    def fetchone(self) -> Any:
+        return self._row(self._cursor.fetchone())
+
+    def fetchall(self) -> List[Any]:
+        return [self._row(row) for row in self._cursor.fetchall()]
+
+    @property
+    def rowcount(self) -> int:
+        return self._cursor.rowcount
+
+    @property
+    def description(self):
+        return self._cursor.description
+
+
+class SQLiteConnection:
+    """psycopg2-style connection wrapper (cursor(cursor_factory=...), commit, rollback)."""
+
+    def __init__(self, conn: sqlite3.Connection):
+        self._conn = conn
+
+    def cursor(self, cursor_factory: Any = None) -> SQLiteCursor:
+        return SQLiteCursor(self._conn.cursor(), dict_rows=cursor_factory is not None)
+
+    def commit(self) -> None:
+        self._conn.commit()
+
+    def rollback(self) -> None:
+        self._conn.rollback()
+
+    def close(self) -> None:
+        self._conn.close()
+
+
+class SQLiteDatabaseManager(DatabaseManager):
+    """
+    DatabaseManager backed by a SQLite file in WAL mode.
+    """
+
+    def __init__(self, url: Optional[str] = None):
+        """
+        Initialize the database.
+
+        Args:
+            url: sqlite connection string (default: DATABASE_URL)
+        """
+        self.path = sqlite_path(url or os.getenv('DATABASE_URL', ''))
+        self.busy_timeout_ms = int(os.getenv('SQLITE_BUSY_TIMEOUT_MS', '5000'))
+        self.synchronous = os.getenv('SQLITE_SYNCHRONOUS', 'NORMAL').strip().upper()
+        if self.synchronous not in ('OFF', 'NORMAL', 'FULL', 'EXTRA'):
+            raise ValueError(f"Invalid SQLITE_SYNCHRONOUS '{self.synchronous}'")
+        self._memory = self.path == ':memory:'
+        self._target = f"file:tradebot-{id(self)}?mode=memory&cache=shared" if self._memory else self.path
+        self.pool = None
+        self.read_pool = None
+        self.pool_stats = {'primary': PoolStats('primary'), 'read': PoolStats('read')}
+        self.read_fallbacks = 0
+        self.tick_storage: Optional[str] = 'plain'
+        self._tick_partitions: set = set()
+        self.bar_storage: Optional[str] = 'plain'
+        self.market_data_writer = None
+        self.durable_writer = None
+        self._local = threading.local()
+        self._connections: List[sqlite3.Connection] = []
+        self._connections_lock = threading.Lock()
+        if self._memory:
+            self._thread_connection()  # A shared in-memory database lives as long as one connection does
+        else:
+            Path(self.path).expanduser().resolve().parent.mkdir(parents=True, exist_ok=True)
+        self._initialize_schema()
+        logger.info(f"✅ SQLite database manager initialized ({self.path})")
+
+    # ---------------------------
+    # Connections
+    # ---------------------------
+    def _connect(self, autocommit: bool = False) -> sqlite3.Connection:
+        """New connection in WAL mode with the SQL functions the shared queries need."""
+        conn = sqlite3.connect(self._target, uri=self._memory, timeout=self.busy_timeout_ms / 1000,
+                               detect_types=sqlite3.PARSE_DECLTYPES, check_same_thread=False,
+                               isolation_level=None if autocommit else '')
+        conn.execute(f"PRAGMA busy_timeout = {self.busy_timeout_ms}")
+        conn.execute("PRAGMA journal_mode = WAL")
+        conn.execute(f"PRAGMA synchronous = {self.synchronous}")
+        conn.create_function('NOW', 0, lambda: format_timestamp(datetime.now(timezone.utc)))
+        conn.create_function('time_bucket', 2, _time_bucket, deterministic=True)
+        conn.create_aggregate('first', 2, _First)
+        conn.create_aggregate('last', 2, _Last)
+        return conn
+
+    def _thread_connection(self) -> sqlite3.Connection:
+        conn = getattr(self._local, 'conn', None)
+        if conn is None:
+            conn = self._local.conn = self._connect()
+            with self._connections_lock:
+                self._connections.append(conn)
+        return conn
+
+    @contextmanager
+    def get_connection(self, read_only: bool = False):
+        """
+        This thread's connection, committed on success and rolled back on error.
+
+        Args:
+            read_only: Accepted for API compatibility (WAL readers never block the writer)
+        """
+        started = time.perf_counter()
+        try:
+            conn = self._thread_connection()
+        except Exception:
+            self.pool_stats['primary'].record_error()
+            raise
+        self.pool_stats['primary'].record_checkout((time.perf_counter() - started) * 1000)
+        wrapper = SQLiteConnection(conn)
+        try:
+            yield wrapper
+            conn.commit()
+        except Exception as e:
+            conn.rollback()
+            logger.error(f"Database error: {e}")
+            raise
+
+    @staticmethod
+    def _execute_values(cur, query: str, values: List[tuple], page_size: int = 100) -> None:
+        """executemany of one row placeholder in place of 'VALUES %s'."""
+        if not values:
+            return
+        row = '(' + ', '.join(['%s'] * len(values[0])) + ')'
+        cur.executemany(VALUES_PLACEHOLDER.sub(f'VALUES {row}', query, count=1), values)
+
+    def get_pool_stats(self) -> Dict[str, Any]:
+        with self._connections_lock:
+            connections = len(self._connections)
+        primary = self.pool_stats['primary'].to_dict(None)
+        primary.update({"backend": "sqlite", "path": self.path, "connections": connections})
+        return {"primary": primary, "read": None, "read_fallbacks": 0}
+
+    def close(self):
+        """Flush buffered market data and close every thread's connection."""
+        if self.durable_writer is not None:
+            self.durable_writer.stop()
+        if self.market_data_writer is not None:
+            self.market_data_writer.stop()
+        with self._connections_lock:
+            connections, self._connections = self._connections, []
+        for conn in connections:
+            try:
+                conn.close()
+            except sqlite3.Error:
+                pass
+        self._local = threading.local()
+        logger.info("✅ SQLite database closed")
+
+    # ---------------------------
+    # Schema
+    # ---------------------------
+    @staticmethod
+    def _statements(script: str) -> List[str]:
+        """Split a SQL script into statements (semicolons inside strings and comments are kept)."""
+        statements, pending = [], ''
+        for line in script.splitlines(keepends=True):
+            pending += line
+            if sqlite3.complete_statement(pending):
+                if pending.strip():
+                    statements.append(pending.strip())
+                pending = ''
+        leftover = [line for line in pending.splitlines() if line.strip() and not line.strip().startswith('--')]
+        if leftover:
+            raise ValueError("SQL script ends with an incomplete statement")
+        return statements
+
+    def migrate(self, target: Optional[int] = None, directory: Optional[Path] = None) -> List[int]:
+        """
+        Apply pending migrations from infrastructure/migrations/sqlite (see DatabaseManager.migrate()).
+
+        Each migration runs in its own BEGIN IMMEDIATE transaction, which also
+        keeps other processes from applying it at the same time.
+        """
+        migrations = load_migrations(directory or SQLITE_MIGRATIONS_DIR)
+        applied_now: List[int] = []
+        conn = self._connect(autocommit=True)
+        try:
+            cur = SQLiteCursor(conn.cursor())
+            cur.execute("""
+                CREATE TABLE IF NOT EXISTS schema_migrations (
+                    version INTEGER PRIMARY KEY,
+                    name VARCHAR(200) NOT NULL,
+                    checksum CHAR(64) NOT NULL,
+                    execution_ms REAL,
+                    applied_at TIMESTAMPTZ DEFAULT (strftime('%Y-%m-%dT%H:%M:%f000+00:00', 'now'))
+                )
+            """)
+            for migration in migrations:
+                if target is not None and migration.version > target:
+                    break
+                conn.execute("BEGIN IMMEDIATE")
+                try:
+                    cur.execute("SELECT version, checksum FROM schema_migrations")
+                    applied = {version: checksum for version, checksum in cur.fetchall()}
+                    self._check_applied(migrations, applied)
+                    if migration.version in applied:
+                        conn.execute("COMMIT")
+                        continue
+                    started = time.perf_counter()
+                    for statement in self._statements(migration.sql):
+                        conn.execute(statement)
+                    cur.execute("""
+                        INSERT INTO schema_migrations (version, name, checksum, execution_ms)
+                        VALUES (%s, %s, %s, %s)
+                    """, (migration.version, migration.name, migration.checksum,
+                          round((time.perf_counter() - started) * 1000, 2)))
+                    conn.execute("COMMIT")
+                except Exception as e:
+                    conn.execute("ROLLBACK")
+                    if isinstance(e, RuntimeError):
+                        raise
+                    raise RuntimeError(f"Migration {migration.filename} failed: {e}") from e
+                applied_now.append(migration.version)
+                logger.info(f"🗄️  Applied migration sqlite/{migration.filename}")
+        finally:
+            conn.close()
+        return applied_now
+
+    def migration_status(self, directory: Optional[Path] = None) -> Dict[str, Any]:
+        migrations = load_migrations(directory or SQLITE_MIGRATIONS_DIR)
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    cur.execute("SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations'")
+                    applied: List[int] = []
+                    if cur.fetchone():
+                        cur.execute("SELECT version FROM schema_migrations ORDER BY version")
+                        applied = [row[0] for row in cur.fetchall()]
+        except Exception as e:
+            logger.error(f"❌ Failed to read migration status: {e}")
+            return {}
+        return {
+            'version': applied[-1] if applied else 0,
+            'applied': applied,
+            'pending': [m.version for m in migrations if m.version not in applied],
+        }
+
+    # ---------------------------
+    # Market data
+    # ---------------------------
+    def _insert_ticks(self, cur, values: List[tuple], copy: bool = False) -> None:
+        """Insert tick rows (no COPY or partitions on SQLite; one executemany in the caller's transaction)."""
+        self._execute_values(cur, "INSERT INTO market_ticks (ts, symbol, price, size, side, bid, ask) VALUES %s",
+                             values)
+
+    def maintain_tick_partitions(self) -> Dict[str, int]:
+        return {'created': 0, 'dropped': 0}
+
+    def get_tick_storage_stats(self) -> Dict:
+        stats: Dict[str, Any] = {'storage': self.tick_storage, 'partitions': 0}
+        try:
+            with self.get_connection(read_only=True) as conn:
+                with conn.cursor() as cur:
+                    cur.execute("SELECT COUNT(*) FROM market_ticks")
+                    stats['rows'] = cur.fetchone()[0]
+                    cur.execute("SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()")
+                    stats['bytes'] = cur.fetchone()[0]
+        except Exception as e:
+            logger.error(f"❌ Failed to get tick storage stats: {e}")
+        return stats
+
+    def _bucket_sql(self, column: str) -> str:
+        return f"time_bucket(%s, {column})"
+
+    def _first_last_sql(self, value: str, column: str) -> tuple:
+        return f"first({value}, {column})", f"last({value}, {column})"
+
+    def get_bars(self, symbol: str, timeframe: str, start_time: datetime, end_time: datetime,
+                 output: str = 'numpy') -> Any:
+        """Cached bars in [start_time, end_time) as columns (see DatabaseManager.get_bars())."""
+        if output not in ('numpy', 'arrow'):
+            raise ValueError(f"Unknown output '{output}', expected 'numpy' or 'arrow'")
+        try:
+            import numpy as np
+        except ImportError as e:
+            raise ImportError("numpy is required for array output (pip install numpy)") from e
+        try:
+            with self.get_connection(read_only=True) as conn:
+                with conn.cursor() as cur:
+                    cur._cursor.execute("""
+                        SELECT (julianday(timestamp) - 2440587.5) * 86400.0,
+                               COALESCE(open, 'NaN'), COALESCE(high, 'NaN'), COALESCE(low, 'NaN'),
+                               COALESCE(close, 'NaN'), COALESCE(volume, 0),
+                               COALESCE(json_extract(metadata, '$.tick_count'), 0)
+                        FROM historical_bars
+                        WHERE symbol = ? AND timeframe = ? AND timestamp >= ? AND timestamp < ?
+                        ORDER BY timestamp ASC
+                    """, (symbol, timeframe, format_timestamp(start_time), format_timestamp(end_time)))
+                    rows = cur._cursor.fetchall()  # Plain numeric tuples, no per-value conversion
+        except Exception as e:
+            logger.error(f"❌ Failed to load {symbol} {timeframe} bars: {e}")
+            raise
+        width = len(BAR_COPY_FIELDS)
+        block = np.ascontiguousarray(np.array(rows, dtype=np.float64).reshape(len(rows), width).T)
+        block[0] = np.round(block[0], 3)  # julianday() is accurate to the millisecond
+        columns = dict(zip(BAR_COPY_FIELDS, block))
+        if output == 'numpy':
+            return columns
+        return self._bar_columns_to_arrow(symbol, timeframe, columns)
+
+    # ---------------------------
+    # Interval queries
+    # ---------------------------
+    def get_cached_order_history(
+        self,
+        account_id: str,
+        start_time: datetime,
+        end_time: datetime,
+        limit: int = 1000,
+        max_age_hours: int = 24
+    ) -> Optional[List[Dict]]:
+        cutoff = datetime.now(timezone.utc) - timedelta(hours=max_age_hours)
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor(cursor_factory=RealDictCursor) as cur:
+                    cur.execute("""
+                        SELECT order_data FROM order_history_cache
+                        WHERE account_id = %s AND order_timestamp >= %s AND order_timestamp <= %s
+                          AND cached_at > %s
+                        ORDER BY order_timestamp DESC
+                        LIMIT %s
+                    """, (account_id, start_time, end_time, cutoff, limit))
+                    rows = cur.fetchall()
+        except Exception as e:
+            logger.error(f"❌ Failed to get cached order history: {e}")
+            return None
+        if not rows:
+            return None
+        orders = [row['order_data'] for row in rows]
+        logger.info(f"✅ DB Cache HIT: {len(orders)} orders for account {account_id}")
+        return orders
+
+    def cleanup_old_data(self, days: int = 30):
+        """Delete bars cached more than days ago, API metrics older than 7 days and expired ticks."""
+        now = datetime.now(timezone.utc)
+        retention_days = int(os.getenv('DB_TICK_RETENTION_DAYS', '0'))
+        try:
+            with self.get_connection() as conn:
+                with conn.cursor() as cur:
+                    cur.execute("DELETE FROM historical_bars WHERE created_at < %s", (now - timedelta(days=days),))
+                    bars_deleted = cur.rowcount
+                    cur.execute("DELETE FROM api_metrics WHERE timestamp < %s", (now - timedelta(days=7),))
+                    metrics_deleted = cur.rowcount
+                    ticks_deleted = 0
+                    if retention_days > 0:
+                        cur.execute("DELETE FROM market_ticks WHERE ts < %s", (now - timedelta(days=retention_days),))
+                        ticks_deleted = cur.rowcount
+            logger.info(f"🧹 Cleanup: Deleted {bars_deleted} old bars, {metrics_deleted} old metrics, "
+                        f"{ticks_deleted} expired ticks")
+        except Exception as e:
+            logger.error(f"❌ Failed to cleanup old data: {e}")
@@ -13,6 +13,8 @@ from dataclasses import dataclass, field
 from datetime import datetime
 import time
 
+from infrastructure.retry_budget import get_retry_budget
+
 logger = logging.getLogger(__name__)
 
 
@@ -69,6 +71,7 @@ class PriorityTaskQueue:
         self.tasks_failed = 0
         self.tasks_timeout = 0
         self.tasks_cancelled = 0
+        self.tasks_retry_shed = 0  # Retries skipped because the retry budget was exhausted
         
         # Workers
         self._workers: list[asyncio.Task] = []
@@ -183,14 +186,14 @@ class PriorityTaskQueue:
             self.tasks_timeout += 1
             
             # Retry logic
-            if task.retry_count < task.max_retries:
+            if task.retry_count < task.max_retries and self._retry_allowed(task_id):
                 task.retry_count += 1
                 backoff = 2 ** task.retry_count  # Exponential backoff
                 logger.info(f"🔄 Retrying task {task_id} in {backoff}s (attempt {task.retry_count}/{task.max_retries})")
                 await asyncio.sleep(backoff)
                 await self.queue.put(task)
             else:
-                logger.error(f"❌ Task failed after {task.max_retries} retries: {task_id}")
+                logger.error(f"❌ Task failed after {task.retry_count} retries: {task_id}")
                 self.tasks_failed += 1
         
         except asyncio.CancelledError:
@@ -202,7 +205,7 @@ class PriorityTaskQueue:
             self.tasks_failed += 1
             
             # Retry logic for transient errors
-            if task.retry_count < task.max_retries:
+            if task.retry_count < task.max_retries and self._retry_allowed(task_id):
                 task.retry_count += 1
                 backoff = 2 ** task.retry_count
                 logger.info(f"🔄 Retrying task {task_id} in {backoff}s (attempt {task.retry_count}/{task.max_retries})")
@@ -213,6 +216,14 @@ class PriorityTaskQueue:
             # Remove from active tasks
             self.active_tasks.pop(task_id, None)
     
+    def _retry_allowed(self, task_id: str) -> bool:
+        """Check the session-wide retry budget before re-queueing a task."""
+        if get_retry_budget().try_acquire("task_queue"):
+            return True
+        logger.warning(f"🚦 Retry budget exhausted - not retrying task {task_id}")
+        self.tasks_retry_shed += 1
+        return False
+    
     async def start(self, num_workers: int = 5):
         """
         Start worker threads to process tasks.
@@ -281,6 +292,7 @@ class PriorityTaskQueue:
             "tasks_failed": self.tasks_failed,
             "tasks_timeout": self.tasks_timeout,
             "tasks_cancelled": self.tasks_cancelled,
+            "tasks_retry_shed": self.tasks_retry_shed,
             "success_rate": f"{(self.tasks_completed / self.tasks_submitted * 100) if self.tasks_submitted > 0 else 0:.1f}%"
         }
     
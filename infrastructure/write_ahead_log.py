@@ -0,0 +1,537 @@
+"""
+Write-Ahead Log Between the Hot Path and the Database
+
+Database writes from the trading loop (market data, fill journaling) go to
+an append-only log on local disk first; a background writer applies them to
+PostgreSQL in order and checkpoints what was applied. An unreachable or
+slow database therefore never blocks order flow: records pile up on disk
+(not in memory, not dropped) and drain once it is back. Records the writer
+had not checkpointed when the process died are replayed on the next start.
+
+Log layout (DB_WAL_DIR):
+    wal-<first seq>.log   Segments of records, one per line:
+                          <crc32 hex> {"seq": n, "kind": "tick", "ts": epoch, "data": {...}}
+    checkpoint            Sequence number of the last applied record
+
+Appends are flushed to the OS immediately (a process crash loses nothing);
+fsync follows DB_WAL_FSYNC. A torn last line (power loss mid-write) fails its
+checksum and is cut off on open. Segments are deleted once every record in
+them is applied.
+
+Delivery is at-least-once: a crash between a database commit and its
+checkpoint replays that run. Bars are upserts and fills are deduplicated by
+fill_id, so only ticks (and order journal entries) can be written twice.
+
+Record kinds applied by DurableWriter:
+- tick, bar: market data, consecutive records written in one transaction
+- fill, order, trade: journal entries (record_fill / record_order / record_trade)
+
+Configuration:
+- DB_WAL_ENABLED: Route the bot's database writes through the log (default false)
+- DB_WAL_DIR: Log directory, claimed by one process (default 'data/wal')
+- DB_WAL_SEGMENT_MB: Segment size before rolling over (default 64)
+- DB_WAL_FSYNC: 'interval' (default), 'always' (every append) or 'never'
+- DB_WAL_FSYNC_INTERVAL: Seconds between fsyncs in interval mode (default 1)
+- DB_WAL_BATCH_SIZE: Records applied per database round (default 5000)
+- DB_WAL_RETRY_MAX_SECONDS: Backoff cap while the database is failing (default 30)
+"""
+
+import json
+import logging
+import os
+import threading
+import time
+import zlib
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import Any, Callable, Dict, List, Optional, Tuple, Union
+
+from infrastructure.file_lock import acquire_artifact_lock, atomic_write
+
+logger = logging.getLogger(__name__)
+
+FSYNC_MODES = ('interval', 'always', 'never')
+MARKET_DATA_KINDS = ('tick', 'bar')
+
+# (seq, kind, append time, data)
+Record = Tuple[int, str, float, Any]
+
+
+def _encode_record(seq: int, kind: str, data: Any) -> bytes:
+    body = json.dumps({'seq': seq, 'kind': kind, 'ts': time.time(), 'data': data},
+                      separators=(',', ':'), default=str).encode()
+    return b'%08x ' % zlib.crc32(body) + body + b'\n'
+
+
+def _decode_record(line: bytes) -> Optional[Record]:
+    """Record of a complete log line (None if it fails its checksum or does not parse)."""
+    if len(line) < 10 or not line.endswith(b'\n') or line[8:9] != b' ':
+        return None
+    body = line[9:-1]
+    try:
+        if int(line[:8], 16) != zlib.crc32(body):
+            return None
+        record = json.loads(body)
+        return record['seq'], record['kind'], record['ts'], record['data']
+    except (ValueError, KeyError, TypeError):
+        return None
+
+
+class WriteAheadLog:
+    """
+    Append-only, segmented, checksummed record log with a checkpoint.
+
+    Usage:
+        wal = WriteAheadLog('data/wal')
+        seq = wal.append('fill', {'account_id': '123', 'fill': {...}})
+        for seq, kind, ts, data in wal.read(limit=100):
+            ...
+        wal.commit(seq)
+    """
+
+    def __init__(self, directory: Union[str, Path, None] = None, segment_bytes: Optional[int] = None,
+                 fsync: Optional[str] = None):
+        """
+        Open (or create) the log and recover its state.
+
+        Args:
+            directory: Log directory (env: DB_WAL_DIR)
+            segment_bytes: Segment size before rolling over (env: DB_WAL_SEGMENT_MB)
+            fsync: 'interval', 'always' or 'never' (env: DB_WAL_FSYNC)
+
+        Raises:
+            ValueError: Unknown fsync mode
+            ArtifactLockError: Another process holds the directory
+        """
+        self.directory = Path(directory or os.getenv('DB_WAL_DIR', 'data/wal'))
+        self.segment_bytes = segment_bytes if segment_bytes is not None else \
+            int(float(os.getenv('DB_WAL_SEGMENT_MB', '64')) * 1024 * 1024)
+        self.fsync = (fsync or os.getenv('DB_WAL_FSYNC', 'interval')).strip().lower()
+        if self.fsync not in FSYNC_MODES:
+            raise ValueError(f"Unknown DB_WAL_FSYNC '{self.fsync}'. Use one of {', '.join(FSYNC_MODES)}")
+        self.directory.mkdir(parents=True, exist_ok=True)
+        acquire_artifact_lock(self.directory)
+        self._lock = threading.Lock()
+        self.corrupt_records = 0
+        self._segments: List[Tuple[int, Path]] = []  # (first seq, path), oldest first
+        last_seq = self._recover()
+        self.committed = self._read_checkpoint()
+        self.next_seq = max(last_seq, self.committed) + 1
+        self._file = None
+        self._file_bytes = 0
+        self._unsynced = False
+        if self._segments:
+            self._file_bytes = self._segments[-1][1].stat().st_size
+            self._file = open(self._segments[-1][1], 'ab')
+        self._read_segment = 0
+        self._read_offset = 0
+        self._seek_after(self.committed)
+        if self.depth():
+            logger.warning(f"⚠️  Write-ahead log {self.directory} has {self.depth()} unapplied records "
+                           f"(seq {self.committed + 1}..{self.next_seq - 1}), replaying")
+
+    # ---------------------------
+    # Recovery
+    # ---------------------------
+    def _recover(self) -> int:
+        """Index segments, cut a torn tail off the last one and return the last valid sequence number."""
+        for path in sorted(self.directory.glob('wal-*.log')):
+            try:
+                self._segments.append((int(path.stem[len('wal-'):]), path))
+            except ValueError:
+                logger.warning(f"⚠️  Ignoring unexpected file {path} in the write-ahead log")
+        last_seq = self._segments[0][0] - 1 if self._segments else 0
+        for index, (_, path) in enumerate(self._segments):
+            valid_bytes = 0
+            with open(path, 'rb') as f:
+                for line in f:
+                    record = _decode_record(line)
+                    if record is None:
+                        if index == len(self._segments) - 1:
+                            break  # Torn write at the end of the log
+                        self.corrupt_records += 1
+                        logger.error(f"❌ Skipping corrupt record in {path} at byte {valid_bytes}")
+                    else:
+                        last_seq = record[0]
+                    valid_bytes += len(line)
+            if index == len(self._segments) - 1 and valid_bytes < path.stat().st_size:
+                logger.warning(f"⚠️  Truncating torn record at the end of {path} "
+                               f"({path.stat().st_size - valid_bytes} bytes)")
+                with open(path, 'r+b') as f:
+                    f.truncate(valid_bytes)
+        return last_seq
+
+    def _read_checkpoint(self) -> int:
+        path = self.directory / 'checkpoint'
+        try:
+            return int(path.read_text().strip() or 0)
+        except FileNotFoundError:
+            return self._segments[0][0] - 1 if self._segments else 0
+
+    def _seek_after(self, seq: int) -> None:
+        """Position the reader on the first record after seq."""
+        self._read_segment, self._read_offset = 0, 0
+        for index, (first, _) in enumerate(self._segments):
+            if first <= seq + 1:
+                self._read_segment = index
+        if not self._segments:
+            return
+        with open(self._segments[self._read_segment][1], 'rb') as f:
+            offset = 0
+            for line in f:
+                record = _decode_record(line)
+                if record is not None and record[0] > seq:
+                    break
+                offset += len(line)
+        self._read_offset = offset
+
+    # ---------------------------
+    # Appending
+    # ---------------------------
+    def append(self, kind: str, data: Any) -> int:
+        """
+        Append a record (data must be JSON-serializable; other values are stored as strings).
+
+        Returns:
+            int: The record's sequence number
+        """
+        with self._lock:
+            seq = self.next_seq
+            line = _encode_record(seq, kind, data)
+            if self._file is None or self._file_bytes + len(line) > self.segment_bytes:
+                self._roll(seq)
+            self._file.write(line)
+            self._file.flush()
+            if self.fsync == 'always':
+                os.fsync(self._file.fileno())
+            else:
+                self._unsynced = True
+            self._file_bytes += len(line)
+            self.next_seq = seq + 1
+            return seq
+
+    def _roll(self, first_seq: int) -> None:
+        """Start a new segment beginning at first_seq."""
+        if self._file is not None:
+            self._file.flush()
+            os.fsync(self._file.fileno())
+            self._file.close()
+        path = self.directory / f"wal-{first_seq:020d}.log"
+        self._file = open(path, 'ab')
+        self._file_bytes = 0
+        self._segments.append((first_seq, path))
+
+    def sync(self) -> None:
+        """fsync appended records (the writer calls this every DB_WAL_FSYNC_INTERVAL in interval mode)."""
+        with self._lock:
+            if self._file is not None and self._unsynced and self.fsync != 'never':
+                os.fsync(self._file.fileno())
+            self._unsynced = False
+
+    # ---------------------------
+    # Reading and checkpointing
+    # ---------------------------
+    def read(self, limit: int = 1000) -> List[Record]:
+        """
+        Next records after the last read (starting after the checkpoint), oldest first.
+
+        A record half-written by a concurrent append is left for the next read.
+        """
+        records: List[Record] = []
+        while len(records) < limit:
+            with self._lock:
+                if self._read_segment >= len(self._segments):
+                    break
+                path = self._segments[self._read_segment][1]
+                is_last = self._read_segment == len(self._segments) - 1
+            with open(path, 'rb') as f:
+                f.seek(self._read_offset)
+                for line in f:
+                    if not line.endswith(b'\n'):
+                        break  # Append in progress
+                    self._read_offset += len(line)
+                    record = _decode_record(line)
+                    if record is not None:
+                        records.append(record)
+                    if len(records) >= limit:
+                        break
+            if len(records) >= limit or is_last:
+                break
+            self._read_segment += 1
+            self._read_offset = 0
+        return records
+
+    def commit(self, seq: int) -> None:
+        """Checkpoint every record up to seq as applied and delete segments that are fully applied."""
+        atomic_write(self.directory / 'checkpoint', str(seq))
+        with self._lock:
+            self.committed = seq
+            removable = 0
+            while removable + 1 < len(self._segments) and self._segments[removable + 1][0] <= seq + 1:
+                removable += 1
+            removed, self._segments = self._segments[:removable], self._segments[removable:]
+            self._read_segment = max(0, self._read_segment - removable)
+        for _, path in removed:
+            path.unlink(missing_ok=True)
+
+    def depth(self) -> int:
+        """Records appended but not yet applied."""
+        return self.next_seq - 1 - self.committed
+
+    def disk_bytes(self) -> int:
+        with self._lock:
+            paths = [path for _, path in self._segments]
+        return sum(path.stat().st_size for path in paths if path.exists())
+
+    def close(self) -> None:
+        with self._lock:
+            if self._file is not None:
+                self._file.flush()
+                if self.fsync != 'never':
+                    os.fsync(self._file.fileno())
+                self._file.close()
+                self._file = None
+
+
+class DurableWriter:
+    """
+    Applies write-ahead log records to the database on a background thread.
+
+    Producers call the non-blocking methods (on_market_event, add_ticks,
+    add_bars, record_fill, record_order, record_trade); they only append to
+    the log. A batch that fails is retried with exponential backoff until
+    the database accepts it; nothing is dropped.
+
+    Usage:
+        writer = db.get_durable_writer()
+        bot.add_market_event_listener(writer.on_market_event)
+        writer.record_fill(account_id, fill)
+        ...
+        db.close()  # Applies what it can and stops
+    """
+
+    def __init__(self, db: Any, wal: Optional[WriteAheadLog] = None, batch_size: Optional[int] = None,
+                 flush_interval: Optional[float] = None, fsync_interval: Optional[float] = None,
+                 retry_max_seconds: Optional[float] = None, use_copy: Optional[bool] = None):
+        """
+        Initialize writer.
+
+        Args:
+            db: DatabaseManager to apply records to
+            wal: Log to drain (default: WriteAheadLog() from the environment)
+            batch_size: Records applied per round (env: DB_WAL_BATCH_SIZE)
+            flush_interval: Seconds between rounds when idle (env: DB_WRITE_FLUSH_INTERVAL)
+            fsync_interval: Seconds between fsyncs in interval mode (env: DB_WAL_FSYNC_INTERVAL)
+            retry_max_seconds: Backoff cap while the database fails (env: DB_WAL_RETRY_MAX_SECONDS)
+            use_copy: COPY ticks instead of multi-row INSERTs (env: DB_WRITE_USE_COPY)
+        """
+        self.db = db
+        self.wal = wal or WriteAheadLog()
+        self.batch_size = batch_size if batch_size is not None else int(os.getenv('DB_WAL_BATCH_SIZE', '5000'))
+        self.flush_interval = flush_interval if flush_interval is not None else \
+            float(os.getenv('DB_WRITE_FLUSH_INTERVAL', '1'))
+        self.fsync_interval = fsync_interval if fsync_interval is not None else \
+            float(os.getenv('DB_WAL_FSYNC_INTERVAL', '1'))
+        self.retry_max_seconds = retry_max_seconds if retry_max_seconds is not None else \
+            float(os.getenv('DB_WAL_RETRY_MAX_SECONDS', '30'))
+        self.use_copy = use_copy if use_copy is not None else \
+            os.getenv('DB_WRITE_USE_COPY', 'true').lower() in ('true', '1', 'yes', 'on')
+        self._journal: Dict[str, Callable[[Dict], Any]] = {
+            'fill': lambda d: db.record_fill(d['account_id'], d['fill']),
+            'order': lambda d: db.record_order(d['account_id'], d['order']),
+            'trade': lambda d: db.record_trade(d['account_id'], d['trade']),
+        }
+        self._pending: List[Record] = []  # Read from the log, not applied yet
+        self._apply_lock = threading.Lock()
+        self._wake = threading.Event()
+        self._stop = threading.Event()
+        self._thread: Optional[threading.Thread] = None
+        self._retry_delay = 0.0
+        self._last_sync = time.monotonic()
+        self.records_applied = 0
+        self.write_errors = 0
+        self.last_error: Optional[str] = None
+
+    # ---------------------------
+    # Producers (append only)
+    # ---------------------------
+    def add_ticks(self, ticks: List[Any]) -> int:
+        """Log ticks (dicts as accepted by save_ticks(), or Trade events)."""
+        for tick in ticks:
+            if not isinstance(tick, dict):
+                tick = {'symbol': tick.symbol, 'timestamp': tick.timestamp, 'price': tick.price,
+                        'size': getattr(tick, 'size', 0), 'side': getattr(tick, 'side', None)}
+            self.wal.append('tick', tick)
+        return len(ticks)
+
+    def add_bars(self, bars: List[Any]) -> int:
+        """Log completed Bar objects."""
+        for bar in bars:
+            self.wal.append('bar', {
+                'symbol': bar.symbol.upper(), 'timeframe': bar.timeframe, 'timestamp': bar.timestamp,
+                'open': bar.open, 'high': bar.high, 'low': bar.low, 'close': bar.close,
+                'volume': bar.volume, 'tick_count': bar.tick_count,
+            })
+        return len(bars)
+
+    def on_market_event(self, event: Any) -> None:
+        """Market event listener: logs Trade and BarClosed events."""
+        kind = getattr(event, 'type', None)
+        if kind == 'trade':
+            self.add_ticks([event])
+        elif kind == 'bar_closed':
+            self.add_bars([event.bar])
+
+    def record_fill(self, account_id: Any, fill: Dict) -> int:
+        """Log a fill for DatabaseManager.record_fill; returns its sequence number."""
+        return self._append_journal('fill', account_id, fill)
+
+    def record_order(self, account_id: Any, order: Dict) -> int:
+        """Log an order for DatabaseManager.record_order."""
+        return self._append_journal('order', account_id, order)
+
+    def record_trade(self, account_id: Any, trade: Dict) -> int:
+        """Log a closed round trip for DatabaseManager.record_trade."""
+        return self._append_journal('trade', account_id, trade)
+
+    def _append_journal(self, kind: str, account_id: Any, entry: Dict) -> int:
+        seq = self.wal.append(kind, {'account_id': str(account_id), kind: entry})
+        self._wake.set()
+        return seq
+
+    # ---------------------------
+    # Applying
+    # ---------------------------
+    def _apply_run(self, run: List[Record]) -> None:
+        """Apply consecutive records of one class (raises if the database did not take them)."""
+        kind = run[0][1]
+        if kind in MARKET_DATA_KINDS:
+            from infrastructure.database import DatabaseManager
+            ticks, bars = [], {}
+            for _, record_kind, _, data in run:
+                if record_kind == 'tick':
+                    row = DatabaseManager._tick_row(data)
+                    if row:
+                        ticks.append(row)
+                else:
+                    row = DatabaseManager._bar_row(data['symbol'], data['timeframe'], data)
+                    if row:
+                        bars[row[:3]] = row  # The latest version of a bar wins
+            if ticks or bars:
+                self.db.write_market_data(ticks, list(bars.values()), copy=self.use_copy)
+        else:
+            handler = self._journal.get(kind)
+            if handler is None:
+                logger.error(f"❌ Skipping write-ahead log record of unknown kind '{kind}' (seq {run[0][0]})")
+            elif handler(run[0][3]) is False:
+                raise RuntimeError(f"{kind} not written")
+
+    def _next_run(self) -> List[Record]:
+        """Leading records of the pending batch to apply together (market data, or one journal entry)."""
+        first = self._pending[0]
+        if first[1] not in MARKET_DATA_KINDS:
+            return self._pending[:1]
+        length = 1
+        while length < len(self._pending) and self._pending[length][1] in MARKET_DATA_KINDS:
+            length += 1
+        return self._pending[:length]
+
+    def apply(self) -> int:
+        """
+        Apply pending records to the database, checkpointing after each run.
+
+        Returns:
+            int: Records applied (stops at the first failure; it is retried on the next call)
+        """
+        applied = 0
+        with self._apply_lock:
+            while True:
+                if not self._pending:
+                    self._pending = self.wal.read(self.batch_size)
+                    if not self._pending:
+                        break
+                run = self._next_run()
+                try:
+                    self._apply_run(run)
+                except Exception as e:
+                    self.write_errors += 1
+                    self.last_error = f"{type(e).__name__}: {e}"
+                    logger.error(f"❌ Write-ahead log apply failed at seq {run[0][0]} "
+                                 f"({self.wal.depth()} records pending), will retry: {self.last_error}")
+                    break
+                self.wal.commit(run[-1][0])
+                del self._pending[:len(run)]
+                applied += len(run)
+        self.records_applied += applied
+        return applied
+
+    def pending(self) -> int:
+        """Backlog depth: records logged but not yet in the database."""
+        return self.wal.depth()
+
+    def oldest_pending_seconds(self) -> Optional[float]:
+        """Age of the oldest unapplied record the writer has read (None when caught up)."""
+        pending = self._pending
+        if not pending or not self.wal.depth():
+            return None
+        return max(0.0, time.time() - pending[0][2])
+
+    def start(self) -> None:
+        """Start applying in the background (replays unapplied records first)."""
+        if self._thread and self._thread.is_alive():
+            return
+        self._stop.clear()
+        self._thread = threading.Thread(target=self._run, name="db-write-ahead-log", daemon=True)
+        self._thread.start()
+        logger.info(f"✅ Write-ahead log writer started ({self.wal.directory}, fsync={self.wal.fsync}, "
+                    f"{self.pending()} records pending)")
+
+    def stop(self, timeout: float = 30.0) -> None:
+        """Stop the writer after one last attempt; unapplied records stay on disk for the next start."""
+        self._stop.set()
+        self._wake.set()
+        if self._thread:
+            self._thread.join(timeout)
+            self._thread = None
+        self.apply()
+        self.wal.close()
+        if self.pending():
+            logger.warning(f"⚠️  Write-ahead log writer stopped with {self.pending()} records pending "
+                           f"(replayed on the next start)")
+
+    def _run(self) -> None:
+        while not self._stop.is_set():
+            self._wake.wait(self._retry_delay or self.flush_interval)
+            self._wake.clear()
+            if self._stop.is_set():
+                break
+            if self.wal.fsync == 'interval' and time.monotonic() - self._last_sync >= self.fsync_interval:
+                self.wal.sync()
+                self._last_sync = time.monotonic()
+            try:
+                self.apply()
+            except Exception as e:
+                self.last_error = f"{type(e).__name__}: {e}"
+                logger.error(f"❌ Write-ahead log writer error: {e}")
+            if self._pending:
+                self._retry_delay = min(max(self._retry_delay * 2, 1.0), self.retry_max_seconds)
+            else:
+                self._retry_delay = 0.0
+
+    def get_stats(self) -> Dict[str, Any]:
+        """Backlog and writer counters."""
+        oldest = self.oldest_pending_seconds()
+        return {
+            "running": bool(self._thread and self._thread.is_alive()),
+            "directory": str(self.wal.directory),
+            "backlog_records": self.pending(),
+            "backlog_bytes": self.wal.disk_bytes(),
+            "oldest_pending_seconds": round(oldest, 3) if oldest is not None else None,
+            "last_seq": self.wal.next_seq - 1,
+            "checkpoint": self.wal.committed,
+            "records_applied": self.records_applied,
+            "write_errors": self.write_errors,
+            "corrupt_records": self.wal.corrupt_records,
+            "last_error": self.last_error,
+            "checked_at": datetime.now(timezone.utc).isoformat(),
+        }
@@ -36,8 +36,29 @@ from servers.dashboard import DashboardAPI
 from servers.websocket_server import WebSocketServer
 from infrastructure.task_queue import get_task_queue, TaskPriority
 from infrastructure.performance_metrics import get_metrics_tracker
-from infrastructure.database import get_database
+from infrastructure.database import DatabaseManager, get_database
 from strategies.strategy_base import StrategyStatus
+from core.plugin_hooks import HookContext, LifecycleEvent, PluginHookRegistry
+from core.bootstrap import BootstrapConfig, BootstrapError, bootstrap
+from core.position_reconciler import PositionConsistencyChecker
+from core.reconciliation import Reconciler
+from core.break_even import BreakEvenManager
+from core.order_expiry import OrderExpiryScheduler
+from core.order_chaser import OrderChaser
+from core.clock_sync import ServerClock
+from infrastructure.http_transport import HttpTransportConfig
+from infrastructure.request_timeouts import RequestTimeouts
+from servers.control_api import ControlAPIServer
+from core.event_exporter import EventExporter
+from core.parquet_store import ParquetStore
+from core.tape_recorder import TapeRecorder
+from core.websocket import (
+    ChannelFanout, FeedLatencyMonitor, FeedWatchdog, SignalRClient, SubscriptionManager, UserHubClient,
+)
+from core.tick_validator import TickValidator
+from infrastructure.leader_election import LeaderElector
+from infrastructure.redis_bridge import RedisBridge
+from infrastructure.write_ahead_log import DurableWriter
 
 logger = logging.getLogger(__name__)
 if os.getenv("ACCESS_LOG_VERBOSE", "false").lower() not in ("1", "true", "yes", "on"):
@@ -122,6 +143,9 @@ class AsyncWebhookServer:
             logger.warning(f"⚠️  Failed to initialize scheduled task manager: {e}")
             self.scheduled_tasks = None
         
+        # Embedded control API (started in run() when CONTROL_API_TOKEN is set)
+        self.control_api: Optional[ControlAPIServer] = None
+        
         # Setup CORS for React frontend (before routes)
         if CORS_AVAILABLE:
             self.cors = cors_setup(self.app, defaults={
@@ -240,6 +264,14 @@ class AsyncWebhookServer:
         self.app.router.add_post('/api/settings', self.handle_save_settings)
         self.app.router.add_get('/api/scheduled-tasks', self.handle_get_scheduled_tasks)
         self.app.router.add_get('/api/risk', self.handle_get_risk)
+        self.app.router.add_get('/api/account/report', self.handle_get_account_report)
+        self.app.router.add_get('/api/leadership', self.handle_get_leadership)
+        self.app.router.add_post('/api/leadership/step-down', self.handle_leadership_step_down)
+        self.app.router.add_get('/api/reconciliation', self.handle_get_reconciliation)
+        self.app.router.add_post('/api/reconciliation/run', self.handle_run_reconciliation)
+        self.app.router.add_get('/api/slippage', self.handle_get_slippage)
+        self.app.router.add_get('/api/symbols/trading', self.handle_get_symbol_switches)
+        self.app.router.add_post('/api/symbols/{symbol}/trading', self.handle_set_symbol_switch)
         self.app.router.add_get('/api/notifications', self.handle_get_notifications)
         
         self.app.router.add_get('/api/strategies', self.handle_get_strategies)
@@ -406,22 +438,8 @@ class AsyncWebhookServer:
         Returns server health status and trading bot state.
         """
         try:
-            is_authenticated = self.trading_bot.session_token is not None
-            selected_account = self.trading_bot.selected_account
-            
-            health_data = {
-                "status": "healthy" if is_authenticated else "degraded",
-                "authenticated": is_authenticated,
-                "selected_account": selected_account.get('name') if selected_account else None,
-                "account_id": selected_account.get('id') if selected_account else None,
-                "server_uptime": str(datetime.now() - self.server_start_time).split('.')[0] if self.server_start_time else None,
-                "requests_processed": self.request_count,
-                "webhooks_processed": self.webhook_count,
-                "task_queue": self.task_queue.get_stats(),
-                "timestamp": datetime.now().isoformat()
-            }
-            
-            status_code = 200 if is_authenticated else 503
+            health_data = self.get_health_data()
+            status_code = 200 if health_data["authenticated"] else 503
             return web.json_response(health_data, status=status_code)
         
         except Exception as e:
@@ -431,6 +449,91 @@ class AsyncWebhookServer:
                 status=500
             )
     
+    def get_health_data(self) -> Dict[str, Any]:
+        """Server health status and trading bot state (served by /health and published to Redis)."""
+        is_authenticated = self.trading_bot.session_token is not None
+        selected_account = self.trading_bot.selected_account
+        
+        health_data = {
+            "status": "healthy" if is_authenticated else "degraded",
+            "authenticated": is_authenticated,
+            "selected_account": selected_account.get('name') if selected_account else None,
+            "account_id": selected_account.get('id') if selected_account else None,
+            "server_uptime": str(datetime.now() - self.server_start_time).split('.')[0] if self.server_start_time else None,
+            "requests_processed": self.request_count,
+            "webhooks_processed": self.webhook_count,
+            "task_queue": self.task_queue.get_stats(),
+            "timestamp": datetime.now().isoformat()
+        }
+        leader_elector = getattr(self.trading_bot, 'leader_elector', None)
+        if isinstance(leader_elector, LeaderElector):
+            health_data["leadership"] = leader_elector.get_status()
+        event_exporter = getattr(self.trading_bot, 'event_exporter', None)
+        if isinstance(event_exporter, EventExporter):
+            health_data["event_export"] = event_exporter.get_stats()
+        parquet_store = getattr(self.trading_bot, 'parquet_store', None)
+        if isinstance(parquet_store, ParquetStore):
+            health_data["parquet_store"] = parquet_store.get_stats()
+        tape_recorder = getattr(self.trading_bot, 'tape_recorder', None)
+        if isinstance(tape_recorder, TapeRecorder):
+            health_data["tape_recorder"] = tape_recorder.get_stats()
+        tick_validator = getattr(self.trading_bot, 'tick_validator', None)
+        if isinstance(tick_validator, TickValidator):
+            health_data["tick_validation"] = tick_validator.get_stats()
+        market_hub = getattr(self.trading_bot, '_market_hub', None)
+        if isinstance(market_hub, SignalRClient):
+            health_data["market_hub"] = market_hub.get_stats()
+        feed_watchdog = getattr(self.trading_bot, 'feed_watchdog', None)
+        if isinstance(feed_watchdog, FeedWatchdog):
+            health_data["feed_watchdog"] = feed_watchdog.get_stats()
+        feed_latency = getattr(self.trading_bot, 'feed_latency', None)
+        if isinstance(feed_latency, FeedLatencyMonitor):
+            health_data["feed_latency"] = feed_latency.get_stats()
+        server_clock = getattr(self.trading_bot, 'server_clock', None)
+        if isinstance(server_clock, ServerClock):
+            health_data["server_clock"] = server_clock.get_stats()
+        http_transport = getattr(self.trading_bot, 'http_transport', None)
+        if isinstance(http_transport, HttpTransportConfig) and http_transport.customized:
+            health_data["http_transport"] = http_transport.describe()
+        request_timeouts = getattr(self.trading_bot, 'request_timeouts', None)
+        if isinstance(request_timeouts, RequestTimeouts):
+            health_data["request_timeouts"] = request_timeouts.get_stats()
+        subscription_manager = getattr(self.trading_bot, 'subscription_manager', None)
+        if isinstance(subscription_manager, SubscriptionManager):
+            health_data["market_subscriptions"] = subscription_manager.get_stats()
+        market_event_channels = getattr(self.trading_bot, 'market_event_channels', None)
+        if isinstance(market_event_channels, ChannelFanout) and len(market_event_channels):
+            health_data["market_event_channels"] = market_event_channels.get_stats()
+        user_hub = getattr(self.trading_bot, 'user_hub', None)
+        if isinstance(user_hub, UserHubClient):
+            health_data["user_hub"] = user_hub.get_stats()
+        db = getattr(self.trading_bot, 'db', None)
+        if isinstance(db, DatabaseManager):
+            health_data["database_pools"] = db.get_pool_stats()
+        durable_writer = getattr(self.trading_bot, 'durable_writer', None)
+        if isinstance(durable_writer, DurableWriter):
+            health_data["write_ahead_log"] = durable_writer.get_stats()
+        break_even_manager = getattr(self.trading_bot, 'break_even_manager', None)
+        if isinstance(break_even_manager, BreakEvenManager):
+            health_data["break_even"] = break_even_manager.get_stats()
+        order_expiry = getattr(self.trading_bot, 'order_expiry', None)
+        if isinstance(order_expiry, OrderExpiryScheduler):
+            health_data["order_expiry"] = order_expiry.get_stats()
+        order_chaser = getattr(self.trading_bot, 'order_chaser', None)
+        if isinstance(order_chaser, OrderChaser):
+            health_data["order_chaser"] = order_chaser.get_stats()
+        redis_bridge = getattr(self.trading_bot, 'redis_bridge', None)
+        if isinstance(redis_bridge, RedisBridge):
+            health_data["redis"] = redis_bridge.get_stats()
+        kill_switch = getattr(self.trading_bot, 'kill_switch', None)
+        if kill_switch:
+            health_data["status"] = "halted"
+            health_data["kill_switch"] = kill_switch
+        if self.control_api is not None:
+            health_data["control_api"] = self.control_api.get_stats()
+        
+        return health_data
+    
     async def handle_status(self, request: web.Request) -> web.Response:
         """
         Status endpoint with detailed server information.
@@ -614,10 +717,15 @@ class AsyncWebhookServer:
             order_id = request.match_info.get('order_id')
             if not order_id:
                 return web.json_response({"error": "order_id required"}, status=400)
+            deadline_ms = request.query.get('deadline_ms')
+            try:
+                deadline = float(deadline_ms) / 1000.0 if deadline_ms else None
+            except ValueError:
+                return web.json_response({"error": "deadline_ms must be a number"}, status=400)
             
-            result = await self.dashboard_api.cancel_order(order_id)
+            result = await self.dashboard_api.cancel_order(order_id, deadline=deadline)
             if "error" in result:
-                return web.json_response(result, status=400)
+                return web.json_response(result, status=504 if result.get("deadline_exceeded") else 400)
             
             # Broadcast update via WebSocket
             await self.broadcast_to_websockets({
@@ -1230,8 +1338,12 @@ class AsyncWebhookServer:
             enable_bracket = bool(data.get('enable_bracket'))
             enable_breakeven = bool(data.get('enable_breakeven'))
             time_in_force = data.get('time_in_force')
+            expire_after_secs = data.get('expire_after_secs')
             reduce_only = bool(data.get('reduce_only'))
             account_id = data.get('account_id')
+            chase_to_market = data.get('chase_to_market')
+            if chase_to_market is not None:
+                chase_to_market = str(chase_to_market).lower() in ('true', '1', 'yes', 'on')
             
             # Normalize numeric fields
             def _to_int(value):
@@ -1251,6 +1363,9 @@ class AsyncWebhookServer:
             take_profit_ticks = _to_int(take_profit_ticks)
             limit_price = _to_float(limit_price)
             stop_price = _to_float(stop_price)
+            expire_after_secs = _to_float(expire_after_secs)
+            chase_interval_ms = _to_float(data.get('chase_interval_ms'))
+            chase_max_offset_ticks = _to_int(data.get('chase_max_offset_ticks'))
             stop_loss_price = _to_float(stop_loss_price)
             take_profit_price = _to_float(take_profit_price)
             
@@ -1275,7 +1390,11 @@ class AsyncWebhookServer:
                 enable_bracket=enable_bracket,
                 enable_breakeven=enable_breakeven,
                 time_in_force=time_in_force,
+                expire_after_secs=expire_after_secs,
                 reduce_only=reduce_only,
+                chase_interval_ms=chase_interval_ms,
+                chase_max_offset_ticks=chase_max_offset_ticks,
+                chase_to_market=chase_to_market,
             )
             
             if "error" in result:
@@ -1879,6 +1998,103 @@ class AsyncWebhookServer:
             return web.json_response({"error": "risk data unavailable"}, status=503)
         return web.json_response(snapshot)
     
+    async def handle_get_account_report(self, request: web.Request) -> web.Response:
+        """Return P&L for all tracked accounts consolidated in the reporting currency."""
+        tracker = getattr(self.trading_bot, 'account_tracker', None)
+        if not tracker:
+            return web.json_response({"error": "account tracker unavailable"}, status=503)
+        try:
+            return web.json_response(tracker.get_consolidated_report())
+        except Exception as e:
+            logger.error(f"Error building account report: {e}")
+            return web.json_response({"error": str(e)}, status=500)
+    
+    async def handle_get_leadership(self, request: web.Request) -> web.Response:
+        """Return this instance's role in the failover pair."""
+        leader_elector = getattr(self.trading_bot, 'leader_elector', None)
+        if not isinstance(leader_elector, LeaderElector):
+            return web.json_response({"failover_enabled": False, "is_leader": True})
+        return web.json_response({"failover_enabled": True, **leader_elector.get_status()})
+    
+    async def handle_leadership_step_down(self, request: web.Request) -> web.Response:
+        """Controlled handover: release the lease so the standby takes over."""
+        leader_elector = getattr(self.trading_bot, 'leader_elector', None)
+        if not isinstance(leader_elector, LeaderElector):
+            return web.json_response({"error": "Failover is not enabled"}, status=400)
+        try:
+            data = await request.json() if request.can_read_body else {}
+            hold_off = data.get('hold_off')
+            await leader_elector.step_down(float(hold_off) if hold_off is not None else None)
+            return web.json_response({"success": True, **leader_elector.get_status()})
+        except Exception as e:
+            logger.error(f"Error stepping down from leadership: {e}")
+            return web.json_response({"error": str(e)}, status=500)
+    
+    async def handle_get_reconciliation(self, request: web.Request) -> web.Response:
+        """Reconciler status and recent reports (?limit=N, newest first)."""
+        reconciler = getattr(self.trading_bot, 'reconciler', None)
+        if not isinstance(reconciler, Reconciler):
+            return web.json_response({"error": "reconciliation unavailable"}, status=503)
+        try:
+            limit = int(request.rel_url.query.get('limit', '10'))
+        except ValueError:
+            return web.json_response({"error": "limit must be an integer"}, status=400)
+        reports = [report.to_dict() for report in reversed(reconciler.reports)][:max(0, limit)]
+        return web.json_response({**reconciler.get_status(), "reports": reports})
+    
+    async def handle_get_slippage(self, request: web.Request) -> web.Response:
+        """Slippage report (?symbol=MNQ&strategy=name, ?trades=N adds the latest trades)."""
+        if not hasattr(self.trading_bot, 'slippage_tracker'):
+            return web.json_response({"error": "slippage tracking unavailable"}, status=503)
+        query = request.rel_url.query
+        try:
+            trades = int(query.get('trades', '0'))
+        except ValueError:
+            return web.json_response({"error": "trades must be an integer"}, status=400)
+        symbol, strategy = query.get('symbol') or None, query.get('strategy') or None
+        report = self.trading_bot.get_slippage_report(symbol=symbol, strategy=strategy)
+        if trades > 0:
+            report["trades"] = self.trading_bot.slippage_tracker.get_trades(symbol, strategy, limit=trades)
+        return web.json_response(report)
+    
+    async def handle_run_reconciliation(self, request: web.Request) -> web.Response:
+        """Reconcile orders and positions with the broker now."""
+        if not isinstance(getattr(self.trading_bot, 'reconciler', None), Reconciler):
+            return web.json_response({"error": "reconciliation unavailable"}, status=503)
+        try:
+            data = await request.json() if request.can_read_body else {}
+            account_id = data.get('account_id') or self._get_selected_account_id()
+            if not account_id:
+                return web.json_response({"error": "No account selected"}, status=400)
+            return web.json_response(await self.trading_bot.reconcile(account_id))
+        except Exception as e:
+            logger.error(f"Error running reconciliation: {e}")
+            return web.json_response({"error": str(e)}, status=500)
+    
+    async def handle_get_symbol_switches(self, request: web.Request) -> web.Response:
+        """List per-symbol trading switches."""
+        switches = getattr(self.trading_bot, 'symbol_switches', None)
+        if switches is None:
+            return web.json_response({"error": "symbol switches unavailable"}, status=503)
+        return web.json_response({"switches": switches.get_all(), "disabled": switches.disabled_symbols()})
+    
+    async def handle_set_symbol_switch(self, request: web.Request) -> web.Response:
+        """Enable/disable new orders for a symbol. Body: {"enabled": bool, "reason": str}."""
+        symbol = request.match_info['symbol'].upper()
+        try:
+            data = await request.json()
+        except Exception:
+            return web.json_response({"error": "Invalid JSON body"}, status=400)
+        if not isinstance(data, dict) or not isinstance(data.get('enabled'), bool):
+            return web.json_response({"error": "'enabled' (true/false) is required"}, status=400)
+        try:
+            switch = self.trading_bot.set_symbol_trading(symbol, data['enabled'], data.get('reason'),
+                                                         data.get('updated_by') or 'api')
+            return web.json_response({"success": True, "switch": switch})
+        except Exception as e:
+            logger.error(f"Error setting trading switch for {symbol}: {e}")
+            return web.json_response({"error": str(e)}, status=500)
+    
     async def handle_get_notifications(self, request: web.Request) -> web.Response:
         """Get recent notifications for the selected or requested account."""
         try:
@@ -1898,6 +2114,16 @@ class AsyncWebhookServer:
             logger.error(f"Error getting notifications: {e}")
             return web.json_response({"error": str(e)}, status=500)
     
+    def _on_order_expired(self, context: HookContext) -> None:
+        """ORDER_EXPIRED hook: record the expiration for the account's notifications."""
+        expiration = context.data.get('expiration') or {}
+        message = (f"Order {expiration.get('order_id')} {expiration.get('symbol') or ''} expired after "
+                   f"{expiration.get('expire_after_secs', 0):g}s")
+        if not expiration.get('cancelled'):
+            message += f" but could not be cancelled: {expiration.get('error')}"
+        self._record_notification(context.account_id or expiration.get('account_id'), 'order_expired', message,
+                                  level='info' if expiration.get('cancelled') else 'warning', meta=expiration)
+    
     def _record_notification(self, account_id: str, notification_type: str, message: str, 
                              level: str = "info", meta: Optional[Dict[str, Any]] = None) -> None:
         """Record a notification for the specified account."""
@@ -2238,6 +2464,7 @@ class AsyncWebhookServer:
             symbol = payload.get('symbol', 'MNQ')
             quantity = int(payload.get('quantity', 1))
             price = payload.get('price')
+            signal_price = float(price) if price not in (None, '') else None
             stop_loss = payload.get('stop_loss')
             take_profit = payload.get('take_profit')
             
@@ -2271,14 +2498,16 @@ class AsyncWebhookServer:
                         side='BUY',
                         quantity=quantity,
                         stop_loss_price=stop_loss,
-                        take_profit_price=take_profit
+                        take_profit_price=take_profit,
+                        signal_price=signal_price
                     )
                 else:
                     # Simple market order
                     result = await self.trading_bot.place_market_order(
                         symbol=symbol,
                         side='BUY',
-                        quantity=quantity
+                        quantity=quantity,
+                        signal_price=signal_price
                     )
             elif action in ['SELL', 'SHORT']:
                 # Open short position
@@ -2290,14 +2519,16 @@ class AsyncWebhookServer:
                         side='SELL',
                         quantity=quantity,
                         stop_loss_price=stop_loss,
-                        take_profit_price=take_profit
+                        take_profit_price=take_profit,
+                        signal_price=signal_price
                     )
                 else:
                     # Simple market order
                     result = await self.trading_bot.place_market_order(
                         symbol=symbol,
                         side='SELL',
-                        quantity=quantity
+                        quantity=quantity,
+                        signal_price=signal_price
                     )
             
             logger.info(f"✅ Trade executed: {action} {symbol} x{quantity}")
@@ -2365,6 +2596,33 @@ class AsyncWebhookServer:
         asyncio.create_task(update_balance())
         logger.debug("✅ Started periodic balance update task")
         
+        # Compare local position ledger with broker positions
+        position_checker = getattr(self.trading_bot, 'position_checker', None)
+        if isinstance(position_checker, PositionConsistencyChecker) and \
+                os.getenv('POSITION_CHECK_ENABLED', 'true').lower() in ('true', '1', 'yes', 'on'):
+            await position_checker.start(self._get_selected_account_id)
+        
+        # Reconcile working orders and positions with the broker and the database
+        reconciler = getattr(self.trading_bot, 'reconciler', None)
+        if isinstance(reconciler, Reconciler) and \
+                os.getenv('RECONCILE_ENABLED', 'false').lower() in ('true', '1', 'yes', 'on'):
+            await reconciler.start(self._get_selected_account_id)
+        
+        # Break-even stop moves triggered on the SignalR thread run on this loop
+        break_even_manager = getattr(self.trading_bot, 'break_even_manager', None)
+        if isinstance(break_even_manager, BreakEvenManager):
+            break_even_manager.bind_loop()
+        
+        # Orders cancelled at their expire_after_secs deadline show up as notifications
+        plugin_hooks = getattr(self.trading_bot, 'plugin_hooks', None)
+        if isinstance(plugin_hooks, PluginHookRegistry):
+            plugin_hooks.register(LifecycleEvent.ORDER_EXPIRED, self._on_order_expired, name='webhook_server')
+        
+        # Compete for the leadership lease (warm standby failover)
+        leader_elector = getattr(self.trading_bot, 'leader_elector', None)
+        if isinstance(leader_elector, LeaderElector):
+            await leader_elector.start()
+        
         # Print task queue stats every 5 minutes
         async def print_stats():
             while True:
@@ -2373,10 +2631,59 @@ class AsyncWebhookServer:
         
         asyncio.create_task(print_stats())
         logger.debug("✅ Started periodic stats task")
+        
+        # Publish health to Redis for external dashboards
+        redis_bridge = getattr(self.trading_bot, 'redis_bridge', None)
+        if isinstance(redis_bridge, RedisBridge):
+            async def publish_health():
+                while True:
+                    try:
+                        redis_bridge.publish_health(self.get_health_data())
+                    except Exception as e:
+                        logger.error(f"Redis health publish error: {e}")
+                    await asyncio.sleep(redis_bridge.health_interval)
+            
+            asyncio.create_task(publish_health())
     
     async def stop_background_tasks(self):
         """Stop all background tasks."""
         logger.info("🛑 Stopping background tasks...")
+        position_checker = getattr(self.trading_bot, 'position_checker', None)
+        if isinstance(position_checker, PositionConsistencyChecker):
+            await position_checker.stop()
+        reconciler = getattr(self.trading_bot, 'reconciler', None)
+        if isinstance(reconciler, Reconciler):
+            await reconciler.stop()
+        order_expiry = getattr(self.trading_bot, 'order_expiry', None)
+        if isinstance(order_expiry, OrderExpiryScheduler):
+            await order_expiry.stop()
+        order_chaser = getattr(self.trading_bot, 'order_chaser', None)
+        if isinstance(order_chaser, OrderChaser):
+            await order_chaser.stop()
+        leader_elector = getattr(self.trading_bot, 'leader_elector', None)
+        if isinstance(leader_elector, LeaderElector):
+            await leader_elector.stop()
+        event_exporter = getattr(self.trading_bot, 'event_exporter', None)
+        if isinstance(event_exporter, EventExporter):
+            await asyncio.to_thread(event_exporter.stop)
+        parquet_store = getattr(self.trading_bot, 'parquet_store', None)
+        if isinstance(parquet_store, ParquetStore):
+            await asyncio.to_thread(parquet_store.stop)
+        tape_recorder = getattr(self.trading_bot, 'tape_recorder', None)
+        if isinstance(tape_recorder, TapeRecorder):
+            await asyncio.to_thread(tape_recorder.stop)
+        redis_bridge = getattr(self.trading_bot, 'redis_bridge', None)
+        if isinstance(redis_bridge, RedisBridge):
+            await asyncio.to_thread(redis_bridge.stop)
+        durable_writer = getattr(self.trading_bot, 'durable_writer', None)
+        if isinstance(durable_writer, DurableWriter):
+            await asyncio.to_thread(durable_writer.stop)  # Unapplied records stay on disk for the next start
+        db = getattr(self.trading_bot, 'db', None)
+        if db is not None and getattr(db, 'market_data_writer', None) is not None:
+            await asyncio.to_thread(db.market_data_writer.stop)  # Flush buffered bars/ticks
+        user_hub = getattr(self.trading_bot, 'user_hub', None)
+        if isinstance(user_hub, UserHubClient):
+            await user_hub.stop()
         await self.task_queue.stop(timeout=30.0)
         logger.info("✅ Background tasks stopped")
     
@@ -2424,6 +2731,14 @@ class AsyncWebhookServer:
             logger.info(f"   - Orders: GET /api/orders")
             logger.info(f"   - Strategies: GET /api/strategies")
             
+            if os.getenv('CONTROL_API_TOKEN'):
+                control_api = ControlAPIServer(self.trading_bot, health=self.get_health_data)
+                try:
+                    await control_api.start()
+                    self.control_api = control_api
+                except OSError as e:
+                    logger.error(f"❌ Control API failed to start: {e}")
+            
             # Keep server running
             try:
                 await asyncio.Event().wait()
@@ -2434,11 +2749,17 @@ class AsyncWebhookServer:
             # Cleanup
             logger.info("🧹 Cleaning up...")
             
+            plugin_hooks = getattr(self.trading_bot, 'plugin_hooks', None)
+            if isinstance(plugin_hooks, PluginHookRegistry):
+                await plugin_hooks.dispatch(LifecycleEvent.PRE_SHUTDOWN, self.trading_bot, source="webhook_server")
+            
             # Stop scheduled task manager
             if hasattr(self, 'scheduled_tasks') and self.scheduled_tasks:
                 await self.scheduled_tasks.stop()
             
             await self.stop_background_tasks()
+            if self.control_api is not None:
+                await self.control_api.stop()
             if hasattr(self, 'websocket_server') and self.websocket_server:
                 await self.websocket_server.stop()
             await runner.cleanup()
@@ -2452,113 +2773,21 @@ async def main():
     # Get configuration from environment
     host = os.getenv('WEBHOOK_HOST', '0.0.0.0')
     port = int(os.getenv('WEBHOOK_PORT', '8080'))
-    api_key = os.getenv('PROJECT_X_API_KEY') or os.getenv('TOPSETPX_API_KEY')
-    username = os.getenv('PROJECT_X_USERNAME') or os.getenv('TOPSETPX_USERNAME')
+    config = BootstrapConfig.from_env()
     
-    if not api_key or not username:
+    if not config.api_key or not config.username:
         logger.error("❌ Missing API credentials in environment")
         sys.exit(1)
     
-    # Initialize trading bot
-    logger.info("🤖 Initializing trading bot...")
-    trading_bot = TopStepXTradingBot(api_key=api_key, username=username)
-    
-    # Ensure valid token (auto-refreshes if expired)
-    logger.info("🔐 Ensuring valid authentication token...")
-    max_auth_retries = 3
-    auth_success = False
-    for attempt in range(1, max_auth_retries + 1):
-        # Use _ensure_valid_token() which checks expiration and auto-refreshes
-        if await trading_bot._ensure_valid_token():
-            auth_success = True
-            break
-        else:
-            if attempt < max_auth_retries:
-                wait_time = attempt * 5  # Exponential backoff: 5s, 10s, 15s
-                logger.warning(f"⚠️  Authentication failed (attempt {attempt}/{max_auth_retries}), retrying in {wait_time}s...")
-                await asyncio.sleep(wait_time)
-            else:
-                logger.error("❌ Authentication failed after all retries")
-    
-    if not auth_success:
-        logger.error("❌ Authentication failed - cannot start server")
-        sys.exit(1)
-    
-    # Select account
-    logger.info("📋 Listing accounts...")
-    accounts = await trading_bot.list_accounts()
-    if not accounts:
-        logger.error("❌ No accounts found")
+    # Authenticate, select account, subscribe/warm up the watchlist, reconcile, start strategies
+    logger.info("🤖 Bootstrapping trading session...")
+    try:
+        session = await bootstrap(config)
+    except BootstrapError as e:
+        logger.error(f"❌ Startup failed ({e}) - cannot start server")
         sys.exit(1)
+    trading_bot = session.bot
     
-    # Determine preferred account order: env override > persisted default > first account
-    env_account_id = os.getenv('TOPSTEPX_ACCOUNT_ID')
-    persisted_account_id = None
-    if getattr(trading_bot, 'db', None):
-        try:
-            settings = trading_bot.db.get_dashboard_settings()
-            persisted_account_id = settings.get('defaultAccount') or settings.get('default_account')
-            if persisted_account_id:
-                logger.info(f"📝 Persisted default account from settings: {persisted_account_id}")
-        except Exception as settings_err:
-            logger.warning(f"⚠️  Failed to load persisted settings: {settings_err}")
-    
-    account_choice = env_account_id or persisted_account_id
-    if account_choice:
-        selected_account = next((acc for acc in accounts if str(acc['id']) == str(account_choice)), None)
-        if selected_account:
-            trading_bot.selected_account = selected_account
-            logger.info(f"✅ Selected account: {selected_account['name']} (ID: {selected_account['id']}, source={'env' if env_account_id else 'settings'})")
-            
-            # CRITICAL: Validate that PRAC account is selected
-            account_name_upper = selected_account.get('name', '').upper()
-            if 'PRAC' not in account_name_upper and 'PRACTICE' not in account_name_upper:
-                logger.warning(f"⚠️  WARNING: Selected account '{selected_account['name']}' does not appear to be a PRAC/PRACTICE account!")
-                logger.warning(f"⚠️  Attempting to find PRAC account...")
-                
-                # Try to find PRAC account
-                prac_account = None
-                for acc in accounts:
-                    acc_name = acc.get('name', '').upper()
-                    if 'PRAC' in acc_name or 'PRACTICE' in acc_name:
-                        prac_account = acc
-                        break
-                
-                if prac_account:
-                    trading_bot.selected_account = prac_account
-                    logger.warning(f"✅ Switched to PRAC account: {prac_account['name']} (ID: {prac_account['id']})")
-                else:
-                    logger.error(f"❌ No PRAC account found! Available accounts: {[acc.get('name') for acc in accounts]}")
-                    logger.error(f"❌ Continuing with non-PRAC account, but this may cause issues!")
-        else:
-            logger.error(f"❌ Preferred account ID {account_choice} not found among available accounts")
-            logger.error(f"❌ Available accounts: {[acc.get('name') for acc in accounts]}")
-            sys.exit(1)
-    else:
-        # Fallback: try to find PRAC account first, then use first account
-        prac_account = None
-        for acc in accounts:
-            acc_name = acc.get('name', '').upper()
-            if 'PRAC' in acc_name or 'PRACTICE' in acc_name:
-                prac_account = acc
-                break
-        
-        if prac_account:
-            trading_bot.selected_account = prac_account
-            logger.info(f"✅ Auto-selected PRAC account: {prac_account['name']} (ID: {prac_account['id']})")
-        else:
-            trading_bot.selected_account = accounts[0]
-            logger.warning(f"⚠️  Auto-selected first account (not PRAC): {accounts[0]['name']} (ID: {accounts[0]['id']})")
-            logger.warning(f"⚠️  Available accounts: {[acc.get('name') for acc in accounts]}")
-    
-    # Apply persisted strategy state (if available)
-    if hasattr(trading_bot, 'strategy_manager'):
-        logger.info("💾 Loading persisted strategy states on server startup...")
-        await trading_bot.strategy_manager.apply_persisted_states()
-        logger.info("🚀 Auto-starting enabled strategies on server startup...")
-        await trading_bot.strategy_manager.auto_start_enabled_strategies()
-        logger.info("✅ Strategy initialization complete on server startup")
-
     # Start webhook server
     server = AsyncWebhookServer(trading_bot, host=host, port=port)
     await server.run()
@@ -0,0 +1,250 @@
+"""
+Embedded REST Control API
+
+A small token-protected JSON API running inside the bot process, for
+external dashboards, scripts and phone shortcuts that need the bot's state
+and a few controls without importing the Python package or going through
+the full dashboard server:
+
+    GET    /control/health                      Bot health (authenticated, account, kill switch, strategies)
+    GET    /control/positions                   Open positions (?account_id=)
+    GET    /control/orders                      Open orders (?account_id=)
+    GET    /control/pnl                         Realized/unrealized/total P&L and balance (?account_id=)
+    GET    /control/strategies                  Strategies with their running state
+    POST   /control/strategies/{name}/enable    Start a strategy (refused while the kill switch is engaged)
+    POST   /control/strategies/{name}/disable   Stop a strategy
+    GET    /control/kill-switch                 Kill switch state
+    POST   /control/kill-switch                 Engage: stop strategies, block orders, cancel and flatten
+    DELETE /control/kill-switch                 Release (strategies stay stopped)
+
+Every request needs the token as "Authorization: Bearer <token>" (or an
+X-API-Key header); the server refuses to start without one. It binds to
+localhost by default; put it behind a TLS proxy before exposing it.
+
+Usage:
+    control = ControlAPIServer(bot)                # Or ControlAPIServer(bot, health=server.get_health_data)
+    await control.start()
+    ...
+    await control.stop()
+
+    curl -X POST -H "Authorization: Bearer $CONTROL_API_TOKEN" -d '{"reason": "news"}' \\
+        http://127.0.0.1:8091/control/kill-switch
+
+Configuration:
+- CONTROL_API_TOKEN: Bearer token for every request (required; the async webhook server starts the API when set)
+- CONTROL_API_HOST: Bind address (default 127.0.0.1)
+- CONTROL_API_PORT: Bind port, 0 = any free port (default 8091)
+"""
+
+import hmac
+import json
+import logging
+import os
+import re
+import time
+from typing import Any, Awaitable, Callable, Dict, List, Mapping, Optional, Pattern, Tuple
+
+logger = logging.getLogger(__name__)
+
+PREFIX = '/control'
+MAX_BODY_BYTES = 16 * 1024
+
+Response = Tuple[int, Any]
+Handler = Callable[..., Awaitable[Response]]
+
+
+class ControlAPIServer:
+    """
+    Token-protected HTTP API exposing bot state and controls.
+    """
+
+    def __init__(self, trading_bot: Any, token: Optional[str] = None, host: Optional[str] = None,
+                 port: Optional[int] = None, health: Optional[Callable[[], Dict[str, Any]]] = None):
+        """
+        Initialize server.
+
+        Args:
+            trading_bot: Running TopStepXTradingBot
+            token: Bearer token (env: CONTROL_API_TOKEN)
+            host: Bind address (env: CONTROL_API_HOST)
+            port: Bind port, 0 = any free port (env: CONTROL_API_PORT)
+            health: Extra health data merged into /control/health (e.g. AsyncWebhookServer.get_health_data)
+        """
+        self.trading_bot = trading_bot
+        self.token = token if token is not None else os.getenv('CONTROL_API_TOKEN', '')
+        self.host = host if host is not None else os.getenv('CONTROL_API_HOST', '127.0.0.1')
+        self.port = port if port is not None else int(os.getenv('CONTROL_API_PORT', '8091'))
+        self.health = health
+        self._runner: Any = None
+        self.started_at: Optional[float] = None
+        self.requests = 0
+        self.unauthorized = 0
+        self._routes: List[Tuple[str, Pattern[str], Handler]] = [
+            ('GET', re.compile(r'/health'), self._health),
+            ('GET', re.compile(r'/positions'), self._positions),
+            ('GET', re.compile(r'/orders'), self._orders),
+            ('GET', re.compile(r'/pnl'), self._pnl),
+            ('GET', re.compile(r'/strategies'), self._strategies),
+            ('POST', re.compile(r'/strategies/(?P<name>[^/]+)/enable'), self._enable_strategy),
+            ('POST', re.compile(r'/strategies/(?P<name>[^/]+)/disable'), self._disable_strategy),
+            ('GET', re.compile(r'/kill-switch'), self._kill_switch_state),
+            ('POST', re.compile(r'/kill-switch'), self._engage_kill_switch),
+            ('DELETE', re.compile(r'/kill-switch'), self._release_kill_switch),
+        ]
+
+    @property
+    def running(self) -> bool:
+        return self._runner is not None
+
+    # ---------------------------
+    # Request handling
+    # ---------------------------
+    def _authorized(self, headers: Mapping[str, str]) -> bool:
+        supplied = headers.get('X-API-Key')
+        authorization = headers.get('Authorization') or ''
+        if supplied is None and authorization.startswith('Bearer '):
+            supplied = authorization[len('Bearer '):].strip()
+        if not supplied or not self.token:
+            return False
+        return hmac.compare_digest(supplied.encode(), self.token.encode())
+
+    async def dispatch(self, method: str, path: str, headers: Optional[Mapping[str, str]] = None,
+                       query: Optional[Mapping[str, str]] = None, body: bytes = b'') -> Response:
+        """
+        Authenticate and run one request.
+
+        Returns:
+            Tuple: (HTTP status, JSON response)
+        """
+        self.requests += 1
+        if not self._authorized(headers or {}):
+            self.unauthorized += 1
+            return 401, {"error": "Unauthorized"}
+        route = path[len(PREFIX):] if path.startswith(PREFIX + '/') else None
+        allowed = []
+        for route_method, pattern, handler in self._routes:
+            match = pattern.fullmatch(route.rstrip('/')) if route else None
+            if not match:
+                continue
+            if route_method != method.upper():
+                allowed.append(route_method)
+                continue
+            try:
+                payload = json.loads(body) if body else {}
+            except (UnicodeDecodeError, ValueError):
+                return 400, {"error": "Body must be JSON"}
+            if not isinstance(payload, dict):
+                return 400, {"error": "Body must be a JSON object"}
+            try:
+                return await handler(query=dict(query or {}), payload=payload, **match.groupdict())
+            except Exception as e:
+                logger.error(f"❌ Control API {method} {path} failed: {e}")
+                return 500, {"error": str(e)}
+        if allowed:
+            return 405, {"error": f"Method not allowed (use {', '.join(allowed)})"}
+        return 404, {"error": "Not found"}
+
+    def _account_id(self, query: Dict[str, str]) -> Optional[str]:
+        selected = self.trading_bot.selected_account
+        return query.get('account_id') or (str(selected['id']) if selected else None)
+
+    async def _health(self, query: Dict[str, str], payload: Dict[str, Any]) -> Response:
+        bot = self.trading_bot
+        selected = bot.selected_account
+        strategy_manager = getattr(bot, 'strategy_manager', None)
+        health = {
+            "status": "halted" if bot.kill_switch else "healthy" if bot.session_token else "degraded",
+            "authenticated": bot.session_token is not None,
+            "account_id": selected.get('id') if selected else None,
+            "selected_account": selected.get('name') if selected else None,
+            "kill_switch": bot.kill_switch,
+            "strategies_running": sorted(strategy_manager.active_strategies) if strategy_manager else [],
+            "uptime_secs": round(time.monotonic() - self.started_at, 1) if self.started_at else None,
+        }
+        if self.health is not None:
+            health = {**self.health(), **health}
+        return 200, health
+
+    async def _positions(self, query: Dict[str, str], payload: Dict[str, Any]) -> Response:
+        return 200, await self.trading_bot.get_open_positions(account_id=self._account_id(query))
+
+    async def _orders(self, query: Dict[str, str], payload: Dict[str, Any]) -> Response:
+        return 200, await self.trading_bot.get_open_orders(account_id=self._account_id(query))
+
+    async def _pnl(self, query: Dict[str, str], payload: Dict[str, Any]) -> Response:
+        state = self.trading_bot.account_tracker.get_state(self._account_id(query))
+        return 200, {key: state.get(key) for key in (
+            'account_id', 'account_name', 'currency', 'starting_balance', 'current_balance',
+            'realized_pnl', 'unrealized_pnl', 'total_pnl', 'last_update')}
+
+    async def _strategies(self, query: Dict[str, str], payload: Dict[str, Any]) -> Response:
+        return 200, self.trading_bot.strategy_manager.get_strategy_summaries()
+
+    async def _enable_strategy(self, query: Dict[str, str], payload: Dict[str, Any], name: str) -> Response:
+        if self.trading_bot.kill_switch:
+            return 409, {"error": "Kill switch engaged: release it before enabling strategies"}
+        success, message = await self.trading_bot.strategy_manager.start_strategy(name, symbols=payload.get('symbols'))
+        logger.info(f"📋 Control API: enable {name}: {message}")
+        return (200 if success else 400), {"success": success, "message": message}
+
+    async def _disable_strategy(self, query: Dict[str, str], payload: Dict[str, Any], name: str) -> Response:
+        success, message = await self.trading_bot.strategy_manager.stop_strategy(name)
+        logger.info(f"📋 Control API: disable {name}: {message}")
+        return (200 if success else 400), {"success": success, "message": message}
+
+    async def _kill_switch_state(self, query: Dict[str, str], payload: Dict[str, Any]) -> Response:
+        kill_switch = self.trading_bot.kill_switch
+        return 200, {**kill_switch, "engaged": True} if kill_switch else {"engaged": False}
+
+    async def _engage_kill_switch(self, query: Dict[str, str], payload: Dict[str, Any]) -> Response:
+        reason = str(payload.get('reason') or 'control API')
+        return 200, await self.trading_bot.engage_kill_switch(reason=reason, engaged_by='control_api')
+
+    async def _release_kill_switch(self, query: Dict[str, str], payload: Dict[str, Any]) -> Response:
+        return 200, self.trading_bot.release_kill_switch(released_by='control_api')
+
+    async def _handle(self, request: Any) -> Any:
+        from aiohttp import web
+        status, body = await self.dispatch(request.method, request.path, request.headers, request.query,
+                                           await request.read())
+        return web.json_response(body, status=status, dumps=lambda data: json.dumps(data, default=str))
+
+    # ---------------------------
+    # Lifecycle
+    # ---------------------------
+    async def start(self) -> None:
+        """Bind and start serving (self.port holds the bound port afterwards)."""
+        if self.running:
+            return
+        if not self.token:
+            raise ValueError("CONTROL_API_TOKEN must be set to start the control API")
+        from aiohttp import web
+        app = web.Application(client_max_size=MAX_BODY_BYTES)
+        app.router.add_route('*', PREFIX + '/{tail:.*}', self._handle)
+        runner = web.AppRunner(app)
+        await runner.setup()
+        site = web.TCPSite(runner, self.host, self.port)
+        try:
+            await site.start()
+        except Exception:
+            await runner.cleanup()
+            raise
+        if runner.addresses:
+            self.port = runner.addresses[0][1]
+        self._runner = runner
+        self.started_at = time.monotonic()
+        logger.info(f"🚀 Control API listening on http://{self.host}:{self.port}{PREFIX}")
+
+    async def stop(self) -> None:
+        runner, self._runner = self._runner, None
+        if runner is not None:
+            await runner.cleanup()
+            logger.info("🛑 Control API stopped")
+
+    def get_stats(self) -> Dict[str, Any]:
+        return {
+            "running": self.running,
+            "bind": f"{self.host}:{self.port}",
+            "requests": self.requests,
+            "unauthorized": self.unauthorized,
+        }
@@ -19,6 +19,7 @@ from collections import defaultdict
 sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
 
 from auth import require_auth, get_cors_headers
+from core.time_in_force import time_in_force_name
 
 logger = logging.getLogger(__name__)
 
@@ -722,7 +723,7 @@ class DashboardAPI:
                 stop_loss = bracket.get('stopLossPrice') or order.get('stopLossPrice')
                 take_profit = bracket.get('takeProfitPrice') or order.get('takeProfitPrice')
                 
-                tif = order.get('timeInForce') or order.get('time_in_force') or 'DAY'
+                tif = time_in_force_name(order.get('timeInForce', order.get('time_in_force'))) or 'DAY'
                 
                 formatted_orders.append({
                     "id": order.get('id'),
@@ -820,8 +821,8 @@ class DashboardAPI:
                             "quantity": float(trade.get('quantity', 0)),
                             "price": float(trade.get('entry_price', 0)),
                             "exit_price": float(trade.get('exit_price', 0)),
-                            "pnl": float(trade.get('pnl', 0)),
-                            "fees": 0.0,  # TopStepX doesn't provide fees in order history
+                            "pnl": float(trade.get('gross_pnl', trade.get('pnl', 0))),
+                            "fees": float(trade.get('fees', 0)),  # Modeled: order history has no fees
                             "net_pnl": float(trade.get('pnl', 0)),
                             "status": "filled",
                             "strategy": trade.get('strategy'),
@@ -955,10 +956,10 @@ class DashboardAPI:
             history = await self.get_trade_history()
             
             total_trades = len(history)
-            winning_trades = len([t for t in history if t.get('pnl', 0) > 0])
-            losing_trades = len([t for t in history if t.get('pnl', 0) < 0])
+            winning_trades = len([t for t in history if t.get('net_pnl', 0) > 0])
+            losing_trades = len([t for t in history if t.get('net_pnl', 0) < 0])
             
-            total_pnl = sum(t.get('pnl', 0) for t in history)
+            total_pnl = sum(t.get('net_pnl', 0) for t in history)  # Net of commissions and fees
             win_rate = (winning_trades / total_trades * 100) if total_trades > 0 else 0
             
             return {
@@ -967,8 +968,9 @@ class DashboardAPI:
                 "losing_trades": losing_trades,
                 "win_rate": round(win_rate, 2),
                 "total_pnl": total_pnl,
-                "avg_win": sum(t.get('pnl', 0) for t in history if t.get('pnl', 0) > 0) / max(winning_trades, 1),
-                "avg_loss": sum(t.get('pnl', 0) for t in history if t.get('pnl', 0) < 0) / max(losing_trades, 1)
+                "total_fees": round(sum(t.get('fees', 0) for t in history), 2),
+                "avg_win": sum(t.get('net_pnl', 0) for t in history if t.get('net_pnl', 0) > 0) / max(winning_trades, 1),
+                "avg_loss": sum(t.get('net_pnl', 0) for t in history if t.get('net_pnl', 0) < 0) / max(losing_trades, 1)
             }
         except Exception as e:
             logger.error(f"Error getting performance stats: {e}")
@@ -1051,16 +1053,16 @@ class DashboardAPI:
                 }
             
             total_trades = len(strategy_trades)
-            winning_trades = [t for t in strategy_trades if t.get('pnl', 0) > 0]
-            losing_trades = [t for t in strategy_trades if t.get('pnl', 0) < 0]
+            winning_trades = [t for t in strategy_trades if t.get('net_pnl', 0) > 0]
+            losing_trades = [t for t in strategy_trades if t.get('net_pnl', 0) < 0]
             
-            total_pnl = sum(t.get('pnl', 0) for t in strategy_trades)
+            total_pnl = sum(t.get('net_pnl', 0) for t in strategy_trades)
             win_rate = (len(winning_trades) / total_trades * 100) if total_trades > 0 else 0
             
-            avg_win = sum(t.get('pnl', 0) for t in winning_trades) / max(len(winning_trades), 1)
-            avg_loss = abs(sum(t.get('pnl', 0) for t in losing_trades) / max(len(losing_trades), 1))
+            avg_win = sum(t.get('net_pnl', 0) for t in winning_trades) / max(len(winning_trades), 1)
+            avg_loss = abs(sum(t.get('net_pnl', 0) for t in losing_trades) / max(len(losing_trades), 1))
             
-            pnl_values = [t.get('pnl', 0) for t in strategy_trades]
+            pnl_values = [t.get('net_pnl', 0) for t in strategy_trades]
             best_trade = max(pnl_values) if pnl_values else 0.0
             worst_trade = min(pnl_values) if pnl_values else 0.0
             
@@ -1265,7 +1267,11 @@ class DashboardAPI:
         enable_bracket: bool = False,
         enable_breakeven: bool = False,
         time_in_force: Optional[str] = None,
+        expire_after_secs: Optional[float] = None,
         reduce_only: bool = False,
+        chase_interval_ms: Optional[float] = None,
+        chase_max_offset_ticks: Optional[int] = None,
+        chase_to_market: Optional[bool] = None,
     ) -> Dict[str, Any]:
         """Place a new order using the trading bot helper methods."""
         try:
@@ -1296,6 +1302,19 @@ class DashboardAPI:
                     take_profit_ticks=take_profit_ticks if enable_bracket else take_profit_ticks,
                     order_type=normalized_type,
                     limit_price=limit_price,
+                    time_in_force=time_in_force,
+                    expire_after_secs=expire_after_secs,
+                )
+            elif normalized_type == "pegged":
+                result = await self.trading_bot.place_pegged_order(
+                    symbol=symbol,
+                    side=side,
+                    quantity=quantity,
+                    account_id=account_id,
+                    limit_price=limit_price,
+                    interval_ms=chase_interval_ms,
+                    max_offset_ticks=chase_max_offset_ticks,
+                    convert_to_market=chase_to_market,
                 )
             elif normalized_type == "stop":
                 if stop_price is None:
@@ -1312,6 +1331,8 @@ class DashboardAPI:
                         take_profit_price=float(take_profit_price),
                         account_id=account_id,
                         enable_breakeven=enable_breakeven,
+                        time_in_force=time_in_force,
+                        expire_after_secs=expire_after_secs,
                     )
                 else:
                     result = await self.trading_bot.place_stop_order(
@@ -1320,6 +1341,8 @@ class DashboardAPI:
                         quantity=quantity,
                         stop_price=float(stop_price),
                         account_id=account_id,
+                        time_in_force=time_in_force,
+                        expire_after_secs=expire_after_secs,
                     )
             else:
                 return {"error": f"Unsupported order_type '{order_type}'"}
@@ -1341,9 +1364,16 @@ class DashboardAPI:
                     "enable_bracket": enable_bracket,
                     "enable_breakeven": enable_breakeven,
                     "time_in_force": time_in_force,
+                    "expire_after_secs": expire_after_secs,
                     "reduce_only": reduce_only,
                     "account_id": account_id,
                 })
+                if normalized_type == "pegged":
+                    result["requested"].update({
+                        "chase_interval_ms": chase_interval_ms,
+                        "chase_max_offset_ticks": chase_max_offset_ticks,
+                        "chase_to_market": chase_to_market,
+                    })
             
             return result
         except Exception as e:
@@ -1351,10 +1381,10 @@ class DashboardAPI:
             logger.exception(e)
             return {"error": str(e)}
     
-    async def cancel_order(self, order_id: str) -> Dict[str, Any]:
-        """Cancel an order"""
+    async def cancel_order(self, order_id: str, deadline: Optional[float] = None) -> Dict[str, Any]:
+        """Cancel an order (within deadline seconds including retries, if given)"""
         try:
-            result = await self.trading_bot.cancel_order(order_id)
+            result = await self.trading_bot.cancel_order(order_id, deadline=deadline)
             return result
         except Exception as e:
             logger.error(f"Error canceling order: {e}")
@@ -496,6 +496,7 @@ async def main():
         return
     
     bot = TopStepXTradingBot(api_key=api_key, username=username)
+    bot.claim_artifact_dirs()
     
     # Authenticate
     if not await bot.authenticate():
@@ -0,0 +1,330 @@
+"""
+gRPC Signal Service
+
+Lets other processes (e.g. signal generation on another machine) submit
+strategy signals to this bot and follow its fills and positions, so signal
+generation and execution can run as separate services. The service is
+defined in servers/proto/signals.proto (package tradebot.v1):
+
+- SubmitSignal: the signal is routed like any strategy signal, through the
+  RiskManager gate (entries dropped while the account is halted, exits
+  always pass) to the OrderExecutor; with from_core() it is published on a
+  TradingCore's engine so every gate applies
+- StreamFills / StreamPositions: user hub fills and position changes,
+  filtered by account and symbol; StreamPositions starts with the account's
+  open positions. Each stream has its own bounded channel that drops the
+  oldest update when a client falls behind, so a slow client never stalls
+  the hub. Streams need USER_HUB_ENABLED
+
+The .proto is compiled when the server starts (grpcio-tools), so clients
+in any language generate their stubs from the same file. Every call needs
+"authorization: Bearer <token>" metadata; set GRPC_TLS_CERT/GRPC_TLS_KEY
+before listening beyond localhost.
+
+Usage:
+    service = GrpcSignalServer(executor, risk=risk, user_hub=lambda: bot.user_hub)
+    await service.start()
+    ...
+    await service.stop()
+
+Configuration:
+- GRPC_API_TOKEN: Bearer token for every call (required)
+- GRPC_HOST: Bind address (default 127.0.0.1)
+- GRPC_PORT: Bind port, 0 = any free port (default 50051)
+- GRPC_TLS_CERT / GRPC_TLS_KEY: PEM server certificate and key (default: plaintext)
+- GRPC_STREAM_CAPACITY: Updates buffered per stream before the oldest are dropped (default 1000)
+"""
+
+import hmac
+import logging
+import os
+from datetime import datetime, timezone
+from typing import Any, AsyncIterator, Callable, Dict, Optional, Sequence, Tuple, Union
+
+from core.strategy_engine.strategy import Direction, Signal
+from core.volume_profile import normalize_symbol
+from core.websocket.channels import OverflowPolicy
+from core.websocket.user_hub import PositionUpdate, UserEvent, UserTrade
+
+logger = logging.getLogger(__name__)
+
+PROTO_PATH = 'servers/proto/signals.proto'  # Relative to the repository root (on sys.path)
+SERVICE_NAME = 'tradebot.v1.SignalRouter'
+
+# Proto Direction enum values
+DIRECTIONS: Dict[int, Direction] = {1: Direction.LONG, 2: Direction.SHORT, 3: Direction.FLAT}
+
+
+class SignalRejected(ValueError):
+    """Submitted signal that can't be routed."""
+
+
+def signal_from_message(message: Any) -> Signal:
+    """
+    Signal from a tradebot.v1.Signal message.
+
+    Raises:
+        SignalRejected: Missing strategy/symbol, unknown direction, bad confidence or timestamp
+    """
+    def optional(name: str) -> Optional[float]:
+        return getattr(message, name) if message.HasField(name) else None
+
+    if not message.strategy_id:
+        raise SignalRejected("strategy_id is required")
+    if not message.symbol:
+        raise SignalRejected("symbol is required")
+    direction = DIRECTIONS.get(message.direction)
+    if direction is None:
+        raise SignalRejected(f"direction must be LONG, SHORT or FLAT (got {message.direction})")
+    confidence = optional('confidence')
+    if confidence is not None and not 0.0 <= confidence <= 1.0:
+        raise SignalRejected(f"confidence must be between 0 and 1 (got {confidence})")
+    stop, target = optional('stop'), optional('target')
+    if direction is not Direction.FLAT and not message.price and (stop is not None or target is not None):
+        raise SignalRejected("stop/target need a price")
+    timestamp = datetime.now(timezone.utc)
+    if message.timestamp:
+        try:
+            timestamp = datetime.fromisoformat(message.timestamp.replace('Z', '+00:00'))
+        except ValueError:
+            raise SignalRejected(f"timestamp should be ISO 8601 (got {message.timestamp!r})")
+        if timestamp.tzinfo is None:
+            timestamp = timestamp.replace(tzinfo=timezone.utc)
+    return Signal(strategy_id=message.strategy_id, symbol=normalize_symbol(message.symbol), direction=direction,
+                  price=message.price, timestamp=timestamp, confidence=confidence if confidence is not None else 1.0,
+                  stop=stop, target=target, timeframe=message.timeframe or None, reason=message.reason or "gRPC")
+
+
+def fill_fields(trade: UserTrade) -> Dict[str, Any]:
+    """tradebot.v1.Fill fields of a user hub fill (unset optionals left out)."""
+    fields = {
+        "account_id": trade.account_id, "trade_id": trade.trade_id, "order_id": trade.order_id,
+        "symbol": trade.symbol, "contract_id": trade.contract_id, "side": trade.side or '', "size": trade.size,
+        "price": trade.price, "profit_and_loss": trade.profit_and_loss, "fees": trade.fees,
+        "voided": trade.voided, "timestamp": trade.timestamp.isoformat(),
+    }
+    return {key: value for key, value in fields.items() if value is not None}
+
+
+def position_fields(position: PositionUpdate) -> Dict[str, Any]:
+    """tradebot.v1.Position fields of a user hub position update (unset optionals left out)."""
+    fields = {
+        "account_id": position.account_id, "position_id": position.position_id, "symbol": position.symbol,
+        "contract_id": position.contract_id, "net_quantity": position.net_quantity,
+        "average_price": position.average_price if position.size else None,
+        "timestamp": position.timestamp.isoformat(),
+    }
+    return {key: value for key, value in fields.items() if value is not None}
+
+
+def stream_matches(request: Any, event: UserEvent) -> bool:
+    """Whether an event passes a StreamRequest's account/symbol filters."""
+    if request.account_id and event.account_id != request.account_id:
+        return False
+    return not request.symbol or getattr(event, 'symbol', '') == normalize_symbol(request.symbol)
+
+
+class GrpcSignalServer:
+    """
+    tradebot.v1.SignalRouter service: signal submission and fill/position streams.
+    """
+
+    def __init__(self, executor: Any = None, risk: Any = None, publish: Optional[Callable[[Signal], bool]] = None,
+                 user_hub: Any = None, token: Optional[str] = None, host: Optional[str] = None,
+                 port: Optional[int] = None, tls_cert: Optional[str] = None, tls_key: Optional[str] = None,
+                 stream_capacity: Optional[int] = None):
+        """
+        Initialize service.
+
+        Args:
+            executor: OrderExecutor submitting the orders of accepted signals
+            risk: RiskManager whose gate entries must pass
+            publish: Route signals here instead of risk/executor (False = dropped by a gate)
+            user_hub: UserHubClient for the streams, or a function returning the current one (e.g.
+                lambda: bot.user_hub, since the bot starts its hub lazily)
+            token: Bearer token (env: GRPC_API_TOKEN)
+            host: Bind address (env: GRPC_HOST)
+            port: Bind port, 0 = any free port (env: GRPC_PORT)
+            tls_cert: PEM certificate path (env: GRPC_TLS_CERT)
+            tls_key: PEM key path (env: GRPC_TLS_KEY)
+            stream_capacity: Updates buffered per stream (env: GRPC_STREAM_CAPACITY)
+        """
+        if executor is None and publish is None:
+            raise ValueError("GrpcSignalServer needs an executor or a publish function")
+        self.executor = executor
+        self.risk = risk
+        self.publish = publish or self._route
+        self.user_hub = user_hub
+        self.token = token if token is not None else os.getenv('GRPC_API_TOKEN', '')
+        self.host = host if host is not None else os.getenv('GRPC_HOST', '127.0.0.1')
+        self.port = port if port is not None else int(os.getenv('GRPC_PORT', '50051'))
+        self.tls_cert = tls_cert if tls_cert is not None else os.getenv('GRPC_TLS_CERT', '')
+        self.tls_key = tls_key if tls_key is not None else os.getenv('GRPC_TLS_KEY', '')
+        self.stream_capacity = (stream_capacity if stream_capacity is not None
+                                else int(os.getenv('GRPC_STREAM_CAPACITY', '1000')))
+        self._server: Any = None
+        self._protos: Any = None
+        self.signals_accepted = 0
+        self.signals_blocked = 0
+        self.signals_rejected = 0
+        self.unauthenticated = 0
+        self.active_streams = 0
+        self.last_error: Optional[str] = None
+
+    @classmethod
+    def from_core(cls, core: Any, **kwargs: Any) -> 'GrpcSignalServer':
+        """Service publishing signals on a TradingCore's engine (risk, pause, portfolio and filter gates)."""
+        return cls(executor=core.executor, risk=core.risk, publish=core.engine.publish, **kwargs)
+
+    @property
+    def running(self) -> bool:
+        return self._server is not None
+
+    # ---------------------------
+    # Calls
+    # ---------------------------
+    def _route(self, signal: Signal) -> bool:
+        if self.risk is not None and not self.risk.allows(signal):
+            return False
+        self.executor.on_signal(signal)
+        return True
+
+    def authorized(self, metadata: Optional[Sequence[Tuple[str, Union[str, bytes]]]]) -> bool:
+        """Whether call metadata carries the bearer token."""
+        authorization = dict(metadata or ()).get('authorization') or ''
+        if not self.token or not isinstance(authorization, str) or not authorization.startswith('Bearer '):
+            return False
+        return hmac.compare_digest(authorization[len('Bearer '):].strip().encode(), self.token.encode())
+
+    async def _authorize(self, context: Any) -> None:
+        if not self.authorized(context.invocation_metadata()):
+            import grpc
+            self.unauthenticated += 1
+            await context.abort(grpc.StatusCode.UNAUTHENTICATED, "Missing or wrong bearer token")
+
+    def submit(self, message: Any) -> Tuple[bool, str]:
+        """
+        Route a tradebot.v1.Signal.
+
+        Returns:
+            Tuple: (accepted, error); error is empty when accepted
+
+        Raises:
+            SignalRejected: Invalid signal
+        """
+        try:
+            signal = signal_from_message(message)
+        except SignalRejected as e:
+            self.signals_rejected += 1
+            self.last_error = str(e)
+            raise
+        if not self.publish(signal):
+            self.signals_blocked += 1
+            logger.warning(f"⚠️ gRPC signal {signal.strategy_id} {signal.symbol} {signal.direction.value} blocked")
+            return False, "Signal blocked (risk halt or gate)"
+        self.signals_accepted += 1
+        logger.info(f"📨 gRPC signal routed: {signal.strategy_id} {signal.symbol} {signal.direction.value}")
+        return True, ''
+
+    async def SubmitSignal(self, request: Any, context: Any) -> Any:
+        await self._authorize(context)
+        try:
+            accepted, error = self.submit(request)
+        except SignalRejected as e:
+            import grpc
+            await context.abort(grpc.StatusCode.INVALID_ARGUMENT, str(e))
+        return self._protos.SubmitSignalReply(accepted=accepted, error=error)
+
+    def _hub(self) -> Any:
+        return self.user_hub() if callable(self.user_hub) else self.user_hub
+
+    async def updates(self, request: Any, event_type: type, snapshot: Sequence[UserEvent] = ()) -> AsyncIterator[Any]:
+        """
+        Snapshot events, then hub events of one type that pass the request's filters.
+
+        Raises:
+            LookupError: No user hub running
+        """
+        hub = self._hub()
+        if hub is None:
+            raise LookupError("User hub is not running (USER_HUB_ENABLED)")
+        stream = hub.events(event_type, capacity=self.stream_capacity, policy=OverflowPolicy.DROP_OLDEST)
+        self.active_streams += 1
+        try:
+            for event in snapshot:
+                if stream_matches(request, event):
+                    yield event
+            async for event in stream:
+                if stream_matches(request, event):
+                    yield event
+        finally:
+            self.active_streams -= 1
+            stream.close()
+
+    async def _stream(self, request: Any, context: Any, event_type: type, message: Callable[[Any], Any],
+                      snapshot: Callable[[Any], Sequence[UserEvent]] = lambda hub: ()) -> AsyncIterator[Any]:
+        await self._authorize(context)
+        hub = self._hub()
+        try:
+            async for event in self.updates(request, event_type, snapshot(hub) if hub is not None else ()):
+                yield message(event)
+        except LookupError as e:
+            import grpc
+            await context.abort(grpc.StatusCode.UNAVAILABLE, str(e))
+
+    def StreamFills(self, request: Any, context: Any) -> AsyncIterator[Any]:
+        return self._stream(request, context, UserTrade, lambda trade: self._protos.Fill(**fill_fields(trade)))
+
+    def StreamPositions(self, request: Any, context: Any) -> AsyncIterator[Any]:
+        def snapshot(hub: Any) -> Sequence[UserEvent]:
+            return hub.positions(request.account_id) if request.account_id else ()
+        return self._stream(request, context, PositionUpdate,
+                            lambda position: self._protos.Position(**position_fields(position)), snapshot)
+
+    # ---------------------------
+    # Lifecycle
+    # ---------------------------
+    async def start(self) -> None:
+        """Compile the proto, bind and start serving (self.port holds the bound port afterwards)."""
+        if self.running:
+            return
+        if not self.token:
+            raise ValueError("GRPC_API_TOKEN must be set to start the gRPC signal service")
+        import grpc
+        self._protos, services = grpc.protos_and_services(PROTO_PATH)
+        server = grpc.aio.server()
+        services.add_SignalRouterServicer_to_server(self, server)
+        address = f"{self.host}:{self.port}"
+        if self.tls_cert or self.tls_key:
+            if not (self.tls_cert and self.tls_key):
+                raise ValueError("GRPC_TLS_CERT and GRPC_TLS_KEY must be set together")
+            with open(self.tls_key, 'rb') as key, open(self.tls_cert, 'rb') as cert:
+                credentials = grpc.ssl_server_credentials([(key.read(), cert.read())])
+            self.port = server.add_secure_port(address, credentials)
+        else:
+            if self.host not in ('127.0.0.1', 'localhost', '::1'):
+                logger.warning(f"⚠️ gRPC signal service on {self.host} without TLS: tokens are sent in plaintext")
+            self.port = server.add_insecure_port(address)
+        await server.start()
+        self._server = server
+        logger.info(f"🚀 gRPC {SERVICE_NAME} listening on {self.host}:{self.port}"
+                    f"{' (TLS)' if self.tls_cert else ''}")
+
+    async def stop(self, grace: float = 5.0) -> None:
+        """Stop serving; open streams get `grace` seconds to finish."""
+        server, self._server = self._server, None
+        if server is not None:
+            await server.stop(grace)
+            logger.info("🛑 gRPC signal service stopped")
+
+    def get_stats(self) -> Dict[str, Any]:
+        return {
+            "running": self.running,
+            "bind": f"{self.host}:{self.port}",
+            "signals_accepted": self.signals_accepted,
+            "signals_blocked": self.signals_blocked,
+            "signals_rejected": self.signals_rejected,
+            "unauthenticated": self.unauthenticated,
+            "active_streams": self.active_streams,
+            "last_error": self.last_error,
+        }
@@ -109,6 +109,7 @@ async def _bootstrap(args: argparse.Namespace) -> None:
 
     # Initialize bot
     bot = TopStepXTradingBot(api_key=api_key, username=username)
+    bot.claim_artifact_dirs()
 
     # Authenticate
     if not await bot.authenticate():
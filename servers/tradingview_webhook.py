@@ -0,0 +1,304 @@
+"""
+Built-in TradingView Webhook Server
+
+Listens for TradingView alert webhooks, checks the shared secret, turns the
+alert into a strategy_engine Signal and routes it like any strategy signal:
+through the RiskManager gate (entries are dropped while the account is
+halted, exits always pass) to the OrderExecutor, which sizes and submits the
+orders. With from_core(), alerts are published on a TradingCore's engine
+instead, so pause(), the portfolio and every other gate apply too.
+
+TradingView cannot set request headers, so the secret is a field of the
+alert message (an X-Webhook-Secret header is accepted for other senders).
+The server refuses to start without a secret.
+
+Alert message (JSON; TradingView placeholders shown):
+    {"secret": "...", "ticker": "{{ticker}}", "action": "{{strategy.order.action}}",
+     "price": {{close}}, "stop": 21010.25, "target": 21060.0, "strategy": "orb",
+     "market_position": "{{strategy.market_position}}", "interval": "{{interval}}",
+     "time": "{{timenow}}", "comment": "{{strategy.order.comment}}"}
+
+- action: buy/long, sell/short, or close/exit/flat/flatten; a
+  market_position of "flat" makes any action an exit
+- ticker: root (MNQ), continuous (MNQ1!), dated (MNQZ2025) or with an
+  exchange prefix (CME_MINI:MNQ1!)
+- stop/target need a price (bracket ticks are measured from it); order
+  size comes from the executor (allocation or TRADING_CORE_QUANTITY)
+
+Usage:
+    webhook = TradingViewWebhookServer(executor, risk=risk)   # Or TradingViewWebhookServer.from_core(core)
+    await webhook.start()                                     # POST http://host:port/tradingview
+    ...
+    await webhook.stop()
+
+Configuration:
+- TRADINGVIEW_WEBHOOK_SECRET: Shared secret alerts must carry (required)
+- TRADINGVIEW_WEBHOOK_HOST: Bind address (default 0.0.0.0)
+- TRADINGVIEW_WEBHOOK_PORT: Bind port, 0 = any free port (default 8090)
+- TRADINGVIEW_WEBHOOK_PATH: Alert URL path (default /tradingview)
+- TRADINGVIEW_WEBHOOK_STRATEGY: Strategy ID of alerts without one (default tradingview)
+"""
+
+import hmac
+import json
+import logging
+import os
+import re
+import threading
+from datetime import datetime, timezone
+from typing import Any, Callable, Dict, Mapping, Optional, Tuple
+
+from core.strategy_engine.strategy import Direction, Signal
+from core.volume_profile import normalize_symbol
+
+logger = logging.getLogger(__name__)
+
+ACTIONS: Dict[str, Direction] = {
+    'buy': Direction.LONG, 'long': Direction.LONG,
+    'sell': Direction.SHORT, 'short': Direction.SHORT,
+    'close': Direction.FLAT, 'exit': Direction.FLAT, 'flat': Direction.FLAT, 'flatten': Direction.FLAT,
+}
+MAX_BODY_BYTES = 64 * 1024
+
+_CONTINUOUS = re.compile(r'^(.+?)\d+!$')  # MNQ1!
+_DATED = re.compile(r'^([A-Z0-9]+?)[FGHJKMNQUVXZ]\d{4}$')  # MNQZ2025
+
+
+class AlertError(ValueError):
+    """Alert payload that can't be turned into a signal."""
+
+
+def alert_symbol(ticker: str) -> str:
+    """Root symbol of a TradingView ticker (CME_MINI:MNQ1! -> MNQ)."""
+    symbol = ticker.strip().upper().rsplit(':', 1)[-1]
+    for pattern in (_CONTINUOUS, _DATED):
+        match = pattern.match(symbol)
+        if match:
+            return match.group(1)
+    return normalize_symbol(symbol)
+
+
+def _number(payload: Mapping[str, Any], *keys: str) -> Optional[float]:
+    for key in keys:
+        value = payload.get(key)
+        if value in (None, ''):
+            continue
+        try:
+            return float(value)
+        except (TypeError, ValueError):
+            raise AlertError(f"{key} should be a number, got {value!r}")
+    return None
+
+
+def _timestamp(value: Any) -> datetime:
+    if value in (None, ''):
+        return datetime.now(timezone.utc)
+    try:
+        parsed = datetime.fromisoformat(str(value).replace('Z', '+00:00'))
+    except ValueError:
+        raise AlertError(f"time should be an ISO timestamp, got {value!r}")
+    return parsed if parsed.tzinfo else parsed.replace(tzinfo=timezone.utc)
+
+
+def parse_alert(payload: Mapping[str, Any], default_strategy: str = 'tradingview') -> Signal:
+    """
+    Turn a TradingView alert payload into a Signal.
+
+    Raises:
+        AlertError: Missing/unknown action or ticker, bad numbers or timestamp
+    """
+    if not isinstance(payload, Mapping):
+        raise AlertError(f"alert should be a JSON object, got {type(payload).__name__}")
+    action = str(payload.get('action') or '').strip().lower()
+    if action not in ACTIONS:
+        raise AlertError(f"unknown action {action!r}" if action else "missing action")
+    direction = ACTIONS[action]
+    if str(payload.get('market_position') or '').strip().lower() == 'flat':
+        direction = Direction.FLAT  # Strategy alert closing its position
+    ticker = str(payload.get('ticker') or payload.get('symbol') or '').strip()
+    if not ticker:
+        raise AlertError("missing ticker")
+
+    price = _number(payload, 'price', 'close')
+    stop = _number(payload, 'stop', 'stop_loss')
+    target = _number(payload, 'target', 'take_profit')
+    confidence = _number(payload, 'confidence')
+    if direction is Direction.FLAT:
+        stop = target = None
+    elif price is None and (stop is not None or target is not None):
+        raise AlertError("stop/target need a price")
+    return Signal(strategy_id=str(payload.get('strategy') or default_strategy), symbol=alert_symbol(ticker),
+                  direction=direction, price=price if price is not None else 0.0,
+                  timestamp=_timestamp(payload.get('time')),
+                  confidence=confidence if confidence is not None else 1.0, stop=stop, target=target,
+                  timeframe=str(payload['interval']) if payload.get('interval') else None,
+                  reason=str(payload.get('comment') or 'TradingView alert'))
+
+
+class TradingViewWebhookServer:
+    """
+    HTTP endpoint turning authenticated TradingView alerts into routed signals.
+    """
+
+    def __init__(self, executor: Any = None, risk: Any = None, secret: Optional[str] = None,
+                 host: Optional[str] = None, port: Optional[int] = None, path: Optional[str] = None,
+                 default_strategy: Optional[str] = None, publish: Optional[Callable[[Signal], bool]] = None):
+        """
+        Initialize server.
+
+        Args:
+            executor: OrderExecutor submitting the orders of accepted signals
+            risk: RiskManager whose gate entries must pass
+            secret: Shared secret (env: TRADINGVIEW_WEBHOOK_SECRET)
+            host: Bind address (env: TRADINGVIEW_WEBHOOK_HOST)
+            port: Bind port, 0 = any free port (env: TRADINGVIEW_WEBHOOK_PORT)
+            path: Alert URL path (env: TRADINGVIEW_WEBHOOK_PATH)
+            default_strategy: Strategy ID of alerts without one (env: TRADINGVIEW_WEBHOOK_STRATEGY)
+            publish: Route signals here instead of risk/executor (False = dropped by a gate)
+        """
+        if executor is None and publish is None:
+            raise ValueError("TradingViewWebhookServer needs an executor or a publish function")
+        self.executor = executor
+        self.risk = risk
+        self.publish = publish or self._route
+        self.secret = secret if secret is not None else os.getenv('TRADINGVIEW_WEBHOOK_SECRET', '')
+        self.host = host if host is not None else os.getenv('TRADINGVIEW_WEBHOOK_HOST', '0.0.0.0')
+        self.port = port if port is not None else int(os.getenv('TRADINGVIEW_WEBHOOK_PORT', '8090'))
+        self.path = path if path is not None else os.getenv('TRADINGVIEW_WEBHOOK_PATH', '/tradingview')
+        self.default_strategy = (default_strategy if default_strategy is not None
+                                 else os.getenv('TRADINGVIEW_WEBHOOK_STRATEGY', 'tradingview'))
+        self._runner: Any = None
+        self._lock = threading.Lock()
+        self.received = 0
+        self.routed = 0
+        self.unauthorized = 0
+        self.invalid = 0
+        self.blocked = 0
+        self.failed = 0
+        self.last_signal: Optional[Signal] = None
+        self.last_error: Optional[str] = None
+
+    @classmethod
+    def from_core(cls, core: Any, **kwargs: Any) -> 'TradingViewWebhookServer':
+        """Server publishing alerts on a TradingCore's engine (risk, pause, portfolio and filter gates)."""
+        return cls(executor=core.executor, risk=core.risk, publish=core.engine.publish, **kwargs)
+
+    @property
+    def running(self) -> bool:
+        return self._runner is not None
+
+    # ---------------------------
+    # Alert handling
+    # ---------------------------
+    def _route(self, signal: Signal) -> bool:
+        if self.risk is not None and not self.risk.allows(signal):
+            return False
+        self.executor.on_signal(signal)
+        return True
+
+    def _authorized(self, payload: Any, headers: Mapping[str, str]) -> bool:
+        supplied = headers.get('X-Webhook-Secret')
+        if supplied is None and isinstance(payload, dict):
+            supplied = payload.get('secret')
+        if not isinstance(supplied, str) or not self.secret:
+            return False
+        return hmac.compare_digest(supplied.encode(), self.secret.encode())
+
+    def _reject(self, counter: str, status: int, error: str) -> Tuple[int, Dict[str, Any]]:
+        with self._lock:
+            setattr(self, counter, getattr(self, counter) + 1)
+            self.last_error = error
+        return status, {"success": False, "error": error}
+
+    def handle_alert(self, body: bytes, headers: Optional[Mapping[str, str]] = None) -> Tuple[int, Dict[str, Any]]:
+        """
+        Authenticate, parse and route one alert body.
+
+        Returns:
+            Tuple: (HTTP status, JSON response)
+        """
+        with self._lock:
+            self.received += 1
+        try:
+            payload = json.loads(body)
+        except (UnicodeDecodeError, ValueError):
+            payload = None
+        # Checked before any parse error is reported, so unauthenticated senders learn nothing
+        if not self._authorized(payload, headers or {}):
+            logger.warning("⚠️ TradingView webhook: rejected alert with a missing or wrong secret")
+            return self._reject('unauthorized', 401, "Unauthorized")
+        try:
+            signal = parse_alert(payload, self.default_strategy)
+        except AlertError as e:
+            logger.warning(f"⚠️ TradingView webhook: invalid alert: {e}")
+            return self._reject('invalid', 400, f"Invalid alert: {e}")
+
+        try:
+            accepted = self.publish(signal)
+        except Exception as e:
+            logger.error(f"❌ TradingView webhook: routing {signal.symbol} {signal.direction.value} failed: {e}")
+            return self._reject('failed', 500, f"{type(e).__name__}: {e}")
+        with self._lock:
+            self.last_signal = signal
+            if not accepted:
+                self.blocked += 1
+            else:
+                self.routed += 1
+        if not accepted:
+            logger.warning(f"⚠️ TradingView webhook: {signal.strategy_id} {signal.symbol} "
+                           f"{signal.direction.value} blocked by a gate")
+            return 409, {"success": False, "error": "Signal blocked (risk halt or gate)", "signal": signal.to_dict()}
+        logger.info(f"📨 TradingView alert routed: {signal.strategy_id} {signal.symbol} {signal.direction.value}")
+        return 200, {"success": True, "signal": signal.to_dict()}
+
+    async def _handle(self, request: Any) -> Any:
+        from aiohttp import web
+        status, body = self.handle_alert(await request.read(), request.headers)
+        return web.json_response(body, status=status)
+
+    # ---------------------------
+    # Lifecycle
+    # ---------------------------
+    async def start(self) -> None:
+        """Bind and start serving alerts (self.port holds the bound port afterwards)."""
+        if self.running:
+            return
+        if not self.secret:
+            raise ValueError("TRADINGVIEW_WEBHOOK_SECRET must be set to accept TradingView alerts")
+        from aiohttp import web
+        app = web.Application(client_max_size=MAX_BODY_BYTES)
+        app.router.add_post(self.path, self._handle)
+        runner = web.AppRunner(app)
+        await runner.setup()
+        site = web.TCPSite(runner, self.host, self.port)
+        try:
+            await site.start()
+        except Exception:
+            await runner.cleanup()
+            raise
+        if runner.addresses:
+            self.port = runner.addresses[0][1]
+        self._runner = runner
+        logger.info(f"🚀 TradingView webhook listening on http://{self.host}:{self.port}{self.path}")
+
+    async def stop(self) -> None:
+        """Stop listening (alerts already routed keep going)."""
+        runner, self._runner = self._runner, None
+        if runner is not None:
+            await runner.cleanup()
+            logger.info("🛑 TradingView webhook stopped")
+
+    def get_stats(self) -> Dict[str, Any]:
+        with self._lock:
+            return {
+                "running": self.running,
+                "bind": f"{self.host}:{self.port}{self.path}",
+                "received": self.received,
+                "routed": self.routed,
+                "unauthorized": self.unauthorized,
+                "invalid": self.invalid,
+                "blocked": self.blocked,
+                "failed": self.failed,
+                "last_signal": self.last_signal.to_dict() if self.last_signal else None,
+                "last_error": self.last_error,
+            }
@@ -640,7 +640,8 @@ class WebhookHandler(BaseHTTPRequestHandler):
                 side=direction,
                 quantity=quantity,
                 stop_loss_ticks=self._calculate_ticks(entry, stop_loss) if stop_loss else None,
-                take_profit_ticks=self._calculate_ticks(entry, take_profit_1) if take_profit_1 else None
+                take_profit_ticks=self._calculate_ticks(entry, take_profit_1) if take_profit_1 else None,
+                signal_price=entry
             )
             
             if "error" in result:
@@ -2978,6 +2979,7 @@ async def main():
     
     # Create trading bot
     bot = TopStepXTradingBot(api_key=api_key, username=username)
+    bot.claim_artifact_dirs()
     
     # Authenticate
     if not await bot.authenticate():
@@ -25,6 +25,7 @@ from dataclasses import dataclass
 from collections import deque
 import pytz
 
+from core.clock_sync import server_now
 from strategies.strategy_base import BaseStrategy, StrategyConfig, MarketCondition, StrategyStatus
 
 logger = logging.getLogger(__name__)
@@ -460,7 +461,7 @@ class OvernightRangeStrategy(BaseStrategy):
             cache_key = f"{symbol}_{timeframe}_{period}"
             
             # Check cache first
-            now = datetime.now(self.timezone)
+            now = server_now(self.timezone)
             if symbol in self._atr_cache and cache_key in self._atr_cache[symbol]:
                 cached_data, cached_time = self._atr_cache[symbol][cache_key]
                 if now - cached_time < self._atr_cache_ttl:
@@ -545,7 +546,7 @@ class OvernightRangeStrategy(BaseStrategy):
             # Get market open price (9:30am candle open)
             # This is used for daily ATR zone calculations
             market_open_price = 0.0
-            now = datetime.now(self.timezone)
+            now = server_now(self.timezone)
             open_hour, open_min = map(int, self.market_open_time.split(':'))
             market_open_today = now.replace(hour=open_hour, minute=open_min, second=0, microsecond=0)
             
@@ -631,7 +632,7 @@ class OvernightRangeStrategy(BaseStrategy):
         try:
             symbol = symbol.upper()
             # Get current time in strategy timezone
-            now = datetime.now(self.timezone)
+            now = server_now(self.timezone)
             
             # Parse configured session times
             start_hour, start_min = map(int, self.overnight_start.split(':'))
@@ -1001,14 +1002,7 @@ class OvernightRangeStrategy(BaseStrategy):
                 
                 # Setup breakeven monitoring ONLY if enabled
                 if self.breakeven_enabled:
-                    self.breakeven_monitoring[order_id] = {
-                        "symbol": symbol,
-                        "side": "LONG",
-                        "entry_price": long_order.entry_price,
-                        "original_stop": long_order.stop_loss,
-                        "breakeven_triggered": False,
-                        "position_filled": False  # Track if entry order has filled
-                    }
+                    self.watch_breakeven(order_id, symbol, "LONG", long_order.entry_price, long_order.stop_loss)
                     logger.debug(f"Breakeven monitoring setup for order {order_id}")
             elif long_result and (long_result.get('error') or long_result.get('errorMessage')):
                 error_msg = long_result.get('error') or long_result.get('errorMessage', 'Unknown error')
@@ -1044,14 +1038,7 @@ class OvernightRangeStrategy(BaseStrategy):
                 
                 # Setup breakeven monitoring ONLY if enabled
                 if self.breakeven_enabled:
-                    self.breakeven_monitoring[order_id] = {
-                        "symbol": symbol,
-                        "side": "SHORT",
-                        "entry_price": short_order.entry_price,
-                        "original_stop": short_order.stop_loss,
-                        "breakeven_triggered": False,
-                        "position_filled": False  # Track if entry order has filled
-                    }
+                    self.watch_breakeven(order_id, symbol, "SHORT", short_order.entry_price, short_order.stop_loss)
                     logger.debug(f"Breakeven monitoring setup for order {order_id}")
             elif short_result and (short_result.get('error') or short_result.get('errorMessage')):
                 error_msg = short_result.get('error') or short_result.get('errorMessage', 'Unknown error')
@@ -1147,14 +1134,8 @@ class OvernightRangeStrategy(BaseStrategy):
         self.breakout_active_orders.setdefault(symbol, {})[order_template.side] = order_id
 
         if self.breakeven_enabled:
-            self.breakeven_monitoring[order_id] = {
-                "symbol": symbol,
-                "side": "LONG" if order_template.side == "BUY" else "SHORT",
-                "entry_price": order_template.entry_price,
-                "original_stop": order_template.stop_loss,
-                "breakeven_triggered": False,
-                "position_filled": False
-            }
+            self.watch_breakeven(order_id, symbol, "LONG" if order_template.side == "BUY" else "SHORT",
+                                 order_template.entry_price, order_template.stop_loss)
 
         logger.info(f"✅ {order_template.side} breakout order submitted: {order_id}")
         return order_id
@@ -1237,6 +1218,38 @@ class OvernightRangeStrategy(BaseStrategy):
                 logger.error(f"Error in breakout monitoring: {exc}")
                 await asyncio.sleep(max(self.breakout_monitor_interval, 5))
     
+    def watch_breakeven(self, order_id: str, symbol: str, side: str, entry_price: float, original_stop: float,
+                        profit_points: Optional[float] = None) -> None:
+        """
+        Put an entry order under breakeven management once it fills.
+        
+        With the bot's BreakEvenManager enabled (BREAK_EVEN_ENABLED), the stop is
+        moved on live quotes; otherwise monitor_breakeven_stops() polls positions.
+        
+        Args:
+            order_id: Entry order ID
+            symbol: Trading symbol
+            side: LONG or SHORT
+            entry_price: Planned entry price
+            original_stop: Initial stop loss price
+            profit_points: Profit that triggers the move (default: BREAKEVEN_PROFIT_POINTS)
+        """
+        profit_points = profit_points if profit_points is not None else self.breakeven_profit_points
+        manager = getattr(self.trading_bot, 'break_even_manager', None)
+        if manager is not None:
+            trigger_ticks = max(round(profit_points / manager.tick_size(symbol)), manager.offset_ticks + 1)
+            manager.watch(str(order_id), trigger_ticks=trigger_ticks)
+            return
+        self.breakeven_monitoring[order_id] = {
+            "symbol": symbol,
+            "side": side,
+            "entry_price": entry_price,
+            "original_stop": original_stop,
+            "breakeven_threshold": profit_points,
+            "breakeven_triggered": False,
+            "position_filled": False  # Track if entry order has filled
+        }
+    
     async def monitor_breakeven_stops(self):
         """
         Background task to monitor FILLED positions and move stops to breakeven when profitable.
@@ -1254,6 +1267,10 @@ class OvernightRangeStrategy(BaseStrategy):
             logger.info("🔄 Breakeven monitoring DISABLED (set BREAKEVEN_ENABLED=true to enable)")
             return
         
+        if getattr(self.trading_bot, 'break_even_manager', None) is not None:
+            logger.info("🔄 Breakeven stops handled on live quotes by the bot's BreakEvenManager")
+            return
+        
         logger.info(f"🔄 Breakeven monitoring ACTIVE (+{self.breakeven_profit_points} pts threshold)")
         
         while self.is_trading:
@@ -1349,7 +1366,7 @@ class OvernightRangeStrategy(BaseStrategy):
             
             while self.is_trading:
                 try:
-                    now = datetime.now(self.timezone)
+                    now = server_now(self.timezone)
                     open_hour, open_min = map(int, self.market_open_time.split(':'))
                     market_open_today = now.replace(hour=open_hour, minute=open_min, second=0, microsecond=0)
                     trading_end_hour, trading_end_min = map(int, self.config.trading_end_time.split(':'))
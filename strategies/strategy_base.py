@@ -13,6 +13,8 @@ from dataclasses import dataclass
 from datetime import datetime
 from enum import Enum
 
+from core.clock_sync import server_now
+
 logger = logging.getLogger(__name__)
 
 
@@ -295,8 +297,8 @@ class BaseStrategy(ABC):
         return True, "All checks passed"
     
     def _in_trading_window(self) -> bool:
-        """Check if current time is within trading window."""
-        now = datetime.now()
+        """Check if current time (local, on the broker-corrected clock) is within trading window."""
+        now = server_now(None)
         current_time = now.hour * 60 + now.minute
         
         start_hour, start_min = map(int, self.config.trading_start_time.split(':'))
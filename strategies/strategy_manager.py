@@ -11,6 +11,7 @@ import asyncio
 from typing import Dict, List, Optional, Type, Any
 from datetime import datetime, timezone
 from strategies.strategy_base import BaseStrategy, StrategyConfig, StrategyStatus, MarketCondition
+from core.plugin_hooks import LifecycleEvent, PluginHookRegistry
 
 logger = logging.getLogger(__name__)
 
@@ -103,6 +104,11 @@ class StrategyManager:
         db = getattr(self.trading_bot, 'db', None)
         account_id = self._get_account_id()
         
+        plugin_hooks = getattr(self.trading_bot, 'plugin_hooks', None)
+        if isinstance(plugin_hooks, PluginHookRegistry):
+            await plugin_hooks.dispatch(LifecycleEvent.PRE_TRADING_OPEN, self.trading_bot, account_id=account_id,
+                                        strategies=list(self.strategy_classes.keys()))
+        
         # Get persisted states for this account
         persisted_states = {}
         if db and account_id:
@@ -319,7 +319,8 @@ class TrendFollowingStrategy(BaseStrategy):
                 stop_loss_price=stop_loss,
                 take_profit_price=take_profit,
                 account_id=self.trading_bot.selected_account if isinstance(self.trading_bot.selected_account, str) else self.trading_bot.selected_account.get('id') if isinstance(self.trading_bot.selected_account, dict) else None,
-                strategy_name=self.config.name  # Add strategy name for tracking
+                strategy_name=self.config.name,  # Add strategy name for tracking
+                signal_price=entry_price
             )
             
             if result and 'order' in result:
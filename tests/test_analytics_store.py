@@ -0,0 +1,100 @@
+"""
+Unit tests for the DuckDB analytics store
+"""
+
+import pytest
+import os
+import sys
+from datetime import date, datetime, timedelta, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+pytest.importorskip('duckdb')
+pa = pytest.importorskip('pyarrow')
+
+from core.analytics_store import AnalyticsStore
+from core.arrow_export import bars_to_arrow
+from core.bar_aggregator import Bar
+from core.market_events import Trade
+
+# 17:58 New York, two minutes before the 18:00 session start
+START = datetime(2025, 11, 19, 22, 58, tzinfo=timezone.utc)
+
+
+def make_bars(count, start=START):
+    return [Bar('MNQ', '1m', start + timedelta(minutes=i), 1.0 + i, 2.0 + i, 0.5 + i, 1.5 + i, 10, 3)
+            for i in range(count)]
+
+
+@pytest.fixture
+def store():
+    store = AnalyticsStore(':memory:', tz='America/New_York', day_start='18:00')
+    yield store
+    store.close()
+
+
+class TestAnalyticsStore:
+    """Test loading, ad-hoc queries and the built-in aggregations."""
+
+    def test_query_returns_arrow_and_bar_loads_replace_by_key(self, store):
+        assert store.load_bars(make_bars(4)) == 4
+        assert store.load_bars(bars_to_arrow(make_bars(1))) == 1  # Same key: replaced, not duplicated
+        table = store.query("SELECT timestamp, close FROM bars WHERE symbol = ? ORDER BY timestamp", ['MNQ'])
+        assert isinstance(table, pa.Table) and table.num_rows == 4
+        assert table.column('close').to_pylist() == [1.5, 2.5, 3.5, 4.5]
+        assert table.column('timestamp')[0].as_py() == START
+        assert store.get_stats()['bars'] == 4
+
+    def test_session_stats_cut_at_day_start(self, store):
+        store.load_bars(make_bars(4))  # 17:58, 17:59 | 18:00, 18:01 New York
+        stats = store.session_stats('mnq', '1m', START, START + timedelta(hours=1)).to_pylist()
+        assert [s['session'] for s in stats] == [date(2025, 11, 19), date(2025, 11, 20)]
+        assert (stats[0]['open'], stats[0]['close'], stats[0]['high'], stats[0]['low']) == (1.0, 2.5, 3.0, 0.5)
+        assert stats[1]['volume'] == 20 and stats[1]['bars'] == 2 and stats[1]['range'] == 2.5
+        with pytest.raises(ValueError, match='attach_parquet'):
+            store.session_stats('MNQ', '1m', START, START, source='parquet')
+
+    def test_volume_profile_from_ticks(self, store):
+        ticks = [Trade('MNQ', START, 21000.25, 2, 'buy'), Trade('MNQ', START, 21000.30, 1, 'sell'),
+                 Trade('MNQ', START + timedelta(seconds=1), 21001.0, 4, 'buy')]
+        assert store.load_ticks(ticks) == 3
+        profile = store.volume_profile('MNQ', START, START + timedelta(minutes=1)).to_pylist()
+        assert profile == [
+            {'price': 21000.25, 'volume': 3, 'buy_volume': 2, 'sell_volume': 1},
+            {'price': 21001.0, 'volume': 4, 'buy_volume': 4, 'sell_volume': 0},
+        ]
+        with pytest.raises(ValueError):
+            store.volume_profile('MNQ', START, START, tick_size=0)
+
+    def test_import_database_and_trades(self, store):
+        class DB:
+            def get_bars(self, symbol, timeframe, start, end, output='numpy'):
+                assert output == 'arrow'
+                return bars_to_arrow(make_bars(2))
+
+            def get_ticks(self, symbol, start, end):
+                return [{'symbol': 'MNQ', 'timestamp': START.isoformat(), 'price': 21000.0, 'size': 1}]
+
+            def get_trades(self, start, end, symbol=None):
+                return [{'id': 7, 'strategy_name': 'orb', 'symbol': 'MNQ', 'side': 'BUY', 'quantity': 1,
+                         'pnl': 12.5, 'exit_time': START.isoformat(), 'metadata': None}]
+
+        loaded = store.import_database(DB(), 'MNQ', START, START + timedelta(hours=1))
+        assert loaded == {'bars': 2, 'ticks': 1, 'trades': 1}
+        store.import_database(DB(), 'MNQ', START, START + timedelta(hours=1), ticks=False)
+        rows = store.query("SELECT strategy_name, sum(pnl) AS pnl, count(*) AS n FROM trades GROUP BY 1").to_pylist()
+        assert rows == [{'strategy_name': 'orb', 'pnl': 12.5, 'n': 1}]
+
+    def test_attach_parquet_views(self, store, tmp_path):
+        pq = pytest.importorskip('pyarrow.parquet')
+        directory = tmp_path / 'bars' / 'symbol=MNQ' / 'timeframe=1m' / 'date=2025-11-19'
+        directory.mkdir(parents=True)
+        pq.write_table(pa.Table.from_batches([bars_to_arrow(make_bars(2))]), directory / 'part-1.parquet')
+        assert store.attach_parquet(tmp_path) == ['parquet_bars']
+        stats = store.session_stats('MNQ', '1m', START, START + timedelta(hours=1), source='parquet')
+        assert stats.num_rows == 1 and stats.column('volume').to_pylist() == [20]
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
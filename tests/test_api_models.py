@@ -0,0 +1,102 @@
+"""
+Unit tests for the typed TopStepX API models
+"""
+
+import pytest
+import asyncio
+import os
+import sys
+from unittest.mock import AsyncMock, MagicMock, patch
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.api_models import (
+    AccountSearchResponse, ApiResponse, ApiSchemaError, BarsResponse, LoginResponse, OrderSearchResponse,
+    OrderStatus, PlaceOrderResponse, PositionSearchResponse, TradeSearchResponse,
+)
+from trading_bot import TopStepXTradingBot
+
+ORDER = {
+    "id": 36598, "accountId": 704, "contractId": "CON.F.US.EP.U25", "symbolId": "F.US.EP",
+    "creationTimestamp": "2025-07-21T14:24:40.264+00:00", "updateTimestamp": "2025-07-21T14:24:40.264+00:00",
+    "status": 1, "type": 1, "side": 0, "size": 1, "limitPrice": 6300, "stopPrice": None,
+    "fillVolume": 0, "filledPrice": None, "customTag": None,
+}
+
+
+class TestApiModels:
+    """Test envelope handling, typed bodies and schema errors"""
+
+    def test_place_order_response(self):
+        placed = PlaceOrderResponse.parse({"orderId": 9056, "success": True, "errorCode": 0, "errorMessage": None})
+        assert placed.success and placed.order_id == 9056 and placed.error_code == 0
+        rejected = PlaceOrderResponse.parse({"success": False, "errorCode": 2, "errorMessage": "Invalid size"})
+        assert not rejected.success and rejected.order_id is None  # Body not required on failure
+        assert rejected.error_text == "Invalid size (Code: 2)"
+        with pytest.raises(ApiSchemaError, match=r"/api/Order/place response: missing orderId"):
+            PlaceOrderResponse.parse({"success": True, "data": {"orderId": 9056}})
+        with pytest.raises(ApiSchemaError, match="orderId should be integer, got str"):
+            PlaceOrderResponse.parse({"success": True, "orderId": "9056"})
+        with pytest.raises(ApiSchemaError, match="success should be boolean"):
+            ApiResponse.parse({"success": "true"}, "/api/Order/cancel")
+        with pytest.raises(ApiSchemaError, match="expected an object, got list"):
+            ApiResponse.parse([], "/api/Order/cancel")
+
+    @pytest.mark.asyncio
+    async def test_malformed_order_success_is_status_unknown(self):
+        """A 200 response that fails the schema may be a live order: not rejected, reconciled instead"""
+        with patch.dict(os.environ, {'PROJECT_X_API_KEY': 'test_key', 'PROJECT_X_USERNAME': 'test_user'}):
+            bot = TopStepXTradingBot(api_key='test_key', username='test_user')
+        bot.session_token = 'token'
+        bot.selected_account = {'id': 12345, 'name': 'TEST_ACCOUNT'}
+        bot._get_contract_id = MagicMock(return_value='CON.F.US.MNQ.Z25')
+        bot._get_tick_size = AsyncMock(return_value=0.25)
+        bot.reconcile = AsyncMock(return_value={})
+        bot._make_curl_request = MagicMock(return_value={"success": True, "orderId": "9056"})
+
+        result = await bot.place_market_order('MNQ', 'BUY', 1)
+        assert result['status_unknown'] and result['error'].startswith('Order status unknown')
+        assert 'rejected' not in result['error']
+        stop = await bot.place_stop_order('MNQ', 'SELL', 1, 20990.0)
+        assert stop['status_unknown'] and stop['error'].startswith('Stop order status unknown')
+        await asyncio.sleep(0)
+        bot.reconcile.assert_awaited_with('12345')
+        assert bot.reconcile.await_count == 2
+
+    def test_order_search_with_nullable_fields(self):
+        search = OrderSearchResponse.parse({"orders": [ORDER, {**ORDER, "id": 36599, "status": 2, "fillVolume": 1,
+                                                              "filledPrice": 6301.25, "customTag": "orb"}],
+                                            "success": True, "errorCode": 0})
+        first, second = search.orders
+        assert (first.limit_price, first.stop_price, first.custom_tag, first.is_working) == (6300.0, None, None, True)
+        assert second.status == OrderStatus.FILLED and second.filled_price == 6301.25 and second.custom_tag == "orb"
+        assert first.raw is ORDER and isinstance(first.limit_price, float)
+        with pytest.raises(ApiSchemaError, match=r"orders\[1\]\.size should be integer, got bool"):
+            OrderSearchResponse.parse({"orders": [ORDER, {**ORDER, "size": True}], "success": True})
+        with pytest.raises(ApiSchemaError, match="'orders' should be a list, got NoneType"):
+            OrderSearchResponse.parse({"result": [ORDER], "success": True})
+
+    def test_accounts_positions_trades_bars_and_login(self):
+        accounts = AccountSearchResponse.parse({"accounts": [
+            {"id": 1, "name": "TEST_ACCOUNT_1", "balance": 50000, "canTrade": True, "isVisible": True}],
+            "success": True, "errorCode": 0, "errorMessage": None})
+        assert accounts.accounts[0].balance == 50000.0 and accounts.accounts[0].simulated is None
+        positions = PositionSearchResponse.parse({"positions": [
+            {"id": 6124, "accountId": 536, "contractId": "CON.F.US.GMET.J25", "type": 2, "size": 3,
+             "averagePrice": 1604.5, "creationTimestamp": "2025-04-21T19:52:32.175721+00:00"}], "success": True})
+        assert positions.positions[0].signed_size == -3
+        trades = TradeSearchResponse.parse({"trades": [
+            {"id": 8604, "accountId": 203, "contractId": "CON.F.US.EP.H25", "price": 6065.25, "profitAndLoss": None,
+             "fees": 1.4, "side": 1, "size": 1, "voided": False, "orderId": 14328}], "success": True})
+        assert trades.trades[0].profit_and_loss is None and trades.trades[0].order_id == 14328
+        bars = BarsResponse.parse({"bars": [{"t": "2024-12-20T14:00:00+00:00", "o": 2208.1, "h": 2217.0,
+                                             "l": 2206.7, "c": 2210.1, "v": 87}], "success": True})
+        assert bars.bars[0].c == 2210.1 and bars.bars[0].v == 87.0
+        assert LoginResponse.parse({"token": "abc", "success": True, "errorCode": 0}).token == "abc"
+        with pytest.raises(ApiSchemaError, match="missing token"):
+            LoginResponse.parse({"success": True})
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,59 @@
+"""
+Unit tests for Arrow export of bars and ticks
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timedelta, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+pa = pytest.importorskip('pyarrow')
+
+from core.arrow_export import bar_schema, bars_to_arrow, tick_schema, ticks_to_arrow
+from core.bar_aggregator import BarAggregator
+from core.market_events import Trade
+
+START = datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc)
+
+
+class TestArrowExport:
+    """Test RecordBatch conversion of bars and ticks."""
+
+    def test_bar_history_to_arrow(self):
+        aggregator = BarAggregator(default_timeframes=['1s'])
+        for i in range(4):
+            aggregator.add_quote('MNQ', 15000.0 + i, volume=3, timestamp=START + timedelta(seconds=i))
+        batch = aggregator.to_arrow('MNQ', '1s')
+        assert batch.schema == bar_schema()
+        assert batch.num_rows == 3
+        assert batch.column('close').to_pylist() == [15000.0, 15001.0, 15002.0]
+        assert batch.column('volume').to_pylist() == [3, 3, 3]
+        assert batch.column('timestamp')[0].as_py() == START
+        assert batch.column('is_rth').null_count == 3
+        assert bars_to_arrow([]).num_rows == 0
+
+    def test_ticks_from_events_and_rows(self):
+        ticks = [
+            Trade(symbol='MNQ', timestamp=START, price=15000.25, size=2, side='buy'),
+            {'symbol': 'MNQ', 'timestamp': '2025-11-19T14:30:01Z', 'price': 15000.5, 'size': 1,
+             'side': None, 'bid': 15000.25, 'ask': 15000.5},
+        ]
+        batch = ticks_to_arrow(ticks)
+        assert batch.schema == tick_schema()
+        assert batch.column('side').to_pylist() == ['buy', None]
+        assert batch.column('bid').to_pylist() == [None, 15000.25]
+        assert batch.column('timestamp')[1].as_py() == START + timedelta(seconds=1)
+
+    def test_to_polars(self):
+        pytest.importorskip('polars')
+        from core.arrow_export import to_polars
+        frame = to_polars(ticks_to_arrow([Trade(symbol='MNQ', timestamp=START, price=1.0, size=1)]))
+        assert frame.shape == (1, 7)
+        assert frame['price'].to_list() == [1.0]
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
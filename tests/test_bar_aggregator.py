@@ -12,7 +12,8 @@ import sys
 # Add parent directory to path
 sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
 
-from core.bar_aggregator import BarAggregator, Bar, BarBuilder
+from core.bar_aggregator import BarAggregator, parse_bar_threshold, Bar, BarBuilder, bars_to_numpy
+from core.session_calendar import SessionCalendar
 
 
 class TestBarBuilder:
@@ -255,6 +256,367 @@ class TestBarAggregator:
         assert 'bar' in message['data']
 
 
+
+class TestMultiTimeframeEmission:
+    """Test completed-bar emission across timeframes"""
+    
+    def test_single_tick_stream_builds_all_timeframes(self):
+        """Test one instance completes 1s/5s/1m bars from the same ticks"""
+        completed = []
+        aggregator = BarAggregator(default_timeframes=['1s', '5s', '1m'],
+                                   bar_close_callback=completed.append)
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        
+        for i in range(61):
+            aggregator.add_quote('MNQ', 15000.0 + i, volume=1, timestamp=start + timedelta(seconds=i))
+        
+        by_tf = {}
+        for bar in completed:
+            by_tf.setdefault(bar.timeframe, []).append(bar)
+        assert len(by_tf['1s']) == 60
+        assert len(by_tf['5s']) == 12
+        assert len(by_tf['1m']) == 1
+        minute_bar = by_tf['1m'][0]
+        assert minute_bar.open == 15000.0
+        assert minute_bar.close == 15059.0
+        assert minute_bar.volume == 60
+        assert aggregator.get_bar_history('MNQ', '5s')[-1].timestamp == start + timedelta(seconds=55)
+    
+    def test_to_numpy_returns_contiguous_float64_columns(self):
+        """Test bar history exports as equal-length contiguous float64 arrays"""
+        np = pytest.importorskip('numpy')
+        aggregator = BarAggregator(default_timeframes=['1s'])
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        for i in range(4):
+            aggregator.add_quote('MNQ', 15000.0 + i, volume=2, timestamp=start + timedelta(seconds=i))
+        
+        arrays = aggregator.to_numpy('mnq', '1s')
+        assert list(arrays) == ['open', 'high', 'low', 'close', 'volume', 'timestamp']
+        for column in arrays.values():
+            assert column.dtype == np.float64
+            assert column.flags['C_CONTIGUOUS']
+        assert arrays['close'].tolist() == [15000.0, 15001.0, 15002.0]
+        assert arrays['volume'].tolist() == [2.0, 2.0, 2.0]
+        assert arrays['timestamp'][0] == start.timestamp()
+        assert len(bars_to_numpy([])['open']) == 0
+    
+    def test_completed_bar_queue(self):
+        """Test completed bars are delivered through the queue"""
+        aggregator = BarAggregator(default_timeframes=['1s'], bar_queue_maxsize=100)
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        for i in range(4):
+            aggregator.add_quote('MNQ', 15000.0 + i, timestamp=start + timedelta(seconds=i))
+        
+        bars = aggregator.get_completed_bars()
+        assert [b.close for b in bars] == [15000.0, 15001.0, 15002.0]
+        assert aggregator.get_completed_bars() == []
+    
+    def test_completed_bar_queue_drops_oldest_when_full(self):
+        """Test a full queue drops the oldest bar and counts it"""
+        aggregator = BarAggregator(default_timeframes=['1s'], bar_queue_maxsize=2)
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        for i in range(5):
+            aggregator.add_quote('MNQ', 15000.0 + i, timestamp=start + timedelta(seconds=i))
+        
+        assert [b.close for b in aggregator.get_completed_bars()] == [15002.0, 15003.0]
+        assert aggregator.dropped_bars == 2
+    
+    def test_completed_bar_queue_concurrent_producers_never_raise(self):
+        """Test concurrent emitters into a full queue drop oldest instead of raising queue.Full"""
+        import threading
+        aggregator = BarAggregator(default_timeframes=['1s'], bar_queue_maxsize=1)
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        bar = Bar(symbol='MNQ', timeframe='1s', timestamp=start, open=1.0, high=1.0, low=1.0, close=1.0)
+        errors = []
+        
+        def produce():
+            try:
+                for _ in range(2000):
+                    aggregator._emit_completed_bar(bar)
+            except Exception as e:
+                errors.append(e)
+        
+        threads = [threading.Thread(target=produce) for _ in range(4)]
+        for thread in threads:
+            thread.start()
+        for thread in threads:
+            thread.join()
+        
+        assert errors == []
+        assert len(aggregator.get_completed_bars()) == 1
+        assert aggregator.dropped_bars == 4 * 2000 - 1
+    
+    def test_close_elapsed_bars_without_new_tick(self):
+        """Test quiet markets still complete bars on time"""
+        aggregator = BarAggregator(default_timeframes=['1m'])
+        start = datetime(2025, 11, 19, 10, 0, 10, tzinfo=timezone.utc)
+        aggregator.add_quote('MNQ', 15000.0, timestamp=start)
+        
+        assert aggregator.close_elapsed_bars(start + timedelta(seconds=20)) == []
+        closed = aggregator.close_elapsed_bars(start + timedelta(minutes=1))
+        
+        assert len(closed) == 1
+        assert aggregator.get_last_completed_bar('MNQ', '1m').close == 15000.0
+        # Builder was reset, so the same bar is not emitted twice
+        assert aggregator.close_elapsed_bars(start + timedelta(minutes=1, seconds=5)) == []
+    
+    def test_callback_error_does_not_break_aggregation(self):
+        """Test a failing bar close callback is isolated"""
+        def bad_callback(bar):
+            raise RuntimeError("boom")
+        
+        aggregator = BarAggregator(default_timeframes=['1s'], bar_close_callback=bad_callback)
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        aggregator.add_quote('MNQ', 15000.0, timestamp=start)
+        aggregator.add_quote('MNQ', 15001.0, timestamp=start + timedelta(seconds=1))
+        
+        assert aggregator.bars_completed == 1
+        assert aggregator.get_current_bar('MNQ', '1s').close == 15001.0
+    
+    def test_on_bar_close_filters_and_unsubscribes(self):
+        """Test on_bar_close listeners get matching bars until unsubscribed"""
+        aggregator = BarAggregator(default_timeframes=['1s', '5s'])
+        all_bars, five_second = [], []
+        aggregator.on_bar_close(lambda bar: 1 / 0)  # Errors are isolated from other listeners
+        aggregator.on_bar_close(all_bars.append)
+        unsubscribe = aggregator.on_bar_close(five_second.append, symbol='mnq', timeframes=['5S'])
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        for i in range(6):
+            aggregator.add_quote('MNQ', 15000.0 + i, timestamp=start + timedelta(seconds=i))
+            aggregator.add_quote('MES', 5000.0 + i, timestamp=start + timedelta(seconds=i))
+        
+        assert len(all_bars) == 12
+        assert [(b.symbol, b.timeframe) for b in five_second] == [('MNQ', '5s')]
+        unsubscribe()
+        aggregator.add_quote('MNQ', 15010.0, timestamp=start + timedelta(seconds=10))
+        assert len(five_second) == 1
+        assert len(all_bars) == 14
+    
+    @pytest.mark.asyncio
+    async def test_async_listener_runs_on_registering_loop(self):
+        """Test coroutine listeners are scheduled on their loop when bars close on another thread"""
+        import threading
+        loop = asyncio.get_running_loop()
+        received = asyncio.Queue()
+        
+        async def listener(bar):
+            assert asyncio.get_running_loop() is loop
+            await received.put(bar)
+        
+        aggregator = BarAggregator(default_timeframes=['1s'])
+        aggregator.on_bar_close(listener)
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        
+        def feed():
+            aggregator.add_quote('MNQ', 15000.0, timestamp=start)
+            aggregator.add_quote('MNQ', 15001.0, timestamp=start + timedelta(seconds=1))
+        thread = threading.Thread(target=feed)
+        thread.start()
+        thread.join()
+        
+        bar = await asyncio.wait_for(received.get(), timeout=1)
+        assert bar.close == 15000.0
+
+
+
+class TestMultiSymbol:
+    """Test per-symbol state in a single aggregator"""
+    
+    def test_concurrent_symbols_and_filtered_queries(self):
+        """Test quotes for many symbols on separate threads keep independent state"""
+        import threading
+        aggregator = BarAggregator(default_timeframes=['1m', '5m'], lock_stripes=4)
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        symbols = ['MNQ', 'MES', 'MGC', 'M2K', 'MCL', 'MYM']
+        
+        def feed(symbol, base):
+            for i in range(300):
+                aggregator.add_quote(symbol, base + i, volume=1, timestamp=start + timedelta(seconds=i))
+        
+        threads = [threading.Thread(target=feed, args=(sym, 1000.0 * n)) for n, sym in enumerate(symbols, 1)]
+        for t in threads:
+            t.start()
+        for t in threads:
+            t.join()
+        
+        assert aggregator.symbols() == sorted(symbols)
+        assert aggregator.bars_completed == len(symbols) * 4  # Four 1m bars each, 5m still forming
+        for n, sym in enumerate(symbols, 1):
+            history = aggregator.get_bar_history(sym, '1m')
+            assert [b.open for b in history] == [1000.0 * n + 60 * k for k in range(4)]
+            assert sum(b.volume for b in history) == 240
+        
+        current = aggregator.get_current_bars(symbols=['mnq', 'MES'], timeframes=['5m'])
+        assert set(current) == {'MNQ', 'MES'}
+        assert list(current['MNQ']) == ['5m'] and current['MNQ']['5m'].volume == 300
+        last = aggregator.get_last_completed_bars(timeframes=['1m'])
+        assert len(last) == len(symbols) and last['MGC']['1m'].open == 3180.0
+        assert aggregator.get_symbol_stats(['MCL'])['MCL']['bars_completed'] == 4
+        
+        assert aggregator.remove_symbol('MNQ') is True
+        assert 'MNQ' not in aggregator.symbols()
+        assert aggregator.get_bar_history('MNQ', '1m') == []
+        assert aggregator.bars_completed == (len(symbols) - 1) * 4
+
+
+class TestThresholdBars:
+    """Test volume, tick-count and dollar bars"""
+    
+    def test_parse_bar_threshold(self):
+        """Test threshold timeframe codes"""
+        assert parse_bar_threshold('1000v') == ('volume', 1000.0)
+        assert parse_bar_threshold('50t') == ('tick', 50.0)
+        assert parse_bar_threshold('250000$') == ('dollar', 250000.0)
+        assert parse_bar_threshold('5m') is None
+        assert parse_bar_threshold('0v') is None
+    
+    def test_volume_bars(self):
+        """Test volume bars close once the contract threshold is reached"""
+        aggregator = BarAggregator(default_timeframes=['10v'])
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        for i, size in enumerate([4, 4, 3, 5]):
+            aggregator.add_quote('MNQ', 15000.0 + i, volume=size, timestamp=start + timedelta(seconds=i))
+        
+        history = aggregator.get_bar_history('MNQ', '10v')
+        assert len(history) == 1
+        assert history[0].volume == 11
+        assert history[0].open == 15000.0 and history[0].close == 15002.0
+        assert history[0].timestamp == start
+        assert aggregator.get_current_bar('MNQ', '10v').volume == 5
+    
+    def test_tick_bars(self):
+        """Test tick bars close every N trades"""
+        aggregator = BarAggregator(default_timeframes=['3t'])
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        for i in range(7):
+            aggregator.add_quote('MNQ', 15000.0 + i, volume=1, timestamp=start + timedelta(seconds=i))
+        
+        closes = [b.close for b in aggregator.get_bar_history('MNQ', '3t')]
+        assert closes == [15002.0, 15005.0]
+    
+    def test_dollar_bars_use_contract_multiplier(self):
+        """Test dollar bars accumulate price x size x multiplier"""
+        aggregator = BarAggregator(default_timeframes=['100000$'], contract_multipliers={'mnq': 2.0})
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        aggregator.add_quote('MNQ', 20000.0, volume=2, timestamp=start)  # 80,000
+        assert aggregator.get_bar_history('MNQ', '100000$') == []
+        aggregator.add_quote('MNQ', 20000.0, volume=1, timestamp=start + timedelta(seconds=1))  # 120,000
+        
+        assert len(aggregator.get_bar_history('MNQ', '100000$')) == 1
+    
+    def test_threshold_bars_not_closed_by_time(self):
+        """Test activity bars ignore elapsed time and mix with time bars"""
+        aggregator = BarAggregator(default_timeframes=['1m', '100v'])
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        aggregator.add_quote('MNQ', 15000.0, volume=5, timestamp=start)
+        
+        closed = aggregator.close_elapsed_bars(start + timedelta(minutes=5))
+        assert [b.timeframe for b in closed] == ['1m']
+        assert aggregator.get_current_bar('MNQ', '100v').volume == 5
+
+
+class TestGapRepair:
+    """Test gap detection and REST backfill"""
+    
+    @staticmethod
+    def _feed_minutes(aggregator, start, minutes):
+        for minute in minutes:
+            aggregator.add_quote('MNQ', 15000.0 + minute, volume=1, timestamp=start + timedelta(minutes=minute, seconds=5))
+    
+    @pytest.mark.asyncio
+    async def test_gap_detected_and_backfilled(self):
+        """Test missing 1m bars are fetched and merged into history in order"""
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        requests, repaired = [], []
+        
+        async def backfill(symbol, timeframe, gap_start, gap_end):
+            requests.append((symbol, timeframe, gap_start, gap_end))
+            return [Bar(symbol, timeframe, start + timedelta(minutes=m), 1.0, 1.0, 1.0, 1.0, volume=2) for m in (2, 3, 4)]
+        
+        aggregator = BarAggregator(default_timeframes=['1m'], gap_backfill=backfill, gap_callback=repaired.append)
+        self._feed_minutes(aggregator, start, [0, 1, 5, 6])  # Dropout during minutes 2-4
+        assert len(aggregator.pending_gaps) == 1
+        
+        gaps = await aggregator.repair_gaps()
+        assert requests[0][:3] == ('MNQ', '1m', start + timedelta(minutes=2))
+        assert requests[0][3] < start + timedelta(minutes=5)
+        assert [(g.missing, g.filled) for g in gaps] == [(3, 3)]
+        assert repaired == gaps
+        history = aggregator.get_bar_history('MNQ', '1m')
+        assert [b.timestamp for b in history] == [start + timedelta(minutes=m) for m in range(6)]
+        assert aggregator.get_gap_stats()['repaired'] == 1
+    
+    @pytest.mark.asyncio
+    async def test_failed_backfill_is_retried(self):
+        """Test backfill errors keep the gap queued until max attempts"""
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        
+        async def backfill(*args):
+            raise RuntimeError("history unavailable")
+        
+        aggregator = BarAggregator(default_timeframes=['1m'], gap_backfill=backfill)
+        self._feed_minutes(aggregator, start, [0, 3, 4])
+        assert await aggregator.repair_gaps(max_attempts=2) == []
+        assert len(aggregator.pending_gaps) == 1
+        await aggregator.repair_gaps(max_attempts=2)
+        assert len(aggregator.pending_gaps) == 0
+    
+    def test_session_breaks_and_unchecked_timeframes_skipped(self):
+        """Test long gaps and non-gap timeframes are not queued"""
+        start = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+        
+        async def backfill(*args):
+            return []
+        
+        aggregator = BarAggregator(default_timeframes=['1m', '1s'], gap_backfill=backfill, max_gap_bars=30)
+        self._feed_minutes(aggregator, start, [0, 1, 120, 121])  # 2h break
+        assert len(aggregator.pending_gaps) == 0
+        assert aggregator.get_gap_stats()['skipped'] == 1
+
+
+class TestSessionAlignment:
+    """Test session-anchored bars with a SessionCalendar"""
+    
+    def test_bars_anchor_to_session_open_and_flag_rth(self):
+        """Test 90m bars start at the 17:00 CT open and RTH bars are flagged"""
+        aggregator = BarAggregator(default_timeframes=['90m', '1m'], session_calendar=SessionCalendar())
+        session_open = datetime(2025, 11, 18, 23, 0, tzinfo=timezone.utc)  # Tue 17:00 CT
+        assert aggregator._get_bar_start_time(session_open + timedelta(minutes=100), '90m', 'MNQ') == \
+            session_open + timedelta(minutes=90)
+        # Epoch alignment without a symbol (or calendar)
+        assert aggregator._get_bar_start_time(session_open + timedelta(minutes=100), '90m') == \
+            datetime(2025, 11, 19, 0, 0, tzinfo=timezone.utc)
+        
+        rth_open = datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc)  # Wed 08:30 CT
+        aggregator.add_quote('MNQ', 15000.0, timestamp=rth_open - timedelta(seconds=30))
+        aggregator.add_quote('MNQ', 15001.0, timestamp=rth_open + timedelta(seconds=5))
+        aggregator.add_quote('MNQ', 15002.0, timestamp=rth_open + timedelta(minutes=1, seconds=5))
+        assert [b.is_rth for b in aggregator.get_bar_history('MNQ', '1m')] == [False, True]
+        assert aggregator.get_current_bar('MNQ', '1m').is_rth is True
+    
+    def test_last_bar_closes_at_session_close(self):
+        """Test a bar spanning the session close ends at the close"""
+        aggregator = BarAggregator(default_timeframes=['4h'], session_calendar=SessionCalendar())
+        session_close = datetime(2025, 11, 19, 22, 0, tzinfo=timezone.utc)  # Wed 16:00 CT
+        bar_start = aggregator._get_bar_start_time(session_close - timedelta(minutes=30), '4h', 'MNQ')
+        assert bar_start == datetime(2025, 11, 19, 19, 0, tzinfo=timezone.utc)
+        assert aggregator._get_bar_end_time(bar_start, '4h', 'MNQ') == session_close
+    
+    def test_maintenance_break_is_not_a_gap(self):
+        """Test missing bars across the daily break are not queued for backfill"""
+        async def backfill(*args):
+            return []
+        
+        aggregator = BarAggregator(default_timeframes=['1m'], gap_backfill=backfill,
+                                   session_calendar=SessionCalendar())
+        close = datetime(2025, 11, 19, 22, 0, tzinfo=timezone.utc)
+        for ts in (close - timedelta(minutes=2), close - timedelta(minutes=1), close + timedelta(hours=1),
+                   close + timedelta(hours=1, minutes=1)):
+            aggregator.add_quote('MNQ', 15000.0, volume=1, timestamp=ts + timedelta(seconds=5))
+        assert len(aggregator.pending_gaps) == 0
+        assert aggregator.gaps_detected == 0
+
+
 if __name__ == '__main__':
     pytest.main([__file__, '-v'])
 
@@ -0,0 +1,103 @@
+"""
+Unit tests for columnar bar loads (binary COPY to numpy/Arrow) in the database module
+"""
+
+import pytest
+import os
+import struct
+import sys
+from contextlib import contextmanager
+from datetime import datetime, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+pytest.importorskip('psycopg2')
+
+from infrastructure.database import BAR_COPY_FIELDS, PGCOPY_SIGNATURE, DatabaseManager, parse_pgcopy_float8
+
+START = datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc)
+
+
+def pgcopy(rows):
+    """Binary COPY payload of float8 rows."""
+    payload = PGCOPY_SIGNATURE + struct.pack('>II', 0, 0)
+    for row in rows:
+        payload += struct.pack('>h', len(row))
+        for value in row:
+            payload += struct.pack('>id', 8, value)
+    return payload + struct.pack('>h', -1)
+
+
+def make_db(payload):
+    db = DatabaseManager.__new__(DatabaseManager)
+    db.copied = []
+
+    class Cursor:
+        def __enter__(self):
+            return self
+
+        def __exit__(self, *exc):
+            return False
+
+        def mogrify(self, query, params):
+            return (query.replace('%s', "'{}'").format(*params)).encode()
+
+        def copy_expert(self, query, buffer):
+            db.copied.append(query)
+            buffer.write(payload)
+
+    class Conn:
+        def cursor(self, cursor_factory=None):
+            return Cursor()
+
+    @contextmanager
+    def get_connection(read_only=False):
+        assert read_only
+        yield Conn()
+
+    db.get_connection = get_connection
+    return db
+
+
+class TestBarArrays:
+    """Test binary COPY decoding and the get_bars() outputs."""
+
+    def rows(self):
+        ts = START.timestamp()
+        return [(ts, 1.0, 2.0, 0.5, 1.5, 10, 4), (ts + 60, 1.5, 3.0, 1.0, 2.5, 20, 0)]
+
+    def test_rejects_payloads_that_are_not_float8_rows(self):
+        with pytest.raises(ValueError, match='Not a binary COPY'):
+            parse_pgcopy_float8(b'ts,open\n', BAR_COPY_FIELDS)
+        with pytest.raises(ValueError, match='truncated'):
+            parse_pgcopy_float8(pgcopy(self.rows())[:-2], BAR_COPY_FIELDS)
+        with pytest.raises(ValueError, match='non-null float8'):
+            parse_pgcopy_float8(pgcopy([row[:6] for row in self.rows()]), BAR_COPY_FIELDS)
+        with pytest.raises(ValueError):
+            make_db(b'').get_bars('MNQ', '1m', START, START, output='pandas')
+
+    def test_get_bars_numpy(self):
+        np = pytest.importorskip('numpy')
+        db = make_db(pgcopy(self.rows()))
+        columns = db.get_bars('MNQ', '1m', START, START.replace(hour=15))
+        assert db.copied[0].startswith('COPY (SELECT') and 'TO STDOUT WITH (FORMAT binary)' in db.copied[0]
+        assert "timestamp >= '2025-11-19 14:30:00+00:00'" in db.copied[0]
+        assert list(columns) == list(BAR_COPY_FIELDS)
+        assert columns['close'].dtype == np.float64 and columns['close'].tolist() == [1.5, 2.5]
+        assert columns['timestamp'][1] - columns['timestamp'][0] == 60
+        assert columns['open'].base is columns['close'].base  # One contiguous block
+        assert parse_pgcopy_float8(pgcopy([]), BAR_COPY_FIELDS)['open'].shape == (0,)
+
+    def test_get_bars_arrow(self):
+        pytest.importorskip('numpy')
+        pytest.importorskip('pyarrow')
+        from core.arrow_export import bar_schema
+        batch = make_db(pgcopy(self.rows())).get_bars('MNQ', '1m', START, START.replace(hour=15), output='arrow')
+        assert batch.schema == bar_schema() and batch.num_rows == 2
+        assert batch.column('timestamp')[0].as_py() == START
+        assert batch.column('symbol').to_pylist() == ['MNQ', 'MNQ'] and batch.column('tick_count').to_pylist() == [4, 0]
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,117 @@
+"""
+Unit tests for the one-call session bootstrap
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timedelta, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar, BarAggregator
+from core.bootstrap import (BootstrapConfig, BootstrapError, BootstrapStage, bootstrap, select_account)
+
+ACCOUNTS = [{'id': 1, 'name': 'EXPRESS-1'}, {'id': 2, 'name': 'PRAC-2'}]
+
+
+class FakeHistory:
+    async def fetch_bars(self, symbol, timeframe, start, end):
+        if symbol == 'MES':
+            raise RuntimeError("history down")
+        base = datetime(2025, 11, 19, 14, 0, tzinfo=timezone.utc)
+        return [Bar(symbol, timeframe, base + timedelta(minutes=i), 1.0, 2.0, 0.5, 1.5, 10) for i in range(5)]
+
+
+class FakeStrategies:
+    def __init__(self):
+        self.calls = []
+
+    async def apply_persisted_states(self):
+        self.calls.append('apply')
+
+    async def auto_start_enabled_strategies(self):
+        self.calls.append('start')
+
+
+class FakeBot:
+    def __init__(self, token_results=(True,)):
+        self.token_results = list(token_results)
+        self.selected_account = None
+        self.db = None
+        self.subscribed = []
+        self.history_client = FakeHistory()
+        self.bar_aggregator = BarAggregator(default_timeframes=['1m'])
+        self.strategy_manager = FakeStrategies()
+        self.claimed = False
+
+    def claim_artifact_dirs(self):
+        self.claimed = True
+
+    async def _ensure_valid_token(self):
+        return self.token_results.pop(0)
+
+    async def list_accounts(self):
+        return ACCOUNTS
+
+    async def get_available_contracts(self):
+        return []
+
+    def _get_contract_id(self, symbol):
+        if symbol == 'XYZ':
+            raise ValueError("not found")
+        return f"CON.F.US.{symbol}.Z25"
+
+    async def _ensure_market_socket_started(self):
+        pass
+
+    async def _ensure_quote_subscription(self, symbol):
+        self.subscribed.append(symbol)
+
+    async def reconcile_account_state(self, account_id):
+        return {'MNQ': 1}, [{'id': 9}]
+
+
+class TestBootstrap:
+    """Test the startup sequence, progress reporting and account selection."""
+
+    @pytest.mark.asyncio
+    async def test_full_sequence_reports_progress_and_collects_warnings(self):
+        bot = FakeBot()
+        events = []
+        config = BootstrapConfig(symbols=['MNQ', 'MES', 'XYZ'], warmup_timeframes=['1m'], warmup_bars=3)
+        session = await bootstrap(config, bot=bot, progress=events.append)
+
+        assert bot.claimed
+        assert session.account['name'] == 'PRAC-2' and bot.selected_account is session.account
+        assert session.contracts == {'MNQ': 'CON.F.US.MNQ.Z25', 'MES': 'CON.F.US.MES.Z25'}
+        assert bot.subscribed == ['MNQ', 'MES']
+        assert session.warmed_bars == {'MNQ:1m': 3}
+        assert len(bot.bar_aggregator.get_bar_history('MNQ', '1m')) == 3
+        assert session.positions == {'MNQ': 1} and session.open_orders == 1
+        assert bot.strategy_manager.calls == ['apply', 'start']
+        assert any('XYZ' in w for w in session.warnings) and any('MES:1m' in w for w in session.warnings)
+        completed = [e.stage for e in events if e.status == 'completed']
+        assert completed == list(BootstrapStage)
+
+    @pytest.mark.asyncio
+    async def test_auth_failure_is_fatal(self):
+        events = []
+        with pytest.raises(BootstrapError) as exc:
+            await bootstrap(BootstrapConfig(auth_retries=1), bot=FakeBot((False,)), progress=events.append)
+        assert exc.value.stage is BootstrapStage.AUTHENTICATE
+        assert events[-1].status == 'failed'
+
+    def test_select_account_prefers_practice(self):
+        assert select_account(ACCOUNTS, '1')['id'] == 2  # Non-PRAC preference swapped for PRAC
+        assert select_account(ACCOUNTS)['id'] == 2
+        assert select_account([{'id': 5, 'name': 'LIVE'}], '5')['id'] == 5
+        with pytest.raises(BootstrapError):
+            select_account(ACCOUNTS, '42')
+        with pytest.raises(BootstrapError):
+            select_account([])
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,127 @@
+"""
+Unit tests for break-even stop management
+"""
+
+import pytest
+import asyncio
+import os
+import sys
+from datetime import datetime, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.break_even import BreakEvenManager
+from core.market_events import Quote, Trade
+
+T0 = datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc)
+
+
+class FakeBroker:
+    """Bracket leg lookup and order modification with canned responses"""
+
+    def __init__(self, stop_price=20990.0):
+        self.stop = {'id': 71, 'type': 4, 'stopPrice': stop_price}
+        self.modified = []
+        self.reject = 0
+
+    async def find_bracket_legs(self, order_id, account_id=None):
+        return {'stop': self.stop, 'target': None} if self.stop else {'error': 'no legs'}
+
+    async def modify_order(self, order_id, new_price=None, account_id=None, order_type=None):
+        self.modified.append((order_id, new_price, account_id, order_type))
+        if self.reject:
+            self.reject -= 1
+            return {'error': 'price outside limits'}
+        return {'success': True}
+
+
+def fill(order_id, side, price):
+    return {'id': order_id, 'contractId': 'CON.F.US.MNQ.Z25', 'side': side, 'fillPrice': price, 'status': 2}
+
+
+async def settle():
+    for _ in range(5):
+        await asyncio.sleep(0)
+
+
+def make_manager(broker, **kwargs):
+    kwargs.setdefault('trigger_ticks', 8)
+    kwargs.setdefault('offset_ticks', 1)
+    return BreakEvenManager(modify=broker.modify_order, find_legs=broker.find_bracket_legs, **kwargs)
+
+
+class TestBreakEvenManager:
+    """Test arming on fill, the live trigger and stop modification"""
+
+    @pytest.mark.asyncio
+    async def test_long_stop_moved_on_first_price_past_trigger(self):
+        broker = FakeBroker()
+        manager = make_manager(broker)
+        manager.watch('500')
+        manager.on_fill('123', fill(500, 0, 21000.0))
+        await settle()
+        assert manager.get_positions()['500']['stop_order_id'] == '71'  # Resolved before the trigger
+        manager.on_market_event(Quote('MNQ', T0, bid=21001.75, ask=21002.0))  # 7 ticks
+        await settle()
+        assert broker.modified == []
+        manager.on_market_event(Quote('MNQ', T0, bid=21002.0, ask=21002.25))  # 8 ticks at the bid
+        manager.on_market_event(Trade('MNQ', T0, 21003.0))  # Already moving: not sent twice
+        await settle()
+        assert broker.modified == [('71', 21000.25, '123', 4)]
+        position = manager.get_positions()['500']
+        assert position['state'] == 'moved' and position['trigger_price'] == 21002.0
+        stats = manager.get_stats()
+        assert stats['stops_moved'] == 1 and stats['armed_positions'] == 0 and stats['watched_orders'] == 0
+
+    @pytest.mark.asyncio
+    async def test_short_uses_ask_and_never_loosens_stop(self):
+        broker = FakeBroker(stop_price=20999.0)  # Stop already below entry
+        manager = make_manager(broker)
+        manager.watch('600', trigger_ticks=4, offset_ticks=0)
+        manager.on_fill('123', fill(600, 1, 21000.0))
+        await settle()
+        manager.on_market_event(Quote('MNQ', T0, bid=20998.75, ask=20999.25))  # 3 ticks at the ask
+        manager.on_market_event(Quote('MNQ', T0, bid=20998.75, ask=20999.0))
+        await settle()
+        assert broker.modified == [] and manager.get_positions()['600']['state'] == 'skipped'
+        assert manager.get_stats()['stops_skipped'] == 1
+
+    @pytest.mark.asyncio
+    async def test_failed_modification_retries_then_gives_up(self):
+        broker = FakeBroker()
+        broker.reject = 5
+        manager = make_manager(broker, max_attempts=2)
+        manager.arm('700', '123', 'MNQ', 'BUY', 21000.0)
+        await settle()
+        for _ in range(3):
+            manager.on_market_event(Trade('MNQ', T0, 21005.0))
+            await settle()
+        assert len(broker.modified) == 2
+        assert manager.get_positions()['700']['state'] == 'failed'
+        stats = manager.get_stats()
+        assert stats['modify_failures'] == 2 and stats['last_error'] == 'price outside limits'
+
+    @pytest.mark.asyncio
+    async def test_opposite_fill_disarms_and_unwatched_fills_are_ignored(self):
+        broker = FakeBroker()
+        manager = make_manager(broker)
+        manager.on_fill('123', fill(800, 0, 21000.0))  # Not watched
+        assert manager.get_positions() == {}
+        manager.watch('801')
+        manager.on_fill('123', fill(801, 0, 21000.0))
+        manager.on_fill('123', fill(71, 1, 20990.0))  # Stop leg filled
+        manager.on_market_event(Trade('MNQ', T0, 21010.0))
+        await settle()
+        assert manager.get_positions() == {} and broker.modified == []
+
+    def test_trigger_must_exceed_offset(self):
+        broker = FakeBroker()
+        with pytest.raises(ValueError):
+            make_manager(broker, trigger_ticks=2, offset_ticks=2)
+        with pytest.raises(ValueError):
+            make_manager(broker).watch('1', trigger_ticks=0)
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
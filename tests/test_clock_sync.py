@@ -0,0 +1,81 @@
+"""
+Unit tests for server time synchronization
+"""
+
+import pytest
+import os
+import sys
+import time
+from datetime import datetime, timezone
+from email.utils import formatdate
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+import core.clock_sync as clock_sync
+from core.bar_aggregator import BarAggregator
+from core.clock_sync import ServerClock, server_now
+
+
+def make_clock(**kwargs):
+    kwargs.setdefault('enabled', True)
+    kwargs.setdefault('min_samples', 3)
+    return ServerClock(window_minutes=15, max_offset_secs=300, warn_ms=250, **kwargs)
+
+
+class TestServerClock:
+    """Test offset estimation and the corrected clock"""
+
+    def test_feed_estimate_uses_fastest_message(self):
+        clock = make_clock()
+        received = 1_700_000_000.0
+        # Exchange clock 2s ahead of ours; messages delayed 50ms, 5ms and 300ms
+        for delay in (0.05, 0.005, 0.3):
+            clock.record_exchange_time(received + 2.0 - delay, received_at=received)
+        assert clock.source == 'feed' and clock.offset_ms == pytest.approx(1995.0)
+        payload = [{'timestamp': datetime.fromtimestamp(received + 2.0, timezone.utc).isoformat()}]
+        assert clock.record_payload(payload, received_at=received) == pytest.approx(2000.0)
+        assert clock.offset_ms == pytest.approx(2000.0)
+
+    def test_http_date_until_feed_has_enough_samples(self):
+        clock = make_clock()
+        sent = 1_700_000_000.2  # Header second 3 -> 3.5 expected, against the request midpoint .3
+        for _ in range(3):
+            clock.record_http_date(formatdate(1_700_000_003.0, usegmt=True), sent, sent + 0.2)
+        assert clock.source == 'http' and clock.offset_ms == pytest.approx(3200.0)
+        assert clock.record_http_date('not a date', sent) is None and clock.record_http_date(None, sent) is None
+        clock.record_exchange_time(sent + 0.5, received_at=sent)
+        assert clock.source == 'http'  # One feed sample is not enough yet
+        clock.record_exchange_time(sent + 0.5, received_at=sent)
+        clock.record_exchange_time(sent + 0.5, received_at=sent)
+        assert clock.source == 'feed' and clock.offset_ms == pytest.approx(500.0)
+
+    def test_rejects_implausible_samples_and_applies_offset(self):
+        clock = make_clock()
+        assert clock.record_exchange_time(time.time() - 3600) is None  # Stale replayed print
+        assert clock.samples_rejected == 1 and clock.source is None and clock.offset_ms == 0.0
+        for _ in range(3):
+            clock.record_exchange_time(time.time() + 10.0)
+        assert clock.time() - time.time() == pytest.approx(10.0, abs=0.1)
+        assert (clock.now() - datetime.now(timezone.utc)).total_seconds() == pytest.approx(10.0, abs=0.1)
+        clock.enabled = False  # Still estimated and reported, no longer applied
+        assert clock.time() == pytest.approx(time.time(), abs=0.1)
+        assert clock.get_stats()['applied_offset_ms'] == 0.0 and clock.get_stats()['offset_ms'] > 9000
+        clock.reset()
+        assert clock.source is None and clock.get_stats()['feed_samples_in_window'] == 0
+
+    def test_bars_follow_the_server_clock(self, monkeypatch):
+        clock = make_clock()
+        for _ in range(3):
+            clock.record_exchange_time(time.time() + 120.0)  # Local clock two minutes behind
+        monkeypatch.setattr(clock_sync, '_server_clock', clock)
+        assert (server_now() - datetime.now(timezone.utc)).total_seconds() == pytest.approx(120.0, abs=0.1)
+        aggregator = BarAggregator(default_timeframes=['1m'], bar_queue_maxsize=0)
+        aggregator.add_quote('MNQ', 21000.0, 1)  # No timestamp: stamped on the server clock
+        bar = aggregator.get_current_bar('MNQ', '1m')
+        expected = server_now().replace(second=0, microsecond=0)
+        assert bar is not None and abs((bar.timestamp - expected).total_seconds()) in (0, 60)
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,86 @@
+"""
+Unit tests for the embedded REST control API
+"""
+
+import pytest
+import json
+import os
+import sys
+from unittest.mock import AsyncMock, patch
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from trading_bot import TopStepXTradingBot
+from servers.control_api import ControlAPIServer
+
+TOKEN = 'control-token'
+AUTH = {'Authorization': f'Bearer {TOKEN}'}
+
+
+@pytest.fixture
+def bot():
+    with patch.dict(os.environ, {'PROJECT_X_API_KEY': 'test_key', 'PROJECT_X_USERNAME': 'test_user'}):
+        bot = TopStepXTradingBot(api_key='test_key', username='test_user')
+    bot.session_token = 'token'
+    bot.selected_account = {'id': 12345, 'name': 'TEST_ACCOUNT'}
+    bot.get_open_positions = AsyncMock(return_value=[{'id': 1, 'contractId': 'CON.F.US.MNQ.Z25', 'size': 2}])
+    bot.get_open_orders = AsyncMock(return_value=[])
+    bot.flatten_all_positions = AsyncMock(return_value={'success': True})
+    bot.strategy_manager.start_strategy = AsyncMock(return_value=(True, 'Strategy started: orb'))
+    bot.strategy_manager.stop_all_strategies = AsyncMock(return_value={'orb': (True, 'Strategy stopped: orb')})
+    return bot
+
+
+class TestControlAPI:
+    """Test authentication, state endpoints, strategy controls and the kill switch"""
+
+    @pytest.mark.asyncio
+    async def test_requires_token(self, bot):
+        api = ControlAPIServer(bot, token=TOKEN)
+        assert (await api.dispatch('GET', '/control/health'))[0] == 401
+        assert (await api.dispatch('GET', '/control/health', {'Authorization': 'Bearer wrong'}))[0] == 401
+        assert (await api.dispatch('GET', '/control/health', {'X-API-Key': TOKEN}))[0] == 200
+        assert (await api.dispatch('GET', '/control/nothing', AUTH))[0] == 404
+        status, body = await api.dispatch('PUT', '/control/kill-switch', AUTH)
+        assert status == 405 and 'GET, POST, DELETE' in body['error']
+        assert api.get_stats()['unauthorized'] == 2
+        with pytest.raises(ValueError, match='CONTROL_API_TOKEN'):
+            await ControlAPIServer(bot, token='').start()
+
+    @pytest.mark.asyncio
+    async def test_state_endpoints(self, bot):
+        api = ControlAPIServer(bot, token=TOKEN, health=lambda: {'task_queue': {'pending': 0}})
+        status, health = await api.dispatch('GET', '/control/health', AUTH)
+        assert status == 200 and health['status'] == 'healthy' and health['account_id'] == 12345
+        assert health['task_queue'] == {'pending': 0} and health['kill_switch'] is None
+        status, positions = await api.dispatch('GET', '/control/positions', AUTH, {'account_id': '999'})
+        assert status == 200 and positions[0]['size'] == 2
+        bot.get_open_positions.assert_awaited_once_with(account_id='999')
+        await api.dispatch('GET', '/control/orders/', AUTH)
+        bot.get_open_orders.assert_awaited_once_with(account_id='12345')
+        status, pnl = await api.dispatch('GET', '/control/pnl', AUTH)
+        assert status == 200 and set(pnl) >= {'realized_pnl', 'unrealized_pnl', 'total_pnl', 'current_balance'}
+        assert (await api.dispatch('POST', '/control/strategies/orb/enable', AUTH, body=b'[1]'))[0] == 400
+
+    @pytest.mark.asyncio
+    async def test_kill_switch(self, bot):
+        api = ControlAPIServer(bot, token=TOKEN)
+        status, body = await api.dispatch('POST', '/control/kill-switch', AUTH, body=json.dumps({'reason': 'news'}))
+        assert status == 200 and body['engaged'] and body['reason'] == 'news'
+        assert body['strategies_stopped'] == ['orb'] and body['engaged_by'] == 'control_api'
+        bot.flatten_all_positions.assert_awaited_once_with(interactive=False)
+        assert bot._pre_trade_symbol_error('MNQ') == {"error": "Kill switch engaged: news", "kill_switch": True}
+        assert (await api.dispatch('GET', '/control/health', AUTH))[1]['status'] == 'halted'
+        status, body = await api.dispatch('POST', '/control/strategies/orb/enable', AUTH)
+        assert status == 409 and 'Kill switch' in body['error']
+        assert (await api.dispatch('DELETE', '/control/kill-switch', AUTH)) == (200, {"engaged": False})
+        assert bot._pre_trade_symbol_error('MNQ') is None
+        status, body = await api.dispatch('POST', '/control/strategies/orb/enable', AUTH,
+                                          body=b'{"symbols": ["MNQ"]}')
+        assert status == 200 and body['success']
+        bot.strategy_manager.start_strategy.assert_awaited_once_with('orb', symbols=['MNQ'])
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,113 @@
+"""
+Unit tests for the correlation / cointegration screener
+"""
+
+import pytest
+import math
+import random
+import os
+import sys
+from unittest.mock import MagicMock
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.correlation_screener import (
+    CorrelationScreener, screen_pair, pearson_correlation, align_series,
+)
+
+
+def _random_walk(n, seed, start=100.0):
+    rng = random.Random(seed)
+    prices = [start]
+    for _ in range(n - 1):
+        prices.append(prices[-1] * math.exp(rng.gauss(0, 0.01)))
+    return prices
+
+
+@pytest.fixture
+def universe():
+    """Two cointegrated symbols plus one independent random walk"""
+    n = 300
+    base = _random_walk(n, seed=1)
+    rng = random.Random(2)
+    paired = [p * 1.5 * math.exp(rng.gauss(0, 0.002)) for p in base]
+    independent = _random_walk(n, seed=3)
+    stamps = [f"2025-01-01T00:00:{i:05d}" for i in range(n)]
+    return {
+        'ES': dict(zip(stamps, base)),
+        'MES': dict(zip(stamps, paired)),
+        'GC': dict(zip(stamps, independent)),
+    }
+
+
+class TestScreenerMath:
+    """Test screener statistics"""
+
+    def test_pearson_perfect(self):
+        """Test perfectly correlated series"""
+        assert pearson_correlation([1, 2, 3, 4], [2, 4, 6, 8]) == pytest.approx(1.0)
+        assert pearson_correlation([1, 2, 3, 4], [8, 6, 4, 2]) == pytest.approx(-1.0)
+
+    def test_pearson_constant_series(self):
+        """Test constant series returns 0 instead of dividing by zero"""
+        assert pearson_correlation([1, 1, 1], [1, 2, 3]) == 0.0
+
+    def test_align_series_inner_join(self):
+        """Test alignment keeps only common timestamps in order"""
+        a, b = align_series({'t2': 2.0, 't1': 1.0, 't3': 3.0}, {'t3': 30.0, 't1': 10.0})
+        assert a == [1.0, 3.0]
+        assert b == [10.0, 30.0]
+
+    def test_screen_pair_rejects_non_positive_prices(self):
+        """Test log-price screening rejects non-positive prices"""
+        assert screen_pair('A', 'B', [1.0, 0.0, 2.0], [1.0, 2.0, 3.0]) is None
+
+    def test_screen_pair_detects_cointegration(self, universe):
+        """Test cointegrated pair is flagged with sensible hedge ratio"""
+        a, b = align_series(universe['MES'], universe['ES'])
+        result = screen_pair('MES', 'ES', a, b)
+        assert result.cointegrated is True
+        assert result.hedge_ratio == pytest.approx(1.0, rel=0.05)
+        assert result.half_life is not None and result.half_life < 5
+
+
+class TestCorrelationScreener:
+    """Test CorrelationScreener"""
+
+    def test_screen_ranks_cointegrated_pair_first(self, universe):
+        """Test ranking puts the cointegrated pair on top"""
+        screener = CorrelationScreener(max_workers=1, min_observations=50)
+        results = screener.screen(universe)
+        assert len(results) == 3
+        assert (results[0].symbol_a, results[0].symbol_b) == ('ES', 'MES')
+        assert results[0].cointegrated is True
+
+    def test_screen_parallel_matches_sequential(self, universe):
+        """Test process pool gives the same ranking as sequential"""
+        sequential = CorrelationScreener(max_workers=1, min_observations=50).screen(universe)
+        parallel = CorrelationScreener(max_workers=2, min_observations=50).screen(universe)
+        assert [(r.symbol_a, r.symbol_b) for r in parallel] == [(r.symbol_a, r.symbol_b) for r in sequential]
+
+    def test_min_observations_filters_pairs(self, universe):
+        """Test pairs shorter than min_observations are skipped"""
+        screener = CorrelationScreener(max_workers=1, min_observations=1000)
+        assert screener.screen(universe) == []
+
+    def test_load_universe_from_database(self):
+        """Test loading closes for all cached symbols"""
+        db = MagicMock()
+        db.get_cached_symbols.return_value = ['ES', 'NQ']
+        db.get_cached_bars.side_effect = lambda symbol, timeframe, limit: [
+            {'timestamp': f't{i}', 'close': 100.0 + i} for i in range(3 if symbol == 'ES' else 1)
+        ]
+        screener = CorrelationScreener(db=db, timeframe='1d', min_observations=2, max_workers=1)
+        universe = screener.load_universe()
+
+        db.get_cached_symbols.assert_called_once_with('1d')
+        assert list(universe) == ['ES']
+        assert universe['ES']['t2'] == 102.0
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,65 @@
+"""
+Unit tests for CSV import/export of bars and ticks
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar
+from core.csv_io import CsvSchema, read_bars_csv, read_ticks_csv, write_bars_csv, write_ticks_csv
+from core.market_events import Trade
+
+
+class TestCsvImport:
+    """Test platform schemas, timezone handling and custom mappings."""
+
+    def test_ninjatrader_bars_are_converted_from_exchange_time(self, tmp_path):
+        path = tmp_path / "MNQ.Last.txt"
+        path.write_text("20251119 083100;21000.25;21001;20999.5;21000.75;152\n"
+                        "20251119 083000;21000;21000.5;20999.75;21000.25;98\n"
+                        "garbage line\n")
+        bars = read_bars_csv(path, 'mnq', '1m', 'ninjatrader')
+        assert [b.timestamp for b in bars] == [datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc),
+                                               datetime(2025, 11, 19, 14, 31, tzinfo=timezone.utc)]
+        assert bars[0].symbol == 'MNQ' and bars[0].volume == 98 and bars[1].close == 21000.75
+
+    def test_sierra_chart_and_ninjatrader_ticks(self, tmp_path):
+        sierra = tmp_path / "sierra.csv"
+        sierra.write_text("Date, Time, Open, High, Low, Last, Volume, NumberOfTrades, BidVolume, AskVolume\n"
+                          "2025/11/19, 08:30:00, 0, 21000.5, 21000.25, 21000.5, 3, 1, 0, 3\n")
+        tick = read_ticks_csv(sierra, 'MNQ', 'sierra_chart_tick')[0]
+        assert tick['timestamp'] == datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc)
+        assert (tick['price'], tick['size'], tick['bid'], tick['ask']) == (21000.5, 3, 21000.25, 21000.5)
+
+        nt = tmp_path / "MNQ.Last.tick.txt"
+        nt.write_text("20251119 083000 1230000;21000.5;21000.25;21000.5;2\n")
+        tick = read_ticks_csv(nt, 'MNQ', 'ninjatrader_tick')[0]
+        assert tick['timestamp'] == datetime(2025, 11, 19, 14, 30, 0, 123000, tzinfo=timezone.utc)
+
+    def test_custom_schema_round_trip(self, tmp_path):
+        schema = CsvSchema('custom', {'timestamp': 'Time', 'price': 'Px', 'size': 'Qty', 'side': 'Aggressor'},
+                           timestamp_format='epoch_ms', delimiter='|')
+        ticks = [Trade(symbol='MNQ', timestamp=datetime(2025, 11, 19, 14, 30, 0, 250000, tzinfo=timezone.utc),
+                       price=21000.25, size=2, side='buy')]
+        path = tmp_path / "ticks.psv"
+        assert write_ticks_csv(path, ticks, schema) == 1
+        assert path.read_text().splitlines()[0] == "Time|Px|Qty|Aggressor"
+        loaded = read_ticks_csv(path, 'MNQ', schema)
+        assert loaded[0]['timestamp'] == ticks[0].timestamp and loaded[0]['side'] == 'buy'
+
+        bars = [Bar('MNQ', '1m', datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc), 1.0, 2.0, 0.5, 1.5, 7)]
+        for name in ('default', 'ninjatrader', 'sierra_chart'):
+            out = tmp_path / f"bars-{name}.csv"
+            write_bars_csv(out, bars, name)
+            assert read_bars_csv(out, 'MNQ', '1m', name) == bars
+        with pytest.raises(ValueError):
+            read_bars_csv(path, 'MNQ', '1m', 'default')  # No timestamp/open columns
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
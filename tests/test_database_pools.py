@@ -0,0 +1,93 @@
+"""
+Unit tests for read/write pool routing in the database module
+"""
+
+import pytest
+import os
+import sys
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+psycopg2 = pytest.importorskip('psycopg2')
+
+from infrastructure.database import DatabaseManager, PoolStats
+
+
+class FakeConnection:
+    isolation_level = 0
+
+    def __init__(self, name):
+        self.name = name
+        self.committed = 0
+
+    def commit(self):
+        self.committed += 1
+
+    def rollback(self):
+        pass
+
+
+class FakePool:
+    def __init__(self, name, fail=False):
+        self.name = name
+        self.fail = fail
+        self.maxconn = 10
+        self._used = {}
+        self._pool = []
+
+    def getconn(self):
+        if self.fail:
+            raise psycopg2.OperationalError(f"{self.name} down")
+        conn = FakeConnection(self.name)
+        self._used[id(conn)] = conn
+        return conn
+
+    def putconn(self, conn, close=False):
+        self._used.pop(id(conn), None)
+        self._pool.append(conn)
+
+    def closeall(self):
+        pass
+
+
+def make_db(read_pool=None):
+    db = DatabaseManager.__new__(DatabaseManager)
+    db.pool = FakePool('primary')
+    db.read_pool = read_pool
+    db.pool_stats = {'primary': PoolStats('primary'), 'read': PoolStats('read')}
+    db.read_fallbacks = 0
+    return db
+
+
+class TestPoolRouting:
+    """Test read-only routing, replica fallback and pool metrics."""
+
+    def test_reads_use_replica_and_writes_use_primary(self):
+        db = make_db(FakePool('read'))
+        with db.get_connection(read_only=True) as conn:
+            assert conn.name == 'read'
+        with db.get_connection() as conn:
+            assert conn.name == 'primary'
+        stats = db.get_pool_stats()
+        assert stats['read']['checkouts'] == 1 and stats['primary']['checkouts'] == 1
+        assert stats['read']['in_use'] == 0 and stats['read']['idle'] == 1
+
+    def test_reads_share_primary_without_replica(self):
+        db = make_db()
+        with db.get_connection(read_only=True) as conn:
+            assert conn.name == 'primary'
+        assert db.get_pool_stats()['read'] is None
+
+    def test_replica_failure_falls_back_to_primary(self):
+        db = make_db(FakePool('read', fail=True))
+        with db.get_connection(read_only=True) as conn:
+            assert conn.name == 'primary'
+        stats = db.get_pool_stats()
+        assert stats['read_fallbacks'] == 1
+        assert stats['read']['errors'] == 1
+        assert db.pool._pool[0].name == 'primary'  # Returned to the pool it came from
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
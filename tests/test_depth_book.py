@@ -0,0 +1,93 @@
+"""
+Unit tests for the L2 depth book
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone, timedelta
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.depth_book import DepthBook
+
+
+T0 = datetime(2025, 11, 19, 14, 30, 0, tzinfo=timezone.utc)
+
+
+class TestDepthBook:
+    """Test DepthBook"""
+
+    def test_sorted_ladders_and_queries(self):
+        """Test best prices, removal, imbalance and snapshots"""
+        book = DepthBook('mnq')
+        book.apply_snapshot(bids=[(99.75, 4), (100.0, 6), (99.5, 10)], asks=[(100.5, 2), (100.25, 8)])
+        assert book.best_bid() == (100.0, 6)
+        assert book.best_ask() == (100.25, 8)
+        assert book.spread() == pytest.approx(0.25)
+        assert book.imbalance(levels=2) == pytest.approx((10 - 10) / 20)
+
+        book.apply_delta('bid', 100.0, 0)
+        assert book.best_bid() == (99.75, 4)
+        snapshot = book.snapshot(levels=2)
+        assert snapshot.bids == ((99.75, 4), (99.5, 10))
+        assert snapshot.to_dict()["asks"][0] == {"price": 100.25, "size": 8}
+
+    def test_sequence_gap_buffered_and_stale_dropped(self):
+        """Test out-of-order sequenced deltas apply in order and stale ones are dropped"""
+        book = DepthBook('MNQ')
+        book.apply_snapshot([(100.0, 1)], [(100.25, 1)], sequence=10)
+        assert book.apply_delta('bid', 100.0, 3, sequence=12) is False  # Early, buffered
+        assert book.best_bid() == (100.0, 1)
+        assert book.apply_delta('bid', 100.0, 2, sequence=11) is True
+        assert book.best_bid() == (100.0, 3)
+        assert book.last_sequence == 12
+        assert book.apply_delta('bid', 100.0, 9, sequence=11) is False
+        assert book.stale_updates == 1
+
+    def test_deltas_before_snapshot_replayed(self):
+        """Test deltas received before the first snapshot are replayed when newer"""
+        book = DepthBook('MNQ', require_snapshot=True)
+        book.apply_delta('ask', 100.25, 5, sequence=4)
+        book.apply_delta('ask', 100.5, 7, sequence=6)
+        assert not book.is_synced and book.best_ask() is None
+
+        book.apply_snapshot([(100.0, 1)], [(100.25, 1)], sequence=5)
+        assert book.is_synced
+        assert book.best_ask() == (100.25, 1)
+        assert book.snapshot().asks == ((100.25, 1), (100.5, 7))
+
+    def test_unfilled_gap_requires_snapshot(self):
+        """Test the book goes out of sync when a gap never fills"""
+        book = DepthBook('MNQ', max_pending=2)
+        book.apply_snapshot([(100.0, 1)], [], sequence=1)
+        for seq in (3, 4, 5):
+            book.apply_delta('bid', 99.0 + seq, 1, sequence=seq)
+        assert not book.is_synced
+        book.apply_snapshot([(101.0, 2)], [], sequence=5)
+        assert book.is_synced and book.best_bid() == (101.0, 2)
+
+    def test_timestamp_ordering_without_sequence(self):
+        """Test an older update for a level is ignored when no sequence is available"""
+        book = DepthBook('MNQ')
+        book.apply_delta('bid', 100.0, 5, timestamp=T0 + timedelta(seconds=2))
+        book.apply_delta('bid', 100.0, 1, timestamp=T0 + timedelta(seconds=1))
+        assert book.best_bid() == (100.0, 5)
+
+    def test_gateway_dom_entries(self):
+        """Test ProjectX DOM entries (bid/ask/reset/trade types)"""
+        book = DepthBook('MNQ')
+        processed = book.apply_gateway([
+            {"type": 2, "price": 100.0, "volume": 4, "timestamp": "2025-11-19T14:30:00Z"},
+            {"type": 1, "price": 100.25, "volume": 6, "timestamp": "2025-11-19T14:30:00Z"},
+            {"type": 5, "price": 100.25, "volume": 1, "timestamp": "2025-11-19T14:30:00Z"},
+        ])
+        assert processed == 2
+        assert book.imbalance() == pytest.approx(-0.2)
+        book.apply_gateway([{"type": 6, "timestamp": "2025-11-19T14:30:01Z"}])
+        assert book.depth() == (0, 0)
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
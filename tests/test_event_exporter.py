@@ -0,0 +1,81 @@
+"""
+Unit tests for the JSONL event bus exporter
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.event_exporter import EventExporter
+from core.market_events import Quote, Trade, market_event_from_dict
+from core.replay_fixtures import iter_recorded_events
+
+TS = datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc)
+
+
+def _trades(count):
+    return [Trade('MNQ', TS, 15000.0 + i * 0.25, 1, 'buy') for i in range(count)]
+
+
+class TestEventExporter:
+    """Test JSONL export, rotation and back-pressure."""
+
+    @pytest.mark.parametrize('compression', ['none', 'gzip'])
+    def test_round_trip_through_replay_reader(self, tmp_path, compression):
+        exporter = EventExporter(directory=tmp_path, compression=compression, flush_interval=0.01)
+        exporter.start()
+        events = _trades(3) + [Quote('MNQ', TS, bid=15000.0, ask=15000.25)]
+        for event in events:
+            exporter.on_market_event(event)
+        exporter.stop()
+
+        files = sorted(tmp_path.iterdir())
+        assert len(files) == 1
+        assert files[0].name.endswith('.jsonl.gz' if compression == 'gzip' else '.jsonl')
+        restored = [market_event_from_dict(d) for d in iter_recorded_events(files[0])]
+        assert restored == events
+        assert exporter.get_stats()['events_written'] == 4
+
+    def test_compact_lines_and_size_rotation(self, tmp_path):
+        exporter = EventExporter(directory=tmp_path, rotate_bytes=200, flush_interval=60)
+        for event in _trades(2):
+            exporter.on_market_event(event)
+        exporter._drain()
+        for event in _trades(2):
+            exporter.on_market_event(event)
+        exporter.stop()
+
+        files = sorted(tmp_path.iterdir())
+        assert len(files) == 2
+        line = files[0].read_text().splitlines()[0]
+        assert ', ' not in line and '": ' not in line  # No whitespace padding
+        assert sum(len(f.read_text().splitlines()) for f in files) == 4
+
+    def test_full_buffer_drops_instead_of_blocking(self, tmp_path):
+        exporter = EventExporter(directory=tmp_path, buffer_size=2)
+        for event in _trades(5):
+            exporter.on_market_event(event)
+        assert exporter.get_stats()['buffered'] == 2
+        assert exporter.events_dropped == 3
+
+    def test_zstd(self, tmp_path):
+        pytest.importorskip('zstandard')
+        exporter = EventExporter(directory=tmp_path, compression='zstd')
+        for event in _trades(3):
+            exporter.on_market_event(event)
+        exporter.stop()
+        path = next(tmp_path.iterdir())
+        assert path.suffix == '.zst'
+        assert len(list(iter_recorded_events(path))) == 3
+
+    def test_unknown_compression_rejected(self, tmp_path):
+        with pytest.raises(ValueError):
+            EventExporter(directory=tmp_path, compression='lz4')
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,135 @@
+"""
+Unit tests for bounded feed channels (core.websocket.channels)
+"""
+
+import pytest
+import asyncio
+import json
+import os
+import sys
+from datetime import datetime, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.market_events import Quote, Trade
+from core.websocket import BoundedChannel, MarketHubClient
+from core.websocket.protocol import RECORD_SEPARATOR
+
+TS = datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc)
+
+
+class FakeConnection:
+    """In-memory WebSocket fed by the test."""
+
+    def __init__(self):
+        self.incoming: asyncio.Queue = asyncio.Queue()
+        self.incoming.put_nowait('{}' + RECORD_SEPARATOR)
+        self.reads = 0
+
+    async def send(self, data):
+        pass
+
+    async def recv(self):
+        frame = await self.incoming.get()
+        self.reads += 1
+        return frame
+
+    async def close(self):
+        pass
+
+    def push_trade(self, price):
+        self.incoming.put_nowait(json.dumps({
+            'type': 1, 'target': 'GatewayTrade',
+            'arguments': ['CON.F.US.MNQ.Z25', [{'price': price, 'volume': 1, 'type': 0}]]}) + RECORD_SEPARATOR)
+
+
+class TestFeedChannels:
+    """Test overflow policies, drop counters and hub backpressure"""
+
+    @pytest.mark.asyncio
+    async def test_drop_oldest_and_conflate(self):
+        """Test drop-oldest eviction and per-symbol quote conflation"""
+        channel = BoundedChannel(capacity=2, policy='drop_oldest')
+        for price in (1.0, 2.0, 3.0):
+            assert channel.offer(Trade('MNQ', TS, price))
+        assert [(await channel.get()).price for _ in range(2)] == [2.0, 3.0]
+        assert channel.get_stats()['dropped'] == 1 and channel.get_stats()['max_depth'] == 2
+
+        channel = BoundedChannel(capacity=10, policy='conflate')
+        channel.offer(Quote('MNQ', TS, bid=21000.0, ask=21000.5))
+        channel.offer(Trade('MNQ', TS, 21000.25))
+        channel.offer(Quote('MNQ', TS, bid=21000.25))
+        channel.offer(Quote('MES', TS, bid=6000.0))
+        assert len(channel) == 3 and channel.get_stats()['conflated'] == 1
+        merged = await channel.get()
+        assert (merged.bid, merged.ask) == (21000.25, 21000.5)
+        assert isinstance(await channel.get(), Trade)
+        channel.close()
+        assert [q.symbol async for q in channel] == ['MES']
+
+    @pytest.mark.asyncio
+    async def test_block_policy_waits_for_consumer(self):
+        """Test put() waits on a full block channel and offer() on the loop drops"""
+        channel = BoundedChannel(capacity=1, policy='block')
+        await channel.put(1)
+        waiter = asyncio.ensure_future(channel.put(2))
+        await asyncio.sleep(0.01)
+        assert not waiter.done() and channel.get_stats()['blocked'] == 1
+        assert await channel.get() == 1
+        await asyncio.wait_for(waiter, 1)
+        assert not channel.offer(3)  # Full, and waiting here would deadlock the consumer
+        assert channel.get_stats()['dropped'] == 1 and await channel.get() == 2
+
+    @pytest.mark.asyncio
+    async def test_hub_consumers(self):
+        """Test a slow consumer backs up only its channel, and a block consumer pauses the reader"""
+        conn = FakeConnection()
+
+        async def connect(url, headers):
+            return conn
+
+        hub = MarketHubClient('https://rtc.example.com/hubs/market', keep_alive_interval=0, connector=connect)
+        fast, release = [], asyncio.Event()
+
+        async def slow(event):
+            await release.wait()
+
+        hub.add_consumer('fast', fast.append, capacity=10, policy='drop_oldest')
+        hub.add_consumer('slow', slow, capacity=2, policy='drop_oldest')
+        await hub.start()
+        for price in range(5):
+            conn.push_trade(21000.0 + price)
+        await asyncio.sleep(0.02)
+        assert len(fast) == 5
+        stats = hub.get_stats()['channels']
+        assert stats['slow']['dropped'] == 3 and stats['fast']['dropped'] == 0
+
+        hub.add_consumer('recorder', slow, capacity=1, policy='block')
+        reads = conn.reads
+        for price in range(4):
+            conn.push_trade(21010.0 + price)
+        await asyncio.sleep(0.02)
+        assert conn.reads == reads + 3  # Third trade waits for room in the recorder channel
+        release.set()
+        await asyncio.sleep(0.02)
+        assert conn.reads == reads + 4 and len(fast) == 9
+        await hub.stop()
+
+    @pytest.mark.asyncio
+    async def test_market_event_stream(self):
+        """Test async iteration over typed hub events with a type filter"""
+        hub = MarketHubClient('offline')
+        trades = hub.events(types=Trade)
+        async with hub.events() as everything:
+            hub._handler('GatewayQuote')('CON.F.US.MNQ.Z25', {'bestBid': 21000.0, 'bestAsk': 21000.25})
+            hub._handler('GatewayTrade')('CON.F.US.MNQ.Z25', [{'price': 21000.25, 'volume': 2, 'type': 0}])
+            assert isinstance(await everything.__anext__(), Quote)
+            assert isinstance(await everything.__anext__(), Trade)
+        assert len(hub.channels) == 1  # Closed by the context manager
+        trades.close()
+        assert [t.size async for t in trades] == [2]
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,84 @@
+"""
+Unit tests for feed latency and clock-skew measurement (core.websocket.latency)
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone
+from unittest.mock import AsyncMock, MagicMock, patch
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.websocket import FeedLatencyMonitor, MarketHubClient
+from core.websocket.latency import parse_exchange_time, payload_exchange_time
+from trading_bot import TopStepXTradingBot
+
+T0 = datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc).timestamp()
+
+
+class TestFeedLatency:
+    """Test timestamp extraction, percentiles, skew estimate and lag detection"""
+
+    def test_exchange_timestamps(self):
+        """Test ISO/epoch parsing and the latest timestamp of a payload"""
+        assert parse_exchange_time('2025-11-19T14:30:00Z') == T0
+        assert parse_exchange_time('2025-11-19T14:30:00') == T0  # Naive = UTC
+        assert parse_exchange_time(T0 * 1000) == T0 and parse_exchange_time(T0) == T0
+        assert parse_exchange_time(None) is None and parse_exchange_time('soon') is None
+        dom = [{'price': 1, 'timestamp': '2025-11-19T14:30:00Z'},
+               {'price': 2, 'timestamp': '2025-11-19T14:30:01.5Z'}, 'junk']
+        assert payload_exchange_time(dom) == T0 + 1.5
+        assert payload_exchange_time({'lastUpdated': '2025-11-19T14:30:00Z'}) == T0
+        assert payload_exchange_time({'bestBid': 1.0}) is None
+
+    def test_percentiles_skew_and_lag(self):
+        """Test clock skew comes from the fastest messages and lag is skew-adjusted"""
+        monitor = FeedLatencyMonitor(window=100, max_lag_ms=500, min_samples=10)
+        # Local clock 2s ahead of the exchange, 20-40ms of transit
+        for i in range(50):
+            monitor.record('quotes', T0 + i, received_at=T0 + i + 2.0 + (0.02 if i % 2 else 0.04), symbol='MNQ')
+        latency = monitor.get_feed_latency()
+        assert latency['samples'] == 50 and latency['clock_skew_ms'] == pytest.approx(2020, abs=0.5)
+        assert latency['p50_ms'] == pytest.approx(2020, abs=0.5) and latency['p99_ms'] == pytest.approx(2040, abs=0.5)
+        assert latency['adjusted_p95_ms'] == pytest.approx(20, abs=0.5)
+        assert not latency['lagging']  # 2s raw latency is clock offset, not lag
+
+        # Reader falls 1.5s behind
+        for i in range(50, 70):
+            monitor.record('trades', T0 + i, received_at=T0 + i + 3.5, symbol='MNQ')
+        assert monitor.is_lagging() and not monitor.is_lagging('quotes')
+        trades = monitor.get_feed_latency()['streams']['trades']
+        assert trades['last_symbol'] == 'MNQ' and trades['adjusted_p50_ms'] == pytest.approx(1480, abs=0.5)
+        assert monitor.record('depth', 'not a time') is None and monitor.get_stats()['samples_skipped'] == 1
+
+    def test_market_hub_records_latency(self):
+        """Test the market hub handler feeds the monitor from payload timestamps"""
+        hub = MarketHubClient('offline')
+        handler = hub._handler('GatewayTrade')
+        handler('CON.F.US.MNQ.Z25', [{'price': 21000.0, 'volume': 1, 'type': 0,
+                                      'timestamp': datetime.now(timezone.utc).isoformat()}])
+        handler('CON.F.US.MNQ.Z25', [{'price': 21000.0, 'volume': 1, 'type': 0}])  # No timestamp
+        latency = hub.get_feed_latency('trades')
+        assert latency['samples'] == 1 and latency['streams']['trades']['last_symbol'] == 'MNQ'
+        assert 0 <= latency['p50_ms'] < 1000
+
+
+    @pytest.mark.asyncio
+    async def test_lagging_feed_blocks_entries_not_exits(self):
+        """Test FEED_LAG_BLOCKS_ENTRIES refuses new exposure but lets the open position be reduced"""
+        with patch.dict(os.environ, {'PROJECT_X_API_KEY': 'test_key', 'PROJECT_X_USERNAME': 'test_user',
+                                     'FEED_LAG_BLOCKS_ENTRIES': 'true'}):
+            bot = TopStepXTradingBot(api_key='test_key', username='test_user')
+        bot.feed_latency = MagicMock()
+        bot.feed_latency.is_lagging.return_value = True
+        bot.feed_latency.get_feed_latency.return_value = {'adjusted_p95_ms': 1480, 'max_lag_ms': 500}
+        bot.get_open_positions = AsyncMock(return_value=[
+            {'id': 1, 'contractId': 'CON.F.US.MNQ.Z25', 'type': 2, 'size': 3}])
+        assert (await bot._order_gate_error('MNQ', 'SELL', 1, '12345'))['feed_lagging']
+        assert await bot._order_gate_error('MNQ', 'BUY', 2, '12345') is None  # Covers part of the short
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,107 @@
+"""
+Unit tests for multi-process artifact locking
+"""
+
+import pytest
+import json
+import os
+import subprocess
+import sys
+from unittest.mock import patch
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from infrastructure.file_lock import (
+    ArtifactLockError, acquire_artifact_lock, release_artifact_locks, atomic_write,
+    read_lock_holder, get_instance_identity,
+)
+
+REPO_ROOT = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+
+_CLAIM_SCRIPT = """
+import sys
+sys.path.insert(0, {root!r})
+from infrastructure.file_lock import acquire_artifact_lock, ArtifactLockError
+try:
+    acquire_artifact_lock({path!r})
+except ArtifactLockError as e:
+    print(e)
+    sys.exit(3)
+"""
+
+
+def _claim_in_subprocess(path):
+    return subprocess.run(
+        [sys.executable, '-c', _CLAIM_SCRIPT.format(root=REPO_ROOT, path=str(path))],
+        capture_output=True, text=True, timeout=30,
+    )
+
+
+class TestArtifactLocking:
+    """Test directory claims and atomic writes"""
+
+    def test_lock_writes_identity_marker(self, tmp_path):
+        """Test the lock file records this instance"""
+        try:
+            acquire_artifact_lock(tmp_path)
+            holder = read_lock_holder(tmp_path)
+            assert holder['pid'] == os.getpid()
+            assert holder['instance_id'] == get_instance_identity().instance_id
+        finally:
+            release_artifact_locks()
+
+    def test_reentrant_within_process(self, tmp_path):
+        """Test several components may claim the same directory"""
+        try:
+            assert acquire_artifact_lock(tmp_path) is acquire_artifact_lock(tmp_path)
+        finally:
+            release_artifact_locks()
+
+    def test_second_process_fails_fast(self, tmp_path):
+        """Test another process gets a clear error naming the holder"""
+        try:
+            acquire_artifact_lock(tmp_path)
+            result = _claim_in_subprocess(tmp_path)
+            assert result.returncode == 3
+            assert f"pid {os.getpid()}" in result.stdout
+        finally:
+            release_artifact_locks()
+
+        assert _claim_in_subprocess(tmp_path).returncode == 0
+
+    def test_error_message(self, tmp_path):
+        """Test ArtifactLockError describes the conflicting instance"""
+        err = ArtifactLockError(tmp_path, {'instance_id': 'abc', 'pid': 42, 'hostname': 'box'})
+        assert 'instance abc' in str(err) and 'pid 42' in str(err)
+
+    def test_bot_claims_its_cache_dir_on_start(self, tmp_path):
+        """Test constructing a bot claims nothing; starting it claims the configured cache directory"""
+        from trading_bot import TopStepXTradingBot
+        with patch.dict(os.environ, {'PROJECT_X_API_KEY': 'test_key', 'PROJECT_X_USERNAME': 'test_user',
+                                     'HISTORY_CACHE_DIR': str(tmp_path)}):
+            bot = TopStepXTradingBot(api_key='test_key', username='test_user')
+        try:
+            assert bot.history_cache_dir == tmp_path and read_lock_holder(tmp_path) is None
+            bot.claim_artifact_dirs()
+            assert read_lock_holder(tmp_path)['pid'] == os.getpid()
+        finally:
+            release_artifact_locks()
+
+    def test_locking_can_be_disabled(self, tmp_path, monkeypatch):
+        """Test ARTIFACT_LOCKING_ENABLED=false skips claims"""
+        monkeypatch.setenv('ARTIFACT_LOCKING_ENABLED', 'false')
+        assert acquire_artifact_lock(tmp_path) is None
+
+    def test_atomic_write(self, tmp_path):
+        """Test atomic writes replace content and leave no temp files"""
+        target = tmp_path / 'state.json'
+        atomic_write(target, json.dumps({'a': 1}))
+        atomic_write(target, json.dumps({'a': 2}))
+
+        assert json.loads(target.read_text()) == {'a': 2}
+        assert not [p for p in tmp_path.iterdir() if p.suffix == '.tmp']
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,60 @@
+"""
+Unit tests for enriched fill context
+"""
+
+import pytest
+import os
+import sys
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.fill_context import BookState, FillContextRecorder
+
+
+class TestFillContextRecorder:
+    """Test submission snapshots and fill context assembly."""
+
+    def test_market_order_slippage_against_touch(self):
+        books = {'MNQ': BookState(bid=15000.0, ask=15000.25, bid_size=12, ask_size=4, source='depth_book')}
+        recorder = FillContextRecorder(book_provider=books.get)
+        recorder.register_indicator('cumulative_delta', lambda symbol: 42)
+        recorder.capture_submission('1001', 'mnq', 'buy', 2, 'Market')
+
+        books['MNQ'] = BookState(bid=15001.0, ask=15001.25)  # Book moved before the fill was polled
+        context = recorder.build_fill_context('1001', 'MNQ', 'BUY', 15000.75)
+
+        submission = context['submission']
+        assert submission['reference_price'] == 15000.25
+        assert submission['market']['book']['spread'] == 0.25
+        assert submission['market']['indicators'] == {'cumulative_delta': 42}
+        assert context['at_fill']['book']['bid'] == 15001.0
+        assert context['slippage'] == 0.5
+        assert context['latency_seconds'] >= 0
+        # Snapshot is released once the fill is reported
+        assert recorder.get_submission('1001') is None
+
+    def test_limit_sell_uses_limit_price(self):
+        recorder = FillContextRecorder(book_provider=lambda symbol: None)
+        recorder.capture_submission('7', 'MES', 'SELL', 1, 'Limit', limit_price=5000.0)
+        context = recorder.build_fill_context('7', 'MES', 'SELL', 5000.5)
+        assert context['slippage'] == -0.5  # Price improvement
+
+    def test_unknown_order_and_failing_providers(self):
+        def broken(symbol):
+            raise RuntimeError("no data")
+
+        recorder = FillContextRecorder(book_provider=broken, max_pending=1)
+        recorder.register_indicator('broken', broken)
+        recorder.capture_submission('1', 'MNQ', 'BUY', 1, 'Market')
+        recorder.capture_submission('2', 'MNQ', 'BUY', 1, 'Market')
+        assert recorder.get_submission('1') is None  # Evicted beyond max_pending
+        context = recorder.build_fill_context('99', 'MNQ', 'BUY', 1.0)
+        assert context['submission'] is None
+        assert context['slippage'] is None
+        assert context['at_fill']['book'] is None
+        assert context['at_fill']['indicators'] == {}
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,113 @@
+"""
+Unit tests for order-flow footprint bars
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone, timedelta
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar
+from core.footprint import FootprintAggregator, FootprintBar, TickRuleClassifier, find_stacked_imbalances
+from core.market_events import Trade, Quote
+
+
+T0 = datetime(2025, 11, 19, 14, 30, 0, tzinfo=timezone.utc)
+
+
+class TestTickRuleClassifier:
+    """Test trade classification"""
+
+    def test_aggressor_side_wins_then_tick_rule(self):
+        """Test feed side is used when present, otherwise upticks/downticks/zero ticks"""
+        classifier = TickRuleClassifier()
+        assert classifier.classify('MNQ', 100.0) is None
+        assert classifier.classify('MNQ', 100.25) == 'buy'
+        assert classifier.classify('MNQ', 100.25) == 'buy'
+        assert classifier.classify('MNQ', 100.0) == 'sell'
+        assert classifier.classify('MNQ', 100.0, side='buy') == 'buy'
+        assert classifier.classify('MNQ', 100.0) == 'buy'
+
+
+class TestFootprintAggregator:
+    """Test FootprintAggregator"""
+
+    def test_footprint_delta_and_ladder(self):
+        """Test delta statistics and volume-at-price ladder for a bar"""
+        completed = []
+        footprints = FootprintAggregator(timeframes=['1m'], footprint_callback=completed.append)
+        footprints.add_trade('MNQ', 100.0, 5, T0, side='buy')
+        footprints.add_trade('MNQ', 100.25, 3, T0 + timedelta(seconds=1), side='buy')
+        footprints.add_trade('MNQ', 100.0, 10, T0 + timedelta(seconds=2), side='sell')
+        footprints.add_trade('MNQ', 99.75, 1, T0 + timedelta(seconds=3))  # Downtick -> sell
+        footprints.add_trade('MNQ', 100.0, 2, T0 + timedelta(minutes=1))
+
+        assert len(completed) == 1
+        bar = completed[0]
+        assert isinstance(bar, FootprintBar) and isinstance(bar, Bar)
+        assert (bar.open, bar.high, bar.low, bar.close, bar.volume) == (100.0, 100.25, 99.75, 99.75, 19)
+        assert (bar.buy_volume, bar.sell_volume, bar.delta) == (8, 11, -3)
+        assert (bar.max_delta, bar.min_delta) == (8, -3)
+        assert bar.ladder == {100.0: (10, 5), 100.25: (0, 3), 99.75: (1, 0)}
+        assert bar.poc == 100.0
+        assert bar.to_dict()["ladder"][0]["price"] == 100.25
+
+    def test_cumulative_delta_across_bars(self):
+        """Test cumulative delta carries across bars and includes the forming bar"""
+        footprints = FootprintAggregator(timeframes=['1m'])
+        footprints.add_trade('MNQ', 100.0, 4, T0, side='buy')
+        footprints.add_trade('MNQ', 100.0, 1, T0 + timedelta(minutes=1), side='sell')
+        assert footprints.get_history('MNQ', '1m')[0].cumulative_delta == 4
+        assert footprints.get_cumulative_delta('MNQ') == 3
+
+        footprints.close_elapsed_bars(T0 + timedelta(minutes=2))
+        assert footprints.get_history('MNQ', '1m')[-1].cumulative_delta == 3
+        footprints.reset_cumulative_delta('MNQ')
+        assert footprints.get_cumulative_delta('MNQ') == 0
+
+    def test_market_event_listener_and_tick_snapping(self):
+        """Test Trade events are consumed, other events ignored, prices snapped to ticks"""
+        footprints = FootprintAggregator(timeframes=['5m'], tick_sizes={'MES': 0.25})
+        footprints.on_market_event(Trade('MES', T0, 5000.1, 2, 'sell'))
+        footprints.on_market_event(Quote('MES', T0, bid=5000.0, ask=5000.25))
+        current = footprints.get_current('MES', '5m')
+        assert current.ladder == {5000.0: (2, 0)}
+        assert current.tick_count == 1
+
+
+    def test_stacked_imbalance_events_and_delta_stream(self):
+        """Test stacked ask imbalances are published and cumulative delta streams per trade"""
+        imbalances, deltas = [], []
+        footprints = FootprintAggregator(timeframes=['1m'], tick_sizes={'MNQ': 0.25}, imbalance_ratio=3.0,
+                                         imbalance_min_volume=5, stacked_levels=3)
+        footprints.on_imbalance(imbalances.append)
+        unsubscribe = footprints.on_delta(deltas.append)
+        for price, bid, ask in [(100.0, 2, 1), (100.25, 1, 9), (100.5, 2, 6), (100.75, 1, 8), (101.0, 1, 2)]:
+            footprints.add_trade('MNQ', price, bid, T0, side='sell')
+            footprints.add_trade('MNQ', price, ask, T0, side='buy')
+        footprints.close_elapsed_bars(T0 + timedelta(minutes=1))
+
+        assert len(imbalances) == 1
+        event = imbalances[0]
+        assert (event.side, event.price_low, event.price_high, event.levels) == ('buy', 100.25, 100.75, 3)
+        assert event.min_ratio == 4.0 and event.type == 'imbalance' and event.timestamp == T0
+        assert [d.cumulative_delta for d in deltas][-2:] == [17, 19]
+        assert footprints.get_delta_series('MNQ', 1)[0].cumulative_delta == 19
+
+        unsubscribe()
+        footprints.add_trade('MNQ', 101.0, 5, T0 + timedelta(minutes=1), side='sell')
+        assert len(deltas) == 10
+        assert footprints.get_delta_series('MNQ')[-1].cumulative_delta == 14
+
+        # Gaps in the ladder break a stack; the tick size is inferred when unknown
+        gapped = FootprintBar('MNQ', '1m', T0, 1.0, 2.0, 1.0, 2.0,
+                              ladder={1.0: (0, 9), 1.25: (0, 9), 1.75: (0, 9), 2.0: (0, 9)})
+        assert [(e.price_low, e.price_high) for e in find_stacked_imbalances(gapped, stacked_levels=2, min_volume=1)
+                ] == [(1.0, 1.25), (1.75, 2.0)]
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
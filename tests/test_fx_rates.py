@@ -0,0 +1,105 @@
+"""
+Unit tests for FX conversion and multi-currency account reporting
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.fx_rates import FXConverter, FXRateError, StaticFXRateSource, CallableFXRateSource
+from core.account_tracker import AccountTracker
+
+
+class TestFXConverter:
+    """Test FXConverter"""
+
+    def test_direct_inverse_and_cross(self):
+        """Test direct, inverse and USD-cross rates"""
+        fx = FXConverter(StaticFXRateSource({'CAD/USD': 0.75, 'EUR/USD': 1.5}))
+        assert fx.convert(100.0, 'cad', 'USD') == pytest.approx(75.0)
+        assert fx.convert(75.0, 'USD', 'CAD') == pytest.approx(100.0)
+        assert fx.convert(100.0, 'CAD', 'EUR') == pytest.approx(50.0)
+        assert fx.convert(10.0, 'JPY', 'JPY') == 10.0
+        with pytest.raises(FXRateError):
+            fx.convert(1.0, 'JPY', 'USD')
+
+    def test_from_env(self, monkeypatch):
+        """Test FX_RATES parsing skips malformed entries"""
+        monkeypatch.setenv('FX_RATES', 'CAD/USD=0.7, bogus ,EUR/USD=1.1')
+        source = StaticFXRateSource.from_env()
+        assert source.get_rate('CAD', 'USD') == 0.7
+        assert source.get_rate('EUR', 'USD') == 1.1
+
+    def test_callable_source_uses_time(self):
+        """Test a callable source receives the requested time and failures become FXRateError"""
+        calls = []
+
+        def fetch(base, quote, at):
+            calls.append(at)
+            if at is None:
+                raise RuntimeError("provider down")
+            return 0.8
+
+        fx = FXConverter(CallableFXRateSource(fetch))
+        at = datetime(2025, 11, 19, tzinfo=timezone.utc)
+        assert fx.rate('CAD', 'USD', at) == 0.8
+        assert calls[0] == at
+        with pytest.raises(FXRateError):
+            fx.rate('CAD', 'USD')
+
+
+class TestMultiCurrencyAccounts:
+    """Test AccountTracker currency handling"""
+
+    @pytest.fixture
+    def setup(self, tmp_path):
+        source = StaticFXRateSource({'CAD/USD': 0.75})
+        fx = FXConverter(source, reporting_currency='USD')
+        tracker = AccountTracker(state_file=str(tmp_path / "state.json"), fx_converter=fx)
+        tracker.initialize_account('1', 'CAD-1', 'practice', 50000.0, 1000.0, 2000.0, currency='cad')
+        tracker.initialize_account('2', 'USD-1', 'practice', 50000.0, 1000.0, 2000.0)
+        return tracker, source
+
+    def test_realised_at_trade_time_unrealised_at_report_time(self, setup):
+        """Test realised PnL keeps the fill-time rate while unrealised uses the current rate"""
+        tracker, source = setup
+        tracker.update_from_fill('1', {'pnl': 100.0, 'commission': 4.0})
+        tracker.update_from_fill('2', {'pnl': 50.0})
+        tracker.update_unrealised_pnl('1', [{'symbol': 'MNQ', 'qty': 1, 'entry_price': 100.0, 'side': 'LONG'}],
+                                      {'MNQ': 110.0})
+
+        source.set_rate('CAD', 'USD', 0.5)
+        report = tracker.get_consolidated_report()
+        cad = next(r for r in report['accounts'] if r['account_id'] == '1')
+        assert cad['realised_pnl'] == pytest.approx(96.0 * 0.75)
+        # $20 USD unrealised = 40 CAD at 0.5, reported back at 0.5
+        assert cad['native']['unrealised_pnl'] == pytest.approx(20.0 / 0.75)
+        assert cad['unrealised_pnl'] == pytest.approx(20.0 / 0.75 * 0.5)
+        assert report['totals']['realised_pnl'] == pytest.approx(72.0 + 50.0)
+
+    def test_fill_in_other_currency_converted_to_account(self, setup):
+        """Test fill amounts in a foreign currency are booked in the account currency"""
+        tracker, _ = setup
+        state = tracker.update_from_fill('1', {'pnl': 75.0, 'currency': 'USD'})
+        assert state.realised_PnL == pytest.approx(100.0)
+        assert state.realised_PnL_reporting == pytest.approx(75.0)
+
+    def test_missing_rate_leaves_state_unchanged(self, setup):
+        """Test a fill needing an unknown rate raises before mutating state"""
+        tracker, _ = setup
+        with pytest.raises(FXRateError):
+            tracker.update_from_fill('1', {'pnl': 10.0, 'currency': 'JPY'})
+        assert tracker.accounts['1'].realised_PnL == 0.0
+
+        tracker.initialize_account('3', 'GBP-1', 'practice', 1000.0, 100.0, 200.0, currency='GBP')
+        report = tracker.get_consolidated_report()
+        assert [u['account_id'] for u in report['unconverted']] == ['3']
+        assert tracker.get_state('1')['currency'] == 'CAD'
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,118 @@
+"""
+Unit tests for the gRPC signal service
+"""
+
+import pytest
+import asyncio
+import os
+import sys
+from types import SimpleNamespace
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.strategy_engine import Direction, OrderExecutor, RiskManager
+from core.websocket import PositionUpdate, UserHubClient, UserTrade
+from servers.grpc_signals import (
+    GrpcSignalServer, SignalRejected, fill_fields, position_fields, signal_from_message,
+)
+
+TOKEN = 'grpc-token'
+
+
+class Message(SimpleNamespace):
+    """tradebot.v1 message stand-in: proto3 defaults plus HasField for optional fields"""
+
+    def __init__(self, **fields):
+        defaults = {'strategy_id': 'remote', 'symbol': 'MNQ', 'direction': 1, 'price': 21000.0, 'timestamp': '',
+                    'timeframe': '', 'reason': '', 'account_id': '', 'confidence': None, 'stop': None,
+                    'target': None}
+        super().__init__(**{**defaults, **fields})
+
+    def HasField(self, name):
+        return getattr(self, name) is not None
+
+
+class FakeHubClient:
+    """SignalRClient stand-in that only records handlers"""
+
+    def __init__(self):
+        self.handlers = {}
+
+    def on(self, target, handler):
+        self.handlers[target] = handler
+
+
+def make_server(**kwargs):
+    orders = []
+    executor = OrderExecutor(lambda **order: orders.append(order) or {"success": True, "orderId": len(orders)},
+                             account_id='ACC1')
+    risk = RiskManager(account_resolver=lambda signal: 'ACC1')
+    return GrpcSignalServer(executor, risk=risk, token=TOKEN, **kwargs), orders, risk
+
+
+class TestGrpcSignals:
+    """Test signal conversion, authentication, routing and fill/position streams"""
+
+    def test_signal_from_message(self):
+        signal = signal_from_message(Message(symbol='CON.F.US.MNQ.Z25', direction=2, stop=21010.0, confidence=0.7,
+                                             timestamp='2025-11-04T16:00:00Z', timeframe='5m'))
+        assert (signal.symbol, signal.direction, signal.stop, signal.target) == ('MNQ', Direction.SHORT, 21010.0, None)
+        assert signal.confidence == 0.7 and signal.timeframe == '5m' and signal.timestamp.tzinfo is not None
+        assert signal_from_message(Message(direction=3, price=0.0)).direction is Direction.FLAT
+        for fields, error in (({'direction': 0}, 'LONG, SHORT or FLAT'), ({'strategy_id': ''}, 'strategy_id'),
+                              ({'price': 0.0, 'target': 21050.0}, 'need a price'), ({'confidence': 1.5}, 'between'),
+                              ({'timestamp': 'yesterday'}, 'ISO 8601')):
+            with pytest.raises(SignalRejected, match=error):
+                signal_from_message(Message(**fields))
+
+    def test_submit_routes_through_risk_and_executor(self):
+        server, orders, risk = make_server()
+        assert server.authorized([('authorization', f'Bearer {TOKEN}')])
+        assert not server.authorized([('authorization', 'Bearer wrong')]) and not server.authorized(None)
+        assert server.submit(Message(stop=20990.0)) == (True, '')
+        assert orders[0]['side'] == 'BUY' and orders[0]['stop_loss_ticks'] == 40
+        risk.halt('ACC1', reason='test')
+        assert server.submit(Message(direction=2)) == (False, "Signal blocked (risk halt or gate)")
+        assert server.submit(Message(direction=3))[0]  # Exits always pass
+        with pytest.raises(SignalRejected):
+            server.submit(Message(symbol=''))
+        stats = server.get_stats()
+        assert (stats['signals_accepted'], stats['signals_blocked'], stats['signals_rejected']) == (2, 1, 1)
+        with pytest.raises(ValueError, match='GRPC_API_TOKEN'):
+            asyncio.run(GrpcSignalServer(server.executor, token='').start())
+
+    @pytest.mark.asyncio
+    async def test_fill_and_position_streams(self):
+        client = FakeHubClient()
+        hub = UserHubClient(client=client)
+        server, _, _ = make_server(user_hub=lambda: hub)
+        position = {'id': 7, 'accountId': 123, 'contractId': 'CON.F.US.MES.Z25', 'type': 2, 'size': 3,
+                    'averagePrice': 6000.5, 'creationTimestamp': '2025-11-04T16:00:00Z'}
+        client.handlers['GatewayUserPosition'](position)
+
+        positions = server.updates(SimpleNamespace(account_id='123', symbol=''), PositionUpdate, hub.positions('123'))
+        fills = server.updates(SimpleNamespace(account_id='', symbol='MNQ'), UserTrade)
+        first = await positions.__anext__()
+        assert position_fields(first)['net_quantity'] == -3 and position_fields(first)['average_price'] == 6000.5
+        pending_fill = asyncio.ensure_future(fills.__anext__())
+        await asyncio.sleep(0)
+        assert server.get_stats()['active_streams'] == 2
+        trade = {'id': 1, 'accountId': 456, 'orderId': 9001, 'contractId': 'CON.F.US.MNQ.Z25', 'price': 21000.25,
+                 'size': 2, 'side': 1, 'fees': 1.48, 'profitAndLoss': None, 'creationTimestamp': '2025-11-04T16:01:00Z'}
+        client.handlers['GatewayUserTrade']({**trade, 'contractId': 'CON.F.US.MES.Z25'})  # Filtered out
+        client.handlers['GatewayUserTrade'](trade)
+        fill = fill_fields(await asyncio.wait_for(pending_fill, 1.0))
+        assert (fill['account_id'], fill['side'], fill['size']) == ('456', 'sell', 2) and 'profit_and_loss' not in fill
+        client.handlers['GatewayUserPosition']({**position, 'size': 0})
+        closed = position_fields(await asyncio.wait_for(positions.__anext__(), 1.0))
+        assert closed['net_quantity'] == 0 and 'average_price' not in closed
+        await positions.aclose()
+        await fills.aclose()
+        assert server.get_stats()['active_streams'] == 0
+        with pytest.raises(LookupError, match='USER_HUB_ENABLED'):
+            await GrpcSignalServer(server.executor, token=TOKEN).updates(SimpleNamespace(), object).__anext__()
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
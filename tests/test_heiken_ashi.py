@@ -0,0 +1,85 @@
+"""
+Unit tests for Heiken Ashi transforms
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone, timedelta
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar
+from core.heiken_ashi import to_heiken_ashi, HeikenAshiAggregator
+
+
+START = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+
+
+@pytest.fixture
+def bars():
+    """Three 5m bars"""
+    return [
+        Bar('MNQ', '5m', START, 100.0, 104.0, 98.0, 102.0, volume=10),
+        Bar('MNQ', '5m', START + timedelta(minutes=5), 102.0, 106.0, 101.0, 105.0, volume=20),
+        Bar('MNQ', '5m', START + timedelta(minutes=10), 105.0, 105.5, 99.0, 100.0, volume=30),
+    ]
+
+
+class TestHeikenAshi:
+    """Test Heiken Ashi transform"""
+
+    def test_batch_transform(self, bars):
+        """Test HA formulas across a short series"""
+        ha = to_heiken_ashi(bars)
+
+        assert ha[0].open == pytest.approx(101.0)
+        assert ha[0].close == pytest.approx(101.0)
+        assert ha[1].open == pytest.approx(101.0)
+        assert ha[1].close == pytest.approx(103.5)
+        assert ha[1].high == 106.0 and ha[1].low == 101.0
+        assert ha[2].open == pytest.approx(102.25)
+        assert ha[2].volume == 30
+        assert bars[0].open == 100.0  # Inputs untouched
+
+    def test_batch_transform_dicts(self):
+        """Test dict bars (database rows) keep their extra fields"""
+        rows = [{'timestamp': 't0', 'open': 1.0, 'high': 3.0, 'low': 0.0, 'close': 2.0, 'volume': 5}]
+        ha = to_heiken_ashi(rows)
+        assert ha[0] == {'timestamp': 't0', 'open': 1.5, 'high': 3.0, 'low': 0.0, 'close': 1.5, 'volume': 5}
+
+    def test_streaming_matches_batch(self, bars):
+        """Test streaming output equals the batch transform"""
+        seen = []
+        aggregator = HeikenAshiAggregator(ha_bar_callback=seen.append)
+        streamed = [aggregator.add_bar(bar) for bar in bars]
+
+        assert streamed == to_heiken_ashi(bars)
+        assert seen == streamed
+        assert aggregator.get_last('mnq', '5m') == streamed[-1]
+
+    def test_seed_then_stream(self, bars):
+        """Test seeding from history continues the series without callbacks"""
+        seen = []
+        aggregator = HeikenAshiAggregator(ha_bar_callback=seen.append)
+        aggregator.seed(bars[:2])
+        last = aggregator.add_bar(bars[2])
+
+        assert seen == [last]
+        assert last == to_heiken_ashi(bars)[2]
+
+    def test_state_is_per_timeframe(self, bars):
+        """Test symbols/timeframes keep independent state"""
+        aggregator = HeikenAshiAggregator()
+        aggregator.add_bar(bars[0])
+        other = Bar('MNQ', '1m', START, 100.0, 104.0, 98.0, 102.0)
+        assert aggregator.add_bar(other).open == pytest.approx(101.0)
+
+        aggregator.reset('MNQ', '5m')
+        assert aggregator.get_last('MNQ', '5m') is None
+        assert aggregator.get_last('MNQ', '1m') is not None
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
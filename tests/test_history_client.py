@@ -0,0 +1,122 @@
+"""
+Unit tests for the paginated historical bar fetcher
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timedelta, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.history_client import HistoryClient, HistoryFetchError, bars_to_arrays, parse_bar, parse_timeframe
+from infrastructure.retry_budget import RetryBudget
+
+START = datetime(2025, 1, 6, 14, 30, tzinfo=timezone.utc)
+
+
+class FakeHistoryAPI:
+    """Serves 1m bars, newest first, truncated to the request limit like the broker API."""
+
+    def __init__(self, count, failures=0, error="HTTP 503: Service Unavailable"):
+        self.bars = [START + timedelta(minutes=i) for i in range(count)]
+        self.failures = failures
+        self.error = error
+        self.requests = []
+
+    def __call__(self, payload):
+        self.requests.append(payload)
+        if self.failures:
+            self.failures -= 1
+            return {"error": self.error}
+        start = datetime.fromisoformat(payload["startTime"].replace('Z', '+00:00'))
+        end = datetime.fromisoformat(payload["endTime"].replace('Z', '+00:00'))
+        in_range = [ts for ts in self.bars if start <= ts <= end]
+        page = sorted(in_range, reverse=True)[:payload["limit"]]
+        return {"success": True, "errorCode": 0, "bars": [
+            {"t": ts.isoformat(), "o": 100.0 + i, "h": 101.0 + i, "l": 99.0 + i, "c": 100.5 + i, "v": 10}
+            for i, ts in enumerate(page)
+        ]}
+
+
+def _client(api, **kwargs):
+    kwargs.setdefault('max_bars_per_request', 100)
+    return HistoryClient(fetch=api, contract_resolver=lambda s: f"CON.F.US.{s}.H25", min_request_interval=0,
+                         retry_budget=RetryBudget(capacity=10, refill_per_sec=0), backoff_base=0, **kwargs)
+
+
+class TestParsing:
+    """Test timeframe and bar parsing."""
+
+    def test_parse_timeframe(self):
+        assert parse_timeframe('5m') == (2, 5, 300)
+        assert parse_timeframe('1h') == (3, 1, 3600)
+        with pytest.raises(ValueError):
+            parse_timeframe('5x')
+
+    def test_parse_bar_epoch_millis_and_full_names(self):
+        bar = parse_bar({"timestamp": 1736173800000, "open": 1, "high": 2, "low": 0.5, "close": 1.5, "volume": 7},
+                        'MNQ', '1m')
+        assert bar.timestamp == START
+        assert (bar.open, bar.high, bar.low, bar.close, bar.volume) == (1.0, 2.0, 0.5, 1.5, 7)
+        assert parse_bar({"o": 1}, 'MNQ', '1m') is None
+
+
+class TestHistoryClient:
+    """Test chunking, pagination and retries."""
+
+    @pytest.mark.asyncio
+    async def test_chunks_long_range(self):
+        api = FakeHistoryAPI(250)
+        bars = await _client(api).fetch_bars('MNQ', '1m', START, START + timedelta(minutes=249))
+        assert len(bars) == 250
+        assert [b.timestamp for b in bars] == api.bars
+        assert len(api.requests) >= 3
+        assert api.requests[0]["contractId"] == "CON.F.US.MNQ.H25"
+
+    @pytest.mark.asyncio
+    async def test_paginates_truncated_response(self):
+        api = FakeHistoryAPI(250)
+        # Range spans a single chunk of 1000 minutes but the API only returns 100 bars per call
+        client = _client(api)
+        client.max_bars_per_request = 100
+        bars = await client._fetch_chunk("CON", "MNQ", "1m", 2, 1, START, START + timedelta(minutes=999), False)
+        assert len(api.requests) == 3
+        assert sorted({b.timestamp for b in bars}) == api.bars
+
+    @pytest.mark.asyncio
+    async def test_retries_then_succeeds(self):
+        api = FakeHistoryAPI(10, failures=2)
+        client = _client(api)
+        bars = await client.fetch_bars('MNQ', '1m', START, START + timedelta(minutes=9))
+        assert len(bars) == 10
+        assert client.stats["retries"] == 2
+
+    @pytest.mark.asyncio
+    async def test_gives_up_after_max_retries(self):
+        api = FakeHistoryAPI(10, failures=10)
+        client = _client(api, max_retries=2)
+        with pytest.raises(HistoryFetchError):
+            await client.fetch_bars('MNQ', '1m', START, START + timedelta(minutes=9))
+        assert len(api.requests) == 3
+
+    @pytest.mark.asyncio
+    async def test_schema_change_fails_without_retry(self):
+        requests = []
+        client = _client(lambda payload: requests.append(payload) or {"success": True, "candles": []})
+        with pytest.raises(HistoryFetchError, match="'bars' should be a list"):
+            await client.fetch_bars('MNQ', '1m', START, START + timedelta(minutes=9))
+        assert len(requests) == 1 and client.stats["retries"] == 0
+
+    def test_bars_to_arrays(self):
+        np = pytest.importorskip('numpy')
+        bars = [parse_bar({"t": START.isoformat(), "o": 1, "h": 2, "l": 0, "c": 1, "v": 3}, 'MNQ', '1m')]
+        arrays = bars_to_arrays(bars)
+        assert arrays["close"].dtype == np.float64
+        assert arrays["volume"].tolist() == [3]
+        assert arrays["timestamp"][0] == np.datetime64('2025-01-06T14:30:00.000')
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -24,7 +24,8 @@ class TestHTTPOptimization:
         with patch.dict(os.environ, {
             'PROJECT_X_API_KEY': 'test_key',
             'PROJECT_X_USERNAME': 'test_user',
-            'API_TIMEOUT': '30'
+            'API_TIMEOUT': '30',
+            'ARTIFACT_LOCKING_ENABLED': 'false'
         }):
             bot = TopStepXTradingBot(api_key='test_key', username='test_user')
             # Replace the real session with a mock for testing
@@ -174,6 +175,24 @@ class TestHTTPOptimization:
         call_kwargs = bot._http_session.request.call_args[1]
         assert call_kwargs['timeout'] == 60
     
+    def test_operation_timeout_and_deadline(self, bot):
+        """Test that cancels use their own timeout and an expired deadline skips the request."""
+        mock_response = Mock(spec=Response)
+        mock_response.text = '{"success": true}'
+        mock_response.json.return_value = {"success": True}
+        mock_response.status_code = 200
+        mock_response.raise_for_status = Mock()
+        bot._http_session.request.return_value = mock_response
+        
+        with patch.dict(os.environ, {'API_TIMEOUT': '60'}):
+            bot._make_curl_request("POST", "/api/Order/cancel", data={"orderId": 1})
+        assert bot._http_session.request.call_args[1]['timeout'] == bot.request_timeouts.timeouts['cancel']
+        
+        bot._http_session.request.reset_mock()
+        result = bot._make_curl_request("POST", "/api/Order/cancel", data={"orderId": 1}, deadline=0)
+        assert result["deadline_exceeded"] is True
+        bot._http_session.request.assert_not_called()
+    
     def test_put_patch_methods(self, bot):
         """Test PUT and PATCH methods also send JSON data."""
         mock_response = Mock(spec=Response)
@@ -217,6 +236,44 @@ class TestHTTPOptimization:
         
         # Verify same session was used for all requests
         assert bot._http_session.request.call_count == 3
+    
+    def test_bulk_endpoints_use_bulk_pool(self, bot):
+        """Test history/search endpoints are routed to the bulk pool."""
+        mock_response = Mock(spec=Response)
+        mock_response.text = '{"bars": []}'
+        mock_response.json.return_value = {"bars": []}
+        mock_response.status_code = 200
+        mock_response.raise_for_status = Mock()
+        bot._bulk_http_session = MagicMock()
+        bot._bulk_http_session.request.return_value = mock_response
+        
+        result = bot._make_curl_request("POST", "/api/History/retrieveBars", data={"contractId": "X"})
+        
+        assert result == {"bars": []}
+        bot._bulk_http_session.request.assert_called_once()
+        bot._http_session.request.assert_not_called()
+    
+    def test_order_endpoints_use_critical_pool(self, bot):
+        """Test order placement never shares the bulk pool."""
+        bot._bulk_http_session = MagicMock()
+        
+        assert bot._select_http_pool("/api/Order/place") == ("critical", bot._http_session)
+        assert bot._select_http_pool("/api/Order/cancel")[0] == "critical"
+        assert bot._select_http_pool("/api/Order/search")[0] == "bulk"
+        assert bot._select_http_pool("/api/Account/search")[0] == "bulk"
+    
+    def test_pool_metrics_recorded(self, bot):
+        """Test per-pool metrics are tracked."""
+        from infrastructure.performance_metrics import MetricsTracker
+        tracker = MetricsTracker()
+        tracker.record_http_pool_start("bulk")
+        tracker.record_http_pool_start("critical")
+        tracker.record_http_pool_call("critical", 12.0, success=True)
+        
+        summary = tracker.get_http_pool_summary()
+        assert summary["bulk"]["in_flight"] == 1
+        assert summary["critical"]["in_flight"] == 0
+        assert summary["critical"]["requests"] == 1
 
 
 if __name__ == "__main__":
@@ -0,0 +1,87 @@
+"""
+Unit tests for bar resampling
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone, timedelta
+from zoneinfo import ZoneInfo
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar
+from core.market_data import resample_bars, resample_bar_dicts
+from core.session_calendar import SessionCalendar
+
+
+START = datetime(2025, 11, 19, 14, 30, 0, tzinfo=timezone.utc)
+
+
+def _minute_bars(symbol, start, count):
+    return [Bar(symbol, '1m', start + timedelta(minutes=i), 100.0 + i, 100.5 + i, 99.5 + i, 100.25 + i, 10, 2)
+            for i in range(count)]
+
+
+class TestResampleBars:
+    """Test rolling 1m bars into coarser timeframes"""
+
+    def test_minute_bars_to_five_minutes(self):
+        """Test OHLCV merge, epoch alignment and partial bucket handling"""
+        bars = _minute_bars('MNQ', START + timedelta(minutes=2), 12)  # 14:32 .. 14:43
+        result = resample_bars(reversed(bars), '1m', '5m')
+
+        assert [b.timestamp.minute for b in result] == [30, 35, 40]
+        first = result[0]
+        assert (first.open, first.high, first.low, first.close) == (100.0, 102.5, 99.5, 102.25)
+        assert (first.volume, first.tick_count, first.timeframe) == (30, 6, '5m')
+        assert result[1].volume == 50
+
+        complete = resample_bars(bars, '1m', '5m', include_partial=False)
+        assert [b.timestamp.minute for b in complete] == [30, 35]
+        with pytest.raises(ValueError):
+            resample_bars(bars, '5m', '7m')
+        with pytest.raises(ValueError):
+            resample_bars(bars, '1m', '1000v')
+
+    def test_daily_bars_follow_globex_session(self):
+        """Test daily bars run 17:00 CT to 16:00 CT (17:00 ET close) with a calendar"""
+        chicago = ZoneInfo('America/Chicago')
+        calendar = SessionCalendar()
+        # Tuesday 15:55 CT (Tuesday session) through Tuesday 17:04 CT (Wednesday session)
+        before_close = _minute_bars('MES', datetime(2025, 11, 18, 15, 55, tzinfo=chicago), 5)
+        after_open = _minute_bars('MES', datetime(2025, 11, 18, 17, 0, tzinfo=chicago), 5)
+
+        daily = resample_bars(before_close + after_open, '1m', '1d', session_calendar=calendar)
+        assert len(daily) == 2
+        assert daily[0].timestamp == datetime(2025, 11, 17, 17, 0, tzinfo=chicago)
+        assert daily[1].timestamp == datetime(2025, 11, 18, 17, 0, tzinfo=chicago)
+        assert daily[0].timestamp.astimezone(ZoneInfo('America/New_York')).hour == 18
+        assert (daily[0].volume, daily[1].volume) == (50, 50)
+        assert daily[0].is_rth is None
+
+        hourly = resample_bars(before_close, '1m', '1h', session_calendar=calendar)
+        assert hourly[0].timestamp == datetime(2025, 11, 18, 15, 0, tzinfo=chicago)
+        assert hourly[0].is_rth is False
+
+        utc_daily = resample_bars(before_close + after_open, '1m', '1d')
+        assert [(b.timestamp, b.volume) for b in utc_daily] == [(datetime(2025, 11, 18, tzinfo=timezone.utc), 100)]
+
+    def test_dict_bars_and_symbols_resampled_independently(self):
+        """Test API-style dict bars and multi-symbol input"""
+        rows = [{'time': (START + timedelta(minutes=i)).isoformat().replace('+00:00', 'Z'),
+                 'open': 1.0 + i, 'high': 2.0 + i, 'low': 0.5, 'close': 1.5 + i, 'volume': 3}
+                for i in range(15)]
+        rows.append({'time': 'not a time', 'open': 9.0})
+        result = resample_bar_dicts(rows, '1m', '15m')
+        assert len(result) == 1
+        assert result[0]['timestamp'] == result[0]['time'] == START.isoformat()
+        assert (result[0]['open'], result[0]['high'], result[0]['close'], result[0]['volume']) == (1.0, 16.0, 15.5, 45)
+
+        mixed = resample_bars(_minute_bars('MNQ', START, 5) + _minute_bars('MES', START, 5), '1m', '5m')
+        assert [(b.symbol, b.volume) for b in mixed] == [('MES', 50), ('MNQ', 50)]
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,119 @@
+"""
+Unit tests for batched bar/tick writes in the database module
+"""
+
+import pytest
+import os
+import sys
+from contextlib import contextmanager
+from datetime import datetime, timedelta, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+pytest.importorskip('psycopg2')
+
+import infrastructure.database as database
+from infrastructure.database import DatabaseManager, MarketDataWriter
+from core.bar_aggregator import Bar
+from core.market_events import BarClosed, Trade
+
+T0 = datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc)
+
+
+class RecordingDB:
+    """Stands in for DatabaseManager.write_market_data."""
+
+    def __init__(self):
+        self.batches = []
+        self.fail = False
+
+    def write_market_data(self, ticks, bars, copy=True):
+        if self.fail:
+            raise RuntimeError("connection refused")
+        self.batches.append((list(ticks), list(bars)))
+
+
+class TestWriteMarketData:
+    """Test the single-transaction COPY/upsert write path."""
+
+    def test_copy_ticks_and_upsert_bars(self, monkeypatch):
+        db = DatabaseManager.__new__(DatabaseManager)
+        db.tick_storage = 'partitioned'
+        db._tick_partitions = set()
+        executed, copied, upserted = [], [], []
+
+        class Cursor:
+            def __enter__(self):
+                return self
+
+            def __exit__(self, *exc):
+                return False
+
+            def execute(self, query, params=None):
+                executed.append(repr(query))
+
+            def copy_expert(self, query, buffer):
+                copied.append((query, buffer.read()))
+
+        class Conn:
+            def cursor(self, cursor_factory=None):
+                return Cursor()
+
+        @contextmanager
+        def get_connection(read_only=False):
+            yield Conn()
+
+        db.get_connection = get_connection
+        monkeypatch.setattr(database, 'execute_values',
+                            lambda cur, query, values, page_size=100: upserted.append((query, values)))
+        ticks = [DatabaseManager._tick_row({'symbol': 'mnq', 'timestamp': T0, 'price': 21000.25, 'size': 2,
+                                            'side': 'sell'})]
+        bars = [DatabaseManager._bar_row('MNQ', '1m', {'timestamp': T0, 'open': 1, 'high': 2, 'low': 0.5,
+                                                       'close': 1.5, 'volume': 10, 'tick_count': 4})]
+        db.write_market_data(ticks, bars)
+        query, text = copied[0]
+        assert query.startswith('COPY market_ticks')
+        assert text == "2025-11-19T14:30:00+00:00\tMNQ\t21000.25\t2\t1\t\\N\t\\N\n"
+        assert any('market_ticks_p20251119' in q for q in executed)
+        assert 'ON CONFLICT' in upserted[0][0] and upserted[0][1][0][-1] == '{"tick_count": 4}'
+
+
+class TestMarketDataWriter:
+    """Test buffering, size-triggered flushes, retries and flush-on-stop."""
+
+    def bar(self, minute, close=1.0):
+        return Bar(symbol='mnq', timeframe='1m', timestamp=T0 + timedelta(minutes=minute), open=1.0,
+                   high=2.0, low=0.5, close=close, volume=10, tick_count=3)
+
+    def test_batches_on_size_and_deduplicates_bars(self):
+        db = RecordingDB()
+        writer = MarketDataWriter(db, batch_size=3, flush_interval=60, max_buffer=100, use_copy=True)
+        writer.on_market_event(Trade(symbol='MNQ', timestamp=T0, price=100.0, size=1, side='buy'))
+        writer.on_market_event(BarClosed.from_bar(self.bar(0)))
+        writer.add_bars([self.bar(0, close=1.5)])  # Revised bar replaces the buffered one
+        assert db.batches == [] and writer.pending() == 2
+        writer.add_ticks([{'symbol': 'MNQ', 'timestamp': T0, 'price': 101.0}, {'symbol': 'MNQ', 'price': 1.0}])
+        ticks, bars = db.batches[0]
+        assert [t[2] for t in ticks] == [100.0, 101.0] and len(bars) == 1 and bars[0][6] == 1.5
+        assert bars[0][0] == 'MNQ' and writer.pending() == 0
+        assert writer.get_stats()['batches_written'] == 1 and writer.get_stats()['ticks_written'] == 2
+
+    def test_failed_batch_is_retried_in_order(self):
+        db = RecordingDB()
+        writer = MarketDataWriter(db, batch_size=1000, flush_interval=60, max_buffer=4, use_copy=True)
+        writer.add_ticks([{'symbol': 'MNQ', 'timestamp': T0, 'price': p} for p in (1.0, 2.0)])
+        db.fail = True
+        assert writer.flush() == 0 and writer.pending() == 2
+        writer.add_ticks([{'symbol': 'MNQ', 'timestamp': T0, 'price': p} for p in (3.0, 4.0, 5.0)])
+        assert writer.flush() == 0  # 5 rows > max_buffer: the oldest tick goes
+        assert writer.get_stats()['rows_dropped'] == 1 and writer.get_stats()['write_errors'] == 2
+        db.fail = False
+        writer.start()
+        writer.stop()  # Flushes what is left
+        assert [t[2] for t in db.batches[0][0]] == [2.0, 3.0, 4.0, 5.0]
+        assert writer.pending() == 0 and not writer.get_stats()['running']
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
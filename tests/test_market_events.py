@@ -0,0 +1,98 @@
+"""
+Unit tests for typed market events
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar
+from core.market_events import (
+    MarketEvent, Trade, Quote, DepthUpdate, BarClosed, GapRepaired, SessionEvent, SessionEventKind,
+    Imbalance, market_event_from_dict,
+)
+
+
+TS = datetime(2025, 11, 19, 14, 30, 0, tzinfo=timezone.utc)
+
+
+def _describe(event: MarketEvent) -> str:
+    match event:
+        case Trade(price=price, side="buy"):
+            return f"buy@{price}"
+        case Quote(bid=bid, ask=ask):
+            return f"{bid}x{ask}"
+        case BarClosed(bar=Bar(timeframe="5m", close=close)):
+            return f"5m:{close}"
+        case SessionEvent(kind=SessionEventKind.OPEN):
+            return "open"
+        case _:
+            return "other"
+
+
+class TestMarketEvents:
+    """Test market event variants"""
+
+    def test_quote_from_gateway(self):
+        """Test GatewayQuote payload conversion"""
+        quote = Quote.from_gateway('MNQ', {'bestBid': 15000.25, 'bestAsk': 15000.5, 'lastPrice': 15000.5}, TS)
+        assert isinstance(quote, MarketEvent)
+        assert quote.mid == pytest.approx(15000.375)
+        assert quote.volume is None
+
+    def test_trade_from_gateway_side(self):
+        """Test GatewayTrade side codes map to buy/sell"""
+        trade = Trade.from_gateway('MES', {'price': 5000.0, 'volume': 3, 'type': 1,
+                                           'timestamp': '2025-11-19T14:30:00Z'})
+        assert trade.side == 'sell'
+        assert trade.timestamp == TS
+
+    def test_depth_from_order_book_payload(self):
+        """Test depth levels from nested orderBook dicts and pairs"""
+        depth = DepthUpdate.from_gateway('MNQ', {'orderBook': {
+            'bids': [{'price': 100.0, 'volume': 5}],
+            'asks': [[100.25, 7]],
+        }}, TS)
+        assert depth.bids == ((100.0, 5),)
+        assert depth.asks == ((100.25, 7),)
+
+    def test_pattern_matching(self):
+        """Test variants are usable with structural pattern matching"""
+        bar = Bar('MNQ', '5m', TS, 1.0, 2.0, 0.5, 1.5)
+        assert _describe(Trade('MNQ', TS, 100.0, 1, 'buy')) == 'buy@100.0'
+        assert _describe(Quote('MNQ', TS, bid=1.0, ask=2.0)) == '1.0x2.0'
+        assert _describe(BarClosed.from_bar(bar)) == '5m:1.5'
+        assert _describe(SessionEvent('MNQ', TS, SessionEventKind.OPEN, 'RTH')) == 'open'
+        assert _describe(DepthUpdate('MNQ', TS)) == 'other'
+
+    @pytest.mark.parametrize('event', [
+        Trade('MNQ', TS, 15000.0, 2, 'buy'),
+        Quote('MNQ', TS, bid=1.0, ask=1.25, last=1.25, volume=10),
+        DepthUpdate('MNQ', TS, bids=((1.0, 2),), asks=((1.25, 3),)),
+        BarClosed.from_bar(Bar('MNQ', '1m', TS, 1.0, 2.0, 0.5, 1.5, volume=9, tick_count=4)),
+        GapRepaired('MNQ', TS, '1m', TS, missing=3, filled=2),
+        SessionEvent('MNQ', TS, SessionEventKind.HALT),
+        Imbalance('MNQ', TS, '1m', 'buy', 15000.0, 15000.5, 3, 4.5),
+    ])
+    def test_dict_round_trip(self, event):
+        """Test to_dict / market_event_from_dict round trip"""
+        assert market_event_from_dict(event.to_dict()) == event
+
+    def test_unknown_type(self):
+        """Test unknown event types are rejected"""
+        with pytest.raises(ValueError, match="Unknown market event type"):
+            market_event_from_dict({'type': 'weather'})
+
+    def test_events_are_immutable(self):
+        """Test events cannot be mutated by consumers"""
+        trade = Trade('MNQ', TS, 1.0)
+        with pytest.raises(Exception):
+            trade.price = 2.0
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,88 @@
+"""
+Unit tests for Market Profile (TPO) computation
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone, timedelta
+from zoneinfo import ZoneInfo
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar
+from core.market_events import Trade
+from core.market_profile import MarketProfileBuilder, build_market_profiles, tpo_letter
+
+
+EASTERN = ZoneInfo('US/Eastern')
+RTH_OPEN = datetime(2025, 11, 19, 9, 30, tzinfo=EASTERN)
+
+
+def _bar(start_minutes, low, high, symbol='MNQ', volume=10):
+    return Bar(symbol, '30m', RTH_OPEN + timedelta(minutes=start_minutes), low, high, low, high, volume)
+
+
+class TestMarketProfile:
+    """Test TPO letters, POC, value area and initial balance"""
+
+    def test_bar_profile_levels(self):
+        """Test a bar-built session profile"""
+        builder = MarketProfileBuilder(period_minutes=30, anchor='rth', tz='US/Eastern', tick_sizes={'MNQ': 1.0})
+        for i, (low, high) in enumerate([(100, 104), (102, 106), (103, 105), (103, 104), (101, 103)]):
+            builder.add_bar(_bar(30 * i, low, high))
+
+        profile = builder.profile('MNQZ25')
+        assert profile.symbol == 'MNQ'
+        assert profile.session_start == RTH_OPEN.astimezone(timezone.utc)
+        assert profile.tpos[100.0] == 'A'
+        assert profile.tpos[103.0] == 'ABCDE'
+        assert profile.tpos[104.0] == 'ABCD'
+        assert profile.tpos[106.0] == 'B'
+        assert profile.poc == 103.0
+        assert (profile.value_area_low, profile.value_area_high) == (102.0, 105.0)
+        assert (profile.initial_balance_low, profile.initial_balance_high) == (100.0, 106.0)
+        assert (profile.low, profile.high, profile.periods) == (100.0, 106.0, 5)
+        assert profile.total_tpos == 18
+        assert profile.volume == 50
+        assert profile.render().splitlines()[0].split() == ['106', 'B']
+        assert tpo_letter(26) == 'a' and tpo_letter(52) == 'A'
+
+    def test_streaming_trades_roll_sessions(self):
+        """Test trades build rows per period and a new anchor starts a new profile"""
+        builder = MarketProfileBuilder(period_minutes=30, anchor='rth', tz='US/Eastern',
+                                       tick_sizes={'MES': 0.25}, row_ticks=2)
+        builder.add_trade('MES', 5000.25, 1, RTH_OPEN + timedelta(minutes=1))   # Row 5000.0, A
+        builder.add_trade('MES', 5000.75, 1, RTH_OPEN + timedelta(minutes=45))  # Row 5000.5, B
+        builder.add_trade('MES', 5000.0, 1, RTH_OPEN + timedelta(minutes=50))   # Row 5000.0, B
+        profile = builder.profile('MES')
+        assert profile.row_size == 0.5
+        assert profile.tpos == {5000.0: 'AB', 5000.5: 'B'}
+        assert profile.poc == 5000.0
+
+        builder.add_trade('MES', 5010.0, 2, RTH_OPEN + timedelta(days=1, minutes=5))
+        builder.add_trade('MES', 4990.0, 2, RTH_OPEN + timedelta(minutes=55))  # Late print, ignored
+        assert builder.profile('MES').tpos == {5010.0: 'A'}
+        completed = builder.completed_profiles('MES')
+        assert len(completed) == 1 and completed[0].tpos == {5000.0: 'AB', 5000.5: 'B'}
+
+    def test_batch_profiles_from_ticks_and_bars(self):
+        """Test the batch function over mixed inputs and multiple sessions"""
+        ticks = [Trade('MNQ', RTH_OPEN + timedelta(minutes=m), 15000.0 + m % 3, 1) for m in range(0, 90, 5)]
+        ticks += [{'symbol': 'MNQ', 'price': 15100.0, 'size': 1,
+                   'timestamp': (RTH_OPEN + timedelta(days=1)).isoformat()}]
+        bars = [_bar(0, 10, 12, symbol='MES')]
+        profiles = build_market_profiles(ticks + bars, anchor='rth', tz='US/Eastern',
+                                         tick_sizes={'MNQ': 1.0, 'MES': 1.0})
+        assert [(p.symbol, p.session_start.date().isoformat()) for p in profiles] == [
+            ('MES', '2025-11-19'), ('MNQ', '2025-11-19'), ('MNQ', '2025-11-20')]
+        assert profiles[1].tpos == {15000.0: 'ABC', 15001.0: 'ABC', 15002.0: 'ABC'}
+        assert profiles[1].poc == 15001.0
+        assert profiles[0].to_dict()['tpos'] == {'10.0': 'A', '11.0': 'A', '12.0': 'A'}
+        with pytest.raises(ValueError):
+            MarketProfileBuilder(period_minutes=0)
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,133 @@
+"""
+Unit tests for limit order chasing (pegged execution)
+"""
+
+import asyncio
+import pytest
+import os
+import sys
+from datetime import datetime, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.market_events import Quote
+from core.order_chaser import OrderChaser
+
+
+class FakeBroker:
+    """Records modifications, cancels and market orders; any call can be made to fail"""
+
+    def __init__(self):
+        self.modified = []
+        self.cancelled = []
+        self.market_orders = []
+        self.fail_modify = False
+        self.fail_cancel = False
+
+    async def modify(self, order_id, new_price=None, account_id=None, order_type=None):
+        self.modified.append((order_id, new_price, order_type))
+        return {'error': 'order not working'} if self.fail_modify else {'success': True}
+
+    async def cancel(self, order_id, account_id=None):
+        self.cancelled.append(order_id)
+        return {'error': 'order already filled'} if self.fail_cancel else {'success': True}
+
+    async def place_market(self, symbol, side, quantity, account_id=None, strategy_name=None):
+        self.market_orders.append((symbol, side, quantity, strategy_name))
+        return {'success': True, 'orderId': 900}
+
+
+def quote(bid=None, ask=None, symbol='MNQ'):
+    return Quote(symbol=symbol, timestamp=datetime.now(timezone.utc), bid=bid, ask=ask)
+
+
+def make_chaser(broker, **kwargs):
+    kwargs.setdefault('interval_ms', 60_000)  # Tests step orders by hand
+    kwargs.setdefault('max_offset_ticks', 4)
+    return OrderChaser(modify=broker.modify, cancel=broker.cancel, place_market=broker.place_market, **kwargs)
+
+
+class TestOrderChaser:
+    """Test repricing, caps, conversion and chase termination"""
+
+    @pytest.mark.asyncio
+    async def test_reprices_buy_toward_market_up_to_cap(self):
+        broker = FakeBroker()
+        chaser = make_chaser(broker)
+        chaser.on_market_event(quote(bid=21000.0, ask=21000.25))
+        order = chaser.chase('1', '7', 'MNQZ5', 'BUY', 2, limit_price=21000.0)
+        await chaser.step(order)
+        assert broker.modified == []  # Already at the bid
+        chaser.on_market_event(quote(bid=21000.5))  # Ask kept from the previous quote
+        assert chaser.touch('MNQ', 'SELL') == 21000.25
+        await chaser.step(order)
+        chaser.on_market_event(quote(bid=20999.0))  # Market comes back: never back off
+        await chaser.step(order)
+        chaser.on_market_event(quote(bid=21003.0))
+        await chaser.step(order)
+        assert broker.modified == [('7', 21000.5, 1), ('7', 21001.0, 1)]
+        assert order.state == 'capped' and order.price == order.cap_price == 21001.0
+        assert chaser.get_orders() == {} and chaser.get_stats()['reprices'] == 2
+        await chaser.stop()
+
+    @pytest.mark.asyncio
+    async def test_sell_converts_to_market_past_cap(self):
+        broker = FakeBroker()
+        chaser = make_chaser(broker, max_offset_ticks=2, convert_to_market=True)
+        order = chaser.chase('1', '8', 'MES', 'SELL', 3, limit_price=6000.0, strategy='orb')
+        chaser.on_market_event(quote(bid=5998.75, ask=5999.0, symbol='MESZ5'))
+        await chaser.step(order)
+        assert broker.modified == [('8', 5999.5, 1)] and order.state == 'converted'
+        assert broker.cancelled == ['8'] and broker.market_orders == [('MES', 'SELL', 3, 'orb')]
+        assert order.market_order_id == '900' and chaser.conversions == 1
+        await chaser.stop()
+
+    @pytest.mark.asyncio
+    async def test_failed_cancel_does_not_go_to_market(self):
+        broker = FakeBroker()
+        broker.fail_cancel = True
+        chaser = make_chaser(broker, max_offset_ticks=0, convert_to_market=True)
+        order = chaser.chase('1', '9', 'MNQ', 'BUY', 1, limit_price=21000.0)
+        chaser.on_market_event(quote(bid=21002.0, ask=21002.25))
+        await chaser.step(order)
+        assert order.state == 'failed' and 'filled?' in order.error and broker.market_orders == []
+        await chaser.stop()
+
+    @pytest.mark.asyncio
+    async def test_fill_cancel_and_failures_end_chase(self):
+        broker = FakeBroker()
+        chaser = make_chaser(broker, max_failures=2)
+        filled = chaser.chase('1', '10', 'MNQ', 'BUY', 1, limit_price=21000.0)
+        cancelled = chaser.chase('1', '11', 'MNQ', 'BUY', 1, limit_price=21000.0)
+        failing = chaser.chase('1', '12', 'MNQ', 'BUY', 1, limit_price=21000.0)
+        assert chaser.on_fill('10') and not chaser.on_fill('10')
+        assert chaser.forget('11')
+        broker.fail_modify = True
+        chaser.on_market_event(quote(bid=21000.25))
+        for order in (filled, cancelled, failing, failing):
+            await chaser.step(order)
+        assert [o.state for o in (filled, cancelled, failing)] == ['filled', 'cancelled', 'failed']
+        assert [m[0] for m in broker.modified] == ['12', '12'] and chaser.modify_failures == 2
+        await chaser.stop()
+
+    @pytest.mark.asyncio
+    async def test_background_task_reprices_every_interval(self):
+        broker = FakeBroker()
+        chaser = make_chaser(broker, interval_ms=5)
+        chaser.on_market_event(quote(bid=21000.0))
+        order = chaser.chase('1', '13', 'MNQ', 'BUY', 1, limit_price=21000.0)
+        chaser.on_market_event(quote(bid=21000.25))
+        await asyncio.sleep(0.05)
+        chaser.on_market_event(quote(bid=21000.5))
+        await asyncio.sleep(0.05)
+        assert [m[1] for m in broker.modified] == [21000.25, 21000.5] and order.state == 'chasing'
+        chaser.on_fill('13')
+        await asyncio.sleep(0.02)
+        assert chaser._tasks == {}
+        with pytest.raises(ValueError):
+            chaser.chase('1', '14', 'MNQ', 'BUY', 1, limit_price=21000.0, interval_ms=0)
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,89 @@
+"""
+Unit tests for order auto-expiration
+"""
+
+import pytest
+import asyncio
+import os
+import sys
+import time
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.order_expiry import OrderExpiryScheduler
+
+
+class FakeBroker:
+    """cancel_order with canned responses"""
+
+    def __init__(self):
+        self.cancelled = []
+        self.response = {'success': True}
+
+    async def cancel_order(self, order_id, account_id=None):
+        self.cancelled.append((order_id, account_id))
+        return self.response
+
+
+class TestOrderExpiryScheduler:
+    """Test deadlines, cancellation and the expiration callback"""
+
+    @pytest.mark.asyncio
+    async def test_due_order_cancelled_and_reported(self):
+        broker = FakeBroker()
+        expired = []
+        scheduler = OrderExpiryScheduler(cancel=broker.cancel_order, on_expired=expired.append)
+        scheduler.schedule('123', 500, expire_after_secs=60, symbol='MNQ')
+        scheduler.schedule('123', 501, expire_after_secs=120, symbol='MNQ')
+        assert await scheduler.expire_due() == []
+        assert set(scheduler.get_pending()) == {'500', '501'}
+        result = await scheduler.expire_due(now=time.monotonic() + 90)
+        assert broker.cancelled == [('500', '123')] and result == expired
+        assert expired[0].cancelled and expired[0].symbol == 'MNQ' and expired[0].expire_after_secs == 60
+        stats = scheduler.get_stats()
+        assert stats['pending'] == 1 and stats['orders_expired'] == 1 and stats['cancel_failures'] == 0
+        await scheduler.stop()
+
+    @pytest.mark.asyncio
+    async def test_forgotten_order_not_cancelled(self):
+        broker = FakeBroker()
+        scheduler = OrderExpiryScheduler(cancel=broker.cancel_order)
+        scheduler.schedule('123', 600, expire_after_secs=30)
+        assert scheduler.forget(600) and not scheduler.forget(600)
+        assert await scheduler.expire_due(now=time.monotonic() + 60) == [] and broker.cancelled == []
+        await scheduler.stop()
+
+    @pytest.mark.asyncio
+    async def test_failed_cancel_reported(self):
+        broker = FakeBroker()
+        broker.response = {'error': 'order already filled'}
+        scheduler = OrderExpiryScheduler(cancel=broker.cancel_order)
+        scheduler.schedule('123', 700, expire_after_secs=5)
+        [expiration] = await scheduler.expire_due(now=time.monotonic() + 10)
+        assert not expiration.cancelled and expiration.error == 'order already filled'
+        assert scheduler.get_stats()['cancel_failures'] == 1
+        await scheduler.stop()
+
+    @pytest.mark.asyncio
+    async def test_background_task_expires_earliest_deadline(self):
+        broker = FakeBroker()
+        expired = []
+        scheduler = OrderExpiryScheduler(cancel=broker.cancel_order, on_expired=expired.append)
+        scheduler.schedule('123', 800, expire_after_secs=30)
+        await asyncio.sleep(0)  # Loop now sleeping towards 30s
+        scheduler.schedule('123', 801, expire_after_secs=0.05)  # Earlier deadline wakes it
+        await asyncio.sleep(0.2)
+        assert [e.order_id for e in expired] == ['801'] and scheduler.get_stats()['running']
+        await scheduler.stop()
+        assert not scheduler.get_stats()['running']
+
+    def test_window_must_be_positive(self):
+        scheduler = OrderExpiryScheduler(cancel=FakeBroker().cancel_order)
+        for window in (0, -5, None):
+            with pytest.raises(ValueError):
+                scheduler.schedule('123', 900, expire_after_secs=window)
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,114 @@
+"""
+Unit tests for per-venue order rounding policies
+"""
+
+import pytest
+import os
+import sys
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.order_rounding import (
+    RoundingMode, VenueRoundingPolicy, VENUE_ROUNDING_POLICIES, get_venue_policy,
+    normalize_order_payload, register_venue_policy, round_price, round_quantity,
+)
+from core.time_in_force import TimeInForce, check_time_in_force, parse_time_in_force, time_in_force_name
+
+
+class TestRoundPrice:
+    """Test price rounding modes."""
+
+    def test_half_even_and_toward_zero(self):
+        assert round_price(15000.125, 0.25, RoundingMode.HALF_EVEN) == 15000.0   # Tie to even tick
+        assert round_price(15000.375, 0.25, RoundingMode.HALF_EVEN) == 15000.5
+        assert round_price(15000.2, 0.25, RoundingMode.TOWARD_ZERO) == 15000.0
+        assert round_price(2000.14, 0.1, RoundingMode.HALF_EVEN) == 2000.1        # Exact, no float residue
+        assert round_price(-1.37, 0.25, RoundingMode.TOWARD_ZERO) == -1.25
+        assert round_price(15000.3, 0, RoundingMode.HALF_EVEN) == 15000.3
+
+    def test_order_direction_is_never_more_aggressive(self):
+        mode = RoundingMode.ORDER_DIRECTION
+        assert round_price(15000.2, 0.25, mode, 'BUY', 'limitPrice') == 15000.0
+        assert round_price(15000.05, 0.25, mode, 1, 'limitPrice') == 15000.25   # Sell limit rounds up
+        assert round_price(15000.05, 0.25, mode, 0, 'stopPrice') == 15000.25    # Buy stop rounds up
+        assert round_price(15000.2, 0.25, mode, 'SELL', 'stopPrice') == 15000.0
+        assert round_price(15000.2, 0.25, mode, None, 'limitPrice') == 15000.25  # No side: half-even
+
+    def test_round_quantity(self):
+        assert round_quantity(2.5, RoundingMode.HALF_EVEN) == 2
+        assert round_quantity(3.5, RoundingMode.HALF_EVEN) == 4
+        assert round_quantity(3.9, RoundingMode.ORDER_DIRECTION) == 3
+        assert round_quantity(7, RoundingMode.TOWARD_ZERO, lot_size=5) == 5
+
+
+class TestVenuePolicies:
+    """Test venue-specific payload normalization."""
+
+    def test_topstepx_rounds_to_nearest_tick(self, monkeypatch):
+        monkeypatch.delenv('ORDER_PRICE_ROUNDING', raising=False)
+        monkeypatch.delenv('ORDER_SIZE_ROUNDING', raising=False)
+        policy = get_venue_policy('topstepx')
+        payload = {"side": 0, "type": 1, "size": 2, "limitPrice": 15000.13, "stopPrice": None}
+        changes = normalize_order_payload(payload, 0.25, policy)
+        assert payload["limitPrice"] == 15000.25
+        assert payload["stopPrice"] is None
+        assert changes == [('limitPrice', 15000.13, 15000.25)]
+
+    def test_truncating_and_directional_venues(self):
+        register_venue_policy(VenueRoundingPolicy('truncating', RoundingMode.TOWARD_ZERO, RoundingMode.TOWARD_ZERO))
+        register_venue_policy(VenueRoundingPolicy('passive', RoundingMode.ORDER_DIRECTION,
+                                                  RoundingMode.ORDER_DIRECTION, lot_size=2))
+        try:
+            order = {"side": 1, "type": 4, "size": 3, "stopPrice": 5000.13}
+            truncated = dict(order)
+            normalize_order_payload(truncated, 0.25, get_venue_policy('truncating'))
+            assert (truncated["stopPrice"], truncated["size"]) == (5000.0, 3)
+
+            passive = {"side": 1, "type": 1, "size": 3, "limitPrice": 5000.13}
+            normalize_order_payload(passive, 0.25, get_venue_policy('passive'))
+            assert (passive["limitPrice"], passive["size"]) == (5000.25, 2)
+
+            with pytest.raises(ValueError):
+                normalize_order_payload({"side": 0, "size": 1}, 0.25, get_venue_policy('passive'))
+        finally:
+            VENUE_ROUNDING_POLICIES.pop('truncating', None)
+            VENUE_ROUNDING_POLICIES.pop('passive', None)
+
+    def test_env_overrides_and_unknown_venue(self, monkeypatch):
+        monkeypatch.setenv('ORDER_VENUE', 'TopStepX')
+        monkeypatch.setenv('ORDER_PRICE_ROUNDING', 'order_direction')
+        policy = get_venue_policy()
+        assert policy.venue == 'topstepx'
+        assert policy.price_mode is RoundingMode.ORDER_DIRECTION
+        assert VENUE_ROUNDING_POLICIES['topstepx'].price_mode is RoundingMode.HALF_EVEN
+        with pytest.raises(ValueError):
+            get_venue_policy('nowhere')
+
+
+
+class TestTimeInForce:
+    """Test TIF parsing and rejection of TIFs the Order/place API cannot express."""
+
+    def test_parse_and_accept_day(self):
+        assert parse_time_in_force(' gtc ') is TimeInForce.GTC and parse_time_in_force(None) is None
+        assert time_in_force_name('fok') == 'FOK' and time_in_force_name('weekly') is None
+        with pytest.raises(ValueError, match='Unsupported time in force'):
+            parse_time_in_force(3)  # No integer codes: the API has no time-in-force field
+        payload = {"side": 0, "type": 1, "size": 1, "limitPrice": 15000.0}
+        assert check_time_in_force(payload, 'day') is TimeInForce.DAY
+        assert check_time_in_force(payload, None) is None
+        assert payload == {"side": 0, "type": 1, "size": 1, "limitPrice": 15000.0}  # Nothing sent
+
+    def test_rejects_unsupported_time_in_force(self):
+        with pytest.raises(ValueError, match='IOC is not supported by the TopStepX API for market orders'):
+            check_time_in_force({"type": 2}, 'IOC')
+        with pytest.raises(ValueError, match='FOK is not supported .* for limit orders'):
+            check_time_in_force({"type": 1, "limitPrice": 15000.0}, 'FOK')
+        with pytest.raises(ValueError, match='GTC is not supported .* for stop orders'):
+            check_time_in_force({"type": 4, "stopPrice": 15000.0}, 'GTC')
+        with pytest.raises(ValueError, match='Unsupported time in force'):
+            parse_time_in_force('GTD')
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,72 @@
+"""
+Unit tests for partitioned Parquet storage of bars and ticks
+"""
+
+import pytest
+import os
+import sys
+from datetime import date, datetime, timedelta, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+pytest.importorskip('polars')
+
+from core.bar_aggregator import Bar
+from core.market_events import BarClosed, Trade
+from core.parquet_store import ParquetStore
+
+START = datetime(2025, 11, 19, 23, 58, tzinfo=timezone.utc)
+
+
+def make_bars(count, start=START):
+    return [Bar('MNQ', '1m', start + timedelta(minutes=i), 1.0 + i, 2.0 + i, 0.5 + i, 1.5 + i, 10, 3)
+            for i in range(count)]
+
+
+class TestParquetStore:
+    """Test partitioned writes, pruned reads and compaction."""
+
+    def test_bars_round_trip_across_date_partitions(self, tmp_path):
+        store = ParquetStore(tmp_path, flush_rows=1000)
+        store.write_bars(make_bars(4))  # 23:58 .. 00:01 spans two UTC dates
+        assert store.flush() == 4
+        assert (store.partition_dir('bars', 'MNQ', date(2025, 11, 19), '1m')).is_dir()
+        assert (store.partition_dir('bars', 'MNQ', date(2025, 11, 20), '1m')).is_dir()
+
+        bars = store.read_bars('MNQ', '1m')
+        assert [b.close for b in bars] == [1.5, 2.5, 3.5, 4.5]
+        assert bars[0].timestamp == START and bars[0].tick_count == 3
+        only_second_day = store.read_bars('mnq', '1m', start=START + timedelta(minutes=2))
+        assert len(only_second_day) == 2
+        assert store.read_bars('MES', '1m') == []
+
+    def test_ticks_stream_from_market_events(self, tmp_path):
+        store = ParquetStore(tmp_path, flush_rows=2)
+        store.on_market_event(Trade(symbol='MNQ', timestamp=START, price=15000.25, size=2, side='buy'))
+        assert store.get_stats()['buffered_rows'] == 1
+        store.on_market_event(Trade(symbol='MNQ', timestamp=START + timedelta(seconds=1), price=15000.5, size=1))
+        assert store.get_stats()['files_written'] == 1  # Flushed inline at flush_rows without the flusher
+        store.on_market_event(BarClosed.from_bar(make_bars(1)[0]))
+        store.stop()
+
+        ticks = store.read_ticks('MNQ', START, START + timedelta(minutes=1))
+        assert [t['price'] for t in ticks] == [15000.25, 15000.5]
+        assert ticks[0]['side'] == 'buy' and ticks[1]['side'] is None
+        assert len(store.read_bars('MNQ', '1m')) == 1
+
+    def test_compact_merges_part_files(self, tmp_path):
+        store = ParquetStore(tmp_path, flush_rows=1000)
+        for bar in make_bars(2, datetime(2025, 11, 19, 10, 0, tzinfo=timezone.utc)):
+            store.write_bars([bar])
+            store.flush()
+        day = date(2025, 11, 19)
+        assert len(list(store.partition_dir('bars', 'MNQ', day, '1m').glob('part-*.parquet'))) == 2
+        assert store.compact('bars', 'MNQ', day, '1m') == 2
+        assert len(list(store.partition_dir('bars', 'MNQ', day, '1m').glob('part-*.parquet'))) == 1
+        assert len(store.read_bars('MNQ', '1m')) == 2
+        assert store.compact('bars', 'MNQ', day, '1m') == 0
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
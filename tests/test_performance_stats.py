@@ -0,0 +1,143 @@
+"""
+Unit tests for strategy performance statistics (core.strategy_engine performance and monte_carlo)
+"""
+
+import pytest
+import math
+import os
+import sys
+from datetime import datetime, timedelta, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.strategy_engine import BacktestTrade, CommissionModel, Direction, MonteCarlo, PerformanceStats
+
+# Monday 2025-11-03 15:00 UTC = 10:00 ET (RTH)
+T0 = datetime(2025, 11, 3, 15, 0, tzinfo=timezone.utc)
+
+
+def trade(day, pnl, minutes=0, direction=Direction.LONG, strategy='ema', symbol='MNQ', mae=0.0, mfe=0.0):
+    entry = T0 + timedelta(days=day, minutes=minutes)
+    price = 20000.0
+    return BacktestTrade(strategy_id=strategy, symbol=symbol, direction=direction, quantity=1, entry_time=entry,
+                         entry_price=price, exit_time=entry + timedelta(minutes=30), exit_price=price + pnl / 2,
+                         exit_reason='signal', gross_pnl=pnl, commission=0.0, mae=mae, mfe=mfe)
+
+
+class TestPerformanceStats:
+    """Test trade statistics from backtest and live trade lists"""
+
+    def test_trade_statistics(self):
+        """Test win rate, profit factor, expectancy, streaks, drawdown, ratios and MAE/MFE"""
+        trades = [
+            trade(0, 100, mae=10, mfe=120), trade(0, -50, minutes=60, mae=60, mfe=5),
+            trade(1, -30, mae=40, mfe=10), trade(1, 0, minutes=60),
+            trade(2, 200, mae=20, mfe=250), trade(3, 80, mae=5, mfe=90),
+        ]
+        stats = PerformanceStats.from_trades(trades, initial_capital=1000)
+        assert (stats.trades, stats.wins, stats.losses, stats.breakeven) == (6, 3, 2, 1)
+        assert stats.win_rate == 50.0 and stats.net_pnl == 300.0
+        assert stats.gross_profit == 380.0 and stats.gross_loss == 80.0 and stats.profit_factor == 4.75
+        assert stats.expectancy == 50.0 and stats.avg_win == pytest.approx(126.67) and stats.avg_loss == -40.0
+        assert stats.largest_win == 200.0 and stats.largest_loss == -50.0
+        assert stats.max_win_streak == 2 and stats.max_loss_streak == 2
+        # Equity 1100 -> 1050 -> 1020: 80 below the peak
+        assert stats.max_drawdown == 80.0 and stats.max_drawdown_pct == pytest.approx(7.27)
+        assert stats.avg_holding_minutes == 30.0
+        assert stats.avg_mae == pytest.approx(22.5) and stats.avg_mfe == pytest.approx(79.17)
+
+        # Daily returns on 1000 -> 1050 -> 1020 -> 1220 -> 1300
+        returns = [50 / 1000, -30 / 1050, 200 / 1020, 80 / 1220]
+        mean = sum(returns) / 4
+        deviation = math.sqrt(sum((r - mean) ** 2 for r in returns) / 3)
+        downside = math.sqrt((-30 / 1050) ** 2 / 4)
+        assert stats.trading_days == 4
+        assert stats.sharpe == pytest.approx(mean / deviation * math.sqrt(252), abs=1e-3)
+        assert stats.sortino == pytest.approx(mean / downside * math.sqrt(252), abs=1e-3)
+
+        empty = PerformanceStats.from_trades([])
+        assert empty.trades == 0 and empty.profit_factor is None and empty.sharpe is None and empty.avg_mae is None
+
+    def test_breakdowns_and_live_trades(self):
+        """Test session/strategy/direction breakdowns and consolidated live trade dicts"""
+        live = [
+            # 10:00 ET (RTH) and 20:00 ET (ETH, next trading date)
+            {'symbol': 'MNQ', 'side': 'LONG', 'quantity': 1, 'entry_price': 20000.0, 'exit_price': 20010.0,
+             'entry_time': '2025-11-03T15:00:00+00:00', 'exit_time': '2025-11-03T15:20:00+00:00', 'pnl': 20.0,
+             'strategy': 'orb'},
+            {'symbol': 'MNQ', 'side': 'SHORT', 'quantity': 1, 'entry_price': 20000.0, 'exit_price': 20015.0,
+             'entry_time': '2025-11-04T01:00:00+00:00', 'exit_time': '2025-11-04T01:40:00+00:00', 'pnl': -30.0,
+             'strategy': None},
+            trade(1, 50, direction=Direction.SHORT, strategy='ema', mae=4).to_dict(),
+        ]
+        stats = PerformanceStats.from_trades(live)
+        assert stats.trades == 3 and stats.net_pnl == 40.0
+        assert stats.avg_mae == 4.0  # Only the trade that has it
+        assert stats.max_drawdown == 30.0 and stats.max_drawdown_pct is None
+        assert {k: v.net_pnl for k, v in stats.by_session.items()} == {'eth': -30.0, 'rth': 70.0}
+        assert {k: v.trades for k, v in stats.by_strategy.items()} == {'ema': 1, 'orb': 1, 'unknown': 1}
+        assert {k: v.win_rate for k, v in stats.by_direction.items()} == {'long': 100.0, 'short': 50.0}
+        # The evening trade belongs to the 11-04 session, like the backtest trade
+        assert stats.trading_days == 2 and stats.by_session['rth'].trading_days == 2
+
+        data = stats.to_dict()
+        assert data['by_session']['rth']['trades'] == 2 and 'by_session' not in data['by_session']['rth']
+        assert data['daily_pnl'] == {'2025-11-03': 20.0, '2025-11-04': 20.0}
+
+        with pytest.raises(ValueError):
+            PerformanceStats.from_trades([{'symbol': 'MNQ', 'pnl': 5.0}])
+
+    def test_fee_model_charges_trades_without_commission(self):
+        """Test modeled round-trip costs for trades without a known commission"""
+        fees = CommissionModel({'MNQ': 0.25}, {'MNQ': 0.37}, 0.0, 0.0)
+        live = [
+            {'symbol': 'MNQ', 'side': 'LONG', 'quantity': 2, 'exit_time': '2025-11-03T15:20:00+00:00', 'pnl': 20.0},
+            {'symbol': 'MNQ', 'side': 'LONG', 'quantity': 1, 'exit_time': '2025-11-03T15:40:00+00:00', 'pnl': 9.0,
+             'fees': 1.0},  # Already net of its fees
+            trade(0, 10, minutes=90),  # Backtest trades carry their commission
+        ]
+        stats = PerformanceStats.from_trades(live, fees=fees)
+        assert stats.net_pnl == pytest.approx(20.0 - 2.48 + 9.0 + 10.0) and stats.commissions == pytest.approx(3.48)
+        assert PerformanceStats.from_trades(live).net_pnl == 39.0 and stats.to_dict()['commissions'] == 3.48
+
+
+
+class TestMonteCarlo:
+    """Test trade-sequence resampling"""
+
+    def test_distributions_and_ruin(self):
+        """Test fixed outcomes, ruin probability, percentiles and worker-count independence"""
+        losses = MonteCarlo([-100.0] * 5, initial_capital=1000, simulations=50, ruin_loss=300, seed=1,
+                            workers=1).run()
+        # Every path is five $100 losses
+        assert losses.max_drawdowns == [500.0] * 50 and losses.ruin_probability == 1.0
+        assert losses.percentiles([50])['max_drawdown_pct'] == {50: 50.0}
+        assert losses.to_dict()['percentiles']['net_pnl']['p95'] == -500.0
+
+        trades = [trade(day, pnl) for day, pnl in enumerate([120, -80, 60, -150, 90, 40, -60, 200])]
+        serial = MonteCarlo(trades, initial_capital=1000, simulations=1200, ruin_loss=300, seed=7, workers=1).run()
+        parallel = MonteCarlo(trades, initial_capital=1000, simulations=1200, ruin_loss=300, seed=7, workers=2).run()
+        assert serial.max_drawdowns == parallel.max_drawdowns and serial.ruined == parallel.ruined
+        assert serial.simulations == 1200 and 0 < serial.ruin_probability < 0.5
+        assert serial.probability_of_drawdown(0) == 1.0 and serial.probability_of_drawdown(10 ** 6) == 0.0
+        p = serial.percentiles()['max_drawdown']
+        assert p[5] <= p[50] <= p[95] <= 8 * 150
+        assert serial.percentiles()['final_equity'][50] == pytest.approx(1000 + 160, abs=200)
+
+    def test_block_bootstrap(self):
+        """Test whole-sequence blocks keep the losing streak, plain resampling doesn't"""
+        pnls = [-10.0, -10.0, -10.0, 50.0, 50.0, 50.0]
+        blocks = MonteCarlo(pnls, simulations=500, block_size=6, seed=3, workers=1).run()
+        # Every path is a rotation of the sequence: same total, never more than the three losses in a row
+        assert set(blocks.final_equities) == {50120.0} and max(blocks.max_loss_streaks) == 3
+        plain = MonteCarlo(pnls, simulations=500, seed=3, workers=1).run()
+        assert max(plain.max_loss_streaks) > 3 and min(plain.max_loss_streaks) < 3
+
+        with pytest.raises(ValueError):
+            MonteCarlo([])
+        with pytest.raises(ValueError):
+            MonteCarlo(pnls, block_size=0)
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
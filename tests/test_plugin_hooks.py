@@ -0,0 +1,127 @@
+"""
+Unit tests for lifecycle plugin hooks
+"""
+
+import pytest
+import asyncio
+import os
+import sys
+import types
+from unittest.mock import MagicMock
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.plugin_hooks import PluginHookRegistry, LifecycleEvent, HookContext
+
+
+@pytest.fixture
+def registry():
+    """Create a fresh hook registry"""
+    return PluginHookRegistry()
+
+
+class TestPluginHookRegistry:
+    """Test PluginHookRegistry"""
+
+    def test_register_by_string_event(self, registry):
+        """Test registering a hook with the event's string value"""
+        registry.register("post_auth", lambda ctx: None, name="noop")
+        hooks = registry.get_hooks(LifecycleEvent.POST_AUTH)
+        assert [h.name for h in hooks] == ["noop"]
+
+    def test_register_unknown_event(self, registry):
+        """Test unknown event names are rejected"""
+        with pytest.raises(ValueError, match="Unknown lifecycle event"):
+            registry.register("pre_lunch", lambda ctx: None)
+
+    def test_register_non_callable(self, registry):
+        """Test non-callables are rejected"""
+        with pytest.raises(TypeError):
+            registry.register(LifecycleEvent.POST_AUTH, "not callable")
+
+    @pytest.mark.asyncio
+    async def test_dispatch_sync_and_async_hooks_in_priority_order(self, registry):
+        """Test hooks run in priority order and receive context"""
+        calls = []
+
+        async def async_hook(ctx: HookContext):
+            calls.append(("async", ctx.data["result"]))
+            return "async"
+
+        def sync_hook(ctx: HookContext):
+            calls.append(("sync", ctx.event))
+            return "sync"
+
+        registry.register(LifecycleEvent.POST_FLATTEN, async_hook, priority=200)
+        registry.register(LifecycleEvent.POST_FLATTEN, sync_hook, priority=10)
+
+        results = await registry.dispatch(LifecycleEvent.POST_FLATTEN, None, result={"positions_count": 2})
+
+        assert results == ["sync", "async"]
+        assert calls[0] == ("sync", LifecycleEvent.POST_FLATTEN)
+        assert calls[1] == ("async", {"positions_count": 2})
+
+    @pytest.mark.asyncio
+    async def test_dispatch_isolates_failing_hook(self, registry):
+        """Test a failing hook does not prevent other hooks from running"""
+        def bad_hook(ctx):
+            raise RuntimeError("boom")
+
+        registry.register(LifecycleEvent.PRE_SHUTDOWN, bad_hook, priority=1)
+        registry.register(LifecycleEvent.PRE_SHUTDOWN, lambda ctx: "ok", priority=2)
+
+        results = await registry.dispatch(LifecycleEvent.PRE_SHUTDOWN)
+
+        assert results == [None, "ok"]
+        assert registry.hook_errors == 1
+
+    @pytest.mark.asyncio
+    async def test_dispatch_resolves_account_from_bot(self, registry):
+        """Test account_id defaults to the bot's selected account"""
+        seen = []
+        registry.register(LifecycleEvent.POST_AUTH, lambda ctx: seen.append(ctx.account_id))
+
+        bot = MagicMock()
+        bot.selected_account = {'id': 12345, 'name': 'Test Account'}
+        await registry.dispatch(LifecycleEvent.POST_AUTH, bot)
+
+        assert seen == ["12345"]
+
+    @pytest.mark.asyncio
+    async def test_hook_timeout(self):
+        """Test slow async hooks are cut off by hook_timeout"""
+        registry = PluginHookRegistry(hook_timeout=0.01)
+
+        async def slow_hook(ctx):
+            await asyncio.sleep(1)
+
+        registry.register(LifecycleEvent.PRE_TRADING_OPEN, slow_hook)
+        results = await registry.dispatch(LifecycleEvent.PRE_TRADING_OPEN)
+
+        assert results == [None]
+        assert registry.hook_errors == 1
+
+    def test_unregister(self, registry):
+        """Test removing a hook by name"""
+        registry.register(LifecycleEvent.POST_AUTH, lambda ctx: None, name="temp")
+        assert registry.unregister(LifecycleEvent.POST_AUTH, "temp") is True
+        assert registry.unregister(LifecycleEvent.POST_AUTH, "temp") is False
+
+    def test_load_plugins(self, registry):
+        """Test loading a plugin module exposing register(registry)"""
+        plugin = types.ModuleType("fake_bot_plugin")
+        plugin.register = lambda reg: reg.register(LifecycleEvent.POST_AUTH, lambda ctx: None, name="fake")
+        sys.modules["fake_bot_plugin"] = plugin
+        try:
+            loaded = registry.load_plugins(["fake_bot_plugin", "missing_bot_plugin_xyz"])
+        finally:
+            del sys.modules["fake_bot_plugin"]
+
+        assert loaded == 1
+        assert registry.loaded_plugins == ["fake_bot_plugin"]
+        assert registry.get_stats()["hooks"]["post_auth"] == ["fake"]
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,133 @@
+"""
+Unit tests for the position consistency checker
+"""
+
+import pytest
+import os
+import sys
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.position_reconciler import PositionTracker, PositionConsistencyChecker, net_broker_positions
+
+
+class FakeBroker:
+    """Broker position source returning configurable records"""
+
+    def __init__(self):
+        self.positions = []
+        self.fail = False
+
+    async def fetch(self, account_id):
+        if self.fail:
+            raise RuntimeError("API down")
+        return list(self.positions)
+
+
+@pytest.fixture
+def broker():
+    return FakeBroker()
+
+
+@pytest.fixture
+def tracker():
+    return PositionTracker()
+
+
+class TestPositionTracker:
+    """Test PositionTracker"""
+
+    def test_apply_fills_nets_position(self, tracker):
+        """Test buys and sells net to a signed position"""
+        tracker.apply_fill('1', 'mnq', 'BUY', 3)
+        assert tracker.apply_fill('1', 'MNQ', 1, 1) == 2
+        assert tracker.apply_fill('1', 'MNQ', 'SELL', 2) == 0
+        assert tracker.get_positions('1') == {}
+
+    def test_net_broker_positions(self):
+        """Test broker records net by symbol using type 1 = long, 2 = short"""
+        positions = [
+            {'contractId': 'CON.F.US.MNQ.Z25', 'type': 1, 'size': 2},
+            {'contractId': 'CON.F.US.MES.Z25', 'type': 2, 'size': 1},
+        ]
+        assert net_broker_positions(positions) == {'MNQ': 2, 'MES': -1}
+
+
+class TestPositionConsistencyChecker:
+    """Test PositionConsistencyChecker"""
+
+    @pytest.mark.asyncio
+    async def test_first_check_seeds_ledger(self, tracker, broker):
+        """Test the first check seeds from the broker instead of alerting"""
+        broker.positions = [{'contractId': 'CON.F.US.MNQ.Z25', 'type': 1, 'size': 2}]
+        checker = PositionConsistencyChecker(tracker, broker.fetch, confirmations=1)
+
+        assert await checker.check('1') == []
+        assert tracker.get_position('1', 'MNQ') == 2
+
+    @pytest.mark.asyncio
+    async def test_divergence_alerts_after_confirmations_and_freezes(self, tracker, broker):
+        """Test alert fires once after N consecutive divergent checks"""
+        alerts = []
+        tracker.set_positions('1', {'MNQ': 1})
+        checker = PositionConsistencyChecker(tracker, broker.fetch, alert_callback=alerts.append,
+                                             confirmations=2, tolerance=0, freeze_on_divergence=True)
+
+        first = await checker.check('1')  # broker flat, local long 1
+        assert len(first) == 1 and alerts == []
+        await checker.check('1')
+        await checker.check('1')
+
+        assert len(alerts) == 1
+        assert alerts[0].to_dict()['alert_type'] == 'position_divergence'
+        assert (alerts[0].local_qty, alerts[0].broker_qty) == (1, 0)
+        assert checker.is_frozen('mnq')
+
+    @pytest.mark.asyncio
+    async def test_transient_divergence_does_not_alert(self, tracker, broker):
+        """Test a divergence that resolves before confirmation is ignored"""
+        alerts = []
+        tracker.set_positions('1', {})
+        checker = PositionConsistencyChecker(tracker, broker.fetch, alert_callback=alerts.append, confirmations=2)
+
+        broker.positions = [{'contractId': 'CON.F.US.MNQ.Z25', 'type': 1, 'size': 1}]
+        await checker.check('1')
+        tracker.apply_fill('1', 'MNQ', 'BUY', 1)  # fill arrives late
+        assert await checker.check('1') == []
+        assert alerts == []
+
+    @pytest.mark.asyncio
+    async def test_tolerance(self, tracker, broker):
+        """Test differences within tolerance are accepted"""
+        tracker.set_positions('1', {'MNQ': 2})
+        broker.positions = [{'contractId': 'CON.F.US.MNQ.Z25', 'type': 1, 'size': 3}]
+        checker = PositionConsistencyChecker(tracker, broker.fetch, tolerance=1, confirmations=1)
+        assert await checker.check('1') == []
+
+    @pytest.mark.asyncio
+    async def test_fetch_failure_is_not_flat(self, tracker, broker):
+        """Test API failures skip the check instead of reporting a flat broker"""
+        tracker.set_positions('1', {'MNQ': 1})
+        broker.fail = True
+        checker = PositionConsistencyChecker(tracker, broker.fetch, confirmations=1)
+
+        assert await checker.check('1') == []
+        assert checker.check_failures == 1
+        assert not checker.is_frozen('MNQ')
+
+    @pytest.mark.asyncio
+    async def test_unfreeze_with_resync(self, tracker, broker):
+        """Test unfreezing can resync the ledger to the broker quantity"""
+        tracker.set_positions('1', {'MNQ': 1})
+        broker.positions = [{'contractId': 'CON.F.US.MNQ.Z25', 'type': 2, 'size': 1}]
+        checker = PositionConsistencyChecker(tracker, broker.fetch, confirmations=1, freeze_on_divergence=True)
+        await checker.check('1')
+
+        assert checker.unfreeze('MNQ', resync_account='1') is True
+        assert tracker.get_position('1', 'MNQ') == -1
+        assert await checker.check('1') == []
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,105 @@
+"""
+Unit tests for the raw WebSocket passthrough client (core.websocket.raw)
+"""
+
+import pytest
+import asyncio
+import gzip
+import os
+import sys
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.websocket import HubConnectionError, RawWebSocketClient
+
+
+class FakeConnection:
+    """In-memory WebSocket: the test pushes server frames, the client's sends are collected."""
+
+    def __init__(self):
+        self.incoming: asyncio.Queue = asyncio.Queue()
+        self.sent = []
+
+    async def send(self, data):
+        self.sent.append(data)
+
+    async def recv(self):
+        frame = await self.incoming.get()
+        if isinstance(frame, Exception):
+            raise frame
+        return frame
+
+    async def close(self):
+        pass
+
+
+class TestRawWebSocketClient:
+    """Test raw frame passthrough, auth, replayed subscriptions and backpressure"""
+
+    @pytest.mark.asyncio
+    async def test_frames_auth_and_resubscribe(self):
+        """Test frames reach callbacks untouched and subscriptions are replayed after a reconnect"""
+        connections, seen = [], []
+
+        async def connect(url, headers):
+            seen.append((url, headers))
+            connections.append(FakeConnection())
+            return connections[-1]
+
+        feed = RawWebSocketClient('https://stream.example.com/ws', access_token_factory=lambda: 'tok',
+                                  token_query_param='token', headers={'X-Api-Key': 'k'}, inflate=True,
+                                  reconnect_initial_delay=0.001, reconnect_jitter=0, connector=connect)
+        frames = []
+        feed.on_frame(frames.append)
+        feed.subscribe('{"op":"subscribe","channel":"trades"}')
+        await feed.start()
+        url, headers = seen[0]
+        assert url == 'wss://stream.example.com/ws?token=tok' and headers['X-Api-Key'] == 'k'
+        assert headers['Authorization'] == 'Bearer tok' and feed.get_stats()['url'].endswith('token=***')
+
+        feed.send(b'\x01\x02')
+        connections[0].incoming.put_nowait('{"trade": 1}')
+        connections[0].incoming.put_nowait(b'\x00binary')
+        connections[0].incoming.put_nowait(gzip.compress(b'{"trade": 2}'))
+        await asyncio.sleep(0.01)
+        assert frames == ['{"trade": 1}', b'\x00binary', b'{"trade": 2}']
+        assert connections[0].sent == ['{"op":"subscribe","channel":"trades"}', b'\x01\x02']  # No handshake
+        with pytest.raises(HubConnectionError):
+            await feed.invoke('Anything')
+
+        connections[0].incoming.put_nowait(ConnectionResetError('reset'))
+        for _ in range(100):
+            await asyncio.sleep(0.01)
+            if feed.connected and len(connections) == 2:
+                break
+        assert connections[1].sent == ['{"op":"subscribe","channel":"trades"}'] and feed.reconnects == 1
+        assert feed.unsubscribe('{"op":"subscribe","channel":"trades"}', unsubscribe_payload='{"op":"unsubscribe"}')
+        await asyncio.sleep(0.01)
+        assert feed.subscriptions() == [] and connections[1].sent[-1] == '{"op":"unsubscribe"}'
+        await feed.stop()
+        assert feed.get_stats()['frames_received'] == 3
+
+    @pytest.mark.asyncio
+    async def test_frames_iterator_backpressure(self):
+        """Test a blocking frames() iterator holds the reader instead of dropping frames"""
+        conn = FakeConnection()
+
+        async def connect(url, headers):
+            return conn
+
+        feed = RawWebSocketClient('wss://stream.example.com/ws', reconnect=False, connector=connect)
+        stream = feed.frames(capacity=2)
+        await feed.start()
+        for i in range(6):
+            conn.incoming.put_nowait(f'frame-{i}')
+        await asyncio.sleep(0.02)
+        assert conn.incoming.qsize() > 0  # Reader is waiting on the full channel
+        received = [await asyncio.wait_for(stream.__anext__(), 1) for _ in range(6)]
+        assert received == [f'frame-{i}' for i in range(6)] and stream.get_stats()['dropped'] == 0
+        await feed.stop()
+        assert [frame async for frame in stream] == []
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
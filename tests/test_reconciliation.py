@@ -0,0 +1,149 @@
+"""
+Unit tests for order and position reconciliation
+"""
+
+import pytest
+import os
+import sys
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.position_reconciler import PositionTracker
+from core.reconciliation import OrderTracker, Reconciler
+
+
+class FakeBroker:
+    """Broker REST source returning configurable positions and open orders"""
+
+    def __init__(self):
+        self.positions = []
+        self.orders = []
+        self.fail = False
+
+    async def fetch_positions(self, account_id):
+        if self.fail:
+            raise RuntimeError("API down")
+        return list(self.positions)
+
+    async def fetch_orders(self, account_id):
+        return list(self.orders)
+
+
+class FakeDB:
+    """Journal position view: None until a snapshot is recorded"""
+
+    def __init__(self):
+        self.positions = None
+        self.snapshots = []
+
+    def get_journal_net_positions(self, account_id):
+        return self.positions
+
+    def record_positions(self, account_id, positions):
+        self.snapshots.append(positions)
+        self.positions = {p['symbol']: p['quantity'] for p in positions if p['quantity']}
+        return len(positions)
+
+
+def order(order_id, size=1, limit=21000.0, status=1):
+    return {'id': order_id, 'contractId': 'CON.F.US.MNQ.Z25', 'side': 0, 'size': size, 'limitPrice': limit,
+            'status': status}
+
+
+@pytest.fixture
+def broker():
+    return FakeBroker()
+
+
+def make_reconciler(broker, **kwargs):
+    return Reconciler(PositionTracker(), OrderTracker(), fetch_positions=broker.fetch_positions,
+                      fetch_orders=broker.fetch_orders, confirmations=2, history=3, **kwargs)
+
+
+class TestOrderTracker:
+    """Test the working-order ledger"""
+
+    def test_updates_add_replace_and_remove(self):
+        tracker = OrderTracker()
+        assert tracker.apply_update('1', {'id': 7, 'symbol': 'mnq', 'side': 'BUY', 'size': 2, 'status': 1})
+        tracker.apply_update('1', dict(order(7, size=2), timeInForce='DAY'))  # Gateway update fills in the price
+        assert tracker.get_orders('1')['7'] == {'order_id': '7', 'symbol': 'MNQ', 'side': 'BUY', 'size': 2,
+                                                'limit_price': 21000.0, 'stop_price': None, 'time_in_force': 'DAY',
+                                                'status': 1}
+        tracker.apply_update('1', order(7, size=2))  # Updates without a TIF keep the one already known
+        assert tracker.get_orders('1')['7']['time_in_force'] == 'DAY'
+        assert not tracker.apply_update('1', order(7, status=2))
+        assert tracker.get_orders('1') == {} and tracker.has_account('1')
+
+
+class TestReconciler:
+    """Test comparison, confirmation and auto-correction"""
+
+    @pytest.mark.asyncio
+    async def test_first_run_seeds_and_is_in_sync(self, broker):
+        broker.positions = [{'contractId': 'CON.F.US.MNQ.Z25', 'type': 1, 'size': 2}]
+        broker.orders = [order(1)]
+        reconciler = make_reconciler(broker)
+        report = await reconciler.run('123')
+        assert report.in_sync and report.broker_positions == {'MNQ': 2} and report.local_orders == 1
+        assert reconciler.position_tracker.get_positions('123') == {'MNQ': 2}
+
+    @pytest.mark.asyncio
+    async def test_flags_order_and_position_discrepancies(self, broker):
+        reconciler = make_reconciler(broker)
+        broker.orders = [order(1), order(2)]
+        await reconciler.run('123')
+        reconciler.order_tracker.apply_update('123', order(3))  # Filled/cancelled without the bot noticing
+        broker.orders = [order(1, size=3), order(2), order(4)]  # Resized, untouched, placed manually
+        reconciler.position_tracker.apply_fill('123', 'MNQ', 'BUY', 1)  # Fill the broker does not show
+        report = await reconciler.run('123')
+        found = {(d.kind, d.order_id or d.symbol) for d in report.discrepancies}
+        assert found == {('position', 'MNQ'), ('order_mismatch', '1'), ('missing_order', '3'),
+                         ('unknown_order', '4')}
+        assert all(not d.corrected for d in report.discrepancies)  # Auto-correct is off
+        assert reconciler.get_status()['discrepancies_found'] == 4
+
+    @pytest.mark.asyncio
+    async def test_auto_correct_after_confirmations(self, broker):
+        reports = []
+        reconciler = make_reconciler(broker, auto_correct=True, report_callback=reports.append)
+        await reconciler.run('123')
+        reconciler.position_tracker.apply_fill('123', 'MNQ', 'SELL', 2)
+        reconciler.order_tracker.apply_update('123', order(9))
+        first = await reconciler.run('123')
+        assert first.corrected == 0 and [d.consecutive_runs for d in first.discrepancies] == [1, 1]
+        second = await reconciler.run('123')
+        assert second.corrected == 2 and all(d.corrected for d in second.discrepancies)
+        assert reconciler.position_tracker.get_positions('123') == {}
+        assert reconciler.order_tracker.get_orders('123') == {}
+        assert (await reconciler.run('123')).in_sync
+        assert reports == [first, second] and len(reconciler.reports) == 3  # History is capped
+
+    @pytest.mark.asyncio
+    async def test_database_positions_seeded_and_corrected(self, broker):
+        db = FakeDB()
+        broker.positions = [{'contractId': 'CON.F.US.MNQ.Z25', 'type': 2, 'size': 1}]
+        reconciler = make_reconciler(broker, db=db, auto_correct=True)
+        report = await reconciler.run('123')
+        assert report.db_positions is None and db.positions == {'MNQ': -1}  # Baseline journaled
+        broker.positions = []
+        reconciler.position_tracker.set_positions('123', {})
+        await reconciler.run('123')
+        report = await reconciler.run('123')
+        assert [(d.kind, d.local, d.broker, d.corrected) for d in report.discrepancies] == [
+            ('db_position', -1, 0, True)]
+        assert db.snapshots[-1] == [{'symbol': 'MNQ', 'quantity': 0, 'source': 'reconciliation'}]
+
+    @pytest.mark.asyncio
+    async def test_broker_failure_skips_comparison(self, broker):
+        reconciler = make_reconciler(broker)
+        broker.fail = True
+        report = await reconciler.run('123')
+        assert not report.in_sync and 'API down' in report.error and report.discrepancies == []
+        assert reconciler.get_status()['run_failures'] == 1
+        assert not reconciler.position_tracker.has_account('123')
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,128 @@
+"""
+Unit tests for the Redis cache and pub/sub bridge
+"""
+
+import pytest
+import asyncio
+import json
+import os
+import sys
+from datetime import datetime, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from infrastructure.redis_bridge import RedisBridge
+from core.market_events import Quote, Trade
+from core.plugin_hooks import HookContext, LifecycleEvent
+from core.strategy_engine.strategy import Direction, Signal
+
+T0 = datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc)
+
+
+class FakeRedis:
+    """Records pipelined commands; fails while down is set."""
+
+    def __init__(self):
+        self.values = {}
+        self.published = []
+        self.lists = {}
+        self.down = False
+
+    def pipeline(self, transaction=True):
+        redis, commands = self, []
+
+        class Pipeline:
+            def set(self, key, value, ex=None):
+                commands.append(lambda: redis.values.__setitem__(key, (value, ex)))
+
+            def publish(self, channel, message):
+                commands.append(lambda: redis.published.append((channel, json.loads(message))))
+
+            def lpush(self, key, value):
+                commands.append(lambda: redis.lists.setdefault(key, []).insert(0, value))
+
+            def ltrim(self, key, start, stop):
+                commands.append(lambda: redis.lists.__setitem__(key, redis.lists[key][start:stop + 1]))
+
+            def execute(self):
+                if redis.down:
+                    raise ConnectionError("Connection refused")
+                for command in commands:
+                    command()
+
+        return Pipeline()
+
+    def get(self, key):
+        return self.values.get(key, (None, None))[0]
+
+
+def make_bridge(**kwargs):
+    client = FakeRedis()
+    return RedisBridge(client=client, prefix='bot', quote_ttl=30, **kwargs), client
+
+
+class TestRedisBridge:
+    """Test quote caching, event publishing and buffering while Redis is down."""
+
+    def test_quotes_are_merged_and_coalesced(self):
+        bridge, redis = make_bridge()
+        bridge.on_market_event(Quote('MNQ', T0, bid=21000.0, ask=21000.25))
+        bridge.on_market_event(Quote('MNQ', T0, last=21000.25))  # Partial update keeps bid/ask
+        bridge.on_market_event(Trade('MNQ', T0, 21000.25, 1, 'buy'))  # Not cached
+        assert bridge.flush() == 1
+        value, ttl = redis.values['bot:quote:MNQ']
+        assert ttl == 30 and redis.published == []
+        assert bridge.get_quote('mnq') == {'symbol': 'MNQ', 'timestamp': T0.isoformat(), 'bid': 21000.0,
+                                           'ask': 21000.25, 'last': 21000.25}
+        assert bridge.flush() == 0  # Nothing new
+
+    def test_fills_signals_positions_and_health(self):
+        bridge, redis = make_bridge(recent_events=2)
+        for order_id in (1, 2, 3):
+            bridge.publish_fill(123, {'order_id': order_id, 'symbol': 'MNQ', 'side': 'BUY', 'quantity': 1})
+        bridge.publish_signal(Signal(strategy_id='orb', symbol='MNQ', direction=Direction.LONG, price=21000.0,
+                                     timestamp=T0))
+        bridge.set_positions('123', {'MNQ': 2})
+        bridge.publish_health({'status': 'healthy'})
+        assert bridge.flush() == 5
+        channels = [channel for channel, _ in redis.published]
+        assert channels == ['bot:fills'] * 3 + ['bot:signals', 'bot:positions', 'bot:health']
+        assert redis.published[0][1]['type'] == 'fill' and redis.published[0][1]['account_id'] == '123'
+        assert redis.published[3][1]['strategy_id'] == 'orb' and redis.published[3][1]['timestamp'] == T0.isoformat()
+        assert [json.loads(m)['order_id'] for m in redis.lists['bot:fills:recent']] == [3, 2]
+        assert bridge.get_positions(123)['positions'] == {'MNQ': 2}
+        assert bridge.get_health()['status'] == 'healthy' and redis.values['bot:health'][1] == 45
+
+    def test_buffers_while_down_and_drops_oldest(self):
+        bridge, redis = make_bridge(buffer_size=3)
+        redis.down = True
+        bridge.publish_fill(1, {'order_id': 1})
+        bridge.publish_fill(1, {'order_id': 2})
+        assert bridge.flush() == 0 and not bridge.connected
+        bridge.publish_fill(1, {'order_id': 3})
+        bridge.publish_fill(1, {'order_id': 4})  # Buffer full: order 1 goes
+        assert bridge.get_stats()['events_dropped'] == 1 and bridge.get_stats()['write_errors'] == 1
+        redis.down = False
+        bridge.start()
+        bridge.stop()  # Flushes what is left
+        assert [m['order_id'] for _, m in redis.published] == [2, 3, 4]
+        assert bridge.connected and bridge.get_stats()['buffered'] == 0
+
+    def test_order_filled_hook(self):
+        bridge, redis = make_bridge()
+        context = HookContext(event=LifecycleEvent.ORDER_FILLED, account_id='123',
+                              data={'fill': {'order_id': 9, 'symbol': 'MNQ', 'context': {'book': 'big'}}})
+        asyncio.run(bridge.on_order_filled(context))
+        bridge.flush()
+        message = redis.published[0][1]
+        assert message['order_id'] == 9 and 'context' not in message
+
+    def test_requires_url_without_client(self, monkeypatch):
+        monkeypatch.delenv('REDIS_URL', raising=False)
+        with pytest.raises(ValueError):
+            RedisBridge()
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,101 @@
+"""
+Unit tests for renko brick aggregation
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone, timedelta
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar
+from core.renko_aggregator import RenkoAggregator
+
+
+START = datetime(2025, 11, 19, 10, 0, 0, tzinfo=timezone.utc)
+
+
+def _feed(renko, prices, symbol='MNQ'):
+    bricks = []
+    for i, price in enumerate(prices):
+        bricks.extend(renko.add_tick(symbol, price, volume=1, timestamp=START + timedelta(seconds=i)))
+    return bricks
+
+
+class TestRenkoAggregator:
+    """Test RenkoAggregator"""
+
+    def test_requires_brick_size(self):
+        """Test a brick size source is required"""
+        with pytest.raises(ValueError):
+            RenkoAggregator()
+        with pytest.raises(ValueError):
+            RenkoAggregator(brick_ticks=4)
+
+    def test_brick_ticks(self):
+        """Test brick size from ticks x tick size"""
+        renko = RenkoAggregator(brick_ticks=4, tick_size=0.25)
+        assert renko.get_brick_size('MNQ') == 1.0
+
+    def test_up_bricks_and_multi_brick_move(self):
+        """Test a large move completes several bricks at once"""
+        renko = RenkoAggregator(brick_size=1.0)
+        bricks = _feed(renko, [100.0, 100.5, 101.0, 103.2])
+
+        assert [(b.open, b.close, b.direction) for b in bricks] == [
+            (100.0, 101.0, 1), (101.0, 102.0, 1), (102.0, 103.0, 1)
+        ]
+        assert renko.bricks_completed == 3
+
+    def test_reversal_requires_two_bricks(self):
+        """Test a reversal must clear the opposite side of the last brick"""
+        renko = RenkoAggregator(brick_size=1.0)
+        _feed(renko, [100.0, 101.0])  # up brick 100 -> 101
+        assert renko.add_tick('MNQ', 99.5) == []
+        bricks = renko.add_tick('MNQ', 99.0)
+
+        assert [(b.open, b.close, b.direction) for b in bricks] == [(100.0, 99.0, -1)]
+
+    def test_wick_tracking(self):
+        """Test up bricks record the pullback below their open"""
+        renko = RenkoAggregator(brick_size=1.0)
+        _feed(renko, [100.0, 101.0])
+        bricks = _feed(renko, [100.4, 101.5, 102.0])
+
+        assert len(bricks) == 1
+        assert bricks[0].low == 100.4
+        assert bricks[0].high == 102.0
+        assert bricks[0].volume == 3
+
+    def test_callback_and_history(self):
+        """Test bricks are emitted to the callback and kept per symbol"""
+        seen = []
+        renko = RenkoAggregator(brick_size=2.0, brick_callback=seen.append)
+        _feed(renko, [50.0, 52.0, 54.0], symbol='mes')
+
+        assert [b.close for b in seen] == [52.0, 54.0]
+        assert [b.close for b in renko.get_bricks('MES', count=1)] == [54.0]
+        assert seen[0].to_dict()['timestamp'] == (START + timedelta(seconds=1)).isoformat()
+
+    def test_atr_brick_size_from_bars(self):
+        """Test ATR sizing waits for warmup and then builds bricks"""
+        renko = RenkoAggregator(atr_period=2, atr_multiplier=1.0)
+        bars = [
+            Bar('MNQ', '5m', START, 100.0, 102.0, 100.0, 101.0),
+            Bar('MNQ', '5m', START + timedelta(minutes=5), 101.0, 103.0, 101.0, 103.0),
+            Bar('MNQ', '5m', START + timedelta(minutes=10), 103.0, 106.0, 103.0, 106.0),
+        ]
+        assert renko.add_bar(bars[0]) == []
+        assert renko.get_brick_size('MNQ') is None
+        assert [b.close for b in renko.add_bar(bars[1])] == [103.0]
+        assert renko.get_brick_size('MNQ') == pytest.approx(2.0)
+
+        bricks = renko.add_bar(bars[2])
+        assert renko.get_brick_size('MNQ') == pytest.approx(2.5)
+        assert [(b.open, b.close) for b in bricks] == [(103.0, 105.5)]
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,92 @@
+"""
+Unit tests for the tick/market replay engine
+"""
+
+import pytest
+import asyncio
+import json
+import os
+import sys
+import time
+from datetime import datetime, timezone, timedelta
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar, BarAggregator
+from core.market_events import Trade, BarClosed
+from core.replay_engine import ReplayEngine, load_replay_events
+
+
+START = datetime(2025, 11, 19, 14, 30, 0, tzinfo=timezone.utc)
+
+
+def _trades(count, spacing=timedelta(seconds=30)):
+    return [Trade('MNQ', START + spacing * i, 15000.0 + i, 1, 'buy') for i in range(count)]
+
+
+class TestReplayEngine:
+    """Test replay sources, speed and controls"""
+
+    @pytest.mark.asyncio
+    async def test_max_speed_replay_builds_bars_and_skips_recorded_bars(self, tmp_path):
+        """Recorded trades rebuild bars; recorded BarClosed events are not replayed"""
+        path = tmp_path / 'session.jsonl'
+        events = _trades(10)
+        with open(path, 'w') as f:
+            for event in reversed(events):  # Out of order on disk
+                f.write(json.dumps(event.to_dict()) + '\n')
+            recorded_bar = BarClosed.from_bar(Bar('MNQ', '1m', START, 1.0, 1.0, 1.0, 1.0, 1))
+            f.write(json.dumps(recorded_bar.to_dict()) + '\n')
+        aggregator = BarAggregator(default_timeframes=['1m'])
+        seen = []
+        engine = ReplayEngine(path, bar_aggregator=aggregator, speed=0,
+                              listeners=[seen.append, lambda e: 1 / 0])
+
+        assert await engine.run() == 10
+        assert [e.price for e in seen] == [e.price for e in events]
+        assert engine.get_status()['finished'] is True
+        assert engine.listener_errors == 10
+        bars = aggregator.get_bar_history('MNQ', '1m')
+        assert len(bars) == 5
+        assert (bars[0].open, bars[0].close) == (15000.0, 15001.0)
+
+    @pytest.mark.asyncio
+    async def test_pause_step_seek_and_speed(self):
+        """Paused replay only advances on step; seek jumps by timestamp"""
+        seen = []
+        engine = ReplayEngine(_trades(20, timedelta(seconds=1)), listeners=[seen.append], speed=1.0)
+        engine.pause()
+        task = engine.start()
+        await asyncio.sleep(0.05)
+        assert seen == []
+
+        engine.step(2)
+        await asyncio.sleep(0.05)
+        assert [e.price for e in seen] == [15000.0, 15001.0]
+
+        assert engine.seek(START + timedelta(seconds=15)) == 15
+        engine.set_speed(1000)
+        started = time.monotonic()
+        engine.resume()
+        await asyncio.wait_for(task, 2)
+        assert time.monotonic() - started < 1.0
+        assert [e.price for e in seen[2:]] == [15015.0, 15016.0, 15017.0, 15018.0, 15019.0]
+
+    def test_csv_source_requires_symbol_and_filters_window(self, tmp_path):
+        """CSV ticks load as Trade events within the requested window"""
+        path = tmp_path / 'ticks.csv'
+        path.write_text('timestamp,price,size,side\n'
+                        + ''.join(f"{(START + timedelta(seconds=i)).isoformat()},{15000 + i},1,buy\n"
+                                  for i in range(5)))
+        with pytest.raises(ValueError):
+            load_replay_events(path)
+
+        events = load_replay_events(path, symbol='MNQ', start=START + timedelta(seconds=1),
+                                    end=START + timedelta(seconds=4))
+        assert [e.price for e in events] == [15001.0, 15002.0, 15003.0]
+        assert all(isinstance(e, Trade) and e.symbol == 'MNQ' for e in events)
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,90 @@
+"""
+Unit tests for replay fixture extraction and loading
+"""
+
+import pytest
+import json
+import os
+import sys
+from datetime import datetime, timezone, timedelta
+from unittest.mock import MagicMock
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import BarAggregator
+from core.market_events import Trade, Quote, BarClosed
+from core.replay_fixtures import (
+    extract_fixture, extract_window_around, extract_bars_fixture, load_fixture,
+)
+
+
+START = datetime(2025, 11, 19, 14, 30, 0, tzinfo=timezone.utc)
+
+
+@pytest.fixture
+def recording(tmp_path):
+    """A 20-minute JSONL recording with one trade per minute for two symbols"""
+    path = tmp_path / 'session.jsonl'
+    with open(path, 'w') as f:
+        for i in range(20):
+            ts = START + timedelta(minutes=i)
+            f.write(json.dumps(Trade('MNQ', ts, 15000.0 + i, 1, 'buy').to_dict()) + '\n')
+            f.write(json.dumps(Quote('MES', ts, bid=5000.0, ask=5000.25, last=5000.25).to_dict()) + '\n')
+        f.write('{not json\n')
+    return path
+
+
+class TestReplayFixtures:
+    """Test fixture generation and replay"""
+
+    def test_extract_window_around_incident(self, recording, tmp_path):
+        """Test a ±2.5 minute window keeps only that slice of the chosen symbol"""
+        out = extract_window_around(recording, START + timedelta(minutes=10), tmp_path / 'bug.json.gz',
+                                    symbols=['mnq'], metadata={'ticket': 'BUG-1'})
+        fixture = load_fixture(out)
+
+        assert len(fixture) == 5
+        assert all(isinstance(e, Trade) for e in fixture.events)
+        assert fixture.events[0].timestamp == START + timedelta(minutes=8)
+        assert fixture.metadata['ticket'] == 'BUG-1'
+        assert fixture.symbols == ['MNQ']
+
+    def test_replay_into_bar_aggregator(self, recording, tmp_path):
+        """Test replaying a fixture rebuilds bars from recorded timestamps"""
+        out = extract_fixture(recording, START, START + timedelta(minutes=3), tmp_path / 'slice.json')
+        fixture = load_fixture(out)
+        aggregator = BarAggregator(default_timeframes=['1m'])
+
+        assert fixture.replay_into(aggregator) == 8
+        assert [b.close for b in aggregator.get_bar_history('MNQ', '1m')] == [15000.0, 15001.0, 15002.0]
+
+    def test_rejects_inverted_window(self, recording, tmp_path):
+        """Test end before start is rejected"""
+        with pytest.raises(ValueError):
+            extract_fixture(recording, START, START - timedelta(minutes=1), tmp_path / 'x.json')
+
+    def test_unsupported_version(self, tmp_path):
+        """Test fixtures from an unknown format version are rejected"""
+        path = tmp_path / 'future.json'
+        path.write_text(json.dumps({'format_version': 99}))
+        with pytest.raises(ValueError, match="Unsupported replay fixture version"):
+            load_fixture(path)
+
+    def test_extract_bars_from_database(self, tmp_path):
+        """Test cached database bars become BarClosed events"""
+        db = MagicMock()
+        db.get_cached_bars.return_value = [
+            {'timestamp': (START + timedelta(minutes=i)).isoformat(), 'open': 1.0, 'high': 2.0,
+             'low': 0.5, 'close': 1.5, 'volume': 10}
+            for i in range(3)
+        ]
+        out = extract_bars_fixture(db, 'MNQ', '1m', START, START + timedelta(minutes=5), tmp_path / 'bars.json')
+        fixture = load_fixture(out)
+
+        assert [type(e) for e in fixture.events] == [BarClosed] * 3
+        assert fixture.events[0].bar.timeframe == '1m'
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,115 @@
+"""
+Unit tests for per-operation request timeouts and deadlines
+"""
+
+import pytest
+import os
+import sys
+import threading
+import time
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import requests
+from requests.adapters import HTTPAdapter
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from infrastructure.request_timeouts import (
+    Deadline, DeadlineTimeout, RequestTimeouts, current_deadline, deadline_scope,
+)
+from infrastructure.retry_budget import BudgetedRetry, RetryBudget
+
+
+class SlowUnavailableHandler(BaseHTTPRequestHandler):
+    """/slow answers after 1s, anything else is a 503"""
+    hits = 0
+
+    def do_GET(self):
+        type(self).hits += 1
+        if self.path == '/slow':
+            time.sleep(1.0)
+        self.send_response(200 if self.path == '/slow' else 503)
+        self.send_header('Content-Length', '0')
+        self.end_headers()
+
+    def log_message(self, *args):
+        pass
+
+
+@pytest.fixture
+def server():
+    httpd = ThreadingHTTPServer(('127.0.0.1', 0), SlowUnavailableHandler)
+    SlowUnavailableHandler.hits = 0
+    thread = threading.Thread(target=httpd.serve_forever, daemon=True)
+    thread.start()
+    yield f"http://127.0.0.1:{httpd.server_address[1]}"
+    httpd.shutdown()
+    httpd.server_close()
+
+
+def make_session(backoff_factor=0.3):
+    session = requests.Session()
+    retry = BudgetedRetry(budget=RetryBudget(capacity=10, refill_per_sec=0), total=3,
+                          backoff_factor=backoff_factor, status_forcelist=[503], allowed_methods=["GET"])
+    session.mount('http://', HTTPAdapter(max_retries=retry))
+    return session
+
+
+class TestRequestTimeouts:
+    """Test operation timeouts, deadline scopes and deadline-bounded retries"""
+
+    def test_operation_timeouts(self, monkeypatch):
+        monkeypatch.setenv('API_TIMEOUT_CANCEL', '2.5')
+        monkeypatch.setenv('API_TIMEOUT', '30')
+        timeouts = RequestTimeouts(history=90)
+        assert timeouts.timeout_for('/api/Order/cancel') == 2.5
+        assert timeouts.timeout_for('/api/Order/place') == 10.0
+        assert timeouts.timeout_for('/api/History/retrieveBars') == 90
+        assert timeouts.timeout_for('/api/Account/search') == 30.0
+        monkeypatch.setenv('API_TIMEOUT', '45')  # Default is read per request
+        assert timeouts.timeout_for('/api/Position/searchOpen') == 45.0
+        assert timeouts.request_timeout('/api/Order/cancel') == 2.5
+        timeouts.record_timeout('/api/Order/cancel')
+        assert timeouts.get_stats()['timed_out'] == {'cancel': 1}
+        with pytest.raises(ValueError):
+            RequestTimeouts(cancel=0)
+
+    def test_deadline_scopes_nest_and_cap_attempts(self):
+        assert current_deadline() is None
+        with deadline_scope(5.0) as outer:
+            with deadline_scope(60.0) as inner:
+                assert inner is outer  # Looser deadline has no effect
+            with deadline_scope(0.5) as tight:
+                assert current_deadline() is tight and tight.remaining() <= 0.5
+            assert current_deadline() is outer
+        assert current_deadline() is None
+        timeout = RequestTimeouts().request_timeout('/api/Order/cancel', deadline=Deadline(1.0))
+        assert isinstance(timeout, DeadlineTimeout) and timeout.clone().read_timeout <= 1.0
+        assert DeadlineTimeout(5.0, Deadline(0)).clone().connect_timeout == 0.001
+        assert Deadline(-1).expired and Deadline.coerce(None) is None
+
+    def test_retries_stop_at_deadline(self, server):
+        session = make_session(backoff_factor=0.4)
+        deadline = Deadline(0.5)
+        started = time.monotonic()
+        with deadline_scope(deadline):
+            with pytest.raises(requests.exceptions.RetryError, match='deadline exceeded'):
+                session.get(f"{server}/unavailable", timeout=DeadlineTimeout(5.0, deadline))
+        # First retry is immediate, the second would sleep 0.4s then 0.8s: refused instead
+        assert time.monotonic() - started < 0.5 and deadline.retries_refused == 1
+        assert SlowUnavailableHandler.hits == 2
+        with pytest.raises(requests.exceptions.RetryError, match='too many 503'):
+            make_session(backoff_factor=0).get(f"{server}/unavailable", timeout=5.0)  # No deadline: all retries
+
+    def test_slow_response_is_cut_at_deadline(self, server):
+        deadline = Deadline(0.3)
+        started = time.monotonic()
+        with deadline_scope(deadline):
+            with pytest.raises(requests.exceptions.ConnectionError):
+                make_session().get(f"{server}/slow", timeout=DeadlineTimeout(30.0, deadline))
+        assert time.monotonic() - started < 0.9 and deadline.expired
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
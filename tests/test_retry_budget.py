@@ -0,0 +1,101 @@
+"""
+Unit tests for the session-wide retry budget
+"""
+
+import pytest
+import os
+import sys
+import threading
+import time
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import requests
+from requests.adapters import HTTPAdapter
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from infrastructure.retry_budget import RetryBudget, BudgetedRetry
+
+
+class _Always503(BaseHTTPRequestHandler):
+    hits = 0
+
+    def do_GET(self):
+        type(self).hits += 1
+        self.send_response(503)
+        self.send_header('Content-Length', '0')
+        self.end_headers()
+
+    def log_message(self, *args):
+        pass
+
+
+@pytest.fixture
+def failing_server():
+    _Always503.hits = 0
+    server = HTTPServer(('127.0.0.1', 0), _Always503)
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    yield f"http://127.0.0.1:{server.server_port}/", _Always503
+    server.shutdown()
+
+
+def _session(budget):
+    session = requests.Session()
+    retry = BudgetedRetry(budget=budget, total=3, backoff_factor=0, status_forcelist=[503],
+                          allowed_methods=["GET"])
+    session.mount("http://", HTTPAdapter(max_retries=retry))
+    return session
+
+
+class TestRetryBudget:
+    """Test token bucket accounting."""
+
+    def test_sheds_when_empty_and_refills(self):
+        budget = RetryBudget(capacity=2, refill_per_sec=0)
+        assert budget.try_acquire("http")
+        assert budget.try_acquire("order_place")
+        assert not budget.try_acquire("http")
+        stats = budget.get_stats()
+        assert stats["granted"] == {"http": 1, "order_place": 1}
+        assert stats["denied"] == {"http": 1}
+        assert stats["exhausted"] is True
+
+        budget.refill_per_sec = 20.0
+        time.sleep(0.1)
+        assert budget.try_acquire("http")
+        assert budget.get_stats()["exhausted"] is False
+
+    def test_refill_capped_at_capacity(self):
+        budget = RetryBudget(capacity=3, refill_per_sec=1000)
+        assert budget.tokens == 3
+
+
+class TestBudgetedRetry:
+    """Test HTTP retries drawing from the shared budget."""
+
+    def test_retries_consume_budget(self, failing_server):
+        url, handler = failing_server
+        budget = RetryBudget(capacity=10, refill_per_sec=0)
+        with pytest.raises(requests.exceptions.RetryError):
+            _session(budget).get(url, timeout=5)
+        assert handler.hits == 4  # First attempt + 3 retries
+        assert budget.get_stats()["total_granted"] == 3
+
+    def test_empty_budget_sheds_retries_across_sessions(self, failing_server):
+        url, handler = failing_server
+        budget = RetryBudget(capacity=4, refill_per_sec=0)
+        with pytest.raises(requests.exceptions.RetryError):
+            _session(budget).get(url, timeout=5)
+        with pytest.raises(requests.exceptions.RetryError):
+            _session(budget).get(url, timeout=5)
+        # 4 + 2: the second session got one retry before the shared budget ran out
+        assert handler.hits == 6
+        stats = budget.get_stats()
+        assert stats["total_granted"] == 4
+        assert stats["total_denied"] == 1
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
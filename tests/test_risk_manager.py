@@ -0,0 +1,189 @@
+"""
+Unit tests for the strategy engine risk manager (daily loss limit kill switch)
+"""
+
+import pytest
+import asyncio
+import os
+import sys
+from types import SimpleNamespace
+from datetime import datetime, timedelta, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.market_events import Quote, Trade
+from core.strategy_engine import CommissionModel, Direction, RiskManager, Signal, StrategyEngine
+
+# Tuesday 2025-11-04 10:00 CT, inside the MNQ session for trading date 2025-11-04
+T0 = datetime(2025, 11, 4, 16, 0, tzinfo=timezone.utc)
+
+
+class FakeExecutor:
+    """Records kill switch calls; cancel fails a configurable number of times"""
+
+    def __init__(self, cancel_failures=0):
+        self.calls = []
+        self.cancel_failures = cancel_failures
+
+    async def cancel_orders(self, account_id):
+        self.calls.append(('cancel', account_id))
+        if self.cancel_failures:
+            self.cancel_failures -= 1
+            raise RuntimeError("API down")
+        return {"success": True}
+
+    async def flatten(self, account_id):
+        self.calls.append(('flatten', account_id))
+        return {"success": True}
+
+
+def make_signal(direction, timestamp=T0):
+    return Signal(strategy_id='s', symbol='MNQ', direction=direction, price=21000.0, timestamp=timestamp)
+
+
+class TestRiskManagerPnl:
+    """Test realized/unrealized session P&L tracking"""
+
+    def test_fills_and_marks(self):
+        """Test averaging, partial closes, reversals, commissions and marking to market"""
+        risk = RiskManager(daily_loss_limit=0)
+        risk.on_fill('1', 'CON.F.US.MNQ.Z25', 'BUY', 1, 21000.0, timestamp=T0)
+        risk.on_fill('1', 'MNQ', 0, 1, 21010.0, commission=0.74, timestamp=T0)
+        state = risk.account('1')
+        assert state.positions['MNQ'].quantity == 2 and state.positions['MNQ'].avg_price == 21005.0
+        risk.on_event(Trade(symbol='MNQ', timestamp=T0, price=21015.0))
+        assert state.unrealized_pnl == pytest.approx(40.0)  # 10 points * 2 contracts * $2
+        risk.on_fill('1', 'MNQ', 'SELL', 3, 21020.0, commission=1.11, timestamp=T0)  # Close 2, open 1 short
+        assert state.realized_pnl == pytest.approx(60.0 - 1.85)
+        assert state.positions['MNQ'].quantity == -1 and state.positions['MNQ'].avg_price == 21020.0
+        risk.on_event(Quote(symbol='MNQ', timestamp=T0, bid=21029.75, ask=21030.25))
+        assert state.unrealized_pnl == pytest.approx(-20.0) and state.daily_pnl == pytest.approx(38.15)
+        risk.on_fill('1', 'MNQ', 'BUY', 1, 21030.0, timestamp=T0)
+        assert state.positions == {} and state.unrealized_pnl == 0
+        assert state.realized_pnl == pytest.approx(38.15) and not risk.is_halted()
+
+    def test_fills_without_commission_use_fee_model(self):
+        """Test the fee model charges fills reported without a commission"""
+        risk = RiskManager(daily_loss_limit=0, fees=CommissionModel({'MNQ': 0.25}, {'MNQ': 0.37}, 0.0, 0.0))
+        risk.on_fill('1', 'MNQ', 'BUY', 2, 21000.0, timestamp=T0)
+        risk.on_fill('1', 'MNQ', 'SELL', 2, 21001.0, commission=0.0, timestamp=T0)  # Reported: not modeled
+        assert risk.account('1').realized_pnl == pytest.approx(4.0 - 1.24)
+
+
+class TestRiskManagerKillSwitch:
+    """Test breach handling, signal blocking and the session reset"""
+
+    @pytest.mark.asyncio
+    async def test_breach_cancels_flattens_and_blocks_until_next_session(self):
+        """Test an unrealized loss trips the switch once, entries are blocked and exits pass"""
+        executor = FakeExecutor(cancel_failures=1)
+        alerts = []
+        risk = RiskManager(daily_loss_limit=500, cancel_orders=executor.cancel_orders, flatten=executor.flatten,
+                           alert_callback=alerts.append, retry_delay=0)
+        engine = StrategyEngine(max_errors=0)
+        risk.attach(engine)
+        published = []
+        engine.on_signal(published.append)
+
+        risk.on_fill('7', 'MNQ', 'BUY', 5, 21000.0, timestamp=T0)
+        risk.on_price('MNQ', 20960.0, T0)  # -400
+        assert not risk.is_halted('7') and risk.account('7').remaining == pytest.approx(100.0)
+        risk.on_price('MNQ', 20950.0, T0 + timedelta(minutes=1))  # -500: limit reached
+        risk.on_price('MNQ', 20940.0, T0 + timedelta(minutes=2))
+        assert risk.is_halted('7') and risk.breaches == 1
+        await asyncio.sleep(0.05)
+        assert executor.calls == [('cancel', '7'), ('cancel', '7'), ('flatten', '7')]
+        assert risk.account('7').kill_switch['cancel_orders'] == 'ok' and alerts == [risk.account('7')]
+
+        engine._emit(make_signal(Direction.LONG, T0 + timedelta(minutes=3)))
+        engine._emit(make_signal(Direction.FLAT, T0 + timedelta(minutes=3)))
+        assert [s.direction for s in published] == [Direction.FLAT] and engine.signals_blocked == 1
+
+        # Next trading date (session reopens 17:00 CT): P&L starts over and entries flow again
+        next_session = T0 + timedelta(hours=8)
+        risk.on_fill('7', 'MNQ', 'SELL', 5, 20940.0, timestamp=T0 + timedelta(minutes=5))
+        engine._emit(make_signal(Direction.SHORT, next_session))
+        assert not risk.is_halted('7') and published[-1].direction is Direction.SHORT
+        assert risk.account('7').daily_pnl == 0 and str(risk.account('7').session_date) == '2025-11-05'
+
+    def test_limits_manual_halt_and_failing_gate(self):
+        """Test per-account limits, manual halt/reset without a loop and fail-closed gates"""
+        calls = []
+        risk = RiskManager(daily_loss_limit=1000, flatten=lambda account_id: calls.append(account_id),
+                           account_resolver=lambda signal: signal.strategy_id, retry_delay=0)
+        risk.set_limit('a', 100)
+        risk.on_fill('a', 'MES', 'SELL', 1, 6000.0, timestamp=T0)
+        risk.on_fill('b', 'MES', 'SELL', 1, 6000.0, timestamp=T0)
+        risk.on_price('MES', 6020.0, T0)  # -$100 on each
+        assert risk.is_halted('a') and not risk.is_halted('b')
+        assert not risk.allows(Signal('a', 'MES', Direction.LONG, 6020.0, T0))
+        assert risk.allows(Signal('b', 'MES', Direction.LONG, 6020.0, T0))
+        assert not risk.halt('a') and risk.reset('a') and not risk.is_halted('a')
+        status = risk.get_status()
+        assert status['breaches'] == 1 and status['accounts']['a']['daily_pnl'] == -100.0
+
+        engine = StrategyEngine(max_errors=0)
+        engine.add_gate(lambda signal: 1 / 0, name='broken')
+        assert not engine._emit(make_signal(Direction.LONG)) and engine.get_stats()['gates'] == ['broken']
+
+
+class TestTrailingDrawdown:
+    """Test high-water mark tracking, proximity warnings and the permanent drawdown halt"""
+
+    def test_eod_trailing_warnings_and_breach(self):
+        """Test the threshold trails end-of-session balance, warns at each level once and halts past sessions"""
+        risk = RiskManager(daily_loss_limit=0, max_loss_limit=2000, warning_levels=[0.8, 0.9], retry_delay=0)
+        events = []
+        risk.on_risk_event(events.append)
+        risk.set_balance('1', 50000)
+        risk.on_fill('1', 'MES', 'BUY', 2, 6000.0, timestamp=T0)
+        risk.on_fill('1', 'MES', 'SELL', 2, 6100.0, timestamp=T0)  # +1000
+        state = risk.account('1')
+        assert state.high_water_mark == 50000 and state.drawdown_threshold() == 48000
+
+        day2 = T0 + timedelta(days=1)
+        risk.on_fill('1', 'MES', 'BUY', 2, 6100.0, timestamp=day2)
+        assert state.session_balance == 51000 and state.high_water_mark == 51000
+        assert state.drawdown_threshold() == 49000 and state.daily_pnl == 0
+        for price in (6000.0, 5940.0, 5930.0, 5920.0, 5915.0):
+            risk.on_price('MES', price, day2)
+        assert [(e.kind, e.limit, e.level) for e in events] == [('warning', 'trailing_drawdown', 0.8),
+                                                                ('warning', 'trailing_drawdown', 0.9)]
+        assert not risk.is_halted('1') and state.equity == 49150
+        risk.on_price('MES', 5900.0, day2)
+        assert events[-1].kind == 'breach' and risk.is_halted('1') and state.halt_limit == 'trailing_drawdown'
+
+        # Unlike a daily loss halt, the account stays blocked in the next session
+        risk.on_fill('1', 'MES', 'SELL', 2, 5900.0, timestamp=day2)
+        risk.roll(day2 + timedelta(days=1))
+        assert risk.is_halted('1') and state.session_balance == 49000 and len(events) == 3
+        assert risk.reset('1') and not risk.is_halted('1')
+
+    def test_intraday_lock_and_load_account(self):
+        """Test intraday high-water marks, the starting balance lock and loading AccountTracker state"""
+        risk = RiskManager(daily_loss_limit=0, max_loss_limit=2000, trailing='intraday', warning_levels=[])
+        risk.set_balance('1', 50000)
+        risk.on_fill('1', 'MES', 'BUY', 2, 6000.0, timestamp=T0)
+        risk.on_price('MES', 6150.0, T0)  # Equity 51500
+        risk.on_price('MES', 6020.0, T0)
+        state = risk.account('1')
+        assert state.high_water_mark == 51500 and state.drawdown_threshold() == 49500
+        risk.on_price('MES', 5949.0, T0)  # Equity 49490: below the intraday-trailed threshold
+        assert risk.is_halted('1')
+
+        locked = risk.set_balance('2', 53000, high_water_mark=53500, starting_balance=50000)
+        assert locked.drawdown_threshold() == 50000 and locked.drawdown_threshold(lock=False) == 51500
+        with pytest.raises(ValueError):
+            RiskManager(trailing='weekly')
+
+        tracker_state = SimpleNamespace(account_id='3', current_balance=50400.0, starting_balance=50000.0,
+                                        highest_EOD_balance=51000.0, maximum_loss_limit=2000.0,
+                                        daily_loss_limit=1000.0)
+        loaded = risk.load_account(tracker_state)
+        assert loaded.session_balance == 50400 and loaded.drawdown_threshold() == 49000
+        assert loaded.daily_loss_limit == 1000 and risk.get_status()['accounts']['3']['equity'] == 50400
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,105 @@
+"""
+Unit tests for versioned schema migrations in the database module
+"""
+
+import pytest
+import os
+import sys
+from contextlib import contextmanager
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+pytest.importorskip('psycopg2')
+
+from infrastructure.database import DatabaseManager, load_migrations
+
+
+def make_db(applied):
+    """DatabaseManager on a fake connection whose schema_migrations holds applied (version -> checksum)."""
+    db = DatabaseManager.__new__(DatabaseManager)
+    db.executed = []
+    db.commits = 0
+
+    class Cursor:
+        def __enter__(self):
+            return self
+
+        def __exit__(self, *exc):
+            return False
+
+        def execute(self, query, params=None):
+            if 'fail_here' in query:
+                raise RuntimeError('syntax error')
+            db.executed.append((' '.join(query.split()), params))
+
+        def fetchall(self):
+            return list(applied.items())
+
+    class Conn:
+        def cursor(self, cursor_factory=None):
+            return Cursor()
+
+        def commit(self):
+            db.commits += 1
+
+        def rollback(self):
+            db.executed.append(('ROLLBACK', None))
+
+    @contextmanager
+    def get_connection(read_only=False):
+        yield Conn()
+
+    db.get_connection = get_connection
+    return db
+
+
+class TestSchemaMigrations:
+    """Test migration loading, ordering, checksums and locking."""
+
+    def test_shipped_migrations_cover_the_schema(self):
+        migrations = load_migrations()
+        assert [m.version for m in migrations] == list(range(1, len(migrations) + 1))
+        schema = '\n'.join(m.sql for m in migrations)
+        for table in ('historical_bars', 'trade_history', 'journal_signals', 'journal_fills', 'notifications'):
+            assert f'CREATE TABLE IF NOT EXISTS {table}' in schema
+
+    def test_load_rejects_misnamed_and_duplicate_files(self, tmp_path):
+        (tmp_path / '0002_b.sql').write_text('SELECT 2;')
+        (tmp_path / '0001_a.sql').write_text('SELECT 1;')
+        assert [m.filename for m in load_migrations(tmp_path)] == ['0001_a.sql', '0002_b.sql']
+        (tmp_path / '2_again.sql').write_text('SELECT 2;')
+        with pytest.raises(ValueError, match='Duplicate'):
+            load_migrations(tmp_path)
+        (tmp_path / '2_again.sql').unlink()
+        (tmp_path / 'notes.sql').write_text('')
+        with pytest.raises(ValueError, match='NNNN_name'):
+            load_migrations(tmp_path)
+
+    def test_migrate_applies_pending_under_lock(self, tmp_path):
+        for name, text in (('0001_bars.sql', 'CREATE TABLE bars ();'), ('0002_trades.sql', 'CREATE TABLE trades ();'),
+                           ('0003_signals.sql', 'CREATE TABLE signals ();')):
+            (tmp_path / name).write_text(text)
+        first = load_migrations(tmp_path)[0]
+        db = make_db({1: first.checksum})
+        assert db.migrate(target=2, directory=tmp_path) == [2]
+        queries = [q for q, _ in db.executed]
+        assert queries[0] == 'SELECT pg_advisory_lock(%s)' and queries[-1] == 'SELECT pg_advisory_unlock(%s)'
+        assert 'CREATE TABLE trades ();' in queries and 'CREATE TABLE bars ();' not in queries
+        assert 'CREATE TABLE signals ();' not in queries
+        insert = next(params for q, params in db.executed if q.startswith('INSERT INTO schema_migrations'))
+        assert insert[:2] == (2, 'trades') and len(insert[2]) == 64
+
+    def test_edited_or_failing_migration_stops(self, tmp_path):
+        (tmp_path / '0001_bars.sql').write_text('CREATE TABLE bars ();')
+        with pytest.raises(RuntimeError, match='modified after it was applied'):
+            make_db({1: '0' * 64}).migrate(directory=tmp_path)
+        (tmp_path / '0002_broken.sql').write_text('fail_here')
+        db = make_db({1: load_migrations(tmp_path)[0].checksum})
+        with pytest.raises(RuntimeError, match='0002_broken.sql failed'):
+            db.migrate(directory=tmp_path)
+        assert ('ROLLBACK', None) in db.executed and db.executed[-1][0] == 'SELECT pg_advisory_unlock(%s)'
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
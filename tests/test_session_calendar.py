@@ -0,0 +1,62 @@
+"""
+Unit tests for the futures session calendar
+"""
+
+import pytest
+import os
+import sys
+from datetime import date, datetime, time, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.session_calendar import ProductSessions, SessionCalendar, SessionState
+
+
+def utc(*args):
+    return datetime(*args, tzinfo=timezone.utc)
+
+
+class TestSessionCalendar:
+    """Test session states, bounds and per-product configuration."""
+
+    def test_states_through_the_week(self):
+        calendar = SessionCalendar()
+        # November 2025 is CST (UTC-6)
+        assert calendar.state('MNQ', utc(2025, 11, 19, 14, 30)) is SessionState.RTH  # Wed 08:30 CT
+        assert calendar.state('MNQ', utc(2025, 11, 19, 14, 29)) is SessionState.ETH
+        assert calendar.state('MNQ', utc(2025, 11, 19, 21, 0)) is SessionState.ETH    # 15:00 CT
+        assert calendar.state('MNQ', utc(2025, 11, 19, 22, 30)) is SessionState.MAINTENANCE
+        assert calendar.state('MNQ', utc(2025, 11, 21, 22, 30)) is SessionState.WEEKEND  # Fri after close
+        assert calendar.state('MNQ', utc(2025, 11, 22, 15, 0)) is SessionState.WEEKEND   # Saturday
+        assert calendar.state('MNQ', utc(2025, 11, 23, 22, 59)) is SessionState.WEEKEND  # Sun 16:59 CT
+        assert calendar.state('MNQ', utc(2025, 11, 23, 23, 0)) is SessionState.ETH       # Sun open
+
+    def test_session_bounds_and_trading_date(self):
+        calendar = SessionCalendar()
+        sunday_evening = utc(2025, 11, 24, 1, 0)  # Sun 19:00 CT trades for Monday
+        assert calendar.trading_date('ES', sunday_evening) == date(2025, 11, 24)
+        assert calendar.session_bounds('ES', sunday_evening) == (utc(2025, 11, 23, 23, 0), utc(2025, 11, 24, 22, 0))
+        assert calendar.rth_bounds('ESZ5', sunday_evening) == (utc(2025, 11, 24, 14, 30), utc(2025, 11, 24, 21, 0))
+        assert calendar.session_bounds('ES', utc(2025, 11, 22, 12, 0)) is None
+        assert calendar.next_open('ES', utc(2025, 11, 21, 22, 30)) == utc(2025, 11, 23, 23, 0)
+        # DST: the open stays at 17:00 CT (22:00 UTC in summer)
+        assert calendar.session_bounds('ES', utc(2025, 7, 15, 12, 0))[0] == utc(2025, 7, 14, 22, 0)
+
+    def test_products_are_configurable(self):
+        calendar = SessionCalendar(products=[
+            ProductSessions('crypto', time(17, 0), time(16, 0), time(8, 0), time(15, 0), ('MBT',)),
+        ])
+        assert calendar.product_for('MCLF6').name == 'energy'
+        assert calendar.product_for('MBTZ5').name == 'crypto'
+        assert calendar.is_rth('MBT', utc(2025, 11, 19, 14, 0))
+        assert not calendar.is_rth('MNQ', utc(2025, 11, 19, 14, 0))
+        assert calendar.state('CL', utc(2025, 11, 19, 20, 0)) is SessionState.ETH  # Energy RTH ends 13:30 CT
+        calendar.assign('XYZ', 'metals')
+        assert calendar.product_for('XYZ').name == 'metals'
+        with pytest.raises(ValueError):
+            calendar.set_product(ProductSessions('bad', time(8, 0), time(16, 0), time(9, 0), time(15, 0)))
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,377 @@
+"""
+Unit tests for the SignalR hub client (core.websocket)
+"""
+
+import pytest
+import asyncio
+import gzip
+import json
+import os
+import sys
+import time
+import types
+import zlib
+from urllib.parse import urlsplit
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.market_events import DepthUpdate, Quote, Trade
+from core.websocket import ConnectionState, FeedWatchdog, HubConnectionError, MarketHubClient, SignalRClient, market_events_from_invocation
+from core.websocket.capture import FrameRecorder, iter_capture, replay_capture
+from core.websocket.client import HubInvocationError, backoff_delay
+from core.websocket.protocol import (
+    RECORD_SEPARATOR, MessageType, ProtocolError, inflate_frame, is_compressed_frame, orjson,
+    parse_handshake_response, parse_messages,
+)
+
+RS = RECORD_SEPARATOR
+
+
+class FakeConnection:
+    """In-memory WebSocket: the test pushes server frames, the client's sends are collected."""
+
+    def __init__(self, handshake: str = '{}' + RS):
+        self.incoming: asyncio.Queue = asyncio.Queue()
+        self.sent = []
+        self.closed = False
+        self.incoming.put_nowait(handshake)
+
+    async def send(self, data):
+        self.sent.append(data)
+
+    async def recv(self):
+        frame = await self.incoming.get()
+        if isinstance(frame, Exception):
+            raise frame
+        return frame
+
+    async def close(self):
+        self.closed = True
+
+    def push(self, *messages):
+        self.incoming.put_nowait(''.join(json.dumps(m) + RS for m in messages))
+
+    def sent_messages(self):
+        return [m for frame in self.sent[1:] for m in parse_messages(frame)]
+
+
+def connector_for(conn, seen_urls=None):
+    async def connect(url, headers):
+        if seen_urls is not None:
+            seen_urls.append((url, headers))
+        return conn
+    return connect
+
+
+class TestSignalRClient:
+    """Test protocol framing, invocation dispatch and market hub parsing"""
+
+    def test_protocol_framing(self):
+        """Test record-separator framing and handshake parsing"""
+        frame = json.dumps({'type': 1, 'target': 'GatewayQuote', 'arguments': ['CON.F.US.MNQ.Z25', {}]}) + RS + \
+            json.dumps({'type': 6}) + RS + json.dumps({'type': 3, 'invocationId': '4', 'result': True}) + RS
+        messages = parse_messages(frame.encode('utf-8'))
+        assert [m.type for m in messages] == [MessageType.INVOCATION, MessageType.PING, MessageType.COMPLETION]
+        assert messages[0].target == 'GatewayQuote' and messages[0].arguments[0] == 'CON.F.US.MNQ.Z25'
+        assert messages[2].invocation_id == '4' and messages[2].result is True
+
+        error, rest = parse_handshake_response('{}' + RS + json.dumps({'type': 6}) + RS)
+        assert error is None and parse_messages(rest)[0].type == MessageType.PING
+        assert parse_handshake_response('{"error":"Requested protocol not available"}' + RS)[0] is not None
+
+    def test_parser_backends_agree(self):
+        """Test the stdlib and orjson decoders produce the same messages"""
+        frame = (json.dumps({'type': 1, 'target': 'GatewayTrade', 'arguments': ['CON.F.US.MNQ.Z25', [{'price': 1.5}]]}) +
+                 RS + json.dumps({'type': 7, 'error': 'bye', 'allowReconnect': True}) + RS +
+                 json.dumps({'type': 2, 'invocationId': '9', 'item': {'x': 'é'}}) + RS).encode('utf-8')
+        decoders = [json.loads] + ([orjson.loads] if orjson is not None else [])
+        parsed = [parse_messages(frame, loads=loads) for loads in decoders]
+        assert all(p == parsed[0] for p in parsed)
+        assert parsed[0][1].type == MessageType.CLOSE and parsed[0][1].allow_reconnect
+        assert parsed[0][2].type == 2 and parsed[0][2].result == {'x': 'é'}
+        for bad in (b'{"type": [1]}' + RS.encode(), b'[1]' + RS.encode(), b'{"type":1' + RS.encode(), b'\xff' + RS.encode()):
+            for loads in decoders:
+                with pytest.raises(ProtocolError):
+                    parse_messages(bad, loads=loads)
+
+    @pytest.mark.asyncio
+    async def test_connect_dispatch_and_invoke(self):
+        """Test handshake, handler dispatch, send/invoke and server close"""
+        conn = FakeConnection()
+        urls = []
+        client = SignalRClient('https://rtc.example.com/hubs/market', access_token_factory=lambda: 'tok',
+                               keep_alive_interval=0, connector=connector_for(conn, urls), name='test')
+        received, events = [], []
+        client.on('gatewayquote', lambda cid, data: received.append((cid, data)))
+        client.on_open(lambda: events.append('open'))
+        client.on_close(lambda: events.append('close'))
+        client.on_error(lambda err: events.append(f'error:{err}'))
+
+        await client.start()
+        url, headers = urls[0]
+        assert url.startswith('wss://rtc.example.com/hubs/market?') and 'access_token=tok' in url
+        assert headers['Authorization'] == 'Bearer tok'
+        assert json.loads(conn.sent[0].rstrip(RS)) == {'protocol': 'json', 'version': 1}
+        assert client.connected and events == ['open']
+
+        conn.push({'type': 1, 'target': 'GatewayQuote', 'arguments': ['CON.F.US.MNQ.Z25', {'bestBid': 1.5}]},
+                  {'type': 1, 'target': 'Unknown', 'arguments': []}, {'type': 6})
+        client.send('SubscribeContractQuotes', ['CON.F.US.MNQ.Z25'])
+        invoke_ok = asyncio.ensure_future(client.invoke('Echo', ['hi'], timeout=2))
+        invoke_err = asyncio.ensure_future(client.invoke('Boom', [], timeout=2))
+        await asyncio.sleep(0.01)
+        sent = conn.sent_messages()
+        assert [(m.target, m.arguments) for m in sent] == [
+            ('SubscribeContractQuotes', ['CON.F.US.MNQ.Z25']), ('Echo', ['hi']), ('Boom', [])]
+        assert sent[0].invocation_id is None and sent[1].invocation_id
+        conn.push({'type': 3, 'invocationId': sent[1].invocation_id, 'result': 'hi'},
+                  {'type': 3, 'invocationId': sent[2].invocation_id, 'error': 'nope'})
+        assert await invoke_ok == 'hi'
+        with pytest.raises(HubInvocationError):
+            await invoke_err
+        assert received == [('CON.F.US.MNQ.Z25', {'bestBid': 1.5})]
+        stats = client.get_stats()
+        assert stats['unhandled_invocations'] == 1 and stats['pings_received'] == 1
+        assert stats['url'].endswith('access_token=***')
+
+        conn.push({'type': 7, 'error': 'Server shutting down'})
+        await asyncio.sleep(0.01)
+        assert not client.connected and conn.closed
+        assert events == ['open', 'error:Server shutting down', 'close']
+        with pytest.raises(HubConnectionError):
+            client.send('SubscribeContractQuotes', ['CON.F.US.MNQ.Z25'])
+
+        rejected = SignalRClient('wss://rtc.example.com/hubs/market', keep_alive_interval=0,
+                                 connector=connector_for(FakeConnection('{"error":"bad"}' + RS)))
+        with pytest.raises(HubConnectionError):
+            await rejected.start()
+        assert not rejected.connected
+
+    @pytest.mark.asyncio
+    async def test_reconnect_replays_subscriptions(self):
+        """Test backoff, state events and subscription replay after a dropped connection"""
+        assert backoff_delay(1, 1, 60) == 1 and backoff_delay(4, 1, 60) == 8 and backoff_delay(10, 1, 60) == 60
+        assert backoff_delay(2, 1, 60, jitter=0.5, rand=lambda: 0.0) == 1.0
+        assert backoff_delay(2, 1, 60, jitter=0.5, rand=lambda: 0.999999) == pytest.approx(3.0)
+
+        first, second = FakeConnection(), FakeConnection()
+        attempts = []
+
+        async def connect(url, headers):
+            attempts.append(url)
+            if len(attempts) == 1:
+                return first
+            if len(attempts) == 2:
+                raise OSError('connection refused')
+            return second
+
+        client = SignalRClient('wss://rtc.example.com/hubs/market', keep_alive_interval=0, connector=connect,
+                               reconnect_initial_delay=0.001, reconnect_jitter=0)
+        states = []
+        client.on_state_change(lambda state, detail: states.append(state))
+        client.subscribe('SubscribeContractQuotes', ['CON.F.US.MNQ.Z25'])
+        await client.start()
+        client.subscribe('SubscribeContractTrades', ['CON.F.US.MNQ.Z25'])
+        client.subscribe('SubscribeContractQuotes', ['CON.F.US.MNQ.Z25'])  # Already registered
+        assert client.unsubscribe('SubscribeContractTrades', ['CON.F.US.MNQ.Z25'], 'UnsubscribeContractTrades')
+        await asyncio.sleep(0.01)
+        assert [m.target for m in first.sent_messages()] == [
+            'SubscribeContractQuotes', 'SubscribeContractTrades', 'SubscribeContractQuotes', 'UnsubscribeContractTrades']
+
+        first.incoming.put_nowait(ConnectionResetError('reset by peer'))
+        for _ in range(100):
+            if client.connected and len(attempts) == 3:
+                break
+            await asyncio.sleep(0.01)
+        assert client.connected and len(attempts) == 3
+        await asyncio.sleep(0.01)
+        assert [(m.target, m.arguments) for m in second.sent_messages()] == [
+            ('SubscribeContractQuotes', ['CON.F.US.MNQ.Z25'])]
+        assert states == [ConnectionState.CONNECTED, ConnectionState.RECONNECTING, ConnectionState.CONNECTED]
+        assert client.get_stats()['reconnects'] == 1 and client.get_stats()['reconnect_attempts'] == 2
+
+        await client.stop()
+        assert states[-1] == ConnectionState.DISCONNECTED and client.state == ConnectionState.DISCONNECTED
+
+    @pytest.mark.asyncio
+    async def test_stale_feed_watchdog_and_server_timeout(self):
+        """Test silent subscriptions are flagged and a silent server forces a reconnect"""
+        connections = []
+
+        async def connect(url, headers):
+            connections.append(FakeConnection())
+            return connections[-1]
+
+        client = SignalRClient('wss://rtc.example.com/hubs/market', keep_alive_interval=0, server_timeout=0,
+                               connector=connect, reconnect_initial_delay=0.001, reconnect_jitter=0)
+        watchdog = FeedWatchdog(client, stale_after=30, check_interval=60)
+        client.subscribe('SubscribeContractQuotes', ['CON.F.US.MNQ.Z25'])
+        client.subscribe('SubscribeContractTrades', ['CON.F.US.MES.Z25'])
+        client.subscribe('SubscribeAccounts', [])
+        await client.start()
+
+        now = time.monotonic()
+        assert watchdog.check(now) == []
+        await asyncio.sleep(0.05)
+        connections[0].push({'type': 1, 'target': 'GatewayQuote', 'arguments': ['CON.F.US.MNQ.Z25', {}]})
+        await asyncio.sleep(0.01)
+        ages = watchdog.feed_ages(time.monotonic())
+        assert set(ages) == {'quotes:CON.F.US.MNQ.Z25', 'trades:CON.F.US.MES.Z25'}
+        alerts = watchdog.check(now + 30.02)
+        assert [(a.stream, a.symbol) for a in alerts] == [('trades', 'MES')]
+        client.unsubscribe('SubscribeContractTrades', ['CON.F.US.MES.Z25'])
+        assert watchdog.check(now + 30.02) == []
+        assert [a.symbol for a in watchdog.check(time.monotonic() + 60)] == ['MNQ']
+
+        await client.force_reconnect('stale feed')
+        for _ in range(100):
+            if client.connected:
+                break
+            await asyncio.sleep(0.01)
+        assert len(connections) == 2 and client.get_stats()['forced_reconnects'] == 1
+
+        client.server_timeout = 0.05
+        await client.force_reconnect('apply server timeout')
+        for _ in range(100):
+            if client.get_stats()['server_timeouts'] >= 1 and client.connected:
+                break
+            await asyncio.sleep(0.01)
+        assert client.get_stats()['server_timeouts'] >= 1 and len(connections) >= 4
+        await client.stop()
+
+    @pytest.mark.asyncio
+    async def test_market_hub_typed_events(self):
+        """Test gateway payloads become Quote/Trade/DepthUpdate events"""
+        quote = market_events_from_invocation('GatewayQuote', [{'contractId': 'CON.F.US.MNQ.Z25',
+                                                                'bestBid': 21000.25, 'bestAsk': 21000.5}])
+        assert isinstance(quote[0], Quote) and quote[0].symbol == 'MNQ' and quote[0].mid == 21000.375
+        trades = market_events_from_invocation('GatewayTrade', ['CON.F.US.MES.Z25', [
+            {'price': 6000.0, 'volume': 2, 'type': 0, 'timestamp': '2025-11-19T14:30:00Z'},
+            {'price': 6000.25, 'volume': 1, 'type': 1, 'timestamp': '2025-11-19T14:30:01Z'}]])
+        assert [(t.symbol, t.price, t.size, t.side) for t in trades] == [
+            ('MES', 6000.0, 2, 'buy'), ('MES', 6000.25, 1, 'sell')]
+        depth = market_events_from_invocation('GatewayDepth', ['CON.F.US.MNQ.Z25', [
+            {'type': 2, 'price': 21000.0, 'volume': 5}, {'type': 1, 'price': 21000.5, 'volume': 3},
+            {'type': 5, 'price': 21000.25, 'volume': 1}]])
+        assert isinstance(depth[0], DepthUpdate)
+        assert depth[0].bids == ((21000.0, 5),) and depth[0].asks == ((21000.5, 3),)
+        assert market_events_from_invocation('GatewayQuote', ['', {}]) == []
+
+        conn = FakeConnection()
+        hub = MarketHubClient('https://rtc.example.com/hubs/market', keep_alive_interval=0,
+                              connector=connector_for(conn))
+        events = []
+        hub.add_listener(events.append)
+        await hub.start()
+        hub.subscribe('CON.F.US.MNQ.Z25', quotes=True, trades=True)
+        conn.push({'type': 1, 'target': 'GatewayTrade',
+                   'arguments': ['CON.F.US.MNQ.Z25', [{'price': 21000.0, 'volume': 4, 'type': 0}]]})
+        await asyncio.sleep(0.01)
+        assert [m.target for m in conn.sent_messages()] == ['SubscribeContractQuotes', 'SubscribeContractTrades']
+        assert len(events) == 1 and isinstance(events[0], Trade) and events[0].size == 4
+        await hub.stop()
+        assert not hub.connected and hub.get_stats()['events_published'] == 1
+
+    @pytest.mark.asyncio
+    async def test_record_and_replay_capture(self, tmp_path):
+        """Test recorded frames replay into the same typed events offline"""
+        conn = FakeConnection(handshake='{}' + RS + json.dumps({'type': 6}) + RS)
+        recorder = FrameRecorder(directory=tmp_path, hub='market', compression='gzip')
+        hub = MarketHubClient('https://rtc.example.com/hubs/market', keep_alive_interval=0,
+                              connector=connector_for(conn), recorder=recorder)
+        live = []
+        hub.add_listener(live.append)
+        await hub.start()
+        conn.push({'type': 1, 'target': 'GatewayQuote',
+                   'arguments': ['CON.F.US.MNQ.Z25', {'bestBid': 21000.25, 'bestAsk': 21000.5}]})
+        trade = {'price': 21000.5, 'volume': 3, 'type': 1, 'timestamp': '2025-11-19T14:30:00Z'}
+        conn.incoming.put_nowait(json.dumps({'type': 1, 'target': 'GatewayTrade', 'arguments': [
+            'CON.F.US.MNQ.Z25', [trade]]}).encode('utf-8') + RS.encode())  # Binary frame
+        await asyncio.sleep(0.01)
+        recorder.flush()
+        path = recorder.current_file
+        await hub.stop()
+        assert path.name.startswith('capture-market-') and path.suffix == '.gz'
+        frames = list(iter_capture(path))
+        assert [f.handshake for f in frames] == [True, False, False] and isinstance(frames[2].data, bytes)
+        assert recorder.get_stats()['frames_written'] == 3
+
+        offline = MarketHubClient('offline')
+        replayed = []
+        offline.add_listener(replayed.append)
+        assert await replay_capture(path, offline.client) == 3
+        assert [(e.type, e.symbol) for e in replayed] == [(e.type, e.symbol) for e in live] == \
+            [('quote', 'MNQ'), ('trade', 'MNQ')]
+        assert replayed[1] == live[1] and offline.client.get_stats()['pings_received'] == 1
+
+    @pytest.mark.asyncio
+    async def test_failover_across_endpoints(self):
+        """Test repeated failures switch to the backup URL, emit an event and resubscribe there"""
+        down = {'primary'}
+        connections = []
+
+        async def connect(url, headers):
+            host = urlsplit(url).hostname.split('.')[0]
+            if host in down:
+                raise OSError(f"{host} gateway degraded")
+            conn = FakeConnection()
+            connections.append((host, conn))
+            return conn
+
+        client = SignalRClient('https://primary.example.com/hubs/market', access_token_factory=lambda: 'tok',
+                               backup_urls=['https://backup.example.com/hubs/market'], failover_after=2,
+                               failover_stable_seconds=60, keep_alive_interval=0, reconnect_initial_delay=0.001,
+                               reconnect_max_delay=0.001, connector=connect)
+        failovers = []
+        client.on_failover(failovers.append)
+        client.subscribe('SubscribeContractQuotes', ['CON.F.US.MNQ.Z25'])
+        await client.start()  # Primary refused, start() moves on to the backup
+        assert [host for host, _ in connections] == ['backup'] and failovers[0].to_url.startswith('https://backup.')
+        assert failovers[0].failures == 1 and client.get_stats()['endpoint'].startswith('https://backup.')
+
+        # Backup degrades: drops soon after connecting, then refuses; fail back to the recovered primary
+        down = {'backup'}
+        connections[0][1].incoming.put_nowait(ConnectionResetError('reset'))
+        for _ in range(100):
+            await asyncio.sleep(0.01)
+            if client.connected and len(connections) == 2:
+                break
+        host, conn = connections[-1]
+        assert host == 'primary' and client.failovers == 2 and 'gateway degraded' in failovers[1].reason
+        assert failovers[1].failures == 2 and client.endpoint_failures == 0
+        assert [m.target for m in conn.sent_messages()] == ['SubscribeContractQuotes']
+        await client.stop()
+
+    @pytest.mark.asyncio
+    async def test_compressed_frames(self, monkeypatch):
+        """Test gzip/zlib frames are inflated, bad ones dropped, and deflate is offered per config"""
+        quote = json.dumps({'type': 1, 'target': 'GatewayQuote',
+                            'arguments': ['CON.F.US.MNQ.Z25', {'bestBid': 1.5}]}) + RS
+        conn = FakeConnection(handshake=gzip.compress(('{}' + RS).encode()))
+        conn.response = types.SimpleNamespace(headers={'Sec-WebSocket-Extensions': 'permessage-deflate'})
+        client = SignalRClient('wss://rtc.example.com/hubs/market', keep_alive_interval=0,
+                               connector=connector_for(conn))
+        received = []
+        client.on('GatewayQuote', lambda cid, data: received.append(data))
+        await client.start()
+        conn.incoming.put_nowait(gzip.compress(quote.encode()))
+        conn.incoming.put_nowait(zlib.compress(quote.encode()))
+        conn.incoming.put_nowait(gzip.compress(quote.encode())[:-6])  # Truncated
+        conn.incoming.put_nowait(quote.encode())  # Plain binary frame
+        await asyncio.sleep(0.01)
+        stats = client.get_stats()
+        assert received == [{'bestBid': 1.5}] * 3 and stats['protocol_errors'] == 1
+        assert stats['compression']['extensions'] == 'permessage-deflate'
+        assert stats['compression']['compressed_frames'] == 3  # Handshake + gzip + zlib
+        await client.stop()
+
+        assert not is_compressed_frame(b'{}' + RS.encode()) and not is_compressed_frame('x\x9c')
+        with pytest.raises(ProtocolError):
+            inflate_frame(gzip.compress(b'{' * 10000), max_size=1000)
+        monkeypatch.setenv('SIGNALR_COMPRESSION', 'false')
+        assert SignalRClient('wss://rtc.example.com/hubs/market')._connector.keywords == {'compression': False}
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
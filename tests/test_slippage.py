@@ -0,0 +1,70 @@
+"""
+Unit tests for slippage tracking
+"""
+
+import pytest
+import os
+import sys
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.slippage import SlippageTracker
+
+
+def make_tracker(**kwargs):
+    kwargs.setdefault('max_pending', 10)
+    kwargs.setdefault('max_trades', 100)
+    return SlippageTracker(**kwargs)
+
+
+class TestSlippageTracker:
+    """Test signal/submit/fill recording and the aggregate report"""
+
+    def test_slippage_measured_against_trade_direction(self):
+        tracker = make_tracker()
+        tracker.record_submission('1', 'MNQ', 'BUY', 'Market', submit_price=21000.25, quantity=2, strategy='orb')
+        assert tracker.set_signal_price('1', 21000.0)
+        buy = tracker.record_fill('1', 21000.75).to_dict()
+        assert (buy['signal_slippage_ticks'], buy['execution_slippage_ticks'], buy['total_slippage_ticks']) == (1, 2, 3)
+        assert buy['cost'] == 3.0  # 0.75 pts x $2 x 2 contracts
+        tracker.record_submission('2', 'MNQ', 'SELL', 'Market', submit_price=21010.0, quantity=1)
+        sell = tracker.record_fill('2', 21010.5).to_dict()  # Sold above the bid: price improvement
+        assert sell['signal_slippage'] is None and sell['total_slippage_ticks'] == -2
+
+    def test_report_groups_by_symbol_strategy_and_order_type(self):
+        tracker = make_tracker()
+        tracker.record_submission('1', 'MNQ', 'BUY', 'Market', 21000.0, quantity=1, strategy='orb',
+                                  signal_price=20999.5)
+        tracker.record_submission('2', 'MNQ', 'BUY', 'Limit', 21000.0, quantity=1, strategy='orb')
+        tracker.record_submission('3', 'ES', 'SELL', 'Market', 5000.0, quantity=1)
+        tracker.record_submission('4', 'ES', 'SELL', 'Market', 5000.0, quantity=1)  # Never fills
+        tracker.record_fill('1', 21000.5)
+        tracker.record_fill('2', 21000.0)
+        tracker.record_fill('3', 4999.75)
+        report = tracker.get_slippage_report()
+        assert report['overall']['trades'] == 3 and report['pending_orders'] == 1
+        assert report['by_symbol']['MNQ']['avg_total_slippage_ticks'] == 2.0  # (4 + 0) / 2
+        assert report['by_symbol']['ES']['worst_total_slippage_ticks'] == 1.0
+        assert set(report['by_strategy']) == {'orb', 'manual'}
+        assert report['by_order_type']['Market']['avg_execution_slippage_ticks'] == 1.5
+        orb = tracker.get_slippage_report(strategy='orb')
+        assert orb['overall']['trades'] == 2 and orb['overall']['with_signal_price'] == 1
+        assert tracker.get_slippage_report(symbol='ESZ5')['overall']['trades'] == 1
+
+    def test_unknown_orders_and_bounds(self):
+        tracker = make_tracker(max_pending=2, max_trades=2)
+        assert tracker.record_fill('99', 100.0) is None  # Manual order or bracket leg
+        for order_id in '123':
+            tracker.record_submission(order_id, 'MES', 'BUY', 'Market', 5000.0)
+        assert tracker.record_fill('1', 5000.25) is None  # Evicted while pending
+        tracker.record_fill('2', 5000.25)
+        tracker.record_fill('3', 5000.5)
+        assert tracker.set_signal_price('3', 4999.75)  # Signal attached after the fill
+        assert [t['order_id'] for t in tracker.get_trades()] == ['3', '2']
+        assert tracker.get_trades(limit=1)[0]['total_slippage_ticks'] == 3
+        assert tracker.get_slippage_report(symbol='NQ')['overall']['avg_total_slippage_ticks'] is None
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,133 @@
+"""
+Unit tests for the SQLite database backend
+"""
+
+import pytest
+import os
+import sqlite3
+import sys
+from datetime import date, datetime, timedelta, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+pytest.importorskip('psycopg2')
+
+from infrastructure.database import DatabaseManager
+from infrastructure.sqlite_database import SQLiteDatabaseManager, sqlite_path
+
+T0 = datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc)
+
+
+@pytest.fixture
+def db(tmp_path, monkeypatch):
+    monkeypatch.setenv('JOURNAL_TIMEZONE', 'UTC')
+    monkeypatch.setenv('JOURNAL_DAY_START', '00:00')
+    manager = SQLiteDatabaseManager(f"sqlite:///{tmp_path / 'trading.db'}")
+    yield manager
+    manager.close()
+
+
+class TestConnection:
+    """Test connection strings, WAL mode and migrations."""
+
+    def test_sqlite_path(self):
+        assert sqlite_path('sqlite:///var/lib/tradebot/trading.db') == '/var/lib/tradebot/trading.db'
+        assert sqlite_path('sqlite://trading.db?mode=rwc') == 'trading.db'
+        assert sqlite_path('sqlite::memory:') == ':memory:'
+        with pytest.raises(ValueError):
+            sqlite_path('postgresql://localhost/trading')
+
+    def test_wal_mode_and_migrations(self, db):
+        with db.get_connection() as conn:
+            with conn.cursor() as cur:
+                cur.execute("PRAGMA journal_mode")
+                assert cur.fetchone()[0] == 'wal'
+        status = db.migration_status()
+        assert status['version'] == 2 and status['pending'] == []
+        assert db.migrate() == []  # Already applied
+        assert db.get_pool_stats()['primary']['backend'] == 'sqlite'
+
+    def test_failing_migration_rolls_back(self, tmp_path, monkeypatch):
+        monkeypatch.setenv('DB_AUTO_MIGRATE', 'false')
+        db = SQLiteDatabaseManager(f"sqlite:///{tmp_path / 'empty.db'}")
+        (tmp_path / '0001_a.sql').write_text('CREATE TABLE a (x INTEGER);\nCREATE TABLE b (x INTEGER);')
+        (tmp_path / '0002_broken.sql').write_text('CREATE TABLE c (x INTEGER);\nCREATE TABLE broken (;')
+        with pytest.raises(RuntimeError, match='0002_broken.sql failed'):
+            db.migrate(directory=tmp_path)
+        db.close()
+        conn = sqlite3.connect(db.path)
+        tables = {row[0] for row in conn.execute("SELECT name FROM sqlite_master WHERE type = 'table'")}
+        assert {'a', 'b'} <= tables and 'c' not in tables
+        assert conn.execute("SELECT version FROM schema_migrations").fetchall() == [(1,)]
+        conn.close()
+
+
+class TestSharedQueries:
+    """Test that DatabaseManager's queries run unchanged on SQLite."""
+
+    def test_state_round_trip(self, db):
+        assert db.save_strategy_state('123', 'orb', enabled=True, symbols=['MNQ'], settings={'risk': 1})
+        state = db.get_strategy_state('123', 'orb')
+        assert state['enabled'] is True and state['symbols'] == ['MNQ'] and state['settings'] == {'risk': 1}
+        assert isinstance(db.get_strategy_states('123')['orb']['updated_at'], str)
+
+    def test_batched_market_data_and_downsampling(self, db):
+        ticks = [DatabaseManager._tick_row({'symbol': 'MNQ', 'timestamp': T0 + timedelta(seconds=s), 'price': p,
+                                            'size': 1, 'side': 'buy'}) for s, p in ((0, 1.0), (30, 3.0), (70, 2.0))]
+        bars = [DatabaseManager._bar_row('MNQ', '1m', {'timestamp': T0 + timedelta(minutes=m), 'open': 1 + m,
+                                                       'high': 5 + m, 'low': m, 'close': 2 + m, 'volume': 10})
+                for m in range(6)]
+        db.write_market_data(ticks, bars)
+        db.write_market_data([], bars[:1])  # Upsert, not a duplicate
+        assert [t['price'] for t in db.get_ticks('MNQ', T0, T0 + timedelta(minutes=5))] == [1.0, 3.0, 2.0]
+        assert len(db.get_cached_bars('MNQ', '1m', T0, T0 + timedelta(minutes=10))) == 6
+
+        five = db.get_downsampled_bars('MNQ', '5m', start_time=T0, end_time=T0 + timedelta(minutes=10))
+        assert [(b['open'], b['high'], b['low'], b['close'], b['volume']) for b in five] == [
+            (1.0, 9.0, 0.0, 6.0, 50), (6.0, 10.0, 5.0, 7.0, 10)]
+        assert five[0]['timestamp'] == T0.isoformat()
+        minute = db.get_tick_bars('MNQ', '1m', T0, T0 + timedelta(minutes=5))
+        assert [(b['open'], b['close'], b['volume']) for b in minute] == [(1.0, 3.0, 2), (2.0, 2.0, 1)]
+
+    def test_bar_upserts_and_rewrite_range(self, db):
+        bar = {'timestamp': T0, 'open': 1.0, 'high': 2.0, 'low': 0.5, 'close': 1.5, 'volume': 10}
+        corrected = dict(bar, close=1.75, volume=12)
+        assert db.cache_historical_bars('MNQ', '1m', [bar, corrected]) == 1  # Re-delivered in one batch
+        assert db.cache_historical_bars('MNQ', '1m', [dict(bar, timestamp=T0 + timedelta(minutes=m))
+                                                      for m in (1, 2, 3)]) == 3
+        stored = db.get_cached_bars('MNQ', '1m', T0, T0 + timedelta(minutes=5))
+        assert (stored[0]['close'], stored[0]['volume']) == (1.75, 12) and len(stored) == 4
+
+        result = db.rewrite_range('MNQ', '1m', T0 + timedelta(minutes=1), T0 + timedelta(minutes=3),
+                                  [dict(bar, timestamp=T0 + timedelta(minutes=1), close=9.0)])
+        assert result == {'deleted': 2, 'written': 1}
+        stored = db.get_cached_bars('MNQ', '1m', T0, T0 + timedelta(minutes=5))
+        assert [b['close'] for b in stored] == [1.75, 9.0, 1.5]  # Minute 2 dropped, minute 3 untouched
+        with pytest.raises(ValueError):
+            db.rewrite_range('MNQ', '1m', T0, T0 + timedelta(minutes=1), [dict(bar, timestamp=T0 + timedelta(hours=1))])
+        with pytest.raises(ValueError):
+            db.rewrite_range('MNQ', '1m', T0, T0, [])
+
+    def test_journal_pnl_by_strategy(self, db):
+        for pnl in (100.0, -40.0):
+            db.record_trade('123', {'strategy_name': 'orb', 'symbol': 'MNQ', 'side': 'long', 'quantity': 1,
+                                    'entry_price': 1.0, 'exit_price': 2.0, 'pnl': pnl,
+                                    'entry_time': T0, 'exit_time': T0 + timedelta(minutes=5)})
+        result = db.pnl_by_strategy(date(2025, 11, 19), date(2025, 11, 19))
+        assert result['orb']['trades'] == 2 and result['orb']['pnl'] == 60.0 and result['orb']['win_rate'] == 50.0
+
+    def test_journal_net_positions(self, db):
+        assert db.get_journal_net_positions('123') is None  # No snapshot yet
+        db.record_positions('123', [{'symbol': 'MNQ', 'quantity': 2}, {'symbol': 'ES', 'quantity': 0}], timestamp=T0)
+        db.record_fill('123', {'fill_id': 'a', 'symbol': 'MNQ', 'side': 'BUY', 'quantity': 1, 'price': 1.0,
+                               'timestamp': T0 - timedelta(seconds=1)})  # Already in the snapshot
+        db.record_fill('123', {'fill_id': 'b', 'symbol': 'MNQ', 'side': 1, 'quantity': 2, 'price': 1.0,
+                               'timestamp': T0 + timedelta(seconds=1)})
+        db.record_fill('123', {'fill_id': 'c', 'symbol': 'ES', 'side': 0, 'quantity': 1, 'price': 1.0,
+                               'timestamp': T0 + timedelta(seconds=2)})
+        assert db.get_journal_net_positions('123') == {'ES': 1}
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,863 @@
+"""
+Unit tests for the event-driven strategy engine (core.strategy_engine)
+"""
+
+import pytest
+import asyncio
+import json
+import os
+import sys
+import threading
+from datetime import datetime, time, timedelta, timezone
+from zoneinfo import ZoneInfo
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar, BarAggregator
+from core.strategy_engine import (
+    ATR, EMA, MACD, SMA, BacktestConfig, Backtester, CommissionModel, Direction, EmaCrossParams, EmaCrossStrategy,
+    MacdMomentumStrategy, MacdParams, MarketContext, OrderExecutor, PortfolioCoordinator, SessionFilter,
+    SessionRule, Signal, SignalBus, SignalThrottle, Strategy, StrategyConfigWatcher, StrategyEngine, TradingCore,
+)
+from core.market_events import Trade
+
+T0 = datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc)
+
+
+def make_bars(closes, symbol='MNQ', timeframe='5m', spread=1.0):
+    return [Bar(symbol=symbol, timeframe=timeframe, timestamp=T0 + timedelta(minutes=5 * i), open=c,
+                high=c + spread, low=c - spread, close=c, volume=100) for i, c in enumerate(closes)]
+
+
+class TestIndicators:
+    """Test streaming indicators against straightforward batch calculations"""
+
+    def test_sma_ema_atr(self):
+        """Test warm-up, seeding and recurrences"""
+        values = [10.0, 11.0, 12.0, 13.0, 12.0, 11.0, 15.0]
+        sma, ema = SMA(3), EMA(3)
+        sma_out = [sma.update(v) for v in values]
+        ema_out = [ema.update(v) for v in values]
+        assert sma_out[:2] == [None, None] and sma_out[2:] == pytest.approx([11.0, 12.0, 37 / 3, 12.0, 38 / 3])
+        expected = 11.0  # SMA seed of the first 3 inputs
+        for v in values[3:]:
+            expected += 0.5 * (v - expected)
+        assert ema_out[:2] == [None, None] and ema_out[2] == 11.0 and ema.value == pytest.approx(expected)
+
+        atr = ATR(2)
+        assert atr.update(11, 9, 10) is None  # TR 2
+        assert atr.update(14, 12, 13) == pytest.approx(3.0)  # TR max(2, 4, 2) = 4, seed (2 + 4) / 2
+        assert atr.update(13, 12, 12.5) == pytest.approx(2.0)  # TR 1, Wilder (3 * 1 + 1) / 2
+        ema.reset()
+        assert not ema.ready and ema.count == 0
+        with pytest.raises(ValueError):
+            EMA(0)
+
+
+class TestEmaCrossStrategy:
+    """Test cross detection, confirmation, side flags and engine routing"""
+
+    def test_cross_signals_and_confirmation(self):
+        """Test one signal per confirmed cross with ATR stop/target"""
+        closes = [104.0, 103, 102, 101, 100, 101, 102, 103, 104, 102, 99, 96, 93, 90]
+        strategy = EmaCrossStrategy('ema', EmaCrossParams(fast_period=2, slow_period=4, atr_period=3,
+                                                          stop_atr=1.0, target_atr=2.0))
+        signals = [(i, s) for i, bar in enumerate(make_bars(closes)) if (s := strategy.on_bar(bar))]
+        assert [(i, s.direction) for i, s in signals] == [(6, Direction.LONG), (9, Direction.SHORT)]
+        long = signals[0][1]
+        assert (long.price, long.stop, long.target) == (102, 100, 106)  # ATR 2
+        assert 0.5 < long.confidence < 0.6  # EMAs barely apart
+        assert long.strategy_id == 'ema' and long.timeframe == '5m' and 'crossed above' in long.reason
+
+        # Two confirmation bars: a cross that reverses after one bar never fires
+        confirmed = EmaCrossStrategy('ema2', EmaCrossParams(fast_period=2, slow_period=4, confirmation_bars=2))
+        choppy = [104.0, 103, 102, 101, 100, 103, 96, 95, 94, 93]
+        fired = [(i, s.direction) for i, bar in enumerate(make_bars(choppy)) if (s := confirmed.on_bar(bar))]
+        assert fired == [(7, Direction.SHORT)]
+        assert confirmed.get_state('MNQ')['trend'] == 'down'
+
+        no_shorts = EmaCrossStrategy('long_only', EmaCrossParams(fast_period=2, slow_period=4, allow_short=False))
+        exits = [s for bar in make_bars(closes) if (s := no_shorts.on_bar(bar))]
+        assert [s.direction for s in exits] == [Direction.LONG, Direction.FLAT] and exits[1].stop is None
+        with pytest.raises(ValueError):
+            EmaCrossParams(fast_period=5, slow_period=5).validate()
+
+    def test_engine_routing_and_error_isolation(self):
+        """Test bars from the aggregator reach matching strategies and a failing strategy is disabled"""
+
+        class Broken(Strategy):
+            def on_bar(self, bar):
+                raise RuntimeError('boom')
+
+        engine = StrategyEngine(max_errors=2)
+        engine.add_strategy(EmaCrossStrategy('ema', EmaCrossParams(fast_period=2, slow_period=4),
+                                             symbols=['MNQ'], timeframes=['5m']))
+        engine.add_strategy(Broken('broken'))
+        with pytest.raises(ValueError):
+            engine.add_strategy(Broken('broken'))
+        received = []
+        engine.on_signal(received.append)
+        aggregator = BarAggregator()
+        engine.attach(aggregator)
+        for bar in make_bars([104.0, 103, 102, 101, 100, 101, 102]) + make_bars([100.0] * 6, symbol='MES'):
+            aggregator._emit_completed_bar(bar)
+        assert [(s.symbol, s.direction) for s in received] == [('MNQ', Direction.LONG)]
+        stats = engine.get_stats()['strategies']
+        assert stats['ema']['bars'] == 7 and stats['broken']['errors'] == 2
+        assert not stats['broken']['enabled'] and 'boom' in stats['broken']['last_error']
+
+
+class TestMacdMomentumStrategy:
+    """Test MACD signal line crosses, zero-line filter and histogram divergence"""
+
+    # Down, up, down, up to a second low under the first with a shallower histogram trough
+    CLOSES = [110.0, 108, 106, 104, 102, 100, 98, 96, 97, 99, 102, 105, 108, 110, 109, 106, 102, 99, 96, 94,
+              93, 95, 98, 101, 103, 104, 102, 100, 98, 96, 94, 92, 91, 90.5, 92, 95]
+
+    def run(self, **kwargs):
+        params = MacdParams(fast_period=3, slow_period=6, signal_period=3, atr_period=3, **kwargs)
+        strategy = MacdMomentumStrategy('macd', params)
+        return strategy, [(i, s) for i, bar in enumerate(make_bars(self.CLOSES)) if (s := strategy.on_bar(bar))]
+
+    def test_signal_line_crosses_and_filters(self):
+        """Test every cross fires unfiltered and the zero-line filter drops counter-trend crosses"""
+        macd = MACD(3, 6, 3)
+        for close in range(1, 9):
+            macd.update(float(close))
+        assert macd.value == pytest.approx(1.5) and macd.histogram == pytest.approx(0.0)  # Linear trend
+
+        strategy, signals = self.run(zero_line_filter=False)
+        assert [(i, s.direction) for i, s in signals] == [
+            (15, Direction.SHORT), (21, Direction.LONG), (27, Direction.SHORT), (34, Direction.LONG)]
+        short = signals[0][1]
+        assert short.stop > short.price > short.target and 'crossed below' in short.reason
+        assert strategy.get_state('MNQ')['momentum'] == 'up'
+
+        # Swing trading around a flat mean: every cross comes before the MACD line changes side
+        filtered, none = self.run()
+        assert none == [] and filtered.get_state('MNQ')['histogram'] > 0
+        uptrend = MacdMomentumStrategy('trend', MacdParams(fast_period=3, slow_period=6, signal_period=3))
+        pullback = [100.0 + 2 * i for i in range(12)] + [120, 119, 120, 123, 127, 131]
+        assert [(i, s.direction) for i, bar in enumerate(make_bars(pullback))
+                if (s := uptrend.on_bar(bar))] == [(16, Direction.LONG)]  # Resumption with MACD above zero
+        _, long_only = self.run(zero_line_filter=False, allow_short=False)
+        assert [s.direction for _, s in long_only] == [Direction.FLAT, Direction.LONG, Direction.FLAT, Direction.LONG]
+        with pytest.raises(ValueError):
+            MacdParams(divergence='sometimes').validate()
+
+    def test_histogram_divergence(self):
+        """Test a lower price low with a higher histogram low boosts or gates the long"""
+        _, plain = self.run(zero_line_filter=False)
+        _, boosted = self.run(zero_line_filter=False, divergence='boost')
+        _, required = self.run(zero_line_filter=False, divergence='require')
+        assert 'bullish histogram divergence' in plain[-1][1].reason
+        assert boosted[-1][1].confidence == pytest.approx(min(1.0, plain[-1][1].confidence + 0.25))
+        assert [(i, s.direction) for i, s in required] == [(34, Direction.LONG)]
+
+
+class TestParallelExecution:
+    """Test symbol worker threads keep per-symbol ordering and match inline results"""
+
+    def test_per_symbol_order_and_results(self):
+        """Test each symbol stays on one worker, in order, with the same signals as inline execution"""
+
+        class Recorder(Strategy):
+            def __init__(self, strategy_id):
+                super().__init__(strategy_id)
+                self.seen = {}
+
+            def on_bar(self, bar):
+                self.seen.setdefault(bar.symbol, []).append((bar.timestamp, threading.current_thread().name))
+
+        closes = [104.0, 103, 102, 101, 100, 101, 102, 103, 104, 102, 99, 96, 93, 90]
+        symbols = ['MNQ', 'MES', 'MGC', 'MCL', 'M2K', 'MYM']
+        bars = [bar for i in range(len(closes)) for bar in (make_bars(closes, symbol=sym)[i] for sym in symbols)]
+
+        inline = StrategyEngine()
+        inline.add_strategy(EmaCrossStrategy('ema', EmaCrossParams(fast_period=2, slow_period=4)))
+        expected = sorted((s.symbol, s.timestamp, s.direction.value) for bar in bars for s in inline.on_bar(bar))
+
+        engine = StrategyEngine(workers=3)
+        recorder = engine.add_strategy(Recorder('recorder'))
+        engine.add_strategy(EmaCrossStrategy('ema', EmaCrossParams(fast_period=2, slow_period=4)))
+        received = []
+        engine.on_signal(received.append)
+        for bar in bars:
+            engine.dispatch_bar(bar)
+        assert engine.flush(timeout=5)
+
+        assert sorted((s.symbol, s.timestamp, s.direction.value) for s in received) == expected
+        for symbol, seen in recorder.seen.items():
+            assert [ts for ts, _ in seen] == [bar.timestamp for bar in make_bars(closes, symbol=symbol)]
+            assert {name for _, name in seen} == {f"strategy-worker-{engine._pool.shard_for(symbol)}"}
+        stats = engine.get_stats()
+        assert stats['bars_processed'] == len(bars) and stats['strategies']['recorder']['bars'] == len(bars)
+        assert sum(shard['processed'] for shard in stats['workers']['shards']) == len(bars)
+        engine.stop()
+        assert not engine.get_stats()['workers']['running']
+
+
+class TestPythonStrategies:
+    """Test plain Python objects registered as strategies"""
+
+    def test_duck_typed_objects_and_legacy_dicts(self):
+        """Test wrapping, legacy signal dicts, batches and error isolation of plain objects"""
+
+        class Breakout:
+            name = 'breakout'
+            symbols = ['MNQ']
+
+            def __init__(self):
+                self.high = None
+
+            def on_bar(self, bar):
+                signal = None
+                if self.high is not None and bar.close > self.high:
+                    signal = {"action": "LONG", "stop_loss": bar.low, "take_profit": bar.close + 10,
+                              "confidence": 0.7, "reason": "new high"}
+                self.high = max(self.high or bar.high, bar.high)
+                return signal
+
+        class Batch:
+            def on_bars(self, bars):
+                return [{"action": "CLOSE", "symbol": bars[-1].symbol, "entry_price": bars[-1].close}]
+
+        class Broken:
+            def on_bar(self, bar):
+                return 42
+
+        engine = StrategyEngine(max_errors=3)
+        wrapped = engine.add_strategy(Breakout())
+        engine.add_strategy(Batch(), strategy_id='batch')
+        engine.add_strategy(Broken())
+        assert wrapped.strategy_id == 'breakout' and wrapped.symbols == {'MNQ'}
+        with pytest.raises(TypeError):
+            engine.add_strategy(object())
+
+        closes = [100.0, 99, 102, 101, 104]
+        signals = engine.on_bars(make_bars(closes) + make_bars(closes, symbol='MES'))
+        longs = [s for s in signals if s.strategy_id == 'breakout']
+        assert [(s.symbol, s.price, s.stop, s.target) for s in longs] == [('MNQ', 102, 101, 112),
+                                                                         ('MNQ', 104, 103, 114)]
+        assert longs[0].timestamp == T0 + timedelta(minutes=10) and longs[0].reason == 'new high'
+        assert [(s.direction, s.symbol) for s in signals if s.strategy_id == 'batch'] == [(Direction.FLAT, 'MES')]
+
+        stats = engine.get_stats()['strategies']
+        assert stats['breakout']['bars'] == 5 and stats['batch']['bars'] == 10
+        assert stats['Broken']['errors'] == 1 and 'TypeError' in stats['Broken']['last_error']
+
+
+class Scripted(Strategy):
+    """Emits prepared signals at given bar numbers (per strategy instance)"""
+
+    def __init__(self, strategy_id, script):
+        super().__init__(strategy_id)
+        self.script = script
+        self.count = 0
+
+    def on_bar(self, bar):
+        step = self.script.get(self.count)
+        self.count += 1
+        if step:
+            direction, stop, target = step
+            return self.signal(bar.symbol, direction, bar.close, bar.timestamp, stop=stop, target=target)
+
+    def reset(self, symbol=None):
+        self.count = 0
+
+
+class TestBacktester:
+    """Test the simulated fill model and parity with live engine signals"""
+
+    def test_fill_model(self):
+        """Test next-open fills, slippage, commissions, stops, targets, reversals and the final close"""
+        #        bar:   0      1      2      3      4      5      6      7
+        closes = [100.0, 101.0, 102.0, 103.0, 100.0, 98.0, 99.0, 97.0]
+        bars = make_bars(closes)
+        bars[2] = Bar(symbol='MNQ', timeframe='5m', timestamp=bars[2].timestamp, open=101.5, high=106.0,
+                      low=101.0, close=102.0)  # Reaches the first target
+        script = {
+            0: (Direction.LONG, 95.0, 105.0),   # Fills at bar 1 open 101 + 0.25 slippage, target hit on bar 2
+            3: (Direction.LONG, 101.0, None),   # Fills at bar 4 open 100: gaps through the stop, out at the open
+            5: (Direction.SHORT, None, None),   # Fills at bar 6 open 99 - 0.25
+            6: (Direction.LONG, None, None),    # Reverses at bar 7 open 97 + 0.25, long closed at the end
+        }
+        config = BacktestConfig(commission=0.5, slippage_ticks=1, initial_capital=10000)
+        backtester = Backtester([Scripted('script', script)], config)
+        result = backtester.run(bars)
+        summary = [(t.direction, t.entry_price, t.exit_price, t.exit_reason) for t in result.trades]
+        assert summary == [
+            (Direction.LONG, 101.25, 105.0, 'target'),
+            (Direction.LONG, 100.25, 99.75, 'stop'),
+            (Direction.SHORT, 98.75, 97.25, 'reverse'),
+            (Direction.LONG, 97.25, 96.75, 'end'),
+        ]
+        assert [t.pnl for t in result.trades] == pytest.approx([6.5, -2.0, 2.0, -2.0])  # $2/pt, $1 round trip
+        # Excursions from the bars seen while open and the exit price
+        assert [t.mae for t in result.trades] == pytest.approx([2.5, 1.0, 2.5, 2.5])
+        assert [t.mfe for t in result.trades] == pytest.approx([7.5, 0.0, 3.0, 1.5])
+        stats = result.stats()
+        assert stats['trades'] == 4 and stats['net_pnl'] == 4.5 and stats['commissions'] == 4.0
+        assert result.equity_curve[-1][1] == pytest.approx(10004.5) and stats['max_drawdown'] > 0
+
+        # Same run again: strategies are reset, results identical
+        assert [t.to_dict() for t in backtester.run(bars).trades] == [t.to_dict() for t in result.trades]
+        signal_fills = Backtester([Scripted('script', {0: (Direction.LONG, None, None)})],
+                                  BacktestConfig(fill='signal')).run(bars)
+        assert signal_fills.trades[0].entry_price == 100.0  # The signal bar's close
+
+    def test_per_symbol_fees(self, monkeypatch):
+        """Test commission plus exchange fees per symbol, charged on both sides of every trade"""
+        fees = CommissionModel(commissions={'MNQ': 0.25}, exchange_fees={'MNQ': 0.37},
+                               default_commission=1.0, default_exchange_fee=0.0)
+        assert fees.cost('MNQZ5', 2) == pytest.approx(1.24) and fees.round_turn('ES') == 2.0
+        script = {0: (Direction.LONG, None, None), 2: (Direction.FLAT, None, None)}
+        config = BacktestConfig(commission=5.0, fees=fees)  # fees replace the flat commission
+        trades = [Backtester([Scripted('script', script)], config).run(
+            make_bars([100.0, 100.0, 101.0, 101.0], symbol=symbol)).trades[0] for symbol in ('MNQ', 'MES')]
+        assert [t.commission for t in trades] == pytest.approx([1.24, 2.0])
+        assert [t.pnl for t in trades] == pytest.approx([2.0 - 1.24, 5.0 - 2.0])
+
+        monkeypatch.setenv('COMMISSIONS', 'MNQ:0.25')
+        monkeypatch.setenv('EXCHANGE_FEES', 'MNQ:0.37,ES:1.38')
+        assert BacktestConfig.from_env().fees.get_schedule()['symbols']['ES'] == {
+            'commission': 0.0, 'exchange_fee': 1.38, 'round_turn': 2.76}
+        monkeypatch.setenv('COMMISSIONS', 'MNQ')
+        with pytest.raises(ValueError):
+            CommissionModel()
+
+    def test_live_parity_and_tick_stops(self):
+        """Test the backtest sees exactly the signals the live engine emits, and ticks trigger stops"""
+        closes = [104.0, 103, 102, 101, 100, 101, 102, 103, 104, 102, 99, 96, 93, 90, 92, 95, 98]
+        bars = make_bars(closes) + make_bars(closes, symbol='MES')
+        params = EmaCrossParams(fast_period=2, slow_period=4, atr_period=3, stop_atr=1.0, target_atr=0)
+
+        live = StrategyEngine()
+        live.add_strategy(EmaCrossStrategy('ema', params))
+        live_signals = [s for bar in sorted(bars, key=lambda b: b.timestamp) for s in live.on_bar(bar)]
+        result = Backtester([EmaCrossStrategy('ema', params)], BacktestConfig()).run(bars)
+        assert result.signals == len(live_signals) and result.bars == len(bars)
+        assert {t.symbol for t in result.trades} == {'MNQ', 'MES'}
+        assert all(t.entry_time > T0 for t in result.trades)
+
+        # A trade print through the long's stop between bars closes it before the next bar
+        ticks = [Trade(symbol='MNQ', timestamp=T0 + timedelta(minutes=42), price=90.0, size=1)]
+        with_ticks = Backtester([EmaCrossStrategy('ema', params, symbols=['MNQ'])], BacktestConfig()).run(
+            make_bars(closes), ticks)
+        long = next(t for t in with_ticks.trades if t.direction is Direction.LONG)
+        assert long.exit_reason == 'stop' and long.exit_time == ticks[0].timestamp and with_ticks.ticks == 1
+
+
+def make_signal(strategy_id='ema', symbol='MNQ', direction=Direction.LONG, confidence=0.8):
+    return Signal(strategy_id=strategy_id, symbol=symbol, direction=direction, price=100.0, timestamp=T0,
+                  confidence=confidence)
+
+
+class TestSignalBus:
+    """Test signal fan-out, per-subscriber filtering and isolation"""
+
+    def test_filtered_inline_subscribers(self):
+        """Test each subscriber only sees matching signals and a failing one doesn't stop the rest"""
+        bus = SignalBus()
+        everything, entries, confident_mnq = [], [], []
+        bus.subscribe(everything.append, name='all')
+        bus.subscribe(entries.append, directions=['long', 'short'], strategies=['ema'])
+        bus.subscribe(confident_mnq.append, symbols=['mnq'], min_confidence=0.7)
+        bus.subscribe(lambda signal: 1 / 0, name='broken', where=lambda signal: signal.symbol == 'MES')
+
+        signals = [make_signal(), make_signal(direction=Direction.FLAT), make_signal('macd', 'MES'),
+                   make_signal(confidence=0.5)]
+        assert [bus.publish(signal) for signal in signals] == [3, 2, 2, 2]
+        assert everything == signals
+        assert entries == [signals[0], signals[3]]
+        assert confident_mnq == signals[:2]
+        stats = bus.get_stats()
+        assert stats['published'] == 4 and stats['subscriber_errors'] == 1
+        broken = stats['subscribers']['broken']
+        assert broken['errors'] == 1 and 'ZeroDivisionError' in broken['last_error']
+        with pytest.raises(ValueError):
+            bus.subscribe(print, directions=['sideways'])
+
+    @pytest.mark.asyncio
+    async def test_queued_consumers_and_streams(self):
+        """Test engine signals reach a queued consumer and an async stream through the bus"""
+        bus = SignalBus(capacity=10)
+        engine = StrategyEngine(bus=bus)
+        engine.add_strategy(EmaCrossStrategy('ema', EmaCrossParams(fast_period=2, slow_period=4)))
+        routed = []
+
+        async def route(signal):
+            await asyncio.sleep(0)
+            routed.append(signal)
+
+        bus.add_consumer('orders', route, symbols=['MNQ'])
+        stream = bus.stream(directions=['short'])
+        inline = []
+        unsubscribe = engine.on_signal(inline.append, strategies=['other'])
+
+        closes = [104.0, 103, 102, 101, 100, 101, 102, 103, 104, 102, 99, 96]
+        for bar in make_bars(closes) + make_bars(closes, symbol='MES'):
+            engine.on_bar(bar)
+        shorts = [await asyncio.wait_for(stream.__anext__(), 1) for _ in range(2)]
+        await asyncio.sleep(0.01)
+        assert [s.direction for s in routed] == [Direction.LONG, Direction.SHORT]
+        assert [s.symbol for s in shorts] == ['MNQ', 'MES'] and inline == []
+        consumers = engine.get_stats()['bus']['consumers']
+        assert consumers['orders']['filter']['symbols'] == ['MNQ']
+        unsubscribe()
+        await bus.close()
+        assert len(bus) == 0
+
+
+class TestSignalThrottle:
+    """Test entry cooldowns, duplicate suppression and concurrency limits"""
+
+    def test_cooldown_and_duplicates_through_engine(self):
+        """Test re-fired entries are dropped before the bus and exits always pass"""
+        engine = StrategyEngine(max_errors=0)
+        script = {0: (Direction.LONG, None, None), 1: (Direction.LONG, None, None), 2: (Direction.FLAT, None, None),
+                  3: (Direction.LONG, None, None), 4: (Direction.LONG, None, None), 6: (Direction.SHORT, None, None)}
+        engine.add_strategy(Scripted('a', script))
+        throttle = SignalThrottle(min_interval=0, max_concurrent=0)
+        throttle.set_rule('a', 'mnq', min_interval=1200)
+        assert throttle.rule_for('a', 'MES').min_interval == 0
+        detach = throttle.attach(engine)
+        published = []
+        engine.on_signal(published.append)
+
+        emitted = [s for bar in make_bars([100.0] * 7) for s in engine.on_bar(bar)]
+        # Bar 1 duplicates the open long, bar 3 is 15 min after the last entry, bar 6 reverses after 10 min
+        assert [(s.direction, (s.timestamp - T0).seconds // 60) for s in published] == \
+            [(Direction.LONG, 0), (Direction.FLAT, 10), (Direction.LONG, 20)]
+        assert emitted == published
+        stats = throttle.get_stats()
+        assert stats['blocked'] == {'cooldown': 2, 'duplicate': 1, 'max_concurrent': 0}
+        assert stats['open'] == {'a/MNQ': 1} and engine.get_stats()['signals_blocked'] == 3
+        detach()
+        assert engine.get_stats()['gates'] == []
+
+    def test_concurrency_limits_and_position_provider(self):
+        """Test global and per-strategy caps, reversals, mark_flat and broker positions"""
+        throttle = SignalThrottle(min_interval=0, max_concurrent=2, max_per_strategy=1)
+
+        def offer(strategy_id, symbol, direction=Direction.LONG):
+            signal = make_signal(strategy_id, symbol, direction)
+            allowed = throttle.allows(signal)
+            if allowed:
+                throttle.record(signal)
+            return allowed
+
+        assert offer('a', 'MNQ') and not offer('a', 'MES')  # One open entry per strategy
+        assert offer('b', 'MES') and not offer('c', 'ES')  # Two overall
+        assert offer('a', 'MNQ', Direction.SHORT)  # Reversal doesn't open a new slot
+        throttle.mark_flat('a', 'MNQ')
+        assert offer('c', 'ES') and throttle.get_stats()['blocked']['max_concurrent'] == 2
+
+        positions = {('a', 'MNQ'): 2}
+        broker = SignalThrottle(min_interval=0, position_provider=lambda s, sym: positions.get((s, sym), 0))
+        assert not broker.allows(make_signal('a', 'MNQ')) and broker.allows(make_signal('a', 'MNQ', Direction.SHORT))
+        positions.clear()
+        assert broker.allows(make_signal('a', 'MNQ'))
+
+
+
+class TestParameterUpdates:
+    """Test parameter changes on running strategies"""
+
+    def test_staged_until_next_bar_keeping_indicators(self):
+        """Test validation, staging, the switch at the next bar and indicator continuity"""
+        engine = StrategyEngine()
+        strategy = engine.add_strategy(EmaCrossStrategy('ema', EmaCrossParams(fast_period=2, slow_period=4)))
+        bars = make_bars([100.0, 101, 102, 103, 104, 105])
+        engine.on_bars(bars[:5])
+        before = strategy.get_state('MNQ')
+
+        params = engine.update_params('ema', {'fast_period': 3, 'confirmation_bars': 2})
+        assert params.fast_period == 3 and strategy.params.fast_period == 2  # Staged only
+        assert engine.get_params('ema')['fast_period'] == 3
+        assert engine.get_stats()['strategies']['ema']['params_pending']
+        # Invalid changes stage nothing (slow 3 isn't above the staged fast 3)
+        with pytest.raises(ValueError):
+            engine.update_params('ema', {'slow_period': 3})
+        with pytest.raises(ValueError):
+            engine.update_params('ema', {'no_such_param': 1})
+        with pytest.raises(KeyError):
+            engine.update_many({'ema': {'slow_period': 6}, 'missing': {}})
+        assert engine.get_params('ema')['slow_period'] == 4
+
+        engine.on_bar(bars[5])
+        assert (strategy.params.fast_period, strategy.params.confirmation_bars) == (3, 2)
+        state = strategy.get_state('MNQ')
+        # Both EMAs continue from their values (new fast alpha 0.5), no second warm-up
+        assert state['fast_ema'] == pytest.approx(before['fast_ema'] + 0.5 * (105 - before['fast_ema']))
+        assert state['slow_ema'] == pytest.approx(before['slow_ema'] + 0.4 * (105 - before['slow_ema']))
+        stats = engine.get_stats()['strategies']['ema']
+        assert stats['param_updates'] == 1 and not stats['params_pending']
+
+        sma = SMA(3)
+        for value in (1.0, 2.0, 3.0, 4.0):
+            sma.update(value)
+        sma.set_period(2)
+        assert sma.value == 3.5 and sma.update(6.0) == 5.0
+
+    def test_config_file_reload(self, tmp_path):
+        """Test a watched file stages changed parameters, toggles strategies and is rejected as a whole"""
+        engine = StrategyEngine()
+        ema = engine.add_strategy(EmaCrossStrategy('ema', EmaCrossParams(fast_period=2, slow_period=4)))
+        engine.add_strategy(MacdMomentumStrategy('macd'))
+        path = tmp_path / 'strategies.json'
+        path.write_text(json.dumps({'strategies': {'ema': {'fast_period': 2, 'slow_period': 6},
+                                                   'macd': {'enabled': False}}}))
+        watcher = StrategyConfigWatcher(engine, str(path), interval=60)
+        assert watcher.check() and not watcher.check()  # An unchanged file isn't read again
+        assert engine.get_params('ema')['slow_period'] == 6
+        assert not engine.get_stats()['strategies']['macd']['enabled']
+
+        path.write_text(json.dumps({'ema': {'slow_period': 8}, 'macd': {'fast_period': 50}}))
+        assert not watcher.check() and watcher.errors == 1
+        assert engine.get_params('ema')['slow_period'] == 6
+        path.write_text('{"ema": ')
+        assert not watcher.check() and watcher.errors == 2
+
+        path.write_text(json.dumps({'ema': {'slow_period': 8}, 'macd': {'enabled': True}}))
+        assert watcher.check() and watcher.reloads == 2
+        assert engine.get_stats()['strategies']['macd']['enabled'] and ema.params.slow_period == 4
+        engine.on_bar(make_bars([100.0])[0])
+        assert ema.params.slow_period == 8
+
+
+
+class TestSessionFilter:
+    """Test session-window gating and flatten-before-close"""
+
+    ET = ZoneInfo('America/New_York')
+
+    def signal(self, strategy_id, day, hour, minute, direction=Direction.LONG):
+        timestamp = datetime(2025, 11, day, hour, minute, tzinfo=self.ET)
+        return Signal(strategy_id=strategy_id, symbol='MNQ', direction=direction, price=100.0, timestamp=timestamp)
+
+    def test_windows_and_skips(self):
+        """Test windows, skip-open, RTH-only and the closed market"""
+        sessions = SessionFilter(SessionRule())
+        sessions.set_rule('ema', SessionRule.parse_windows('09:30-11:30,14:00-15:30', skip_open=5))
+        sessions.set_rule('rth', SessionRule(rth_only=True, skip_close=10))
+        allowed = {(h, m): sessions.allows(self.signal('ema', 19, h, m))
+                   for h, m in [(9, 32), (9, 40), (12, 0), (14, 30), (15, 45)]}
+        assert allowed == {(9, 32): False, (9, 40): True, (12, 0): False, (14, 30): True, (15, 45): False}
+        assert sessions.allows(self.signal('ema', 19, 12, 0, Direction.FLAT))
+        assert sessions.allows(self.signal('other', 19, 12, 0))  # No rule, no default
+        assert not sessions.allows(self.signal('other', 19, 17, 30))  # Maintenance break
+        assert not sessions.allows(self.signal('other', 22, 12, 0))  # Saturday
+        assert not sessions.allows(self.signal('rth', 19, 8, 0)) and sessions.allows(self.signal('rth', 19, 10, 0))
+        assert not sessions.allows(self.signal('rth', 19, 15, 55))  # Last 10 minutes of RTH
+        assert sessions.get_stats()['blocked'] == {"closed": 2, "rth": 1, "window": 2, "open": 1, "close": 1}
+        assert SessionRule.parse_windows('18:00-02:00').in_windows(time(1, 0))
+        with pytest.raises(ValueError):
+            SessionRule(anchor='globex').validate()
+
+    def test_flatten_before_close_in_backtest(self):
+        """Test the clock flattens an open position before the close and later entries are dropped"""
+        start = datetime(2025, 11, 19, 15, 0, tzinfo=self.ET)
+        bars = [Bar(symbol='MNQ', timeframe='5m', timestamp=start + timedelta(minutes=5 * i), open=100.0 + i,
+                    high=101.0 + i, low=99.0 + i, close=100.0 + i) for i in range(12)]
+        sessions = SessionFilter(SessionRule())
+        sessions.set_rule('script', SessionRule(flatten_before=15))
+        script = {0: (Direction.LONG, None, None), 10: (Direction.LONG, None, None)}
+        backtester = Backtester([Scripted('script', script)], BacktestConfig(), filters=[sessions])
+        result = backtester.run(bars)
+        # FLAT at the 15:45 bar close, filled at the next open; the 15:55 entry is inside the flatten window
+        assert [(t.entry_price, t.exit_price, t.exit_reason) for t in result.trades] == [(101.0, 109.0, 'signal')]
+        assert result.trades[0].exit_time == datetime(2025, 11, 19, 15, 45, tzinfo=self.ET)
+        assert sessions.get_stats()['blocked']['close'] == 1 and sessions.get_stats()['open'] == {}
+        assert [t.to_dict() for t in backtester.run(bars).trades] == [t.to_dict() for t in result.trades]
+
+
+class TestMarketContext:
+    """Test higher-timeframe bars and shared indicators for strategies"""
+
+    def test_engine_fed_context_without_lookahead(self):
+        """Test a 5m strategy only sees the 15m bars closed so far, and indicators are shared"""
+
+        class Filtered:
+            strategy_id = 'mtf'
+            timeframes = ['5m']
+
+            def __init__(self):
+                self.seen = []
+
+            def on_bar(self, bar):
+                self.seen.append((self.context.sma(bar.symbol, '15m', 1), len(self.context.bars(bar.symbol, '15m'))))
+
+        engine = StrategyEngine()
+        strategy = Filtered()
+        engine.add_strategy(strategy)
+        five = make_bars([100.0, 101, 102, 103, 104, 105])
+        fifteen = [Bar(symbol='MNQ', timeframe='15m', timestamp=T0 + timedelta(minutes=15 * i), open=c, high=c + 1,
+                       low=c - 1, close=c) for i, c in enumerate([100.0, 106.0])]
+        # Replay order: a 15m bar closes together with the last 5m bar of its period
+        for bar in five[:3] + fifteen[:1] + five[3:] + fifteen[1:]:
+            engine.on_bar(bar)
+        assert strategy.seen == [(None, 0)] * 3 + [(100.0, 1)] * 3
+        assert engine.context.sma('MNQ', '15m', 1) == 106.0
+        assert engine.context.trend('MNQ', '5m', 2, 3) == 1
+        assert engine.context.get_stats()['indicators'] == 3
+
+    def test_aggregator_backed_context(self):
+        """Test timeframes are registered on the aggregator on first use and seeded by the loader"""
+        history = [Bar(symbol='MNQ', timeframe='1h', timestamp=T0 - timedelta(hours=5 - i), open=c, high=c + 2,
+                       low=c - 2, close=c) for i, c in enumerate([100.0, 98, 99, 97, 96])]
+        loads = []
+
+        def loader(symbol, timeframe):
+            loads.append((symbol, timeframe))
+            return history if timeframe == '1h' else []
+
+        aggregator = BarAggregator()
+        engine = StrategyEngine(context=MarketContext(loader=loader))
+        engine.attach(aggregator)
+        assert engine.context.aggregator is aggregator
+        assert engine.context.trend('mnq', '1h', 2, 3) == -1 and engine.context.atr('MNQ', '1h', 3) is not None
+        assert engine.context.bars('MNQ', '1h', 2) == history[-2:]
+        assert engine.context.bars('MNQ', '15m') == [] and '15m' in aggregator.symbol_timeframes['MNQ']
+        assert loads == [('MNQ', '1h'), ('MNQ', '15m')]  # Once per timeframe
+
+
+class TestPortfolioCoordinator:
+    """Test cross-strategy net contract, margin and conflict limits"""
+
+    def signal(self, strategy_id, direction=Direction.LONG, symbol='MNQ'):
+        return Signal(strategy_id=strategy_id, symbol=symbol, direction=direction, price=100.0, timestamp=T0)
+
+    def test_net_contracts_and_margin(self):
+        """Test entries that would grow exposure past the limits are dropped and reductions pass"""
+        portfolio = PortfolioCoordinator(max_net_contracts=3, max_margin=5000, margins={'MNQZ5': 1000, 'MES': 1500},
+                                         symbol_limits={}, preempt=True, default_quantity=1)
+        portfolio.set_allocation('orb', quantity=2)
+        engine = StrategyEngine()
+        portfolio.attach(engine)
+        assert engine.publish(self.signal('orb')) and engine.publish(self.signal('ema'))
+        assert not engine.publish(self.signal('macd'))  # 4 MNQ > 3
+        assert portfolio.net_position('MNQH6') == 3 and portfolio.margin_used() == 3000
+        assert engine.publish(self.signal('vwap', symbol='MES'))  # 3000 + 1500
+        assert not engine.publish(self.signal('macd', symbol='MES'))  # 6000 > 5000
+        assert engine.publish(self.signal('orb', Direction.FLAT))
+        assert not engine.publish(self.signal('orb', symbol='MES'))  # MES 3 contracts, MNQ 1: 5500 > 5000
+        assert portfolio.get_stats()['blocked'] == {"opposing": 0, "max_net": 1, "max_margin": 2}
+        assert portfolio.get_stats()['net'] == {'MES': 1, 'MNQ': 1}
+        with pytest.raises(ValueError):
+            portfolio.set_allocation('orb', quantity=0)
+
+    def test_opposing_positions_and_priority(self):
+        """Test equal priority keeps the first position and higher priority flattens lower ones"""
+        portfolio = PortfolioCoordinator(max_net_contracts=0, max_margin=0, margins={}, symbol_limits={},
+                                         preempt=True, default_quantity=1)
+        portfolio.set_allocation('orb', priority=10)
+        engine = StrategyEngine()
+        published = []
+        engine.on_signal(published.append)
+        portfolio.attach(engine)
+        assert engine.publish(self.signal('ema')) and engine.publish(self.signal('macd'))
+        assert not engine.publish(self.signal('vwap', Direction.SHORT))  # Same priority as the longs
+        assert engine.publish(self.signal('orb', Direction.SHORT))
+        assert [(s.strategy_id, s.direction) for s in published[2:]] == [
+            ('ema', Direction.FLAT), ('macd', Direction.FLAT), ('orb', Direction.SHORT)]
+        assert published[2].reason == 'Preempted by orb'
+        assert portfolio.net_position('MNQ') == -1 and portfolio.get_stats()['preemptions'] == 2
+        assert not engine.publish(self.signal('ema'))  # Now opposes the higher priority
+
+        cautious = PortfolioCoordinator(max_net_contracts=0, max_margin=0, margins={}, symbol_limits={},
+                                        preempt=False, default_quantity=1)
+        cautious.set_allocation('orb', priority=10)
+        cautious.record(self.signal('ema'))
+        assert not cautious.allows(self.signal('orb', Direction.SHORT))
+        cautious.mark_flat('ema', 'MNQ')
+        assert cautious.allows(self.signal('orb', Direction.SHORT))
+
+
+class TestTradingCore:
+    """Test the tick-to-order pipeline and its controls"""
+
+    def trades(self, prices, start=0):
+        return [Trade(symbol='MNQ', timestamp=T0 + timedelta(minutes=start + i), price=p, size=1)
+                for i, p in enumerate(prices)]
+
+    def test_ticks_to_orders(self):
+        """Test bar closes run strategies inline and signals become entry, reversal and exit orders"""
+        orders = []
+        script = Scripted('script', {0: (Direction.LONG, 99.0, 102.0), 1: (Direction.SHORT, None, None),
+                                     2: (Direction.FLAT, None, None)})
+        script.timeframes = frozenset(['1m'])
+        portfolio = PortfolioCoordinator(max_net_contracts=0, max_margin=0, margins={}, symbol_limits={},
+                                         preempt=True, default_quantity=1)
+        portfolio.set_allocation('script', quantity=2)
+        core = TradingCore(submit=lambda **order: orders.append(order) or {"success": True}, account_id='42',
+                           strategies=[script], aggregator=BarAggregator(default_timeframes=['1m']),
+                           portfolio=portfolio)
+        for event in self.trades([100.0, 100.0, 101.0, 100.5]):
+            core.on_event(event)
+        assert [(o['side'], o['quantity'], o['stop_loss_ticks'], o['take_profit_ticks']) for o in orders] == [
+            ('BUY', 2, 4, 8), ('SELL', 2, None, None), ('SELL', 2, None, None), ('BUY', 2, None, None)]
+        assert {o['account_id'] for o in orders} == {'42'} and core.executor.positions() == {}
+        stats = core.get_stats()
+        assert stats['events'] == 4 and stats['latency_ms']['samples'] == 3
+        assert stats['executor']['orders_submitted'] == 3
+
+    def test_controls_and_failed_orders(self):
+        """Test pause blocks entries, manual flatten and a rejected order leaving no position behind"""
+        orders, reject = [], []
+
+        def submit(**order):
+            orders.append(order)
+            return {"error": "rejected"} if reject else {"success": True}
+
+        script = Scripted('script', {0: (Direction.LONG, None, None), 1: (Direction.LONG, None, None),
+                                     2: (Direction.SHORT, None, None), 3: (Direction.LONG, None, None)})
+        script.timeframes = frozenset(['1m'])
+        core = TradingCore(submit=submit, strategies=[script], aggregator=BarAggregator(default_timeframes=['1m']))
+        core.pause()
+        for event in self.trades([100.0, 100.0]):
+            core.on_event(event)
+        assert orders == [] and core.get_stats()['engine']['signals_blocked'] == 1
+        core.resume()
+        core.on_event(self.trades([100.0], start=2)[0])
+        assert core.executor.positions() == {'MNQ': {'script': 1}}
+        assert core.flatten() == 1 and orders[-1]['side'] == 'SELL' and core.executor.positions() == {}
+        reject.append(True)
+        core.on_event(self.trades([100.0], start=3)[0])
+        assert core.executor.positions() == {} and core.executor.get_stats()['orders_failed'] == 1
+        core.stop()
+        core.on_event(self.trades([100.0], start=4)[0])
+        assert len(orders) == 3  # Detached: bar 3 reaches no strategy
+
+    def test_modify_bracket_after_entry(self):
+        """Test bracket legs of a filled entry are found and repriced in ticks from the fill"""
+        modified = []
+
+        async def find_legs(order_id, account_id=None):
+            if order_id != '501':
+                return {"error": f"Order {order_id} not found among filled orders"}
+            return {'parent': {'id': 501, 'side': 1, 'filledPrice': 21000.25, 'contractId': 'CON.F.US.MNQ.Z25'},
+                    'symbol': 'MNQ', 'stop': {'id': 502, 'type': 4}, 'target': {'id': 503, 'type': 1}}
+
+        def modify(order_id, new_price=None, account_id=None, order_type=None):
+            modified.append((order_id, new_price, order_type))
+            return {"error": "rejected"} if order_id == '503' else {"success": True}
+
+        executor = OrderExecutor(lambda **order: {"success": True, "orderId": 501}, account_id='42',
+                                 find_legs=find_legs, modify=modify)
+        executor.on_signal(make_signal(direction=Direction.SHORT))
+        order_id = executor.entry_order('ema', 'MNQ')
+        assert order_id == '501'
+        result = asyncio.run(executor.modify_bracket(order_id, stop_ticks=8))
+        assert result['stop'] == {'order_id': '502', 'price': 21002.25, 'ticks': 8} and 'target' not in result
+        assert modified == [('502', 21002.25, 4)]  # Short entry: stop above the fill
+        result = asyncio.run(executor.modify_bracket(order_id, stop_ticks=4, target_ticks=10))
+        assert not result['success'] and 'rejected' in result['error'] and result['stop']['price'] == 21001.25
+        assert modified[-1] == ('503', 20997.75, 1)
+        assert 'not found' in asyncio.run(executor.modify_bracket('999', stop_ticks=4))['error']
+        assert 'error' in asyncio.run(executor.modify_bracket(order_id))
+        assert executor.get_stats()['brackets_modified'] == 2
+        executor.on_signal(make_signal(direction=Direction.FLAT))
+        assert executor.entry_order('ema', 'MNQ') is None
+
+    @pytest.mark.asyncio
+    async def test_status_unknown_order_keeps_position(self):
+        """Test an order the broker may have accepted is not rolled back like a rejection"""
+        results = [{"error": "Order status unknown: bad orderId", "status_unknown": True},
+                   {"error": "Order failed: Invalid size"}]
+
+        def submit(**order):
+            return results.pop(0)
+
+        executor = OrderExecutor(submit, account_id='42')
+        executor.on_signal(make_signal())
+        assert executor.position('ema', 'MNQ') == 1  # Kept for reconciliation to settle
+        executor.on_signal(make_signal(direction=Direction.FLAT))
+        assert executor.position('ema', 'MNQ') == 1  # A real rejection is undone
+        stats = executor.get_stats()
+        assert (stats['orders_unknown'], stats['orders_failed']) == (1, 1) and 'Invalid size' in stats['last_error']
+
+        async def submit_async(**order):
+            return {"error": "Order status unknown: bad orderId", "status_unknown": True}
+
+        executor = OrderExecutor(submit_async, account_id='42')
+        executor.on_signal(make_signal())
+        for _ in range(3):
+            await asyncio.sleep(0)
+        assert executor.position('ema', 'MNQ') == 1 and executor.get_stats()['orders_unknown'] == 1
+
+    @pytest.mark.asyncio
+    async def test_scale_in_and_out_follow_position_size(self):
+        """Test laddered entries and partial exits stay consistent as the position changes"""
+        placed, cancelled, resized = [], [], []
+
+        def submit(**order):
+            placed.append(order)
+            return {"success": True, "orderId": 600 + len(placed)}
+
+        async def find_legs(order_id, account_id=None):
+            return {'parent': {'id': 601, 'side': 0, 'filledPrice': 21000.0}, 'symbol': 'MNQ',
+                    'stop': {'id': 590, 'type': 4, 'size': 3}, 'target': {'id': 591, 'type': 1}}
+
+        def modify(order_id, new_quantity=None, account_id=None):
+            resized.append((order_id, new_quantity))
+            return {"success": True}
+
+        async def cancel(order_id, account_id=None):
+            cancelled.append(order_id)
+            return {"success": True}
+
+        async def settle():
+            for _ in range(5):
+                await asyncio.sleep(0)
+
+        executor = OrderExecutor(submit, account_id='42', quantity_for=lambda strategy_id: 3,
+                                 find_legs=find_legs, modify=modify, cancel=cancel)
+        executor.on_signal(make_signal())  # Bracketed entry 601, 3 contracts
+        assert 'exceed' in (await executor.scale_out('ema', 'MNQ', [(2, 4), (2, 8)]))['error']
+        result = await executor.scale_out('ema', 'MNQ', [(1, 4), (2, 8)])
+        assert result['entry_price'] == 21000.0 and cancelled == ['591']  # Ladder replaces the bracket target
+        assert [(t['order_id'], t['quantity'], t['price']) for t in result['targets']] == [
+            ('602', 1, 21001.0), ('603', 2, 21002.0)]
+        assert placed[1]['side'] == 'SELL' and placed[1]['order_type'] == 'limit'
+        executor.set_position('ema', 'MNQ', 2)  # One contract closed by hand
+        await settle()
+        assert resized == [('603', 1), ('590', 2)]  # Farthest target trimmed, stop follows
+        assert executor.on_fill({'id': 602, 'fillVolume': 1, 'fillPrice': 21001.0})
+        await settle()
+        assert executor.position('ema', 'MNQ') == 1 and resized[-1] == ('590', 1)
+        executor.on_fill({'id': 603, 'fillVolume': 1, 'fillPrice': 21002.0})
+        await settle()
+        assert executor.position('ema', 'MNQ') == 0 and executor.get_stats()['ladder_orders'] == 0
+
+        result = await executor.scale_in('macd', 'MNQ', 'SELL', 21000.0, [(1, 0), (2, 4)])
+        assert [(e['quantity'], e['price']) for e in result['entries']] == [(1, 21000.0), (2, 21001.0)]
+        assert executor.position('macd', 'MNQ') == 0  # Nothing filled yet
+        assert executor.on_fill({'id': 604, 'fillVolume': 1, 'fillPrice': 21000.0})
+        assert not executor.on_fill({'id': 999, 'fillVolume': 1})
+        assert executor.position('macd', 'MNQ') == -1
+        executor.set_position('macd', 'MNQ', 0)  # Stopped out: the rest of the ladder goes
+        await settle()
+        assert cancelled[-1] == '605' and executor.get_stats()['ladder_orders'] == 0
+        assert executor.get_stats()['ladders_rebalanced'] == 3
+
+    @pytest.mark.asyncio
+    async def test_partial_fills_apply_cumulative_fill_volume(self):
+        """Test repeated updates for one order only apply the contracts filled since the last"""
+        def submit(**order):
+            return {"success": True, "orderId": 700}
+
+        executor = OrderExecutor(submit, account_id='42')
+        await executor.scale_in('ema', 'MNQ', 'BUY', 21000.0, [(3, 0)])
+        assert executor.on_fill({'id': 700, 'status': 1, 'fillVolume': 1, 'fillPrice': 21000.0})
+        assert executor.position('ema', 'MNQ') == 1
+        assert executor.on_fill({'id': 700, 'status': 1, 'fillVolume': 1, 'fillPrice': 21000.0})  # Repeat
+        assert executor.position('ema', 'MNQ') == 1
+        assert executor.on_fill({'id': 700, 'status': 2, 'fillVolume': 3, 'fillPrice': 21001.0})
+        assert executor.position('ema', 'MNQ') == 3 and executor.get_stats()['ladder_orders'] == 0
+        assert executor._ladders[('ema', 'MNQ')]['avg_price'] == 21001.0
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,197 @@
+"""
+Unit tests for strategy optimization tooling (core.strategy_engine runner and walk-forward)
+"""
+
+import pytest
+import math
+import os
+import sys
+from datetime import datetime, timedelta, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar
+from core.strategy_engine import (
+    BacktestConfig, BacktestRunner, EmaCrossParams, EmaCrossStrategy, GeneticOptimizer, GeneticParams, GridSearch,
+    ParameterSpace, Range, WalkForward,
+)
+
+T0 = datetime(2025, 11, 3, 14, 0, tzinfo=timezone.utc)
+
+
+def make_ema(params):
+    """Module-level factory so worker processes can unpickle it"""
+    return EmaCrossStrategy('ema', EmaCrossParams(atr_period=5, **params), symbols=['MNQ'])
+
+
+def wave_bars(days=12, per_day=24, symbol='MNQ'):
+    """Hourly bars of two overlaid waves, so different EMA pairs win in different periods"""
+    bars = []
+    for i in range(days * per_day):
+        close = 20000 + 40 * math.sin(i / 9) + 15 * math.sin(i / 2.5) + i * 0.3
+        bars.append(Bar(symbol=symbol, timeframe='1h', timestamp=T0 + timedelta(hours=i), open=close - 1,
+                        high=close + 4, low=close - 4, close=close, volume=100))
+    return bars
+
+
+CANDIDATES = [{'fast_period': f, 'slow_period': s} for f, s in [(2, 5), (3, 8), (5, 13), (8, 21)]]
+
+
+class TestBacktestRunner:
+    """Test running parameter sets in-process and in worker processes"""
+
+    def test_parallel_matches_serial(self):
+        """Test worker processes produce the same results, failures are isolated and ranked last"""
+        bars = wave_bars(days=4)
+        params = CANDIDATES + [{'fast_period': 9, 'slow_period': 3}]  # Invalid: fast >= slow
+        with BacktestRunner(make_ema, bars, BacktestConfig(commission=0.5), workers=1) as serial:
+            expected = serial.run(params)
+        with BacktestRunner(make_ema, bars, BacktestConfig(commission=0.5), workers=2) as parallel:
+            results = parallel.run(params)
+        assert [r.stats for r in results] == [r.stats for r in expected]
+        assert all(r.error is None and r.stats['trades'] > 0 for r in results[:-1])
+        assert 'ValueError' in results[-1].error and results[-1].score('net_pnl') == -math.inf
+
+        # Lambdas can't reach worker processes: the runner falls back to in-process
+        runner = BacktestRunner(lambda p: make_ema(p), bars, BacktestConfig(), workers=4)
+        assert runner.workers == 1 and runner.run(CANDIDATES[:1])[0].stats == \
+            BacktestRunner(make_ema, bars, BacktestConfig(), workers=1).run(CANDIDATES[:1])[0].stats
+
+
+class TestGridSearch:
+    """Test parameter spaces and ranked grid search"""
+
+    def test_parameter_space(self):
+        """Test ranges, value lists, fixed values and constraints"""
+        assert Range(5, 20, 5).values() == [5, 10, 15, 20]
+        assert Range(1.0, 2.0, 0.25).values() == [1.0, 1.25, 1.5, 1.75, 2.0]
+        assert Range(0.1, 0.3, 0.1).values() == [0.1, 0.2, 0.3]
+        with pytest.raises(ValueError):
+            Range(5, 1).values()
+
+        space = ParameterSpace({'fast_period': Range(2, 6, 2), 'slow_period': {'start': 4, 'stop': 8, 'step': 4},
+                                'stop_atr': [1.0, 2.0], 'confirmation_bars': 1},
+                               constraint=lambda p: p['fast_period'] < p['slow_period'])
+        assert space.size == 12 and space.names == ['fast_period', 'slow_period', 'stop_atr', 'confirmation_bars']
+        grid = list(space.grid())
+        assert len(grid) == 8 and all(p['fast_period'] < p['slow_period'] and p['confirmation_bars'] == 1 for p in grid)
+        with pytest.raises(ValueError):
+            ParameterSpace({'fast_period': []})
+
+    def test_ranked_results(self):
+        """Test every combination runs, ranking by objective and min_trades/failures ranked last"""
+        space = ParameterSpace({'fast_period': [2, 3, 5, 40], 'slow_period': [8, 21, 50]})
+        search = GridSearch(make_ema, space, objective='sharpe', config=BacktestConfig(commission=0.5),
+                            workers=2, min_trades=2)
+        result = search.run(wave_bars(days=6))
+        assert result.evaluated == 12 and len(result.results) == 12
+        failed = [r for r in result.results if r.error]  # fast >= slow is rejected by EmaCrossParams
+        assert {r.params['fast_period'] for r in failed} == {40} and result.results[-len(failed):] == failed
+        ranked = [r for r in result.results if not r.error and r.stats['trades'] >= 2]
+        scores = [r.stats['sharpe'] for r in ranked]
+        assert scores == sorted(scores, reverse=True) and result.best is ranked[0]
+        assert result.results[:len(ranked)] == ranked
+
+        by_pnl = GridSearch(make_ema, space, objective='net_pnl', config=BacktestConfig(commission=0.5),
+                            workers=1, min_trades=2).run(wave_bars(days=6))
+        assert by_pnl.best.stats['net_pnl'] == max(r.stats['net_pnl'] for r in ranked)
+        data = result.to_dict(top=3)
+        assert data['objective'] == 'sharpe' and [r['rank'] for r in data['results']] == [1, 2, 3]
+        with pytest.raises(ValueError):
+            GridSearch(make_ema, space, max_combinations=10).run(wave_bars(days=1))
+
+
+class TestGeneticOptimizer:
+    """Test evolutionary search, caching, early stopping and progress callbacks"""
+
+    SPACE = ParameterSpace({'fast_period': Range(2, 10), 'slow_period': Range(8, 40, 2)},
+                           constraint=lambda p: p['fast_period'] < p['slow_period'])
+
+    def test_finds_grid_optimum_region(self):
+        """Test the search stays valid, is reproducible and ends close to the exhaustive optimum"""
+        bars = wave_bars(days=6)
+        config = BacktestConfig(commission=0.5)
+        progress = []
+        params = GeneticParams(population=12, generations=8, elite=2, patience=0, seed=7)
+        result = GeneticOptimizer(make_ema, self.SPACE, params=params, config=config, workers=2,
+                                  min_trades=2).run(bars, on_generation=progress.append)
+        assert [p.generation for p in progress] == list(range(1, 9)) and result.extra['stop_reason'] == 'generations'
+        assert all(r.error is None and r.params['fast_period'] < r.params['slow_period'] for r in result.results)
+        # Each parameter set is backtested once, and elitism never loses the best
+        assert result.evaluated == len({tuple(r.params.items()) for r in result.results}) == progress[-1].evaluated
+        assert result.evaluated < self.SPACE.size
+        best_scores = [p.best_score for p in progress]
+        assert best_scores == sorted(best_scores) and result.best.stats['net_pnl'] == best_scores[-1]
+
+        again = GeneticOptimizer(make_ema, self.SPACE, params=params, config=config, workers=1,
+                                 min_trades=2).run(bars)
+        assert again.best.params == result.best.params and again.evaluated == result.evaluated
+
+        grid = GridSearch(make_ema, self.SPACE, config=config, workers=2, min_trades=2).run(bars)
+        top_pnls = sorted((r.stats['net_pnl'] for r in grid.results[:10]), reverse=True)
+        assert result.best.stats['net_pnl'] >= top_pnls[-1]
+
+    def test_early_stopping_and_callback(self):
+        """Test patience, callback stop and a failing callback not breaking the search"""
+        bars = wave_bars(days=3)
+        flat = ParameterSpace({'fast_period': [3], 'slow_period': [8, 9, 10, 11, 12, 13, 14, 15]})
+        result = GeneticOptimizer(make_ema, flat, params=GeneticParams(population=3, generations=50, patience=3,
+                                                                       elite=1, seed=1), workers=1).run(bars)
+        assert result.extra['stop_reason'] in ('patience', 'exhausted') and result.extra['generations'] < 50
+        assert len(result.extra['history']) == result.extra['generations']
+
+        calls = []
+
+        def stop_after_two(progress):
+            calls.append(progress.generation)
+            return progress.generation < 2
+
+        stopped = GeneticOptimizer(make_ema, self.SPACE, params=GeneticParams(population=4, generations=20, seed=3),
+                                   workers=1).run(bars, on_generation=stop_after_two)
+        assert calls == [1, 2] and stopped.extra['stop_reason'] == 'callback'
+
+        def broken(progress):
+            raise RuntimeError("boom")
+
+        survived = GeneticOptimizer(make_ema, self.SPACE, params=GeneticParams(population=4, generations=3,
+                                                                               patience=0, seed=3),
+                                    workers=1).run(bars, on_generation=broken)
+        assert survived.extra['generations'] == 3 and survived.best is not None
+        with pytest.raises(ValueError):
+            GeneticParams(population=4, elite=4).validate()
+        with pytest.raises(ValueError):
+            GeneticOptimizer(make_ema, ParameterSpace({'fast_period': [5]}, constraint=lambda p: False),
+                             params=GeneticParams(population=4)).run(bars)
+
+
+class TestWalkForward:
+    """Test window generation and out-of-sample only aggregation"""
+
+    def test_windows_and_out_of_sample_trades(self):
+        """Test rolling/anchored windows and that only unseen-data trades are aggregated"""
+        wf = WalkForward(make_ema, CANDIDATES, train=timedelta(days=4), test=timedelta(days=2),
+                         config=BacktestConfig(commission=0.5), workers=2)
+        end = T0 + timedelta(days=12)
+        windows = wf.windows(T0, end)
+        assert [(w.train_start - T0).days for w in windows] == [0, 2, 4, 6]
+        assert windows[-1].test_end == end and windows[0].train_end == T0 + timedelta(days=4)
+        anchored = WalkForward(make_ema, CANDIDATES, train=timedelta(days=4), test=timedelta(days=2),
+                               anchored=True).windows(T0, end)
+        assert {w.train_start for w in anchored} == {T0} and anchored[-1].train_end == T0 + timedelta(days=10)
+
+        result = wf.run(wave_bars())
+        assert len(result.windows) == 4
+        for window in result.windows:
+            assert window.best_params in CANDIDATES and window.in_sample['trades'] > 0
+            assert all(window.train_end <= t.entry_time < window.test_end + timedelta(hours=1)
+                       for t in window.trades)
+        stats = result.stats()
+        assert stats['trades'] == sum(w.out_of_sample['trades'] for w in result.windows) > 0
+        assert stats['net_pnl'] == pytest.approx(sum(w.out_of_sample['net_pnl'] for w in result.windows), abs=0.05)
+        assert stats['windows'] == 4 and stats['objective'] == 'net_pnl'
+        assert result.to_dict()['windows'][0]['best_params'] == result.windows[0].best_params
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,87 @@
+"""
+Unit tests for reference-counted market hub subscriptions
+"""
+
+import pytest
+import os
+import sys
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.websocket import SignalRClient, SubscriptionManager
+
+
+class RecordingClient(SignalRClient):
+    """Disconnected client that records subscription calls instead of sending."""
+
+    def __init__(self):
+        super().__init__('wss://rtc.example.com/hubs/market')
+        self.calls = []
+
+    def subscribe(self, method, arguments=()):
+        self.calls.append(('subscribe', method, list(arguments)))
+        super().subscribe(method, arguments)
+
+    def unsubscribe(self, method, arguments=(), unsubscribe_method=None):
+        self.calls.append(('unsubscribe', unsubscribe_method, list(arguments)))
+        return super().unsubscribe(method, arguments, unsubscribe_method)
+
+
+CONTRACTS = {'MNQ': 'CON.F.US.MNQ.Z25', 'MES': 'CON.F.US.MES.Z25'}
+
+
+def resolve(symbol):
+    if symbol not in CONTRACTS:
+        raise ValueError(f"Unknown symbol {symbol}")
+    return CONTRACTS[symbol]
+
+
+class TestSubscriptionManager:
+    """Test shared subscriptions only hit the hub on first subscribe / last unsubscribe"""
+
+    def test_reference_counting(self):
+        """Test several consumers share one upstream subscription per stream"""
+        client = RecordingClient()
+        subs = SubscriptionManager(client, contract_resolver=resolve)
+
+        assert subs.subscribe_quotes('mnq') == 1
+        assert subs.subscribe_quotes('CON.F.US.MNQ.Z25') == 2  # Same contract
+        assert subs.subscribe_trades('MNQ') == 1
+        assert subs.subscribe_depth('MES') == 1
+        assert client.calls == [
+            ('subscribe', 'SubscribeContractQuotes', ['CON.F.US.MNQ.Z25']),
+            ('subscribe', 'SubscribeContractTrades', ['CON.F.US.MNQ.Z25']),
+            ('subscribe', 'SubscribeContractMarketDepth', ['CON.F.US.MES.Z25']),
+        ]
+        assert subs.ref_count('quotes', 'MNQ') == 2
+
+        client.calls.clear()
+        assert subs.unsubscribe_quotes('MNQ') == 1
+        assert client.calls == []
+        assert subs.unsubscribe_quotes('MNQ') == 0
+        assert subs.unsubscribe_quotes('MNQ') == 0  # Extra unsubscribe is a no-op
+        assert client.calls == [('unsubscribe', 'UnsubscribeContractQuotes', ['CON.F.US.MNQ.Z25'])]
+        # Only live feeds are replayed after a reconnect
+        assert client.subscriptions() == [('SubscribeContractTrades', ['CON.F.US.MNQ.Z25']),
+                                          ('SubscribeContractMarketDepth', ['CON.F.US.MES.Z25'])]
+        assert subs.active() == {'quotes': {}, 'trades': {'CON.F.US.MNQ.Z25': 1}, 'depth': {'CON.F.US.MES.Z25': 1}}
+        stats = subs.get_stats()
+        assert stats['upstream_subscribes'] == 3 and stats['upstream_unsubscribes'] == 1
+
+    def test_invalid_requests(self):
+        """Test unknown streams and unresolvable symbols raise without subscribing"""
+        client = RecordingClient()
+        subs = SubscriptionManager(client, contract_resolver=resolve)
+        with pytest.raises(ValueError):
+            subs.subscribe('bars', 'MNQ')
+        with pytest.raises(ValueError):
+            subs.subscribe_quotes('ZZZ')
+        with pytest.raises(ValueError):
+            SubscriptionManager(client).subscribe_quotes('MNQ')
+        assert SubscriptionManager(client).subscribe_quotes('CON.F.US.MNQ.Z25') == 1
+        assert client.calls == [('subscribe', 'SubscribeContractQuotes', ['CON.F.US.MNQ.Z25'])]
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,106 @@
+"""
+Unit tests for per-symbol trading switches
+"""
+
+import pytest
+import os
+import sys
+from unittest.mock import AsyncMock, MagicMock, patch
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.position_reconciler import PositionDivergence
+from core.symbol_switches import SymbolTradingSwitches
+from trading_bot import TopStepXTradingBot
+
+
+class TestSymbolTradingSwitches:
+    """Test enable/disable and persistence."""
+
+    def test_disable_blocks_and_survives_restart(self, tmp_path, monkeypatch):
+        monkeypatch.delenv('TRADING_DISABLED_SYMBOLS', raising=False)
+        path = tmp_path / 'switches.json'
+        switches = SymbolTradingSwitches(path=path)
+        assert switches.check('MNQ') is None
+
+        switches.disable('mes', reason='bad fills during roll', updated_by='test')
+        assert not switches.is_enabled('MES')
+        assert switches.check('MES') == "Trading disabled for MES: bad fills during roll"
+
+        restarted = SymbolTradingSwitches(path=path)
+        assert restarted.disabled_symbols() == ['MES']
+        restarted.enable('MES')
+        assert SymbolTradingSwitches(path=path).check('MES') is None
+
+    def test_env_disabled_symbols_do_not_override_saved_state(self, tmp_path, monkeypatch):
+        path = tmp_path / 'switches.json'
+        SymbolTradingSwitches(path=path).enable('NQ', reason='reviewed')
+        monkeypatch.setenv('TRADING_DISABLED_SYMBOLS', 'NQ, ym')
+        switches = SymbolTradingSwitches(path=path)
+        assert switches.is_enabled('NQ')
+        assert switches.disabled_symbols() == ['YM']
+
+    def test_database_preferred_with_file_fallback(self, tmp_path, monkeypatch):
+        monkeypatch.delenv('TRADING_DISABLED_SYMBOLS', raising=False)
+        path = tmp_path / 'switches.json'
+        db = MagicMock()
+        db.get_symbol_switches.return_value = {'MNQ': {'enabled': False, 'reason': 'halted'}}
+        db.save_symbol_switch.return_value = True
+        switches = SymbolTradingSwitches(db=db, path=path)
+        assert switches.check('MNQ') == "Trading disabled for MNQ: halted"
+
+        switches.disable('ES')
+        db.save_symbol_switch.assert_called_with('ES', False, None, None)
+        assert not path.exists()
+
+        db.save_symbol_switch.return_value = False  # Database write failed
+        switches.disable('RTY')
+        assert 'RTY' in path.read_text()
+
+
+
+class TestExitsWhileBlocked:
+    """Test a switched-off or killed symbol can still be reduced, never increased"""
+
+    @pytest.mark.asyncio
+    async def test_reducing_orders_pass_the_gate(self, tmp_path):
+        with patch.dict(os.environ, {'PROJECT_X_API_KEY': 'test_key', 'PROJECT_X_USERNAME': 'test_user',
+                                     'TRADING_DISABLED_SYMBOLS': ''}):
+            bot = TopStepXTradingBot(api_key='test_key', username='test_user')
+        bot.session_token = 'token'
+        bot.selected_account = {'id': 12345, 'name': 'TEST_ACCOUNT'}
+        bot.symbol_switches = SymbolTradingSwitches(path=tmp_path / 'switches.json')
+        bot.symbol_switches.disable('MNQ', reason='roll')
+        bot.get_open_positions = AsyncMock(return_value=[
+            {'id': 1, 'contractId': 'CON.F.US.MNQ.Z25', 'type': 1, 'size': 2}])
+
+        for side, quantity in (('BUY', 1), ('SELL', 3)):  # Adds to the long, or flips it short
+            result = await bot._order_gate_error('MNQ', side, quantity, '12345')
+            assert result['trading_disabled']
+        assert await bot._order_gate_error('MNQ', 'SELL', 2, '12345') is None  # Full close
+        assert (await bot._order_gate_error('MES', 'SELL', 1, '12345')) is None  # Not blocked at all
+
+        bot.kill_switch = {'reason': 'news'}
+        assert (await bot._order_gate_error('MNQ', 'BUY', 1, '12345'))['kill_switch']
+        assert await bot._order_gate_error('MNQ', 'SELL', 1, '12345') is None  # TP1 partial close
+        bot.get_open_positions.side_effect = RuntimeError('API down')
+        assert (await bot._order_gate_error('MNQ', 'SELL', 1, '12345'))['kill_switch']  # Unknown: blocked
+
+    @pytest.mark.asyncio
+    async def test_frozen_symbol_can_be_reduced(self, tmp_path):
+        with patch.dict(os.environ, {'PROJECT_X_API_KEY': 'test_key', 'PROJECT_X_USERNAME': 'test_user'}):
+            bot = TopStepXTradingBot(api_key='test_key', username='test_user')
+        bot.symbol_switches = SymbolTradingSwitches(path=tmp_path / 'switches.json')
+        bot.position_checker.frozen_symbols['MNQ'] = PositionDivergence(
+            account_id='12345', symbol='MNQ', local_qty=1, broker_qty=-2, difference=-3,
+            consecutive_checks=2, first_detected='2025-01-01T00:00:00', frozen=True)
+        bot.get_open_positions = AsyncMock(return_value=[
+            {'id': 1, 'contractId': 'CON.F.US.MNQ.Z25', 'type': 2, 'size': 2}])
+
+        assert (await bot._order_gate_error('MNQ', 'SELL', 1, '12345'))['frozen']  # Adds to the short
+        assert await bot._order_gate_error('MNQ', 'BUY', 2, '12345') is None  # Flattens the broker position
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
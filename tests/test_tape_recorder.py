@@ -0,0 +1,71 @@
+"""
+Unit tests for the time-and-sales tape recorder
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone, timedelta
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.market_events import Quote, Trade
+from core.replay_engine import load_replay_events
+from core.tape_recorder import TapeRecorder
+
+
+T0 = datetime(2025, 11, 19, 14, 30, 0, tzinfo=timezone.utc)
+
+
+class TestTapeRecorder:
+    """Test tape recording, window queries and spill-to-disk"""
+
+    def test_window_queries_use_tape_time(self, tmp_path):
+        """Test largest_trades and volume_in_window over a trailing window"""
+        tape = TapeRecorder(buffer_size=100, spill_dir=tmp_path)
+        sizes = [3, 12, 1, 7, 12, 2]
+        for i, size in enumerate(sizes):
+            tape.record('mnq', 15000.0 + i, size, 'buy' if i % 2 else 'sell', T0 + timedelta(seconds=10 * i))
+        tape.on_market_event(Trade('MES', T0, 5000.0, 50, 'buy'))
+        tape.on_market_event(Quote('MNQ', T0, bid=1.0, ask=1.25))
+
+        largest = tape.largest_trades(2, window=30, symbol='MNQ')
+        assert [(t.size, t.price) for t in largest] == [(12, 15004.0), (7, 15003.0)]
+        assert [t.size for t in tape.largest_trades(2, symbol='MNQ')] == [12, 12]
+        assert tape.largest_trades(1)[0].symbol == 'MES'
+        assert tape.volume_in_window(timedelta(seconds=20), symbol='MNQ') == 21
+        assert tape.volume_in_window(20, symbol='MNQ', side='buy') == 9
+        assert tape.volume_in_window(60, symbol='MNQ', now=T0 + timedelta(seconds=15)) == 15
+        assert tape.symbols() == ['MES', 'MNQ']
+
+    def test_evicted_prints_spill_to_disk(self, tmp_path):
+        """Test prints pushed out of the ring buffer are written and queryable"""
+        tape = TapeRecorder(buffer_size=3, spill_dir=tmp_path, flush_interval=60)
+        for i in range(5):
+            tape.record('MNQ', 100.0 + i, i + 1, 'buy', T0 + timedelta(seconds=i))
+        assert [t.size for t in tape.prints('MNQ')] == [3, 4, 5]
+        assert tape.get_stats()['pending_spill'] == 2
+
+        assert tape.volume_in_window(10, symbol='MNQ', include_spilled=True) == 15
+        spill_file = tmp_path / 'MNQ' / 'tape-20251119.jsonl'
+        assert spill_file.exists()
+        spilled = load_replay_events(spill_file)
+        assert [(e.price, e.size) for e in spilled] == [(100.0, 1), (101.0, 2)]
+        assert tape.get_stats()['spilled'] == 2
+
+    def test_spill_disabled_counts_evictions(self, tmp_path):
+        """Test evictions are counted and nothing is written when spilling is off"""
+        tape = TapeRecorder(buffer_size=2, spill_dir=tmp_path / 'tape', spill=False)
+        tape.start()
+        for i in range(4):
+            tape.record('MNQ', 100.0, 1, None, T0 + timedelta(seconds=i))
+        tape.stop()
+        assert tape.get_stats()['evicted'] == 2
+        assert not (tmp_path / 'tape').exists()
+        with pytest.raises(ValueError):
+            TapeRecorder(buffer_size=0)
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
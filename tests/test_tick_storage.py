@@ -0,0 +1,152 @@
+"""
+Unit tests for partitioned tick storage and bar hypertables in the database module
+"""
+
+import pytest
+import os
+import sys
+from contextlib import contextmanager
+from datetime import date, datetime, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+pytest.importorskip('psycopg2')
+
+import infrastructure.database as database
+from infrastructure.database import DatabaseManager
+
+
+class FakeCursor:
+    def __init__(self, db):
+        self.db = db
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        return False
+
+    def execute(self, query, params=None):
+        self.db.executed.append((repr(query) if not isinstance(query, str) else query, params))
+
+    def fetchall(self):
+        return self.db.rows.pop(0) if self.db.rows else []
+
+    def fetchone(self):
+        return self.db.ones.pop(0) if self.db.ones else None
+
+
+def make_db(storage, bar_storage=None):
+    db = DatabaseManager.__new__(DatabaseManager)
+    db.tick_storage = storage
+    db.bar_storage = bar_storage
+    db._tick_partitions = set()
+    db.executed = []
+    db.rows = []
+    db.ones = []
+
+    class Conn:
+        def cursor(self, cursor_factory=None):
+            return FakeCursor(db)
+
+    @contextmanager
+    def get_connection(read_only=False):
+        yield Conn()
+
+    db.get_connection = get_connection
+    return db
+
+
+class TestTickStorage:
+    """Test partition management, tick writes and pruning-friendly reads."""
+
+    def test_save_ticks_creates_missing_partitions_once(self, monkeypatch):
+        db = make_db('partitioned')
+        inserted = []
+        monkeypatch.setattr(database, 'execute_values', lambda cur, query, values, page_size: inserted.extend(values))
+        ticks = [
+            {'symbol': 'mnq', 'timestamp': '2025-11-19T14:30:00Z', 'price': 21000.25, 'size': 2, 'side': 'buy'},
+            {'symbol': 'MNQ', 'timestamp': datetime(2025, 11, 20, 1, 0), 'price': 21001.0},
+            {'symbol': 'MNQ', 'timestamp': None, 'price': 1.0},  # Dropped
+        ]
+        assert db.save_ticks(ticks) == 2
+        assert db.save_ticks(ticks[:1]) == 1
+        creates = [q for q, _ in db.executed if 'PARTITION OF' in q]
+        assert len(creates) == 2
+        assert "market_ticks_p20251119" in creates[0] and "market_ticks_p20251120" in creates[1]
+        assert inserted[0][1:5] == ('MNQ', 21000.25, 2, 0)
+        assert inserted[1][0].tzinfo is timezone.utc and inserted[1][4] is None
+
+    def test_get_ticks_bounds_the_time_range(self):
+        db = make_db('timescale')
+        start, end = datetime(2025, 11, 19, tzinfo=timezone.utc), datetime(2025, 11, 20, tzinfo=timezone.utc)
+        db.rows = [[{'ts': start, 'symbol': 'MNQ', 'price': 1.0, 'size': 1, 'side': 1, 'bid': None, 'ask': None}]]
+        ticks = db.get_ticks('mnq', start, end, limit=10)
+        query, params = db.executed[-1]
+        assert 'ts >= %s AND ts < %s' in query and 'LIMIT' in query
+        assert params == ['MNQ', start, end, 10]
+        assert ticks[0]['side'] == 'sell'
+        assert make_db(None).get_ticks('MNQ', start, end) == []
+
+    def test_maintenance_drops_expired_partitions(self, monkeypatch):
+        monkeypatch.setenv('DB_TICK_RETENTION_DAYS', '30')
+        monkeypatch.setenv('DB_TICK_PARTITIONS_AHEAD', '1')
+        db = make_db('partitioned')
+        today = datetime.now(timezone.utc).date()
+        db.rows = [[('market_ticks_p20000101',), (f"market_ticks_p{today.strftime('%Y%m%d')}",), ('other',)]]
+        result = db.maintain_tick_partitions()
+        assert result == {'created': 2, 'dropped': 1}  # Yesterday and tomorrow created
+        assert any('DROP TABLE' in q and 'market_ticks_p20000101' in q for q, _ in db.executed)
+        assert date(2000, 1, 1) not in db._tick_partitions
+        assert make_db('timescale').maintain_tick_partitions() == {'created': 0, 'dropped': 0}
+
+
+class TestBarStorage:
+    """Test the bar hypertable conversion and time_bucket/date_bin downsampling."""
+
+    def test_auto_converts_bars_when_timescale_is_enabled(self, monkeypatch):
+        monkeypatch.delenv('DB_BAR_STORAGE', raising=False)
+        monkeypatch.setenv('DB_BAR_COMPRESS_AFTER_DAYS', '14')
+        db = make_db('timescale')
+        db.ones = [(True,), (False,)]  # Extension enabled, not a hypertable yet
+        db._initialize_bar_storage()
+        assert db.bar_storage == 'timescale'
+        queries = [q for q, _ in db.executed]
+        assert any('DROP CONSTRAINT IF EXISTS historical_bars_pkey' in q for q in queries)
+        assert any('create_hypertable' in q and 'migrate_data' in q for q in queries)
+        assert db.executed[-1][1] == ('historical_bars', '14 days')
+        db = make_db('partitioned')
+        db.ones = [(False,)]
+        db._initialize_bar_storage()
+        assert db.bar_storage == 'plain' and len(db.executed) == 1
+
+    def test_downsampled_bars_use_time_bucket(self):
+        db = make_db('timescale', 'timescale')
+        t0 = datetime(2025, 11, 19, 14, 0, tzinfo=timezone.utc)
+        db.rows = [[{'bucket': t0.replace(hour=15), 'open': 2, 'high': 3, 'low': 1, 'close': 2.5, 'volume': 40,
+                     'bar_count': 12},
+                    {'bucket': t0, 'open': 1, 'high': 2, 'low': 0.5, 'close': 2, 'volume': 60, 'bar_count': 60}]]
+        bars = db.get_downsampled_bars('MNQ', '1h', limit=2)
+        query, params = db.executed[-1]
+        assert 'time_bucket(%s::interval, timestamp' in query and 'first(open, timestamp)' in query
+        assert 'ORDER BY bucket DESC LIMIT %s' in query and params == ['1 hours', 'MNQ', '1m', 2]
+        assert [b['timestamp'] for b in bars] == [t0.isoformat(), t0.replace(hour=15).isoformat()]
+        assert bars[0]['open'] == 1.0 and bars[0]['bar_count'] == 60 and bars[0]['timeframe'] == '1h'
+        with pytest.raises(ValueError):
+            db.get_downsampled_bars('MNQ', '100t')
+
+    def test_tick_bars_fall_back_to_date_bin(self):
+        db = make_db('partitioned', 'plain')
+        start, end = datetime(2025, 11, 19, tzinfo=timezone.utc), datetime(2025, 11, 20, tzinfo=timezone.utc)
+        db.rows = [[{'bucket': start, 'open': 1.0, 'high': 2.0, 'low': 0.5, 'close': 1.5, 'volume': 9,
+                     'tick_count': 4, 'buy_volume': 5, 'sell_volume': 4}]]
+        bars = db.get_tick_bars('mnq', '5m', start, end)
+        query, params = db.executed[-1]
+        assert 'date_bin(%s::interval, ts' in query and 'array_agg(price ORDER BY ts ASC)' in query
+        assert 'ORDER BY bucket ASC' in query and params == ['5 minutes', 'MNQ', start, end, 10000]
+        assert bars[0]['symbol'] == 'MNQ' and bars[0]['buy_volume'] == 5
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
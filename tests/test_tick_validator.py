@@ -0,0 +1,71 @@
+"""
+Unit tests for tick validation and outlier filtering
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timedelta, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar
+from core.tick_validator import TickRejectReason, TickValidator
+
+NOW = datetime(2025, 11, 19, 15, 0, tzinfo=timezone.utc)
+
+
+def _validator(**kwargs):
+    kwargs.setdefault('max_jump_atr', 10)
+    kwargs.setdefault('max_jump_pct', 2.0)
+    kwargs.setdefault('jump_confirm', 3)
+    kwargs.setdefault('max_age_seconds', 30)
+    kwargs.setdefault('atr_period', 3)
+    kwargs.setdefault('atr_timeframe', '1m')
+    return TickValidator(**kwargs)
+
+
+class TestTickValidator:
+    """Test rejection rules and counters."""
+
+    def test_basic_rejections(self):
+        validator = _validator()
+        assert validator.validate('MNQ', 0.0, NOW, now=NOW) is TickRejectReason.NON_POSITIVE
+        assert validator.validate('MNQ', 15000.0, NOW, bid=15000.5, ask=15000.25, now=NOW) is TickRejectReason.CROSSED
+        assert validator.validate('MNQ', 15000.0, NOW - timedelta(minutes=5), now=NOW) is TickRejectReason.STALE
+        validator.set_price_limits('mnq', 14000.0, 16000.0)
+        assert validator.validate('MNQ', 16500.0, NOW, now=NOW) is TickRejectReason.PRICE_LIMIT
+        assert validator.validate('MNQ', 15000.0, NOW, bid=15000.0, ask=15000.25, now=NOW) is None
+
+        stats = validator.get_stats()
+        assert stats['accepted'] == 1
+        assert stats['rejected'] == {'non_positive': 1, 'crossed': 1, 'stale': 1, 'price_limit': 1}
+
+    def test_jump_uses_atr_once_known(self):
+        validator = _validator()
+        assert validator.validate('MNQ', 15000.0, NOW, now=NOW) is None
+        # No ATR yet: 2% band (300 points)
+        assert validator.validate('MNQ', 15200.0, NOW, now=NOW) is None
+        for i in range(3):
+            validator.add_bar(Bar('MNQ', '1m', NOW, 15200.0, 15202.0, 15198.0, 15200.0))  # 4-point ranges
+        validator.add_bar(Bar('MNQ', '5m', NOW, 1.0, 500.0, 1.0, 1.0))  # Other timeframes ignored
+        assert validator.get_atr('MNQ') == 4.0
+        assert validator.validate('MNQ', 15235.0, NOW, now=NOW) is None
+        assert validator.validate('MNQ', 15300.0, NOW, now=NOW) is TickRejectReason.PRICE_JUMP
+        assert validator.get_stats()['symbols']['MNQ']['last_price'] == 15235.0
+
+    def test_single_bad_print_filtered_but_level_shift_accepted(self):
+        validator = _validator()
+        validator.validate('ES', 5000.0, NOW, now=NOW)
+        assert validator.validate('ES', 50000.0, NOW, now=NOW) is TickRejectReason.PRICE_JUMP  # Fat finger
+        assert validator.validate('ES', 5000.25, NOW, now=NOW) is None
+        # Gap open: three agreeing ticks confirm the new level
+        assert validator.validate('ES', 5200.0, NOW, now=NOW) is TickRejectReason.PRICE_JUMP
+        assert validator.validate('ES', 5200.5, NOW, now=NOW) is TickRejectReason.PRICE_JUMP
+        assert validator.validate('ES', 5201.0, NOW, now=NOW) is None
+        assert validator.validate('ES', 5201.25, NOW, now=NOW) is None
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
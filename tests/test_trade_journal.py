@@ -0,0 +1,123 @@
+"""
+Unit tests for the trade journal in the database module
+"""
+
+import pytest
+import os
+import sys
+from contextlib import contextmanager
+from datetime import date, datetime, timezone
+from decimal import Decimal
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+pytest.importorskip('psycopg2')
+
+from infrastructure.database import DatabaseManager, trading_day_bounds
+from core.strategy_engine.strategy import Direction, Signal
+
+
+class FakeDB:
+    """DatabaseManager whose connections record queries and return canned rows."""
+
+    def __init__(self, rows=None, returning=None):
+        self.db = DatabaseManager.__new__(DatabaseManager)
+        self.executed = []
+        executed = self.executed
+
+        class Cursor:
+            def __enter__(self):
+                return self
+
+            def __exit__(self, *exc):
+                return False
+
+            def execute(self, query, params=None):
+                executed.append((' '.join(query.split()), params))
+
+            def fetchone(self):
+                return returning
+
+            def fetchall(self):
+                return rows or []
+
+        class Conn:
+            def cursor(self, cursor_factory=None):
+                return Cursor()
+
+        @contextmanager
+        def get_connection(read_only=False):
+            yield Conn()
+
+        self.db.get_connection = get_connection
+
+
+class TestTradingDay:
+    """Test trading-day boundaries."""
+
+    def test_default_day_starts_at_globex_open(self, monkeypatch):
+        monkeypatch.delenv('JOURNAL_TIMEZONE', raising=False)
+        monkeypatch.delenv('JOURNAL_DAY_START', raising=False)
+        start, end = trading_day_bounds(date(2025, 11, 19))
+        assert start == datetime(2025, 11, 18, 23, 0, tzinfo=timezone.utc)
+        assert end == datetime(2025, 11, 19, 23, 0, tzinfo=timezone.utc)
+        monkeypatch.setenv('JOURNAL_TIMEZONE', 'UTC')
+        monkeypatch.setenv('JOURNAL_DAY_START', '00:00')
+        assert trading_day_bounds(date(2025, 11, 19))[0] == datetime(2025, 11, 19, tzinfo=timezone.utc)
+
+
+class TestJournalWrites:
+    """Test signal and fill journaling."""
+
+    def test_record_signal_and_fill(self):
+        fake = FakeDB(returning=(7,))
+        signal = Signal(strategy_id='orb', symbol='mnq', direction=Direction.LONG, price=21000.0,
+                        timestamp=datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc), stop=20990.0,
+                        timeframe='5m', reason='Range breakout')
+        assert fake.db.record_signal(signal, account_id=123) == 7
+        query, params = fake.executed[0]
+        assert query.startswith('INSERT INTO journal_signals') and 'RETURNING id' in query
+        assert params[1:5] == ('123', 'orb', 'MNQ', 'long') and params[6] == 20990.0
+        assert params[-1] == '{"timeframe": "5m"}'
+
+        assert fake.db.record_fill('123', {'fill_id': 99, 'order_id': 99, 'strategy_name': 'orb', 'symbol': 'MNQ',
+                                          'side': 1, 'quantity': 2, 'price': 21010.5})
+        query, params = fake.executed[1]
+        assert 'ON CONFLICT (account_id, fill_id) DO NOTHING' in query
+        assert params[3:8] == ('99', '99', 'MNQ', 'SELL', 2) and params[8] == 21010.5
+
+
+class TestJournalQueries:
+    """Test trading-day and per-strategy P&L queries."""
+
+    def test_pnl_by_strategy(self):
+        rows = [
+            {'strategy': 'orb', 'trades': 4, 'wins': 3, 'losses': 1, 'pnl': Decimal('250.00'),
+             'best': Decimal('150.00'), 'worst': Decimal('-50.00')},
+            {'strategy': 'unattributed', 'trades': 1, 'wins': 0, 'losses': 1, 'pnl': Decimal('-20.00'),
+             'best': Decimal('-20.00'), 'worst': Decimal('-20.00')},
+        ]
+        fake = FakeDB(rows=rows)
+        result = fake.db.pnl_by_strategy(date(2025, 11, 17), date(2025, 11, 19), account_id='123')
+        query, params = fake.executed[0]
+        assert 'GROUP BY 1' in query and 'AND account_id = %s' in query
+        start, end = trading_day_bounds(date(2025, 11, 17))[0], trading_day_bounds(date(2025, 11, 19))[1]
+        assert params == [start, end, '123']
+        assert result['orb'] == {'trades': 4, 'wins': 3, 'losses': 1, 'pnl': 250.0, 'best': 150.0,
+                                 'worst': -50.0, 'win_rate': 75.0, 'avg_pnl': 62.5}
+        assert result['unattributed']['pnl'] == -20.0
+
+    def test_trades_for_day_and_journal_kinds(self):
+        exit_time = datetime(2025, 11, 19, 15, 0, tzinfo=timezone.utc)
+        fake = FakeDB(rows=[{'id': 1, 'strategy_name': 'orb', 'pnl': Decimal('12.50'), 'exit_time': exit_time}])
+        trades = fake.db.trades_for_day(date(2025, 11, 19), strategy_name='orb')
+        assert trades == [{'id': 1, 'strategy_name': 'orb', 'pnl': 12.5, 'exit_time': exit_time.isoformat()}]
+        query, params = fake.executed[0]
+        assert 'FROM trade_history' in query and params[2:] == ['orb']
+        with pytest.raises(ValueError):
+            fake.db.get_journal('quotes', exit_time, exit_time)
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,136 @@
+"""
+Unit tests for the tradebot command line
+"""
+
+import pytest
+import csv
+import json
+import os
+import sys
+from datetime import datetime, timezone
+from io import StringIO
+from pathlib import Path
+from unittest.mock import AsyncMock, MagicMock, patch
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar
+from infrastructure.file_lock import ArtifactLockError
+from tradebot import CommandError, build_bot, main, resolve_account
+
+CREDENTIALS = {'PROJECT_X_API_KEY': 'test_key', 'PROJECT_X_USERNAME': 'test_user'}
+ACCOUNTS = [{'id': 111, 'name': 'PRAC-V2-1', 'balance': 50000.0}, {'id': 222, 'name': 'TC-2', 'balance': 150000.0}]
+
+
+@pytest.fixture
+def bot():
+    bot = MagicMock()
+    bot.selected_account = None
+    bot._ensure_valid_token = AsyncMock(return_value=True)
+    bot.list_accounts = AsyncMock(return_value=ACCOUNTS)
+    bot.get_open_positions = AsyncMock(return_value=[
+        {'id': 7, 'contractId': 'CON.F.US.MNQ.Z25', 'type': 1, 'size': 2, 'averagePrice': 21000.25},
+        {'id': 8, 'contractId': 'CON.F.US.MES.Z25', 'type': 2, 'size': 1, 'averagePrice': 6000.5}])
+    bot.get_open_orders = AsyncMock(return_value=[
+        {'id': 901, 'contractId': 'CON.F.US.MNQ.Z25', 'side': 1, 'size': 2, 'type': 1, 'limitPrice': 21050.0},
+        {'id': 902, 'contractId': 'CON.F.US.MES.Z25', 'side': 0, 'size': 1, 'type': 4, 'stopPrice': 5990.0}])
+    bot.place_market_order = AsyncMock(return_value={'success': True, 'orderId': 5001})
+    bot.place_stop_order = AsyncMock(return_value={'success': True, 'orderId': 5002})
+    bot.cancel_order = AsyncMock(return_value={'success': True})
+    bot.close_position = AsyncMock(return_value={'success': True})
+    bot.flatten_all_positions = AsyncMock(return_value={'success': True})
+    return bot
+
+
+def run(bot, *argv, answer='y'):
+    out = StringIO()
+    prompts = []
+    code = main(list(argv), bot=bot, out=out, confirm_input=lambda prompt: prompts.append(prompt) or answer)
+    return code, out.getvalue(), prompts
+
+
+class TestTradebotCli:
+    """Test account selection, order/position commands, confirmation and history download"""
+
+    def test_account_selection(self, bot, monkeypatch):
+        monkeypatch.delenv('TOPSTEPX_ACCOUNT_ID', raising=False)
+        assert resolve_account(ACCOUNTS, 'tc-2')['id'] == 222 and resolve_account(ACCOUNTS, '111')['id'] == 111
+        assert resolve_account(ACCOUNTS[:1], None)['id'] == 111
+        for accounts, wanted, error in ((ACCOUNTS, None, 'several accounts'), (ACCOUNTS, '999', 'not found'),
+                                        ([], None, 'no accounts')):
+            with pytest.raises(CommandError, match=error):
+                resolve_account(accounts, wanted)
+        assert run(bot, 'positions')[0] == 1
+        monkeypatch.setenv('TOPSTEPX_ACCOUNT_ID', '222')
+        code, out, _ = run(bot, 'positions', '--json')
+        assert code == 0 and bot.selected_account['id'] == 222 and json.loads(out)[1]['id'] == 8
+        code, out, _ = run(bot, 'orders', '--account', 'PRAC-V2-1')
+        assert code == 0 and bot.selected_account['id'] == 111 and 'MNQ' in out and 'sell' in out and 'stop' in out
+        bot._ensure_valid_token.return_value = False
+        assert run(bot, 'accounts')[0] == 1
+
+    def test_orders_and_confirmation(self, bot):
+        code, out, prompts = run(bot, 'order', 'place', 'mnq', 'BUY', '2', '--limit', '21000.25', '--sl-ticks', '20',
+                                 '--account', '111')
+        assert code == 0 and 'Place limit buy 2 MNQ @ 21000.25 on PRAC-V2-1' in prompts[0] and '5001' in out
+        bot.place_market_order.assert_awaited_once_with('MNQ', 'BUY', 2, stop_loss_ticks=20, take_profit_ticks=None,
+                                                        order_type='limit', limit_price=21000.25, strategy_name='cli')
+        assert run(bot, 'order', 'place', 'MES', 'sell', '1', '--stop', '5990', '--account', '111', answer='n')[0] == 1
+        bot.place_stop_order.assert_not_awaited()
+        assert run(bot, 'order', 'place', 'MES', 'sell', '1', '--stop', '5990', '--account', '111', '-y')[2] == []
+        bot.place_stop_order.assert_awaited_once_with('MES', 'SELL', 1, 5990.0, strategy_name='cli')
+        bot.place_market_order.return_value = {'error': 'Kill switch engaged: news'}
+        assert run(bot, 'order', 'place', 'MNQ', 'buy', '1', '--account', '111', '-y')[0] == 1
+        with pytest.raises(SystemExit) as usage:
+            run(bot, 'order', 'place', 'MNQ', 'buy', '1', '--limit', '1', '--stop', '2')
+        assert usage.value.code == 2
+
+        bot.cancel_order.side_effect = [{'success': True}, {'error': 'Order not found'}]
+        code, out, _ = run(bot, 'order', 'cancel-all', '--account', '111', '--json', '-y')
+        assert code == 1 and json.loads(out) == {'cancelled': [901], 'failed': [902]}
+        bot.cancel_order.reset_mock(side_effect=True)
+        assert run(bot, 'order', 'cancel-all', '--symbol', 'MES', '--account', '111', '-y')[0] == 0
+        bot.cancel_order.assert_awaited_once_with(902)
+
+    def test_flatten(self, bot):
+        code, out, prompts = run(bot, 'flatten', 'mes', '--account', '222')
+        assert code == 0 and 'Close 1 MES position(s)' in prompts[0]
+        bot.close_position.assert_awaited_once_with('8')
+        bot.flatten_all_positions.assert_not_awaited()
+        assert run(bot, 'flatten', 'ES', '--account', '222')[1] == "No open ES positions\n"
+        code, out, prompts = run(bot, 'flatten', '--account', '222')
+        assert code == 0 and 'ALL positions' in prompts[0]
+        bot.flatten_all_positions.assert_awaited_once_with(interactive=False)
+
+    def test_history_download(self, bot, tmp_path):
+        start = datetime(2025, 11, 3, 14, 30, tzinfo=timezone.utc)
+        bars = [Bar(symbol='MNQ', timeframe='5m', timestamp=start, open=21000.0, high=21010.0, low=20995.0,
+                    close=21005.0, volume=1200)]
+        bot.history_client.fetch_bars = AsyncMock(return_value=bars)
+        path = tmp_path / 'mnq.csv'
+        code, out, _ = run(bot, 'history', 'download', 'mnq', '--timeframe', '5m', '--start', '2025-11-03',
+                           '--end', '2025-11-04T00:00:00Z', '--out', str(path), '--account', '111', '--json')
+        assert code == 0 and json.loads(out)['bars'] == 1
+        bot.history_client.fetch_bars.assert_awaited_once_with('MNQ', '5m', datetime(2025, 11, 3, tzinfo=timezone.utc),
+                                                               datetime(2025, 11, 4, tzinfo=timezone.utc))
+        with open(path) as f:
+            rows = list(csv.DictReader(f))
+        assert len(rows) == 1 and float(rows[0]['close']) == 21005.0
+
+
+    def test_bot_runs_without_background_services(self):
+        """Test the CLI bot never claims artifacts or waits on a failover lease held by the running bot"""
+        with patch.dict(os.environ, {**CREDENTIALS, 'FAILOVER_ENABLED': 'true'}):
+            bot = build_bot()
+        assert bot.leader_elector is None and bot.order_flow_enabled()  # Never a standby
+        assert bot.redis_bridge is None and bot.event_exporter is None
+        with patch.dict(os.environ, CREDENTIALS), \
+                patch('trading_bot.TopStepXTradingBot', side_effect=ArtifactLockError(Path('.cache'))), \
+                patch('sys.stderr', new_callable=StringIO) as stderr:
+            assert main(['positions']) == 1
+        assert 'tradebot: Artifact directory .cache is held by another process' in stderr.getvalue()
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,100 @@
+"""
+Unit tests for the built-in TradingView webhook server
+"""
+
+import pytest
+import asyncio
+import json
+import os
+import sys
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.strategy_engine import Direction, OrderExecutor, RiskManager, StrategyEngine
+from servers.tradingview_webhook import AlertError, TradingViewWebhookServer, alert_symbol, parse_alert
+
+SECRET = 's3cret'
+
+
+def alert(**fields):
+    return json.dumps({"secret": SECRET, "ticker": "CME_MINI:MNQ1!", "action": "buy", "price": 21000.0,
+                       **fields}).encode()
+
+
+def make_server(**kwargs):
+    orders = []
+    executor = OrderExecutor(lambda **order: orders.append(order) or {"success": True, "orderId": len(orders)},
+                             account_id='ACC1', quantity_for=lambda strategy_id: 2)
+    risk = RiskManager(account_resolver=lambda signal: 'ACC1')
+    return TradingViewWebhookServer(executor, risk=risk, secret=SECRET, **kwargs), orders, risk
+
+
+class TestTradingViewWebhook:
+    """Test alert parsing, authentication and routing through the risk gate and executor"""
+
+    def test_parse_alert(self):
+        signal = parse_alert({"ticker": "MNQZ2025", "action": "Sell", "close": "21000.5", "stop_loss": 21010.5,
+                              "strategy": "orb", "interval": "5", "time": "2025-11-04T16:00:00Z"})
+        assert (signal.symbol, signal.direction, signal.price, signal.stop) == ('MNQ', Direction.SHORT, 21000.5,
+                                                                                21010.5)
+        assert signal.strategy_id == 'orb' and signal.timeframe == '5' and signal.timestamp.tzinfo is not None
+        closing = parse_alert({"ticker": "ES1!", "action": "sell", "market_position": "flat", "stop": 1})
+        assert closing.direction is Direction.FLAT and closing.stop is None
+        assert alert_symbol('CON.F.US.MNQ.Z25') == 'MNQ' and alert_symbol('mes') == 'MES'
+        for payload, error in (({"ticker": "MNQ", "action": "hold"}, "unknown action 'hold'"),
+                               ({"action": "buy"}, "missing ticker"),
+                               ({"ticker": "MNQ", "action": "buy", "target": 21050}, "need a price"),
+                               ({"ticker": "MNQ", "action": "buy", "price": "{{close}}"}, "price should be a number"),
+                               ([], "JSON object")):
+            with pytest.raises(AlertError, match=error):
+                parse_alert(payload)
+
+    def test_secret_is_required(self):
+        server, orders, _ = make_server()
+        assert server.handle_alert(alert(secret='wrong'))[0] == 401
+        assert server.handle_alert(b'not json')[0] == 401  # No parse details before authentication
+        assert server.handle_alert(alert(secret=None), {'X-Webhook-Secret': SECRET})[0] == 200
+        status, body = server.handle_alert(alert(action='hold'))
+        assert status == 400 and 'unknown action' in body['error']
+        assert orders and server.get_stats()['unauthorized'] == 2 and server.get_stats()['invalid'] == 1
+        with pytest.raises(ValueError, match='TRADINGVIEW_WEBHOOK_SECRET'):
+            asyncio.run(TradingViewWebhookServer(server.executor, secret='').start())
+
+    def test_routes_through_risk_and_executor(self):
+        server, orders, risk = make_server()
+        status, body = server.handle_alert(alert(stop=20990.0, target=21020.0))
+        assert status == 200 and body['signal']['symbol'] == 'MNQ'
+        assert orders == [{"symbol": "MNQ", "side": "BUY", "quantity": 2, "account_id": "ACC1",
+                           "strategy_name": "tradingview", "stop_loss_ticks": 40, "take_profit_ticks": 80}]
+        assert server.executor.position('tradingview', 'MNQ') == 2
+        risk.halt('ACC1', reason='test')
+        assert server.handle_alert(alert(action='sell'))[0] == 409  # Entry blocked while halted
+        assert server.handle_alert(alert(action='close'))[0] == 200  # Exits always pass
+        assert [order['side'] for order in orders] == ['BUY', 'SELL'] and orders[1]['quantity'] == 2
+        assert server.get_stats()['blocked'] == 1 and server.get_stats()['routed'] == 2
+
+    def test_start_and_stop(self):
+        aiohttp = pytest.importorskip('aiohttp')
+        engine = StrategyEngine()
+        published = []
+        engine.on_signal(published.append)
+
+        async def run():
+            core = type('Core', (), {'executor': None, 'risk': None, 'engine': engine})()
+            server = TradingViewWebhookServer.from_core(core, secret=SECRET, host='127.0.0.1', port=0)
+            await server.start()
+            try:
+                async with aiohttp.ClientSession() as session:
+                    async with session.post(f"http://127.0.0.1:{server.port}/tradingview", data=alert()) as resp:
+                        return resp.status, server.running
+            finally:
+                await server.stop()
+                assert not server.running
+
+        assert asyncio.run(run()) == (200, True)
+        assert engine.flush(5.0) and published[0].direction is Direction.LONG
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
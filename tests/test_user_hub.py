@@ -0,0 +1,165 @@
+"""
+Unit tests for the user hub (account/order/position/fill streaming)
+"""
+
+import pytest
+import asyncio
+import json
+import os
+import sys
+from unittest.mock import AsyncMock, MagicMock, patch
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.websocket import AccountUpdate, OrderUpdate, PositionUpdate, UserHubClient, UserTrade
+from core.websocket.protocol import RECORD_SEPARATOR, parse_messages
+from core.websocket.user_hub import user_event_from_invocation
+from trading_bot import TopStepXTradingBot
+
+RS = RECORD_SEPARATOR
+
+
+class FakeConnection:
+    """In-memory WebSocket that accepts the handshake and collects sent frames."""
+
+    def __init__(self):
+        self.incoming = asyncio.Queue()
+        self.incoming.put_nowait('{}' + RS)
+        self.sent = []
+
+    async def send(self, data):
+        self.sent.append(data)
+
+    async def recv(self):
+        return await self.incoming.get()
+
+    async def close(self):
+        pass
+
+    def push(self, target, *arguments):
+        self.incoming.put_nowait(json.dumps({'type': 1, 'target': target, 'arguments': list(arguments)}) + RS)
+
+
+ORDER = {'id': 9001, 'accountId': 123, 'contractId': 'CON.F.US.MNQ.Z25', 'status': 2, 'type': 2, 'side': 1,
+         'size': 2, 'fillVolume': 2, 'filledPrice': 21000.25, 'customTag': 'TradingBot-v1.0-abc',
+         'updateTimestamp': '2025-11-19T14:30:00Z'}
+
+
+class TestUserHub:
+    """Test user hub payload parsing and streaming"""
+
+    def test_event_parsing(self):
+        """Test gateway payloads (bare and enveloped) become typed events"""
+        order = user_event_from_invocation('GatewayUserOrder', [ORDER])
+        assert isinstance(order, OrderUpdate) and order.is_filled and order.status_name == 'filled'
+        assert (order.account_id, order.order_id, order.symbol, order.side, order.fill_volume) == \
+            ('123', '9001', 'MNQ', 'sell', 2)
+        assert order.raw is ORDER and order.to_dict()['type'] == 'order'
+
+        position = user_event_from_invocation('gatewayuserposition', [
+            {'action': 1, 'data': {'id': 7, 'accountId': 123, 'contractId': 'CON.F.US.MES.Z25',
+                                   'type': 2, 'size': 3, 'averagePrice': 6000.5}}])
+        assert isinstance(position, PositionUpdate) and position.net_quantity == -3 and position.symbol == 'MES'
+
+        trade = user_event_from_invocation('GatewayUserTrade', [{'id': 1, 'accountId': 123, 'orderId': 9001,
+                                                                 'contractId': 'CON.F.US.MNQ.Z25', 'price': 21000.25,
+                                                                 'size': 2, 'side': 1, 'fees': 1.48,
+                                                                 'profitAndLoss': None}])
+        assert isinstance(trade, UserTrade) and trade.order_id == '9001' and trade.profit_and_loss is None
+        account = user_event_from_invocation('GatewayUserAccount', [{'id': 123, 'name': 'PRAC', 'balance': 50000}])
+        assert isinstance(account, AccountUpdate) and account.balance == 50000.0
+        assert user_event_from_invocation('GatewayQuote', [ORDER]) is None
+        assert user_event_from_invocation('GatewayUserOrder', ['not a dict']) is None
+
+    @pytest.mark.asyncio
+    async def test_streaming_account_updates(self):
+        """Test account subscriptions are sent and streamed updates reach listeners and position state"""
+        conn = FakeConnection()
+
+        async def connect(url, headers):
+            return conn
+
+        hub = UserHubClient('https://rtc.example.com/hubs/user', keep_alive_interval=0, connector=connect)
+        events = []
+        hub.add_listener(events.append)
+        hub.subscribe_account(123)
+        hub.subscribe_account('123')  # Already streaming
+        await hub.start()
+        await asyncio.sleep(0.01)
+        sent = [(m.target, m.arguments) for frame in conn.sent[1:] for m in parse_messages(frame)]
+        assert sent == [('SubscribeAccounts', []), ('SubscribeOrders', [123]),
+                        ('SubscribePositions', [123]), ('SubscribeTrades', [123])]
+
+        conn.push('GatewayUserOrder', ORDER)
+        conn.push('GatewayUserPosition', {'id': 7, 'accountId': 123, 'contractId': 'CON.F.US.MNQ.Z25',
+                                          'type': 1, 'size': 2, 'averagePrice': 21000.25})
+        conn.push('GatewayUserAccount', {'id': 123, 'name': 'PRAC', 'balance': 49990.5, 'canTrade': True})
+        await asyncio.sleep(0.01)
+        assert [e.type for e in events] == ['order', 'position', 'account']
+        assert [p.net_quantity for p in hub.positions(123)] == [2]
+        assert hub.account('123').balance == 49990.5
+
+        conn.push('GatewayUserPosition', {'id': 7, 'accountId': 123, 'contractId': 'CON.F.US.MNQ.Z25',
+                                          'type': 1, 'size': 0})
+        await asyncio.sleep(0.01)
+        assert hub.positions(123) == [] and hub.get_stats()['events_published'] == 4
+
+        hub.unsubscribe_account(123)
+        await asyncio.sleep(0.01)
+        sent = [m.target for frame in conn.sent[1:] for m in parse_messages(frame)]
+        assert sent[-3:] == ['UnsubscribeOrders', 'UnsubscribePositions', 'UnsubscribeTrades']
+        assert not hub.is_subscribed(123)
+        await hub.stop()
+
+    @pytest.mark.asyncio
+    async def test_async_event_stream(self):
+        """Test events() yields typed updates in order and ends when the hub stops"""
+        conn = FakeConnection()
+
+        async def connect(url, headers):
+            return conn
+
+        hub = UserHubClient('https://rtc.example.com/hubs/user', keep_alive_interval=0, connector=connect)
+        stream = hub.events(types=OrderUpdate, capacity=1)
+        await hub.start()
+        for status in (1, 1, 2):
+            conn.push('GatewayUserOrder', dict(ORDER, status=status))
+        conn.push('GatewayUserAccount', {'id': 123, 'balance': 50000})
+
+        received = []
+
+        async def consume():
+            async for update in stream:
+                received.append(update.status_name)
+                await asyncio.sleep(0.005)  # Slower than the feed
+
+        consumer = asyncio.ensure_future(consume())
+        await asyncio.sleep(0.05)
+        await hub.stop()
+        await asyncio.wait_for(consumer, 1)
+        assert received == ['open', 'open', 'filled']  # Blocked, not dropped
+        assert stream.get_stats()['dropped'] == 0 and stream.get_stats()['blocked'] >= 1
+
+
+class TestBotOrderFills:
+    """Test the bot hands fills and streamed partial fills to order fill listeners"""
+
+    @pytest.mark.asyncio
+    async def test_fill_listeners_get_partial_and_final_fills(self):
+        with patch.dict(os.environ, {'PROJECT_X_API_KEY': 'test_key', 'PROJECT_X_USERNAME': 'test_user'}):
+            bot = TopStepXTradingBot(api_key='test_key', username='test_user')
+        bot._journal_fill = AsyncMock()
+        bot._notification_warmup_done['123'] = True
+        fills = []
+        bot.add_order_fill_listener(fills.append)
+        bot.add_order_fill_listener(MagicMock(side_effect=RuntimeError('boom')))  # Isolated
+
+        bot._on_user_hub_event(OrderUpdate.from_gateway(dict(ORDER, status=1, size=3, fillVolume=1)))
+        bot._on_user_hub_event(OrderUpdate.from_gateway(dict(ORDER, status=1, size=3, fillVolume=0)))  # Nothing filled
+        await bot._process_order_fills('123', [dict(ORDER, status=2, size=3, fillVolume=3)])
+        assert [order['fillVolume'] for order in fills] == [1, 3]
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,98 @@
+"""
+Unit tests for composite multi-session volume profiles
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone, timedelta
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.volume_profile import CompositeVolumeProfile, normalize_symbol
+
+
+# 2025-11-19 10:00 US/Eastern, inside the globex session that opened 11-18 18:00
+DAY1 = datetime(2025, 11, 19, 15, 0, 0, tzinfo=timezone.utc)
+DAY2 = DAY1 + timedelta(days=1)
+DAY3 = DAY1 + timedelta(days=2)
+
+
+class TestNormalizeSymbol:
+    """Test symbol normalization"""
+
+    @pytest.mark.parametrize("raw,expected", [
+        ("CON.F.US.MNQ.Z25", "MNQ"),
+        ("MNQZ25", "MNQ"),
+        ("esh6", "ES"),
+        ("NQ", "NQ"),
+    ])
+    def test_normalize(self, raw, expected):
+        """Test contract IDs and dated contracts map to the root"""
+        assert normalize_symbol(raw) == expected
+
+
+class TestCompositeVolumeProfile:
+    """Test CompositeVolumeProfile"""
+
+    def test_decay_weighting_and_window(self):
+        """Test older sessions are decayed and dropped beyond the window"""
+        profile = CompositeVolumeProfile(sessions=1, decay=0.5, anchor='globex')
+        profile.add_trade('CON.F.US.MNQ.Z25', 100.0, 10, DAY1)
+        profile.add_trade('MNQZ25', 100.0, 10, DAY2)
+        assert profile.get_composite('MNQ') == {100.0: pytest.approx(15.0)}
+
+        profile.add_trade('MNQ', 100.25, 4, DAY3)
+        # Day 1 fell out of the window, day 2 now weighted 0.5
+        assert profile.get_composite('MNQ') == {100.0: pytest.approx(5.0), 100.25: pytest.approx(4.0)}
+
+    def test_poc_and_value_area(self):
+        """Test POC and value area expansion"""
+        profile = CompositeVolumeProfile(sessions=0, decay=1.0, tick_sizes={'ES': 0.25})
+        for price, volume in [(99.5, 5), (99.75, 20), (100.0, 50), (100.25, 15), (100.5, 10)]:
+            profile.add_trade('ES', price, volume, DAY1)
+        assert profile.poc('ES') == 100.0
+        assert profile.value_area('ES', 0.7) == (99.75, 100.0)
+        assert profile.value_area('ES', 0.8) == (99.75, 100.25)
+
+    def test_hvn_lvn(self):
+        """Test local volume maxima/minima, including empty buckets as LVNs"""
+        profile = CompositeVolumeProfile(sessions=0, decay=1.0)
+        volumes = [5, 10, 30, 10, 5, 0, 5, 10, 20, 10, 5]
+        for i, volume in enumerate(volumes):
+            if volume:
+                profile.add_trade('MNQ', 100.0 + i * 0.25, volume, DAY1)
+        nodes = profile.hvn_lvn('MNQ', window=2)
+        assert nodes["hvn"] == [100.5, 102.0]
+        assert nodes["lvn"] == [101.25]
+
+    def test_naked_pocs(self):
+        """Test prior session POCs stay naked until traded through"""
+        profile = CompositeVolumeProfile(sessions=2, decay=0.9)
+        profile.add_trade('MNQ', 100.0, 50, DAY1)
+        profile.add_trade('MNQ', 101.0, 5, DAY1)
+        profile.add_trade('MNQ', 105.0, 1, DAY2)
+        naked = profile.naked_pocs('MNQ')
+        assert [n.price for n in naked] == [100.0]
+
+        profile.add_trade('MNQ', 102.0, 1, DAY2 + timedelta(minutes=1))
+        assert profile.naked_pocs('MNQ')[0].price == 100.0
+        profile.add_trade('MNQ', 99.5, 1, DAY2 + timedelta(minutes=2))
+        assert profile.naked_pocs('MNQ') == []
+
+    def test_late_trades_ignored(self):
+        """Test trades from an already-rolled session are dropped"""
+        profile = CompositeVolumeProfile(sessions=1, decay=1.0)
+        profile.add_trade('MNQ', 100.0, 1, DAY2)
+        profile.add_trade('MNQ', 200.0, 1, DAY1)
+        assert profile.get_composite('MNQ') == {100.0: 1}
+
+    def test_invalid_config(self):
+        """Test decay outside (0, 1] is rejected"""
+        with pytest.raises(ValueError):
+            CompositeVolumeProfile(decay=1.5)
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,76 @@
+"""
+Unit tests for session-anchored VWAP
+"""
+
+import pytest
+import math
+import os
+import sys
+from datetime import datetime, timezone, timedelta
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.bar_aggregator import Bar
+from core.vwap import SessionVWAP
+
+
+# 2025-11-19 09:30 US/Eastern (EST, UTC-5)
+RTH_OPEN = datetime(2025, 11, 19, 14, 30, 0, tzinfo=timezone.utc)
+
+
+class TestSessionVWAP:
+    """Test SessionVWAP"""
+
+    def test_vwap_and_bands(self):
+        """Test volume-weighted average and standard deviation bands"""
+        vwap = SessionVWAP(anchor='rth', band_multipliers=(1.0, 2.0))
+        vwap.update('MNQ', 100.0, 1, RTH_OPEN + timedelta(minutes=1))
+        vwap.update('MNQ', 110.0, 3, RTH_OPEN + timedelta(minutes=2))
+
+        snapshot = vwap.get_snapshot('mnq')
+        assert snapshot.vwap == pytest.approx(107.5)
+        assert snapshot.std_dev == pytest.approx(math.sqrt(18.75))
+        assert snapshot.bands[2.0][1] == pytest.approx(107.5 + 2 * math.sqrt(18.75))
+        assert snapshot.volume == 4
+        assert snapshot.anchor_start == RTH_OPEN
+
+    def test_resets_at_anchor(self):
+        """Test the next session's first tick starts a fresh VWAP"""
+        vwap = SessionVWAP(anchor='rth')
+        vwap.update('MNQ', 100.0, 5, RTH_OPEN + timedelta(hours=1))
+        # Overnight tick still belongs to today's RTH session
+        assert vwap.update('MNQ', 200.0, 5, RTH_OPEN + timedelta(hours=12)) == pytest.approx(150.0)
+        assert vwap.update('MNQ', 300.0, 1, RTH_OPEN + timedelta(days=1, minutes=1)) == pytest.approx(300.0)
+
+    def test_custom_anchor_and_dst(self):
+        """Test a custom HH:MM anchor resolves in exchange time across DST"""
+        vwap = SessionVWAP(anchor='18:00', tz='US/Eastern')
+        # Summer (EDT, UTC-4): 18:00 local = 22:00 UTC
+        summer = vwap.session_anchor(datetime(2025, 7, 1, 23, 0, tzinfo=timezone.utc))
+        assert summer == datetime(2025, 7, 1, 22, 0, tzinfo=timezone.utc)
+        # Before the anchor falls back to the previous day
+        winter = vwap.session_anchor(datetime(2025, 12, 2, 15, 0, tzinfo=timezone.utc))
+        assert winter == datetime(2025, 12, 1, 23, 0, tzinfo=timezone.utc)
+
+    def test_invalid_anchor(self):
+        """Test malformed anchors are rejected"""
+        with pytest.raises(ValueError, match="Invalid session anchor"):
+            SessionVWAP(anchor='25:00')
+
+    def test_zero_volume_and_late_ticks_ignored(self):
+        """Test zero-volume ticks and ticks from a previous session don't move VWAP"""
+        vwap = SessionVWAP(anchor='midnight')
+        assert vwap.update('MES', 100.0, 0, RTH_OPEN) is None
+        vwap.update('MES', 100.0, 2, RTH_OPEN)
+        assert vwap.update('MES', 500.0, 2, RTH_OPEN - timedelta(days=1)) == pytest.approx(100.0)
+
+    def test_add_bar_uses_typical_price(self):
+        """Test bar input uses (H+L+C)/3 weighted by volume"""
+        vwap = SessionVWAP()
+        vwap.add_bar(Bar('MNQ', '1m', RTH_OPEN, 10.0, 12.0, 9.0, 12.0, volume=10))
+        assert vwap.get_vwap('MNQ') == pytest.approx(11.0)
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,120 @@
+"""
+Unit tests for the multiplexed hub manager (core.websocket.manager)
+"""
+
+import pytest
+import asyncio
+import json
+import os
+import sys
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from core.market_events import Trade
+from core.websocket import HubConnectionError, OrderUpdate, WebSocketManager
+from core.websocket.manager import parse_shard_map
+from core.websocket.protocol import RECORD_SEPARATOR, parse_messages
+
+RS = RECORD_SEPARATOR
+
+
+class FakeConnection:
+    """In-memory WebSocket that accepts the handshake and collects sent frames."""
+
+    def __init__(self):
+        self.incoming = asyncio.Queue()
+        self.incoming.put_nowait('{}' + RS)
+        self.sent = []
+
+    async def send(self, data):
+        self.sent.append(data)
+
+    async def recv(self):
+        return await self.incoming.get()
+
+    async def close(self):
+        pass
+
+    def push(self, target, *arguments):
+        self.incoming.put_nowait(json.dumps({'type': 1, 'target': target, 'arguments': list(arguments)}) + RS)
+
+    def sent_targets(self):
+        return [m.target for frame in self.sent[1:] for m in parse_messages(frame)]
+
+
+class FakeNetwork:
+    """Hands out one FakeConnection per connect, by hub path; can refuse a path."""
+
+    def __init__(self):
+        self.connections = {}
+        self.refuse = set()
+
+    async def connect(self, url, headers):
+        path = url.split('/hubs/')[-1].split('?')[0]
+        if path in self.refuse:
+            raise OSError(f"{path} unreachable")
+        conn = FakeConnection()
+        self.connections.setdefault(path, []).append(conn)
+        return conn
+
+
+class TestWebSocketManager:
+    """Test shard routing, unified event delivery and per-connection health"""
+
+    def test_shard_routing(self):
+        """Test symbols map to stable shards and pinned shards win"""
+        assert parse_shard_map(' MNQ:0, mes:1 ') == {'MNQ': 0, 'MES': 1}
+        with pytest.raises(ValueError):
+            parse_shard_map('MNQ')
+        with pytest.raises(ValueError):
+            WebSocketManager(market_shards=2, shard_map={'MNQ': 2})
+
+        manager = WebSocketManager(market_shards=3, shard_map={'MES': 2}, user_hub=False)
+        assert [hub.client.name for hub in manager.hubs] == ['market-0', 'market-1', 'market-2']
+        assert manager.shard_for('CON.F.US.MNQ.Z25') == manager.shard_for('MNQ') == \
+            WebSocketManager(market_shards=3, user_hub=False).shard_for('mnq')
+        assert manager.shard_for('CON.F.US.MES.H26') == 2
+        shard = manager.subscribe('CON.F.US.MES.Z25', quotes=True, trades=True)
+        assert shard == 2 and manager.market_hubs[2].client.subscriptions()[0][1] == ['CON.F.US.MES.Z25']
+        assert all(not hub.client.subscriptions() for hub in manager.market_hubs[:2])
+        manager.unsubscribe('CON.F.US.MES.Z25', quotes=True, trades=True)
+        assert manager.get_health()['connections']['market-2']['contracts'] == []
+
+    @pytest.mark.asyncio
+    async def test_unified_events_and_health(self):
+        """Test market and user events arrive on one stream and a failed hub shows in health"""
+        network = FakeNetwork()
+        network.refuse.add('user')
+        manager = WebSocketManager(market_url='https://rtc.example.com/hubs/market',
+                                   user_url='https://rtc.example.com/hubs/user', market_shards=2,
+                                   keep_alive_interval=0, connector=network.connect)
+        manager.subscribe('CON.F.US.MNQ.Z25', quotes=False, trades=True)
+        manager.subscribe_account(123)
+        with pytest.raises(HubConnectionError, match='user'):
+            await manager.start()
+        health = manager.get_health()
+        assert not health['healthy'] and not health['connections']['user']['connected']
+        assert health['connections']['market-0']['connected'] and health['connections']['market-1']['connected']
+
+        network.refuse.clear()
+        await manager.start()  # Retries only the user hub
+        assert manager.connected and len(network.connections['market']) == 2
+        stream = manager.events(types=(Trade, OrderUpdate))
+        shard = manager.shard_for('MNQ')
+        market_conn = network.connections['market'][shard]
+        await asyncio.sleep(0.01)
+        assert market_conn.sent_targets() == ['SubscribeContractTrades']
+        market_conn.push('GatewayTrade', 'CON.F.US.MNQ.Z25', [{'price': 21000.0, 'volume': 1, 'type': 0}])
+        network.connections['user'][0].push('GatewayUserOrder', {'id': 1, 'accountId': 123, 'status': 2,
+                                                                  'contractId': 'CON.F.US.MNQ.Z25'})
+        events = [await asyncio.wait_for(stream.__anext__(), 1) for _ in range(2)]
+        assert sorted(type(e).__name__ for e in events) == ['OrderUpdate', 'Trade']
+        health = manager.get_health()
+        assert health['healthy'] and health['connections'][f'market-{shard}']['contracts'] == ['CON.F.US.MNQ.Z25']
+        await manager.stop()
+        assert [e async for e in stream] == [] and not manager.connected
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
@@ -0,0 +1,127 @@
+"""
+Unit tests for the write-ahead log between the bot and the database
+"""
+
+import pytest
+import os
+import sys
+from datetime import datetime, timezone
+
+# Add parent directory to path
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+from infrastructure.write_ahead_log import DurableWriter, WriteAheadLog
+from core.bar_aggregator import Bar
+from core.market_events import BarClosed, Trade
+
+T0 = datetime(2025, 11, 19, 14, 30, tzinfo=timezone.utc)
+
+
+class FakeDB:
+    """Records applied writes; raises while down is set."""
+
+    def __init__(self):
+        self.ticks = []
+        self.bars = []
+        self.fills = []
+        self.down = False
+
+    def write_market_data(self, ticks, bars, copy=True):
+        if self.down:
+            raise ConnectionError("database unreachable")
+        self.ticks.extend(ticks)
+        self.bars.extend(bars)
+
+    def record_fill(self, account_id, fill):
+        if self.down:
+            return False
+        self.fills.append((account_id, fill))
+        return True
+
+
+def make_writer(directory, db=None, **kwargs):
+    wal = WriteAheadLog(directory, fsync='never', **kwargs)
+    return DurableWriter(db or FakeDB(), wal, batch_size=100, flush_interval=0.05, retry_max_seconds=0.1), wal
+
+
+class TestWriteAheadLog:
+    """Test appending, applying in order, retrying and recovery after a restart."""
+
+    def test_applies_runs_in_order_and_checkpoints(self, tmp_path):
+        writer, wal = make_writer(tmp_path)
+        writer.on_market_event(Trade('MNQ', T0, 21000.25, 2, 'buy'))
+        writer.on_market_event(BarClosed('MNQ', T0, Bar('mnq', '1m', T0, 1.0, 2.0, 0.5, 1.5, 10, 3)))
+        writer.record_fill(123, {'fill_id': 1, 'symbol': 'MNQ', 'quantity': 1, 'price': 21000.25})
+        writer.on_market_event(Trade('MNQ', T0, 21000.5, 1, 'sell'))
+        assert writer.pending() == 4
+        assert writer.apply() == 4
+        db = writer.db
+        assert [row[2] for row in db.ticks] == [21000.25, 21000.5]
+        assert db.ticks[0][0] == T0 and db.ticks[0][4] == 0
+        assert db.bars[0][:3] == ('MNQ', '1m', T0)
+        assert db.fills == [('123', {'fill_id': 1, 'symbol': 'MNQ', 'quantity': 1, 'price': 21000.25})]
+        assert wal.committed == 4 and writer.pending() == 0
+        assert (tmp_path / 'checkpoint').read_text() == '4'
+
+    def test_database_outage_keeps_records_and_retries(self, tmp_path):
+        db = FakeDB()
+        writer, wal = make_writer(tmp_path, db)
+        db.down = True
+        writer.record_fill('1', {'fill_id': 1})
+        writer.record_fill('1', {'fill_id': 2})
+        assert writer.apply() == 0
+        stats = writer.get_stats()
+        assert stats['backlog_records'] == 2 and stats['write_errors'] == 1
+        assert stats['oldest_pending_seconds'] is not None and 'not written' in stats['last_error']
+        db.down = False
+        writer.start()
+        writer.stop()
+        assert [fill['fill_id'] for _, fill in db.fills] == [1, 2]
+        assert writer.get_stats()['backlog_records'] == 0
+
+    def test_replays_unapplied_records_after_restart(self, tmp_path):
+        writer, wal = make_writer(tmp_path)
+        for fill_id in (1, 2, 3):
+            writer.record_fill('1', {'fill_id': fill_id})
+        writer._pending = wal.read(1)
+        writer._apply_run(writer._next_run())
+        wal.commit(1)  # Crash after the first record was applied
+        wal.close()
+
+        writer, wal = make_writer(tmp_path)
+        assert wal.depth() == 2 and wal.next_seq == 4
+        assert writer.apply() == 2
+        assert [fill['fill_id'] for _, fill in writer.db.fills] == [2, 3]
+        assert writer.record_fill('1', {'fill_id': 4}) == 4
+
+    def test_torn_tail_is_truncated(self, tmp_path):
+        writer, wal = make_writer(tmp_path)
+        writer.record_fill('1', {'fill_id': 1})
+        writer.record_fill('1', {'fill_id': 2})
+        wal.close()
+        segment = next(tmp_path.glob('wal-*.log'))
+        data = segment.read_bytes()
+        segment.write_bytes(data[:-7])  # Power loss halfway through the second record
+
+        writer, wal = make_writer(tmp_path)
+        assert wal.depth() == 1 and segment.read_bytes() == data[:data.index(b'\n') + 1]
+        assert writer.record_fill('1', {'fill_id': 3}) == 2
+        assert writer.apply() == 2
+        assert [fill['fill_id'] for _, fill in writer.db.fills] == [1, 3]
+
+    def test_segments_roll_over_and_are_deleted_once_applied(self, tmp_path):
+        writer, wal = make_writer(tmp_path, segment_bytes=200)
+        for fill_id in range(10):
+            writer.record_fill('1', {'fill_id': fill_id})
+        assert len(list(tmp_path.glob('wal-*.log'))) > 2
+        assert writer.apply() == 10
+        assert len(list(tmp_path.glob('wal-*.log'))) == 1  # The active segment stays
+        assert writer.get_stats()['backlog_bytes'] < 200
+
+    def test_rejects_unknown_fsync_mode(self, tmp_path):
+        with pytest.raises(ValueError):
+            WriteAheadLog(tmp_path, fsync='sometimes')
+
+
+if __name__ == '__main__':
+    pytest.main([__file__, '-v'])
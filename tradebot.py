@@ -0,0 +1,322 @@
+"""
+tradebot - command line for manual operations
+
+Talks to the TopStepX API directly through the bot's own order, position
+and history code, so an operator can intervene from a terminal when the
+bot service (webhook server, strategies) is down or unresponsive. The bot
+is built without its background services (no artifact lock, failover lease,
+exporters or Redis), so the CLI also works next to a running or stuck bot
+in the same directory, and its orders are never refused as a standby's.
+
+Usage:
+    python tradebot.py accounts
+    python tradebot.py positions [--json]
+    python tradebot.py orders
+    python tradebot.py order place MNQ buy 1 [--limit 21000.25 | --stop 21010] [--sl-ticks 20 --tp-ticks 40]
+    python tradebot.py order cancel 123456
+    python tradebot.py order cancel-all [--symbol MNQ]
+    python tradebot.py flatten [MNQ]
+    python tradebot.py history download MNQ --timeframe 1m --start 2025-11-03 [--end 2025-11-07] [--out bars.csv]
+
+    Every command accepts --account ID_OR_NAME and --json; order place/
+    cancel-all and flatten ask for confirmation unless --yes is given (and
+    refuse without a terminal). Exit status: 0 ok, 1 failed, 2 bad usage.
+
+Configuration:
+- PROJECT_X_API_KEY / PROJECT_X_USERNAME: Credentials (TOPSETPX_* accepted)
+- TOPSTEPX_ACCOUNT_ID: Account used without --account (required when there are several)
+"""
+
+import argparse
+import asyncio
+import contextlib
+import json
+import os
+import sys
+from datetime import datetime, timezone
+from typing import Any, Callable, Dict, List, Optional, Sequence, TextIO
+
+from core.csv_io import CSV_SCHEMAS, write_bars_csv
+from core.volume_profile import normalize_symbol
+
+ORDER_SIDES = {0: 'buy', 1: 'sell'}
+ORDER_TYPES = {1: 'limit', 2: 'market', 4: 'stop', 5: 'trailing', 6: 'join bid', 7: 'join ask'}
+POSITION_TYPES = {1: 'long', 2: 'short'}
+
+
+class CommandError(Exception):
+    """Command failed (reported on stderr, exit status 1)."""
+
+
+def resolve_account(accounts: List[Dict], wanted: Optional[str]) -> Dict:
+    """
+    Account by ID or name; the only account when none is asked for.
+
+    Raises:
+        CommandError: Not found, or several accounts and none chosen
+    """
+    if wanted:
+        for account in accounts:
+            if str(account.get('id')) == str(wanted) or str(account.get('name', '')).lower() == wanted.lower():
+                return account
+        raise CommandError(f"account {wanted!r} not found (have: {', '.join(_account_names(accounts))})")
+    if len(accounts) == 1:
+        return accounts[0]
+    if not accounts:
+        raise CommandError("no accounts found")
+    raise CommandError(f"several accounts, choose one with --account or TOPSTEPX_ACCOUNT_ID "
+                       f"({', '.join(_account_names(accounts))})")
+
+
+def _account_names(accounts: List[Dict]) -> List[str]:
+    return [f"{account.get('name')} ({account.get('id')})" for account in accounts]
+
+
+def _parse_time(value: str) -> datetime:
+    try:
+        parsed = datetime.fromisoformat(value.replace('Z', '+00:00'))
+    except ValueError:
+        raise argparse.ArgumentTypeError(f"expected an ISO date or time, got {value!r}")
+    return parsed if parsed.tzinfo else parsed.replace(tzinfo=timezone.utc)
+
+
+def build_bot() -> Any:
+    """
+    REST-only TopStepXTradingBot from the PROJECT_X_* credentials.
+
+    Raises:
+        CommandError: Missing credentials, bad settings or artifacts held by another process
+    """
+    from infrastructure.file_lock import ArtifactLockError
+    from trading_bot import TopStepXTradingBot
+    api_key = os.getenv('PROJECT_X_API_KEY') or os.getenv('TOPSETPX_API_KEY')
+    username = os.getenv('PROJECT_X_USERNAME') or os.getenv('TOPSETPX_USERNAME')
+    if not api_key or not username:
+        raise CommandError("PROJECT_X_API_KEY and PROJECT_X_USERNAME must be set")
+    try:
+        return TopStepXTradingBot(api_key=api_key, username=username, background_services=False)
+    except (ArtifactLockError, ValueError) as e:
+        raise CommandError(str(e))
+
+
+def _check(result: Any, action: str) -> Any:
+    if isinstance(result, dict) and result.get('error'):
+        raise CommandError(f"{action} failed: {result['error']}")
+    return result
+
+
+class Cli:
+    """Runs one parsed command against a bot and writes the result to `out`."""
+
+    def __init__(self, bot: Any, args: argparse.Namespace, out: TextIO,
+                 confirm_input: Optional[Callable[[str], str]] = None):
+        self.bot = bot
+        self.args = args
+        self.out = out
+        self.confirm_input = confirm_input
+
+    def emit(self, data: Any, lines: Sequence[str]) -> None:
+        if self.args.json:
+            self.out.write(json.dumps(data, indent=2, default=str) + "\n")
+        else:
+            self.out.write("".join(f"{line}\n" for line in lines))
+
+    def confirm(self, prompt: str) -> None:
+        if self.args.yes:
+            return
+        confirm_input = self.confirm_input
+        if confirm_input is None:
+            if not sys.stdin.isatty():
+                raise CommandError(f"{prompt}: not confirmed (no terminal; pass --yes)")
+            confirm_input = input
+        answer = confirm_input(f"{prompt} on {self.bot.selected_account.get('name')}? [y/N] ")
+        if answer.strip().lower() not in ('y', 'yes'):
+            raise CommandError("cancelled")
+
+    async def select_account(self) -> Dict:
+        if not await self.bot._ensure_valid_token():
+            raise CommandError("authentication failed (check PROJECT_X_API_KEY / PROJECT_X_USERNAME)")
+        accounts = await self.bot.list_accounts()
+        if self.args.command == 'accounts':
+            return {}
+        account = resolve_account(accounts, self.args.account or os.getenv('TOPSTEPX_ACCOUNT_ID'))
+        self.bot.selected_account = account
+        return account
+
+    # ---------------------------
+    # Commands
+    # ---------------------------
+    async def accounts(self) -> None:
+        accounts = await self.bot.list_accounts()
+        self.emit(accounts, [f"{a.get('id'):>10}  {a.get('name')}  balance {a.get('balance')}"
+                             f"{'' if a.get('canTrade', True) else '  (cannot trade)'}" for a in accounts]
+                  or ["No accounts"])
+
+    async def positions(self) -> None:
+        positions = await self.bot.get_open_positions()
+        self.emit(positions, [
+            f"{p.get('id'):>10}  {normalize_symbol(p.get('contractId') or ''):<6} "
+            f"{POSITION_TYPES.get(p.get('type'), '?'):<5} {p.get('size'):>4} @ {p.get('averagePrice')}"
+            for p in positions] or ["No open positions"])
+
+    async def orders(self) -> None:
+        orders = await self.bot.get_open_orders()
+        self.emit(orders, [self._order_line(o) for o in orders] or ["No open orders"])
+
+    @staticmethod
+    def _order_line(order: Dict) -> str:
+        price = order.get('limitPrice') if order.get('limitPrice') is not None else order.get('stopPrice')
+        return (f"{order.get('id'):>10}  {normalize_symbol(order.get('contractId') or ''):<6} "
+                f"{ORDER_SIDES.get(order.get('side'), '?'):<4} {order.get('size'):>4} "
+                f"{ORDER_TYPES.get(order.get('type'), '?'):<8} {price if price is not None else ''}")
+
+    async def order_place(self) -> None:
+        args = self.args
+        kind = 'stop' if args.stop is not None else 'limit' if args.limit is not None else 'market'
+        price = args.stop if args.stop is not None else args.limit
+        self.confirm(f"Place {kind} {args.side} {args.quantity} {args.symbol.upper()}"
+                     f"{f' @ {price}' if price is not None else ''}")
+        if kind == 'stop':
+            result = await self.bot.place_stop_order(args.symbol.upper(), args.side.upper(), args.quantity, args.stop,
+                                                     strategy_name='cli')
+        else:
+            result = await self.bot.place_market_order(args.symbol.upper(), args.side.upper(), args.quantity,
+                                                       stop_loss_ticks=args.sl_ticks, take_profit_ticks=args.tp_ticks,
+                                                       order_type=kind, limit_price=args.limit, strategy_name='cli')
+        _check(result, "order")
+        order_id = result.get('orderId') if isinstance(result, dict) else None
+        self.emit(result, [f"✅ Order placed: {order_id}"])
+
+    async def order_cancel(self) -> None:
+        result = _check(await self.bot.cancel_order(self.args.order_id), f"cancel {self.args.order_id}")
+        self.emit(result, [f"✅ Order {self.args.order_id} cancelled"])
+
+    async def order_cancel_all(self) -> None:
+        orders = await self.bot.get_open_orders()
+        if self.args.symbol:
+            orders = [o for o in orders if normalize_symbol(o.get('contractId') or '') == self.args.symbol.upper()]
+        if not orders:
+            self.emit({"cancelled": [], "failed": []}, ["No open orders"])
+            return
+        self.confirm(f"Cancel {len(orders)} order(s)")
+        cancelled, failed = [], []
+        for order in orders:
+            result = await self.bot.cancel_order(order.get('id'))
+            (failed if isinstance(result, dict) and result.get('error') else cancelled).append(order.get('id'))
+        self.emit({"cancelled": cancelled, "failed": failed},
+                  [f"✅ Cancelled {len(cancelled)} order(s)"] + [f"❌ Failed to cancel {oid}" for oid in failed])
+        if failed:
+            raise CommandError(f"{len(failed)} order(s) not cancelled")
+
+    async def flatten(self) -> None:
+        symbol = self.args.symbol.upper() if self.args.symbol else None
+        if symbol is None:
+            self.confirm("Close ALL positions and cancel ALL orders")
+            result = _check(await self.bot.flatten_all_positions(interactive=False), "flatten")
+            self.emit(result, ["✅ Flattened all positions and cancelled all orders"])
+            return
+        positions = [p for p in await self.bot.get_open_positions()
+                     if normalize_symbol(p.get('contractId') or '') == symbol]
+        if not positions:
+            self.emit({"closed": []}, [f"No open {symbol} positions"])
+            return
+        self.confirm(f"Close {len(positions)} {symbol} position(s)")
+        closed = []
+        for position in positions:
+            _check(await self.bot.close_position(str(position.get('id'))), f"close {position.get('id')}")
+            closed.append(position.get('id'))
+        self.emit({"closed": closed}, [f"✅ Closed {len(closed)} {symbol} position(s)"])
+
+    async def history_download(self) -> None:
+        args = self.args
+        symbol = args.symbol.upper()
+        end = args.end or datetime.now(timezone.utc)
+        bars = await self.bot.history_client.fetch_bars(symbol, args.timeframe, args.start, end)
+        path = args.out or f"{symbol}_{args.timeframe}_{args.start:%Y%m%d}_{end:%Y%m%d}.csv"
+        written = write_bars_csv(path, bars, schema=args.schema)
+        self.emit({"path": path, "bars": written, "symbol": symbol, "timeframe": args.timeframe},
+                  [f"✅ {written} {symbol} {args.timeframe} bars written to {path}"])
+
+    async def run(self) -> None:
+        await self.select_account()
+        command = self.args.command
+        action = getattr(self.args, 'action', None)
+        handler = getattr(self, f"{command}_{action.replace('-', '_')}" if action else command)
+        await handler()
+
+
+def build_parser() -> argparse.ArgumentParser:
+    common = argparse.ArgumentParser(add_help=False)
+    common.add_argument('--account', help="Account ID or name (default: TOPSTEPX_ACCOUNT_ID, or the only account)")
+    common.add_argument('--json', action='store_true', help="Print JSON instead of text")
+    confirm = argparse.ArgumentParser(add_help=False)
+    confirm.add_argument('-y', '--yes', action='store_true', help="Don't ask for confirmation")
+
+    parser = argparse.ArgumentParser(prog='tradebot', description="Manual TopStepX operations")
+    commands = parser.add_subparsers(dest='command', required=True)
+    commands.add_parser('accounts', parents=[common], help="List accounts")
+    commands.add_parser('positions', parents=[common], help="List open positions")
+    commands.add_parser('orders', parents=[common], help="List open orders")
+
+    order = commands.add_parser('order', help="Place or cancel orders").add_subparsers(dest='action', required=True)
+    place = order.add_parser('place', parents=[common, confirm], help="Place an order (market unless --limit/--stop)")
+    place.add_argument('symbol')
+    place.add_argument('side', type=str.lower, choices=['buy', 'sell'])
+    place.add_argument('quantity', type=int)
+    price = place.add_mutually_exclusive_group()
+    price.add_argument('--limit', type=float, help="Limit price")
+    price.add_argument('--stop', type=float, help="Stop (entry) price")
+    place.add_argument('--sl-ticks', type=int, help="Bracket stop loss in ticks (market/limit)")
+    place.add_argument('--tp-ticks', type=int, help="Bracket take profit in ticks (market/limit)")
+    cancel = order.add_parser('cancel', parents=[common], help="Cancel one order")
+    cancel.add_argument('order_id')
+    cancel_all = order.add_parser('cancel-all', parents=[common, confirm], help="Cancel all open orders")
+    cancel_all.add_argument('--symbol', help="Only this symbol's orders")
+
+    flatten = commands.add_parser('flatten', parents=[common, confirm],
+                                  help="Close positions (all, cancelling all orders, or one symbol's)")
+    flatten.add_argument('symbol', nargs='?')
+
+    history = commands.add_parser('history', help="Historical data").add_subparsers(dest='action', required=True)
+    download = history.add_parser('download', parents=[common], help="Download bars to CSV")
+    download.add_argument('symbol')
+    download.add_argument('--timeframe', default='1m', help="Bar timeframe, e.g. 1m, 5m, 1h, 1d (default 1m)")
+    download.add_argument('--start', type=_parse_time, required=True, help="Range start (ISO date/time, UTC)")
+    download.add_argument('--end', type=_parse_time, help="Range end (default: now)")
+    download.add_argument('--out', help="CSV path (default: SYMBOL_TF_START_END.csv)")
+    download.add_argument('--schema', default='default', choices=sorted(CSV_SCHEMAS), help="CSV layout")
+    return parser
+
+
+def main(argv: Optional[Sequence[str]] = None, bot: Any = None, out: Optional[TextIO] = None,
+         confirm_input: Optional[Callable[[str], str]] = None) -> int:
+    """
+    Run one command.
+
+    Args:
+        argv: Arguments (default: sys.argv[1:])
+        bot: TopStepXTradingBot to use (default: one built from PROJECT_X_* credentials)
+        out: Where command output goes (default: stdout)
+        confirm_input: Confirmation prompt (default: input(), refused without a terminal)
+
+    Returns:
+        int: Exit status
+    """
+    args = build_parser().parse_args(argv)
+    args.yes = getattr(args, 'yes', False)
+    out = out or sys.stdout
+    # Library code prints progress to stdout; keep it on stderr so stdout is only command output
+    with contextlib.redirect_stdout(sys.stderr):
+        try:
+            asyncio.run(Cli(bot if bot is not None else build_bot(), args, out, confirm_input).run())
+        except CommandError as e:
+            sys.stderr.write(f"tradebot: {e}\n")
+            return 1
+        except KeyboardInterrupt:
+            sys.stderr.write("tradebot: interrupted\n")
+            return 1
+    return 0
+
+
+if __name__ == '__main__':
+    sys.exit(main())
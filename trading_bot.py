@@ -24,14 +24,19 @@ import hashlib
 import csv
 import jwt
 from pathlib import Path
-from typing import List, Dict, Optional, Any
+from typing import List, Dict, Optional, Any, Tuple, Union
 from datetime import datetime, timedelta, timezone
 from threading import Lock
 from collections import deque, OrderedDict
 import time
 import requests
-from requests.adapters import HTTPAdapter
-from urllib3.util.retry import Retry
+from infrastructure.http_transport import HttpTransportConfig, TransportAdapter
+from infrastructure.retry_budget import BudgetedRetry, get_retry_budget
+from infrastructure.request_timeouts import Deadline, RequestTimeouts, current_deadline, deadline_scope, tighter
+from core.api_models import (
+    AccountSearchResponse, ApiSchemaError, LoginResponse, OrderSearchResponse, OrderStatus, PlaceOrderResponse,
+    PositionSearchResponse,
+)
 from signalrcore.hub_connection_builder import HubConnectionBuilder
 from signalrcore.transport.websockets.websocket_transport import WebsocketTransport
 
@@ -44,6 +49,39 @@ from strategies.trend_following_strategy import TrendFollowingStrategy
 from strategies.strategy_manager import StrategyManager
 from infrastructure.performance_metrics import get_metrics_tracker
 from infrastructure.database import get_database
+from core.plugin_hooks import get_plugin_registry, LifecycleEvent
+from core.market_events import MarketEvent, Trade, Quote, DepthUpdate, BarClosed, GapRepaired
+from core.websocket import (
+    ChannelFanout, ConnectionState, FeedLatencyMonitor, FeedWatchdog, HubConnectionError, SignalRClient,
+    SubscriptionManager,
+)
+from core.websocket.client import parse_url_list
+from core.websocket.user_hub import DEFAULT_USER_HUB_URL, OrderUpdate, PositionUpdate, UserHubClient
+from core.websocket.market_hub import stream_for_method
+from core.footprint import FootprintAggregator
+from core.event_exporter import EventExporter
+from core.parquet_store import ParquetStore
+from core.tape_recorder import TapeRecorder
+from core.depth_book import DepthBook
+from core.history_client import HistoryClient
+from core.symbol_switches import SymbolTradingSwitches
+from core.fill_context import BookState, FillContextRecorder
+from core.slippage import SlippageTracker
+from core.strategy_engine.fees import CommissionModel
+from core.session_calendar import SessionCalendar
+from core.clock_sync import ServerClock, get_server_clock
+from core.market_data import resample_bar_dicts
+from core.tick_validator import TickValidator
+from core.order_rounding import get_venue_policy, normalize_order_payload, round_price
+from core.time_in_force import check_time_in_force, time_in_force_name
+from core.position_reconciler import PositionTracker, PositionConsistencyChecker, net_broker_positions
+from core.reconciliation import OrderTracker, Reconciler
+from core.break_even import BreakEvenManager
+from core.order_expiry import OrderExpiration, OrderExpiryScheduler
+from core.order_chaser import OrderChaser
+from infrastructure.leader_election import LeaderElector, LeaderRole, Lease, failover_enabled, create_lease_backend
+from infrastructure.redis_bridge import RedisBridge
+from infrastructure.file_lock import ArtifactLockError, acquire_artifact_lock, atomic_write
 
 # Optional ProjectX SDK adapter
 try:
@@ -96,6 +134,30 @@ logger.info("Logging initialized - file: trading_bot.log (INFO+), console: stdou
 # Bot identifier for order tagging - will be made unique per order
 BOT_ORDER_TAG_PREFIX = "TradingBot-v1.0"
 
+# Endpoints routed to the bulk HTTP pool (history downloads, searches, account polling).
+# Everything else (order placement/cancel/modify, position closes, auth) uses the
+# latency-critical pool so large transfers never delay order submissions.
+# Override with HTTP_BULK_ENDPOINTS (comma-separated path prefixes).
+BULK_ENDPOINT_PREFIXES = (
+    "/api/History/",
+    "/api/Contract/",
+    "/api/Account/search",
+    "/api/Order/search",
+    "/api/Fill/search",
+    "/api/Trade/search",
+    "/api/Position/searchOpen",
+)
+
+# Endpoints that change broker state. A standby instance (no leadership lease)
+# is refused these so only one bot of a failover pair ever submits orders.
+ORDER_FLOW_ENDPOINTS = (
+    "/api/Order/place",
+    "/api/Order/cancel",
+    "/api/Order/modify",
+    "/api/Position/closeContract",
+    "/api/Position/partialCloseContract",
+)
+
 
 class RateLimiter:
     """
@@ -163,7 +225,8 @@ class TopStepXTradingBot:
     Uses actual ProjectX API calls via cURL.
     """
     
-    def __init__(self, api_key: str = None, username: str = None, base_url: str = "https://api.topstepx.com"):
+    def __init__(self, api_key: str = None, username: str = None, base_url: str = "https://api.topstepx.com",
+                 http_transport: Optional[HttpTransportConfig] = None, background_services: bool = True):
         """
         Initialize the trading bot.
         
@@ -171,6 +234,12 @@ class TopStepXTradingBot:
             api_key: TopStepX API key
             username: TopStepX username
             base_url: TopStepX API base URL
+            http_transport: Proxy and TLS settings for API requests (default: HTTP_* environment variables)
+            background_services: False for one-shot REST use next to a running bot (the tradebot CLI):
+                no failover lease, exporters, recorders, Redis or write-ahead log
+        
+        Raises:
+            ValueError: Missing credentials or invalid proxy/TLS settings
         """
         self.api_key = api_key or os.getenv('PROJECT_X_API_KEY') or os.getenv('TOPSETPX_API_KEY')
         self.username = username or os.getenv('PROJECT_X_USERNAME') or os.getenv('TOPSETPX_USERNAME')
@@ -210,12 +279,28 @@ class TopStepXTradingBot:
         # Real-time depth cache: { SYMBOL: { 'bids': [], 'asks': [], 'ts': iso } }
         self._depth_cache: Dict[str, Dict] = {}
         self._depth_cache_lock: Lock = Lock()
+        # L2 order books maintained from depth updates: { SYMBOL: DepthBook }
+        self.depth_books: Dict[str, DepthBook] = {}
         # Contract list cache: { 'contracts': List[Dict], 'timestamp': datetime, 'ttl_minutes': int }
         self._contract_cache: Optional[Dict] = None
         self._contract_cache_lock: Lock = Lock()
         self._market_hub = None
         self._market_hub_connected = False
+        self.feed_watchdog: Optional[FeedWatchdog] = None  # Native SignalR client only
+        self.subscription_manager: Optional[SubscriptionManager] = None  # Native SignalR client only
+        # Exchange-to-receive latency and clock skew of the market feed (get_feed_latency)
+        self.feed_latency = FeedLatencyMonitor()
+        # Local clock corrected to broker/exchange time (bars, session windows, journal timestamps)
+        self.server_clock: ServerClock = get_server_clock()
+        self._feed_lag_blocks_entries = os.getenv("FEED_LAG_BLOCKS_ENTRIES", "false").lower() in ("true", "1", "yes", "on")
         self._market_hub_url = os.getenv("PROJECT_X_MARKET_HUB_URL", "https://rtc.topstepx.com/hubs/market")
+        # User hub streams orders/fills/positions instead of polling order history (USER_HUB_ENABLED)
+        self.user_hub: Optional[UserHubClient] = None
+        self._user_hub_enabled = os.getenv("USER_HUB_ENABLED", "false").lower() in ("true", "1", "yes", "on")
+        self._user_hub_url = os.getenv("PROJECT_X_USER_HUB_URL", DEFAULT_USER_HUB_URL)
+        self._streamed_fills: Dict[str, List[str]] = {}  # {account_id: [order_id, ...]} since last check
+        self._streamed_position_changes = set()  # Accounts with position updates since last check
+        self._order_fill_listeners: List = []  # Raw filled/partially filled orders (add_order_fill_listener)
         # Allow overriding hub method names via env to adapt without code change
         # Default to the naming used by the REST quote command (`/api/MarketData/quote`)
         # while still allowing overrides via env vars.
@@ -236,6 +321,9 @@ class TopStepXTradingBot:
         # Initialize Discord notifier
         self.discord_notifier = DiscordNotifier()
         
+        # Lifecycle plugin hooks (plugins listed in BOT_PLUGINS)
+        self.plugin_hooks = get_plugin_registry()
+        
         # Initialize PostgreSQL database (for persistent caching and state)
         try:
             self.db = get_database()
@@ -248,15 +336,179 @@ class TopStepXTradingBot:
         self.account_tracker = AccountTracker(db=self.db)
         logger.debug("Account tracker initialized with database support")
         
+        # Local position ledger + periodic broker consistency check
+        self.position_tracker = PositionTracker()
+        self.position_checker = PositionConsistencyChecker(
+            self.position_tracker,
+            fetch_positions=self._fetch_positions_strict,
+            symbol_resolver=self._get_symbol_from_contract_id,
+            alert_callback=self._on_position_divergence,
+        )
+        
+        # Local working-order ledger + periodic order/position reconciliation (broker vs local vs database)
+        self.order_tracker = OrderTracker(symbol_resolver=self._get_symbol_from_contract_id)
+        self.reconciler = Reconciler(
+            self.position_tracker,
+            self.order_tracker,
+            fetch_positions=self._fetch_positions_strict,
+            fetch_orders=self._fetch_open_orders_strict,
+            db=self.db,
+            symbol_resolver=self._get_symbol_from_contract_id,
+            report_callback=self._on_reconciliation_report,
+        )
+        
+        # Orders placed with expire_after_secs are cancelled if still working when the window ends
+        self.order_expiry = OrderExpiryScheduler(cancel=self.cancel_order, on_expired=self._on_order_expired)
+        
+        # Per-symbol trading switches (persisted blacklist enforced pre-trade)
+        self.symbol_switches = SymbolTradingSwitches(db=self.db)
+        
+        # Kill switch (control API): while engaged every new order is refused
+        self.kill_switch: Optional[Dict] = None
+        
+        # Venue rounding policy applied to every order payload before it is sent
+        self.rounding_policy = get_venue_policy()
+        
+        # Warm standby failover: only the lease holder may submit orders
+        self.leader_elector: Optional[LeaderElector] = None
+        if background_services and failover_enabled():
+            self.leader_elector = LeaderElector(
+                create_lease_backend(self.db),
+                takeover=self._prepare_takeover,
+                on_role_change=self._on_leader_role_change,
+            )
+            logger.warning(f"👑 Failover enabled - starting on standby as {self.leader_elector.holder}")
+        
         # Initialize Strategy Manager (modular strategy system)
         self.strategy_manager = StrategyManager(trading_bot=self)
         logger.debug("Strategy manager initialized")
         
+        # Paginated/chunked history downloads for long date ranges
+        self.history_client = HistoryClient(fetch=self._retrieve_bars, contract_resolver=self._get_contract_id)
+        
         # Initialize bar aggregator for real-time chart updates
         from core.bar_aggregator import BarAggregator
-        self.bar_aggregator = BarAggregator(broadcast_callback=None)  # Will be set by webhook server
+        self.session_calendar = SessionCalendar()
+        self._market_event_listeners: List = []  # Typed MarketEvent consumers
+        self.market_event_channels = ChannelFanout()  # Bounded per-consumer channels (add_market_event_consumer)
+        self.bar_aggregator = BarAggregator(
+            broadcast_callback=None,  # Will be set by webhook server
+            bar_close_callback=lambda bar: self._publish_market_event(BarClosed.from_bar(bar)),
+            gap_backfill=self.history_client.fetch_bars,  # Repair bars lost to websocket dropouts
+            gap_callback=lambda gap: self._publish_market_event(GapRepaired.from_gap(gap)),
+            session_calendar=self.session_calendar,  # Session-anchored bars, RTH flag
+        )
         logger.debug("Bar aggregator initialized")
         
+        # Bad prints are dropped before they reach bars or trade listeners
+        self.tick_validator: Optional[TickValidator] = None
+        if os.getenv('TICK_VALIDATION_ENABLED', 'true').lower() in ('true', '1', 'yes', 'on'):
+            self.tick_validator = TickValidator()
+            self.bar_aggregator.on_bar_close(self.tick_validator.add_bar, timeframes=[self.tick_validator.atr_timeframe])
+        
+        # Order-flow footprints from the trade stream (opt-in: needs SubscribeContractTrades)
+        self.footprint_aggregator: Optional[FootprintAggregator] = None
+        if os.getenv('FOOTPRINT_ENABLED', 'false').lower() in ('true', '1', 'yes', 'on'):
+            self.footprint_aggregator = FootprintAggregator()
+            self.add_market_event_listener(self.footprint_aggregator.on_market_event)
+            # Stacked imbalances go out on the event bus so strategies can subscribe to "imbalance" events
+            self.footprint_aggregator.on_imbalance(self._publish_market_event)
+            logger.info(f"📊 Footprint bars enabled: {', '.join(self.footprint_aggregator.timeframes)}")
+        
+        # Full session record of the event bus as JSONL (opt-in)
+        self.event_exporter: Optional[EventExporter] = None
+        if background_services and os.getenv('EVENT_EXPORT_ENABLED', 'false').lower() in ('true', '1', 'yes', 'on'):
+            try:
+                self.event_exporter = EventExporter()
+                self.event_exporter.start()
+                self.add_market_event_listener(self.event_exporter.on_market_event)
+            except (ValueError, OSError) as e:
+                self.event_exporter = None
+                logger.error(f"❌ Event export disabled: {e}")
+        
+        # Trades and completed bars streamed into partitioned Parquet files (opt-in)
+        self.parquet_store: Optional[ParquetStore] = None
+        if background_services and os.getenv('PARQUET_STORE_ENABLED', 'false').lower() in ('true', '1', 'yes', 'on'):
+            self.parquet_store = ParquetStore()
+            self.parquet_store.start()
+            self.add_market_event_listener(self.parquet_store.on_market_event)
+        
+        # Time-and-sales tape with spill-to-disk (opt-in)
+        self.tape_recorder: Optional[TapeRecorder] = None
+        if background_services and os.getenv('TAPE_RECORDER_ENABLED', 'false').lower() in ('true', '1', 'yes', 'on'):
+            self.tape_recorder = TapeRecorder()
+            self.tape_recorder.start()
+            self.add_market_event_listener(self.tape_recorder.on_market_event)
+        
+        # Database writes go through an on-disk write-ahead log so outages never block order flow (opt-in)
+        self.durable_writer = None
+        if background_services and self.db and os.getenv(
+                'DB_WAL_ENABLED', 'false').lower() in ('true', '1', 'yes', 'on'):
+            try:
+                self.durable_writer = self.db.get_durable_writer()
+            except (ValueError, OSError, ArtifactLockError) as e:
+                logger.error(f"❌ Write-ahead log disabled: {e}")
+        
+        # Live trades and completed bars batched into PostgreSQL (opt-in)
+        if background_services and self.db and os.getenv(
+                'DB_MARKET_DATA_WRITER_ENABLED', 'false').lower() in ('true', '1', 'yes', 'on'):
+            writer = self.durable_writer or self.db.get_market_data_writer()
+            self.add_market_event_listener(writer.on_market_event)
+        
+        # Quotes, positions and fills mirrored into Redis for external consumers (enabled by REDIS_URL)
+        self.redis_bridge: Optional[RedisBridge] = None
+        if background_services and os.getenv('REDIS_URL'):
+            try:
+                self.redis_bridge = RedisBridge()
+                self.redis_bridge.start()
+                self.add_market_event_listener(self.redis_bridge.on_market_event)
+                self.plugin_hooks.register(LifecycleEvent.ORDER_FILLED, self.redis_bridge.on_order_filled,
+                                           name='redis_bridge')
+            except (ValueError, ImportError) as e:
+                self.redis_bridge = None
+                logger.error(f"❌ Redis bridge disabled: {e}")
+        
+        # Pegged limit orders repriced toward the market on live quotes (place_pegged_order)
+        try:
+            self.order_chaser = OrderChaser(modify=self.modify_order, cancel=self.cancel_order,
+                                            place_market=self.place_market_order)
+        except ValueError as e:
+            logger.error(f"❌ Invalid order chase configuration, using defaults: {e}")
+            self.order_chaser = OrderChaser(modify=self.modify_order, cancel=self.cancel_order,
+                                            place_market=self.place_market_order, interval_ms=250,
+                                            max_offset_ticks=4, max_failures=3)
+        self.add_market_event_listener(self.order_chaser.on_market_event)
+        
+        # Stops moved to break-even on live quotes once a position is far enough in profit (opt-in)
+        self.break_even_manager: Optional[BreakEvenManager] = None
+        if os.getenv('BREAK_EVEN_ENABLED', 'false').lower() in ('true', '1', 'yes', 'on'):
+            try:
+                self.break_even_manager = BreakEvenManager(modify=self.modify_order, find_legs=self.find_bracket_legs)
+                self.add_market_event_listener(self.break_even_manager.on_market_event)
+                logger.info(f"🔧 Break-even stops enabled: +{self.break_even_manager.trigger_ticks} ticks "
+                            f"-> entry {self.break_even_manager.offset_ticks:+d} ticks")
+            except ValueError as e:
+                logger.error(f"❌ Break-even stops disabled: {e}")
+        
+        # Per-contract commission and exchange fees, deducted from locally computed realized P&L
+        try:
+            self.commission_model = CommissionModel()
+        except ValueError as e:
+            logger.error(f"❌ Invalid commission configuration, fees not deducted: {e}")
+            self.commission_model = CommissionModel({}, {}, 0.0, 0.0)
+        
+        # Every fill journaled with its strategy (trades_for_day / pnl_by_strategy read the journal)
+        self.trade_journal_enabled = bool(self.db) and os.getenv(
+            'TRADE_JOURNAL_ENABLED', 'true').lower() in ('true', '1', 'yes', 'on')
+        
+        # Market context captured at order submission and at fill for enriched fill notifications
+        self.fill_context = FillContextRecorder(book_provider=self.get_book_state)
+        self.fill_context.register_indicator('last_1m_bar', self._last_bar_indicator)
+        if self.footprint_aggregator is not None:
+            self.fill_context.register_indicator('cumulative_delta', self.footprint_aggregator.get_cumulative_delta)
+        # Signal, submit and fill price of every order the bot places (get_slippage_report)
+        self.slippage_tracker = SlippageTracker()
+        
         # Register all available strategies
         self.strategy_manager.register_strategy("overnight_range", OvernightRangeStrategy)
         self.strategy_manager.register_strategy("mean_reversion", MeanReversionStrategy)
@@ -298,6 +550,12 @@ class TopStepXTradingBot:
             self._cache_format = 'parquet'
             logger.warning(f"Invalid CACHE_FORMAT, defaulting to 'parquet'")
         
+        # History cache directory (relative paths are under the project, not the working directory);
+        # claimed by claim_artifact_dirs() when the bot starts
+        self.history_cache_dir = Path(os.getenv('HISTORY_CACHE_DIR', '.cache'))
+        if not self.history_cache_dir.is_absolute():
+            self.history_cache_dir = Path(__file__).resolve().parent / self.history_cache_dir
+        
         logger.debug(f"Cache initialized: format={self._cache_format}, memory_cache_size={memory_cache_max}")
         
         # Monitoring state - only monitor after market orders are placed
@@ -322,8 +580,22 @@ class TopStepXTradingBot:
         self._prefetch_timeframes = [tf.strip() for tf in os.getenv('PREFETCH_TIMEFRAMES', '1m,5m').split(',')]
         self._prefetch_task = None
         
-        # HTTP session with connection pooling for efficient API calls
+        # HTTP sessions with connection pooling for efficient API calls.
+        # _http_session is the latency-critical pool (orders, position closes, auth);
+        # bulk endpoints get their own pool so they can't starve order submissions.
+        # Both go through the configured proxy and TLS settings (corporate proxies, certificate pinning).
+        self.http_transport = http_transport or HttpTransportConfig.from_env()
+        self.request_timeouts = RequestTimeouts()  # Per-operation timeouts (cancel/order/history)
+        if self.http_transport.customized:
+            logger.info(f"🔒 API transport: {self.http_transport.describe()}")
         self._http_session = self._create_http_session()
+        self._bulk_http_session = self._create_http_session(
+            pool_maxsize=int(os.getenv('HTTP_BULK_POOL_SIZE', '10'))
+        )
+        env_bulk = os.getenv('HTTP_BULK_ENDPOINTS')
+        self._bulk_endpoint_prefixes = (
+            tuple(p.strip() for p in env_bulk.split(',') if p.strip()) if env_bulk else BULK_ENDPOINT_PREFIXES
+        )
 
     # ---------------------------
     # SignalR Market Hub Support
@@ -335,7 +607,9 @@ class TopStepXTradingBot:
         """
         if self._market_hub_connected:
             return
-        
+        if isinstance(self._market_hub, SignalRClient) and self._market_hub.state == ConnectionState.RECONNECTING:
+            return  # The native client is reconnecting and will replay its subscriptions
+
         # Don't auto-start SDK realtime - it will start on-demand when needed
         # This avoids unnecessary overhead and WebSocket errors
         use_sdk = os.getenv("USE_PROJECTX_SDK", "0").lower() in ("1", "true", "yes")
@@ -361,27 +635,39 @@ class TopStepXTradingBot:
         elif url_ws.startswith("http://"):
             url_ws = "ws://" + url_ws[len("http://"):]
 
-        hub = (
-            HubConnectionBuilder()
-            .with_url(
+        # SIGNALR_CLIENT=native uses the asyncio client in core.websocket on this event loop
+        native_client = os.getenv("SIGNALR_CLIENT", "signalrcore").lower() == "native"
+        if native_client:
+            hub = SignalRClient(
                 url_ws,
-                options={
-                    "headers": headers,
-                    "skip_negotiation": True,
-                    "access_token_factory": (lambda: self.session_token or ""),
-                    "transport": WebsocketTransport
-                }
+                access_token_factory=(lambda: self.session_token or ""),
+                headers=headers,
+                skip_negotiation=True,
+                name="market",
+                backup_urls=parse_url_list(os.getenv("PROJECT_X_MARKET_HUB_BACKUP_URLS")),
+            )
+        else:
+            hub = (
+                HubConnectionBuilder()
+                .with_url(
+                    url_ws,
+                    options={
+                        "headers": headers,
+                        "skip_negotiation": True,
+                        "access_token_factory": (lambda: self.session_token or ""),
+                        "transport": WebsocketTransport
+                    }
+                )
+                # Implement exponential backoff: [5s, 10s, 20s, 40s, 60s, 60s, ...]
+                # Max 10 attempts to prevent infinite retry spam
+                .with_automatic_reconnect({
+                    "type": "raw", 
+                    "keep_alive_interval": 15,
+                    "reconnect_interval": 5,  # Start at 5 seconds
+                    "max_attempts": 10  # Limit retry attempts
+                })
+                .build()
             )
-            # Implement exponential backoff: [5s, 10s, 20s, 40s, 60s, 60s, ...]
-            # Max 10 attempts to prevent infinite retry spam
-            .with_automatic_reconnect({
-                "type": "raw", 
-                "keep_alive_interval": 15,
-                "reconnect_interval": 5,  # Start at 5 seconds
-                "max_attempts": 10  # Limit retry attempts
-            })
-            .build()
-        )
 
         def on_open():
             logger.info("✅ SignalR Market Hub connected")
@@ -460,6 +746,9 @@ class TopStepXTradingBot:
                         logger.warning(f"⚠️  Received quote payload without resolvable symbol. cid={cid}, data_keys={list(data.keys())}")
                         self._missing_symbol_log_count += 1
                     return
+                self.feed_latency.record_payload("quotes", symbol, data)
+                self.server_clock.record_payload(data)
+                now = self.server_clock.now()
                 with self._quote_cache_lock:
                     entry = self._quote_cache.setdefault(symbol, {})
                     # GatewayQuote payload fields per docs
@@ -471,19 +760,26 @@ class TopStepXTradingBot:
                         entry["last"] = data.get("lastPrice")
                     if "volume" in data:
                         entry["volume"] = data.get("volume")
-                    entry["ts"] = datetime.now(datetime.UTC).isoformat()
+                    entry["ts"] = now.isoformat()
+                
+                if self._market_event_listeners:
+                    self._publish_market_event(Quote.from_gateway(symbol, data, now))
                 
                 # Feed quote to bar aggregator for real-time bar updates
                 if hasattr(self, 'bar_aggregator') and self.bar_aggregator:
                     last_price = data.get("lastPrice")
                     volume = data.get("volume", 0)
+                    if last_price is not None and self.tick_validator is not None:
+                        if self.tick_validator.validate(symbol, float(last_price), now,
+                                                        bid=data.get("bestBid"), ask=data.get("bestAsk")) is not None:
+                            last_price = None  # Bad print: keep it out of the bars
                     if last_price is not None:
                         try:
                             self.bar_aggregator.add_quote(
                                 symbol=symbol,
                                 price=float(last_price),
                                 volume=int(volume) if volume else 0,
-                                timestamp=datetime.now(datetime.UTC)
+                                timestamp=now
                             )
                             # Log first few quotes per symbol to verify flow
                             if not hasattr(self, '_quote_log_count'):
@@ -523,7 +819,7 @@ class TopStepXTradingBot:
                 if isinstance(cid, str) and "." in cid:
                     parts = cid.split(".")
                     symbol = parts[-2].upper() if len(parts) >= 2 else cid
-                if not symbol:
+                if not symbol and isinstance(data, dict):
                     sym_id = (data.get("symbol") or data.get("symbolId") or "").upper()
                     if sym_id and "." in sym_id:
                         parts = sym_id.split(".")
@@ -533,6 +829,13 @@ class TopStepXTradingBot:
                 if not symbol:
                     return
                 
+                # Maintain the L2 book (GatewayDepth sends lists of DOM entries)
+                self.feed_latency.record_payload("depth", symbol, data)
+                self.server_clock.record_payload(data)
+                self.get_depth_book(symbol).apply_gateway(data)
+                if not isinstance(data, dict):
+                    return
+                
                 with self._depth_cache_lock:
                     entry = self._depth_cache.setdefault(symbol, {})
                     # Handle different depth data formats
@@ -544,13 +847,42 @@ class TopStepXTradingBot:
                         order_book = data.get("orderBook", {})
                         entry["bids"] = order_book.get("bids", [])
                         entry["asks"] = order_book.get("asks", [])
-                    entry["ts"] = datetime.now(datetime.UTC).isoformat()
+                    entry["ts"] = self.server_clock.now().isoformat()
+                
+                if self._market_event_listeners:
+                    self._publish_market_event(DepthUpdate.from_gateway(symbol, data, self.server_clock.now()))
             except Exception as e:
                 logger.debug(f"Failed processing depth message: {e}")
 
+        def on_trade(*args):
+            try:
+                # GatewayTrade: (contractId, [trade, ...]) or a single trade dict
+                cid = args[0] if len(args) >= 2 else ""
+                payload = args[1] if len(args) >= 2 else (args[0] if args else [])
+                trades = payload if isinstance(payload, list) else [payload]
+                symbol = ""
+                if isinstance(cid, str) and "." in cid:
+                    parts = cid.split(".")
+                    symbol = parts[-2].upper() if len(parts) >= 2 else cid
+                if symbol:
+                    self.feed_latency.record_payload("trades", symbol, trades)
+                    self.server_clock.record_payload(trades)
+                if not symbol or not self._market_event_listeners:
+                    return
+                for data in trades:
+                    if isinstance(data, dict) and data.get("price") is not None:
+                        trade = Trade.from_gateway(symbol, data)
+                        if self.tick_validator is not None and \
+                                self.tick_validator.validate(symbol, trade.price, trade.timestamp) is not None:
+                            continue
+                        self._publish_market_event(trade)
+            except Exception as e:
+                logger.debug(f"Failed processing trade message: {e}")
+
         hub.on_open(on_open)
         hub.on_close(on_close)
         hub.on_error(on_error)
+        hub.on("GatewayTrade", on_trade)
         # Register multiple likely quote event names; env var takes precedence
         event_names = [
             self._market_hub_quote_event,
@@ -579,6 +911,20 @@ class TopStepXTradingBot:
             except Exception:
                 pass
 
+        if native_client:
+            self._market_hub = hub
+            if self.feed_watchdog is not None:
+                self.feed_watchdog.stop()
+            self.feed_watchdog = FeedWatchdog(hub, session_calendar=self.session_calendar)
+            self.subscription_manager = SubscriptionManager(hub, contract_resolver=self._get_contract_id)
+            try:
+                await hub.start()
+            except HubConnectionError as e:
+                logger.error(f"❌ SignalR Market Hub connect failed: {e}")
+                return
+            self.feed_watchdog.start()
+            return
+
         # Start the hub (non-blocking)
         hub.start()
         self._market_hub = hub
@@ -589,6 +935,212 @@ class TopStepXTradingBot:
         while not self._market_hub_connected and time.time() - start < 10:
             time.sleep(0.05)
 
+    def add_market_event_listener(self, callback) -> None:
+        """
+        Subscribe to typed market events (Trade, Quote, DepthUpdate, BarClosed, GapRepaired).
+        
+        Callbacks run on the SignalR thread and must not block.
+        
+        Args:
+            callback: Callable taking a MarketEvent
+        """
+        if callback not in self._market_event_listeners:
+            self._market_event_listeners.append(callback)
+    
+    def remove_market_event_listener(self, callback) -> None:
+        """Unsubscribe a market event listener."""
+        if callback in self._market_event_listeners:
+            self._market_event_listeners.remove(callback)
+    
+    def add_market_event_consumer(self, name: str, callback, capacity: Optional[int] = None,
+                                  policy: Optional[str] = None):
+        """
+        Deliver market events to a callback through its own bounded channel.
+        
+        Unlike add_market_event_listener, the callback runs in a task on the
+        event loop (call this from the loop), so a slow consumer only backs up
+        its own channel. When the channel is full, policy decides what gives:
+        'drop_oldest', 'conflate' (latest quote per symbol) or 'block' (waits
+        on the SignalR thread; drops on the event loop). Counters are in
+        /health under market_event_channels.
+        
+        Args:
+            name: Consumer name
+            callback: Sync or async callable taking a MarketEvent
+            capacity: Channel capacity (env: FEED_CHANNEL_CAPACITY)
+            policy: Overflow policy (env: FEED_CHANNEL_POLICY)
+            
+        Returns:
+            BoundedChannel: The consumer's channel
+        """
+        channel = self.market_event_channels.add_consumer(name, callback, capacity, policy)
+        self.add_market_event_listener(self.market_event_channels.publish)
+        return channel
+    
+    def market_events(self, types=None, capacity: Optional[int] = None, policy: Optional[str] = None):
+        """
+        Market events as an async iterator, for asyncio code on the event loop.
+        
+        Usage:
+            async for event in bot.market_events(types=(Quote, Trade)):
+                ...
+        
+        Args:
+            types: Only these MarketEvent classes (default: all)
+            capacity: Channel capacity (env: FEED_CHANNEL_CAPACITY)
+            policy: Overflow policy (env: FEED_CHANNEL_POLICY)
+            
+        Returns:
+            EventStream: Call close() (or use `async with`) to stop receiving
+        """
+        self.add_market_event_listener(self.market_event_channels.publish)
+        return self.market_event_channels.events(types, capacity, policy)
+    
+    def remove_market_event_consumer(self, name: str) -> None:
+        """Remove a consumer added with add_market_event_consumer."""
+        self.market_event_channels.remove(name)
+        if not len(self.market_event_channels):
+            self.remove_market_event_listener(self.market_event_channels.publish)
+    
+    def get_feed_latency(self, stream: Optional[str] = None) -> Dict:
+        """
+        Market feed latency (exchange timestamp vs local receive time).
+        
+        Args:
+            stream: 'quotes', 'trades' or 'depth' (default: all)
+            
+        Returns:
+            Dict: Raw and skew-adjusted p50/p95/p99 in ms, clock_skew_ms, and
+            lagging (adjusted p95 above FEED_MAX_LAG_MS)
+        """
+        return self.feed_latency.get_feed_latency(stream)
+    
+    def get_clock_offset(self) -> Dict:
+        """
+        Estimated offset of the local clock from broker/exchange time.
+        
+        Returns:
+            Dict: offset_ms (positive: local clock behind), the offset applied to
+            the bot's clock, its source ('feed' or 'http') and sample counts
+        """
+        return self.server_clock.get_stats()
+    
+    def get_depth_book(self, symbol: str) -> DepthBook:
+        """Get (or create) the L2 depth book for a symbol."""
+        symbol = symbol.upper()
+        with self._depth_cache_lock:
+            book = self.depth_books.get(symbol)
+            if book is None:
+                book = self.depth_books[symbol] = DepthBook(symbol)
+            return book
+    
+    def get_book_state(self, symbol: str) -> Optional[BookState]:
+        """Top-of-book state from the synced depth book, falling back to the quote cache."""
+        symbol = symbol.upper()
+        book = self.depth_books.get(symbol)
+        if book is not None and book.is_synced:
+            bid, ask = book.best_bid(), book.best_ask()
+            if bid or ask:
+                return BookState(bid=bid[0] if bid else None, ask=ask[0] if ask else None,
+                                 bid_size=bid[1] if bid else None, ask_size=ask[1] if ask else None,
+                                 imbalance=book.imbalance(1), source="depth_book")
+        with self._quote_cache_lock:
+            quote = dict(self._quote_cache.get(symbol) or {})
+        if not quote:
+            return None
+        return BookState(bid=quote.get("bid"), ask=quote.get("ask"), last=quote.get("last"), source="quote")
+    
+    def _last_bar_indicator(self, symbol: str) -> Optional[Dict]:
+        """Last completed 1m bar, for fill context snapshots."""
+        bar = self.bar_aggregator.get_last_completed_bar(symbol.upper(), '1m')
+        if bar is None:
+            return None
+        return {"timestamp": bar.timestamp.isoformat(), "open": bar.open, "high": bar.high,
+                "low": bar.low, "close": bar.close, "volume": bar.volume}
+    
+    def _capture_order_submission(self, order_request: Optional[Dict], response: Any) -> None:
+        """Snapshot market state for an accepted order (called from the HTTP layer)."""
+        try:
+            if not order_request or not isinstance(response, dict) or not response.get("orderId"):
+                return
+            type_map = {1: 'Limit', 2: 'Market', 4: 'Stop', 5: 'Stop Limit'}
+            submission = self.fill_context.capture_submission(
+                order_id=str(response["orderId"]),
+                symbol=self._get_symbol_from_contract_id(order_request.get("contractId", "")),
+                side='BUY' if order_request.get("side", 0) == 0 else 'SELL',
+                quantity=order_request.get("size", 0),
+                order_type=type_map.get(order_request.get("type"), 'Unknown'),
+                limit_price=order_request.get("limitPrice"),
+                stop_price=order_request.get("stopPrice"),
+                custom_tag=order_request.get("customTag"),
+            )
+            self.slippage_tracker.record_submission(
+                submission.order_id, submission.symbol, submission.side, submission.order_type,
+                submit_price=submission.reference_price, quantity=submission.quantity,
+                strategy=self._strategy_from_custom_tag(submission.custom_tag),
+            )
+        except Exception as e:
+            logger.debug(f"Failed to capture order submission context: {e}")
+    
+    def get_slippage_report(self, symbol: Optional[str] = None, strategy: Optional[str] = None) -> Dict:
+        """
+        Slippage between signal, submission and fill for the bot's filled orders.
+        
+        Args:
+            symbol: Only this symbol
+            strategy: Only this strategy
+            
+        Returns:
+            Dict: 'overall', 'by_symbol', 'by_strategy' and 'by_order_type' summaries
+            in ticks (positive = adverse), see SlippageTracker.get_slippage_report
+        """
+        return self.slippage_tracker.get_slippage_report(symbol=symbol, strategy=strategy)
+    
+    def add_order_fill_listener(self, callback) -> None:
+        """
+        Subscribe to order fills (e.g. OrderExecutor.on_fill for scale_in/scale_out ladders).
+        
+        Callbacks run on the event loop and must not block. They get the raw order dict for
+        each fill and, while the user hub streams, each partial fill; fillVolume is cumulative,
+        so the same order can be reported more than once.
+        
+        Args:
+            callback: Callable taking an order dict
+        """
+        if callback not in self._order_fill_listeners:
+            self._order_fill_listeners.append(callback)
+    
+    def remove_order_fill_listener(self, callback) -> None:
+        """Unsubscribe an order fill listener."""
+        if callback in self._order_fill_listeners:
+            self._order_fill_listeners.remove(callback)
+    
+    def _publish_order_fill(self, order: Dict) -> None:
+        """Deliver a (partial) fill to all order fill listeners, isolating listener errors."""
+        for listener in list(self._order_fill_listeners):
+            try:
+                listener(order)
+            except Exception as e:
+                logger.warning(f"Order fill listener error (order {order.get('id')}): {e}")
+    
+    def _publish_market_event(self, event: MarketEvent) -> None:
+        """Deliver a market event to all listeners, isolating listener errors."""
+        for listener in list(self._market_event_listeners):
+            try:
+                listener(event)
+            except Exception as e:
+                logger.debug(f"Market event listener error ({event.type} {event.symbol}): {e}")
+    
+    def _market_hub_subscribe(self, method: str, arguments: List[Any]) -> None:
+        """Invoke a market hub subscription (the native client replays it after reconnects)."""
+        stream = stream_for_method(method)
+        if self.subscription_manager is not None and stream and arguments:
+            self.subscription_manager.subscribe(stream, arguments[0])
+        elif isinstance(self._market_hub, SignalRClient):
+            self._market_hub.subscribe(method, arguments)
+        else:
+            self._market_hub.send(method, arguments)
+
     async def _ensure_quote_subscription(self, symbol: str) -> None:
         """
         Subscribe to real-time quotes using ProjectX Gateway Market Hub API.
@@ -613,7 +1165,9 @@ class TopStepXTradingBot:
             
             # Per ProjectX docs: invoke SubscribeContractQuotes with contract ID string
             logger.info(f"📡 Subscribing to live quotes for {sym} (contract: {contract_id})")
-            self._market_hub.send("SubscribeContractQuotes", [contract_id])
+            self._market_hub_subscribe("SubscribeContractQuotes", [contract_id])
+            if self.footprint_aggregator is not None:
+                self._market_hub_subscribe("SubscribeContractTrades", [contract_id])
             
             self._subscribed_symbols.add(sym)
             logger.info(f"✅ Subscribed to GatewayQuote events for {sym} via {contract_id}")
@@ -649,7 +1203,7 @@ class TopStepXTradingBot:
             subscribed = False
             for method in depth_methods:
                 try:
-                    self._market_hub.send(method, [cid])
+                    self._market_hub_subscribe(method, [cid])
                     logger.debug(f"Attempted depth subscription for {sym} via {cid} using {method}")
                     # Don't log success immediately - wait to see if it actually works
                     subscribed = True
@@ -672,22 +1226,27 @@ class TopStepXTradingBot:
         if not self.api_key or not self.username:
             raise ValueError("API key and username must be provided either as parameters or environment variables")
     
-    def _create_http_session(self) -> requests.Session:
+    def _create_http_session(self, pool_maxsize: int = 20) -> requests.Session:
         """
         Create a reusable HTTP session with connection pooling.
         This significantly improves performance by reusing TCP connections.
         
+        Args:
+            pool_maxsize: Maximum number of connections to keep in the pool
+        
         Returns:
-            requests.Session: Configured session with connection pooling
+            requests.Session: Configured session with connection pooling, proxy and TLS settings
         """
         session = requests.Session()
         
         # Configure connection pooling
-        # Use HTTPAdapter with connection pool for better performance
-        adapter = HTTPAdapter(
+        # Use HTTPAdapter with connection pool for better performance; its pools apply the TLS settings
+        adapter = TransportAdapter(
+            self.http_transport,
             pool_connections=10,  # Number of connection pools to cache
-            pool_maxsize=20,  # Maximum number of connections to save in the pool
-            max_retries=Retry(
+            pool_maxsize=pool_maxsize,  # Maximum number of connections to save in the pool
+            max_retries=BudgetedRetry(
+                budget=get_retry_budget(),  # Shared across pools so retries can't storm the API
                 total=3,
                 backoff_factor=0.3,
                 status_forcelist=[500, 502, 503, 504],
@@ -697,10 +1256,24 @@ class TopStepXTradingBot:
         
         session.mount("http://", adapter)
         session.mount("https://", adapter)
+        self.http_transport.apply(session)
         
         return session
     
-    def _make_curl_request(self, method: str, endpoint: str, data: Dict = None, headers: Dict = None, skip_rate_limit: bool = False, suppress_errors: bool = False) -> Dict:
+    def _select_http_pool(self, endpoint: str):
+        """
+        Pick the HTTP pool for an endpoint.
+        
+        Returns:
+            tuple: (pool_name, session) where pool_name is 'critical' or 'bulk'
+        """
+        bulk_session = getattr(self, '_bulk_http_session', None)
+        if bulk_session is not None and endpoint.startswith(self._bulk_endpoint_prefixes):
+            return "bulk", bulk_session
+        return "critical", self._http_session
+    
+    def _make_curl_request(self, method: str, endpoint: str, data: Dict = None, headers: Dict = None, skip_rate_limit: bool = False, suppress_errors: bool = False,
+                           timeout: Optional[float] = None, deadline: Union[Deadline, float, None] = None) -> Dict:
         """
         Make HTTP request using requests library with connection pooling and rate limiting.
         
@@ -715,15 +1288,23 @@ class TopStepXTradingBot:
             headers: Request headers
             skip_rate_limit: If True, skip rate limiting (for critical operations)
             suppress_errors: If True, log errors as debug instead of error (for expected failures)
+            timeout: Per-attempt timeout in seconds (default: the endpoint's operation timeout)
+            deadline: Overall deadline (seconds or Deadline) for the request including retries;
+                the enclosing deadline_scope() applies too, whichever expires first
             
         Returns:
-            Dict: Response data
+            Dict: Response data ({"error": ..., "deadline_exceeded": True} when the deadline ran out)
         """
+        if endpoint in ORDER_FLOW_ENDPOINTS and not self.order_flow_enabled():
+            logger.error(f"🚫 Standby instance - refusing {method} {endpoint} (no leadership lease)")
+            return {"error": "Order flow disabled: standby instance without leadership lease", "standby": True}
+        
         # Start performance tracking
         start_time = time.time()
         status_code = None
         success = False
         error_message = None
+        pool_name, http_session = self._select_http_pool(endpoint)
         
         # Check if token is expired (synchronous check)
         # Note: Actual refresh must be done by caller if 401/403 is returned
@@ -734,15 +1315,24 @@ class TopStepXTradingBot:
         if not skip_rate_limit:
             self._rate_limiter.acquire()
         
+        deadline = tighter(Deadline.coerce(deadline), current_deadline())
+        if deadline is not None and deadline.expired:
+            return self._deadline_exceeded(method, endpoint, "before the request was sent")
+        
+        try:
+            get_metrics_tracker(db=getattr(self, 'db', None)).record_http_pool_start(pool_name)
+        except Exception as metrics_err:
+            logger.debug(f"Failed to record pool metrics: {metrics_err}")
+        
         try:
             url = f"{self.base_url}{endpoint}"
             
-            # Get timeout from environment or use default
-            api_timeout = int(os.getenv('API_TIMEOUT', '30'))
+            # Per-operation timeout (API_TIMEOUT_* / API_TIMEOUT), capped by the deadline on every attempt
+            api_timeout = timeout if timeout is not None else self.request_timeouts.timeout_for(endpoint)
             
             # Prepare request kwargs
             request_kwargs = {
-                'timeout': api_timeout,
+                'timeout': self.request_timeouts.request_timeout(endpoint, api_timeout, deadline),
                 'headers': headers or {}
             }
             
@@ -762,14 +1352,17 @@ class TopStepXTradingBot:
             
             logger.debug(f"HTTP {method} request to {endpoint}")
             
-            # Make request using session (connection pooling enabled)
-            response = self._http_session.request(
-                method=method,
-                url=url,
-                **request_kwargs
-            )
+            # Make request using the endpoint's pool (connection pooling enabled)
+            sent_at = time.time()
+            with deadline_scope(deadline):  # Adapter retries give up when the deadline would be overrun
+                response = http_session.request(
+                    method=method,
+                    url=url,
+                    **request_kwargs
+                )
             
             status_code = response.status_code
+            self.server_clock.record_http_date(getattr(response, 'headers', {}).get('Date'), sent_at)
             
             # Handle response
             try:
@@ -798,6 +1391,8 @@ class TopStepXTradingBot:
                 
                 response_data = response.json()
                 success = True
+                if endpoint == "/api/Order/place":
+                    self._capture_order_submission(data, response_data)
                 return response_data
             except json.JSONDecodeError as e:
                 error_message = f"Invalid JSON response: {e}"
@@ -806,14 +1401,27 @@ class TopStepXTradingBot:
                 return {"error": error_message}
                 
         except requests.exceptions.Timeout:
+            self.request_timeouts.record_timeout(endpoint)
+            if deadline is not None and (deadline.expired or deadline.retries_refused):
+                result = self._deadline_exceeded(method, endpoint, f"after {deadline.seconds}s")
+                error_message = result["error"]
+                return result
             error_message = "Request timed out"
             logger.error(f"HTTP request timed out after {api_timeout}s")
             return {"error": error_message}
         except requests.exceptions.ConnectionError as e:
+            if deadline is not None and (deadline.expired or deadline.retries_refused):
+                result = self._deadline_exceeded(method, endpoint, f"after {deadline.seconds}s ({e})")
+                error_message = result["error"]
+                return result
             error_message = f"Connection error: {str(e)}"
             logger.error(f"HTTP connection error: {e}")
             return {"error": error_message}
         except Exception as e:
+            if deadline is not None and deadline.retries_refused:
+                result = self._deadline_exceeded(method, endpoint, f"after {deadline.seconds}s ({e})")
+                error_message = result["error"]
+                return result
             error_message = str(e)
             logger.error(f"HTTP request failed: {str(e)}")
             return {"error": str(e)}
@@ -830,9 +1438,16 @@ class TopStepXTradingBot:
                     success=success,
                     error_message=error_message
                 )
+                metrics_tracker.record_http_pool_call(pool_name, duration_ms, success)
             except Exception as metrics_err:
                 logger.debug(f"Failed to record metrics: {metrics_err}")
     
+    def _deadline_exceeded(self, method: str, endpoint: str, detail: str) -> Dict:
+        """Record and log a request that ran out of its deadline; returns the error response."""
+        self.request_timeouts.record_deadline_exceeded(endpoint)
+        logger.error(f"⏱️  Deadline exceeded for {method} {endpoint} {detail}")
+        return {"error": f"Deadline exceeded {detail}", "deadline_exceeded": True}
+    
     async def authenticate(self) -> bool:
         """
         Authenticate with the TopStepX API using username and API key.
@@ -862,9 +1477,10 @@ class TopStepXTradingBot:
                 logger.error(f"Authentication failed: {response['error']}")
                 return False
             
-            # Check if login was successful
-            if response.get("success") and response.get("token"):
-                self.session_token = response["token"]
+            # Check if login was successful (raises ApiSchemaError on an unexpected response)
+            login = LoginResponse.parse(response)
+            if login.success:
+                self.session_token = login.token
                 
                 # Parse JWT to extract expiration time
                 try:
@@ -923,10 +1539,11 @@ class TopStepXTradingBot:
                     await self._ensure_market_socket_started()
                 except Exception as sock_err:
                     logger.warning(f"Failed to start market hub (will fallback to REST): {sock_err}")
+                await self.plugin_hooks.dispatch(LifecycleEvent.POST_AUTH, self, username=self.username,
+                                                 token_expiry=self.token_expiry)
                 return True
             else:
-                error_msg = response.get("errorMessage", "Unknown error")
-                logger.error(f"Authentication failed: {error_msg}")
+                logger.error(f"Authentication failed: {login.error_text}")
                 return False
             
         except Exception as e:
@@ -1007,24 +1624,17 @@ class TopStepXTradingBot:
                 logger.error(f"Failed to fetch accounts: {response['error']}")
                 return []
             
-            # Parse the response - adjust based on actual API response structure
-            if isinstance(response, list):
-                accounts = response
-            elif isinstance(response, dict) and "accounts" in response:
-                accounts = response["accounts"]
-            elif isinstance(response, dict) and "data" in response:
-                accounts = response["data"]
-            elif isinstance(response, dict) and "result" in response:
-                accounts = response["result"]
-            else:
-                logger.warning(f"Unexpected API response format: {response}")
-                accounts = []
+            search = AccountSearchResponse.parse(response)
+            if not search.success:
+                logger.error(f"Failed to fetch accounts: {search.error_text}")
+                return []
             
             # Normalize account data structure
             normalized_accounts = []
-            for account in accounts:
+            for typed_account in search.accounts:
+                account = typed_account.raw
                 # Determine account type from name or other fields
-                account_name = account.get("name") or account.get("accountName", "Unknown Account")
+                account_name = typed_account.name
                 account_type = "unknown"
                 
                 if "PRAC" in account_name.upper():
@@ -1037,10 +1647,10 @@ class TopStepXTradingBot:
                     account_type = "evaluation"
                 
                 normalized_account = {
-                    "id": account.get("id") or account.get("accountId"),
+                    "id": typed_account.id,
                     "name": account_name,
                     "status": account.get("status", "active"),
-                    "balance": account.get("balance", 0.0),
+                    "balance": typed_account.balance,
                     "currency": account.get("currency", "USD"),
                     "account_type": account_type
                 }
@@ -1117,7 +1727,8 @@ class TopStepXTradingBot:
                     self.account_tracker.initialize(
                         account_id=selected_account['id'],
                         starting_balance=account_balance,
-                        account_type=account_type
+                        account_type=account_type,
+                        currency=selected_account.get('currency', 'USD')
                     )
                     logger.info(f"Account tracker initialized for {selected_account['name']} (${account_balance:,.2f})")
                     
@@ -1486,118 +2097,574 @@ class TopStepXTradingBot:
             if not target_account:
                 return {"error": "No account selected"}
 
+            account_key = str(target_account)
+            if self._user_hub_streaming(account_key):
+                return await self._drain_streamed_fills(account_key)
+
             # Get order history to check for fills - limit to recent orders only
             orders = await self.get_order_history(target_account, limit=10)  # Reduced from 50 to 10
-            filled_orders = []
+            filled_orders = await self._process_order_fills(account_key, orders)
 
-            account_key = str(target_account)
+            # Also check for position closes (manual closes, TP hits, stop hits)
+            await self._check_position_closes(target_account)
 
-            # Warm-up notifications after restart so we don't re-announce historical fills
-            if not self._notification_warmup_done.get(account_key):
-                warmup_count = 0
-                for order in orders:
-                    order_id = str(order.get('id', ''))
-                    status = order.get('status', '')
-                    if isinstance(status, int):
-                        is_filled = status in [2, 3, 4]
-                    else:
-                        status_str = str(status).lower()
-                        is_filled = status_str in ['filled', 'executed', 'complete']
+            # Check for any order fills that might have closed positions
+            await self._check_order_fills_for_closes(target_account)
 
-                    if is_filled:
-                        unique_id = f"{account_key}:{order_id}"
-                        self._notified_orders.add(unique_id)
-                        warmup_count += 1
+            # Once warmed up, stream further updates over the user hub instead of polling
+            await self._ensure_user_hub_started(account_key)
 
-                if warmup_count:
-                    logger.info(f"🔕 Notification warm-up: marked {warmup_count} existing filled orders for account {account_key}")
-                self._notification_warmup_done[account_key] = True
+            return {
+                "success": True,
+                "checked_orders": len(orders),
+                "filled_orders": len(filled_orders),
+                "new_fills": filled_orders
+            }
 
+        except Exception as e:
+            logger.error(f"Failed to check order fills: {str(e)}")
+            return {"error": str(e)}
+    
+    async def _process_order_fills(self, account_key: str, orders: List[Dict]) -> List[str]:
+        """
+        Notify and ledger newly filled orders (from order history or the user hub).
+
+        Returns:
+            List[str]: IDs of orders a fill notification was sent for
+        """
+        filled_orders = []
+
+        # Warm-up notifications after restart so we don't re-announce historical fills
+        if not self._notification_warmup_done.get(account_key):
+            warmup_count = 0
             for order in orders:
                 order_id = str(order.get('id', ''))
-                unique_id = f"{account_key}:{order_id}"
-                if unique_id in self._notified_orders:
-                    continue  # Already notified
-
-                # Check if order is filled
                 status = order.get('status', '')
-                # Handle both string and integer status values
                 if isinstance(status, int):
-                    # Status codes: 1=Open, 2=Filled, 3=Executed, 4=Complete, 5=Cancelled
                     is_filled = status in [2, 3, 4]
                 else:
-                    # Handle string status
                     status_str = str(status).lower()
                     is_filled = status_str in ['filled', 'executed', 'complete']
-                
+
                 if is_filled:
-                    # CRITICAL: Only notify for orders we placed (with customTag) - check BEFORE processing
-                    custom_tag = order.get('customTag', '')
-                    if not custom_tag or not custom_tag.startswith('TradingBot-v1.0'):
-                        # Skip orders not placed by our bot, but still mark as notified to avoid re-checking
-                        self._notified_orders.add(unique_id)
-                        continue
+                    unique_id = f"{account_key}:{order_id}"
+                    self._notified_orders.add(unique_id)
+                    warmup_count += 1
+
+            if warmup_count:
+                logger.info(f"🔕 Notification warm-up: marked {warmup_count} existing filled orders for account {account_key}")
+            self._notification_warmup_done[account_key] = True
+
+        for order in orders:
+            order_id = str(order.get('id', ''))
+            unique_id = f"{account_key}:{order_id}"
+            if unique_id in self._notified_orders:
+                continue  # Already notified
+
+            # Check if order is filled
+            status = order.get('status', '')
+            # Handle both string and integer status values
+            if isinstance(status, int):
+                # Status codes: 1=Open, 2=Filled, 3=Executed, 4=Complete, 5=Cancelled
+                is_filled = status in [2, 3, 4]
+            else:
+                # Handle string status
+                status_str = str(status).lower()
+                is_filled = status_str in ['filled', 'executed', 'complete']
+                
+            if is_filled:
+                # Every new fill (ours, brackets, manual) moves the local position ledger
+                self._apply_fill_to_position_tracker(account_key, order)
+                self.order_tracker.apply_update(account_key, order)
+                self.order_expiry.forget(order_id)
+                self.order_chaser.on_fill(order_id)
+                self.slippage_tracker.record_fill(
+                    order_id, order.get('fillPrice') or order.get('executionPrice') or order.get('filledPrice'),
+                    order.get('fillVolume') or order.get('size'))
+                if self.break_even_manager is not None:
+                    self.break_even_manager.on_fill(account_key, order)
+                self._publish_order_fill(order)
+                await self._journal_fill(account_key, order)
+                    
+                # CRITICAL: Only notify for orders we placed (with customTag) - check BEFORE processing
+                custom_tag = order.get('customTag', '')
+                if not custom_tag or not custom_tag.startswith('TradingBot-v1.0'):
+                    # Skip orders not placed by our bot, but still mark as notified to avoid re-checking
+                    self._notified_orders.add(unique_id)
+                    continue
                     
-                    # Additional validation: ensure order has a fill price (actually filled, not just status change)
-                    fill_price = order.get('fillPrice') or order.get('executionPrice') or order.get('filledPrice')
-                    if not fill_price:
-                        logger.debug(f"Order {order_id} marked as filled but has no fill price - skipping notification")
-                        self._notified_orders.add(unique_id)
-                        continue
+                # Additional validation: ensure order has a fill price (actually filled, not just status change)
+                fill_price = order.get('fillPrice') or order.get('executionPrice') or order.get('filledPrice')
+                if not fill_price:
+                    logger.debug(f"Order {order_id} marked as filled but has no fill price - skipping notification")
+                    self._notified_orders.add(unique_id)
+                    continue
                     
-                    # Get order details
-                    symbol = self._get_symbol_from_contract_id(order.get('contractId', ''))
-                    side = 'BUY' if order.get('side', 0) == 0 else 'SELL'
-                    quantity = order.get('size', 0)
-                    order_type = order.get('type', 0)
+                # Get order details
+                symbol = self._get_symbol_from_contract_id(order.get('contractId', ''))
+                side = 'BUY' if order.get('side', 0) == 0 else 'SELL'
+                quantity = order.get('size', 0)
+                order_type = order.get('type', 0)
 
-                    # Map order type to string
-                    type_map = {1: 'Limit', 2: 'Market', 4: 'Stop', 5: 'Stop Limit'}
-                    order_type_str = type_map.get(order_type, 'Unknown')
+                # Map order type to string
+                type_map = {1: 'Limit', 2: 'Market', 4: 'Stop', 5: 'Stop Limit'}
+                order_type_str = type_map.get(order_type, 'Unknown')
 
-                    # Get position ID if available
-                    position_id = order.get('positionId', 'Unknown')
+                # Get position ID if available
+                position_id = order.get('positionId', 'Unknown')
 
-                    # Send Discord notification
-                    try:
-                        account_name = self.selected_account.get('name', 'Unknown') if self.selected_account else 'Unknown'
+                # Send Discord notification
+                try:
+                    account_name = self.selected_account.get('name', 'Unknown') if self.selected_account else 'Unknown'
                             
-                        notification_data = {
-                            'symbol': symbol,
-                            'side': side,
-                            'quantity': quantity,
-                            'fill_price': f"${float(fill_price):.2f}" if fill_price else "Unknown",
-                            'order_type': order_type_str,
-                            'order_id': order_id,
-                            'position_id': position_id
-                        }
+                    notification_data = {
+                        'symbol': symbol,
+                        'side': side,
+                        'quantity': quantity,
+                        'fill_price': f"${float(fill_price):.2f}" if fill_price else "Unknown",
+                        'order_type': order_type_str,
+                        'order_id': order_id,
+                        'position_id': position_id,
+                        'context': self.fill_context.build_fill_context(order_id, symbol, side, float(fill_price)),
+                    }
+                    await self.plugin_hooks.dispatch(LifecycleEvent.ORDER_FILLED, self, account_id=account_key,
+                                                     fill=notification_data, order=order)
 
-                        logger.info(f"📢 Sending Discord notification for filled order: {order_id} ({symbol} {side} x{quantity} @ ${fill_price})")
-                        self.discord_notifier.send_order_fill_notification(notification_data, account_name)
-                        self._notified_orders.add(unique_id)
-                        filled_orders.append(order_id)
+                    logger.info(f"📢 Sending Discord notification for filled order: {order_id} ({symbol} {side} x{quantity} @ ${fill_price})")
+                    self.discord_notifier.send_order_fill_notification(notification_data, account_name)
+                    self._notified_orders.add(unique_id)
+                    filled_orders.append(order_id)
 
-                    except Exception as notif_err:
-                        logger.warning(f"Failed to send order fill notification: {notif_err}")
-                        # Still mark as notified to avoid retrying
-                        self._notified_orders.add(unique_id)
+                except Exception as notif_err:
+                    logger.warning(f"Failed to send order fill notification: {notif_err}")
+                    # Still mark as notified to avoid retrying
+                    self._notified_orders.add(unique_id)
 
-            # Also check for position closes (manual closes, TP hits, stop hits)
-            await self._check_position_closes(target_account)
+        return filled_orders
 
-            # Check for any order fills that might have closed positions
-            await self._check_order_fills_for_closes(target_account)
+    # ---------------------------
+    # SignalR User Hub Support
+    # ---------------------------
+    async def _ensure_user_hub_started(self, account_id: str) -> None:
+        """Stream order/fill/position updates for an account over the user hub (USER_HUB_ENABLED)."""
+        if not self._user_hub_enabled or not self.session_token:
+            return
+        if self.user_hub is None:
+            self.user_hub = UserHubClient(self._user_hub_url, access_token_factory=(lambda: self.session_token or ""),
+                                          backup_urls=parse_url_list(os.getenv("PROJECT_X_USER_HUB_BACKUP_URLS")))
+            self.user_hub.add_listener(self._on_user_hub_event)
+        self.user_hub.subscribe_account(account_id)
+        if self.user_hub.client.state == ConnectionState.DISCONNECTED:
+            try:
+                await self.user_hub.start()
+                logger.info(f"✅ Streaming orders, fills and positions for account {account_id} over the user hub")
+            except HubConnectionError as e:
+                logger.warning(f"⚠️  User hub unavailable, polling order history instead: {e}")
 
-            return {
-                "success": True,
-                "checked_orders": len(orders),
-                "filled_orders": len(filled_orders),
-                "new_fills": filled_orders
-            }
+    def _user_hub_streaming(self, account_id: str) -> bool:
+        """True while the user hub is connected and streaming an account."""
+        return self.user_hub is not None and self.user_hub.connected and self.user_hub.is_subscribed(account_id)
+
+    def _on_user_hub_event(self, event) -> None:
+        """User hub listener: handle fills right away, flag position changes for the next check."""
+        if isinstance(event, OrderUpdate):
+            self.order_tracker.apply_update(event.account_id, event.raw)
+        if isinstance(event, OrderUpdate) and event.is_filled:
+            asyncio.ensure_future(self._on_streamed_fill(event))
+        elif isinstance(event, OrderUpdate) and event.fill_volume:
+            self._publish_order_fill(event.raw)  # Partial fill: the order is still working
+        elif isinstance(event, PositionUpdate):
+            self._streamed_position_changes.add(event.account_id)
 
+    async def _on_streamed_fill(self, event: OrderUpdate) -> None:
+        try:
+            notified = await self._process_order_fills(event.account_id, [event.raw])
+            self._streamed_fills.setdefault(event.account_id, []).extend(notified)
         except Exception as e:
-            logger.error(f"Failed to check order fills: {str(e)}")
-            return {"error": str(e)}
+            logger.warning(f"Failed to process streamed fill for order {event.order_id}: {e}")
+
+    async def _drain_streamed_fills(self, account_id: str) -> Dict:
+        """check_order_fills result from streamed updates (no order history request)."""
+        fills = self._streamed_fills.pop(account_id, [])
+        if account_id in self._streamed_position_changes:
+            self._streamed_position_changes.discard(account_id)
+            await self._check_position_closes(account_id)
+        return {
+            "success": True,
+            "checked_orders": 0,
+            "filled_orders": len(fills),
+            "new_fills": fills,
+            "source": "user_hub"
+        }
+
+    def _apply_fill_to_position_tracker(self, account_id: str, order: Dict) -> None:
+        """Apply a filled order to the local position ledger (skipped until the ledger is seeded)."""
+        try:
+            if not self.position_tracker.has_account(account_id):
+                return
+            quantity = order.get('fillVolume') or order.get('size') or 0
+            symbol = self._get_symbol_from_contract_id(order.get('contractId', ''))
+            if symbol and quantity:
+                self.position_tracker.apply_fill(account_id, symbol, order.get('side', 0), int(quantity))
+                if self.redis_bridge is not None:
+                    self.redis_bridge.set_positions(account_id, self.position_tracker.get_positions(account_id))
+        except Exception as e:
+            logger.warning(f"Failed to apply fill to position ledger: {e}")
+    
+    def _order_fees(self, order: Dict, symbol: str, quantity: int) -> float:
+        """Commission and fees of quantity contracts of a filled order: as reported by the broker, else modeled."""
+        reported = order.get('fees') if order.get('fees') is not None else order.get('commission')
+        size = order.get('size') or order.get('fillVolume')
+        if reported is not None and size:
+            return float(reported) * quantity / float(size)  # Prorated over partial closes
+        return self.commission_model.cost(symbol, quantity)
+    
+    @staticmethod
+    def _strategy_from_custom_tag(custom_tag: Optional[str]) -> Optional[str]:
+        """Strategy name from a customTag (TradingBot-v1.0-strategy-{strategy_name}-{order_type}-...)."""
+        if not custom_tag or '-strategy-' not in custom_tag:
+            return None
+        return custom_tag.split('-strategy-')[1].split('-')[0] or None
+    
+    async def _journal_fill(self, account_id: str, order: Dict) -> None:
+        """Journal a filled order, attributed to the strategy in its customTag (replays are ignored)."""
+        if not self.trade_journal_enabled:
+            return
+        price = order.get('fillPrice') or order.get('executionPrice') or order.get('filledPrice')
+        quantity = order.get('fillVolume') or order.get('size') or 0
+        if not price or not quantity:
+            return
+        custom_tag = str(order.get('customTag') or '')
+        symbol = self._get_symbol_from_contract_id(order.get('contractId', '')) or order.get('contractId')
+        fill = {
+            'fill_id': order.get('id'),
+            'order_id': order.get('id'),
+            'strategy_name': self._strategy_from_custom_tag(custom_tag),
+            'symbol': symbol,
+            'side': order.get('side', 0),
+            'quantity': int(quantity),
+            'price': float(price),
+            'commission': self._order_fees(order, symbol or '', int(quantity)),
+            'timestamp': (order.get('executionTimestamp') or order.get('updateTimestamp')
+                          or self.server_clock.now().isoformat()),
+            'custom_tag': custom_tag or None,
+        }
+        try:
+            if self.durable_writer is not None:
+                self.durable_writer.record_fill(account_id, fill)
+            else:
+                await asyncio.to_thread(self.db.record_fill, account_id, fill)
+        except Exception as e:
+            logger.warning(f"Failed to journal fill: {e}")
+    
+    async def _fetch_positions_strict(self, account_id: str) -> List[Dict]:
+        """
+        Fetch open positions, raising on API failure.
+        
+        Unlike get_open_positions (which returns [] on error), the consistency
+        checker must never mistake a failed request for a flat account.
+        """
+        if not self.session_token:
+            raise RuntimeError("No session token available")
+        headers = {
+            "accept": "text/plain",
+            "Content-Type": "application/json",
+            "Authorization": f"Bearer {self.session_token}"
+        }
+        response = self._make_curl_request("POST", "/api/Position/searchOpen",
+                                           data={"accountId": int(account_id)}, headers=headers)
+        if "error" in response:
+            raise RuntimeError(f"Position search failed: {response['error']}")
+        search = PositionSearchResponse.parse(response)
+        if not search.success:
+            raise RuntimeError(f"Position search failed: {search.error_text}")
+        return [position.raw for position in search.positions]
+    
+    async def _fetch_open_orders_strict(self, account_id: str) -> List[Dict]:
+        """Fetch working orders, raising on API failure (see _fetch_positions_strict)."""
+        if not self.session_token:
+            raise RuntimeError("No session token available")
+        headers = {
+            "accept": "text/plain",
+            "Content-Type": "application/json",
+            "Authorization": f"Bearer {self.session_token}"
+        }
+        response = self._make_curl_request("POST", "/api/Order/searchOpen",
+                                           data={"accountId": int(account_id)}, headers=headers)
+        if "error" in response:
+            raise RuntimeError(f"Open order search failed: {response['error']}")
+        search = OrderSearchResponse.parse(response, "/api/Order/searchOpen")
+        if not search.success:
+            raise RuntimeError(f"Open order search failed: {search.error_text}")
+        return [order.raw for order in search.orders]
+    
+    def order_flow_enabled(self) -> bool:
+        """True if this instance may submit orders (always, unless failover is enabled and we are standby)."""
+        elector = getattr(self, 'leader_elector', None)
+        return elector is None or elector.is_leader
+    
+    async def reconcile_account_state(self, account_id: str) -> Tuple[Dict[str, int], List[Dict]]:
+        """
+        Reseed the local position ledger from broker positions and load working orders.
+        
+        Args:
+            account_id: Account to reconcile
+            
+        Returns:
+            Tuple of (net position per symbol, open orders)
+            
+        Raises:
+            RuntimeError: If broker positions cannot be loaded
+        """
+        positions = await self._fetch_positions_strict(str(account_id))
+        ledger = net_broker_positions(positions, self._get_symbol_from_contract_id)
+        self.position_tracker.set_positions(str(account_id), ledger)
+        if self.redis_bridge is not None:
+            self.redis_bridge.set_positions(account_id, ledger)
+        open_orders = await self.get_open_orders(str(account_id))
+        self.order_tracker.set_orders(str(account_id), open_orders)
+        return ledger, open_orders
+    
+    async def _prepare_takeover(self, lease: Lease) -> bool:
+        """
+        Reconcile open state with the broker before enabling order flow.
+        
+        Reseeds the local position ledger from broker positions and loads open
+        orders so the new leader starts from what the previous leader left behind.
+        
+        Args:
+            lease: Newly acquired leadership lease
+            
+        Returns:
+            bool: True if reconciliation succeeded and trading may start
+        """
+        if not self.session_token and not await self.authenticate():
+            logger.error("❌ Takeover aborted: authentication failed")
+            return False
+        account_id = self.selected_account['id'] if self.selected_account else None
+        if not account_id:
+            logger.warning("⚠️  Takeover with no selected account - nothing to reconcile")
+            return True
+        try:
+            ledger, open_orders = await self.reconcile_account_state(str(account_id))
+        except Exception as e:
+            logger.error(f"❌ Takeover aborted: could not load broker positions: {e}")
+            return False
+        logger.warning(f"🔁 Takeover reconciled account {account_id} (epoch {lease.epoch}): "
+                       f"{len(ledger)} open position(s) {ledger}, {len(open_orders)} working order(s)")
+        try:
+            self.discord_notifier.send_error_notification(
+                f"Leadership taken over by {lease.holder} (epoch {lease.epoch}): "
+                f"{len(ledger)} open position(s), {len(open_orders)} working order(s)",
+                context="failover",
+            )
+        except Exception as e:
+            logger.debug(f"Failed to send takeover notification: {e}")
+        return True
+    
+    def _on_leader_role_change(self, old_role: LeaderRole, new_role: LeaderRole) -> None:
+        """Log order-flow state changes for the failover pair."""
+        if new_role == LeaderRole.LEADER:
+            logger.warning("✅ Order flow ENABLED (leader)")
+        elif old_role == LeaderRole.LEADER:
+            logger.warning("🚫 Order flow DISABLED (lost leadership)")
+    
+    async def check_position_consistency(self, account_id: str = None) -> Dict:
+        """
+        Compare the local position ledger with broker positions.
+        
+        Args:
+            account_id: Account ID (uses selected account if not provided)
+            
+        Returns:
+            Dict: Divergences found and checker status
+        """
+        target_account = account_id or (self.selected_account['id'] if self.selected_account else None)
+        if not target_account:
+            return {"error": "No account selected"}
+        divergences = await self.position_checker.check(str(target_account))
+        return {
+            "success": True,
+            "divergences": [d.to_dict() for d in divergences],
+            "status": self.position_checker.get_status(),
+        }
+    
+    def _on_position_divergence(self, divergence) -> None:
+        """Alert channel for confirmed position divergences."""
+        account_name = self.selected_account.get('name', 'Unknown') if self.selected_account else 'Unknown'
+        self.discord_notifier.send_position_divergence_notification(divergence.to_dict(), account_name)
+    
+    async def reconcile(self, account_id: str = None) -> Dict:
+        """
+        Run an order/position reconciliation now.
+        
+        Args:
+            account_id: Account ID (uses selected account if not provided)
+            
+        Returns:
+            Dict: Reconciliation report
+        """
+        target_account = account_id or (self.selected_account['id'] if self.selected_account else None)
+        if not target_account:
+            return {"error": "No account selected"}
+        report = await self.reconciler.run(str(target_account))
+        return report.to_dict()
+    
+    def _order_status_unknown(self, label: str, error: Exception, response: Dict, account_id: str) -> Dict:
+        """
+        Result for an order the broker answered but whose response could not be read.
+        
+        The HTTP call succeeded, so the order may be live: callers must not treat it as
+        rejected (OrderExecutor keeps its tracked position), and a reconciliation runs now
+        to pick up whatever the broker actually holds.
+        """
+        logger.error(f"❌ {label} status unknown, reconciling account {account_id}: {error}")
+        try:
+            asyncio.create_task(self.reconcile(str(account_id)))
+        except RuntimeError:
+            logger.warning("No running event loop - reconcile manually to settle the order")
+        return {"error": f"{label} status unknown: {error}", "status_unknown": True, "api_response": response}
+    
+    def _on_reconciliation_report(self, report) -> None:
+        """Alert once per discrepancy, when it is confirmed (the run that would auto-correct it)."""
+        confirmed = [d for d in report.discrepancies if d.consecutive_runs == self.reconciler.confirmations]
+        if not confirmed:
+            return
+        summary = ", ".join(f"{d.kind} {d.order_id or d.symbol} (local {d.local}, broker {d.broker})"
+                            if d.kind.endswith('position') else f"{d.kind} {d.order_id} {d.symbol}"
+                            for d in confirmed)
+        corrected = sum(d.corrected for d in confirmed)
+        try:
+            self.discord_notifier.send_error_notification(
+                f"Reconciliation found {len(confirmed)} discrepancies on account {report.account_id}: {summary}"
+                + (f" ({corrected} corrected to the broker's state)" if corrected else ""),
+                context="reconciliation",
+            )
+        except Exception as e:
+            logger.debug(f"Failed to send reconciliation notification: {e}")
+    
+    async def _on_order_expired(self, expiration: OrderExpiration) -> None:
+        """Expiration event: plugins hear about orders cancelled (or found filled) at their expiry."""
+        await self.plugin_hooks.dispatch(LifecycleEvent.ORDER_EXPIRED, self, account_id=expiration.account_id,
+                                         expiration=expiration.to_dict())
+    
+    def _pre_trade_symbol_error(self, symbol: str, reducing: bool = False) -> Optional[Dict]:
+        """
+        Error response if new entries on a symbol are blocked (kill switch, trading switch off or frozen).
+        
+        Args:
+            symbol: Trading symbol
+            reducing: The order only shrinks the open position (exits pass the kill switch, trading switch,
+                feed-lag block and position-divergence freeze)
+        """
+        if self.kill_switch and not reducing:
+            message = f"Kill switch engaged: {self.kill_switch['reason']}"
+            logger.error(f"❌ Order blocked: {message}")
+            return {"error": message, "kill_switch": True}
+        disabled = None if reducing else self.symbol_switches.check(symbol)
+        if disabled:
+            logger.error(f"❌ Order blocked: {disabled}")
+            return {"error": disabled, "trading_disabled": True}
+        if self._feed_lag_blocks_entries and not reducing and self.feed_latency.is_lagging():
+            latency = self.feed_latency.get_feed_latency()
+            message = (f"Market feed is lagging (p95 {latency['adjusted_p95_ms']}ms behind, "
+                       f"limit {latency['max_lag_ms']:.0f}ms)")
+            logger.error(f"❌ Order blocked: {message}")
+            return {"error": message, "feed_lagging": True}
+        return None if reducing else self._frozen_symbol_error(symbol)
+    
+    async def _reduces_position(self, symbol: str, side: str, quantity: int, account_id: str) -> bool:
+        """True if an order only shrinks the broker's open position on a symbol (a close or partial exit)."""
+        try:
+            positions = await self.get_open_positions(account_id)
+        except Exception as e:
+            logger.warning(f"⚠️  Could not load positions to check whether the {symbol} order reduces: {e}")
+            return False
+        net = net_broker_positions(positions).get(symbol.upper(), 0)
+        direction = 1 if side.upper() == "BUY" else -1
+        return net * direction < 0 and 0 < quantity <= abs(net)
+    
+    async def _order_gate_error(self, symbol: str, side: str, quantity: int, account_id: str) -> Optional[Dict]:
+        """
+        Pre-trade gate for market/limit orders: entries are blocked, exits of the open position are not.
+        
+        Closes, partial exits and protective limits must still go out while a symbol is switched
+        off or frozen, the kill switch is engaged or the feed is lagging, so a blocked order is
+        checked against the broker position and let through if it only reduces it.
+        """
+        error = self._pre_trade_symbol_error(symbol)
+        if error is None or not await self._reduces_position(symbol, side, quantity, account_id):
+            return error
+        error = self._pre_trade_symbol_error(symbol, reducing=True)
+        if error is None:
+            logger.warning(f"⚠️  {side.upper()} {quantity} {symbol} allowed: it only reduces the open position")
+        return error
+    
+    def set_symbol_trading(self, symbol: str, enabled: bool, reason: Optional[str] = None,
+                           updated_by: Optional[str] = None) -> Dict:
+        """
+        Enable or disable new orders for a symbol (market data and analytics keep running).
+        
+        Args:
+            symbol: Trading symbol
+            enabled: False to block new entries
+            reason: Reason shown in order rejections
+            updated_by: Who changed the switch
+            
+        Returns:
+            Dict: New switch state
+        """
+        return self.symbol_switches.set_enabled(symbol, enabled, reason, updated_by).to_dict()
+    
+    async def engage_kill_switch(self, reason: str = 'manual', engaged_by: Optional[str] = None) -> Dict:
+        """
+        Stop all strategies, refuse new orders, then cancel all orders and flatten all positions.
+        
+        New orders are blocked first, so nothing opens while positions are being closed.
+        The switch holds until release_kill_switch(); it is not persisted across restarts,
+        but stopped strategies stay stopped.
+        
+        Args:
+            reason: Reason shown in order rejections
+            engaged_by: Who engaged it
+            
+        Returns:
+            Dict: Kill switch state with the strategy and flatten results
+        """
+        if not self.kill_switch:
+            self.kill_switch = {
+                "reason": reason,
+                "engaged_by": engaged_by,
+                "engaged_at": datetime.now(timezone.utc).isoformat(),
+            }
+        logger.critical(f"🛑 Kill switch engaged by {engaged_by or 'unknown'}: {reason}")
+        stopped = await self.strategy_manager.stop_all_strategies()
+        flatten = await self.flatten_all_positions(interactive=False)
+        try:
+            self.discord_notifier.send_error_notification(
+                f"Kill switch engaged by {engaged_by or 'unknown'}: {reason}", context="kill_switch")
+        except Exception as e:
+            logger.debug(f"Failed to send kill switch notification: {e}")
+        return {**self.kill_switch, "engaged": True, "strategies_stopped": sorted(stopped), "flatten": flatten}
+    
+    def release_kill_switch(self, released_by: Optional[str] = None) -> Dict:
+        """Allow new orders again (strategies stay stopped until started)."""
+        if self.kill_switch:
+            logger.warning(f"⚠️ Kill switch released by {released_by or 'unknown'}")
+        self.kill_switch = None
+        return {"engaged": False}
+    
+    def _frozen_symbol_error(self, symbol: str) -> Optional[Dict]:
+        """Error response if new entries on a symbol are frozen by the consistency checker."""
+        if not self.position_checker.is_frozen(symbol):
+            return None
+        divergence = self.position_checker.frozen_symbols.get(symbol.upper())
+        logger.error(f"❌ Order blocked: {symbol.upper()} is frozen after position divergence")
+        return {
+            "error": f"{symbol.upper()} is frozen: local/broker position divergence "
+                     f"(local={divergence.local_qty}, broker={divergence.broker_qty}). Review and unfreeze before trading.",
+            "frozen": True,
+        }
     
     async def _check_position_closes(self, account_id: str) -> None:
         """Check for position closes and send notifications"""
@@ -1774,8 +2841,12 @@ class TopStepXTradingBot:
                             'fill_price': f"${float(fill_price):.2f}" if fill_price else "Unknown",
                             'order_type': f"{order_type_str} (Close)",
                             'order_id': order_id,
-                            'position_id': position_id
+                            'position_id': position_id,
+                            'context': self.fill_context.build_fill_context(
+                                order_id, symbol, side, float(fill_price) if fill_price else None),
                         }
+                        await self.plugin_hooks.dispatch(LifecycleEvent.ORDER_FILLED, self, account_id=str(account_id),
+                                                         fill=notification_data, order=order)
                         
                         self.discord_notifier.send_order_fill_notification(notification_data, account_name)
                         self._notified_orders.add(unique_id)
@@ -1862,11 +2933,35 @@ class TopStepXTradingBot:
         logger.warning(f"Unknown symbol {symbol}, using default tick size: 0.25")
         return 0.25
     
-    def _round_to_tick_size(self, price: float, tick_size: float) -> float:
-        """Round price to nearest valid tick size."""
-        if tick_size <= 0:
-            return price
-        return round(price / tick_size) * tick_size
+    def _round_to_tick_size(self, price: float, tick_size: float, side: Optional[str] = None,
+                            price_field: str = 'limitPrice') -> float:
+        """Round price to a valid tick using the venue rounding policy (side needed for order_direction)."""
+        return round_price(price, tick_size, self.rounding_policy.price_mode, side, price_field)
+    
+    async def _normalize_order_payload(self, symbol: Optional[str], payload: Dict,
+                                       time_in_force: Optional[str] = None) -> Optional[str]:
+        """
+        Apply the venue rounding policy to an order payload in place and check its time in force.
+        
+        Args:
+            symbol: Trading symbol (for the tick size)
+            payload: /api/Order/place or /api/Order/modify payload
+            time_in_force: DAY (None: venue default; GTC/IOC/FOK are not supported by the API)
+        
+        Returns:
+            Error message if the order cannot be sent (e.g. size rounds to 0, IOC requested), else None
+        """
+        tick_size = await self._get_tick_size(symbol) if symbol else 0
+        try:
+            check_time_in_force(payload, time_in_force)
+            changes = normalize_order_payload(payload, tick_size, self.rounding_policy)
+        except ValueError as e:
+            return str(e)
+        policy = self.rounding_policy
+        for field, original, rounded in changes:
+            mode = policy.quantity_mode if field == 'size' else policy.price_mode
+            logger.info(f"🔢 {field} {original} -> {rounded} ({policy.venue} {mode.value})")
+        return None
     
     def _get_point_value(self, symbol: str) -> float:
         """
@@ -1923,7 +3018,9 @@ class TopStepXTradingBot:
 
     async def place_market_order(self, symbol: str, side: str, quantity: int, account_id: str = None, 
                                 stop_loss_ticks: int = None, take_profit_ticks: int = None, order_type: str = "market", 
-                                limit_price: float = None, strategy_name: str = None) -> Dict:
+                                limit_price: float = None, strategy_name: str = None,
+                                time_in_force: Optional[str] = None, expire_after_secs: Optional[float] = None,
+                                signal_price: Optional[float] = None) -> Dict:
         """
         Place a market or limit order on the selected account.
         
@@ -1936,6 +3033,9 @@ class TopStepXTradingBot:
             take_profit_ticks: Optional take profit in ticks
             order_type: "market" or "limit"
             limit_price: Price for limit orders (required if order_type="limit")
+            time_in_force: DAY (GTC/IOC/FOK are rejected: the API has no time-in-force field)
+            expire_after_secs: Cancel a limit order still working after this many seconds
+            signal_price: Price the signal fired at, for slippage tracking
             
         Returns:
             Dict: Order response or error
@@ -1946,6 +3046,10 @@ class TopStepXTradingBot:
             if not target_account:
                 return {"error": "No account selected"}
             
+            symbol_error = await self._order_gate_error(symbol, side, quantity, target_account)
+            if symbol_error:
+                return symbol_error
+            
             if not self.session_token:
                 return {"error": "No session token available. Please authenticate first."}
             
@@ -1958,6 +3062,9 @@ class TopStepXTradingBot:
             if order_type.lower() == "limit" and limit_price is None:
                 return {"error": "Limit price is required for limit orders"}
             
+            if expire_after_secs is not None and (order_type.lower() != "limit" or expire_after_secs <= 0):
+                return {"error": "expire_after_secs must be positive and only applies to limit orders"}
+            
             logger.info(f"Placing {side} {order_type} order for {quantity} {symbol} on account {target_account}")
             if order_type.lower() == "limit":
                 logger.info(f"Limit price: {limit_price}")
@@ -2026,6 +3133,11 @@ class TopStepXTradingBot:
                 "Authorization": f"Bearer {self.session_token}"
             }
             
+            rounding_error = await self._normalize_order_payload(symbol, order_data, time_in_force)
+            if rounding_error:
+                logger.error(f"❌ {rounding_error}")
+                return {"error": rounding_error}
+            
             response = self._make_curl_request("POST", "/api/Order/place", data=order_data, headers=headers)
             
             # Log FULL API response
@@ -2040,28 +3152,22 @@ class TopStepXTradingBot:
                 logger.error(f"API returned error: {response['error']}")
                 return response
 
-            # Validate response structure
-            if not isinstance(response, dict):
-                logger.error(f"API returned non-dict response: {type(response)}")
-                return {"error": f"Invalid API response type: {type(response)}"}
+            # Validate the response against the API schema (success flag, integer orderId)
+            try:
+                placed = PlaceOrderResponse.parse(response)
+            except ApiSchemaError as e:
+                return self._order_status_unknown("Order", e, response, target_account)
 
-            # Check success field explicitly
-            success = response.get("success")
-            if success is False or success is None or success == "false":
-                error_code = response.get("errorCode", "Unknown")
-                error_message = response.get("errorMessage", response.get("message", "No error message"))
-                logger.error(f"Order failed - success={success}, errorCode={error_code}, message={error_message}")
+            if not placed.success:
+                logger.error(f"Order failed - errorCode={placed.error_code}, message={placed.error_message}")
                 logger.error(f"Full response: {json.dumps(response, indent=2)}")
-                return {"error": f"Order failed: {error_message} (Code: {error_code})"}
+                return {"error": f"Order failed: {placed.error_text}"}
 
-            # Check for order ID - real orders always have IDs
-            order_id = response.get("orderId") or response.get("id") or response.get("data", {}).get("orderId")
-            if not order_id:
-                logger.error(f"API returned success but NO order ID! Full response: {json.dumps(response, indent=2)}")
-                return {"error": "Order rejected: No order ID returned", "api_response": response}
+            order_id = placed.order_id
 
             logger.info(f"Order placed successfully with ID: {order_id}")
             logger.info(f"Full response: {json.dumps(response, indent=2)}")
+            self.slippage_tracker.set_signal_price(order_id, signal_price)
 
             # Activate monitoring for market orders (not limit orders)
             if order_type.lower() == "market":
@@ -2165,7 +3271,7 @@ class TopStepXTradingBot:
                     'quantity': quantity,
                     'price': execution_price,
                     'order_type': order_type_display,
-                    'order_id': response.get('orderId', 'Unknown'),
+                    'order_id': order_id,
                     'status': order_status,
                     'account_id': target_account
                 }
@@ -2178,6 +3284,13 @@ class TopStepXTradingBot:
                 self._cache_ids_from_response(response, target_account, symbol)
             except Exception as cache_err:
                 logger.warning(f"Failed to cache IDs from order response: {cache_err}")
+            if order_type.lower() == "limit" and order_id:
+                self.order_tracker.apply_update(target_account, {
+                    'id': order_id, 'symbol': symbol, 'side': side, 'size': quantity,
+                    'limitPrice': limit_price, 'timeInForce': time_in_force_name(time_in_force), 'status': 1,
+                })
+                if expire_after_secs:
+                    self.order_expiry.schedule(target_account, order_id, expire_after_secs, symbol=symbol)
 
             return response
             
@@ -2185,6 +3298,67 @@ class TopStepXTradingBot:
             logger.error(f"Failed to place order: {str(e)}")
             return {"error": str(e)}
     
+    async def place_pegged_order(self, symbol: str, side: str, quantity: int, account_id: str = None,
+                                 limit_price: Optional[float] = None, interval_ms: Optional[float] = None,
+                                 max_offset_ticks: Optional[int] = None, convert_to_market: Optional[bool] = None,
+                                 strategy_name: Optional[str] = None, signal_price: Optional[float] = None) -> Dict:
+        """
+        Place a limit order that is repriced toward the market until it fills.
+        
+        The order starts at limit_price (default: the near touch, bid for buys and
+        ask for sells) and is moved with the touch every interval_ms, at most
+        max_offset_ticks from where it started. Past that it stays working at the
+        cap, or is replaced by a market order when convert_to_market is set.
+        
+        Args:
+            symbol: Trading symbol
+            side: "BUY" or "SELL"
+            quantity: Number of contracts
+            account_id: Account ID (uses selected account if not provided)
+            limit_price: Starting price (default: the near touch)
+            interval_ms: Milliseconds between reprices (default: ORDER_CHASE_INTERVAL_MS)
+            max_offset_ticks: Maximum move from the starting price (default: ORDER_CHASE_MAX_OFFSET_TICKS)
+            convert_to_market: Go to market past the cap (default: ORDER_CHASE_CONVERT_TO_MARKET)
+            strategy_name: Optional strategy name for custom tagging
+            signal_price: Price the signal fired at, for slippage tracking
+            
+        Returns:
+            Dict: Order response with the chase settings under 'chase', or error
+        """
+        if side.upper() not in ["BUY", "SELL"]:
+            return {"error": "Side must be 'BUY' or 'SELL'"}
+        target_account = account_id or (self.selected_account['id'] if self.selected_account else None)
+        if not target_account:
+            return {"error": "No account selected"}
+        if (interval_ms is not None and interval_ms <= 0) or (max_offset_ticks is not None and max_offset_ticks < 0):
+            return {"error": "interval_ms must be positive and max_offset_ticks >= 0"}
+        
+        if limit_price is None:
+            limit_price = self.order_chaser.touch(symbol, side)
+        if limit_price is None:
+            quote = await self.get_market_quote(symbol)
+            limit_price = quote.get('bid' if side.upper() == 'BUY' else 'ask') or quote.get('last')
+        if limit_price is None:
+            return {"error": f"No quote for {symbol} to start the pegged order at; pass limit_price"}
+        
+        response = await self.place_market_order(symbol, side, quantity, account_id=target_account,
+                                                 order_type="limit", limit_price=float(limit_price),
+                                                 strategy_name=strategy_name, signal_price=signal_price)
+        order_id = response.get('orderId') if isinstance(response, dict) else None
+        if not order_id:
+            return response
+        try:
+            chased = self.order_chaser.chase(target_account, order_id, symbol, side.upper(), quantity,
+                                             float(limit_price), interval_ms=interval_ms,
+                                             max_offset_ticks=max_offset_ticks, convert_to_market=convert_to_market,
+                                             strategy=strategy_name)
+        except ValueError as e:
+            logger.error(f"❌ Pegged order {order_id} placed but not chased: {e}")
+            response['chase_error'] = str(e)
+            return response
+        response['chase'] = chased.to_dict()
+        return response
+    
     async def get_available_contracts(self, use_cache: bool = True, cache_ttl_minutes: int = 60) -> List[Dict]:
         """
         Get available trading contracts with caching support.
@@ -2494,6 +3668,8 @@ class TopStepXTradingBot:
                 logger.info("No positions or orders found to close/cancel")
                 print("✅ No positions or orders found to close/cancel")
             
+            await self.plugin_hooks.dispatch(LifecycleEvent.POST_FLATTEN, self, account_id=str(target_account),
+                                             result=result)
             return result
             
         except Exception as e:
@@ -2550,11 +3726,12 @@ class TopStepXTradingBot:
                 logger.error(f"TopStepX Gateway API failed: {response['error']}")
                 return []
             
-            if not response.get("success"):
-                logger.error(f"TopStepX Gateway API returned error: {response}")
+            search = PositionSearchResponse.parse(response)
+            if not search.success:
+                logger.error(f"TopStepX Gateway API returned error: {search.error_text}")
                 return []
             
-            positions = response.get("positions", [])
+            positions = [position.raw for position in search.positions]
             if not positions:
                 logger.info(f"No open positions found for account {target_account}")
                 return []
@@ -2846,27 +4023,24 @@ class TopStepXTradingBot:
                 logger.error(f"TopStepX Gateway API failed: {response['error']}")
                 return []
             
-            if not response.get("success"):
-                logger.error(f"TopStepX Gateway API returned error: {response}")
+            search = OrderSearchResponse.parse(response)
+            if not search.success:
+                logger.error(f"TopStepX Gateway API returned error: {search.error_text}")
                 return []
             
-            # Check for different possible order data fields
-            orders = []
-            for field in ["orders", "data", "result", "items", "list"]:
-                if field in response and isinstance(response[field], list):
-                    orders = response[field]
-                    break
+            orders = [order.raw for order in search.orders]
             
             if not orders:
                 logger.info(f"No open orders found for account {target_account}")
                 return []
             
             total_orders = len(orders)
-            # Filter strictly to OPEN orders (status == 1)
-            open_only = [o for o in orders if o.get("status") == 1]
+            # Filter strictly to OPEN orders
+            open_only = [o.raw for o in search.orders if o.status == OrderStatus.OPEN]
             logger.info(f"Orders returned: {total_orders}; OPEN filtered: {len(open_only)}")
             if total_orders != len(open_only):
-                logger.debug(f"Filtered out non-open orders; first 3 removed examples: {[o for o in orders if o.get('status') != 1][:3]}")
+                removed = [o.raw for o in search.orders if o.status != OrderStatus.OPEN][:3]
+                logger.debug(f"Filtered out non-open orders; first 3 removed examples: {removed}")
             logger.info(f"Open Orders data: {open_only}")
             
             return open_only
@@ -2875,13 +4049,14 @@ class TopStepXTradingBot:
             logger.error(f"Failed to fetch orders: {str(e)}")
             return []
     
-    async def cancel_order(self, order_id: str, account_id: str = None) -> Dict:
+    async def cancel_order(self, order_id: str, account_id: str = None, deadline: Optional[float] = None) -> Dict:
         """
         Cancel a specific order.
         
         Args:
             order_id: Order ID to cancel
             account_id: Account ID (uses selected account if not provided)
+            deadline: Seconds the cancel may take including retries (default: API_TIMEOUT_CANCEL per attempt)
             
         Returns:
             Dict: Cancel response or error
@@ -2908,13 +4083,17 @@ class TopStepXTradingBot:
                 "accountId": int(target_account)
             }
             
-            response = self._make_curl_request("POST", "/api/Order/cancel", data=cancel_data, headers=headers)
+            response = self._make_curl_request("POST", "/api/Order/cancel", data=cancel_data, headers=headers,
+                                               deadline=deadline)
             
             if "error" in response:
                 logger.error(f"Failed to cancel order: {response['error']}")
                 return response
             
             logger.info(f"Order canceled successfully: {response}")
+            self.order_tracker.remove(target_account, order_id)
+            self.order_expiry.forget(order_id)
+            self.order_chaser.forget(order_id)
             return response
             
         except Exception as e:
@@ -2922,7 +4101,7 @@ class TopStepXTradingBot:
             return {"error": str(e)}
     
     async def modify_order(self, order_id: str, new_quantity: int = None, new_price: float = None, 
-                          account_id: str = None, order_type: int = None) -> Dict:
+                          account_id: str = None, order_type: int = None, deadline: Optional[float] = None) -> Dict:
         """
         Modify an existing order.
         
@@ -2932,10 +4111,12 @@ class TopStepXTradingBot:
             new_price: New price (None to keep current)
             account_id: Account ID (uses selected account if not provided)
             order_type: Order type (1=Limit, 4=Stop, etc.) to determine price field
+            deadline: Seconds the modification may take, including the open-order lookup and retries
             
         Returns:
             Dict: Modify response or error
         """
+        deadline = Deadline.coerce(deadline)
         try:
             target_account = account_id or (self.selected_account['id'] if self.selected_account else None)
             
@@ -2948,7 +4129,8 @@ class TopStepXTradingBot:
             # Get order info to determine type and check if it's a bracket order
             order_info = None
             if new_quantity is not None or new_price is not None:
-                orders = await self.get_open_orders(target_account)
+                with deadline_scope(deadline):
+                    orders = await self.get_open_orders(target_account)
                 for order in orders:
                     if str(order.get('id', '')) == str(order_id):
                         order_info = order
@@ -3006,7 +4188,18 @@ class TopStepXTradingBot:
                 else:  # Limit order or other types
                     modify_data["limitPrice"] = new_price
             
-            response = self._make_curl_request("POST", "/api/Order/modify", data=modify_data, headers=headers)
+            # Round with the order's side; side is not part of the modify payload
+            modify_symbol = self._get_symbol_from_contract_id(order_info['contractId']) \
+                if order_info and order_info.get('contractId') else None
+            modify_data["side"] = order_info.get('side') if order_info else None
+            rounding_error = await self._normalize_order_payload(modify_symbol, modify_data)
+            modify_data.pop("side", None)
+            if rounding_error:
+                logger.error(f"❌ {rounding_error}")
+                return {"error": rounding_error}
+            
+            response = self._make_curl_request("POST", "/api/Order/modify", data=modify_data, headers=headers,
+                                               deadline=deadline)
             
             if "error" in response:
                 logger.error(f"Failed to modify order: {response['error']}")
@@ -3255,6 +4448,7 @@ class TopStepXTradingBot:
             
         Returns:
             List[Dict]: List of consolidated trades with entry/exit info and P&L
+            (pnl is net of fees; gross_pnl and fees hold the parts)
         """
         if not orders:
             return []
@@ -3317,6 +4511,8 @@ class TopStepXTradingBot:
                         exit_price = price
                         point_value = self._get_point_value(symbol)
                         pnl = (entry_price - exit_price) * closed_qty * point_value  # Reversed for short
+                        fees = (self._order_fees(position['entry_order'], symbol, closed_qty)
+                                + self._order_fees(order, symbol, closed_qty))
                         
                         # Extract strategy from entry order's custom tag
                         entry_order = position['entry_order']
@@ -3340,7 +4536,9 @@ class TopStepXTradingBot:
                             'exit_price': exit_price,
                             'entry_time': position['entry_time'],
                             'exit_time': timestamp,
-                            'pnl': pnl,
+                            'gross_pnl': pnl,
+                            'fees': fees,
+                            'pnl': pnl - fees,  # Net, as on the broker statement
                             'entry_order_id': position['entry_order'].get('id'),
                             'exit_order_id': order.get('id'),
                             'strategy': strategy_name  # Add strategy name from custom tag
@@ -3380,6 +4578,8 @@ class TopStepXTradingBot:
                         exit_price = price
                         point_value = self._get_point_value(symbol)
                         pnl = (exit_price - entry_price) * closed_qty * point_value
+                        fees = (self._order_fees(position['entry_order'], symbol, closed_qty)
+                                + self._order_fees(order, symbol, closed_qty))
                         
                         # Extract strategy from entry order's custom tag
                         entry_order = position['entry_order']
@@ -3403,7 +4603,9 @@ class TopStepXTradingBot:
                             'exit_price': exit_price,
                             'entry_time': position['entry_time'],
                             'exit_time': timestamp,
-                            'pnl': pnl,
+                            'gross_pnl': pnl,
+                            'fees': fees,
+                            'pnl': pnl - fees,  # Net, as on the broker statement
                             'entry_order_id': position['entry_order'].get('id'),
                             'exit_order_id': order.get('id'),
                             'strategy': strategy_name  # Add strategy name from custom tag
@@ -3608,25 +4810,19 @@ class TopStepXTradingBot:
                 logger.error(f"TopStepX Gateway API failed: {response['error']}")
                 return []
             
-            if not response.get("success"):
-                logger.error(f"TopStepX Gateway API returned error: {response}")
+            search = OrderSearchResponse.parse(response)
+            if not search.success:
+                logger.error(f"TopStepX Gateway API returned error: {search.error_text}")
                 return []
             
-            # Check for different possible order data fields
-            orders = []
-            for field in ["orders", "data", "result", "items", "list"]:
-                if field in response and isinstance(response[field], list):
-                    orders = response[field]
-                    break
+            orders = [order.raw for order in search.orders]
             
             if not orders:
                 logger.info(f"No historical orders found for account {target_account}")
                 return []
             
             # Filter to only filled/executed orders for history
-            # Status codes: 0=Pending, 1=Working, 2=Filled, 3=Cancelled, 4=Rejected
-            # Only include status=2 (Filled) and ensure fillVolume > 0
-            filled_orders = [o for o in orders if o.get("status") == 2 and o.get("fillVolume", 0) > 0]
+            filled_orders = [o.raw for o in search.orders if o.status == OrderStatus.FILLED and o.fill_volume > 0]
             logger.info(f"Total orders returned: {len(orders)}; Filled orders: {len(filled_orders)}")
             
             # If no filled orders found, try the Fill API endpoint
@@ -3713,6 +4909,10 @@ class TopStepXTradingBot:
             if not target_account:
                 return {"error": "No account selected"}
             
+            symbol_error = self._pre_trade_symbol_error(symbol)
+            if symbol_error:
+                return symbol_error
+            
             if not self.session_token:
                 return {"error": "No session token available. Please authenticate first."}
             
@@ -3842,7 +5042,8 @@ class TopStepXTradingBot:
     async def create_bracket_order(self, symbol: str, side: str, quantity: int, 
                                  stop_loss_price: float = None, take_profit_price: float = None,
                                  stop_loss_ticks: int = None, take_profit_ticks: int = None,
-                                 account_id: str = None, strategy_name: str = None) -> Dict:
+                                 account_id: str = None, strategy_name: str = None,
+                                 signal_price: Optional[float] = None) -> Dict:
         """
         Create a native TopStepX bracket order with linked stop loss and take profit.
         Uses the same approach as the working place_market_order method.
@@ -3856,6 +5057,7 @@ class TopStepXTradingBot:
             stop_loss_ticks: Stop loss in ticks (optional if stop_loss_price provided)
             take_profit_ticks: Take profit in ticks (optional if take_profit_price provided)
             account_id: Account ID (uses selected account if not provided)
+            signal_price: Price the signal fired at, for slippage tracking
             
         Returns:
             Dict: Bracket order response or error
@@ -3866,6 +5068,10 @@ class TopStepXTradingBot:
             if not target_account:
                 return {"error": "No account selected"}
             
+            symbol_error = self._pre_trade_symbol_error(symbol)
+            if symbol_error:
+                return symbol_error
+            
             if not self.session_token:
                 return {"error": "No session token available. Please authenticate first."}
             
@@ -4017,6 +5223,11 @@ class TopStepXTradingBot:
                 "Authorization": f"Bearer {self.session_token}"
             }
             
+            rounding_error = await self._normalize_order_payload(symbol, order_data)
+            if rounding_error:
+                logger.error(f"❌ {rounding_error}")
+                return {"error": rounding_error}
+            
             # Use the same endpoint as place_market_order
             response = self._make_curl_request("POST", "/api/Order/place", data=order_data, headers=headers)
             
@@ -4024,10 +5235,14 @@ class TopStepXTradingBot:
                 logger.error(f"Failed to create bracket order: {response['error']}")
                 return response
             
-            # Check if order was actually successful
-            if response.get("success") == False:
-                error_code = response.get("errorCode", "Unknown")
-                error_message = response.get("errorMessage", "No error message")
+            # Check if order was actually successful (a malformed success may still have placed the order)
+            try:
+                placed = PlaceOrderResponse.parse(response)
+            except ApiSchemaError as e:
+                return self._order_status_unknown("Bracket order", e, response, target_account)
+            if not placed.success:
+                error_code = placed.error_code
+                error_message = placed.error_message or "No error message"
                 logger.error(f"Bracket order failed: Error Code {error_code}, Message: {error_message}")
                 
                 # If bracket order fails due to tick limits, try a regular market order
@@ -4039,7 +5254,8 @@ class TopStepXTradingBot:
                         symbol=symbol,
                         side=side,
                         quantity=quantity,
-                        account_id=target_account
+                        account_id=target_account,
+                        signal_price=signal_price
                     )
                     
                     if "error" not in fallback_result:
@@ -4056,6 +5272,7 @@ class TopStepXTradingBot:
                     return {"error": f"Bracket order failed: Error Code {error_code}, Message: {error_message}"}
             
             logger.info(f"Bracket order created successfully: {response}")
+            self.slippage_tracker.set_signal_price(placed.order_id, signal_price)
             
             # Send Discord notification for successful bracket order
             try:
@@ -4091,7 +5308,7 @@ class TopStepXTradingBot:
                     'quantity': quantity,
                     'price': execution_price,
                     'order_type': 'Native Bracket',
-                    'order_id': response.get('orderId', 'Unknown'),
+                    'order_id': placed.order_id,
                     'status': order_status,
                     'account_id': target_account,
                     'stop_loss': stop_loss_price,
@@ -4102,7 +5319,7 @@ class TopStepXTradingBot:
                 logger.warning(f"Failed to send Discord notification: {notif_err}")
             
             # Start monitoring for this position if we have a position ID
-            if "orderId" in response and response.get("success"):
+            if placed.success:
                 # We need to get the position ID from the order response or by checking positions
                 # For now, we'll start monitoring after a brief delay to let the position be created
                 import asyncio
@@ -4214,11 +5431,11 @@ class TopStepXTradingBot:
             stop_result = None
             if stop_loss_price:
                 # Round stop loss price to valid tick size
+                stop_side = "SELL" if side.upper() == "BUY" else "BUY"
                 tick_size = await self._get_tick_size(symbol)
-                rounded_stop_price = self._round_to_tick_size(stop_loss_price, tick_size)
+                rounded_stop_price = self._round_to_tick_size(stop_loss_price, tick_size, stop_side, 'stopPrice')
                 logger.info(f"Stop loss price: {stop_loss_price} -> {rounded_stop_price} (tick_size: {tick_size})")
                 
-                stop_side = "SELL" if side.upper() == "BUY" else "BUY"
                 stop_result = await self.place_stop_order(
                     symbol=symbol,
                     side=stop_side,
@@ -4236,11 +5453,11 @@ class TopStepXTradingBot:
             tp1_result = None
             if take_profit_1_price:
                 # Round TP1 price to valid tick size
+                tp1_side = "SELL" if side.upper() == "BUY" else "BUY"
                 tick_size = await self._get_tick_size(symbol)
-                rounded_tp1_price = self._round_to_tick_size(take_profit_1_price, tick_size)
+                rounded_tp1_price = self._round_to_tick_size(take_profit_1_price, tick_size, tp1_side)
                 logger.info(f"TP1 price: {take_profit_1_price} -> {rounded_tp1_price} (tick_size: {tick_size})")
                 
-                tp1_side = "SELL" if side.upper() == "BUY" else "BUY"
                 tp1_result = await self.place_market_order(
                     symbol=symbol,
                     side=tp1_side,
@@ -4258,11 +5475,11 @@ class TopStepXTradingBot:
             tp2_result = None
             if take_profit_2_price:
                 # Round TP2 price to valid tick size
+                tp2_side = "SELL" if side.upper() == "BUY" else "BUY"
                 tick_size = await self._get_tick_size(symbol)
-                rounded_tp2_price = self._round_to_tick_size(take_profit_2_price, tick_size)
+                rounded_tp2_price = self._round_to_tick_size(take_profit_2_price, tick_size, tp2_side)
                 logger.info(f"TP2 price: {take_profit_2_price} -> {rounded_tp2_price} (tick_size: {tick_size})")
                 
-                tp2_side = "SELL" if side.upper() == "BUY" else "BUY"
                 tp2_quantity = quantity - tp1_quantity  # Remaining contracts after TP1
                 if tp2_quantity > 0:
                     tp2_result = await self.place_market_order(
@@ -4576,6 +5793,52 @@ class TopStepXTradingBot:
         except Exception as e:
             logger.error(f"Failed to check unprotected positions: {str(e)}")
     
+    async def find_bracket_legs(self, order_id: str, account_id: str = None) -> Dict:
+        """
+        Find the working stop and target orders a filled entry order's bracket created.
+        
+        Legs are open orders on the parent's contract and opposite side: stops (type 4/5)
+        and limits (type 1), preferring AutoBracket -SL/-TP tags, then the legs created
+        first after the parent, with the parent's size.
+        
+        Args:
+            order_id: Entry (parent) order ID
+            account_id: Account ID (uses selected account if not provided)
+            
+        Returns:
+            Dict: parent (filled order), symbol, stop and target (order dicts or None), or error
+        """
+        target_account = account_id or (self.selected_account['id'] if self.selected_account else None)
+        if not target_account:
+            return {"error": "No account selected"}
+        history = await self.get_order_history(str(target_account), limit=500)
+        parent = next((o for o in history if str(o.get('id', '')) == str(order_id)), None)
+        if parent is None:
+            return {"error": f"Order {order_id} not found among filled orders"}
+        parent_side = 1 if str(parent.get('side')).upper() in ('1', 'SELL') else 0
+        parent_created = str(parent.get('creationTimestamp') or '')
+        candidates = [
+            o for o in await self.get_open_orders(str(target_account))
+            if o.get('contractId') == parent.get('contractId') and o.get('side') == 1 - parent_side
+            and str(o.get('creationTimestamp') or '') >= parent_created
+        ]
+        
+        def pick(types, tag_suffix):
+            legs = [o for o in candidates if o.get('type') in types]
+            # Tagged bracket legs first, then the parent's size, then the earliest created
+            legs.sort(key=lambda o: ('AutoBracket' not in str(o.get('customTag') or '')
+                                     or tag_suffix not in str(o.get('customTag') or ''),
+                                     o.get('size') != parent.get('size'),
+                                     str(o.get('creationTimestamp') or '')))
+            return legs[0] if legs else None
+        
+        return {
+            "parent": parent,
+            "symbol": self._get_symbol_from_contract_id(parent.get('contractId', '')) or parent.get('contractId'),
+            "stop": pick((4, 5), '-SL'),
+            "target": pick((1,), '-TP'),
+        }
+    
     async def get_linked_orders(self, position_id: str, account_id: str = None) -> List[Dict]:
         """
         Get all orders linked to a specific position.
@@ -4629,16 +5892,12 @@ class TopStepXTradingBot:
                 logger.error(f"TopStepX Gateway API failed: {response['error']}")
                 return []
             
-            if not response.get("success"):
-                logger.error(f"TopStepX Gateway API returned error: {response}")
+            search = OrderSearchResponse.parse(response)
+            if not search.success:
+                logger.error(f"TopStepX Gateway API returned error: {search.error_text}")
                 return []
             
-            # Check for different possible order data fields
-            orders = []
-            for field in ["orders", "data", "result", "items", "list"]:
-                if field in response and isinstance(response[field], list):
-                    orders = response[field]
-                    break
+            orders = [order.raw for order in search.orders]
             
             # Filter orders that are linked to this position
             # Since we don't have direct position linking, we'll find orders for the same contract
@@ -4926,7 +6185,8 @@ class TopStepXTradingBot:
     # ============================================================================
     
     async def place_stop_order(self, symbol: str, side: str, quantity: int, stop_price: float,
-                              account_id: str = None, strategy_name: Optional[str] = None) -> Dict:
+                              account_id: str = None, strategy_name: Optional[str] = None,
+                              time_in_force: Optional[str] = None, expire_after_secs: Optional[float] = None) -> Dict:
         """
         Place a stop order (entry stop - triggers market order when price is hit).
         Use stop_buy for BUY stop orders or stop_sell for SELL stop orders.
@@ -4938,6 +6198,8 @@ class TopStepXTradingBot:
             stop_price: Stop price (triggers when price reaches this level)
             account_id: Account ID (uses selected account if not provided)
             strategy_name: Optional strategy name for custom tagging
+            time_in_force: DAY (GTC/IOC/FOK are rejected: the API has no time-in-force field)
+            expire_after_secs: Cancel the stop if it has not triggered after this many seconds
             
         Returns:
             Dict: Stop order response or error
@@ -4954,9 +6216,12 @@ class TopStepXTradingBot:
             if side.upper() not in ["BUY", "SELL"]:
                 return {"error": "Side must be 'BUY' or 'SELL'"}
             
+            if expire_after_secs is not None and expire_after_secs <= 0:
+                return {"error": "expire_after_secs must be positive"}
+            
             # Round stop price to valid tick size
             tick_size = await self._get_tick_size(symbol)
-            rounded_stop_price = self._round_to_tick_size(stop_price, tick_size)
+            rounded_stop_price = self._round_to_tick_size(stop_price, tick_size, side, 'stopPrice')
             logger.info(f"Stop price: {stop_price} -> {rounded_stop_price} (tick_size: {tick_size})")
             logger.info(f"Placing stop {side} order for {quantity} {symbol} at {rounded_stop_price}")
             
@@ -4988,11 +6253,29 @@ class TopStepXTradingBot:
                 "Authorization": f"Bearer {self.session_token}"
             }
             
+            rounding_error = await self._normalize_order_payload(symbol, stop_data, time_in_force)
+            if rounding_error:
+                logger.error(f"❌ {rounding_error}")
+                return {"error": rounding_error}
+            
             response = self._make_curl_request("POST", "/api/Order/place", data=stop_data, headers=headers)
             
             if "error" in response:
                 logger.error(f"Failed to place stop order: {response['error']}")
                 return response
+            try:
+                placed = PlaceOrderResponse.parse(response)
+            except ApiSchemaError as e:
+                return self._order_status_unknown("Stop order", e, response, target_account)
+            if not placed.success:
+                logger.error(f"Stop order failed: {placed.error_text}")
+                return {"error": f"Stop order failed: {placed.error_text}"}
+            self.order_tracker.apply_update(target_account, {
+                'id': placed.order_id, 'symbol': symbol, 'side': side, 'size': quantity,
+                'stopPrice': stop_data['stopPrice'], 'timeInForce': time_in_force_name(time_in_force), 'status': 1,
+            })
+            if expire_after_secs:
+                self.order_expiry.schedule(target_account, placed.order_id, expire_after_secs, symbol=symbol)
             
             # Update order activity timestamp
             self._update_order_activity()
@@ -5007,7 +6290,9 @@ class TopStepXTradingBot:
     async def place_oco_bracket_with_stop_entry(self, symbol: str, side: str, quantity: int,
                                                 entry_price: float, stop_loss_price: float,
                                                 take_profit_price: float, account_id: str = None,
-                                                enable_breakeven: bool = False, strategy_name: str = None) -> Dict:
+                                                enable_breakeven: bool = False, strategy_name: str = None,
+                                                time_in_force: Optional[str] = None,
+                                                expire_after_secs: Optional[float] = None) -> Dict:
         """
         Place OCO bracket order with stop order as entry.
         
@@ -5023,6 +6308,8 @@ class TopStepXTradingBot:
             take_profit_price: Take profit price
             account_id: Account ID (uses selected account if not provided)
             enable_breakeven: Enable breakeven stop monitoring (default: False)
+            time_in_force: DAY for the stop entry (GTC/IOC/FOK are rejected)
+            expire_after_secs: Cancel the entry if it has not triggered after this many seconds
             
         Returns:
             Dict: OCO bracket response or error
@@ -5033,6 +6320,10 @@ class TopStepXTradingBot:
             if not target_account:
                 return {"error": "No account selected"}
             
+            symbol_error = self._pre_trade_symbol_error(symbol)
+            if symbol_error:
+                return symbol_error
+            
             if side.upper() not in ["BUY", "SELL"]:
                 return {"error": "Side must be 'BUY' or 'SELL'"}
             
@@ -5062,7 +6353,7 @@ class TopStepXTradingBot:
             tick_size = await self._get_tick_size(symbol)
             
             # Round entry price to valid tick size (CRITICAL: prevents "Invalid stop price" rejections)
-            rounded_entry_price = self._round_to_tick_size(entry_price, tick_size)
+            rounded_entry_price = self._round_to_tick_size(entry_price, tick_size, side, 'stopPrice')
             logger.info(f"Entry price: {entry_price} -> {rounded_entry_price} (tick_size: {tick_size})")
             logger.info(f"Bracket context: contract={contract_id}, tick_size={tick_size}, entry_price=${rounded_entry_price:.2f}")
             
@@ -5147,6 +6438,8 @@ class TopStepXTradingBot:
                 return {"error": f"Invalid quantity: {quantity}"}
             if entry_price <= 0:
                 return {"error": f"Invalid entry price: {entry_price}"}
+            if expire_after_secs is not None and expire_after_secs <= 0:
+                return {"error": "expire_after_secs must be positive"}
             
             # Add bracket orders using the same format as create_bracket_order
             order_data["stopLossBracket"] = {
@@ -5177,6 +6470,11 @@ class TopStepXTradingBot:
                 "Authorization": f"Bearer {self.session_token}"
             }
             
+            rounding_error = await self._normalize_order_payload(symbol, order_data, time_in_force)
+            if rounding_error:
+                logger.error(f"❌ {rounding_error}")
+                return {"error": rounding_error}
+            
             # Use the same endpoint as create_bracket_order
             response = self._make_curl_request("POST", "/api/Order/place", data=order_data, headers=headers)
             
@@ -5184,8 +6482,16 @@ class TopStepXTradingBot:
             if "error" in response and "500" in str(response.get("error", "")):
                 logger.warning(f"⚠️  Received 500 error on order placement. Attempting token refresh and retry...")
                 
-                # Refresh token
-                token_refreshed = await self._ensure_valid_token()
+                # Refresh token (only if the shared retry budget and the caller's deadline allow another attempt)
+                deadline = current_deadline()
+                if deadline is not None and deadline.remaining() <= 0.75:
+                    logger.error("⏱️  Deadline too close - not retrying order placement")
+                    token_refreshed = False
+                elif not get_retry_budget().try_acquire("order_place"):
+                    logger.error("🚦 Retry budget exhausted - not retrying order placement")
+                    token_refreshed = False
+                else:
+                    token_refreshed = await self._ensure_valid_token()
                 if token_refreshed:
                     # Update headers with new token
                     headers["Authorization"] = f"Bearer {self.session_token}"
@@ -5265,10 +6571,15 @@ class TopStepXTradingBot:
                     )
                 return response
             
-            # Check if order was actually successful
-            if response.get("success") == False:
-                error_code = response.get("errorCode", "Unknown")
-                error_message = response.get("errorMessage", "No error message")
+            # Check if order was actually successful (a malformed success may still have placed the order,
+            # so it must not fall through to the hybrid fallback below)
+            try:
+                placed = PlaceOrderResponse.parse(response)
+            except ApiSchemaError as e:
+                return self._order_status_unknown("Stop bracket order", e, response, target_account)
+            if not placed.success:
+                error_code = placed.error_code
+                error_message = placed.error_message or "No error message"
                 logger.error(f"Stop bracket order failed: Error Code {error_code}, Message: {error_message}")
                 
                 # Check for bracket-related errors
@@ -5304,7 +6615,7 @@ class TopStepXTradingBot:
                     'quantity': quantity,
                     'price': f"${entry_price:.2f} (stop)",
                     'order_type': 'Stop Bracket',
-                    'order_id': response.get('orderId', 'Unknown'),
+                    'order_id': placed.order_id,
                     'status': 'Placed',
                     'account_id': account_id_str,
                     'account_name': account_name
@@ -5327,7 +6638,7 @@ class TopStepXTradingBot:
                                 'side': side,
                                 'quantity': quantity,
                                 'order_type': 'Stop Bracket',
-                                'order_id': response.get('orderId'),
+                                'order_id': placed.order_id,
                                 'account_name': account_name
                             }
                         )
@@ -5338,24 +6649,21 @@ class TopStepXTradingBot:
             
             # Setup breakeven monitoring if enabled
             if enable_breakeven:
-                order_id = response.get("orderId")
+                order_id = placed.order_id
                 if order_id and hasattr(self, 'overnight_strategy'):
                     logger.info(f"Setting up breakeven monitoring for order {order_id}")
                     breakeven_points = float(os.getenv('MANUAL_BREAKEVEN_PROFIT_POINTS', '15.0'))
-                    self.overnight_strategy.breakeven_monitoring[order_id] = {
-                        "symbol": symbol,
-                        "side": "LONG" if side.upper() == "BUY" else "SHORT",
-                        "entry_price": entry_price,
-                        "original_stop": stop_loss_price,
-                        "breakeven_threshold": breakeven_points,
-                        "breakeven_triggered": False,
-                        "is_filled": False  # Will be set to True when entry order fills
-                    }
+                    self.overnight_strategy.watch_breakeven(
+                        order_id, symbol, "LONG" if side.upper() == "BUY" else "SHORT",
+                        entry_price, stop_loss_price, profit_points=breakeven_points)
                     logger.info(f"Breakeven monitoring active: {breakeven_points} pts profit threshold")
             
+            if expire_after_secs:
+                self.order_expiry.schedule(target_account, placed.order_id, expire_after_secs, symbol=symbol)
+            
             return {
                 "success": True,
-                "orderId": response.get("orderId"),
+                "orderId": placed.order_id,
                 "method": "oco_native",
                 "symbol": symbol,
                 "side": side,
@@ -5513,15 +6821,9 @@ class TopStepXTradingBot:
             if enable_breakeven and order_id and hasattr(self, 'overnight_strategy'):
                 logger.info(f"Setting up breakeven monitoring for hybrid order {order_id}")
                 breakeven_points = float(os.getenv('MANUAL_BREAKEVEN_PROFIT_POINTS', '15.0'))
-                self.overnight_strategy.breakeven_monitoring[order_id] = {
-                    "symbol": symbol,
-                    "side": "LONG" if side.upper() == "BUY" else "SHORT",
-                    "entry_price": entry_price,
-                    "original_stop": stop_loss_price,
-                    "breakeven_threshold": breakeven_points,
-                    "breakeven_triggered": False,
-                    "is_filled": False  # Will be set to True when entry order fills
-                }
+                self.overnight_strategy.watch_breakeven(
+                    order_id, symbol, "LONG" if side.upper() == "BUY" else "SHORT",
+                    entry_price, stop_loss_price, profit_points=breakeven_points)
                 logger.info(f"Breakeven monitoring active: {breakeven_points} pts profit threshold")
             
             return {
@@ -5912,14 +7214,28 @@ class TopStepXTradingBot:
                 import time
                 start_wait = time.time()
                 depth_data = None
+                book = None
                 
                 while time.time() - start_wait < 2.0:  # Wait up to 2 seconds
                     with self._depth_cache_lock:
                         depth_data = self._depth_cache.get(symbol_up)
+                        book = self.depth_books.get(symbol_up)
                     if depth_data and (depth_data.get('bids') or depth_data.get('asks')):
                         break
+                    if book and any(book.depth()):
+                        break
                     time.sleep(0.05)
                 
+                if book and any(book.depth()):
+                    snapshot = book.snapshot().to_dict()
+                    logger.info(f"Got market depth from L2 book for {symbol_up}")
+                    return {
+                        "bids": snapshot["bids"],
+                        "asks": snapshot["asks"],
+                        "imbalance": book.imbalance(),
+                        "source": "signalr_book"
+                    }
+                
                 if depth_data and (depth_data.get('bids') or depth_data.get('asks')):
                     logger.info(f"Got market depth data via SignalR for {symbol_up}")
                     return {
@@ -6061,8 +7377,8 @@ class TopStepXTradingBot:
     
     def _get_cache_path(self, cache_key: str) -> Path:
         """Get the full path to a cache file."""
-        cache_dir = Path(".cache")
-        cache_dir.mkdir(exist_ok=True)
+        cache_dir = self.history_cache_dir
+        cache_dir.mkdir(parents=True, exist_ok=True)
         extension = '.parquet' if self._cache_format == 'parquet' else '.pkl'
         return cache_dir / f"history_{cache_key}{extension}"
     
@@ -6266,8 +7582,10 @@ class TopStepXTradingBot:
             # Convert to Polars DataFrame
             df = pl.DataFrame(data)
             
-            # Save to Parquet with compression
-            df.write_parquet(cache_path, compression='lz4')
+            # Save to Parquet with compression (temp file + rename so readers never see a partial file)
+            tmp_path = cache_path.with_name(f".{cache_path.name}.{os.getpid()}.tmp")
+            df.write_parquet(tmp_path, compression='lz4')
+            os.replace(tmp_path, cache_path)
             
             logger.debug(f"Cached {len(data)} bars to Parquet: {cache_path}")
         except ImportError:
@@ -6299,8 +7617,7 @@ class TopStepXTradingBot:
     def _save_to_pickle(self, cache_path: Path, data: List[Dict]) -> None:
         """Save data to pickle file (fallback)."""
         try:
-            with open(cache_path, 'wb') as f:
-                pickle.dump(data, f)
+            atomic_write(cache_path, pickle.dumps(data))
             logger.debug(f"Cached {len(data)} bars to pickle: {cache_path}")
         except Exception as e:
             logger.warning(f"Failed to save pickle cache: {e}")
@@ -6432,6 +7749,15 @@ class TopStepXTradingBot:
             logger.error(f"Failed to export CSV: {e}")
             return None
     
+    def _retrieve_bars(self, bars_request: Dict) -> Dict:
+        """Send one /api/History/retrieveBars request (used by the history client)."""
+        headers = {
+            "Authorization": f"Bearer {self.session_token}",
+            "Content-Type": "application/json",
+            "accept": "text/plain"
+        }
+        return self._make_curl_request("POST", "/api/History/retrieveBars", data=bars_request, headers=headers)
+    
     def _parse_timeframe(self, timeframe: str):
         """
         Parse timeframe string to API unit format.
@@ -6506,15 +7832,18 @@ class TopStepXTradingBot:
         logger.debug(f"Parsed timeframe '{timeframe}' -> unit={api_unit}, unitNumber={api_unit_number}")
         return api_unit, api_unit_number, time_delta_func
     
-    def _aggregate_bars(self, bars: List[Dict], target_timeframe: str) -> List[Dict]:
+    def _aggregate_bars(self, bars: List[Dict], target_timeframe: str, symbol: Optional[str] = None) -> List[Dict]:
         """
         Aggregate 1-minute bars into higher timeframes (5m, 15m, 30m, 1h, etc.).
         
         This ensures accurate data by using reliable 1m data as the source.
+        With a symbol, buckets are session-aligned like live bars (daily bars
+        are one Globex session); see core.market_data.resample_bars.
         
         Args:
             bars: List of 1-minute bars (must be sorted by timestamp)
             target_timeframe: Target timeframe to aggregate to (e.g., '5m', '15m', '1h')
+            symbol: Symbol the bars belong to (enables session alignment)
             
         Returns:
             List[Dict]: Aggregated bars in the target timeframe
@@ -6528,72 +7857,12 @@ class TopStepXTradingBot:
             # Can't aggregate to same or lower timeframe
             return bars
         
-        # Group 1m bars into target timeframe periods
-        aggregated = []
-        current_group = []
-        current_group_start = None
-        
-        for bar in bars:
-            # Get timestamp
-            ts_str = bar.get('timestamp') or bar.get('time')
-            if isinstance(ts_str, str):
-                try:
-                    ts = datetime.fromisoformat(ts_str.replace('Z', '+00:00'))
-                except:
-                    continue
-            elif isinstance(ts_str, datetime):
-                ts = ts_str
-            else:
-                continue
-            
-            if ts.tzinfo is None:
-                ts = ts.replace(tzinfo=timezone.utc)
-            
-            # Calculate which target timeframe bar this belongs to
-            bar_start_seconds = int(ts.timestamp()) // target_seconds * target_seconds
-            bar_start = datetime.fromtimestamp(bar_start_seconds, tz=timezone.utc)
-            
-            # Start new group if needed
-            if current_group_start is None or bar_start != current_group_start:
-                # Finalize previous group
-                if current_group:
-                    agg_bar = {
-                        'timestamp': current_group_start.isoformat(),
-                        'time': current_group_start.isoformat(),
-                        'open': current_group[0].get('open', 0),
-                        'high': max(b.get('high', 0) for b in current_group),
-                        'low': min(b.get('low', float('inf')) for b in current_group if b.get('low') is not None),
-                        'close': current_group[-1].get('close', 0),
-                        'volume': sum(b.get('volume', 0) or 0 for b in current_group),
-                    }
-                    # Ensure low is valid
-                    if agg_bar['low'] == float('inf'):
-                        agg_bar['low'] = agg_bar['open']
-                    aggregated.append(agg_bar)
-                
-                # Start new group
-                current_group = [bar]
-                current_group_start = bar_start
-            else:
-                # Add to current group
-                current_group.append(bar)
-        
-        # Finalize last group
-        if current_group:
-            agg_bar = {
-                'timestamp': current_group_start.isoformat(),
-                'time': current_group_start.isoformat(),
-                'open': current_group[0].get('open', 0),
-                'high': max(b.get('high', 0) for b in current_group),
-                'low': min(b.get('low', float('inf')) for b in current_group if b.get('low') is not None),
-                'close': current_group[-1].get('close', 0),
-                'volume': sum(b.get('volume', 0) or 0 for b in current_group),
-            }
-            if agg_bar['low'] == float('inf'):
-                agg_bar['low'] = agg_bar['open']
-            aggregated.append(agg_bar)
-        
-        return aggregated
+        try:
+            return resample_bar_dicts(bars, '1m', target_timeframe, symbol or '',
+                                      self.session_calendar if symbol else None)
+        except ValueError as e:
+            logger.warning(f"⚠️  Cannot aggregate bars to {target_timeframe}: {e}")
+            return bars
     
     def _parse_timeframe_to_seconds(self, timeframe: str) -> Optional[int]:
         """Parse timeframe string to seconds."""
@@ -6775,7 +8044,7 @@ class TopStepXTradingBot:
                                     # If using aggregation, aggregate the cached 1m bars
                                     if use_aggregation and cache_timeframe == "1m":
                                         logger.info(f"✅ DB Cache HIT: {len(cached_bars)} 1m bars for {symbol}, aggregating to {timeframe}...")
-                                        aggregated_bars = self._aggregate_bars(cached_bars, timeframe)
+                                        aggregated_bars = self._aggregate_bars(cached_bars, timeframe, symbol)
                                         logger.info(f"✅ Aggregated to {len(aggregated_bars)} {timeframe} bars")
                                         result = aggregated_bars[-limit:] if len(aggregated_bars) > limit else aggregated_bars
                                         metrics_tracker = get_metrics_tracker(db=self.db)
@@ -6812,7 +8081,7 @@ class TopStepXTradingBot:
                                                 # Cache is fresh - if using aggregation, aggregate the cached 1m bars
                                                 if use_aggregation and cache_timeframe == "1m":
                                                     logger.info(f"✅ DB Cache HIT: {len(cached_bars)} 1m bars for {symbol} (newest: {newest_dt}), aggregating to {timeframe}...")
-                                                    aggregated_bars = self._aggregate_bars(cached_bars, timeframe)
+                                                    aggregated_bars = self._aggregate_bars(cached_bars, timeframe, symbol)
                                                     logger.info(f"✅ Aggregated to {len(aggregated_bars)} {timeframe} bars")
                                                     result = aggregated_bars[-limit:] if len(aggregated_bars) > limit else aggregated_bars
                                                     metrics_tracker = get_metrics_tracker(db=self.db)
@@ -6827,7 +8096,7 @@ class TopStepXTradingBot:
                                             # Can't parse timestamp - if using aggregation, aggregate anyway
                                             if use_aggregation and cache_timeframe == "1m":
                                                 logger.info(f"✅ DB Cache HIT: {len(cached_bars)} 1m bars for {symbol}, aggregating to {timeframe}...")
-                                                aggregated_bars = self._aggregate_bars(cached_bars, timeframe)
+                                                aggregated_bars = self._aggregate_bars(cached_bars, timeframe, symbol)
                                                 logger.info(f"✅ Aggregated to {len(aggregated_bars)} {timeframe} bars")
                                                 result = aggregated_bars[-limit:] if len(aggregated_bars) > limit else aggregated_bars
                                                 metrics_tracker = get_metrics_tracker(db=self.db)
@@ -6843,7 +8112,7 @@ class TopStepXTradingBot:
                                         # If using aggregation, try to aggregate anyway
                                         if use_aggregation and cache_timeframe == "1m":
                                             logger.info(f"✅ DB Cache HIT: {len(cached_bars)} 1m bars for {symbol}, aggregating to {timeframe}...")
-                                            aggregated_bars = self._aggregate_bars(cached_bars, timeframe)
+                                            aggregated_bars = self._aggregate_bars(cached_bars, timeframe, symbol)
                                             logger.info(f"✅ Aggregated to {len(aggregated_bars)} {timeframe} bars")
                                             result = aggregated_bars[-limit:] if len(aggregated_bars) > limit else aggregated_bars
                                             metrics_tracker = get_metrics_tracker(db=self.db)
@@ -7105,7 +8374,7 @@ class TopStepXTradingBot:
             # If we're using aggregation strategy, aggregate 1m bars to target timeframe
             if use_aggregation and source_timeframe == "1m":
                 logger.info(f"📊 Aggregating {len(parsed_bars)} 1m bars into {timeframe} bars...")
-                parsed_bars = self._aggregate_bars(parsed_bars, timeframe)
+                parsed_bars = self._aggregate_bars(parsed_bars, timeframe, symbol)
                 logger.info(f"✅ Aggregated to {len(parsed_bars)} {timeframe} bars")
             
             # Filter based on mode (after aggregation)
@@ -7335,6 +8604,15 @@ class TopStepXTradingBot:
                 # On error, wait 1 hour before retrying
                 await asyncio.sleep(3600)
     
+    def claim_artifact_dirs(self) -> None:
+        """
+        Claim the history cache directory for this process (call once when the bot starts).
+        
+        Raises:
+            ArtifactLockError: Another bot process already holds it
+        """
+        acquire_artifact_lock(self.history_cache_dir)
+    
     async def run(self):
         """
         Main bot execution flow with parallel initialization and performance timing.
@@ -7346,6 +8624,7 @@ class TopStepXTradingBot:
         try:
             print("🤖 TopStepX Trading Bot - Real API Version")
             print("="*50)
+            self.claim_artifact_dirs()
             
             # Step 1: Authenticate (must be first - required for all other operations)
             _total_start = _t.time()
@@ -7452,6 +8731,7 @@ class TopStepXTradingBot:
             logger.error(f"Bot execution failed: {str(e)}")
             print(f"❌ Bot execution failed: {str(e)}")
         finally:
+            await self.plugin_hooks.dispatch(LifecycleEvent.PRE_SHUTDOWN, self, source="cli")
             # Ensure cache is cleaned up even on error
             if sdk_adapter is not None and sdk_adapter.is_cache_initialized():
                 try:
@@ -7728,7 +9008,8 @@ class TopStepXTradingBot:
                                 self.account_tracker.initialize(
                                     account_id=target_account['id'],
                                     starting_balance=balance,
-                                    account_type=target_account.get('type', 'unknown')
+                                    account_type=target_account.get('type', 'unknown'),
+                                    currency=target_account.get('currency', 'USD')
                                 )
                                 logger.info(f"Account tracker reinitialized for {target_account.get('name')} (${balance:,.2f})")
                             
@@ -7780,7 +9061,8 @@ class TopStepXTradingBot:
                                 self.account_tracker.initialize(
                                     account_id=target_account['id'],
                                     starting_balance=balance,
-                                    account_type=target_account.get('type', 'unknown')
+                                    account_type=target_account.get('type', 'unknown'),
+                                    currency=target_account.get('currency', 'USD')
                                 )
                                 logger.info(f"Account tracker reinitialized for {target_account.get('name')} (${balance:,.2f})")
                             